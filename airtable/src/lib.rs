@@ -522,6 +522,72 @@ impl Airtable {
         Ok(())
     }
 
+    /// Add a collaborator to a base.
+    /// This is for an enterprise admin to do only.
+    /// FROM: https://airtable.com/api/enterprise#enterpriseBaseAddCollaborator
+    /// Permission level can be: create | edit | comment | read
+    pub async fn add_collaborator_to_base(&self, base_id: &str, user_id: &str, permission_level: &str) -> Result<()> {
+        if self.enterprise_account_id.is_empty() {
+            // Return an error early.
+            bail!("An enterprise account id is required.");
+        }
+
+        // Build the request.
+        let request = self.request(
+            Method::POST,
+            format!("v0/meta/bases/{base_id}/collaborators"),
+            NewCollaborator {
+                collaborators: vec![Collaborator {
+                    user: User {
+                        id: user_id.to_string(),
+                        email: Default::default(),
+                        name: Default::default(),
+                    },
+                    permission_level: permission_level.to_string(),
+                }],
+            },
+            None,
+        )?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Remove a collaborator from a base.
+    /// This is for an enterprise admin to do only.
+    /// FROM: https://airtable.com/api/enterprise#enterpriseBaseRemoveCollaborator
+    pub async fn remove_collaborator_from_base(&self, base_id: &str, user_id: &str) -> Result<()> {
+        if self.enterprise_account_id.is_empty() {
+            // Return an error early.
+            bail!("An enterprise account id is required.");
+        }
+
+        // Build the request.
+        let request = self.request(
+            Method::DELETE,
+            format!("v0/meta/bases/{base_id}/collaborators/{user_id}"),
+            (),
+            None,
+        )?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        Ok(())
+    }
+
     /// Returns basic information on the workspace. Does not include deleted collaborators
     /// and only include outstanding invites.
     /// FROM: https://airtable.com/api/enterprise#enterpriseWorkspaceGetInformation
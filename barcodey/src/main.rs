@@ -1,8 +1,8 @@
 use std::{env, process::Command};
 
-use cio_api::swag_inventory::BarcodeScan;
+use cio_api::{asset_assignments::check_in_scanned, db::Database, swag_inventory::BarcodeScan};
 use hidapi::HidApi;
-use log::info;
+use log::{info, warn};
 use sentry::IntoDsn;
 
 #[tokio::main]
@@ -49,6 +49,9 @@ async fn main() -> Result<(), String> {
         ..sentry::ClientOptions::default()
     });
 
+    // The location of the stock that the scanner takes items out of.
+    let location = env::var("BARCODEY_LOCATION").unwrap_or_else(|_| "office".to_string());
+
     let api = HidApi::new().expect("Failed to create API instance");
     let mut vendor_id: u16 = u16::MIN;
     let mut product_id: u16 = u16::MIN;
@@ -195,8 +198,15 @@ async fn main() -> Result<(), String> {
             let barcode: String = chars.into_iter().collect();
             info!("got barcode: {}", barcode);
 
-            // We got a barcode scan, lets add it to our database.
-            BarcodeScan::scan(barcode.trim().to_string()).await.unwrap();
+            // We got a barcode scan. Assets that are scanned are being returned, anything
+            // else is swag, lets add it to our database.
+            let db = Database::new().await;
+            match check_in_scanned(&db, &barcode).await {
+                Ok(true) => (),
+                Ok(false) => BarcodeScan::scan(barcode.trim().to_string(), &location).await.unwrap(),
+                // Do not count it as swag when we could not tell whether it is an asset.
+                Err(e) => warn!("could not check in barcode {}: {}", barcode, e),
+            }
 
             // Clear out the vector so we can scan again.
             chars = vec![];
@@ -171,6 +171,29 @@ impl Checkr {
         Ok(resp.json().await.unwrap())
     }
 
+    /// Find the candidate with the given email, if there is one.
+    pub async fn find_candidate_by_email(&self, email: &str) -> Result<Option<Candidate>, APIError> {
+        // Build the request.
+        let request = self.request(Method::GET, "candidates", (), Some(vec![("email", email.to_string())]));
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        let r: CandidatesResponse = resp.json().await.unwrap();
+
+        Ok(r.candidates
+            .into_iter()
+            .find(|candidate| candidate.email.eq_ignore_ascii_case(email)))
+    }
+
     /// Create a new candidate.
     pub async fn create_candidate(&self, email: &str) -> Result<Candidate, APIError> {
         // Build the request.
@@ -266,6 +289,148 @@ impl Checkr {
 
         Ok(resp.json().await.unwrap())
     }
+
+    /// Enroll a candidate in continuous checks of the given type, ie. `criminal`.
+    pub async fn create_continuous_check(&self, candidate_id: &str, type_: &str) -> Result<ContinuousCheck, APIError> {
+        // Build the request.
+        let request = self.request(
+            Method::POST,
+            "continuous_checks",
+            ContinuousCheckRequest {
+                candidate_id: candidate_id.to_string(),
+                type_: type_.to_string(),
+            },
+            None,
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            StatusCode::CREATED => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(resp.json().await.unwrap())
+    }
+
+    /// List the continuous checks a candidate is enrolled in.
+    pub async fn list_continuous_checks(&self, candidate_id: &str) -> Result<Vec<ContinuousCheck>, APIError> {
+        // Build the request.
+        let request = self.request(
+            Method::GET,
+            &format!("candidates/{candidate_id}/continuous_checks"),
+            (),
+            None,
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        let r: ContinuousChecksResponse = resp.json().await.unwrap();
+
+        Ok(r.continuous_checks)
+    }
+
+    /// Unenroll a candidate from a continuous check.
+    pub async fn delete_continuous_check(&self, id: &str) -> Result<(), APIError> {
+        // Build the request.
+        let request = self.request(Method::DELETE, &format!("continuous_checks/{id}"), (), None);
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            StatusCode::NO_CONTENT => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Start the adverse action flow for a report by sending the pre-adverse action notice.
+    /// Checkr sends the post-adverse action notice once the waiting period is over.
+    pub async fn create_adverse_action(
+        &self,
+        report_id: &str,
+        body: &AdverseActionRequest,
+    ) -> Result<AdverseAction, APIError> {
+        // Build the request.
+        let request = self.request(
+            Method::POST,
+            &format!("reports/{report_id}/adverse_actions"),
+            body,
+            None,
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            StatusCode::CREATED => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(resp.json().await.unwrap())
+    }
+
+    /// Get an adverse action.
+    pub async fn get_adverse_action(&self, id: &str) -> Result<AdverseAction, APIError> {
+        // Build the request.
+        let request = self.request(Method::GET, &format!("adverse_actions/{id}"), (), None);
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(resp.json().await.unwrap())
+    }
+
+    /// Cancel an adverse action before the post-adverse action notice is sent.
+    pub async fn cancel_adverse_action(&self, id: &str) -> Result<AdverseAction, APIError> {
+        // Build the request.
+        let request = self.request(Method::DELETE, &format!("adverse_actions/{id}"), (), None);
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(resp.json().await.unwrap())
+    }
 }
 
 /// Error type returned by our library.
@@ -748,6 +913,103 @@ pub struct Invitation {
     pub report_id: String,
 }
 
+#[derive(Clone, Debug, JsonSchema, Serialize, Deserialize)]
+pub struct ContinuousCheckRequest {
+    pub candidate_id: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+}
+
+#[derive(Clone, Debug, JsonSchema, Default, Serialize, Deserialize)]
+pub struct ContinuousChecksResponse {
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "data")]
+    pub continuous_checks: Vec<ContinuousCheck>,
+}
+
+/// The data type for a continuous check.
+#[derive(Clone, Debug, JsonSchema, Default, Serialize, Deserialize)]
+pub struct ContinuousCheck {
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        deserialize_with = "deserialize_null_string::deserialize"
+    )]
+    pub id: String,
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        deserialize_with = "deserialize_null_string::deserialize"
+    )]
+    pub object: String,
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        rename = "type",
+        deserialize_with = "deserialize_null_string::deserialize"
+    )]
+    pub type_: String,
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        deserialize_with = "deserialize_null_string::deserialize"
+    )]
+    pub candidate_id: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, JsonSchema, Default, Serialize, Deserialize)]
+pub struct AdverseActionRequest {
+    /// When to send the post-adverse action notice, Checkr defaults to the end of the
+    /// mandatory waiting period.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_notice_scheduled_at: Option<DateTime<Utc>>,
+    /// The items of the report the decision is based on.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub adverse_item_ids: Vec<String>,
+}
+
+/// The data type for an adverse action.
+#[derive(Clone, Debug, JsonSchema, Default, Serialize, Deserialize)]
+pub struct AdverseAction {
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        deserialize_with = "deserialize_null_string::deserialize"
+    )]
+    pub id: String,
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        deserialize_with = "deserialize_null_string::deserialize"
+    )]
+    pub object: String,
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        deserialize_with = "deserialize_null_string::deserialize"
+    )]
+    pub uri: String,
+    /// One of `pending`, `complete`, `canceled`, etc.
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        deserialize_with = "deserialize_null_string::deserialize"
+    )]
+    pub status: String,
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        deserialize_with = "deserialize_null_string::deserialize"
+    )]
+    pub report_id: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub post_notice_scheduled_at: Option<DateTime<Utc>>,
+    pub post_notice_ready_at: Option<DateTime<Utc>>,
+    pub canceled_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub individualized_assessment_engaged: bool,
+}
+
 /// The data type for a webhook event.
 #[derive(Clone, Debug, JsonSchema, Serialize, Deserialize)]
 pub struct WebhookEvent {
@@ -782,6 +1044,81 @@ pub struct WebhookEvent {
     pub data: EventData,
 }
 
+impl WebhookEvent {
+    pub fn event_type(&self) -> WebhookEventType {
+        WebhookEventType::from(self.type_.as_str())
+    }
+}
+
+/// The report and invitation events we handle.
+///
+/// FROM: https://docs.checkr.com/#section/Webhooks/Subscribing-to-webhooks
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WebhookEventType {
+    ReportCreated,
+    ReportUpgraded,
+    ReportCompleted,
+    ReportSuspended,
+    ReportResumed,
+    ReportDisputed,
+    ReportCanceled,
+    ReportEngaged,
+    ReportPreAdverseAction,
+    ReportPostAdverseAction,
+    InvitationCreated,
+    InvitationCompleted,
+    InvitationExpired,
+    InvitationDeleted,
+    Other(String),
+}
+
+impl WebhookEventType {
+    pub fn is_report(&self) -> bool {
+        matches!(
+            self,
+            Self::ReportCreated
+                | Self::ReportUpgraded
+                | Self::ReportCompleted
+                | Self::ReportSuspended
+                | Self::ReportResumed
+                | Self::ReportDisputed
+                | Self::ReportCanceled
+                | Self::ReportEngaged
+                | Self::ReportPreAdverseAction
+                | Self::ReportPostAdverseAction
+        )
+    }
+
+    pub fn is_invitation(&self) -> bool {
+        matches!(
+            self,
+            Self::InvitationCreated | Self::InvitationCompleted | Self::InvitationExpired | Self::InvitationDeleted
+        )
+    }
+}
+
+impl From<&str> for WebhookEventType {
+    fn from(s: &str) -> Self {
+        match s {
+            "report.created" => Self::ReportCreated,
+            "report.upgraded" => Self::ReportUpgraded,
+            "report.completed" => Self::ReportCompleted,
+            "report.suspended" => Self::ReportSuspended,
+            "report.resumed" => Self::ReportResumed,
+            "report.disputed" => Self::ReportDisputed,
+            "report.canceled" => Self::ReportCanceled,
+            "report.engaged" => Self::ReportEngaged,
+            "report.pre_adverse_action" => Self::ReportPreAdverseAction,
+            "report.post_adverse_action" => Self::ReportPostAdverseAction,
+            "invitation.created" => Self::InvitationCreated,
+            "invitation.completed" => Self::InvitationCompleted,
+            "invitation.expired" => Self::InvitationExpired,
+            "invitation.deleted" => Self::InvitationDeleted,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
 #[derive(Clone, Default, Debug, JsonSchema, Serialize, Deserialize)]
 pub struct EventData {
     #[serde(default)]
@@ -875,6 +1212,13 @@ pub struct EventObject {
         deserialize_with = "deserialize_null_string::deserialize"
     )]
     pub package: String,
+    /// The report an invitation resulted in, only set for invitation events.
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        deserialize_with = "deserialize_null_string::deserialize"
+    )]
+    pub report_id: String,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub personal_reference_verification_ids: Vec<String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -0,0 +1,3 @@
+DROP INDEX idx_policy_acknowledgments_batch;
+
+DROP TABLE policy_acknowledgments;
@@ -0,0 +1,14 @@
+CREATE TABLE policy_acknowledgments (
+    id SERIAL PRIMARY KEY,
+    policy VARCHAR NOT NULL,
+    user_email VARCHAR NOT NULL,
+    user_name VARCHAR NOT NULL,
+    docusign_batch_id VARCHAR NOT NULL,
+    docusign_envelope_id VARCHAR NOT NULL DEFAULT '',
+    docusign_envelope_status VARCHAR NOT NULL DEFAULT '',
+    sent_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ DEFAULT NULL,
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_policy_acknowledgments_batch ON policy_acknowledgments(docusign_batch_id, user_email);
@@ -0,0 +1,2 @@
+ALTER TABLE applicants DROP COLUMN offer_salary;
+ALTER TABLE applicants DROP COLUMN offer_equity;
@@ -0,0 +1,9 @@
+CREATE TABLE email_suppressions (
+    id SERIAL PRIMARY KEY,
+    email VARCHAR NOT NULL,
+    reason VARCHAR NOT NULL,
+    source VARCHAR NOT NULL,
+    suppressed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    UNIQUE (email, cio_company_id)
+);
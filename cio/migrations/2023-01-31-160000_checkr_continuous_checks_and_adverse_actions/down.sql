@@ -0,0 +1,5 @@
+ALTER TABLE applicants DROP COLUMN checkr_report_id;
+ALTER TABLE applicants DROP COLUMN checkr_adverse_action_id;
+ALTER TABLE applicants DROP COLUMN adverse_action_status;
+ALTER TABLE users DROP COLUMN checkr_candidate_id;
+ALTER TABLE users DROP COLUMN continuous_check_status;
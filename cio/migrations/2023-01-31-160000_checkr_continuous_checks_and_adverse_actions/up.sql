@@ -0,0 +1,5 @@
+ALTER TABLE applicants ADD COLUMN checkr_report_id VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE applicants ADD COLUMN checkr_adverse_action_id VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE applicants ADD COLUMN adverse_action_status VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE users ADD COLUMN checkr_candidate_id VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE users ADD COLUMN continuous_check_status VARCHAR NOT NULL DEFAULT '';
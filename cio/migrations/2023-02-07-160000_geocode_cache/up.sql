@@ -0,0 +1,8 @@
+CREATE TABLE geocode_cache_entries (
+    id SERIAL PRIMARY KEY,
+    address_hash VARCHAR NOT NULL UNIQUE,
+    address VARCHAR NOT NULL,
+    latitude DOUBLE PRECISION NOT NULL,
+    longitude DOUBLE PRECISION NOT NULL,
+    cached_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
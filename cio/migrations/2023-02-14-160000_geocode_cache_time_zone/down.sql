@@ -0,0 +1 @@
+ALTER TABLE geocode_cache_entries DROP COLUMN time_zone_id;
@@ -0,0 +1 @@
+ALTER TABLE geocode_cache_entries ADD COLUMN time_zone_id VARCHAR NOT NULL DEFAULT '';
@@ -0,0 +1,2 @@
+ALTER TABLE asset_items DROP COLUMN qr_code_png;
+ALTER TABLE asset_items DROP COLUMN qr_code_svg;
@@ -0,0 +1,2 @@
+ALTER TABLE asset_items ADD COLUMN qr_code_png VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE asset_items ADD COLUMN qr_code_svg VARCHAR NOT NULL DEFAULT '';
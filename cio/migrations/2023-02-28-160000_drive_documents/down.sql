@@ -0,0 +1,4 @@
+ALTER TABLE applicants DROP COLUMN employee_agreements_link;
+ALTER TABLE applicants DROP COLUMN offer_documents_link;
+
+DROP TABLE drive_documents;
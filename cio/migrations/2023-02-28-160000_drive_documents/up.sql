@@ -0,0 +1,15 @@
+CREATE TABLE drive_documents (
+    id SERIAL PRIMARY KEY,
+    shared_drive VARCHAR NOT NULL,
+    path VARCHAR NOT NULL,
+    name VARCHAR NOT NULL,
+    content_hash VARCHAR NOT NULL,
+    file_id VARCHAR NOT NULL,
+    url VARCHAR NOT NULL,
+    uploaded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    cio_company_id INTEGER NOT NULL,
+    UNIQUE (cio_company_id, shared_drive, path, name)
+);
+
+ALTER TABLE applicants ADD COLUMN offer_documents_link VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE applicants ADD COLUMN employee_agreements_link VARCHAR NOT NULL DEFAULT '';
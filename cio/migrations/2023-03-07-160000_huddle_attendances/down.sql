@@ -0,0 +1 @@
+DROP TABLE huddle_attendances;
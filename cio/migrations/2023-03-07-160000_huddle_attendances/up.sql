@@ -0,0 +1,10 @@
+CREATE TABLE huddle_attendances (
+    id SERIAL PRIMARY KEY,
+    huddle VARCHAR NOT NULL,
+    meeting_date DATE NOT NULL,
+    email VARCHAR NOT NULL,
+    duration_seconds INTEGER NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    cio_company_id INTEGER NOT NULL,
+    UNIQUE (cio_company_id, huddle, meeting_date, email)
+);
@@ -0,0 +1,4 @@
+DROP TABLE huddle_zoom_meetings;
+
+ALTER TABLE applicant_interviews DROP COLUMN zoom_join_url;
+ALTER TABLE applicant_interviews DROP COLUMN zoom_meeting_id;
@@ -0,0 +1,12 @@
+ALTER TABLE applicant_interviews ADD COLUMN zoom_meeting_id VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE applicant_interviews ADD COLUMN zoom_join_url VARCHAR NOT NULL DEFAULT '';
+
+CREATE TABLE huddle_zoom_meetings (
+    id SERIAL PRIMARY KEY,
+    huddle VARCHAR NOT NULL,
+    meeting_id VARCHAR NOT NULL,
+    webinar BOOLEAN NOT NULL DEFAULT FALSE,
+    join_url VARCHAR NOT NULL,
+    cio_company_id INTEGER NOT NULL,
+    UNIQUE (cio_company_id, huddle)
+);
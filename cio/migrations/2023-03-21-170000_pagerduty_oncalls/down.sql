@@ -0,0 +1 @@
+DROP TABLE pagerduty_oncalls;
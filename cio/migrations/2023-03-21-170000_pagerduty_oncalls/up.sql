@@ -0,0 +1,15 @@
+CREATE TABLE pagerduty_oncalls (
+    id SERIAL PRIMARY KEY,
+    schedule_id VARCHAR NOT NULL,
+    schedule_name VARCHAR NOT NULL,
+    escalation_level INTEGER NOT NULL,
+    pagerduty_user_id VARCHAR NOT NULL,
+    name VARCHAR NOT NULL,
+    email VARCHAR NOT NULL,
+    username VARCHAR NOT NULL DEFAULT '',
+    start_time TIMESTAMPTZ,
+    end_time TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    cio_company_id INTEGER NOT NULL,
+    UNIQUE (cio_company_id, schedule_id, escalation_level)
+);
@@ -0,0 +1,21 @@
+CREATE TABLE payments (
+    id SERIAL PRIMARY KEY,
+    stripe_id VARCHAR NOT NULL,
+    kind VARCHAR NOT NULL,
+    status VARCHAR NOT NULL,
+    amount REAL NOT NULL DEFAULT 0,
+    amount_refunded REAL NOT NULL DEFAULT 0,
+    currency VARCHAR NOT NULL DEFAULT 'usd',
+    customer_name VARCHAR NOT NULL DEFAULT '',
+    customer_email VARCHAR NOT NULL DEFAULT '',
+    description VARCHAR NOT NULL DEFAULT '',
+    stripe_payment_intent VARCHAR NOT NULL DEFAULT '',
+    stripe_invoice_number VARCHAR NOT NULL DEFAULT '',
+    quickbooks_id VARCHAR NOT NULL DEFAULT '',
+    quickbooks_type VARCHAR NOT NULL DEFAULT '',
+    paid_at TIMESTAMPTZ NOT NULL,
+    refunded_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    cio_company_id INTEGER NOT NULL,
+    UNIQUE (cio_company_id, stripe_id)
+);
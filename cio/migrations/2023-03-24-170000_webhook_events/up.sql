@@ -0,0 +1,15 @@
+CREATE TABLE webhook_events (
+    id SERIAL PRIMARY KEY,
+    source VARCHAR NOT NULL,
+    event_type VARCHAR NOT NULL DEFAULT '',
+    payload JSONB NOT NULL,
+    status VARCHAR NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error VARCHAR NOT NULL DEFAULT '',
+    received_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    available_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    finished_at TIMESTAMPTZ
+);
+
+CREATE INDEX webhook_events_queue ON webhook_events (source, status, available_at);
@@ -0,0 +1,5 @@
+CREATE TABLE scheduled_job_runs (
+    job VARCHAR PRIMARY KEY,
+    runner VARCHAR NOT NULL,
+    last_run_at TIMESTAMPTZ NOT NULL
+);
@@ -0,0 +1 @@
+ALTER TABLE webhook_events ADD COLUMN correlation_id VARCHAR NOT NULL DEFAULT '';
@@ -0,0 +1,15 @@
+CREATE TABLE github_event_routes (
+    id SERIAL PRIMARY KEY,
+    event_type VARCHAR NOT NULL,
+    repo_glob VARCHAR NOT NULL,
+    action VARCHAR NOT NULL DEFAULT '*',
+    handler VARCHAR NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT true,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO github_event_routes (event_type, repo_glob, action, handler) VALUES
+    ('push', 'rfd', '*', 'rfd-push'),
+    ('pull_request', 'rfd', '*', 'rfd-pull-request'),
+    ('push', 'configs', '*', 'configs-push');
@@ -0,0 +1,2 @@
+ALTER TABLE applicants ADD COLUMN score REAL NOT NULL DEFAULT 0;
+ALTER TABLE applicants ADD COLUMN score_details VARCHAR NOT NULL DEFAULT '';
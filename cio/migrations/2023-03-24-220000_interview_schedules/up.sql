@@ -0,0 +1,16 @@
+CREATE TABLE interview_schedules (
+    id SERIAL PRIMARY KEY,
+    applicant_id INTEGER NOT NULL,
+    email VARCHAR NOT NULL,
+    panel JSONB NOT NULL,
+    proposed_times TIMESTAMPTZ[] NOT NULL DEFAULT '{}',
+    token VARCHAR NOT NULL UNIQUE,
+    status VARCHAR NOT NULL DEFAULT 'proposed',
+    booked_time TIMESTAMPTZ,
+    google_event_ids VARCHAR[] NOT NULL DEFAULT '{}',
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX interview_schedules_applicant ON interview_schedules (applicant_id, status);
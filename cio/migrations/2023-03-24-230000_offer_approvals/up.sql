@@ -0,0 +1,18 @@
+CREATE TABLE offer_approvals (
+    id SERIAL PRIMARY KEY,
+    applicant_id INTEGER NOT NULL,
+    salary VARCHAR NOT NULL,
+    equity VARCHAR NOT NULL,
+    start_date DATE,
+    status VARCHAR NOT NULL DEFAULT 'pending',
+    decided_by VARCHAR NOT NULL DEFAULT '',
+    decided_at TIMESTAMPTZ,
+    envelope_id VARCHAR NOT NULL DEFAULT '',
+    sent_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX offer_approvals_applicant ON offer_approvals (applicant_id, id);
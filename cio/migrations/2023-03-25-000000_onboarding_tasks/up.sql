@@ -0,0 +1,16 @@
+CREATE TABLE onboarding_tasks (
+    id SERIAL PRIMARY KEY,
+    username VARCHAR NOT NULL,
+    name VARCHAR NOT NULL,
+    kind VARCHAR NOT NULL,
+    due_date DATE NOT NULL,
+    status VARCHAR NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error VARCHAR NOT NULL DEFAULT '',
+    next_attempt_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (cio_company_id, username, name)
+);
@@ -0,0 +1 @@
+DROP TABLE offboarding_actions;
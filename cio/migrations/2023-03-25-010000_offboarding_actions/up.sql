@@ -0,0 +1,11 @@
+CREATE TABLE offboarding_actions (
+    id SERIAL PRIMARY KEY,
+    username VARCHAR NOT NULL,
+    service VARCHAR NOT NULL,
+    status VARCHAR NOT NULL,
+    detail VARCHAR NOT NULL DEFAULT '',
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX offboarding_actions_user ON offboarding_actions (cio_company_id, username, service);
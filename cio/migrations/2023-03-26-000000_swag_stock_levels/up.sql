@@ -0,0 +1,16 @@
+CREATE TABLE swag_stock_levels (
+    id SERIAL PRIMARY KEY,
+    swag_inventory_item_id INTEGER NOT NULL REFERENCES swag_inventory_items(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    location VARCHAR NOT NULL,
+    on_hand INTEGER NOT NULL DEFAULT 0 CHECK (on_hand >= 0),
+    low_stock_threshold INTEGER NOT NULL DEFAULT 0,
+    low_stock_alerted_at TIMESTAMPTZ,
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (swag_inventory_item_id, location)
+);
+
+-- Start every inventory item off with what it has in stock today, kept at the office.
+INSERT INTO swag_stock_levels (swag_inventory_item_id, location, on_hand, cio_company_id)
+SELECT id, 'office', GREATEST(current_stock, 0), cio_company_id FROM swag_inventory_items;
@@ -0,0 +1,4 @@
+DROP TABLE asset_assignments;
+
+ALTER TABLE asset_items DROP COLUMN purchase_date;
+ALTER TABLE asset_items DROP COLUMN vendor;
@@ -0,0 +1,17 @@
+ALTER TABLE asset_items ADD COLUMN purchase_date DATE;
+ALTER TABLE asset_items ADD COLUMN vendor VARCHAR NOT NULL DEFAULT '';
+
+CREATE TABLE asset_assignments (
+    id SERIAL PRIMARY KEY,
+    asset_item_id INTEGER NOT NULL REFERENCES asset_items(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    username VARCHAR NOT NULL,
+    checked_out_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    due_back DATE,
+    checked_in_at TIMESTAMPTZ,
+    last_nagged_at TIMESTAMPTZ,
+    notes VARCHAR NOT NULL DEFAULT '',
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+-- An asset can only be checked out to one person at a time.
+CREATE UNIQUE INDEX asset_assignments_checked_out ON asset_assignments (asset_item_id) WHERE checked_in_at IS NULL;
@@ -0,0 +1,16 @@
+CREATE TABLE device_credentials (
+    id SERIAL PRIMARY KEY,
+    username VARCHAR NOT NULL,
+    kind VARCHAR NOT NULL,
+    external_id VARCHAR NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ,
+    download_token_hash VARCHAR NOT NULL UNIQUE,
+    download_expires_at TIMESTAMPTZ NOT NULL,
+    downloaded_at TIMESTAMPTZ,
+    payload BYTEA,
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX device_credentials_username ON device_credentials (cio_company_id, username);
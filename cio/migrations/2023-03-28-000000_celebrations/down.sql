@@ -0,0 +1,2 @@
+DROP TABLE celebration_posts;
+DROP TABLE celebration_preferences;
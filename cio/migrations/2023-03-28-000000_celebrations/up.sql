@@ -0,0 +1,19 @@
+CREATE TABLE celebration_preferences (
+    id SERIAL PRIMARY KEY,
+    username VARCHAR NOT NULL,
+    opted_out BOOLEAN NOT NULL DEFAULT false,
+    share_birthday BOOLEAN NOT NULL DEFAULT false,
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (cio_company_id, username)
+);
+
+CREATE TABLE celebration_posts (
+    id SERIAL PRIMARY KEY,
+    username VARCHAR NOT NULL,
+    occasion VARCHAR NOT NULL,
+    occasion_date DATE NOT NULL,
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    posted_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (cio_company_id, username, occasion, occasion_date)
+);
@@ -0,0 +1,7 @@
+CREATE TABLE applicant_sources (
+    id SERIAL PRIMARY KEY,
+    applicant_id INTEGER NOT NULL UNIQUE REFERENCES applicants(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    source VARCHAR NOT NULL,
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
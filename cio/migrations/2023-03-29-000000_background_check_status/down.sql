@@ -0,0 +1,3 @@
+DROP TABLE background_check_events;
+ALTER TABLE users DROP COLUMN background_check_status;
+ALTER TABLE applicants DROP COLUMN background_check_status;
@@ -0,0 +1,18 @@
+ALTER TABLE applicants ADD COLUMN background_check_status VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE users ADD COLUMN background_check_status VARCHAR NOT NULL DEFAULT '';
+
+CREATE TABLE background_check_events (
+    id SERIAL PRIMARY KEY,
+    subject VARCHAR NOT NULL,
+    email VARCHAR NOT NULL,
+    name VARCHAR NOT NULL,
+    role VARCHAR NOT NULL DEFAULT '',
+    status VARCHAR NOT NULL,
+    provider VARCHAR NOT NULL,
+    provider_event_type VARCHAR NOT NULL,
+    report_id VARCHAR NOT NULL DEFAULT '',
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX background_check_events_company_created_at ON background_check_events (cio_company_id, created_at);
@@ -0,0 +1,18 @@
+CREATE TABLE applicant_duplicates (
+    id SERIAL PRIMARY KEY,
+    applicant_id INTEGER NOT NULL,
+    duplicate_id INTEGER NOT NULL,
+    applicant_name VARCHAR NOT NULL,
+    applicant_email VARCHAR NOT NULL,
+    applicant_role VARCHAR NOT NULL,
+    duplicate_name VARCHAR NOT NULL,
+    duplicate_email VARCHAR NOT NULL,
+    duplicate_role VARCHAR NOT NULL,
+    reasons TEXT [] NOT NULL,
+    score INTEGER NOT NULL,
+    status VARCHAR NOT NULL,
+    applicants TEXT [] NOT NULL,
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    airtable_record_id VARCHAR NOT NULL DEFAULT '',
+    UNIQUE (applicant_id, duplicate_id)
+);
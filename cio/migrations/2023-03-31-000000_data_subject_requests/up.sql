@@ -0,0 +1,11 @@
+CREATE TABLE data_subject_requests (
+    id SERIAL PRIMARY KEY,
+    kind VARCHAR NOT NULL,
+    subject_hash VARCHAR NOT NULL,
+    requested_by VARCHAR NOT NULL,
+    actions TEXT [] NOT NULL,
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX data_subject_requests_subject_hash ON data_subject_requests (subject_hash);
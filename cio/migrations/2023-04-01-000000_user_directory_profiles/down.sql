@@ -0,0 +1,2 @@
+ALTER TABLE users DROP COLUMN photo_url;
+ALTER TABLE users DROP COLUMN title;
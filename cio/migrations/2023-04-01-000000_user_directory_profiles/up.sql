@@ -0,0 +1,2 @@
+ALTER TABLE users ADD COLUMN title VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE users ADD COLUMN photo_url VARCHAR NOT NULL DEFAULT '';
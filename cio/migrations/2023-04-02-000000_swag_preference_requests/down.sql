@@ -0,0 +1 @@
+DROP TABLE swag_preference_requests;
@@ -0,0 +1,12 @@
+CREATE TABLE swag_preference_requests (
+    id SERIAL PRIMARY KEY,
+    outbound_shipment_id INTEGER NOT NULL REFERENCES outbound_shipments(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    name VARCHAR NOT NULL,
+    email VARCHAR NOT NULL,
+    token_hash VARCHAR NOT NULL UNIQUE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    shirt_size VARCHAR NOT NULL DEFAULT '',
+    submitted_at TIMESTAMPTZ,
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
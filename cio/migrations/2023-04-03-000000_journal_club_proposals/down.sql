@@ -0,0 +1,2 @@
+DELETE FROM github_event_routes WHERE handler = 'paper-proposal';
+DROP TABLE journal_club_proposals;
@@ -0,0 +1,30 @@
+CREATE TABLE journal_club_proposals (
+    id SERIAL PRIMARY KEY,
+    link VARCHAR NOT NULL,
+    title VARCHAR NOT NULL,
+    proposed_by VARCHAR NOT NULL,
+    source VARCHAR NOT NULL,
+    source_link VARCHAR NOT NULL DEFAULT '',
+    status VARCHAR NOT NULL DEFAULT 'proposed',
+    doi VARCHAR NOT NULL DEFAULT '',
+    authors TEXT [] NOT NULL DEFAULT '{}',
+    published_year INTEGER,
+    venue VARCHAR NOT NULL DEFAULT '',
+    abstract_text TEXT NOT NULL DEFAULT '',
+    pdf_url VARCHAR NOT NULL DEFAULT '',
+    fetched_at TIMESTAMPTZ,
+    huddle VARCHAR NOT NULL DEFAULT '',
+    meeting_date DATE,
+    airtable_topic_id VARCHAR NOT NULL DEFAULT '',
+    slack_channel VARCHAR NOT NULL DEFAULT '',
+    slack_ts VARCHAR NOT NULL DEFAULT '',
+    discussion_notes TEXT NOT NULL DEFAULT '',
+    recording VARCHAR NOT NULL DEFAULT '',
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (cio_company_id, link)
+);
+
+INSERT INTO github_event_routes (event_type, repo_glob, action, handler) VALUES
+    ('issues', 'papers', 'opened', 'paper-proposal');
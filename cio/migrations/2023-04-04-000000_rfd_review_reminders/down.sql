@@ -0,0 +1 @@
+DROP TABLE rfd_review_reminders;
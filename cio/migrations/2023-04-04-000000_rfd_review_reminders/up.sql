@@ -0,0 +1,10 @@
+CREATE TABLE rfd_review_reminders (
+    id SERIAL PRIMARY KEY,
+    rfd_number INTEGER NOT NULL,
+    pull_request_number BIGINT NOT NULL,
+    last_activity_at TIMESTAMPTZ NOT NULL,
+    escalation_level INTEGER NOT NULL DEFAULT 0,
+    reminded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    UNIQUE (cio_company_id, rfd_number)
+);
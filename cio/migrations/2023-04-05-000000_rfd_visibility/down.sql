@@ -0,0 +1,2 @@
+ALTER TABLE rfds DROP COLUMN embargoed_until;
+ALTER TABLE rfds DROP COLUMN visibility;
@@ -0,0 +1,2 @@
+ALTER TABLE rfds ADD COLUMN visibility VARCHAR NOT NULL DEFAULT 'public';
+ALTER TABLE rfds ADD COLUMN embargoed_until DATE;
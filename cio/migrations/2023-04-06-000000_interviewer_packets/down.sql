@@ -0,0 +1 @@
+DROP TABLE interviewer_packets;
@@ -0,0 +1,8 @@
+CREATE TABLE interviewer_packets (
+    id SERIAL PRIMARY KEY,
+    google_event_id VARCHAR NOT NULL,
+    interviewer VARCHAR NOT NULL,
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    sent_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (cio_company_id, google_event_id, interviewer)
+);
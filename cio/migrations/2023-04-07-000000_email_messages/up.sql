@@ -0,0 +1,15 @@
+CREATE TABLE email_messages (
+    id SERIAL PRIMARY KEY,
+    provider VARCHAR NOT NULL,
+    provider_message_id VARCHAR NOT NULL DEFAULT '',
+    template VARCHAR NOT NULL DEFAULT '',
+    subject VARCHAR NOT NULL,
+    recipients TEXT[] NOT NULL,
+    status VARCHAR NOT NULL,
+    status_reason VARCHAR NOT NULL DEFAULT '',
+    sent_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    status_updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_email_messages_provider_message_id ON email_messages(provider,provider_message_id);
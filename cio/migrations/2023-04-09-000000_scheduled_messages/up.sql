@@ -0,0 +1,14 @@
+CREATE TABLE scheduled_messages (
+    id SERIAL PRIMARY KEY,
+    kind VARCHAR NOT NULL,
+    recipient VARCHAR NOT NULL,
+    message JSONB NOT NULL,
+    send_at TIMESTAMPTZ NOT NULL,
+    sent_at TIMESTAMPTZ,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error VARCHAR NOT NULL DEFAULT '',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_scheduled_messages_due ON scheduled_messages(cio_company_id,sent_at,send_at);
@@ -0,0 +1,2 @@
+DROP VIEW graph_rfd_publications;
+DROP TABLE rfd_history_events;
@@ -0,0 +1,18 @@
+CREATE TABLE rfd_history_events (
+    id SERIAL PRIMARY KEY,
+    rfd_number INTEGER NOT NULL,
+    kind VARCHAR NOT NULL,
+    previous_value VARCHAR NOT NULL DEFAULT '',
+    value VARCHAR NOT NULL DEFAULT '',
+    sha VARCHAR NOT NULL,
+    committed_at TIMESTAMPTZ NOT NULL,
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    UNIQUE (cio_company_id, rfd_number, sha, kind)
+);
+
+CREATE INDEX IF NOT EXISTS idx_rfd_history_events_committed_at ON rfd_history_events(cio_company_id,committed_at);
+
+CREATE VIEW graph_rfd_publications AS
+  SELECT cio_company_id, rfd_number, MIN(committed_at) AS published_at FROM rfd_history_events
+  WHERE (kind = 'state' OR kind = 'created') AND value = 'published'
+  GROUP BY cio_company_id, rfd_number;
@@ -0,0 +1,22 @@
+CREATE TABLE access_requests (
+    id SERIAL PRIMARY KEY,
+    kind VARCHAR NOT NULL,
+    resource VARCHAR NOT NULL,
+    permission VARCHAR NOT NULL DEFAULT '',
+    requester_email VARCHAR NOT NULL,
+    requester_slack_id VARCHAR NOT NULL,
+    reason VARCHAR NOT NULL DEFAULT '',
+    days INTEGER,
+    status VARCHAR NOT NULL DEFAULT 'pending',
+    decided_by VARCHAR NOT NULL DEFAULT '',
+    decided_at TIMESTAMPTZ,
+    granted_at TIMESTAMPTZ,
+    expires_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ,
+    error VARCHAR NOT NULL DEFAULT '',
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX access_requests_expiring ON access_requests (cio_company_id, status, expires_at);
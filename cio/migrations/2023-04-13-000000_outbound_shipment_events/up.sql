@@ -0,0 +1,12 @@
+CREATE TABLE outbound_shipment_events (
+    id SERIAL PRIMARY KEY,
+    outbound_shipment_id INTEGER NOT NULL REFERENCES outbound_shipments(id) ON DELETE CASCADE,
+    kind VARCHAR NOT NULL,
+    previous_value VARCHAR NOT NULL DEFAULT '',
+    value VARCHAR NOT NULL DEFAULT '',
+    changed_by VARCHAR NOT NULL DEFAULT '',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_outbound_shipment_events_shipment ON outbound_shipment_events(outbound_shipment_id,created_at);
@@ -0,0 +1 @@
+ALTER TABLE upload_tokens DROP COLUMN cio_company_id;
@@ -0,0 +1,4 @@
+-- Tokens only live for minutes, so the ones issued before tokens belonged to a company are dropped
+-- rather than guessed at.
+DELETE FROM upload_tokens;
+ALTER TABLE upload_tokens ADD COLUMN cio_company_id INTEGER NOT NULL REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
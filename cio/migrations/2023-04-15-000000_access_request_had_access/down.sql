@@ -0,0 +1 @@
+ALTER TABLE access_requests DROP COLUMN had_access;
@@ -0,0 +1,3 @@
+-- Whether the requester already had the access before the request was granted, in which case
+-- it is left alone when the request expires.
+ALTER TABLE access_requests ADD COLUMN had_access BOOLEAN NOT NULL DEFAULT false;
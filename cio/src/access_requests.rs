@@ -0,0 +1,852 @@
+/*!
+ * Requesting access to resources in Slack.
+ *
+ * Employees ask for access to a GitHub repo, an Airtable base, a Google group or a Tailscale tag
+ * with `/access`. The request is posted to Slack with buttons for the owners of the resource to
+ * approve or reject it, and once approved the access is granted through the same integrations
+ * the rest of the syncs use. Access can be asked for a number of days, after which it is revoked
+ * again. The Google group syncs keep the members that were granted access until then, and
+ * revoking only takes away access the request itself added.
+ *
+ * Each request is kept from the moment it is made, with who decided on it, when it was granted
+ * and when it was revoked, so that it doubles as the record of who was given access to what.
+ */
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    str::FromStr,
+};
+
+use anyhow::{anyhow, bail, Result};
+use async_bb8_diesel::{AsyncRunQueryDsl, OptionalExtension};
+use chrono::{DateTime, Duration, Utc};
+use diesel::{ExpressionMethods, Insertable, QueryDsl, Queryable};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{
+    ActionBlock, BlockOption, FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType,
+};
+use tailscale_api::AclPolicy;
+
+use crate::{
+    app_config::AccessRequestsConfig,
+    companies::Company,
+    configs::User,
+    db::Database,
+    providers::ProviderWriteOps,
+    schema::{access_requests, users},
+};
+
+/// The `action_id` of the button that approves a request. Its value is the id of the request.
+pub const ACCESS_APPROVE_ACTION: &str = "access_approve";
+/// The `action_id` of the button that rejects a request. Its value is the id of the request.
+pub const ACCESS_REJECT_ACTION: &str = "access_reject";
+
+/// The kinds of resources access can be requested to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessResourceKind {
+    GithubRepo,
+    AirtableBase,
+    GoogleGroup,
+    TailscaleTag,
+}
+
+impl AccessResourceKind {
+    fn describe(&self) -> &'static str {
+        match self {
+            AccessResourceKind::GithubRepo => "GitHub repo",
+            AccessResourceKind::AirtableBase => "Airtable base",
+            AccessResourceKind::GoogleGroup => "Google group",
+            AccessResourceKind::TailscaleTag => "Tailscale tag",
+        }
+    }
+
+    fn default_permission(&self) -> &'static str {
+        match self {
+            AccessResourceKind::GithubRepo => "push",
+            AccessResourceKind::AirtableBase => "edit",
+            AccessResourceKind::GoogleGroup => "member",
+            AccessResourceKind::TailscaleTag => "owner",
+        }
+    }
+}
+
+impl fmt::Display for AccessResourceKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            AccessResourceKind::GithubRepo => "github_repo",
+            AccessResourceKind::AirtableBase => "airtable_base",
+            AccessResourceKind::GoogleGroup => "google_group",
+            AccessResourceKind::TailscaleTag => "tailscale_tag",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for AccessResourceKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "github_repo" | "repo" => Ok(AccessResourceKind::GithubRepo),
+            "airtable_base" | "base" => Ok(AccessResourceKind::AirtableBase),
+            "google_group" | "group" => Ok(AccessResourceKind::GoogleGroup),
+            "tailscale_tag" | "tag" => Ok(AccessResourceKind::TailscaleTag),
+            _ => bail!("`{}` is not a kind of resource access can be requested to", s),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessRequestStatus {
+    Pending,
+    Rejected,
+    Granted,
+    /// The request was approved, but granting the access failed.
+    Failed,
+    /// The access expired and was taken away again.
+    Revoked,
+}
+
+impl fmt::Display for AccessRequestStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            AccessRequestStatus::Pending => "pending",
+            AccessRequestStatus::Rejected => "rejected",
+            AccessRequestStatus::Granted => "granted",
+            AccessRequestStatus::Failed => "failed",
+            AccessRequestStatus::Revoked => "revoked",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct AccessRequest {
+    pub id: i32,
+    pub kind: String,
+    pub resource: String,
+    pub permission: String,
+    pub requester_email: String,
+    pub requester_slack_id: String,
+    pub reason: String,
+    /// How many days the access lasts once granted. It does not expire without them.
+    pub days: Option<i32>,
+    pub status: String,
+    /// The Slack user id of the owner who decided.
+    pub decided_by: String,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub granted_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    /// Why granting or revoking the access failed.
+    pub error: String,
+    pub cio_company_id: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Whether the requester already had the access when the request was granted. Revoking the
+    /// request then leaves that access alone.
+    pub had_access: bool,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = access_requests)]
+struct NewAccessRequest {
+    kind: String,
+    resource: String,
+    permission: String,
+    requester_email: String,
+    requester_slack_id: String,
+    reason: String,
+    days: Option<i32>,
+    status: String,
+    cio_company_id: i32,
+}
+
+/// A request as written after `/access`: `<kind> <resource> [<days>d] [reason]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AccessCommand {
+    kind: AccessResourceKind,
+    resource: String,
+    days: Option<i32>,
+    reason: String,
+}
+
+const USAGE: &str = "Use `/access <kind> <resource> [<days>d] [reason]` to request access, where the kind is one of \
+                     `github-repo`, `airtable-base`, `google-group` or `tailscale-tag`. For example \
+                     `/access github-repo omicron 30d debugging the sled agent`.";
+
+fn parse_access_command(text: &str) -> Result<AccessCommand> {
+    let mut words = text.split_whitespace().peekable();
+
+    let kind = words.next().ok_or_else(|| anyhow!("{}", USAGE))?.parse()?;
+    let resource = words.next().ok_or_else(|| anyhow!("{}", USAGE))?.to_string();
+
+    let days = match words.peek().and_then(|word| word.strip_suffix('d')) {
+        Some(days) => match days.parse::<i32>() {
+            Ok(days) if days > 0 => {
+                words.next();
+                Some(days)
+            }
+            _ => None,
+        },
+        None => None,
+    };
+
+    Ok(AccessCommand {
+        kind,
+        resource,
+        days,
+        reason: words.collect::<Vec<_>>().join(" "),
+    })
+}
+
+impl AccessRequest {
+    pub fn kind(&self) -> Result<AccessResourceKind> {
+        self.kind.parse()
+    }
+
+    pub async fn get(db: &Database, id: i32) -> Result<Option<AccessRequest>> {
+        Ok(access_requests::dsl::access_requests
+            .find(id)
+            .first_async::<AccessRequest>(db.pool())
+            .await
+            .optional()?)
+    }
+
+    fn describe(&self) -> String {
+        let kind = self.kind().map(|kind| kind.describe()).unwrap_or("resource");
+        format!("*{}* access to {} `{}`", self.permission, kind, self.resource)
+    }
+
+    async fn set_status(&self, db: &Database, status: AccessRequestStatus, error: &str) -> Result<AccessRequest> {
+        let now = Utc::now();
+        // Only the grant itself starts the clock, so that recording a failed revoke does not
+        // extend the access.
+        let (granted_at, expires_at) = match (status, self.granted_at) {
+            (AccessRequestStatus::Granted, None) => {
+                (Some(now), self.days.map(|days| now + Duration::days(days.into())))
+            }
+            _ => (self.granted_at, self.expires_at),
+        };
+        let revoked_at = if status == AccessRequestStatus::Revoked {
+            Some(now)
+        } else {
+            self.revoked_at
+        };
+
+        Ok(diesel::update(access_requests::dsl::access_requests.find(self.id))
+            .set((
+                access_requests::dsl::status.eq(status.to_string()),
+                access_requests::dsl::granted_at.eq(granted_at),
+                access_requests::dsl::expires_at.eq(expires_at),
+                access_requests::dsl::revoked_at.eq(revoked_at),
+                access_requests::dsl::error.eq(error.to_string()),
+                access_requests::dsl::updated_at.eq(now),
+            ))
+            .get_result_async::<AccessRequest>(db.pool())
+            .await?)
+    }
+
+    async fn set_had_access(&self, db: &Database) -> Result<AccessRequest> {
+        Ok(diesel::update(access_requests::dsl::access_requests.find(self.id))
+            .set((
+                access_requests::dsl::had_access.eq(true),
+                access_requests::dsl::updated_at.eq(Utc::now()),
+            ))
+            .get_result_async::<AccessRequest>(db.pool())
+            .await?)
+    }
+}
+
+/// The members that Google groups have through granted access requests, as lowercase emails
+/// keyed by the name of the group. The group syncs keep these members along with the ones in
+/// our configs.
+pub async fn granted_google_group_members(
+    db: &Database,
+    company_id: i32,
+) -> Result<BTreeMap<String, BTreeSet<String>>> {
+    let granted = access_requests::dsl::access_requests
+        .filter(access_requests::dsl::cio_company_id.eq(company_id))
+        .filter(access_requests::dsl::kind.eq(AccessResourceKind::GoogleGroup.to_string()))
+        .filter(access_requests::dsl::status.eq(AccessRequestStatus::Granted.to_string()))
+        .load_async::<AccessRequest>(db.pool())
+        .await?;
+
+    let mut members: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for request in granted {
+        members
+            .entry(request.resource)
+            .or_default()
+            .insert(request.requester_email.to_lowercase());
+    }
+
+    Ok(members)
+}
+
+/// Request access for the user behind a Slack account, from the text of the `/access` command.
+/// Returns the reply to the command.
+pub async fn handle_access_command(
+    db: &Database,
+    company: &Company,
+    config: &AccessRequestsConfig,
+    slack_user_id: &str,
+    text: &str,
+) -> Result<String> {
+    let command = match text.trim() {
+        "" | "help" => return Ok(USAGE.to_string()),
+        text => match parse_access_command(text) {
+            Ok(command) => command,
+            Err(e) => return Ok(e.to_string()),
+        },
+    };
+
+    let owners = match config.owners_of(command.kind, &command.resource) {
+        Some(owners) => owners,
+        None => {
+            return Ok(format!(
+                "Nobody owns the {} `{}`, so access to it cannot be requested.",
+                command.kind.describe(),
+                command.resource
+            ))
+        }
+    };
+    if config.slack_channel.is_empty() {
+        bail!("there is no slack channel to request access in");
+    }
+
+    let days = match command.days {
+        Some(days) if config.max_days > 0 => Some(days.min(config.max_days)),
+        None if config.max_days > 0 => Some(config.max_days),
+        days => days,
+    };
+
+    let slack_user = company
+        .authenticate_slack(db)
+        .await?
+        .list_users()
+        .await?
+        .into_iter()
+        .find(|slack_user| slack_user.id == slack_user_id)
+        .ok_or_else(|| anyhow!("could not find Slack user `{}`", slack_user_id))?;
+    let user = employee(db, company, &slack_user.email).await?;
+
+    let open = access_requests::dsl::access_requests
+        .filter(access_requests::dsl::cio_company_id.eq(company.id))
+        .filter(access_requests::dsl::requester_email.eq(user.email.to_string()))
+        .filter(access_requests::dsl::kind.eq(command.kind.to_string()))
+        .filter(access_requests::dsl::resource.eq(command.resource.to_string()))
+        .filter(access_requests::dsl::status.eq(AccessRequestStatus::Pending.to_string()))
+        .first_async::<AccessRequest>(db.pool())
+        .await
+        .optional()?;
+    if open.is_some() {
+        return Ok(format!(
+            "You already asked for access to the {} `{}`, it is waiting on its owners.",
+            command.kind.describe(),
+            command.resource
+        ));
+    }
+
+    let permission = if owners.permission.is_empty() {
+        command.kind.default_permission().to_string()
+    } else {
+        owners.permission.to_string()
+    };
+
+    let request = diesel::insert_into(access_requests::table)
+        .values(NewAccessRequest {
+            kind: command.kind.to_string(),
+            resource: command.resource,
+            permission,
+            requester_email: user.email.to_string(),
+            requester_slack_id: slack_user_id.to_string(),
+            reason: command.reason,
+            days,
+            status: AccessRequestStatus::Pending.to_string(),
+            cio_company_id: company.id,
+        })
+        .get_result_async::<AccessRequest>(db.pool())
+        .await?;
+
+    company
+        .post_to_slack_channel(
+            db,
+            &access_request_message(&config.slack_channel, &owners.owners, &request),
+        )
+        .await?;
+
+    info!(
+        "[access] {} requested {} access to {} `{}` ({})",
+        request.requester_email, request.permission, request.kind, request.resource, request.id
+    );
+
+    Ok(format!(
+        "Asked the owners for {}. You will hear back here once they decide.",
+        request.describe()
+    ))
+}
+
+fn button(text: &str, action_id: &str, request: &AccessRequest) -> BlockOption {
+    BlockOption::ActionBlock(ActionBlock {
+        text_type: MessageType::Button,
+        text: MessageBlockText {
+            text_type: MessageType::PlainText,
+            text: text.to_string(),
+        },
+        value: request.id.to_string(),
+        action_id: action_id.to_string(),
+    })
+}
+
+fn access_request_message(channel: &str, owners: &[String], request: &AccessRequest) -> FormattedMessage {
+    let days = match request.days {
+        Some(days) => format!("for {} days", days),
+        None => "without an expiry".to_string(),
+    };
+    let reason = if request.reason.is_empty() {
+        "no reason given".to_string()
+    } else {
+        request.reason.to_string()
+    };
+    let owners = owners
+        .iter()
+        .map(|owner| format!("<@{}>", owner))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    FormattedMessage {
+        channel: channel.to_string(),
+        attachments: Default::default(),
+        blocks: vec![
+            MessageBlock {
+                block_type: MessageBlockType::Section,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text: format!(
+                        ":key: <@{}> requested {} {}: {}\nOwners: {}",
+                        request.requester_slack_id,
+                        request.describe(),
+                        days,
+                        reason,
+                        owners
+                    ),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+            MessageBlock {
+                block_type: MessageBlockType::Actions,
+                text: Default::default(),
+                elements: vec![
+                    button("Approve and grant", ACCESS_APPROVE_ACTION, request),
+                    button("Reject", ACCESS_REJECT_ACTION, request),
+                ],
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+        ],
+    }
+}
+
+fn direct_message(slack_user_id: &str, text: String) -> FormattedMessage {
+    FormattedMessage {
+        channel: slack_user_id.to_string(),
+        attachments: Default::default(),
+        blocks: vec![MessageBlock {
+            block_type: MessageBlockType::Section,
+            text: Some(MessageBlockText {
+                text_type: MessageType::Markdown,
+                text,
+            }),
+            elements: Default::default(),
+            accessory: Default::default(),
+            block_id: Default::default(),
+            fields: Default::default(),
+        }],
+    }
+}
+
+/// Record the decision of the owner with the Slack user id `slack_user_id` on the request with
+/// `id`, granting the access when it was approved. Returns the text that replaces the request in
+/// Slack.
+pub async fn decide_access_request(
+    db: &Database,
+    company: &Company,
+    config: &AccessRequestsConfig,
+    id: i32,
+    slack_user_id: &str,
+    approve: bool,
+) -> Result<String> {
+    let request = match AccessRequest::get(db, id).await? {
+        Some(request) if request.cio_company_id == company.id => request,
+        _ => bail!("there is no access request {} for {}", id, company.name),
+    };
+    if request.status != AccessRequestStatus::Pending.to_string() {
+        bail!("the request was {} already", request.status);
+    }
+
+    match config.owners_of(request.kind()?, &request.resource) {
+        Some(owners) if owners.owners.iter().any(|owner| owner == slack_user_id) => {}
+        _ => bail!("only the owners of the {} can decide on access to it", request.resource),
+    }
+
+    // Owners can click at the same time, so only the first decision on a pending request counts.
+    let request = match diesel::update(access_requests::dsl::access_requests.find(request.id))
+        .filter(access_requests::dsl::status.eq(AccessRequestStatus::Pending.to_string()))
+        .filter(access_requests::dsl::decided_at.is_null())
+        .set((
+            access_requests::dsl::decided_by.eq(slack_user_id.to_string()),
+            access_requests::dsl::decided_at.eq(Some(Utc::now())),
+            access_requests::dsl::updated_at.eq(Utc::now()),
+        ))
+        .get_result_async::<AccessRequest>(db.pool())
+        .await
+        .optional()?
+    {
+        Some(request) => request,
+        None => bail!("the request was decided on already"),
+    };
+
+    let (request, outcome) = if !approve {
+        (
+            request.set_status(db, AccessRequestStatus::Rejected, "").await?,
+            "rejected".to_string(),
+        )
+    } else {
+        match grant(db, company, &request).await {
+            Ok(had_access) => {
+                let mut request = request.set_status(db, AccessRequestStatus::Granted, "").await?;
+                if had_access {
+                    request = request.set_had_access(db).await?;
+                }
+                let outcome = match request.expires_at {
+                    Some(expires_at) => format!("granted until {}", expires_at.format("%B %-d, %Y")),
+                    None => "granted".to_string(),
+                };
+                (request, outcome)
+            }
+            Err(e) => {
+                warn!("[access] failed to grant access request {}: {}", request.id, e);
+                (
+                    request
+                        .set_status(db, AccessRequestStatus::Failed, &e.to_string())
+                        .await?,
+                    format!("approved, but granting it failed: {}", e),
+                )
+            }
+        }
+    };
+
+    info!(
+        "[access] access request {} by {} was {} by {}",
+        request.id, request.requester_email, request.status, slack_user_id
+    );
+
+    company
+        .post_to_slack_channel(
+            db,
+            &direct_message(
+                &request.requester_slack_id,
+                format!(
+                    "Your request for {} was {} by <@{}>.",
+                    request.describe(),
+                    outcome,
+                    slack_user_id
+                ),
+            ),
+        )
+        .await?;
+
+    Ok(format!(
+        "<@{}>'s request for {} was {} by <@{}>.",
+        request.requester_slack_id,
+        request.describe(),
+        outcome,
+        slack_user_id
+    ))
+}
+
+/// Revoke the access that was granted for a number of days once those days are up.
+pub async fn revoke_expired_access(db: &Database, company: &Company) -> Result<()> {
+    let expired = access_requests::dsl::access_requests
+        .filter(access_requests::dsl::cio_company_id.eq(company.id))
+        .filter(access_requests::dsl::status.eq(AccessRequestStatus::Granted.to_string()))
+        .filter(access_requests::dsl::expires_at.le(Utc::now()))
+        .load_async::<AccessRequest>(db.pool())
+        .await?;
+
+    let mut failed = 0;
+    for request in expired {
+        if let Err(e) = revoke(db, company, &request).await {
+            warn!("[access] failed to revoke access request {}: {}", request.id, e);
+            // Stays granted so that it is retried, with the error on the record.
+            request
+                .set_status(db, AccessRequestStatus::Granted, &e.to_string())
+                .await?;
+            failed += 1;
+            continue;
+        }
+
+        let request = request.set_status(db, AccessRequestStatus::Revoked, "").await?;
+        info!(
+            "[access] revoked the expired access of {} to {} `{}` ({})",
+            request.requester_email, request.kind, request.resource, request.id
+        );
+
+        company
+            .post_to_slack_channel(
+                db,
+                &direct_message(
+                    &request.requester_slack_id,
+                    format!(
+                        "Your {} expired. Use `/access` to request it again.",
+                        request.describe()
+                    ),
+                ),
+            )
+            .await?;
+    }
+
+    if failed > 0 {
+        bail!("failed to revoke {} expired access requests", failed);
+    }
+
+    Ok(())
+}
+
+async fn employee(db: &Database, company: &Company, email: &str) -> Result<User> {
+    users::dsl::users
+        .filter(users::dsl::cio_company_id.eq(company.id))
+        .filter(users::dsl::email.eq(email.to_string()))
+        .first_async::<User>(db.pool())
+        .await
+        .map_err(|_| anyhow!("there is no employee with the email `{}`", email))
+}
+
+fn github_permission(permission: &str) -> Result<octorust::types::TeamsAddUpdateRepoPermissionsInOrgRequestPermission> {
+    use octorust::types::TeamsAddUpdateRepoPermissionsInOrgRequestPermission as Permission;
+
+    Ok(match permission {
+        "pull" => Permission::Pull,
+        "triage" => Permission::Triage,
+        "push" => Permission::Push,
+        "maintain" => Permission::Maintain,
+        "admin" => Permission::Admin,
+        _ => bail!("`{}` is not a permission on GitHub repos", permission),
+    })
+}
+
+/// Give the requester the access they asked for. Returns whether they had it already, in which
+/// case nothing was changed.
+async fn grant(db: &Database, company: &Company, request: &AccessRequest) -> Result<bool> {
+    let user = employee(db, company, &request.requester_email).await?;
+
+    match request.kind()? {
+        AccessResourceKind::GithubRepo => {
+            if user.github.is_empty() {
+                bail!("{} does not have a GitHub account in the configs", user.email);
+            }
+
+            company
+                .authenticate_github()?
+                .repos()
+                .add_collaborator(
+                    &company.github_org,
+                    &request.resource,
+                    &user.github,
+                    &octorust::types::ReposAddCollaboratorRequest {
+                        permission: Some(github_permission(&request.permission)?),
+                        permissions: Default::default(),
+                    },
+                )
+                .await?;
+        }
+        AccessResourceKind::AirtableBase => {
+            let airtable = company.authenticate_airtable(&request.resource);
+            let airtable_user = airtable.get_enterprise_user(&user.email).await?;
+            airtable
+                .add_collaborator_to_base(&request.resource, &airtable_user.id, &request.permission)
+                .await?;
+        }
+        AccessResourceKind::GoogleGroup => {
+            if user.groups.contains(&request.resource) {
+                return Ok(true);
+            }
+
+            let gsuite = company.authenticate_google_admin(db).await?;
+            let group_key = format!("{}@{}", request.resource, company.gsuite_domain);
+            if gsuite.members().has(&group_key, &user.email).await?.is_member {
+                return Ok(true);
+            }
+
+            gsuite.add_user_to_group(company, &user, &request.resource).await?;
+        }
+        AccessResourceKind::TailscaleTag => {
+            set_tailscale_tag_owner(company, &request.resource, &user.email, true).await?;
+        }
+    }
+
+    Ok(false)
+}
+
+/// Whether the requester has another granted request for the same resource, which keeps the
+/// access after this one is revoked.
+async fn has_other_grant(db: &Database, request: &AccessRequest) -> Result<bool> {
+    let other = access_requests::dsl::access_requests
+        .filter(access_requests::dsl::cio_company_id.eq(request.cio_company_id))
+        .filter(access_requests::dsl::requester_email.eq(request.requester_email.to_string()))
+        .filter(access_requests::dsl::kind.eq(request.kind.to_string()))
+        .filter(access_requests::dsl::resource.eq(request.resource.to_string()))
+        .filter(access_requests::dsl::status.eq(AccessRequestStatus::Granted.to_string()))
+        .filter(access_requests::dsl::id.ne(request.id))
+        .first_async::<AccessRequest>(db.pool())
+        .await
+        .optional()?;
+
+    Ok(other.is_some())
+}
+
+async fn revoke(db: &Database, company: &Company, request: &AccessRequest) -> Result<()> {
+    let user = employee(db, company, &request.requester_email).await?;
+
+    match request.kind()? {
+        AccessResourceKind::GithubRepo => {
+            company
+                .authenticate_github()?
+                .repos()
+                .remove_collaborator(&company.github_org, &request.resource, &user.github)
+                .await?;
+        }
+        AccessResourceKind::AirtableBase => {
+            let airtable = company.authenticate_airtable(&request.resource);
+            let airtable_user = airtable.get_enterprise_user(&user.email).await?;
+            airtable
+                .remove_collaborator_from_base(&request.resource, &airtable_user.id)
+                .await?;
+        }
+        AccessResourceKind::GoogleGroup => {
+            // Membership the requester had before asking, or has through our configs or another
+            // request, is not this request's to take away.
+            if request.had_access || user.groups.contains(&request.resource) || has_other_grant(db, request).await? {
+                return Ok(());
+            }
+
+            company
+                .authenticate_google_admin(db)
+                .await?
+                .remove_user_from_group(company, &user, &request.resource)
+                .await?;
+        }
+        AccessResourceKind::TailscaleTag => {
+            set_tailscale_tag_owner(company, &request.resource, &user.email, false).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The policy with `email` added to or removed from the owners of `tag:<tag>`.
+fn with_tag_owner(policy: &AclPolicy, tag: &str, email: &str, owner: bool) -> AclPolicy {
+    let mut policy = policy.clone();
+    let owners = policy.tag_owners.entry(format!("tag:{}", tag)).or_default();
+
+    owners.retain(|existing| existing != email);
+    if owner {
+        owners.push(email.to_string());
+    } else if owners.is_empty() {
+        policy.tag_owners.remove(&format!("tag:{}", tag));
+    }
+
+    policy
+}
+
+async fn set_tailscale_tag_owner(company: &Company, tag: &str, email: &str, owner: bool) -> Result<()> {
+    let tailscale = company.authenticate_tailscale();
+
+    let acl = tailscale.get_acl().await?;
+    let policy = with_tag_owner(&acl.policy, tag, email, owner);
+    if policy == acl.policy {
+        return Ok(());
+    }
+
+    let validation = tailscale.validate_acl(&policy).await?;
+    if !validation.is_valid() {
+        bail!("the tailscale acl policy would be invalid: {}", validation.message);
+    }
+
+    tailscale.set_acl(&policy, acl.etag.as_deref()).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tailscale_api::AclPolicy;
+
+    use super::{parse_access_command, with_tag_owner, AccessCommand, AccessResourceKind};
+
+    #[test]
+    fn test_parse_access_command() {
+        assert_eq!(
+            parse_access_command("github-repo omicron 30d debugging the sled agent").unwrap(),
+            AccessCommand {
+                kind: AccessResourceKind::GithubRepo,
+                resource: "omicron".to_string(),
+                days: Some(30),
+                reason: "debugging the sled agent".to_string(),
+            }
+        );
+
+        // Without days the access does not expire, and a reason can start with a word ending in d.
+        assert_eq!(
+            parse_access_command("google_group eng need the mail").unwrap(),
+            AccessCommand {
+                kind: AccessResourceKind::GoogleGroup,
+                resource: "eng".to_string(),
+                days: None,
+                reason: "need the mail".to_string(),
+            }
+        );
+        assert_eq!(
+            parse_access_command("tag lab-hosts").unwrap(),
+            AccessCommand {
+                kind: AccessResourceKind::TailscaleTag,
+                resource: "lab-hosts".to_string(),
+                days: None,
+                reason: String::new(),
+            }
+        );
+
+        assert!(parse_access_command("printer lobby").is_err());
+        assert!(parse_access_command("airtable-base").is_err());
+    }
+
+    #[test]
+    fn test_with_tag_owner() {
+        let mut policy = AclPolicy::default();
+        policy
+            .tag_owners
+            .insert("tag:lab".to_string(), vec!["ops@example.com".to_string()]);
+
+        let granted = with_tag_owner(&policy, "lab", "jane@example.com", true);
+        assert_eq!(
+            granted.tag_owners["tag:lab"],
+            vec!["ops@example.com".to_string(), "jane@example.com".to_string()]
+        );
+
+        // Granting twice does not add the owner twice.
+        assert_eq!(with_tag_owner(&granted, "lab", "jane@example.com", true), granted);
+
+        let revoked = with_tag_owner(&granted, "lab", "jane@example.com", false);
+        assert_eq!(revoked, policy);
+    }
+}
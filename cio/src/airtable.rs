@@ -23,9 +23,11 @@ pub static AIRTABLE_APPLICATIONS_TABLE: &str = "Applicants";
 pub static AIRTABLE_INTERVIEWS_TABLE: &str = "Interviews";
 pub static AIRTABLE_REVIEWER_LEADERBOARD_TABLE: &str = "Reviewer Leaderboard";
 pub static AIRTABLE_REVIEWS_TABLE: &str = "Reviews";
+pub static AIRTABLE_APPLICANT_DUPLICATES_TABLE: &str = "Suspected Duplicates";
 
 pub static AIRTABLE_DISCUSSION_TOPICS_TABLE: &str = "Discussion topics";
 pub static AIRTABLE_MEETING_SCHEDULE_TABLE: &str = "Meeting schedule";
+pub static AIRTABLE_SIGN_UPS_TABLE: &str = "Sign ups";
 
 pub static AIRTABLE_OUTBOUND_TABLE: &str = "Outbound";
 pub static AIRTABLE_INBOUND_TABLE: &str = "Inbound";
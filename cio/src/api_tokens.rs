@@ -1,7 +1,10 @@
+use std::{future::Future, ops::DerefMut};
+
 use anyhow::Result;
-use async_bb8_diesel::AsyncRunQueryDsl;
+use async_bb8_diesel::{AsyncConnection, AsyncRunQueryDsl};
 use async_trait::async_trait;
 use chrono::{DateTime, Duration, Utc};
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
 use macros::db;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -135,6 +138,62 @@ impl APIToken {
         true
         // }
     }
+
+    /// Returns if the token expires within the given duration, or if we do not know
+    /// when it expires.
+    pub fn expires_within(&self, duration: Duration) -> bool {
+        match self.expires_date {
+            Some(d) => Utc::now() + duration >= d,
+            None => true,
+        }
+    }
+
+    /// Refresh the token with `refresh` if it expires within `margin`, holding a lock on the
+    /// token's row until the refreshed token is saved. Some providers, like QuickBooks, rotate
+    /// the refresh token on every refresh, so two jobs refreshing at the same time would
+    /// otherwise overwrite the valid refresh token with one that has been revoked.
+    ///
+    /// `refresh` is given the latest copy of the token from the database and returns the
+    /// refreshed token.
+    pub async fn refresh_with_lock<F, Fut>(&self, db: &Database, margin: Duration, refresh: F) -> Result<APIToken>
+    where
+        F: FnOnce(APIToken) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<APIToken>>,
+    {
+        let id = self.id;
+        // The transaction runs on a blocking thread, so we drive the refresh from there.
+        let handle = tokio::runtime::Handle::current();
+
+        db.pool()
+            .transaction(move |conn| {
+                let token = api_tokens::dsl::api_tokens
+                    .filter(api_tokens::dsl::id.eq(id))
+                    .for_update()
+                    .first::<APIToken>(conn.deref_mut())?;
+
+                // Another job may have refreshed the token while we waited for the lock.
+                if !token.expires_within(margin) {
+                    return Ok(token);
+                }
+
+                let t = handle.block_on(refresh(token))?;
+
+                let updated = diesel::update(api_tokens::dsl::api_tokens.filter(api_tokens::dsl::id.eq(id)))
+                    .set((
+                        api_tokens::dsl::access_token.eq(t.access_token),
+                        api_tokens::dsl::expires_in.eq(t.expires_in),
+                        api_tokens::dsl::expires_date.eq(t.expires_date),
+                        api_tokens::dsl::refresh_token.eq(t.refresh_token),
+                        api_tokens::dsl::refresh_token_expires_in.eq(t.refresh_token_expires_in),
+                        api_tokens::dsl::refresh_token_expires_date.eq(t.refresh_token_expires_date),
+                        api_tokens::dsl::last_updated_at.eq(t.last_updated_at),
+                    ))
+                    .get_result::<APIToken>(conn.deref_mut())?;
+
+                Ok(updated)
+            })
+            .await
+    }
 }
 
 pub async fn refresh_api_tokens(db: &Database, company: &Company) -> Result<()> {
@@ -1,13 +1,48 @@
-use docusign::Envelope;
+use docusign::{BulkSendingCopy, BulkSendingCopyRecipient, BulkSendingList, Envelope};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt};
 
-use crate::{applicants::Applicant, companies::Company, configs::User};
+use crate::{
+    access_requests::AccessResourceKind, applicants::Applicant, companies::Company, configs::User,
+    github_event_routes::glob_matches, redaction::RedactionTarget,
+};
 
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct DocuSignConfig {
     offer: Envelope,
     piia: Envelope,
+    /// Policies that every employee must periodically acknowledge, keyed by the
+    /// name of the policy.
+    #[serde(default)]
+    policies: HashMap<String, PolicyAcknowledgmentConfig>,
+    /// Who has to approve offers before they are sent.
+    #[serde(default)]
+    pub offer_approval: OfferApprovalConfig,
+}
+
+/// Offers wait for one of the approvers to approve their terms in Slack before they are sent.
+/// Offers are sent as soon as the applicant is giving an offer when there are no approvers.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct OfferApprovalConfig {
+    /// The Slack user ids of the people who can approve offers.
+    #[serde(default)]
+    pub approvers: Vec<String>,
+    /// The Slack channel approvals are requested in. The requests have the terms of the offer in
+    /// them, so this should be a private channel.
+    #[serde(default)]
+    pub slack_channel: String,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct PolicyAcknowledgmentConfig {
+    /// The id of the DocuSign template that is sent to each employee.
+    pub template_id: String,
+    /// The role in the template that the employee signs as.
+    pub role_name: String,
+    #[serde(default)]
+    pub email_subject: String,
+    #[serde(default)]
+    pub email_blurb: String,
 }
 
 impl DocuSignConfig {
@@ -25,8 +60,58 @@ impl DocuSignConfig {
         envelope
     }
 
+    pub fn policy(&self, policy: &str) -> Option<&PolicyAcknowledgmentConfig> {
+        self.policies.get(policy)
+    }
+
+    /// Create a bulk send list with a copy of the policy for each of the given users.
+    pub fn create_policy_acknowledgment_list(&self, policy: &str, users: &[User]) -> Option<BulkSendingList> {
+        self.policies.get(policy).map(|config| BulkSendingList {
+            list_id: String::new(),
+            name: policy.to_string(),
+            bulk_copies: users
+                .iter()
+                .map(|user| BulkSendingCopy {
+                    recipients: vec![BulkSendingCopyRecipient {
+                        recipient_id: String::new(),
+                        role_name: config.role_name.to_string(),
+                        name: user.full_name(),
+                        email: user.email.to_string(),
+                    }],
+                    custom_fields: vec![],
+                    email_subject: config.email_subject.replace("{user_name}", &user.full_name()),
+                    email_blurb: config.email_blurb.replace("{user_name}", &user.full_name()),
+                })
+                .collect(),
+        })
+    }
+
     fn fill_envelope(envelope: &mut Envelope, applicant: &Applicant) {
+        let start_date = applicant
+            .start_date
+            .map(|d| d.format("%m/%d/%Y").to_string())
+            .unwrap_or_default();
+        let fill_terms = |value: &str| -> String {
+            value
+                .replace("{applicant_name}", &applicant.name)
+                .replace("{applicant_email}", &applicant.email)
+                .replace("{applicant_start_date}", &start_date)
+                .replace("{applicant_salary}", &applicant.offer_salary)
+                .replace("{applicant_equity}", &applicant.offer_equity)
+        };
+
+        for custom_field in envelope.custom_fields.text_custom_fields.iter_mut() {
+            custom_field.value = fill_terms(&custom_field.value);
+        }
+
         for template_role in envelope.template_roles.iter_mut() {
+            for tab in template_role.tabs.text_tabs.iter_mut() {
+                tab.value = fill_terms(&tab.value);
+            }
+            for tab in template_role.tabs.date_tabs.iter_mut() {
+                tab.value = fill_terms(&tab.value);
+            }
+
             template_role.name = template_role.name.replace("{applicant_name}", &applicant.name);
             template_role.email = template_role.email.replace("{applicant_email}", &applicant.email);
             template_role.signer_name = template_role.signer_name.replace("{applicant_name}", &applicant.name);
@@ -50,6 +135,56 @@ impl DocuSignConfig {
 pub struct OnboardingConfig {
     pub new_hire_issue: NewHireIssue,
     welcome_letter: Letter,
+    /// The tasks that are run for every new hire, scheduled around their start date. A task
+    /// only runs once the tasks it depends on are done. Nothing is run when this is empty.
+    #[serde(default)]
+    pub tasks: Vec<OnboardingTaskConfig>,
+    /// The Slack channel the checklist goes to when the new hire's manager cannot be found in
+    /// Slack.
+    #[serde(default)]
+    pub checklist_channel: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct OnboardingTaskConfig {
+    /// Unique among the tasks, and what other tasks depend on.
+    pub name: String,
+    pub kind: OnboardingTaskKind,
+    /// When the task is due, in days from the start date. Negative is before the start date.
+    pub day: i64,
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// What is in the package, for `ship_hardware` and `swag_preferences` tasks.
+    #[serde(default)]
+    pub contents: String,
+    /// The channel the welcome is posted in, for `slack_welcome` tasks.
+    #[serde(default)]
+    pub channel: String,
+    /// How many times the task is tried before it is given up on.
+    #[serde(default = "default_onboarding_task_max_attempts")]
+    pub max_attempts: i32,
+}
+
+fn default_onboarding_task_max_attempts() -> i32 {
+    5
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingTaskKind {
+    /// Create the new hire's Google Workspace account.
+    GoogleAccount,
+    /// Invite the new hire to the GitHub organization.
+    #[serde(rename = "github_invite")]
+    GitHubInvite,
+    /// Ship hardware to the new hire's home address.
+    ShipHardware,
+    /// Welcome the new hire in a Slack channel.
+    SlackWelcome,
+    /// Issue the new hire's device credentials and send them the links to download them.
+    DeviceCredentials,
+    /// Ask the new hire for their shirt size and where to ship their swag.
+    SwagPreferences,
 }
 
 impl OnboardingConfig {
@@ -71,105 +206,1183 @@ impl OnboardingConfig {
             .replace("{user_github}", &user.github)
             .replace("{company_github}", &company.github_org);
 
-        letter.cc.push(user.email.to_string());
+        letter.cc.push(user.email.to_string());
+
+        letter
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct OffboardingConfig {
+    /// The Slack channel that the report of what was done to offboard someone is posted to.
+    /// Reports are not posted when this is empty.
+    #[serde(default)]
+    pub report_channel: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AssetsConfig {
+    /// The Slack channel that hardware that was not returned after offboarding is brought up
+    /// in. Nobody is reminded when this is empty.
+    #[serde(default)]
+    pub unreturned_channel: String,
+    /// How often the same unreturned hardware is brought up again.
+    #[serde(default = "default_unreturned_reminder_days")]
+    pub unreturned_reminder_days: i64,
+}
+
+impl Default for AssetsConfig {
+    fn default() -> Self {
+        Self {
+            unreturned_channel: String::new(),
+            unreturned_reminder_days: default_unreturned_reminder_days(),
+        }
+    }
+}
+
+fn default_unreturned_reminder_days() -> i64 {
+    3
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SwagConfig {
+    /// The location that store orders are shipped from, and that received stock goes to when
+    /// no location is given.
+    #[serde(default = "default_swag_location")]
+    pub default_location: String,
+    /// The number of units at or below which stock at a location is considered low, for
+    /// locations that do not set a threshold of their own.
+    #[serde(default)]
+    pub low_stock_threshold: i32,
+    /// The Slack user id of the office manager, who is sent a message when stock runs low.
+    #[serde(default)]
+    pub office_manager: String,
+    /// How people that are sent swag tell us their shirt size and where to ship it.
+    #[serde(default)]
+    pub preferences: SwagPreferencesConfig,
+}
+
+impl Default for SwagConfig {
+    fn default() -> Self {
+        Self {
+            default_location: default_swag_location(),
+            low_stock_threshold: 0,
+            office_manager: String::new(),
+            preferences: Default::default(),
+        }
+    }
+}
+
+fn default_swag_location() -> String {
+    "office".to_string()
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SwagPreferencesConfig {
+    /// The URL of the form that preferences are submitted through, the token is appended to it.
+    #[serde(default)]
+    pub form_url: String,
+    /// The shirt sizes that can be picked on the form.
+    #[serde(default = "default_swag_shirt_sizes")]
+    pub shirt_sizes: Vec<String>,
+    /// How long a link to the form works for.
+    #[serde(default = "default_swag_preferences_expires_days")]
+    pub expires_days: i64,
+    /// The email sent to people that are not in our Slack, ie. conference winners. Replaces
+    /// `{name}`, `{contents}` and `{form_link}`.
+    #[serde(default)]
+    pub letter: Option<Letter>,
+}
+
+impl Default for SwagPreferencesConfig {
+    fn default() -> Self {
+        Self {
+            form_url: String::new(),
+            shirt_sizes: default_swag_shirt_sizes(),
+            expires_days: default_swag_preferences_expires_days(),
+            letter: None,
+        }
+    }
+}
+
+impl SwagPreferencesConfig {
+    /// The email asking someone for their preferences, if one is configured.
+    pub fn create_letter(&self, name: &str, contents: &str, form_link: &str) -> Option<Letter> {
+        let mut letter = self.letter.clone()?;
+        letter.subject = letter.subject.replace("{name}", name).replace("{contents}", contents);
+        letter.body = letter
+            .body
+            .replace("{name}", name)
+            .replace("{contents}", contents)
+            .replace("{form_link}", form_link);
+
+        Some(letter)
+    }
+}
+
+fn default_swag_shirt_sizes() -> Vec<String> {
+    ["XS", "S", "M", "L", "XL", "2XL", "3XL"]
+        .iter()
+        .map(|size| size.to_string())
+        .collect()
+}
+
+fn default_swag_preferences_expires_days() -> i64 {
+    14
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DeviceCredentialsConfig {
+    /// The credentials every employee is issued for their devices.
+    #[serde(default)]
+    pub kinds: Vec<DeviceCredentialKind>,
+    /// The URL that credentials are downloaded from, the download token is appended to it.
+    #[serde(default)]
+    pub download_url: String,
+    /// How long a download link works for. Links also stop working once they are used.
+    #[serde(default = "default_device_credential_download_hours")]
+    pub download_hours: i64,
+    #[serde(default = "default_device_certificate_days")]
+    pub certificate_days: u32,
+    /// Certificates that expire within this many days are reissued.
+    #[serde(default = "default_device_certificate_renew_within_days")]
+    pub certificate_renew_within_days: i64,
+    /// How long a Tailscale auth key can be used to add a device to the tailnet.
+    #[serde(default = "default_device_tailscale_key_days")]
+    pub tailscale_key_days: i64,
+    /// The ACL tags applied to devices that join with an employee's auth key.
+    #[serde(default)]
+    pub tailscale_tags: Vec<String>,
+}
+
+impl Default for DeviceCredentialsConfig {
+    fn default() -> Self {
+        Self {
+            kinds: vec![],
+            download_url: String::new(),
+            download_hours: default_device_credential_download_hours(),
+            certificate_days: default_device_certificate_days(),
+            certificate_renew_within_days: default_device_certificate_renew_within_days(),
+            tailscale_key_days: default_device_tailscale_key_days(),
+            tailscale_tags: vec![],
+        }
+    }
+}
+
+fn default_device_credential_download_hours() -> i64 {
+    24
+}
+
+fn default_device_certificate_days() -> u32 {
+    365
+}
+
+fn default_device_certificate_renew_within_days() -> i64 {
+    30
+}
+
+fn default_device_tailscale_key_days() -> i64 {
+    7
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceCredentialKind {
+    /// A client certificate signed by our device CA.
+    Certificate,
+    /// A single use, preauthorized key that adds a device to the tailnet.
+    TailscaleAuthKey,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JournalClubConfig {
+    /// The huddle, by its slug in the configs, that proposed papers are put on the agenda of.
+    /// Papers are not scheduled without one.
+    #[serde(default)]
+    pub huddle: String,
+    /// How many papers are put on the agenda of a single meeting.
+    #[serde(default = "default_journal_club_papers_per_meeting")]
+    pub papers_per_meeting: usize,
+    /// The Slack channel each paper gets a discussion thread in. No threads are started without
+    /// one.
+    #[serde(default)]
+    pub channel: String,
+}
+
+impl Default for JournalClubConfig {
+    fn default() -> Self {
+        Self {
+            huddle: String::new(),
+            papers_per_meeting: default_journal_club_papers_per_meeting(),
+            channel: String::new(),
+        }
+    }
+}
+
+fn default_journal_club_papers_per_meeting() -> usize {
+    1
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CelebrationsConfig {
+    /// The Slack channel birthdays and work anniversaries are posted in. Nothing is posted
+    /// without one.
+    #[serde(default)]
+    pub channel: String,
+    /// The hour of the day, in each person's own time zone, that their celebrations are posted.
+    #[serde(default = "default_celebration_hour")]
+    pub post_hour: u32,
+    /// The time zone of people who do not have one in Slack, which is also the time zone the
+    /// weekly digest goes out in.
+    #[serde(default = "default_celebrations_time_zone")]
+    pub time_zone: String,
+    /// The work anniversaries, in years, that are called out as milestones.
+    #[serde(default = "default_milestone_years")]
+    pub milestone_years: Vec<i32>,
+}
+
+impl Default for CelebrationsConfig {
+    fn default() -> Self {
+        Self {
+            channel: String::new(),
+            post_hour: default_celebration_hour(),
+            time_zone: default_celebrations_time_zone(),
+            milestone_years: default_milestone_years(),
+        }
+    }
+}
+
+fn default_celebration_hour() -> u32 {
+    9
+}
+
+fn default_celebrations_time_zone() -> String {
+    "America/Los_Angeles".to_string()
+}
+
+fn default_milestone_years() -> Vec<i32> {
+    vec![1, 5, 10, 15, 20, 25]
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct NewHireIssue {
+    pub assignees: Vec<String>,
+    pub alerts: Vec<String>,
+    pub default_groups: Vec<String>,
+    pub aws_roles: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Letter {
+    pub subject: String,
+    pub body: String,
+    pub from: String,
+    #[serde(default)]
+    pub cc: Vec<String>,
+    #[serde(default)]
+    pub bcc: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct ApplyConfig {
+    received: Letter,
+    rejection: HashMap<String, Letter>,
+    /// How applicants are scored.
+    #[serde(default)]
+    pub scoring: ApplicantScoringConfig,
+    /// How interviews are scheduled with applicants.
+    #[serde(default)]
+    pub interviews: InterviewSchedulingConfig,
+    /// The Slack channel the weekly recruiting funnel summary is posted to. Defaults to the
+    /// company's applicants channel.
+    #[serde(default)]
+    pub funnel_summary_slack_channel: String,
+    /// How applications emailed to the careers inbox are turned into applicants.
+    #[serde(default)]
+    pub careers_inbox: CareersInboxConfig,
+    /// What interviewers are sent ahead of their interviews.
+    #[serde(default)]
+    pub interviewer_packets: InterviewerPacketsConfig,
+}
+
+/// Applications emailed to the careers inbox are matched to a role by the role's name in the
+/// subject, or failing that in the body of the email.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CareersInboxConfig {
+    /// The names of the open roles, as they are named on applicants.
+    #[serde(default)]
+    pub roles: Vec<String>,
+    /// The role of applications that do not name one. When this is empty, those applications
+    /// are posted to the applicants channel to be triaged by hand instead.
+    #[serde(default)]
+    pub default_role: String,
+}
+
+/// When interviews can be scheduled, and how the times that work for the panel are proposed
+/// to applicants.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct InterviewSchedulingConfig {
+    /// The time zone of the working hours, ie. `America/Los_Angeles`.
+    #[serde(default = "default_interview_time_zone")]
+    pub time_zone: String,
+    /// The hour of the day interviews can start at, in `time_zone`.
+    #[serde(default = "default_interview_day_start_hour")]
+    pub day_start_hour: u32,
+    /// The hour of the day interviews have to end by, in `time_zone`.
+    #[serde(default = "default_interview_day_end_hour")]
+    pub day_end_hour: u32,
+    /// How far out to look for times, in days.
+    #[serde(default = "default_interview_search_days")]
+    pub search_days: i64,
+    /// How much notice the applicant and the panel get at the least, in hours.
+    #[serde(default = "default_interview_notice_hours")]
+    pub notice_hours: i64,
+    /// How many times to propose. Only one time is proposed a day, so that applicants have a
+    /// choice of days.
+    #[serde(default = "default_interview_proposals")]
+    pub proposals: usize,
+    /// The page applicants pick a time on. The link to it in the email has the `token` of the
+    /// proposal as a query parameter.
+    #[serde(default)]
+    pub confirm_url: String,
+    /// The email that proposes the times. Supports `{applicant_name}`, `{applicant_role}`,
+    /// `{interview_times}` and `{confirm_link}`.
+    #[serde(default)]
+    pub proposal_letter: Option<Letter>,
+}
+
+impl Default for InterviewSchedulingConfig {
+    fn default() -> Self {
+        Self {
+            time_zone: default_interview_time_zone(),
+            day_start_hour: default_interview_day_start_hour(),
+            day_end_hour: default_interview_day_end_hour(),
+            search_days: default_interview_search_days(),
+            notice_hours: default_interview_notice_hours(),
+            proposals: default_interview_proposals(),
+            confirm_url: String::new(),
+            proposal_letter: None,
+        }
+    }
+}
+
+fn default_interview_time_zone() -> String {
+    "America/Los_Angeles".to_string()
+}
+
+fn default_interview_day_start_hour() -> u32 {
+    9
+}
+
+fn default_interview_day_end_hour() -> u32 {
+    17
+}
+
+fn default_interview_search_days() -> i64 {
+    14
+}
+
+fn default_interview_notice_hours() -> i64 {
+    48
+}
+
+fn default_interview_proposals() -> usize {
+    5
+}
+
+/// Interviewers are sent a packet in Slack the evening before each of their interviews.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct InterviewerPacketsConfig {
+    /// Packets are only sent when this is set.
+    #[serde(default)]
+    pub enabled: bool,
+    /// The hour of the evening before an interview its packets are sent at, in the time zone of
+    /// the interview working hours.
+    #[serde(default = "default_interviewer_packet_hour")]
+    pub send_hour: u32,
+    /// The description of each role, by its name as it is on applicants.
+    #[serde(default)]
+    pub role_descriptions: HashMap<String, String>,
+    /// The questions to ask in each interview module, by the name of the session of the panel,
+    /// ie. `Technical`.
+    #[serde(default)]
+    pub question_banks: HashMap<String, Vec<String>>,
+}
+
+impl Default for InterviewerPacketsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            send_hour: default_interviewer_packet_hour(),
+            role_descriptions: HashMap::new(),
+            question_banks: HashMap::new(),
+        }
+    }
+}
+
+fn default_interviewer_packet_hour() -> u32 {
+    18
+}
+
+/// The model applicants are scored with. An applicant's score is the weighted average of the
+/// ratings the criteria give it, out of 100.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ApplicantScoringConfig {
+    #[serde(default)]
+    pub criteria: Vec<ScoringCriterion>,
+}
+
+impl Default for ApplicantScoringConfig {
+    fn default() -> Self {
+        let criterion = |kind, weight| ScoringCriterion {
+            kind,
+            weight,
+            keywords: vec![],
+            roles: vec![],
+        };
+
+        Self {
+            criteria: vec![
+                criterion(ScoringCriterionKind::ReviewerEvaluations, 4.0),
+                criterion(ScoringCriterionKind::AnsweredQuestions, 1.0),
+                criterion(ScoringCriterionKind::WorkSamples, 1.0),
+                criterion(ScoringCriterionKind::PublicWork, 1.0),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ScoringCriterion {
+    pub kind: ScoringCriterionKind,
+    #[serde(default = "default_scoring_weight")]
+    pub weight: f32,
+    /// The words that `materials_keywords` looks for, ie. `rust`.
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// The roles the criterion applies to. It applies to every role when this is empty.
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+fn default_scoring_weight() -> f32 {
+    1.0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoringCriterionKind {
+    /// How favorably reviewers evaluated the applicant.
+    ReviewerEvaluations,
+    /// How many of the application's questions the applicant answered in their materials.
+    AnsweredQuestions,
+    /// Whether the applicant shared samples of their work, writing, analysis, presentations or
+    /// exploration.
+    WorkSamples,
+    /// Whether the applicant linked to public work, ie. their GitHub or a portfolio.
+    PublicWork,
+    /// How many of the criterion's keywords the applicant's resume and materials mention.
+    MaterialsKeywords,
+}
+
+impl ApplyConfig {
+    pub fn create_received_letter(&self, applicant: &Applicant) -> Letter {
+        let mut letter = self.received.clone();
+        letter.subject = letter
+            .subject
+            .replace("{applicant_name}", &applicant.name)
+            .replace("{applicant_email}", &applicant.email)
+            .replace("{applicant_role}", &applicant.role);
+        letter.body = letter
+            .body
+            .replace("{applicant_name}", &applicant.name)
+            .replace("{applicant_email}", &applicant.email)
+            .replace("{applicant_role}", &applicant.role);
+
+        letter
+    }
+
+    pub fn create_rejection_letter(&self, letter_key: &str, applicant: &Applicant) -> Option<Letter> {
+        self.rejection.get(letter_key).map(|letter| {
+            let mut letter = letter.clone();
+            letter.subject = letter
+                .subject
+                .replace("{applicant_name}", &applicant.name)
+                .replace("{applicant_email}", &applicant.email)
+                .replace("{applicant_role}", &applicant.role);
+            letter.body = letter
+                .body
+                .replace("{applicant_name}", &applicant.name)
+                .replace("{applicant_email}", &applicant.email)
+                .replace("{applicant_role}", &applicant.role);
+
+            letter
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct LegacyExpensifyConfig {
+    pub aliases: HashMap<String, String>,
+    pub emails_to_exclude: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct FinanceConfig {
+    pub legacy_expensify: LegacyExpensifyConfig,
+    pub merchant_aliases: HashMap<String, String>,
+    pub vendor_aliases: HashMap<String, String>,
+    /// The QuickBooks account Ramp card purchases are booked to. When set, receipts from
+    /// Ramp are attached to the matching QuickBooks purchases.
+    #[serde(default)]
+    pub quickbooks_ramp_account: String,
+    /// The Slack channel the monthly finance summary is posted to. Defaults to the
+    /// company's finance channel.
+    #[serde(default)]
+    pub summary_slack_channel: String,
+    /// The spending limits of the Ramp cards issued to employees.
+    #[serde(default)]
+    pub ramp_cards: RampCardsConfig,
+    /// How payments taken through Stripe are booked in QuickBooks.
+    #[serde(default)]
+    pub stripe: StripeConfig,
+    /// What the weekly expense report flags.
+    #[serde(default)]
+    pub expense_anomalies: ExpenseAnomaliesConfig,
+    /// The QuickBooks classes and departments spend is attributed to.
+    #[serde(default)]
+    pub cost_centers: CostCentersConfig,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct CostCentersConfig {
+    /// Cost centers keyed by vendor, as the vendor is named once the merchant aliases are
+    /// applied.
+    #[serde(default)]
+    pub vendors: HashMap<String, CostCenter>,
+    /// Cost centers keyed by the expense account a line is booked to, for vendors that do not
+    /// have their own.
+    #[serde(default)]
+    pub accounts: HashMap<String, CostCenter>,
+}
+
+impl CostCentersConfig {
+    pub fn is_empty(&self) -> bool {
+        self.vendors.is_empty() && self.accounts.is_empty()
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CostCenter {
+    /// The name of the QuickBooks class.
+    #[serde(default)]
+    pub class: String,
+    /// The name of the QuickBooks department.
+    #[serde(default)]
+    pub department: String,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct RampCardsConfig {
+    /// The limit for employees whose department does not have its own limit. When this is not
+    /// set, cards are only issued to employees in the configured departments.
+    #[serde(default)]
+    pub default_limit: Option<RampCardLimit>,
+    /// Limits keyed by department name.
+    #[serde(default)]
+    pub department_limits: HashMap<String, RampCardLimit>,
+}
+
+impl RampCardsConfig {
+    pub fn limit_for(&self, department: &str) -> Option<&RampCardLimit> {
+        self.department_limits.get(department).or(self.default_limit.as_ref())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RampCardLimit {
+    pub amount: f64,
+    pub interval: ramp_minimal_api::SpendingInterval,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct StripeConfig {
+    /// The QuickBooks item that store orders are sold as. Store orders are not booked in
+    /// QuickBooks when this is not set.
+    #[serde(default)]
+    pub quickbooks_store_item: String,
+    /// The QuickBooks item that donations are sold as. Donations are not booked in QuickBooks
+    /// when this is not set.
+    #[serde(default)]
+    pub quickbooks_donation_item: String,
+    /// The Slack channel refunds are posted to. Defaults to the company's finance channel.
+    #[serde(default)]
+    pub refunds_slack_channel: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ExpenseAnomaliesConfig {
+    /// Merchants that are out of policy, matched against the merchant name after vendor aliases
+    /// are applied, ignoring case.
+    #[serde(default)]
+    pub disallowed_merchants: Vec<String>,
+    /// Merchant categories that are out of policy, ie. `Gambling`.
+    #[serde(default)]
+    pub disallowed_categories: Vec<String>,
+    /// Charges by the same person, at the same merchant, for the same amount, this many hours
+    /// apart or less are flagged as duplicates.
+    #[serde(default = "default_duplicate_window_hours")]
+    pub duplicate_window_hours: i64,
+    /// A department's spend for the week is a spike when it is this many times its weekly
+    /// average over the weeks before.
+    #[serde(default = "default_spike_ratio")]
+    pub spike_ratio: f32,
+    /// Spikes in spend below this amount are not worth flagging.
+    #[serde(default = "default_spike_min_amount")]
+    pub spike_min_amount: f32,
+    /// The number of weeks before the report's week that spend is averaged over.
+    #[serde(default = "default_spike_baseline_weeks")]
+    pub spike_baseline_weeks: i64,
+    /// The Slack channel the report is posted to. Defaults to the company's finance channel.
+    #[serde(default)]
+    pub slack_channel: String,
+}
+
+impl Default for ExpenseAnomaliesConfig {
+    fn default() -> Self {
+        Self {
+            disallowed_merchants: vec![],
+            disallowed_categories: vec![],
+            duplicate_window_hours: default_duplicate_window_hours(),
+            spike_ratio: default_spike_ratio(),
+            spike_min_amount: default_spike_min_amount(),
+            spike_baseline_weeks: default_spike_baseline_weeks(),
+            slack_channel: String::new(),
+        }
+    }
+}
+
+fn default_duplicate_window_hours() -> i64 {
+    72
+}
+
+fn default_spike_ratio() -> f32 {
+    2.0
+}
+
+fn default_spike_min_amount() -> f32 {
+    1000.0
+}
+
+fn default_spike_baseline_weeks() -> i64 {
+    8
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct GitHubConfig {
+    #[serde(default)]
+    pub ignored_repos: Vec<String>,
+    /// Only report the changes the GitHub teams sync would make to teams, team members and
+    /// repo permissions, without making them.
+    #[serde(default)]
+    pub teams_dry_run: bool,
+    /// Only report the repo settings that differ from the `github-repo-settings` configs,
+    /// without changing them.
+    #[serde(default)]
+    pub repo_settings_dry_run: bool,
+    /// The Slack channel the repo settings that differed from the configs are reported to. Only
+    /// the logs have them when this is empty.
+    #[serde(default)]
+    pub repo_settings_channel: String,
+    /// When to stop syncing from GitHub while it is failing.
+    #[serde(default)]
+    pub circuit_breaker: GitHubCircuitBreakerConfig,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GitHubCircuitBreakerConfig {
+    /// How many requests in a row have to fail for the sync to pause.
+    #[serde(default = "default_github_failure_threshold")]
+    pub failure_threshold: u32,
+    /// How long the sync pauses for before trying again. It gives up when GitHub is still failing
+    /// after the pause.
+    #[serde(default = "default_github_cooldown_secs")]
+    pub cooldown_secs: u64,
+    /// The Slack channel to alert when the sync pauses. Only the logs have it when this is empty.
+    #[serde(default)]
+    pub alert_channel: String,
+}
+
+impl Default for GitHubCircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: default_github_failure_threshold(),
+            cooldown_secs: default_github_cooldown_secs(),
+            alert_channel: String::new(),
+        }
+    }
+}
+
+fn default_github_failure_threshold() -> u32 {
+    5
+}
+
+fn default_github_cooldown_secs() -> u64 {
+    300
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct MailingListConfig {
+    /// Subscription settings keyed by MailChimp audience (list) id. Signups are only added
+    /// to audiences that are configured here.
+    #[serde(default)]
+    pub audiences: HashMap<String, AudienceConfig>,
+    /// The customer journey step to start for each of our events, ie. `shipment_delivered`.
+    #[serde(default)]
+    pub journeys: HashMap<String, JourneyStepConfig>,
+    /// The service that holds each company's mailing list, keyed by company name. Companies
+    /// that are not listed use MailChimp.
+    #[serde(default)]
+    pub providers: HashMap<String, MailingListProviderKind>,
+}
+
+impl MailingListConfig {
+    pub fn audience(&self, list_id: &str) -> Option<&AudienceConfig> {
+        self.audiences.get(list_id)
+    }
+
+    pub fn journey(&self, event: &str) -> Option<&JourneyStepConfig> {
+        self.journeys.get(event)
+    }
+
+    pub fn provider(&self, company_name: &str) -> MailingListProviderKind {
+        self.providers.get(company_name).copied().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MailingListProviderKind {
+    #[default]
+    MailChimp,
+    Mailerlite,
+}
+
+impl fmt::Display for MailingListProviderKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MailChimp => write!(f, "mailchimp"),
+            Self::Mailerlite => write!(f, "mailerlite"),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct JourneyStepConfig {
+    pub journey_id: i64,
+    /// The id of the journey's "API trigger" starting point.
+    pub step_id: i64,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct AudienceConfig {
+    /// Add new members as pending so that MailChimp asks them to confirm their subscription
+    /// before they receive anything.
+    #[serde(default)]
+    pub double_opt_in: bool,
+    /// The ids of the audience's marketing permissions that the signup form asks consent
+    /// for. Submitting the form grants each of them.
+    #[serde(default)]
+    pub marketing_permission_ids: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct TailscaleConfig {
+    /// Auth keys used by our infrastructure that are reissued before they expire, keyed by a
+    /// name that is used as the description of the key in Tailscale.
+    #[serde(default)]
+    pub auth_keys: HashMap<String, TailscaleAuthKeyConfig>,
+    /// The settings for the servers in our tailnet, keyed by hostname.
+    #[serde(default)]
+    pub devices: HashMap<String, TailscaleDeviceConfig>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct TailscaleDeviceConfig {
+    /// The ACL tags of the device. Tags that are not listed here are removed.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub disable_key_expiry: bool,
+    /// The subnet routes the device advertises that are approved.
+    #[serde(default)]
+    pub routes: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct TailscaleAuthKeyConfig {
+    #[serde(default)]
+    pub reusable: bool,
+    #[serde(default)]
+    pub ephemeral: bool,
+    #[serde(default)]
+    pub preauthorized: bool,
+    /// The ACL tags applied to devices that join with the key, ie. `tag:ci`.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// How long each key is valid for. Tailscale does not allow more than 90 days.
+    #[serde(default = "default_auth_key_expiry_days")]
+    pub expiry_days: i64,
+    /// How long before the current key expires that it is replaced.
+    #[serde(default = "default_auth_key_rotate_days_before_expiry")]
+    pub rotate_days_before_expiry: i64,
+    /// The repos that use the key as a GitHub Actions secret. The repos must be in the org
+    /// for the company.
+    #[serde(default)]
+    pub repos: Vec<String>,
+    #[serde(default)]
+    pub github_actions_secret_name: String,
+}
+
+fn default_auth_key_expiry_days() -> i64 {
+    90
+}
+
+fn default_auth_key_rotate_days_before_expiry() -> i64 {
+    14
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct BackgroundCheckConfig {
+    /// The Checkr continuous checks every full-time employee is enrolled in, ie. `criminal`.
+    /// Nobody is enrolled when this is empty.
+    #[serde(default)]
+    pub continuous_check_types: Vec<String>,
+    /// The Slack user ids of the hiring managers, by role. They are told when the background
+    /// check of an applicant for the role changes status.
+    #[serde(default)]
+    pub hiring_managers: HashMap<String, String>,
+    /// The Slack channel background check changes are posted to for employees, and for
+    /// applicants to roles without a hiring manager. Nothing is posted when this is empty.
+    #[serde(default)]
+    pub slack_channel: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CertificatesConfig {
+    /// Certificates that expire within this many days are renewed.
+    #[serde(default = "default_certificate_renew_within_days")]
+    pub renew_within_days: i64,
+    /// Where renewed certificates are written in addition to the default GCS bucket and
+    /// GitHub repo.
+    #[serde(default)]
+    pub storage: Vec<CertificateStorageConfig>,
+}
+
+impl Default for CertificatesConfig {
+    fn default() -> Self {
+        Self {
+            renew_within_days: default_certificate_renew_within_days(),
+            storage: vec![],
+        }
+    }
+}
+
+fn default_certificate_renew_within_days() -> i64 {
+    12
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum CertificateStorageConfig {
+    /// A KV version 2 secrets engine. The token is read from `VAULT_TOKEN`.
+    Vault {
+        address: String,
+        #[serde(default = "default_vault_mount")]
+        mount: String,
+        #[serde(default)]
+        path_prefix: String,
+    },
+    /// A TLS secret in the cluster that webhooky runs in.
+    Kubernetes { namespace: String },
+}
+
+fn default_vault_mount() -> String {
+    "secret".to_string()
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct GoogleWorkspaceConfig {
+    /// The organizational unit that users are placed in. When this is not set, users are left
+    /// in whichever unit they are already in.
+    #[serde(default)]
+    pub org_unit_path: String,
+    /// Organizational units keyed by department name.
+    #[serde(default)]
+    pub department_org_units: HashMap<String, String>,
+    /// The organizational unit that users are moved to when they are suspended.
+    #[serde(default)]
+    pub suspended_org_unit_path: String,
+    /// Transfer the Drive files of offboarded users to their manager.
+    #[serde(default)]
+    pub transfer_drive_to_manager: bool,
+}
+
+impl GoogleWorkspaceConfig {
+    pub fn org_unit_for(&self, department: &str) -> Option<&str> {
+        self.department_org_units
+            .get(department)
+            .map(|path| path.as_str())
+            .or(Some(self.org_unit_path.as_str()))
+            .filter(|path| !path.is_empty())
+    }
+}
 
-        letter
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct IdentityConfig {
+    /// The identity provider of each company, keyed by company name. Companies that are not
+    /// listed use Google.
+    #[serde(default)]
+    pub providers: HashMap<String, IdentityProviderKind>,
+}
+
+impl IdentityConfig {
+    pub fn provider(&self, company_name: &str) -> IdentityProviderKind {
+        self.providers.get(company_name).copied().unwrap_or_default()
+    }
+}
+
+/// The service that accounts are created in for new users, and that every other service signs
+/// in through.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdentityProviderKind {
+    #[default]
+    Google,
+    Okta,
+}
+
+impl fmt::Display for IdentityProviderKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Google => write!(f, "google"),
+            Self::Okta => write!(f, "okta"),
+        }
     }
 }
 
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
-pub struct NewHireIssue {
-    pub assignees: Vec<String>,
-    pub alerts: Vec<String>,
-    pub default_groups: Vec<String>,
-    pub aws_roles: Vec<String>,
+pub struct OktaConfig {
+    /// The groups whose members are assigned to each Okta application, keyed by the label of
+    /// the application.
+    #[serde(default)]
+    pub app_assignments: HashMap<String, Vec<String>>,
 }
 
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
-pub struct Letter {
-    pub subject: String,
-    pub body: String,
-    pub from: String,
+pub struct PagerDutyConfig {
+    /// The Slack channel that on-call handoffs are posted to. Handoffs are not posted when this
+    /// is empty.
     #[serde(default)]
-    pub cc: Vec<String>,
+    pub handoff_channel: String,
+    /// The PagerDuty teams that are provisioned from the configs, keyed by team name.
     #[serde(default)]
-    pub bcc: Vec<String>,
+    pub teams: HashMap<String, PagerDutyTeamConfig>,
 }
 
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
-pub struct ApplyConfig {
-    received: Letter,
-    rejection: HashMap<String, Letter>,
+pub struct PagerDutyTeamConfig {
+    /// The group whose members are on the team.
+    pub group: String,
+    /// The schedules that are escalated to in order, by name. The schedules themselves, and
+    /// who is on them, are managed in PagerDuty.
+    #[serde(default)]
+    pub schedules: Vec<String>,
+    /// The name of the team's escalation policy. Defaults to the name of the team.
+    #[serde(default)]
+    pub escalation_policy: String,
+    #[serde(default = "default_escalation_delay_in_minutes")]
+    pub escalation_delay_in_minutes: i64,
 }
 
-impl ApplyConfig {
-    pub fn create_received_letter(&self, applicant: &Applicant) -> Letter {
-        let mut letter = self.received.clone();
-        letter.subject = letter
-            .subject
-            .replace("{applicant_name}", &applicant.name)
-            .replace("{applicant_email}", &applicant.email)
-            .replace("{applicant_role}", &applicant.role);
-        letter.body = letter
-            .body
-            .replace("{applicant_name}", &applicant.name)
-            .replace("{applicant_email}", &applicant.email)
-            .replace("{applicant_role}", &applicant.role);
+fn default_escalation_delay_in_minutes() -> i64 {
+    30
+}
 
-        letter
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RFDReviewsConfig {
+    /// The file in the rfd repo that maps RFDs to their reviewers, in the format of a CODEOWNERS
+    /// file. Reviews are not requested when the file does not exist.
+    #[serde(default = "default_rfd_reviewers_file")]
+    pub reviewers_file: String,
+    /// How many days an RFD in discussion can go without a review before its reviewers are
+    /// reminded. Every time this many more days pass the reminder escalates, first to the
+    /// reviewers again and then to `escalation_channel`.
+    #[serde(default = "default_rfd_review_stale_days")]
+    pub stale_days: i64,
+    #[serde(default)]
+    pub escalation_channel: String,
+}
+
+impl Default for RFDReviewsConfig {
+    fn default() -> Self {
+        Self {
+            reviewers_file: default_rfd_reviewers_file(),
+            stale_days: default_rfd_review_stale_days(),
+            escalation_channel: String::new(),
+        }
     }
+}
 
-    pub fn create_rejection_letter(&self, letter_key: &str, applicant: &Applicant) -> Option<Letter> {
-        self.rejection.get(letter_key).map(|letter| {
-            let mut letter = letter.clone();
-            letter.subject = letter
-                .subject
-                .replace("{applicant_name}", &applicant.name)
-                .replace("{applicant_email}", &applicant.email)
-                .replace("{applicant_role}", &applicant.role);
-            letter.body = letter
-                .body
-                .replace("{applicant_name}", &applicant.name)
-                .replace("{applicant_email}", &applicant.email)
-                .replace("{applicant_role}", &applicant.role);
+fn default_rfd_reviewers_file() -> String {
+    ".github/RFD_REVIEWERS".to_string()
+}
 
-            letter
-        })
-    }
+fn default_rfd_review_stale_days() -> i64 {
+    7
 }
 
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
-pub struct LegacyExpensifyConfig {
-    pub aliases: HashMap<String, String>,
-    pub emails_to_exclude: Vec<String>,
+pub struct ZoomConfig {
+    /// Create a Zoom meeting for every scheduled interview, hosted by the interviewers.
+    #[serde(default)]
+    pub interview_meetings: bool,
 }
 
+/// Employees request access to resources with `/access`, and the owners of the resource approve
+/// or reject the request in Slack. Only resources that have owners can be requested.
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
-pub struct FinanceConfig {
-    pub legacy_expensify: LegacyExpensifyConfig,
-    pub merchant_aliases: HashMap<String, String>,
-    pub vendor_aliases: HashMap<String, String>,
+pub struct AccessRequestsConfig {
+    /// The Slack channel requests are posted to for their owners to decide on.
+    #[serde(default)]
+    pub slack_channel: String,
+    /// Who owns which resources. The first entry that matches a resource decides who can approve
+    /// access to it.
+    #[serde(default)]
+    pub owners: Vec<AccessOwnersConfig>,
+    /// The most days access can be requested for. Access can be requested without an expiry
+    /// when this is 0.
+    #[serde(default)]
+    pub max_days: i32,
 }
 
-#[derive(Debug, Default, Clone, Deserialize, Serialize)]
-pub struct GitHubConfig {
+impl AccessRequestsConfig {
+    pub fn owners_of(&self, kind: AccessResourceKind, resource: &str) -> Option<&AccessOwnersConfig> {
+        self.owners
+            .iter()
+            .find(|owners| owners.kind == kind && owners.resources.iter().any(|glob| glob_matches(glob, resource)))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AccessOwnersConfig {
+    pub kind: AccessResourceKind,
+    /// Globs of the resources, ie. repo names, Airtable base ids, Google group names without the
+    /// domain, or Tailscale tags without `tag:`.
+    pub resources: Vec<String>,
+    /// The Slack user ids of the owners.
+    pub owners: Vec<String>,
+    /// The permission that is granted on GitHub repos (pull, triage, push, maintain or admin) or
+    /// Airtable bases (read, comment, edit or create). Defaults to push and edit.
     #[serde(default)]
-    pub ignored_repos: Vec<String>,
+    pub permission: String,
+}
+
+/// What is redacted from logs, Sentry events and stored webhook bodies, keyed by data class, ie.
+/// `email` or `tracking_number`. The built-in classes are redacted everywhere unless they are
+/// configured otherwise, and any other class that is configured is added to them.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RedactionConfig {
+    #[serde(default, flatten)]
+    pub classes: HashMap<String, DataClassConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct DataClassConfig {
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+    /// Names of fields whose values are redacted outright, matched without regard to case. These
+    /// are added to the fields of a built-in class.
+    #[serde(default)]
+    pub fields: Vec<String>,
+    /// Regular expressions for values that are redacted wherever they appear in text. These are
+    /// added to the patterns of a built-in class.
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    /// Where the class is redacted. Defaults to everywhere.
+    #[serde(default = "RedactionTarget::all")]
+    pub targets: Vec<RedactionTarget>,
+}
+
+impl Default for DataClassConfig {
+    fn default() -> Self {
+        DataClassConfig {
+            enabled: true,
+            fields: Default::default(),
+            patterns: Default::default(),
+            targets: RedactionTarget::all(),
+        }
+    }
+}
+
+fn enabled() -> bool {
+    true
 }
 
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct AppConfig {
     pub envelopes: DocuSignConfig,
     pub onboarding: OnboardingConfig,
+    #[serde(default)]
+    pub offboarding: OffboardingConfig,
     pub apply: ApplyConfig,
+    #[serde(default)]
+    pub assets: AssetsConfig,
     pub finance: FinanceConfig,
     #[serde(default)]
     pub github: GitHubConfig,
+    #[serde(default)]
+    pub mailing_list: MailingListConfig,
+    #[serde(default)]
+    pub tailscale: TailscaleConfig,
+    #[serde(default)]
+    pub access_requests: AccessRequestsConfig,
+    #[serde(default)]
+    pub background_checks: BackgroundCheckConfig,
+    #[serde(default)]
+    pub celebrations: CelebrationsConfig,
+    #[serde(default)]
+    pub certificates: CertificatesConfig,
+    #[serde(default)]
+    pub device_credentials: DeviceCredentialsConfig,
+    #[serde(default)]
+    pub google_workspace: GoogleWorkspaceConfig,
+    #[serde(default)]
+    pub identity: IdentityConfig,
+    #[serde(default)]
+    pub journal_club: JournalClubConfig,
+    #[serde(default)]
+    pub okta: OktaConfig,
+    #[serde(default)]
+    pub pagerduty: PagerDutyConfig,
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+    #[serde(default)]
+    pub rfd_reviews: RFDReviewsConfig,
+    #[serde(default)]
+    pub swag: SwagConfig,
+    #[serde(default)]
+    pub zoom: ZoomConfig,
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ApplyConfig, DocuSignConfig, GitHubConfig, OnboardingConfig};
+    use super::{
+        ApplicantScoringConfig, ApplyConfig, CertificateStorageConfig, CertificatesConfig, DocuSignConfig,
+        GitHubConfig, GoogleWorkspaceConfig, IdentityConfig, IdentityProviderKind, InterviewSchedulingConfig,
+        MailingListConfig, MailingListProviderKind, OktaConfig, OnboardingConfig, OnboardingTaskKind, PagerDutyConfig,
+        RampCardsConfig, ScoringCriterionKind,
+    };
     use crate::{applicants::tests::mock_applicant, companies::tests::mock_company, configs::tests::mock_user};
 
     fn mock_docusign_toml(label: &str) -> String {
@@ -200,6 +1413,24 @@ routingOrder = '2'
 emailNotification.emailSubject = 'Sign the Letter {{applicant_name}}'
 emailNotification.emailBody = """Please sign the letter {{applicant_name}}"""
 emailNotification.language = ''
+
+[[{label}.templateRoles.tabs.textTabs]]
+tabLabel = 'salary'
+value = '{{applicant_salary}}'
+locked = 'true'
+
+[[{label}.templateRoles.tabs.textTabs]]
+tabLabel = 'equity'
+value = '{{applicant_equity}}'
+locked = 'true'
+
+[[{label}.templateRoles.tabs.dateTabs]]
+tabLabel = 'start_date'
+value = '{{applicant_start_date}}'
+
+[[{label}.customFields.textCustomFields]]
+name = 'applicant'
+value = '{{applicant_email}}'
 "#
         )
     }
@@ -247,6 +1478,59 @@ Once it is created it will be sent to Test User at random-test@testemaildomain.c
             expected_recipient_body,
             envelope.template_roles[1].email_notification.email_body
         );
+
+        let tabs = &envelope.template_roles[1].tabs;
+        assert_eq!(applicant.offer_salary, tabs.text_tabs[0].value);
+        assert_eq!(applicant.offer_equity, tabs.text_tabs[1].value);
+        assert_eq!("01/01/2092", tabs.date_tabs[0].value);
+        assert_eq!(applicant.email, envelope.custom_fields.text_custom_fields[0].value);
+    }
+
+    #[test]
+    fn test_policy_acknowledgment_list() {
+        let mut config = mock_docusign_config();
+        config.policies = toml::from_str(
+            r#"
+[code-of-conduct]
+template_id = 'coc-template'
+role_name = 'Employee'
+email_subject = 'Please acknowledge the code of conduct, {user_name}'
+"#,
+        )
+        .unwrap();
+        let user = mock_user();
+
+        let list = config
+            .create_policy_acknowledgment_list("code-of-conduct", &[user.clone()])
+            .unwrap();
+
+        assert_eq!("code-of-conduct", list.name);
+        assert_eq!(1, list.bulk_copies.len());
+        assert_eq!(
+            format!("Please acknowledge the code of conduct, {}", user.full_name()),
+            list.bulk_copies[0].email_subject
+        );
+        assert_eq!("Employee", list.bulk_copies[0].recipients[0].role_name);
+        assert_eq!(user.email, list.bulk_copies[0].recipients[0].email);
+
+        assert!(config.create_policy_acknowledgment_list("missing", &[user]).is_none());
+    }
+
+    #[test]
+    fn test_ramp_card_limit_for_department() {
+        let config: RampCardsConfig = toml::from_str(
+            r#"
+default_limit = { amount = 1000.0, interval = 'MONTHLY' }
+
+[department_limits]
+Operations = { amount = 5000.0, interval = 'MONTHLY' }
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(5000.0, config.limit_for("Operations").unwrap().amount);
+        assert_eq!(1000.0, config.limit_for("Engineering").unwrap().amount);
+        assert!(RampCardsConfig::default().limit_for("Engineering").is_none());
     }
 
     fn mock_apply_toml() -> &'static str {
@@ -300,6 +1584,61 @@ from = 'test@testemaildomain.com'
         assert!(letter.is_none());
     }
 
+    #[test]
+    fn test_applicant_scoring_config() {
+        let config: ApplyConfig = toml::from_str(&mock_apply_toml()).unwrap();
+        assert_eq!(ApplicantScoringConfig::default(), config.scoring);
+
+        let config: ApplicantScoringConfig = toml::from_str(
+            r#"
+[[criteria]]
+kind = "reviewer_evaluations"
+weight = 3
+
+[[criteria]]
+kind = "materials_keywords"
+keywords = ["rust", "firmware"]
+roles = ["Embedded Software Engineer"]
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(ScoringCriterionKind::ReviewerEvaluations, config.criteria[0].kind);
+        assert_eq!(3.0, config.criteria[0].weight);
+        assert_eq!(ScoringCriterionKind::MaterialsKeywords, config.criteria[1].kind);
+        assert_eq!(1.0, config.criteria[1].weight);
+        assert_eq!(
+            vec!["rust".to_string(), "firmware".to_string()],
+            config.criteria[1].keywords
+        );
+    }
+
+    #[test]
+    fn test_interview_scheduling_config() {
+        let config: ApplyConfig = toml::from_str(&mock_apply_toml()).unwrap();
+        assert_eq!(InterviewSchedulingConfig::default(), config.interviews);
+
+        let config: InterviewSchedulingConfig = toml::from_str(
+            r#"
+time_zone = "America/New_York"
+day_start_hour = 10
+confirm_url = "https://example.com/careers/schedule"
+
+[proposal_letter]
+subject = "Interviews for {applicant_role}"
+body = "{interview_times}"
+from = "careers@example.com"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!("America/New_York", config.time_zone);
+        assert_eq!(10, config.day_start_hour);
+        assert_eq!(17, config.day_end_hour);
+        assert_eq!(5, config.proposals);
+        assert_eq!("careers@example.com", config.proposal_letter.unwrap().from);
+    }
+
     fn mock_onboarding_toml() -> &'static str {
         r#"
 [new_hire_issue]
@@ -350,10 +1689,43 @@ github org: super_computer_org
         );
     }
 
+    #[test]
+    fn test_onboarding_tasks() {
+        let config: OnboardingConfig = toml::from_str(mock_onboarding_toml()).unwrap();
+        assert!(config.tasks.is_empty());
+
+        let config: OnboardingConfig = toml::from_str(&format!(
+            r#"{}
+[[tasks]]
+name = 'google'
+kind = 'google_account'
+day = -5
+
+[[tasks]]
+name = 'github'
+kind = 'github_invite'
+day = -3
+depends_on = ['google']
+max_attempts = 2
+"#,
+            mock_onboarding_toml()
+        ))
+        .unwrap();
+
+        assert_eq!(2, config.tasks.len());
+        assert_eq!(OnboardingTaskKind::GoogleAccount, config.tasks[0].kind);
+        assert_eq!(5, config.tasks[0].max_attempts);
+        assert_eq!(OnboardingTaskKind::GitHubInvite, config.tasks[1].kind);
+        assert_eq!(-3, config.tasks[1].day);
+        assert_eq!(vec!["google"], config.tasks[1].depends_on);
+        assert_eq!(2, config.tasks[1].max_attempts);
+    }
+
     #[test]
     fn test_missing_github_config() {
         let config: GitHubConfig = toml::from_str("").unwrap();
         assert!(config.ignored_repos.is_empty());
+        assert!(!config.teams_dry_run);
     }
 
     #[test]
@@ -364,9 +1736,184 @@ ignored_repos = [
     "12345",
     "67890"
 ]
+teams_dry_run = true
 "#,
         )
         .unwrap();
         assert_eq!(vec!["12345".to_string(), "67890".to_string(),], config.ignored_repos);
+        assert!(config.teams_dry_run);
+    }
+
+    #[test]
+    fn test_mailing_list_config() {
+        let config: MailingListConfig = toml::from_str(
+            r#"
+[audiences.8a6d823488]
+double_opt_in = true
+marketing_permission_ids = ["a1b2c3", "d4e5f6"]
+
+[audiences.f64af23d78]
+"#,
+        )
+        .unwrap();
+
+        let audience = config.audience("8a6d823488").unwrap();
+        assert!(audience.double_opt_in);
+        assert_eq!(
+            vec!["a1b2c3".to_string(), "d4e5f6".to_string()],
+            audience.marketing_permission_ids
+        );
+
+        let audience = config.audience("f64af23d78").unwrap();
+        assert!(!audience.double_opt_in);
+        assert!(audience.marketing_permission_ids.is_empty());
+
+        assert!(config.audience("unknown").is_none());
+        assert!(config.journey("shipment_delivered").is_none());
+    }
+
+    #[test]
+    fn test_mailing_list_journeys() {
+        let config: MailingListConfig = toml::from_str(
+            r#"
+[journeys.shipment_delivered]
+journey_id = 12
+step_id = 34
+"#,
+        )
+        .unwrap();
+
+        let journey = config.journey("shipment_delivered").unwrap();
+        assert_eq!(12, journey.journey_id);
+        assert_eq!(34, journey.step_id);
+        assert!(config.journey("applicant_hired").is_none());
+    }
+
+    #[test]
+    fn test_mailing_list_providers() {
+        let config: MailingListConfig = toml::from_str(
+            r#"
+[providers]
+"Oxide Computer Company" = "mailerlite"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            MailingListProviderKind::Mailerlite,
+            config.provider("Oxide Computer Company")
+        );
+        assert_eq!(MailingListProviderKind::MailChimp, config.provider("Other"));
+    }
+
+    #[test]
+    fn test_certificates_config() {
+        let config: CertificatesConfig = toml::from_str(
+            r#"
+renew_within_days = 30
+
+[[storage]]
+kind = "vault"
+address = "https://vault.internal:8200"
+path_prefix = "certs"
+
+[[storage]]
+kind = "kubernetes"
+namespace = "ingress"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(30, config.renew_within_days);
+        assert_eq!(
+            vec![
+                CertificateStorageConfig::Vault {
+                    address: "https://vault.internal:8200".to_string(),
+                    mount: "secret".to_string(),
+                    path_prefix: "certs".to_string(),
+                },
+                CertificateStorageConfig::Kubernetes {
+                    namespace: "ingress".to_string(),
+                },
+            ],
+            config.storage
+        );
+
+        let config: CertificatesConfig = toml::from_str("").unwrap();
+        assert_eq!(12, config.renew_within_days);
+        assert!(config.storage.is_empty());
+    }
+
+    #[test]
+    fn test_google_workspace_org_unit_for_department() {
+        let config: GoogleWorkspaceConfig = toml::from_str(
+            r#"
+org_unit_path = "/Employees"
+suspended_org_unit_path = "/Suspended"
+
+[department_org_units]
+Operations = "/Employees/Operations"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(Some("/Employees/Operations"), config.org_unit_for("Operations"));
+        assert_eq!(Some("/Employees"), config.org_unit_for("Engineering"));
+        assert!(!config.transfer_drive_to_manager);
+        assert!(GoogleWorkspaceConfig::default().org_unit_for("Engineering").is_none());
+    }
+
+    #[test]
+    fn test_identity_providers() {
+        let config: IdentityConfig = toml::from_str(
+            r#"
+[providers]
+"Oxide Computer Company" = "okta"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(IdentityProviderKind::Okta, config.provider("Oxide Computer Company"));
+        assert_eq!(IdentityProviderKind::Google, config.provider("Other"));
+    }
+
+    #[test]
+    fn test_okta_app_assignments() {
+        let config: OktaConfig = toml::from_str(
+            r#"
+[app_assignments]
+Slack = ["all"]
+"AWS Console" = ["eng", "ops"]
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(vec!["eng", "ops"], config.app_assignments["AWS Console"]);
+        assert!(OktaConfig::default().app_assignments.is_empty());
+    }
+
+    #[test]
+    fn test_pagerduty_teams() {
+        let config: PagerDutyConfig = toml::from_str(
+            r#"
+handoff_channel = "on-call"
+
+[teams.Infrastructure]
+group = "infra"
+schedules = ["Infrastructure primary", "Infrastructure secondary"]
+escalation_delay_in_minutes = 15
+
+[teams.Support]
+group = "support"
+"#,
+        )
+        .unwrap();
+
+        let infra = &config.teams["Infrastructure"];
+        assert_eq!("infra", infra.group);
+        assert_eq!(2, infra.schedules.len());
+        assert_eq!(15, infra.escalation_delay_in_minutes);
+        assert_eq!(30, config.teams["Support"].escalation_delay_in_minutes);
+        assert!(config.teams["Support"].escalation_policy.is_empty());
     }
 }
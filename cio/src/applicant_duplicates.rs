@@ -0,0 +1,719 @@
+/*!
+ * Finding applicants who applied more than once, and merging them.
+ *
+ * People apply again for other roles, from another email, or through the careers inbox after
+ * applying on the site. Pairs of applicants that share an email, phone number or profile, or
+ * that have nearly the same name, are kept as suspected duplicates in Airtable, where the hiring
+ * team confirms or dismisses them. Confirmed pairs are merged into the applicant who applied
+ * first, keeping the interviews, reviews and offers of both.
+ */
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+    str::FromStr,
+};
+
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use async_trait::async_trait;
+use log::{info, warn};
+use macros::db;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    airtable::AIRTABLE_APPLICANT_DUPLICATES_TABLE,
+    applicant_reviews::ApplicantReview,
+    applicants::Applicant,
+    companies::Company,
+    core::UpdateAirtableRecord,
+    db::Database,
+    interviews::ApplicantInterview,
+    schema::{
+        applicant_duplicates, applicant_interviews, applicant_reviews, applicants, interview_schedules, offer_approvals,
+    },
+};
+
+/// Pairs of applicants that score at least this are suspected to be the same person. A name
+/// alone is never enough.
+const SUSPECTED_SCORE: i32 = 50;
+
+const EMAIL_SCORE: i32 = 60;
+const PHONE_SCORE: i32 = 50;
+const PROFILE_SCORE: i32 = 50;
+const NAME_SCORE: f64 = 30.0;
+
+/// How alike two names have to be to count towards a match, from 0 to 1.
+const NAME_SIMILARITY: f64 = 0.85;
+
+/// Whether a suspected duplicate is the same person, as decided in Airtable.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DuplicateStatus {
+    /// Found by matching, and waiting for someone to look at it.
+    Suspected,
+    /// Someone confirmed that both applicants are the same person, so they are merged on the
+    /// next sync.
+    Confirmed,
+    /// Someone confirmed that the applicants are different people.
+    Dismissed,
+    /// The duplicate was merged into the applicant.
+    Merged,
+}
+
+impl fmt::Display for DuplicateStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let status = match self {
+            DuplicateStatus::Suspected => "Suspected",
+            DuplicateStatus::Confirmed => "Confirmed",
+            DuplicateStatus::Dismissed => "Not a duplicate",
+            DuplicateStatus::Merged => "Merged",
+        };
+
+        write!(f, "{}", status)
+    }
+}
+
+impl FromStr for DuplicateStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(status: &str) -> Result<Self> {
+        Ok(match status {
+            "Suspected" => DuplicateStatus::Suspected,
+            "Confirmed" => DuplicateStatus::Confirmed,
+            "Not a duplicate" => DuplicateStatus::Dismissed,
+            "Merged" => DuplicateStatus::Merged,
+            _ => bail!("unknown duplicate status `{}`", status),
+        })
+    }
+}
+
+/// A pair of applicants that look like the same person. The applicant is the one who applied
+/// first, and is the one that is kept when they are merged.
+#[db {
+    new_struct_name = "ApplicantDuplicate",
+    airtable_base = "hiring",
+    airtable_table = "AIRTABLE_APPLICANT_DUPLICATES_TABLE",
+    match_on = {
+        "applicant_id" = "i32",
+        "duplicate_id" = "i32",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = applicant_duplicates)]
+pub struct NewApplicantDuplicate {
+    pub applicant_id: i32,
+    pub duplicate_id: i32,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub applicant_name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub applicant_email: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub applicant_role: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub duplicate_name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub duplicate_email: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub duplicate_role: String,
+    /// What the applicants have in common, ie. `email` or `phone`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reasons: Vec<String>,
+    #[serde(default)]
+    pub score: i32,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub status: String,
+    /// link to another table in Airtable
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub applicants: Vec<String>,
+    /// The CIO company ID.
+    #[serde(default)]
+    pub cio_company_id: i32,
+}
+
+/// Implement updating the Airtable record for a ApplicantDuplicate.
+#[async_trait]
+impl UpdateAirtableRecord<ApplicantDuplicate> for ApplicantDuplicate {
+    async fn update_airtable_record(&mut self, _record: ApplicantDuplicate) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// How alike two applicants are, and why.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DuplicateMatch {
+    pub score: i32,
+    pub reasons: Vec<String>,
+}
+
+/// What was kept of a duplicate when it was merged into an applicant.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct ApplicantMerge {
+    pub applicant_id: i32,
+    pub duplicate_id: i32,
+    /// The fields of the applicant that were empty, and were filled in from the duplicate.
+    pub filled: Vec<String>,
+    /// The number of interviews of the duplicate that are now the applicant's.
+    pub interviews: usize,
+    /// The number of reviews of the duplicate that are now the applicant's.
+    pub reviews: usize,
+}
+
+/// Compare emails without the `+` tag, and for Gmail without the dots, since they all go to the
+/// same inbox.
+fn normalize_email(email: &str) -> Option<String> {
+    let email = email.trim().to_lowercase();
+    let (local, domain) = email.split_once('@')?;
+    let local = local.split('+').next().unwrap_or_default();
+
+    let (local, domain) = match domain {
+        "gmail.com" | "googlemail.com" => (local.replace('.', ""), "gmail.com"),
+        domain => (local.to_string(), domain),
+    };
+    if local.is_empty() {
+        return None;
+    }
+
+    Some(format!("{}@{}", local, domain))
+}
+
+/// Compare phone numbers by their last ten digits, so that the country code does not matter.
+fn normalize_phone(phone: &str) -> Option<String> {
+    let digits = phone.chars().filter(char::is_ascii_digit).collect::<String>();
+    if digits.len() < 7 {
+        return None;
+    }
+
+    Some(digits[digits.len().saturating_sub(10)..].to_string())
+}
+
+/// The handle of a profile, whether it was given as a link or not, ie. `janedoe` for
+/// `https://www.github.com/janedoe/`.
+fn profile_handle(profile: &str, host: &str, prefix: &str) -> Option<String> {
+    let profile = profile.trim().to_lowercase();
+    let profile = profile.trim_start_matches("https://").trim_start_matches("http://");
+    let profile = profile.trim_start_matches("www.");
+    let profile = profile.strip_prefix(host).unwrap_or(profile).trim_start_matches('/');
+    let profile = profile.strip_prefix(prefix).unwrap_or(profile);
+
+    let handle = profile
+        .trim_start_matches('@')
+        .split(|c| c == '/' || c == '?' || c == '#')
+        .next()
+        .unwrap_or_default();
+    if handle.is_empty() {
+        return None;
+    }
+
+    Some(handle.to_string())
+}
+
+/// The words of a name, lowercase and without punctuation.
+fn name_words(name: &str) -> Vec<String> {
+    name.to_lowercase()
+        .split(|c: char| c.is_whitespace() || c == '-' || c == ',')
+        .map(|word| word.chars().filter(|c| c.is_alphanumeric()).collect::<String>())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// How alike two names are, from 0 to 1, regardless of the order of their words.
+pub fn name_similarity(a: &str, b: &str) -> f64 {
+    let sorted = |name: &str| {
+        let mut words = name_words(name);
+        words.sort();
+        words.join(" ").chars().collect::<Vec<_>>()
+    };
+    let (a, b) = (sorted(a), sorted(b));
+
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 0.0;
+    }
+
+    1.0 - levenshtein(&a, &b) as f64 / longest as f64
+}
+
+/// How alike two applicants are. Returns `None` for applicants that are not suspected to be the
+/// same person.
+pub fn match_applicants(a: &Applicant, b: &Applicant) -> Option<DuplicateMatch> {
+    let mut found = DuplicateMatch::default();
+    let mut add = |reason: &str, score: i32| {
+        found.score += score;
+        found.reasons.push(reason.to_string());
+    };
+
+    let same = |a: Option<String>, b: Option<String>| a.is_some() && a == b;
+    if same(normalize_email(&a.email), normalize_email(&b.email)) {
+        add("email", EMAIL_SCORE);
+    }
+    if same(normalize_phone(&a.phone), normalize_phone(&b.phone)) {
+        add("phone", PHONE_SCORE);
+    }
+    if same(
+        profile_handle(&a.github, "github.com", ""),
+        profile_handle(&b.github, "github.com", ""),
+    ) {
+        add("github", PROFILE_SCORE);
+    }
+    if same(
+        profile_handle(&a.linkedin, "linkedin.com", "in/"),
+        profile_handle(&b.linkedin, "linkedin.com", "in/"),
+    ) {
+        add("linkedin", PROFILE_SCORE);
+    }
+
+    let similarity = name_similarity(&a.name, &b.name);
+    if similarity >= NAME_SIMILARITY {
+        add("name", (NAME_SCORE * similarity).round() as i32);
+    }
+
+    if found.score < SUSPECTED_SCORE {
+        return None;
+    }
+    found.score = found.score.min(100);
+
+    Some(found)
+}
+
+/// The keys applicants are grouped by before they are compared, so that only applicants that
+/// could match are. Names are grouped by each of their words together with the first letter of
+/// the rest, so that a typo in one word still groups them.
+fn blocking_keys(applicant: &Applicant) -> Vec<String> {
+    let mut keys = Vec::new();
+    if let Some(email) = normalize_email(&applicant.email) {
+        keys.push(format!("email:{}", email));
+    }
+    if let Some(phone) = normalize_phone(&applicant.phone) {
+        keys.push(format!("phone:{}", phone));
+    }
+    if let Some(github) = profile_handle(&applicant.github, "github.com", "") {
+        keys.push(format!("github:{}", github));
+    }
+    if let Some(linkedin) = profile_handle(&applicant.linkedin, "linkedin.com", "in/") {
+        keys.push(format!("linkedin:{}", linkedin));
+    }
+
+    let words = name_words(&applicant.name);
+    for (i, word) in words.iter().enumerate() {
+        for (j, other) in words.iter().enumerate() {
+            if i != j {
+                keys.push(format!("name:{}:{}", word, other.chars().next().unwrap_or_default()));
+            }
+        }
+    }
+
+    keys
+}
+
+/// The pairs of applicants that are suspected to be the same person, by their index, with the
+/// one who applied first first.
+pub fn find_duplicates(applicants: &[Applicant]) -> Vec<(usize, usize, DuplicateMatch)> {
+    let mut blocks: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, applicant) in applicants.iter().enumerate() {
+        for key in blocking_keys(applicant) {
+            blocks.entry(key).or_default().push(i);
+        }
+    }
+
+    let mut candidates = BTreeSet::new();
+    for block in blocks.values() {
+        for (n, i) in block.iter().enumerate() {
+            for j in &block[n + 1..] {
+                if i != j {
+                    candidates.insert((*i.min(j), *i.max(j)));
+                }
+            }
+        }
+    }
+
+    candidates
+        .into_iter()
+        .filter_map(|(i, j)| {
+            let (a, b) = (&applicants[i], &applicants[j]);
+            let found = match_applicants(a, b)?;
+
+            if (b.submitted_time, b.id) < (a.submitted_time, a.id) {
+                Some((j, i, found))
+            } else {
+                Some((i, j, found))
+            }
+        })
+        .collect()
+}
+
+/// Fill the fields of `applicant` that are empty from `duplicate`. Returns the names of the
+/// fields that were filled.
+fn fill_from(applicant: &mut Applicant, duplicate: &Applicant) -> Vec<String> {
+    let mut filled = Vec::new();
+
+    macro_rules! fill {
+        ($($field:ident),*) => {
+            $(
+                if applicant.$field.is_empty() && !duplicate.$field.is_empty() {
+                    applicant.$field = duplicate.$field.clone();
+                    filled.push(stringify!($field).to_string());
+                }
+            )*
+        };
+    }
+    fill!(
+        phone,
+        country_code,
+        location,
+        github,
+        gitlab,
+        linkedin,
+        portfolio,
+        portfolio_pdf,
+        website,
+        resume,
+        materials,
+        resume_contents,
+        materials_contents
+    );
+
+    // The other role they applied for is one they are interested in.
+    for role in std::iter::once(&duplicate.role).chain(duplicate.interested_in.iter()) {
+        if !role.is_empty() && *role != applicant.role && !applicant.interested_in.contains(role) {
+            applicant.interested_in.push(role.to_string());
+        }
+    }
+
+    filled
+}
+
+fn replace_link(links: &mut Vec<String>, from: &str, to: &str) {
+    links.retain(|link| link != from);
+    if !to.is_empty() && !links.iter().any(|link| link == to) {
+        links.push(to.to_string());
+    }
+}
+
+/// Merge `duplicate` into `applicant`: fill in what the applicant is missing, move the
+/// interviews, reviews, interview schedules and offers of the duplicate over to them, and delete
+/// the duplicate.
+pub async fn merge_applicants(
+    db: &Database,
+    company: &Company,
+    applicant_id: i32,
+    duplicate_id: i32,
+) -> Result<ApplicantMerge> {
+    if applicant_id == duplicate_id {
+        bail!("cannot merge applicant {} into itself", applicant_id);
+    }
+
+    let mut applicant = Applicant::get_by_id(db, applicant_id).await?;
+    let duplicate = Applicant::get_by_id(db, duplicate_id).await?;
+    if applicant.cio_company_id != company.id || duplicate.cio_company_id != company.id {
+        bail!(
+            "applicants {} and {} are not both applicants of company {}",
+            applicant_id,
+            duplicate_id,
+            company.id
+        );
+    }
+
+    let mut merge = ApplicantMerge {
+        applicant_id,
+        duplicate_id,
+        filled: fill_from(&mut applicant, &duplicate),
+        ..Default::default()
+    };
+
+    // Interviews and reviews are linked to applicants in Airtable, by their record.
+    if !duplicate.airtable_record_id.is_empty() {
+        let interviews = applicant_interviews::dsl::applicant_interviews
+            .filter(applicant_interviews::dsl::applicant.contains(vec![duplicate.airtable_record_id.to_string()]))
+            .load_async::<ApplicantInterview>(db.pool())
+            .await?;
+        for mut interview in interviews {
+            replace_link(
+                &mut interview.applicant,
+                &duplicate.airtable_record_id,
+                &applicant.airtable_record_id,
+            );
+            interview.email = applicant.email.to_string();
+            interview.update(db).await?;
+            merge.interviews += 1;
+        }
+
+        let reviews = applicant_reviews::dsl::applicant_reviews
+            .filter(applicant_reviews::dsl::applicant.contains(vec![duplicate.airtable_record_id.to_string()]))
+            .load_async::<ApplicantReview>(db.pool())
+            .await?;
+        for mut review in reviews {
+            replace_link(
+                &mut review.applicant,
+                &duplicate.airtable_record_id,
+                &applicant.airtable_record_id,
+            );
+            review.update(db).await?;
+            merge.reviews += 1;
+        }
+
+        for link in duplicate.interviews.iter().chain(duplicate.link_to_reviews.iter()) {
+            if !applicant.interviews.contains(link) && !applicant.link_to_reviews.contains(link) {
+                if duplicate.interviews.contains(link) {
+                    applicant.interviews.push(link.to_string());
+                } else {
+                    applicant.link_to_reviews.push(link.to_string());
+                }
+            }
+        }
+    }
+
+    diesel::update(interview_schedules::dsl::interview_schedules)
+        .filter(interview_schedules::dsl::applicant_id.eq(duplicate.id))
+        .set(interview_schedules::dsl::applicant_id.eq(applicant.id))
+        .execute_async(db.pool())
+        .await?;
+    diesel::update(offer_approvals::dsl::offer_approvals)
+        .filter(offer_approvals::dsl::applicant_id.eq(duplicate.id))
+        .set(offer_approvals::dsl::applicant_id.eq(applicant.id))
+        .execute_async(db.pool())
+        .await?;
+
+    if applicant.interviews_started.is_none() {
+        applicant.interviews_started = duplicate.interviews_started;
+    }
+    if applicant.interviews_completed.is_none() {
+        applicant.interviews_completed = duplicate.interviews_completed;
+    }
+
+    applicant.update(db).await?;
+    duplicate.delete(db).await?;
+
+    // Other suspected pairs with the duplicate are found again with the applicant, if they
+    // still match.
+    diesel::delete(applicant_duplicates::dsl::applicant_duplicates)
+        .filter(applicant_duplicates::dsl::status.eq(DuplicateStatus::Suspected.to_string()))
+        .filter(
+            applicant_duplicates::dsl::applicant_id
+                .eq(duplicate.id)
+                .or(applicant_duplicates::dsl::duplicate_id.eq(duplicate.id)),
+        )
+        .execute_async(db.pool())
+        .await?;
+
+    if let Some(mut pair) = ApplicantDuplicate::get_from_db(db, applicant.id, duplicate.id).await {
+        pair.status = DuplicateStatus::Merged.to_string();
+        pair.applicants = vec![applicant.airtable_record_id.to_string()];
+        pair.update(db).await?;
+    }
+
+    info!(
+        "merged applicant {} ({}) into {} ({})",
+        duplicate.id, duplicate.email, applicant.id, applicant.email
+    );
+
+    Ok(merge)
+}
+
+/// Find the applicants who look like they applied more than once, keep what the hiring team
+/// decided about them in Airtable, and merge the ones they confirmed.
+pub async fn refresh_applicant_duplicates(db: &Database, company: &Company) -> Result<()> {
+    if company.airtable_base_id_hiring.is_empty() {
+        // Return early.
+        return Ok(());
+    }
+
+    // Whether a pair is a duplicate is decided in Airtable, so take that first.
+    let records = ApplicantDuplicates::get_from_airtable(db, company.id).await?;
+    for (id, record) in records {
+        let status = match record.fields.status.parse::<DuplicateStatus>() {
+            Ok(status) => status,
+            Err(e) => {
+                warn!("ignoring suspected duplicate {} in Airtable: {}", id, e);
+                continue;
+            }
+        };
+
+        if let Ok(mut pair) = ApplicantDuplicate::get_by_id(db, id).await {
+            if pair.status != status.to_string() && pair.status != DuplicateStatus::Merged.to_string() {
+                pair.status = status.to_string();
+                pair.update_in_db(db).await?;
+            }
+        }
+    }
+
+    let all = applicants::dsl::applicants
+        .filter(applicants::dsl::cio_company_id.eq(company.id))
+        .load_async::<Applicant>(db.pool())
+        .await?;
+    for (i, j, found) in find_duplicates(&all) {
+        let (applicant, duplicate) = (&all[i], &all[j]);
+        if ApplicantDuplicate::get_from_db(db, applicant.id, duplicate.id)
+            .await
+            .is_some()
+        {
+            continue;
+        }
+
+        let pair = NewApplicantDuplicate {
+            applicant_id: applicant.id,
+            duplicate_id: duplicate.id,
+            applicant_name: applicant.name.to_string(),
+            applicant_email: applicant.email.to_string(),
+            applicant_role: applicant.role.to_string(),
+            duplicate_name: duplicate.name.to_string(),
+            duplicate_email: duplicate.email.to_string(),
+            duplicate_role: duplicate.role.to_string(),
+            reasons: found.reasons,
+            score: found.score,
+            status: DuplicateStatus::Suspected.to_string(),
+            applicants: [&applicant.airtable_record_id, &duplicate.airtable_record_id]
+                .iter()
+                .filter(|id| !id.is_empty())
+                .map(|id| id.to_string())
+                .collect(),
+            cio_company_id: company.id,
+        };
+        pair.upsert_in_db(db).await?;
+
+        info!(
+            "applicant {} ({}) looks like a duplicate of {} ({})",
+            duplicate.id, duplicate.email, applicant.id, applicant.email
+        );
+    }
+
+    let confirmed = applicant_duplicates::dsl::applicant_duplicates
+        .filter(applicant_duplicates::dsl::cio_company_id.eq(company.id))
+        .filter(applicant_duplicates::dsl::status.eq(DuplicateStatus::Confirmed.to_string()))
+        .load_async::<ApplicantDuplicate>(db.pool())
+        .await?;
+    for pair in confirmed {
+        if let Err(e) = merge_applicants(db, company, pair.applicant_id, pair.duplicate_id).await {
+            warn!(
+                "merging applicant {} into {} failed: {}",
+                pair.duplicate_id, pair.applicant_id, e
+            );
+        }
+    }
+
+    ApplicantDuplicates::get_from_db(db, company.id)
+        .await?
+        .update_airtable(db)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::{find_duplicates, match_applicants, name_similarity, normalize_email, profile_handle};
+    use crate::applicants::tests::mock_applicant;
+
+    #[test]
+    fn test_normalize_email() {
+        assert_eq!(
+            Some("janedoe@gmail.com".to_string()),
+            normalize_email(" Jane.Doe+jobs@googlemail.com")
+        );
+        assert_eq!(
+            Some("jane.doe@example.com".to_string()),
+            normalize_email("jane.doe+jobs@example.com")
+        );
+        assert_eq!(None, normalize_email("not an email"));
+    }
+
+    #[test]
+    fn test_profile_handle() {
+        for github in [
+            "https://www.github.com/janedoe/",
+            "github.com/JaneDoe",
+            "@janedoe",
+            "janedoe",
+        ] {
+            assert_eq!(
+                Some("janedoe".to_string()),
+                profile_handle(github, "github.com", ""),
+                "{}",
+                github
+            );
+        }
+        assert_eq!(
+            Some("jane-doe".to_string()),
+            profile_handle("https://www.linkedin.com/in/jane-doe?trk=1", "linkedin.com", "in/")
+        );
+        assert_eq!(None, profile_handle("https://github.com/", "github.com", ""));
+    }
+
+    #[test]
+    fn test_name_similarity() {
+        assert_eq!(1.0, name_similarity("Jane Doe", "doe, jane"));
+        assert!(name_similarity("Jane Doe", "Jane Do") > 0.85);
+        assert!(name_similarity("Jane Doe", "John Smith") < 0.5);
+    }
+
+    #[test]
+    fn test_match_applicants() {
+        let mut a = mock_applicant();
+        a.name = "Jane Doe".to_string();
+        a.email = "jane.doe@gmail.com".to_string();
+        a.phone = "+1 (555) 123-4567".to_string();
+
+        let mut b = a.clone();
+        b.id = a.id + 1;
+        b.email = "janedoe+work@gmail.com".to_string();
+        b.phone = "555.123.4567".to_string();
+        let found = match_applicants(&a, &b).unwrap();
+        assert_eq!(vec!["email", "phone", "name"], found.reasons);
+        assert_eq!(100, found.score);
+
+        // Having the same name is not enough.
+        b.email = "jane@example.com".to_string();
+        b.phone = String::new();
+        assert_eq!(None, match_applicants(&a, &b));
+
+        b.github = "https://github.com/janedoe".to_string();
+        a.github = "janedoe".to_string();
+        let found = match_applicants(&a, &b).unwrap();
+        assert_eq!(vec!["github", "name"], found.reasons);
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let mut first = mock_applicant();
+        first.id = 1;
+        first.name = "Jane Doe".to_string();
+        first.email = "jane@example.com".to_string();
+        first.submitted_time = Utc.ymd(2023, 1, 1).and_hms(0, 0, 0);
+
+        let mut other = mock_applicant();
+        other.id = 2;
+        other.name = "John Smith".to_string();
+        other.email = "john@example.com".to_string();
+
+        let mut again = first.clone();
+        again.id = 3;
+        again.name = "Jane Dow".to_string();
+        again.email = "jane@example.com".to_string();
+        again.role = "Software Engineer".to_string();
+        again.submitted_time = Utc.ymd(2022, 6, 1).and_hms(0, 0, 0);
+
+        let found = find_duplicates(&[first, other, again]);
+        assert_eq!(1, found.len());
+
+        // The application that was submitted first is kept.
+        let (applicant, duplicate, found) = &found[0];
+        assert_eq!((2, 0), (*applicant, *duplicate));
+        assert_eq!(vec!["email", "name"], found.reasons);
+    }
+}
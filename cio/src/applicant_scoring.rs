@@ -0,0 +1,266 @@
+/*!
+ * Scoring applicants by how well they fit, so that the pipeline can be sorted by fit rather
+ * than by when people applied.
+ *
+ * Each criterion of the company's [`ApplicantScoringConfig`] rates an applicant from 0 to 1,
+ * and the score is the weighted average of those ratings, out of 100. A criterion that has
+ * nothing to go on yet, ie. reviews of an applicant nobody has reviewed, is left out of the
+ * average rather than counted as a 0, so that new applicants do not sink below ones that were
+ * reviewed poorly.
+ *
+ * Scores are worked out as applicants are synced, and go to Airtable with the rest of the
+ * applicant, in the `score` and `score_details` fields of the applications table.
+ */
+use std::fmt;
+
+use crate::{
+    app_config::{ApplicantScoringConfig, ScoringCriterion, ScoringCriterionKind},
+    applicant_status::Status,
+    applicants::Applicant,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApplicantScore {
+    /// Out of 100.
+    pub score: f32,
+    /// A line for each criterion, with its rating and weight.
+    pub details: String,
+}
+
+impl fmt::Display for ScoringCriterionKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ScoringCriterionKind::ReviewerEvaluations => "reviewer_evaluations",
+            ScoringCriterionKind::AnsweredQuestions => "answered_questions",
+            ScoringCriterionKind::WorkSamples => "work_samples",
+            ScoringCriterionKind::PublicWork => "public_work",
+            ScoringCriterionKind::MaterialsKeywords => "materials_keywords",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl ScoringCriterion {
+    fn applies_to(&self, applicant: &Applicant) -> bool {
+        self.roles.is_empty() || self.roles.iter().any(|role| role.eq_ignore_ascii_case(&applicant.role))
+    }
+
+    /// Rate `applicant` from 0 to 1, or `None` when there is nothing to rate it on yet.
+    fn rate(&self, applicant: &Applicant) -> Option<f32> {
+        match self.kind {
+            ScoringCriterionKind::ReviewerEvaluations => {
+                // Evaluations of N/A say nothing about the applicant.
+                let evaluations = applicant.scoring_enthusiastic_yes_count
+                    + applicant.scoring_yes_count
+                    + applicant.scoring_pass_count
+                    + applicant.scoring_no_count;
+                if evaluations == 0 {
+                    return None;
+                }
+
+                let favor = applicant.scoring_enthusiastic_yes_count as f32
+                    + 0.75 * applicant.scoring_yes_count as f32
+                    + 0.25 * applicant.scoring_pass_count as f32;
+
+                Some(favor / evaluations as f32)
+            }
+            ScoringCriterionKind::AnsweredQuestions => {
+                // The answers are parsed out of the materials.
+                if applicant.materials_contents.is_empty() {
+                    return None;
+                }
+
+                Some(share_present(&[
+                    &applicant.question_technically_challenging,
+                    &applicant.question_proud_of,
+                    &applicant.question_happiest,
+                    &applicant.question_unhappiest,
+                    &applicant.question_value_reflected,
+                    &applicant.question_value_violated,
+                    &applicant.question_values_in_tension,
+                    &applicant.question_why_oxide,
+                ]))
+            }
+            ScoringCriterionKind::WorkSamples => Some(any_present(&[
+                &applicant.work_samples,
+                &applicant.writing_samples,
+                &applicant.analysis_samples,
+                &applicant.presentation_samples,
+                &applicant.exploratory_samples,
+            ])),
+            ScoringCriterionKind::PublicWork => Some(any_present(&[
+                &applicant.github,
+                &applicant.gitlab,
+                &applicant.portfolio,
+                &applicant.portfolio_pdf,
+                &applicant.website,
+            ])),
+            ScoringCriterionKind::MaterialsKeywords => {
+                if self.keywords.is_empty()
+                    || (applicant.resume_contents.is_empty() && applicant.materials_contents.is_empty())
+                {
+                    return None;
+                }
+
+                let contents =
+                    format!("{}\n{}", applicant.resume_contents, applicant.materials_contents).to_lowercase();
+                let mentioned = self
+                    .keywords
+                    .iter()
+                    .filter(|keyword| contents.contains(&keyword.to_lowercase()))
+                    .count();
+
+                Some(mentioned as f32 / self.keywords.len() as f32)
+            }
+        }
+    }
+}
+
+fn share_present(fields: &[&String]) -> f32 {
+    fields.iter().filter(|field| !field.trim().is_empty()).count() as f32 / fields.len() as f32
+}
+
+fn any_present(fields: &[&String]) -> f32 {
+    if fields.iter().any(|field| !field.trim().is_empty()) {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// Score `applicant` with the model in `config`.
+pub fn score(applicant: &Applicant, config: &ApplicantScoringConfig) -> ApplicantScore {
+    let mut total = 0.0;
+    let mut weights = 0.0;
+    let mut details = vec![];
+
+    for criterion in config
+        .criteria
+        .iter()
+        .filter(|criterion| criterion.applies_to(applicant))
+    {
+        match criterion.rate(applicant) {
+            Some(rating) => {
+                total += rating * criterion.weight;
+                weights += criterion.weight;
+                details.push(format!(
+                    "{}: {:.2} (weight {})",
+                    criterion.kind, rating, criterion.weight
+                ));
+            }
+            None => details.push(format!("{}: n/a", criterion.kind)),
+        }
+    }
+
+    let score = if weights > 0.0 {
+        // Rounded, so that small changes in the ratings do not make for an update every sync.
+        (total / weights * 1000.0).round() / 10.0
+    } else {
+        0.0
+    };
+
+    ApplicantScore {
+        score,
+        details: details.join("\n"),
+    }
+}
+
+impl Applicant {
+    /// Score the applicant with the model in `config`. Returns whether the score changed.
+    ///
+    /// People who are onboarding or were hired are not scored, for the same reason their review
+    /// counts are cleared: we do not want people who join to know their scores.
+    pub fn update_score(&mut self, config: &ApplicantScoringConfig) -> bool {
+        let scored = if self.status == Status::Onboarding.to_string() || self.status == Status::Hired.to_string() {
+            ApplicantScore {
+                score: 0.0,
+                details: String::new(),
+            }
+        } else {
+            score(self, config)
+        };
+
+        let changed = self.score != scored.score || self.score_details != scored.details;
+
+        self.score = scored.score;
+        self.score_details = scored.details;
+
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::score;
+    use crate::{
+        app_config::{ApplicantScoringConfig, ScoringCriterion, ScoringCriterionKind},
+        applicant_status::Status,
+        applicants::tests::mock_applicant,
+    };
+
+    #[test]
+    fn test_score() {
+        let config = ApplicantScoringConfig {
+            criteria: vec![
+                ScoringCriterion {
+                    kind: ScoringCriterionKind::ReviewerEvaluations,
+                    weight: 3.0,
+                    keywords: vec![],
+                    roles: vec![],
+                },
+                ScoringCriterion {
+                    kind: ScoringCriterionKind::MaterialsKeywords,
+                    weight: 1.0,
+                    keywords: vec!["Rust".to_string(), "firmware".to_string()],
+                    roles: vec![],
+                },
+                ScoringCriterion {
+                    kind: ScoringCriterionKind::PublicWork,
+                    weight: 1.0,
+                    keywords: vec![],
+                    roles: vec!["Hardware Engineer".to_string()],
+                },
+            ],
+        };
+
+        let mut applicant = mock_applicant();
+        applicant.resume_contents = "Wrote a lot of rust.".to_string();
+        applicant.materials_contents = String::new();
+
+        // Nobody has reviewed the applicant yet, so only the keywords count.
+        let unreviewed = score(&applicant, &config);
+        assert_eq!(50.0, unreviewed.score);
+        assert_eq!(
+            "reviewer_evaluations: n/a\nmaterials_keywords: 0.50 (weight 1)",
+            unreviewed.details
+        );
+
+        applicant.scoring_enthusiastic_yes_count = 1;
+        applicant.scoring_yes_count = 0;
+        applicant.scoring_pass_count = 1;
+        applicant.scoring_no_count = 0;
+        applicant.scoring_not_applicable_count = 2;
+
+        // Reviews rate (1 + 0.25) / 2, weighted 3 to the keywords' 1.
+        let reviewed = score(&applicant, &config);
+        assert_eq!(59.4, reviewed.score);
+    }
+
+    #[test]
+    fn test_update_score_skips_hires() {
+        let config = ApplicantScoringConfig::default();
+
+        let mut applicant = mock_applicant();
+        applicant.github = "https://github.com/oxidecomputer".to_string();
+        applicant.status = Status::Interviewing.to_string();
+        assert!(applicant.update_score(&config));
+        assert!(applicant.score > 0.0);
+        assert!(!applicant.update_score(&config));
+
+        applicant.status = Status::Hired.to_string();
+        assert!(applicant.update_score(&config));
+        assert_eq!(0.0, applicant.score);
+        assert!(applicant.score_details.is_empty());
+    }
+}
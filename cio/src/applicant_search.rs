@@ -0,0 +1,119 @@
+use anyhow::Result;
+use meilisearch_minimal_api::{IndexSettings, MeiliClient};
+use serde::{Deserialize, Serialize};
+
+use crate::{applicants::Applicant, search_index};
+
+/// The Meilisearch index applicants are searched in.
+pub const APPLICANTS_INDEX: &str = "applicants";
+
+pub struct ApplicantSearchIndex {}
+
+/// An applicant as it is searched, with the text of their resume and materials so recruiters can
+/// find applicants by their skills and where they have worked before.
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct ApplicantDocument {
+    pub id: i32,
+    pub cio_company_id: i32,
+    pub name: String,
+    pub email: String,
+    pub role: String,
+    pub status: String,
+    pub location: String,
+    /// When the application was submitted, as a unix timestamp so it can be sorted on.
+    pub submitted_time: i64,
+    pub resume_contents: String,
+    pub materials_contents: String,
+}
+
+impl From<&Applicant> for ApplicantDocument {
+    fn from(applicant: &Applicant) -> Self {
+        Self {
+            id: applicant.id,
+            cio_company_id: applicant.cio_company_id,
+            name: applicant.name.to_string(),
+            email: applicant.email.to_string(),
+            role: applicant.role.to_string(),
+            status: applicant.status.to_string(),
+            location: applicant.location.to_string(),
+            submitted_time: applicant.submitted_time.timestamp(),
+            resume_contents: applicant.resume_contents.to_string(),
+            materials_contents: applicant.materials_contents.to_string(),
+        }
+    }
+}
+
+impl ApplicantSearchIndex {
+    pub fn default_client() -> Result<MeiliClient> {
+        Ok(MeiliClient::new(
+            std::env::var("MEILI_URL")?,
+            std::env::var("MEILI_KEY")?,
+        ))
+    }
+
+    /// Matches in resumes rank above matches in the rest of the materials, and applicants can be
+    /// filtered by company, role and status.
+    pub fn index_settings() -> IndexSettings {
+        let searchable = ["name", "email", "resume_contents", "materials_contents", "location"];
+
+        IndexSettings {
+            searchable_attributes: Some(searchable.iter().map(|s| s.to_string()).collect()),
+            filterable_attributes: Some(vec![
+                "cio_company_id".to_string(),
+                "role".to_string(),
+                "status".to_string(),
+            ]),
+            sortable_attributes: Some(vec!["submitted_time".to_string()]),
+            ..Default::default()
+        }
+    }
+
+    /// Apply the index settings. Meilisearch only rebuilds the index when the settings change.
+    pub async fn configure_index(client: &MeiliClient) -> Result<()> {
+        search_index::configure_index(client, APPLICANTS_INDEX, &Self::index_settings()).await
+    }
+
+    /// Add the applicants to the index, replacing what was indexed for them before.
+    pub async fn index_applicants(client: &MeiliClient, applicants: &[Applicant]) -> Result<()> {
+        if applicants.is_empty() {
+            return Ok(());
+        }
+
+        let documents = applicants.iter().map(ApplicantDocument::from).collect::<Vec<_>>();
+        let task = client
+            .index(APPLICANTS_INDEX.to_string())
+            .index_documents(&documents, "id")
+            .await?;
+        search_index::wait_for_task(client, task).await
+    }
+
+    /// Remove the applicants with the given ids from the index, ie. when they are erased.
+    pub async fn delete_applicants(client: &MeiliClient, ids: &[i32]) -> Result<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let task = client.index(APPLICANTS_INDEX.to_string()).delete_documents(ids).await?;
+        search_index::wait_for_task(client, task).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::ApplicantDocument;
+    use crate::applicants::tests::mock_applicant;
+
+    #[test]
+    fn test_applicant_document() {
+        let mut applicant = mock_applicant();
+        applicant.submitted_time = Utc.ymd(2023, 3, 1).and_hms(0, 0, 0);
+        applicant.resume_contents = "Firmware engineer at Example Corp, Rust and C".to_string();
+
+        let document = ApplicantDocument::from(&applicant);
+        assert_eq!(applicant.id, document.id);
+        assert_eq!(1677628800, document.submitted_time);
+        assert_eq!(applicant.resume_contents, document.resume_contents);
+    }
+}
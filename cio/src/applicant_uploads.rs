@@ -19,6 +19,7 @@ pub struct UploadToken {
     pub created_at: DateTime<Utc>,
     pub expires_at: DateTime<Utc>,
     pub used_at: Option<DateTime<Utc>>,
+    pub cio_company_id: i32,
 }
 
 #[derive(Clone, Debug)]
@@ -35,7 +36,7 @@ impl UploadTokenStore {
         }
     }
 
-    pub async fn get(&self, email: &str) -> Result<UploadToken, UploadTokenError> {
+    pub async fn get(&self, email: &str, cio_company_id: i32) -> Result<UploadToken, UploadTokenError> {
         let pool = self.db.pool();
         let email = email.to_string();
         let token_lifetime = self.token_lifetime;
@@ -43,6 +44,7 @@ impl UploadTokenStore {
         pool.transaction(move |conn| {
             let token = upload_tokens::dsl::upload_tokens
                 .filter(upload_tokens::dsl::email.eq(email.clone()))
+                .filter(upload_tokens::dsl::cio_company_id.eq(cio_company_id))
                 .filter(upload_tokens::dsl::expires_at.gt(Utc::now()))
                 .filter(upload_tokens::dsl::used_at.is_null())
                 .first::<UploadToken>(conn.deref_mut());
@@ -57,6 +59,7 @@ impl UploadTokenStore {
                                 upload_tokens::dsl::email.eq(email),
                                 upload_tokens::dsl::token.eq(hex::encode(token)),
                                 upload_tokens::dsl::expires_at.eq(Utc::now() + token_lifetime),
+                                upload_tokens::dsl::cio_company_id.eq(cio_company_id),
                             ))
                             .get_result(conn.deref_mut())
                             .map_err(UploadTokenError::DB)
@@ -68,10 +71,11 @@ impl UploadTokenStore {
         .await
     }
 
-    pub async fn test(&self, email: String, token: String) -> Result<bool, UploadTokenError> {
+    pub async fn test(&self, email: String, token: String, cio_company_id: i32) -> Result<bool, UploadTokenError> {
         Ok(upload_tokens::dsl::upload_tokens
             .filter(upload_tokens::dsl::email.eq(email))
             .filter(upload_tokens::dsl::token.eq(token))
+            .filter(upload_tokens::dsl::cio_company_id.eq(cio_company_id))
             .filter(upload_tokens::dsl::expires_at.gt(Utc::now()))
             .filter(upload_tokens::dsl::used_at.is_null())
             .first_async::<UploadToken>(self.db.pool())
@@ -79,10 +83,16 @@ impl UploadTokenStore {
             .map(|_| true)?)
     }
 
-    pub async fn consume(&self, email: &str, token: &str) -> Result<UploadToken, UploadTokenError> {
+    pub async fn consume(
+        &self,
+        email: &str,
+        token: &str,
+        cio_company_id: i32,
+    ) -> Result<UploadToken, UploadTokenError> {
         let target = upload_tokens::dsl::upload_tokens
             .filter(upload_tokens::dsl::email.eq(email.to_string()))
             .filter(upload_tokens::dsl::token.eq(token.to_string()))
+            .filter(upload_tokens::dsl::cio_company_id.eq(cio_company_id))
             .filter(upload_tokens::dsl::expires_at.gt(Utc::now()))
             .filter(upload_tokens::dsl::used_at.is_null());
 
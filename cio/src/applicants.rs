@@ -7,16 +7,11 @@ use async_trait::async_trait;
 use chrono::{offset::Utc, DateTime, Duration, NaiveDate};
 use chrono_humanize::HumanTime;
 use docusign::DocuSign;
-use google_drive::{
-    traits::{DriveOps, FileOps},
-    Client as GoogleDrive,
-};
-use google_geocode::Geocode;
+use google_drive::{traits::FileOps, Client as GoogleDrive};
 use log::{info, warn};
 use macros::db;
 use regex::Regex;
 use schemars::JsonSchema;
-use sendgrid_api::{traits::MailOps, Client as SendGrid};
 use serde::{Deserialize, Serialize};
 use slack_chat_api::{
     FormattedMessage, MessageAttachment, MessageBlock, MessageBlockText, MessageBlockType, MessageType,
@@ -26,14 +21,19 @@ use tokio::io::AsyncWriteExt;
 
 use crate::{
     airtable::{AIRTABLE_APPLICATIONS_TABLE, AIRTABLE_REVIEWER_LEADERBOARD_TABLE},
-    app_config::{AppConfig, ApplyConfig, Letter, NewHireIssue},
+    app_config::{AppConfig, ApplyConfig, Letter, NewHireIssue, OfferApprovalConfig},
     applicant_reviews::ApplicantReview,
+    applicant_search::ApplicantSearchIndex,
+    background_checks::BackgroundCheckStatus,
     companies::Company,
     configs::User,
     core::UpdateAirtableRecord,
     db::Database,
+    drive::{DocumentStore, OFFER_LETTERS_DRIVE},
+    email::Mailer,
     enclose,
     interviews::ApplicantInterview,
+    offer_approvals::{ensure_offer_approved, OfferApproval, OfferApprovalState},
     schema::{applicant_interviews, applicant_reviewers, applicants, users},
     utils::{check_if_github_issue_exists, truncate},
 };
@@ -211,6 +211,17 @@ pub struct NewApplicant {
     pub criminal_background_check_status: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub motor_vehicle_background_check_status: String,
+    /// The Checkr report for the criminal background check, set once the applicant has
+    /// completed their invitation.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub checkr_report_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub checkr_adverse_action_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub adverse_action_status: String,
+    /// The status of the background check, the same whichever provider ran it, ie. `clear`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub background_check_status: String,
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub start_date: Option<NaiveDate>,
@@ -240,9 +251,30 @@ pub struct NewApplicant {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub piia_envelope_completed: Option<DateTime<Utc>>,
 
+    /// The salary to prefill in the offer letter, as it should be displayed.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub offer_salary: String,
+    /// The equity to prefill in the offer letter, as it should be displayed.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub offer_equity: String,
+    /// A link to the signed offer documents in Google Drive.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub offer_documents_link: String,
+    /// A link to the signed employee agreements in Google Drive.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub employee_agreements_link: String,
+
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub link_to_reviews: Vec<String>,
 
+    /// How well the applicant fits, out of 100, from the scoring model of the company's apply
+    /// config.
+    #[serde(default)]
+    pub score: f32,
+    /// How each criterion of the scoring model rated the applicant.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub score_details: String,
+
     /// The CIO company ID.
     #[serde(default)]
     pub cio_company_id: i32,
@@ -461,6 +493,9 @@ impl Applicant {
         // Initialize the GSuite sheets client.
         let drive_client = company.authenticate_google_drive(db).await?;
 
+        // The apply config is handed off before the applicant is scored.
+        let scoring = app_config.apply.scoring.clone();
+
         self.keep_fields_from_airtable(db).await;
 
         // Expand the application.
@@ -496,6 +531,11 @@ impl Applicant {
         // This function will update the database so we don't have to.
         self.update_reviews_scoring(db).await?;
 
+        // Score the applicant now that its reviews are counted.
+        if self.update_score(&scoring) {
+            self.update(db).await?;
+        }
+
         // TODO: we could move docusign stuff here as well, and out of its own function.
         Ok(())
     }
@@ -762,25 +802,22 @@ impl Applicant {
         let checkr = checkr_auth.unwrap();
 
         // Check if we already sent them an invitation.
-        let candidates = checkr.list_candidates().await?;
-        for candidate in candidates {
-            if candidate.email == self.email {
-                // Check if we already have sent their invitation.
-                if self.criminal_background_check_status.is_empty() {
-                    // Create an invitation for the candidate.
-                    checkr.create_invitation(&candidate.id, "premium_criminal").await?;
+        if let Some(candidate) = checkr.find_candidate_by_email(&self.email).await? {
+            // Check if we already have sent their invitation.
+            if self.criminal_background_check_status.is_empty() {
+                // Create an invitation for the candidate.
+                checkr.create_invitation(&candidate.id, "premium_criminal").await?;
 
-                    // Update the database.
-                    self.criminal_background_check_status = "requested".to_string();
+                // Update the database.
+                self.criminal_background_check_status = "requested".to_string();
 
-                    self.update(db).await?;
+                self.update(db).await?;
 
-                    info!("sent background check invitation to: {}", self.email);
-                }
-                // We can return early they already exist as a candidate and we have sent them an
-                // invite.
-                return Ok(());
+                info!("sent background check invitation to: {}", self.email);
             }
+            // We can return early they already exist as a candidate and we have sent them an
+            // invite.
+            return Ok(());
         }
 
         // Create a new candidate for the applicant in checkr.
@@ -799,14 +836,134 @@ impl Applicant {
         Ok(())
     }
 
-    /// Send an email to a scorer that they are assigned to an applicant.
-    pub async fn send_email_to_scorer(&self, scorer: &str, company: &Company) {
-        // Initialize the SendGrid client.
-        let sendgrid_client = SendGrid::new_from_env();
+    /// Update the background check status from a Checkr report or invitation event. Returns
+    /// whether anything changed.
+    pub fn update_background_check_from_checkr(&mut self, event: &checkr::WebhookEvent) -> bool {
+        let event_type = event.event_type();
+        let object = &event.data.object;
+
+        // The status that is the same across providers follows the criminal background check.
+        let mut changed = object.package.contains("premium_criminal")
+            && BackgroundCheckStatus::update_from_checkr(&mut self.background_check_status, event);
+
+        // The adverse action flow is tracked separately from the status of the report, which
+        // stays complete throughout.
+        let adverse_action_status = match event_type {
+            checkr::WebhookEventType::ReportPreAdverseAction => Some("pending"),
+            checkr::WebhookEventType::ReportPostAdverseAction => Some("complete"),
+            _ => None,
+        };
+        if let Some(status) = adverse_action_status {
+            if self.adverse_action_status == status {
+                return changed;
+            }
 
-        // Send the message.
-        sendgrid_client
-            .mail_send()
+            self.adverse_action_status = status.to_string();
+            return true;
+        }
+
+        let (status, report_id) = if event_type.is_report() {
+            (object.status.to_string(), object.id.to_string())
+        } else {
+            let status = match event_type {
+                checkr::WebhookEventType::InvitationCreated => "requested",
+                checkr::WebhookEventType::InvitationExpired => "expired",
+                checkr::WebhookEventType::InvitationDeleted => "canceled",
+                // A completed invitation is followed by a `report.created` event.
+                _ => return changed,
+            };
+            (status.to_string(), object.report_id.to_string())
+        };
+
+        let package = &object.package;
+        let current = if package.contains("premium_criminal") {
+            // We only ever take adverse action based on the criminal background check.
+            if !report_id.is_empty() && self.checkr_report_id != report_id {
+                self.checkr_report_id = report_id;
+                changed = true;
+            }
+
+            &mut self.criminal_background_check_status
+        } else if package.contains("motor_vehicle") {
+            &mut self.motor_vehicle_background_check_status
+        } else {
+            return changed;
+        };
+
+        if !status.is_empty() && *current != status {
+            *current = status;
+            changed = true;
+        }
+
+        changed
+    }
+
+    /// Start the adverse action flow for the applicant's criminal background check. Checkr
+    /// sends the pre-adverse action notice right away and the post-adverse action notice once
+    /// the waiting period is over, or at `post_notice_scheduled_at` if it is given.
+    pub async fn start_adverse_action(
+        &mut self,
+        db: &Database,
+        checkr: &checkr::Checkr,
+        post_notice_scheduled_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        if self.checkr_report_id.is_empty() {
+            bail!("applicant {} does not have a checkr report", self.email);
+        }
+
+        if !self.checkr_adverse_action_id.is_empty() && self.adverse_action_status != "canceled" {
+            bail!(
+                "adverse action {} was already started for applicant {}",
+                self.checkr_adverse_action_id,
+                self.email
+            );
+        }
+
+        let adverse_action = checkr
+            .create_adverse_action(
+                &self.checkr_report_id,
+                &checkr::AdverseActionRequest {
+                    post_notice_scheduled_at,
+                    ..Default::default()
+                },
+            )
+            .await?;
+
+        self.checkr_adverse_action_id = adverse_action.id.to_string();
+        self.adverse_action_status = adverse_action.status;
+        self.update(db).await?;
+
+        info!(
+            "started adverse action {} for applicant {}",
+            adverse_action.id, self.email
+        );
+
+        Ok(())
+    }
+
+    /// Cancel the adverse action flow for the applicant, this is only possible until the
+    /// post-adverse action notice is sent.
+    pub async fn cancel_adverse_action(&mut self, db: &Database, checkr: &checkr::Checkr) -> Result<()> {
+        if self.checkr_adverse_action_id.is_empty() {
+            bail!("applicant {} does not have an adverse action", self.email);
+        }
+
+        let adverse_action = checkr.cancel_adverse_action(&self.checkr_adverse_action_id).await?;
+
+        self.adverse_action_status = adverse_action.status;
+        self.update(db).await?;
+
+        info!(
+            "canceled adverse action {} for applicant {}",
+            self.checkr_adverse_action_id, self.email
+        );
+
+        Ok(())
+    }
+
+    /// Send an email to a scorer that they are assigned to an applicant.
+    pub async fn send_email_to_scorer(&self, db: &Database, scorer: &str, company: &Company) -> Result<()> {
+        Mailer::new(db, company)
             .send_plain_text(
                 &format!("[applicants] Reviewing applicant {}", self.name),
                 &self.as_scorer_email(),
@@ -816,7 +973,6 @@ impl Applicant {
                 &format!("careers@{}", company.gsuite_domain),
             )
             .await
-            .unwrap();
     }
 
     /// Get the applicant's information in the form of the body of an email for a
@@ -1213,6 +1369,10 @@ pub async fn get_file_contents(drive_client: &GoogleDrive, url: &str) -> Result<
         file.write_all(&contents).await?;
 
         read_pdf(&name, path.clone()).await?
+    } else if mime_type == "text/plain" {
+        let contents = drive_client.files().download_by_id(&id).await?;
+
+        String::from_utf8_lossy(&contents).to_string()
     } else {
         let contents = drive_client.files().download_by_id(&id).await?;
         path.push(&name);
@@ -1222,11 +1382,19 @@ pub async fn get_file_contents(drive_client: &GoogleDrive, url: &str) -> Result<
 
         output.push(format!("{}.txt", id));
 
-        match tokio::task::spawn_blocking(enclose! { (output, path) move || {Command::new("pandoc")
-        .args(["-o", output.clone().to_str().unwrap(), path.to_str().unwrap()])
-        .output()}})
-        .await?
-        {
+        // Uploads are not always named with an extension, so tell pandoc what the file is when
+        // we know, and otherwise let it guess from the name.
+        let mut args = vec!["-t".to_string(), "plain".to_string()];
+        if let Some(format) = pandoc_input_format(&mime_type) {
+            args.extend(["-f".to_string(), format.to_string()]);
+        }
+        args.extend([
+            "-o".to_string(),
+            output.to_str().unwrap().to_string(),
+            path.to_str().unwrap().to_string(),
+        ]);
+
+        match tokio::task::spawn_blocking(move || Command::new("pandoc").args(args).output()).await? {
             Ok(_) => (),
             Err(e) => {
                 warn!("pandoc failed: {}", e);
@@ -1246,6 +1414,19 @@ pub async fn get_file_contents(drive_client: &GoogleDrive, url: &str) -> Result<
     Ok(result.trim().to_string())
 }
 
+/// The pandoc reader for the type of document, for the types applicants upload other than PDFs.
+fn pandoc_input_format(mime_type: &str) -> Option<&'static str> {
+    match mime_type {
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => Some("docx"),
+        "application/vnd.oasis.opendocument.text" => Some("odt"),
+        "application/rtf" | "text/rtf" => Some("rtf"),
+        "application/epub+zip" => Some("epub"),
+        "text/html" => Some("html"),
+        "text/markdown" => Some("markdown"),
+        _ => None,
+    }
+}
+
 async fn read_pdf(name: &str, path: std::path::PathBuf) -> Result<String> {
     let mut output = env::temp_dir();
     output.push(&format!("tempfile-{}.txt", name));
@@ -1342,6 +1523,16 @@ pub async fn refresh_docusign_for_applicants(db: &Database, company: &Company, c
             return Ok(());
         }
 
+        if let Some(docusign::AuthError::ConsentRequired { consent_url }) = e.downcast_ref::<docusign::AuthError>() {
+            // Someone needs to consent again, skip DocuSign until they do rather than
+            // failing the whole sync.
+            warn!(
+                "docusign consent is required for company {}, skipping: {}",
+                company.name, consent_url
+            );
+            return Ok(());
+        }
+
         bail!("authenticating docusign failed: {}", e);
     }
     let ds = dsa.unwrap();
@@ -1352,7 +1543,12 @@ pub async fn refresh_docusign_for_applicants(db: &Database, company: &Company, c
     // Iterate over the applicants and find any that have the status: giving offer.
     for mut applicant in applicants {
         applicant
-            .do_docusign_offer(db, &ds, config.envelopes.create_offer_letter(&applicant))
+            .do_docusign_offer(
+                db,
+                &ds,
+                config.envelopes.create_offer_letter(&applicant),
+                &config.envelopes.offer_approval,
+            )
             .await?;
 
         applicant
@@ -1396,21 +1592,18 @@ impl Applicant {
         );
     }
 
-    pub async fn set_lat_long(&mut self) {
+    pub async fn set_lat_long(&mut self, db: &Database) {
         // Get the latitude and longitude if we don't already have it.
         if self.latitude != 0.0 && self.longitude != 0.0 {
             // Return early we alreaedy have lat and long set.
             return;
         }
 
-        // Create the geocode client.
-        let geocode = Geocode::new_from_env();
         // Attempt to get the lat and lng.
-        match geocode.get(&self.location).await {
-            Ok(result) => {
-                let location = result.geometry.location;
-                self.latitude = location.lat as f32;
-                self.longitude = location.lng as f32;
+        match crate::geocode::geocode_address(db, &self.location).await {
+            Ok(location) => {
+                self.latitude = location.latitude as f32;
+                self.longitude = location.longitude as f32;
             }
             Err(e) => {
                 if !self.location.is_empty() {
@@ -1490,12 +1683,10 @@ impl Applicant {
                 status
             );
 
-            // Initialize the SendGrid client.
-            let sendgrid_client = SendGrid::new_from_env();
+            let company = self.company(db).await?;
 
             // Send the message.
-            sendgrid_client
-                .mail_send()
+            Mailer::new(db, &company)
                 .send_plain_text(
                     &letter.subject,
                     &letter.body,
@@ -1547,7 +1738,7 @@ impl Applicant {
             let letter = config.create_received_letter(self);
 
             // Send them an email.
-            self.send_email_recieved_application_to_applicant(&letter).await?;
+            self.send_email_recieved_application_to_applicant(db, &letter).await?;
             self.sent_email_received = true;
             // Update it in the database just in case.
             self.update(db).await?;
@@ -1558,7 +1749,7 @@ impl Applicant {
         }
 
         // Set the latitude and longitude if we don't already have it.
-        self.set_lat_long().await;
+        self.set_lat_long(db).await;
 
         // Get the time seven days ago.
         let duration_from_now = Utc::now().signed_duration_since(self.submitted_time);
@@ -1663,12 +1854,9 @@ The applicants Airtable \
     /// Send an email internally that we have a new application.
     async fn send_email_internally(&self, db: &Database) -> Result<()> {
         let company = self.company(db).await?;
-        // Initialize the SendGrid client.
-        let sendgrid_client = SendGrid::new_from_env();
 
         // Send the message.
-        sendgrid_client
-            .mail_send()
+        Mailer::new(db, &company)
             .send_plain_text(
                 &format!("New {} Application: {}", self.role, self.name),
                 &self.as_company_notification_email(),
@@ -1683,13 +1871,11 @@ The applicants Airtable \
     }
 
     /// Send an email to the applicant that we recieved their application.
-    async fn send_email_recieved_application_to_applicant(&self, letter: &Letter) -> Result<()> {
-        // Initialize the SendGrid client.
-        let sendgrid_client = SendGrid::new_from_env();
+    async fn send_email_recieved_application_to_applicant(&self, db: &Database, letter: &Letter) -> Result<()> {
+        let company = self.company(db).await?;
 
         // Send the message.
-        sendgrid_client
-            .mail_send()
+        Mailer::new(db, &company)
             .send_plain_text(
                 &letter.subject,
                 &letter.body,
@@ -1967,6 +2153,7 @@ The applicants Airtable \
         db: &Database,
         ds: &DocuSign,
         new_envelope: docusign::Envelope,
+        approval_config: &OfferApprovalConfig,
     ) -> Result<()> {
         // Keep the fields from Airtable we need just in case they changed.
         self.keep_fields_from_airtable(db).await;
@@ -1984,6 +2171,13 @@ The applicants Airtable \
         if self.docusign_envelope_id.is_empty()
             && self.status == crate::applicant_status::Status::GivingOffer.to_string()
         {
+            // The terms of the offer may need to be approved before it is sent.
+            let approval = match ensure_offer_approved(db, approval_config, self).await? {
+                OfferApprovalState::NotRequired => None,
+                OfferApprovalState::Approved(approval) => Some(approval),
+                OfferApprovalState::Waiting => return Ok(()),
+            };
+
             info!(
                 "applicant has status giving offer: {}, generating offer in docusign for them!",
                 self.name
@@ -1999,6 +2193,10 @@ The applicants Airtable \
 
             // Update the applicant in the database.
             self.update(db).await?;
+
+            if let Some(approval) = approval {
+                approval.mark_sent(db, &envelope.envelope_id).await?;
+            }
         } else if !self.docusign_envelope_id.is_empty() {
             // We have sent their offer.
             // Let's get the status of the envelope in Docusign.
@@ -2011,6 +2209,64 @@ The applicants Airtable \
         Ok(())
     }
 
+    /// Void the offer letter envelope for the applicant, for example when it was sent
+    /// with the wrong terms. When `reissue` is set, the envelope is detached from the
+    /// applicant so that a new offer is generated from their current record on the
+    /// next sync.
+    pub async fn void_docusign_offer(
+        &mut self,
+        db: &Database,
+        ds: &DocuSign,
+        reason: &str,
+        reissue: bool,
+    ) -> Result<()> {
+        if self.docusign_envelope_id.is_empty() {
+            bail!("applicant {} does not have an offer envelope to void", self.email);
+        }
+
+        // Keep the fields from Airtable we need just in case they changed.
+        self.keep_fields_from_airtable(db).await;
+
+        ds.void_envelope(&self.docusign_envelope_id, reason).await?;
+
+        info!(
+            "voided offer envelope {} for applicant {}: {}",
+            self.docusign_envelope_id, self.email, reason
+        );
+
+        self.docusign_envelope_status = "voided".to_string();
+        if reissue {
+            self.docusign_envelope_id = "".to_string();
+            self.offer_created = None;
+        }
+
+        self.update(db).await?;
+
+        Ok(())
+    }
+
+    /// Resend the offer letter envelope to any of its recipients that have not yet signed.
+    pub async fn resend_docusign_offer(&self, ds: &DocuSign) -> Result<()> {
+        if self.docusign_envelope_id.is_empty() {
+            bail!("applicant {} does not have an offer envelope to resend", self.email);
+        }
+
+        ds.resend_envelope(&self.docusign_envelope_id).await?;
+
+        Ok(())
+    }
+
+    /// Get a URL that can be used to correct the offer letter envelope in DocuSign.
+    pub async fn correct_docusign_offer(&self, ds: &DocuSign, return_url: &str) -> Result<String> {
+        if self.docusign_envelope_id.is_empty() {
+            bail!("applicant {} does not have an offer envelope to correct", self.email);
+        }
+
+        let view = ds.create_correct_view(&self.docusign_envelope_id, return_url).await?;
+
+        Ok(view.url)
+    }
+
     pub async fn update_applicant_from_docusign_offer_envelope(
         &mut self,
         db: &Database,
@@ -2036,6 +2292,9 @@ The applicants Airtable \
 
         // Set the completed time.
         self.offer_completed = envelope.completed_date_time;
+        if let Some(completed) = envelope.completed_date_time {
+            OfferApproval::mark_completed(db, &envelope.envelope_id, completed).await?;
+        }
         if self.status == crate::applicant_status::Status::GivingOffer.to_string() {
             // Since the status of the envelope is completed, let's set their status to "Onboarding".
             // Only do this if they are not already hired.
@@ -2050,12 +2309,8 @@ The applicants Airtable \
             }
         }
 
-        // Initialize the Google Drive client.
-        let drive_client = company.authenticate_google_drive(db).await?;
-        // Figure out where our directory is.
-        // It should be in the shared drive : "Offer Letters"
-        let shared_drive = drive_client.drives().get_by_name("Offer Letters").await?;
-        let drive_id = shared_drive.id.to_string();
+        // The documents are stored in the shared drive : "Offer Letters"/<applicant name>
+        let store = DocumentStore::new(db, company, OFFER_LETTERS_DRIVE).await?;
 
         // TODO: only save the documents if we don't already have them.
         for document in &envelope.documents {
@@ -2070,12 +2325,6 @@ The applicants Airtable \
                 bytes = ds.get_document(&envelope.envelope_id, &document.id).await?.to_vec();
             }
 
-            // Create the folder for our applicant with their name.
-            let name_folder_id = drive_client
-                .files()
-                .create_folder(&shared_drive.id, "", &self.name)
-                .await?;
-
             let mut filename = format!("{} - {}.pdf", self.name, document.name);
             if document.name.contains("Offer Letter") {
                 filename = format!("{} - Offer.pdf", self.name);
@@ -2087,10 +2336,8 @@ The applicants Airtable \
                 filename = format!("{} - PIIA.pdf", self.name);
             }
 
-            // Create or update the file in the google_drive.
-            drive_client
-                .files()
-                .create_or_update(&drive_id, &name_folder_id, &filename, "application/pdf", &bytes)
+            store
+                .upload(&[&self.name], &filename, "application/pdf", &bytes)
                 .await?;
             info!(
                 "uploaded completed `{}` file for user {} to drive",
@@ -2098,6 +2345,11 @@ The applicants Airtable \
             );
         }
 
+        self.offer_documents_link = self
+            .save_docusign_combined_documents(&store, ds, &envelope.envelope_id, "Offer")
+            .await?;
+        self.update(db).await?;
+
         // In order to not "over excessively poll the API here, we need to sleep for 15
         // min before getting each of the documents.
         // https://developers.docusign.com/docs/esign-rest-api/esign101/rules-and-limits/
@@ -2281,6 +2533,10 @@ The applicants Airtable \
             // are someone who worked remotely, we might have to manually set it.
             // If docusign is incorrect, make sure Airtable always has the source of truth.
             self.start_date = existing.start_date;
+
+            // The terms of the offer are only ever set in Airtable.
+            self.offer_salary = existing.offer_salary;
+            self.offer_equity = existing.offer_equity;
         } else {
             log::warn!(
                 "Could not find existing Airtable record for email -> {}, id -> {}",
@@ -2322,12 +2578,8 @@ The applicants Airtable \
         // Let's update the database here since nothing else has to do with that.
         self.update(db).await?;
 
-        // Initialize the Google Drive client.
-        let drive_client = company.authenticate_google_drive(db).await?;
-        // Figure out where our directory is.
-        // It should be in the shared drive : "Offer Letters"
-        let shared_drive = drive_client.drives().get_by_name("Offer Letters").await?;
-        let drive_id = shared_drive.id.to_string();
+        // The documents are stored in the shared drive : "Offer Letters"/<applicant name>
+        let store = DocumentStore::new(db, company, OFFER_LETTERS_DRIVE).await?;
 
         // TODO: only save the documents if we don't already have them.
         for document in &envelope.documents {
@@ -2342,12 +2594,6 @@ The applicants Airtable \
                 bytes = ds.get_document(&envelope.envelope_id, &document.id).await?.to_vec();
             }
 
-            // Create the folder for our applicant with their name.
-            let name_folder_id = drive_client
-                .files()
-                .create_folder(&shared_drive.id, "", &self.name)
-                .await?;
-
             let mut filename = format!("{} - {}.pdf", self.name, document.name);
             if document.name.contains("Employee Mediation") || document.name.contains("Employee_Mediation") {
                 filename = format!("{} - Mediation Agreement.pdf", self.name);
@@ -2359,32 +2605,51 @@ The applicants Airtable \
                 filename = format!("{} - Offer.pdf", self.name);
             }
 
-            // Create or update the file in the google_drive.
-            drive_client
-                .files()
-                .create_or_update(&drive_id, &name_folder_id, &filename, "application/pdf", &bytes)
+            store
+                .upload(&[&self.name], &filename, "application/pdf", &bytes)
                 .await?;
             info!("uploaded completed file `{}` to drive", filename);
         }
 
+        self.employee_agreements_link = self
+            .save_docusign_combined_documents(&store, ds, &envelope.envelope_id, "Employee Agreements")
+            .await?;
+        self.update(db).await?;
+
         Ok(())
     }
-}
 
-pub async fn refresh_new_applicants_and_reviews(
-    db: &Database,
-    company: &Company,
-    app_config: &AppConfig,
-) -> Result<()> {
-    if company.airtable_base_id_hiring.is_empty() {
-        // Return early.
-        return Ok(());
-    }
+    /// Save the combined signed documents and the certificate of completion for a completed
+    /// envelope into the applicant's folder in the "Offer Letters" shared drive. Returns the
+    /// link to the combined signed documents.
+    async fn save_docusign_combined_documents(
+        &self,
+        store: &DocumentStore,
+        ds: &DocuSign,
+        envelope_id: &str,
+        label: &str,
+    ) -> Result<String> {
+        let combined = ds.get_combined_document(envelope_id).await?;
+        let filename = format!("{} - {} - Signed.pdf", self.name, label);
+        let signed = store
+            .upload(&[&self.name], &filename, "application/pdf", &combined)
+            .await?;
+        info!("uploaded completed file `{}` to drive", filename);
 
-    let github = company.authenticate_github()?;
+        let certificate = ds.get_certificate_of_completion(envelope_id).await?;
+        let filename = format!("{} - {} - Certificate of Completion.pdf", self.name, label);
+        store
+            .upload(&[&self.name], &filename, "application/pdf", &certificate)
+            .await?;
+        info!("uploaded completed file `{}` to drive", filename);
 
-    // Get all the hiring issues on the configs repository.
-    let configs_issues = github
+        Ok(signed.url)
+    }
+}
+
+/// Get all the hiring issues on the configs repository.
+async fn list_hiring_issues(github: &octorust::Client, company: &Company) -> Result<Vec<octorust::types::IssueSimple>> {
+    Ok(github
         .issues()
         .list_all_for_repo(
             &company.github_org,
@@ -2407,7 +2672,52 @@ pub async fn refresh_new_applicants_and_reviews(
             // since
             None,
         )
-        .await?;
+        .await?)
+}
+
+/// Refresh a single applicant, the same way `refresh_new_applicants_and_reviews` refreshes all
+/// of them.
+pub async fn refresh_applicant(db: &Database, company: &Company, app_config: &AppConfig, email: &str) -> Result<()> {
+    let mut applicant = Applicant::get_from_db(db, email.to_string(), "".to_string())
+        .await
+        .ok_or_else(|| anyhow::anyhow!("no applicant was found with email `{}`", email))?;
+
+    let github = company.authenticate_github()?;
+    let configs_issues = list_hiring_issues(&github, company).await?;
+
+    applicant
+        .refresh(db, company, &github, &configs_issues, app_config.clone())
+        .await
+}
+
+pub async fn refresh_new_applicants_and_reviews(
+    db: &Database,
+    company: &Company,
+    app_config: &AppConfig,
+) -> Result<()> {
+    if company.airtable_base_id_hiring.is_empty() {
+        // Return early.
+        return Ok(());
+    }
+
+    let github = company.authenticate_github()?;
+    let configs_issues = list_hiring_issues(&github, company).await?;
+
+    // Search is kept up to date as applicants are refreshed, but a sync should not fail because
+    // the search index is unavailable.
+    let search = match ApplicantSearchIndex::default_client() {
+        Ok(client) => match ApplicantSearchIndex::configure_index(&client).await {
+            Ok(()) => Some(client),
+            Err(e) => {
+                warn!("configuring the applicant search index failed: {}", e);
+                None
+            }
+        },
+        Err(e) => {
+            warn!("creating the applicant search client failed: {}", e);
+            None
+        }
+    };
 
     // We want all the applicants without a sheet id, since this is the list of applicants we care
     // about. Everything else came from Google Sheets and therefore uses the old system.
@@ -2423,6 +2733,10 @@ pub async fn refresh_new_applicants_and_reviews(
         let applicant_ids = (min..=max).collect::<Vec<i32>>();
         let applicant_id_chunks = applicant_ids.chunks(100);
 
+        // Progress is counted in ids, some of which may belong to deleted applicants.
+        let total = applicant_ids.len();
+        let mut done = 0;
+
         for chunk in applicant_id_chunks {
             log::info!("Fetching applicants {:?} through {:?}", chunk.first(), chunk.last());
 
@@ -2448,7 +2762,7 @@ pub async fn refresh_new_applicants_and_reviews(
                 let tasks: Vec<_> = applicant_chunk
                     .into_iter()
                     .map(|mut applicant| {
-                        tokio::spawn(
+                        crate::correlation::spawn(
                             enclose! { (db, company, github, configs_issues, app_config) async move {
                                 applicant.refresh(&db, &company, &github, &configs_issues, app_config).await
                             }},
@@ -2465,6 +2779,26 @@ pub async fn refresh_new_applicants_and_reviews(
                     result?;
                 }
             }
+
+            if let Some(client) = &search {
+                // Index the applicants as they were saved, with the contents of their resume and
+                // materials.
+                let refreshed = applicants::dsl::applicants
+                    .filter(applicants::dsl::id.eq_any(fetched_applicant_ids))
+                    .load_async::<Applicant>(db.pool())
+                    .await?;
+                if let Err(e) = ApplicantSearchIndex::index_applicants(client, &refreshed).await {
+                    warn!(
+                        "indexing applicants {:?} through {:?} for search failed: {}",
+                        chunk.first(),
+                        chunk.last(),
+                        e
+                    );
+                }
+            }
+
+            done += chunk.len();
+            crate::progress::stage_progress("applicants", done, Some(total));
         }
     }
 
@@ -2555,6 +2889,10 @@ pub mod tests {
             scoring_underwhelming_materials_count: 0,
             criminal_background_check_status: String::default(),
             motor_vehicle_background_check_status: String::default(),
+            checkr_report_id: String::default(),
+            checkr_adverse_action_id: String::default(),
+            adverse_action_status: String::default(),
+            background_check_status: String::default(),
             start_date: Some(NaiveDate::from_ymd(2092, 01, 01)),
             interested_in: vec![],
             geocode_cache: String::default(),
@@ -2566,7 +2904,13 @@ pub mod tests {
             docusign_piia_envelope_status: String::default(),
             piia_envelope_created: None,
             piia_envelope_completed: None,
+            offer_salary: "$100,000".to_string(),
+            offer_equity: "10,000 shares".to_string(),
+            offer_documents_link: String::default(),
+            employee_agreements_link: String::default(),
             link_to_reviews: vec![],
+            score: 0.0,
+            score_details: String::default(),
             cio_company_id: 0,
             airtable_record_id: String::default(),
         }
@@ -2651,4 +2995,74 @@ manager = ''
             body
         );
     }
+
+    fn mock_checkr_event(type_: &str, package: &str, status: &str) -> checkr::WebhookEvent {
+        serde_json::from_value(json!({
+            "id": "event-id",
+            "object": "event",
+            "type": type_,
+            "created_at": "2023-01-24T16:00:00Z",
+            "data": {
+                "object": {
+                    "id": "report-id",
+                    "candidate_id": "candidate-id",
+                    "package": package,
+                    "status": status,
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_updates_background_check_from_checkr() {
+        let mut applicant = mock_applicant();
+        applicant.criminal_background_check_status = "requested".to_string();
+
+        let event = mock_checkr_event("report.completed", "premium_criminal", "complete");
+        assert!(applicant.update_background_check_from_checkr(&event));
+        assert_eq!("complete", applicant.criminal_background_check_status);
+        assert_eq!("", applicant.motor_vehicle_background_check_status);
+        // The report has no result yet.
+        assert_eq!("pending", applicant.background_check_status);
+
+        // Nothing changes when the same event is delivered again.
+        assert!(!applicant.update_background_check_from_checkr(&event));
+
+        let event = mock_checkr_event("invitation.expired", "motor_vehicle_report", "expired");
+        assert!(applicant.update_background_check_from_checkr(&event));
+        assert_eq!("expired", applicant.motor_vehicle_background_check_status);
+
+        let event = mock_checkr_event("invitation.completed", "premium_criminal", "completed");
+        assert!(!applicant.update_background_check_from_checkr(&event));
+        assert_eq!("complete", applicant.criminal_background_check_status);
+    }
+
+    #[test]
+    fn test_tracks_adverse_action_from_checkr() {
+        let mut applicant = mock_applicant();
+
+        let event = mock_checkr_event("report.completed", "premium_criminal", "complete");
+        assert!(applicant.update_background_check_from_checkr(&event));
+        assert_eq!("report-id", applicant.checkr_report_id);
+
+        let event = mock_checkr_event("report.pre_adverse_action", "premium_criminal", "complete");
+        assert!(applicant.update_background_check_from_checkr(&event));
+        assert_eq!("pending", applicant.adverse_action_status);
+        assert_eq!("complete", applicant.criminal_background_check_status);
+        assert_eq!("adverse-action", applicant.background_check_status);
+
+        let event = mock_checkr_event("report.post_adverse_action", "premium_criminal", "complete");
+        assert!(applicant.update_background_check_from_checkr(&event));
+        assert_eq!("complete", applicant.adverse_action_status);
+    }
+
+    #[test]
+    fn test_pandoc_input_format() {
+        assert_eq!(
+            Some("docx"),
+            super::pandoc_input_format("application/vnd.openxmlformats-officedocument.wordprocessingml.document")
+        );
+        assert_eq!(None, super::pandoc_input_format("application/octet-stream"));
+    }
 }
@@ -4,7 +4,13 @@ use chrono::Utc;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::{app_config::AppConfig, applicants::NewApplicant, companies::Company, db::Database};
+use crate::{
+    app_config::AppConfig,
+    applicants::{Applicant, NewApplicant},
+    companies::Company,
+    db::Database,
+    recruiting_funnel::record_applicant_source,
+};
 
 #[derive(Debug, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
 pub struct ApplicationForm {
@@ -34,6 +40,10 @@ pub struct ApplicationForm {
     pub materials: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub portfolio_pdf: String,
+    /// Where the applicant heard about the role, ie. `referral` or the `utm_source` of the link
+    /// they followed.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub source: String,
     #[serde(default)]
     pub cio_company_id: i32,
 }
@@ -94,12 +104,24 @@ impl ApplicationForm {
             return Ok(());
         }
 
+        self.create_applicant(db, config).await?;
+
+        Ok(())
+    }
+
+    /// Add the applicant to the database and expand their application, without checking that
+    /// the form has everything the apply page asks for.
+    pub async fn create_applicant(&self, db: &Database, config: AppConfig) -> Result<Applicant> {
         // Convert the application form to an applicant.
         let new_applicant: NewApplicant = self.clone().into();
 
         // Add the applicant to the database.
         let mut applicant = new_applicant.upsert(db).await?;
 
+        if !self.source.is_empty() {
+            record_applicant_source(db, &applicant, &self.source).await?;
+        }
+
         let company = Company::get_by_id(db, applicant.cio_company_id).await?;
 
         // Initialize the GSuite sheets client.
@@ -109,9 +131,7 @@ impl ApplicationForm {
         applicant.expand(db, &drive_client, &config.apply).await?;
 
         // Update airtable and the database again.
-        applicant.update(db).await?;
-
-        Ok(())
+        applicant.update(db).await
     }
 }
 
@@ -181,6 +201,10 @@ impl From<ApplicationForm> for NewApplicant {
             scoring_underwhelming_materials_count: Default::default(),
             criminal_background_check_status: Default::default(),
             motor_vehicle_background_check_status: Default::default(),
+            checkr_report_id: Default::default(),
+            checkr_adverse_action_id: Default::default(),
+            adverse_action_status: Default::default(),
+            background_check_status: Default::default(),
             start_date: None,
             geocode_cache: Default::default(),
             docusign_envelope_id: Default::default(),
@@ -191,7 +215,13 @@ impl From<ApplicationForm> for NewApplicant {
             docusign_piia_envelope_status: Default::default(),
             piia_envelope_created: Default::default(),
             piia_envelope_completed: Default::default(),
+            offer_salary: Default::default(),
+            offer_equity: Default::default(),
+            offer_documents_link: Default::default(),
+            employee_agreements_link: Default::default(),
             link_to_reviews: Default::default(),
+            score: Default::default(),
+            score_details: Default::default(),
             cio_company_id: form.cio_company_id,
         }
     }
@@ -0,0 +1,368 @@
+/*!
+ * Who has which hardware, and who had it before.
+ *
+ * Assets are checked out to employees and checked back in when they are returned. Every check-out
+ * is kept in `asset_assignments`, so the history of an asset is the list of who had it and when,
+ * and the asset's `current_employee_borrowing` in Airtable follows whoever has it now. Scanning
+ * the barcode of an asset with barcodey checks it back in.
+ *
+ * Hardware that is still checked out to someone who was offboarded is a loaner that was not
+ * returned, and is brought up in Slack every few days until it is.
+ */
+use std::{collections::HashSet, fmt};
+
+use anyhow::{anyhow, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use diesel::{ExpressionMethods, Insertable, QueryDsl, Queryable};
+use log::info;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    app_config::AssetsConfig,
+    asset_inventory::AssetItem,
+    companies::Company,
+    configs::User,
+    db::Database,
+    schema::{asset_assignments, asset_items, offboarding_actions},
+};
+
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct AssetAssignment {
+    pub id: i32,
+    pub asset_item_id: i32,
+    /// The username of the employee the asset is checked out to.
+    pub username: String,
+    pub checked_out_at: DateTime<Utc>,
+    /// When a loaner is expected back, if it is.
+    pub due_back: Option<NaiveDate>,
+    pub checked_in_at: Option<DateTime<Utc>>,
+    /// When the asset was last brought up as not returned after offboarding.
+    pub last_nagged_at: Option<DateTime<Utc>>,
+    pub notes: String,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = asset_assignments)]
+struct NewAssetAssignment {
+    asset_item_id: i32,
+    username: String,
+    due_back: Option<NaiveDate>,
+    notes: String,
+    cio_company_id: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct CheckOutRequest {
+    pub asset_item_id: i32,
+    /// The username of the employee to check the asset out to.
+    pub username: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_back: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+}
+
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct CheckInRequest {
+    pub asset_item_id: i32,
+}
+
+/// An asset could not be checked out, because someone has it already.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetCheckedOut {
+    pub asset: String,
+    pub username: String,
+}
+
+impl fmt::Display for AssetCheckedOut {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} is checked out to {} already", self.asset, self.username)
+    }
+}
+
+impl std::error::Error for AssetCheckedOut {}
+
+impl AssetAssignment {
+    pub fn is_checked_out(&self) -> bool {
+        self.checked_in_at.is_none()
+    }
+
+    /// Whether it has been long enough since the asset was last brought up as not returned.
+    fn reminder_due(&self, now: DateTime<Utc>, every: Duration) -> bool {
+        self.is_checked_out()
+            && self
+                .last_nagged_at
+                .map(|last_nagged_at| last_nagged_at + every <= now)
+                .unwrap_or(true)
+    }
+}
+
+/// Who the asset is checked out to now, if anyone.
+pub async fn checked_out_to(db: &Database, asset_item_id: i32) -> Result<Option<AssetAssignment>> {
+    Ok(asset_assignments::dsl::asset_assignments
+        .filter(asset_assignments::dsl::asset_item_id.eq(asset_item_id))
+        .filter(asset_assignments::dsl::checked_in_at.is_null())
+        .load_async::<AssetAssignment>(db.pool())
+        .await?
+        .pop())
+}
+
+/// Everyone the asset was checked out to, latest first.
+pub async fn assignment_history(db: &Database, asset_item_id: i32) -> Result<Vec<AssetAssignment>> {
+    Ok(asset_assignments::dsl::asset_assignments
+        .filter(asset_assignments::dsl::asset_item_id.eq(asset_item_id))
+        .order_by(asset_assignments::dsl::checked_out_at.desc())
+        .load_async::<AssetAssignment>(db.pool())
+        .await?)
+}
+
+impl AssetItem {
+    /// Check the asset out to an employee. Fails with [`AssetCheckedOut`] if someone has it
+    /// already, it has to be checked in first.
+    pub async fn check_out(
+        &mut self,
+        db: &Database,
+        username: &str,
+        due_back: Option<NaiveDate>,
+        notes: &str,
+    ) -> Result<AssetAssignment> {
+        let user = User::get_from_db(db, self.cio_company_id, username.to_string())
+            .await
+            .ok_or_else(|| anyhow!("no user {} to check {} out to", username, self.name))?;
+
+        if let Some(current) = checked_out_to(db, self.id).await? {
+            return Err(AssetCheckedOut {
+                asset: self.name.to_string(),
+                username: current.username,
+            }
+            .into());
+        }
+
+        let assignment = diesel::insert_into(asset_assignments::table)
+            .values(NewAssetAssignment {
+                asset_item_id: self.id,
+                username: user.username.to_string(),
+                due_back,
+                notes: notes.to_string(),
+                cio_company_id: self.cio_company_id,
+            })
+            .get_result_async::<AssetAssignment>(db.pool())
+            .await?;
+
+        self.current_employee_borrowing = user.email.to_string();
+        self.update(db).await?;
+
+        info!("checked out {} to {}", self.name, user.username);
+
+        Ok(assignment)
+    }
+
+    /// Check the asset back in. Returns the assignment that was ended, or `None` if nobody had
+    /// the asset checked out.
+    pub async fn check_in(&mut self, db: &Database) -> Result<Option<AssetAssignment>> {
+        let assignment = diesel::update(asset_assignments::dsl::asset_assignments)
+            .filter(asset_assignments::dsl::asset_item_id.eq(self.id))
+            .filter(asset_assignments::dsl::checked_in_at.is_null())
+            .set(asset_assignments::dsl::checked_in_at.eq(Some(Utc::now())))
+            .get_results_async::<AssetAssignment>(db.pool())
+            .await?
+            .pop();
+
+        if !self.current_employee_borrowing.is_empty() {
+            self.current_employee_borrowing = String::new();
+            self.update(db).await?;
+        }
+
+        if let Some(assignment) = &assignment {
+            info!("checked in {} from {}", self.name, assignment.username);
+        }
+
+        Ok(assignment)
+    }
+}
+
+/// Check in the asset with the scanned `barcode`. Returns `false` if the barcode is not the
+/// barcode of an asset.
+pub async fn check_in_scanned(db: &Database, barcode: &str) -> Result<bool> {
+    let asset = asset_items::dsl::asset_items
+        .filter(asset_items::dsl::barcode.eq(barcode.trim().to_uppercase()))
+        .load_async::<AssetItem>(db.pool())
+        .await?
+        .pop();
+
+    match asset {
+        Some(mut asset) => {
+            asset.check_in(db).await?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+fn unreturned_line(asset: &AssetItem, assignment: &AssetAssignment) -> String {
+    let serial = if asset.serial_number.is_empty() {
+        String::new()
+    } else {
+        format!(", serial {}", asset.serial_number)
+    };
+
+    format!(
+        "• *{}* ({} {}{}), checked out to {} on {}",
+        asset.name,
+        asset.manufacturer,
+        asset.model_number,
+        serial,
+        assignment.username,
+        assignment.checked_out_at.format("%a %b %-d %Y")
+    )
+}
+
+fn unreturned_message(channel: &str, lines: &[String]) -> FormattedMessage {
+    FormattedMessage {
+        channel: channel.to_string(),
+        attachments: Default::default(),
+        blocks: vec![MessageBlock {
+            block_type: MessageBlockType::Section,
+            text: Some(MessageBlockText {
+                text_type: MessageType::Markdown,
+                text: format!(
+                    "*Hardware that was not returned after offboarding*\n{}",
+                    lines.join("\n")
+                ),
+            }),
+            elements: Default::default(),
+            accessory: Default::default(),
+            block_id: Default::default(),
+            fields: Default::default(),
+        }],
+    }
+}
+
+/// Bring up the hardware that is still checked out to people who were offboarded.
+pub async fn remind_unreturned_assets(db: &Database, company: &Company, config: &AssetsConfig) -> Result<()> {
+    if config.unreturned_channel.is_empty() {
+        return Ok(());
+    }
+
+    let offboarded = offboarding_actions::dsl::offboarding_actions
+        .filter(offboarding_actions::dsl::cio_company_id.eq(company.id))
+        .select(offboarding_actions::dsl::username)
+        .distinct()
+        .load_async::<String>(db.pool())
+        .await?
+        .into_iter()
+        .collect::<HashSet<_>>();
+
+    let now = Utc::now();
+    let every = Duration::days(config.unreturned_reminder_days);
+    let unreturned = asset_assignments::dsl::asset_assignments
+        .filter(asset_assignments::dsl::cio_company_id.eq(company.id))
+        .filter(asset_assignments::dsl::checked_in_at.is_null())
+        .order_by(asset_assignments::dsl::checked_out_at)
+        .load_async::<AssetAssignment>(db.pool())
+        .await?
+        .into_iter()
+        .filter(|assignment| offboarded.contains(&assignment.username) && assignment.reminder_due(now, every))
+        .collect::<Vec<_>>();
+    if unreturned.is_empty() {
+        return Ok(());
+    }
+
+    let mut lines = vec![];
+    for assignment in &unreturned {
+        let asset = AssetItem::get_by_id(db, assignment.asset_item_id).await?;
+        lines.push(unreturned_line(&asset, assignment));
+    }
+
+    company
+        .post_to_slack_channel(db, &unreturned_message(&config.unreturned_channel, &lines))
+        .await?;
+
+    diesel::update(asset_assignments::dsl::asset_assignments)
+        .filter(
+            asset_assignments::dsl::id.eq_any(unreturned.iter().map(|assignment| assignment.id).collect::<Vec<_>>()),
+        )
+        .set(asset_assignments::dsl::last_nagged_at.eq(Some(now)))
+        .execute_async(db.pool())
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::{unreturned_line, AssetAssignment};
+    use crate::asset_inventory::AssetItem;
+
+    fn mock_assignment() -> AssetAssignment {
+        AssetAssignment {
+            id: 1,
+            asset_item_id: 1,
+            username: "random_username".to_string(),
+            checked_out_at: Utc.ymd(2023, 1, 9).and_hms(17, 0, 0),
+            due_back: None,
+            checked_in_at: None,
+            last_nagged_at: None,
+            notes: String::new(),
+            cio_company_id: 1,
+        }
+    }
+
+    #[test]
+    fn test_reminder_due() {
+        let now = Utc.ymd(2023, 3, 27).and_hms(12, 0, 0);
+        let every = Duration::days(3);
+
+        let mut assignment = mock_assignment();
+        assert!(assignment.reminder_due(now, every));
+
+        assignment.last_nagged_at = Some(now - Duration::days(1));
+        assert!(!assignment.reminder_due(now, every));
+
+        assignment.last_nagged_at = Some(now - Duration::days(3));
+        assert!(assignment.reminder_due(now, every));
+
+        assignment.checked_in_at = Some(now);
+        assert!(!assignment.reminder_due(now, every));
+    }
+
+    #[test]
+    fn test_unreturned_line() {
+        let asset = AssetItem {
+            id: 1,
+            name: "loud-laptop".to_string(),
+            picture: String::new(),
+            type_: "Laptop".to_string(),
+            qualities: vec![],
+            status: String::new(),
+            manufacturer: "Lenovo".to_string(),
+            model_number: "X1".to_string(),
+            serial_number: "PF123".to_string(),
+            purchase_price: 0.0,
+            current_employee_borrowing: String::new(),
+            conference_room_using: vec![],
+            notes: String::new(),
+            barcode: String::new(),
+            barcode_png: String::new(),
+            barcode_svg: String::new(),
+            barcode_pdf_label: String::new(),
+            qr_code_png: String::new(),
+            qr_code_svg: String::new(),
+            purchase_date: None,
+            vendor: String::new(),
+            cio_company_id: 1,
+            airtable_record_id: String::new(),
+        };
+
+        assert_eq!(
+            "• *loud-laptop* (Lenovo X1, serial PF123), checked out to random_username on Mon Jan 9 2023",
+            unreturned_line(&asset, &mock_assignment())
+        );
+    }
+}
@@ -5,6 +5,7 @@ use barcoders::{
     generators::{image::Image, svg::SVG},
     sym::code39::Code39,
 };
+use chrono::NaiveDate;
 use google_drive::{
     traits::{DriveOps, FileOps},
     Client as GoogleDrive,
@@ -16,10 +17,19 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    airtable::AIRTABLE_ASSET_ITEMS_TABLE, companies::Company, core::UpdateAirtableRecord, db::Database,
-    printer::Printer, schema::asset_items, swag_inventory::generate_pdf_barcode_label,
+    airtable::AIRTABLE_ASSET_ITEMS_TABLE,
+    barcodes::{Barcode, RenderOptions, Symbology},
+    companies::Company,
+    core::UpdateAirtableRecord,
+    db::Database,
+    printer::Printer,
+    schema::asset_items,
+    swag_inventory::generate_pdf_barcode_label,
 };
 
+/// The resolution of our label printer.
+const LABEL_DPI: u32 = 300;
+
 #[db {
     new_struct_name = "AssetItem",
     airtable_base = "assets",
@@ -91,6 +101,26 @@ pub struct NewAssetItem {
         deserialize_with = "airtable_api::attachment_format_as_string::deserialize"
     )]
     pub barcode_pdf_label: String,
+    /// A QR code that links to the asset's record, for asset tags that are too small for the
+    /// barcode.
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        deserialize_with = "airtable_api::attachment_format_as_string::deserialize"
+    )]
+    pub qr_code_png: String,
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        deserialize_with = "airtable_api::attachment_format_as_string::deserialize"
+    )]
+    pub qr_code_svg: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purchase_date: Option<NaiveDate>,
+    /// Who the asset was bought from.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub vendor: String,
 
     /// The CIO company ID.
     #[serde(default)]
@@ -139,6 +169,7 @@ impl NewAssetItem {
         drive_client: &GoogleDrive,
         drive_id: &str,
         parent_id: &str,
+        record_url: &str,
     ) -> Result<String> {
         let barcode_value = self.generate_barcode();
 
@@ -209,14 +240,64 @@ impl NewAssetItem {
             self.barcode_pdf_label = format!("https://drive.google.com/uc?export=download&id={}", label_file.id);
         }
 
+        if !self.name.is_empty() && (self.qr_code_png.is_empty() || self.qr_code_svg.is_empty()) {
+            self.generate_qr_code_images(drive_client, drive_id, parent_id, record_url)
+                .await?;
+        }
+
         Ok(self.barcode_pdf_label.to_string())
     }
 
-    pub async fn expand(&mut self, drive_client: &GoogleDrive, drive_id: &str, parent_id: &str) -> Result<String> {
-        self.generate_barcode_images(drive_client, drive_id, parent_id).await
+    async fn generate_qr_code_images(
+        &mut self,
+        drive_client: &GoogleDrive,
+        drive_id: &str,
+        parent_id: &str,
+        record_url: &str,
+    ) -> Result<()> {
+        let qr_code = Barcode::new(Symbology::QrCode, record_url)?;
+        let options = RenderOptions::for_symbology(Symbology::QrCode, LABEL_DPI);
+
+        let file_name = format!("{} {} - QR Code.png", self.type_, self.name.replace('/', ""));
+        let png_file = drive_client
+            .files()
+            .create_or_update(drive_id, parent_id, &file_name, "image/png", &qr_code.png(&options)?)
+            .await?;
+        self.qr_code_png = format!("https://drive.google.com/uc?export=download&id={}", png_file.id);
+
+        let file_name = format!("{} {} - QR Code.svg", self.type_, self.name.replace('/', ""));
+        let svg_file = drive_client
+            .files()
+            .create_or_update(
+                drive_id,
+                parent_id,
+                &file_name,
+                "image/svg+xml",
+                qr_code.svg(&options)?.as_bytes(),
+            )
+            .await?;
+        self.qr_code_svg = format!("https://drive.google.com/uc?export=download&id={}", svg_file.id);
+
+        Ok(())
+    }
+
+    pub async fn expand(
+        &mut self,
+        drive_client: &GoogleDrive,
+        drive_id: &str,
+        parent_id: &str,
+        record_url: &str,
+    ) -> Result<String> {
+        self.generate_barcode_images(drive_client, drive_id, parent_id, record_url)
+            .await
     }
 }
 
+/// The link to the asset's record in Airtable, which is what the QR code on an asset tag opens.
+pub fn asset_record_url(company: &Company, record_id: &str) -> String {
+    format!("https://airtable.com/{}/{}", company.airtable_base_id_assets, record_id)
+}
+
 /// A request to print labels.
 #[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
 pub struct PrintLabelsRequest {
@@ -250,7 +331,13 @@ impl AssetItem {
             let parent_id = drive_client.files().create_folder(&drive_id, "", "assets").await?;
 
             let mut sw: NewAssetItem = From::from(self.clone());
-            sw.expand(&drive_client, &drive_id, &parent_id).await?
+            sw.expand(
+                &drive_client,
+                &drive_id,
+                &parent_id,
+                &asset_record_url(&company, &self.airtable_record_id),
+            )
+            .await?
         } else {
             self.barcode_pdf_label.trim().to_string()
         };
@@ -301,6 +388,7 @@ pub async fn refresh_asset_items(db: &Database, company: &Company) -> Result<()>
         .await?;
 
     for item_record in results {
+        let record_url = asset_record_url(company, &item_record.id);
         let mut item: NewAssetItem = item_record.fields.into();
         if item.name.is_empty() {
             // A new generator is created for each use as the Generator is not Send
@@ -310,7 +398,7 @@ pub async fn refresh_asset_items(db: &Database, company: &Company) -> Result<()>
         // Iterating through and processing all of the asset items can take over an hour. This
         // exceeds the time limit that Google Drive allots for a single token. Therefore we may
         // need to refresh the access token mid processing if an item expansion fails
-        match item.expand(&drive_client, &drive_id, &parent_id).await {
+        match item.expand(&drive_client, &drive_id, &parent_id, &record_url).await {
             Ok(_) => (),
             Err(err) => {
                 log::info!("Handling drive error. This is likely to be an authentication error. Further work is needed to differentiate. {:?}", err);
@@ -319,7 +407,7 @@ pub async fn refresh_asset_items(db: &Database, company: &Company) -> Result<()>
 
                 // Now using a client with fresh credentials, we can retry the expansion. If this
                 // again, it is unlikely due to an authentication error
-                item.expand(&drive_client, &drive_id, &parent_id).await?;
+                item.expand(&drive_client, &drive_id, &parent_id, &record_url).await?;
             }
         }
 
@@ -0,0 +1,578 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use checkr::{Checkr, WebhookEvent, WebhookEventType};
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, Insertable, QueryDsl, Queryable};
+use log::{info, warn};
+use serde::Serialize;
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    app_config::BackgroundCheckConfig,
+    applicants::Applicant,
+    companies::Company,
+    configs::{User, Users},
+    db::Database,
+    schema::{applicants, background_check_events},
+};
+
+/// The adverse action statuses after which Checkr will no longer change the adverse action.
+const FINAL_ADVERSE_ACTION_STATUSES: &[&str] = &["complete", "canceled"];
+
+/// The status of a background check, the same whichever provider ran it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BackgroundCheckStatus {
+    /// The candidate was invited to the check, but has not submitted their information yet.
+    Initiated,
+    /// The check is running.
+    Pending,
+    /// The check completed with results that need to be reviewed.
+    Consider,
+    /// The check completed without anything to review, or its results were reviewed and the
+    /// candidate is moving forward.
+    Clear,
+    /// Adverse action was started based on the results of the check.
+    AdverseAction,
+}
+
+impl fmt::Display for BackgroundCheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let status = match self {
+            BackgroundCheckStatus::Initiated => "initiated",
+            BackgroundCheckStatus::Pending => "pending",
+            BackgroundCheckStatus::Consider => "consider",
+            BackgroundCheckStatus::Clear => "clear",
+            BackgroundCheckStatus::AdverseAction => "adverse-action",
+        };
+
+        write!(f, "{}", status)
+    }
+}
+
+impl FromStr for BackgroundCheckStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(status: &str) -> Result<Self> {
+        Ok(match status {
+            "initiated" => BackgroundCheckStatus::Initiated,
+            "pending" => BackgroundCheckStatus::Pending,
+            "consider" => BackgroundCheckStatus::Consider,
+            "clear" => BackgroundCheckStatus::Clear,
+            "adverse-action" => BackgroundCheckStatus::AdverseAction,
+            _ => bail!("unknown background check status `{}`", status),
+        })
+    }
+}
+
+impl BackgroundCheckStatus {
+    /// The status a Checkr event moves the check to, if it moves it at all. Motor vehicle
+    /// reports are only tracked on their own, and invitations that expire or are deleted leave
+    /// the status as it was so that the check still shows as never completed.
+    pub fn from_checkr_event(event: &WebhookEvent) -> Option<Self> {
+        if event.data.object.package.contains("motor_vehicle") {
+            return None;
+        }
+
+        match event.event_type() {
+            WebhookEventType::InvitationCreated => Some(BackgroundCheckStatus::Initiated),
+            WebhookEventType::ReportCreated
+            | WebhookEventType::ReportUpgraded
+            | WebhookEventType::ReportResumed
+            | WebhookEventType::ReportSuspended
+            | WebhookEventType::ReportDisputed => Some(BackgroundCheckStatus::Pending),
+            WebhookEventType::ReportCompleted => match event.data.object.result.as_str() {
+                "clear" => Some(BackgroundCheckStatus::Clear),
+                "consider" => Some(BackgroundCheckStatus::Consider),
+                _ => Some(BackgroundCheckStatus::Pending),
+            },
+            // The results were reviewed and the candidate is moving forward.
+            WebhookEventType::ReportEngaged => Some(BackgroundCheckStatus::Clear),
+            WebhookEventType::ReportPreAdverseAction | WebhookEventType::ReportPostAdverseAction => {
+                Some(BackgroundCheckStatus::AdverseAction)
+            }
+            _ => None,
+        }
+    }
+
+    /// Move `status` along for the Checkr event. Returns whether it changed.
+    pub fn update_from_checkr(status: &mut String, event: &WebhookEvent) -> bool {
+        match Self::from_checkr_event(event) {
+            Some(next) if *status != next.to_string() => {
+                *status = next.to_string();
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Who a background check is for.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BackgroundCheckSubject {
+    Applicant,
+    Employee,
+}
+
+impl fmt::Display for BackgroundCheckSubject {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BackgroundCheckSubject::Applicant => write!(f, "applicant"),
+            BackgroundCheckSubject::Employee => write!(f, "employee"),
+        }
+    }
+}
+
+/// A change in the status of a background check, kept for compliance.
+#[derive(Debug, Clone, PartialEq, Queryable, Serialize)]
+pub struct BackgroundCheckEvent {
+    pub id: i32,
+    pub subject: String,
+    pub email: String,
+    pub name: String,
+    pub role: String,
+    pub status: String,
+    pub provider: String,
+    pub provider_event_type: String,
+    pub report_id: String,
+    pub cio_company_id: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = background_check_events)]
+struct NewBackgroundCheckEvent {
+    subject: String,
+    email: String,
+    name: String,
+    role: String,
+    status: String,
+    provider: String,
+    provider_event_type: String,
+    report_id: String,
+    cio_company_id: i32,
+}
+
+/// Enroll employees in continuous checks and sync the status of any open adverse actions.
+pub async fn refresh_background_checks(db: &Database, company: &Company, config: &BackgroundCheckConfig) -> Result<()> {
+    let checkr = match company.authenticate_checkr() {
+        Some(checkr) => checkr,
+        None => {
+            info!("skipping `refresh_background_checks` for company `{}`", company.name);
+
+            // Return early.
+            return Ok(());
+        }
+    };
+
+    enroll_in_continuous_checks(db, company, &checkr, config).await?;
+    refresh_adverse_actions(db, company, &checkr).await?;
+
+    Ok(())
+}
+
+/// Enroll every full-time employee that is not enrolled yet in the configured continuous checks.
+async fn enroll_in_continuous_checks(
+    db: &Database,
+    company: &Company,
+    checkr: &Checkr,
+    config: &BackgroundCheckConfig,
+) -> Result<()> {
+    if config.continuous_check_types.is_empty() {
+        return Ok(());
+    }
+
+    let users = Users::get_from_db(db, company.id).await?;
+    for mut user in users {
+        if !user.is_full_time() || user.email.is_empty() || !user.continuous_check_status.is_empty() {
+            continue;
+        }
+
+        // Employees that went through our hiring process are already candidates under the
+        // personal email they applied with.
+        let mut candidate = None;
+        if !user.recovery_email.is_empty() {
+            candidate = checkr.find_candidate_by_email(&user.recovery_email).await?;
+        }
+        if candidate.is_none() {
+            candidate = checkr.find_candidate_by_email(&user.email).await?;
+        }
+        let candidate = match candidate {
+            Some(candidate) => candidate,
+            None => checkr.create_candidate(&user.email).await?,
+        };
+
+        let enrolled: Vec<String> = checkr
+            .list_continuous_checks(&candidate.id)
+            .await?
+            .into_iter()
+            .map(|check| check.type_)
+            .collect();
+        for type_ in &config.continuous_check_types {
+            if !enrolled.contains(type_) {
+                checkr.create_continuous_check(&candidate.id, type_).await?;
+            }
+        }
+
+        user.checkr_candidate_id = candidate.id.to_string();
+        user.continuous_check_status = "enrolled".to_string();
+        user.update(db).await?;
+
+        info!("enrolled {} in checkr continuous checks", user.email);
+    }
+
+    Ok(())
+}
+
+/// Webhooks tell us about the notices being sent, this catches anything we missed along with
+/// adverse actions that were canceled.
+async fn refresh_adverse_actions(db: &Database, company: &Company, checkr: &Checkr) -> Result<()> {
+    let pending = applicants::dsl::applicants
+        .filter(applicants::dsl::cio_company_id.eq(company.id))
+        .filter(applicants::dsl::checkr_adverse_action_id.ne(""))
+        .filter(applicants::dsl::adverse_action_status.ne_all(FINAL_ADVERSE_ACTION_STATUSES.to_vec()))
+        .load_async::<Applicant>(db.pool())
+        .await?;
+
+    for mut applicant in pending {
+        let adverse_action = match checkr.get_adverse_action(&applicant.checkr_adverse_action_id).await {
+            Ok(adverse_action) => adverse_action,
+            Err(e) => {
+                warn!(
+                    "failed to get checkr adverse action {} for applicant {}: {}",
+                    applicant.checkr_adverse_action_id, applicant.email, e
+                );
+                continue;
+            }
+        };
+
+        if adverse_action.status != applicant.adverse_action_status {
+            applicant.adverse_action_status = adverse_action.status;
+            applicant.update(db).await?;
+        }
+    }
+
+    Ok(())
+}
+
+fn markdown_message(channel: &str, text: String) -> FormattedMessage {
+    FormattedMessage {
+        channel: channel.to_string(),
+        attachments: Default::default(),
+        blocks: vec![MessageBlock {
+            block_type: MessageBlockType::Section,
+            text: Some(MessageBlockText {
+                text_type: MessageType::Markdown,
+                text,
+            }),
+            elements: Default::default(),
+            accessory: Default::default(),
+            block_id: Default::default(),
+            fields: Default::default(),
+        }],
+    }
+}
+
+/// Where a change to the background check of the subject is posted. Applicants' hiring
+/// managers are sent a direct message.
+fn notification_channel(config: &BackgroundCheckConfig, subject: BackgroundCheckSubject, role: &str) -> String {
+    if subject == BackgroundCheckSubject::Applicant {
+        if let Some(manager) = config.hiring_managers.get(role).filter(|manager| !manager.is_empty()) {
+            return manager.to_string();
+        }
+    }
+
+    config.slack_channel.to_string()
+}
+
+/// Record the change to the background check of the subject and let the people who need to
+/// know about it know. Only the status is shared, never the results of the check.
+#[allow(clippy::too_many_arguments)]
+async fn record_change(
+    db: &Database,
+    company: &Company,
+    config: &BackgroundCheckConfig,
+    subject: BackgroundCheckSubject,
+    name: &str,
+    email: &str,
+    role: &str,
+    status: &str,
+    event: &WebhookEvent,
+) -> Result<()> {
+    let report_id = if event.event_type().is_report() {
+        event.data.object.id.to_string()
+    } else {
+        event.data.object.report_id.to_string()
+    };
+
+    diesel::insert_into(background_check_events::table)
+        .values(NewBackgroundCheckEvent {
+            subject: subject.to_string(),
+            email: email.to_string(),
+            name: name.to_string(),
+            role: role.to_string(),
+            status: status.to_string(),
+            provider: "checkr".to_string(),
+            provider_event_type: event.type_.to_string(),
+            report_id,
+            cio_company_id: company.id,
+        })
+        .execute_async(db.pool())
+        .await?;
+
+    let channel = notification_channel(config, subject, role);
+    if channel.is_empty() {
+        return Ok(());
+    }
+
+    let who = if role.is_empty() {
+        name.to_string()
+    } else {
+        format!("{} ({})", name, role)
+    };
+    company
+        .post_to_slack_channel(
+            db,
+            &markdown_message(
+                &channel,
+                format!("The background check for {} is now *{}*.", who, status),
+            ),
+        )
+        .await?;
+
+    info!("background check for {} {} is now {}", subject, email, status);
+
+    Ok(())
+}
+
+/// Record a change to the background check of an applicant, after
+/// `Applicant::update_background_check_from_checkr` changed its status.
+pub async fn record_applicant_background_check(
+    db: &Database,
+    company: &Company,
+    config: &BackgroundCheckConfig,
+    applicant: &Applicant,
+    event: &WebhookEvent,
+) -> Result<()> {
+    record_change(
+        db,
+        company,
+        config,
+        BackgroundCheckSubject::Applicant,
+        &applicant.name,
+        &applicant.email,
+        &applicant.role,
+        &applicant.background_check_status,
+        event,
+    )
+    .await
+}
+
+/// Update the background check of an employee enrolled in continuous checks from a Checkr
+/// event, and record the change if there was one.
+pub async fn update_employee_background_check(
+    db: &Database,
+    company: &Company,
+    config: &BackgroundCheckConfig,
+    user: &mut User,
+    event: &WebhookEvent,
+) -> Result<()> {
+    if !BackgroundCheckStatus::update_from_checkr(&mut user.background_check_status, event) {
+        return Ok(());
+    }
+
+    user.update(db).await?;
+
+    record_change(
+        db,
+        company,
+        config,
+        BackgroundCheckSubject::Employee,
+        &user.full_name(),
+        &user.email,
+        "",
+        &user.background_check_status,
+        event,
+    )
+    .await
+}
+
+/// Every change to a background check in the window, oldest first, as a CSV for compliance
+/// reviews.
+pub async fn export_background_checks(
+    db: &Database,
+    company: &Company,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+) -> Result<String> {
+    let mut query = background_check_events::dsl::background_check_events
+        .filter(background_check_events::dsl::cio_company_id.eq(company.id))
+        .into_boxed();
+    if let Some(since) = since {
+        query = query.filter(background_check_events::dsl::created_at.ge(since));
+    }
+    if let Some(until) = until {
+        query = query.filter(background_check_events::dsl::created_at.lt(until));
+    }
+
+    let events = query
+        .order_by(background_check_events::dsl::created_at.asc())
+        .load_async::<BackgroundCheckEvent>(db.pool())
+        .await?;
+
+    events_csv(&events)
+}
+
+fn events_csv(events: &[BackgroundCheckEvent]) -> Result<String> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    writer.write_record([
+        "changed_at",
+        "subject",
+        "name",
+        "email",
+        "role",
+        "status",
+        "provider",
+        "provider_event_type",
+        "report_id",
+    ])?;
+    for event in events {
+        writer.write_record([
+            event.created_at.to_rfc3339().as_str(),
+            event.subject.as_str(),
+            event.name.as_str(),
+            event.email.as_str(),
+            event.role.as_str(),
+            event.status.as_str(),
+            event.provider.as_str(),
+            event.provider_event_type.as_str(),
+            event.report_id.as_str(),
+        ])?;
+    }
+
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+
+    use super::{
+        events_csv, notification_channel, BackgroundCheckEvent, BackgroundCheckStatus, BackgroundCheckSubject,
+    };
+    use crate::app_config::BackgroundCheckConfig;
+
+    fn mock_checkr_event(type_: &str, package: &str, result: &str) -> checkr::WebhookEvent {
+        serde_json::from_value(json!({
+            "id": "event-id",
+            "object": "event",
+            "type": type_,
+            "created_at": "2023-01-24T16:00:00Z",
+            "data": {
+                "object": {
+                    "id": "report-id",
+                    "candidate_id": "candidate-id",
+                    "package": package,
+                    "status": "complete",
+                    "result": result,
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_status_from_checkr_event() {
+        let status = |type_, result| {
+            BackgroundCheckStatus::from_checkr_event(&mock_checkr_event(type_, "premium_criminal", result))
+        };
+
+        assert_eq!(Some(BackgroundCheckStatus::Initiated), status("invitation.created", ""));
+        assert_eq!(Some(BackgroundCheckStatus::Pending), status("report.created", ""));
+        assert_eq!(Some(BackgroundCheckStatus::Clear), status("report.completed", "clear"));
+        assert_eq!(
+            Some(BackgroundCheckStatus::Consider),
+            status("report.completed", "consider")
+        );
+        assert_eq!(Some(BackgroundCheckStatus::Clear), status("report.engaged", "consider"));
+        assert_eq!(
+            Some(BackgroundCheckStatus::AdverseAction),
+            status("report.pre_adverse_action", "consider")
+        );
+        assert_eq!(None, status("invitation.expired", ""));
+
+        let motor_vehicle = mock_checkr_event("report.completed", "motor_vehicle_report", "clear");
+        assert_eq!(None, BackgroundCheckStatus::from_checkr_event(&motor_vehicle));
+
+        for status in ["initiated", "pending", "consider", "clear", "adverse-action"] {
+            assert_eq!(status, status.parse::<BackgroundCheckStatus>().unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn test_update_from_checkr() {
+        let mut status = "pending".to_string();
+
+        let event = mock_checkr_event("report.completed", "premium_criminal", "consider");
+        assert!(BackgroundCheckStatus::update_from_checkr(&mut status, &event));
+        assert_eq!("consider", status);
+
+        // Nothing changes when the same event is delivered again.
+        assert!(!BackgroundCheckStatus::update_from_checkr(&mut status, &event));
+    }
+
+    #[test]
+    fn test_notification_channel() {
+        let mut config = BackgroundCheckConfig {
+            slack_channel: "#people-ops".to_string(),
+            ..Default::default()
+        };
+        config
+            .hiring_managers
+            .insert("Hardware Engineer".to_string(), "U123".to_string());
+
+        assert_eq!(
+            "U123",
+            notification_channel(&config, BackgroundCheckSubject::Applicant, "Hardware Engineer")
+        );
+        assert_eq!(
+            "#people-ops",
+            notification_channel(&config, BackgroundCheckSubject::Applicant, "Software Engineer")
+        );
+        assert_eq!(
+            "#people-ops",
+            notification_channel(&config, BackgroundCheckSubject::Employee, "")
+        );
+    }
+
+    #[test]
+    fn test_events_csv() {
+        let csv = events_csv(&[BackgroundCheckEvent {
+            id: 1,
+            subject: "applicant".to_string(),
+            email: "jane@example.com".to_string(),
+            name: "Jane Doe".to_string(),
+            role: "Hardware Engineer".to_string(),
+            status: "clear".to_string(),
+            provider: "checkr".to_string(),
+            provider_event_type: "report.completed".to_string(),
+            report_id: "report-id".to_string(),
+            cio_company_id: 1,
+            created_at: Utc.ymd(2023, 3, 1).and_hms(17, 0, 0),
+        }])
+        .unwrap();
+
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(
+            "changed_at,subject,name,email,role,status,provider,provider_event_type,report_id",
+            lines[0]
+        );
+        assert_eq!(
+            "2023-03-01T17:00:00+00:00,applicant,Jane Doe,jane@example.com,Hardware Engineer,clear,checkr,\
+             report.completed,report-id",
+            lines[1]
+        );
+    }
+}
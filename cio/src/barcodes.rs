@@ -0,0 +1,283 @@
+/*!
+ * Rendering barcodes for labels.
+ *
+ * Every symbology is reduced to a grid of modules which is then drawn at a fixed number of
+ * pixels per module, so that bars line up with the printer's dots instead of being resampled.
+ */
+use std::fmt::Write;
+
+use anyhow::{anyhow, Result};
+use barcoders::sym::{code128::Code128, code39::Code39};
+use datamatrix::{DataMatrix, SymbolList};
+use image::{DynamicImage, GrayImage, ImageOutputFormat, Luma};
+use qrcode::{Color, QrCode};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Symbology {
+    Code39,
+    Code128,
+    QrCode,
+    DataMatrix,
+}
+
+impl Symbology {
+    pub fn is_linear(&self) -> bool {
+        matches!(self, Symbology::Code39 | Symbology::Code128)
+    }
+}
+
+/// Sizes are in inches so that the same label can be rendered for printers with different
+/// resolutions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderOptions {
+    pub dpi: u32,
+    /// The width of the narrowest bar, or of a single cell for 2D symbologies.
+    pub module_size: f64,
+    /// The height of the bars. Ignored for 2D symbologies, which are always square.
+    pub bar_height: f64,
+    /// The blank margin around the barcode, in modules.
+    pub quiet_zone: u32,
+}
+
+impl RenderOptions {
+    /// Defaults that scan reliably from a 2" x 1" label.
+    pub fn for_symbology(symbology: Symbology, dpi: u32) -> Self {
+        if symbology.is_linear() {
+            RenderOptions {
+                dpi,
+                module_size: 0.01,
+                bar_height: 0.5,
+                quiet_zone: 10,
+            }
+        } else {
+            RenderOptions {
+                dpi,
+                module_size: 0.02,
+                bar_height: 0.0,
+                quiet_zone: if symbology == Symbology::QrCode { 4 } else { 2 },
+            }
+        }
+    }
+
+    /// Modules are always drawn with a whole number of pixels, never less than one.
+    fn pixels_per_module(&self) -> u32 {
+        ((self.module_size * self.dpi as f64).round() as u32).max(1)
+    }
+}
+
+enum Modules {
+    /// A single row of bars, dark bars are `true`.
+    Linear(Vec<bool>),
+    /// A square grid of cells in row order, dark cells are `true`.
+    Matrix { size: usize, cells: Vec<bool> },
+}
+
+pub struct Barcode {
+    pub symbology: Symbology,
+    pub value: String,
+    modules: Modules,
+}
+
+impl Barcode {
+    pub fn new(symbology: Symbology, value: &str) -> Result<Self> {
+        let modules = match symbology {
+            Symbology::Code39 => Modules::Linear(to_bars(&Code39::new(value)?.encode())),
+            // Character set B covers all printable ASCII characters.
+            Symbology::Code128 => Modules::Linear(to_bars(&Code128::new(format!("Ɓ{}", value))?.encode())),
+            Symbology::QrCode => {
+                let code = QrCode::new(value.as_bytes())?;
+                Modules::Matrix {
+                    size: code.width(),
+                    cells: code.to_colors().into_iter().map(|c| c == Color::Dark).collect(),
+                }
+            }
+            Symbology::DataMatrix => {
+                let bitmap = DataMatrix::encode(value.as_bytes(), SymbolList::default())
+                    .map_err(|e| anyhow!("could not encode `{}` as a data matrix: {:?}", value, e))?
+                    .bitmap();
+
+                // Data matrix symbols may be rectangular, so they are padded to a square.
+                let size = bitmap.width().max(bitmap.height());
+                let mut cells = vec![false; size * size];
+                for (x, y) in bitmap.pixels() {
+                    cells[y * size + x] = true;
+                }
+
+                Modules::Matrix { size, cells }
+            }
+        };
+
+        Ok(Barcode {
+            symbology,
+            value: value.to_string(),
+            modules,
+        })
+    }
+
+    /// The size of the barcode in modules, including the quiet zone.
+    fn dimensions(&self, options: &RenderOptions) -> (u32, u32) {
+        let quiet_zone = options.quiet_zone * 2;
+
+        match &self.modules {
+            Modules::Linear(bars) => {
+                let height = ((options.bar_height / options.module_size).round() as u32).max(1);
+                (bars.len() as u32 + quiet_zone, height)
+            }
+            Modules::Matrix { size, .. } => (*size as u32 + quiet_zone, *size as u32 + quiet_zone),
+        }
+    }
+
+    fn is_dark(&self, x: u32, y: u32, options: &RenderOptions) -> bool {
+        let x = x as i64 - options.quiet_zone as i64;
+
+        match &self.modules {
+            Modules::Linear(bars) => x >= 0 && bars.get(x as usize).copied().unwrap_or(false),
+            Modules::Matrix { size, cells } => {
+                let y = y as i64 - options.quiet_zone as i64;
+                let size = *size as i64;
+                x >= 0 && y >= 0 && x < size && y < size && cells[(y * size + x) as usize]
+            }
+        }
+    }
+
+    pub fn png(&self, options: &RenderOptions) -> Result<Vec<u8>> {
+        let scale = options.pixels_per_module();
+        let (width, height) = self.dimensions(options);
+
+        let image = GrayImage::from_fn(width * scale, height * scale, |x, y| {
+            if self.is_dark(x / scale, y / scale, options) {
+                Luma([0u8])
+            } else {
+                Luma([255u8])
+            }
+        });
+
+        let mut bytes = Vec::new();
+        DynamicImage::ImageLuma8(image).write_to(&mut bytes, ImageOutputFormat::Png)?;
+
+        Ok(bytes)
+    }
+
+    /// The SVG is sized in inches and drawn in modules, so it is sharp at any resolution.
+    pub fn svg(&self, options: &RenderOptions) -> Result<String> {
+        let (width, height) = self.dimensions(options);
+        let quiet_zone = options.quiet_zone as usize;
+
+        let mut path = String::new();
+        match &self.modules {
+            Modules::Linear(bars) => {
+                for (x, len) in runs(bars) {
+                    write!(path, "M{},0h{}v{}h-{}z", x + quiet_zone, len, height, len)?;
+                }
+            }
+            Modules::Matrix { size, cells } => {
+                for (y, row) in cells.chunks(*size).enumerate() {
+                    for (x, len) in runs(row) {
+                        write!(path, "M{},{}h{}v1h-{}z", x + quiet_zone, y + quiet_zone, len, len)?;
+                    }
+                }
+            }
+        }
+
+        Ok(format!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="{}in" height="{}in" viewBox="0 0 {} {}" shape-rendering="crispEdges"><rect width="{}" height="{}" fill="#fff"/><path d="{}" fill="#000"/></svg>"##,
+            width as f64 * options.module_size,
+            height as f64 * options.module_size,
+            width,
+            height,
+            width,
+            height,
+            path
+        ))
+    }
+}
+
+fn to_bars(encoded: &[u8]) -> Vec<bool> {
+    encoded.iter().map(|b| *b == 1).collect()
+}
+
+/// The start and length of each run of dark modules.
+fn runs(modules: &[bool]) -> Vec<(usize, usize)> {
+    let mut runs = vec![];
+    let mut start = None;
+
+    for (i, dark) in modules.iter().chain(std::iter::once(&false)).enumerate() {
+        match (start, dark) {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                runs.push((s, i - s));
+                start = None;
+            }
+            _ => (),
+        }
+    }
+
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{runs, Barcode, RenderOptions, Symbology};
+
+    #[test]
+    fn test_runs() {
+        assert_eq!(
+            vec![(0, 2), (3, 1), (6, 3)],
+            runs(&[true, true, false, true, false, false, true, true, true])
+        );
+        assert!(runs(&[false, false]).is_empty());
+    }
+
+    #[test]
+    fn test_png_scales_with_dpi() {
+        let barcode = Barcode::new(Symbology::QrCode, "https://example.com/assets/rec123").unwrap();
+
+        let low = image::load_from_memory(
+            &barcode
+                .png(&RenderOptions::for_symbology(Symbology::QrCode, 203))
+                .unwrap(),
+        )
+        .unwrap();
+        let high = image::load_from_memory(
+            &barcode
+                .png(&RenderOptions::for_symbology(Symbology::QrCode, 600))
+                .unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(low.width(), low.height());
+        assert_eq!(high.width() / 12, low.width() / 4);
+    }
+
+    #[test]
+    fn test_linear_dimensions() {
+        let barcode = Barcode::new(Symbology::Code128, "000MACBOOKPRO").unwrap();
+        let options = RenderOptions::for_symbology(Symbology::Code128, 300);
+
+        let (width, height) = barcode.dimensions(&options);
+        assert_eq!(50, height);
+        assert!(width > 20);
+
+        // The quiet zone is never dark.
+        assert!(!barcode.is_dark(0, 0, &options));
+        assert!(!barcode.is_dark(width - 1, 0, &options));
+    }
+
+    #[test]
+    fn test_svg() {
+        for symbology in [
+            Symbology::Code39,
+            Symbology::Code128,
+            Symbology::QrCode,
+            Symbology::DataMatrix,
+        ] {
+            let barcode = Barcode::new(symbology, "ABC123").unwrap();
+            let svg = barcode.svg(&RenderOptions::for_symbology(symbology, 300)).unwrap();
+
+            assert!(svg.starts_with("<svg"));
+            assert!(svg.contains("<path d=\"M"));
+        }
+    }
+}
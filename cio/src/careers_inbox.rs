@@ -0,0 +1,358 @@
+/*!
+ * Applications emailed to the careers inbox.
+ *
+ * The mail provider posts each email sent to the inbox to us. The sender becomes the applicant,
+ * the role is found by name in the subject or the body, and the attachments become their resume
+ * and materials, falling back to the body of the email for the materials so that answers written
+ * inline are kept. People who already applied are not added again, only the files their
+ * application was missing are filled in.
+ */
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use diesel::{ExpressionMethods, QueryDsl};
+use log::info;
+use regex::Regex;
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    app_config::{AppConfig, CareersInboxConfig},
+    applicants::Applicant,
+    application_form::ApplicationForm,
+    companies::Company,
+    db::Database,
+    schema::applicants,
+};
+
+/// The applications sent by email are counted under this source in the recruiting funnel.
+const CAREERS_INBOX_SOURCE: &str = "careers inbox";
+
+/// The file extensions of attachments that can be a resume or materials. Anything else, ie. the
+/// images in a signature, is ignored.
+const DOCUMENT_EXTENSIONS: &[&str] = &["pdf", "doc", "docx", "odt", "rtf", "txt", "md"];
+
+/// An email sent to the careers inbox.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InboundEmail {
+    /// The `From` header, ie. `Jane Doe <jane@example.com>`.
+    pub from: String,
+    pub subject: String,
+    /// The plain text body.
+    pub text: String,
+}
+
+/// An application read from an email, with its files already stored.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InboundApplication {
+    pub name: String,
+    pub email: String,
+    pub role: String,
+    pub resume: String,
+    pub materials: String,
+    pub github: String,
+    pub linkedin: String,
+}
+
+/// What became of an email sent to the careers inbox.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InboxOutcome {
+    /// A new applicant was added.
+    Created(i32),
+    /// The sender already applied, and their application was missing files the email had.
+    Updated(i32),
+    /// The sender already applied, and the email had nothing their application was missing.
+    Duplicate(i32),
+}
+
+/// Which attachment is the resume and which the materials, by their index.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ApplicationAttachments {
+    pub resume: Option<usize>,
+    pub materials: Option<usize>,
+}
+
+/// The name and email of the sender, from a `From` header. The name falls back to the part of
+/// the email before the `@`.
+pub fn parse_sender(from: &str) -> Option<(String, String)> {
+    let from = from.trim();
+    let (name, email) = match (from.rfind('<'), from.rfind('>')) {
+        (Some(start), Some(end)) if start < end => (
+            from[..start].trim().trim_matches('"').trim(),
+            from[start + 1..end].trim(),
+        ),
+        _ => ("", from),
+    };
+
+    let email = email.to_lowercase();
+    let local = match email.split_once('@') {
+        Some((local, domain)) if !local.is_empty() && domain.contains('.') => local.to_string(),
+        _ => return None,
+    };
+
+    let name = if name.is_empty() { local } else { name.to_string() };
+
+    Some((name, email))
+}
+
+fn find_role<'a>(roles: &'a [String], text: &str) -> Option<&'a String> {
+    let text = text.to_lowercase();
+
+    // The longest name that matches wins, so that `Senior Software Engineer` is not taken for
+    // `Software Engineer`.
+    roles
+        .iter()
+        .filter(|role| !role.is_empty() && text.contains(&role.to_lowercase()))
+        .max_by_key(|role| role.len())
+}
+
+/// The role an application is for, by its name in the subject, or failing that in the body.
+pub fn match_role(config: &CareersInboxConfig, email: &InboundEmail) -> Option<String> {
+    find_role(&config.roles, &email.subject)
+        .or_else(|| find_role(&config.roles, &email.text))
+        .cloned()
+        .or_else(|| Some(config.default_role.to_string()).filter(|role| !role.is_empty()))
+}
+
+/// Pick the resume and the materials out of the attachments of an email, by their file names.
+/// Documents that are not named as either fill in the resume first and then the materials, in
+/// the order they were attached.
+pub fn classify_attachments(file_names: &[String]) -> ApplicationAttachments {
+    let documents = file_names
+        .iter()
+        .enumerate()
+        .filter(|(_, name)| {
+            let extension = name
+                .rsplit_once('.')
+                .map(|(_, ext)| ext.to_lowercase())
+                .unwrap_or_default();
+            DOCUMENT_EXTENSIONS.contains(&extension.as_str())
+        })
+        .map(|(i, name)| (i, name.to_lowercase()))
+        .collect::<Vec<_>>();
+
+    let mut attachments = ApplicationAttachments {
+        resume: documents
+            .iter()
+            .find(|(_, name)| name.contains("resume") || name.contains("résumé") || name.contains("cv"))
+            .map(|(i, _)| *i),
+        ..Default::default()
+    };
+    attachments.materials = documents
+        .iter()
+        .find(|(i, name)| {
+            Some(*i) != attachments.resume
+                && (name.contains("material") || name.contains("question") || name.contains("cover"))
+        })
+        .map(|(i, _)| *i);
+
+    for (i, _) in &documents {
+        if attachments.resume == Some(*i) || attachments.materials == Some(*i) {
+            continue;
+        }
+
+        if attachments.resume.is_none() {
+            attachments.resume = Some(*i);
+        } else if attachments.materials.is_none() {
+            attachments.materials = Some(*i);
+        }
+    }
+
+    attachments
+}
+
+/// The GitHub and LinkedIn profiles linked in the body of an email.
+pub fn profile_links(text: &str) -> (String, String) {
+    let github = Regex::new(r"(?i)https?://(www\.)?github\.com/[A-Za-z0-9-]+").unwrap();
+    let linkedin = Regex::new(r"(?i)https?://([a-z]+\.)?linkedin\.com/in/[A-Za-z0-9_%-]+").unwrap();
+
+    let find = |re: &Regex| re.find(text).map(|m| m.as_str().to_string()).unwrap_or_default();
+
+    (find(&github), find(&linkedin))
+}
+
+/// The applicant who applied with `email`. People apply through the form, the job boards and the
+/// inbox, so they are looked up by their email alone.
+pub async fn find_applicant(db: &Database, company: &Company, email: &str) -> Option<Applicant> {
+    applicants::dsl::applicants
+        .filter(applicants::dsl::email.eq(email.to_string()))
+        .filter(applicants::dsl::cio_company_id.eq(company.id))
+        .first_async::<Applicant>(db.pool())
+        .await
+        .ok()
+}
+
+/// Add the application to the applicants, unless the sender already applied, in which case
+/// only the files their application was missing are filled in.
+pub async fn ingest_application(
+    db: &Database,
+    company: &Company,
+    config: &AppConfig,
+    application: InboundApplication,
+) -> Result<InboxOutcome> {
+    if let Some(mut applicant) = find_applicant(db, company, &application.email).await {
+        let mut updated = false;
+        if applicant.resume.is_empty() && !application.resume.is_empty() {
+            applicant.resume = application.resume.to_string();
+            updated = true;
+        }
+        if applicant.materials.is_empty() && !application.materials.is_empty() {
+            applicant.materials = application.materials.to_string();
+            updated = true;
+        }
+
+        if !updated {
+            info!(
+                "ignoring email to the careers inbox from {}, who already applied",
+                application.email
+            );
+            return Ok(InboxOutcome::Duplicate(applicant.id));
+        }
+
+        applicant.update(db).await?;
+        info!(
+            "added the files emailed to the careers inbox to the application of {}",
+            application.email
+        );
+
+        return Ok(InboxOutcome::Updated(applicant.id));
+    }
+
+    let form = ApplicationForm {
+        name: application.name,
+        email: application.email,
+        role: application.role,
+        interested_in: Default::default(),
+        location: Default::default(),
+        phone: Default::default(),
+        github: application.github,
+        linkedin: application.linkedin,
+        portfolio: Default::default(),
+        website: Default::default(),
+        resume: application.resume,
+        materials: application.materials,
+        portfolio_pdf: Default::default(),
+        source: CAREERS_INBOX_SOURCE.to_string(),
+        cio_company_id: company.id,
+    };
+
+    let applicant = form.create_applicant(db, config.clone()).await?;
+    info!(
+        "added {} as an applicant for {} from the careers inbox",
+        applicant.email, applicant.role
+    );
+
+    Ok(InboxOutcome::Created(applicant.id))
+}
+
+/// Post an application that could not be matched to a role to the applicants channel, so that
+/// it is triaged by hand.
+pub async fn post_unmatched_application(db: &Database, company: &Company, email: &InboundEmail) -> Result<()> {
+    let msg = FormattedMessage {
+        channel: company.slack_channel_applicants.to_string(),
+        attachments: Default::default(),
+        blocks: vec![MessageBlock {
+            block_type: MessageBlockType::Section,
+            text: Some(MessageBlockText {
+                text_type: MessageType::Markdown,
+                text: format!(
+                    "An application emailed to the careers inbox by {} does not say which role it is for, it \
+                     needs to be added by hand: _{}_",
+                    email.from, email.subject
+                ),
+            }),
+            elements: Default::default(),
+            accessory: Default::default(),
+            block_id: Default::default(),
+            fields: Default::default(),
+        }],
+    };
+
+    company.post_to_slack_channel(db, &msg).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{classify_attachments, match_role, parse_sender, profile_links, ApplicationAttachments, InboundEmail};
+    use crate::app_config::CareersInboxConfig;
+
+    #[test]
+    fn test_parse_sender() {
+        assert_eq!(
+            Some(("Jane Doe".to_string(), "jane@example.com".to_string())),
+            parse_sender("\"Jane Doe\" <Jane@Example.com>")
+        );
+        assert_eq!(
+            Some(("jane".to_string(), "jane@example.com".to_string())),
+            parse_sender("jane@example.com")
+        );
+        assert_eq!(None, parse_sender("Jane Doe"));
+    }
+
+    #[test]
+    fn test_match_role() {
+        let mut config = CareersInboxConfig {
+            roles: vec!["Software Engineer".to_string(), "Senior Software Engineer".to_string()],
+            ..Default::default()
+        };
+
+        let email = InboundEmail {
+            from: "jane@example.com".to_string(),
+            subject: "Application: senior software engineer".to_string(),
+            text: "Hi, I'm applying for the Software Engineer role.".to_string(),
+        };
+        assert_eq!(
+            Some("Senior Software Engineer".to_string()),
+            match_role(&config, &email)
+        );
+
+        let email = InboundEmail {
+            subject: "Hello".to_string(),
+            ..email
+        };
+        assert_eq!(Some("Software Engineer".to_string()), match_role(&config, &email));
+
+        let email = InboundEmail {
+            text: "Hi, are you hiring?".to_string(),
+            ..email
+        };
+        assert_eq!(None, match_role(&config, &email));
+
+        config.default_role = "General".to_string();
+        assert_eq!(Some("General".to_string()), match_role(&config, &email));
+    }
+
+    #[test]
+    fn test_classify_attachments() {
+        let names = |names: &[&str]| names.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            ApplicationAttachments {
+                resume: Some(2),
+                materials: Some(1),
+            },
+            classify_attachments(&names(&["signature.png", "Oxide Materials.pdf", "Jane Doe CV.pdf"]))
+        );
+        assert_eq!(
+            ApplicationAttachments {
+                resume: Some(0),
+                materials: Some(1),
+            },
+            classify_attachments(&names(&["jane.pdf", "answers.docx"]))
+        );
+        assert_eq!(
+            ApplicationAttachments::default(),
+            classify_attachments(&names(&["logo.png"]))
+        );
+    }
+
+    #[test]
+    fn test_profile_links() {
+        assert_eq!(
+            (
+                "https://github.com/janedoe".to_string(),
+                "https://www.linkedin.com/in/jane-doe".to_string()
+            ),
+            profile_links("Code: https://github.com/janedoe/project\nhttps://www.linkedin.com/in/jane-doe/")
+        );
+        assert_eq!((String::new(), String::new()), profile_links("no links here"));
+    }
+}
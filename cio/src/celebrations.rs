@@ -0,0 +1,526 @@
+/*!
+ * Celebrating birthdays and work anniversaries in Slack.
+ *
+ * Celebrations are posted in each person's own time zone, taken from their Slack profile, once
+ * the configured hour has come on the day. Every post is claimed in `celebration_posts` before it
+ * is made, so nobody is celebrated twice however often the job runs.
+ *
+ * Work anniversaries are celebrated for everyone, with the years in
+ * [`CelebrationsConfig::milestone_years`] called out as milestones. Birthdays are only
+ * celebrated for people who have chosen to share theirs. Anyone can opt out of all of it with
+ * the `/celebrations` Slack command.
+ *
+ * On Mondays, managers are also sent a digest of what is coming up for their reports that week.
+ */
+use std::{collections::HashMap, fmt};
+
+use anyhow::{anyhow, bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
+use diesel::{upsert::excluded, ExpressionMethods, Insertable, QueryDsl, Queryable};
+use log::{info, warn};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    app_config::CelebrationsConfig,
+    companies::Company,
+    configs::User,
+    db::Database,
+    schema::{celebration_posts, celebration_preferences, users},
+};
+
+/// What someone chose to have celebrated. People without a row get the defaults: anniversaries
+/// are celebrated, birthdays are not.
+#[derive(Debug, Queryable, PartialEq, Clone)]
+pub struct CelebrationPreference {
+    pub id: i32,
+    pub username: String,
+    pub opted_out: bool,
+    pub share_birthday: bool,
+    pub cio_company_id: i32,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = celebration_preferences)]
+struct NewCelebrationPreference {
+    username: String,
+    opted_out: bool,
+    share_birthday: bool,
+    cio_company_id: i32,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = celebration_posts)]
+struct NewCelebrationPost {
+    username: String,
+    occasion: String,
+    occasion_date: NaiveDate,
+    cio_company_id: i32,
+}
+
+/// The weekly digest is claimed like a celebration, under the manager's username.
+const DIGEST_OCCASION: &str = "upcoming_digest";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Occasion {
+    Birthday,
+    /// The number of years since they started.
+    WorkAnniversary(i32),
+}
+
+impl fmt::Display for Occasion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Occasion::Birthday => "birthday",
+            Occasion::WorkAnniversary(_) => "work_anniversary",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// Whether the yearly date of `date` falls on `day`. Dates on February 29th fall on the 28th in
+/// years that are not leap years.
+fn falls_on(date: NaiveDate, day: NaiveDate) -> bool {
+    if date.month() == day.month() && date.day() == day.day() {
+        return true;
+    }
+
+    date.month() == 2
+        && date.day() == 29
+        && day.month() == 2
+        && day.day() == 28
+        && NaiveDate::from_ymd_opt(day.year(), 2, 29).is_none()
+}
+
+/// What there is to celebrate for the user on `day`.
+fn occasions_on(user: &User, preference: Option<&CelebrationPreference>, day: NaiveDate) -> Vec<Occasion> {
+    if preference.map(|preference| preference.opted_out).unwrap_or(false) {
+        return vec![];
+    }
+
+    let mut occasions = vec![];
+    if preference.map(|preference| preference.share_birthday).unwrap_or(false) && falls_on(user.birthday, day) {
+        occasions.push(Occasion::Birthday);
+    }
+
+    let years = day.year() - user.start_date.year();
+    if years > 0 && falls_on(user.start_date, day) {
+        occasions.push(Occasion::WorkAnniversary(years));
+    }
+
+    occasions
+}
+
+/// What there is to celebrate for the users in the `days` from `from`, in date order.
+fn upcoming<'a>(
+    users: &[&'a User],
+    preferences: &HashMap<String, CelebrationPreference>,
+    from: NaiveDate,
+    days: i64,
+) -> Vec<(NaiveDate, &'a User, Occasion)> {
+    let mut upcoming = vec![];
+    for offset in 0..days {
+        let day = from + Duration::days(offset);
+        for user in users {
+            for occasion in occasions_on(user, preferences.get(&user.username), day) {
+                upcoming.push((day, *user, occasion));
+            }
+        }
+    }
+
+    upcoming
+}
+
+fn parse_time_zone(time_zone: &str) -> Result<Tz> {
+    time_zone
+        .parse()
+        .map_err(|e| anyhow!("invalid time zone `{}`: {}", time_zone, e))
+}
+
+/// How to refer to someone in a message: a mention when they are in Slack.
+fn mention(user: &User, slack_id: Option<&str>) -> String {
+    match slack_id {
+        Some(slack_id) => format!("<@{}>", slack_id),
+        None => format!("*{}*", user.full_name()),
+    }
+}
+
+fn celebration_text(config: &CelebrationsConfig, who: &str, occasion: Occasion) -> String {
+    match occasion {
+        Occasion::Birthday => format!(":birthday: Happy birthday, {}!", who),
+        Occasion::WorkAnniversary(years) if config.milestone_years.contains(&years) => format!(
+            ":trophy: {} has been with us for *{} year{}* today! Thank you for everything :tada:",
+            who,
+            years,
+            if years == 1 { "" } else { "s" }
+        ),
+        Occasion::WorkAnniversary(years) => format!(":tada: Happy {} year work anniversary, {}!", years, who),
+    }
+}
+
+fn digest_text(upcoming: &[(NaiveDate, &User, Occasion)]) -> String {
+    let lines = upcoming
+        .iter()
+        .map(|(day, user, occasion)| {
+            let what = match occasion {
+                Occasion::Birthday => "birthday".to_string(),
+                Occasion::WorkAnniversary(years) => format!("{} year work anniversary", years),
+            };
+            format!("• {}: {}'s {}", day.format("%a %b %-d"), user.full_name(), what)
+        })
+        .collect::<Vec<_>>();
+
+    format!(":calendar: Coming up for your team this week:\n{}", lines.join("\n"))
+}
+
+fn markdown_message(channel: &str, text: String) -> FormattedMessage {
+    FormattedMessage {
+        channel: channel.to_string(),
+        attachments: Default::default(),
+        blocks: vec![MessageBlock {
+            block_type: MessageBlockType::Section,
+            text: Some(MessageBlockText {
+                text_type: MessageType::Markdown,
+                text,
+            }),
+            elements: Default::default(),
+            accessory: Default::default(),
+            block_id: Default::default(),
+            fields: Default::default(),
+        }],
+    }
+}
+
+/// Claim the post for `occasion` on `day`. Returns whether nobody had claimed it yet.
+async fn claim_post(db: &Database, company: &Company, username: &str, occasion: &str, day: NaiveDate) -> Result<bool> {
+    let claimed = diesel::insert_into(celebration_posts::table)
+        .values(NewCelebrationPost {
+            username: username.to_string(),
+            occasion: occasion.to_string(),
+            occasion_date: day,
+            cio_company_id: company.id,
+        })
+        .on_conflict((
+            celebration_posts::cio_company_id,
+            celebration_posts::username,
+            celebration_posts::occasion,
+            celebration_posts::occasion_date,
+        ))
+        .do_nothing()
+        .execute_async(db.pool())
+        .await?;
+
+    Ok(claimed > 0)
+}
+
+/// Let go of a claim whose post failed, so that the next run tries again.
+async fn release_post(db: &Database, company: &Company, username: &str, occasion: &str, day: NaiveDate) -> Result<()> {
+    diesel::delete(celebration_posts::dsl::celebration_posts)
+        .filter(celebration_posts::dsl::cio_company_id.eq(company.id))
+        .filter(celebration_posts::dsl::username.eq(username.to_string()))
+        .filter(celebration_posts::dsl::occasion.eq(occasion.to_string()))
+        .filter(celebration_posts::dsl::occasion_date.eq(day))
+        .execute_async(db.pool())
+        .await?;
+
+    Ok(())
+}
+
+/// Make a claimed post, letting go of the claim if it fails.
+async fn post(
+    db: &Database,
+    company: &Company,
+    message: &FormattedMessage,
+    username: &str,
+    occasion: &str,
+    day: NaiveDate,
+) -> Result<()> {
+    if !claim_post(db, company, username, occasion, day).await? {
+        return Ok(());
+    }
+
+    if let Err(e) = company.post_to_slack_channel(db, message).await {
+        release_post(db, company, username, occasion, day).await?;
+        bail!("posting the {} of {} failed: {}", occasion, username, e);
+    }
+
+    info!("posted the {} of {} for {}", occasion, username, day);
+
+    Ok(())
+}
+
+/// Post today's birthdays and work anniversaries, and send managers their weekly digest.
+pub async fn send_celebrations(db: &Database, company: &Company, config: &CelebrationsConfig) -> Result<()> {
+    if config.channel.is_empty() {
+        info!("skipping `send_celebrations` for company `{}`", company.name);
+
+        // Return early.
+        return Ok(());
+    }
+
+    let default_tz = parse_time_zone(&config.time_zone)?;
+    let now = Utc::now();
+    let today = now.with_timezone(&default_tz).date().naive_local();
+
+    let users = users::dsl::users
+        .filter(users::dsl::cio_company_id.eq(company.id))
+        .load_async::<User>(db.pool())
+        .await?
+        .into_iter()
+        .filter(|user| !user.email.is_empty() && user.termination_date.map(|date| date > today).unwrap_or(true))
+        .collect::<Vec<_>>();
+
+    let preferences = celebration_preferences::dsl::celebration_preferences
+        .filter(celebration_preferences::dsl::cio_company_id.eq(company.id))
+        .load_async::<CelebrationPreference>(db.pool())
+        .await?
+        .into_iter()
+        .map(|preference| (preference.username.to_string(), preference))
+        .collect::<HashMap<_, _>>();
+
+    let slack_users = company
+        .authenticate_slack(db)
+        .await?
+        .list_users()
+        .await?
+        .into_iter()
+        .filter(|slack_user| !slack_user.deleted && !slack_user.email.is_empty())
+        .map(|slack_user| (slack_user.email.to_lowercase(), slack_user))
+        .collect::<HashMap<_, _>>();
+
+    let mut failures = vec![];
+    for user in &users {
+        let slack_user = slack_users.get(&user.email.to_lowercase());
+        let tz = match slack_user.filter(|slack_user| !slack_user.tz.is_empty()) {
+            Some(slack_user) => parse_time_zone(&slack_user.tz).unwrap_or(default_tz),
+            None => default_tz,
+        };
+        let local = now.with_timezone(&tz);
+        if local.hour() < config.post_hour {
+            continue;
+        }
+
+        let day = local.date().naive_local();
+        let who = mention(user, slack_user.map(|slack_user| slack_user.id.as_str()));
+        for occasion in occasions_on(user, preferences.get(&user.username), day) {
+            let message = markdown_message(&config.channel, celebration_text(config, &who, occasion));
+            if let Err(e) = post(db, company, &message, &user.username, &occasion.to_string(), day).await {
+                warn!("{}", e);
+                failures.push(e.to_string());
+            }
+        }
+    }
+
+    if today.weekday() == Weekday::Mon && now.with_timezone(&default_tz).hour() >= config.post_hour {
+        for manager in &users {
+            let reports = users
+                .iter()
+                .filter(|user| user.manager == manager.username)
+                .collect::<Vec<_>>();
+            let upcoming = upcoming(&reports, &preferences, today, 7);
+            if upcoming.is_empty() {
+                continue;
+            }
+            let slack_user = match slack_users.get(&manager.email.to_lowercase()) {
+                Some(slack_user) => slack_user,
+                None => continue,
+            };
+
+            let message = markdown_message(&slack_user.id, digest_text(&upcoming));
+            if let Err(e) = post(db, company, &message, &manager.username, DIGEST_OCCASION, today).await {
+                warn!("{}", e);
+                failures.push(e.to_string());
+            }
+        }
+    }
+
+    if !failures.is_empty() {
+        bail!(
+            "{} celebrations failed to post: {}",
+            failures.len(),
+            failures.join("; ")
+        );
+    }
+
+    Ok(())
+}
+
+/// Change what the user behind a Slack account has celebrated, from the text of the
+/// `/celebrations` command. Returns the reply to the command.
+pub async fn handle_celebrations_command(
+    db: &Database,
+    company: &Company,
+    slack_user_id: &str,
+    text: &str,
+) -> Result<String> {
+    let slack_user = company
+        .authenticate_slack(db)
+        .await?
+        .list_users()
+        .await?
+        .into_iter()
+        .find(|slack_user| slack_user.id == slack_user_id)
+        .ok_or_else(|| anyhow!("could not find Slack user `{}`", slack_user_id))?;
+
+    let user = users::dsl::users
+        .filter(users::dsl::cio_company_id.eq(company.id))
+        .filter(users::dsl::email.eq(slack_user.email.to_string()))
+        .first_async::<User>(db.pool())
+        .await
+        .map_err(|_| anyhow!("there is no employee with the email `{}`", slack_user.email))?;
+
+    let existing = celebration_preferences::dsl::celebration_preferences
+        .filter(celebration_preferences::dsl::cio_company_id.eq(company.id))
+        .filter(celebration_preferences::dsl::username.eq(user.username.to_string()))
+        .first_async::<CelebrationPreference>(db.pool())
+        .await
+        .ok();
+    let (mut opted_out, mut share_birthday) = existing
+        .map(|preference| (preference.opted_out, preference.share_birthday))
+        .unwrap_or((false, false));
+
+    let reply = match text.trim().to_lowercase().as_str() {
+        "off" => {
+            opted_out = true;
+            "Got it, nothing will be posted about you."
+        }
+        "on" => {
+            opted_out = false;
+            "Got it, your work anniversaries will be celebrated."
+        }
+        "share birthday" => {
+            share_birthday = true;
+            "Got it, your birthday will be celebrated."
+        }
+        "hide birthday" => {
+            share_birthday = false;
+            "Got it, your birthday will not be posted."
+        }
+        _ => {
+            return Ok(
+                "Use `/celebrations off` or `/celebrations on` to opt out of or back in to celebrations, and \
+                 `/celebrations share birthday` or `/celebrations hide birthday` for your birthday."
+                    .to_string(),
+            )
+        }
+    };
+
+    diesel::insert_into(celebration_preferences::table)
+        .values(NewCelebrationPreference {
+            username: user.username.to_string(),
+            opted_out,
+            share_birthday,
+            cio_company_id: company.id,
+        })
+        .on_conflict((
+            celebration_preferences::cio_company_id,
+            celebration_preferences::username,
+        ))
+        .do_update()
+        .set((
+            celebration_preferences::opted_out.eq(excluded(celebration_preferences::opted_out)),
+            celebration_preferences::share_birthday.eq(excluded(celebration_preferences::share_birthday)),
+            celebration_preferences::updated_at.eq(Utc::now()),
+        ))
+        .execute_async(db.pool())
+        .await?;
+
+    Ok(reply.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chrono::{NaiveDate, TimeZone, Utc};
+
+    use super::{celebration_text, falls_on, occasions_on, upcoming, CelebrationPreference, Occasion};
+    use crate::{app_config::CelebrationsConfig, configs::tests::mock_user};
+
+    fn preference(opted_out: bool, share_birthday: bool) -> CelebrationPreference {
+        CelebrationPreference {
+            id: 1,
+            username: "random_username".to_string(),
+            opted_out,
+            share_birthday,
+            cio_company_id: 1,
+            updated_at: Utc.ymd(2023, 3, 1).and_hms(12, 0, 0),
+        }
+    }
+
+    #[test]
+    fn test_falls_on() {
+        let date = NaiveDate::from_ymd(2020, 3, 28);
+        assert!(falls_on(date, NaiveDate::from_ymd(2023, 3, 28)));
+        assert!(!falls_on(date, NaiveDate::from_ymd(2023, 3, 27)));
+
+        let leap_day = NaiveDate::from_ymd(2020, 2, 29);
+        assert!(falls_on(leap_day, NaiveDate::from_ymd(2023, 2, 28)));
+        assert!(!falls_on(leap_day, NaiveDate::from_ymd(2024, 2, 28)));
+        assert!(falls_on(leap_day, NaiveDate::from_ymd(2024, 2, 29)));
+    }
+
+    #[test]
+    fn test_occasions_on() {
+        let mut user = mock_user();
+        user.start_date = NaiveDate::from_ymd(2018, 6, 4);
+        user.birthday = NaiveDate::from_ymd(1990, 3, 28);
+
+        // Birthdays are only celebrated when they are shared.
+        let birthday = NaiveDate::from_ymd(2023, 3, 28);
+        assert!(occasions_on(&user, None, birthday).is_empty());
+        assert_eq!(
+            vec![Occasion::Birthday],
+            occasions_on(&user, Some(&preference(false, true)), birthday)
+        );
+
+        let anniversary = NaiveDate::from_ymd(2023, 6, 4);
+        assert_eq!(
+            vec![Occasion::WorkAnniversary(5)],
+            occasions_on(&user, None, anniversary)
+        );
+        assert!(occasions_on(&user, Some(&preference(true, true)), anniversary).is_empty());
+
+        // The first day is not an anniversary.
+        assert!(occasions_on(&user, None, user.start_date).is_empty());
+    }
+
+    #[test]
+    fn test_upcoming() {
+        let mut first = mock_user();
+        first.username = "first".to_string();
+        first.start_date = NaiveDate::from_ymd(2021, 3, 30);
+        let mut second = mock_user();
+        second.username = "second".to_string();
+        second.start_date = NaiveDate::from_ymd(2022, 3, 28);
+
+        let upcoming = upcoming(&[&first, &second], &HashMap::new(), NaiveDate::from_ymd(2023, 3, 27), 7);
+        assert_eq!(
+            vec![
+                (NaiveDate::from_ymd(2023, 3, 28), "second", Occasion::WorkAnniversary(1)),
+                (NaiveDate::from_ymd(2023, 3, 30), "first", Occasion::WorkAnniversary(2)),
+            ],
+            upcoming
+                .into_iter()
+                .map(|(day, user, occasion)| (day, user.username.as_str(), occasion))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_celebration_text() {
+        let config = CelebrationsConfig::default();
+
+        assert_eq!(
+            ":tada: Happy 3 year work anniversary, <@U123>!",
+            celebration_text(&config, "<@U123>", Occasion::WorkAnniversary(3))
+        );
+        assert_eq!(
+            ":trophy: <@U123> has been with us for *1 year* today! Thank you for everything :tada:",
+            celebration_text(&config, "<@U123>", Occasion::WorkAnniversary(1))
+        );
+    }
+}
@@ -1,11 +1,11 @@
 #![allow(clippy::from_over_into)]
-use std::{env, time};
+use std::{env, fs, time};
 
 use acme_lib::{create_p384_key, persist::FilePersist, Certificate as AcmeCertificate, Directory, DirectoryUrl};
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_bb8_diesel::AsyncRunQueryDsl;
 use async_trait::async_trait;
-use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
 use google_storage1::{
     api::{Object, Storage},
     hyper,
@@ -16,16 +16,20 @@ use macros::db;
 use mime::Mime;
 use octorust::types::FullRepository;
 use openssl::x509::X509;
+use reqwest::{header::CONTENT_TYPE, StatusCode};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use tokio::io::{AsyncRead, AsyncWrite};
 
 use crate::{
     airtable::AIRTABLE_CERTIFICATES_TABLE,
+    app_config::CertificatesConfig,
     companies::Company,
     core::UpdateAirtableRecord,
     db::Database,
     dns_providers::{DNSProviderOps, DnsRecord, DnsRecordType, DnsUpdateMode},
+    features::Features,
+    notifications::{notify, Notification, NotificationEvent},
     schema::certificates,
     utils::{create_or_update_file_in_github_repo, get_file_content_from_repo},
 };
@@ -81,7 +85,7 @@ pub struct NewCertificate {
 
 impl NewCertificate {
     /// Creates a Let's Encrypt SSL certificate for a domain by using a DNS challenge.
-    /// The DNS Challenge TXT record is added to Cloudflare automatically.
+    /// The DNS Challenge TXT record is added to the DNS provider that hosts the domain.
     pub async fn create_cert(&mut self, company: &Company) -> Result<AcmeCertificate> {
         let api_client = company.authenticate_dns_providers().await?;
 
@@ -126,7 +130,6 @@ impl NewCertificate {
 
             // Create a TXT record for _acme-challenge.{domain} with the value of
             // the proof.
-            // Use the Cloudflare API for this.
             let record_name = format!("_acme-challenge.{}", &self.domain.replace("*.", ""));
 
             // Ensure our DNS record exists.
@@ -288,6 +291,84 @@ impl Certificate {
     }
 }
 
+/// Renew every certificate of the company that expires within the configured window. A failed
+/// renewal does not stop the remaining certificates from being renewed, instead it is posted to
+/// Slack so that it can be fixed before the certificate expires.
+pub async fn renew_expiring_certificates(db: &Database, company: &Company, config: &CertificatesConfig) -> Result<()> {
+    if !Features::is_enabled("RENEW_CERTS") {
+        log::info!(
+            "skipping `renew_expiring_certificates` for company `{}`, cert renewal is disabled",
+            company.name
+        );
+        return Ok(());
+    }
+
+    let storage = company.cert_storage_with_config(config).await?;
+    let renew_before = (Utc::now() + Duration::days(config.renew_within_days))
+        .naive_utc()
+        .date();
+
+    for mut certificate in Certificates::get_from_db(db, company.id).await? {
+        if certificate.expiration_date > renew_before {
+            log::info!(
+                "cert {} expires on {}, skipping",
+                certificate.domain,
+                certificate.expiration_date
+            );
+            continue;
+        }
+
+        log::info!(
+            "Renewing certificate for {} which expires on {}",
+            certificate.domain,
+            certificate.expiration_date
+        );
+
+        if let Err(err) = certificate.renew(db, company, &storage).await {
+            log::error!(
+                "Failed to renew certificate for {} due to {:?}",
+                certificate.domain,
+                err
+            );
+
+            if let Err(err) = notify_renewal_failure(db, company, &certificate, &err).await {
+                log::error!(
+                    "Failed to send renewal failure notification for {}: {:?}",
+                    certificate.domain,
+                    err
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn notify_renewal_failure(
+    db: &Database,
+    company: &Company,
+    certificate: &Certificate,
+    err: &anyhow::Error,
+) -> Result<()> {
+    let mut notification = Notification::new(
+        NotificationEvent::CertExpiring,
+        &certificate.domain,
+        &format!("Failed to renew the certificate for {}", certificate.domain),
+        &format!("It expires on {}.\n```{}```", certificate.expiration_date, err),
+    )
+    .with_attribute("domain", &certificate.domain);
+
+    if certificate.notify_slack_channels.is_empty() {
+        notification = notification.with_fallback_channel(&company.slack_channel_debug);
+    } else {
+        for channel in &certificate.notify_slack_channels {
+            notification = notification.with_fallback_channel(channel);
+        }
+    }
+
+    notify(db, company, &notification).await
+}
+
 pub trait SslCertificateStorage: CertificateStorage + KeyStorage + Send + Sync + 'static {}
 impl<T> SslCertificateStorage for T where T: CertificateStorage + KeyStorage + Send + Sync + 'static {}
 
@@ -453,3 +534,263 @@ where
         Ok(())
     }
 }
+
+#[derive(Debug, Deserialize)]
+struct VaultSecretResponse {
+    data: VaultSecretData,
+}
+
+#[derive(Debug, Deserialize)]
+struct VaultSecretData {
+    data: VaultSecret,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct VaultSecret {
+    value: String,
+}
+
+/// Stores certificates and keys in a Vault KV version 2 secrets engine. The certificate and key
+/// are written as separate secrets so that writing one does not replace the other.
+pub struct VaultBackend {
+    client: reqwest::Client,
+    address: String,
+    token: String,
+    mount: String,
+    path_prefix: String,
+}
+
+impl VaultBackend {
+    pub fn new(address: String, token: String, mount: String, path_prefix: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            address,
+            token,
+            mount,
+            path_prefix,
+        }
+    }
+
+    fn url(&self, domain: &str, file: &str) -> String {
+        let domain = domain.replace("*.", "wildcard.");
+        let path = [self.path_prefix.trim_matches('/'), &domain, file]
+            .iter()
+            .filter(|part| !part.is_empty())
+            .copied()
+            .collect::<Vec<_>>()
+            .join("/");
+
+        format!(
+            "{}/v1/{}/data/{}",
+            self.address.trim_end_matches('/'),
+            self.mount.trim_matches('/'),
+            path
+        )
+    }
+
+    async fn read(&self, domain: &str, file: &str) -> Result<Vec<u8>> {
+        let response: VaultSecretResponse = self
+            .client
+            .get(self.url(domain, file))
+            .header("X-Vault-Token", &self.token)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(response.data.data.value.into_bytes())
+    }
+
+    async fn write(&self, domain: &str, file: &str, data: &[u8]) -> Result<()> {
+        let secret = VaultSecret {
+            value: String::from_utf8(data.to_vec())?,
+        };
+
+        self.client
+            .post(self.url(domain, file))
+            .header("X-Vault-Token", &self.token)
+            .json(&serde_json::json!({ "data": secret }))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CertificateStorage for VaultBackend {
+    async fn read_cert(&self, domain: &str) -> Result<Vec<u8>> {
+        self.read(domain, "certificate").await
+    }
+
+    async fn write_cert(&self, domain: &str, data: &[u8]) -> Result<()> {
+        self.write(domain, "certificate", data).await
+    }
+}
+
+#[async_trait]
+impl KeyStorage for VaultBackend {
+    async fn write_key(&self, domain: &str, data: &[u8]) -> Result<()> {
+        self.write(domain, "key", data).await
+    }
+}
+
+const SERVICE_ACCOUNT_DIR: &str = "/var/run/secrets/kubernetes.io/serviceaccount";
+
+/// Stores certificates and keys as a `kubernetes.io/tls` secret, so that they can be mounted
+/// directly by ingress controllers.
+pub struct KubernetesBackend {
+    client: reqwest::Client,
+    host: String,
+    token: String,
+    namespace: String,
+}
+
+impl KubernetesBackend {
+    pub fn new(client: reqwest::Client, host: String, token: String, namespace: String) -> Self {
+        Self {
+            client,
+            host,
+            token,
+            namespace,
+        }
+    }
+
+    /// Authenticate with the service account that is mounted in to every pod.
+    pub fn in_cluster(namespace: String) -> Result<Self> {
+        let host = format!(
+            "https://{}:{}",
+            env::var("KUBERNETES_SERVICE_HOST")?,
+            env::var("KUBERNETES_SERVICE_PORT")?
+        );
+        let token = fs::read_to_string(format!("{}/token", SERVICE_ACCOUNT_DIR))?;
+        let ca = reqwest::Certificate::from_pem(&fs::read(format!("{}/ca.crt", SERVICE_ACCOUNT_DIR))?)?;
+
+        let client = reqwest::Client::builder().add_root_certificate(ca).build()?;
+
+        Ok(Self::new(client, host, token.trim().to_string(), namespace))
+    }
+
+    fn secret_name(domain: &str) -> String {
+        format!("{}-tls", domain.replace("*.", "wildcard.").replace('.', "-"))
+    }
+
+    fn url(&self, name: &str) -> String {
+        format!("{}/api/v1/namespaces/{}/secrets/{}", self.host, self.namespace, name)
+    }
+
+    async fn write_field(&self, domain: &str, field: &str, data: &[u8]) -> Result<()> {
+        let name = Self::secret_name(domain);
+        let mut fields = serde_json::Map::new();
+        fields.insert(field.to_string(), base64::encode(data).into());
+
+        let response = self
+            .client
+            .patch(self.url(&name))
+            .bearer_auth(&self.token)
+            .header(CONTENT_TYPE, "application/merge-patch+json")
+            .body(serde_json::json!({ "data": fields }).to_string())
+            .send()
+            .await?;
+
+        if response.status() != StatusCode::NOT_FOUND {
+            response.error_for_status()?;
+            return Ok(());
+        }
+
+        // TLS secrets must always have both a certificate and a key, the other one is filled in
+        // when it is written.
+        for key in ["tls.crt", "tls.key"] {
+            fields.entry(key).or_insert_with(|| "".into());
+        }
+
+        self.client
+            .post(format!("{}/api/v1/namespaces/{}/secrets", self.host, self.namespace))
+            .bearer_auth(&self.token)
+            .json(&serde_json::json!({
+                "apiVersion": "v1",
+                "kind": "Secret",
+                "metadata": { "name": name },
+                "type": "kubernetes.io/tls",
+                "data": fields,
+            }))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CertificateStorage for KubernetesBackend {
+    async fn read_cert(&self, domain: &str) -> Result<Vec<u8>> {
+        let secret: serde_json::Value = self
+            .client
+            .get(self.url(&Self::secret_name(domain)))
+            .bearer_auth(&self.token)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let cert = secret["data"]["tls.crt"]
+            .as_str()
+            .ok_or_else(|| anyhow!("secret for {} does not contain a certificate", domain))?;
+
+        Ok(base64::decode(cert)?)
+    }
+
+    async fn write_cert(&self, domain: &str, data: &[u8]) -> Result<()> {
+        self.write_field(domain, "tls.crt", data).await
+    }
+}
+
+#[async_trait]
+impl KeyStorage for KubernetesBackend {
+    async fn write_key(&self, domain: &str, data: &[u8]) -> Result<()> {
+        self.write_field(domain, "tls.key", data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KubernetesBackend, VaultBackend};
+
+    #[test]
+    fn test_vault_paths() {
+        let backend = VaultBackend::new(
+            "https://vault.internal:8200/".to_string(),
+            String::new(),
+            "secret".to_string(),
+            "/certs/".to_string(),
+        );
+        assert_eq!(
+            "https://vault.internal:8200/v1/secret/data/certs/wildcard.example.com/key",
+            backend.url("*.example.com", "key")
+        );
+
+        let backend = VaultBackend::new(
+            "https://vault.internal:8200".to_string(),
+            String::new(),
+            "kv".to_string(),
+            String::new(),
+        );
+        assert_eq!(
+            "https://vault.internal:8200/v1/kv/data/example.com/certificate",
+            backend.url("example.com", "certificate")
+        );
+    }
+
+    #[test]
+    fn test_kubernetes_secret_name() {
+        assert_eq!(
+            "wildcard-example-com-tls",
+            KubernetesBackend::secret_name("*.example.com")
+        );
+        assert_eq!("rfd-example-com-tls", KubernetesBackend::secret_name("rfd.example.com"));
+    }
+}
@@ -0,0 +1,142 @@
+/*!
+ * A circuit breaker for syncs that make a request to a service for every item they sync, ie. a
+ * sync of every RFD from GitHub.
+ *
+ * When the service is down, every item fails and retrying them one after the other only adds to
+ * the load on it. The breaker trips after a number of failures in a row, and the sync pauses
+ * until the cooldown is over. The next request after the pause decides whether the breaker closes
+ * again or trips for another cooldown.
+ */
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerState {
+    /// Requests go through.
+    Closed,
+    /// Too many requests failed, nothing should be sent until the cooldown is over.
+    Open,
+    /// The cooldown is over, the next request is a trial.
+    HalfOpen,
+}
+
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    trips: u32,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            consecutive_failures: 0,
+            opened_at: None,
+            trips: 0,
+        }
+    }
+
+    pub fn state(&self, now: Instant) -> BreakerState {
+        match self.opened_at {
+            None => BreakerState::Closed,
+            Some(opened_at) if now.saturating_duration_since(opened_at) < self.cooldown => BreakerState::Open,
+            Some(_) => BreakerState::HalfOpen,
+        }
+    }
+
+    /// How long is left of the cooldown, `None` when requests can be sent.
+    pub fn cooldown_remaining(&self, now: Instant) -> Option<Duration> {
+        match self.state(now) {
+            BreakerState::Open => self
+                .opened_at
+                .map(|opened_at| self.cooldown - now.saturating_duration_since(opened_at)),
+            _ => None,
+        }
+    }
+
+    /// How many times the breaker has tripped.
+    pub fn trips(&self) -> u32 {
+        self.trips
+    }
+
+    pub fn record_success(&mut self) {
+        self.consecutive_failures = 0;
+        self.opened_at = None;
+    }
+
+    /// Returns true when this failure tripped the breaker.
+    pub fn record_failure(&mut self, now: Instant) -> bool {
+        self.consecutive_failures += 1;
+
+        let trip = match self.state(now) {
+            BreakerState::Closed => self.consecutive_failures >= self.failure_threshold,
+            // Failures of requests that were sent before the breaker tripped.
+            BreakerState::Open => false,
+            BreakerState::HalfOpen => true,
+        };
+
+        if trip {
+            self.opened_at = Some(now);
+            self.trips += 1;
+        }
+
+        trip
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{BreakerState, CircuitBreaker};
+
+    #[test]
+    fn test_trips_after_consecutive_failures() {
+        let now = Instant::now();
+        let mut breaker = CircuitBreaker::new(3, Duration::from_secs(60));
+
+        assert!(!breaker.record_failure(now));
+        assert!(!breaker.record_failure(now));
+        breaker.record_success();
+        assert!(!breaker.record_failure(now));
+        assert!(!breaker.record_failure(now));
+        assert_eq!(BreakerState::Closed, breaker.state(now));
+
+        assert!(breaker.record_failure(now));
+        assert_eq!(BreakerState::Open, breaker.state(now));
+        assert_eq!(1, breaker.trips());
+        assert_eq!(
+            Some(Duration::from_secs(45)),
+            breaker.cooldown_remaining(now + Duration::from_secs(15))
+        );
+
+        // Requests that were already sent do not trip it again.
+        assert!(!breaker.record_failure(now + Duration::from_secs(1)));
+        assert_eq!(1, breaker.trips());
+    }
+
+    #[test]
+    fn test_half_open_after_cooldown() {
+        let now = Instant::now();
+        let later = now + Duration::from_secs(60);
+        let mut breaker = CircuitBreaker::new(1, Duration::from_secs(60));
+
+        assert!(breaker.record_failure(now));
+        assert_eq!(BreakerState::HalfOpen, breaker.state(later));
+        assert_eq!(None, breaker.cooldown_remaining(later));
+
+        // A failed trial trips it for another cooldown.
+        assert!(breaker.record_failure(later));
+        assert_eq!(BreakerState::Open, breaker.state(later));
+        assert_eq!(2, breaker.trips());
+
+        // A successful one closes it.
+        let after = later + Duration::from_secs(60);
+        assert_eq!(BreakerState::HalfOpen, breaker.state(after));
+        breaker.record_success();
+        assert_eq!(BreakerState::Closed, breaker.state(after));
+    }
+}
@@ -2,14 +2,14 @@ use std::{convert::TryInto, env};
 
 use airtable_api::Airtable;
 use anyhow::{anyhow, bail, Result};
-use async_bb8_diesel::AsyncRunQueryDsl;
+use async_bb8_diesel::{AsyncRunQueryDsl, OptionalExtension};
 use async_trait::async_trait;
 use checkr::Checkr;
-use chrono::Utc;
+use chrono::{Duration, Utc};
 use cloudflare::framework::{
     async_api::Client as Cloudflare, auth::Credentials as CloudflareCredentials, Environment, HttpApiClientConfig,
 };
-use docusign::DocuSign;
+use docusign::{DocuSign, TOKEN_REFRESH_MARGIN_SECONDS};
 use google_calendar::Client as GoogleCalendar;
 use google_dns1::Dns;
 use google_drive::Client as GoogleDrive;
@@ -31,6 +31,8 @@ use octorust::{
     http_cache::FileBasedCache,
 };
 use okta::Client as Okta;
+use okta_minimal_api::OktaClient;
+use pagerduty_minimal_api::PagerDutyClient;
 use quickbooks::QuickBooks;
 use ramp_minimal_api::RampClient as Ramp;
 use schemars::JsonSchema;
@@ -47,16 +49,36 @@ use zoom_api::Client as Zoom;
 use crate::{
     airtable::{AIRTABLE_COMPANIES_TABLE, AIRTABLE_GRID_VIEW},
     api_tokens::{APIToken, NewAPIToken},
-    certs::{GcsBackend, GitHubBackend, SslCertificateStorage},
+    app_config::{
+        CertificateStorageConfig, CertificatesConfig, IdentityConfig, IdentityProviderKind, MailingListConfig,
+        MailingListProviderKind,
+    },
+    certs::{GcsBackend, GitHubBackend, KubernetesBackend, SslCertificateStorage, VaultBackend},
     cloud_dns::CloudDnsClient,
     cloudflare::CloudFlareClient,
     configs::{Building, Buildings},
     core::UpdateAirtableRecord,
     db::Database,
     dns_proxy::DnsProviderProxy,
+    gsuite::{GoogleDataTransfer, GoogleReports},
+    mailerlite::Mailerlite,
+    mailing_list_providers::{MailChimpAudience, MailingListProviderOps},
+    route53::Route53Client,
     schema::{api_tokens, companys},
+    zoom::ZoomMeetings,
 };
 
+/// The scopes requested when acting as another user with the Google service account.
+const GOOGLE_SERVICE_ACCOUNT_SCOPES: &[&str] = &[
+    "https://www.googleapis.com/auth/admin.directory.group",
+    "https://www.googleapis.com/auth/admin.directory.resource.calendar",
+    "https://www.googleapis.com/auth/admin.directory.user",
+    "https://www.googleapis.com/auth/calendar",
+    "https://www.googleapis.com/auth/apps.groups.settings",
+    "https://www.googleapis.com/auth/spreadsheets",
+    "https://www.googleapis.com/auth/drive",
+];
+
 #[db {
     new_struct_name = "Company",
     airtable_base = "cio",
@@ -263,6 +285,19 @@ impl Company {
         Company::get_by_id(db, token.auth_company_id).await
     }
 
+    pub async fn get_from_quickbooks_realm_id(db: &Database, realm_id: &str) -> Result<Self> {
+        let token = api_tokens::dsl::api_tokens
+            .filter(
+                api_tokens::dsl::company_id
+                    .eq(realm_id.to_string())
+                    .and(api_tokens::dsl::product.eq("quickbooks".to_string())),
+            )
+            .first_async::<APIToken>(db.pool())
+            .await?;
+
+        Company::get_by_id(db, token.auth_company_id).await
+    }
+
     pub async fn get_from_mailchimp_list_id(db: &Database, list_id: &str) -> Result<Self> {
         Ok(companys::dsl::companys
             .filter(companys::dsl::mailchimp_list_id.eq(list_id.to_string()))
@@ -329,6 +364,39 @@ impl Company {
         Some(Okta::new(&self.okta_api_key).with_host(self.okta_endpoint()))
     }
 
+    /// Authenticate with Okta when it is the company's identity provider. Companies that use
+    /// Okta must have Okta credentials.
+    pub fn authenticate_okta_identity_provider(&self, config: &IdentityConfig) -> Result<Option<Okta>> {
+        match config.provider(&self.name) {
+            IdentityProviderKind::Google => Ok(None),
+            IdentityProviderKind::Okta => self.authenticate_okta().map(Some).ok_or_else(|| {
+                anyhow!(
+                    "company `{}` uses Okta as its identity provider, but does not have Okta credentials",
+                    self.name
+                )
+            }),
+        }
+    }
+
+    /// Authenticate with Okta for managing application assignments, which the `okta` crate
+    /// does not support.
+    pub fn authenticate_okta_apps(&self) -> Option<OktaClient> {
+        if self.okta_api_key.is_empty() || self.okta_domain.is_empty() {
+            // Return early.
+            return None;
+        }
+        Some(OktaClient::new(self.okta_endpoint(), &self.okta_api_key))
+    }
+
+    /// Authenticate with PagerDuty. Changes are made on behalf of the user in
+    /// `PAGERDUTY_FROM_EMAIL`, which defaults to the company's admin user.
+    pub fn authenticate_pagerduty(&self) -> Option<PagerDutyClient> {
+        let key = env::var("PAGERDUTY_API_KEY").ok()?;
+        let from = env::var("PAGERDUTY_FROM_EMAIL").unwrap_or_else(|_| self.gsuite_subject.to_string());
+
+        Some(PagerDutyClient::new(key, from))
+    }
+
     fn okta_endpoint(&self) -> String {
         format!(
             "https://{}.okta.com",
@@ -435,10 +503,33 @@ impl Company {
         Ok(MailChimp::new(AuthMode::new_basic_auth(key)?))
     }
 
+    /// Authenticate with the service that holds this company's mailing list.
+    pub async fn authenticate_mailing_list_provider(
+        &self,
+        config: &MailingListConfig,
+    ) -> Result<Box<dyn MailingListProviderOps + Send + Sync>> {
+        Ok(match config.provider(&self.name) {
+            MailingListProviderKind::MailChimp => Box::new(MailChimpAudience::new(
+                self.authenticate_mailchimp().await?,
+                self.mailchimp_list_id.to_string(),
+                config.audience(&self.mailchimp_list_id).cloned(),
+            )),
+            MailingListProviderKind::Mailerlite => Box::new(Mailerlite::new()?),
+        })
+    }
+
     /// Authenticate with Slack.
     pub async fn authenticate_slack(&self, db: &Database) -> Result<Slack> {
+        match self.authenticate_slack_if_installed(db).await? {
+            Some(slack) => Ok(slack),
+            None => bail!("no token"),
+        }
+    }
+
+    /// Authenticate with Slack, or `None` when the company has not installed our Slack app.
+    pub async fn authenticate_slack_if_installed(&self, db: &Database) -> Result<Option<Slack>> {
         // Get the bot token and user token from the database.
-        if let Ok(bot_token) = api_tokens::dsl::api_tokens
+        let bot_token = api_tokens::dsl::api_tokens
             .filter(
                 api_tokens::dsl::cio_company_id
                     .eq(self.id)
@@ -447,31 +538,28 @@ impl Company {
             )
             .first_async::<APIToken>(db.pool())
             .await
-        {
-            if let Ok(user_token) = api_tokens::dsl::api_tokens
-                .filter(
-                    api_tokens::dsl::cio_company_id
-                        .eq(self.id)
-                        .and(api_tokens::dsl::product.eq("slack".to_string()))
-                        .and(api_tokens::dsl::token_type.eq("user".to_string())),
-                )
-                .first_async::<APIToken>(db.pool())
-                .await
-            {
-                // Initialize the Slack client.
-                let slack = Slack::new_from_env(
-                    bot_token.company_id.to_string(),
-                    bot_token.access_token,
-                    user_token.access_token,
-                );
-                // Slack does not give you refresh tokens.
-                // So we don't need to do any song and dance to refresh.
-
-                return Ok(slack);
-            }
-        }
-
-        bail!("no token");
+            .optional()?;
+        let user_token = api_tokens::dsl::api_tokens
+            .filter(
+                api_tokens::dsl::cio_company_id
+                    .eq(self.id)
+                    .and(api_tokens::dsl::product.eq("slack".to_string()))
+                    .and(api_tokens::dsl::token_type.eq("user".to_string())),
+            )
+            .first_async::<APIToken>(db.pool())
+            .await
+            .optional()?;
+
+        // Slack does not give you refresh tokens.
+        // So we don't need to do any song and dance to refresh.
+        Ok(match (bot_token, user_token) {
+            (Some(bot_token), Some(user_token)) => Some(Slack::new_from_env(
+                bot_token.company_id.to_string(),
+                bot_token.access_token,
+                user_token.access_token,
+            )),
+            _ => None,
+        })
     }
 
     /// Authenticate with Ramp.
@@ -485,6 +573,9 @@ impl Company {
                 "departments:read".to_string(),
                 "transactions:read".to_string(),
                 "reimbursements:read".to_string(),
+                "bills:read".to_string(),
+                "cards:read".to_string(),
+                "cards:write".to_string(),
             ],
         ))
     }
@@ -523,6 +614,17 @@ impl Company {
         bail!("no token");
     }
 
+    /// Authenticate with Zoom for managing meetings and webinars.
+    pub async fn authenticate_zoom_meetings(&self, db: &Database) -> Result<ZoomMeetings> {
+        // This refreshes the token in the database if it has expired.
+        self.authenticate_zoom(db).await?;
+
+        match APIToken::get_from_db(db, self.id, "zoom".to_string()).await {
+            Some(t) => Ok(ZoomMeetings::new(&t.access_token)),
+            None => bail!("no token"),
+        }
+    }
+
     /// Authenticate with Zoho.
     pub async fn authenticate_zoho(&self, db: &Database) -> Result<Zoho> {
         // Get the APIToken from the database.
@@ -557,6 +659,10 @@ impl Company {
     }
 
     /// Authenticate with DocuSign.
+    ///
+    /// Tokens are stored per DocuSign account in the APIToken row and only refreshed
+    /// when they are about to expire. If `DOCUSIGN_JWT_USER_ID` and `DOCUSIGN_JWT_PRIVATE_KEY`
+    /// are set we use the JWT grant instead of the refresh token.
     pub async fn authenticate_docusign(&self, db: &Database) -> Result<DocuSign> {
         // Get the APIToken from the database.
         if let Some(mut t) = APIToken::get_from_db(db, self.id, "docusign".to_string()).await {
@@ -568,26 +674,38 @@ impl Company {
                 t.endpoint.to_string(),
             );
 
-            if t.is_expired() {
-                // Only refresh the token if it is expired.
-                let nt = ds.refresh_access_token().await?;
-                if !nt.access_token.is_empty() {
-                    t.access_token = nt.access_token.to_string();
-                }
-                if nt.expires_in > 0 {
-                    t.expires_in = nt.expires_in as i32;
-                }
-                if !nt.refresh_token.is_empty() {
-                    t.refresh_token = nt.refresh_token.to_string();
-                }
-                if nt.x_refresh_token_expires_in > 0 {
-                    t.refresh_token_expires_in = nt.x_refresh_token_expires_in as i32;
+            // Another call in this process may have already refreshed the token.
+            if ds.use_cached_token() {
+                return Ok(ds);
+            }
+
+            if !t.expires_within(Duration::seconds(TOKEN_REFRESH_MARGIN_SECONDS)) {
+                return Ok(ds);
+            }
+
+            let nt = match (env::var("DOCUSIGN_JWT_USER_ID"), env::var("DOCUSIGN_JWT_PRIVATE_KEY")) {
+                (Ok(user_id), Ok(private_key)) => {
+                    ds.request_jwt_user_token(&user_id, &private_key.replace("\\n", "\n"))
+                        .await?
                 }
-                t.last_updated_at = Utc::now();
-                t.expand();
-                // Update the token in the database.
-                t.update(db).await?;
+                _ => ds.refresh_access_token().await?,
+            };
+            if !nt.access_token.is_empty() {
+                t.access_token = nt.access_token.to_string();
             }
+            if nt.expires_in > 0 {
+                t.expires_in = nt.expires_in as i32;
+            }
+            if !nt.refresh_token.is_empty() {
+                t.refresh_token = nt.refresh_token.to_string();
+            }
+            if nt.x_refresh_token_expires_in > 0 {
+                t.refresh_token_expires_in = nt.x_refresh_token_expires_in as i32;
+            }
+            t.last_updated_at = Utc::now();
+            t.expand();
+            // Update the token in the database.
+            t.update(db).await?;
 
             return Ok(ds);
         }
@@ -710,35 +828,43 @@ impl Company {
     /// Authenticate with QuickBooks.
     pub async fn authenticate_quickbooks(&self, db: &Database) -> Result<QuickBooks> {
         // Get the APIToken from the database.
-        if let Some(mut t) = APIToken::get_from_db(db, self.id, "quickbooks".to_string()).await {
+        if let Some(t) = APIToken::get_from_db(db, self.id, "quickbooks".to_string()).await {
+            // QuickBooks access tokens last an hour, refresh them before they expire.
+            let t = t
+                .refresh_with_lock(db, Duration::minutes(10), |mut t| async move {
+                    let mut qb = QuickBooks::new_from_env(
+                        t.company_id.to_string(),
+                        t.access_token.to_string(),
+                        t.refresh_token.to_string(),
+                    );
+
+                    let nt = qb.refresh_access_token().await?;
+                    if !nt.access_token.is_empty() {
+                        t.access_token = nt.access_token.to_string();
+                    }
+                    if nt.expires_in > 0 {
+                        t.expires_in = nt.expires_in as i32;
+                    }
+                    if !nt.refresh_token.is_empty() {
+                        t.refresh_token = nt.refresh_token.to_string();
+                    }
+                    if nt.x_refresh_token_expires_in > 0 {
+                        t.refresh_token_expires_in = nt.x_refresh_token_expires_in as i32;
+                    }
+                    t.last_updated_at = Utc::now();
+                    t.expand();
+
+                    Ok(t)
+                })
+                .await?;
+
             // Initialize the QuickBooks client.
-            let mut qb = QuickBooks::new_from_env(
+            let qb = QuickBooks::new_from_env(
                 t.company_id.to_string(),
                 t.access_token.to_string(),
                 t.refresh_token.to_string(),
             );
 
-            if t.is_expired() {
-                // Only refresh the token if it is expired.
-                let nt = qb.refresh_access_token().await?;
-                if !nt.access_token.is_empty() {
-                    t.access_token = nt.access_token.to_string();
-                }
-                if nt.expires_in > 0 {
-                    t.expires_in = nt.expires_in as i32;
-                }
-                if !nt.refresh_token.is_empty() {
-                    t.refresh_token = nt.refresh_token.to_string();
-                }
-                if nt.x_refresh_token_expires_in > 0 {
-                    t.refresh_token_expires_in = nt.x_refresh_token_expires_in as i32;
-                }
-                t.last_updated_at = Utc::now();
-                t.expand();
-                // Update the token in the database.
-                t.update(db).await?;
-            }
-
             return Ok(qb);
         }
 
@@ -819,7 +945,9 @@ impl Company {
     /// This allows mocking as another user.
     /// TODO: figure out why we can't mock with the standard token.
     pub async fn authenticate_google_calendar_with_service_account(&self, as_user: &str) -> Result<GoogleCalendar> {
-        let token = self.get_google_service_account_token(as_user).await?;
+        let token = self
+            .get_google_service_account_token(as_user, GOOGLE_SERVICE_ACCOUNT_SCOPES)
+            .await?;
 
         // Initialize the client.
         Ok(GoogleCalendar::new_from_env(&token, "").await)
@@ -864,13 +992,35 @@ impl Company {
     /// This allows mocking as another user.
     /// TODO: figure out why we can't mock with the standard token.
     pub async fn authenticate_google_drive_with_service_account(&self, as_user: &str) -> Result<GoogleDrive> {
-        let token = self.get_google_service_account_token(as_user).await?;
+        let token = self
+            .get_google_service_account_token(as_user, GOOGLE_SERVICE_ACCOUNT_SCOPES)
+            .await?;
 
         // Initialize the client.
         Ok(GoogleDrive::new_from_env(&token, "").await)
     }
 
-    async fn get_google_service_account_token(&self, as_user: &str) -> Result<String> {
+    /// Authenticate the Google Data Transfer API with the service account, acting as the
+    /// GSuite subject.
+    pub async fn authenticate_google_data_transfer(&self) -> Result<GoogleDataTransfer> {
+        let token = self
+            .get_google_service_account_token("", &["https://www.googleapis.com/auth/admin.datatransfer"])
+            .await?;
+
+        Ok(GoogleDataTransfer::new(&token, &self.gsuite_account_id))
+    }
+
+    /// Authenticate the Google Reports API with the service account, acting as the GSuite
+    /// subject.
+    pub async fn authenticate_google_reports(&self) -> Result<GoogleReports> {
+        let token = self
+            .get_google_service_account_token("", &["https://www.googleapis.com/auth/admin.reports.audit.readonly"])
+            .await?;
+
+        Ok(GoogleReports::new(&token))
+    }
+
+    pub(crate) async fn get_google_service_account_token(&self, as_user: &str, scopes: &[&str]) -> Result<String> {
         if self.google_service_account.is_empty() {
             bail!("no service account");
         }
@@ -887,17 +1037,7 @@ impl Company {
             .build()
             .await?;
 
-        let token = auth
-            .token(&[
-                "https://www.googleapis.com/auth/admin.directory.group",
-                "https://www.googleapis.com/auth/admin.directory.resource.calendar",
-                "https://www.googleapis.com/auth/admin.directory.user",
-                "https://www.googleapis.com/auth/calendar",
-                "https://www.googleapis.com/auth/apps.groups.settings",
-                "https://www.googleapis.com/auth/spreadsheets",
-                "https://www.googleapis.com/auth/drive",
-            ])
-            .await?;
+        let token = auth.token(scopes).await?;
 
         let token_string = token.as_str().to_string();
         if token_string.is_empty() {
@@ -1006,6 +1146,8 @@ impl Company {
         let client = reqwest_middleware::ClientBuilder::new(http)
             // Trace HTTP requests. See the tracing crate to make use of these traces.
             .with(reqwest_tracing::TracingMiddleware)
+            // Tell GitHub which webhook or scheduled run the requests were made for.
+            .with(crate::correlation::CorrelationIdMiddleware)
             // Retry failed requests.
             .with(reqwest_retry::RetryTransientMiddleware::new_with_policy(retry_policy))
             .build();
@@ -1050,11 +1192,24 @@ impl Company {
         ))
     }
 
+    /// Authenticate with Route53. Credentials are read from the standard AWS environment
+    /// variables, and Route53 is only used when they are present.
+    pub async fn authenticate_route53(&self) -> Option<Route53Client> {
+        if std::env::var("AWS_ACCESS_KEY_ID").is_err() {
+            return None;
+        }
+
+        let config = aws_config::load_from_env().await;
+        Some(Route53Client::new(aws_sdk_route53::Client::new(&config)))
+    }
+
     pub async fn authenticate_dns_providers(&self) -> Result<DnsProviderProxy> {
-        Ok(DnsProviderProxy::new(
-            self.authenticate_cloudflare()?,
-            self.authenticate_cloud_dns().await?,
-        ))
+        let proxy = DnsProviderProxy::new(self.authenticate_cloudflare()?, self.authenticate_cloud_dns().await?);
+
+        Ok(match self.authenticate_route53().await {
+            Some(route53) => proxy.with_route53(route53),
+            None => proxy,
+        })
     }
 
     pub async fn cert_storage(&self) -> Result<Vec<Box<dyn SslCertificateStorage>>> {
@@ -1082,6 +1237,34 @@ impl Company {
         ])
     }
 
+    /// The default certificate storage along with any additional backends that are configured.
+    pub async fn cert_storage_with_config(
+        &self,
+        config: &CertificatesConfig,
+    ) -> Result<Vec<Box<dyn SslCertificateStorage>>> {
+        let mut storage = self.cert_storage().await?;
+
+        for backend in &config.storage {
+            match backend {
+                CertificateStorageConfig::Vault {
+                    address,
+                    mount,
+                    path_prefix,
+                } => storage.push(Box::new(VaultBackend::new(
+                    address.to_string(),
+                    std::env::var("VAULT_TOKEN")?,
+                    mount.to_string(),
+                    path_prefix.to_string(),
+                ))),
+                CertificateStorageConfig::Kubernetes { namespace } => {
+                    storage.push(Box::new(KubernetesBackend::in_cluster(namespace.to_string())?))
+                }
+            }
+        }
+
+        Ok(storage)
+    }
+
     pub fn certs_gcs(&self) -> String {
         std::env::var("CERTS_GCS").unwrap()
     }
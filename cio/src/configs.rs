@@ -8,7 +8,7 @@ use std::{
 use anyhow::{bail, Result};
 use async_bb8_diesel::AsyncRunQueryDsl;
 use async_trait::async_trait;
-use chrono::naive::NaiveDate;
+use chrono::naive::{NaiveDate, NaiveDateTime};
 use diesel::{
     deserialize::{self, FromSql},
     pg::{Pg, PgValue},
@@ -24,7 +24,6 @@ use gusto_api::Client as Gusto;
 use log::{info, warn};
 use macros::db;
 use schemars::JsonSchema;
-use sendgrid_api::{traits::MailOps, Client as SendGrid};
 use serde::{Deserialize, Serialize};
 use zoom_api::Client as Zoom;
 
@@ -33,14 +32,17 @@ use crate::{
         AIRTABLE_BUILDINGS_TABLE, AIRTABLE_EMPLOYEES_TABLE, AIRTABLE_GROUPS_TABLE, AIRTABLE_LINKS_TABLE,
         AIRTABLE_RESOURCES_TABLE,
     },
-    app_config::{AppConfig, OnboardingConfig},
+    app_config::{AppConfig, IdentityProviderKind, OnboardingConfig},
     applicants::Applicant,
     certs::{Certificate, Certificates, GitHubBackend, NewCertificate},
     companies::Company,
     core::UpdateAirtableRecord,
     db::Database,
+    email::Mailer,
     features::Features,
     gsuite::{update_gsuite_building, update_gsuite_calendar_resource},
+    gusto::GustoEmployees,
+    offboarding::OffboardingActionStatus,
     providers::{ProviderReadOps, ProviderWriteOps},
     schema::{applicants, buildings, groups, links, resources, users},
     shipments::NewOutboundShipment,
@@ -70,6 +72,12 @@ pub struct Config {
     #[serde(default, alias = "github-outside-collaborators")]
     pub github_outside_collaborators: BTreeMap<String, GitHubOutsideCollaboratorsConfig>,
 
+    #[serde(default, alias = "github-repo-permissions")]
+    pub github_repo_permissions: BTreeMap<String, GitHubRepoPermissionsConfig>,
+
+    #[serde(default, alias = "github-repo-settings")]
+    pub github_repo_settings: BTreeMap<String, GitHubRepoSettingsConfig>,
+
     #[serde(default)]
     pub huddles: BTreeMap<String, HuddleConfig>,
 
@@ -195,6 +203,9 @@ pub struct UserConfig {
 
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub department: String,
+    /// The job title of the user, Gusto is the source of truth for it once they are in Gusto.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub title: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub manager: String,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -314,6 +325,23 @@ pub struct UserConfig {
     #[serde(default)]
     pub gusto_pull_permission: bool,
 
+    /// The following fields do not exist in the config files but are populated
+    /// when the user is enrolled in Checkr continuous checks.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub checkr_candidate_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub continuous_check_status: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub background_check_status: String,
+
+    /// The last day of work of a user that has been terminated (automatically populated by
+    /// Gusto).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub termination_date: Option<NaiveDate>,
+    /// The profile photo of the user in Google Workspace (automatically populated).
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub photo_url: String,
+
     /// The CIO company ID.
     #[serde(default)]
     pub cio_company_id: i32,
@@ -385,8 +413,7 @@ impl UserConfig {
         ramp_users: &HashMap<String, ramp_minimal_api::User>,
         zoom_users: &HashMap<String, zoom_api::types::UsersResponse>,
         zoom_users_pending: &HashMap<String, zoom_api::types::UsersResponse>,
-        gusto_users: &HashMap<String, gusto_api::types::Employee>,
-        gusto_users_by_id: &HashMap<String, gusto_api::types::Employee>,
+        gusto_employees: &GustoEmployees,
     ) -> Result<()> {
         // Get everything we need to authenticate with GSuite.
         // Initialize the GSuite client.
@@ -398,8 +425,8 @@ impl UserConfig {
         // Initialize the Gusto client.
         let gusto_auth = company.authenticate_gusto(db).await;
 
-        // Initialize the Okta client.
-        let okta_auth = company.authenticate_okta();
+        // Initialize the Okta client, only if Okta is the company's identity provider.
+        let okta_auth = company.authenticate_okta_identity_provider(&config.identity)?;
 
         // Initialize the Ramp client.
         let ramp = company.authenticate_ramp()?;
@@ -416,11 +443,19 @@ impl UserConfig {
         // Update or create the user in the database.
         if let Some(e) = existing.clone() {
             self.google_anniversary_event_id = e.google_anniversary_event_id;
+            self.checkr_candidate_id = e.checkr_candidate_id;
+            self.continuous_check_status = e.continuous_check_status;
+            self.background_check_status = e.background_check_status;
+            self.termination_date = e.termination_date;
+            self.photo_url = e.photo_url;
         }
 
         // See if we have a gsuite user for the user.
         if let Some(gsuite_user) = gsuite_users_map.get(&self.email) {
             self.google_id = gsuite_user.id.to_string();
+            if !gsuite_user.thumbnail_photo_url.is_empty() {
+                self.photo_url = gsuite_user.thumbnail_photo_url.to_string();
+            }
         }
 
         // See if we have a okta user for the user.
@@ -470,10 +505,10 @@ impl UserConfig {
 
         // See if we have a gusto user for the user.
         // The user's email can either be their personal email or their oxide email.
-        if let Some(gusto_user) = gusto_users.get(&self.email) {
-            self.update_from_gusto(gusto_user);
-        } else if let Some(gusto_user) = gusto_users.get(&self.recovery_email) {
-            self.update_from_gusto(gusto_user);
+        if let Some(gusto_user) = gusto_employees.get_by_email(&self.email) {
+            self.update_from_gusto(gusto_user, gusto_employees);
+        } else if let Some(gusto_user) = gusto_employees.get_by_email(&self.recovery_email) {
+            self.update_from_gusto(gusto_user, gusto_employees);
         } else {
             // For a new hire we may have an airtable entry, but not a Gusto record. Grab their
             // date of birth, start date, and address from Airtable.
@@ -495,8 +530,8 @@ impl UserConfig {
                 // It is possible (though unlikely) that the two of these diverge and result in
                 // returning different accounts)
                 if !e.gusto_id.is_empty() {
-                    if let Some(gusto_user) = gusto_users_by_id.get(&e.gusto_id) {
-                        self.update_from_gusto(gusto_user);
+                    if let Some(gusto_user) = gusto_employees.get_by_id(&e.gusto_id) {
+                        self.update_from_gusto(gusto_user, gusto_employees);
                     }
                 } else if let Ok((ref gusto, ref gusto_company_id)) = gusto_auth {
                     self.populate_home_address().await?;
@@ -690,8 +725,23 @@ impl UserConfig {
         Ok(())
     }
 
-    fn update_from_gusto(&mut self, gusto_user: &gusto_api::types::Employee) {
-        self.gusto_id = gusto_user.id.to_string();
+    fn update_from_gusto(&mut self, gusto_user: &gusto_api::types::Employee, gusto_employees: &GustoEmployees) {
+        let record = gusto_employees.record(gusto_user);
+
+        self.gusto_id = record.gusto_id.to_string();
+
+        // Gusto is the source of truth for departments, managers and terminations, the
+        // configs are only used for people that have not been entered into Gusto yet.
+        if !record.department.is_empty() {
+            self.department = record.department.to_string();
+        }
+        if !record.title.is_empty() {
+            self.title = record.title.to_string();
+        }
+        if !record.manager.is_empty() {
+            self.manager = record.manager.to_string();
+        }
+        self.termination_date = record.termination_date;
 
         if gusto_user.jobs.is_empty() {
             // Return early.
@@ -729,7 +779,7 @@ impl UserConfig {
         }
 
         // We always fetch the employee's start date from Gusto
-        if let Some(start_date) = gusto_user.jobs[0].hire_date {
+        if let Some(start_date) = record.start_date {
             self.start_date = start_date;
         }
     }
@@ -990,15 +1040,11 @@ impl User {
     pub async fn send_email_new_consultant(&self, db: &Database) -> Result<()> {
         let company = self.company(db).await?;
 
-        // Initialize the SendGrid client.
-        let sendgrid = SendGrid::new_from_env();
-
         // Get the user's aliases if they have one.
         let aliases = self.aliases.join(", ");
 
         // Send the message.
-        sendgrid
-            .mail_send()
+        Mailer::new(db, &company)
             .send_plain_text(
                 &format!("Your New Email Account: {}", self.email),
                 &format!(
@@ -1047,13 +1093,9 @@ xoxo,
     ) -> Result<()> {
         let company = self.company(db).await?;
 
-        // Initialize the SendGrid client.
-        let sendgrid = SendGrid::new_from_env();
-
         let letter = config.create_welcome_letter(&company, self, password);
 
-        sendgrid
-            .mail_send()
+        Mailer::new(db, &company)
             .send_plain_text(
                 &letter.subject,
                 &letter.body,
@@ -1070,9 +1112,6 @@ xoxo,
     /// Send an email to the new user about their account.
     pub async fn send_email_new_user(&self, db: &Database) -> Result<()> {
         let company = self.company(db).await?;
-        // Initialize the SendGrid client.
-        let sendgrid = SendGrid::new_from_env();
-
         // Get the user's aliases if they have one.
         let aliases = self.aliases.join(", ");
 
@@ -1098,8 +1137,7 @@ let jess@{} know what your GitHub handle is.",
         }
 
         // Send the message.
-        sendgrid
-            .mail_send()
+        Mailer::new(db, &company)
             .send_plain_text(
                 &format!("Your New Email Account: {}", self.email),
                 &format!(
@@ -1703,6 +1741,67 @@ pub struct GitHubOutsideCollaboratorsConfig {
     pub perm: String,
 }
 
+/// The data type for the permissions GitHub teams have on repositories. Repos listed in a
+/// group's `repos` get push access, this grants other levels of access.
+#[derive(Debug, Default, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct GitHubRepoPermissionsConfig {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    pub teams: Vec<String>,
+    pub repos: Vec<String>,
+    /// One of `pull`, `triage`, `push`, `maintain` or `admin`.
+    pub perm: String,
+}
+
+/// The data type for the settings of the repos in the GitHub org. Settings that are not given
+/// are left however they are set in GitHub.
+#[derive(Debug, Default, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct GitHubRepoSettingsConfig {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    /// Globs of the names of the repos the settings are for, ie. `*` for every repo.
+    pub repos: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow_merge_commit: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow_squash_merge: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow_rebase_merge: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delete_branch_on_merge: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_scanning: Option<bool>,
+    /// The protection of the default branch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch_protection: Option<GitHubBranchProtectionConfig>,
+    /// Labels every repo should have. Other labels are left alone.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<GitHubLabelConfig>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct GitHubBranchProtectionConfig {
+    /// The status checks that have to pass before a pull request can be merged.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_checks: Vec<String>,
+    /// How many approving reviews a pull request needs. Reviews are not required when this is 0.
+    #[serde(default)]
+    pub required_approving_reviews: i64,
+    #[serde(default)]
+    pub enforce_admins: bool,
+    #[serde(default)]
+    pub allow_force_pushes: bool,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct GitHubLabelConfig {
+    pub name: String,
+    /// The hex code of the color, without the `#`.
+    pub color: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+}
+
 /// The data type for a huddle meeting that syncs with Airtable and notes in GitHub.
 #[derive(Debug, Default, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
 pub struct HuddleConfig {
@@ -1723,6 +1822,10 @@ pub struct HuddleConfig {
     pub time_to_cancel: i32,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub calendar_owner: String,
+    /// When this is set the recurring event for the huddle is created and kept up to date
+    /// from the config. Otherwise the event is managed by hand.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<HuddleSchedule>,
 }
 
 impl HuddleConfig {
@@ -1731,6 +1834,45 @@ impl HuddleConfig {
         format!("{}@{}", self.calendar_owner, company.gsuite_domain)
     }
 }
+
+/// When a huddle meets.
+#[derive(Debug, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct HuddleSchedule {
+    /// The start of the first meeting, in the huddle's time zone.
+    pub start: NaiveDateTime,
+    #[serde(default = "default_huddle_duration_minutes")]
+    pub duration_minutes: i64,
+    #[serde(default = "default_huddle_time_zone")]
+    pub time_zone: String,
+    /// An RFC 5545 recurrence rule, for example `RRULE:FREQ=WEEKLY;BYDAY=TH`.
+    pub recurrence: String,
+    /// When this is set the huddle meets on Zoom, and the join link is added to the event.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zoom: Option<HuddleZoomConfig>,
+}
+
+/// The Zoom meeting for a huddle. One recurring meeting is used for every occurrence of the
+/// huddle.
+#[derive(Debug, Default, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct HuddleZoomConfig {
+    /// The username of the person that hosts the meeting.
+    pub host: String,
+    /// The usernames of the people that can start the meeting without the host.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternative_hosts: Vec<String>,
+    /// Hold the huddle as a webinar rather than a meeting.
+    #[serde(default)]
+    pub webinar: bool,
+}
+
+fn default_huddle_duration_minutes() -> i64 {
+    60
+}
+
+fn default_huddle_time_zone() -> String {
+    "America/Los_Angeles".to_string()
+}
+
 /// Get the configs from the GitHub repository and parse them.
 pub async fn get_configs_from_repo(github: &octorust::Client, company: &Company) -> Result<Config> {
     let owner = &company.github_org;
@@ -1918,24 +2060,23 @@ pub async fn sync_users(
     let gsuite = company.authenticate_google_admin(db).await?;
     let gcal = company.authenticate_google_calendar(db).await?;
 
-    // We don't need a base id here since we are only using the enterprise api features.
-    let airtable_auth = company.authenticate_airtable("");
-
-    // Initialize the Gusto client.
-    let mut gusto_users: HashMap<String, gusto_api::types::Employee> = HashMap::new();
-    let mut gusto_users_by_id: HashMap<String, gusto_api::types::Employee> = HashMap::new();
-    let gusto_auth = company.authenticate_gusto(db).await;
-    if let Ok((ref gusto, ref gusto_company_id)) = gusto_auth {
-        let gu = gusto.employees().get_all_company(gusto_company_id, false, &[]).await?;
-        for g in gu {
-            gusto_users.insert(g.email.to_string(), g.clone());
-            gusto_users_by_id.insert(g.id.to_string(), g);
+    // Get the employees from Gusto. Managers are matched with their username by either of
+    // their emails.
+    let mut usernames: HashMap<String, String> = HashMap::new();
+    for user in users.values() {
+        usernames.insert(
+            format!("{}@{}", user.username, company.gsuite_domain),
+            user.username.to_string(),
+        );
+        if !user.recovery_email.is_empty() {
+            usernames.insert(user.recovery_email.to_lowercase(), user.username.to_string());
         }
     }
+    let gusto_employees = GustoEmployees::fetch(db, company, usernames).await?;
 
-    // Initialize the Okta client.
+    // Initialize the Okta client, only if Okta is the company's identity provider.
     let mut okta_users: HashMap<String, okta::types::User> = HashMap::new();
-    let okta_auth = company.authenticate_okta();
+    let okta_auth = company.authenticate_okta_identity_provider(&config.identity)?;
     if let Some(ref okta) = okta_auth {
         let gu = okta.list_provider_users(company).await?;
         for g in gu {
@@ -2048,7 +2189,7 @@ pub async fn sync_users(
             .skip(skip)
             .take(take)
             .map(|(_, mut user)| {
-                tokio::spawn(crate::enclose! { (db, company, config, github, gsuite_users_map, okta_users, ramp_users, zoom_users, zoom_users_pending, gusto_users, gusto_users_by_id) async move {
+                crate::correlation::spawn(crate::enclose! { (db, company, config, github, gsuite_users_map, okta_users, ramp_users, zoom_users, zoom_users_pending, gusto_employees) async move {
                 user.sync(
                     &db,
                     &company,
@@ -2059,8 +2200,7 @@ pub async fn sync_users(
                     &ramp_users,
                     &zoom_users,
                     &zoom_users_pending,
-                    &gusto_users,
-                    &gusto_users_by_id,
+                    &gusto_employees,
                 )
                 .await
                 }})
@@ -2156,77 +2296,23 @@ pub async fn sync_users(
                 }
             }
 
-            // Supend the user from okta.
-            if let Some(ref okta) = okta_auth {
-                match okta.delete_user(db, company, &user).await {
-                    Ok(_) => {
-                        info!("Deleted user {} from okta", username);
-                    }
-                    Err(err) => {
-                        warn!("Failed to delete user {} from okta. err: {:?}", username, err);
-
-                        has_failures = true;
-                    }
-                }
-            }
-
-            if company.okta_domain.is_empty() {
-                // Delete the user from GSuite and other apps.
-                // ONLY DO THIS IF THE COMPANY DOES NOT USE OKTA.
-                // Suspend the user from GSuite so we can transfer their data.
-                match gsuite.delete_user(db, company, &user).await {
-                    Ok(_) => {
-                        info!("Deactivated user {} in GSuite", username);
-                    }
-                    Err(err) => {
-                        warn!("Failed to deactivate user {} in GSuite. err: {:?}", username, err);
+            // Take away their access everywhere, the same way as for people whose last day has
+            // passed. This records what was done in the offboarding report.
+            match crate::offboarding::offboard_user(db, company, config, &user).await {
+                Ok(actions) => {
+                    if actions
+                        .iter()
+                        .any(|action| action.status == OffboardingActionStatus::Failed.to_string())
+                    {
+                        warn!("Failed to offboard user {} from every service", username);
 
                         has_failures = true;
                     }
                 }
-            }
-
-            // Remove the user from the github org.
-            match github.delete_user(db, company, &user).await {
-                Ok(_) => {
-                    info!("Deleted user {} from GitHub", username);
-                }
                 Err(err) => {
-                    warn!("Failed to delete user {} from GitHub. err: {:?}", username, err);
-                }
-            }
-
-            // TODO: Deactivate the user from Ramp.
-            // We only want to lock the cards from more purchases. Removing GSuite/Okta
-            // will disallow them from logging in. And we want their purchase history so
-            // we don't want to delete them.
+                    warn!("Failed to offboard user {}. err: {:?}", username, err);
 
-            // TODO: Delete the user from Slack.
-            // Removing SSO (GSuite/Okta) will disallow them from logging in.
-
-            // Delete the user from Zoom.
-            if let Ok(ref zoom) = zoom_auth {
-                match zoom.delete_user(db, company, &user).await {
-                    Ok(_) => {
-                        info!("Deleted user {} from Zoom", username);
-                    }
-                    Err(err) => {
-                        warn!("Failed to delete user {} from Zoom. err: {:?}", username, err);
-
-                        has_failures = true;
-                    }
-                }
-            }
-
-            // Delete the user from Airtable.
-            // Okta should take care of this if we are using Okta.
-            // But let's do it anyway.
-            match airtable_auth.delete_user(db, company, &user).await {
-                Ok(_) => {
-                    info!("Deleted user {} from Airtable", username);
-                }
-                Err(err) => {
-                    warn!("Failed to delete user {} from Airtable. err: {:?}", username, err);
+                    has_failures = true;
                 }
             }
 
@@ -2511,14 +2597,19 @@ pub async fn sync_resources(
 }
 
 /// Sync our groups with our database and then update Airtable from the database.
-pub async fn sync_groups(db: &Database, groups: BTreeMap<String, GroupConfig>, company: &Company) -> Result<()> {
+pub async fn sync_groups(
+    db: &Database,
+    groups: BTreeMap<String, GroupConfig>,
+    company: &Company,
+    config: &AppConfig,
+) -> Result<()> {
     // Get everything we need to authenticate with GSuite.
     // Initialize the GSuite client.
     let gsuite = company.authenticate_google_admin(db).await?;
 
     let github = company.authenticate_github()?;
 
-    let okta_auth = company.authenticate_okta();
+    let okta_auth = company.authenticate_okta_identity_provider(&config.identity)?;
 
     // Get all the groups.
     let db_groups = Groups::get_from_db(db, company.id).await?;
@@ -2663,7 +2754,6 @@ pub async fn sync_certificates(
     }
 
     let cert_reader = GitHubBackend::new(github.clone(), company.github_org.clone(), company.certs_repo());
-    let cert_storage = company.cert_storage().await?;
 
     // Sync certificates.
     for (_, mut certificate) in certificates {
@@ -2671,29 +2761,11 @@ pub async fn sync_certificates(
 
         certificate.load_from_reader(&cert_reader).await?;
 
-        // If the cert is going to expire in less than 12 days, renew it.
-        // Otherwise, return early.
-        if certificate.valid_days_left > 12 {
-            info!(
-                "cert {} is valid for {} more days, skipping",
-                certificate.domain, certificate.valid_days_left
-            );
-        } else {
-            // Renew
-            if Features::is_enabled("RENEW_CERTS") {
-                log::info!("Renewing certificate for {}", certificate.domain);
-
-                if let Err(err) = certificate.renew(db, company, &cert_storage).await {
-                    log::error!(
-                        "Failed to renew certificate for {} due to {:?}",
-                        certificate.domain,
-                        err
-                    );
-                }
-            } else {
-                log::info!("Cert renewal is disabled. Skipping renewal for {}", certificate.domain);
-            }
-        }
+        // Expiring certificates are renewed by the `renew-certificates` job.
+        info!(
+            "cert {} is valid for {} more days",
+            certificate.domain, certificate.valid_days_left
+        );
 
         // Update the database and Airtable.
         certificate.upsert(db).await?;
@@ -2733,11 +2805,19 @@ pub async fn refresh_db_configs_and_airtable(db: &Database, company: &Company, c
 
     // Sync groups.
     // Syncing groups must happen before we sync the users.
-    sync_groups(db, configs.groups, company).await?;
+    sync_groups(db, configs.groups, company, config).await?;
 
     // Sync users.
     sync_users(db, &github, configs.users, company, config).await?;
 
+    // Assign Okta applications.
+    // This must happen after we sync the users, so that new users have an Okta id.
+    if config.identity.provider(&company.name) == IdentityProviderKind::Okta {
+        if let Err(e) = crate::okta::sync_okta_app_assignments(db, company, &config.okta).await {
+            warn!("error syncing okta app assignments: {}", e);
+        }
+    }
+
     // Sync links.
     let (links, certs, ghout, ann) = tokio::join!(
         sync_links(db, configs.links, configs.huddles, company),
@@ -2918,6 +2998,7 @@ pub mod tests {
             github: "random_github_user".to_string(),
             twitter: String::default(),
             department: String::default(),
+            title: String::default(),
             manager: String::default(),
             link_to_manager: vec![],
             groups: vec![],
@@ -2959,6 +3040,11 @@ pub mod tests {
             geocode_cache: String::default(),
             working_on: vec![],
             gusto_pull_permission: false,
+            checkr_candidate_id: String::default(),
+            continuous_check_status: String::default(),
+            background_check_status: String::default(),
+            termination_date: None,
+            photo_url: String::default(),
             cio_company_id: 1,
             airtable_record_id: String::default(),
         }
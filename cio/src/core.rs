@@ -90,6 +90,18 @@ pub struct Meeting {
     pub cancelled: bool,
 }
 
+/// The data type for someone that has signed up to be invited to a huddle.
+/// This is inline with our Airtable workspace for huddles.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HuddleSignUp {
+    #[serde(default, rename = "Person")]
+    pub person: AirtableUser,
+    /// Set when someone no longer wants to be invited, so that they are removed from the
+    /// event without deleting their sign up.
+    #[serde(default, rename = "Opted out")]
+    pub opted_out: bool,
+}
+
 /// The data type for sending reminders for meetings.
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct MeetingReminderEmailData {
@@ -254,8 +266,13 @@ impl From<octorust::types::PullRequestSimple> for GitHubPullRequest {
                 commit_ref: item.base.ref_.to_string(),
                 sha: item.base.sha.to_string(),
             },
-            // TODO: actually do these.
-            user: Default::default(),
+            user: item
+                .user
+                .map(|user| crate::repos::GitHubUser {
+                    login: user.login,
+                    ..Default::default()
+                })
+                .unwrap_or_default(),
             merged: item.merged_at.is_some(),
             labels: item.labels,
         }
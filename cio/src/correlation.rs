@@ -0,0 +1,147 @@
+/*!
+ * Correlation ids, for following a single piece of work, ie. the sync of one RFD, through every
+ * part of the system that touches it.
+ *
+ * An id is made for each inbound webhook and each scheduled run, and is carried along with the
+ * work: it is stored with queued webhook events and saga parameters, added to log records and
+ * tracing spans, and sent to providers in the `X-Correlation-Id` header.
+ *
+ * Within a task the id lives in a task local, so code running inside [`scope`] can read it with
+ * [`current`] without it being passed around. Spawned tasks do not inherit task locals, so work
+ * that fans out should use [`spawn`] to keep the id.
+ */
+use std::{fmt, future::Future};
+
+use async_trait::async_trait;
+use reqwest::header::HeaderValue;
+use reqwest_middleware::{Middleware, Next};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use task_local_extensions::Extensions;
+use tracing::Instrument;
+
+pub const CORRELATION_ID_HEADER: &str = "X-Correlation-Id";
+
+/// Ids sent by callers longer than this are ignored and a new one is made instead.
+const MAX_CORRELATION_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static CURRENT: CorrelationId;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, JsonSchema, Deserialize, Serialize)]
+pub struct CorrelationId(String);
+
+impl CorrelationId {
+    pub fn new() -> Self {
+        CorrelationId(uuid::Uuid::new_v4().to_string())
+    }
+
+    /// Accept an id sent by a caller, so that work can be followed back into the system that
+    /// asked for it. Only ids that are safe to put in logs and headers are accepted.
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+
+        let valid = !value.is_empty()
+            && value.len() <= MAX_CORRELATION_ID_LEN
+            && value
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'));
+
+        valid.then(|| CorrelationId(value.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for CorrelationId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for CorrelationId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The correlation id of the work the current task is doing, if it is inside a [`scope`].
+pub fn current() -> Option<CorrelationId> {
+    CURRENT.try_with(|id| id.clone()).ok()
+}
+
+/// Run `f` with `id` as its correlation id.
+pub async fn scope<F: Future>(id: CorrelationId, f: F) -> F::Output {
+    let span = tracing::info_span!("correlation", correlation_id = %id);
+    CURRENT.scope(id, f.instrument(span)).await
+}
+
+/// Spawn a task that keeps the correlation id of the task that spawned it.
+pub fn spawn<F>(f: F) -> tokio::task::JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    match current() {
+        Some(id) => tokio::spawn(scope(id, f)),
+        None => tokio::spawn(f),
+    }
+}
+
+/// Sends the current correlation id along with outbound requests.
+pub struct CorrelationIdMiddleware;
+
+#[async_trait]
+impl Middleware for CorrelationIdMiddleware {
+    async fn handle(
+        &self,
+        mut req: reqwest::Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<reqwest::Response> {
+        if let Some(id) = current() {
+            if let Ok(value) = HeaderValue::from_str(id.as_str()) {
+                req.headers_mut().insert(CORRELATION_ID_HEADER, value);
+            }
+        }
+
+        next.run(req, extensions).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{current, scope, spawn, CorrelationId};
+
+    #[test]
+    fn test_parse_correlation_id() {
+        assert_eq!(
+            Some("github-delivery:72d3162e".to_string()),
+            CorrelationId::parse(" github-delivery:72d3162e ").map(|id| id.to_string())
+        );
+
+        assert!(CorrelationId::parse("").is_none());
+        assert!(CorrelationId::parse("has spaces").is_none());
+        assert!(CorrelationId::parse("new\nline").is_none());
+        assert!(CorrelationId::parse(&"a".repeat(129)).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_correlation_id_scope() {
+        assert!(current().is_none());
+
+        let id = CorrelationId::new();
+        let (inside, spawned) = scope(id.clone(), async {
+            let spawned = spawn(async { current() }).await.unwrap();
+            (current(), spawned)
+        })
+        .await;
+
+        assert_eq!(Some(id.clone()), inside);
+        assert_eq!(Some(id), spawned);
+        assert!(current().is_none());
+    }
+}
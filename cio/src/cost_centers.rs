@@ -0,0 +1,319 @@
+/*!
+ * Attributing spend to cost centers in QuickBooks, for board reporting.
+ *
+ * A cost center is a QuickBooks class, a department, or both. Purchases and bills are given the
+ * cost center of their vendor, or else of the expense account their lines are booked to. Only
+ * what is missing is filled in, so a class or department someone picked by hand is kept. The
+ * changes are sent to QuickBooks as sparse updates in batches rather than one request per
+ * transaction, since the nightly sync can touch hundreds of them. QuickBooks replaces the lines of
+ * a transaction as a whole, so a transaction with a line we do not model completely is skipped
+ * rather than have that line sent back with fields missing.
+ */
+use std::collections::HashMap;
+
+use anyhow::Result;
+use log::{info, warn};
+use quickbooks::{
+    BatchEntity, BatchItemRequest, Bill, Class, Department, Line, NtRef, Purchase, QuickBooks, SparseTransaction,
+};
+
+use crate::{
+    app_config::{CostCenter, CostCentersConfig, FinanceConfig},
+    finance::clean_merchant_name,
+};
+
+/// The cost center of a transaction from `vendor` with `lines`.
+fn cost_center_for<'a>(config: &'a CostCentersConfig, vendor: &str, lines: &[Line]) -> Option<&'a CostCenter> {
+    config.vendors.get(vendor).or_else(|| {
+        lines.iter().find_map(|line| {
+            let detail = line.account_based_expense_line_detail.as_ref()?;
+            config.accounts.get(&detail.account_ref.name)
+        })
+    })
+}
+
+/// Whether none of the expense lines of a transaction have a class.
+fn is_missing_class(lines: &[Line]) -> bool {
+    let mut expense_lines = lines.iter().filter(|line| line.has_class()).peekable();
+
+    expense_lines.peek().is_some() && expense_lines.all(|line| line.class_ref().is_none())
+}
+
+/// The sparse update that gives a transaction the class and department of its cost center, or
+/// `None` if it needs neither or has a line that can not be sent back unchanged.
+fn cost_center_update(
+    refs: &CostCenterRefs,
+    cost_center: &CostCenter,
+    id: &str,
+    sync_token: &str,
+    lines: &[Line],
+    department_ref: Option<&NtRef>,
+) -> Option<SparseTransaction> {
+    let mut update = SparseTransaction::new(id, sync_token);
+    if let (true, Some(class)) = (is_missing_class(lines), refs.class(cost_center)) {
+        if !update.set_class(lines, class) {
+            warn!(
+                "not attributing quickbooks transaction `{}` to its cost center, it has lines we can not update",
+                id
+            );
+            return None;
+        }
+    }
+    if let (None, Some(department)) = (department_ref, refs.department(cost_center)) {
+        update.set_department(department);
+    }
+
+    if update.is_empty() {
+        None
+    } else {
+        Some(update)
+    }
+}
+
+/// The classes and departments in QuickBooks, by name and by fully qualified name.
+struct CostCenterRefs {
+    classes: HashMap<String, Class>,
+    departments: HashMap<String, Department>,
+}
+
+impl CostCenterRefs {
+    async fn list(qb: &QuickBooks) -> Result<Self> {
+        let mut classes = HashMap::new();
+        for class in qb.list_classes().await? {
+            classes.insert(class.fully_qualified_name.to_string(), class.clone());
+            classes.insert(class.name.to_string(), class);
+        }
+
+        let mut departments = HashMap::new();
+        for department in qb.list_departments().await? {
+            departments.insert(department.fully_qualified_name.to_string(), department.clone());
+            departments.insert(department.name.to_string(), department);
+        }
+
+        Ok(CostCenterRefs { classes, departments })
+    }
+
+    /// The class of the cost center. Names that are not in QuickBooks are logged and skipped.
+    fn class(&self, cost_center: &CostCenter) -> Option<&Class> {
+        if cost_center.class.is_empty() {
+            return None;
+        }
+
+        let class = self.classes.get(&cost_center.class);
+        if class.is_none() {
+            warn!("there is no quickbooks class `{}`", cost_center.class);
+        }
+        class
+    }
+
+    /// The department of the cost center. Names that are not in QuickBooks are logged and
+    /// skipped.
+    fn department(&self, cost_center: &CostCenter) -> Option<&Department> {
+        if cost_center.department.is_empty() {
+            return None;
+        }
+
+        let department = self.departments.get(&cost_center.department);
+        if department.is_none() {
+            warn!("there is no quickbooks department `{}`", cost_center.department);
+        }
+        department
+    }
+}
+
+/// Give the purchases and bills that are missing the class or department of their cost center
+/// those in QuickBooks.
+pub async fn attribute_cost_centers(
+    qb: &QuickBooks,
+    config: &FinanceConfig,
+    purchases: &[Purchase],
+    bills: &[Bill],
+) -> Result<()> {
+    let cost_centers = &config.cost_centers;
+    if cost_centers.is_empty() {
+        return Ok(());
+    }
+
+    let refs = CostCenterRefs::list(qb).await?;
+
+    let mut updates = Vec::new();
+    for purchase in purchases {
+        let vendor = clean_merchant_name(&purchase.entity_ref.name, config);
+        let cost_center = match cost_center_for(cost_centers, &vendor, &purchase.line) {
+            Some(cost_center) => cost_center,
+            None => continue,
+        };
+
+        if let Some(update) = cost_center_update(
+            &refs,
+            cost_center,
+            &purchase.id,
+            &purchase.sync_token,
+            &purchase.line,
+            purchase.department_ref.as_ref(),
+        ) {
+            updates.push(BatchItemRequest::update(
+                &format!("purchase-{}", purchase.id),
+                BatchEntity::SparsePurchase(update),
+            ));
+        }
+    }
+    for bill in bills {
+        let vendor = clean_merchant_name(&bill.vendor_ref.name, config);
+        let cost_center = match cost_center_for(cost_centers, &vendor, &bill.line) {
+            Some(cost_center) => cost_center,
+            None => continue,
+        };
+
+        if let Some(update) = cost_center_update(
+            &refs,
+            cost_center,
+            &bill.id,
+            &bill.sync_token,
+            &bill.line,
+            bill.department_ref.as_ref(),
+        ) {
+            updates.push(BatchItemRequest::update(
+                &format!("bill-{}", bill.id),
+                BatchEntity::SparseBill(update),
+            ));
+        }
+    }
+
+    if updates.is_empty() {
+        return Ok(());
+    }
+
+    let count = updates.len();
+    let mut failed = 0;
+    for response in qb.batch(updates).await? {
+        if let Some(fault) = response.fault {
+            warn!("attributing `{}` to its cost center failed: {}", response.b_id, fault);
+            failed += 1;
+        }
+    }
+    info!(
+        "attributed {} quickbooks transactions to their cost centers, {} failed",
+        count - failed,
+        failed
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use quickbooks::{AccountBasedExpenseLineDetail, Class, Line, NtRef};
+
+    use super::{cost_center_for, cost_center_update, is_missing_class, CostCenterRefs};
+    use crate::app_config::{CostCenter, CostCentersConfig};
+
+    fn expense_line(account: &str, class: Option<&str>) -> Line {
+        Line {
+            detail_type: "AccountBasedExpenseLineDetail".to_string(),
+            account_based_expense_line_detail: Some(AccountBasedExpenseLineDetail {
+                account_ref: NtRef {
+                    value: "1".to_string(),
+                    name: account.to_string(),
+                    entity_ref_type: String::new(),
+                },
+                class_ref: class.map(|class| NtRef {
+                    value: "1".to_string(),
+                    name: class.to_string(),
+                    entity_ref_type: String::new(),
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn cost_center(class: &str, department: &str) -> CostCenter {
+        CostCenter {
+            class: class.to_string(),
+            department: department.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_cost_center_for() {
+        let mut config = CostCentersConfig::default();
+        config
+            .vendors
+            .insert("GitHub".to_string(), cost_center("Engineering", ""));
+        config.accounts.insert("Travel".to_string(), cost_center("", "Sales"));
+
+        let lines = vec![expense_line("Travel", None)];
+        assert_eq!(
+            Some(&cost_center("Engineering", "")),
+            cost_center_for(&config, "GitHub", &lines)
+        );
+        assert_eq!(
+            Some(&cost_center("", "Sales")),
+            cost_center_for(&config, "Delta", &lines)
+        );
+        assert_eq!(
+            None,
+            cost_center_for(&config, "Delta", &[expense_line("Software", None)])
+        );
+    }
+
+    #[test]
+    fn test_is_missing_class() {
+        assert!(is_missing_class(&[
+            expense_line("Software", None),
+            expense_line("Travel", None)
+        ]));
+        // A class that was picked by hand for any of the lines is kept.
+        assert!(!is_missing_class(&[
+            expense_line("Software", None),
+            expense_line("Travel", Some("Ops"))
+        ]));
+        // Lines that are not expenses can not have a class.
+        assert!(!is_missing_class(&[Line::default()]));
+        assert!(!is_missing_class(&[]));
+    }
+
+    #[test]
+    fn test_cost_center_update() {
+        let mut classes = HashMap::new();
+        classes.insert(
+            "Engineering".to_string(),
+            Class {
+                id: "7".to_string(),
+                name: "Engineering".to_string(),
+                fully_qualified_name: "Engineering".to_string(),
+                ..Default::default()
+            },
+        );
+        let refs = CostCenterRefs {
+            classes,
+            departments: HashMap::new(),
+        };
+        let engineering = cost_center("Engineering", "");
+
+        // Every line is sent, with the class set, and nothing else of the transaction.
+        let lines = vec![expense_line("Software", None), expense_line("Travel", None)];
+        let update = cost_center_update(&refs, &engineering, "12", "3", &lines, None).unwrap();
+        assert!(update.sparse);
+        assert_eq!(2, update.line.len());
+        assert!(update.line.iter().all(|line| line.class_ref().unwrap().value == "7"));
+        assert!(update.department_ref.is_none());
+
+        // A line that is not modeled completely would lose fields, so the transaction is skipped.
+        let lines = vec![
+            expense_line("Software", None),
+            Line {
+                detail_type: "DiscountLineDetail".to_string(),
+                ..Default::default()
+            },
+        ];
+        assert!(cost_center_update(&refs, &engineering, "12", "3", &lines, None).is_none());
+
+        // Nothing to change.
+        let lines = vec![expense_line("Software", Some("Ops"))];
+        assert!(cost_center_update(&refs, &engineering, "12", "3", &lines, None).is_none());
+    }
+}
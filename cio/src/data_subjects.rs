@@ -0,0 +1,682 @@
+/*!
+ * Requests from people for the data we hold on them, or for it to be erased.
+ *
+ * An export collects every record we have for an email into one bundle. An erasure deletes
+ * those records, along with the copies pushed to Airtable, the applicant search index and the
+ * mailing list provider, except for the ones we have to keep: shipments and background checks are
+ * anonymized instead, and employees are removed from the configs repository rather than here.
+ * Every request is kept as an audit record, with a hash of the email rather than the email
+ * itself, so that the record does not undo the erasure it records.
+ */
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Utc};
+use diesel::{
+    dsl::sql,
+    sql_types::{Bool, Text},
+    BoolExpressionMethods, ExpressionMethods, Insertable, PgArrayExpressionMethods, QueryDsl, Queryable,
+};
+use log::{info, warn};
+use ring::digest;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    access_requests::AccessRequest,
+    analytics::PageView,
+    app_config::MailingListConfig,
+    applicant_duplicates::ApplicantDuplicate,
+    applicant_reviews::ApplicantReview,
+    applicant_search::ApplicantSearchIndex,
+    applicants::Applicant,
+    auth_logins::{AuthUser, AuthUserLogin},
+    background_checks::BackgroundCheckEvent,
+    companies::Company,
+    configs::User,
+    db::Database,
+    delivery::ScheduledMessage,
+    email::EmailMessage,
+    interview_scheduling::InterviewSchedule,
+    interviews::ApplicantInterview,
+    mailing_list::MailingListSubscriber,
+    offer_approvals::OfferApproval,
+    rack_line::RackLineSubscriber,
+    schema::{
+        access_requests, applicant_duplicates, applicant_interviews, applicant_reviews, applicants, auth_user_logins,
+        auth_users, background_check_events, data_subject_requests, email_messages, interview_schedules,
+        mailing_list_subscribers, offer_approvals, outbound_shipment_events, outbound_shipments, page_views,
+        rack_line_subscribers, scheduled_messages, swag_preference_requests, upload_tokens, users, webhook_deliveries,
+    },
+    shipment_changes::SHIPMENT_EVENT_ADDRESS_CORRECTED,
+    shipments::OutboundShipment,
+    swag_preferences::SwagPreferenceRequest,
+    webhook_outbox::WebhookDelivery,
+};
+
+/// What the personal fields of records that are kept are replaced with.
+const REDACTED: &str = "[redacted]";
+
+/// Everything we hold on a person, by their email.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DataSubjectExport {
+    pub email: String,
+    pub generated_at: DateTime<Utc>,
+    pub applicants: Vec<Applicant>,
+    pub applicant_interviews: Vec<ApplicantInterview>,
+    pub applicant_reviews: Vec<ApplicantReview>,
+    pub interview_schedules: Vec<InterviewSchedule>,
+    pub offer_approvals: Vec<OfferApproval>,
+    pub background_checks: Vec<BackgroundCheckEvent>,
+    pub mailing_list_subscribers: Vec<MailingListSubscriber>,
+    pub rack_line_subscribers: Vec<RackLineSubscriber>,
+    pub outbound_shipments: Vec<OutboundShipment>,
+    pub swag_preference_requests: Vec<SwagPreferenceRequest>,
+    pub auth_users: Vec<AuthUser>,
+    pub auth_user_logins: Vec<AuthUserLogin>,
+    pub page_views: Vec<PageView>,
+    pub email_messages: Vec<EmailMessage>,
+    pub scheduled_messages: Vec<ScheduledMessage>,
+    pub access_requests: Vec<AccessRequest>,
+    pub webhook_deliveries: Vec<WebhookDelivery>,
+    pub users: Vec<User>,
+}
+
+/// What was done with one kind of record during an erasure.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct ErasureAction {
+    /// The kind of record, ie. `applicants`.
+    pub records: String,
+    /// One of `deleted`, `anonymized` or `retained`.
+    pub action: String,
+    pub count: usize,
+    /// Why the records were kept, or what is left to do by hand.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
+}
+
+impl ErasureAction {
+    fn new(records: &str, action: &str, count: usize) -> Self {
+        ErasureAction {
+            records: records.to_string(),
+            action: action.to_string(),
+            count,
+            note: Default::default(),
+        }
+    }
+
+    fn with_note(mut self, note: &str) -> Self {
+        self.note = note.to_string();
+        self
+    }
+}
+
+impl std::fmt::Display for ErasureAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}: {} {}", self.records, self.action, self.count)?;
+        if !self.note.is_empty() {
+            write!(f, " ({})", self.note)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The outcome of erasing a person's data.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct DataSubjectErasure {
+    /// The id of the audit record of the erasure.
+    pub request_id: i32,
+    pub actions: Vec<ErasureAction>,
+}
+
+/// The audit record of a request.
+#[derive(Debug, Clone, PartialEq, Queryable)]
+pub struct DataSubjectRequest {
+    pub id: i32,
+    pub kind: String,
+    pub subject_hash: String,
+    pub requested_by: String,
+    pub actions: Vec<String>,
+    pub cio_company_id: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = data_subject_requests)]
+struct NewDataSubjectRequest {
+    kind: String,
+    subject_hash: String,
+    requested_by: String,
+    actions: Vec<String>,
+    cio_company_id: i32,
+}
+
+/// The hash an email is kept as in the audit records, so that a request can be looked up by the
+/// email it was for without the records holding it.
+pub fn subject_hash(email: &str) -> String {
+    hex::encode(digest::digest(&digest::SHA256, email.trim().to_lowercase().as_bytes()))
+}
+
+// Emails are stored however they were typed, so they are matched ignoring case, against the
+// lowercase email of the request.
+sql_function!(fn lower(x: Text) -> Text);
+
+fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// The SQL that matches email messages that were sent to `email`, ignoring case.
+const SENT_TO_EMAIL: &str = "EXISTS (SELECT 1 FROM unnest(recipients) AS recipient WHERE lower(recipient) = ";
+
+/// The SQL that matches webhook deliveries of events about `email`, ignoring case. The events
+/// about a person, ie. `new_hire`, have their email in their data.
+const DELIVERED_EMAIL: &str = "lower(payload -> 'data' ->> 'email') = ";
+
+async fn record_request(
+    db: &Database,
+    company: &Company,
+    kind: &str,
+    email: &str,
+    requested_by: &str,
+    actions: Vec<String>,
+) -> Result<i32> {
+    let request = NewDataSubjectRequest {
+        kind: kind.to_string(),
+        subject_hash: subject_hash(email),
+        requested_by: requested_by.to_string(),
+        actions,
+        cio_company_id: company.id,
+    };
+
+    Ok(diesel::insert_into(data_subject_requests::table)
+        .values(request)
+        .returning(data_subject_requests::dsl::id)
+        .get_result_async::<i32>(db.pool())
+        .await?)
+}
+
+fn check_request(email: &str, requested_by: &str) -> Result<()> {
+    if !email.contains('@') {
+        bail!("`{}` is not an email", email);
+    }
+    if requested_by.trim().is_empty() {
+        bail!("requests for a person's data have to say who asked for them");
+    }
+
+    Ok(())
+}
+
+async fn load_applicants(db: &Database, company: &Company, email: &str) -> Result<Vec<Applicant>> {
+    Ok(applicants::dsl::applicants
+        .filter(applicants::dsl::cio_company_id.eq(company.id))
+        .filter(lower(applicants::dsl::email).eq(email.to_string()))
+        .load_async::<Applicant>(db.pool())
+        .await?)
+}
+
+/// The interviews of the applicants, by the applicants they are linked to in Airtable or by the
+/// email of the person being interviewed.
+async fn load_interviews(
+    db: &Database,
+    company: &Company,
+    email: &str,
+    records: &[String],
+) -> Result<Vec<ApplicantInterview>> {
+    Ok(applicant_interviews::dsl::applicant_interviews
+        .filter(applicant_interviews::dsl::cio_company_id.eq(company.id))
+        .filter(
+            lower(applicant_interviews::dsl::email)
+                .eq(email.to_string())
+                .or(applicant_interviews::dsl::applicant.overlaps_with(records.to_vec())),
+        )
+        .load_async::<ApplicantInterview>(db.pool())
+        .await?)
+}
+
+async fn load_reviews(db: &Database, company: &Company, records: &[String]) -> Result<Vec<ApplicantReview>> {
+    Ok(applicant_reviews::dsl::applicant_reviews
+        .filter(applicant_reviews::dsl::cio_company_id.eq(company.id))
+        .filter(applicant_reviews::dsl::applicant.overlaps_with(records.to_vec()))
+        .load_async::<ApplicantReview>(db.pool())
+        .await?)
+}
+
+fn airtable_records(applicants: &[Applicant]) -> Vec<String> {
+    applicants
+        .iter()
+        .map(|a| a.airtable_record_id.to_string())
+        .filter(|id| !id.is_empty())
+        .collect()
+}
+
+/// Collect everything we hold on the person with `email`, which has to be normalized already.
+async fn collect_data_subject(db: &Database, company: &Company, email: &str) -> Result<DataSubjectExport> {
+    let email = email.to_string();
+
+    let applicants = load_applicants(db, company, &email).await?;
+    let ids = applicants.iter().map(|a| a.id).collect::<Vec<_>>();
+    let records = airtable_records(&applicants);
+
+    let export = DataSubjectExport {
+        email: email.to_string(),
+        generated_at: Utc::now(),
+        applicant_interviews: load_interviews(db, company, &email, &records).await?,
+        applicant_reviews: load_reviews(db, company, &records).await?,
+        interview_schedules: interview_schedules::dsl::interview_schedules
+            .filter(interview_schedules::dsl::cio_company_id.eq(company.id))
+            .filter(
+                interview_schedules::dsl::applicant_id
+                    .eq_any(ids.clone())
+                    .or(lower(interview_schedules::dsl::email).eq(email.clone())),
+            )
+            .load_async::<InterviewSchedule>(db.pool())
+            .await?,
+        offer_approvals: offer_approvals::dsl::offer_approvals
+            .filter(offer_approvals::dsl::applicant_id.eq_any(ids))
+            .load_async::<OfferApproval>(db.pool())
+            .await?,
+        background_checks: background_check_events::dsl::background_check_events
+            .filter(background_check_events::dsl::cio_company_id.eq(company.id))
+            .filter(lower(background_check_events::dsl::email).eq(email.clone()))
+            .load_async::<BackgroundCheckEvent>(db.pool())
+            .await?,
+        mailing_list_subscribers: mailing_list_subscribers::dsl::mailing_list_subscribers
+            .filter(mailing_list_subscribers::dsl::cio_company_id.eq(company.id))
+            .filter(lower(mailing_list_subscribers::dsl::email).eq(email.clone()))
+            .load_async::<MailingListSubscriber>(db.pool())
+            .await?,
+        rack_line_subscribers: rack_line_subscribers::dsl::rack_line_subscribers
+            .filter(rack_line_subscribers::dsl::cio_company_id.eq(company.id))
+            .filter(lower(rack_line_subscribers::dsl::email).eq(email.clone()))
+            .load_async::<RackLineSubscriber>(db.pool())
+            .await?,
+        outbound_shipments: outbound_shipments::dsl::outbound_shipments
+            .filter(outbound_shipments::dsl::cio_company_id.eq(company.id))
+            .filter(lower(outbound_shipments::dsl::email).eq(email.clone()))
+            .load_async::<OutboundShipment>(db.pool())
+            .await?,
+        swag_preference_requests: swag_preference_requests::dsl::swag_preference_requests
+            .filter(swag_preference_requests::dsl::cio_company_id.eq(company.id))
+            .filter(lower(swag_preference_requests::dsl::email).eq(email.clone()))
+            .load_async::<SwagPreferenceRequest>(db.pool())
+            .await?,
+        auth_users: auth_users::dsl::auth_users
+            .filter(auth_users::dsl::cio_company_id.eq(company.id))
+            .filter(lower(auth_users::dsl::email).eq(email.clone()))
+            .load_async::<AuthUser>(db.pool())
+            .await?,
+        auth_user_logins: auth_user_logins::dsl::auth_user_logins
+            .filter(auth_user_logins::dsl::cio_company_id.eq(company.id))
+            .filter(lower(auth_user_logins::dsl::email).eq(email.clone()))
+            .load_async::<AuthUserLogin>(db.pool())
+            .await?,
+        page_views: page_views::dsl::page_views
+            .filter(page_views::dsl::cio_company_id.eq(company.id))
+            .filter(lower(page_views::dsl::user_email).eq(email.clone()))
+            .load_async::<PageView>(db.pool())
+            .await?,
+        email_messages: email_messages::dsl::email_messages
+            .filter(email_messages::dsl::cio_company_id.eq(company.id))
+            .filter(sql::<Bool>(SENT_TO_EMAIL).bind::<Text, _>(email.clone()).sql(")"))
+            .load_async::<EmailMessage>(db.pool())
+            .await?,
+        scheduled_messages: scheduled_messages::dsl::scheduled_messages
+            .filter(scheduled_messages::dsl::cio_company_id.eq(company.id))
+            .filter(lower(scheduled_messages::dsl::recipient).eq(email.clone()))
+            .load_async::<ScheduledMessage>(db.pool())
+            .await?,
+        access_requests: access_requests::dsl::access_requests
+            .filter(access_requests::dsl::cio_company_id.eq(company.id))
+            .filter(lower(access_requests::dsl::requester_email).eq(email.clone()))
+            .load_async::<AccessRequest>(db.pool())
+            .await?,
+        webhook_deliveries: webhook_deliveries::dsl::webhook_deliveries
+            .filter(webhook_deliveries::dsl::cio_company_id.eq(company.id))
+            .filter(sql::<Bool>(DELIVERED_EMAIL).bind::<Text, _>(email.clone()))
+            .load_async::<WebhookDelivery>(db.pool())
+            .await?,
+        users: users::dsl::users
+            .filter(users::dsl::cio_company_id.eq(company.id))
+            .filter(
+                lower(users::dsl::email)
+                    .eq(email.clone())
+                    .or(lower(users::dsl::recovery_email).eq(email.clone())),
+            )
+            .load_async::<User>(db.pool())
+            .await?,
+        applicants,
+    };
+
+    Ok(export)
+}
+
+/// Collect everything we hold on the person with `email`.
+pub async fn export_data_subject(
+    db: &Database,
+    company: &Company,
+    email: &str,
+    requested_by: &str,
+) -> Result<DataSubjectExport> {
+    check_request(email, requested_by)?;
+    let email = normalize_email(email);
+
+    let export = collect_data_subject(db, company, &email).await?;
+
+    record_request(db, company, "export", &email, requested_by, Default::default()).await?;
+    info!("exported the data of a person for {}", requested_by);
+
+    Ok(export)
+}
+
+/// Clear the personal fields of a shipment, keeping what was sent and what it cost.
+fn anonymize_shipment(shipment: &mut OutboundShipment) {
+    shipment.name = REDACTED.to_string();
+    shipment.email = REDACTED.to_string();
+    shipment.phone = Default::default();
+    shipment.street_1 = Default::default();
+    shipment.street_2 = Default::default();
+    shipment.city = Default::default();
+    shipment.zipcode = Default::default();
+    shipment.address_formatted = Default::default();
+    shipment.latitude = 0.0;
+    shipment.longitude = 0.0;
+    shipment.geocode_cache = Default::default();
+    shipment.notes = Default::default();
+}
+
+/// Erase the person with `email` from our records and from the copies pushed to Airtable and the
+/// mailing list provider.
+pub async fn erase_data_subject(
+    db: &Database,
+    company: &Company,
+    mailing_list: &MailingListConfig,
+    email: &str,
+    requested_by: &str,
+) -> Result<DataSubjectErasure> {
+    check_request(email, requested_by)?;
+    let email = normalize_email(email);
+
+    // Collect what there is first, so that links between records are still there to follow. This
+    // is not an export, so it is not recorded as one.
+    let export = collect_data_subject(db, company, &email).await?;
+    let ids = export.applicants.iter().map(|a| a.id).collect::<Vec<_>>();
+    let mut actions = Vec::new();
+
+    // Records are deleted from Airtable before the database, so that an erasure that fails part
+    // way through still finds the copies that were pushed to Airtable when it is run again.
+    for interview in &export.applicant_interviews {
+        interview.delete_from_airtable(db).await?;
+        interview.delete_from_db(db).await?;
+    }
+    actions.push(ErasureAction::new(
+        "applicant_interviews",
+        "deleted",
+        export.applicant_interviews.len(),
+    ));
+
+    for review in &export.applicant_reviews {
+        review.delete_from_airtable(db).await?;
+        review.delete_from_db(db).await?;
+    }
+    actions.push(ErasureAction::new(
+        "applicant_reviews",
+        "deleted",
+        export.applicant_reviews.len(),
+    ));
+
+    let schedules = diesel::delete(interview_schedules::dsl::interview_schedules)
+        .filter(interview_schedules::dsl::cio_company_id.eq(company.id))
+        .filter(
+            interview_schedules::dsl::applicant_id
+                .eq_any(ids.clone())
+                .or(lower(interview_schedules::dsl::email).eq(email.clone())),
+        )
+        .execute_async(db.pool())
+        .await?;
+    actions.push(ErasureAction::new("interview_schedules", "deleted", schedules));
+
+    let offers = diesel::delete(offer_approvals::dsl::offer_approvals)
+        .filter(offer_approvals::dsl::applicant_id.eq_any(ids.clone()))
+        .execute_async(db.pool())
+        .await?;
+    actions.push(ErasureAction::new("offer_approvals", "deleted", offers));
+
+    let duplicates = applicant_duplicates::dsl::applicant_duplicates
+        .filter(
+            applicant_duplicates::dsl::applicant_id
+                .eq_any(ids.clone())
+                .or(applicant_duplicates::dsl::duplicate_id.eq_any(ids.clone())),
+        )
+        .load_async::<ApplicantDuplicate>(db.pool())
+        .await?;
+    for duplicate in &duplicates {
+        duplicate.delete_from_airtable(db).await?;
+        duplicate.delete_from_db(db).await?;
+    }
+
+    diesel::delete(upload_tokens::dsl::upload_tokens)
+        .filter(upload_tokens::dsl::cio_company_id.eq(company.id))
+        .filter(lower(upload_tokens::dsl::email).eq(email.clone()))
+        .execute_async(db.pool())
+        .await?;
+
+    // Applicants are taken out of search before their records go, so that an erasure that fails
+    // here still finds them when it is run again. Without a search client nothing was indexed.
+    match ApplicantSearchIndex::default_client() {
+        Ok(client) => ApplicantSearchIndex::delete_applicants(&client, &ids).await?,
+        Err(e) => warn!("not removing applicants from search, there is no search client: {}", e),
+    }
+
+    for applicant in &export.applicants {
+        applicant.delete_from_airtable(db).await?;
+        applicant.delete_from_db(db).await?;
+    }
+    actions.push(
+        ErasureAction::new("applicants", "deleted", export.applicants.len())
+            .with_note("their resumes and materials in Google Drive have to be deleted by hand"),
+    );
+
+    // Background checks are kept for as long as the law asks, without who they were for.
+    let checks = diesel::update(background_check_events::dsl::background_check_events)
+        .filter(background_check_events::dsl::cio_company_id.eq(company.id))
+        .filter(lower(background_check_events::dsl::email).eq(email.clone()))
+        .set((
+            background_check_events::dsl::email.eq(REDACTED),
+            background_check_events::dsl::name.eq(REDACTED),
+        ))
+        .execute_async(db.pool())
+        .await?;
+    actions.push(ErasureAction::new("background_checks", "anonymized", checks));
+
+    // A subscriber is only deleted once they are off the provider's list, so that an erasure that
+    // fails part way through still finds them when it is run again.
+    if !export.mailing_list_subscribers.is_empty() {
+        let provider = company.authenticate_mailing_list_provider(mailing_list).await?;
+        for subscriber in &export.mailing_list_subscribers {
+            provider.remove_member(&subscriber.email).await?;
+            subscriber.delete_from_airtable(db).await?;
+            subscriber.delete_from_db(db).await?;
+        }
+    }
+    actions.push(ErasureAction::new(
+        "mailing_list_subscribers",
+        "deleted",
+        export.mailing_list_subscribers.len(),
+    ));
+
+    for subscriber in &export.rack_line_subscribers {
+        subscriber.delete_from_airtable(db).await?;
+        subscriber.delete_from_db(db).await?;
+    }
+    actions.push(ErasureAction::new(
+        "rack_line_subscribers",
+        "deleted",
+        export.rack_line_subscribers.len(),
+    ));
+
+    // What was shipped and what it cost is kept for the books.
+    for shipment in &export.outbound_shipments {
+        let mut shipment = shipment.clone();
+        anonymize_shipment(&mut shipment);
+        let record = shipment.upsert_in_airtable(db).await?;
+        shipment.airtable_record_id = record.id.to_string();
+        shipment.update_in_db(db).await?;
+    }
+    actions.push(ErasureAction::new(
+        "outbound_shipments",
+        "anonymized",
+        export.outbound_shipments.len(),
+    ));
+
+    // The history of a shipment has the addresses it was corrected from and to.
+    let shipment_ids = export
+        .outbound_shipments
+        .iter()
+        .map(|shipment| shipment.id)
+        .collect::<Vec<_>>();
+    let corrections = diesel::delete(outbound_shipment_events::dsl::outbound_shipment_events)
+        .filter(outbound_shipment_events::dsl::cio_company_id.eq(company.id))
+        .filter(outbound_shipment_events::dsl::outbound_shipment_id.eq_any(shipment_ids))
+        .filter(outbound_shipment_events::dsl::kind.eq(SHIPMENT_EVENT_ADDRESS_CORRECTED))
+        .execute_async(db.pool())
+        .await?;
+    actions.push(ErasureAction::new("outbound_shipment_events", "deleted", corrections));
+
+    let swag_requests = diesel::delete(swag_preference_requests::dsl::swag_preference_requests)
+        .filter(swag_preference_requests::dsl::cio_company_id.eq(company.id))
+        .filter(lower(swag_preference_requests::dsl::email).eq(email.clone()))
+        .execute_async(db.pool())
+        .await?;
+    actions.push(ErasureAction::new("swag_preference_requests", "deleted", swag_requests));
+
+    for view in &export.page_views {
+        view.delete_from_airtable(db).await?;
+        view.delete_from_db(db).await?;
+    }
+    actions.push(ErasureAction::new("page_views", "deleted", export.page_views.len()));
+
+    let messages = diesel::delete(email_messages::dsl::email_messages)
+        .filter(email_messages::dsl::cio_company_id.eq(company.id))
+        .filter(sql::<Bool>(SENT_TO_EMAIL).bind::<Text, _>(email.clone()).sql(")"))
+        .execute_async(db.pool())
+        .await?;
+    actions.push(ErasureAction::new("email_messages", "deleted", messages));
+
+    let scheduled = diesel::delete(scheduled_messages::dsl::scheduled_messages)
+        .filter(scheduled_messages::dsl::cio_company_id.eq(company.id))
+        .filter(lower(scheduled_messages::dsl::recipient).eq(email.clone()))
+        .execute_async(db.pool())
+        .await?;
+    actions.push(ErasureAction::new("scheduled_messages", "deleted", scheduled));
+
+    // A delivery that has not been sent yet is not sent at all.
+    let deliveries = diesel::delete(webhook_deliveries::dsl::webhook_deliveries)
+        .filter(webhook_deliveries::dsl::cio_company_id.eq(company.id))
+        .filter(sql::<Bool>(DELIVERED_EMAIL).bind::<Text, _>(email.clone()))
+        .execute_async(db.pool())
+        .await?;
+    actions.push(ErasureAction::new("webhook_deliveries", "deleted", deliveries));
+
+    for login in &export.auth_user_logins {
+        login.delete_from_airtable(db).await?;
+        login.delete_from_db(db).await?;
+    }
+    actions.push(ErasureAction::new(
+        "auth_user_logins",
+        "deleted",
+        export.auth_user_logins.len(),
+    ));
+
+    for user in &export.auth_users {
+        user.delete_from_airtable(db).await?;
+        user.delete_from_db(db).await?;
+    }
+    actions.push(ErasureAction::new("auth_users", "deleted", export.auth_users.len()));
+
+    if !export.users.is_empty() {
+        actions.push(
+            ErasureAction::new("users", "retained", export.users.len())
+                .with_note("employees are removed from the configs repository"),
+        );
+    }
+
+    let request_id = record_request(
+        db,
+        company,
+        "erasure",
+        &email,
+        requested_by,
+        actions.iter().map(|action| action.to_string()).collect(),
+    )
+    .await?;
+
+    for action in actions.iter().filter(|action| action.count > 0) {
+        info!("erasure {} for {}: {}", request_id, requested_by, action);
+    }
+    if !export.users.is_empty() {
+        warn!(
+            "erasure {} is for an employee, who has to be removed from the configs repository",
+            request_id
+        );
+    }
+
+    Ok(DataSubjectErasure { request_id, actions })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{anonymize_shipment, normalize_email, subject_hash, ErasureAction, REDACTED};
+
+    #[test]
+    fn test_subject_hash() {
+        assert_eq!(subject_hash("Jane@Example.com "), subject_hash("jane@example.com"));
+        assert_eq!(64, subject_hash("jane@example.com").len());
+        assert_ne!(subject_hash("jane@example.com"), subject_hash("john@example.com"));
+    }
+
+    #[test]
+    fn test_normalize_email() {
+        assert_eq!("jane@example.com", normalize_email(" jane@example.com"));
+        assert_eq!("jane.doe@example.com", normalize_email("Jane.Doe@Example.com"));
+    }
+
+    #[test]
+    fn test_erasure_action_display() {
+        assert_eq!(
+            "applicants: deleted 2 (by hand)",
+            ErasureAction::new("applicants", "deleted", 2)
+                .with_note("by hand")
+                .to_string()
+        );
+        assert_eq!(
+            "page_views: deleted 0",
+            ErasureAction::new("page_views", "deleted", 0).to_string()
+        );
+    }
+
+    #[test]
+    fn test_anonymize_shipment() {
+        let mut shipment: crate::shipments::OutboundShipment = serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "name": "Jane Doe",
+            "contents": "Hoodie",
+            "street_1": "1 Main St",
+            "city": "Oakland",
+            "state": "CA",
+            "zipcode": "94607",
+            "country": "US",
+            "email": "jane@example.com",
+            "phone": "+15551234567",
+            "created_time": "2023-03-01T00:00:00Z",
+            "cio_company_id": 1
+        }))
+        .unwrap();
+
+        anonymize_shipment(&mut shipment);
+        assert_eq!(REDACTED, shipment.name);
+        assert_eq!(REDACTED, shipment.email);
+        assert!(shipment.street_1.is_empty() && shipment.phone.is_empty());
+        assert_eq!("Hoodie", shipment.contents);
+        assert_eq!("CA", shipment.state);
+    }
+}
@@ -0,0 +1,364 @@
+/*!
+ * Sending messages to people at a time of day that suits them.
+ *
+ * The cron jobs run on one clock, so a message sent straight from a job arrives in the middle of
+ * the night for anyone far enough away from it. Messages for a person can instead be sent within
+ * a delivery window in their own time zone, which is looked up from where they work in the
+ * directory. A message that is sent outside its recipient's window is queued in
+ * `scheduled_messages` and sent by the `send-scheduled-messages` job once the window opens.
+ */
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Datelike, NaiveTime, TimeZone, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
+use diesel::{BoolExpressionMethods, ExpressionMethods, Insertable, QueryDsl, Queryable};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::FormattedMessage;
+
+use crate::{
+    companies::Company,
+    configs::User,
+    db::Database,
+    directory::location,
+    email::{Mailer, OutgoingEmail},
+    geocode::timezone_for_address,
+    schema::{scheduled_messages, users},
+};
+
+/// The time zone of people we cannot find one for, the same one the cron jobs are scheduled in.
+pub const DEFAULT_TIME_ZONE: Tz = chrono_tz::US::Pacific;
+
+/// A queued message is given up on after this many failed attempts to send it.
+const MAX_ATTEMPTS: i32 = 5;
+
+/// The local hours of the day a message can be delivered in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeliveryWindow {
+    pub start_hour: u32,
+    pub end_hour: u32,
+    pub weekdays_only: bool,
+}
+
+impl DeliveryWindow {
+    /// For notifications and reminders, which can come at any point of the working day.
+    pub const WORKING_HOURS: DeliveryWindow = DeliveryWindow {
+        start_hour: 9,
+        end_hour: 17,
+        weekdays_only: true,
+    };
+
+    /// For digests, which are best read first thing.
+    pub const MORNING: DeliveryWindow = DeliveryWindow {
+        start_hour: 9,
+        end_hour: 11,
+        weekdays_only: true,
+    };
+
+    fn is_delivery_day(&self, day: Weekday) -> bool {
+        !self.weekdays_only || !matches!(day, Weekday::Sat | Weekday::Sun)
+    }
+
+    /// Whether `at` falls within the window in `tz`.
+    pub fn contains(&self, tz: &Tz, at: DateTime<Utc>) -> bool {
+        let local = at.with_timezone(tz);
+        self.is_delivery_day(local.weekday()) && (self.start_hour..self.end_hour).contains(&local.hour())
+    }
+
+    /// The earliest time at or after `at` that falls within the window in `tz`.
+    pub fn next_open(&self, tz: &Tz, at: DateTime<Utc>) -> DateTime<Utc> {
+        if self.contains(tz, at) {
+            return at;
+        }
+
+        // The start of the window today if we are before it, otherwise start looking tomorrow.
+        let local = at.with_timezone(tz);
+        let mut date = local.naive_local().date();
+        if !self.is_delivery_day(local.weekday()) || local.hour() >= self.start_hour {
+            date = date.succ_opt().unwrap_or(date);
+        }
+        while !self.is_delivery_day(date.weekday()) {
+            date = date.succ_opt().unwrap_or(date);
+        }
+
+        let start = date.and_time(NaiveTime::from_hms_opt(self.start_hour, 0, 0).unwrap());
+        tz.from_local_datetime(&start)
+            .earliest()
+            .map(|start| start.with_timezone(&Utc))
+            .unwrap_or(at)
+    }
+}
+
+/// The time zone of the person with `email`, from where they work in the directory. People we
+/// do not know, or whose location cannot be found, get the default time zone.
+pub async fn recipient_timezone(db: &Database, company: &Company, email: &str) -> Tz {
+    let email = email.trim().to_lowercase();
+    let user = users::dsl::users
+        .filter(users::dsl::cio_company_id.eq(company.id))
+        .filter(users::dsl::email.eq(&email).or(users::dsl::recovery_email.eq(&email)))
+        .first_async::<User>(db.pool())
+        .await;
+
+    let location = match user {
+        Ok(user) => location(&user),
+        Err(_) => return DEFAULT_TIME_ZONE,
+    };
+    if location.is_empty() {
+        return DEFAULT_TIME_ZONE;
+    }
+
+    match timezone_for_address(db, &location).await {
+        Ok(tz) => tz,
+        Err(e) => {
+            warn!("failed to look up the time zone of `{}`: {}", location, e);
+            DEFAULT_TIME_ZONE
+        }
+    }
+}
+
+/// A message waiting for its recipient's delivery window to open.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct ScheduledMessage {
+    pub id: i32,
+    /// Either `email` or `slack`.
+    pub kind: String,
+    /// The email of the person the message was timed for.
+    pub recipient: String,
+    /// The email or Slack message to send.
+    pub message: serde_json::Value,
+    pub send_at: DateTime<Utc>,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub attempts: i32,
+    pub last_error: String,
+    pub created_at: DateTime<Utc>,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = scheduled_messages)]
+struct NewScheduledMessage {
+    kind: String,
+    recipient: String,
+    message: serde_json::Value,
+    send_at: DateTime<Utc>,
+    cio_company_id: i32,
+}
+
+/// When a message for `recipient` should go out, or `None` when that is now.
+async fn queue_until(
+    db: &Database,
+    company: &Company,
+    recipient: &str,
+    window: DeliveryWindow,
+) -> Option<DateTime<Utc>> {
+    let now = Utc::now();
+    let tz = recipient_timezone(db, company, recipient).await;
+    let send_at = window.next_open(&tz, now);
+
+    if send_at > now {
+        Some(send_at)
+    } else {
+        None
+    }
+}
+
+async fn queue(
+    db: &Database,
+    company: &Company,
+    kind: &str,
+    recipient: &str,
+    message: serde_json::Value,
+    send_at: DateTime<Utc>,
+) -> Result<()> {
+    diesel::insert_into(scheduled_messages::table)
+        .values(NewScheduledMessage {
+            kind: kind.to_string(),
+            recipient: recipient.trim().to_lowercase(),
+            message,
+            send_at,
+            cio_company_id: company.id,
+        })
+        .execute_async(db.pool())
+        .await?;
+
+    info!("queued {} for {} until {}", kind, recipient, send_at);
+
+    Ok(())
+}
+
+/// Send the email within `window` in the time zone of its first recipient.
+pub async fn send_email_in_window(
+    db: &Database,
+    company: &Company,
+    email: OutgoingEmail,
+    window: DeliveryWindow,
+) -> Result<()> {
+    let recipient = match email.to.first() {
+        Some(recipient) => recipient.to_string(),
+        None => bail!("email `{}` has no recipients", email.subject),
+    };
+
+    match queue_until(db, company, &recipient, window).await {
+        Some(send_at) => queue(db, company, "email", &recipient, serde_json::to_value(&email)?, send_at).await,
+        None => Mailer::new(db, company).send(email).await,
+    }
+}
+
+/// Post the Slack message within `window` in the time zone of the person with `recipient` as
+/// their email, who is usually the person the message is a DM to.
+pub async fn post_to_slack_in_window(
+    db: &Database,
+    company: &Company,
+    recipient: &str,
+    message: &FormattedMessage,
+    window: DeliveryWindow,
+) -> Result<()> {
+    match queue_until(db, company, recipient, window).await {
+        Some(send_at) => queue(db, company, "slack", recipient, serde_json::to_value(message)?, send_at).await,
+        None => company.post_to_slack_channel(db, message).await,
+    }
+}
+
+impl ScheduledMessage {
+    async fn send(&self, db: &Database, company: &Company) -> Result<()> {
+        match self.kind.as_str() {
+            "email" => {
+                let email: OutgoingEmail = serde_json::from_value(self.message.clone())?;
+                Mailer::new(db, company).send(email).await
+            }
+            "slack" => {
+                let message: FormattedMessage = serde_json::from_value(self.message.clone())?;
+                company.post_to_slack_channel(db, &message).await
+            }
+            kind => bail!("unknown kind of scheduled message `{}`", kind),
+        }
+    }
+
+    /// Claim the message for sending. Returns whether nobody else had, so that a message is not
+    /// sent twice by overlapping runs.
+    async fn claim(&self, db: &Database) -> Result<bool> {
+        let claimed = diesel::update(scheduled_messages::dsl::scheduled_messages)
+            .filter(scheduled_messages::dsl::id.eq(self.id))
+            .filter(scheduled_messages::dsl::sent_at.is_null())
+            .set(scheduled_messages::dsl::sent_at.eq(Some(Utc::now())))
+            .execute_async(db.pool())
+            .await?;
+
+        Ok(claimed > 0)
+    }
+
+    /// Put the message back in the queue after it failed to send.
+    async fn release(&self, db: &Database, error: &str) -> Result<()> {
+        diesel::update(scheduled_messages::dsl::scheduled_messages)
+            .filter(scheduled_messages::dsl::id.eq(self.id))
+            .set((
+                scheduled_messages::dsl::sent_at.eq(None::<DateTime<Utc>>),
+                scheduled_messages::dsl::attempts.eq(self.attempts + 1),
+                scheduled_messages::dsl::last_error.eq(error),
+            ))
+            .execute_async(db.pool())
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Send the queued messages whose delivery windows have opened.
+pub async fn send_scheduled_messages(db: &Database, company: &Company) -> Result<()> {
+    let due = scheduled_messages::dsl::scheduled_messages
+        .filter(scheduled_messages::dsl::cio_company_id.eq(company.id))
+        .filter(scheduled_messages::dsl::sent_at.is_null())
+        .filter(scheduled_messages::dsl::send_at.le(Utc::now()))
+        .filter(scheduled_messages::dsl::attempts.lt(MAX_ATTEMPTS))
+        .order_by(scheduled_messages::dsl::send_at)
+        .load_async::<ScheduledMessage>(db.pool())
+        .await?;
+
+    let mut failed = 0;
+    for message in &due {
+        if !message.claim(db).await? {
+            continue;
+        }
+
+        if let Err(e) = message.send(db, company).await {
+            warn!(
+                "failed to send scheduled {} {} to {}: {}",
+                message.kind, message.id, message.recipient, e
+            );
+            message.release(db, &e.to_string()).await?;
+            failed += 1;
+        }
+    }
+
+    info!("sent {} of {} scheduled messages", due.len() - failed, due.len());
+
+    if failed > 0 {
+        bail!("{} scheduled messages failed to send", failed);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+
+    use super::DeliveryWindow;
+
+    fn at(time: &str) -> DateTime<Utc> {
+        time.parse::<DateTime<Utc>>().unwrap()
+    }
+
+    #[test]
+    fn test_next_open_working_hours() {
+        let tz = chrono_tz::US::Pacific;
+        let window = DeliveryWindow::WORKING_HOURS;
+
+        // Tuesday at 10am Pacific is already within working hours.
+        let during = at("2023-02-07T18:00:00Z");
+        assert!(window.contains(&tz, during));
+        assert_eq!(during, window.next_open(&tz, during));
+
+        // Tuesday at 6am Pacific waits until 9am that day.
+        assert_eq!(
+            at("2023-02-07T17:00:00Z"),
+            window.next_open(&tz, at("2023-02-07T14:00:00Z"))
+        );
+
+        // Friday at 6pm Pacific waits until 9am on Monday.
+        assert_eq!(
+            at("2023-02-13T17:00:00Z"),
+            window.next_open(&tz, at("2023-02-11T02:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_next_open_other_time_zones() {
+        // 3am in London on a Wednesday is 7pm on Tuesday in the Pacific, and waits until 9am
+        // in London.
+        let london = chrono_tz::Europe::London;
+        let window = DeliveryWindow::MORNING;
+        assert_eq!(
+            at("2023-02-08T09:00:00Z"),
+            window.next_open(&london, at("2023-02-08T03:00:00Z"))
+        );
+
+        // Noon in London is past the morning, so it waits until the next one.
+        assert!(!window.contains(&london, at("2023-02-08T12:00:00Z")));
+        assert_eq!(
+            at("2023-02-09T09:00:00Z"),
+            window.next_open(&london, at("2023-02-08T12:00:00Z"))
+        );
+
+        // Weekends count when the window is not only for weekdays.
+        let every_day = DeliveryWindow {
+            weekdays_only: false,
+            ..DeliveryWindow::WORKING_HOURS
+        };
+        assert_eq!(
+            at("2023-02-11T09:00:00Z"),
+            every_day.next_open(&london, at("2023-02-10T20:00:00Z"))
+        );
+    }
+}
@@ -0,0 +1,344 @@
+/*!
+ * The employee directory.
+ *
+ * Profiles are built from the users table, which is kept in sync with the configs, Gusto and
+ * Google Workspace, so that internal tools can look people up here instead of each keeping their
+ * own copy of the users. A profile has who someone is, what they do, who they report to and who
+ * reports to them, where they are and what time it is there.
+ *
+ * Only the city of a person is shared, their home address is never part of a profile.
+ */
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{NaiveDate, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use log::warn;
+use ring::digest;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{companies::Company, configs::User, db::Database, schema::users};
+
+/// A person in the directory.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct DirectoryProfile {
+    pub username: String,
+    pub name: String,
+    pub email: String,
+    pub title: String,
+    pub team: String,
+    /// The username of the person's manager.
+    pub manager: String,
+    pub manager_name: String,
+    /// The usernames of the people that report to the person.
+    pub reports: Vec<String>,
+    /// The city, region and country the person works from.
+    pub location: String,
+    /// The IANA time zone of `location`, empty when it could not be looked up.
+    pub timezone: String,
+    /// Their photo in Google Workspace, or their Gravatar if they do not have one.
+    pub photo: String,
+    pub github: String,
+    pub chat: String,
+    pub start_date: NaiveDate,
+}
+
+/// Filters for the directory, as query parameters. Every filter is case insensitive.
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize)]
+pub struct DirectoryQuery {
+    /// Matches the name, username, email, title, team or GitHub handle of a person.
+    #[serde(default)]
+    pub search: String,
+    /// Only people on this team.
+    #[serde(default)]
+    pub team: String,
+    /// Only people that report to the manager with this username.
+    #[serde(default)]
+    pub manager: String,
+    /// Only people whose location contains this, ie. a city or a country.
+    #[serde(default)]
+    pub location: String,
+}
+
+/// The Gravatar of an email, an identicon is shown by Gravatar when there is none.
+pub fn gravatar_url(email: &str) -> String {
+    format!(
+        "https://gravatar.com/avatar/{}?d=identicon",
+        hex::encode(digest::digest(&digest::SHA256, email.trim().to_lowercase().as_bytes()))
+    )
+}
+
+/// Where the user works from, down to the city. The work address wins over the home address.
+pub(crate) fn location(user: &User) -> String {
+    let parts = if !user.work_address_city.is_empty() {
+        [
+            &user.work_address_city,
+            &user.work_address_state,
+            &user.work_address_country,
+        ]
+    } else {
+        [
+            &user.home_address_city,
+            &user.home_address_state,
+            &user.home_address_country,
+        ]
+    };
+
+    parts
+        .iter()
+        .filter(|part| !part.is_empty())
+        .map(|part| part.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Whether a user belongs in the directory. System accounts and people that have left do not.
+fn is_listed(user: &User, today: NaiveDate) -> bool {
+    !user.is_system_account()
+        && !user.email.is_empty()
+        && user.termination_date.map(|date| date > today).unwrap_or(true)
+}
+
+/// Build the profiles of the users, without their time zones.
+pub fn profiles(users: &[User]) -> Vec<DirectoryProfile> {
+    let names = users
+        .iter()
+        .map(|user| (user.username.as_str(), user.full_name()))
+        .collect::<HashMap<_, _>>();
+
+    let mut reports: HashMap<&str, Vec<String>> = HashMap::new();
+    for user in users {
+        if !user.manager.is_empty() {
+            reports
+                .entry(user.manager.as_str())
+                .or_default()
+                .push(user.username.to_string());
+        }
+    }
+
+    let mut profiles = users
+        .iter()
+        .map(|user| {
+            let mut user_reports = reports.get(user.username.as_str()).cloned().unwrap_or_default();
+            user_reports.sort();
+
+            DirectoryProfile {
+                username: user.username.to_string(),
+                name: user.full_name(),
+                email: user.email.to_string(),
+                title: user.title.to_string(),
+                team: user.department.to_string(),
+                manager: user.manager.to_string(),
+                manager_name: names.get(user.manager.as_str()).cloned().unwrap_or_default(),
+                reports: user_reports,
+                location: location(user),
+                timezone: Default::default(),
+                photo: if user.photo_url.is_empty() {
+                    gravatar_url(&user.email)
+                } else {
+                    user.photo_url.to_string()
+                },
+                github: user.github.to_string(),
+                chat: user.chat.to_string(),
+                start_date: user.start_date,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    profiles.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+
+    profiles
+}
+
+fn contains(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.trim().to_lowercase())
+}
+
+impl DirectoryQuery {
+    /// Whether a profile passes every filter of the query.
+    pub fn matches(&self, profile: &DirectoryProfile) -> bool {
+        let search = self.search.trim().is_empty()
+            || [
+                &profile.name,
+                &profile.username,
+                &profile.email,
+                &profile.title,
+                &profile.team,
+                &profile.github,
+            ]
+            .iter()
+            .any(|field| contains(field, &self.search));
+
+        search
+            && (self.team.trim().is_empty() || profile.team.eq_ignore_ascii_case(self.team.trim()))
+            && (self.manager.trim().is_empty() || profile.manager.eq_ignore_ascii_case(self.manager.trim()))
+            && (self.location.trim().is_empty() || contains(&profile.location, &self.location))
+    }
+}
+
+/// The users that belong in the directory.
+pub async fn listed_users(db: &Database, company: &Company) -> Result<Vec<User>> {
+    let today = Utc::now().date().naive_utc();
+
+    Ok(users::dsl::users
+        .filter(users::dsl::cio_company_id.eq(company.id))
+        .load_async::<User>(db.pool())
+        .await?
+        .into_iter()
+        .filter(|user| is_listed(user, today))
+        .collect())
+}
+
+/// Fill in the time zones of the profiles from their locations. Locations are geocoded once and
+/// cached, so this only reaches out to Google for places we have not seen before.
+async fn fill_timezones(db: &Database, profiles: &mut [DirectoryProfile]) {
+    let mut timezones: HashMap<String, String> = HashMap::new();
+
+    for profile in profiles.iter_mut().filter(|profile| !profile.location.is_empty()) {
+        if let Some(timezone) = timezones.get(&profile.location) {
+            profile.timezone = timezone.to_string();
+            continue;
+        }
+
+        let timezone = match crate::geocode::timezone_for_address(db, &profile.location).await {
+            Ok(tz) => tz.name().to_string(),
+            Err(e) => {
+                warn!("failed to look up the time zone of `{}`: {}", profile.location, e);
+                String::new()
+            }
+        };
+
+        timezones.insert(profile.location.to_string(), timezone.to_string());
+        profile.timezone = timezone;
+    }
+}
+
+/// The profiles of everyone in the directory that match the query, by name.
+pub async fn directory(db: &Database, company: &Company, query: &DirectoryQuery) -> Result<Vec<DirectoryProfile>> {
+    let users = listed_users(db, company).await?;
+
+    let mut profiles = profiles(&users)
+        .into_iter()
+        .filter(|profile| query.matches(profile))
+        .collect::<Vec<_>>();
+    fill_timezones(db, &mut profiles).await;
+
+    Ok(profiles)
+}
+
+/// The profile of a single person in the directory.
+pub async fn directory_profile(db: &Database, company: &Company, username: &str) -> Result<DirectoryProfile> {
+    let users = listed_users(db, company).await?;
+
+    let mut profile = match profiles(&users)
+        .into_iter()
+        .find(|profile| profile.username == username)
+    {
+        Some(profile) => profile,
+        None => bail!("`{}` is not in the directory", username),
+    };
+    fill_timezones(db, std::slice::from_mut(&mut profile)).await;
+
+    Ok(profile)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{gravatar_url, is_listed, profiles, DirectoryQuery};
+    use crate::configs::tests::mock_user;
+
+    #[test]
+    fn test_gravatar_url() {
+        assert_eq!(gravatar_url("Jane@Example.com "), gravatar_url("jane@example.com"));
+        assert!(gravatar_url("jane@example.com").starts_with("https://gravatar.com/avatar/"));
+    }
+
+    #[test]
+    fn test_profiles() {
+        let mut jess = mock_user();
+        jess.username = "jess".to_string();
+        jess.first_name = "Jess".to_string();
+        jess.last_name = "Frazelle".to_string();
+        jess.photo_url = "https://lh3.googleusercontent.com/jess".to_string();
+        jess.home_address_street_1 = "1 Main St".to_string();
+        jess.home_address_city = "Oakland".to_string();
+        jess.home_address_state = "CA".to_string();
+        jess.home_address_country = "United States".to_string();
+
+        let mut sam = mock_user();
+        sam.username = "sam".to_string();
+        sam.first_name = "Sam".to_string();
+        sam.last_name = "Smith".to_string();
+        sam.email = "sam@example.com".to_string();
+        sam.title = "Hardware Engineer".to_string();
+        sam.department = "Hardware".to_string();
+        sam.manager = "jess".to_string();
+        sam.work_address_city = "Emeryville".to_string();
+        sam.home_address_city = "Berkeley".to_string();
+
+        let profiles = profiles(&[sam, jess]);
+        assert_eq!(
+            vec!["jess", "sam"],
+            profiles.iter().map(|p| p.username.as_str()).collect::<Vec<_>>()
+        );
+
+        assert_eq!(vec!["sam"], profiles[0].reports);
+        assert_eq!("https://lh3.googleusercontent.com/jess", profiles[0].photo);
+        assert_eq!("Oakland, CA, United States", profiles[0].location);
+
+        assert_eq!("Jess Frazelle", profiles[1].manager_name);
+        assert_eq!(gravatar_url("sam@example.com"), profiles[1].photo);
+        assert_eq!("Emeryville", profiles[1].location);
+    }
+
+    #[test]
+    fn test_directory_query_matches() {
+        let mut sam = mock_user();
+        sam.username = "sam".to_string();
+        sam.title = "Hardware Engineer".to_string();
+        sam.department = "Hardware".to_string();
+        sam.manager = "jess".to_string();
+        sam.work_address_city = "Emeryville".to_string();
+        let profile = &profiles(&[sam])[0];
+
+        assert!(DirectoryQuery::default().matches(profile));
+        assert!(DirectoryQuery {
+            search: "engineer".to_string(),
+            team: "hardware".to_string(),
+            manager: "Jess".to_string(),
+            location: "emery".to_string(),
+        }
+        .matches(profile));
+        assert!(!DirectoryQuery {
+            search: "designer".to_string(),
+            ..Default::default()
+        }
+        .matches(profile));
+        assert!(!DirectoryQuery {
+            team: "Hard".to_string(),
+            ..Default::default()
+        }
+        .matches(profile));
+    }
+
+    #[test]
+    fn test_is_listed() {
+        let today = NaiveDate::from_ymd(2023, 4, 1);
+        let mut user = mock_user();
+        assert!(is_listed(&user, today));
+
+        user.termination_date = Some(NaiveDate::from_ymd(2023, 4, 1));
+        assert!(!is_listed(&user, today));
+
+        user.termination_date = Some(NaiveDate::from_ymd(2023, 4, 14));
+        assert!(is_listed(&user, today));
+
+        user.termination_date = None;
+        user.typev = "system account".to_string();
+        assert!(!is_listed(&user, today));
+    }
+}
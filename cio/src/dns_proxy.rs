@@ -5,16 +5,39 @@ use crate::{
     cloud_dns::CloudDnsClient,
     cloudflare::CloudFlareClient,
     dns_providers::{DNSProviderOps, DnsRecord, DnsUpdateMode},
+    route53::Route53Client,
 };
 
 pub struct DnsProviderProxy {
     cloudflare: CloudFlareClient,
     cloud_dns: CloudDnsClient,
+    route53: Option<Route53Client>,
 }
 
 impl DnsProviderProxy {
     pub fn new(cloudflare: CloudFlareClient, cloud_dns: CloudDnsClient) -> Self {
-        Self { cloudflare, cloud_dns }
+        Self {
+            cloudflare,
+            cloud_dns,
+            route53: None,
+        }
+    }
+
+    /// Records for domains in a Route53 hosted zone are written to Route53 instead of
+    /// Cloudflare and Cloud DNS.
+    pub fn with_route53(mut self, route53: Route53Client) -> Self {
+        self.route53 = Some(route53);
+        self
+    }
+
+    async fn route53_for(&self, record: &DnsRecord) -> Result<Option<&Route53Client>> {
+        if let Some(route53) = &self.route53 {
+            if route53.translate_domain_to_zone(&record.name).await?.is_some() {
+                return Ok(Some(route53));
+            }
+        }
+
+        Ok(None)
     }
 }
 
@@ -22,6 +45,10 @@ impl DnsProviderProxy {
 impl DNSProviderOps for DnsProviderProxy {
     /// Ensure the record exists and has the correct information.
     async fn ensure_record(&self, record: DnsRecord, mode: DnsUpdateMode) -> Result<()> {
+        if let Some(route53) = self.route53_for(&record).await? {
+            return route53.ensure_record(record, mode).await;
+        }
+
         // Do not exit on CF failures
         if let Err(err) = self.cloudflare.ensure_record(record.clone(), mode.clone()).await {
             log::info!("Failed to ensure dns record for {} in CloudFlare. This may be expected if the domain is not configured yet. :: {}", record.name, err);
@@ -34,6 +61,10 @@ impl DNSProviderOps for DnsProviderProxy {
 
     /// Delete the record if it exists.
     async fn delete_record(&self, record: DnsRecord) -> Result<()> {
+        if let Some(route53) = self.route53_for(&record).await? {
+            return route53.delete_record(record).await;
+        }
+
         // Do not exit on CF failures
         if let Err(err) = self.cloudflare.delete_record(record.clone()).await {
             log::info!("Failed to delete dns record for {} from CloudFlare. This may be expected if the domain is not configured yet. :: {}", record.name, err);
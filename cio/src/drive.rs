@@ -0,0 +1,197 @@
+/*!
+ * Storing generated documents in Google Drive.
+ *
+ * Documents are uploaded into a company's shared drive under a fixed folder path, so the same
+ * document always ends up in the same place. The content hash of every upload is recorded, and
+ * a document whose contents have not changed is not uploaded again.
+ */
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Utc};
+use diesel::{AsChangeset, ExpressionMethods, Insertable, QueryDsl, Queryable};
+use google_drive::{
+    traits::{DriveOps, FileOps},
+    Client as GoogleDrive,
+};
+use log::info;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sodiumoxide::crypto::hash::sha256;
+
+use crate::{companies::Company, db::Database, schema::drive_documents};
+
+/// The shared drive for documents generated by automations, like RFD PDFs and interview
+/// packets.
+pub const AUTOMATED_DOCUMENTS_DRIVE: &str = "Automated Documents";
+
+/// The shared drive for signed offer letters and employee agreements.
+pub const OFFER_LETTERS_DRIVE: &str = "Offer Letters";
+
+/// A document that has been uploaded to Google Drive.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct DriveDocument {
+    pub id: i32,
+    pub shared_drive: String,
+    /// The folders the document is in, separated by `/`.
+    pub path: String,
+    pub name: String,
+    /// The hex encoded sha256 of the contents of the document.
+    pub content_hash: String,
+    pub file_id: String,
+    /// A link that can be shared with anyone that has access to the shared drive.
+    pub url: String,
+    pub uploaded_at: DateTime<Utc>,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = drive_documents)]
+pub struct NewDriveDocument {
+    pub shared_drive: String,
+    pub path: String,
+    pub name: String,
+    pub content_hash: String,
+    pub file_id: String,
+    pub url: String,
+    pub uploaded_at: DateTime<Utc>,
+    pub cio_company_id: i32,
+}
+
+/// Uploads documents into one of a company's shared drives.
+pub struct DocumentStore {
+    db: Database,
+    client: GoogleDrive,
+    company_id: i32,
+    shared_drive: String,
+    drive_id: String,
+}
+
+impl DocumentStore {
+    pub async fn new(db: &Database, company: &Company, shared_drive: &str) -> Result<Self> {
+        let client = company.authenticate_google_drive(db).await?;
+        let drive_id = client.drives().get_by_name(shared_drive).await?.id;
+
+        Ok(DocumentStore {
+            db: db.clone(),
+            client,
+            company_id: company.id,
+            shared_drive: shared_drive.to_string(),
+            drive_id,
+        })
+    }
+
+    /// The Google Drive client, for anything other than uploading documents.
+    pub fn client(&self) -> &GoogleDrive {
+        &self.client
+    }
+
+    /// The id of the shared drive.
+    pub fn drive_id(&self) -> &str {
+        &self.drive_id
+    }
+
+    /// Create the folders in `path` that do not exist yet and return the id of the last one.
+    pub async fn folder(&self, path: &[&str]) -> Result<String> {
+        let mut parent_id = String::new();
+        for folder in path {
+            parent_id = self
+                .client
+                .files()
+                .create_folder(&self.drive_id, &parent_id, folder)
+                .await?;
+        }
+
+        Ok(parent_id)
+    }
+
+    /// Upload a document into the folder at `path`, replacing any document with the same name.
+    /// If the document has not changed since it was last uploaded, the existing document is
+    /// returned instead.
+    pub async fn upload(&self, path: &[&str], name: &str, mime_type: &str, contents: &[u8]) -> Result<DriveDocument> {
+        let joined_path = path.join("/");
+        let content_hash = content_hash(contents);
+
+        let existing = drive_documents::dsl::drive_documents
+            .filter(drive_documents::dsl::cio_company_id.eq(self.company_id))
+            .filter(drive_documents::dsl::shared_drive.eq(self.shared_drive.to_string()))
+            .filter(drive_documents::dsl::path.eq(joined_path.to_string()))
+            .filter(drive_documents::dsl::name.eq(name.to_string()))
+            .first_async::<DriveDocument>(self.db.pool())
+            .await
+            .ok();
+
+        if let Some(existing) = existing {
+            if existing.content_hash == content_hash {
+                info!(
+                    "`{}/{}` in drive `{}` has not changed, skipping upload",
+                    joined_path, name, self.shared_drive
+                );
+                return Ok(existing);
+            }
+        }
+
+        let parent_id = self.folder(path).await?;
+        let file = self
+            .client
+            .files()
+            .create_or_update(&self.drive_id, &parent_id, name, mime_type, contents)
+            .await?;
+
+        let document = NewDriveDocument {
+            shared_drive: self.shared_drive.to_string(),
+            path: joined_path,
+            name: name.to_string(),
+            content_hash,
+            url: drive_link(&file.id),
+            file_id: file.id,
+            uploaded_at: Utc::now(),
+            cio_company_id: self.company_id,
+        };
+
+        let document = diesel::insert_into(drive_documents::table)
+            .values(document.clone())
+            .on_conflict((
+                drive_documents::cio_company_id,
+                drive_documents::shared_drive,
+                drive_documents::path,
+                drive_documents::name,
+            ))
+            .do_update()
+            .set(document)
+            .get_result_async::<DriveDocument>(self.db.pool())
+            .await?;
+
+        info!(
+            "uploaded `{}/{}` to drive `{}`",
+            document.path, document.name, self.shared_drive
+        );
+
+        Ok(document)
+    }
+}
+
+/// A link that opens the file in Google Drive.
+pub fn drive_link(file_id: &str) -> String {
+    format!("https://drive.google.com/open?id={}", file_id)
+}
+
+fn content_hash(contents: &[u8]) -> String {
+    hex::encode(sha256::hash(contents))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{content_hash, drive_link};
+
+    #[test]
+    fn test_content_hash() {
+        assert_eq!(content_hash(b"RFD 1"), content_hash(b"RFD 1"));
+        assert_ne!(content_hash(b"RFD 1"), content_hash(b"RFD 2"));
+        assert_eq!(64, content_hash(b"").len());
+    }
+
+    #[test]
+    fn test_drive_link() {
+        assert_eq!("https://drive.google.com/open?id=abc123", drive_link("abc123"));
+    }
+}
@@ -0,0 +1,839 @@
+/*!
+ * Sending transactional email.
+ *
+ * Every email goes through a `Mailer`, which drops recipients on the suppression list, sends the
+ * email with the configured `EmailSender` and records the message so that delivery events from
+ * the provider can be matched back to it. Templates are Handlebars, with an optional MJML body
+ * that is compiled to HTML with the `mjml` binary.
+ */
+use std::{env, fs, process::Command};
+
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use diesel::{ExpressionMethods, Insertable, QueryDsl, Queryable};
+use handlebars::Handlebars;
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    companies::Company,
+    db::Database,
+    email_suppressions::{remove_suppressed_recipients, suppress_email, SuppressionReason},
+    schema::email_messages,
+};
+
+/// The SendGrid endpoint for sending email.
+const SENDGRID_MAIL_SEND_ENDPOINT: &str = "https://api.sendgrid.com/v3/mail/send";
+
+/// The Gmail endpoint for sending email as the user the token was issued for.
+const GMAIL_SEND_ENDPOINT: &str = "https://gmail.googleapis.com/gmail/v1/users/me/messages/send";
+
+/// The scope the service account needs to send email as a user.
+const GMAIL_SEND_SCOPE: &str = "https://www.googleapis.com/auth/gmail.send";
+
+/// An email, ready to be sent.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct OutgoingEmail {
+    pub subject: String,
+    pub text: String,
+    /// Sent as an alternative to the text body, for clients that show HTML.
+    pub html: Option<String>,
+    pub to: Vec<String>,
+    pub cc: Vec<String>,
+    pub bcc: Vec<String>,
+    pub from: String,
+    /// The name of the template the email was rendered from, if any, for tracking.
+    pub template: String,
+}
+
+/// A service that can send email.
+#[async_trait]
+pub trait EmailSender {
+    /// The name the messages sent by this sender are recorded with.
+    fn provider(&self) -> &'static str;
+
+    /// Send the email and return the id the provider gave the message.
+    async fn send(&self, email: &OutgoingEmail) -> Result<String>;
+}
+
+/// Sends email through SendGrid. Delivery events are posted back to us by SendGrid's event
+/// webhook.
+pub struct SendGridSender {
+    client: reqwest::Client,
+    api_key: String,
+}
+
+impl SendGridSender {
+    pub fn new_from_env() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_key: env::var("SENDGRID_API_KEY").unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SendGridMail {
+    personalizations: Vec<SendGridPersonalization>,
+    from: SendGridAddress,
+    subject: String,
+    content: Vec<SendGridContent>,
+}
+
+#[derive(Debug, Serialize)]
+struct SendGridPersonalization {
+    to: Vec<SendGridAddress>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    cc: Vec<SendGridAddress>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    bcc: Vec<SendGridAddress>,
+}
+
+#[derive(Debug, Serialize)]
+struct SendGridAddress {
+    email: String,
+}
+
+#[derive(Debug, Serialize)]
+struct SendGridContent {
+    #[serde(rename = "type")]
+    type_: String,
+    value: String,
+}
+
+fn sendgrid_addresses(emails: &[String]) -> Vec<SendGridAddress> {
+    emails
+        .iter()
+        .map(|email| SendGridAddress {
+            email: email.to_string(),
+        })
+        .collect()
+}
+
+#[async_trait]
+impl EmailSender for SendGridSender {
+    fn provider(&self) -> &'static str {
+        "sendgrid"
+    }
+
+    async fn send(&self, email: &OutgoingEmail) -> Result<String> {
+        // SendGrid wants the plain text part first.
+        let mut content = vec![SendGridContent {
+            type_: "text/plain".to_string(),
+            value: email.text.to_string(),
+        }];
+        if let Some(html) = &email.html {
+            content.push(SendGridContent {
+                type_: "text/html".to_string(),
+                value: html.to_string(),
+            });
+        }
+
+        let resp = self
+            .client
+            .post(SENDGRID_MAIL_SEND_ENDPOINT)
+            .bearer_auth(&self.api_key)
+            .json(&SendGridMail {
+                personalizations: vec![SendGridPersonalization {
+                    to: sendgrid_addresses(&email.to),
+                    cc: sendgrid_addresses(&email.cc),
+                    bcc: sendgrid_addresses(&email.bcc),
+                }],
+                from: SendGridAddress {
+                    email: email.from.to_string(),
+                },
+                subject: email.subject.to_string(),
+                content,
+            })
+            .send()
+            .await?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            bail!(
+                "sendgrid failed to send `{}`: {} {}",
+                email.subject,
+                status,
+                resp.text().await?
+            );
+        }
+
+        // The events SendGrid sends for the message carry this id as the start of theirs.
+        Ok(resp
+            .headers()
+            .get("X-Message-Id")
+            .and_then(|id| id.to_str().ok())
+            .unwrap_or_default()
+            .to_string())
+    }
+}
+
+/// Sends email through the Gmail API, as the sender of the email. The company's service account
+/// needs to be allowed the `gmail.send` scope. Gmail does not report deliveries, so messages sent
+/// this way stay `sent`.
+pub struct GmailSender {
+    company: Company,
+    client: reqwest::Client,
+}
+
+impl GmailSender {
+    pub fn new(company: &Company) -> Self {
+        Self {
+            company: company.clone(),
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct GmailMessage {
+    raw: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GmailSentMessage {
+    #[serde(default)]
+    id: String,
+}
+
+#[async_trait]
+impl EmailSender for GmailSender {
+    fn provider(&self) -> &'static str {
+        "gmail"
+    }
+
+    async fn send(&self, email: &OutgoingEmail) -> Result<String> {
+        let token = self
+            .company
+            .get_google_service_account_token(&email.from, &[GMAIL_SEND_SCOPE])
+            .await?;
+
+        let boundary = uuid::Uuid::new_v4().simple().to_string();
+        let raw = base64::encode_config(mime_message(email, &boundary), base64::URL_SAFE);
+
+        let resp = self
+            .client
+            .post(GMAIL_SEND_ENDPOINT)
+            .bearer_auth(&token)
+            .json(&GmailMessage { raw })
+            .send()
+            .await?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            bail!(
+                "gmail failed to send `{}`: {} {}",
+                email.subject,
+                status,
+                resp.text().await?
+            );
+        }
+
+        let sent: GmailSentMessage = resp.json().await?;
+
+        Ok(sent.id)
+    }
+}
+
+/// Headers can only hold ASCII, anything else is encoded as a MIME encoded-word.
+fn encode_header(value: &str) -> String {
+    if value.is_ascii() {
+        value.to_string()
+    } else {
+        format!("=?UTF-8?B?{}?=", base64::encode(value))
+    }
+}
+
+/// Wrap the base64 of a body part at the 76 characters MIME allows per line.
+fn encode_body(body: &str) -> String {
+    base64::encode(body)
+        .as_bytes()
+        .chunks(76)
+        .map(|line| String::from_utf8_lossy(line).to_string())
+        .collect::<Vec<_>>()
+        .join("\r\n")
+}
+
+/// The RFC 2822 message Gmail sends. Gmail removes the `Bcc` header before delivering it.
+fn mime_message(email: &OutgoingEmail, boundary: &str) -> String {
+    let mut headers = vec![format!("From: {}", email.from), format!("To: {}", email.to.join(", "))];
+    if !email.cc.is_empty() {
+        headers.push(format!("Cc: {}", email.cc.join(", ")));
+    }
+    if !email.bcc.is_empty() {
+        headers.push(format!("Bcc: {}", email.bcc.join(", ")));
+    }
+    headers.push(format!("Subject: {}", encode_header(&email.subject)));
+    headers.push("MIME-Version: 1.0".to_string());
+    headers.push(format!(
+        "Content-Type: multipart/alternative; boundary=\"{}\"",
+        boundary
+    ));
+
+    let mut parts = vec![("text/plain", email.text.as_str())];
+    if let Some(html) = &email.html {
+        parts.push(("text/html", html.as_str()));
+    }
+
+    let mut message = headers.join("\r\n");
+    message.push_str("\r\n");
+    for (content_type, body) in parts {
+        message.push_str(&format!(
+            "\r\n--{}\r\nContent-Type: {}; charset=UTF-8\r\nContent-Transfer-Encoding: base64\r\n\r\n{}\r\n",
+            boundary,
+            content_type,
+            encode_body(body)
+        ));
+    }
+    message.push_str(&format!("--{}--\r\n", boundary));
+
+    message
+}
+
+/// An email template. The subject and text are Handlebars templates rendered without HTML
+/// escaping. The MJML body is optional; when it is set it is rendered with escaping and compiled to
+/// the HTML part of the email.
+#[derive(Debug, Clone, Default, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+pub struct EmailTemplate {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    pub subject: String,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub mjml: String,
+}
+
+/// A rendered template, without its recipients.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub text: String,
+    pub html: Option<String>,
+}
+
+impl EmailTemplate {
+    pub fn new(name: &str, subject: &str, text: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            subject: subject.to_string(),
+            text: text.to_string(),
+            mjml: Default::default(),
+        }
+    }
+
+    /// Render the subject, text and MJML of the template, the MJML is not compiled yet.
+    fn render_sources<T: Serialize>(&self, data: &T) -> Result<(String, String, Option<String>)> {
+        let mut plain = Handlebars::new();
+        // We are rendering plain text so we need to disable HTML escaping.
+        plain.register_escape_fn(handlebars::no_escape);
+
+        let subject = plain.render_template(&self.subject, data)?;
+        let text = plain.render_template(&self.text, data)?;
+
+        let mjml = if self.mjml.is_empty() {
+            None
+        } else {
+            let html = Handlebars::new();
+            Some(html.render_template(&self.mjml, data)?)
+        };
+
+        // A subject has to stay on one line.
+        Ok((subject.lines().collect::<Vec<_>>().join(" "), text, mjml))
+    }
+
+    pub async fn render<T: Serialize>(&self, data: &T) -> Result<RenderedEmail> {
+        let (subject, text, mjml) = self.render_sources(data)?;
+
+        let html = match mjml {
+            Some(mjml) => Some(compile_mjml(&mjml).await?),
+            None => None,
+        };
+
+        Ok(RenderedEmail { subject, text, html })
+    }
+}
+
+/// Compile MJML to HTML with the `mjml` binary.
+async fn compile_mjml(mjml: &str) -> Result<String> {
+    let mut path = env::temp_dir();
+    path.push(format!("email-{}.mjml", uuid::Uuid::new_v4().simple()));
+    fs::write(&path, mjml)?;
+
+    let args = [path.to_str().unwrap().to_string(), "--stdout".to_string()];
+    let output = tokio::task::spawn_blocking(move || Command::new("mjml").args(args).output()).await??;
+    fs::remove_file(&path)?;
+
+    if !output.status.success() {
+        bail!(
+            "mjml failed to compile the template: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// Where a message is in its delivery. A message to several recipients takes the furthest status
+/// any of them reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmailStatus {
+    /// Every recipient was on the suppression list, nothing was sent.
+    Suppressed,
+    Failed,
+    Sent,
+    Deferred,
+    Delivered,
+    Opened,
+    Bounced,
+    Dropped,
+    Complained,
+}
+
+impl EmailStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Suppressed => "suppressed",
+            Self::Failed => "failed",
+            Self::Sent => "sent",
+            Self::Deferred => "deferred",
+            Self::Delivered => "delivered",
+            Self::Opened => "opened",
+            Self::Bounced => "bounced",
+            Self::Dropped => "dropped",
+            Self::Complained => "complained",
+        }
+    }
+
+    pub fn parse(status: &str) -> Option<Self> {
+        Some(match status {
+            "suppressed" => Self::Suppressed,
+            "failed" => Self::Failed,
+            "sent" => Self::Sent,
+            "deferred" => Self::Deferred,
+            "delivered" => Self::Delivered,
+            "opened" => Self::Opened,
+            "bounced" => Self::Bounced,
+            "dropped" => Self::Dropped,
+            "complained" => Self::Complained,
+            _ => return None,
+        })
+    }
+
+    /// The status a SendGrid event moves a message to. `processed` and clicks are left out, the
+    /// first is the same as being sent and a click is always after an open.
+    ///
+    /// FROM: https://docs.sendgrid.com/for-developers/tracking-events/event
+    pub fn from_sendgrid_event(event: &str) -> Option<Self> {
+        Some(match event {
+            "deferred" => Self::Deferred,
+            "delivered" => Self::Delivered,
+            "open" => Self::Opened,
+            "bounce" => Self::Bounced,
+            "dropped" => Self::Dropped,
+            "spamreport" => Self::Complained,
+            _ => return None,
+        })
+    }
+
+    /// Events arrive out of order, so a status only replaces one that comes before it.
+    fn rank(&self) -> u8 {
+        match self {
+            Self::Suppressed | Self::Failed | Self::Sent => 0,
+            Self::Deferred => 1,
+            Self::Delivered => 2,
+            Self::Opened => 3,
+            Self::Bounced | Self::Dropped | Self::Complained => 4,
+        }
+    }
+
+    pub fn replaces(&self, current: Self) -> bool {
+        self.rank() >= current.rank()
+    }
+}
+
+/// An email we sent, or decided not to send, and how far it got.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct EmailMessage {
+    pub id: i32,
+    pub provider: String,
+    pub provider_message_id: String,
+    pub template: String,
+    pub subject: String,
+    pub recipients: Vec<String>,
+    pub status: String,
+    pub status_reason: String,
+    pub sent_at: DateTime<Utc>,
+    pub status_updated_at: DateTime<Utc>,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = email_messages)]
+struct NewEmailMessage {
+    provider: String,
+    provider_message_id: String,
+    template: String,
+    subject: String,
+    recipients: Vec<String>,
+    status: String,
+    status_reason: String,
+    cio_company_id: i32,
+}
+
+/// Sends email, dropping any recipients that are on the suppression list, and records every
+/// message that was sent.
+pub struct Mailer<'a> {
+    db: &'a Database,
+    company_id: i32,
+    sender: Box<dyn EmailSender + Send + Sync>,
+}
+
+impl<'a> Mailer<'a> {
+    /// Send with the provider in `EMAIL_SENDER`, either `sendgrid` or `gmail`. SendGrid is used
+    /// when it is not set.
+    pub fn new(db: &'a Database, company: &Company) -> Self {
+        let sender: Box<dyn EmailSender + Send + Sync> = match env::var("EMAIL_SENDER").as_deref() {
+            Ok("gmail") => Box::new(GmailSender::new(company)),
+            _ => Box::new(SendGridSender::new_from_env()),
+        };
+
+        Self::with_sender(db, company, sender)
+    }
+
+    pub fn with_sender(db: &'a Database, company: &Company, sender: Box<dyn EmailSender + Send + Sync>) -> Self {
+        Self {
+            db,
+            company_id: company.id,
+            sender,
+        }
+    }
+
+    /// Send a plain text email. Nothing is sent when every recipient in `to` is suppressed.
+    pub async fn send_plain_text(
+        &self,
+        subject: &str,
+        message: &str,
+        to: &[String],
+        cc: &[String],
+        bcc: &[String],
+        from: &str,
+    ) -> Result<()> {
+        self.send(OutgoingEmail {
+            subject: subject.to_string(),
+            text: message.to_string(),
+            html: None,
+            to: to.to_vec(),
+            cc: cc.to_vec(),
+            bcc: bcc.to_vec(),
+            from: from.to_string(),
+            template: Default::default(),
+        })
+        .await
+    }
+
+    /// Render a template with `data` and send it.
+    pub async fn send_template<T: Serialize>(
+        &self,
+        template: &EmailTemplate,
+        data: &T,
+        to: &[String],
+        cc: &[String],
+        bcc: &[String],
+        from: &str,
+    ) -> Result<()> {
+        let rendered = template.render(data).await?;
+
+        self.send(OutgoingEmail {
+            subject: rendered.subject,
+            text: rendered.text,
+            html: rendered.html,
+            to: to.to_vec(),
+            cc: cc.to_vec(),
+            bcc: bcc.to_vec(),
+            from: from.to_string(),
+            template: template.name.to_string(),
+        })
+        .await
+    }
+
+    pub async fn send(&self, mut email: OutgoingEmail) -> Result<()> {
+        let recipients = email
+            .to
+            .iter()
+            .chain(email.cc.iter())
+            .chain(email.bcc.iter())
+            .cloned()
+            .collect::<Vec<_>>();
+
+        remove_suppressed_recipients(self.db, self.company_id, &mut email).await?;
+        if email.to.is_empty() {
+            info!(
+                "skipping email `{}` as all of its recipients are suppressed",
+                email.subject
+            );
+            self.record(&email, recipients, "", EmailStatus::Suppressed, "").await;
+            return Ok(());
+        }
+
+        dedupe_recipients(&mut email);
+
+        match self.sender.send(&email).await {
+            Ok(message_id) => {
+                self.record(&email, recipients, &message_id, EmailStatus::Sent, "")
+                    .await;
+                Ok(())
+            }
+            Err(e) => {
+                self.record(&email, recipients, "", EmailStatus::Failed, &e.to_string())
+                    .await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Failing to record a message does not fail sending it, it has already gone out.
+    async fn record(
+        &self,
+        email: &OutgoingEmail,
+        recipients: Vec<String>,
+        message_id: &str,
+        status: EmailStatus,
+        reason: &str,
+    ) {
+        let result = diesel::insert_into(email_messages::table)
+            .values(NewEmailMessage {
+                provider: self.sender.provider().to_string(),
+                provider_message_id: message_id.to_string(),
+                template: email.template.to_string(),
+                subject: email.subject.to_string(),
+                recipients,
+                status: status.as_str().to_string(),
+                status_reason: reason.to_string(),
+                cio_company_id: self.company_id,
+            })
+            .execute_async(self.db.pool())
+            .await;
+
+        if let Err(e) = result {
+            warn!("failed to record email `{}`: {}", email.subject, e);
+        }
+    }
+}
+
+/// SendGrid rejects an email with the same address in more than one of to, cc and bcc.
+fn dedupe_recipients(email: &mut OutgoingEmail) {
+    let mut seen: Vec<String> = Vec::new();
+
+    for list in [&mut email.to, &mut email.cc, &mut email.bcc] {
+        list.retain(|address| {
+            let address = address.trim().to_lowercase();
+            if seen.contains(&address) {
+                false
+            } else {
+                seen.push(address);
+                true
+            }
+        });
+    }
+}
+
+/// An event from SendGrid's event webhook.
+///
+/// FROM: https://docs.sendgrid.com/for-developers/tracking-events/event
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct SendGridEvent {
+    #[serde(default)]
+    pub email: String,
+    #[serde(default)]
+    pub event: String,
+    #[serde(default)]
+    pub sg_message_id: String,
+    #[serde(default)]
+    pub timestamp: i64,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub reason: String,
+}
+
+impl SendGridEvent {
+    /// The event's message id is the id returned when the email was sent, followed by a `.` and
+    /// the id of the part of SendGrid that handled it.
+    fn message_id(&self) -> &str {
+        self.sg_message_id.split('.').next().unwrap_or_default()
+    }
+
+    fn suppression_reason(&self) -> Option<SuppressionReason> {
+        match self.event.as_str() {
+            "bounce" => Some(SuppressionReason::Bounced),
+            "spamreport" => Some(SuppressionReason::Complained),
+            "unsubscribe" | "group_unsubscribe" => Some(SuppressionReason::Unsubscribed),
+            _ => None,
+        }
+    }
+}
+
+/// Update the messages the events are for, and suppress the addresses that bounced, complained
+/// or unsubscribed.
+pub async fn record_sendgrid_events(db: &Database, events: &[SendGridEvent]) -> Result<()> {
+    for event in events {
+        if event.message_id().is_empty() {
+            continue;
+        }
+
+        let message = match email_messages::dsl::email_messages
+            .filter(email_messages::dsl::provider.eq("sendgrid"))
+            .filter(email_messages::dsl::provider_message_id.eq(event.message_id()))
+            .first_async::<EmailMessage>(db.pool())
+            .await
+        {
+            Ok(message) => message,
+            Err(_) => {
+                info!("ignoring sendgrid `{}` event for an unknown message", event.event);
+                continue;
+            }
+        };
+
+        let at = Utc.timestamp(event.timestamp, 0);
+
+        if let Some(reason) = event.suppression_reason() {
+            suppress_email(db, message.cio_company_id, &event.email, reason, "sendgrid", at).await?;
+        }
+
+        let status = match EmailStatus::from_sendgrid_event(&event.event) {
+            Some(status) => status,
+            None => continue,
+        };
+        let current = EmailStatus::parse(&message.status).unwrap_or(EmailStatus::Sent);
+        if !status.replaces(current) {
+            continue;
+        }
+
+        diesel::update(email_messages::dsl::email_messages)
+            .filter(email_messages::dsl::id.eq(message.id))
+            .set((
+                email_messages::dsl::status.eq(status.as_str()),
+                email_messages::dsl::status_reason.eq(&event.reason),
+                email_messages::dsl::status_updated_at.eq(at),
+            ))
+            .execute_async(db.pool())
+            .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{dedupe_recipients, mime_message, EmailStatus, EmailTemplate, OutgoingEmail, SendGridEvent};
+
+    fn email() -> OutgoingEmail {
+        OutgoingEmail {
+            subject: "Your package is on the way".to_string(),
+            text: "Tracking: https://example.com".to_string(),
+            html: None,
+            to: vec!["jane@example.com".to_string()],
+            cc: vec!["packages@example.com".to_string(), "Jane@example.com".to_string()],
+            bcc: vec!["packages@example.com".to_string()],
+            from: "packages@example.com".to_string(),
+            template: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_render_template() {
+        let template = EmailTemplate::new(
+            "huddle_reminder",
+            "Reminder {{huddle}} huddle tomorrow",
+            "Topics: {{#each topics}}{{this}}; {{/each}}",
+        );
+
+        let (subject, text, mjml) = template
+            .render_sources(&json!({ "huddle": "hw", "topics": ["<fans>", "racks"] }))
+            .unwrap();
+        assert_eq!("Reminder hw huddle tomorrow", subject);
+        assert_eq!("Topics: <fans>; racks; ", text);
+        assert_eq!(None, mjml);
+
+        // The MJML body is HTML, so values are escaped there.
+        let template = EmailTemplate {
+            mjml: "<mj-text>{{huddle}}</mj-text>".to_string(),
+            ..template
+        };
+        let (_, _, mjml) = template
+            .render_sources(&json!({ "huddle": "<hw>", "topics": [] }))
+            .unwrap();
+        assert_eq!(Some("<mj-text>&lt;hw&gt;</mj-text>".to_string()), mjml);
+    }
+
+    #[test]
+    fn test_dedupe_recipients() {
+        let mut email = email();
+        dedupe_recipients(&mut email);
+
+        assert_eq!(vec!["jane@example.com"], email.to);
+        assert_eq!(vec!["packages@example.com"], email.cc);
+        assert!(email.bcc.is_empty());
+    }
+
+    #[test]
+    fn test_mime_message() {
+        let mut email = email();
+        email.subject = "Votre colis est en route ✈".to_string();
+        email.html = Some("<p>Tracking</p>".to_string());
+
+        let message = mime_message(&email, "b1");
+
+        assert!(message.starts_with("From: packages@example.com\r\nTo: jane@example.com\r\n"));
+        assert!(message.contains("\r\nBcc: packages@example.com\r\n"));
+        assert!(message.contains(&format!(
+            "\r\nSubject: =?UTF-8?B?{}?=\r\n",
+            base64::encode("Votre colis est en route ✈")
+        )));
+        assert!(message.contains("Content-Type: multipart/alternative; boundary=\"b1\""));
+        assert!(message.contains(&format!(
+            "--b1\r\nContent-Type: text/html; charset=UTF-8\r\nContent-Transfer-Encoding: base64\r\n\r\n{}\r\n",
+            base64::encode("<p>Tracking</p>")
+        )));
+        assert!(message.ends_with("--b1--\r\n"));
+    }
+
+    #[test]
+    fn test_sendgrid_events() {
+        let event: SendGridEvent = serde_json::from_value(json!({
+            "email": "jane@example.com",
+            "timestamp": 1680000000,
+            "event": "bounce",
+            "sg_message_id": "14c5d75ce93.dfd.64b469.filter0001.16648.5515E0B88.0",
+            "reason": "550 5.1.1 unknown user",
+            "sg_event_id": "ZGVsaXZlcmVk"
+        }))
+        .unwrap();
+
+        assert_eq!("14c5d75ce93", event.message_id());
+        assert!(event.suppression_reason().is_some());
+        assert_eq!(
+            Some(EmailStatus::Bounced),
+            EmailStatus::from_sendgrid_event(&event.event)
+        );
+        assert_eq!(None, EmailStatus::from_sendgrid_event("processed"));
+    }
+
+    #[test]
+    fn test_status_order() {
+        assert!(EmailStatus::Delivered.replaces(EmailStatus::Sent));
+        assert!(EmailStatus::Delivered.replaces(EmailStatus::Deferred));
+        assert!(EmailStatus::Opened.replaces(EmailStatus::Delivered));
+        // A delivery that arrives after the open does not undo it.
+        assert!(!EmailStatus::Delivered.replaces(EmailStatus::Opened));
+        assert!(EmailStatus::Complained.replaces(EmailStatus::Opened));
+        assert!(!EmailStatus::Deferred.replaces(EmailStatus::Bounced));
+
+        for status in [EmailStatus::Suppressed, EmailStatus::Opened, EmailStatus::Complained] {
+            assert_eq!(Some(status), EmailStatus::parse(status.as_str()));
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, Insertable, QueryDsl, Queryable};
+use log::info;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::{db::Database, email::OutgoingEmail, schema::email_suppressions};
+
+/// An address that we must not send email to, no matter which system is sending it.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct EmailSuppression {
+    pub id: i32,
+    pub email: String,
+    pub reason: String,
+    /// The system that reported the suppression, ie. `mailchimp`.
+    pub source: String,
+    pub suppressed_at: DateTime<Utc>,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = email_suppressions)]
+pub struct NewEmailSuppression {
+    pub email: String,
+    pub reason: String,
+    pub source: String,
+    pub suppressed_at: DateTime<Utc>,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuppressionReason {
+    Unsubscribed,
+    Bounced,
+    Complained,
+}
+
+impl fmt::Display for SuppressionReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Unsubscribed => write!(f, "unsubscribed"),
+            Self::Bounced => write!(f, "bounced"),
+            Self::Complained => write!(f, "complained"),
+        }
+    }
+}
+
+/// Addresses are compared case insensitively.
+fn normalize(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// Record that an address must no longer be sent email. The first report for an address is
+/// kept, later reports from other sources are ignored.
+pub async fn suppress_email(
+    db: &Database,
+    company_id: i32,
+    email: &str,
+    reason: SuppressionReason,
+    source: &str,
+    suppressed_at: DateTime<Utc>,
+) -> Result<()> {
+    diesel::insert_into(email_suppressions::table)
+        .values(NewEmailSuppression {
+            email: normalize(email),
+            reason: reason.to_string(),
+            source: source.to_string(),
+            suppressed_at,
+            cio_company_id: company_id,
+        })
+        .on_conflict((email_suppressions::email, email_suppressions::cio_company_id))
+        .do_nothing()
+        .execute_async(db.pool())
+        .await?;
+
+    info!("suppressed email to {} ({} via {})", email, reason, source);
+
+    Ok(())
+}
+
+pub async fn is_suppressed(db: &Database, company_id: i32, email: &str) -> Result<bool> {
+    Ok(filter_suppressed(db, company_id, &[email.to_string()])
+        .await?
+        .is_empty())
+}
+
+/// Take every suppressed address out of the recipients of `email`, whether it is in `to`, `cc`
+/// or `bcc`. This is what keeps email from going to suppressed addresses, whatever sends it.
+pub async fn remove_suppressed_recipients(db: &Database, company_id: i32, email: &mut OutgoingEmail) -> Result<()> {
+    email.to = filter_suppressed(db, company_id, &email.to).await?;
+    email.cc = filter_suppressed(db, company_id, &email.cc).await?;
+    email.bcc = filter_suppressed(db, company_id, &email.bcc).await?;
+
+    Ok(())
+}
+
+/// Return the given addresses with every suppressed address removed.
+pub async fn filter_suppressed(db: &Database, company_id: i32, emails: &[String]) -> Result<Vec<String>> {
+    if emails.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let suppressed = email_suppressions::dsl::email_suppressions
+        .filter(email_suppressions::dsl::cio_company_id.eq(company_id))
+        .filter(email_suppressions::dsl::email.eq_any(emails.iter().map(|email| normalize(email))))
+        .select(email_suppressions::dsl::email)
+        .load_async::<String>(db.pool())
+        .await?;
+
+    Ok(emails
+        .iter()
+        .filter(|email| !suppressed.contains(&normalize(email)))
+        .cloned()
+        .collect())
+}
@@ -0,0 +1,460 @@
+/*!
+ * Flagging spend that finance should look at.
+ *
+ * Once a week the card transactions and reimbursements synced from Ramp are checked for charges
+ * that look duplicated, charges at merchants or in categories that are out of policy, and
+ * departments whose spend for the week is far above what they usually spend. What is found is
+ * posted to the finance channel, with a link to each transaction's record in Airtable.
+ */
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use log::info;
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    app_config::{ExpenseAnomaliesConfig, FinanceConfig},
+    companies::Company,
+    configs::User,
+    db::Database,
+    finance::{clean_vendor_name, CreditCardTransaction, ExpensedItem},
+    schema::{credit_card_transactions, expensed_items, users},
+};
+
+/// A card transaction or a reimbursement, as far as the report is concerned.
+#[derive(Debug, Clone, PartialEq)]
+struct Expense {
+    transaction_id: String,
+    employee_email: String,
+    department: String,
+    merchant: String,
+    category: String,
+    amount: f32,
+    time: DateTime<Utc>,
+    /// The link to the record in Airtable, when it has one.
+    link: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Anomaly {
+    /// The later charge looks like a repeat of the earlier one.
+    DuplicateCharge {
+        earlier: Expense,
+        later: Expense,
+    },
+    OutOfPolicy {
+        expense: Expense,
+        reason: String,
+    },
+    SpendSpike {
+        department: String,
+        spend: f32,
+        usual: f32,
+    },
+}
+
+fn record_link(company: &Company, record_id: &str) -> Option<String> {
+    if record_id.is_empty() {
+        None
+    } else {
+        Some(format!(
+            "https://airtable.com/{}/{}",
+            company.airtable_base_id_finance, record_id
+        ))
+    }
+}
+
+/// Charges by the same person, at the same merchant, for the same amount, within `window` of each
+/// other. Only pairs whose later charge is at or after `since` are returned, so that a pair is
+/// reported once.
+fn find_duplicates(expenses: &[Expense], window: Duration, since: DateTime<Utc>) -> Vec<Anomaly> {
+    let mut by_charge: BTreeMap<(String, String, i64), Vec<&Expense>> = BTreeMap::new();
+    for expense in expenses {
+        by_charge
+            .entry((
+                expense.employee_email.to_lowercase(),
+                expense.merchant.to_lowercase(),
+                (expense.amount * 100.0).round() as i64,
+            ))
+            .or_default()
+            .push(expense);
+    }
+
+    let mut duplicates = vec![];
+    for charges in by_charge.values_mut() {
+        charges.sort_by_key(|expense| expense.time);
+        for pair in charges.windows(2) {
+            let (earlier, later) = (pair[0], pair[1]);
+            if later.time >= since && later.time - earlier.time <= window {
+                duplicates.push(Anomaly::DuplicateCharge {
+                    earlier: earlier.clone(),
+                    later: later.clone(),
+                });
+            }
+        }
+    }
+
+    duplicates
+}
+
+fn find_out_of_policy(expenses: &[Expense], config: &ExpenseAnomaliesConfig) -> Vec<Anomaly> {
+    expenses
+        .iter()
+        .filter_map(|expense| {
+            let merchant = expense.merchant.to_lowercase();
+            let reason = if let Some(disallowed) = config
+                .disallowed_merchants
+                .iter()
+                .find(|disallowed| merchant.contains(&disallowed.to_lowercase()))
+            {
+                format!("`{}` is not an allowed merchant", disallowed)
+            } else if let Some(disallowed) = config
+                .disallowed_categories
+                .iter()
+                .find(|disallowed| disallowed.eq_ignore_ascii_case(&expense.category))
+            {
+                format!("`{}` is not an allowed category", disallowed)
+            } else {
+                return None;
+            };
+
+            Some(Anomaly::OutOfPolicy {
+                expense: expense.clone(),
+                reason,
+            })
+        })
+        .collect()
+}
+
+/// Departments whose spend in the week from `week_start` is a spike compared to their weekly
+/// average over the weeks before it. Weeks before a department spent anything do not count
+/// toward its average.
+fn find_spend_spikes(expenses: &[Expense], week_start: DateTime<Utc>, config: &ExpenseAnomaliesConfig) -> Vec<Anomaly> {
+    let baseline_start = week_start - Duration::weeks(config.spike_baseline_weeks);
+
+    let mut this_week: BTreeMap<&str, f32> = BTreeMap::new();
+    let mut before: BTreeMap<&str, f32> = BTreeMap::new();
+    for expense in expenses {
+        if expense.department.is_empty() || expense.time < baseline_start {
+            continue;
+        }
+        if expense.time >= week_start + Duration::weeks(1) {
+            continue;
+        }
+
+        let totals = if expense.time >= week_start {
+            &mut this_week
+        } else {
+            &mut before
+        };
+        *totals.entry(expense.department.as_str()).or_default() += expense.amount;
+    }
+
+    this_week
+        .into_iter()
+        .filter_map(|(department, spend)| {
+            let usual = before.get(department).copied().unwrap_or_default() / config.spike_baseline_weeks as f32;
+            if spend >= config.spike_min_amount && usual > 0.0 && spend >= usual * config.spike_ratio {
+                Some(Anomaly::SpendSpike {
+                    department: department.to_string(),
+                    spend,
+                    usual,
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn expense_line(expense: &Expense) -> String {
+    let what = format!("{} ${:.2}", expense.merchant, expense.amount);
+    let what = match &expense.link {
+        Some(link) => format!("<{}|{}>", link, what),
+        // Without a record to link to, the id is what finds the transaction in Ramp.
+        None => format!("{} (`{}`)", what, expense.transaction_id),
+    };
+
+    format!(
+        "{} by {} on {}",
+        what,
+        expense.employee_email,
+        expense.time.format("%a %b %-d")
+    )
+}
+
+fn section(text: String) -> MessageBlock {
+    MessageBlock {
+        block_type: MessageBlockType::Section,
+        text: Some(MessageBlockText {
+            text_type: MessageType::Markdown,
+            text,
+        }),
+        elements: Default::default(),
+        accessory: Default::default(),
+        block_id: Default::default(),
+        fields: Default::default(),
+    }
+}
+
+fn elapsed(duration: Duration) -> String {
+    if duration < Duration::hours(1) {
+        format!("{} minutes", duration.num_minutes())
+    } else {
+        format!("{} hours", duration.num_hours())
+    }
+}
+
+fn report_message(channel: &str, week_start: NaiveDate, anomalies: &[Anomaly]) -> FormattedMessage {
+    let mut duplicates = vec![];
+    let mut out_of_policy = vec![];
+    let mut spikes = vec![];
+    for anomaly in anomalies {
+        match anomaly {
+            Anomaly::DuplicateCharge { earlier, later } => duplicates.push(format!(
+                "• {}, {} after {}",
+                expense_line(later),
+                elapsed(later.time - earlier.time),
+                expense_line(earlier)
+            )),
+            Anomaly::OutOfPolicy { expense, reason } => {
+                out_of_policy.push(format!("• {}: {}", expense_line(expense), reason))
+            }
+            Anomaly::SpendSpike {
+                department,
+                spend,
+                usual,
+            } => spikes.push(format!(
+                "• *{}* spent `${:.2}`, against `${:.2}` in a usual week",
+                department, spend, usual
+            )),
+        }
+    }
+
+    let mut blocks = vec![MessageBlock {
+        block_type: MessageBlockType::Header,
+        text: Some(MessageBlockText {
+            text_type: MessageType::PlainText,
+            text: format!("Expense report for the week of {}", week_start.format("%B %-d")),
+        }),
+        elements: Default::default(),
+        accessory: Default::default(),
+        block_id: Default::default(),
+        fields: Default::default(),
+    }];
+    if anomalies.is_empty() {
+        blocks.push(section("Nothing looked out of the ordinary this week.".to_string()));
+    }
+    for (title, lines) in [
+        ("Possible duplicate charges", duplicates),
+        ("Out of policy", out_of_policy),
+        ("Spend spikes", spikes),
+    ] {
+        if !lines.is_empty() {
+            blocks.push(section(format!("*{}*\n{}", title, lines.join("\n"))));
+        }
+    }
+
+    FormattedMessage {
+        channel: channel.to_string(),
+        blocks,
+        attachments: Default::default(),
+    }
+}
+
+/// Check the past week's expenses for anomalies, and post what was found to the finance channel.
+pub async fn send_expense_report(db: &Database, company: &Company, config: &FinanceConfig) -> Result<()> {
+    let anomalies_config = &config.expense_anomalies;
+
+    // The report covers the week up to the start of today.
+    let today = Utc::now().naive_utc().date();
+    let week_start = today - Duration::weeks(1);
+    let week_start_time = DateTime::<Utc>::from_utc(week_start.and_hms(0, 0, 0), Utc);
+    let from = week_start_time
+        - Duration::weeks(anomalies_config.spike_baseline_weeks)
+        - Duration::hours(anomalies_config.duplicate_window_hours);
+
+    let departments = users::dsl::users
+        .filter(users::dsl::cio_company_id.eq(company.id))
+        .load_async::<User>(db.pool())
+        .await?
+        .into_iter()
+        .map(|user| (user.email.to_lowercase(), user.department))
+        .collect::<HashMap<_, _>>();
+    let department = |email: &str| departments.get(&email.to_lowercase()).cloned().unwrap_or_default();
+
+    let transactions = credit_card_transactions::dsl::credit_card_transactions
+        .filter(credit_card_transactions::dsl::cio_company_id.eq(company.id))
+        .filter(credit_card_transactions::dsl::time.ge(from))
+        .filter(credit_card_transactions::dsl::state.ne("DECLINED".to_string()))
+        .load_async::<CreditCardTransaction>(db.pool())
+        .await?;
+    let reimbursements = expensed_items::dsl::expensed_items
+        .filter(expensed_items::dsl::cio_company_id.eq(company.id))
+        .filter(expensed_items::dsl::time.ge(from))
+        .load_async::<ExpensedItem>(db.pool())
+        .await?;
+
+    let mut expenses = transactions
+        .into_iter()
+        .map(|transaction| Expense {
+            department: department(&transaction.employee_email),
+            merchant: clean_vendor_name(&transaction.merchant_name, config),
+            link: record_link(company, &transaction.airtable_record_id),
+            transaction_id: transaction.transaction_id,
+            employee_email: transaction.employee_email,
+            category: transaction.category_name,
+            amount: transaction.amount,
+            time: transaction.time,
+        })
+        .collect::<Vec<_>>();
+    expenses.extend(reimbursements.into_iter().map(|item| Expense {
+        department: department(&item.employee_email),
+        merchant: clean_vendor_name(&item.merchant_name, config),
+        link: record_link(company, &item.airtable_record_id),
+        transaction_id: item.transaction_id,
+        employee_email: item.employee_email,
+        category: item.category_name,
+        amount: item.amount,
+        time: item.time,
+    }));
+
+    let this_week = expenses
+        .iter()
+        .filter(|expense| expense.time >= week_start_time)
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let mut anomalies = find_duplicates(
+        &expenses,
+        Duration::hours(anomalies_config.duplicate_window_hours),
+        week_start_time,
+    );
+    anomalies.extend(find_out_of_policy(&this_week, anomalies_config));
+    anomalies.extend(find_spend_spikes(&expenses, week_start_time, anomalies_config));
+
+    let channel = if anomalies_config.slack_channel.is_empty() {
+        company.slack_channel_finance.to_string()
+    } else {
+        anomalies_config.slack_channel.to_string()
+    };
+    company
+        .post_to_slack_channel(db, &report_message(&channel, week_start, &anomalies))
+        .await?;
+
+    info!(
+        "posted the expense report for the week of {} with {} anomalies",
+        week_start,
+        anomalies.len()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::{find_duplicates, find_out_of_policy, find_spend_spikes, Anomaly, Expense};
+    use crate::app_config::ExpenseAnomaliesConfig;
+
+    fn expense(id: &str, merchant: &str, amount: f32, days: i64) -> Expense {
+        Expense {
+            transaction_id: id.to_string(),
+            employee_email: "random@example.com".to_string(),
+            department: "Engineering".to_string(),
+            merchant: merchant.to_string(),
+            category: "Software".to_string(),
+            amount,
+            time: Utc.ymd(2023, 3, 20).and_hms(12, 0, 0) + Duration::days(days),
+            link: None,
+        }
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let since = Utc.ymd(2023, 3, 20).and_hms(0, 0, 0);
+        let expenses = vec![
+            expense("1", "GitHub", 21.0, 0),
+            expense("2", "GitHub", 21.0, 1),
+            // Too long after the last one.
+            expense("3", "GitHub", 21.0, 5),
+            expense("4", "GitHub", 4.0, 1),
+            // Before the week being reported on.
+            expense("5", "Zoom", 15.0, -3),
+            expense("6", "Zoom", 15.0, -2),
+        ];
+
+        let duplicates = find_duplicates(&expenses, Duration::hours(72), since);
+        assert_eq!(
+            vec![Anomaly::DuplicateCharge {
+                earlier: expenses[0].clone(),
+                later: expenses[1].clone(),
+            }],
+            duplicates
+        );
+    }
+
+    #[test]
+    fn test_find_out_of_policy() {
+        let config = ExpenseAnomaliesConfig {
+            disallowed_merchants: vec!["casino".to_string()],
+            disallowed_categories: vec!["Gambling".to_string()],
+            ..Default::default()
+        };
+
+        let mut betting = expense("2", "Betting Shop", 50.0, 0);
+        betting.category = "gambling".to_string();
+        let expenses = vec![
+            expense("1", "Lucky Casino", 100.0, 0),
+            betting,
+            expense("3", "GitHub", 21.0, 0),
+        ];
+
+        let flagged = find_out_of_policy(&expenses, &config)
+            .into_iter()
+            .map(|anomaly| match anomaly {
+                Anomaly::OutOfPolicy { expense, reason } => (expense.transaction_id, reason),
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("1".to_string(), "`casino` is not an allowed merchant".to_string()),
+                ("2".to_string(), "`Gambling` is not an allowed category".to_string()),
+            ],
+            flagged
+        );
+    }
+
+    #[test]
+    fn test_find_spend_spikes() {
+        let config = ExpenseAnomaliesConfig {
+            spike_baseline_weeks: 2,
+            ..Default::default()
+        };
+        let week_start = Utc.ymd(2023, 3, 20).and_hms(0, 0, 0);
+
+        // 1000 a week before, 2500 this week.
+        let mut expenses = vec![
+            expense("1", "AWS", 1000.0, -10),
+            expense("2", "AWS", 1000.0, -3),
+            expense("3", "AWS", 2500.0, 1),
+        ];
+        assert_eq!(
+            vec![Anomaly::SpendSpike {
+                department: "Engineering".to_string(),
+                spend: 2500.0,
+                usual: 1000.0,
+            }],
+            find_spend_spikes(&expenses, week_start, &config)
+        );
+
+        // Not enough of a spike.
+        expenses[2].amount = 1900.0;
+        assert!(find_spend_spikes(&expenses, week_start, &config).is_empty());
+    }
+}
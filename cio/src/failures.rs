@@ -0,0 +1,171 @@
+/*!
+ * Finding the webhook events and jobs that failed, ie. while a provider was down, so that they
+ * can be looked at and tried again.
+ *
+ * Failures are told apart by a rough class of their error. Errors reach us as text, from any
+ * number of API clients, so the class is a best guess from what the text mentions.
+ */
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorClass {
+    /// A provider turned us away for sending too many requests.
+    RateLimited,
+    /// A request took too long.
+    Timeout,
+    /// We could not reach a provider at all.
+    Connection,
+    /// A provider failed with a `5xx`.
+    ServerError,
+    /// A provider did not accept our credentials.
+    Unauthorized,
+    /// Something we asked for does not exist.
+    NotFound,
+    Other,
+}
+
+impl ErrorClass {
+    /// Guess the class of an error from its message. When a message mentions more than one
+    /// class, the more specific ones win, ie. a `503` that mentions a timeout is a timeout.
+    pub fn of(message: &str) -> Self {
+        let message = message.to_lowercase();
+        let mentions = |needles: &[&str]| needles.iter().any(|needle| message.contains(needle));
+        let mentions_status = |codes: &[&str]| codes.iter().any(|code| mentions_status_code(&message, code));
+
+        if mentions_status(&["429"]) || mentions(&["too many requests", "rate limit", "ratelimit"]) {
+            ErrorClass::RateLimited
+        } else if mentions(&["timed out", "timeout", "deadline has elapsed"]) {
+            ErrorClass::Timeout
+        } else if mentions(&[
+            "connection refused",
+            "connection reset",
+            "connection closed",
+            "error trying to connect",
+            "dns error",
+            "broken pipe",
+        ]) {
+            ErrorClass::Connection
+        } else if mentions_status(&["500", "502", "503", "504"])
+            || mentions(&["internal server error", "bad gateway", "service unavailable"])
+        {
+            ErrorClass::ServerError
+        } else if mentions_status(&["401", "403"]) || mentions(&["unauthorized", "forbidden", "bad credentials"]) {
+            ErrorClass::Unauthorized
+        } else if mentions_status(&["404"]) || mentions(&["not found"]) {
+            ErrorClass::NotFound
+        } else {
+            ErrorClass::Other
+        }
+    }
+
+    /// Whether failures of this class are likely to succeed when they are tried again later.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            ErrorClass::RateLimited | ErrorClass::Timeout | ErrorClass::Connection | ErrorClass::ServerError
+        )
+    }
+}
+
+/// Whether `message` has `code` in it as a number of its own, rather than as part of a longer
+/// number like an id.
+fn mentions_status_code(message: &str, code: &str) -> bool {
+    message.match_indices(code).any(|(i, _)| {
+        let before = message[..i].chars().next_back();
+        let after = message[i + code.len()..].chars().next();
+
+        !before.map_or(false, |c| c.is_ascii_digit()) && !after.map_or(false, |c| c.is_ascii_digit())
+    })
+}
+
+impl fmt::Display for ErrorClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ErrorClass::RateLimited => "rate_limited",
+            ErrorClass::Timeout => "timeout",
+            ErrorClass::Connection => "connection",
+            ErrorClass::ServerError => "server_error",
+            ErrorClass::Unauthorized => "unauthorized",
+            ErrorClass::NotFound => "not_found",
+            ErrorClass::Other => "other",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// Which failures to look at. Each field that is set narrows the failures down, so an empty
+/// filter matches every failure.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct FailureFilter {
+    /// The source of webhook events, ie. `github`, or the name of jobs, ie. `sync-rfds`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Only failures of events received, or jobs started, at or after this time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<DateTime<Utc>>,
+    /// Only failures of events received, or jobs started, before this time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_class: Option<ErrorClass>,
+}
+
+impl FailureFilter {
+    /// Whether a failure with `error` is of the class the filter is for.
+    pub fn matches_error(&self, error: &str) -> bool {
+        self.error_class.map_or(true, |class| ErrorClass::of(error) == class)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ErrorClass, FailureFilter};
+
+    #[test]
+    fn test_error_class_of() {
+        assert_eq!(
+            ErrorClass::RateLimited,
+            ErrorClass::of("code: 429, error: You have exceeded a secondary rate limit")
+        );
+        assert_eq!(
+            ErrorClass::Timeout,
+            ErrorClass::of("error sending request for url (https://api.github.com/): operation timed out")
+        );
+        assert_eq!(
+            ErrorClass::Connection,
+            ErrorClass::of("error trying to connect: tcp connect error: Connection refused (os error 111)")
+        );
+        assert_eq!(
+            ErrorClass::ServerError,
+            ErrorClass::of("code: 502 Bad Gateway, error: upstream failed")
+        );
+        assert_eq!(
+            ErrorClass::Unauthorized,
+            ErrorClass::of("code: 401, error: Bad credentials")
+        );
+        assert_eq!(ErrorClass::NotFound, ErrorClass::of("code: 404, error: Not Found"));
+        assert_eq!(ErrorClass::Other, ErrorClass::of("missing field `number` at line 1"));
+        assert_eq!(ErrorClass::Other, ErrorClass::of("applicant 15002 has no email"));
+
+        assert!(ErrorClass::of("503 Service Unavailable").is_transient());
+        assert!(!ErrorClass::of("Bad credentials").is_transient());
+    }
+
+    #[test]
+    fn test_failure_filter_matches_error() {
+        assert!(FailureFilter::default().matches_error("code: 401, error: Bad credentials"));
+
+        let filter = FailureFilter {
+            error_class: Some(ErrorClass::RateLimited),
+            ..Default::default()
+        };
+        assert!(filter.matches_error("code: 429, error: Too Many Requests"));
+        assert!(!filter.matches_error("code: 401, error: Bad credentials"));
+    }
+}
@@ -3,9 +3,10 @@ use std::{collections::HashMap, env, fs::File};
 use anyhow::{bail, Result};
 use async_bb8_diesel::AsyncRunQueryDsl;
 use async_trait::async_trait;
-use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc};
 use log::{info, warn};
 use macros::db;
+use quickbooks::{BillPayment, Purchase, QuickBooks, ReportQuery};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use slack_chat_api::{
@@ -400,9 +401,12 @@ pub async fn refresh_ramp_transactions(db: &Database, company: &Company, config:
 
     let transactions = ramp
         .transactions()
-        .list(&ramp_minimal_api::ListTransactionsQuery::default())
+        .list_all(&ramp_minimal_api::ListTransactionsQuery {
+            page_size: Some(100),
+            ..Default::default()
+        })
         .await?;
-    for transaction in transactions.data {
+    for transaction in transactions {
         let mut attachments = Vec::new();
         // Get the reciept for the transaction, if they exist.
         for receipt_id in transaction.receipts {
@@ -470,8 +474,14 @@ pub async fn refresh_ramp_reimbursements(db: &Database, company: &Company, confi
         ramp_users.insert(user.id.to_string(), user.email.to_string());
     }
 
-    let reimbursements = ramp.reimbursements().list().await?;
-    for reimbursement in reimbursements.data {
+    let reimbursements = ramp
+        .reimbursements()
+        .list_all(&ramp_minimal_api::ListReimbursementsQuery {
+            page_size: Some(100),
+            ..Default::default()
+        })
+        .await?;
+    for reimbursement in reimbursements {
         let mut attachments = Vec::new();
         // Get the reciepts for the reimbursement, if they exist.
         for receipt_id in reimbursement.receipts {
@@ -525,8 +535,78 @@ pub async fn refresh_ramp_reimbursements(db: &Database, company: &Company, confi
     Ok(())
 }
 
+/// Sync the bills paid through Ramp into accounts payable.
+pub async fn refresh_ramp_bills(db: &Database, company: &Company, config: &FinanceConfig) -> Result<()> {
+    // Create the Ramp client.
+    let ramp = company.authenticate_ramp()?;
+
+    let bills = ramp
+        .bills()
+        .list_all(&ramp_minimal_api::ListBillsQuery {
+            page_size: Some(100),
+            ..Default::default()
+        })
+        .await?;
+    for bill in bills {
+        let vendor_name = bill
+            .vendor
+            .as_ref()
+            .map(|vendor| vendor.remote_name.to_string())
+            .unwrap_or_default();
+
+        let mut link_to_vendor: Vec<String> = Default::default();
+        let vendor = clean_vendor_name(&vendor_name, config);
+        // Try to find the vendor in our list of vendors.
+        match SoftwareVendor::get_from_db(db, company.id, vendor.to_string()).await {
+            Some(v) => {
+                link_to_vendor = vec![v.airtable_record_id.to_string()];
+            }
+            None => {
+                info!("could not find vendor that matches {}", vendor);
+            }
+        }
+
+        // Bills that have not been paid yet are dated by when they were issued.
+        let date = bill
+            .paid_at
+            .or(bill.issued_at)
+            .map(|date| date.naive_utc().date())
+            .unwrap_or_else(crate::utils::default_date);
+
+        let payment_type = bill
+            .payment
+            .as_ref()
+            .and_then(|payment| payment.payment_method.clone())
+            .unwrap_or_default();
+
+        let nap = NewAccountsPayable {
+            confirmation_number: bill.id.to_string(),
+            amount: bill.amount.to_major_units() as f32,
+            invoice_number: bill.invoice_number.unwrap_or_default(),
+            vendor,
+            currency: bill.amount.currency_code.to_string(),
+            date,
+            payment_type,
+            status: bill.status.to_string(),
+            notes: bill.memo.unwrap_or_default(),
+            invoices: bill.invoice_urls,
+            link_to_vendor,
+            cio_company_id: company.id,
+        };
+
+        nap.upsert(db).await?;
+    }
+
+    AccountsPayables::get_from_db(db, company.id)
+        .await?
+        .update_airtable(db)
+        .await?;
+
+    Ok(())
+}
+
 // Changes the vendor name to one that matches our existing list.
-fn clean_vendor_name(vendor_name: &str, config: &FinanceConfig) -> String {
+pub(crate) fn clean_vendor_name(vendor_name: &str, config: &FinanceConfig) -> String {
     if let Some(alias) = config.vendor_aliases.get(vendor_name) {
         alias.to_string()
     } else {
@@ -992,115 +1072,271 @@ pub async fn sync_quickbooks(db: &Database, company: &Company, config: &FinanceC
 
     let bill_payments = qb.list_bill_payments().await?;
     for bill_payment in bill_payments {
-        // Let's check if there are any attachments.
-        let attachments = qb.list_attachments_for_bill_payment(&bill_payment.id).await?;
+        sync_quickbooks_bill_payment(db, company, &qb, bill_payment).await?;
+    }
 
-        if (attachments.is_empty() && bill_payment.line.is_empty()) || bill_payment.total_amt == 0.0 {
-            // Continue early if we have no lines on the bill.
-            continue;
-        }
+    let purchases = qb.list_purchases().await?;
+    if !config.cost_centers.is_empty() {
+        let bills = qb.list_bills().await?;
+        crate::cost_centers::attribute_cost_centers(&qb, config, &purchases, &bills).await?;
+    }
+    for purchase in purchases {
+        sync_quickbooks_purchase(db, config, &qb, purchase).await?;
+    }
 
-        let merchant_name = bill_payment.vendor_ref.name.to_string();
-        match accounts_payables::dsl::accounts_payables
-            .filter(
-                accounts_payables::dsl::vendor
-                    .eq(merchant_name.to_string())
-                    .and(accounts_payables::dsl::amount.eq(bill_payment.total_amt))
-                    .and(accounts_payables::dsl::date.eq(bill_payment.txn_date)),
-            )
-            .first_async::<AccountsPayable>(db.pool())
-            .await
-        {
-            Ok(mut transaction) => {
-                // Add the receipt.
-                // Clear out existing invoices.
-                transaction.invoices = vec![];
-                for line in bill_payment.line {
-                    // Iterate over each of the linked transactions.
-                    for txn in line.linked_txn {
-                        if txn.txn_type == "Bill" {
-                            // Get the bill.
-                            let bill = qb.get_bill(&txn.txn_id).await?;
-                            // Get the attachments for the bill.
-                            let attachments = qb.list_attachments_for_bill(&bill.id).await?;
-                            for attachment in attachments {
-                                transaction.invoices.push(attachment.temp_download_uri.to_string());
-                            }
+    // Book the Stripe payments that could not be booked when they were paid.
+    crate::payments::reconcile_payments(db, company, &qb, &config.stripe).await?;
+
+    Ok(())
+}
+
+/// Sync only the QuickBooks purchases and bill payments that changed since the given time.
+/// This is driven by the QuickBooks webhooks so changes show up without waiting for the
+/// nightly `sync_quickbooks`.
+pub async fn sync_quickbooks_changes(
+    db: &Database,
+    company: &Company,
+    config: &FinanceConfig,
+    changed_since: DateTime<Utc>,
+) -> Result<()> {
+    let qb = company.authenticate_quickbooks(db).await?;
+
+    let changes = qb
+        .change_data_capture(&["Purchase", "BillPayment"], changed_since)
+        .await?;
+
+    info!(
+        "syncing {} purchases and {} bill payments changed in quickbooks since {}",
+        changes.purchases.len(),
+        changes.bill_payments.len(),
+        changed_since
+    );
+
+    for bill_payment in changes.bill_payments {
+        sync_quickbooks_bill_payment(db, company, &qb, bill_payment).await?;
+    }
+
+    for purchase in changes.purchases {
+        sync_quickbooks_purchase(db, config, &qb, purchase).await?;
+    }
+
+    Ok(())
+}
+
+/// Attach the invoices for a QuickBooks bill payment to the matching accounts payable.
+async fn sync_quickbooks_bill_payment(
+    db: &Database,
+    company: &Company,
+    qb: &QuickBooks,
+    bill_payment: BillPayment,
+) -> Result<()> {
+    // Let's check if there are any attachments.
+    let attachments = qb.list_attachments_for_bill_payment(&bill_payment.id).await?;
+
+    if (attachments.is_empty() && bill_payment.line.is_empty()) || bill_payment.total_amt == 0.0 {
+        // Return early if we have no lines on the bill.
+        return Ok(());
+    }
+
+    let merchant_name = bill_payment.vendor_ref.name.to_string();
+    match accounts_payables::dsl::accounts_payables
+        .filter(
+            accounts_payables::dsl::vendor
+                .eq(merchant_name.to_string())
+                .and(accounts_payables::dsl::amount.eq(bill_payment.total_amt))
+                .and(accounts_payables::dsl::date.eq(bill_payment.txn_date)),
+        )
+        .first_async::<AccountsPayable>(db.pool())
+        .await
+    {
+        Ok(mut transaction) => {
+            // Add the receipt.
+            // Clear out existing invoices.
+            transaction.invoices = vec![];
+            for line in bill_payment.line {
+                // Iterate over each of the linked transactions.
+                for txn in line.linked_txn {
+                    if txn.txn_type == "Bill" {
+                        // Get the bill.
+                        let bill = qb.get_bill(&txn.txn_id).await?;
+                        // Get the attachments for the bill.
+                        let attachments = qb.list_attachments_for_bill(&bill.id).await?;
+                        for attachment in attachments {
+                            transaction.invoices.push(attachment.temp_download_uri.to_string());
                         }
                     }
                 }
-                transaction.cio_company_id = company.id;
-
-                transaction.update(db).await?;
-                continue;
-            }
-            Err(e) => {
-                info!(
-                    "could not find transaction with merchant_name `{}` -> `{}` amount `{}`  date `{}`: {}",
-                    bill_payment.vendor_ref.name, merchant_name, bill_payment.total_amt, bill_payment.txn_date, e
-                );
             }
+            transaction.cio_company_id = company.id;
+
+            transaction.update(db).await?;
+        }
+        Err(e) => {
+            info!(
+                "could not find transaction with merchant_name `{}` -> `{}` amount `{}`  date `{}`: {}",
+                bill_payment.vendor_ref.name, merchant_name, bill_payment.total_amt, bill_payment.txn_date, e
+            );
         }
     }
 
-    let purchases = qb.list_purchases().await?;
-    for purchase in purchases.clone() {
-        // Let's try to match the Brex reciepts to the transactions.
-        if purchase.account_ref.name == "Credit Cards:Brex" {
-            // See if we even have attachments.
-            let attachments = qb.list_attachments_for_purchase(&purchase.id).await?;
-            if attachments.is_empty() {
-                // We can continue early since we don't have attachments.
-                continue;
-            }
+    Ok(())
+}
 
-            // This is a brex transaction, let's try to find it in our database to update it.
-            // We know we have attachments as well.
-            let time_start = NaiveTime::from_hms_milli(0, 0, 0, 0);
-            let sdt = purchase
-                .txn_date
-                .checked_sub_signed(Duration::days(10))
-                .unwrap()
-                .and_time(time_start);
-            let time_end = NaiveTime::from_hms_milli(23, 59, 59, 59);
-            let edt = purchase.txn_date.and_time(time_end);
-            let merchant_name = clean_merchant_name(&purchase.entity_ref.name, config);
-            match credit_card_transactions::dsl::credit_card_transactions
-                .filter(
-                    credit_card_transactions::dsl::merchant_name
-                        .eq(merchant_name.to_string())
-                        .and(credit_card_transactions::dsl::card_vendor.eq("Brex".to_string()))
-                        .and(credit_card_transactions::dsl::amount.eq(purchase.total_amt))
-                        .and(credit_card_transactions::dsl::time.ge(DateTime::<Utc>::from_utc(sdt, Utc)))
-                        .and(credit_card_transactions::dsl::time.le(DateTime::<Utc>::from_utc(edt, Utc))),
-                )
-                .first_async::<CreditCardTransaction>(db.pool())
-                .await
-            {
-                Ok(mut transaction) => {
-                    // Add the receipt.
-                    // Clear out existing receipts.
-                    transaction.receipts = vec![];
-                    for attachment in attachments {
-                        transaction.receipts.push(attachment.temp_download_uri.to_string());
-                    }
-                    transaction.update(db).await?;
-                    continue;
-                }
-                Err(e) => {
-                    info!(
-                        "could not find transaction with merchant_name `{}` -> `{}` amount `{}` date `{}` --> less than `{}` greater than `{}`: {}",
-                        purchase.entity_ref.name, merchant_name, purchase.total_amt, purchase.txn_date, sdt, edt, e
-                    );
-                }
+/// Attach the receipts for a QuickBooks purchase to the matching credit card transaction.
+async fn sync_quickbooks_purchase(
+    db: &Database,
+    config: &FinanceConfig,
+    qb: &QuickBooks,
+    purchase: Purchase,
+) -> Result<()> {
+    if !config.quickbooks_ramp_account.is_empty() && purchase.account_ref.name == config.quickbooks_ramp_account {
+        return attach_ramp_receipts_to_quickbooks_purchase(db, config, qb, purchase).await;
+    }
+
+    // Let's try to match the Brex reciepts to the transactions.
+    if purchase.account_ref.name != "Credit Cards:Brex" {
+        return Ok(());
+    }
+
+    // See if we even have attachments.
+    let attachments = qb.list_attachments_for_purchase(&purchase.id).await?;
+    if attachments.is_empty() {
+        // We can return early since we don't have attachments.
+        return Ok(());
+    }
+
+    // This is a brex transaction, let's try to find it in our database to update it.
+    // We know we have attachments as well.
+    let time_start = NaiveTime::from_hms_milli(0, 0, 0, 0);
+    let sdt = purchase
+        .txn_date
+        .checked_sub_signed(Duration::days(10))
+        .unwrap()
+        .and_time(time_start);
+    let time_end = NaiveTime::from_hms_milli(23, 59, 59, 59);
+    let edt = purchase.txn_date.and_time(time_end);
+    let merchant_name = clean_merchant_name(&purchase.entity_ref.name, config);
+    match credit_card_transactions::dsl::credit_card_transactions
+        .filter(
+            credit_card_transactions::dsl::merchant_name
+                .eq(merchant_name.to_string())
+                .and(credit_card_transactions::dsl::card_vendor.eq("Brex".to_string()))
+                .and(credit_card_transactions::dsl::amount.eq(purchase.total_amt))
+                .and(credit_card_transactions::dsl::time.ge(DateTime::<Utc>::from_utc(sdt, Utc)))
+                .and(credit_card_transactions::dsl::time.le(DateTime::<Utc>::from_utc(edt, Utc))),
+        )
+        .first_async::<CreditCardTransaction>(db.pool())
+        .await
+    {
+        Ok(mut transaction) => {
+            // Add the receipt.
+            // Clear out existing receipts.
+            transaction.receipts = vec![];
+            for attachment in attachments {
+                transaction.receipts.push(attachment.temp_download_uri.to_string());
             }
+            transaction.update(db).await?;
+        }
+        Err(e) => {
+            info!(
+                "could not find transaction with merchant_name `{}` -> `{}` amount `{}` date `{}` --> less than `{}` greater than `{}`: {}",
+                purchase.entity_ref.name, merchant_name, purchase.total_amt, purchase.txn_date, sdt, edt, e
+            );
         }
     }
 
     Ok(())
 }
 
-fn clean_merchant_name(merchant_name: &str, config: &FinanceConfig) -> String {
+/// Upload the receipts of the matching Ramp transaction to a QuickBooks purchase that does not
+/// have any attachments yet.
+async fn attach_ramp_receipts_to_quickbooks_purchase(
+    db: &Database,
+    config: &FinanceConfig,
+    qb: &QuickBooks,
+    purchase: Purchase,
+) -> Result<()> {
+    let attachments = qb.list_attachments_for_purchase(&purchase.id).await?;
+    if !attachments.is_empty() {
+        // The receipts are already attached.
+        return Ok(());
+    }
+
+    // Ramp posts the transaction to QuickBooks a few days after the card was swiped.
+    let time_start = NaiveTime::from_hms_milli(0, 0, 0, 0);
+    let sdt = purchase
+        .txn_date
+        .checked_sub_signed(Duration::days(10))
+        .unwrap()
+        .and_time(time_start);
+    let time_end = NaiveTime::from_hms_milli(23, 59, 59, 59);
+    let edt = purchase.txn_date.and_time(time_end);
+    let merchant_name = clean_merchant_name(&purchase.entity_ref.name, config);
+    let transaction = match credit_card_transactions::dsl::credit_card_transactions
+        .filter(
+            credit_card_transactions::dsl::merchant_name
+                .eq(merchant_name.to_string())
+                .and(credit_card_transactions::dsl::card_vendor.eq("Ramp".to_string()))
+                .and(credit_card_transactions::dsl::amount.eq(purchase.total_amt))
+                .and(credit_card_transactions::dsl::time.ge(DateTime::<Utc>::from_utc(sdt, Utc)))
+                .and(credit_card_transactions::dsl::time.le(DateTime::<Utc>::from_utc(edt, Utc))),
+        )
+        .first_async::<CreditCardTransaction>(db.pool())
+        .await
+    {
+        Ok(transaction) => transaction,
+        Err(e) => {
+            info!(
+                "could not find ramp transaction with merchant_name `{}` -> `{}` amount `{}` date `{}`: {}",
+                purchase.entity_ref.name, merchant_name, purchase.total_amt, purchase.txn_date, e
+            );
+            return Ok(());
+        }
+    };
+
+    let entity_ref = quickbooks::NtRef {
+        value: purchase.id.to_string(),
+        name: String::new(),
+        entity_ref_type: "Purchase".to_string(),
+    };
+
+    for (i, receipt_url) in transaction.receipts.iter().enumerate() {
+        let resp = reqwest::get(receipt_url).await?.error_for_status()?;
+        let content_type = resp
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("application/pdf")
+            .to_string();
+        let extension = match content_type.as_str() {
+            "image/png" => "png",
+            "image/jpeg" => "jpg",
+            "image/gif" => "gif",
+            _ => "pdf",
+        };
+        let content = resp.bytes().await?.to_vec();
+
+        let file_name = format!(
+            "{} {} receipt {}.{}",
+            transaction.merchant_name,
+            purchase.txn_date,
+            i + 1,
+            extension
+        );
+
+        qb.upload_attachment(&file_name, &content_type, content, Some(entity_ref.clone()))
+            .await?;
+
+        info!(
+            "attached ramp receipt `{}` to quickbooks purchase {}",
+            file_name, purchase.id
+        );
+    }
+
+    Ok(())
+}
+
+pub(crate) fn clean_merchant_name(merchant_name: &str, config: &FinanceConfig) -> String {
     if let Some(alias) = config.merchant_aliases.get(merchant_name) {
         alias.to_string()
     } else {
@@ -1108,11 +1344,103 @@ fn clean_merchant_name(merchant_name: &str, config: &FinanceConfig) -> String {
     }
 }
 
+/// Post the key figures for last month from QuickBooks to Slack.
+pub async fn send_monthly_finance_summary(db: &Database, company: &Company, config: &FinanceConfig) -> Result<()> {
+    // Authenticate QuickBooks.
+    let qba = company.authenticate_quickbooks(db).await;
+    if let Err(e) = qba {
+        if e.to_string().contains("no token") {
+            // Return early, this company does not use QuickBooks.
+            return Ok(());
+        }
+
+        bail!("authenticating quickbooks failed: {}", e);
+    }
+    let qb = qba?;
+
+    // Get the first and last day of last month.
+    let today = Utc::now().naive_utc().date();
+    let end_date = NaiveDate::from_ymd_opt(today.year(), today.month(), 1).unwrap() - Duration::days(1);
+    let start_date = NaiveDate::from_ymd_opt(end_date.year(), end_date.month(), 1).unwrap();
+
+    let query = ReportQuery {
+        start_date: Some(start_date),
+        end_date: Some(end_date),
+        ..Default::default()
+    };
+
+    let (pl, bs, ar) = tokio::join!(
+        qb.profit_and_loss(&query),
+        qb.balance_sheet(&query),
+        qb.aged_receivables(&query),
+    );
+    let (pl, bs, ar) = (pl?, bs?, ar?);
+
+    let figures = [
+        ("Income", pl.total("Income")),
+        ("Expenses", pl.total("Expenses")),
+        ("Net income", pl.total("NetIncome")),
+        ("Cash", bs.total("BankAccounts")),
+        ("Total assets", bs.total("TotalAssets")),
+        ("Total liabilities", bs.total("Liabilities")),
+        ("Accounts receivable", ar.total("GrandTotal")),
+    ];
+
+    let text = figures
+        .iter()
+        .map(|(name, value)| match value {
+            Some(value) => format!("*{}:* `${:.2}`", name, value),
+            None => format!("*{}:* _not reported_", name),
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let channel = if config.summary_slack_channel.is_empty() {
+        company.slack_channel_finance.to_string()
+    } else {
+        config.summary_slack_channel.to_string()
+    };
+
+    let msg = FormattedMessage {
+        channel,
+        blocks: vec![
+            MessageBlock {
+                block_type: MessageBlockType::Header,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::PlainText,
+                    text: format!("Finance summary for {}", start_date.format("%B %Y")),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+            MessageBlock {
+                block_type: MessageBlockType::Section,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text,
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+        ],
+        attachments: Default::default(),
+    };
+
+    company.post_to_slack_channel(db, &msg).await?;
+
+    Ok(())
+}
+
 pub async fn refresh_all_finance(db: &Database, company: &Company, config: &FinanceConfig) -> Result<()> {
-    let (sv, reim, trans, ap, qb) = tokio::join!(
+    let (sv, reim, trans, bills, ap, qb) = tokio::join!(
         refresh_software_vendors(db, company),
         refresh_ramp_reimbursements(db, company, config),
         refresh_ramp_transactions(db, company, config),
+        refresh_ramp_bills(db, company, config),
         refresh_accounts_payable(db, company, config),
         sync_quickbooks(db, company, config),
     );
@@ -1120,6 +1448,7 @@ pub async fn refresh_all_finance(db: &Database, company: &Company, config: &Fina
     sv?;
     reim?;
     trans?;
+    bills?;
     ap?;
     qb?;
 
@@ -15,7 +15,7 @@ use slack_chat_api::{
 
 use crate::{
     airtable::AIRTABLE_FUNCTIONS_TABLE, companies::Company, core::UpdateAirtableRecord, db::Database,
-    schema::functions, utils::truncate,
+    failures::FailureFilter, schema::functions, utils::truncate,
 };
 
 #[db {
@@ -231,6 +231,38 @@ impl Function {
         n.send_slack_notification(db, company).await
     }
 
+    /// The runs of jobs that failed or timed out and match `filter`, newest first, at most
+    /// `limit` of them. A failed run's logs are the error it failed with.
+    pub async fn list_failed(db: &Database, filter: &FailureFilter, limit: usize) -> Result<Vec<Function>> {
+        let mut query = functions::dsl::functions
+            .filter(functions::dsl::conclusion.eq_any(vec![
+                octorust::types::Conclusion::Failure.to_string(),
+                octorust::types::Conclusion::TimedOut.to_string(),
+            ]))
+            .into_boxed();
+
+        if let Some(name) = &filter.source {
+            query = query.filter(functions::dsl::name.eq(name.to_string()));
+        }
+        if let Some(since) = filter.since {
+            query = query.filter(functions::dsl::created_at.ge(since));
+        }
+        if let Some(until) = filter.until {
+            query = query.filter(functions::dsl::created_at.lt(until));
+        }
+
+        let failed = query
+            .order_by(functions::dsl::created_at.desc())
+            .load_async::<Function>(db.pool())
+            .await?;
+
+        Ok(failed
+            .into_iter()
+            .filter(|f| filter.matches_error(&f.logs))
+            .take(limit)
+            .collect())
+    }
+
     /// Add logs to a running saga.
     pub async fn add_logs(db: &Database, saga_id: &uuid::Uuid, logs: &str) -> Result<()> {
         if logs.is_empty() {
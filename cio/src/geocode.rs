@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use diesel::{AsChangeset, ExpressionMethods, Insertable, QueryDsl, Queryable};
+use google_geocode::Geocode;
+use lazy_static::lazy_static;
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sodiumoxide::crypto::hash::sha256;
+
+use crate::{db::Database, schema::geocode_cache_entries};
+
+/// How long a geocoded address is trusted before it is looked up again.
+const CACHE_TTL_DAYS: i64 = 90;
+
+/// Google allows 50 queries per second. We stay well below that so concurrent syncs do not
+/// get throttled.
+const GEOCODE_QPS: u32 = 10;
+
+/// The cached coordinates of a single address.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct GeocodeCacheEntry {
+    pub id: i32,
+    /// The hex encoded sha256 of the normalized address.
+    pub address_hash: String,
+    pub address: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub cached_at: DateTime<Utc>,
+    /// The IANA time zone of the address, this is looked up separately the first time it is
+    /// needed.
+    pub time_zone_id: String,
+}
+
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = geocode_cache_entries)]
+pub struct NewGeocodeCacheEntry {
+    pub address_hash: String,
+    pub address: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub cached_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LatLng {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+lazy_static! {
+    /// Every caller shares one client so the rate limit applies across all of them.
+    static ref CLIENT: Geocode = Geocode::new_from_env().with_qps(GEOCODE_QPS);
+}
+
+/// Addresses that only differ in case or whitespace are treated as the same address.
+fn normalize(address: &str) -> String {
+    address.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+fn address_hash(address: &str) -> String {
+    hex::encode(sha256::hash(normalize(address).as_bytes()))
+}
+
+/// Get the coordinates of an address, using the cached coordinates if we have them.
+pub async fn geocode_address(db: &Database, address: &str) -> Result<LatLng> {
+    geocode_addresses(db, &[address])
+        .await?
+        .remove(address)
+        .ok_or_else(|| anyhow!("could not geocode address `{}`", address))
+}
+
+/// Get the coordinates of a batch of addresses, keyed by address. Only addresses that are
+/// not in the cache are sent to Google, and each of those is only looked up once. Addresses
+/// that could not be geocoded are left out of the result.
+pub async fn geocode_addresses<S>(db: &Database, addresses: &[S]) -> Result<HashMap<String, LatLng>>
+where
+    S: AsRef<str>,
+{
+    let mut by_hash: HashMap<String, Vec<String>> = HashMap::new();
+    for address in addresses {
+        let address = address.as_ref();
+        if address.trim().is_empty() {
+            continue;
+        }
+
+        by_hash
+            .entry(address_hash(address))
+            .or_default()
+            .push(address.to_string());
+    }
+
+    if by_hash.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let cached = geocode_cache_entries::dsl::geocode_cache_entries
+        .filter(geocode_cache_entries::dsl::address_hash.eq_any(by_hash.keys().cloned().collect::<Vec<_>>()))
+        .filter(geocode_cache_entries::dsl::cached_at.gt(Utc::now() - Duration::days(CACHE_TTL_DAYS)))
+        .load_async::<GeocodeCacheEntry>(db.pool())
+        .await?;
+
+    let mut results = HashMap::new();
+    for entry in cached {
+        if let Some(addresses) = by_hash.remove(&entry.address_hash) {
+            let location = LatLng {
+                latitude: entry.latitude,
+                longitude: entry.longitude,
+            };
+
+            for address in addresses {
+                results.insert(address, location);
+            }
+        }
+    }
+
+    // Look up one address for every hash that missed the cache.
+    let misses: Vec<String> = by_hash.values().map(|addresses| addresses[0].to_string()).collect();
+    let replies = CLIENT.get_many(&misses).await;
+
+    for (address, reply) in replies {
+        let reply = match reply {
+            Ok(reply) => reply,
+            Err(e) => {
+                warn!("could not get lat lng for address `{}`: {}", address, e);
+                continue;
+            }
+        };
+
+        let location = LatLng {
+            latitude: reply.geometry.location.lat,
+            longitude: reply.geometry.location.lng,
+        };
+
+        let entry = NewGeocodeCacheEntry {
+            address_hash: address_hash(&address),
+            address: address.to_string(),
+            latitude: location.latitude,
+            longitude: location.longitude,
+            cached_at: Utc::now(),
+        };
+
+        diesel::insert_into(geocode_cache_entries::table)
+            .values(entry.clone())
+            .on_conflict(geocode_cache_entries::address_hash)
+            .do_update()
+            .set(entry.clone())
+            .execute_async(db.pool())
+            .await?;
+
+        for address in by_hash.remove(&entry.address_hash).unwrap_or_default() {
+            results.insert(address, location);
+        }
+    }
+
+    Ok(results)
+}
+
+/// Get the time zone of an address. The time zone is cached along with the coordinates of
+/// the address.
+pub async fn timezone_for_address(db: &Database, address: &str) -> Result<Tz> {
+    let location = geocode_address(db, address).await?;
+    let hash = address_hash(address);
+
+    let entry = geocode_cache_entries::dsl::geocode_cache_entries
+        .filter(geocode_cache_entries::dsl::address_hash.eq(hash.to_string()))
+        .first_async::<GeocodeCacheEntry>(db.pool())
+        .await?;
+
+    let time_zone_id = if entry.time_zone_id.is_empty() {
+        let tz = CLIENT
+            .timezone(location.latitude, location.longitude, Utc::now().timestamp())
+            .await?;
+
+        diesel::update(geocode_cache_entries::dsl::geocode_cache_entries.find(entry.id))
+            .set(geocode_cache_entries::dsl::time_zone_id.eq(tz.time_zone_id.to_string()))
+            .execute_async(db.pool())
+            .await?;
+
+        info!("looked up time zone `{}` for address `{}`", tz.time_zone_id, address);
+
+        tz.time_zone_id
+    } else {
+        entry.time_zone_id
+    };
+
+    time_zone_id
+        .parse::<Tz>()
+        .map_err(|e| anyhow!("invalid time zone `{}` for address `{}`: {}", time_zone_id, address, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::address_hash;
+
+    #[test]
+    fn test_address_hash_ignores_case_and_whitespace() {
+        assert_eq!(
+            address_hash("1251 Park Avenue, Emeryville, CA 94608"),
+            address_hash("  1251 park avenue,\nEmeryville,  CA 94608 ")
+        );
+        assert_ne!(
+            address_hash("1251 Park Avenue, Emeryville, CA 94608"),
+            address_hash("1252 Park Avenue, Emeryville, CA 94608")
+        );
+    }
+}
@@ -0,0 +1,163 @@
+/*!
+ * The routing table that decides which handler runs for a GitHub webhook.
+ *
+ * Each route matches an event type, a glob of repo names and an action, and names the handler
+ * to run for events that match it. An event runs every enabled route it matches. The event type
+ * and action can be `*` to match any, and repo globs can use `*` to match any part of a name,
+ * ie. `rfd-*`.
+ *
+ * Routes live in the database so that a repo can be automated without a deploy. The handlers
+ * themselves are part of webhooky, which checks that a route names one of them before it is
+ * saved.
+ */
+use anyhow::Result;
+use async_bb8_diesel::{AsyncRunQueryDsl, OptionalExtension};
+use chrono::{DateTime, Utc};
+use diesel::{AsChangeset, ExpressionMethods, Insertable, QueryDsl, Queryable};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{db::Database, schema::github_event_routes};
+
+/// Matches any event type or action.
+pub const ROUTE_WILDCARD: &str = "*";
+
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct GitHubEventRoute {
+    pub id: i32,
+    /// The event from the `X-GitHub-Event` header, ie. `push`.
+    pub event_type: String,
+    /// A glob of the names of the repos the route applies to, ie. `rfd` or `rfd-*`.
+    pub repo_glob: String,
+    /// The `action` of the event, for events that have one, ie. `opened`.
+    pub action: String,
+    /// The name of the handler to run.
+    pub handler: String,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = github_event_routes)]
+pub struct NewGitHubEventRoute {
+    pub event_type: String,
+    pub repo_glob: String,
+    #[serde(default = "wildcard")]
+    pub action: String,
+    pub handler: String,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+}
+
+fn wildcard() -> String {
+    ROUTE_WILDCARD.to_string()
+}
+
+fn enabled() -> bool {
+    true
+}
+
+impl NewGitHubEventRoute {
+    pub async fn create(&self, db: &Database) -> Result<GitHubEventRoute> {
+        Ok(diesel::insert_into(github_event_routes::table)
+            .values(self.clone())
+            .get_result_async::<GitHubEventRoute>(db.pool())
+            .await?)
+    }
+}
+
+impl GitHubEventRoute {
+    pub async fn list(db: &Database) -> Result<Vec<GitHubEventRoute>> {
+        Ok(github_event_routes::dsl::github_event_routes
+            .order_by(github_event_routes::dsl::id)
+            .load_async::<GitHubEventRoute>(db.pool())
+            .await?)
+    }
+
+    /// The enabled routes for events of `event_type`, including those for any event type.
+    pub async fn list_enabled_for_event(db: &Database, event_type: &str) -> Result<Vec<GitHubEventRoute>> {
+        Ok(github_event_routes::dsl::github_event_routes
+            .filter(github_event_routes::dsl::enabled.eq(true))
+            .filter(github_event_routes::dsl::event_type.eq_any(vec![event_type.to_string(), wildcard()]))
+            .order_by(github_event_routes::dsl::id)
+            .load_async::<GitHubEventRoute>(db.pool())
+            .await?)
+    }
+
+    /// Replace the route with `id`. Returns `None` when there is no such route.
+    pub async fn update(db: &Database, id: i32, route: &NewGitHubEventRoute) -> Result<Option<GitHubEventRoute>> {
+        Ok(diesel::update(github_event_routes::dsl::github_event_routes.find(id))
+            .set((route.clone(), github_event_routes::dsl::updated_at.eq(Utc::now())))
+            .get_result_async::<GitHubEventRoute>(db.pool())
+            .await
+            .optional()?)
+    }
+
+    /// Delete the route with `id`. Returns whether there was such a route.
+    pub async fn delete(db: &Database, id: i32) -> Result<bool> {
+        let deleted = diesel::delete(github_event_routes::dsl::github_event_routes.find(id))
+            .execute_async(db.pool())
+            .await?;
+
+        Ok(deleted > 0)
+    }
+
+    /// Whether the route applies to an event of its type for `repo` with `action`.
+    pub fn matches(&self, repo: &str, action: &str) -> bool {
+        glob_matches(&self.repo_glob, repo) && (self.action == ROUTE_WILDCARD || self.action == action)
+    }
+}
+
+/// Match `value` against `pattern`, where `*` in the pattern matches any run of characters.
+pub fn glob_matches(pattern: &str, value: &str) -> bool {
+    let mut parts = pattern.split('*');
+
+    // Without a `*` the pattern has to match exactly.
+    let first = parts.next().unwrap_or_default();
+    let rest = match value.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+
+    let mut parts: Vec<&str> = parts.collect();
+    let last = match parts.pop() {
+        Some(last) => last,
+        None => return rest.is_empty(),
+    };
+
+    // Take each part between two `*` at its first match, which leaves the most room for the
+    // parts after it.
+    let mut rest = rest;
+    for part in parts {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::glob_matches;
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("rfd", "rfd"));
+        assert!(!glob_matches("rfd", "rfd-site"));
+        assert!(!glob_matches("rfd", "configs"));
+
+        assert!(glob_matches("*", "rfd"));
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("rfd-*", "rfd-site"));
+        assert!(glob_matches("rfd-*", "rfd-"));
+        assert!(!glob_matches("rfd-*", "rfd"));
+        assert!(glob_matches("*-site", "rfd-site"));
+        assert!(glob_matches("*fd*", "rfd-site"));
+        assert!(glob_matches("a*b*c", "abbbc"));
+        assert!(!glob_matches("a*b*c", "acb"));
+        assert!(!glob_matches("ab*ba", "aba"));
+    }
+}
@@ -0,0 +1,740 @@
+/*!
+ * Enforcing the settings of the repos in the GitHub org from the `github-repo-settings` configs.
+ *
+ * Each entry of the configs applies to the repos its globs match. When more than one entry
+ * matches a repo, the entries are applied in the order of their names, so a later entry
+ * overrides the settings of an earlier one, and the labels of every entry are added together.
+ * Settings an entry does not give are left however they are set in GitHub, as are labels that
+ * are not in the configs. The same goes for the parts of a branch's protection the configs do
+ * not model, like push restrictions or code owner reviews, which are kept when it is changed.
+ *
+ * Repos whose settings differ from the configs are reported to Slack, whether the sync changed
+ * them or, as a dry run, only found them.
+ */
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use log::{info, warn};
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    app_config::GitHubConfig,
+    companies::Company,
+    configs::{get_configs_from_repo, GitHubBranchProtectionConfig, GitHubLabelConfig, GitHubRepoSettingsConfig},
+    db::Database,
+    github_event_routes::glob_matches,
+    repos::{GithubRepo, GithubRepos},
+};
+
+/// The settings of a repo that are either on or off.
+const REPO_OPTIONS: [&str; 5] = [
+    "allow_merge_commit",
+    "allow_squash_merge",
+    "allow_rebase_merge",
+    "delete_branch_on_merge",
+    "secret_scanning",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GitHubRepoSettingsChange {
+    SetOption {
+        repo: String,
+        option: String,
+        value: bool,
+    },
+    ProtectBranch {
+        repo: String,
+        branch: String,
+        protection: GitHubBranchProtectionConfig,
+        /// How the existing protection differs from the configs.
+        drift: Vec<String>,
+    },
+    CreateLabel {
+        repo: String,
+        label: GitHubLabelConfig,
+    },
+    UpdateLabel {
+        repo: String,
+        label: GitHubLabelConfig,
+    },
+}
+
+impl GitHubRepoSettingsChange {
+    fn repo(&self) -> &str {
+        match self {
+            GitHubRepoSettingsChange::SetOption { repo, .. }
+            | GitHubRepoSettingsChange::ProtectBranch { repo, .. }
+            | GitHubRepoSettingsChange::CreateLabel { repo, .. }
+            | GitHubRepoSettingsChange::UpdateLabel { repo, .. } => repo,
+        }
+    }
+}
+
+impl std::fmt::Display for GitHubRepoSettingsChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GitHubRepoSettingsChange::SetOption { repo, option, value } => {
+                write!(f, "set `{}` to {} on `{}`", option, value, repo)
+            }
+            GitHubRepoSettingsChange::ProtectBranch {
+                repo, branch, drift, ..
+            } => write!(f, "protect `{}` on `{}`: {}", branch, repo, drift.join(", ")),
+            GitHubRepoSettingsChange::CreateLabel { repo, label } => {
+                write!(f, "create label `{}` on `{}`", label.name, repo)
+            }
+            GitHubRepoSettingsChange::UpdateLabel { repo, label } => {
+                write!(f, "update label `{}` on `{}`", label.name, repo)
+            }
+        }
+    }
+}
+
+/// What the sync changed, or would change when it is a dry run.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GitHubRepoSettingsReport {
+    pub dry_run: bool,
+    pub changes: Vec<GitHubRepoSettingsChange>,
+    /// Repos whose settings could not be read or changed.
+    pub failed_repos: Vec<String>,
+}
+
+impl std::fmt::Display for GitHubRepoSettingsReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let verb = if self.dry_run { "would make" } else { "made" };
+        writeln!(f, "github repo settings sync {} {} changes", verb, self.changes.len())?;
+
+        for change in &self.changes {
+            writeln!(f, "- {}", change)?;
+        }
+
+        if !self.failed_repos.is_empty() {
+            writeln!(f, "repos that failed: {}", self.failed_repos.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The settings of a repo, either as the configs want them or as they are in GitHub.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct RepoSettings {
+    options: BTreeMap<String, bool>,
+    branch_protection: Option<GitHubBranchProtectionConfig>,
+    /// Keyed by the lowercased name, since GitHub does not care about the case of label names.
+    labels: BTreeMap<String, GitHubLabelConfig>,
+}
+
+impl RepoSettings {
+    fn is_empty(&self) -> bool {
+        self.options.is_empty() && self.branch_protection.is_none() && self.labels.is_empty()
+    }
+}
+
+/// The settings the configs want `repo` to have.
+fn desired_settings(repo: &str, configs: &BTreeMap<String, GitHubRepoSettingsConfig>) -> RepoSettings {
+    let mut desired = RepoSettings::default();
+
+    for config in configs.values() {
+        if !config.repos.iter().any(|glob| glob_matches(glob, repo)) {
+            continue;
+        }
+
+        for (option, value) in [
+            ("allow_merge_commit", config.allow_merge_commit),
+            ("allow_squash_merge", config.allow_squash_merge),
+            ("allow_rebase_merge", config.allow_rebase_merge),
+            ("delete_branch_on_merge", config.delete_branch_on_merge),
+            ("secret_scanning", config.secret_scanning),
+        ] {
+            if let Some(value) = value {
+                desired.options.insert(option.to_string(), value);
+            }
+        }
+
+        if let Some(protection) = &config.branch_protection {
+            desired.branch_protection = Some(protection.clone());
+        }
+
+        for label in &config.labels {
+            desired.labels.insert(label.name.to_lowercase(), label.clone());
+        }
+    }
+
+    desired
+}
+
+/// The changes that turn the existing settings of a repo into the desired ones.
+fn repo_changes(
+    repo: &str,
+    branch: &str,
+    existing: &RepoSettings,
+    desired: &RepoSettings,
+) -> Vec<GitHubRepoSettingsChange> {
+    let mut changes = vec![];
+
+    for (option, value) in &desired.options {
+        if existing.options.get(option) != Some(value) {
+            changes.push(GitHubRepoSettingsChange::SetOption {
+                repo: repo.to_string(),
+                option: option.to_string(),
+                value: *value,
+            });
+        }
+    }
+
+    if let Some(protection) = &desired.branch_protection {
+        let drift = protection_drift(existing.branch_protection.as_ref(), protection);
+        if !drift.is_empty() {
+            changes.push(GitHubRepoSettingsChange::ProtectBranch {
+                repo: repo.to_string(),
+                branch: branch.to_string(),
+                protection: protection.clone(),
+                drift,
+            });
+        }
+    }
+
+    for (name, label) in &desired.labels {
+        match existing.labels.get(name) {
+            None => changes.push(GitHubRepoSettingsChange::CreateLabel {
+                repo: repo.to_string(),
+                label: label.clone(),
+            }),
+            Some(existing)
+                if !existing.color.eq_ignore_ascii_case(&label.color) || existing.description != label.description =>
+            {
+                changes.push(GitHubRepoSettingsChange::UpdateLabel {
+                    repo: repo.to_string(),
+                    // Keep the name as it is in GitHub, so the label is found by it.
+                    label: GitHubLabelConfig {
+                        name: existing.name.to_string(),
+                        ..label.clone()
+                    },
+                })
+            }
+            Some(_) => (),
+        }
+    }
+
+    changes
+}
+
+/// How the `existing` protection of a branch differs from the `desired` one, a line for each
+/// setting that does not match.
+fn protection_drift(
+    existing: Option<&GitHubBranchProtectionConfig>,
+    desired: &GitHubBranchProtectionConfig,
+) -> Vec<String> {
+    let existing = match existing {
+        Some(existing) => existing,
+        None => return vec!["the branch is not protected".to_string()],
+    };
+
+    let mut drift = vec![];
+
+    let mut existing_checks = existing.required_checks.clone();
+    let mut desired_checks = desired.required_checks.clone();
+    existing_checks.sort();
+    desired_checks.sort();
+    if existing_checks != desired_checks {
+        drift.push(format!(
+            "required checks are [{}] instead of [{}]",
+            existing_checks.join(", "),
+            desired_checks.join(", ")
+        ));
+    }
+
+    if existing.required_approving_reviews != desired.required_approving_reviews {
+        drift.push(format!(
+            "{} required reviews instead of {}",
+            existing.required_approving_reviews, desired.required_approving_reviews
+        ));
+    }
+
+    for (setting, existing, desired) in [
+        ("enforce_admins", existing.enforce_admins, desired.enforce_admins),
+        (
+            "allow_force_pushes",
+            existing.allow_force_pushes,
+            desired.allow_force_pushes,
+        ),
+    ] {
+        if existing != desired {
+            drift.push(format!("`{}` is {} instead of {}", setting, existing, desired));
+        }
+    }
+
+    drift
+}
+
+/// The request that sets the protection of a branch to `protection`. GitHub replaces the whole
+/// protection of the branch with what is in the request, so everything the configs do not model
+/// is copied over from the `existing` protection, as GitHub returned it, rather than reset.
+fn protection_request(
+    existing: Option<&serde_json::Value>,
+    protection: &GitHubBranchProtectionConfig,
+) -> serde_json::Value {
+    let existing = existing.cloned().unwrap_or_default();
+    let logins = |pointer: &str, field: &str| -> Vec<String> {
+        existing
+            .pointer(pointer)
+            .and_then(|entries| entries.as_array())
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter_map(|entry| entry.get(field).and_then(|value| value.as_str()))
+                    .map(|value| value.to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let required_status_checks = if protection.required_checks.is_empty() {
+        serde_json::Value::Null
+    } else {
+        json!({
+            "strict": json_flag(&existing, "/required_status_checks/strict"),
+            "contexts": protection.required_checks,
+        })
+    };
+
+    let required_pull_request_reviews = if protection.required_approving_reviews > 0 {
+        let reviews = &existing["required_pull_request_reviews"];
+        let mut required_pull_request_reviews = json!({
+            "dismiss_stale_reviews": json_flag(reviews, "/dismiss_stale_reviews"),
+            "require_code_owner_reviews": json_flag(reviews, "/require_code_owner_reviews"),
+            "required_approving_review_count": protection.required_approving_reviews,
+        });
+        if !reviews["dismissal_restrictions"].is_null() {
+            required_pull_request_reviews["dismissal_restrictions"] = json!({
+                "users": logins("/required_pull_request_reviews/dismissal_restrictions/users", "login"),
+                "teams": logins("/required_pull_request_reviews/dismissal_restrictions/teams", "slug"),
+            });
+        }
+        required_pull_request_reviews
+    } else {
+        serde_json::Value::Null
+    };
+
+    let restrictions = if existing["restrictions"].is_null() {
+        serde_json::Value::Null
+    } else {
+        json!({
+            "users": logins("/restrictions/users", "login"),
+            "teams": logins("/restrictions/teams", "slug"),
+            "apps": logins("/restrictions/apps", "slug"),
+        })
+    };
+
+    json!({
+        "required_status_checks": required_status_checks,
+        "enforce_admins": protection.enforce_admins,
+        "required_pull_request_reviews": required_pull_request_reviews,
+        "restrictions": restrictions,
+        "allow_force_pushes": protection.allow_force_pushes,
+        "required_linear_history": json_flag(&existing, "/required_linear_history/enabled"),
+        "allow_deletions": json_flag(&existing, "/allow_deletions/enabled"),
+        "required_conversation_resolution": json_flag(&existing, "/required_conversation_resolution/enabled"),
+        "block_creations": json_flag(&existing, "/block_creations/enabled"),
+    })
+}
+
+/// The protection of `branch` as GitHub returns it, or `None` when the branch is not protected.
+async fn get_branch_protection(
+    github: &octorust::Client,
+    org: &str,
+    repo: &str,
+    branch: &str,
+) -> Result<Option<serde_json::Value>> {
+    // GitHub answers with a 404 for branches without protection.
+    match github.repos().get_branch_protection(org, repo, branch).await {
+        Ok(protection) => Ok(Some(serde_json::to_value(protection)?)),
+        Err(e) if e.to_string().contains("404") || e.to_string().contains("Not Found") => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Read a flag out of a GitHub response, where a missing flag is off.
+fn json_flag(value: &serde_json::Value, pointer: &str) -> bool {
+    match value.pointer(pointer) {
+        Some(serde_json::Value::Bool(flag)) => *flag,
+        Some(serde_json::Value::String(status)) => status == "enabled",
+        _ => false,
+    }
+}
+
+/// Build a request to GitHub from JSON. GitHub changes every setting that is in a request, so
+/// only the settings that are being changed are sent, rather than defaults for the rest.
+fn request<T: DeserializeOwned>(body: serde_json::Value) -> Result<T> {
+    Ok(serde_json::from_value(body)?)
+}
+
+async fn existing_settings(github: &octorust::Client, company: &Company, repo: &GithubRepo) -> Result<RepoSettings> {
+    let org = &company.github_org;
+    let mut existing = RepoSettings::default();
+
+    let full = serde_json::to_value(github.repos().get(org, &repo.name).await?)?;
+    for option in REPO_OPTIONS {
+        let pointer = if option == "secret_scanning" {
+            "/security_and_analysis/secret_scanning/status".to_string()
+        } else {
+            format!("/{}", option)
+        };
+        existing.options.insert(option.to_string(), json_flag(&full, &pointer));
+    }
+
+    if let Some(protection) = get_branch_protection(github, org, &repo.name, &repo.default_branch).await? {
+        existing.branch_protection = Some(GitHubBranchProtectionConfig {
+            required_checks: protection
+                .pointer("/required_status_checks/contexts")
+                .and_then(|contexts| serde_json::from_value(contexts.clone()).ok())
+                .unwrap_or_default(),
+            required_approving_reviews: protection
+                .pointer("/required_pull_request_reviews/required_approving_review_count")
+                .and_then(|count| count.as_i64())
+                .unwrap_or_default(),
+            enforce_admins: json_flag(&protection, "/enforce_admins/enabled"),
+            allow_force_pushes: json_flag(&protection, "/allow_force_pushes/enabled"),
+        });
+    }
+
+    for label in github.issues().list_all_labels_for_repo(org, &repo.name).await? {
+        existing.labels.insert(
+            label.name.to_lowercase(),
+            GitHubLabelConfig {
+                name: label.name,
+                color: label.color,
+                description: label.description,
+            },
+        );
+    }
+
+    Ok(existing)
+}
+
+async fn apply_change(github: &octorust::Client, company: &Company, change: &GitHubRepoSettingsChange) -> Result<()> {
+    let org = &company.github_org;
+
+    match change {
+        GitHubRepoSettingsChange::SetOption { repo, option, value } => {
+            let body = if option == "secret_scanning" {
+                let status = if *value { "enabled" } else { "disabled" };
+                json!({ "security_and_analysis": { "secret_scanning": { "status": status } } })
+            } else {
+                let mut body = serde_json::Map::new();
+                body.insert(option.to_string(), json!(value));
+                serde_json::Value::Object(body)
+            };
+
+            github.repos().update(org, repo, &request(body)?).await?;
+        }
+        GitHubRepoSettingsChange::ProtectBranch {
+            repo,
+            branch,
+            protection,
+            ..
+        } => {
+            // Read the protection again, so whatever was changed since the sync read it is kept.
+            let existing = get_branch_protection(github, org, repo, branch).await?;
+
+            github
+                .repos()
+                .update_branch_protection(
+                    org,
+                    repo,
+                    branch,
+                    &request(protection_request(existing.as_ref(), protection))?,
+                )
+                .await?;
+        }
+        GitHubRepoSettingsChange::CreateLabel { repo, label } => {
+            github
+                .issues()
+                .create_label(
+                    org,
+                    repo,
+                    &request(json!({
+                        "name": label.name,
+                        "color": label.color,
+                        "description": label.description,
+                    }))?,
+                )
+                .await?;
+        }
+        GitHubRepoSettingsChange::UpdateLabel { repo, label } => {
+            github
+                .issues()
+                .update_label(
+                    org,
+                    repo,
+                    &label.name,
+                    &request(json!({
+                        "color": label.color,
+                        "description": label.description,
+                    }))?,
+                )
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+fn drift_message(channel: &str, report: &GitHubRepoSettingsReport) -> FormattedMessage {
+    let mut repos: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for change in &report.changes {
+        repos.entry(change.repo()).or_default().push(format!("• {}", change));
+    }
+
+    let heading = if report.dry_run {
+        "These repos have drifted from the GitHub repo settings in the configs:"
+    } else {
+        "These repos had drifted from the GitHub repo settings in the configs, and were changed back:"
+    };
+
+    let mut text = heading.to_string();
+    for (repo, lines) in repos {
+        text.push_str(&format!("\n*{}*\n{}", repo, lines.join("\n")));
+    }
+
+    if !report.failed_repos.is_empty() {
+        text.push_str(&format!(
+            "\nThe settings of these repos could not be synced: {}",
+            report.failed_repos.join(", ")
+        ));
+    }
+
+    FormattedMessage {
+        channel: channel.to_string(),
+        attachments: Default::default(),
+        blocks: vec![MessageBlock {
+            block_type: MessageBlockType::Section,
+            text: Some(MessageBlockText {
+                text_type: MessageType::Markdown,
+                text,
+            }),
+            elements: Default::default(),
+            accessory: Default::default(),
+            block_id: Default::default(),
+            fields: Default::default(),
+        }],
+    }
+}
+
+/// Bring the settings of the repos in the company's GitHub org in line with the configs. When
+/// `config.repo_settings_dry_run` is set, the report lists the changes without making them.
+/// Archived and ignored repos are left alone.
+pub async fn sync_github_repo_settings(
+    db: &Database,
+    company: &Company,
+    config: &GitHubConfig,
+) -> Result<GitHubRepoSettingsReport> {
+    let github = company.authenticate_github()?;
+    let configs = get_configs_from_repo(&github, company).await?;
+
+    let mut report = GitHubRepoSettingsReport {
+        dry_run: config.repo_settings_dry_run,
+        ..Default::default()
+    };
+
+    if configs.github_repo_settings.is_empty() {
+        return Ok(report);
+    }
+
+    for repo in GithubRepos::get_from_db(db, company.id).await? {
+        if repo.archived || config.ignored_repos.contains(&repo.github_id) {
+            continue;
+        }
+
+        let desired = desired_settings(&repo.name, &configs.github_repo_settings);
+        if desired.is_empty() {
+            continue;
+        }
+
+        match existing_settings(&github, company, &repo).await {
+            Ok(existing) => {
+                report
+                    .changes
+                    .append(&mut repo_changes(&repo.name, &repo.default_branch, &existing, &desired))
+            }
+            Err(e) => {
+                warn!("could not read the settings of repo {}: {}", repo.name, e);
+                report.failed_repos.push(repo.name.to_string());
+            }
+        }
+    }
+
+    if !report.dry_run {
+        for change in &report.changes {
+            match apply_change(&github, company, change).await {
+                Ok(()) => info!("github repo settings: {}", change),
+                Err(e) => {
+                    warn!("github repo settings: could not {}: {}", change, e);
+                    if !report.failed_repos.iter().any(|repo| repo == change.repo()) {
+                        report.failed_repos.push(change.repo().to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    info!("{}", report);
+
+    if !config.repo_settings_channel.is_empty() && (!report.changes.is_empty() || !report.failed_repos.is_empty()) {
+        company
+            .post_to_slack_channel(db, &drift_message(&config.repo_settings_channel, &report))
+            .await?;
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde_json::json;
+
+    use super::{desired_settings, protection_request, repo_changes, GitHubRepoSettingsChange, RepoSettings};
+    use crate::configs::{GitHubBranchProtectionConfig, GitHubLabelConfig, GitHubRepoSettingsConfig};
+
+    fn label(name: &str, color: &str) -> GitHubLabelConfig {
+        GitHubLabelConfig {
+            name: name.to_string(),
+            color: color.to_string(),
+            description: String::new(),
+        }
+    }
+
+    fn configs() -> BTreeMap<String, GitHubRepoSettingsConfig> {
+        BTreeMap::from([
+            (
+                "all".to_string(),
+                GitHubRepoSettingsConfig {
+                    repos: vec!["*".to_string()],
+                    allow_merge_commit: Some(false),
+                    secret_scanning: Some(true),
+                    labels: vec![label("bug", "d73a4a")],
+                    ..Default::default()
+                },
+            ),
+            (
+                "firmware".to_string(),
+                GitHubRepoSettingsConfig {
+                    repos: vec!["hubris*".to_string()],
+                    allow_merge_commit: Some(true),
+                    branch_protection: Some(GitHubBranchProtectionConfig {
+                        required_checks: vec!["build".to_string()],
+                        required_approving_reviews: 1,
+                        ..Default::default()
+                    }),
+                    labels: vec![label("hardware", "0e8a16")],
+                    ..Default::default()
+                },
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_desired_settings_later_entries_win() {
+        let desired = desired_settings("hubris", &configs());
+        assert_eq!(Some(&true), desired.options.get("allow_merge_commit"));
+        assert_eq!(Some(&true), desired.options.get("secret_scanning"));
+        assert!(desired.branch_protection.is_some());
+        assert_eq!(
+            vec!["bug", "hardware"],
+            desired.labels.keys().map(|name| name.as_str()).collect::<Vec<_>>()
+        );
+
+        let desired = desired_settings("omicron", &configs());
+        assert_eq!(Some(&false), desired.options.get("allow_merge_commit"));
+        assert!(desired.branch_protection.is_none());
+        assert!(desired_settings("omicron", &BTreeMap::new()).is_empty());
+    }
+
+    #[test]
+    fn test_repo_changes() {
+        let desired = desired_settings("hubris", &configs());
+        let mut existing = RepoSettings::default();
+        existing.options.insert("allow_merge_commit".to_string(), true);
+        existing.options.insert("secret_scanning".to_string(), false);
+        existing.labels.insert("bug".to_string(), label("Bug", "D73A4A"));
+        existing.branch_protection = Some(GitHubBranchProtectionConfig {
+            required_checks: vec!["build".to_string()],
+            ..Default::default()
+        });
+
+        let changes = repo_changes("hubris", "master", &existing, &desired);
+        assert_eq!(
+            vec![
+                GitHubRepoSettingsChange::SetOption {
+                    repo: "hubris".to_string(),
+                    option: "secret_scanning".to_string(),
+                    value: true,
+                },
+                GitHubRepoSettingsChange::ProtectBranch {
+                    repo: "hubris".to_string(),
+                    branch: "master".to_string(),
+                    protection: desired.branch_protection.clone().unwrap(),
+                    drift: vec!["0 required reviews instead of 1".to_string()],
+                },
+                GitHubRepoSettingsChange::CreateLabel {
+                    repo: "hubris".to_string(),
+                    label: label("hardware", "0e8a16"),
+                },
+            ],
+            changes
+        );
+
+        // Nothing changes once the repo matches the configs.
+        assert!(repo_changes("hubris", "master", &desired, &desired).is_empty());
+    }
+
+    #[test]
+    fn test_protection_request_keeps_what_the_configs_do_not_model() {
+        let protection = GitHubBranchProtectionConfig {
+            required_checks: vec!["build".to_string()],
+            required_approving_reviews: 2,
+            ..Default::default()
+        };
+        let existing = json!({
+            "required_status_checks": { "strict": true, "contexts": ["lint"] },
+            "enforce_admins": { "enabled": true },
+            "required_pull_request_reviews": {
+                "dismiss_stale_reviews": true,
+                "require_code_owner_reviews": true,
+                "required_approving_review_count": 1,
+            },
+            "restrictions": {
+                "users": [{ "login": "jess" }],
+                "teams": [{ "slug": "release" }],
+                "apps": [],
+            },
+            "required_linear_history": { "enabled": true },
+        });
+
+        assert_eq!(
+            json!({
+                "required_status_checks": { "strict": true, "contexts": ["build"] },
+                "enforce_admins": false,
+                "required_pull_request_reviews": {
+                    "dismiss_stale_reviews": true,
+                    "require_code_owner_reviews": true,
+                    "required_approving_review_count": 2,
+                },
+                "restrictions": { "users": ["jess"], "teams": ["release"], "apps": [] },
+                "allow_force_pushes": false,
+                "required_linear_history": true,
+                "allow_deletions": false,
+                "required_conversation_resolution": false,
+                "block_creations": false,
+            }),
+            protection_request(Some(&existing), &protection)
+        );
+
+        // A branch that was not protected gets only what the configs give.
+        assert_eq!(json!(null), protection_request(None, &protection)["restrictions"]);
+    }
+}
@@ -0,0 +1,522 @@
+/*!
+ * Reconciling GitHub org teams with the groups and users in our configs.
+ *
+ * Every group that can be provisioned in GitHub has a team with the same name. The members of
+ * the team are the users in the group, and the repos of the team are the group's `repos`, with
+ * push access, plus any grants from `github-repo-permissions`. Anyone else on a team and any
+ * other repo a team has access to is removed, so that taking someone out of a group or out of
+ * the configs entirely also takes away their access.
+ *
+ * Teams that are not in the configs are left alone and reported, as are org members that are
+ * not a user in the configs.
+ */
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use log::{info, warn};
+
+use crate::{
+    app_config::GitHubConfig,
+    companies::Company,
+    configs::{Config, ExternalServices, GitHubRepoPermissionsConfig, GroupConfig, UserConfig},
+};
+
+/// The permissions a team can have on a repo, from least to most access.
+const REPO_PERMISSIONS: [&str; 5] = ["pull", "triage", "push", "maintain", "admin"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GitHubTeamChange {
+    CreateTeam { team: String },
+    AddMember { team: String, login: String, role: String },
+    RemoveMember { team: String, login: String },
+    GrantRepo { team: String, repo: String, perm: String },
+    RevokeRepo { team: String, repo: String },
+}
+
+impl std::fmt::Display for GitHubTeamChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GitHubTeamChange::CreateTeam { team } => write!(f, "create team `{}`", team),
+            GitHubTeamChange::AddMember { team, login, role } => {
+                write!(f, "add `{}` to team `{}` as {}", login, team, role)
+            }
+            GitHubTeamChange::RemoveMember { team, login } => write!(f, "remove `{}` from team `{}`", login, team),
+            GitHubTeamChange::GrantRepo { team, repo, perm } => {
+                write!(f, "grant team `{}` {} access to `{}`", team, perm, repo)
+            }
+            GitHubTeamChange::RevokeRepo { team, repo } => write!(f, "revoke team `{}` access to `{}`", team, repo),
+        }
+    }
+}
+
+/// What the sync changed, or would change when it is a dry run.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GitHubTeamsReport {
+    pub dry_run: bool,
+    pub changes: Vec<GitHubTeamChange>,
+    /// Teams in the org that are not a group in the configs.
+    pub unmanaged_teams: Vec<String>,
+    /// Members of the org that are not a user in the configs.
+    pub unknown_members: Vec<String>,
+}
+
+impl std::fmt::Display for GitHubTeamsReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let verb = if self.dry_run { "would make" } else { "made" };
+        writeln!(f, "github teams sync {} {} changes", verb, self.changes.len())?;
+
+        for change in &self.changes {
+            writeln!(f, "- {}", change)?;
+        }
+
+        if !self.unmanaged_teams.is_empty() {
+            writeln!(f, "teams not in the configs: {}", self.unmanaged_teams.join(", "))?;
+        }
+
+        if !self.unknown_members.is_empty() {
+            writeln!(f, "org members not in the configs: {}", self.unknown_members.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The GitHub logins of the members of `group`, and whether they maintain the team. Logins are
+/// lowercased since GitHub does not care about case.
+fn desired_team_members(group: &str, users: &BTreeMap<String, UserConfig>) -> BTreeMap<String, String> {
+    users
+        .values()
+        .filter(|user| {
+            !user.github.is_empty()
+                && !user.denied_services.contains(&ExternalServices::GitHub)
+                && user.groups.iter().any(|g| g == group)
+        })
+        .map(|user| {
+            let role = if user.is_group_admin { "maintainer" } else { "member" };
+            (user.github.to_lowercase(), role.to_string())
+        })
+        .collect()
+}
+
+/// The repos a team should have access to, and the permission it should have on each. When a
+/// repo is granted more than once the most access wins.
+fn desired_team_repos(
+    group: &GroupConfig,
+    permissions: &BTreeMap<String, GitHubRepoPermissionsConfig>,
+) -> Result<BTreeMap<String, String>> {
+    let mut repos: BTreeMap<String, String> = group
+        .repos
+        .iter()
+        .map(|repo| (repo.to_string(), "push".to_string()))
+        .collect();
+
+    for (name, config) in permissions {
+        if !config.teams.contains(&group.name) {
+            continue;
+        }
+
+        let rank = match permission_rank(&config.perm) {
+            Some(rank) => rank,
+            None => bail!(
+                "github repo permissions `{}` has an unknown perm `{}`",
+                name,
+                config.perm
+            ),
+        };
+
+        for repo in &config.repos {
+            let existing = repos.get(repo).and_then(|perm| permission_rank(perm));
+            if existing.map(|existing| existing < rank).unwrap_or(true) {
+                repos.insert(repo.to_string(), config.perm.to_string());
+            }
+        }
+    }
+
+    Ok(repos)
+}
+
+fn permission_rank(perm: &str) -> Option<usize> {
+    REPO_PERMISSIONS.iter().position(|p| *p == perm)
+}
+
+/// The highest permission in the permissions GitHub returns for a repo listed for a team.
+fn highest_permission(permissions: &octorust::types::MinimalRepositoryPermissions) -> &'static str {
+    if permissions.admin {
+        "admin"
+    } else if permissions.maintain {
+        "maintain"
+    } else if permissions.push {
+        "push"
+    } else if permissions.triage {
+        "triage"
+    } else {
+        "pull"
+    }
+}
+
+/// The changes that turn the existing members and repos of a team into the desired ones.
+fn team_changes(
+    team: &str,
+    existing_members: &BTreeMap<String, String>,
+    desired_members: &BTreeMap<String, String>,
+    existing_repos: &BTreeMap<String, String>,
+    desired_repos: &BTreeMap<String, String>,
+) -> Vec<GitHubTeamChange> {
+    let mut changes = vec![];
+
+    for (login, role) in desired_members {
+        if existing_members.get(login) != Some(role) {
+            changes.push(GitHubTeamChange::AddMember {
+                team: team.to_string(),
+                login: login.to_string(),
+                role: role.to_string(),
+            });
+        }
+    }
+
+    for login in existing_members.keys() {
+        if !desired_members.contains_key(login) {
+            changes.push(GitHubTeamChange::RemoveMember {
+                team: team.to_string(),
+                login: login.to_string(),
+            });
+        }
+    }
+
+    for (repo, perm) in desired_repos {
+        if existing_repos.get(repo) != Some(perm) {
+            changes.push(GitHubTeamChange::GrantRepo {
+                team: team.to_string(),
+                repo: repo.to_string(),
+                perm: perm.to_string(),
+            });
+        }
+    }
+
+    for repo in existing_repos.keys() {
+        if !desired_repos.contains_key(repo) {
+            changes.push(GitHubTeamChange::RevokeRepo {
+                team: team.to_string(),
+                repo: repo.to_string(),
+            });
+        }
+    }
+
+    changes
+}
+
+async fn list_team_members(
+    github: &octorust::Client,
+    company: &Company,
+    team: &octorust::types::Team,
+) -> Result<BTreeMap<String, String>> {
+    let mut members = BTreeMap::new();
+
+    for (role, name) in [
+        (octorust::types::TeamsListMembersInOrgRole::Member, "member"),
+        (octorust::types::TeamsListMembersInOrgRole::Maintainer, "maintainer"),
+    ] {
+        for member in github
+            .teams()
+            .list_all_members_in_org(&company.github_org, &team.slug, role)
+            .await?
+        {
+            members.insert(member.login.to_lowercase(), name.to_string());
+        }
+    }
+
+    Ok(members)
+}
+
+async fn list_team_repos(
+    github: &octorust::Client,
+    company: &Company,
+    team: &octorust::types::Team,
+) -> Result<BTreeMap<String, String>> {
+    Ok(github
+        .teams()
+        .list_all_repos_in_org(&company.github_org, &team.slug)
+        .await?
+        .into_iter()
+        // Only repos in our org can be granted from the configs.
+        .filter(|repo| repo.full_name == format!("{}/{}", company.github_org, repo.name))
+        .map(|repo| {
+            let perm = repo
+                .permissions
+                .as_ref()
+                .map(highest_permission)
+                .unwrap_or("pull")
+                .to_string();
+            (repo.name, perm)
+        })
+        .collect())
+}
+
+async fn apply_change(github: &octorust::Client, company: &Company, change: &GitHubTeamChange) -> Result<()> {
+    let org = &company.github_org;
+
+    match change {
+        GitHubTeamChange::CreateTeam { team } => {
+            github
+                .teams()
+                .create(
+                    org,
+                    &octorust::types::TeamsCreateRequest {
+                        name: team.to_string(),
+                        description: String::new(),
+                        maintainers: Default::default(),
+                        privacy: Some(octorust::types::Privacy::Closed),
+                        permission: None, // This is depreciated, so just pass none.
+                        parent_team_id: 0,
+                        repo_names: Default::default(),
+                    },
+                )
+                .await?;
+        }
+        GitHubTeamChange::AddMember { team, login, role } => {
+            let role = if role == "maintainer" {
+                octorust::types::TeamMembershipRole::Maintainer
+            } else {
+                octorust::types::TeamMembershipRole::Member
+            };
+
+            github
+                .teams()
+                .add_or_update_membership_for_user_in_org(
+                    org,
+                    team,
+                    login,
+                    &octorust::types::TeamsAddUpdateMembershipUserInOrgRequest { role: Some(role) },
+                )
+                .await?;
+        }
+        GitHubTeamChange::RemoveMember { team, login } => {
+            github
+                .teams()
+                .remove_membership_for_user_in_org(org, team, login)
+                .await?;
+        }
+        GitHubTeamChange::GrantRepo { team, repo, perm } => {
+            let permission = match perm.as_str() {
+                "pull" => octorust::types::TeamsAddUpdateRepoPermissionsInOrgRequestPermission::Pull,
+                "triage" => octorust::types::TeamsAddUpdateRepoPermissionsInOrgRequestPermission::Triage,
+                "push" => octorust::types::TeamsAddUpdateRepoPermissionsInOrgRequestPermission::Push,
+                "maintain" => octorust::types::TeamsAddUpdateRepoPermissionsInOrgRequestPermission::Maintain,
+                "admin" => octorust::types::TeamsAddUpdateRepoPermissionsInOrgRequestPermission::Admin,
+                _ => bail!("unknown github repo permission `{}`", perm),
+            };
+
+            github
+                .teams()
+                .add_or_update_repo_permissions_in_org(
+                    org,
+                    team,
+                    org,
+                    repo,
+                    &octorust::types::TeamsAddUpdateRepoPermissionsInOrgRequest {
+                        permission: Some(permission),
+                    },
+                )
+                .await?;
+        }
+        GitHubTeamChange::RevokeRepo { team, repo } => {
+            github.teams().remove_repo_in_org(org, team, org, repo).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Reconcile the teams in the company's GitHub org with the configs. When `config.teams_dry_run`
+/// is set, the report lists the changes without making them.
+pub async fn sync_github_teams(company: &Company, config: &GitHubConfig) -> Result<GitHubTeamsReport> {
+    let github = company.authenticate_github()?;
+    let configs: Config = crate::configs::get_configs_from_repo(&github, company).await?;
+
+    let mut report = GitHubTeamsReport {
+        dry_run: config.teams_dry_run,
+        ..Default::default()
+    };
+
+    let teams = github.teams().list_all(&company.github_org).await?;
+
+    let groups: Vec<&GroupConfig> = configs
+        .groups
+        .values()
+        .filter(|group| group.supports_provisioning_in(&ExternalServices::GitHub))
+        .collect();
+
+    report.unmanaged_teams = teams
+        .iter()
+        .filter(|team| !groups.iter().any(|group| group.name == team.name))
+        .map(|team| team.name.to_string())
+        .collect();
+
+    for group in groups {
+        let team = teams.iter().find(|team| team.name == group.name);
+
+        let (existing_members, existing_repos) = match team {
+            Some(team) => (
+                list_team_members(&github, company, team).await?,
+                list_team_repos(&github, company, team).await?,
+            ),
+            None => {
+                report.changes.push(GitHubTeamChange::CreateTeam {
+                    team: group.name.to_string(),
+                });
+                (BTreeMap::new(), BTreeMap::new())
+            }
+        };
+
+        let desired_members = desired_team_members(&group.name, &configs.users);
+        let desired_repos = match desired_team_repos(group, &configs.github_repo_permissions) {
+            Ok(repos) => repos,
+            Err(e) => {
+                warn!("skipping the repos of github team `{}`: {}", group.name, e);
+                existing_repos.clone()
+            }
+        };
+
+        // Teams are addressed by their slug, which is what GitHub derives from the name.
+        let slug = team
+            .map(|team| team.slug.to_string())
+            .unwrap_or_else(|| group.name.to_string());
+
+        report.changes.append(&mut team_changes(
+            &slug,
+            &existing_members,
+            &desired_members,
+            &existing_repos,
+            &desired_repos,
+        ));
+    }
+
+    let known: Vec<String> = configs
+        .users
+        .values()
+        .filter(|user| !user.github.is_empty())
+        .map(|user| user.github.to_lowercase())
+        .chain(
+            configs
+                .github_outside_collaborators
+                .values()
+                .flat_map(|collaborators| collaborators.users.iter().map(|user| user.to_lowercase())),
+        )
+        .collect();
+
+    report.unknown_members = github
+        .orgs()
+        .list_all_members(
+            &company.github_org,
+            octorust::types::OrgsListMembersFilter::All,
+            octorust::types::OrgsListMembersRole::All,
+        )
+        .await?
+        .into_iter()
+        .map(|member| member.login)
+        .filter(|login| !known.contains(&login.to_lowercase()))
+        .collect();
+
+    if !report.dry_run {
+        for change in &report.changes {
+            apply_change(&github, company, change).await?;
+            info!("github teams: {}", change);
+        }
+    }
+
+    info!("{}", report);
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{desired_team_repos, team_changes, GitHubTeamChange};
+    use crate::configs::{GitHubRepoPermissionsConfig, GroupConfig};
+
+    fn map(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_desired_team_repos() {
+        let group = GroupConfig {
+            name: "eng".to_string(),
+            repos: vec!["omicron".to_string(), "docs".to_string()],
+            ..Default::default()
+        };
+
+        let mut permissions = BTreeMap::new();
+        permissions.insert(
+            "eng-admin".to_string(),
+            GitHubRepoPermissionsConfig {
+                teams: vec!["eng".to_string()],
+                repos: vec!["omicron".to_string()],
+                perm: "admin".to_string(),
+                ..Default::default()
+            },
+        );
+        permissions.insert(
+            "read-only".to_string(),
+            GitHubRepoPermissionsConfig {
+                teams: vec!["eng".to_string(), "sales".to_string()],
+                repos: vec!["docs".to_string(), "handbook".to_string()],
+                perm: "pull".to_string(),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            map(&[("docs", "push"), ("handbook", "pull"), ("omicron", "admin")]),
+            desired_team_repos(&group, &permissions).unwrap()
+        );
+
+        permissions.get_mut("read-only").unwrap().perm = "read".to_string();
+        assert!(desired_team_repos(&group, &permissions).is_err());
+    }
+
+    #[test]
+    fn test_team_changes() {
+        let changes = team_changes(
+            "eng",
+            &map(&[("jane", "member"), ("joe", "member"), ("sam", "maintainer")]),
+            &map(&[("jane", "member"), ("sam", "member"), ("kim", "member")]),
+            &map(&[("omicron", "push"), ("old", "pull")]),
+            &map(&[("omicron", "admin")]),
+        );
+
+        assert_eq!(
+            vec![
+                GitHubTeamChange::AddMember {
+                    team: "eng".to_string(),
+                    login: "kim".to_string(),
+                    role: "member".to_string(),
+                },
+                GitHubTeamChange::AddMember {
+                    team: "eng".to_string(),
+                    login: "sam".to_string(),
+                    role: "member".to_string(),
+                },
+                GitHubTeamChange::RemoveMember {
+                    team: "eng".to_string(),
+                    login: "joe".to_string(),
+                },
+                GitHubTeamChange::GrantRepo {
+                    team: "eng".to_string(),
+                    repo: "omicron".to_string(),
+                    perm: "admin".to_string(),
+                },
+                GitHubTeamChange::RevokeRepo {
+                    team: "eng".to_string(),
+                    repo: "old".to_string(),
+                },
+            ],
+            changes
+        );
+    }
+}
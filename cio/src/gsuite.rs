@@ -1,20 +1,27 @@
-use std::{collections::HashMap, time};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    time,
+};
 
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Utc};
 use gsuite_api::{
     types::{
         Building as GSuiteBuilding, BuildingAddress, CalendarResource as GSuiteCalendarResource, Group as GSuiteGroup,
-        Ims, User as GSuiteUser, UserAddress, UserEmail, UserGender, UserLocation, UserName, UserPhone,
-        UserSshPublicKey,
+        Ims, Member as GSuiteMember, User as GSuiteUser, UserAddress, UserEmail, UserGender, UserLocation, UserName,
+        UserOrganization, UserPhone, UserSshPublicKey,
     },
     Client as GSuite,
 };
-use log::info;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::{
+    access_requests::granted_google_group_members,
+    app_config::GoogleWorkspaceConfig,
     companies::Company,
-    configs::{Building, Group, Resource, User},
+    configs::{Building, ExternalServices, Group, Groups, Resource, User, Users},
     db::Database,
     providers::{ProviderReadOps, ProviderWriteOps},
     utils::generate_password,
@@ -139,6 +146,15 @@ pub async fn update_gsuite_user(gu: &GSuiteUser, user: &User, change_password: b
         });
     }
 
+    if !user.department.is_empty() {
+        gsuite_user.organizations = vec![UserOrganization {
+            department: user.department.to_string(),
+            primary: true,
+            type_: "work".to_string(),
+            ..Default::default()
+        }];
+    }
+
     if !user.building.is_empty() {
         gsuite_user.locations = vec![UserLocation {
             area: user.building.to_string(),
@@ -217,6 +233,305 @@ pub async fn update_gsuite_user(gu: &GSuiteUser, user: &User, change_password: b
     gsuite_user
 }
 
+/// Suspend a user in GSuite, move them to the suspended organizational unit and, if configured,
+/// transfer their Drive files to their manager. Returns what was done, for the offboarding report.
+pub async fn offboard_gsuite_user(
+    gsuite: &GSuite,
+    db: &Database,
+    company: &Company,
+    user: &User,
+    config: &GoogleWorkspaceConfig,
+) -> Result<String> {
+    // First get the user from gsuite.
+    let mut gsuite_user = gsuite
+        .users()
+        .get(
+            &user.email,
+            gsuite_api::types::DirectoryUsersListProjection::Full,
+            gsuite_api::types::ViewType::AdminView,
+        )
+        .await?;
+
+    // Set them to be suspended.
+    gsuite_user.suspended = true;
+    gsuite_user.suspension_reason = "Offboarded.".to_string();
+
+    if !config.suspended_org_unit_path.is_empty() {
+        gsuite_user.org_unit_path = config.suspended_org_unit_path.to_string();
+    }
+
+    // Update the user.
+    gsuite.users().update(&user.email, &gsuite_user).await?;
+
+    info!("suspended user `{}` from gsuite", user.email);
+
+    if !config.transfer_drive_to_manager {
+        return Ok("suspended the account".to_string());
+    }
+
+    // Suspended users keep their files, so a failed transfer can be retried by hand.
+    let manager = match User::get_from_db(db, user.cio_company_id, user.manager.to_string()).await {
+        Some(manager) if !manager.google_id.is_empty() => manager,
+        _ => {
+            warn!(
+                "user `{}` does not have a manager in GSuite, not transferring their Drive files",
+                user.email
+            );
+            return Ok("suspended the account, there is no manager to transfer the Drive files to".to_string());
+        }
+    };
+
+    let data_transfer = company.authenticate_google_data_transfer().await?;
+    let transfer = data_transfer
+        .transfer_drive_files(&gsuite_user.id, &manager.google_id)
+        .await?;
+
+    info!(
+        "started transfer `{}` of the Drive files of `{}` to `{}`",
+        transfer.id, user.email, manager.email
+    );
+
+    Ok(format!(
+        "suspended the account and started transfer `{}` of the Drive files to {}",
+        transfer.id, manager.email
+    ))
+}
+
+const DATA_TRANSFER_ENDPOINT: &str = "https://admin.googleapis.com/admin/datatransfer/v1";
+
+/// The name of the Drive application in the Data Transfer API.
+const DRIVE_APPLICATION_NAME: &str = "Drive and Docs";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DataTransferApplication {
+    id: String,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DataTransferApplications {
+    #[serde(default)]
+    applications: Vec<DataTransferApplication>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ApplicationTransferParam {
+    key: String,
+    value: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ApplicationDataTransfer {
+    application_id: String,
+    #[serde(default)]
+    application_transfer_params: Vec<ApplicationTransferParam>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataTransfer {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    pub old_owner_user_id: String,
+    pub new_owner_user_id: String,
+    #[serde(default)]
+    application_data_transfers: Vec<ApplicationDataTransfer>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub overall_transfer_status_code: String,
+}
+
+/// A minimal client for the Admin SDK Data Transfer API, which moves the data owned by one user
+/// to another user.
+pub struct GoogleDataTransfer {
+    client: reqwest::Client,
+    token: String,
+    customer_id: String,
+}
+
+impl GoogleDataTransfer {
+    pub fn new(token: &str, customer_id: &str) -> Self {
+        GoogleDataTransfer {
+            client: reqwest::Client::new(),
+            token: token.to_string(),
+            customer_id: customer_id.to_string(),
+        }
+    }
+
+    async fn drive_application_id(&self) -> Result<String> {
+        let response: DataTransferApplications = self
+            .client
+            .get(format!("{}/applications", DATA_TRANSFER_ENDPOINT))
+            .bearer_auth(&self.token)
+            .query(&[("customerId", &self.customer_id)])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        response
+            .applications
+            .into_iter()
+            .find(|application| application.name == DRIVE_APPLICATION_NAME)
+            .map(|application| application.id)
+            .ok_or_else(|| {
+                anyhow!(
+                    "could not find the `{}` data transfer application",
+                    DRIVE_APPLICATION_NAME
+                )
+            })
+    }
+
+    /// Transfer both the private and shared Drive files of one user to another. Users are
+    /// identified by their GSuite ids, not their email addresses.
+    pub async fn transfer_drive_files(&self, old_owner_user_id: &str, new_owner_user_id: &str) -> Result<DataTransfer> {
+        let transfer = DataTransfer {
+            old_owner_user_id: old_owner_user_id.to_string(),
+            new_owner_user_id: new_owner_user_id.to_string(),
+            application_data_transfers: vec![ApplicationDataTransfer {
+                application_id: self.drive_application_id().await?,
+                application_transfer_params: vec![ApplicationTransferParam {
+                    key: "PRIVACY_LEVEL".to_string(),
+                    value: vec!["PRIVATE".to_string(), "SHARED".to_string()],
+                }],
+            }],
+            ..Default::default()
+        };
+
+        Ok(self
+            .client
+            .post(format!("{}/transfers", DATA_TRANSFER_ENDPOINT))
+            .bearer_auth(&self.token)
+            .json(&transfer)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+}
+
+const REPORTS_ENDPOINT: &str = "https://admin.googleapis.com/admin/reports/v1";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ActivityParameter {
+    name: String,
+    #[serde(default)]
+    value: String,
+    #[serde(default, with = "int_value")]
+    int_value: i64,
+}
+
+/// The Reports API returns 64 bit integers as strings.
+mod int_value {
+    use serde::{Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<i64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ActivityEvent {
+    #[serde(default)]
+    parameters: Vec<ActivityParameter>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Activity {
+    #[serde(default)]
+    events: Vec<ActivityEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Activities {
+    #[serde(default)]
+    items: Vec<Activity>,
+    #[serde(default)]
+    next_page_token: String,
+}
+
+/// A minimal client for the Admin SDK Reports API.
+pub struct GoogleReports {
+    client: reqwest::Client,
+    token: String,
+}
+
+impl GoogleReports {
+    pub fn new(token: &str) -> Self {
+        GoogleReports {
+            client: reqwest::Client::new(),
+            token: token.to_string(),
+        }
+    }
+
+    /// The people that joined a Google Meet call, keyed by email, along with the number of
+    /// seconds they were in the call. The meeting code is the code from the Meet link, for
+    /// instance `abc-defg-hij`.
+    pub async fn meet_attendance(
+        &self,
+        meeting_code: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<HashMap<String, i64>> {
+        // Reports list meeting codes in upper case and without dashes.
+        let meeting_code = meeting_code.replace('-', "").to_uppercase();
+
+        let mut attendance: HashMap<String, i64> = HashMap::new();
+        let mut page_token = String::new();
+
+        loop {
+            let response: Activities = self
+                .client
+                .get(format!("{}/activity/users/all/applications/meet", REPORTS_ENDPOINT))
+                .bearer_auth(&self.token)
+                .query(&[
+                    ("eventName", "call_ended"),
+                    ("filters", &format!("meeting_code=={}", meeting_code)),
+                    ("startTime", &start.to_rfc3339()),
+                    ("endTime", &end.to_rfc3339()),
+                    ("pageToken", &page_token),
+                ])
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+
+            for event in response.items.iter().flat_map(|activity| activity.events.iter()) {
+                let parameter = |name: &str| event.parameters.iter().find(|p| p.name == name);
+
+                // Only people that are signed in to their account have an identifier.
+                let email = match parameter("identifier") {
+                    Some(p) if p.value.contains('@') => p.value.to_lowercase(),
+                    _ => continue,
+                };
+                let seconds = parameter("duration_seconds").map(|p| p.int_value).unwrap_or_default();
+
+                // People that drop and rejoin end up with one event for each time they joined.
+                *attendance.entry(email).or_default() += seconds;
+            }
+
+            if response.next_page_token.is_empty() {
+                break;
+            }
+            page_token = response.next_page_token;
+        }
+
+        Ok(attendance)
+    }
+}
+
 /// Update a user's aliases in GSuite to match our database.
 pub async fn update_user_aliases(
     gsuite: &GSuite,
@@ -265,10 +580,125 @@ pub async fn update_user_aliases(
     Ok(())
 }
 
-/// Update a user's groups in GSuite to match our database.
-pub async fn update_user_google_groups(gsuite: &GSuite, user: &User, company: &Company) -> Result<()> {
+/// The members a Google group should have, keyed by email, with the role they should have.
+/// These are the users that are in the group in our configs and the ones in `granted`, who were
+/// given access to it through an access request.
+fn desired_group_members(group: &str, users: &[User], granted: &BTreeSet<String>) -> BTreeMap<String, String> {
+    users
+        .iter()
+        .filter(|user| {
+            !user.email.is_empty()
+                && (user.groups.iter().any(|g| g == group) || granted.contains(&user.email.to_lowercase()))
+                && !user.denied_services.contains(&ExternalServices::Google)
+        })
+        .map(|user| {
+            let role = if user.is_group_admin { "OWNER" } else { "MEMBER" };
+            (user.email.to_lowercase(), role.to_string())
+        })
+        .collect()
+}
+
+/// The emails of the members that need to be added to a Google group, or whose role needs to
+/// change, and the emails of the members that need to be removed. Only users in our own domain
+/// are ever removed, so that nested groups and external members that were added by hand are
+/// left alone.
+fn group_membership_changes(
+    existing: &[GSuiteMember],
+    desired: &BTreeMap<String, String>,
+    domain: &str,
+) -> (Vec<String>, Vec<String>) {
+    let domain = format!("@{}", domain.to_lowercase());
+
+    let to_add = desired
+        .iter()
+        .filter(|(email, role)| {
+            !existing
+                .iter()
+                .any(|member| member.email.to_lowercase() == **email && member.role == **role)
+        })
+        .map(|(email, _)| email.to_string())
+        .collect();
+
+    let to_remove = existing
+        .iter()
+        .filter(|member| {
+            let email = member.email.to_lowercase();
+            (member.type_.is_empty() || member.type_ == "USER")
+                && email.ends_with(&domain)
+                && !desired.contains_key(&email)
+        })
+        .map(|member| member.email.to_string())
+        .collect();
+
+    (to_add, to_remove)
+}
+
+/// Reconcile the members of a Google group with the users that are in the group in our
+/// configs, and the ones in `granted` that were given access to it through an access request.
+pub async fn sync_google_group_members(
+    gsuite: &GSuite,
+    company: &Company,
+    group: &Group,
+    users: &[User],
+    granted: &BTreeSet<String>,
+) -> Result<()> {
+    let group_key = format!("{}@{}", group.name, company.gsuite_domain);
+    let existing = gsuite.members().list_all(&group_key, false, "").await?;
+    let desired = desired_group_members(&group.name, users, granted);
+
+    let (to_add, to_remove) = group_membership_changes(&existing, &desired, &company.gsuite_domain);
+
+    for email in to_add {
+        if let Some(user) = users.iter().find(|user| user.email.to_lowercase() == email) {
+            gsuite.add_user_to_group(company, user, &group.name).await?;
+        }
+    }
+
+    // People that have left the company are no longer in our users table, so members are
+    // removed by their email.
+    for email in to_remove {
+        gsuite.members().delete(&group_key, &email).await?;
+        info!("removed `{}` from GSuite group `{}`", email, group.name);
+    }
+
+    Ok(())
+}
+
+/// Make sure every group in our configs exists in GSuite with the right settings, like who can
+/// post, and that its members match the groups our users are in. This mirrors how GitHub teams
+/// are kept in sync.
+pub async fn sync_google_groups(db: &Database, company: &Company) -> Result<()> {
+    let gsuite = company.authenticate_google_admin(db).await?;
+
+    let users: Vec<User> = Users::get_from_db(db, company.id).await?.into();
+    let groups = Groups::get_from_db(db, company.id).await?;
+    let granted = granted_google_group_members(db, company.id).await?;
+    let none = BTreeSet::new();
+
+    for group in groups {
+        if !group.supports_provisioning_in(&ExternalServices::Google) {
+            continue;
+        }
+
+        // This creates the group if it does not exist yet and updates its settings.
+        gsuite.ensure_group(db, company, &group).await?;
+
+        let granted = granted.get(&group.name).unwrap_or(&none);
+        if let Err(e) = sync_google_group_members(&gsuite, company, &group, &users, granted).await {
+            warn!("failed to sync the members of GSuite group `{}`: {}", group.name, e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Update a user's groups in GSuite to match our database. Groups the user was given access to
+/// through an access request are left alone.
+pub async fn update_user_google_groups(db: &Database, gsuite: &GSuite, user: &User, company: &Company) -> Result<()> {
     // Get all the GSuite groups.
     let gsuite_groups = gsuite.list_provider_groups(company).await?;
+    let granted = granted_google_group_members(db, company.id).await?;
+    let email = user.email.to_lowercase();
 
     // Iterate over the groups and add the user as a member to it.
     for group in &user.groups {
@@ -281,7 +711,12 @@ pub async fn update_user_google_groups(gsuite: &GSuite, user: &User, company: &C
     // Iterate over all the groups and if the user is a member and should not
     // be, remove them from the group.
     for group in &gsuite_groups {
-        if user.groups.contains(&group.name) {
+        if user.groups.contains(&group.name)
+            || granted
+                .get(&group.name)
+                .map(|members| members.contains(&email))
+                .unwrap_or_default()
+        {
             // They should be in the group, continue.
             continue;
         }
@@ -427,3 +862,46 @@ pub fn update_gsuite_calendar_resource(
 
     gsuite_resource
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use gsuite_api::types::Member;
+
+    use super::group_membership_changes;
+
+    fn member(email: &str, role: &str, type_: &str) -> Member {
+        Member {
+            role: role.to_string(),
+            email: email.to_string(),
+            delivery_settings: "ALL_MAIL".to_string(),
+            etag: "".to_string(),
+            id: "".to_string(),
+            kind: "".to_string(),
+            status: "".to_string(),
+            type_: type_.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_group_membership_changes() {
+        let existing = vec![
+            member("Jane@Example.com", "MEMBER", "USER"),
+            member("joe@example.com", "MEMBER", "USER"),
+            member("left@example.com", "MEMBER", "USER"),
+            member("eng@example.com", "MEMBER", "GROUP"),
+            member("vendor@partner.com", "MEMBER", "USER"),
+        ];
+
+        let mut desired = BTreeMap::new();
+        desired.insert("jane@example.com".to_string(), "MEMBER".to_string());
+        desired.insert("joe@example.com".to_string(), "OWNER".to_string());
+        desired.insert("new@example.com".to_string(), "MEMBER".to_string());
+
+        let (to_add, to_remove) = group_membership_changes(&existing, &desired, "example.com");
+
+        assert_eq!(vec!["joe@example.com", "new@example.com"], to_add);
+        assert_eq!(vec!["left@example.com"], to_remove);
+    }
+}
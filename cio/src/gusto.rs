@@ -0,0 +1,255 @@
+/*!
+ * Pulling employee records from Gusto.
+ *
+ * Gusto is the source of truth for when people start, which department they are in, their title,
+ * who they report to and when they leave. These are copied onto users when the configs are
+ * synced, and `sync_gusto_employees` copies them in between, so edits in Gusto do not wait for a
+ * change to the configs.
+ */
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use log::{info, warn};
+
+use crate::{
+    companies::Company,
+    configs::{User, Users},
+    db::Database,
+};
+
+/// The parts of a Gusto employee that are copied onto a user.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GustoEmployeeRecord {
+    pub gusto_id: String,
+    pub start_date: Option<NaiveDate>,
+    pub department: String,
+    /// The title of the employee's primary job.
+    pub title: String,
+    /// The username of the employee's manager, empty when they do not have one or when their
+    /// manager is not a user.
+    pub manager: String,
+    pub termination_date: Option<NaiveDate>,
+}
+
+/// All the employees of a company in Gusto.
+#[derive(Debug, Default, Clone)]
+pub struct GustoEmployees {
+    by_email: HashMap<String, gusto_api::types::Employee>,
+    by_id: HashMap<String, gusto_api::types::Employee>,
+    /// Usernames keyed by both work and personal emails, so that managers can be matched with
+    /// their user no matter which email Gusto has for them.
+    usernames: HashMap<String, String>,
+}
+
+impl GustoEmployees {
+    pub fn new(employees: Vec<gusto_api::types::Employee>, usernames: HashMap<String, String>) -> Self {
+        let mut gusto_employees = GustoEmployees {
+            usernames,
+            ..Default::default()
+        };
+
+        for employee in employees {
+            gusto_employees
+                .by_email
+                .insert(employee.email.to_lowercase(), employee.clone());
+            gusto_employees.by_id.insert(employee.id.to_string(), employee);
+        }
+
+        gusto_employees
+    }
+
+    /// Get the company's employees from Gusto. Companies that do not use Gusto have no employees.
+    pub async fn fetch(db: &Database, company: &Company, usernames: HashMap<String, String>) -> Result<Self> {
+        let employees = match company.authenticate_gusto(db).await {
+            Ok((gusto, gusto_company_id)) => gusto.employees().get_all_company(&gusto_company_id, false, &[]).await?,
+            Err(_) => vec![],
+        };
+
+        Ok(GustoEmployees::new(employees, usernames))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_id.is_empty()
+    }
+
+    pub fn get_by_email(&self, email: &str) -> Option<&gusto_api::types::Employee> {
+        if email.is_empty() {
+            return None;
+        }
+
+        self.by_email.get(&email.to_lowercase())
+    }
+
+    pub fn get_by_id(&self, id: &str) -> Option<&gusto_api::types::Employee> {
+        self.by_id.get(id)
+    }
+
+    /// Find the employee for a user, by their Gusto id if we know it, otherwise by either of
+    /// their emails.
+    pub fn find(&self, gusto_id: &str, email: &str, recovery_email: &str) -> Option<&gusto_api::types::Employee> {
+        self.get_by_id(gusto_id)
+            .or_else(|| self.get_by_email(email))
+            .or_else(|| self.get_by_email(recovery_email))
+    }
+
+    pub fn record(&self, employee: &gusto_api::types::Employee) -> GustoEmployeeRecord {
+        let manager = if employee.manager_id > 0 {
+            self.get_by_id(&employee.manager_id.to_string())
+                .and_then(|manager| self.usernames.get(&manager.email.to_lowercase()))
+                .cloned()
+                .unwrap_or_default()
+        } else {
+            String::new()
+        };
+
+        // Someone that was terminated and then rehired keeps their old terminations, so only
+        // the latest one counts and only while they are terminated.
+        let termination_date = if employee.terminated {
+            employee
+                .terminations
+                .iter()
+                .filter_map(|termination| termination.effective_date)
+                .max()
+        } else {
+            None
+        };
+
+        let job = employee
+            .jobs
+            .iter()
+            .find(|job| job.primary)
+            .or_else(|| employee.jobs.first());
+
+        GustoEmployeeRecord {
+            gusto_id: employee.id.to_string(),
+            start_date: employee.jobs.first().and_then(|job| job.hire_date),
+            department: employee.department.trim().to_string(),
+            title: job.map(|job| job.title.trim().to_string()).unwrap_or_default(),
+            manager,
+            termination_date,
+        }
+    }
+}
+
+/// Usernames keyed by the work and personal emails of the users.
+pub fn usernames_by_email(users: &[User]) -> HashMap<String, String> {
+    let mut usernames = HashMap::new();
+
+    for user in users {
+        for email in [&user.email, &user.recovery_email] {
+            if !email.is_empty() {
+                usernames.insert(email.to_lowercase(), user.username.to_string());
+            }
+        }
+    }
+
+    usernames
+}
+
+impl User {
+    /// Copy the fields that Gusto is the source of truth for onto the user. Returns whether
+    /// anything changed.
+    pub fn apply_gusto_record(&mut self, record: &GustoEmployeeRecord) -> bool {
+        let before = self.clone();
+
+        self.gusto_id = record.gusto_id.to_string();
+
+        if let Some(start_date) = record.start_date {
+            self.start_date = start_date;
+        }
+
+        if !record.department.is_empty() {
+            self.department = titlecase::titlecase(&record.department);
+        }
+
+        if !record.title.is_empty() {
+            self.title = record.title.to_string();
+        }
+
+        if !record.manager.is_empty() {
+            self.manager = record.manager.to_string();
+            self.link_to_manager = vec![record.manager.to_string()];
+        }
+
+        self.termination_date = record.termination_date;
+
+        *self != before
+    }
+}
+
+/// Copy start dates, departments, titles, managers and terminations from Gusto onto the users in
+/// the database.
+pub async fn sync_gusto_employees(db: &Database, company: &Company) -> Result<()> {
+    let users: Vec<User> = Users::get_from_db(db, company.id).await?.into();
+
+    let employees = GustoEmployees::fetch(db, company, usernames_by_email(&users)).await?;
+    if employees.is_empty() {
+        info!("company `{}` has no employees in gusto, skipping", company.name);
+        return Ok(());
+    }
+
+    for mut user in users {
+        let employee = match employees.find(&user.gusto_id, &user.email, &user.recovery_email) {
+            Some(employee) => employee,
+            None => continue,
+        };
+
+        let record = employees.record(employee);
+
+        if record.termination_date.is_some() && user.termination_date.is_none() {
+            warn!(
+                "user `{}` was terminated in gusto on {}, they still need to be removed from the configs",
+                user.username,
+                record.termination_date.unwrap()
+            );
+        }
+
+        if user.apply_gusto_record(&record) {
+            user.update(db).await?;
+            info!("updated user `{}` from gusto", user.username);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::GustoEmployeeRecord;
+    use crate::configs::tests::mock_user;
+
+    #[test]
+    fn test_apply_gusto_record() {
+        let mut user = mock_user();
+        user.manager = "jess".to_string();
+
+        let mut record = GustoEmployeeRecord {
+            gusto_id: "7757500908".to_string(),
+            start_date: Some(NaiveDate::from_ymd(2021, 6, 1)),
+            department: "hardware engineering".to_string(),
+            title: "Hardware Engineer".to_string(),
+            manager: String::new(),
+            termination_date: None,
+        };
+
+        assert!(user.apply_gusto_record(&record));
+        assert_eq!("7757500908", user.gusto_id);
+        assert_eq!(NaiveDate::from_ymd(2021, 6, 1), user.start_date);
+        assert_eq!("Hardware Engineering", user.department);
+        assert_eq!("Hardware Engineer", user.title);
+        // An employee without a manager in Gusto keeps the one from the configs.
+        assert_eq!("jess", user.manager);
+
+        assert!(!user.apply_gusto_record(&record));
+
+        record.manager = "sam".to_string();
+        record.termination_date = Some(NaiveDate::from_ymd(2023, 3, 17));
+        assert!(user.apply_gusto_record(&record));
+        assert_eq!("sam", user.manager);
+        assert_eq!(vec!["sam"], user.link_to_manager);
+        assert_eq!(Some(NaiveDate::from_ymd(2023, 3, 17)), user.termination_date);
+    }
+}
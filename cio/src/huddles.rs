@@ -1,22 +1,526 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use airtable_api::{Airtable, Record};
-use anyhow::Result;
-use chrono::{Duration, NaiveDate, Utc};
-use google_calendar::types::Event;
-use handlebars::Handlebars;
+use anyhow::{anyhow, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use diesel::{AsChangeset, ExpressionMethods, Insertable, QueryDsl, Queryable};
+use google_calendar::{
+    types::{Event, EventAttachment, EventAttendee, EventDateTime},
+    Client as GoogleCalendar,
+};
 use log::{debug, info, warn};
-use sendgrid_api::{traits::MailOps, Client as SendGrid};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    airtable::{AIRTABLE_DISCUSSION_TOPICS_TABLE, AIRTABLE_MEETING_SCHEDULE_TABLE},
+    airtable::{AIRTABLE_DISCUSSION_TOPICS_TABLE, AIRTABLE_MEETING_SCHEDULE_TABLE, AIRTABLE_SIGN_UPS_TABLE},
     companies::Company,
-    configs::{get_configs_from_repo, User},
-    core::{DiscussionTopic, Meeting, MeetingReminderEmailData},
+    configs::{get_configs_from_repo, HuddleConfig, HuddleSchedule, HuddleZoomConfig, User},
+    core::{DiscussionTopic, HuddleSignUp, Meeting, MeetingReminderEmailData},
     db::Database,
+    drive::{DocumentStore, AUTOMATED_DOCUMENTS_DRIVE},
+    email::{EmailTemplate, Mailer},
+    schema::{huddle_attendances, huddle_zoom_meetings},
     utils::create_or_update_file_in_github_repo,
+    zoom::{ZoomMeetingKind, ZoomMeetingRequest, ZoomMeetings},
 };
 
+/// Attendance is only looked up for meetings in the last month, older meetings are assumed to
+/// have been recorded already.
+const ATTENDANCE_LOOKBACK_DAYS: i64 = 30;
+
+/// Someone that joined the call for a huddle meeting.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct HuddleAttendance {
+    pub id: i32,
+    pub huddle: String,
+    pub meeting_date: NaiveDate,
+    pub email: String,
+    /// How long they were in the call for, summed over every time they joined.
+    pub duration_seconds: i32,
+    pub recorded_at: DateTime<Utc>,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = huddle_attendances)]
+pub struct NewHuddleAttendance {
+    pub huddle: String,
+    pub meeting_date: NaiveDate,
+    pub email: String,
+    pub duration_seconds: i32,
+    pub recorded_at: DateTime<Utc>,
+    pub cio_company_id: i32,
+}
+
+/// The recurring Zoom meeting or webinar for a huddle.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct HuddleZoomMeeting {
+    pub id: i32,
+    pub huddle: String,
+    pub meeting_id: String,
+    pub webinar: bool,
+    pub join_url: String,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = huddle_zoom_meetings)]
+pub struct NewHuddleZoomMeeting {
+    pub huddle: String,
+    pub meeting_id: String,
+    pub webinar: bool,
+    pub join_url: String,
+    pub cio_company_id: i32,
+}
+
+fn zoom_meeting_kind(webinar: bool) -> ZoomMeetingKind {
+    if webinar {
+        ZoomMeetingKind::Webinar
+    } else {
+        ZoomMeetingKind::Meeting
+    }
+}
+
+/// Create or update the Zoom meeting for a huddle and return its join link.
+async fn ensure_huddle_zoom_meeting(
+    db: &Database,
+    zoom: &ZoomMeetings,
+    company: &Company,
+    slug: &str,
+    topic: &str,
+    config: &HuddleZoomConfig,
+) -> Result<String> {
+    let existing = huddle_zoom_meetings::dsl::huddle_zoom_meetings
+        .filter(huddle_zoom_meetings::dsl::cio_company_id.eq(company.id))
+        .filter(huddle_zoom_meetings::dsl::huddle.eq(slug.to_string()))
+        .first_async::<HuddleZoomMeeting>(db.pool())
+        .await
+        .ok();
+
+    let mut meeting_id = String::new();
+    if let Some(existing) = existing {
+        if existing.webinar == config.webinar {
+            meeting_id = existing.meeting_id;
+        } else {
+            // Meetings can not be turned into webinars, so the old one is replaced.
+            zoom.delete(zoom_meeting_kind(existing.webinar), &existing.meeting_id)
+                .await?;
+        }
+    }
+
+    let kind = zoom_meeting_kind(config.webinar);
+    let host = format!("{}@{}", config.host, company.gsuite_domain);
+    let alternative_hosts: Vec<String> = config
+        .alternative_hosts
+        .iter()
+        .map(|username| format!("{}@{}", username, company.gsuite_domain))
+        .collect();
+    let request = ZoomMeetingRequest::recurring(kind, topic, "", &alternative_hosts);
+
+    let meeting = zoom.ensure(kind, &meeting_id, &host, &request).await?;
+
+    let record = NewHuddleZoomMeeting {
+        huddle: slug.to_string(),
+        meeting_id: meeting.id.to_string(),
+        webinar: config.webinar,
+        join_url: meeting.join_url,
+        cio_company_id: company.id,
+    };
+    diesel::insert_into(huddle_zoom_meetings::table)
+        .values(record.clone())
+        .on_conflict((huddle_zoom_meetings::cio_company_id, huddle_zoom_meetings::huddle))
+        .do_update()
+        .set(record.clone())
+        .execute_async(db.pool())
+        .await?;
+
+    Ok(record.join_url)
+}
+
+/// Delete the Zoom meetings of huddles that no longer meet on Zoom.
+async fn delete_unused_huddle_zoom_meetings(
+    db: &Database,
+    zoom: &ZoomMeetings,
+    huddles: &BTreeMap<String, HuddleConfig>,
+    meetings: Vec<HuddleZoomMeeting>,
+) -> Result<()> {
+    for meeting in meetings {
+        let in_use = huddles
+            .get(&meeting.huddle)
+            .and_then(|huddle| huddle.schedule.as_ref())
+            .map(|schedule| schedule.zoom.is_some())
+            .unwrap_or(false);
+        if in_use {
+            continue;
+        }
+
+        zoom.delete(zoom_meeting_kind(meeting.webinar), &meeting.meeting_id)
+            .await?;
+        diesel::delete(huddle_zoom_meetings::table.find(meeting.id))
+            .execute_async(db.pool())
+            .await?;
+
+        info!("deleted the zoom meeting for the {} huddle", meeting.huddle);
+    }
+
+    Ok(())
+}
+
+/// The title of the recurring event for a huddle. This contains the fuzzy search string so
+/// that the events we create are found by `sync_huddles`.
+fn event_summary(slug: &str, huddle: &HuddleConfig) -> String {
+    if !huddle.calendar_event_fuzzy_search.is_empty() {
+        huddle.calendar_event_fuzzy_search.to_string()
+    } else if !huddle.name.is_empty() {
+        huddle.name.to_string()
+    } else {
+        format!("{} huddle", slug.replace('-', " "))
+    }
+}
+
+fn event_description(slug: &str, huddle: &HuddleConfig, company: &Company, agenda: &str) -> String {
+    let notes = if !huddle.link_to_notes.is_empty() {
+        format!("Notes Doc: {}\n", huddle.link_to_notes)
+    } else {
+        String::new()
+    };
+
+    format!(
+        r#"This is the event for {} huddles.
+
+You can submit topics at: https://{}-huddle-form.corp.{}
+
+The Airtable workspace lives at: https://{}-huddle.corp.{}
+
+{}
+{}"#,
+        slug.replace('-', " "),
+        slug,
+        company.domain,
+        slug,
+        company.domain,
+        notes,
+        agenda
+    )
+    .trim()
+    .to_string()
+}
+
+fn event_time(schedule: &HuddleSchedule, offset: Duration) -> Result<EventDateTime> {
+    let tz: Tz = schedule
+        .time_zone
+        .parse()
+        .map_err(|e| anyhow!("invalid time zone `{}`: {}", schedule.time_zone, e))?;
+    let start = tz
+        .from_local_datetime(&schedule.start)
+        .single()
+        .ok_or_else(|| anyhow!("`{}` is not a valid time in {}", schedule.start, schedule.time_zone))?;
+
+    Ok(EventDateTime {
+        date: None,
+        date_time: Some((start + offset).with_timezone(&Utc)),
+        time_zone: schedule.time_zone.to_string(),
+    })
+}
+
+/// Everyone that should be invited to a huddle: the huddle's group and anyone that has signed
+/// up in Airtable and has not opted out since.
+fn huddle_attendees(huddle: &HuddleConfig, company: &Company, sign_ups: &[HuddleSignUp]) -> BTreeSet<String> {
+    let mut attendees: BTreeSet<String> = sign_ups
+        .iter()
+        .filter(|sign_up| !sign_up.opted_out && !sign_up.person.email.is_empty())
+        .map(|sign_up| sign_up.person.email.to_lowercase())
+        .collect();
+
+    if !huddle.email.is_empty() {
+        attendees.insert(format!("{}@{}", huddle.email, company.gsuite_domain));
+    }
+
+    attendees
+}
+
+/// Replace the people invited to an event, keeping the responses of anyone that was already
+/// invited. Rooms and the organizer are always kept.
+fn merge_attendees(existing: &[EventAttendee], emails: &BTreeSet<String>) -> Vec<EventAttendee> {
+    let mut attendees: Vec<EventAttendee> = existing
+        .iter()
+        .filter(|a| a.resource || a.organizer || emails.contains(&a.email.to_lowercase()))
+        .cloned()
+        .collect();
+
+    for email in emails {
+        if !attendees.iter().any(|a| a.email.to_lowercase() == *email) {
+            attendees.push(EventAttendee {
+                id: Default::default(),
+                email: email.to_string(),
+                display_name: Default::default(),
+                organizer: false,
+                resource: false,
+                optional: false,
+                response_status: Default::default(),
+                comment: Default::default(),
+                additional_guests: 0,
+                self_: false,
+            });
+        }
+    }
+
+    attendees
+}
+
+fn attendee_emails(attendees: &[EventAttendee]) -> BTreeSet<String> {
+    attendees.iter().map(|a| a.email.to_lowercase()).collect()
+}
+
+/// Find the recurring event that we created for the huddle.
+async fn find_recurring_event(gcal: &GoogleCalendar, calendar_id: &str, summary: &str) -> Result<Option<Event>> {
+    let events = gcal
+        .events()
+        .list_all(
+            calendar_id,
+            "", // iCalID
+            0,  // Max attendees, set to 0 to ignore.
+            google_calendar::types::OrderBy::Noop,
+            &[],     // private_extended_property
+            summary, // q
+            &[],     // shared_extended_property
+            false,   // show_deleted
+            false,   // show_hidden_invitations
+            false,   // single_events
+            "",      // time_max
+            "",      // time_min
+            "",      // time_zone
+            "",      // updated_min
+        )
+        .await?;
+
+    Ok(events
+        .into_iter()
+        .find(|event| !event.recurrence.is_empty() && event.summary.to_lowercase() == summary.to_lowercase()))
+}
+
+/// Create or update the recurring event for every huddle that has a schedule in its config,
+/// and invite everyone that has signed up for the huddle in Airtable.
+pub async fn sync_huddle_events(db: &Database, company: &Company) -> Result<()> {
+    let github = company.authenticate_github()?;
+    let configs = get_configs_from_repo(&github, company).await?;
+
+    let zoom_meetings = huddle_zoom_meetings::dsl::huddle_zoom_meetings
+        .filter(huddle_zoom_meetings::dsl::cio_company_id.eq(company.id))
+        .load_async::<HuddleZoomMeeting>(db.pool())
+        .await?;
+    let uses_zoom = configs
+        .huddles
+        .values()
+        .any(|huddle| huddle.schedule.as_ref().map(|s| s.zoom.is_some()).unwrap_or(false));
+
+    let zoom = if uses_zoom || !zoom_meetings.is_empty() {
+        let zoom = company.authenticate_zoom_meetings(db).await?;
+        delete_unused_huddle_zoom_meetings(db, &zoom, &configs.huddles, zoom_meetings).await?;
+        Some(zoom)
+    } else {
+        None
+    };
+
+    if configs.huddles.values().all(|huddle| huddle.schedule.is_none()) {
+        // Return early.
+        return Ok(());
+    }
+
+    let gcal = company.authenticate_google_calendar(db).await?;
+
+    for (slug, huddle) in &configs.huddles {
+        let schedule = match &huddle.schedule {
+            Some(schedule) => schedule,
+            None => continue,
+        };
+
+        let airtable = Airtable::new(&company.airtable_api_key, huddle.airtable_base_id.to_string(), "");
+        let sign_ups: Vec<Record<HuddleSignUp>> = airtable
+            .list_records(AIRTABLE_SIGN_UPS_TABLE, "Grid view", vec![])
+            .await?;
+        let sign_ups: Vec<HuddleSignUp> = sign_ups.into_iter().map(|record| record.fields).collect();
+        let attendees = huddle_attendees(huddle, company, &sign_ups);
+
+        let calendar_id = huddle.calendar_id(company);
+        let summary = event_summary(slug, huddle);
+        let existing = find_recurring_event(&gcal, &calendar_id, &summary).await?;
+
+        let mut event = existing.clone().unwrap_or_default();
+        event.summary = summary;
+        event.description = event_description(slug, huddle, company, "");
+        event.start = Some(event_time(schedule, Duration::zero())?);
+        event.end = Some(event_time(schedule, Duration::minutes(schedule.duration_minutes))?);
+        event.recurrence = vec![schedule.recurrence.to_string()];
+        event.attendees = merge_attendees(&event.attendees, &attendees);
+
+        // Huddles that meet on Zoom have the join link as the location of the event.
+        if let (Some(zoom_config), Some(zoom)) = (&schedule.zoom, &zoom) {
+            event.location = ensure_huddle_zoom_meeting(db, zoom, company, slug, &event.summary, zoom_config).await?;
+        }
+
+        match existing {
+            Some(existing) => {
+                let start = |e: &Event| e.start.as_ref().and_then(|s| s.date_time);
+                let end = |e: &Event| e.end.as_ref().and_then(|s| s.date_time);
+
+                if existing.description == event.description
+                    && existing.location == event.location
+                    && existing.recurrence == event.recurrence
+                    && start(&existing) == start(&event)
+                    && end(&existing) == end(&event)
+                    && attendee_emails(&existing.attendees) == attendee_emails(&event.attendees)
+                {
+                    // Only update the event if it has changed, since everyone gets notified.
+                    continue;
+                }
+
+                gcal.events()
+                    .update(
+                        &calendar_id,
+                        &event.id,
+                        0,    // conference data version
+                        0,    // max attendees, 0 to ignore
+                        true, // send notifications
+                        google_calendar::types::SendUpdates::All,
+                        true, // supports_attachments
+                        &event,
+                    )
+                    .await?;
+                info!("updated the recurring event for the {} huddle", slug);
+            }
+            None => {
+                gcal.events()
+                    .insert(
+                        &calendar_id,
+                        0,    // conference data version
+                        0,    // max attendees, 0 to ignore
+                        true, // send notifications
+                        google_calendar::types::SendUpdates::All,
+                        true, // supports_attachments
+                        &event,
+                    )
+                    .await?;
+                info!("created the recurring event for the {} huddle", slug);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The agenda for a single huddle meeting.
+fn render_agenda(slug: &str, date: NaiveDate, huddle: &HuddleConfig, topics: &[DiscussionTopic]) -> String {
+    let mut agenda = format!(
+        "# {} Huddle on {}\n\n",
+        slug.replace('-', " ").to_uppercase(),
+        date.format("%A, %-d %B, %C%y")
+    );
+
+    if topics.is_empty() {
+        agenda.push_str("There are no topics on the agenda yet!\n");
+    } else {
+        agenda.push_str("## Discussion topics\n\n");
+        for topic in topics {
+            agenda.push_str(&format!(
+                "- {}\n  Submitted by: {}\n",
+                topic.topic, topic.submitter.name
+            ));
+            if !topic.priority.is_empty() {
+                agenda.push_str(&format!("  Priority: {}\n", topic.priority));
+            }
+            if !topic.notes.is_empty() {
+                agenda.push_str(&format!("  Notes: {}\n", topic.notes));
+            }
+        }
+    }
+
+    if !huddle.link_to_notes.is_empty() {
+        agenda.push_str(&format!("\nNotes Doc: {}\n", huddle.link_to_notes));
+    }
+
+    agenda
+}
+
+/// The code of a Google Meet call, from its link.
+fn meeting_code(hangout_link: &str) -> Option<&str> {
+    let code = hangout_link
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()?
+        .split('?')
+        .next()?;
+
+    if hangout_link.contains("meet.google.com/") && !code.is_empty() {
+        Some(code)
+    } else {
+        None
+    }
+}
+
+/// Save who joined the call for a meeting and return the emails of those people.
+async fn record_attendance(
+    db: &Database,
+    company: &Company,
+    reports: &crate::gsuite::GoogleReports,
+    slug: &str,
+    date: NaiveDate,
+    event: &Event,
+) -> Result<Vec<String>> {
+    let (code, start, end) = match (
+        meeting_code(&event.hangout_link),
+        event.start.as_ref().and_then(|s| s.date_time),
+        event.end.as_ref().and_then(|e| e.date_time),
+    ) {
+        (Some(code), Some(start), Some(end)) => (code, start, end),
+        _ => return Ok(vec![]),
+    };
+
+    // Meetings often run over, so look a little past the scheduled end.
+    let attendance = reports
+        .meet_attendance(code, start - Duration::minutes(15), end + Duration::hours(1))
+        .await?;
+
+    let mut emails = vec![];
+    for (email, seconds) in attendance {
+        let attendance = NewHuddleAttendance {
+            huddle: slug.to_string(),
+            meeting_date: date,
+            email: email.to_string(),
+            duration_seconds: seconds as i32,
+            recorded_at: Utc::now(),
+            cio_company_id: company.id,
+        };
+
+        diesel::insert_into(huddle_attendances::table)
+            .values(attendance.clone())
+            .on_conflict((
+                huddle_attendances::cio_company_id,
+                huddle_attendances::huddle,
+                huddle_attendances::meeting_date,
+                huddle_attendances::email,
+            ))
+            .do_update()
+            .set(attendance)
+            .execute_async(db.pool())
+            .await?;
+
+        emails.push(email);
+    }
+
+    info!(
+        "recorded {} attendees for the {} huddle on {}",
+        emails.len(),
+        slug,
+        date
+    );
+
+    Ok(emails)
+}
+
 /// Make sure if an event is moved in Google Calendar that Airtable is updated.
 pub async fn sync_changes_to_google_events(db: &Database, company: &Company) -> Result<()> {
     let github = company.authenticate_github()?;
@@ -28,11 +532,13 @@ pub async fn sync_changes_to_google_events(db: &Database, company: &Company) ->
     }
 
     let gcal = company.authenticate_google_calendar(db).await?;
+    // Agendas are stored in the shared drive : "Automated Documents"/"huddles"/<huddle>
+    let store = DocumentStore::new(db, company, AUTOMATED_DOCUMENTS_DRIVE).await?;
 
     // Iterate over the huddle meetings.
     for (slug, huddle) in configs.huddles {
         // Initialize the Airtable client.
-        let airtable = Airtable::new(&company.airtable_api_key, huddle.airtable_base_id, "");
+        let airtable = Airtable::new(&company.airtable_api_key, huddle.airtable_base_id.to_string(), "");
 
         // Get the meeting schedule table from airtable.
         let records: Vec<Record<Meeting>> = airtable
@@ -78,6 +584,7 @@ pub async fn sync_changes_to_google_events(db: &Database, company: &Company) ->
                     .await?;
 
                 // Get the discussion topics for the meeting.
+                let mut topics: Vec<DiscussionTopic> = vec![];
                 let mut discussion_topics = String::new();
                 for id in &record.fields.proposed_discussion {
                     // Get the topic from Airtable.
@@ -89,6 +596,7 @@ pub async fn sync_changes_to_google_events(db: &Database, company: &Company) ->
                             "{}\n- {} from {}",
                             discussion_topics, topic.fields.topic, topic.fields.submitter.name
                         );
+                        topics.push(topic.fields);
                     }
                 }
                 discussion_topics = discussion_topics.trim().to_string();
@@ -96,30 +604,25 @@ pub async fn sync_changes_to_google_events(db: &Database, company: &Company) ->
                     discussion_topics = format!("Discussion topics:\n{}", discussion_topics);
                 }
 
-                let notes = if !huddle.link_to_notes.is_empty() {
-                    format!("Notes Doc: {}\n", huddle.link_to_notes)
+                // Generate the agenda for upcoming meetings, past agendas are left as they were.
+                let agenda = if !record.fields.cancelled && record.fields.date >= Utc::now().date().naive_utc() {
+                    let document = store
+                        .upload(
+                            &["huddles", &slug],
+                            &format!("{} - Agenda.txt", record.fields.date.format("%Y%m%d")),
+                            "text/plain",
+                            render_agenda(&slug, record.fields.date, &huddle, &topics).as_bytes(),
+                        )
+                        .await?;
+                    discussion_topics = format!("Agenda Doc: {}\n\n{}", document.url, discussion_topics);
+
+                    Some(document)
                 } else {
-                    String::new()
+                    None
                 };
 
                 // Update the event description.
-                let description = format!(
-                    r#"This is the event for {} huddles.
-
-You can submit topics at: https://{}-huddle-form.corp.{}
-
-The Airtable workspace lives at: https://{}-huddle.corp.{}
-
-{}
-{}"#,
-                    slug.replace('-', " "),
-                    slug,
-                    company.domain,
-                    slug,
-                    company.domain,
-                    notes,
-                    discussion_topics
-                );
+                let description = event_description(&slug, &huddle, company, &discussion_topics);
 
                 if event.recurring_event_id != event.id {
                     let organizer_email = event.organizer.unwrap().email.to_string();
@@ -137,6 +640,18 @@ The Airtable workspace lives at: https://{}-huddle.corp.{}
                     {
                         // Modify the properties of the event so we can update it.
                         event.description = description.trim().to_string();
+                        if let Some(agenda) = &agenda {
+                            // Replace the previous agenda, keeping any other attachments like
+                            // the recording.
+                            event.attachments.retain(|a| !a.title.ends_with(" - Agenda.txt"));
+                            event.attachments.push(EventAttachment {
+                                file_id: agenda.file_id.to_string(),
+                                file_url: agenda.url.to_string(),
+                                mime_type: "text/plain".to_string(),
+                                title: agenda.name.to_string(),
+                                ..Default::default()
+                            });
+                        }
                         if !event.recurring_event_id.is_empty() {
                             // Individual instances are similar to single events. Unlike their parent recurring events, instances do not have the recurrence field set.
                             // FROM: https://developers.google.com/calendar/recurringevents#ruby_1
@@ -335,25 +850,16 @@ pub async fn send_huddle_reminders(db: &Database, company: &Company) -> Result<(
 
                 email_data.time = pacific_time.format("%r %Z").to_string();
 
-                // Format the email template.
-                // Initialize handlebars.
-                let mut handlebars = Handlebars::new();
-
-                // We are sending plain text emails so we need to disable HTML escaping
-                handlebars.register_escape_fn(handlebars::no_escape);
-
-                // Render the email template.
-                let template = &handlebars.render_template(EMAIL_TEMPLATE, &email_data)?;
-
-                // Send the email.
-                // Initialize the SendGrid client.
-                let sendgrid = SendGrid::new_from_env();
                 // Send the email.
-                sendgrid
-                    .mail_send()
-                    .send_plain_text(
-                        &format!("Reminder {} huddle tomorrow", slug),
-                        template,
+                let mailer = Mailer::new(db, company);
+                mailer
+                    .send_template(
+                        &EmailTemplate::new(
+                            "huddle_reminder",
+                            "Reminder {{this.huddle_name}} huddle tomorrow",
+                            EMAIL_TEMPLATE,
+                        ),
+                        &email_data,
                         &[format!("{}@{}", huddle.email, company.gsuite_domain)],
                         &[],
                         &[],
@@ -490,6 +996,16 @@ pub async fn sync_huddles(db: &Database, company: &Company) -> Result<()> {
     }
 
     let gcal = company.authenticate_google_calendar(db).await?;
+    let reports = match company.authenticate_google_reports().await {
+        Ok(reports) => Some(reports),
+        Err(e) => {
+            warn!(
+                "not recording huddle attendance, could not authenticate with Google: {}",
+                e
+            );
+            None
+        }
+    };
 
     // Iterate over the huddles.
     for (slug, huddle) in configs.huddles {
@@ -634,6 +1150,39 @@ pub async fn sync_huddles(db: &Database, company: &Company) -> Result<()> {
                     }
                     record.fields.attendees = attendees;
 
+                    // Once a meeting is over, replace the people that were invited with the
+                    // people that actually joined the call.
+                    let ended = event
+                        .end
+                        .as_ref()
+                        .and_then(|e| e.date_time)
+                        .map(|end| end < Utc::now())
+                        .unwrap_or(false);
+                    let recent = record.fields.date
+                        >= (Utc::now() - Duration::days(ATTENDANCE_LOOKBACK_DAYS))
+                            .date()
+                            .naive_utc();
+                    let attendance_reports = reports.as_ref().filter(|_| ended && recent && !record.fields.cancelled);
+                    if let Some(reports) = attendance_reports {
+                        match record_attendance(db, company, reports, &slug, record.fields.date, event).await {
+                            Ok(emails) if !emails.is_empty() => {
+                                let mut attended = vec![];
+                                for email in emails {
+                                    let username = email.trim_end_matches(&format!("@{}", company.gsuite_domain));
+                                    if let Some(user) = User::get_from_db(db, company.id, username.to_string()).await {
+                                        attended.push(user.email);
+                                    }
+                                }
+                                record.fields.attendees = attended;
+                            }
+                            Ok(_) => (),
+                            Err(e) => warn!(
+                                "could not record attendance for the {} huddle on {}: {}",
+                                slug, record.fields.date, e
+                            ),
+                        }
+                    }
+
                     // Send the updated record to Airtable.
                     match airtable
                         .update_records(AIRTABLE_MEETING_SCHEDULE_TABLE, vec![record.clone()])
@@ -693,3 +1242,123 @@ pub async fn sync_huddles(db: &Database, company: &Company) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use chrono::{Duration, NaiveDate, TimeZone, Utc};
+    use google_calendar::types::EventAttendee;
+
+    use super::{event_time, meeting_code, merge_attendees, render_agenda};
+    use crate::configs::{HuddleConfig, HuddleSchedule};
+
+    fn attendee(email: &str, resource: bool) -> EventAttendee {
+        EventAttendee {
+            id: Default::default(),
+            email: email.to_string(),
+            display_name: Default::default(),
+            organizer: false,
+            resource,
+            optional: false,
+            response_status: "accepted".to_string(),
+            comment: Default::default(),
+            additional_guests: 0,
+            self_: false,
+        }
+    }
+
+    #[test]
+    fn test_meeting_code() {
+        assert_eq!(
+            Some("abc-defg-hij"),
+            meeting_code("https://meet.google.com/abc-defg-hij")
+        );
+        assert_eq!(
+            Some("abc-defg-hij"),
+            meeting_code("https://meet.google.com/abc-defg-hij?authuser=0")
+        );
+        assert_eq!(None, meeting_code("https://zoom.us/j/123"));
+        assert_eq!(None, meeting_code(""));
+    }
+
+    #[test]
+    fn test_merge_attendees() {
+        let existing = vec![
+            attendee("room@resource.calendar.google.com", true),
+            attendee("Jane@example.com", false),
+            attendee("left@example.com", false),
+        ];
+        let emails: BTreeSet<String> = ["jane@example.com", "new@example.com"]
+            .iter()
+            .map(|e| e.to_string())
+            .collect();
+
+        let attendees = merge_attendees(&existing, &emails);
+
+        assert_eq!(
+            vec![
+                "room@resource.calendar.google.com",
+                "Jane@example.com",
+                "new@example.com"
+            ],
+            attendees.iter().map(|a| a.email.as_str()).collect::<Vec<_>>()
+        );
+        // Existing responses are kept.
+        assert_eq!("accepted", attendees[1].response_status);
+        assert_eq!("", attendees[2].response_status);
+    }
+
+    #[test]
+    fn test_event_time() {
+        let schedule: HuddleSchedule = toml::from_str(
+            r#"
+start = "2023-03-02T10:00:00"
+recurrence = "RRULE:FREQ=WEEKLY;BYDAY=TH"
+"#,
+        )
+        .unwrap();
+        assert_eq!(60, schedule.duration_minutes);
+
+        let start = event_time(&schedule, Duration::zero()).unwrap();
+        assert_eq!(Some(Utc.ymd(2023, 3, 2).and_hms(18, 0, 0)), start.date_time);
+        assert_eq!("America/Los_Angeles", start.time_zone);
+
+        let end = event_time(&schedule, Duration::minutes(schedule.duration_minutes)).unwrap();
+        assert_eq!(Some(Utc.ymd(2023, 3, 2).and_hms(19, 0, 0)), end.date_time);
+    }
+
+    #[test]
+    fn test_schedule_zoom_config() {
+        let schedule: HuddleSchedule = toml::from_str(
+            r#"
+start = "2023-03-02T10:00:00"
+recurrence = "RRULE:FREQ=WEEKLY;BYDAY=TH"
+
+[zoom]
+host = "jane"
+alternative_hosts = ["joe"]
+"#,
+        )
+        .unwrap();
+
+        let zoom = schedule.zoom.unwrap();
+        assert_eq!("jane", zoom.host);
+        assert_eq!(vec!["joe"], zoom.alternative_hosts);
+        assert!(!zoom.webinar);
+    }
+
+    #[test]
+    fn test_render_agenda_without_topics() {
+        let huddle = HuddleConfig {
+            link_to_notes: "https://docs.example.com/notes".to_string(),
+            ..Default::default()
+        };
+
+        let agenda = render_agenda("product", NaiveDate::from_ymd(2023, 3, 2), &huddle, &[]);
+
+        assert!(agenda.starts_with("# PRODUCT Huddle on Thursday, 2 March, 2023"));
+        assert!(agenda.contains("There are no topics on the agenda yet!"));
+        assert!(agenda.ends_with("Notes Doc: https://docs.example.com/notes\n"));
+    }
+}
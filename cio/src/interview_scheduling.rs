@@ -0,0 +1,796 @@
+/*!
+ * Scheduling interviews with applicants.
+ *
+ * An interview panel is a number of sessions that run back to back, each with its own
+ * interviewers. Scheduling a panel works in two steps:
+ *
+ * 1. [`propose_interview_times`] looks up when the interviewers are busy in Google Calendar,
+ *    finds times within working hours where every session fits, and emails those times to the
+ *    applicant with a link to pick one of them.
+ * 2. [`book_interview`] runs once the applicant has picked a time. It checks that the panel is
+ *    still free, creates an event on the Interviews calendar for each session, with a Zoom
+ *    meeting when interviews are held on Zoom, and records the interviews on the applicant.
+ *
+ * The link in the email carries the token of the proposal, which is all the applicant needs to
+ * book it. Proposing new times for an applicant supersedes the times proposed before.
+ */
+use std::{collections::HashMap, fmt};
+
+use anyhow::{anyhow, bail, Result};
+use async_bb8_diesel::{AsyncRunQueryDsl, OptionalExtension};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use diesel::{ExpressionMethods, Insertable, QueryDsl, Queryable};
+use google_calendar::{
+    types::{Event, EventAttendee, EventDateTime},
+    Client as GoogleCalendar,
+};
+use log::info;
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api_tokens::APIToken,
+    app_config::{InterviewSchedulingConfig, Letter, ZoomConfig},
+    applicant_status::Status,
+    applicants::Applicant,
+    companies::Company,
+    db::Database,
+    email::Mailer,
+    interviews::NewApplicantInterview,
+    schema::{applicants, interview_schedules},
+    zoom::{ZoomMeetingKind, ZoomMeetingRequest},
+};
+
+const FREE_BUSY_ENDPOINT: &str = "https://www.googleapis.com/calendar/v3/freeBusy";
+
+/// How far apart the start times that are tried are.
+const TIME_STEP_MINUTES: i64 = 30;
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+pub struct InterviewPanel {
+    /// The sessions of the interview, in the order they run in.
+    pub sessions: Vec<InterviewSession>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+pub struct InterviewSession {
+    /// What the session is about, ie. `Technical`.
+    pub name: String,
+    /// The emails of the interviewers. The first one hosts the Zoom meeting.
+    pub interviewers: Vec<String>,
+    pub duration_minutes: i64,
+}
+
+impl InterviewPanel {
+    fn validate(&self) -> Result<()> {
+        if self.sessions.is_empty() {
+            bail!("an interview panel needs at least one session");
+        }
+
+        for session in &self.sessions {
+            if session.duration_minutes <= 0 {
+                bail!("session `{}` needs a duration", session.name);
+            }
+            if session.interviewers.is_empty() {
+                bail!("session `{}` needs at least one interviewer", session.name);
+            }
+            if let Some(interviewer) = session.interviewers.iter().find(|i| !i.contains('@')) {
+                bail!(
+                    "interviewer `{}` of session `{}` is not an email",
+                    interviewer,
+                    session.name
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::minutes(self.sessions.iter().map(|session| session.duration_minutes).sum())
+    }
+
+    /// Every interviewer on the panel, once.
+    pub fn interviewers(&self) -> Vec<String> {
+        let mut interviewers: Vec<String> = vec![];
+        for interviewer in self.sessions.iter().flat_map(|session| &session.interviewers) {
+            if !interviewers.contains(interviewer) {
+                interviewers.push(interviewer.to_string());
+            }
+        }
+
+        interviewers
+    }
+
+    /// The sessions with their start and end times, for a panel that starts at `start`.
+    fn timeline(&self, start: DateTime<Utc>) -> Vec<(&InterviewSession, DateTime<Utc>, DateTime<Utc>)> {
+        let mut session_start = start;
+
+        self.sessions
+            .iter()
+            .map(|session| {
+                let session_end = session_start + Duration::minutes(session.duration_minutes);
+                let timeline = (session, session_start, session_end);
+                session_start = session_end;
+                timeline
+            })
+            .collect()
+    }
+
+    /// Whether every interviewer is free for their sessions, for a panel that starts at `start`.
+    fn is_free(&self, busy: &BusyTimes, start: DateTime<Utc>) -> bool {
+        self.timeline(start)
+            .iter()
+            .all(|(session, session_start, session_end)| {
+                session.interviewers.iter().all(|interviewer| {
+                    busy.get(interviewer)
+                        .map(|periods| {
+                            !periods
+                                .iter()
+                                .any(|period| period.start < *session_end && *session_start < period.end)
+                        })
+                        .unwrap_or(true)
+                })
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InterviewScheduleStatus {
+    /// Times were proposed to the applicant, who has not picked one yet.
+    Proposed,
+    /// The applicant picked a time and the interviews are on the calendar.
+    Booked,
+    /// Newer times were proposed to the applicant.
+    Superseded,
+}
+
+impl fmt::Display for InterviewScheduleStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            InterviewScheduleStatus::Proposed => "proposed",
+            InterviewScheduleStatus::Booked => "booked",
+            InterviewScheduleStatus::Superseded => "superseded",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// The times proposed to an applicant for an interview panel, and the one they booked.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct InterviewSchedule {
+    pub id: i32,
+    pub applicant_id: i32,
+    /// The applicant's email, which the times were proposed to.
+    pub email: String,
+    pub panel: serde_json::Value,
+    pub proposed_times: Vec<DateTime<Utc>>,
+    /// Lets the applicant book one of the proposed times, so it is only ever sent to them.
+    #[serde(skip_serializing)]
+    pub token: String,
+    pub status: String,
+    pub booked_time: Option<DateTime<Utc>>,
+    /// The calendar events of the sessions, once booked.
+    pub google_event_ids: Vec<String>,
+    pub cio_company_id: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = interview_schedules)]
+struct NewInterviewSchedule {
+    applicant_id: i32,
+    email: String,
+    panel: serde_json::Value,
+    proposed_times: Vec<DateTime<Utc>>,
+    token: String,
+    status: String,
+    cio_company_id: i32,
+}
+
+impl InterviewSchedule {
+    pub fn panel(&self) -> Result<InterviewPanel> {
+        Ok(serde_json::from_value(self.panel.clone())?)
+    }
+
+    pub async fn get_by_token(db: &Database, token: &str) -> Result<Option<InterviewSchedule>> {
+        Ok(interview_schedules::dsl::interview_schedules
+            .filter(interview_schedules::dsl::token.eq(token.to_string()))
+            .first_async::<InterviewSchedule>(db.pool())
+            .await
+            .optional()?)
+    }
+}
+
+/// Why an applicant could not book an interview.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BookingRejection {
+    /// There are no times proposed with the token.
+    UnknownToken,
+    /// The times were booked already, or newer ones were proposed.
+    NotProposed(String),
+    /// The time is not one of the times that were proposed.
+    TimeNotProposed(DateTime<Utc>),
+    /// Someone on the panel got busy since the time was proposed.
+    TimeUnavailable(DateTime<Utc>),
+}
+
+impl fmt::Display for BookingRejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BookingRejection::UnknownToken => write!(f, "no interview times were proposed with this token"),
+            BookingRejection::NotProposed(status) => write!(f, "the interview times are {}", status),
+            BookingRejection::TimeNotProposed(time) => write!(f, "{} is not one of the proposed times", time),
+            BookingRejection::TimeUnavailable(time) => write!(f, "{} is no longer available", time),
+        }
+    }
+}
+
+impl std::error::Error for BookingRejection {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct BusyPeriod {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+/// When each calendar is busy, keyed by the calendar's email.
+pub type BusyTimes = HashMap<String, Vec<BusyPeriod>>;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FreeBusyRequest {
+    time_min: DateTime<Utc>,
+    time_max: DateTime<Utc>,
+    items: Vec<FreeBusyRequestItem>,
+}
+
+#[derive(Debug, Serialize)]
+struct FreeBusyRequestItem {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct FreeBusyResponse {
+    #[serde(default)]
+    calendars: HashMap<String, FreeBusyCalendar>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FreeBusyCalendar {
+    #[serde(default)]
+    busy: Vec<BusyPeriod>,
+    #[serde(default)]
+    errors: Vec<FreeBusyError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FreeBusyError {
+    #[serde(default)]
+    reason: String,
+}
+
+/// Look up when the `calendars` are busy between `time_min` and `time_max`. The
+/// `google-calendar` crate has no free/busy types we can use, so this goes to the API directly.
+async fn query_free_busy(
+    db: &Database,
+    company: &Company,
+    calendars: &[String],
+    time_min: DateTime<Utc>,
+    time_max: DateTime<Utc>,
+) -> Result<BusyTimes> {
+    // This refreshes the token in the database if it has expired.
+    company.authenticate_google_calendar(db).await?;
+    let token = APIToken::get_from_db(db, company.id, "google".to_string())
+        .await
+        .ok_or_else(|| anyhow!("no Google token for company {}", company.id))?;
+
+    let request = FreeBusyRequest {
+        time_min,
+        time_max,
+        items: calendars
+            .iter()
+            .map(|calendar| FreeBusyRequestItem {
+                id: calendar.to_string(),
+            })
+            .collect(),
+    };
+
+    let response: FreeBusyResponse = reqwest::Client::new()
+        .post(FREE_BUSY_ENDPOINT)
+        .bearer_auth(&token.access_token)
+        .json(&request)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let mut busy = BusyTimes::new();
+    for (calendar, free_busy) in response.calendars {
+        // A calendar we cannot see would look free, which it might not be.
+        if let Some(error) = free_busy.errors.first() {
+            bail!("looking up when {} is busy failed: {}", calendar, error.reason);
+        }
+
+        busy.insert(calendar, free_busy.busy);
+    }
+
+    Ok(busy)
+}
+
+/// Find the times within working hours, from `earliest` on, at which every interviewer on the
+/// panel is free for their sessions. Only the first such time of each weekday is taken, up to
+/// the number of times the config asks for.
+pub fn find_interview_times(
+    panel: &InterviewPanel,
+    busy: &BusyTimes,
+    config: &InterviewSchedulingConfig,
+    earliest: DateTime<Utc>,
+) -> Result<Vec<DateTime<Utc>>> {
+    let tz: Tz = config
+        .time_zone
+        .parse()
+        .map_err(|e| anyhow!("invalid time zone `{}`: {}", config.time_zone, e))?;
+    let first_day = earliest.with_timezone(&tz).naive_local().date();
+
+    let mut times = vec![];
+    for day in 0..=config.search_days {
+        if times.len() >= config.proposals {
+            break;
+        }
+
+        let date = first_day + Duration::days(day);
+        if date.weekday() == Weekday::Sat || date.weekday() == Weekday::Sun {
+            continue;
+        }
+
+        let (day_start, day_end) = match (
+            local_time(&tz, date, config.day_start_hour),
+            local_time(&tz, date, config.day_end_hour),
+        ) {
+            (Some(day_start), Some(day_end)) => (day_start, day_end),
+            _ => continue,
+        };
+
+        let mut start = day_start;
+        while start + panel.duration() <= day_end {
+            if start >= earliest && panel.is_free(busy, start) {
+                times.push(start);
+                break;
+            }
+
+            start = start + Duration::minutes(TIME_STEP_MINUTES);
+        }
+    }
+
+    Ok(times)
+}
+
+fn local_time(tz: &Tz, date: NaiveDate, hour: u32) -> Option<DateTime<Utc>> {
+    tz.from_local_datetime(&date.and_hms_opt(hour, 0, 0)?)
+        .single()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+fn generate_token() -> String {
+    thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(40)
+        .map(char::from)
+        .collect()
+}
+
+impl InterviewSchedulingConfig {
+    fn format_time(&self, time: DateTime<Utc>) -> String {
+        match self.time_zone.parse::<Tz>() {
+            Ok(tz) => time.with_timezone(&tz).format("%A, %B %-d at %-I:%M %p %Z").to_string(),
+            Err(_) => time.format("%A, %B %-d at %-I:%M %p UTC").to_string(),
+        }
+    }
+
+    pub fn create_proposal_letter(
+        &self,
+        applicant: &Applicant,
+        panel: &InterviewPanel,
+        times: &[DateTime<Utc>],
+        token: &str,
+    ) -> Option<Letter> {
+        self.proposal_letter.as_ref().map(|letter| {
+            let hours = panel.duration().num_minutes() as f64 / 60.0;
+            let interview_times = times
+                .iter()
+                .map(|time| format!("- {} ({} hours)", self.format_time(*time), hours))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let confirm_link = format!("{}?token={}", self.confirm_url, token);
+
+            let mut letter = letter.clone();
+            letter.subject = letter
+                .subject
+                .replace("{applicant_name}", &applicant.name)
+                .replace("{applicant_role}", &applicant.role);
+            letter.body = letter
+                .body
+                .replace("{applicant_name}", &applicant.name)
+                .replace("{applicant_role}", &applicant.role)
+                .replace("{interview_times}", &interview_times)
+                .replace("{confirm_link}", &confirm_link);
+
+            letter
+        })
+    }
+}
+
+/// Find times that work for the panel and email them to the applicant. Times that were
+/// proposed to the applicant before can no longer be booked.
+pub async fn propose_interview_times(
+    db: &Database,
+    company: &Company,
+    config: &InterviewSchedulingConfig,
+    applicant: &Applicant,
+    panel: &InterviewPanel,
+) -> Result<InterviewSchedule> {
+    panel.validate()?;
+    if config.proposal_letter.is_none() || config.confirm_url.is_empty() {
+        bail!("interview scheduling needs a proposal letter and a confirm url in the config");
+    }
+
+    let earliest = Utc::now() + Duration::hours(config.notice_hours);
+    let latest = earliest + Duration::days(config.search_days + 1);
+    let busy = query_free_busy(db, company, &panel.interviewers(), earliest, latest).await?;
+
+    let times = find_interview_times(panel, &busy, config, earliest)?;
+    if times.is_empty() {
+        bail!(
+            "the panel for {} has no time in common in the next {} days",
+            applicant.email,
+            config.search_days
+        );
+    }
+
+    diesel::update(
+        interview_schedules::dsl::interview_schedules
+            .filter(interview_schedules::dsl::applicant_id.eq(applicant.id))
+            .filter(interview_schedules::dsl::status.eq(InterviewScheduleStatus::Proposed.to_string())),
+    )
+    .set((
+        interview_schedules::dsl::status.eq(InterviewScheduleStatus::Superseded.to_string()),
+        interview_schedules::dsl::updated_at.eq(Utc::now()),
+    ))
+    .execute_async(db.pool())
+    .await?;
+
+    let schedule = diesel::insert_into(interview_schedules::table)
+        .values(NewInterviewSchedule {
+            applicant_id: applicant.id,
+            email: applicant.email.to_string(),
+            panel: serde_json::to_value(panel)?,
+            proposed_times: times.clone(),
+            token: generate_token(),
+            status: InterviewScheduleStatus::Proposed.to_string(),
+            cio_company_id: company.id,
+        })
+        .get_result_async::<InterviewSchedule>(db.pool())
+        .await?;
+
+    // Checked above.
+    let letter = config
+        .create_proposal_letter(applicant, panel, &times, &schedule.token)
+        .unwrap();
+
+    Mailer::new(db, company)
+        .send_plain_text(
+            &letter.subject,
+            &letter.body,
+            &[applicant.email.to_string()],
+            &letter.cc,
+            &letter.bcc,
+            &letter.from,
+        )
+        .await?;
+
+    info!("proposed {} interview times to applicant {}", times.len(), applicant.id);
+
+    Ok(schedule)
+}
+
+/// The calendar the interviews are on, which is the one named `Interviews`.
+async fn interviews_calendar_id(gcal: &GoogleCalendar) -> Result<String> {
+    let calendars = gcal
+        .calendar_list()
+        .list_all(google_calendar::types::MinAccessRole::Noop, false, false)
+        .await?;
+
+    calendars
+        .into_iter()
+        .find(|calendar| calendar.summary == "Interviews")
+        .map(|calendar| calendar.id)
+        .ok_or_else(|| anyhow!("there is no Interviews calendar"))
+}
+
+fn attendee(email: &str) -> EventAttendee {
+    EventAttendee {
+        id: Default::default(),
+        email: email.to_string(),
+        display_name: Default::default(),
+        organizer: false,
+        resource: false,
+        optional: false,
+        response_status: Default::default(),
+        comment: Default::default(),
+        additional_guests: 0,
+        self_: false,
+    }
+}
+
+/// Book the interviews for the time the applicant picked out of the proposed times. Fails with
+/// a [`BookingRejection`] when the time cannot be booked.
+pub async fn book_interview(
+    db: &Database,
+    company: &Company,
+    config: &InterviewSchedulingConfig,
+    zoom_config: &ZoomConfig,
+    token: &str,
+    time: DateTime<Utc>,
+) -> Result<InterviewSchedule> {
+    let schedule = InterviewSchedule::get_by_token(db, token)
+        .await?
+        .ok_or(BookingRejection::UnknownToken)?;
+    if schedule.status != InterviewScheduleStatus::Proposed.to_string() {
+        return Err(BookingRejection::NotProposed(schedule.status).into());
+    }
+    if !schedule.proposed_times.contains(&time) {
+        return Err(BookingRejection::TimeNotProposed(time).into());
+    }
+
+    let panel = schedule.panel()?;
+    let busy = query_free_busy(db, company, &panel.interviewers(), time, time + panel.duration()).await?;
+    if !panel.is_free(&busy, time) {
+        return Err(BookingRejection::TimeUnavailable(time).into());
+    }
+
+    let mut applicant = applicants::dsl::applicants
+        .find(schedule.applicant_id)
+        .first_async::<Applicant>(db.pool())
+        .await?;
+
+    let gcal = company.authenticate_google_calendar(db).await?;
+    let calendar_id = interviews_calendar_id(&gcal).await?;
+    let zoom = if zoom_config.interview_meetings {
+        Some(company.authenticate_zoom_meetings(db).await?)
+    } else {
+        None
+    };
+
+    let mut google_event_ids = vec![];
+    for (session, start, end) in panel.timeline(time) {
+        let usernames: Vec<&str> = session
+            .interviewers
+            .iter()
+            .map(|email| email.split('@').next().unwrap_or_default())
+            .collect();
+        // Interviews are named after the applicant and their interviewers, see
+        // `refresh_interviews`.
+        let summary = format!("{} ({})", applicant.name, usernames.join(", "));
+
+        let meeting = match &zoom {
+            Some(zoom) => {
+                let (host, alternative_hosts) = session.interviewers.split_first().unwrap();
+                let request =
+                    ZoomMeetingRequest::scheduled(ZoomMeetingKind::Meeting, &summary, start, end, alternative_hosts);
+                Some(zoom.create(ZoomMeetingKind::Meeting, host, &request).await?)
+            }
+            None => None,
+        };
+
+        let mut attendees: Vec<EventAttendee> = session.interviewers.iter().map(|email| attendee(email)).collect();
+        attendees.push(attendee(&applicant.email));
+
+        let event = Event {
+            summary,
+            description: format!("{} interview for {}.", session.name, applicant.role),
+            location: meeting.as_ref().map(|m| m.join_url.to_string()).unwrap_or_default(),
+            start: Some(EventDateTime {
+                date: None,
+                date_time: Some(start),
+                time_zone: config.time_zone.to_string(),
+            }),
+            end: Some(EventDateTime {
+                date: None,
+                date_time: Some(end),
+                time_zone: config.time_zone.to_string(),
+            }),
+            attendees,
+            ..Default::default()
+        };
+
+        let event = gcal
+            .events()
+            .insert(
+                &calendar_id,
+                0,    // conference data version
+                0,    // max attendees, 0 to ignore
+                true, // send notifications
+                google_calendar::types::SendUpdates::All,
+                true, // supports_attachments
+                &event,
+            )
+            .await?;
+
+        NewApplicantInterview {
+            start_time: start,
+            end_time: end,
+            name: event.summary.to_string(),
+            email: applicant.email.to_string(),
+            interviewers: session.interviewers.clone(),
+            google_event_id: event.id.to_string(),
+            event_link: event.html_link.to_string(),
+            zoom_meeting_id: meeting.as_ref().map(|m| m.id.to_string()).unwrap_or_default(),
+            zoom_join_url: meeting.map(|m| m.join_url).unwrap_or_default(),
+            applicant: vec![applicant.airtable_record_id.to_string()],
+            cio_company_id: company.id,
+        }
+        .upsert(db)
+        .await?;
+
+        google_event_ids.push(event.id);
+    }
+
+    let schedule = diesel::update(interview_schedules::dsl::interview_schedules.find(schedule.id))
+        .set((
+            interview_schedules::dsl::status.eq(InterviewScheduleStatus::Booked.to_string()),
+            interview_schedules::dsl::booked_time.eq(Some(time)),
+            interview_schedules::dsl::google_event_ids.eq(google_event_ids),
+            interview_schedules::dsl::updated_at.eq(Utc::now()),
+        ))
+        .get_result_async::<InterviewSchedule>(db.pool())
+        .await?;
+
+    // Record the interviews on the applicant, as the sync of the calendar would.
+    if applicant.status == Status::NextSteps.to_string() || applicant.status == Status::NeedsToBeTriaged.to_string() {
+        applicant.status = Status::Interviewing.to_string();
+    }
+    if applicant.interviews_started.map_or(true, |started| time < started) {
+        applicant.interviews_started = Some(time);
+    }
+    let end = time + panel.duration();
+    if applicant.interviews_completed.map_or(true, |completed| end > completed) {
+        applicant.interviews_completed = Some(end);
+    }
+    applicant.update(db).await?;
+
+    info!("booked interviews at {} for applicant {}", time, applicant.id);
+
+    Ok(schedule)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::{find_interview_times, BusyPeriod, BusyTimes, InterviewPanel, InterviewSession};
+    use crate::app_config::{InterviewSchedulingConfig, Letter};
+
+    fn mock_panel() -> InterviewPanel {
+        InterviewPanel {
+            sessions: vec![
+                InterviewSession {
+                    name: "Technical".to_string(),
+                    interviewers: vec!["alice@example.com".to_string()],
+                    duration_minutes: 60,
+                },
+                InterviewSession {
+                    name: "Values".to_string(),
+                    interviewers: vec!["bob@example.com".to_string(), "carol@example.com".to_string()],
+                    duration_minutes: 30,
+                },
+            ],
+        }
+    }
+
+    fn mock_config() -> InterviewSchedulingConfig {
+        InterviewSchedulingConfig {
+            time_zone: "UTC".to_string(),
+            proposals: 3,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_find_interview_times() {
+        let panel = mock_panel();
+        let config = mock_config();
+
+        let mut busy = BusyTimes::new();
+        // Alice is busy Friday morning, so the first session moves to the afternoon.
+        busy.insert(
+            "alice@example.com".to_string(),
+            vec![BusyPeriod {
+                start: Utc.ymd(2023, 3, 24).and_hms(9, 0, 0),
+                end: Utc.ymd(2023, 3, 24).and_hms(13, 0, 0),
+            }],
+        );
+        // Carol is busy until Monday afternoon, which only the second session needs her for.
+        busy.insert(
+            "carol@example.com".to_string(),
+            vec![BusyPeriod {
+                start: Utc.ymd(2023, 3, 27).and_hms(0, 0, 0),
+                end: Utc.ymd(2023, 3, 27).and_hms(14, 0, 0),
+            }],
+        );
+
+        // A Thursday, late enough that the panel no longer fits in the day.
+        let earliest = Utc.ymd(2023, 3, 23).and_hms(15, 45, 0);
+        let times = find_interview_times(&panel, &busy, &config, earliest).unwrap();
+
+        assert_eq!(
+            vec![
+                Utc.ymd(2023, 3, 24).and_hms(13, 0, 0),
+                // Over the weekend, and the second session starts once Carol is free.
+                Utc.ymd(2023, 3, 27).and_hms(13, 0, 0),
+                Utc.ymd(2023, 3, 28).and_hms(9, 0, 0),
+            ],
+            times
+        );
+    }
+
+    #[test]
+    fn test_find_interview_times_none_free() {
+        let panel = mock_panel();
+        let config = InterviewSchedulingConfig {
+            search_days: 2,
+            ..mock_config()
+        };
+
+        let mut busy = BusyTimes::new();
+        busy.insert(
+            "bob@example.com".to_string(),
+            vec![BusyPeriod {
+                start: Utc.ymd(2023, 3, 20).and_hms(0, 0, 0),
+                end: Utc.ymd(2023, 3, 30).and_hms(0, 0, 0),
+            }],
+        );
+
+        let earliest = Utc.ymd(2023, 3, 20).and_hms(8, 0, 0);
+        assert!(find_interview_times(&panel, &busy, &config, earliest)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_proposal_letter() {
+        let config = InterviewSchedulingConfig {
+            confirm_url: "https://example.com/careers/schedule".to_string(),
+            proposal_letter: Some(Letter {
+                subject: "Interviews for {applicant_role}".to_string(),
+                body: "Hi {applicant_name},\n{interview_times}\nPick one at {confirm_link}".to_string(),
+                from: "careers@example.com".to_string(),
+                cc: vec![],
+                bcc: vec![],
+            }),
+            ..mock_config()
+        };
+        let applicant = crate::applicants::tests::mock_applicant();
+
+        let letter = config
+            .create_proposal_letter(
+                &applicant,
+                &mock_panel(),
+                &[Utc.ymd(2023, 3, 24).and_hms(13, 0, 0)],
+                "abc",
+            )
+            .unwrap();
+
+        assert_eq!("Interviews for Engineering", letter.subject);
+        assert_eq!(
+            "Hi Test User,\n- Friday, March 24 at 1:00 PM UTC (1.5 hours)\nPick one at https://example.com/careers/schedule?token=abc",
+            letter.body
+        );
+    }
+}
@@ -0,0 +1,566 @@
+/*!
+ * Packets for interviewers.
+ *
+ * The evening before an interview, each of its interviewers is sent a packet in Slack with what
+ * they need for it: the description of the role, the applicant's resume, what was written about
+ * the applicant in earlier reviews, and the questions of the module they are interviewing for.
+ * The module is the session of the panel the interview was booked for, so interviews that were
+ * not booked through [`crate::interview_scheduling`] get a packet without questions.
+ *
+ * Packets are rendered to PDFs and uploaded to the automated documents drive. Every packet is
+ * claimed in `interviewer_packets` before it is sent, so nobody is sent the same packet twice
+ * however often the job runs.
+ */
+use std::{collections::HashMap, env, fs, process::Command};
+
+use anyhow::{anyhow, bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use diesel::{ExpressionMethods, Insertable, PgArrayExpressionMethods, QueryDsl};
+use google_drive::traits::PermissionOps;
+use log::{info, warn};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    app_config::ApplyConfig,
+    applicant_reviews::ApplicantReview,
+    applicants::Applicant,
+    companies::Company,
+    db::Database,
+    drive::{DocumentStore, AUTOMATED_DOCUMENTS_DRIVE},
+    interview_scheduling::InterviewSchedule,
+    interviews::{ApplicantInterview, ApplicantInterviews},
+    schema::{applicant_reviews, applicants, interview_schedules, interviewer_packets},
+};
+
+#[derive(Insertable)]
+#[diesel(table_name = interviewer_packets)]
+struct NewInterviewerPacket {
+    google_event_id: String,
+    interviewer: String,
+    cio_company_id: i32,
+}
+
+/// What was written about the applicant in an earlier review.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PriorNote {
+    pub reviewer: String,
+    pub evaluation: String,
+    pub notes: String,
+}
+
+/// What an interviewer is sent ahead of an interview.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterviewerPacket {
+    pub applicant_name: String,
+    pub role: String,
+    pub role_description: String,
+    /// The session of the panel the interview is for, empty when it is not known.
+    pub module: String,
+    /// When the interview is, in the time zone of interviews.
+    pub time: String,
+    pub meeting_link: String,
+    pub resume_url: String,
+    pub resume_text: String,
+    pub materials_url: String,
+    pub prior_notes: Vec<PriorNote>,
+    pub questions: Vec<String>,
+}
+
+impl InterviewerPacket {
+    pub fn to_html(&self) -> String {
+        let mut html = format!(
+            "<html>\n<body>\n<h1>Interview with {}</h1>\n<p>{} interview for {}, {}.</p>\n",
+            escape(&self.applicant_name),
+            if self.module.is_empty() {
+                "An".to_string()
+            } else {
+                format!("The {}", escape(&self.module))
+            },
+            escape(&self.role),
+            escape(&self.time),
+        );
+        if !self.meeting_link.is_empty() {
+            html += &link_paragraph("Join the interview", &self.meeting_link);
+        }
+
+        html += "<h2>The role</h2>\n";
+        if self.role_description.is_empty() {
+            html += "<p>There is no description of this role yet.</p>\n";
+        } else {
+            html += &paragraphs(&self.role_description);
+        }
+
+        html += "<h2>Resume</h2>\n";
+        if !self.resume_url.is_empty() {
+            html += &link_paragraph("Original resume", &self.resume_url);
+        }
+        if !self.materials_url.is_empty() {
+            html += &link_paragraph("Materials", &self.materials_url);
+        }
+        if self.resume_text.trim().is_empty() {
+            html += "<p>The text of the resume could not be read.</p>\n";
+        } else {
+            html += &format!("<pre>{}</pre>\n", escape(self.resume_text.trim()));
+        }
+
+        html += "<h2>Notes from earlier reviews</h2>\n";
+        if self.prior_notes.is_empty() {
+            html += "<p>Nobody has reviewed this applicant yet.</p>\n";
+        } else {
+            html += "<ul>\n";
+            for note in &self.prior_notes {
+                html += &format!("<li><b>{}</b>", escape(&note.reviewer));
+                if !note.evaluation.is_empty() {
+                    html += &format!(" ({})", escape(&note.evaluation));
+                }
+                if !note.notes.is_empty() {
+                    html += &format!(": {}", escape(&note.notes));
+                }
+                html += "</li>\n";
+            }
+            html += "</ul>\n";
+        }
+
+        html += "<h2>Questions</h2>\n";
+        if self.questions.is_empty() {
+            html += "<p>There is no question bank for this interview.</p>\n";
+        } else {
+            html += "<ol>\n";
+            for question in &self.questions {
+                html += &format!("<li>{}</li>\n", escape(question));
+            }
+            html += "</ol>\n";
+        }
+
+        html += "</body>\n</html>\n";
+        html
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn link_paragraph(text: &str, url: &str) -> String {
+    format!("<p><a href=\"{}\">{}</a></p>\n", escape(url), escape(text))
+}
+
+fn paragraphs(text: &str) -> String {
+    text.split("\n\n")
+        .map(str::trim)
+        .filter(|paragraph| !paragraph.is_empty())
+        .map(|paragraph| format!("<p>{}</p>\n", escape(paragraph)))
+        .collect()
+}
+
+/// Whether the packets of an interview that starts at `start` should be sent at `now`: from
+/// `send_hour` on the evening before the interview until it starts. Interviews that are booked
+/// later than that get their packets right away.
+pub fn packet_due(start: DateTime<Utc>, now: DateTime<Utc>, tz: Tz, send_hour: u32) -> bool {
+    let day_before = start.with_timezone(&tz).date().naive_local() - Duration::days(1);
+    let send_at = day_before.and_hms(send_hour.min(23), 0, 0);
+
+    now < start && now.with_timezone(&tz).naive_local() >= send_at
+}
+
+/// The name of the session of the panel that was booked as the event, if it was booked through
+/// a schedule.
+pub fn session_module(schedule: &InterviewSchedule, google_event_id: &str) -> Option<String> {
+    let position = schedule.google_event_ids.iter().position(|id| id == google_event_id)?;
+    let panel = schedule.panel().ok()?;
+
+    panel.sessions.get(position).map(|session| session.name.to_string())
+}
+
+/// Send the packets of the interviews that start within the next day to their interviewers.
+pub async fn send_interviewer_packets(db: &Database, company: &Company, config: &ApplyConfig) -> Result<()> {
+    let packets_config = &config.interviewer_packets;
+    if !packets_config.enabled || company.airtable_base_id_hiring.is_empty() {
+        info!("skipping `send_interviewer_packets` for company `{}`", company.name);
+
+        // Return early.
+        return Ok(());
+    }
+
+    let tz: Tz = config
+        .interviews
+        .time_zone
+        .parse()
+        .map_err(|e| anyhow!("invalid time zone `{}`: {}", config.interviews.time_zone, e))?;
+    let now = Utc::now();
+
+    let interviews = ApplicantInterviews::get_from_db(db, company.id)
+        .await?
+        .into_iter()
+        .filter(|interview| {
+            !interview.google_event_id.is_empty() && packet_due(interview.start_time, now, tz, packets_config.send_hour)
+        })
+        .collect::<Vec<_>>();
+    if interviews.is_empty() {
+        return Ok(());
+    }
+
+    let slack_users = company
+        .authenticate_slack(db)
+        .await?
+        .list_users()
+        .await?
+        .into_iter()
+        .filter(|slack_user| !slack_user.deleted && !slack_user.email.is_empty())
+        .map(|slack_user| (slack_user.email.to_lowercase(), slack_user.id))
+        .collect::<HashMap<_, _>>();
+
+    let store = DocumentStore::new(db, company, AUTOMATED_DOCUMENTS_DRIVE).await?;
+
+    let mut failures = vec![];
+    for interview in &interviews {
+        if let Err(e) = send_packets(db, company, config, &store, &slack_users, tz, interview).await {
+            warn!("{}", e);
+            failures.push(e.to_string());
+        }
+    }
+
+    if !failures.is_empty() {
+        bail!(
+            "{} interviewer packets failed to send: {}",
+            failures.len(),
+            failures.join("; ")
+        );
+    }
+
+    Ok(())
+}
+
+async fn send_packets(
+    db: &Database,
+    company: &Company,
+    config: &ApplyConfig,
+    store: &DocumentStore,
+    slack_users: &HashMap<String, String>,
+    tz: Tz,
+    interview: &ApplicantInterview,
+) -> Result<()> {
+    let sent = interviewer_packets::dsl::interviewer_packets
+        .filter(interviewer_packets::dsl::cio_company_id.eq(company.id))
+        .filter(interviewer_packets::dsl::google_event_id.eq(interview.google_event_id.to_string()))
+        .select(interviewer_packets::dsl::interviewer)
+        .load_async::<String>(db.pool())
+        .await?;
+    let pending = interview
+        .interviewers
+        .iter()
+        .filter(|interviewer| !sent.contains(interviewer))
+        .collect::<Vec<_>>();
+    if pending.is_empty() {
+        return Ok(());
+    }
+
+    let applicant = applicants::dsl::applicants
+        .filter(applicants::dsl::cio_company_id.eq(company.id))
+        .filter(applicants::dsl::email.eq(interview.email.to_string()))
+        .first_async::<Applicant>(db.pool())
+        .await
+        .map_err(|_| anyhow!("there is no applicant for interview `{}`", interview.name))?;
+
+    let packet = build_packet(db, company, config, tz, interview, &applicant).await?;
+    let pdf = render_pdf(&packet.to_html(), &interview.google_event_id).await?;
+    let filename = format!(
+        "Interviewer Packet - {} - {}.pdf",
+        applicant.name,
+        interview.start_time.with_timezone(&tz).format("%Y-%m-%d %H%M")
+    );
+    let document = store
+        .upload(&["interviewer_packets"], &filename, "application/pdf", &pdf)
+        .await?;
+
+    for interviewer in pending {
+        let slack_id = match slack_users.get(&interviewer.to_lowercase()) {
+            Some(slack_id) => slack_id,
+            None => {
+                warn!(
+                    "interviewer `{}` of interview `{}` is not in Slack, not sending their packet",
+                    interviewer, interview.name
+                );
+                continue;
+            }
+        };
+
+        if !claim_packet(db, company, &interview.google_event_id, interviewer).await? {
+            continue;
+        }
+
+        let sent = async {
+            store
+                .client()
+                .permissions()
+                .add_if_not_exists(
+                    &document.file_id,
+                    interviewer,
+                    "",
+                    "reader",
+                    "user",
+                    false, // use domain admin access
+                    false, // send notification email
+                )
+                .await?;
+
+            company
+                .post_to_slack_channel(db, &packet_message(slack_id, &packet, &document.url))
+                .await
+        }
+        .await;
+
+        if let Err(e) = sent {
+            release_packet(db, company, &interview.google_event_id, interviewer).await?;
+            bail!(
+                "sending the packet of interview `{}` to `{}` failed: {}",
+                interview.name,
+                interviewer,
+                e
+            );
+        }
+
+        info!("sent the packet of interview `{}` to `{}`", interview.name, interviewer);
+    }
+
+    Ok(())
+}
+
+async fn build_packet(
+    db: &Database,
+    company: &Company,
+    config: &ApplyConfig,
+    tz: Tz,
+    interview: &ApplicantInterview,
+    applicant: &Applicant,
+) -> Result<InterviewerPacket> {
+    let module = interview_schedules::dsl::interview_schedules
+        .filter(interview_schedules::dsl::cio_company_id.eq(company.id))
+        .filter(interview_schedules::dsl::google_event_ids.contains(vec![interview.google_event_id.to_string()]))
+        .first_async::<InterviewSchedule>(db.pool())
+        .await
+        .ok()
+        .and_then(|schedule| session_module(&schedule, &interview.google_event_id))
+        .unwrap_or_default();
+
+    let prior_notes = if applicant.airtable_record_id.is_empty() {
+        vec![]
+    } else {
+        applicant_reviews::dsl::applicant_reviews
+            .filter(applicant_reviews::dsl::cio_company_id.eq(company.id))
+            .filter(applicant_reviews::dsl::applicant.contains(vec![applicant.airtable_record_id.to_string()]))
+            .load_async::<ApplicantReview>(db.pool())
+            .await?
+            .into_iter()
+            .filter(|review| !review.evaluation.is_empty() || !review.notes.is_empty())
+            .map(|review| PriorNote {
+                reviewer: review.reviewer,
+                evaluation: review.evaluation,
+                notes: review.notes,
+            })
+            .collect()
+    };
+
+    let packets_config = &config.interviewer_packets;
+    let start = interview.start_time.with_timezone(&tz);
+    let end = interview.end_time.with_timezone(&tz);
+
+    Ok(InterviewerPacket {
+        applicant_name: applicant.name.to_string(),
+        role: applicant.role.to_string(),
+        role_description: packets_config
+            .role_descriptions
+            .get(&applicant.role)
+            .cloned()
+            .unwrap_or_default(),
+        questions: packets_config.question_banks.get(&module).cloned().unwrap_or_default(),
+        module,
+        time: format!(
+            "{} to {}",
+            start.format("%A, %B %-d from %-I:%M %p"),
+            end.format("%-I:%M %p %Z")
+        ),
+        meeting_link: if interview.zoom_join_url.is_empty() {
+            interview.event_link.to_string()
+        } else {
+            interview.zoom_join_url.to_string()
+        },
+        resume_url: applicant.resume.to_string(),
+        resume_text: applicant.resume_contents.to_string(),
+        materials_url: applicant.materials.to_string(),
+        prior_notes,
+    })
+}
+
+/// Render the packet to a PDF with pandoc.
+async fn render_pdf(html: &str, name: &str) -> Result<Vec<u8>> {
+    let mut html_path = env::temp_dir();
+    html_path.push(format!("interviewer-packet-{}.html", name));
+    fs::write(&html_path, html)?;
+    let mut pdf_path = env::temp_dir();
+    pdf_path.push(format!("interviewer-packet-{}.pdf", name));
+
+    let args = [
+        "-o".to_string(),
+        pdf_path.to_str().unwrap().to_string(),
+        html_path.to_str().unwrap().to_string(),
+    ];
+    let output = tokio::task::spawn_blocking(move || Command::new("pandoc").args(args).output()).await??;
+    if !output.status.success() {
+        bail!(
+            "pandoc failed to render the packet: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let pdf = fs::read(&pdf_path)?;
+    fs::remove_file(&html_path)?;
+    fs::remove_file(&pdf_path)?;
+
+    Ok(pdf)
+}
+
+fn packet_message(slack_id: &str, packet: &InterviewerPacket, url: &str) -> FormattedMessage {
+    let interview = if packet.module.is_empty() {
+        "interview".to_string()
+    } else {
+        format!("{} interview", packet.module)
+    };
+
+    FormattedMessage {
+        channel: slack_id.to_string(),
+        attachments: Default::default(),
+        blocks: vec![MessageBlock {
+            block_type: MessageBlockType::Section,
+            text: Some(MessageBlockText {
+                text_type: MessageType::Markdown,
+                text: format!(
+                    ":clipboard: Your {} with *{}* for {} is {}. Here is your <{}|interview packet>, with the \
+                     role, their resume, notes from earlier reviews and the questions to ask.",
+                    interview, packet.applicant_name, packet.role, packet.time, url
+                ),
+            }),
+            elements: Default::default(),
+            accessory: Default::default(),
+            block_id: Default::default(),
+            fields: Default::default(),
+        }],
+    }
+}
+
+/// Claim the packet of an interview for an interviewer. Returns whether nobody had claimed it
+/// yet.
+async fn claim_packet(db: &Database, company: &Company, google_event_id: &str, interviewer: &str) -> Result<bool> {
+    let claimed = diesel::insert_into(interviewer_packets::table)
+        .values(NewInterviewerPacket {
+            google_event_id: google_event_id.to_string(),
+            interviewer: interviewer.to_string(),
+            cio_company_id: company.id,
+        })
+        .on_conflict((
+            interviewer_packets::cio_company_id,
+            interviewer_packets::google_event_id,
+            interviewer_packets::interviewer,
+        ))
+        .do_nothing()
+        .execute_async(db.pool())
+        .await?;
+
+    Ok(claimed > 0)
+}
+
+/// Let go of a claim whose packet failed to send, so that the next run tries again.
+async fn release_packet(db: &Database, company: &Company, google_event_id: &str, interviewer: &str) -> Result<()> {
+    diesel::delete(interviewer_packets::dsl::interviewer_packets)
+        .filter(interviewer_packets::dsl::cio_company_id.eq(company.id))
+        .filter(interviewer_packets::dsl::google_event_id.eq(google_event_id.to_string()))
+        .filter(interviewer_packets::dsl::interviewer.eq(interviewer.to_string()))
+        .execute_async(db.pool())
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::{packet_due, session_module, InterviewerPacket, PriorNote};
+    use crate::interview_scheduling::InterviewSchedule;
+
+    #[test]
+    fn test_packet_due() {
+        let tz = chrono_tz::America::Los_Angeles;
+        // 10am on Tuesday in Los Angeles.
+        let start = Utc.ymd(2023, 4, 11).and_hms(17, 0, 0);
+
+        // 5pm and 6pm on Monday in Los Angeles.
+        assert!(!packet_due(start, Utc.ymd(2023, 4, 11).and_hms(0, 0, 0), tz, 18));
+        assert!(packet_due(start, Utc.ymd(2023, 4, 11).and_hms(1, 0, 0), tz, 18));
+        // Booked on the day of the interview.
+        assert!(packet_due(start, Utc.ymd(2023, 4, 11).and_hms(16, 0, 0), tz, 18));
+        // The interview has started.
+        assert!(!packet_due(start, start, tz, 18));
+    }
+
+    #[test]
+    fn test_session_module() {
+        let schedule = InterviewSchedule {
+            id: 1,
+            applicant_id: 1,
+            email: "applicant@example.com".to_string(),
+            panel: serde_json::json!({
+                "sessions": [
+                    {"name": "Technical", "interviewers": ["alice@example.com"], "duration_minutes": 60},
+                    {"name": "Values", "interviewers": ["bob@example.com"], "duration_minutes": 30},
+                ]
+            }),
+            proposed_times: vec![],
+            token: "token".to_string(),
+            status: "booked".to_string(),
+            booked_time: None,
+            google_event_ids: vec!["first".to_string(), "second".to_string()],
+            cio_company_id: 1,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        assert_eq!(Some("Values".to_string()), session_module(&schedule, "second"));
+        assert_eq!(None, session_module(&schedule, "third"));
+    }
+
+    #[test]
+    fn test_packet_html() {
+        let packet = InterviewerPacket {
+            applicant_name: "Jess <Doe>".to_string(),
+            role: "Engineer".to_string(),
+            role_description: "Build things.\n\nFix things.".to_string(),
+            module: "Technical".to_string(),
+            time: "Tuesday, April 11 from 10:00 AM to 11:00 AM PDT".to_string(),
+            meeting_link: String::new(),
+            resume_url: String::new(),
+            resume_text: "Rust & C".to_string(),
+            materials_url: String::new(),
+            prior_notes: vec![PriorNote {
+                reviewer: "alice@example.com".to_string(),
+                evaluation: "Yes".to_string(),
+                notes: "Great writing.".to_string(),
+            }],
+            questions: vec!["Tell us about a bug you fixed.".to_string()],
+        };
+
+        let html = packet.to_html();
+        assert!(html.contains("<h1>Interview with Jess &lt;Doe&gt;</h1>"));
+        assert!(html.contains("<p>The Technical interview for Engineer, Tuesday"));
+        assert!(html.contains("<p>Build things.</p>\n<p>Fix things.</p>"));
+        assert!(html.contains("<pre>Rust &amp; C</pre>"));
+        assert!(html.contains("<li><b>alice@example.com</b> (Yes): Great writing.</li>"));
+        assert!(html.contains("<li>Tell us about a bug you fixed.</li>"));
+        assert!(!html.contains("Join the interview"));
+    }
+}
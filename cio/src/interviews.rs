@@ -13,7 +13,7 @@ use chrono::Duration;
 use chrono::{DateTime, Utc};
 use chrono_tz::Tz;
 use google_drive::{
-    traits::{DriveOps, FileOps, PermissionOps},
+    traits::{FileOps, PermissionOps},
     Client as GoogleDrive,
 };
 use log::{info, warn};
@@ -24,12 +24,15 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     airtable::AIRTABLE_INTERVIEWS_TABLE,
+    app_config::ZoomConfig,
     applicants::Applicant,
     companies::Company,
     configs::{User, Users},
     core::UpdateAirtableRecord,
     db::Database,
+    drive::{DocumentStore, AUTOMATED_DOCUMENTS_DRIVE},
     schema::{applicant_interviews, applicants, users},
+    zoom::{ZoomMeetingKind, ZoomMeetingRequest},
 };
 
 #[db {
@@ -60,6 +63,11 @@ pub struct NewApplicantInterview {
     pub google_event_id: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub event_link: String,
+    /// The Zoom meeting for the interview, if interviews are held on Zoom.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub zoom_meeting_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub zoom_join_url: String,
     /// link to another table in Airtable
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub applicant: Vec<String>,
@@ -85,6 +93,9 @@ pub async fn refresh_interviews(db: &Database, company: &Company) -> Result<()>
 
     let gcal = company.authenticate_google_calendar(db).await?;
 
+    // Zoom is only needed to clean up the meetings of cancelled interviews.
+    let zoom = company.authenticate_zoom_meetings(db).await.ok();
+
     // Get the list of our calendars.
     let calendars = gcal
         .calendar_list()
@@ -126,6 +137,19 @@ pub async fn refresh_interviews(db: &Database, company: &Company) -> Result<()>
             if event.status == "cancelled" {
                 // See if we have the event.
                 if let Some(db_event) = ApplicantInterview::get_from_db(db, event.id.to_string()).await {
+                    // Delete the Zoom meeting for the interview as well.
+                    match &zoom {
+                        Some(zoom) if !db_event.zoom_meeting_id.is_empty() => {
+                            if let Err(e) = zoom.delete(ZoomMeetingKind::Meeting, &db_event.zoom_meeting_id).await {
+                                warn!(
+                                    "deleting the zoom meeting for interview `{}` failed: {}",
+                                    db_event.name, e
+                                );
+                            }
+                        }
+                        _ => (),
+                    }
+
                     db_event.delete(db).await?;
                 }
 
@@ -144,6 +168,8 @@ pub async fn refresh_interviews(db: &Database, company: &Company) -> Result<()>
 
                 google_event_id: event.id.to_string(),
                 event_link: event.html_link.to_string(),
+                zoom_meeting_id: Default::default(),
+                zoom_join_url: Default::default(),
                 applicant: Default::default(),
                 cio_company_id: company.id,
             };
@@ -303,6 +329,13 @@ pub async fn refresh_interviews(db: &Database, company: &Company) -> Result<()>
                 // We only care about interviews where the candidate has interviewers.
                 continue;
             }
+
+            // Keep the Zoom meeting that was created for the interview.
+            if let Some(existing) = ApplicantInterview::get_from_db(db, interview.google_event_id.to_string()).await {
+                interview.zoom_meeting_id = existing.zoom_meeting_id;
+                interview.zoom_join_url = existing.zoom_join_url;
+            }
+
             interview.upsert(db).await?;
         }
     }
@@ -315,6 +348,60 @@ pub async fn refresh_interviews(db: &Database, company: &Company) -> Result<()>
     Ok(())
 }
 
+/// Make sure every upcoming interview has a Zoom meeting that matches its calendar event. The
+/// first interviewer hosts the meeting and the other interviewers are alternative hosts.
+pub async fn sync_interview_zoom_meetings(db: &Database, company: &Company, config: &ZoomConfig) -> Result<()> {
+    if !config.interview_meetings {
+        // Return early.
+        return Ok(());
+    }
+
+    let zoom = company.authenticate_zoom_meetings(db).await?;
+
+    let interviews = ApplicantInterviews::get_from_db(db, company.id).await?;
+    for mut interview in interviews {
+        if interview.end_time < Utc::now() {
+            continue;
+        }
+
+        let (host, alternative_hosts) = match interview.interviewers.split_first() {
+            Some(hosts) => hosts,
+            None => continue,
+        };
+
+        let request = ZoomMeetingRequest::scheduled(
+            ZoomMeetingKind::Meeting,
+            &interview.name,
+            interview.start_time,
+            interview.end_time,
+            alternative_hosts,
+        );
+
+        let meeting = match zoom
+            .ensure(ZoomMeetingKind::Meeting, &interview.zoom_meeting_id, host, &request)
+            .await
+        {
+            Ok(meeting) => meeting,
+            Err(e) => {
+                warn!(
+                    "ensuring the zoom meeting for interview `{}` failed: {}",
+                    interview.name, e
+                );
+                continue;
+            }
+        };
+
+        let meeting_id = meeting.id.to_string();
+        if interview.zoom_meeting_id != meeting_id || interview.zoom_join_url != meeting.join_url {
+            interview.zoom_meeting_id = meeting_id;
+            interview.zoom_join_url = meeting.join_url;
+            interview.update(db).await?;
+        }
+    }
+
+    Ok(())
+}
+
 /// Compile interview packets for each interviewee.
 #[allow(clippy::type_complexity)]
 pub async fn compile_packets(db: &Database, company: &Company) -> Result<()> {
@@ -323,18 +410,9 @@ pub async fn compile_packets(db: &Database, company: &Company) -> Result<()> {
         return Ok(());
     }
 
-    // Initialize the Google Drive client.
-    let drive_client = company.authenticate_google_drive(db).await?;
-    // Figure out where our directory is.
-    // It should be in the shared drive : "Automated Documents"/"rfds"
-    let shared_drive = drive_client.drives().get_by_name("Automated Documents").await?;
-    let drive_id = shared_drive.id.to_string();
-
-    // Get the directory by the name.
-    let parent_id = drive_client
-        .files()
-        .create_folder(&drive_id, "", "interview_packets")
-        .await?;
+    // Packets are stored in the shared drive : "Automated Documents"/"interview_packets"
+    let store = DocumentStore::new(db, company, AUTOMATED_DOCUMENTS_DRIVE).await?;
+    let drive_client = store.client();
 
     // Iterate over each user we have in gsuite and download their materials
     // locally.
@@ -365,7 +443,7 @@ pub async fn compile_packets(db: &Database, company: &Company) -> Result<()> {
         }
 
         // Let's download the contents of their materials locally.
-        if let Err(err) = download_materials_as_pdf(&drive_client, &materials_url, &employee.username).await {
+        if let Err(err) = download_materials_as_pdf(drive_client, &materials_url, &employee.username).await {
             log::warn!(
                 "Failed to download materials for employee {} when constructing interview packets. err: {:?}",
                 employee.id,
@@ -580,19 +658,17 @@ The Oxide Team
 
         let buffer = combine_pdfs(packet_args.to_vec())?;
 
-        // Create or update the file in the google_drive.
-        let drive_file = drive_client
-            .files()
-            .create_or_update(&drive_id, &parent_id, &filename, "application/pdf", &buffer)
+        let packet = store
+            .upload(&["interview_packets"], &filename, "application/pdf", &buffer)
             .await?;
-        applicant.interview_packet = format!("https://drive.google.com/open?id={}", drive_file.id);
+        applicant.interview_packet = packet.url;
         applicant.update(db).await?;
 
         // Add the applicant as a reader to their packet file.
         if let Err(err) = drive_client
             .permissions()
             .add_if_not_exists(
-                &drive_file.id,
+                &packet.file_id,
                 &applicant.email,
                 "",
                 "reader",
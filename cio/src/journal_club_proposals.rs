@@ -0,0 +1,1163 @@
+/*!
+ * Taking papers proposed for the journal club from proposal to archive.
+ *
+ * Papers are proposed by opening an issue in the papers repo with the paper template, or with
+ * `/papers propose <link>` in Slack. Every proposal then goes through the same steps, and a step
+ * that fails is tried again the next time the proposals are synced:
+ *
+ * - its metadata is looked up by its DOI, and its PDF is stored in the shared drive,
+ * - it is put on the agenda of the next journal club meeting with room for it, as a discussion
+ *   topic in the Airtable base of the journal club huddle,
+ * - a thread is started for it in Slack, to discuss it ahead of the meeting,
+ * - once the meeting is over, the notes and recording of the meeting are archived with it.
+ *
+ * Every paper is indexed in Meilisearch, so that past discussions can be searched.
+ */
+use std::{collections::HashMap, fmt};
+
+use airtable_api::{Airtable, Record, User as AirtableUser};
+use anyhow::{anyhow, bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use diesel::{ExpressionMethods, Insertable, PgTextExpressionMethods, QueryDsl, Queryable};
+use log::{info, warn};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    airtable::{AIRTABLE_DISCUSSION_TOPICS_TABLE, AIRTABLE_MEETING_SCHEDULE_TABLE},
+    app_config::JournalClubConfig,
+    companies::Company,
+    configs::get_configs_from_repo,
+    core::Meeting,
+    db::Database,
+    drive::{DocumentStore, AUTOMATED_DOCUMENTS_DRIVE},
+    journal_club_search::JournalClubSearchIndex,
+    schema::{journal_club_proposals, users},
+};
+
+/// The notes of meetings are still picked up for this long after the meeting, since they are
+/// often cleaned up in the days after.
+const ARCHIVE_LOOKBACK_DAYS: i64 = 30;
+
+/// Abstracts are cut down to this many characters in Slack.
+const SLACK_ABSTRACT_LENGTH: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalStatus {
+    Proposed,
+    /// The paper is on the agenda of a meeting.
+    Scheduled,
+    /// The meeting the paper was on the agenda of is over.
+    Discussed,
+}
+
+impl fmt::Display for ProposalStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ProposalStatus::Proposed => "proposed",
+            ProposalStatus::Scheduled => "scheduled",
+            ProposalStatus::Discussed => "discussed",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// Where a paper was proposed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+pub enum ProposalSource {
+    #[serde(rename = "github")]
+    GitHub,
+    #[serde(rename = "slack")]
+    Slack,
+}
+
+impl fmt::Display for ProposalSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ProposalSource::GitHub => "github",
+            ProposalSource::Slack => "slack",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct JournalClubProposal {
+    pub id: i32,
+    pub link: String,
+    /// Empty until the metadata is fetched, when the paper was proposed without a title.
+    pub title: String,
+    /// The email of the person that proposed the paper, or their handle where it was proposed if
+    /// we do not know their email.
+    pub proposed_by: String,
+    pub source: String,
+    /// The issue the paper was proposed in.
+    pub source_link: String,
+    pub status: String,
+    pub doi: String,
+    pub authors: Vec<String>,
+    pub published_year: Option<i32>,
+    pub venue: String,
+    pub abstract_text: String,
+    /// The copy of the PDF in the shared drive.
+    pub pdf_url: String,
+    pub fetched_at: Option<DateTime<Utc>>,
+    /// The huddle the paper is on the agenda of.
+    pub huddle: String,
+    pub meeting_date: Option<NaiveDate>,
+    pub airtable_topic_id: String,
+    pub slack_channel: String,
+    pub slack_ts: String,
+    pub discussion_notes: String,
+    pub recording: String,
+    pub cio_company_id: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = journal_club_proposals)]
+struct NewJournalClubProposal {
+    link: String,
+    title: String,
+    proposed_by: String,
+    source: String,
+    source_link: String,
+    cio_company_id: i32,
+}
+
+/// A paper someone would like the journal club to read.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct PaperProposal {
+    pub link: String,
+    /// Looked up from the DOI of the paper when it is empty.
+    #[serde(default)]
+    pub title: String,
+    pub proposed_by: String,
+    pub source: ProposalSource,
+    #[serde(default)]
+    pub source_link: String,
+}
+
+/// Why a paper could not be proposed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProposalRejection {
+    InvalidLink(String),
+    /// The paper was proposed before, with the title it was proposed with.
+    AlreadyProposed(String),
+}
+
+impl fmt::Display for ProposalRejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProposalRejection::InvalidLink(link) => write!(f, "`{}` is not a link to a paper", link),
+            ProposalRejection::AlreadyProposed(title) => write!(f, "{} was proposed already", title),
+        }
+    }
+}
+
+impl std::error::Error for ProposalRejection {}
+
+impl JournalClubProposal {
+    fn is(&self, status: ProposalStatus) -> bool {
+        self.status == status.to_string()
+    }
+
+    /// The title, or the link for papers we do not know the title of.
+    pub fn display_title(&self) -> &str {
+        if self.title.is_empty() {
+            &self.link
+        } else {
+            &self.title
+        }
+    }
+
+    /// A link to the Slack thread of the paper, empty when it does not have one yet.
+    pub fn thread_link(&self) -> String {
+        if self.slack_channel.is_empty() || self.slack_ts.is_empty() {
+            return String::new();
+        }
+
+        format!(
+            "https://slack.com/archives/{}/p{}",
+            self.slack_channel,
+            self.slack_ts.replace('.', "")
+        )
+    }
+}
+
+/// What is known about a paper from its DOI.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct PaperMetadata {
+    title: String,
+    authors: Vec<String>,
+    published_year: Option<i32>,
+    venue: String,
+    abstract_text: String,
+    /// Where the publisher has the full text as a PDF.
+    pdf_link: String,
+}
+
+/// The DOI in a link or text, ie. `https://doi.org/10.1145/3132747.3132763`.
+fn find_doi(text: &str) -> Option<String> {
+    Regex::new(r#"\b10\.\d{4,9}/[^\s"<>]+"#).unwrap().find(text).map(|m| {
+        m.as_str()
+            .trim_end_matches(|c| matches!(c, '.' | ',' | ';' | ')'))
+            .to_string()
+    })
+}
+
+fn arxiv_id(link: &str) -> Option<String> {
+    Regex::new(r"arxiv\.org/(?:abs|pdf)/(\d{4}\.\d{4,5})")
+        .unwrap()
+        .captures(link)
+        .map(|captures| captures[1].to_string())
+}
+
+/// The DOI of the paper at a link. Papers on arXiv have one even if it is not in the link.
+fn doi_for(link: &str) -> Option<String> {
+    find_doi(link).or_else(|| arxiv_id(link).map(|id| format!("10.48550/arXiv.{}", id)))
+}
+
+/// Where to download the PDF of a paper from.
+fn pdf_source(link: &str, metadata: &PaperMetadata) -> Option<String> {
+    if let Some(id) = arxiv_id(link) {
+        Some(format!("https://arxiv.org/pdf/{}.pdf", id))
+    } else if link.to_lowercase().ends_with(".pdf") {
+        Some(link.to_string())
+    } else if !metadata.pdf_link.is_empty() {
+        Some(metadata.pdf_link.to_string())
+    } else {
+        None
+    }
+}
+
+fn strip_markup(text: &str) -> String {
+    Regex::new(r"<[^>]+>")
+        .unwrap()
+        .replace_all(text, " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// CSL fields can be a string or a list of strings, depending on who registered the DOI.
+fn csl_string(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.trim().to_string(),
+        serde_json::Value::Array(values) => values.first().map(csl_string).unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+/// Read the metadata out of the CSL JSON that doi.org has for a DOI.
+fn metadata_from_csl(csl: &serde_json::Value) -> PaperMetadata {
+    let authors = csl["author"]
+        .as_array()
+        .map(|authors| {
+            authors
+                .iter()
+                .map(|author| {
+                    let name = csl_string(&author["literal"]);
+                    if !name.is_empty() {
+                        return name;
+                    }
+
+                    format!("{} {}", csl_string(&author["given"]), csl_string(&author["family"]))
+                        .trim()
+                        .to_string()
+                })
+                .filter(|name| !name.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let pdf_link = csl["link"]
+        .as_array()
+        .and_then(|links| {
+            links
+                .iter()
+                .find(|link| csl_string(&link["content-type"]) == "application/pdf")
+                .map(|link| csl_string(&link["URL"]))
+        })
+        .unwrap_or_default();
+
+    PaperMetadata {
+        title: strip_markup(&csl_string(&csl["title"])),
+        authors,
+        published_year: csl["issued"]["date-parts"][0][0].as_i64().map(|year| year as i32),
+        venue: csl_string(&csl["container-title"]),
+        abstract_text: strip_markup(&csl_string(&csl["abstract"])),
+        pdf_link,
+    }
+}
+
+async fn lookup_doi(doi: &str) -> Result<PaperMetadata> {
+    let csl: serde_json::Value = reqwest::Client::new()
+        .get(format!("https://doi.org/{}", doi))
+        .header(reqwest::header::ACCEPT, "application/vnd.citationstyles.csl+json")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(metadata_from_csl(&csl))
+}
+
+async fn download_pdf(link: &str) -> Result<Vec<u8>> {
+    let resp = reqwest::get(link).await?.error_for_status()?;
+    let content_type = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    if !content_type.contains("pdf") {
+        bail!("`{}` is a `{}`, not a PDF", link, content_type);
+    }
+
+    Ok(resp.bytes().await?.to_vec())
+}
+
+fn pdf_file_name(proposal: &JournalClubProposal, title: &str) -> String {
+    let title = title
+        .chars()
+        .map(|c| if c == '/' || c == '\\' { '-' } else { c })
+        .collect::<String>();
+
+    format!("{} - {}.pdf", proposal.id, title.trim())
+}
+
+/// The link and title of a paper proposed in an issue. Issues opened with the paper template
+/// have `### Link` and `### Title` sections, otherwise the first link in the issue is used.
+pub fn proposal_from_issue(issue_title: &str, body: &str) -> Option<(String, String)> {
+    let mut sections: HashMap<String, String> = HashMap::new();
+    let mut heading = String::new();
+    for line in body.lines() {
+        if let Some(name) = line.strip_prefix("### ") {
+            heading = name.trim().to_lowercase();
+            continue;
+        }
+
+        let section = sections.entry(heading.to_string()).or_default();
+        section.push_str(line);
+        section.push('\n');
+    }
+
+    let url = Regex::new(r"https?://[^\s<>)\]]+").unwrap();
+    let link = sections
+        .get("link")
+        .and_then(|section| url.find(section))
+        .or_else(|| url.find(body))?
+        .as_str()
+        .to_string();
+
+    let title = match sections.get("title").map(|section| section.trim()) {
+        // Issue forms fill in optional fields that were left empty with this.
+        Some(title) if !title.is_empty() && title != "_No response_" => title.to_string(),
+        _ => {
+            let title = issue_title.trim();
+            let title = match title.strip_prefix('[').and_then(|rest| rest.split_once(']')) {
+                Some((_, rest)) => rest.trim_start_matches(':').trim(),
+                None => title,
+            };
+            match title.split_once(':') {
+                Some((prefix, rest)) if prefix.trim().eq_ignore_ascii_case("paper") => rest.trim().to_string(),
+                _ => title.to_string(),
+            }
+        }
+    };
+
+    Some((link, title))
+}
+
+/// Who proposed a paper on GitHub: their email if they are one of us, their GitHub login
+/// otherwise.
+pub async fn proposer_from_github(db: &Database, company: &Company, login: &str) -> Result<String> {
+    let email = users::dsl::users
+        .filter(users::dsl::cio_company_id.eq(company.id))
+        .filter(users::dsl::github.ilike(login.to_string()))
+        .select(users::dsl::email)
+        .first_async::<String>(db.pool())
+        .await
+        .ok();
+
+    Ok(email.unwrap_or_else(|| format!("@{}", login)))
+}
+
+/// The meetings of the journal club huddle, so papers can be put on their agendas.
+struct Agenda {
+    huddle: String,
+    airtable: Airtable,
+    /// Sorted by date.
+    meetings: Vec<Record<Meeting>>,
+}
+
+async fn load_agenda(db: &Database, company: &Company, config: &JournalClubConfig) -> Result<Option<Agenda>> {
+    if config.huddle.is_empty() {
+        return Ok(None);
+    }
+
+    let github = company.authenticate_github()?;
+    let configs = get_configs_from_repo(&github, company).await?;
+    let huddle = configs
+        .huddles
+        .get(&config.huddle)
+        .ok_or_else(|| anyhow!("there is no `{}` huddle in the configs", config.huddle))?;
+
+    let airtable = Airtable::new(&company.airtable_api_key, huddle.airtable_base_id.to_string(), "");
+    let mut meetings: Vec<Record<Meeting>> = airtable
+        .list_records(AIRTABLE_MEETING_SCHEDULE_TABLE, "All Meetings", vec![])
+        .await?;
+    meetings.sort_by_key(|meeting| meeting.fields.date);
+
+    Ok(Some(Agenda {
+        huddle: config.huddle.to_string(),
+        airtable,
+        meetings,
+    }))
+}
+
+/// A paper as a discussion topic in the Airtable base of a huddle.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct PaperTopic {
+    #[serde(rename = "Topic")]
+    topic: String,
+    #[serde(default, rename = "Submitter", skip_serializing_if = "Option::is_none")]
+    submitter: Option<AirtableUser>,
+    #[serde(default, rename = "Notes", skip_serializing_if = "String::is_empty")]
+    notes: String,
+    #[serde(default, rename = "Associated meetings")]
+    associated_meetings: Vec<String>,
+}
+
+/// How many papers are on the agenda of each meeting of the huddle already.
+fn scheduled_counts(proposals: &[JournalClubProposal], huddle: &str) -> HashMap<NaiveDate, usize> {
+    let mut counts = HashMap::new();
+    for proposal in proposals.iter().filter(|proposal| proposal.huddle == huddle) {
+        if let Some(date) = proposal.meeting_date {
+            *counts.entry(date).or_default() += 1;
+        }
+    }
+
+    counts
+}
+
+/// The first meeting from today on that has room for another paper.
+fn next_open_meeting<'a>(
+    meetings: &'a [Record<Meeting>],
+    scheduled: &HashMap<NaiveDate, usize>,
+    papers_per_meeting: usize,
+    today: NaiveDate,
+) -> Option<&'a Record<Meeting>> {
+    meetings.iter().find(|meeting| {
+        !meeting.fields.cancelled
+            && meeting.fields.date >= today
+            && scheduled.get(&meeting.fields.date).copied().unwrap_or_default() < papers_per_meeting
+    })
+}
+
+fn discussion_notes(meeting: &Meeting) -> String {
+    let mut notes = meeting.notes.trim().to_string();
+    if !meeting.action_items.trim().is_empty() {
+        notes = format!("{}\n\nAction items:\n{}", notes, meeting.action_items.trim());
+    }
+
+    notes.trim().to_string()
+}
+
+fn thread_message(channel: &str, proposal: &JournalClubProposal) -> FormattedMessage {
+    let mut text = format!(":page_facing_up: *<{}|{}>*", proposal.link, proposal.display_title());
+
+    let mut published = proposal.authors.join(", ");
+    if !proposal.venue.is_empty() {
+        published = format!("{} | {}", published, proposal.venue);
+    }
+    if let Some(year) = proposal.published_year {
+        published = format!("{} | {}", published, year);
+    }
+    let published = published.trim_start_matches(" | ");
+    if !published.is_empty() {
+        text.push_str(&format!("\n{}", published));
+    }
+
+    let mut details = vec![format!("Proposed by {}", proposal.proposed_by)];
+    if let Some(date) = proposal.meeting_date {
+        details.push(format!("On the agenda for {}", date.format("%A %B %-d")));
+    }
+    if !proposal.pdf_url.is_empty() {
+        details.push(format!("<{}|PDF>", proposal.pdf_url));
+    }
+    text.push_str(&format!("\n{}", details.join(" | ")));
+
+    if !proposal.abstract_text.is_empty() {
+        let mut summary = proposal
+            .abstract_text
+            .chars()
+            .take(SLACK_ABSTRACT_LENGTH)
+            .collect::<String>();
+        if summary.len() < proposal.abstract_text.len() {
+            summary.push('…');
+        }
+        text.push_str(&format!("\n> {}", summary));
+    }
+
+    text.push_str("\nDiscuss it in the thread :thread:");
+
+    FormattedMessage {
+        channel: channel.to_string(),
+        attachments: Default::default(),
+        blocks: vec![MessageBlock {
+            block_type: MessageBlockType::Section,
+            text: Some(MessageBlockText {
+                text_type: MessageType::Markdown,
+                text,
+            }),
+            elements: Default::default(),
+            accessory: Default::default(),
+            block_id: Default::default(),
+            fields: Default::default(),
+        }],
+    }
+}
+
+/// Look up the metadata of the paper, and keep a copy of its PDF in the shared drive.
+async fn fetch_paper(db: &Database, company: &Company, proposal: &JournalClubProposal) -> Result<JournalClubProposal> {
+    let doi = doi_for(&proposal.link).unwrap_or_default();
+    let metadata = if doi.is_empty() {
+        PaperMetadata::default()
+    } else {
+        lookup_doi(&doi).await?
+    };
+
+    let title = if !proposal.title.is_empty() {
+        proposal.title.to_string()
+    } else {
+        metadata.title.to_string()
+    };
+
+    let mut pdf_url = proposal.pdf_url.to_string();
+    if let Some(source) = pdf_source(&proposal.link, &metadata) {
+        // Plenty of publishers do not let us download the PDF, the paper is still worth reading.
+        match download_pdf(&source).await {
+            Ok(contents) => {
+                let store = DocumentStore::new(db, company, AUTOMATED_DOCUMENTS_DRIVE).await?;
+                let document = store
+                    .upload(
+                        &["journal-club"],
+                        &pdf_file_name(proposal, if title.is_empty() { &doi } else { &title }),
+                        "application/pdf",
+                        &contents,
+                    )
+                    .await?;
+                pdf_url = document.url;
+            }
+            Err(e) => warn!(
+                "failed to download the PDF of journal club paper {}: {}",
+                proposal.id, e
+            ),
+        }
+    }
+
+    Ok(
+        diesel::update(journal_club_proposals::dsl::journal_club_proposals.find(proposal.id))
+            .set((
+                journal_club_proposals::dsl::title.eq(title),
+                journal_club_proposals::dsl::doi.eq(doi),
+                journal_club_proposals::dsl::authors.eq(metadata.authors),
+                journal_club_proposals::dsl::published_year.eq(metadata.published_year),
+                journal_club_proposals::dsl::venue.eq(metadata.venue),
+                journal_club_proposals::dsl::abstract_text.eq(metadata.abstract_text),
+                journal_club_proposals::dsl::pdf_url.eq(pdf_url),
+                journal_club_proposals::dsl::fetched_at.eq(Some(Utc::now())),
+                journal_club_proposals::dsl::updated_at.eq(Utc::now()),
+            ))
+            .get_result_async::<JournalClubProposal>(db.pool())
+            .await?,
+    )
+}
+
+/// Put the paper on the agenda of the next meeting with room for it.
+async fn schedule_paper(
+    db: &Database,
+    config: &JournalClubConfig,
+    agenda: &Agenda,
+    scheduled: &mut HashMap<NaiveDate, usize>,
+    proposal: &JournalClubProposal,
+    today: NaiveDate,
+) -> Result<JournalClubProposal> {
+    let meeting = match next_open_meeting(&agenda.meetings, scheduled, config.papers_per_meeting, today) {
+        Some(meeting) => meeting,
+        None => bail!(
+            "there are no upcoming {} meetings with room for another paper",
+            agenda.huddle
+        ),
+    };
+
+    let mut notes = vec![proposal.link.to_string()];
+    if !proposal.pdf_url.is_empty() {
+        notes.push(format!("PDF: {}", proposal.pdf_url));
+    }
+    let topic = PaperTopic {
+        topic: format!("Paper: {}", proposal.display_title()),
+        submitter: if proposal.proposed_by.contains('@') && !proposal.proposed_by.starts_with('@') {
+            Some(AirtableUser {
+                email: proposal.proposed_by.to_string(),
+                ..Default::default()
+            })
+        } else {
+            None
+        },
+        notes: notes.join("\n"),
+        associated_meetings: vec![meeting.id.to_string()],
+    };
+    let created = agenda
+        .airtable
+        .create_records(
+            AIRTABLE_DISCUSSION_TOPICS_TABLE,
+            vec![Record {
+                id: String::new(),
+                fields: topic,
+                created_time: None,
+            }],
+        )
+        .await?;
+    let topic_id = created.first().map(|record| record.id.to_string()).unwrap_or_default();
+
+    *scheduled.entry(meeting.fields.date).or_default() += 1;
+    info!(
+        "put journal club paper {} on the agenda of the {} {} meeting",
+        proposal.id, agenda.huddle, meeting.fields.date
+    );
+
+    Ok(
+        diesel::update(journal_club_proposals::dsl::journal_club_proposals.find(proposal.id))
+            .set((
+                journal_club_proposals::dsl::status.eq(ProposalStatus::Scheduled.to_string()),
+                journal_club_proposals::dsl::huddle.eq(agenda.huddle.to_string()),
+                journal_club_proposals::dsl::meeting_date.eq(Some(meeting.fields.date)),
+                journal_club_proposals::dsl::airtable_topic_id.eq(topic_id),
+                journal_club_proposals::dsl::updated_at.eq(Utc::now()),
+            ))
+            .get_result_async::<JournalClubProposal>(db.pool())
+            .await?,
+    )
+}
+
+/// Start the Slack thread the paper is discussed in.
+async fn start_thread(
+    db: &Database,
+    company: &Company,
+    config: &JournalClubConfig,
+    proposal: &JournalClubProposal,
+) -> Result<JournalClubProposal> {
+    let response = company
+        .authenticate_slack(db)
+        .await?
+        .post_message(&thread_message(&config.channel, proposal))
+        .await?;
+    let ts = response.message["ts"]
+        .as_str()
+        .ok_or_else(|| anyhow!("Slack did not return the thread of journal club paper {}", proposal.id))?;
+
+    Ok(
+        diesel::update(journal_club_proposals::dsl::journal_club_proposals.find(proposal.id))
+            .set((
+                journal_club_proposals::dsl::slack_channel.eq(response.channel.to_string()),
+                journal_club_proposals::dsl::slack_ts.eq(ts.to_string()),
+                journal_club_proposals::dsl::updated_at.eq(Utc::now()),
+            ))
+            .get_result_async::<JournalClubProposal>(db.pool())
+            .await?,
+    )
+}
+
+/// Archive the notes and recording of the meeting the paper was on the agenda of. Papers whose
+/// meeting was cancelled go back to being proposed, to be put on another agenda.
+async fn archive_discussion(
+    db: &Database,
+    agenda: &Agenda,
+    proposal: &JournalClubProposal,
+) -> Result<JournalClubProposal> {
+    let meeting = agenda
+        .meetings
+        .iter()
+        .find(|meeting| Some(meeting.fields.date) == proposal.meeting_date);
+
+    let update = diesel::update(journal_club_proposals::dsl::journal_club_proposals.find(proposal.id));
+    let updated = match meeting {
+        Some(meeting) if !meeting.fields.cancelled => {
+            update
+                .set((
+                    journal_club_proposals::dsl::status.eq(ProposalStatus::Discussed.to_string()),
+                    journal_club_proposals::dsl::discussion_notes.eq(discussion_notes(&meeting.fields)),
+                    journal_club_proposals::dsl::recording.eq(meeting.fields.recording.to_string()),
+                    journal_club_proposals::dsl::updated_at.eq(Utc::now()),
+                ))
+                .get_result_async::<JournalClubProposal>(db.pool())
+                .await?
+        }
+        _ => {
+            if !proposal.airtable_topic_id.is_empty() {
+                if let Err(e) = agenda
+                    .airtable
+                    .delete_record(AIRTABLE_DISCUSSION_TOPICS_TABLE, &proposal.airtable_topic_id)
+                    .await
+                {
+                    warn!(
+                        "failed to delete the topic of journal club paper {} from Airtable: {}",
+                        proposal.id, e
+                    );
+                }
+            }
+            info!(
+                "the meeting journal club paper {} was on the agenda of did not happen, rescheduling it",
+                proposal.id
+            );
+
+            update
+                .set((
+                    journal_club_proposals::dsl::status.eq(ProposalStatus::Proposed.to_string()),
+                    journal_club_proposals::dsl::meeting_date.eq(None::<NaiveDate>),
+                    journal_club_proposals::dsl::airtable_topic_id.eq(String::new()),
+                    journal_club_proposals::dsl::updated_at.eq(Utc::now()),
+                ))
+                .get_result_async::<JournalClubProposal>(db.pool())
+                .await?
+        }
+    };
+
+    Ok(updated)
+}
+
+/// Take the proposal through every step that is due. Steps that fail are logged and tried
+/// again on the next sync.
+async fn process_proposal(
+    db: &Database,
+    company: &Company,
+    config: &JournalClubConfig,
+    agenda: Option<&Agenda>,
+    scheduled: &mut HashMap<NaiveDate, usize>,
+    mut proposal: JournalClubProposal,
+    today: NaiveDate,
+) -> JournalClubProposal {
+    if proposal.fetched_at.is_none() {
+        match fetch_paper(db, company, &proposal).await {
+            Ok(fetched) => proposal = fetched,
+            Err(e) => warn!("failed to fetch journal club paper {}: {}", proposal.id, e),
+        }
+    }
+
+    if let Some(agenda) = agenda {
+        if proposal.is(ProposalStatus::Proposed) {
+            match schedule_paper(db, config, agenda, scheduled, &proposal, today).await {
+                Ok(on_agenda) => proposal = on_agenda,
+                Err(e) => warn!("failed to schedule journal club paper {}: {}", proposal.id, e),
+            }
+        }
+
+        let archive = match proposal.meeting_date {
+            Some(date) if proposal.is(ProposalStatus::Scheduled) => date < today,
+            Some(date) if proposal.is(ProposalStatus::Discussed) => {
+                date >= today - Duration::days(ARCHIVE_LOOKBACK_DAYS)
+            }
+            _ => false,
+        };
+        if archive && proposal.huddle == agenda.huddle {
+            match archive_discussion(db, agenda, &proposal).await {
+                Ok(archived) => proposal = archived,
+                Err(e) => warn!("failed to archive journal club paper {}: {}", proposal.id, e),
+            }
+        }
+    }
+
+    if proposal.slack_ts.is_empty() && !config.channel.is_empty() {
+        match start_thread(db, company, config, &proposal).await {
+            Ok(started) => proposal = started,
+            Err(e) => warn!(
+                "failed to start the thread of journal club paper {}: {}",
+                proposal.id, e
+            ),
+        }
+    }
+
+    proposal
+}
+
+async fn load_proposals(db: &Database, company: &Company) -> Result<Vec<JournalClubProposal>> {
+    Ok(journal_club_proposals::dsl::journal_club_proposals
+        .filter(journal_club_proposals::dsl::cio_company_id.eq(company.id))
+        .order_by(journal_club_proposals::dsl::id.asc())
+        .load_async::<JournalClubProposal>(db.pool())
+        .await?)
+}
+
+async fn index_proposals(proposals: &[JournalClubProposal]) -> Result<()> {
+    let client = JournalClubSearchIndex::default_client()?;
+    JournalClubSearchIndex::configure_index(&client).await?;
+    JournalClubSearchIndex::index_proposals(&client, proposals).await
+}
+
+/// Propose a paper for the journal club, and take it as far through the pipeline as it goes
+/// right away. Fails with a [`ProposalRejection`] for links that are not papers, or papers that
+/// were proposed before.
+pub async fn propose_paper(
+    db: &Database,
+    company: &Company,
+    config: &JournalClubConfig,
+    proposal: PaperProposal,
+) -> Result<JournalClubProposal> {
+    let link = proposal
+        .link
+        .trim()
+        .trim_start_matches('<')
+        .trim_end_matches('>')
+        .to_string();
+    match url::Url::parse(&link) {
+        Ok(url) if url.scheme() == "http" || url.scheme() == "https" => (),
+        _ => return Err(ProposalRejection::InvalidLink(link).into()),
+    }
+
+    let inserted = diesel::insert_into(journal_club_proposals::table)
+        .values(NewJournalClubProposal {
+            link: link.to_string(),
+            title: proposal.title.trim().to_string(),
+            proposed_by: proposal.proposed_by.to_string(),
+            source: proposal.source.to_string(),
+            source_link: proposal.source_link.to_string(),
+            cio_company_id: company.id,
+        })
+        .on_conflict((journal_club_proposals::cio_company_id, journal_club_proposals::link))
+        .do_nothing()
+        .get_results_async::<JournalClubProposal>(db.pool())
+        .await?;
+    let inserted = match inserted.into_iter().next() {
+        Some(inserted) => inserted,
+        None => {
+            let existing = journal_club_proposals::dsl::journal_club_proposals
+                .filter(journal_club_proposals::dsl::cio_company_id.eq(company.id))
+                .filter(journal_club_proposals::dsl::link.eq(link))
+                .first_async::<JournalClubProposal>(db.pool())
+                .await?;
+            return Err(ProposalRejection::AlreadyProposed(existing.display_title().to_string()).into());
+        }
+    };
+    info!("{} proposed journal club paper {}", inserted.proposed_by, inserted.link);
+
+    let agenda = match load_agenda(db, company, config).await {
+        Ok(agenda) => agenda,
+        Err(e) => {
+            warn!("failed to get the journal club agenda: {}", e);
+            None
+        }
+    };
+    let mut scheduled = scheduled_counts(&load_proposals(db, company).await?, &config.huddle);
+
+    let proposal = process_proposal(
+        db,
+        company,
+        config,
+        agenda.as_ref(),
+        &mut scheduled,
+        inserted,
+        Utc::now().date().naive_utc(),
+    )
+    .await;
+
+    if let Err(e) = index_proposals(std::slice::from_ref(&proposal)).await {
+        warn!("failed to index journal club paper {}: {}", proposal.id, e);
+    }
+
+    Ok(proposal)
+}
+
+/// Take every proposed paper through the steps that are due, and index them all.
+pub async fn sync_journal_club_proposals(db: &Database, company: &Company, config: &JournalClubConfig) -> Result<()> {
+    let proposals = load_proposals(db, company).await?;
+    if proposals.is_empty() {
+        return Ok(());
+    }
+
+    let agenda = load_agenda(db, company, config).await?;
+    let mut scheduled = scheduled_counts(&proposals, &config.huddle);
+    let today = Utc::now().date().naive_utc();
+
+    let mut processed = vec![];
+    for proposal in proposals {
+        processed.push(process_proposal(db, company, config, agenda.as_ref(), &mut scheduled, proposal, today).await);
+    }
+
+    index_proposals(&processed).await?;
+
+    Ok(())
+}
+
+/// Handle `/papers propose <link> [title]` and `/papers search <query>` from Slack, returns the
+/// reply. Returns `None` for everything else `/papers` does.
+pub async fn handle_papers_command(
+    db: &Database,
+    company: &Company,
+    config: &JournalClubConfig,
+    slack_user_id: &str,
+    text: &str,
+) -> Result<Option<String>> {
+    let (subcommand, args) = text.split_once(' ').unwrap_or((text, ""));
+    let args = args.trim();
+
+    let reply = match subcommand {
+        "propose" => {
+            let (link, title) = args.split_once(' ').unwrap_or((args, ""));
+            if link.is_empty() {
+                return Ok(Some("Usage: `/papers propose <link> [title]`".to_string()));
+            }
+
+            let slack_user = company
+                .authenticate_slack(db)
+                .await?
+                .list_users()
+                .await?
+                .into_iter()
+                .find(|slack_user| slack_user.id == slack_user_id)
+                .ok_or_else(|| anyhow!("could not find Slack user `{}`", slack_user_id))?;
+            let proposal = PaperProposal {
+                link: link.to_string(),
+                title: title.to_string(),
+                proposed_by: if slack_user.email.is_empty() {
+                    format!("@{}", slack_user.name)
+                } else {
+                    slack_user.email.to_string()
+                },
+                source: ProposalSource::Slack,
+                source_link: String::new(),
+            };
+
+            match propose_paper(db, company, config, proposal).await {
+                Ok(proposal) => {
+                    let mut reply = format!("Proposed *{}* for the journal club.", proposal.display_title());
+                    if let Some(date) = proposal.meeting_date {
+                        reply.push_str(&format!(" It is on the agenda for {}.", date.format("%A %B %-d")));
+                    }
+                    if !proposal.thread_link().is_empty() {
+                        reply.push_str(&format!(" Discuss it <{}|in its thread>.", proposal.thread_link()));
+                    }
+                    reply
+                }
+                Err(e) => match e.downcast_ref::<ProposalRejection>() {
+                    Some(rejection) => format!("Could not propose the paper: {}.", rejection),
+                    None => return Err(e),
+                },
+            }
+        }
+        "search" => {
+            if args.is_empty() {
+                return Ok(Some("Usage: `/papers search <query>`".to_string()));
+            }
+
+            let client = JournalClubSearchIndex::default_client()?;
+            let papers = JournalClubSearchIndex::search(&client, company.id, args).await?;
+            if papers.is_empty() {
+                format!("No journal club papers match `{}`.", args)
+            } else {
+                papers
+                    .iter()
+                    .map(|paper| {
+                        let mut line = format!(
+                            "• <{}|{}>",
+                            paper.link,
+                            if paper.title.is_empty() {
+                                &paper.link
+                            } else {
+                                &paper.title
+                            }
+                        );
+                        if let Some(date) = paper.meeting_date {
+                            line.push_str(&format!(" ({} {})", paper.status, date));
+                        }
+                        if !paper.thread_link.is_empty() {
+                            line.push_str(&format!(" <{}|thread>", paper.thread_link));
+                        }
+                        if !paper.recording.is_empty() {
+                            line.push_str(&format!(" <{}|recording>", paper.recording));
+                        }
+                        line
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(Some(reply))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use airtable_api::Record;
+    use chrono::NaiveDate;
+    use serde_json::json;
+
+    use super::{
+        discussion_notes, doi_for, metadata_from_csl, next_open_meeting, pdf_source, proposal_from_issue, PaperMetadata,
+    };
+    use crate::core::Meeting;
+
+    fn meeting(id: &str, date: NaiveDate, cancelled: bool) -> Record<Meeting> {
+        Record {
+            id: id.to_string(),
+            fields: serde_json::from_value(json!({
+                "date": date,
+                "cancelled": cancelled,
+            }))
+            .unwrap(),
+            created_time: None,
+        }
+    }
+
+    #[test]
+    fn test_doi_for() {
+        assert_eq!(
+            Some("10.1145/3132747.3132763".to_string()),
+            doi_for("https://doi.org/10.1145/3132747.3132763")
+        );
+        assert_eq!(
+            Some("10.1145/3132747.3132763".to_string()),
+            doi_for("https://dl.acm.org/doi/10.1145/3132747.3132763.")
+        );
+        assert_eq!(
+            Some("10.48550/arXiv.2101.00001".to_string()),
+            doi_for("https://arxiv.org/abs/2101.00001v2")
+        );
+        assert_eq!(
+            None,
+            doi_for("https://www.usenix.org/conference/osdi20/presentation/foo")
+        );
+    }
+
+    #[test]
+    fn test_pdf_source() {
+        let metadata = PaperMetadata {
+            pdf_link: "https://publisher.example.com/paper.pdf".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            Some("https://arxiv.org/pdf/2101.00001.pdf".to_string()),
+            pdf_source("https://arxiv.org/abs/2101.00001", &metadata)
+        );
+        assert_eq!(
+            Some("https://example.com/Paper.PDF".to_string()),
+            pdf_source("https://example.com/Paper.PDF", &metadata)
+        );
+        assert_eq!(
+            Some("https://publisher.example.com/paper.pdf".to_string()),
+            pdf_source("https://doi.org/10.1145/3132747.3132763", &metadata)
+        );
+        assert_eq!(
+            None,
+            pdf_source("https://doi.org/10.1145/3132747.3132763", &Default::default())
+        );
+    }
+
+    #[test]
+    fn test_metadata_from_csl() {
+        let metadata = metadata_from_csl(&json!({
+            "title": "The <i>Unwritten</i> Contract of Solid State Drives",
+            "author": [
+                {"given": "Jun", "family": "He"},
+                {"literal": "The SSD Team"},
+            ],
+            "issued": {"date-parts": [[2017, 4, 23]]},
+            "container-title": ["Proceedings of EuroSys"],
+            "abstract": "<jats:p>We investigate how to use SSDs.</jats:p>",
+            "link": [
+                {"URL": "https://example.com/paper.xml", "content-type": "text/xml"},
+                {"URL": "https://example.com/paper.pdf", "content-type": "application/pdf"},
+            ],
+        }));
+
+        assert_eq!("The Unwritten Contract of Solid State Drives", metadata.title);
+        assert_eq!(vec!["Jun He", "The SSD Team"], metadata.authors);
+        assert_eq!(Some(2017), metadata.published_year);
+        assert_eq!("Proceedings of EuroSys", metadata.venue);
+        assert_eq!("We investigate how to use SSDs.", metadata.abstract_text);
+        assert_eq!("https://example.com/paper.pdf", metadata.pdf_link);
+
+        assert_eq!(PaperMetadata::default(), metadata_from_csl(&json!({})));
+    }
+
+    #[test]
+    fn test_proposal_from_issue() {
+        let body = "### Link\n\nhttps://arxiv.org/abs/2101.00001\n\n### Title\n\n_No response_\n\n### Why should we \
+                    read it?\n\nSee https://example.com/blog";
+        assert_eq!(
+            Some((
+                "https://arxiv.org/abs/2101.00001".to_string(),
+                "Attention Is All You Need".to_string()
+            )),
+            proposal_from_issue("[paper]: Attention Is All You Need", body)
+        );
+
+        let body = "### Title\n\nFast Paxos\n\n### Link\n\nhttps://example.com/fast-paxos.pdf";
+        assert_eq!(
+            Some((
+                "https://example.com/fast-paxos.pdf".to_string(),
+                "Fast Paxos".to_string()
+            )),
+            proposal_from_issue("Paper: something else", body)
+        );
+
+        assert_eq!(
+            Some(("https://example.com/paper".to_string(), "Paxos Made Simple".to_string())),
+            proposal_from_issue("Paxos Made Simple", "Read <https://example.com/paper>!")
+        );
+        assert_eq!(None, proposal_from_issue("Paper: a", "no link here"));
+    }
+
+    #[test]
+    fn test_next_open_meeting() {
+        let today = NaiveDate::from_ymd(2023, 4, 3);
+        let meetings = vec![
+            meeting("past", NaiveDate::from_ymd(2023, 3, 27), false),
+            meeting("cancelled", NaiveDate::from_ymd(2023, 4, 3), true),
+            meeting("full", NaiveDate::from_ymd(2023, 4, 10), false),
+            meeting("open", NaiveDate::from_ymd(2023, 4, 17), false),
+        ];
+        let mut scheduled = HashMap::new();
+        scheduled.insert(NaiveDate::from_ymd(2023, 4, 10), 1);
+
+        assert_eq!(
+            Some("open"),
+            next_open_meeting(&meetings, &scheduled, 1, today).map(|m| m.id.as_str())
+        );
+        assert_eq!(
+            Some("full"),
+            next_open_meeting(&meetings, &scheduled, 2, today).map(|m| m.id.as_str())
+        );
+
+        scheduled.insert(NaiveDate::from_ymd(2023, 4, 17), 1);
+        assert!(next_open_meeting(&meetings, &scheduled, 1, today).is_none());
+    }
+
+    #[test]
+    fn test_discussion_notes() {
+        let mut meeting = meeting("a", NaiveDate::from_ymd(2023, 4, 3), false).fields;
+        assert_eq!("", discussion_notes(&meeting));
+
+        meeting.notes = "We liked it.\n".to_string();
+        meeting.action_items = "- Try it out".to_string();
+        assert_eq!(
+            "We liked it.\n\nAction items:\n- Try it out",
+            discussion_notes(&meeting)
+        );
+    }
+}
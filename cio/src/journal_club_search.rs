@@ -0,0 +1,121 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use meilisearch_minimal_api::{IndexSettings, MeiliClient, SearchQuery};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{journal_club_proposals::JournalClubProposal, search_index};
+
+/// The Meilisearch index the journal club archive is searched in.
+pub const JOURNAL_CLUB_INDEX: &str = "journal-club";
+
+/// Searches return at most this many papers.
+const MAX_RESULTS: u32 = 50;
+
+pub struct JournalClubSearchIndex {}
+
+/// A paper as it is searched, with what was said about it when it was discussed.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, PartialEq)]
+pub struct PaperDocument {
+    pub id: i32,
+    pub cio_company_id: i32,
+    pub title: String,
+    pub link: String,
+    pub authors: Vec<String>,
+    pub published_year: Option<i32>,
+    pub venue: String,
+    pub abstract_text: String,
+    pub pdf_url: String,
+    pub status: String,
+    pub meeting_date: Option<NaiveDate>,
+    /// The day of the meeting as a unix timestamp so it can be sorted on, zero when the paper has
+    /// not been scheduled.
+    pub meeting_time: i64,
+    /// The notes and action items of the meeting the paper was discussed at.
+    pub discussion_notes: String,
+    pub recording: String,
+    /// The Slack thread the paper is discussed in.
+    pub thread_link: String,
+}
+
+impl From<&JournalClubProposal> for PaperDocument {
+    fn from(proposal: &JournalClubProposal) -> Self {
+        Self {
+            id: proposal.id,
+            cio_company_id: proposal.cio_company_id,
+            title: proposal.title.to_string(),
+            link: proposal.link.to_string(),
+            authors: proposal.authors.clone(),
+            published_year: proposal.published_year,
+            venue: proposal.venue.to_string(),
+            abstract_text: proposal.abstract_text.to_string(),
+            pdf_url: proposal.pdf_url.to_string(),
+            status: proposal.status.to_string(),
+            meeting_date: proposal.meeting_date,
+            meeting_time: proposal
+                .meeting_date
+                .map(|date| date.and_hms(0, 0, 0).timestamp())
+                .unwrap_or_default(),
+            discussion_notes: proposal.discussion_notes.to_string(),
+            recording: proposal.recording.to_string(),
+            thread_link: proposal.thread_link(),
+        }
+    }
+}
+
+impl JournalClubSearchIndex {
+    pub fn default_client() -> Result<MeiliClient> {
+        Ok(MeiliClient::new(
+            std::env::var("MEILI_URL")?,
+            std::env::var("MEILI_KEY")?,
+        ))
+    }
+
+    /// Titles rank above authors and abstracts, which rank above what was said in the meeting.
+    /// The newest meetings come first.
+    pub fn index_settings() -> IndexSettings {
+        let searchable = ["title", "authors", "abstract_text", "venue", "discussion_notes"];
+
+        IndexSettings {
+            searchable_attributes: Some(searchable.iter().map(|s| s.to_string()).collect()),
+            filterable_attributes: Some(vec!["cio_company_id".to_string(), "status".to_string()]),
+            sortable_attributes: Some(vec!["meeting_time".to_string()]),
+            ..Default::default()
+        }
+    }
+
+    /// Apply the index settings. Meilisearch only rebuilds the index when the settings change.
+    pub async fn configure_index(client: &MeiliClient) -> Result<()> {
+        search_index::configure_index(client, JOURNAL_CLUB_INDEX, &Self::index_settings()).await
+    }
+
+    /// Add the papers to the index, replacing what was indexed for them before.
+    pub async fn index_proposals(client: &MeiliClient, proposals: &[JournalClubProposal]) -> Result<()> {
+        if proposals.is_empty() {
+            return Ok(());
+        }
+
+        let documents = proposals.iter().map(PaperDocument::from).collect::<Vec<_>>();
+        let task = client
+            .index(JOURNAL_CLUB_INDEX.to_string())
+            .index_documents(&documents, "id")
+            .await?;
+        search_index::wait_for_task(client, task).await
+    }
+
+    /// Search the papers of a company, the most recently discussed first.
+    pub async fn search(client: &MeiliClient, cio_company_id: i32, query: &str) -> Result<Vec<PaperDocument>> {
+        let response = client
+            .index(JOURNAL_CLUB_INDEX.to_string())
+            .search::<PaperDocument>(SearchQuery {
+                query: Some(query.to_string()),
+                filter: Some(vec![format!("cio_company_id = {}", cio_company_id)]),
+                sort: Some(vec!["meeting_time:desc".to_string()]),
+                limit: Some(MAX_RESULTS),
+                ..Default::default()
+            })
+            .await?;
+
+        Ok(response.hits)
+    }
+}
@@ -2,60 +2,113 @@
 #![allow(clippy::field_reassign_with_default)]
 #![allow(clippy::nonstandard_macro_braces)]
 
+pub mod access_requests;
 pub mod airtable;
 pub mod analytics;
 pub mod api_tokens;
 pub mod app_config;
+pub mod applicant_duplicates;
 pub mod applicant_reviews;
+pub mod applicant_scoring;
+pub mod applicant_search;
 pub mod applicant_status;
 pub mod applicant_uploads;
 pub mod applicants;
 pub mod application_form;
+pub mod asset_assignments;
 pub mod asset_inventory;
 pub mod auth_logins;
+pub mod background_checks;
+pub mod barcodes;
+pub mod careers_inbox;
+pub mod celebrations;
 pub mod certs;
+pub mod circuit_breaker;
 pub mod cloud_dns;
 pub mod cloudflare;
 pub mod colors;
 pub mod companies;
 pub mod configs;
 pub mod core;
+pub mod correlation;
+pub mod cost_centers;
 pub mod customers;
+pub mod data_subjects;
 pub mod db;
+pub mod delivery;
+pub mod device_credentials;
+pub mod directory;
 pub mod dns_providers;
 pub mod dns_proxy;
+pub mod drive;
+pub mod email;
+pub mod email_suppressions;
 #[macro_use]
 pub mod enclose;
+pub mod expense_anomalies;
+pub mod failures;
 pub mod features;
 pub mod finance;
 pub mod functions;
+pub mod geocode;
 pub mod github_commits;
+pub mod github_event_routes;
 pub mod github_prs;
+pub mod github_repo_settings;
+pub mod github_teams;
 pub mod gsuite;
+pub mod gusto;
 pub mod huddles;
+pub mod interview_scheduling;
+pub mod interviewer_packets;
 pub mod interviews;
+pub mod journal_club_proposals;
+pub mod journal_club_search;
 pub mod journal_clubs;
 pub mod mailerlite;
 pub mod mailing_list;
+pub mod mailing_list_providers;
+pub mod notifications;
 pub mod octorust_utils;
+pub mod offboarding;
+pub mod offer_approvals;
+pub mod okta;
+pub mod onboarding;
+pub mod org_chart;
+pub mod pagerduty;
+pub mod payments;
+pub mod policy_acknowledgments;
 pub mod printer;
+pub mod progress;
 pub mod providers;
+pub mod provisioning;
 pub mod rack_line;
 pub mod recorded_meetings;
+pub mod recruiting_funnel;
+pub mod redaction;
 pub mod repos;
 pub mod rfd;
+pub mod route53;
+pub mod scheduled_jobs;
+pub mod search_index;
 pub mod schema;
+pub mod shipment_changes;
 pub mod shipment_status;
 pub mod shipments;
 pub mod shorturls;
 pub mod states;
 pub mod swag_inventory;
+pub mod swag_preferences;
+pub mod swag_stock;
 pub mod swag_store;
 pub mod tailscale;
 pub mod templates;
 pub mod travel;
 pub mod utils;
+pub mod webhook_events;
+pub mod webhook_outbox;
 pub mod zoho;
+pub mod zoom;
 
 #[macro_use]
 extern crate diesel;
@@ -1,15 +1,26 @@
 use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::Utc;
 use mailerlite::{
     endpoints::{
-        BatchRequestBuilder, BatchRequestEntryBuilder, BatchRequestEntryBuilderError, BatchResponse,
-        GetSubscriberRequestBuilder, GetSubscriberResponse, ListSegmentSubscribersRequestBuilder,
-        ListSegmentSubscribersResponse, WriteSubscriberRequestBuilder, WriteSubscriberRequestBuilderError,
+        AssignSubscriberToGroupRequestBuilder, BatchRequestBuilder, BatchRequestEntryBuilder,
+        BatchRequestEntryBuilderError, BatchResponse, CreateFieldRequestBuilder, CreateWebhookRequestBuilder,
+        DeleteResponse, DeleteWebhookRequestBuilder, GetSubscriberRequestBuilder, GetSubscriberResponse,
+        ListFieldsRequestBuilder, ListGroupsRequestBuilder, ListPageResponse, ListSegmentSubscribersRequestBuilder,
+        ListSegmentSubscribersResponse, ListSegmentsRequestBuilder, UnassignSubscriberFromGroupRequestBuilder,
+        WriteGroupRequestBuilder, WriteResponse, WriteSubscriberRequestBuilder, WriteSubscriberRequestBuilderError,
         WriteSubscriberResponse,
     },
-    MailerliteClient, MailerliteResponse, Subscriber, SubscriberFieldValue,
+    Field, FieldType, MailerliteClient, MailerliteResponse, Subscriber, SubscriberFieldValue, SubscriberGroup,
+    SubscriberStatus, Webhook, WebhookEventType,
 };
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    mailing_list::NewMailingListSubscriber,
+    mailing_list_providers::{MailingListProviderOps, MailingListSegment},
+};
+
 #[derive(Debug)]
 pub struct Mailerlite<Tz> {
     pub client: MailerliteClient<Tz>,
@@ -64,6 +75,240 @@ impl Mailerlite<chrono_tz::Tz> {
         self.mark_batch(subscribers, "wait_list").await
     }
 
+    /// Find the group with the given name, creating it if it does not yet exist.
+    pub async fn ensure_group(&self, name: &str) -> Result<SubscriberGroup> {
+        let response = self
+            .client
+            .run(
+                ListGroupsRequestBuilder::default()
+                    .filter_name(name.to_string())
+                    .build()?,
+            )
+            .await?;
+
+        log::info!(
+            "[mailerlite:list_groups] Rate-limit max: {:?} remaining: {:?}",
+            response.rate_limit,
+            response.rate_limit_remaining,
+        );
+
+        match response.response {
+            MailerliteResponse::AuthenticationError { .. } => {
+                return Err(anyhow!("Failed to authenticate with Mailerlite"))
+            }
+            // The name filter is a partial match, so the results still need to be checked
+            MailerliteResponse::EndpointResponse(ListPageResponse::Success { data, .. }) => {
+                if let Some(group) = data.into_iter().find(|group| group.name == name) {
+                    return Ok(group);
+                }
+            }
+            MailerliteResponse::EndpointResponse(ListPageResponse::Error { message }) => {
+                return Err(anyhow!("Listing groups from Mailerlite failed with {}", message))
+            }
+        }
+
+        let response = self
+            .client
+            .run(WriteGroupRequestBuilder::default().name(name.to_string()).build()?)
+            .await?;
+
+        log::info!(
+            "[mailerlite:create_group] Rate-limit max: {:?} remaining: {:?}",
+            response.rate_limit,
+            response.rate_limit_remaining,
+        );
+
+        match response.response {
+            MailerliteResponse::AuthenticationError { .. } => Err(anyhow!("Failed to authenticate with Mailerlite")),
+            MailerliteResponse::EndpointResponse(WriteResponse::Success { data }) => Ok(data),
+            MailerliteResponse::EndpointResponse(WriteResponse::Error { message, .. }) => {
+                Err(anyhow!("Creating group {} in Mailerlite failed with {}", name, message))
+            }
+        }
+    }
+
+    pub async fn assign_subscriber_to_group(&self, subscriber_id: &str, group_id: &str) -> Result<SubscriberGroup> {
+        let response = self
+            .client
+            .run(
+                AssignSubscriberToGroupRequestBuilder::default()
+                    .subscriber_id(subscriber_id.to_string())
+                    .group_id(group_id.to_string())
+                    .build()?,
+            )
+            .await?;
+
+        log::info!(
+            "[mailerlite:assign_group] Rate-limit max: {:?} remaining: {:?}",
+            response.rate_limit,
+            response.rate_limit_remaining,
+        );
+
+        match response.response {
+            MailerliteResponse::AuthenticationError { .. } => Err(anyhow!("Failed to authenticate with Mailerlite")),
+            MailerliteResponse::EndpointResponse(WriteResponse::Success { data }) => Ok(data),
+            MailerliteResponse::EndpointResponse(WriteResponse::Error { message, .. }) => Err(anyhow!(
+                "Assigning subscriber {} to group {} failed with {}",
+                subscriber_id,
+                group_id,
+                message
+            )),
+        }
+    }
+
+    /// Remove a subscriber from a group. Removing a subscriber that is not in the group is not an error.
+    pub async fn remove_subscriber_from_group(&self, subscriber_id: &str, group_id: &str) -> Result<()> {
+        let response = self
+            .client
+            .run(
+                UnassignSubscriberFromGroupRequestBuilder::default()
+                    .subscriber_id(subscriber_id.to_string())
+                    .group_id(group_id.to_string())
+                    .build()?,
+            )
+            .await?;
+
+        log::info!(
+            "[mailerlite:unassign_group] Rate-limit max: {:?} remaining: {:?}",
+            response.rate_limit,
+            response.rate_limit_remaining,
+        );
+
+        match response.response {
+            MailerliteResponse::AuthenticationError { .. } => Err(anyhow!("Failed to authenticate with Mailerlite")),
+            MailerliteResponse::EndpointResponse(DeleteResponse::Success | DeleteResponse::NotFound) => Ok(()),
+            MailerliteResponse::EndpointResponse(DeleteResponse::Error { message }) => Err(anyhow!(
+                "Removing subscriber {} from group {} failed with {}",
+                subscriber_id,
+                group_id,
+                message
+            )),
+        }
+    }
+
+    /// Find the custom field with the given key, creating it if it does not yet exist.
+    pub async fn ensure_field(&self, name: &str, field_type: FieldType) -> Result<Field> {
+        let response = self
+            .client
+            .run(
+                ListFieldsRequestBuilder::default()
+                    .filter_keyword(name.to_string())
+                    .build()?,
+            )
+            .await?;
+
+        log::info!(
+            "[mailerlite:list_fields] Rate-limit max: {:?} remaining: {:?}",
+            response.rate_limit,
+            response.rate_limit_remaining,
+        );
+
+        match response.response {
+            MailerliteResponse::AuthenticationError { .. } => {
+                return Err(anyhow!("Failed to authenticate with Mailerlite"))
+            }
+            MailerliteResponse::EndpointResponse(ListPageResponse::Success { data, .. }) => {
+                if let Some(field) = data.into_iter().find(|field| field.key == name || field.name == name) {
+                    if field.field_type != field_type {
+                        return Err(anyhow!(
+                            "Mailerlite field {} exists with type {:?} rather than {:?}",
+                            name,
+                            field.field_type,
+                            field_type
+                        ));
+                    }
+
+                    return Ok(field);
+                }
+            }
+            MailerliteResponse::EndpointResponse(ListPageResponse::Error { message }) => {
+                return Err(anyhow!("Listing fields from Mailerlite failed with {}", message))
+            }
+        }
+
+        let response = self
+            .client
+            .run(
+                CreateFieldRequestBuilder::default()
+                    .name(name.to_string())
+                    .field_type(field_type)
+                    .build()?,
+            )
+            .await?;
+
+        log::info!(
+            "[mailerlite:create_field] Rate-limit max: {:?} remaining: {:?}",
+            response.rate_limit,
+            response.rate_limit_remaining,
+        );
+
+        match response.response {
+            MailerliteResponse::AuthenticationError { .. } => Err(anyhow!("Failed to authenticate with Mailerlite")),
+            MailerliteResponse::EndpointResponse(WriteResponse::Success { data }) => Ok(data),
+            MailerliteResponse::EndpointResponse(WriteResponse::Error { message, .. }) => {
+                Err(anyhow!("Creating field {} in Mailerlite failed with {}", name, message))
+            }
+        }
+    }
+
+    /// Register a webhook for the given events. The returned webhook holds the secret that
+    /// deliveries are signed with.
+    pub async fn create_webhook(&self, name: &str, url: &str, events: Vec<WebhookEventType>) -> Result<Webhook> {
+        let response = self
+            .client
+            .run(
+                CreateWebhookRequestBuilder::default()
+                    .name(name.to_string())
+                    .url(url.to_string())
+                    .events(events)
+                    .build()?,
+            )
+            .await?;
+
+        log::info!(
+            "[mailerlite:create_webhook] Rate-limit max: {:?} remaining: {:?}",
+            response.rate_limit,
+            response.rate_limit_remaining,
+        );
+
+        match response.response {
+            MailerliteResponse::AuthenticationError { .. } => Err(anyhow!("Failed to authenticate with Mailerlite")),
+            MailerliteResponse::EndpointResponse(WriteResponse::Success { data }) => Ok(data),
+            MailerliteResponse::EndpointResponse(WriteResponse::Error { message, .. }) => Err(anyhow!(
+                "Creating webhook {} in Mailerlite failed with {}",
+                name,
+                message
+            )),
+        }
+    }
+
+    pub async fn delete_webhook(&self, webhook_id: &str) -> Result<()> {
+        let response = self
+            .client
+            .run(
+                DeleteWebhookRequestBuilder::default()
+                    .webhook_id(webhook_id.to_string())
+                    .build()?,
+            )
+            .await?;
+
+        log::info!(
+            "[mailerlite:delete_webhook] Rate-limit max: {:?} remaining: {:?}",
+            response.rate_limit,
+            response.rate_limit_remaining,
+        );
+
+        match response.response {
+            MailerliteResponse::AuthenticationError { .. } => Err(anyhow!("Failed to authenticate with Mailerlite")),
+            MailerliteResponse::EndpointResponse(DeleteResponse::Success | DeleteResponse::NotFound) => Ok(()),
+            MailerliteResponse::EndpointResponse(DeleteResponse::Error { message }) => Err(anyhow!(
+                "Deleting webhook {} from Mailerlite failed with {}",
+                webhook_id,
+                message
+            )),
+        }
+    }
+
     async fn get_pending_list_page(
         &self,
         segment_id: &str,
@@ -268,6 +513,147 @@ impl Mailerlite<chrono_tz::Tz> {
     }
 }
 
+impl Mailerlite<chrono_tz::Tz> {
+    async fn get_subscriber(&self, email: &str) -> Result<Option<Subscriber>> {
+        let response = self
+            .client
+            .run(
+                GetSubscriberRequestBuilder::default()
+                    .subscriber_identifier(email.to_string())
+                    .build()?,
+            )
+            .await?;
+
+        log::info!(
+            "[mailerlite:get_subscriber] Rate-limit max: {:?} remaining: {:?}",
+            response.rate_limit,
+            response.rate_limit_remaining,
+        );
+
+        match response.response {
+            MailerliteResponse::AuthenticationError { .. } => Err(anyhow!("Failed to authenticate with Mailerlite")),
+            MailerliteResponse::EndpointResponse(GetSubscriberResponse::Success { data }) => Ok(Some(data)),
+            MailerliteResponse::EndpointResponse(GetSubscriberResponse::NotFound) => Ok(None),
+        }
+    }
+
+    async fn write_subscriber(&self, request: WriteSubscriberRequestBuilder) -> Result<Subscriber> {
+        let response = self.client.run(request.build()?).await?;
+
+        log::info!(
+            "[mailerlite:update_subscriber] Rate-limit max: {:?} remaining: {:?}",
+            response.rate_limit,
+            response.rate_limit_remaining,
+        );
+
+        match response.response {
+            MailerliteResponse::AuthenticationError { .. } => Err(anyhow!("Failed to authenticate with Mailerlite")),
+            MailerliteResponse::EndpointResponse(WriteSubscriberResponse::Success { data }) => Ok(data),
+            MailerliteResponse::EndpointResponse(WriteSubscriberResponse::Error { message, .. }) => {
+                Err(anyhow!("Writing subscriber to Mailerlite failed with {}", message))
+            }
+        }
+    }
+}
+
+/// Mailerlite has no tags, so groups are used in their place.
+#[async_trait]
+impl MailingListProviderOps for Mailerlite<chrono_tz::Tz> {
+    async fn upsert_member(&self, subscriber: &NewMailingListSubscriber) -> Result<()> {
+        let mut fields = std::collections::HashMap::new();
+        fields.insert("name".to_string(), Some(subscriber.name.to_string().into()));
+        fields.insert("company".to_string(), Some(subscriber.company.to_string().into()));
+
+        self.write_subscriber(
+            WriteSubscriberRequestBuilder::default()
+                .email(subscriber.email.to_string())
+                .fields(Some(fields))
+                .subscribed_at(Some(subscriber.date_added)),
+        )
+        .await?;
+
+        self.tag_member(&subscriber.email, &subscriber.tags, &[]).await
+    }
+
+    async fn remove_member(&self, email: &str) -> Result<()> {
+        self.write_subscriber(
+            WriteSubscriberRequestBuilder::default()
+                .email(email.to_string())
+                .status(Some(SubscriberStatus::Unsubscribed))
+                .unsubscribed_at(Some(Utc::now())),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn tag_member(&self, email: &str, add: &[String], remove: &[String]) -> Result<()> {
+        if add.is_empty() && remove.is_empty() {
+            return Ok(());
+        }
+
+        let subscriber = self
+            .get_subscriber(email)
+            .await?
+            .ok_or_else(|| anyhow!("Failed to find subscriber {}", email))?;
+
+        for tag in add {
+            if !subscriber.groups.iter().any(|group| &group.name == tag) {
+                let group = self.ensure_group(tag).await?;
+                self.assign_subscriber_to_group(&subscriber.id, &group.id).await?;
+            }
+        }
+
+        for group in subscriber.groups.iter().filter(|group| remove.contains(&group.name)) {
+            self.remove_subscriber_from_group(&subscriber.id, &group.id).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn list_segments(&self) -> Result<Vec<MailingListSegment>> {
+        let mut segments = vec![];
+        let mut page = 1;
+
+        loop {
+            let response = self
+                .client
+                .run(ListSegmentsRequestBuilder::default().limit(250).page(page).build()?)
+                .await?;
+
+            log::info!(
+                "[mailerlite:list_segments] Rate-limit max: {:?} remaining: {:?}",
+                response.rate_limit,
+                response.rate_limit_remaining,
+            );
+
+            match response.response {
+                MailerliteResponse::AuthenticationError { .. } => {
+                    return Err(anyhow!("Failed to authenticate with Mailerlite"))
+                }
+                MailerliteResponse::EndpointResponse(ListPageResponse::Success { data, meta, .. }) => {
+                    segments.extend(data.into_iter().map(|segment| MailingListSegment {
+                        id: segment.id,
+                        name: segment.name,
+                        member_count: segment.total as i64,
+                    }));
+
+                    if meta.current_page >= meta.last_page {
+                        break;
+                    }
+
+                    page += 1;
+                }
+                MailerliteResponse::EndpointResponse(ListPageResponse::Error { message }) => {
+                    return Err(anyhow!("Listing segments from Mailerlite failed with {}", message))
+                }
+            }
+        }
+
+        Ok(segments)
+    }
+}
+
 impl MailerliteSegments {
     pub fn new() -> Result<Self> {
         Ok(MailerliteSegments {
@@ -4,14 +4,15 @@ use async_trait::async_trait;
 use chrono::{offset::Utc, DateTime};
 use chrono_humanize::HumanTime;
 use macros::db;
+use mailchimp_minimal_api::{MemberEventData, WebhookPayload};
 use mailerlite::SubscriberFieldValue;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
 
 use crate::{
-    airtable::AIRTABLE_MAILING_LIST_SIGNUPS_TABLE, companies::Company, core::UpdateAirtableRecord, db::Database,
-    schema::mailing_list_subscribers,
+    airtable::AIRTABLE_MAILING_LIST_SIGNUPS_TABLE, app_config::MailingListConfig, companies::Company,
+    core::UpdateAirtableRecord, db::Database, schema::mailing_list_subscribers,
 };
 
 /// The data type for a MailingListSubscriber.
@@ -101,6 +102,29 @@ impl NewMailingListSubscriber {
     }
 }
 
+/// Start the customer journey configured for an event for the given contact. Nothing is
+/// sent if no journey is configured for the event.
+pub async fn trigger_mailchimp_journey(
+    company: &Company,
+    config: &MailingListConfig,
+    event: &str,
+    email: &str,
+) -> Result<()> {
+    let journey = match config.journey(event) {
+        Some(journey) => journey,
+        None => return Ok(()),
+    };
+
+    let mailchimp = company.authenticate_mailchimp().await?;
+    mailchimp
+        .trigger_journey_step(journey.journey_id, journey.step_id, email)
+        .await?;
+
+    log::info!("started mailchimp journey for `{}` for {}", event, email);
+
+    Ok(())
+}
+
 impl MailingListSubscriber {
     pub async fn send_slack_notification(&self, db: &Database, company: &Company) -> Result<()> {
         let n: NewMailingListSubscriber = self.into();
@@ -290,3 +314,47 @@ impl From<mailerlite::Subscriber> for NewMailingListSubscriber {
         new_sub
     }
 }
+
+impl From<WebhookPayload<MemberEventData>> for NewMailingListSubscriber {
+    fn from(payload: WebhookPayload<MemberEventData>) -> Self {
+        let merges = payload.data.merges;
+        let mut new_sub = NewMailingListSubscriber {
+            email: payload.data.email,
+            first_name: merges.first_name.unwrap_or_default(),
+            last_name: merges.last_name.unwrap_or_default(),
+            name: merges.name.unwrap_or_default(),
+            company: merges.company.unwrap_or_default(),
+            interest: merges.interest.unwrap_or_default(),
+            notes: merges.notes.unwrap_or_default(),
+            phone: merges.phone.unwrap_or_default(),
+            source: "MailChimp".to_string(),
+            date_added: payload.fired_at,
+            date_optin: payload.fired_at,
+            date_last_changed: payload.fired_at,
+            ..Default::default()
+        };
+
+        if new_sub.name.is_empty() {
+            new_sub.name = format!("{} {}", new_sub.first_name, new_sub.last_name)
+                .trim()
+                .to_string();
+        }
+
+        // The update preferences are groups in the audience, a subscriber opted in to a group
+        // when its value is `Yes`.
+        for grouping in merges.groupings.unwrap_or_default() {
+            let opted_in = grouping.groups.as_deref() == Some("Yes");
+            let name = grouping.name.to_lowercase();
+
+            if name.contains("podcast") {
+                new_sub.wants_podcast_updates = opted_in;
+            } else if name.contains("newsletter") {
+                new_sub.wants_newsletter = opted_in;
+            } else if name.contains("product updates") {
+                new_sub.wants_product_updates = opted_in;
+            }
+        }
+
+        new_sub
+    }
+}
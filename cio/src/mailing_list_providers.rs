@@ -0,0 +1,127 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use mailchimp_minimal_api::{MailChimp, MarketingPermissions, MemberTag, MemberUpsert};
+
+use crate::{app_config::AudienceConfig, mailing_list::NewMailingListSubscriber};
+
+/// A segment of a mailing list that members can be targeted by.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MailingListSegment {
+    pub id: String,
+    pub name: String,
+    pub member_count: i64,
+}
+
+/// This trait defines how to implement a provider for a vendor that hosts our mailing list.
+#[async_trait]
+pub trait MailingListProviderOps {
+    /// Ensure the subscriber is a member of the list and has the correct information.
+    async fn upsert_member(&self, subscriber: &NewMailingListSubscriber) -> Result<()>;
+
+    /// Remove the member from the list so that they are sent nothing further.
+    async fn remove_member(&self, email: &str) -> Result<()>;
+
+    /// Add and remove tags on a member. Tags that do not exist yet are created.
+    async fn tag_member(&self, email: &str, add: &[String], remove: &[String]) -> Result<()>;
+
+    async fn list_segments(&self) -> Result<Vec<MailingListSegment>>;
+}
+
+/// A single MailChimp audience.
+pub struct MailChimpAudience {
+    client: MailChimp,
+    list_id: String,
+    config: Option<AudienceConfig>,
+}
+
+impl MailChimpAudience {
+    pub fn new(client: MailChimp, list_id: String, config: Option<AudienceConfig>) -> Self {
+        Self {
+            client,
+            list_id,
+            config,
+        }
+    }
+}
+
+#[async_trait]
+impl MailingListProviderOps for MailChimpAudience {
+    /// Subscribers are only added to audiences that have been configured, as the
+    /// configuration decides whether they must first confirm their subscription.
+    async fn upsert_member(&self, subscriber: &NewMailingListSubscriber) -> Result<()> {
+        let audience = match &self.config {
+            Some(audience) => audience,
+            None => {
+                log::info!(
+                    "MailChimp audience {} is not configured, skipping {}",
+                    self.list_id,
+                    subscriber.email
+                );
+                return Ok(());
+            }
+        };
+
+        let mut merge_fields = std::collections::HashMap::new();
+        merge_fields.insert("FNAME".to_string(), subscriber.first_name.to_string());
+        merge_fields.insert("LNAME".to_string(), subscriber.last_name.to_string());
+        merge_fields.insert("COMPANY".to_string(), subscriber.company.to_string());
+
+        let member = MemberUpsert {
+            email_address: subscriber.email.to_string(),
+            status_if_new: if audience.double_opt_in {
+                "pending".to_string()
+            } else {
+                "subscribed".to_string()
+            },
+            merge_fields,
+            tags: subscriber.tags.clone(),
+            marketing_permissions: audience
+                .marketing_permission_ids
+                .iter()
+                .map(|id| MarketingPermissions {
+                    marketing_permission_id: id.to_string(),
+                    enabled: true,
+                    ..Default::default()
+                })
+                .collect(),
+            timestamp_signup: subscriber.date_added.to_rfc3339(),
+            ..Default::default()
+        };
+
+        self.client.upsert_member(&self.list_id, &member).await?;
+
+        Ok(())
+    }
+
+    async fn remove_member(&self, email: &str) -> Result<()> {
+        self.client.archive_member(&self.list_id, email).await?;
+
+        Ok(())
+    }
+
+    async fn tag_member(&self, email: &str, add: &[String], remove: &[String]) -> Result<()> {
+        let tags = add
+            .iter()
+            .map(|tag| MemberTag::add(tag))
+            .chain(remove.iter().map(|tag| MemberTag::remove(tag)))
+            .collect();
+
+        self.client.update_member_tags(&self.list_id, email, tags).await?;
+
+        Ok(())
+    }
+
+    async fn list_segments(&self) -> Result<Vec<MailingListSegment>> {
+        Ok(self
+            .client
+            .list_segments(&self.list_id)
+            .await?
+            .into_iter()
+            .map(|segment| MailingListSegment {
+                id: segment.id.to_string(),
+                name: segment.name,
+                member_count: segment.member_count,
+            })
+            .collect())
+    }
+}
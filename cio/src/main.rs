@@ -9,6 +9,7 @@ use cio_api::{
     db::Database,
     journal_clubs::{JournalClubMeeting, JournalClubMeetings},
     mailing_list::{MailingListSubscriber, MailingListSubscribers},
+    pagerduty::{get_pagerduty_oncalls, PagerDutyOnCall},
     repos::{GithubRepo, GithubRepos},
     rfd::{RFDs, RFD},
     schema::resources,
@@ -60,6 +61,7 @@ async fn main() -> Result<(), String> {
     api.register(api_get_journal_club_meetings).unwrap();
     api.register(api_get_links).unwrap();
     api.register(api_get_mailing_list_subscribers).unwrap();
+    api.register(api_get_oncalls).unwrap();
     api.register(api_get_rfds).unwrap();
     api.register(api_get_users).unwrap();
 
@@ -286,7 +288,23 @@ async fn api_get_mailing_list_subscribers(
 }
 
 /**
- * Fetch all RFDs.
+ * Fetch who is on call right now for each PagerDuty schedule.
+ */
+#[endpoint {
+    method = GET,
+    path = "/oncalls",
+}]
+async fn api_get_oncalls(
+    rqctx: Arc<RequestContext<Context>>,
+) -> Result<HttpResponseOk<Vec<PagerDutyOnCall>>, HttpError> {
+    let api_context = rqctx.context();
+    let db = &api_context.db;
+
+    Ok(HttpResponseOk(get_pagerduty_oncalls(db, 1).await.unwrap()))
+}
+
+/**
+ * Fetch all public RFDs.
  */
 #[endpoint {
     method = GET,
@@ -296,7 +314,9 @@ async fn api_get_rfds(rqctx: Arc<RequestContext<Context>>) -> Result<HttpRespons
     let api_context = rqctx.context();
     let db = &api_context.db;
 
-    Ok(HttpResponseOk(RFDs::get_from_db(db, 1).await.unwrap().0))
+    let rfds = RFDs::get_from_db(db, 1).await.unwrap().0;
+
+    Ok(HttpResponseOk(rfds.into_iter().filter(|rfd| rfd.is_public()).collect()))
 }
 
 /**
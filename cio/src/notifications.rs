@@ -0,0 +1,508 @@
+/*!
+ * Routing notifications about things that happen, ie. an RFD being published, to the people that
+ * want to hear about them.
+ *
+ * Each company has its own rules. A rule sends one kind of event to a Slack channel, a Slack
+ * user, an email address or a PagerDuty service, and can be narrowed with conditions on the
+ * event's attributes, ie. `{"role": "Hardware*"}` for applicants to hardware roles. Condition
+ * values are globs, the same as the repo globs of GitHub event routes. An event goes to every
+ * enabled rule it matches, and to the fallback channels of the code that sent it when it matches
+ * none, so that a company without rules keeps getting the messages it got before.
+ *
+ * Rules live in the database so that who hears about what can change without a deploy.
+ */
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use async_bb8_diesel::{AsyncRunQueryDsl, OptionalExtension};
+use chrono::{DateTime, Utc};
+use diesel::{AsChangeset, ExpressionMethods, Insertable, QueryDsl, Queryable};
+use log::{info, warn};
+use pagerduty_minimal_api::{trigger_alert, AlertPayload};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    companies::Company,
+    db::Database,
+    delivery::{send_email_in_window, DeliveryWindow},
+    email::OutgoingEmail,
+    github_event_routes::glob_matches,
+    schema::notification_rules,
+};
+
+/// The things that can be notified about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationEvent {
+    RfdPublished,
+    ShipmentDelivered,
+    ApplicantStageChanged,
+    CertExpiring,
+}
+
+impl NotificationEvent {
+    pub const ALL: [NotificationEvent; 4] = [
+        NotificationEvent::RfdPublished,
+        NotificationEvent::ShipmentDelivered,
+        NotificationEvent::ApplicantStageChanged,
+        NotificationEvent::CertExpiring,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationEvent::RfdPublished => "rfd_published",
+            NotificationEvent::ShipmentDelivered => "shipment_delivered",
+            NotificationEvent::ApplicantStageChanged => "applicant_stage_changed",
+            NotificationEvent::CertExpiring => "cert_expiring",
+        }
+    }
+
+    pub fn parse(event: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| e.as_str() == event)
+    }
+}
+
+/// Where a rule sends the events it matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationChannel {
+    /// The target is the name of a channel, ie. `#shipments`.
+    SlackChannel,
+    /// The target is the Slack id of a user, who gets the message as a DM.
+    SlackUser,
+    /// The target is an email address.
+    Email,
+    /// The target is the routing key of a PagerDuty service.
+    PagerDuty,
+}
+
+impl NotificationChannel {
+    pub const ALL: [NotificationChannel; 4] = [
+        NotificationChannel::SlackChannel,
+        NotificationChannel::SlackUser,
+        NotificationChannel::Email,
+        NotificationChannel::PagerDuty,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationChannel::SlackChannel => "slack_channel",
+            NotificationChannel::SlackUser => "slack_user",
+            NotificationChannel::Email => "email",
+            NotificationChannel::PagerDuty => "pagerduty",
+        }
+    }
+
+    pub fn parse(channel: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str() == channel)
+    }
+}
+
+/// Something that happened, to be sent wherever the rules for its event say.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub event: NotificationEvent,
+    /// What the event is about, ie. the domain of a certificate. PagerDuty groups repeats of an
+    /// event with the same key into one incident.
+    pub key: String,
+    pub title: String,
+    /// Slack markdown.
+    pub body: String,
+    pub url: String,
+    /// What the conditions of rules are matched against.
+    pub attributes: BTreeMap<String, String>,
+    /// The Slack channels to post to when no rule matches.
+    pub fallback_channels: Vec<String>,
+}
+
+impl Notification {
+    pub fn new(event: NotificationEvent, key: &str, title: &str, body: &str) -> Self {
+        Self {
+            event,
+            key: key.to_string(),
+            title: title.to_string(),
+            body: body.to_string(),
+            url: Default::default(),
+            attributes: Default::default(),
+            fallback_channels: Default::default(),
+        }
+    }
+
+    pub fn with_url(mut self, url: &str) -> Self {
+        self.url = url.to_string();
+        self
+    }
+
+    pub fn with_attribute(mut self, name: &str, value: &str) -> Self {
+        self.attributes.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Empty channels are ignored, so that unset channels from the company can be passed as is.
+    pub fn with_fallback_channel(mut self, channel: &str) -> Self {
+        if !channel.is_empty() {
+            self.fallback_channels.push(channel.to_string());
+        }
+        self
+    }
+
+    fn text(&self) -> String {
+        if self.url.is_empty() {
+            self.body.to_string()
+        } else {
+            format!("{}\n\n{}", self.body, self.url)
+        }
+    }
+
+    fn slack_message(&self, channel: &str) -> FormattedMessage {
+        FormattedMessage {
+            channel: channel.to_string(),
+            attachments: Default::default(),
+            blocks: vec![MessageBlock {
+                block_type: MessageBlockType::Section,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text: format!("*{}*\n{}", self.title, self.text()),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            }],
+        }
+    }
+
+    fn alert(&self) -> AlertPayload {
+        AlertPayload {
+            summary: self.title.to_string(),
+            source: "cio".to_string(),
+            severity: "warning".to_string(),
+            custom_details: Some(serde_json::json!({
+                "event": self.event.as_str(),
+                "body": self.body,
+                "url": self.url,
+                "attributes": self.attributes,
+            })),
+        }
+    }
+}
+
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct NotificationRule {
+    pub id: i32,
+    /// The event the rule is for, ie. `shipment_delivered`.
+    pub event: String,
+    /// One of `slack_channel`, `slack_user`, `email` or `pagerduty`.
+    pub channel: String,
+    /// The channel name, Slack user id, email address or PagerDuty routing key to send to.
+    pub target: String,
+    /// Attributes of the event and the globs their values have to match.
+    pub conditions: serde_json::Value,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = notification_rules)]
+pub struct NewNotificationRule {
+    pub event: String,
+    pub channel: String,
+    pub target: String,
+    #[serde(default = "no_conditions")]
+    pub conditions: serde_json::Value,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+    /// Set from the company the rule is saved for.
+    #[serde(default)]
+    pub cio_company_id: i32,
+}
+
+fn no_conditions() -> serde_json::Value {
+    serde_json::json!({})
+}
+
+fn enabled() -> bool {
+    true
+}
+
+impl NewNotificationRule {
+    /// Check the rule names an event and a channel we know, and that its conditions are globs.
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        if NotificationEvent::parse(&self.event).is_none() {
+            return Err(format!(
+                "unknown event `{}`, expected one of: {}",
+                self.event,
+                NotificationEvent::ALL.map(|e| e.as_str()).join(", ")
+            ));
+        }
+
+        if NotificationChannel::parse(&self.channel).is_none() {
+            return Err(format!(
+                "unknown channel `{}`, expected one of: {}",
+                self.channel,
+                NotificationChannel::ALL.map(|c| c.as_str()).join(", ")
+            ));
+        }
+
+        if self.target.trim().is_empty() {
+            return Err("a rule needs a target to send to".to_string());
+        }
+
+        match self.conditions.as_object() {
+            Some(conditions) if conditions.values().all(|value| value.is_string()) => Ok(()),
+            _ => Err("conditions have to be an object of attribute names to globs".to_string()),
+        }
+    }
+
+    pub async fn create(&self, db: &Database) -> Result<NotificationRule> {
+        Ok(diesel::insert_into(notification_rules::table)
+            .values(self.clone())
+            .get_result_async::<NotificationRule>(db.pool())
+            .await?)
+    }
+}
+
+impl NotificationRule {
+    pub async fn list(db: &Database, cio_company_id: i32) -> Result<Vec<NotificationRule>> {
+        Ok(notification_rules::dsl::notification_rules
+            .filter(notification_rules::dsl::cio_company_id.eq(cio_company_id))
+            .order_by(notification_rules::dsl::id)
+            .load_async::<NotificationRule>(db.pool())
+            .await?)
+    }
+
+    pub async fn list_enabled_for_event(
+        db: &Database,
+        cio_company_id: i32,
+        event: NotificationEvent,
+    ) -> Result<Vec<NotificationRule>> {
+        Ok(notification_rules::dsl::notification_rules
+            .filter(notification_rules::dsl::cio_company_id.eq(cio_company_id))
+            .filter(notification_rules::dsl::event.eq(event.as_str()))
+            .filter(notification_rules::dsl::enabled.eq(true))
+            .order_by(notification_rules::dsl::id)
+            .load_async::<NotificationRule>(db.pool())
+            .await?)
+    }
+
+    /// Replace the company's rule with `id`. Returns `None` when there is no such rule.
+    pub async fn update(
+        db: &Database,
+        cio_company_id: i32,
+        id: i32,
+        rule: &NewNotificationRule,
+    ) -> Result<Option<NotificationRule>> {
+        Ok(diesel::update(
+            notification_rules::dsl::notification_rules
+                .filter(notification_rules::dsl::cio_company_id.eq(cio_company_id))
+                .filter(notification_rules::dsl::id.eq(id)),
+        )
+        .set((rule.clone(), notification_rules::dsl::updated_at.eq(Utc::now())))
+        .get_result_async::<NotificationRule>(db.pool())
+        .await
+        .optional()?)
+    }
+
+    /// Delete the company's rule with `id`. Returns whether there was such a rule.
+    pub async fn delete(db: &Database, cio_company_id: i32, id: i32) -> Result<bool> {
+        let deleted = diesel::delete(
+            notification_rules::dsl::notification_rules
+                .filter(notification_rules::dsl::cio_company_id.eq(cio_company_id))
+                .filter(notification_rules::dsl::id.eq(id)),
+        )
+        .execute_async(db.pool())
+        .await?;
+
+        Ok(deleted > 0)
+    }
+
+    /// Whether every condition of the rule matches an attribute of the notification. A condition
+    /// on an attribute the notification does not have never matches.
+    pub fn matches(&self, notification: &Notification) -> bool {
+        let conditions = match self.conditions.as_object() {
+            Some(conditions) => conditions,
+            None => return true,
+        };
+
+        conditions.iter().all(
+            |(name, pattern)| match (notification.attributes.get(name), pattern.as_str()) {
+                (Some(value), Some(pattern)) => glob_matches(pattern, value),
+                _ => false,
+            },
+        )
+    }
+
+    async fn deliver(&self, db: &Database, company: &Company, notification: &Notification) -> Result<()> {
+        match NotificationChannel::parse(&self.channel) {
+            Some(NotificationChannel::SlackChannel) | Some(NotificationChannel::SlackUser) => {
+                company
+                    .post_to_slack_channel(db, &notification.slack_message(&self.target))
+                    .await
+            }
+            // Emails wait for the working hours of the person they are to, unlike pages.
+            Some(NotificationChannel::Email) => {
+                let email = OutgoingEmail {
+                    subject: notification.title.to_string(),
+                    text: notification.text(),
+                    to: vec![self.target.to_string()],
+                    from: format!("notifications@{}", company.gsuite_domain),
+                    ..Default::default()
+                };
+                send_email_in_window(db, company, email, DeliveryWindow::WORKING_HOURS).await
+            }
+            Some(NotificationChannel::PagerDuty) => {
+                trigger_alert(
+                    &self.target,
+                    &format!("{}:{}", notification.event.as_str(), notification.key),
+                    &notification.alert(),
+                )
+                .await?;
+                Ok(())
+            }
+            None => bail!("rule {} has an unknown channel `{}`", self.id, self.channel),
+        }
+    }
+}
+
+/// Send the notification to everywhere the company's rules for its event say. Every delivery is
+/// tried, even when one fails.
+pub async fn notify(db: &Database, company: &Company, notification: &Notification) -> Result<()> {
+    let rules = NotificationRule::list_enabled_for_event(db, company.id, notification.event)
+        .await?
+        .into_iter()
+        .filter(|rule| rule.matches(notification))
+        .collect::<Vec<_>>();
+
+    if rules.is_empty() {
+        for channel in &notification.fallback_channels {
+            company
+                .post_to_slack_channel(db, &notification.slack_message(channel))
+                .await?;
+        }
+
+        return Ok(());
+    }
+
+    let mut failed = 0;
+    for rule in &rules {
+        if let Err(e) = rule.deliver(db, company, notification).await {
+            warn!(
+                "failed to send `{}` notification for {} through rule {}: {}",
+                notification.event.as_str(),
+                notification.key,
+                rule.id,
+                e
+            );
+            failed += 1;
+        }
+    }
+
+    if failed > 0 {
+        bail!(
+            "{} of {} deliveries of the `{}` notification for {} failed",
+            failed,
+            rules.len(),
+            notification.event.as_str(),
+            notification.key
+        );
+    }
+
+    info!(
+        "sent `{}` notification for {} through {} rules",
+        notification.event.as_str(),
+        notification.key,
+        rules.len()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::{NewNotificationRule, Notification, NotificationChannel, NotificationEvent, NotificationRule};
+
+    fn rule(conditions: serde_json::Value) -> NotificationRule {
+        NotificationRule {
+            id: 1,
+            event: "applicant_stage_changed".to_string(),
+            channel: "slack_channel".to_string(),
+            target: "#hiring-hardware".to_string(),
+            conditions,
+            enabled: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            cio_company_id: 1,
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        for event in NotificationEvent::ALL {
+            assert_eq!(Some(event), NotificationEvent::parse(event.as_str()));
+        }
+        for channel in NotificationChannel::ALL {
+            assert_eq!(Some(channel), NotificationChannel::parse(channel.as_str()));
+        }
+        assert_eq!(None, NotificationEvent::parse("rfd-published"));
+        assert_eq!(None, NotificationChannel::parse("slack"));
+    }
+
+    #[test]
+    fn test_rule_matches() {
+        let notification = Notification::new(
+            NotificationEvent::ApplicantStageChanged,
+            "42",
+            "Jane Doe moved to Interviewing",
+            "",
+        )
+        .with_attribute("role", "Hardware Engineer")
+        .with_attribute("status", "Interviewing");
+
+        assert!(rule(serde_json::json!({})).matches(&notification));
+        assert!(rule(serde_json::json!({"role": "Hardware*"})).matches(&notification));
+        assert!(rule(serde_json::json!({"role": "Hardware*", "status": "Interviewing"})).matches(&notification));
+        assert!(!rule(serde_json::json!({"role": "Software*"})).matches(&notification));
+        assert!(!rule(serde_json::json!({"team": "*"})).matches(&notification));
+    }
+
+    #[test]
+    fn test_validate_rule() {
+        let mut rule = NewNotificationRule {
+            event: "cert_expiring".to_string(),
+            channel: "pagerduty".to_string(),
+            target: "R0UT1NG".to_string(),
+            conditions: serde_json::json!({"domain": "*.example.com"}),
+            enabled: true,
+            cio_company_id: 1,
+        };
+        assert!(rule.validate().is_ok());
+
+        rule.conditions = serde_json::json!({"domain": 1});
+        assert!(rule.validate().is_err());
+
+        rule.conditions = serde_json::json!({});
+        rule.event = "cert_expired".to_string();
+        assert!(rule.validate().is_err());
+
+        rule.event = "cert_expiring".to_string();
+        rule.channel = "sms".to_string();
+        assert!(rule.validate().is_err());
+
+        rule.channel = "email".to_string();
+        rule.target = " ".to_string();
+        assert!(rule.validate().is_err());
+    }
+
+    #[test]
+    fn test_fallback_channels() {
+        let notification = Notification::new(NotificationEvent::CertExpiring, "example.com", "Renewal failed", "")
+            .with_fallback_channel("")
+            .with_fallback_channel("#debug");
+
+        assert_eq!(vec!["#debug".to_string()], notification.fallback_channels);
+    }
+}
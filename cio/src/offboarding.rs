@@ -0,0 +1,559 @@
+/*!
+ * Taking away the access of people who leave.
+ *
+ * Once someone's last day has passed, they are offboarded from every service we provision them
+ * in: their Google Workspace account is suspended and their Drive files go to their manager,
+ * they are removed from the GitHub organization, their Okta, Slack, Airtable and Zoom accounts
+ * are deactivated, their Tailscale devices are removed and their Ramp cards are locked.
+ *
+ * Every action that is taken, skipped or fails is recorded in `offboarding_actions`, which makes
+ * up the audit report of the offboarding and is posted to Slack. Services whose action failed
+ * are tried again on the next runs, up to `MAX_ATTEMPTS` times, after which someone has to finish
+ * them by hand. The report is only posted again when the outcome of an action changed, so an
+ * action that keeps failing the same way does not post on every run.
+ */
+use std::{collections::HashSet, fmt};
+
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, Insertable, QueryDsl, Queryable};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    app_config::AppConfig,
+    companies::Company,
+    configs::User,
+    db::Database,
+    features::Features,
+    providers::ProviderWriteOps,
+    schema::{offboarding_actions, users},
+};
+
+/// How many times an action is tried before it is given up on. Offboarding runs every six hours,
+/// so this is about a day.
+const MAX_ATTEMPTS: usize = 4;
+
+/// The services someone is offboarded from, in the order they are offboarded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OffboardingService {
+    /// Okta goes first when it is the identity provider, since it signs people in everywhere.
+    Okta,
+    GoogleWorkspace,
+    #[serde(rename = "github")]
+    GitHub,
+    Slack,
+    Airtable,
+    Tailscale,
+    /// The certificates and auth keys they were issued for their devices.
+    DeviceCredentials,
+    Ramp,
+    Zoom,
+    /// The people who report to them need a new manager.
+    DirectReports,
+}
+
+const OFFBOARDING_SERVICES: [OffboardingService; 10] = [
+    OffboardingService::Okta,
+    OffboardingService::GoogleWorkspace,
+    OffboardingService::GitHub,
+    OffboardingService::Slack,
+    OffboardingService::Airtable,
+    OffboardingService::Tailscale,
+    OffboardingService::DeviceCredentials,
+    OffboardingService::Ramp,
+    OffboardingService::Zoom,
+    OffboardingService::DirectReports,
+];
+
+impl fmt::Display for OffboardingService {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            OffboardingService::Okta => "okta",
+            OffboardingService::GoogleWorkspace => "google_workspace",
+            OffboardingService::GitHub => "github",
+            OffboardingService::Slack => "slack",
+            OffboardingService::Airtable => "airtable",
+            OffboardingService::Tailscale => "tailscale",
+            OffboardingService::DeviceCredentials => "device_credentials",
+            OffboardingService::Ramp => "ramp",
+            OffboardingService::Zoom => "zoom",
+            OffboardingService::DirectReports => "direct_reports",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OffboardingActionStatus {
+    Succeeded,
+    /// There was nothing to do, ie. they never had an account.
+    Skipped,
+    Failed,
+}
+
+impl fmt::Display for OffboardingActionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            OffboardingActionStatus::Succeeded => "succeeded",
+            OffboardingActionStatus::Skipped => "skipped",
+            OffboardingActionStatus::Failed => "failed",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// One action taken to offboard someone from a service.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct OffboardingAction {
+    pub id: i32,
+    pub username: String,
+    pub service: String,
+    pub status: String,
+    /// What was done, why nothing was, or what went wrong.
+    pub detail: String,
+    pub cio_company_id: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = offboarding_actions)]
+struct NewOffboardingAction {
+    username: String,
+    service: String,
+    status: String,
+    detail: String,
+    cio_company_id: i32,
+}
+
+impl OffboardingAction {
+    fn report_line(&self) -> String {
+        let emoji = if self.status == OffboardingActionStatus::Succeeded.to_string() {
+            ":white_check_mark:"
+        } else if self.status == OffboardingActionStatus::Skipped.to_string() {
+            ":heavy_minus_sign:"
+        } else {
+            ":x:"
+        };
+
+        format!("{} *{}*: {}", emoji, self.service, self.detail)
+    }
+
+    /// Every action taken to offboard `username`, oldest first.
+    pub async fn get_for_user(db: &Database, company: &Company, username: &str) -> Result<Vec<OffboardingAction>> {
+        Ok(offboarding_actions::dsl::offboarding_actions
+            .filter(offboarding_actions::dsl::cio_company_id.eq(company.id))
+            .filter(offboarding_actions::dsl::username.eq(username.to_string()))
+            .order_by(offboarding_actions::dsl::id.asc())
+            .load_async::<OffboardingAction>(db.pool())
+            .await?)
+    }
+}
+
+/// The services that still need to be offboarded from, given the actions taken so far. Services
+/// whose action failed `MAX_ATTEMPTS` times are not tried again.
+fn remaining_services(actions: &[OffboardingAction]) -> Vec<OffboardingService> {
+    let done = actions
+        .iter()
+        .filter(|action| action.status != OffboardingActionStatus::Failed.to_string())
+        .map(|action| action.service.to_string())
+        .collect::<HashSet<_>>();
+
+    OFFBOARDING_SERVICES
+        .into_iter()
+        .filter(|service| {
+            let service = service.to_string();
+            let failed = actions
+                .iter()
+                .filter(|action| {
+                    action.service == service && action.status == OffboardingActionStatus::Failed.to_string()
+                })
+                .count();
+
+            !done.contains(&service) && failed < MAX_ATTEMPTS
+        })
+        .collect()
+}
+
+/// Whether `action` had a different outcome than the last action for its service in `previous`.
+fn status_changed(previous: &[OffboardingAction], action: &OffboardingAction) -> bool {
+    previous
+        .iter()
+        .rev()
+        .find(|previous| previous.service == action.service)
+        .map(|previous| previous.status != action.status)
+        .unwrap_or(true)
+}
+
+/// Offboard the user from one service. Returns whether anything was done, and what.
+async fn offboard_from(
+    db: &Database,
+    company: &Company,
+    config: &AppConfig,
+    user: &User,
+    service: OffboardingService,
+) -> Result<(OffboardingActionStatus, String)> {
+    let okta = company.authenticate_okta_identity_provider(&config.identity)?;
+
+    let done = match service {
+        OffboardingService::Okta => {
+            let okta = match okta {
+                Some(okta) => okta,
+                None => return skipped("Okta is not the identity provider"),
+            };
+            if user.okta_id.is_empty() {
+                return skipped("they do not have an Okta account");
+            }
+
+            okta.delete_user(db, company, user).await?;
+            "deactivated the account".to_string()
+        }
+        OffboardingService::GoogleWorkspace => {
+            // When Okta is the identity provider, deactivating them there takes care of this.
+            if okta.is_some() {
+                return skipped("Okta is the identity provider");
+            }
+            if user.google_id.is_empty() {
+                return skipped("they do not have a Google Workspace account");
+            }
+
+            let gsuite = company.authenticate_google_admin(db).await?;
+            crate::gsuite::offboard_gsuite_user(&gsuite, db, company, user, &config.google_workspace).await?
+        }
+        OffboardingService::GitHub => {
+            if user.github.is_empty() {
+                return skipped("they do not have a GitHub username in the configs");
+            }
+
+            let github = company.authenticate_github()?;
+            github.delete_user(db, company, user).await?;
+            format!(
+                "removed `{}` from the `{}` organization",
+                user.github, company.github_org
+            )
+        }
+        OffboardingService::Slack => {
+            let slack = match company.authenticate_slack_if_installed(db).await? {
+                Some(slack) => slack,
+                None => return skipped("the company does not use Slack"),
+            };
+
+            let slack_user = slack
+                .list_users()
+                .await?
+                .into_iter()
+                .find(|slack_user| !slack_user.deleted && slack_user.email.eq_ignore_ascii_case(&user.email));
+            match slack_user {
+                Some(slack_user) => {
+                    slack.remove_user(&slack_user.id).await?;
+                    format!("removed `{}` from the workspace", slack_user.id)
+                }
+                None => return skipped("they do not have an active Slack account"),
+            }
+        }
+        OffboardingService::Airtable => {
+            if company.airtable_enterprise_account_id.is_empty() {
+                return skipped("the company does not have an Airtable enterprise account");
+            }
+
+            company.authenticate_airtable("").delete_user(db, company, user).await?;
+            "removed them from the enterprise account".to_string()
+        }
+        OffboardingService::Tailscale => {
+            if company.tailscale_api_key.is_empty() {
+                return skipped("the company does not use Tailscale");
+            }
+
+            let tailscale = company.authenticate_tailscale();
+            let mut removed = vec![];
+            for device in tailscale.list_devices().await? {
+                if device.user.eq_ignore_ascii_case(&user.email) {
+                    tailscale.delete_device(&device.id).await?;
+                    removed.push(device.hostname);
+                }
+            }
+            if removed.is_empty() {
+                return skipped("they do not have any devices");
+            }
+
+            format!("removed their devices: {}", removed.join(", "))
+        }
+        OffboardingService::DeviceCredentials => {
+            let revoked = crate::device_credentials::revoke_device_credentials(db, company, user).await?;
+            if revoked.is_empty() {
+                return skipped("they were not issued any device credentials");
+            }
+
+            format!("revoked {}", revoked)
+        }
+        OffboardingService::Ramp => {
+            if user.ramp_id.is_empty() {
+                return skipped("they do not have a Ramp account");
+            }
+
+            company.authenticate_ramp()?.delete_user(db, company, user).await?;
+            "locked their cards, the account is kept for its history".to_string()
+        }
+        OffboardingService::Zoom => {
+            if user.zoom_id.is_empty() {
+                return skipped("they do not have a Zoom account");
+            }
+
+            company
+                .authenticate_zoom(db)
+                .await?
+                .delete_user(db, company, user)
+                .await?;
+            "deleted the account and transferred its meetings and recordings to their manager".to_string()
+        }
+        OffboardingService::DirectReports => {
+            let reports = users::dsl::users
+                .filter(users::dsl::cio_company_id.eq(company.id))
+                .filter(users::dsl::manager.eq(user.username.to_string()))
+                .filter(users::dsl::termination_date.is_null())
+                .select(users::dsl::username)
+                .load_async::<String>(db.pool())
+                .await?;
+            if reports.is_empty() {
+                return skipped("nobody reports to them");
+            }
+
+            // Managers come from Gusto, so someone has to pick the new manager there.
+            bail!(
+                "{} still report to them and need a new manager in Gusto",
+                reports.join(", ")
+            );
+        }
+    };
+
+    Ok((OffboardingActionStatus::Succeeded, done))
+}
+
+fn skipped(reason: &str) -> Result<(OffboardingActionStatus, String)> {
+    Ok((OffboardingActionStatus::Skipped, reason.to_string()))
+}
+
+fn report_message(channel: &str, user: &User, actions: &[OffboardingAction]) -> FormattedMessage {
+    let lines = actions.iter().map(|action| action.report_line()).collect::<Vec<_>>();
+    let last_day = user
+        .termination_date
+        .map(|date| format!(", last day {}", date.format("%a %b %-d")))
+        .unwrap_or_default();
+
+    FormattedMessage {
+        channel: channel.to_string(),
+        attachments: Default::default(),
+        blocks: vec![MessageBlock {
+            block_type: MessageBlockType::Section,
+            text: Some(MessageBlockText {
+                text_type: MessageType::Markdown,
+                text: format!(
+                    ":wave: Offboarding report for *{}* (`{}`){}\n{}",
+                    user.full_name(),
+                    user.username,
+                    last_day,
+                    lines.join("\n")
+                ),
+            }),
+            elements: Default::default(),
+            accessory: Default::default(),
+            block_id: Default::default(),
+            fields: Default::default(),
+        }],
+    }
+}
+
+/// Offboard the user from every service they have not been offboarded from yet, and post the
+/// report of what was done when any outcome changed. Returns the actions taken in this run.
+pub async fn offboard_user(
+    db: &Database,
+    company: &Company,
+    config: &AppConfig,
+    user: &User,
+) -> Result<Vec<OffboardingAction>> {
+    let previous = OffboardingAction::get_for_user(db, company, &user.username).await?;
+
+    let mut actions = vec![];
+    for service in remaining_services(&previous) {
+        let (status, detail) = match offboard_from(db, company, config, user, service).await {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                warn!("offboarding {} from {} failed: {}", user.username, service, e);
+                (OffboardingActionStatus::Failed, e.to_string())
+            }
+        };
+        info!("offboarding {} from {}: {}: {}", user.username, service, status, detail);
+
+        let action = diesel::insert_into(offboarding_actions::table)
+            .values(NewOffboardingAction {
+                username: user.username.to_string(),
+                service: service.to_string(),
+                status: status.to_string(),
+                detail,
+                cio_company_id: company.id,
+            })
+            .get_result_async::<OffboardingAction>(db.pool())
+            .await?;
+        actions.push(action);
+    }
+
+    let changed = actions.iter().any(|action| status_changed(&previous, action));
+    if changed && !config.offboarding.report_channel.is_empty() {
+        company
+            .post_to_slack_channel(db, &report_message(&config.offboarding.report_channel, user, &actions))
+            .await?;
+    }
+
+    Ok(actions)
+}
+
+/// Offboard everyone whose last day has passed and who still has access somewhere.
+pub async fn run_offboarding(db: &Database, company: &Company, config: &AppConfig) -> Result<()> {
+    let today = Utc::now().naive_utc().date();
+    let terminated = users::dsl::users
+        .filter(users::dsl::cio_company_id.eq(company.id))
+        .filter(users::dsl::termination_date.lt(today))
+        .load_async::<User>(db.pool())
+        .await?;
+
+    for user in terminated {
+        if user.is_system_account() {
+            continue;
+        }
+
+        if !Features::is_enabled("REMOTE_USER_DELETES") {
+            info!(
+                "User {} meets criteria for offboarding, but removals are currently disabled",
+                user.id
+            );
+            continue;
+        }
+
+        if let Err(e) = offboard_user(db, company, config, &user).await {
+            warn!("offboarding {} failed: {}", user.username, e);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, Utc};
+
+    use super::{
+        remaining_services, report_message, status_changed, OffboardingAction, OffboardingService, MAX_ATTEMPTS,
+    };
+    use crate::configs::tests::mock_user;
+
+    fn action(service: OffboardingService, status: &str, detail: &str) -> OffboardingAction {
+        OffboardingAction {
+            id: 1,
+            username: "random_username".to_string(),
+            service: service.to_string(),
+            status: status.to_string(),
+            detail: detail.to_string(),
+            cio_company_id: 1,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_remaining_services() {
+        assert_eq!(10, remaining_services(&[]).len());
+
+        let actions = vec![
+            action(OffboardingService::Okta, "skipped", "Okta is not the identity provider"),
+            action(OffboardingService::GitHub, "failed", "status code: 500"),
+            action(
+                OffboardingService::GoogleWorkspace,
+                "succeeded",
+                "suspended the account",
+            ),
+            action(OffboardingService::Zoom, "failed", "status code: 500"),
+            action(OffboardingService::Zoom, "succeeded", "deleted the account"),
+        ];
+
+        assert_eq!(
+            vec![
+                OffboardingService::GitHub,
+                OffboardingService::Slack,
+                OffboardingService::Airtable,
+                OffboardingService::Tailscale,
+                OffboardingService::DeviceCredentials,
+                OffboardingService::Ramp,
+                OffboardingService::DirectReports,
+            ],
+            remaining_services(&actions)
+        );
+    }
+
+    #[test]
+    fn test_remaining_services_gives_up() {
+        let failed = vec![
+            action(
+                OffboardingService::DirectReports,
+                "failed",
+                "jane still reports to them"
+            );
+            MAX_ATTEMPTS
+        ];
+
+        assert!(remaining_services(&failed[..MAX_ATTEMPTS - 1]).contains(&OffboardingService::DirectReports));
+        assert!(!remaining_services(&failed).contains(&OffboardingService::DirectReports));
+    }
+
+    #[test]
+    fn test_status_changed() {
+        let previous = vec![
+            action(OffboardingService::GitHub, "failed", "status code: 500"),
+            action(OffboardingService::Zoom, "succeeded", "deleted the account"),
+        ];
+
+        assert!(!status_changed(
+            &previous,
+            &action(OffboardingService::GitHub, "failed", "status code: 502")
+        ));
+        assert!(status_changed(
+            &previous,
+            &action(OffboardingService::GitHub, "succeeded", "removed them")
+        ));
+        assert!(status_changed(
+            &previous,
+            &action(OffboardingService::Slack, "failed", "status code: 500")
+        ));
+    }
+
+    #[test]
+    fn test_report_message() {
+        let mut user = mock_user();
+        user.termination_date = Some(NaiveDate::from_ymd(2023, 3, 17));
+
+        let message = report_message(
+            "#offboarding",
+            &user,
+            &[
+                action(OffboardingService::Okta, "skipped", "Okta is not the identity provider"),
+                action(OffboardingService::GitHub, "failed", "status code: 500"),
+            ],
+        );
+
+        assert_eq!("#offboarding", message.channel);
+        assert_eq!(
+            format!(
+                ":wave: Offboarding report for *{}* (`random_username`), last day Fri Mar 17\n\
+                 :heavy_minus_sign: *okta*: Okta is not the identity provider\n\
+                 :x: *github*: status code: 500",
+                user.full_name()
+            ),
+            message.blocks[0].text.as_ref().unwrap().text
+        );
+    }
+}
@@ -0,0 +1,421 @@
+/*!
+ * Approving the terms of an offer in Slack before it is sent.
+ *
+ * When an applicant is giving an offer and the company has approvers for offers, the terms of
+ * the offer (the salary, equity and start date on the applicant) are posted to Slack with
+ * buttons to approve or reject them. The offer letter is only created in DocuSign once one of
+ * the approvers approves those exact terms. Changing the terms asks for a new approval.
+ *
+ * Each approval also records the envelope that was sent for it and when it was signed, so that
+ * an offer can be followed from the request through to the signature.
+ */
+use std::fmt;
+
+use anyhow::{bail, Result};
+use async_bb8_diesel::{AsyncRunQueryDsl, OptionalExtension};
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::{ExpressionMethods, Insertable, QueryDsl, Queryable};
+use log::info;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{
+    ActionBlock, BlockOption, FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType,
+};
+
+use crate::{
+    app_config::OfferApprovalConfig, applicants::Applicant, companies::Company, db::Database, schema::offer_approvals,
+};
+
+/// The `action_id` of the button that approves an offer. Its value is the id of the approval.
+pub const OFFER_APPROVE_ACTION: &str = "offer_approve";
+/// The `action_id` of the button that rejects an offer. Its value is the id of the approval.
+pub const OFFER_REJECT_ACTION: &str = "offer_reject";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OfferApprovalStatus {
+    Pending,
+    Approved,
+    Rejected,
+    /// The terms changed before anyone decided on them.
+    Superseded,
+}
+
+impl fmt::Display for OfferApprovalStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            OfferApprovalStatus::Pending => "pending",
+            OfferApprovalStatus::Approved => "approved",
+            OfferApprovalStatus::Rejected => "rejected",
+            OfferApprovalStatus::Superseded => "superseded",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// The terms of an offer that are approved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OfferTerms {
+    pub salary: String,
+    pub equity: String,
+    pub start_date: Option<NaiveDate>,
+}
+
+impl From<&Applicant> for OfferTerms {
+    fn from(applicant: &Applicant) -> Self {
+        OfferTerms {
+            salary: applicant.offer_salary.trim().to_string(),
+            equity: applicant.offer_equity.trim().to_string(),
+            start_date: applicant.start_date,
+        }
+    }
+}
+
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct OfferApproval {
+    pub id: i32,
+    pub applicant_id: i32,
+    pub salary: String,
+    pub equity: String,
+    pub start_date: Option<NaiveDate>,
+    pub status: String,
+    /// The Slack user id of the approver who decided.
+    pub decided_by: String,
+    pub decided_at: Option<DateTime<Utc>>,
+    /// The DocuSign envelope of the offer that was sent once approved.
+    pub envelope_id: String,
+    pub sent_at: Option<DateTime<Utc>>,
+    /// When the applicant signed the offer.
+    pub completed_at: Option<DateTime<Utc>>,
+    pub cio_company_id: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = offer_approvals)]
+struct NewOfferApproval {
+    applicant_id: i32,
+    salary: String,
+    equity: String,
+    start_date: Option<NaiveDate>,
+    status: String,
+    cio_company_id: i32,
+}
+
+/// Where the offer of an applicant stands with its approval.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OfferApprovalState {
+    /// The company does not approve offers.
+    NotRequired,
+    /// The applicant's current terms were approved.
+    Approved(OfferApproval),
+    /// The applicant's current terms are waiting on a decision, or were rejected.
+    Waiting,
+}
+
+impl OfferApproval {
+    pub fn terms(&self) -> OfferTerms {
+        OfferTerms {
+            salary: self.salary.to_string(),
+            equity: self.equity.to_string(),
+            start_date: self.start_date,
+        }
+    }
+
+    pub async fn get(db: &Database, id: i32) -> Result<Option<OfferApproval>> {
+        Ok(offer_approvals::dsl::offer_approvals
+            .find(id)
+            .first_async::<OfferApproval>(db.pool())
+            .await
+            .optional()?)
+    }
+
+    /// The most recent approval requested for the applicant.
+    pub async fn latest_for_applicant(db: &Database, applicant_id: i32) -> Result<Option<OfferApproval>> {
+        Ok(offer_approvals::dsl::offer_approvals
+            .filter(offer_approvals::dsl::applicant_id.eq(applicant_id))
+            .order_by(offer_approvals::dsl::id.desc())
+            .first_async::<OfferApproval>(db.pool())
+            .await
+            .optional()?)
+    }
+
+    /// Record the envelope that was sent for the approved offer.
+    pub async fn mark_sent(&self, db: &Database, envelope_id: &str) -> Result<()> {
+        diesel::update(offer_approvals::dsl::offer_approvals.find(self.id))
+            .set((
+                offer_approvals::dsl::envelope_id.eq(envelope_id.to_string()),
+                offer_approvals::dsl::sent_at.eq(Some(Utc::now())),
+                offer_approvals::dsl::updated_at.eq(Utc::now()),
+            ))
+            .execute_async(db.pool())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Record when the offer sent as `envelope_id` was signed, if it was sent for an approval.
+    pub async fn mark_completed(db: &Database, envelope_id: &str, completed_at: DateTime<Utc>) -> Result<()> {
+        diesel::update(
+            offer_approvals::dsl::offer_approvals
+                .filter(offer_approvals::dsl::envelope_id.eq(envelope_id.to_string()))
+                .filter(offer_approvals::dsl::completed_at.is_null()),
+        )
+        .set((
+            offer_approvals::dsl::completed_at.eq(Some(completed_at)),
+            offer_approvals::dsl::updated_at.eq(Utc::now()),
+        ))
+        .execute_async(db.pool())
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Whether the applicant's offer can be sent. When the applicant's current terms have not been
+/// put up for approval yet, this requests an approval for them.
+pub async fn ensure_offer_approved(
+    db: &Database,
+    config: &OfferApprovalConfig,
+    applicant: &Applicant,
+) -> Result<OfferApprovalState> {
+    if config.approvers.is_empty() {
+        return Ok(OfferApprovalState::NotRequired);
+    }
+
+    let terms = OfferTerms::from(applicant);
+    if terms.salary.is_empty() {
+        info!(
+            "applicant {} is giving an offer without a salary, waiting for one before requesting approval",
+            applicant.id
+        );
+        return Ok(OfferApprovalState::Waiting);
+    }
+
+    if let Some(latest) = OfferApproval::latest_for_applicant(db, applicant.id).await? {
+        if latest.terms() == terms {
+            return Ok(if latest.status == OfferApprovalStatus::Approved.to_string() {
+                OfferApprovalState::Approved(latest)
+            } else {
+                // Pending, or rejected until the terms change.
+                OfferApprovalState::Waiting
+            });
+        }
+
+        if latest.status == OfferApprovalStatus::Pending.to_string() {
+            diesel::update(offer_approvals::dsl::offer_approvals.find(latest.id))
+                .set((
+                    offer_approvals::dsl::status.eq(OfferApprovalStatus::Superseded.to_string()),
+                    offer_approvals::dsl::updated_at.eq(Utc::now()),
+                ))
+                .execute_async(db.pool())
+                .await?;
+        }
+    }
+
+    request_offer_approval(db, config, applicant, terms).await?;
+
+    Ok(OfferApprovalState::Waiting)
+}
+
+async fn request_offer_approval(
+    db: &Database,
+    config: &OfferApprovalConfig,
+    applicant: &Applicant,
+    terms: OfferTerms,
+) -> Result<OfferApproval> {
+    if config.slack_channel.is_empty() {
+        bail!("offers need approval, but there is no slack channel to request it in");
+    }
+
+    let approval = diesel::insert_into(offer_approvals::table)
+        .values(NewOfferApproval {
+            applicant_id: applicant.id,
+            salary: terms.salary,
+            equity: terms.equity,
+            start_date: terms.start_date,
+            status: OfferApprovalStatus::Pending.to_string(),
+            cio_company_id: applicant.cio_company_id,
+        })
+        .get_result_async::<OfferApproval>(db.pool())
+        .await?;
+
+    let company = applicant.company(db).await?;
+    company
+        .post_to_slack_channel(
+            db,
+            &approval_request_message(&config.slack_channel, applicant, &approval),
+        )
+        .await?;
+
+    info!(
+        "requested approval {} of the offer for applicant {}",
+        approval.id, applicant.id
+    );
+
+    Ok(approval)
+}
+
+fn button(text: &str, action_id: &str, approval: &OfferApproval) -> BlockOption {
+    BlockOption::ActionBlock(ActionBlock {
+        text_type: MessageType::Button,
+        text: MessageBlockText {
+            text_type: MessageType::PlainText,
+            text: text.to_string(),
+        },
+        value: approval.id.to_string(),
+        action_id: action_id.to_string(),
+    })
+}
+
+fn approval_request_message(channel: &str, applicant: &Applicant, approval: &OfferApproval) -> FormattedMessage {
+    let start_date = approval
+        .start_date
+        .map(|date| date.format("%B %-d, %Y").to_string())
+        .unwrap_or_else(|| "not set".to_string());
+    let equity = if approval.equity.is_empty() {
+        "none".to_string()
+    } else {
+        approval.equity.to_string()
+    };
+
+    FormattedMessage {
+        channel: channel.to_string(),
+        attachments: Default::default(),
+        blocks: vec![
+            MessageBlock {
+                block_type: MessageBlockType::Section,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text: format!(
+                        ":memo: Offer for *{}* <mailto:{}|{}> as {}\nSalary: *{}* | Equity: *{}* | Start date: *{}*",
+                        applicant.name,
+                        applicant.email,
+                        applicant.email,
+                        applicant.role,
+                        approval.salary,
+                        equity,
+                        start_date
+                    ),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+            MessageBlock {
+                block_type: MessageBlockType::Actions,
+                text: Default::default(),
+                elements: vec![
+                    button("Approve and send", OFFER_APPROVE_ACTION, approval),
+                    button("Reject", OFFER_REJECT_ACTION, approval),
+                ],
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+        ],
+    }
+}
+
+/// Record the decision of the approver with the Slack user id `slack_user_id` on the approval
+/// with `id`, which has to belong to `company`, the company of the Slack workspace the decision
+/// came from.
+pub async fn decide_offer_approval(
+    db: &Database,
+    company: &Company,
+    config: &OfferApprovalConfig,
+    id: i32,
+    slack_user_id: &str,
+    approve: bool,
+) -> Result<OfferApproval> {
+    if !config.approvers.iter().any(|approver| approver == slack_user_id) {
+        bail!("only the approvers of offers can approve or reject them");
+    }
+
+    let approval = match OfferApproval::get(db, id).await? {
+        Some(approval) if approval.cio_company_id == company.id => approval,
+        _ => bail!("there is no offer approval {} for {}", id, company.name),
+    };
+    if approval.status != OfferApprovalStatus::Pending.to_string() {
+        bail!("the offer was {} already", approval.status);
+    }
+
+    let status = if approve {
+        OfferApprovalStatus::Approved
+    } else {
+        OfferApprovalStatus::Rejected
+    };
+
+    // Approvers can click at the same time, so only the first decision on a pending offer counts.
+    let approval = match diesel::update(offer_approvals::dsl::offer_approvals.find(approval.id))
+        .filter(offer_approvals::dsl::cio_company_id.eq(company.id))
+        .filter(offer_approvals::dsl::status.eq(OfferApprovalStatus::Pending.to_string()))
+        .set((
+            offer_approvals::dsl::status.eq(status.to_string()),
+            offer_approvals::dsl::decided_by.eq(slack_user_id.to_string()),
+            offer_approvals::dsl::decided_at.eq(Some(Utc::now())),
+            offer_approvals::dsl::updated_at.eq(Utc::now()),
+        ))
+        .get_result_async::<OfferApproval>(db.pool())
+        .await
+        .optional()?
+    {
+        Some(approval) => approval,
+        None => bail!("the offer was decided on already"),
+    };
+
+    info!(
+        "offer approval {} for applicant {} was {} by {}",
+        approval.id, approval.applicant_id, approval.status, slack_user_id
+    );
+
+    Ok(approval)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, Utc};
+
+    use super::{approval_request_message, OfferApproval, OfferTerms};
+    use crate::applicants::tests::mock_applicant;
+
+    #[test]
+    fn test_offer_terms() {
+        let mut applicant = mock_applicant();
+        applicant.offer_salary = " $200,000 ".to_string();
+        applicant.offer_equity = "10,000 options".to_string();
+        applicant.start_date = NaiveDate::from_ymd_opt(2023, 5, 1);
+
+        let approval = OfferApproval {
+            id: 1,
+            applicant_id: applicant.id,
+            salary: "$200,000".to_string(),
+            equity: "10,000 options".to_string(),
+            start_date: NaiveDate::from_ymd_opt(2023, 5, 1),
+            status: "approved".to_string(),
+            decided_by: "U123".to_string(),
+            decided_at: Some(Utc::now()),
+            envelope_id: String::new(),
+            sent_at: None,
+            completed_at: None,
+            cio_company_id: applicant.cio_company_id,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        assert_eq!(approval.terms(), OfferTerms::from(&applicant));
+
+        // Moving the start date needs a new approval.
+        applicant.start_date = NaiveDate::from_ymd_opt(2023, 6, 1);
+        assert_ne!(approval.terms(), OfferTerms::from(&applicant));
+
+        let message = serde_json::to_value(approval_request_message("#offers", &applicant, &approval)).unwrap();
+        assert_eq!("#offers", message["channel"]);
+        assert_eq!("offer_approve", message["blocks"][1]["elements"][0]["action_id"]);
+        assert_eq!("1", message["blocks"][1]["elements"][0]["value"]);
+        assert_eq!("offer_reject", message["blocks"][1]["elements"][1]["action_id"]);
+    }
+}
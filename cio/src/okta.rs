@@ -0,0 +1,129 @@
+/*!
+ * Assigning Okta applications to users, for companies that use Okta as their identity provider.
+ *
+ * Users and groups are kept in sync by the Okta provider in `providers`. This assigns each
+ * application in the config to the members of its groups, and removes everyone else that was
+ * assigned to it directly. Assignments that Okta made through a group are left alone.
+ */
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use log::{info, warn};
+use okta_minimal_api::AppUser;
+
+use crate::{
+    app_config::OktaConfig,
+    companies::Company,
+    configs::{ExternalServices, User, Users},
+    db::Database,
+};
+
+/// The Okta ids of the users that should be assigned to an application given to `groups`.
+fn desired_app_users(groups: &[String], users: &[User]) -> BTreeSet<String> {
+    users
+        .iter()
+        .filter(|user| {
+            !user.okta_id.is_empty()
+                && !user.denied_services.contains(&ExternalServices::Okta)
+                && user.groups.iter().any(|group| groups.contains(group))
+        })
+        .map(|user| user.okta_id.to_string())
+        .collect()
+}
+
+/// The Okta ids of the users that need to be assigned to an application, and of the users
+/// whose direct assignment needs to be removed.
+fn app_assignment_changes(existing: &[AppUser], desired: &BTreeSet<String>) -> (Vec<String>, Vec<String>) {
+    let to_assign = desired
+        .iter()
+        .filter(|id| !existing.iter().any(|app_user| app_user.id == **id))
+        .map(|id| id.to_string())
+        .collect();
+
+    let to_unassign = existing
+        .iter()
+        .filter(|app_user| app_user.scope == "USER" && !desired.contains(&app_user.id))
+        .map(|app_user| app_user.id.to_string())
+        .collect();
+
+    (to_assign, to_unassign)
+}
+
+pub async fn sync_okta_app_assignments(db: &Database, company: &Company, config: &OktaConfig) -> Result<()> {
+    if config.app_assignments.is_empty() {
+        return Ok(());
+    }
+
+    let okta = match company.authenticate_okta_apps() {
+        Some(okta) => okta,
+        None => {
+            warn!(
+                "company `{}` has okta app assignments, but no okta credentials",
+                company.name
+            );
+            return Ok(());
+        }
+    };
+
+    let users: Vec<User> = Users::get_from_db(db, company.id).await?.into();
+    let apps = okta.list_apps().await?;
+
+    for (label, groups) in &config.app_assignments {
+        let app = match apps.iter().find(|app| &app.label == label) {
+            Some(app) => app,
+            None => {
+                warn!("could not find okta app `{}`", label);
+                continue;
+            }
+        };
+
+        let existing = okta.list_app_users(&app.id).await?;
+        let desired = desired_app_users(groups, &users);
+
+        let (to_assign, to_unassign) = app_assignment_changes(&existing, &desired);
+
+        for id in to_assign {
+            okta.assign_user_to_app(&app.id, &id).await?;
+            info!("assigned okta user `{}` to app `{}`", id, label);
+        }
+
+        for id in to_unassign {
+            okta.unassign_user_from_app(&app.id, &id).await?;
+            info!("unassigned okta user `{}` from app `{}`", id, label);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use okta_minimal_api::AppUser;
+
+    use super::app_assignment_changes;
+
+    fn app_user(id: &str, scope: &str) -> AppUser {
+        AppUser {
+            id: id.to_string(),
+            scope: scope.to_string(),
+            status: "PROVISIONED".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_app_assignment_changes() {
+        let existing = vec![
+            app_user("00u1", "USER"),
+            app_user("00u2", "USER"),
+            app_user("00u3", "GROUP"),
+        ];
+        let desired: BTreeSet<String> = ["00u1", "00u4"].iter().map(|id| id.to_string()).collect();
+
+        let (to_assign, to_unassign) = app_assignment_changes(&existing, &desired);
+
+        assert_eq!(vec!["00u4"], to_assign);
+        assert_eq!(vec!["00u2"], to_unassign);
+    }
+}
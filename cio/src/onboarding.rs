@@ -0,0 +1,604 @@
+/*!
+ * Running the tasks that get a new hire ready for their first day.
+ *
+ * The tasks are configured as a graph in [`OnboardingConfig`]: each task is due some days before
+ * or after the start date, and only runs once the tasks it depends on are done. Every user with
+ * an upcoming start date gets a row per task in `onboarding_tasks`, which is where the progress
+ * of their onboarding is tracked.
+ *
+ * A task that fails is tried again later, backing off each time, until it runs out of attempts
+ * and is marked as failed for a person to look into. Whenever the tasks of a new hire make
+ * progress, their manager gets the checklist of where things stand in Slack.
+ */
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use diesel::{upsert::excluded, ExpressionMethods, Insertable, QueryDsl, Queryable};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    app_config::{AppConfig, OnboardingConfig, OnboardingTaskConfig, OnboardingTaskKind},
+    companies::Company,
+    configs::User,
+    db::Database,
+    device_credentials::issue_device_credentials,
+    providers::ProviderWriteOps,
+    schema::{onboarding_tasks, users},
+    shipments::NewOutboundShipment,
+    swag_preferences::request_swag_preferences,
+    webhook_outbox::{publish, OutboxEvent},
+};
+
+/// How long after their start date a new hire's tasks are still run. This gives tasks that are
+/// due on or after the start date, and their retries, time to finish.
+const ONBOARDING_GRACE_DAYS: i64 = 14;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingTaskStatus {
+    Pending,
+    Completed,
+    /// The task ran out of attempts.
+    Failed,
+}
+
+impl fmt::Display for OnboardingTaskStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            OnboardingTaskStatus::Pending => "pending",
+            OnboardingTaskStatus::Completed => "completed",
+            OnboardingTaskStatus::Failed => "failed",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl fmt::Display for OnboardingTaskKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            OnboardingTaskKind::GoogleAccount => "google_account",
+            OnboardingTaskKind::GitHubInvite => "github_invite",
+            OnboardingTaskKind::ShipHardware => "ship_hardware",
+            OnboardingTaskKind::SlackWelcome => "slack_welcome",
+            OnboardingTaskKind::DeviceCredentials => "device_credentials",
+            OnboardingTaskKind::SwagPreferences => "swag_preferences",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// One task of a new hire's onboarding.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct OnboardingTask {
+    pub id: i32,
+    pub username: String,
+    /// The name of the task in the config.
+    pub name: String,
+    pub kind: String,
+    pub due_date: NaiveDate,
+    pub status: String,
+    pub attempts: i32,
+    pub last_error: String,
+    /// When a task that failed is tried again.
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub cio_company_id: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = onboarding_tasks)]
+struct NewOnboardingTask {
+    username: String,
+    name: String,
+    kind: String,
+    due_date: NaiveDate,
+    cio_company_id: i32,
+}
+
+impl OnboardingTask {
+    fn is(&self, status: OnboardingTaskStatus) -> bool {
+        self.status == status.to_string()
+    }
+
+    /// Whether the task should run now. Tasks run once they are due, the tasks they depend on
+    /// are done, and any wait before trying them again is over.
+    fn is_ready(&self, dependencies_done: bool, now: DateTime<Utc>) -> bool {
+        self.is(OnboardingTaskStatus::Pending)
+            && dependencies_done
+            && self.due_date <= now.naive_utc().date()
+            && self.next_attempt_at.map_or(true, |at| at <= now)
+    }
+
+    /// The line for the task in the checklist.
+    fn checklist_line(&self) -> String {
+        if self.is(OnboardingTaskStatus::Completed) {
+            format!(":white_check_mark: {}", self.name)
+        } else if self.is(OnboardingTaskStatus::Failed) {
+            format!(
+                ":x: {} failed after {} attempts: {}",
+                self.name, self.attempts, self.last_error
+            )
+        } else if self.attempts > 0 {
+            format!(
+                ":repeat: {} is retrying after {} failed attempts: {}",
+                self.name, self.attempts, self.last_error
+            )
+        } else {
+            format!(
+                ":hourglass_flowing_sand: {} is due {}",
+                self.name,
+                self.due_date.format("%a %b %-d")
+            )
+        }
+    }
+
+    async fn complete(&self, db: &Database) -> Result<()> {
+        diesel::update(onboarding_tasks::dsl::onboarding_tasks.find(self.id))
+            .set((
+                onboarding_tasks::dsl::status.eq(OnboardingTaskStatus::Completed.to_string()),
+                onboarding_tasks::dsl::attempts.eq(self.attempts + 1),
+                onboarding_tasks::dsl::last_error.eq(String::new()),
+                onboarding_tasks::dsl::next_attempt_at.eq(None::<DateTime<Utc>>),
+                onboarding_tasks::dsl::completed_at.eq(Some(Utc::now())),
+                onboarding_tasks::dsl::updated_at.eq(Utc::now()),
+            ))
+            .execute_async(db.pool())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Record a failed attempt, and when to try again, or that the task is out of attempts.
+    async fn fail(&self, db: &Database, max_attempts: i32, error: &str) -> Result<()> {
+        let attempts = self.attempts + 1;
+        let (status, next_attempt_at) = if attempts >= max_attempts {
+            (OnboardingTaskStatus::Failed, None)
+        } else {
+            (OnboardingTaskStatus::Pending, Some(Utc::now() + retry_delay(attempts)))
+        };
+
+        diesel::update(onboarding_tasks::dsl::onboarding_tasks.find(self.id))
+            .set((
+                onboarding_tasks::dsl::status.eq(status.to_string()),
+                onboarding_tasks::dsl::attempts.eq(attempts),
+                onboarding_tasks::dsl::last_error.eq(error.to_string()),
+                onboarding_tasks::dsl::next_attempt_at.eq(next_attempt_at),
+                onboarding_tasks::dsl::updated_at.eq(Utc::now()),
+            ))
+            .execute_async(db.pool())
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// How long to wait before trying a task again after its `attempts`th failed attempt. Starts at
+/// half an hour and doubles each time, up to a day.
+fn retry_delay(attempts: i32) -> Duration {
+    let doublings = (attempts.max(1) - 1).min(6) as u32;
+    std::cmp::min(Duration::minutes(30 * 2i64.pow(doublings)), Duration::days(1))
+}
+
+/// Order the tasks so that every task comes after the tasks it depends on. Errors when the
+/// tasks do not make a graph that can be run: when names repeat, a task depends on a task that
+/// does not exist, or tasks depend on each other in a cycle.
+pub fn order_tasks(tasks: &[OnboardingTaskConfig]) -> Result<Vec<&OnboardingTaskConfig>> {
+    let mut waiting_on: HashMap<&str, usize> = HashMap::new();
+    for task in tasks {
+        if waiting_on.insert(&task.name, task.depends_on.len()).is_some() {
+            bail!("there is more than one onboarding task named `{}`", task.name);
+        }
+    }
+
+    for task in tasks {
+        for dependency in &task.depends_on {
+            if !waiting_on.contains_key(dependency.as_str()) {
+                bail!(
+                    "onboarding task `{}` depends on `{}`, which is not a task",
+                    task.name,
+                    dependency
+                );
+            }
+        }
+    }
+
+    // Take the tasks whose dependencies are all ordered, keeping the order of the config among
+    // the tasks that are ready at the same time.
+    let mut ordered: Vec<&OnboardingTaskConfig> = Vec::with_capacity(tasks.len());
+    while ordered.len() < tasks.len() {
+        let ready = tasks
+            .iter()
+            .filter(|task| waiting_on.get(task.name.as_str()) == Some(&0))
+            .collect::<Vec<_>>();
+        if ready.is_empty() {
+            let cycle = tasks
+                .iter()
+                .filter(|task| waiting_on.contains_key(task.name.as_str()))
+                .map(|task| task.name.to_string())
+                .collect::<Vec<_>>();
+            bail!("onboarding tasks depend on each other in a cycle: {}", cycle.join(", "));
+        }
+
+        for task in ready {
+            waiting_on.remove(task.name.as_str());
+            for dependent in tasks.iter().filter(|t| t.depends_on.contains(&task.name)) {
+                if let Some(count) = waiting_on.get_mut(dependent.name.as_str()) {
+                    *count -= dependent.depends_on.iter().filter(|d| **d == task.name).count();
+                }
+            }
+            ordered.push(task);
+        }
+    }
+
+    Ok(ordered)
+}
+
+/// Make sure the user has a row for each task, due relative to their current start date.
+async fn plan_tasks(
+    db: &Database,
+    company: &Company,
+    user: &User,
+    tasks: &[&OnboardingTaskConfig],
+) -> Result<Vec<OnboardingTask>> {
+    let planned = tasks
+        .iter()
+        .map(|task| NewOnboardingTask {
+            username: user.username.to_string(),
+            name: task.name.to_string(),
+            kind: task.kind.to_string(),
+            due_date: user.start_date + Duration::days(task.day),
+            cio_company_id: company.id,
+        })
+        .collect::<Vec<_>>();
+
+    // Start dates move, so the due dates of tasks that were already planned follow them.
+    diesel::insert_into(onboarding_tasks::table)
+        .values(planned)
+        .on_conflict((
+            onboarding_tasks::cio_company_id,
+            onboarding_tasks::username,
+            onboarding_tasks::name,
+        ))
+        .do_update()
+        .set((
+            onboarding_tasks::kind.eq(excluded(onboarding_tasks::kind)),
+            onboarding_tasks::due_date.eq(excluded(onboarding_tasks::due_date)),
+        ))
+        .execute_async(db.pool())
+        .await?;
+
+    let existing = onboarding_tasks::dsl::onboarding_tasks
+        .filter(onboarding_tasks::dsl::cio_company_id.eq(company.id))
+        .filter(onboarding_tasks::dsl::username.eq(user.username.to_string()))
+        .load_async::<OnboardingTask>(db.pool())
+        .await?;
+
+    // Keep the order of the graph, and leave out rows of tasks that are no longer configured.
+    Ok(tasks
+        .iter()
+        .filter_map(|task| existing.iter().find(|row| row.name == task.name).cloned())
+        .collect())
+}
+
+async fn run_task(
+    db: &Database,
+    company: &Company,
+    config: &AppConfig,
+    task: &OnboardingTaskConfig,
+    user: &User,
+) -> Result<()> {
+    match task.kind {
+        OnboardingTaskKind::GoogleAccount => {
+            let gsuite = company.authenticate_google_admin(db).await?;
+            gsuite.ensure_user(db, company, user, config).await?;
+        }
+        OnboardingTaskKind::GitHubInvite => {
+            if user.github.is_empty() {
+                bail!("{} does not have a GitHub username in the configs", user.username);
+            }
+
+            let github = company.authenticate_github()?;
+            github.ensure_user(db, company, user, config).await?;
+        }
+        OnboardingTaskKind::ShipHardware => {
+            if user.home_address_formatted.is_empty() {
+                bail!("we do not know the home address of {}", user.username);
+            }
+
+            let mut new_shipment = NewOutboundShipment::from(user.clone());
+            new_shipment.contents = task.contents.to_string();
+
+            let mut shipment = new_shipment.upsert_in_db(db).await?;
+            shipment.create_or_get_shippo_shipment(db).await?;
+            shipment.update(db).await?;
+        }
+        OnboardingTaskKind::SlackWelcome => {
+            if task.channel.is_empty() {
+                bail!("onboarding task `{}` does not have a channel to post in", task.name);
+            }
+
+            company
+                .post_to_slack_channel(db, &markdown_message(&task.channel, welcome_text(user)))
+                .await?;
+        }
+        OnboardingTaskKind::DeviceCredentials => {
+            issue_device_credentials(db, company, &config.device_credentials, user).await?;
+        }
+        OnboardingTaskKind::SwagPreferences => {
+            // Whatever address we have is filled in on the form for them to check.
+            let mut new_shipment = NewOutboundShipment::from(user.clone());
+            new_shipment.contents = task.contents.to_string();
+
+            request_swag_preferences(db, company, &config.swag.preferences, new_shipment).await?;
+        }
+    }
+
+    Ok(())
+}
+
+fn welcome_text(user: &User) -> String {
+    let mut text = format!(
+        ":wave: Please welcome *{}* to the team! They start on {}.",
+        user.full_name(),
+        user.start_date.format("%A %B %-d")
+    );
+    if !user.github.is_empty() {
+        text.push_str(&format!(" You can find them on GitHub as `{}`.", user.github));
+    }
+
+    text
+}
+
+fn markdown_message(channel: &str, text: String) -> FormattedMessage {
+    FormattedMessage {
+        channel: channel.to_string(),
+        attachments: Default::default(),
+        blocks: vec![MessageBlock {
+            block_type: MessageBlockType::Section,
+            text: Some(MessageBlockText {
+                text_type: MessageType::Markdown,
+                text,
+            }),
+            elements: Default::default(),
+            accessory: Default::default(),
+            block_id: Default::default(),
+            fields: Default::default(),
+        }],
+    }
+}
+
+fn checklist_text(user: &User, tasks: &[OnboardingTask]) -> String {
+    let lines = tasks.iter().map(|task| task.checklist_line()).collect::<Vec<_>>();
+
+    format!(
+        ":clipboard: Onboarding checklist for *{}* (`{}`), starting {}\n{}",
+        user.full_name(),
+        user.username,
+        user.start_date.format("%a %b %-d"),
+        lines.join("\n")
+    )
+}
+
+/// Send the checklist to the new hire's manager, directly when we can find them in Slack, and
+/// otherwise in the checklist channel.
+async fn send_checklist(
+    db: &Database,
+    company: &Company,
+    config: &OnboardingConfig,
+    user: &User,
+    tasks: &[OnboardingTask],
+) -> Result<()> {
+    let manager = User::get_from_db(db, company.id, user.manager.to_string()).await;
+
+    let slack = company.authenticate_slack(db).await?;
+    let slack_users = slack.list_users().await?;
+    let channel = match manager.and_then(|manager| {
+        slack_users
+            .into_iter()
+            .find(|slack_user| slack_user.email.eq_ignore_ascii_case(&manager.email))
+    }) {
+        Some(slack_user) => slack_user.id,
+        None if !config.checklist_channel.is_empty() => config.checklist_channel.to_string(),
+        None => {
+            warn!(
+                "not sending the onboarding checklist of {}: their manager is not in Slack",
+                user.username
+            );
+            return Ok(());
+        }
+    };
+
+    slack
+        .post_message(&markdown_message(&channel, checklist_text(user, tasks)))
+        .await?;
+
+    Ok(())
+}
+
+/// Run the onboarding tasks that are ready for everyone who starts soon, or started recently.
+pub async fn run_onboarding(db: &Database, company: &Company, config: &AppConfig) -> Result<()> {
+    if config.onboarding.tasks.is_empty() {
+        return Ok(());
+    }
+
+    let tasks = order_tasks(&config.onboarding.tasks)?;
+
+    // Nothing is due further ahead of the start date than the earliest task.
+    let lead_days = tasks.iter().map(|task| -task.day).max().unwrap_or_default().max(0);
+    let today = Utc::now().naive_utc().date();
+
+    let new_hires = users::dsl::users
+        .filter(users::dsl::cio_company_id.eq(company.id))
+        .filter(users::dsl::start_date.ge(today - Duration::days(ONBOARDING_GRACE_DAYS)))
+        .filter(users::dsl::start_date.le(today + Duration::days(lead_days)))
+        .load_async::<User>(db.pool())
+        .await?;
+
+    for user in new_hires {
+        if user.is_system_account() {
+            continue;
+        }
+
+        // Consumers only get a new hire once, however many runs they are in the window for.
+        let data = serde_json::json!({
+            "username": user.username,
+            "name": user.full_name(),
+            "email": user.email,
+            "start_date": user.start_date,
+            "department": user.department,
+            "manager": user.manager,
+        });
+        if let Err(e) = publish(db, company, OutboxEvent::NewHire, &user.username, data).await {
+            warn!("publishing the new hire {} failed: {}", user.username, e);
+        }
+
+        let rows = plan_tasks(db, company, &user, &tasks).await?;
+        let mut completed = rows
+            .iter()
+            .filter(|row| row.is(OnboardingTaskStatus::Completed))
+            .map(|row| row.name.to_string())
+            .collect::<HashSet<_>>();
+
+        // The tasks are in the order of the graph, so a task that a later one depends on has
+        // already had its turn in this run.
+        let mut progressed = false;
+        for (task, row) in tasks.iter().zip(rows) {
+            let dependencies_done = task.depends_on.iter().all(|dependency| completed.contains(dependency));
+            if !row.is_ready(dependencies_done, Utc::now()) {
+                continue;
+            }
+
+            progressed = true;
+            match run_task(db, company, config, task, &user).await {
+                Ok(()) => {
+                    row.complete(db).await?;
+                    completed.insert(task.name.to_string());
+                    info!("completed onboarding task `{}` for {}", task.name, user.username);
+                }
+                Err(e) => {
+                    row.fail(db, task.max_attempts, &e.to_string()).await?;
+                    warn!(
+                        "onboarding task `{}` for {} failed on attempt {}: {}",
+                        task.name,
+                        user.username,
+                        row.attempts + 1,
+                        e
+                    );
+                }
+            }
+        }
+
+        if progressed {
+            let checklist = plan_tasks(db, company, &user, &tasks).await?;
+            if let Err(e) = send_checklist(db, company, &config.onboarding, &user, &checklist).await {
+                warn!("sending the onboarding checklist of {} failed: {}", user.username, e);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, NaiveDate, TimeZone, Utc};
+
+    use super::{order_tasks, retry_delay, OnboardingTask};
+    use crate::app_config::{OnboardingTaskConfig, OnboardingTaskKind};
+
+    fn task(name: &str, kind: OnboardingTaskKind, day: i64, depends_on: &[&str]) -> OnboardingTaskConfig {
+        OnboardingTaskConfig {
+            name: name.to_string(),
+            kind,
+            day,
+            depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+            contents: String::new(),
+            channel: String::new(),
+            max_attempts: 5,
+        }
+    }
+
+    #[test]
+    fn test_order_tasks() {
+        let tasks = vec![
+            task("welcome", OnboardingTaskKind::SlackWelcome, 0, &["google"]),
+            task("github", OnboardingTaskKind::GitHubInvite, -3, &["google"]),
+            task("google", OnboardingTaskKind::GoogleAccount, -5, &[]),
+            task("hardware", OnboardingTaskKind::ShipHardware, -10, &[]),
+        ];
+
+        let names = order_tasks(&tasks)
+            .unwrap()
+            .into_iter()
+            .map(|task| task.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["google", "hardware", "welcome", "github"], names);
+
+        let mut unknown = tasks.clone();
+        unknown.push(task("laptop", OnboardingTaskKind::ShipHardware, -10, &["order"]));
+        assert!(order_tasks(&unknown).is_err());
+
+        let mut duplicate = tasks.clone();
+        duplicate.push(task("google", OnboardingTaskKind::GoogleAccount, -1, &[]));
+        assert!(order_tasks(&duplicate).is_err());
+
+        let mut cycle = tasks;
+        cycle[2].depends_on = vec!["github".to_string()];
+        let err = order_tasks(&cycle).unwrap_err();
+        assert_eq!(
+            "onboarding tasks depend on each other in a cycle: welcome, github, google",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        assert_eq!(Duration::minutes(30), retry_delay(1));
+        assert_eq!(Duration::hours(1), retry_delay(2));
+        assert_eq!(Duration::hours(4), retry_delay(4));
+        assert_eq!(Duration::days(1), retry_delay(10));
+    }
+
+    #[test]
+    fn test_is_ready() {
+        let now = Utc.ymd(2023, 3, 20).and_hms(12, 0, 0);
+        let mut row = OnboardingTask {
+            id: 1,
+            username: "jane".to_string(),
+            name: "github".to_string(),
+            kind: OnboardingTaskKind::GitHubInvite.to_string(),
+            due_date: NaiveDate::from_ymd(2023, 3, 20),
+            status: "pending".to_string(),
+            attempts: 0,
+            last_error: String::new(),
+            next_attempt_at: None,
+            completed_at: None,
+            cio_company_id: 1,
+            created_at: now,
+            updated_at: now,
+        };
+
+        assert!(row.is_ready(true, now));
+        assert!(!row.is_ready(false, now));
+        assert!(!row.is_ready(true, now - Duration::days(1)));
+
+        row.next_attempt_at = Some(now + Duration::minutes(30));
+        assert!(!row.is_ready(true, now));
+        assert!(row.is_ready(true, now + Duration::hours(1)));
+
+        row.status = "failed".to_string();
+        assert!(!row.is_ready(true, now + Duration::hours(1)));
+    }
+}
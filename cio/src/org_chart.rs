@@ -0,0 +1,489 @@
+/*!
+ * The org chart.
+ *
+ * The reporting structure is built from the manager of each user, and people are grouped into
+ * teams by their department. The chart is written to the configs repo as JSON, as a Graphviz DOT
+ * file and as an SVG, so the org chart on the intranet is rebuilt from the same users as
+ * everything else instead of being kept up to date by hand.
+ *
+ * Anyone whose manager is not in the chart is at the top of it. A loop in the managers, which
+ * should never happen but only takes a typo in the configs, is broken at the first person in it
+ * by username.
+ */
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use anyhow::Result;
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    companies::Company, configs::User, db::Database, directory::listed_users,
+    utils::create_or_update_file_in_github_repo,
+};
+
+/// Where the org chart is written to in the configs repo.
+const ORG_CHART_PATH: &str = "/org-chart";
+
+/// The size of a person in the SVG, and the space between people and between levels.
+const NODE_WIDTH: i64 = 200;
+const NODE_HEIGHT: i64 = 56;
+const NODE_GAP: i64 = 24;
+const LEVEL_GAP: i64 = 48;
+const MARGIN: i64 = 24;
+
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct OrgChartPerson {
+    pub username: String,
+    pub name: String,
+    pub title: String,
+    pub team: String,
+    /// The username of the person's manager, empty for the people at the top of the chart.
+    pub manager: String,
+    /// The usernames of the people that report to the person, by name.
+    pub reports: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct OrgChartTeam {
+    pub name: String,
+    /// The usernames of the people on the team, by name.
+    pub members: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct OrgChart {
+    /// The usernames of the people at the top of the chart, by name.
+    pub roots: Vec<String>,
+    /// Everyone in the chart, by name.
+    pub people: Vec<OrgChartPerson>,
+    pub teams: Vec<OrgChartTeam>,
+}
+
+/// Break any loops in the managers, returning the usernames whose manager was dropped.
+fn break_manager_loops(managers: &mut BTreeMap<String, String>) -> Vec<String> {
+    let mut broken = vec![];
+
+    let usernames = managers.keys().cloned().collect::<Vec<_>>();
+    for username in usernames {
+        let mut seen = HashSet::new();
+        let mut current = username.to_string();
+        while let Some(manager) = managers.get(&current).filter(|manager| !manager.is_empty()).cloned() {
+            if manager == username {
+                managers.insert(username.to_string(), String::new());
+                broken.push(username.to_string());
+                break;
+            }
+            if !seen.insert(manager.to_string()) {
+                // A loop further up, it is broken when we get to the people in it.
+                break;
+            }
+            current = manager;
+        }
+    }
+
+    broken
+}
+
+impl OrgChart {
+    /// Build the chart from the users in it.
+    pub fn build(users: &[User]) -> Self {
+        let mut users = users.iter().collect::<Vec<_>>();
+        users.sort_by(|a, b| {
+            a.full_name()
+                .to_lowercase()
+                .cmp(&b.full_name().to_lowercase())
+                .then(a.username.cmp(&b.username))
+        });
+
+        let usernames = users.iter().map(|user| user.username.as_str()).collect::<HashSet<_>>();
+
+        // People whose manager is not in the chart, ie. because they left, are at the top.
+        let mut managers = users
+            .iter()
+            .map(|user| {
+                let manager = if usernames.contains(user.manager.as_str()) && user.manager != user.username {
+                    user.manager.to_string()
+                } else {
+                    String::new()
+                };
+                (user.username.to_string(), manager)
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        for username in break_manager_loops(&mut managers) {
+            warn!(
+                "the managers of `{}` loop back to them, they are at the top of the org chart",
+                username
+            );
+        }
+
+        let mut reports: HashMap<&str, Vec<String>> = HashMap::new();
+        let mut teams: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        let mut roots = vec![];
+        for user in &users {
+            let manager = &managers[&user.username];
+            if manager.is_empty() {
+                roots.push(user.username.to_string());
+            } else {
+                reports
+                    .entry(manager.as_str())
+                    .or_default()
+                    .push(user.username.to_string());
+            }
+
+            if !user.department.is_empty() {
+                teams
+                    .entry(user.department.to_string())
+                    .or_default()
+                    .push(user.username.to_string());
+            }
+        }
+
+        OrgChart {
+            roots,
+            people: users
+                .iter()
+                .map(|user| OrgChartPerson {
+                    username: user.username.to_string(),
+                    name: user.full_name(),
+                    title: user.title.to_string(),
+                    team: user.department.to_string(),
+                    manager: managers[&user.username].to_string(),
+                    reports: reports.remove(user.username.as_str()).unwrap_or_default(),
+                })
+                .collect(),
+            teams: teams
+                .into_iter()
+                .map(|(name, members)| OrgChartTeam { name, members })
+                .collect(),
+        }
+    }
+
+    fn person(&self, username: &str) -> Option<&OrgChartPerson> {
+        self.people.iter().find(|person| person.username == username)
+    }
+
+    /// The chart as a Graphviz DOT graph, with a cluster for each team.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph org_chart {\n");
+        dot.push_str("    rankdir=TB;\n");
+        dot.push_str("    node [shape=box, style=rounded, fontname=\"Helvetica\"];\n");
+
+        let node = |person: &OrgChartPerson| {
+            let label = if person.title.is_empty() {
+                dot_escape(&person.name)
+            } else {
+                format!("{}\\n{}", dot_escape(&person.name), dot_escape(&person.title))
+            };
+            format!("\"{}\" [label=\"{}\"];", dot_escape(&person.username), label)
+        };
+
+        for team in &self.teams {
+            dot.push_str(&format!("\n    subgraph \"cluster_{}\" {{\n", dot_escape(&team.name)));
+            dot.push_str(&format!("        label=\"{}\";\n", dot_escape(&team.name)));
+            for person in team.members.iter().filter_map(|member| self.person(member)) {
+                dot.push_str(&format!("        {}\n", node(person)));
+            }
+            dot.push_str("    }\n");
+        }
+
+        let people_without_team = self
+            .people
+            .iter()
+            .filter(|person| person.team.is_empty())
+            .collect::<Vec<_>>();
+        if !people_without_team.is_empty() {
+            dot.push('\n');
+            for person in people_without_team {
+                dot.push_str(&format!("    {}\n", node(person)));
+            }
+        }
+
+        dot.push('\n');
+        for person in &self.people {
+            for report in &person.reports {
+                dot.push_str(&format!(
+                    "    \"{}\" -> \"{}\";\n",
+                    dot_escape(&person.username),
+                    dot_escape(report)
+                ));
+            }
+        }
+
+        dot.push_str("}\n");
+
+        dot
+    }
+
+    /// Lay the chart out as a tree, returning the position of the top left corner of each
+    /// person, and the width and height of the whole chart.
+    fn layout(&self) -> (HashMap<&str, (i64, i64)>, i64, i64) {
+        fn place<'a>(
+            chart: &'a OrgChart,
+            username: &'a str,
+            depth: i64,
+            next_slot: &mut i64,
+            positions: &mut HashMap<&'a str, (i64, i64)>,
+        ) -> i64 {
+            let y = MARGIN + depth * (NODE_HEIGHT + LEVEL_GAP);
+            let reports = chart
+                .person(username)
+                .map(|person| person.reports.as_slice())
+                .unwrap_or_default();
+
+            // Managers are centered over the people that report to them.
+            let x = if reports.is_empty() {
+                let x = MARGIN + *next_slot * (NODE_WIDTH + NODE_GAP);
+                *next_slot += 1;
+                x
+            } else {
+                let xs = reports
+                    .iter()
+                    .map(|report| place(chart, report, depth + 1, next_slot, positions))
+                    .collect::<Vec<_>>();
+                (xs[0] + xs[xs.len() - 1]) / 2
+            };
+
+            positions.insert(username, (x, y));
+
+            x
+        }
+
+        let mut positions = HashMap::new();
+        let mut next_slot = 0;
+        for root in &self.roots {
+            place(self, root, 0, &mut next_slot, &mut positions);
+        }
+
+        let depth = positions.values().map(|(_, y)| *y).max().unwrap_or(0);
+        let width = 2 * MARGIN + (next_slot * (NODE_WIDTH + NODE_GAP) - NODE_GAP).max(0);
+        let height = if positions.is_empty() {
+            2 * MARGIN
+        } else {
+            depth + NODE_HEIGHT + MARGIN
+        };
+
+        (positions, width, height)
+    }
+
+    /// The chart rendered as an SVG tree.
+    pub fn to_svg(&self) -> String {
+        let (positions, width, height) = self.layout();
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" \
+             font-family=\"Helvetica, Arial, sans-serif\">\n",
+            width, height, width, height
+        );
+        svg.push_str(&format!(
+            "  <rect width=\"{}\" height=\"{}\" fill=\"#ffffff\"/>\n",
+            width, height
+        ));
+
+        // The lines go first so the boxes are drawn over them.
+        for person in &self.people {
+            let (x, y) = match positions.get(person.username.as_str()) {
+                Some(position) => *position,
+                None => continue,
+            };
+            for (report_x, report_y) in person
+                .reports
+                .iter()
+                .filter_map(|report| positions.get(report.as_str()))
+            {
+                let middle = y + NODE_HEIGHT + LEVEL_GAP / 2;
+                svg.push_str(&format!(
+                    "  <path d=\"M{} {} V{} H{} V{}\" fill=\"none\" stroke=\"#9ca3af\"/>\n",
+                    x + NODE_WIDTH / 2,
+                    y + NODE_HEIGHT,
+                    middle,
+                    report_x + NODE_WIDTH / 2,
+                    report_y
+                ));
+            }
+        }
+
+        for person in &self.people {
+            let (x, y) = match positions.get(person.username.as_str()) {
+                Some(position) => *position,
+                None => continue,
+            };
+            svg.push_str(&format!(
+                "  <g id=\"{}\">\n    <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"6\" fill=\"#f9fafb\" \
+                 stroke=\"#4b5563\"/>\n",
+                xml_escape(&person.username),
+                x,
+                y,
+                NODE_WIDTH,
+                NODE_HEIGHT
+            ));
+            svg.push_str(&format!(
+                "    <text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"14\" font-weight=\"bold\">{}</text>\n",
+                x + NODE_WIDTH / 2,
+                y + 24,
+                xml_escape(&person.name)
+            ));
+            if !person.title.is_empty() {
+                svg.push_str(&format!(
+                    "    <text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"12\" fill=\"#4b5563\">{}</text>\n",
+                    x + NODE_WIDTH / 2,
+                    y + 42,
+                    xml_escape(&person.title)
+                ));
+            }
+            svg.push_str("  </g>\n");
+        }
+
+        svg.push_str("</svg>\n");
+
+        svg
+    }
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Rebuild the org chart and write it to the configs repo. The files are only committed when
+/// the chart changed.
+pub async fn refresh_org_chart(db: &Database, company: &Company) -> Result<()> {
+    let users = listed_users(db, company).await?;
+    if users.is_empty() {
+        info!("company `{}` has no users, skipping the org chart", company.name);
+        return Ok(());
+    }
+
+    let chart = OrgChart::build(&users);
+    let github = company.authenticate_github()?;
+
+    for (file, content) in [
+        ("org-chart.json", serde_json::to_string_pretty(&chart)?),
+        ("org-chart.dot", chart.to_dot()),
+        ("org-chart.svg", chart.to_svg()),
+    ] {
+        create_or_update_file_in_github_repo(
+            &github,
+            &company.github_org,
+            "configs",
+            "", // leaving the branch blank gives us the default branch
+            &format!("{}/{}", ORG_CHART_PATH, file),
+            content.into_bytes(),
+        )
+        .await?;
+    }
+
+    info!(
+        "updated the org chart of company `{}` with {} people",
+        company.name,
+        chart.people.len()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OrgChart;
+    use crate::configs::{tests::mock_user, User};
+
+    fn user(username: &str, name: &str, manager: &str, department: &str) -> User {
+        let mut user = mock_user();
+        user.username = username.to_string();
+        user.first_name = name.to_string();
+        user.manager = manager.to_string();
+        user.department = department.to_string();
+        user
+    }
+
+    #[test]
+    fn test_build_org_chart() {
+        let chart = OrgChart::build(&[
+            user("sam", "Sam", "jess", "Hardware"),
+            user("jess", "Jess", "", "Executive"),
+            user("alex", "Alex", "jess", "Hardware"),
+            user("robin", "Robin", "gone", ""),
+        ]);
+
+        assert_eq!(vec!["jess", "robin"], chart.roots);
+        assert_eq!(
+            vec!["alex", "jess", "robin", "sam"],
+            chart.people.iter().map(|p| p.username.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!(vec!["alex", "sam"], chart.people[1].reports);
+        // A manager that is not in the chart is dropped.
+        assert_eq!("", chart.people[2].manager);
+        assert_eq!(
+            vec![("Executive", vec!["jess"]), ("Hardware", vec!["alex", "sam"])],
+            chart
+                .teams
+                .iter()
+                .map(|t| (
+                    t.name.as_str(),
+                    t.members.iter().map(|m| m.as_str()).collect::<Vec<_>>()
+                ))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_build_org_chart_manager_loop() {
+        let chart = OrgChart::build(&[
+            user("a", "A", "b", ""),
+            user("b", "B", "c", ""),
+            user("c", "C", "a", ""),
+            user("d", "D", "c", ""),
+        ]);
+
+        assert_eq!(vec!["a"], chart.roots);
+        assert_eq!(
+            vec!["b", "d"],
+            chart.people.iter().find(|p| p.username == "c").unwrap().reports
+        );
+        assert_eq!(4, chart.layout().0.len());
+    }
+
+    #[test]
+    fn test_org_chart_to_dot() {
+        let mut jess = user("jess", "Jess", "", "Executive");
+        jess.title = "CEO \"Boss\"".to_string();
+        let chart = OrgChart::build(&[jess, user("sam", "Sam", "jess", "")]);
+
+        let dot = chart.to_dot();
+        assert!(dot.starts_with("digraph org_chart {\n"));
+        assert!(dot.contains("subgraph \"cluster_Executive\" {\n        label=\"Executive\";\n"));
+        assert!(dot.contains("\"jess\" [label=\"Jess \\nCEO \\\"Boss\\\"\"];"));
+        assert!(dot.contains("    \"sam\" [label=\"Sam \"];\n"));
+        assert!(dot.contains("    \"jess\" -> \"sam\";\n"));
+    }
+
+    #[test]
+    fn test_org_chart_to_svg() {
+        let chart = OrgChart::build(&[
+            user("jess", "Jess & Co", "", ""),
+            user("sam", "Sam", "jess", ""),
+            user("alex", "Alex", "jess", ""),
+        ]);
+
+        let (positions, width, height) = chart.layout();
+        // The manager is centered over their two reports, one level up.
+        assert_eq!(positions["alex"].1, positions["sam"].1);
+        assert!(positions["jess"].1 < positions["sam"].1);
+        assert_eq!(positions["jess"].0, (positions["alex"].0 + positions["sam"].0) / 2);
+        assert!(width > positions["sam"].0);
+        assert!(height > positions["sam"].1);
+
+        let svg = chart.to_svg();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.contains(">Jess &amp; Co </text>"));
+        assert_eq!(2, svg.matches("<path ").count());
+        assert!(svg.ends_with("</svg>\n"));
+    }
+}
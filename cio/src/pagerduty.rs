@@ -0,0 +1,491 @@
+/*!
+ * Provisioning PagerDuty teams and escalation policies from the configs, and keeping track of
+ * who is on call.
+ *
+ * Each team in the PagerDuty config is made up of the members of a group, and escalates through
+ * its schedules in order. Who is on each schedule is managed in PagerDuty, this only records who
+ * is on call right now so that other tools do not need to ask PagerDuty, and posts to Slack when
+ * that changes.
+ */
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Utc};
+use diesel::{AsChangeset, ExpressionMethods, Insertable, QueryDsl, Queryable};
+use log::{info, warn};
+use pagerduty_minimal_api::{EscalationPolicy, EscalationRule, NewUser, PagerDutyClient, Reference, Schedule};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    app_config::{PagerDutyConfig, PagerDutyTeamConfig},
+    companies::Company,
+    configs::{User, Users},
+    db::Database,
+    schema::pagerduty_oncalls,
+};
+
+/// The person that is on call for one level of a schedule.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct PagerDutyOnCall {
+    pub id: i32,
+    pub schedule_id: String,
+    pub schedule_name: String,
+    pub escalation_level: i32,
+    pub pagerduty_user_id: String,
+    pub name: String,
+    pub email: String,
+    /// The username of the person on call, empty when they are not a user in the configs.
+    pub username: String,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = pagerduty_oncalls)]
+pub struct NewPagerDutyOnCall {
+    pub schedule_id: String,
+    pub schedule_name: String,
+    pub escalation_level: i32,
+    pub pagerduty_user_id: String,
+    pub name: String,
+    pub email: String,
+    pub username: String,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+    pub cio_company_id: i32,
+}
+
+/// Get who is on call right now for every schedule we know about.
+pub async fn get_pagerduty_oncalls(db: &Database, cio_company_id: i32) -> Result<Vec<PagerDutyOnCall>> {
+    Ok(pagerduty_oncalls::dsl::pagerduty_oncalls
+        .filter(pagerduty_oncalls::dsl::cio_company_id.eq(cio_company_id))
+        .order_by((
+            pagerduty_oncalls::dsl::schedule_name,
+            pagerduty_oncalls::dsl::escalation_level,
+        ))
+        .load_async::<PagerDutyOnCall>(db.pool())
+        .await?)
+}
+
+/// The emails of the members of `group`, and the role they should have on the team. Emails are
+/// lowercased since that is how PagerDuty stores them.
+fn desired_team_members(group: &str, users: &[User]) -> BTreeMap<String, String> {
+    users
+        .iter()
+        .filter(|user| !user.email.is_empty() && user.groups.iter().any(|g| g == group))
+        .map(|user| {
+            let role = if user.is_group_admin { "manager" } else { "responder" };
+            (user.email.to_lowercase(), role.to_string())
+        })
+        .collect()
+}
+
+/// The members that need to be added to a team or have their role changed, and the members
+/// that need to be removed, both keyed by PagerDuty user id.
+fn team_member_changes(
+    existing: &BTreeMap<String, String>,
+    desired: &BTreeMap<String, String>,
+) -> (Vec<(String, String)>, Vec<String>) {
+    let to_add = desired
+        .iter()
+        .filter(|(id, role)| existing.get(*id) != Some(*role))
+        .map(|(id, role)| (id.to_string(), role.to_string()))
+        .collect();
+
+    let to_remove = existing
+        .keys()
+        .filter(|id| !desired.contains_key(*id))
+        .map(|id| id.to_string())
+        .collect();
+
+    (to_add, to_remove)
+}
+
+/// One escalation rule per schedule, in order.
+fn escalation_rules(schedule_ids: &[String], delay: i64) -> Vec<EscalationRule> {
+    schedule_ids
+        .iter()
+        .map(|id| EscalationRule {
+            escalation_delay_in_minutes: delay,
+            targets: vec![Reference::new(id, "schedule_reference")],
+        })
+        .collect()
+}
+
+/// Whether an existing policy escalates the same way as the rules. PagerDuty fills in fields
+/// like the summary of each target, so only the ids and delays are compared.
+fn escalation_rules_match(existing: &[EscalationRule], desired: &[EscalationRule]) -> bool {
+    let key = |rules: &[EscalationRule]| -> Vec<(i64, Vec<String>)> {
+        rules
+            .iter()
+            .map(|rule| {
+                (
+                    rule.escalation_delay_in_minutes,
+                    rule.targets.iter().map(|target| target.id.to_string()).collect(),
+                )
+            })
+            .collect()
+    };
+
+    key(existing) == key(desired)
+}
+
+async fn provision_team(
+    pagerduty: &PagerDutyClient,
+    name: &str,
+    config: &PagerDutyTeamConfig,
+    users: &[User],
+    pagerduty_users: &mut Vec<pagerduty_minimal_api::User>,
+    schedules: &[Schedule],
+) -> Result<()> {
+    let team = match pagerduty.list_teams().await?.into_iter().find(|team| team.name == name) {
+        Some(team) => team,
+        None => {
+            let team = pagerduty.create_team(name, "").await?;
+            info!("created pagerduty team `{}`", name);
+            team
+        }
+    };
+
+    // Make sure everyone on the team has a PagerDuty user.
+    let mut desired = BTreeMap::new();
+    for (email, role) in desired_team_members(&config.group, users) {
+        let existing = pagerduty_users.iter().find(|user| user.email.to_lowercase() == email);
+        let id = match existing {
+            Some(user) => user.id.to_string(),
+            None => {
+                let full_name = users
+                    .iter()
+                    .find(|user| user.email.to_lowercase() == email)
+                    .map(|user| user.full_name())
+                    .unwrap_or_default();
+                let created = pagerduty
+                    .create_user(&NewUser {
+                        name: full_name,
+                        email: email.to_string(),
+                        role: "user".to_string(),
+                        time_zone: String::new(),
+                    })
+                    .await?;
+                info!("created pagerduty user `{}`", email);
+
+                let id = created.id.to_string();
+                pagerduty_users.push(created);
+                id
+            }
+        };
+
+        desired.insert(id, role);
+    }
+
+    let existing: BTreeMap<String, String> = pagerduty
+        .list_team_members(&team.id)
+        .await?
+        .into_iter()
+        .map(|member| (member.user.id, member.role))
+        .collect();
+
+    let (to_add, to_remove) = team_member_changes(&existing, &desired);
+
+    for (id, role) in to_add {
+        pagerduty.add_user_to_team(&team.id, &id, &role).await?;
+        info!("added pagerduty user `{}` to team `{}` as {}", id, name, role);
+    }
+
+    for id in to_remove {
+        pagerduty.remove_user_from_team(&team.id, &id).await?;
+        info!("removed pagerduty user `{}` from team `{}`", id, name);
+    }
+
+    // Escalate through the team's schedules.
+    let mut schedule_ids = vec![];
+    for schedule_name in &config.schedules {
+        match schedules.iter().find(|schedule| &schedule.name == schedule_name) {
+            Some(schedule) => schedule_ids.push(schedule.id.to_string()),
+            None => warn!(
+                "could not find pagerduty schedule `{}` for team `{}`",
+                schedule_name, name
+            ),
+        }
+    }
+
+    if schedule_ids.is_empty() {
+        return Ok(());
+    }
+
+    let policy_name = if config.escalation_policy.is_empty() {
+        name
+    } else {
+        &config.escalation_policy
+    };
+    let rules = escalation_rules(&schedule_ids, config.escalation_delay_in_minutes);
+
+    let existing = pagerduty
+        .list_escalation_policies()
+        .await?
+        .into_iter()
+        .find(|policy| policy.name == policy_name);
+
+    match existing {
+        Some(policy) if escalation_rules_match(&policy.escalation_rules, &rules) => (),
+        Some(mut policy) => {
+            policy.escalation_rules = rules;
+            pagerduty.update_escalation_policy(&policy).await?;
+            info!("updated pagerduty escalation policy `{}`", policy_name);
+        }
+        None => {
+            pagerduty
+                .create_escalation_policy(&EscalationPolicy {
+                    id: String::new(),
+                    type_: "escalation_policy".to_string(),
+                    name: policy_name.to_string(),
+                    description: None,
+                    escalation_rules: rules,
+                    teams: vec![Reference::new(&team.id, "team_reference")],
+                })
+                .await?;
+            info!("created pagerduty escalation policy `{}`", policy_name);
+        }
+    }
+
+    Ok(())
+}
+
+/// A Slack message about the people that just went on call.
+fn handoff_message(channel: &str, handoffs: &[NewPagerDutyOnCall]) -> FormattedMessage {
+    let lines: Vec<String> = handoffs
+        .iter()
+        .map(|oncall| {
+            let who = if oncall.username.is_empty() {
+                oncall.name.to_string()
+            } else {
+                format!("{} (`{}`)", oncall.name, oncall.username)
+            };
+            let until = oncall
+                .end_time
+                .map(|end| format!(" until {}", end.format("%a %b %-d %H:%M UTC")))
+                .unwrap_or_default();
+
+            format!(
+                "• *{}* level {}: {}{}",
+                oncall.schedule_name, oncall.escalation_level, who, until
+            )
+        })
+        .collect();
+
+    FormattedMessage {
+        channel: channel.to_string(),
+        attachments: Default::default(),
+        blocks: vec![MessageBlock {
+            block_type: MessageBlockType::Section,
+            text: Some(MessageBlockText {
+                text_type: MessageType::Markdown,
+                text: format!(":pager: On-call handoff\n{}", lines.join("\n")),
+            }),
+            elements: Default::default(),
+            accessory: Default::default(),
+            block_id: Default::default(),
+            fields: Default::default(),
+        }],
+    }
+}
+
+/// Record who is on call right now for each schedule of the configured teams, and post the
+/// handoffs to Slack.
+async fn sync_oncalls(
+    db: &Database,
+    company: &Company,
+    pagerduty: &PagerDutyClient,
+    config: &PagerDutyConfig,
+    users: &[User],
+    pagerduty_users: &[pagerduty_minimal_api::User],
+    schedules: &[Schedule],
+) -> Result<()> {
+    let schedules: Vec<&Schedule> = schedules
+        .iter()
+        .filter(|schedule| {
+            config
+                .teams
+                .values()
+                .any(|team| team.schedules.contains(&schedule.name))
+        })
+        .collect();
+    if schedules.is_empty() {
+        return Ok(());
+    }
+
+    let schedule_ids: Vec<String> = schedules.iter().map(|schedule| schedule.id.to_string()).collect();
+    let existing = get_pagerduty_oncalls(db, company.id).await?;
+
+    let mut handoffs = vec![];
+    for oncall in pagerduty.list_oncalls(&schedule_ids).await? {
+        let schedule = match oncall
+            .schedule
+            .as_ref()
+            .and_then(|reference| schedules.iter().find(|schedule| schedule.id == reference.id))
+        {
+            Some(schedule) => schedule,
+            None => continue,
+        };
+
+        let pagerduty_user = pagerduty_users.iter().find(|user| user.id == oncall.user.id);
+        let email = pagerduty_user.map(|user| user.email.to_lowercase()).unwrap_or_default();
+        let username = users
+            .iter()
+            .find(|user| !email.is_empty() && user.email.to_lowercase() == email)
+            .map(|user| user.username.to_string())
+            .unwrap_or_default();
+
+        let new_oncall = NewPagerDutyOnCall {
+            schedule_id: schedule.id.to_string(),
+            schedule_name: schedule.name.to_string(),
+            escalation_level: oncall.escalation_level,
+            pagerduty_user_id: oncall.user.id.to_string(),
+            name: pagerduty_user
+                .map(|user| user.name.to_string())
+                .unwrap_or_else(|| oncall.user.summary.to_string()),
+            email,
+            username,
+            start_time: oncall.start,
+            end_time: oncall.end,
+            updated_at: Utc::now(),
+            cio_company_id: company.id,
+        };
+
+        let previous = existing.iter().find(|existing| {
+            existing.schedule_id == new_oncall.schedule_id && existing.escalation_level == new_oncall.escalation_level
+        });
+        if previous.map(|previous| previous.pagerduty_user_id != new_oncall.pagerduty_user_id) == Some(true) {
+            handoffs.push(new_oncall.clone());
+        }
+
+        diesel::insert_into(pagerduty_oncalls::table)
+            .values(new_oncall.clone())
+            .on_conflict((
+                pagerduty_oncalls::cio_company_id,
+                pagerduty_oncalls::schedule_id,
+                pagerduty_oncalls::escalation_level,
+            ))
+            .do_update()
+            .set(new_oncall)
+            .execute_async(db.pool())
+            .await?;
+    }
+
+    if !handoffs.is_empty() && !config.handoff_channel.is_empty() {
+        company
+            .post_to_slack_channel(db, &handoff_message(&config.handoff_channel, &handoffs))
+            .await?;
+        info!("posted {} pagerduty handoffs to slack", handoffs.len());
+    }
+
+    Ok(())
+}
+
+/// Provision the PagerDuty teams in the config and record who is on call.
+pub async fn sync_pagerduty(db: &Database, company: &Company, config: &PagerDutyConfig) -> Result<()> {
+    if config.teams.is_empty() {
+        return Ok(());
+    }
+
+    let pagerduty = match company.authenticate_pagerduty() {
+        Some(pagerduty) => pagerduty,
+        None => {
+            warn!(
+                "company `{}` has pagerduty teams, but no pagerduty credentials",
+                company.name
+            );
+            return Ok(());
+        }
+    };
+
+    let users: Vec<User> = Users::get_from_db(db, company.id).await?.into();
+    let mut pagerduty_users = pagerduty.list_users().await?;
+    let schedules = pagerduty.list_schedules().await?;
+
+    for (name, team) in &config.teams {
+        if let Err(e) = provision_team(&pagerduty, name, team, &users, &mut pagerduty_users, &schedules).await {
+            warn!("failed to provision pagerduty team `{}`: {}", name, e);
+        }
+    }
+
+    sync_oncalls(db, company, &pagerduty, config, &users, &pagerduty_users, &schedules).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use chrono::{TimeZone, Utc};
+
+    use super::{escalation_rules, escalation_rules_match, handoff_message, team_member_changes, NewPagerDutyOnCall};
+
+    fn map(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_team_member_changes() {
+        let (to_add, to_remove) = team_member_changes(
+            &map(&[("PUSER1", "responder"), ("PUSER2", "responder")]),
+            &map(&[("PUSER1", "manager"), ("PUSER3", "responder")]),
+        );
+
+        assert_eq!(
+            vec![
+                ("PUSER1".to_string(), "manager".to_string()),
+                ("PUSER3".to_string(), "responder".to_string())
+            ],
+            to_add
+        );
+        assert_eq!(vec!["PUSER2"], to_remove);
+    }
+
+    #[test]
+    fn test_escalation_rules_match() {
+        let rules = escalation_rules(&["PSCHED1".to_string(), "PSCHED2".to_string()], 30);
+
+        let mut existing = rules.clone();
+        existing[0].targets[0].summary = "Infrastructure primary".to_string();
+        assert!(escalation_rules_match(&existing, &rules));
+
+        existing[1].escalation_delay_in_minutes = 15;
+        assert!(!escalation_rules_match(&existing, &rules));
+        assert!(!escalation_rules_match(&rules[..1], &rules));
+    }
+
+    #[test]
+    fn test_handoff_message() {
+        let msg = handoff_message(
+            "#on-call",
+            &[NewPagerDutyOnCall {
+                schedule_id: "PSCHED1".to_string(),
+                schedule_name: "Infrastructure primary".to_string(),
+                escalation_level: 1,
+                pagerduty_user_id: "PUSER1".to_string(),
+                name: "Jane Doe".to_string(),
+                email: "jane@example.com".to_string(),
+                username: "jane".to_string(),
+                start_time: None,
+                end_time: Some(Utc.ymd(2023, 3, 27).and_hms(17, 0, 0)),
+                updated_at: Utc::now(),
+                cio_company_id: 1,
+            }],
+        );
+
+        assert_eq!("#on-call", msg.channel);
+        assert_eq!(
+            ":pager: On-call handoff\n• *Infrastructure primary* level 1: Jane Doe (`jane`) until Mon Mar 27 17:00 UTC",
+            msg.blocks[0].text.as_ref().unwrap().text
+        );
+    }
+}
@@ -0,0 +1,719 @@
+/*!
+ * Recording payments taken through Stripe and reconciling them with QuickBooks.
+ *
+ * Store orders and donations are paid for with Stripe Checkout, and invoices are paid through
+ * Stripe invoicing. Each payment is recorded once Stripe tells us it was paid, then booked in
+ * QuickBooks: store orders and donations get a sales receipt, and invoice payments are matched
+ * with the QuickBooks invoice of the same number. Payments that fail to reconcile are retried
+ * by the nightly QuickBooks sync.
+ */
+use std::{collections::HashMap, fmt};
+
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, TimeZone, Utc};
+use diesel::{ExpressionMethods, Insertable, QueryDsl, Queryable};
+use log::{info, warn};
+use quickbooks::{Email, InvoiceLine, Item, NtRef, Query, QuickBooks, SalesItemLineDetail, SalesReceipt};
+use schemars::JsonSchema;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{app_config::StripeConfig, companies::Company, db::Database, schema::payments};
+
+/// What a payment was for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaymentKind {
+    Store,
+    Donation,
+    Invoice,
+}
+
+impl PaymentKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaymentKind::Store => "store",
+            PaymentKind::Donation => "donation",
+            PaymentKind::Invoice => "invoice",
+        }
+    }
+}
+
+impl fmt::Display for PaymentKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+pub const PAYMENT_STATUS_PAID: &str = "paid";
+pub const PAYMENT_STATUS_PARTIALLY_REFUNDED: &str = "partially_refunded";
+pub const PAYMENT_STATUS_REFUNDED: &str = "refunded";
+
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct Payment {
+    pub id: i32,
+    /// The id of the Stripe checkout session or invoice that was paid.
+    pub stripe_id: String,
+    pub kind: String,
+    pub status: String,
+    pub amount: f32,
+    pub amount_refunded: f32,
+    pub currency: String,
+    pub customer_name: String,
+    pub customer_email: String,
+    pub description: String,
+    pub stripe_payment_intent: String,
+    pub stripe_invoice_number: String,
+    /// The id of the QuickBooks record the payment is booked as, empty until it is reconciled.
+    pub quickbooks_id: String,
+    /// Either `SalesReceipt` or `Invoice`.
+    pub quickbooks_type: String,
+    pub paid_at: DateTime<Utc>,
+    pub refunded_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = payments)]
+pub struct NewPayment {
+    pub stripe_id: String,
+    pub kind: String,
+    pub status: String,
+    pub amount: f32,
+    pub amount_refunded: f32,
+    pub currency: String,
+    pub customer_name: String,
+    pub customer_email: String,
+    pub description: String,
+    pub stripe_payment_intent: String,
+    pub stripe_invoice_number: String,
+    pub paid_at: DateTime<Utc>,
+    pub cio_company_id: i32,
+}
+
+impl NewPayment {
+    /// The payment for a completed checkout session. Sessions that are not one-off payments, or
+    /// that have not been paid yet, are not payments: subscriptions are recorded when their
+    /// invoices are paid, and delayed payment methods complete the session before the money
+    /// arrives.
+    pub fn from_checkout_session(session: &StripeCheckoutSession, cio_company_id: i32) -> Option<Self> {
+        if session.mode != "payment" || session.payment_status != "paid" {
+            return None;
+        }
+
+        let kind = if session.metadata.get("kind").map(|kind| kind.as_str()) == Some("donation") {
+            PaymentKind::Donation
+        } else {
+            PaymentKind::Store
+        };
+        let customer = session.customer_details.clone().unwrap_or_default();
+
+        Some(NewPayment {
+            stripe_id: session.id.to_string(),
+            kind: kind.to_string(),
+            status: PAYMENT_STATUS_PAID.to_string(),
+            amount: from_minor_units(session.amount_total.unwrap_or_default()),
+            amount_refunded: 0.0,
+            currency: session.currency.to_string(),
+            customer_name: customer.name.unwrap_or_default(),
+            customer_email: customer.email.unwrap_or_default(),
+            description: session.metadata.get("description").cloned().unwrap_or_default(),
+            stripe_payment_intent: session.payment_intent.clone().unwrap_or_default(),
+            stripe_invoice_number: String::new(),
+            paid_at: Utc.timestamp(session.created, 0),
+            cio_company_id,
+        })
+    }
+
+    pub fn from_invoice(invoice: &StripeInvoice, cio_company_id: i32) -> Self {
+        NewPayment {
+            stripe_id: invoice.id.to_string(),
+            kind: PaymentKind::Invoice.to_string(),
+            status: PAYMENT_STATUS_PAID.to_string(),
+            amount: from_minor_units(invoice.amount_paid),
+            amount_refunded: 0.0,
+            currency: invoice.currency.to_string(),
+            customer_name: invoice.customer_name.clone().unwrap_or_default(),
+            customer_email: invoice.customer_email.clone().unwrap_or_default(),
+            description: invoice.description.clone().unwrap_or_default(),
+            stripe_payment_intent: invoice.payment_intent.clone().unwrap_or_default(),
+            stripe_invoice_number: invoice.number.clone().unwrap_or_default(),
+            paid_at: Utc.timestamp(invoice.status_transitions.paid_at.unwrap_or(invoice.created), 0),
+            cio_company_id,
+        }
+    }
+
+    /// Record the payment. Stripe sends events at least once, so a payment that was already
+    /// recorded is left alone and returned as is.
+    pub async fn record(&self, db: &Database) -> Result<Payment> {
+        diesel::insert_into(payments::table)
+            .values(self.clone())
+            .on_conflict((payments::cio_company_id, payments::stripe_id))
+            .do_nothing()
+            .execute_async(db.pool())
+            .await?;
+
+        Ok(payments::dsl::payments
+            .filter(payments::dsl::cio_company_id.eq(self.cio_company_id))
+            .filter(payments::dsl::stripe_id.eq(self.stripe_id.to_string()))
+            .first_async::<Payment>(db.pool())
+            .await?)
+    }
+}
+
+/// Stripe amounts are in the smallest unit of the currency, ie. cents.
+fn from_minor_units(amount: i64) -> f32 {
+    amount as f32 / 100.0
+}
+
+impl Payment {
+    pub async fn get_by_payment_intent(
+        db: &Database,
+        cio_company_id: i32,
+        payment_intent: &str,
+    ) -> Result<Option<Payment>> {
+        Ok(payments::dsl::payments
+            .filter(payments::dsl::cio_company_id.eq(cio_company_id))
+            .filter(payments::dsl::stripe_payment_intent.eq(payment_intent.to_string()))
+            .load_async::<Payment>(db.pool())
+            .await?
+            .pop())
+    }
+
+    pub async fn get_unreconciled(db: &Database, cio_company_id: i32) -> Result<Vec<Payment>> {
+        Ok(payments::dsl::payments
+            .filter(payments::dsl::cio_company_id.eq(cio_company_id))
+            .filter(payments::dsl::quickbooks_id.eq(String::new()))
+            .order_by(payments::dsl::paid_at)
+            .load_async::<Payment>(db.pool())
+            .await?)
+    }
+
+    pub fn kind(&self) -> Option<PaymentKind> {
+        [PaymentKind::Store, PaymentKind::Donation, PaymentKind::Invoice]
+            .into_iter()
+            .find(|kind| kind.as_str() == self.kind)
+    }
+
+    /// The QuickBooks item the payment is sold as, for the kinds of payments that are booked
+    /// as sales receipts.
+    fn quickbooks_item<'a>(&self, config: &'a StripeConfig) -> Option<&'a str> {
+        let item = match self.kind()? {
+            PaymentKind::Store => &config.quickbooks_store_item,
+            PaymentKind::Donation => &config.quickbooks_donation_item,
+            PaymentKind::Invoice => return None,
+        };
+
+        if item.is_empty() {
+            None
+        } else {
+            Some(item)
+        }
+    }
+
+    /// The sales receipt that books the payment as a sale of `item`. The Stripe id is kept in
+    /// the private note so the receipt can be traced back to the payment.
+    pub fn sales_receipt(&self, item: &Item) -> SalesReceipt {
+        let description = if self.description.is_empty() {
+            format!("Stripe {} payment", self.kind)
+        } else {
+            self.description.to_string()
+        };
+
+        SalesReceipt {
+            txn_date: Some(self.paid_at.naive_utc().date()),
+            bill_email: if self.customer_email.is_empty() {
+                None
+            } else {
+                Some(Email {
+                    address: self.customer_email.to_string(),
+                })
+            },
+            line: vec![InvoiceLine {
+                description,
+                amount: self.amount as f64,
+                detail_type: "SalesItemLineDetail".to_string(),
+                sales_item_line_detail: Some(SalesItemLineDetail {
+                    item_ref: NtRef {
+                        value: item.id.to_string(),
+                        name: item.name.to_string(),
+                        ..Default::default()
+                    },
+                    qty: 1.0,
+                    unit_price: self.amount as f64,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+            private_note: format!("Stripe {} {}", self.stripe_id, self.customer_name)
+                .trim()
+                .to_string(),
+            ..Default::default()
+        }
+    }
+
+    async fn set_quickbooks_record(&mut self, db: &Database, quickbooks_type: &str, quickbooks_id: &str) -> Result<()> {
+        *self = diesel::update(payments::dsl::payments.find(self.id))
+            .set((
+                payments::dsl::quickbooks_type.eq(quickbooks_type.to_string()),
+                payments::dsl::quickbooks_id.eq(quickbooks_id.to_string()),
+                payments::dsl::updated_at.eq(Utc::now()),
+            ))
+            .get_result_async::<Payment>(db.pool())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Record a refund of the charge for this payment. `amount_refunded` is the total refunded
+    /// so far, not the amount of this refund.
+    pub async fn record_refund(
+        &mut self,
+        db: &Database,
+        amount_refunded: f32,
+        refunded_at: DateTime<Utc>,
+    ) -> Result<()> {
+        let status = if amount_refunded >= self.amount {
+            PAYMENT_STATUS_REFUNDED
+        } else {
+            PAYMENT_STATUS_PARTIALLY_REFUNDED
+        };
+
+        *self = diesel::update(payments::dsl::payments.find(self.id))
+            .set((
+                payments::dsl::status.eq(status.to_string()),
+                payments::dsl::amount_refunded.eq(amount_refunded),
+                payments::dsl::refunded_at.eq(Some(refunded_at)),
+                payments::dsl::updated_at.eq(Utc::now()),
+            ))
+            .get_result_async::<Payment>(db.pool())
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Book a payment in QuickBooks, if it is not already.
+pub async fn reconcile_payment(
+    db: &Database,
+    qb: &QuickBooks,
+    config: &StripeConfig,
+    payment: &mut Payment,
+) -> Result<()> {
+    if !payment.quickbooks_id.is_empty() {
+        return Ok(());
+    }
+
+    if payment.kind() == Some(PaymentKind::Invoice) {
+        if payment.stripe_invoice_number.is_empty() {
+            bail!(
+                "stripe invoice `{}` has no number to match in quickbooks",
+                payment.stripe_id
+            );
+        }
+
+        let invoice = qb
+            .query_all(
+                Query::<quickbooks::Invoice>::new().where_eq("DocNumber", payment.stripe_invoice_number.as_str()),
+            )
+            .await?
+            .pop();
+        let invoice = match invoice {
+            Some(invoice) => invoice,
+            None => bail!(
+                "could not find quickbooks invoice `{}` for stripe invoice `{}`",
+                payment.stripe_invoice_number,
+                payment.stripe_id
+            ),
+        };
+
+        if invoice.balance > 0.0 {
+            warn!(
+                "quickbooks invoice `{}` was paid through stripe but still has a balance of {}",
+                invoice.doc_number, invoice.balance
+            );
+        }
+
+        payment.set_quickbooks_record(db, "Invoice", &invoice.id).await?;
+        info!(
+            "reconciled stripe invoice `{}` with quickbooks invoice `{}`",
+            payment.stripe_id, invoice.doc_number
+        );
+
+        return Ok(());
+    }
+
+    let item_name = match payment.quickbooks_item(config) {
+        Some(item_name) => item_name,
+        None => return Ok(()),
+    };
+
+    let item = match qb
+        .query_all(Query::<Item>::new().where_eq("Name", item_name))
+        .await?
+        .pop()
+    {
+        Some(item) => item,
+        None => bail!("could not find quickbooks item `{}`", item_name),
+    };
+
+    let sales_receipt = qb.create_sales_receipt(&payment.sales_receipt(&item)).await?;
+    payment
+        .set_quickbooks_record(db, "SalesReceipt", &sales_receipt.id)
+        .await?;
+    info!(
+        "booked stripe {} payment `{}` as quickbooks sales receipt `{}`",
+        payment.kind, payment.stripe_id, sales_receipt.id
+    );
+
+    Ok(())
+}
+
+/// Retry booking the payments that are not in QuickBooks yet.
+pub async fn reconcile_payments(
+    db: &Database,
+    company: &Company,
+    qb: &QuickBooks,
+    config: &StripeConfig,
+) -> Result<()> {
+    for mut payment in Payment::get_unreconciled(db, company.id).await? {
+        if let Err(e) = reconcile_payment(db, qb, config, &mut payment).await {
+            warn!("reconciling stripe payment `{}` failed: {}", payment.stripe_id, e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Let finance know about a refund, since refunds need to be booked against the QuickBooks
+/// record by hand.
+async fn send_refund_notification(
+    db: &Database,
+    company: &Company,
+    config: &StripeConfig,
+    payment: &Payment,
+) -> Result<()> {
+    let channel = if config.refunds_slack_channel.is_empty() {
+        company.slack_channel_finance.to_string()
+    } else {
+        config.refunds_slack_channel.to_string()
+    };
+
+    let booked = if payment.quickbooks_id.is_empty() {
+        "It is not booked in QuickBooks.".to_string()
+    } else {
+        format!(
+            "It is booked as QuickBooks {} `{}`.",
+            payment.quickbooks_type, payment.quickbooks_id
+        )
+    };
+
+    let msg = FormattedMessage {
+        channel,
+        blocks: vec![MessageBlock {
+            block_type: MessageBlockType::Section,
+            text: Some(MessageBlockText {
+                text_type: MessageType::Markdown,
+                text: format!(
+                    "Refunded {:.2} {} of the {:.2} {} paid by {} for Stripe {} payment `{}`. {}",
+                    payment.amount_refunded,
+                    payment.currency.to_uppercase(),
+                    payment.amount,
+                    payment.currency.to_uppercase(),
+                    if payment.customer_name.is_empty() {
+                        &payment.customer_email
+                    } else {
+                        &payment.customer_name
+                    },
+                    payment.kind,
+                    payment.stripe_id,
+                    booked
+                ),
+            }),
+            ..Default::default()
+        }],
+        attachments: vec![],
+    };
+
+    company.post_to_slack_channel(db, &msg).await
+}
+
+/// A Stripe webhook event. Only the data of the events we handle is parsed, see
+/// [`StripeEvent::payload`].
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct StripeEvent {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub created: i64,
+    pub data: StripeEventData,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct StripeEventData {
+    pub object: serde_json::Value,
+}
+
+/// The events we handle, with their typed data.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StripeEventPayload {
+    CheckoutSessionCompleted(StripeCheckoutSession),
+    InvoicePaid(StripeInvoice),
+    ChargeRefunded(StripeCharge),
+}
+
+impl StripeEvent {
+    /// The typed data of the event, `None` for the events we do not handle.
+    pub fn payload(&self) -> Result<Option<StripeEventPayload>> {
+        fn object<T: DeserializeOwned>(event: &StripeEvent) -> Result<T> {
+            Ok(serde_json::from_value(event.data.object.clone())?)
+        }
+
+        Ok(match self.type_.as_str() {
+            "checkout.session.completed" => Some(StripeEventPayload::CheckoutSessionCompleted(object(self)?)),
+            "invoice.paid" => Some(StripeEventPayload::InvoicePaid(object(self)?)),
+            "charge.refunded" => Some(StripeEventPayload::ChargeRefunded(object(self)?)),
+            _ => None,
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct StripeCheckoutSession {
+    pub id: String,
+    /// One of `payment`, `setup` or `subscription`.
+    pub mode: String,
+    /// One of `paid`, `unpaid` or `no_payment_required`.
+    pub payment_status: String,
+    #[serde(default)]
+    pub amount_total: Option<i64>,
+    #[serde(default)]
+    pub currency: String,
+    #[serde(default)]
+    pub customer_details: Option<StripeCustomerDetails>,
+    #[serde(default)]
+    pub payment_intent: Option<String>,
+    /// The metadata set when the session was created. `kind` is `donation` for donations.
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    pub created: i64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct StripeCustomerDetails {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct StripeInvoice {
+    pub id: String,
+    #[serde(default)]
+    pub number: Option<String>,
+    #[serde(default)]
+    pub amount_paid: i64,
+    #[serde(default)]
+    pub currency: String,
+    #[serde(default)]
+    pub customer_name: Option<String>,
+    #[serde(default)]
+    pub customer_email: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub payment_intent: Option<String>,
+    #[serde(default)]
+    pub status_transitions: StripeInvoiceStatusTransitions,
+    pub created: i64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct StripeInvoiceStatusTransitions {
+    #[serde(default)]
+    pub paid_at: Option<i64>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct StripeCharge {
+    pub id: String,
+    #[serde(default)]
+    pub amount: i64,
+    #[serde(default)]
+    pub amount_refunded: i64,
+    #[serde(default)]
+    pub currency: String,
+    #[serde(default)]
+    pub payment_intent: Option<String>,
+    #[serde(default)]
+    pub refunded: bool,
+}
+
+/// Record the payment or refund a Stripe event is about, and book it in QuickBooks.
+pub async fn handle_stripe_event(
+    db: &Database,
+    company: &Company,
+    config: &StripeConfig,
+    event: StripeEvent,
+) -> Result<()> {
+    let new_payment = match event.payload()? {
+        Some(StripeEventPayload::CheckoutSessionCompleted(session)) => {
+            match NewPayment::from_checkout_session(&session, company.id) {
+                Some(new_payment) => new_payment,
+                None => {
+                    info!(
+                        "stripe checkout session `{}` is not a paid payment, skipping",
+                        session.id
+                    );
+                    return Ok(());
+                }
+            }
+        }
+        Some(StripeEventPayload::InvoicePaid(invoice)) => NewPayment::from_invoice(&invoice, company.id),
+        Some(StripeEventPayload::ChargeRefunded(charge)) => {
+            let payment_intent = charge.payment_intent.unwrap_or_default();
+            let mut payment = match Payment::get_by_payment_intent(db, company.id, &payment_intent).await? {
+                Some(payment) if !payment_intent.is_empty() => payment,
+                _ => {
+                    info!(
+                        "stripe charge `{}` is not for a payment we recorded, skipping",
+                        charge.id
+                    );
+                    return Ok(());
+                }
+            };
+
+            payment
+                .record_refund(
+                    db,
+                    from_minor_units(charge.amount_refunded),
+                    Utc.timestamp(event.created, 0),
+                )
+                .await?;
+            info!(
+                "recorded refund of {} for stripe payment `{}`",
+                payment.amount_refunded, payment.stripe_id
+            );
+
+            return send_refund_notification(db, company, config, &payment).await;
+        }
+        None => {
+            info!("ignoring stripe event `{}` of type `{}`", event.id, event.type_);
+            return Ok(());
+        }
+    };
+
+    let mut payment = new_payment.record(db).await?;
+    info!("recorded stripe {} payment `{}`", payment.kind, payment.stripe_id);
+
+    // A payment that can not be booked now is retried by the nightly QuickBooks sync, so this
+    // does not fail the webhook.
+    match company.authenticate_quickbooks(db).await {
+        Ok(qb) => {
+            if let Err(e) = reconcile_payment(db, &qb, config, &mut payment).await {
+                warn!("reconciling stripe payment `{}` failed: {}", payment.stripe_id, e);
+            }
+        }
+        Err(e) => warn!("authenticating quickbooks failed: {}", e),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::{NewPayment, PaymentKind, StripeEvent, StripeEventPayload};
+
+    #[test]
+    fn test_checkout_session_payment() {
+        let event: StripeEvent = serde_json::from_value(serde_json::json!({
+            "id": "evt_1",
+            "type": "checkout.session.completed",
+            "created": 1679500000,
+            "data": {
+                "object": {
+                    "id": "cs_test_a1",
+                    "object": "checkout.session",
+                    "mode": "payment",
+                    "payment_status": "paid",
+                    "amount_total": 2500,
+                    "currency": "usd",
+                    "customer_details": { "name": "Jane Doe", "email": "jane@example.com" },
+                    "payment_intent": "pi_1",
+                    "metadata": { "kind": "donation" },
+                    "created": 1679499990
+                }
+            }
+        }))
+        .unwrap();
+
+        let session = match event.payload().unwrap() {
+            Some(StripeEventPayload::CheckoutSessionCompleted(session)) => session,
+            payload => panic!("unexpected payload {:?}", payload),
+        };
+
+        let payment = NewPayment::from_checkout_session(&session, 1).unwrap();
+        assert_eq!(PaymentKind::Donation.as_str(), payment.kind);
+        assert_eq!(25.0, payment.amount);
+        assert_eq!("jane@example.com", payment.customer_email);
+        assert_eq!("pi_1", payment.stripe_payment_intent);
+        assert_eq!(Utc.timestamp(1679499990, 0), payment.paid_at);
+
+        let mut unpaid = session.clone();
+        unpaid.payment_status = "unpaid".to_string();
+        assert!(NewPayment::from_checkout_session(&unpaid, 1).is_none());
+
+        let mut subscription = session;
+        subscription.mode = "subscription".to_string();
+        assert!(NewPayment::from_checkout_session(&subscription, 1).is_none());
+    }
+
+    #[test]
+    fn test_unhandled_events_have_no_payload() {
+        let event: StripeEvent = serde_json::from_value(serde_json::json!({
+            "id": "evt_2",
+            "type": "customer.created",
+            "created": 1679500000,
+            "data": { "object": { "id": "cus_1" } }
+        }))
+        .unwrap();
+
+        assert!(event.payload().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_invoice_payment() {
+        let event: StripeEvent = serde_json::from_value(serde_json::json!({
+            "id": "evt_3",
+            "type": "invoice.paid",
+            "created": 1679500000,
+            "data": {
+                "object": {
+                    "id": "in_1",
+                    "number": "ABCD-0001",
+                    "amount_paid": 1234567,
+                    "currency": "usd",
+                    "customer_name": "Example Corp",
+                    "status_transitions": { "paid_at": 1679499000 },
+                    "created": 1679000000
+                }
+            }
+        }))
+        .unwrap();
+
+        let invoice = match event.payload().unwrap() {
+            Some(StripeEventPayload::InvoicePaid(invoice)) => invoice,
+            payload => panic!("unexpected payload {:?}", payload),
+        };
+
+        let payment = NewPayment::from_invoice(&invoice, 1);
+        assert_eq!(PaymentKind::Invoice.as_str(), payment.kind);
+        assert_eq!(12345.67, payment.amount);
+        assert_eq!("ABCD-0001", payment.stripe_invoice_number);
+        assert_eq!(Utc.timestamp(1679499000, 0), payment.paid_at);
+    }
+}
@@ -0,0 +1,239 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, Insertable, QueryDsl, Queryable};
+use docusign::DocuSign;
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_config::{AppConfig, PolicyAcknowledgmentConfig},
+    companies::Company,
+    configs::{User, Users},
+    db::Database,
+    schema::policy_acknowledgments,
+};
+
+/// The envelope statuses after which DocuSign will no longer change the envelope.
+const FINAL_ENVELOPE_STATUSES: &[&str] = &["completed", "declined", "voided"];
+
+/// How long to wait for DocuSign to send the envelopes of a batch before giving up on deleting
+/// the bulk send list it was sent from.
+const BATCH_SEND_TIMEOUT: Duration = Duration::from_secs(120);
+
+const BATCH_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A record of a single employee being asked to acknowledge a policy via a DocuSign bulk send.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct PolicyAcknowledgment {
+    pub id: i32,
+    pub policy: String,
+    pub user_email: String,
+    pub user_name: String,
+    pub docusign_batch_id: String,
+    pub docusign_envelope_id: String,
+    pub docusign_envelope_status: String,
+    pub sent_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = policy_acknowledgments)]
+pub struct NewPolicyAcknowledgment {
+    pub policy: String,
+    pub user_email: String,
+    pub user_name: String,
+    pub docusign_batch_id: String,
+    pub docusign_envelope_status: String,
+    pub cio_company_id: i32,
+}
+
+/// Send the given policy to every full-time employee in a single DocuSign bulk send.
+/// Returns the id of the bulk send batch.
+pub async fn send_policy_acknowledgments(
+    db: &Database,
+    company: &Company,
+    config: &AppConfig,
+    policy: &str,
+) -> Result<String> {
+    let policy_config = match config.envelopes.policy(policy) {
+        Some(policy_config) => policy_config,
+        None => bail!("no policy acknowledgment is configured for `{}`", policy),
+    };
+
+    let users: Vec<User> = Users::get_from_db(db, company.id)
+        .await?
+        .into_iter()
+        .filter(|user| user.is_full_time() && !user.email.is_empty())
+        .collect();
+    if users.is_empty() {
+        bail!("no employees to send the `{}` policy to", policy);
+    }
+
+    let list = config
+        .envelopes
+        .create_policy_acknowledgment_list(policy, &users)
+        .ok_or_else(|| anyhow::anyhow!("no policy acknowledgment is configured for `{}`", policy))?;
+
+    let ds = company.authenticate_docusign(db).await?;
+
+    let list = ds.create_bulk_send_list(list).await?;
+    let sent = send_policy_acknowledgment_list(db, company, &ds, &list.list_id, policy, policy_config, &users).await;
+
+    // DocuSign sends the envelopes of a batch from its list in the background, so the list can
+    // only go once nothing in the batch is queued. A list that did not make a batch is not needed.
+    let list_done = match &sent {
+        Ok(batch_id) => wait_for_batch(&ds, batch_id, policy).await,
+        Err(_) => true,
+    };
+    if !list_done {
+        warn!(
+            "not deleting docusign bulk send list {}, its batch is still being sent",
+            list.list_id
+        );
+    } else if let Err(e) = ds.delete_bulk_send_list(&list.list_id).await {
+        warn!("failed to delete docusign bulk send list {}: {}", list.list_id, e);
+    }
+
+    sent
+}
+
+/// Wait for DocuSign to send every envelope of a bulk send batch, and report the ones it could
+/// not send. Returns whether nothing in the batch is still queued.
+async fn wait_for_batch(ds: &DocuSign, batch_id: &str, policy: &str) -> bool {
+    let started = Instant::now();
+
+    let status = loop {
+        match ds.get_bulk_send_batch_status(batch_id).await {
+            Ok(status) if status.queued.is_empty() || status.queued == "0" => break status,
+            Ok(_) => (),
+            Err(e) => {
+                warn!("failed to get the status of docusign batch {}: {}", batch_id, e);
+                return false;
+            }
+        }
+
+        if started.elapsed() >= BATCH_SEND_TIMEOUT {
+            return false;
+        }
+
+        tokio::time::sleep(BATCH_POLL_INTERVAL).await;
+    };
+
+    for error in &status.bulk_errors {
+        warn!(
+            "docusign could not send policy `{}` to {}: {}",
+            policy,
+            error.recipient_emails.join(", "),
+            error.error_message
+        );
+    }
+
+    true
+}
+
+/// Send the envelopes for a bulk send list and record who they were sent to. Returns the id
+/// of the batch.
+async fn send_policy_acknowledgment_list(
+    db: &Database,
+    company: &Company,
+    ds: &DocuSign,
+    list_id: &str,
+    policy: &str,
+    policy_config: &PolicyAcknowledgmentConfig,
+    users: &[User],
+) -> Result<String> {
+    let batch = ds
+        .send_bulk_send_list(
+            list_id,
+            &policy_config.template_id,
+            &format!("{} {}", policy, Utc::now().format("%Y-%m-%d")),
+        )
+        .await?;
+
+    for error in &batch.errors {
+        warn!(
+            "docusign bulk send for policy `{}` reported an error: {}",
+            policy, error
+        );
+    }
+
+    info!(
+        "sent policy `{}` to {} employees in docusign batch {}",
+        policy,
+        users.len(),
+        batch.batch_id
+    );
+
+    let records: Vec<NewPolicyAcknowledgment> = users
+        .iter()
+        .map(|user| NewPolicyAcknowledgment {
+            policy: policy.to_string(),
+            user_email: user.email.to_string(),
+            user_name: user.full_name(),
+            docusign_batch_id: batch.batch_id.to_string(),
+            docusign_envelope_status: "queued".to_string(),
+            cio_company_id: company.id,
+        })
+        .collect();
+
+    diesel::insert_into(policy_acknowledgments::table)
+        .values(records)
+        .execute_async(db.pool())
+        .await?;
+
+    Ok(batch.batch_id)
+}
+
+/// Sync the per-recipient envelope status of any outstanding policy acknowledgments back
+/// into the database.
+pub async fn refresh_policy_acknowledgments(db: &Database, company: &Company) -> Result<()> {
+    let pending = policy_acknowledgments::dsl::policy_acknowledgments
+        .filter(policy_acknowledgments::dsl::cio_company_id.eq(company.id))
+        .filter(policy_acknowledgments::dsl::docusign_envelope_status.ne_all(FINAL_ENVELOPE_STATUSES.to_vec()))
+        .load_async::<PolicyAcknowledgment>(db.pool())
+        .await?;
+
+    if pending.is_empty() {
+        return Ok(());
+    }
+
+    let ds = company.authenticate_docusign(db).await?;
+
+    let mut batch_ids: Vec<String> = pending.iter().map(|p| p.docusign_batch_id.to_string()).collect();
+    batch_ids.sort();
+    batch_ids.dedup();
+
+    for batch_id in batch_ids {
+        let envelopes = ds.list_bulk_send_batch_envelopes(&batch_id).await?;
+
+        for envelope in envelopes {
+            for signer in &envelope.recipients.signers {
+                let completed_at = if envelope.status == "completed" {
+                    envelope.completed_date_time
+                } else {
+                    None
+                };
+
+                diesel::update(
+                    policy_acknowledgments::dsl::policy_acknowledgments
+                        .filter(policy_acknowledgments::dsl::docusign_batch_id.eq(batch_id.to_string()))
+                        .filter(policy_acknowledgments::dsl::user_email.eq(signer.email.to_string())),
+                )
+                .set((
+                    policy_acknowledgments::dsl::docusign_envelope_id.eq(envelope.envelope_id.to_string()),
+                    policy_acknowledgments::dsl::docusign_envelope_status.eq(envelope.status.to_string()),
+                    policy_acknowledgments::dsl::completed_at.eq(completed_at),
+                ))
+                .execute_async(db.pool())
+                .await?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,3 +1,6 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
 pub struct Printer;
 
 impl Printer {
@@ -5,3 +8,21 @@ impl Printer {
         std::env::var("PRINT_TOKEN").unwrap_or_else(|_| "".to_string())
     }
 }
+
+/// The printers that shipping labels can be printed on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LabelPrinter {
+    #[default]
+    Rollo,
+    Zebra,
+}
+
+impl LabelPrinter {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LabelPrinter::Rollo => "rollo",
+            LabelPrinter::Zebra => "zebra",
+        }
+    }
+}
@@ -0,0 +1,226 @@
+/*!
+ * Structured progress of long running jobs, ie. a sync of every RFD, for anyone who wants to
+ * watch one as it runs.
+ *
+ * A job runs inside [`scope`] with a [`ProgressReporter`], and the code it calls reports the
+ * stages it goes through with [`stage_started`], [`stage_progress`], [`stage_completed`] and
+ * [`stage_failed`]. Outside of a scope, reporting does nothing, so code can report its progress
+ * no matter how it was called.
+ *
+ * The reporter lives in a task local, which spawned tasks do not inherit. Report from the task
+ * that waits on the spawned ones.
+ */
+use std::{
+    fmt,
+    future::Future,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+/// How many events are kept for watchers that start watching after the job has started. Only
+/// the latest are kept for jobs that report more.
+const MAX_HISTORY: usize = 1000;
+
+/// How many events a watcher can fall behind before it misses some.
+const CHANNEL_CAPACITY: usize = 256;
+
+tokio::task_local! {
+    static REPORTER: ProgressReporter;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgressEventKind {
+    StageStarted,
+    StageProgress,
+    StageCompleted,
+    StageFailed,
+}
+
+impl fmt::Display for ProgressEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ProgressEventKind::StageStarted => "stage_started",
+            ProgressEventKind::StageProgress => "stage_progress",
+            ProgressEventKind::StageCompleted => "stage_completed",
+            ProgressEventKind::StageFailed => "stage_failed",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct ProgressEvent {
+    pub kind: ProgressEventKind,
+    /// The part of the job the event is for, ie. `rfds`.
+    pub stage: String,
+    /// How many items of the stage are done, for stages that work through a number of them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub done: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<usize>,
+    /// What went wrong, for failures. A stage can report failures of single items and still go
+    /// on to complete.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+struct ProgressLog {
+    history: Vec<ProgressEvent>,
+    events: broadcast::Sender<ProgressEvent>,
+}
+
+/// Collects the progress events of a job and hands them to whoever is watching it. Watchers
+/// see the end of the job as their channel closing, which happens once every clone of the
+/// reporter is dropped.
+#[derive(Debug, Clone)]
+pub struct ProgressReporter {
+    log: Arc<Mutex<ProgressLog>>,
+}
+
+impl ProgressReporter {
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(CHANNEL_CAPACITY);
+
+        ProgressReporter {
+            log: Arc::new(Mutex::new(ProgressLog {
+                history: vec![],
+                events,
+            })),
+        }
+    }
+
+    pub fn report(&self, event: ProgressEvent) {
+        let mut log = self.log.lock().unwrap();
+
+        if log.history.len() >= MAX_HISTORY {
+            log.history.remove(0);
+        }
+        log.history.push(event.clone());
+
+        // Nobody watching is fine.
+        let _ = log.events.send(event);
+    }
+
+    /// The events reported so far, and a channel of the ones reported from now on.
+    pub fn subscribe(&self) -> (Vec<ProgressEvent>, broadcast::Receiver<ProgressEvent>) {
+        let log = self.log.lock().unwrap();
+
+        (log.history.clone(), log.events.subscribe())
+    }
+}
+
+impl Default for ProgressReporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Run `f` with `reporter` collecting the progress it reports.
+pub async fn scope<F: Future>(reporter: ProgressReporter, f: F) -> F::Output {
+    REPORTER.scope(reporter, f).await
+}
+
+fn report(kind: ProgressEventKind, stage: &str, done: Option<usize>, total: Option<usize>, error: Option<String>) {
+    let _ = REPORTER.try_with(|reporter| {
+        reporter.report(ProgressEvent {
+            kind,
+            stage: stage.to_string(),
+            done,
+            total,
+            error,
+            at: Utc::now(),
+        })
+    });
+}
+
+pub fn stage_started(stage: &str, total: Option<usize>) {
+    report(ProgressEventKind::StageStarted, stage, None, total, None);
+}
+
+pub fn stage_progress(stage: &str, done: usize, total: Option<usize>) {
+    report(ProgressEventKind::StageProgress, stage, Some(done), total, None);
+}
+
+pub fn stage_completed(stage: &str, done: Option<usize>) {
+    report(ProgressEventKind::StageCompleted, stage, done, None, None);
+}
+
+pub fn stage_failed<E: fmt::Display>(stage: &str, error: E) {
+    report(
+        ProgressEventKind::StageFailed,
+        stage,
+        None,
+        None,
+        Some(error.to_string()),
+    );
+}
+
+/// Run `f` as the stage `stage`, reporting when it starts and how it ends.
+pub async fn stage<F, T, E>(stage: &str, f: F) -> Result<T, E>
+where
+    F: Future<Output = Result<T, E>>,
+    E: fmt::Display,
+{
+    stage_started(stage, None);
+
+    let result = f.await;
+    match &result {
+        Ok(_) => stage_completed(stage, None),
+        Err(e) => stage_failed(stage, e),
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        scope, stage_completed, stage_failed, stage_progress, stage_started, ProgressEventKind, ProgressReporter,
+    };
+
+    #[tokio::test]
+    async fn test_progress_reporter() {
+        // Reporting outside of a scope goes nowhere.
+        stage_started("rfds", Some(2));
+
+        let reporter = ProgressReporter::new();
+
+        scope(reporter.clone(), async {
+            stage_started("rfds", Some(2));
+            stage_progress("rfds", 1, Some(2));
+        })
+        .await;
+
+        let (history, mut events) = reporter.subscribe();
+        assert_eq!(
+            vec![ProgressEventKind::StageStarted, ProgressEventKind::StageProgress],
+            history.iter().map(|event| event.kind).collect::<Vec<_>>()
+        );
+
+        scope(reporter.clone(), async {
+            stage_failed("rfds", "RFD 2 is missing its README");
+            stage_completed("rfds", Some(2));
+        })
+        .await;
+
+        let failed = events.recv().await.unwrap();
+        assert_eq!(ProgressEventKind::StageFailed, failed.kind);
+        assert_eq!(Some("RFD 2 is missing its README".to_string()), failed.error);
+
+        let completed = events.recv().await.unwrap();
+        assert_eq!(ProgressEventKind::StageCompleted, completed.kind);
+        assert_eq!(Some(2), completed.done);
+
+        // Watchers see the job end once the reporter is gone.
+        drop(reporter);
+        assert!(events.recv().await.is_err());
+    }
+}
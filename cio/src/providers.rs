@@ -5,7 +5,7 @@ use sodiumoxide::{base64, crypto::hash};
 use std::convert::TryInto;
 
 use crate::{
-    app_config::AppConfig,
+    app_config::{AppConfig, RampCardsConfig},
     companies::Company,
     configs::{ExternalServices, Group, User},
     db::Database,
@@ -44,7 +44,7 @@ pub trait ProviderReadOps {
 
 #[async_trait]
 impl ProviderWriteOps for ramp_minimal_api::RampClient {
-    async fn ensure_user(&self, db: &Database, _company: &Company, user: &User, _config: &AppConfig) -> Result<String> {
+    async fn ensure_user(&self, db: &Database, _company: &Company, user: &User, config: &AppConfig) -> Result<String> {
         if user.denied_services.contains(&ExternalServices::Ramp) {
             log::info!(
                 "User {} is denied access to {}. Exiting provisioning.",
@@ -114,6 +114,10 @@ impl ProviderWriteOps for ramp_minimal_api::RampClient {
 
             info!("updated ramp user `{}`", user.email);
 
+            if let Err(e) = ensure_ramp_card(self, user, &config.finance.ramp_cards).await {
+                warn!("failed to ensure ramp card for `{}`: {}", user.email, e);
+            }
+
             // Return early.
             return Ok(user.ramp_id.to_string());
         }
@@ -122,7 +126,7 @@ impl ProviderWriteOps for ramp_minimal_api::RampClient {
         let mut ramp_user = ramp_minimal_api::CreateUserDeferred {
             // Hashing here is only used to get a stable random value, so we are re-using what the
             // crate already has available
-            idempotency_key: base64::encode(hash::hash(user.email.as_bytes()).as_ref(), base64::Variant::Original),
+            idempotency_key: idempotency_key(&user.email),
             email: user.email.to_string(),
             first_name: user.first_name.to_string(),
             last_name: user.last_name.to_string(),
@@ -151,7 +155,8 @@ impl ProviderWriteOps for ramp_minimal_api::RampClient {
 
         info!("created new ramp user `{}`", user.email);
 
-        // TODO(should we?): Create them a card.
+        // The user is created asynchronously, so their card is issued on the next sync once
+        // we have their Ramp id.
 
         Ok(r.id)
     }
@@ -176,8 +181,40 @@ impl ProviderWriteOps for ramp_minimal_api::RampClient {
         Ok(())
     }
 
-    async fn delete_user(&self, _db: &Database, _company: &Company, _user: &User) -> Result<()> {
-        log::info!("Skipping Ramp user deletion as access is controlled via GSuite account. Ramp account is left in tact for auditing.");
+    async fn delete_user(&self, _db: &Database, _company: &Company, user: &User) -> Result<()> {
+        // Access is controlled via the GSuite account and the Ramp account is left intact for
+        // auditing, so we only lock the user's cards from any further purchases.
+        if user.ramp_id.is_empty() {
+            return Ok(());
+        }
+
+        let cards = self
+            .cards()
+            .list_all(&ramp_minimal_api::ListCardsQuery {
+                user_id: Some(user.ramp_id.to_string()),
+                ..Default::default()
+            })
+            .await?;
+
+        for card in cards {
+            if card.state != ramp_minimal_api::CardState::Active
+                && card.state != ramp_minimal_api::CardState::Unactivated
+            {
+                continue;
+            }
+
+            self.cards()
+                .deferred_suspend(
+                    &card.id,
+                    &ramp_minimal_api::CardStateChange {
+                        idempotency_key: idempotency_key(&format!("{}-suspend", card.id)),
+                    },
+                )
+                .await?;
+
+            info!("suspended ramp card `{}` for `{}`", card.display_name, user.email);
+        }
+
         Ok(())
     }
 
@@ -187,6 +224,90 @@ impl ProviderWriteOps for ramp_minimal_api::RampClient {
     }
 }
 
+// Hashing here is only used to get a stable random value, so we are re-using what the crate
+// already has available
+fn idempotency_key(value: &str) -> String {
+    base64::encode(hash::hash(value.as_bytes()).as_ref(), base64::Variant::Original)
+}
+
+/// Issue a virtual card to the user if they do not have one, and keep the spending limit of
+/// their active cards in line with the limit for their department.
+async fn ensure_ramp_card(ramp: &ramp_minimal_api::RampClient, user: &User, config: &RampCardsConfig) -> Result<()> {
+    let limit = match config.limit_for(&user.department) {
+        Some(limit) => limit,
+        None => return Ok(()),
+    };
+
+    let cards: Vec<ramp_minimal_api::Card> = ramp
+        .cards()
+        .list_all(&ramp_minimal_api::ListCardsQuery {
+            user_id: Some(user.ramp_id.to_string()),
+            ..Default::default()
+        })
+        .await?
+        .into_iter()
+        .filter(|card| card.state != ramp_minimal_api::CardState::Terminated)
+        .collect();
+
+    if cards.is_empty() {
+        ramp.cards()
+            .deferred_create_virtual(&ramp_minimal_api::CreateVirtualCardDeferred {
+                idempotency_key: idempotency_key(&format!("{}-card", user.email)),
+                user_id: user.ramp_id.to_string(),
+                display_name: format!("{} Card", user.full_name()),
+                spending_restrictions: ramp_minimal_api::SpendingRestrictions {
+                    amount: limit.amount,
+                    interval: limit.interval.clone(),
+                    transaction_amount_limit: None,
+                    lock_date: None,
+                    categories: vec![],
+                    blocked_categories: vec![],
+                },
+            })
+            .await?;
+
+        info!("issued ramp card for `{}`", user.email);
+
+        return Ok(());
+    }
+
+    for card in cards {
+        // Suspended cards were locked on purpose, so leave them alone.
+        if card.state == ramp_minimal_api::CardState::Suspended {
+            continue;
+        }
+
+        let mut restrictions = match card.spending_restrictions {
+            Some(restrictions) => restrictions,
+            None => continue,
+        };
+
+        if restrictions.amount == limit.amount && restrictions.interval == limit.interval {
+            continue;
+        }
+
+        restrictions.amount = limit.amount;
+        restrictions.interval = limit.interval.clone();
+
+        ramp.cards()
+            .update(
+                &card.id,
+                &ramp_minimal_api::UpdateCard {
+                    display_name: None,
+                    spending_restrictions: Some(restrictions),
+                },
+            )
+            .await?;
+
+        info!(
+            "updated the spending limit of ramp card `{}` for `{}`",
+            card.display_name, user.email
+        );
+    }
+
+    Ok(())
+}
+
 #[async_trait]
 impl ProviderReadOps for ramp_minimal_api::RampClient {
     type ProviderUser = ramp_minimal_api::User;
@@ -565,7 +686,10 @@ impl ProviderWriteOps for gsuite_api::Client {
         {
             Ok(u) => {
                 // Update the user with the settings from the config for the user.
-                let gsuite_user = crate::gsuite::update_gsuite_user(&u, user, false, company).await;
+                let mut gsuite_user = crate::gsuite::update_gsuite_user(&u, user, false, company).await;
+                if let Some(org_unit_path) = config.google_workspace.org_unit_for(&user.department) {
+                    gsuite_user.org_unit_path = org_unit_path.to_string();
+                }
 
                 match self.users().update(&gsuite_user.id, &gsuite_user).await {
                     Ok(_) => {}
@@ -577,7 +701,7 @@ impl ProviderWriteOps for gsuite_api::Client {
                 crate::gsuite::update_user_aliases(self, &gsuite_user, user.aliases.clone(), company).await?;
 
                 // Add the user to their teams and groups.
-                crate::gsuite::update_user_google_groups(self, user, company).await?;
+                crate::gsuite::update_user_google_groups(db, self, user, company).await?;
 
                 info!("updated user `{}` in GSuite", user.id);
 
@@ -598,7 +722,10 @@ impl ProviderWriteOps for gsuite_api::Client {
 
         // The last argument here tell us to create a password!
         // Make sure it is set to true.
-        let gsuite_user = crate::gsuite::update_gsuite_user(&u, user, true, company).await;
+        let mut gsuite_user = crate::gsuite::update_gsuite_user(&u, user, true, company).await;
+        if let Some(org_unit_path) = config.google_workspace.org_unit_for(&user.department) {
+            gsuite_user.org_unit_path = org_unit_path.to_string();
+        }
 
         let new_gsuite_user = self.users().insert(&gsuite_user).await?;
 
@@ -609,7 +736,7 @@ impl ProviderWriteOps for gsuite_api::Client {
 
         crate::gsuite::update_user_aliases(self, &gsuite_user, user.aliases.clone(), company).await?;
 
-        crate::gsuite::update_user_google_groups(self, user, company).await?;
+        crate::gsuite::update_user_google_groups(db, self, user, company).await?;
 
         info!("created user `{}` in GSuite", user.email);
 
@@ -795,27 +922,10 @@ impl ProviderWriteOps for gsuite_api::Client {
         Ok(())
     }
 
-    async fn delete_user(&self, _db: &Database, _company: &Company, user: &User) -> Result<()> {
-        // First get the user from gsuite.
-        let mut gsuite_user = self
-            .users()
-            .get(
-                &user.email,
-                gsuite_api::types::DirectoryUsersListProjection::Full,
-                gsuite_api::types::ViewType::AdminView,
-            )
-            .await?;
-
-        // Set them to be suspended.
-        gsuite_user.suspended = true;
-        gsuite_user.suspension_reason = "No longer in config file.".to_string();
-
-        // Update the user.
-        self.users().update(&user.email, &gsuite_user).await?;
-
-        info!("suspended user `{}` from gsuite", user.email);
-
-        Ok(())
+    async fn delete_user(&self, db: &Database, company: &Company, user: &User) -> Result<()> {
+        // Without a config the user is only suspended, see `offboard_gsuite_user` for moving
+        // them to another organizational unit and transferring their files.
+        crate::gsuite::offboard_gsuite_user(self, db, company, user, &Default::default()).await
     }
 
     async fn delete_group(&self, company: &Company, group: &Group) -> Result<()> {
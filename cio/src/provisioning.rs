@@ -0,0 +1,879 @@
+/*!
+ * Setting up a new tenant.
+ *
+ * A company is created from the credentials it was given, then every integration is checked,
+ * the Airtable bases it is missing are created from the host company's bases, a default config
+ * is committed to its `configs` repo and the webhooks we listen on are registered. Anything that
+ * cannot be done from here, ie. an OAuth consent only an admin of the company can give, is
+ * reported as a step that needs a person to follow up on it.
+ */
+use std::env;
+
+use anyhow::{anyhow, Result};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_config::AppConfig,
+    companies::{Company, NewCompany},
+    configs::Config,
+    db::Database,
+    utils::create_or_update_file_in_github_repo,
+};
+
+/// The Airtable metadata API, used to read the schema of a base and to create bases.
+const AIRTABLE_META_ENDPOINT: &str = "https://api.airtable.com/v0/meta";
+
+/// The bases a company needs, by the suffix of their `airtable_base_id_` field and the name they
+/// are given. The `cio` base only exists for the host company.
+const AIRTABLE_BASES: &[(&str, &str)] = &[
+    ("customer_leads", "Customer Leads"),
+    ("directory", "Directory"),
+    ("misc", "Misc"),
+    ("roadmap", "Roadmap"),
+    ("hiring", "Hiring"),
+    ("shipments", "Shipments"),
+    ("finance", "Finance"),
+    ("swag", "Swag"),
+    ("assets", "Assets"),
+    ("travel", "Travel"),
+];
+
+/// The field types that can be created with the metadata API. Computed fields and links to other
+/// tables are left for a person to recreate.
+const COPYABLE_FIELD_TYPES: &[&str] = &[
+    "singleLineText",
+    "email",
+    "url",
+    "multilineText",
+    "number",
+    "percent",
+    "currency",
+    "singleSelect",
+    "multipleSelects",
+    "singleCollaborator",
+    "multipleCollaborators",
+    "date",
+    "dateTime",
+    "phoneNumber",
+    "multipleAttachments",
+    "checkbox",
+    "rating",
+    "richText",
+    "duration",
+    "barcode",
+];
+
+/// The field types Airtable allows as the primary field of a table.
+const PRIMARY_FIELD_TYPES: &[&str] = &[
+    "singleLineText",
+    "email",
+    "url",
+    "multilineText",
+    "number",
+    "percent",
+    "currency",
+    "date",
+    "dateTime",
+    "phoneNumber",
+    "duration",
+    "barcode",
+];
+
+/// The file in the `configs` repo the default config is written to.
+const DEFAULT_CONFIG_PATH: &str = "/configs/app.toml";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProvisioningStatus {
+    /// The step was done by this run.
+    Done,
+    /// Nothing had to be done, ie. the base or webhook already existed.
+    Skipped,
+    /// A person has to finish the step, the detail says what to do.
+    NeedsFollowUp,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+pub struct ProvisioningStep {
+    pub name: String,
+    pub status: ProvisioningStatus,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub detail: String,
+}
+
+/// What provisioning a company did and what is left to do by hand.
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct ProvisioningReport {
+    pub company: String,
+    pub steps: Vec<ProvisioningStep>,
+}
+
+impl ProvisioningReport {
+    fn push(&mut self, name: &str, status: ProvisioningStatus, detail: impl ToString) {
+        let detail = detail.to_string();
+        match status {
+            ProvisioningStatus::Failed | ProvisioningStatus::NeedsFollowUp => {
+                warn!("provisioning `{}`: {} {:?}: {}", self.company, name, status, detail)
+            }
+            _ => info!("provisioning `{}`: {} {:?}: {}", self.company, name, status, detail),
+        }
+
+        self.steps.push(ProvisioningStep {
+            name: name.to_string(),
+            status,
+            detail,
+        });
+    }
+
+    /// Record the outcome of a step that either worked or needs a person to look at it.
+    fn push_result(&mut self, name: &str, result: Result<String>, follow_up: &str) {
+        match result {
+            Ok(detail) => self.push(name, ProvisioningStatus::Done, detail),
+            Err(e) => self.push(name, ProvisioningStatus::NeedsFollowUp, format!("{}: {}", follow_up, e)),
+        }
+    }
+
+    /// The steps a person still has to finish.
+    pub fn follow_ups(&self) -> Vec<&ProvisioningStep> {
+        self.steps
+            .iter()
+            .filter(|step| {
+                matches!(
+                    step.status,
+                    ProvisioningStatus::NeedsFollowUp | ProvisioningStatus::Failed
+                )
+            })
+            .collect()
+    }
+
+    /// If the company is ready to use without anything left to do by hand.
+    pub fn is_complete(&self) -> bool {
+        self.follow_ups().is_empty()
+    }
+}
+
+impl Company {
+    /// Create a company from the credentials it was given and set up everything it needs. The
+    /// Airtable bases are copied from the bases of `host`, which is also the company the new one
+    /// is stored with.
+    ///
+    /// Every step is safe to run again, so a company can be provisioned a second time once the
+    /// follow ups of the first run are done.
+    pub async fn provision(db: &Database, host: &Company, new_company: &NewCompany) -> Result<ProvisioningReport> {
+        let mut new_company = new_company.clone();
+        new_company.cio_company_id = host.id;
+        let mut company = new_company.upsert(db).await?;
+
+        let mut report = ProvisioningReport {
+            company: company.name.to_string(),
+            steps: Default::default(),
+        };
+
+        company.validate_integrations(db, &mut report).await;
+        company.create_airtable_bases(db, host, &mut report).await;
+        company.seed_configs(&mut report).await;
+        company.register_webhooks(db, &mut report).await;
+
+        Ok(report)
+    }
+
+    async fn validate_integrations(&self, db: &Database, report: &mut ProvisioningReport) {
+        if self.github_app_installation_id == 0 || self.github_org.is_empty() {
+            report.push(
+                "GitHub",
+                ProvisioningStatus::NeedsFollowUp,
+                "install the GitHub app on the company's organization and set its installation id and org",
+            );
+        } else {
+            report.push_result("GitHub", self.check_github().await, "check the GitHub app installation");
+        }
+
+        report.push_result(
+            "Slack",
+            self.check_slack(db).await,
+            "connect Slack from the `/auth/slack/consent` endpoint",
+        );
+
+        let google = self
+            .authenticate_google_admin(db)
+            .await
+            .map(|_| format!("authenticated with the `{}` Google Workspace", self.gsuite_domain));
+        report.push_result(
+            "Google Workspace",
+            google,
+            "connect Google from the `/auth/google/consent` endpoint",
+        );
+
+        let docusign = self
+            .authenticate_docusign(db)
+            .await
+            .map(|_| "authenticated with DocuSign".to_string());
+        report.push_result(
+            "DocuSign",
+            docusign,
+            "connect DocuSign from the `/auth/docusign/consent` endpoint",
+        );
+
+        match self.authenticate_okta_apps() {
+            Some(okta) => {
+                let apps = okta
+                    .list_apps()
+                    .await
+                    .map(|apps| format!("found {} Okta apps", apps.len()))
+                    .map_err(Into::into);
+                report.push_result("Okta", apps, "check the Okta domain and api key");
+            }
+            None => report.push("Okta", ProvisioningStatus::Skipped, "no Okta domain or api key"),
+        }
+
+        match self.authenticate_checkr() {
+            Some(checkr) => {
+                let invitations = checkr
+                    .list_invitations()
+                    .await
+                    .map(|_| "listed Checkr invitations".to_string())
+                    .map_err(Into::into);
+                report.push_result("Checkr", invitations, "check the Checkr api key");
+            }
+            None => report.push("Checkr", ProvisioningStatus::Skipped, "no Checkr api key"),
+        }
+
+        if self.airtable_api_key.is_empty() {
+            report.push(
+                "Airtable",
+                ProvisioningStatus::NeedsFollowUp,
+                "set an Airtable api key with access to the company's workspace",
+            );
+        } else {
+            let bases = airtable_meta_request(reqwest::Method::GET, "bases", &self.airtable_api_key)
+                .send()
+                .await
+                .and_then(|resp| resp.error_for_status())
+                .map(|_| "the api key can list bases".to_string())
+                .map_err(Into::into);
+            report.push_result("Airtable", bases, "check the Airtable api key");
+        }
+    }
+
+    async fn check_github(&self) -> Result<String> {
+        let org = self.authenticate_github()?.orgs().get(&self.github_org).await?;
+
+        Ok(format!("the app can read the `{}` organization", org.login))
+    }
+
+    async fn check_slack(&self, db: &Database) -> Result<String> {
+        let users = self.authenticate_slack(db).await?.list_users().await?;
+
+        Ok(format!("found {} Slack users", users.len()))
+    }
+
+    /// Create the Airtable bases the company does not have yet, with the tables and fields of the
+    /// host company's bases.
+    async fn create_airtable_bases(&mut self, db: &Database, host: &Company, report: &mut ProvisioningReport) {
+        for (base, name) in AIRTABLE_BASES {
+            let step = format!("Airtable {} base", name);
+
+            if !airtable_base_id(self, base).map(|id| id.is_empty()).unwrap_or(false) {
+                report.push(&step, ProvisioningStatus::Skipped, "the company already has the base");
+                continue;
+            }
+
+            let template = airtable_base_id(host, base).cloned().unwrap_or_default();
+            if template.is_empty() {
+                report.push(
+                    &step,
+                    ProvisioningStatus::NeedsFollowUp,
+                    format!("`{}` has no base to copy, create one by hand", host.name),
+                );
+                continue;
+            }
+
+            if self.airtable_workspace_id.is_empty() || self.airtable_api_key.is_empty() {
+                report.push(
+                    &step,
+                    ProvisioningStatus::NeedsFollowUp,
+                    "set the Airtable workspace id and api key to create the base",
+                );
+                continue;
+            }
+
+            match self.create_airtable_base(host, &template, name).await {
+                Ok((id, dropped)) => {
+                    if let Some(field) = airtable_base_id_mut(self, base) {
+                        *field = id.to_string();
+                    }
+
+                    if dropped.is_empty() {
+                        report.push(&step, ProvisioningStatus::Done, format!("created base `{}`", id));
+                    } else {
+                        report.push(
+                            &step,
+                            ProvisioningStatus::NeedsFollowUp,
+                            format!(
+                                "created base `{}`, recreate these fields and the views by hand: {}",
+                                id,
+                                dropped.join(", ")
+                            ),
+                        );
+                    }
+                }
+                Err(e) => report.push(&step, ProvisioningStatus::Failed, e),
+            }
+        }
+
+        if let Err(e) = self.update(db).await {
+            report.push(
+                "Save Airtable bases",
+                ProvisioningStatus::Failed,
+                format!("saving the new base ids failed: {}", e),
+            );
+        }
+    }
+
+    async fn create_airtable_base(&self, host: &Company, template: &str, name: &str) -> Result<(String, Vec<String>)> {
+        let schema: AirtableTables = airtable_meta_request(
+            reqwest::Method::GET,
+            &format!("bases/{}/tables", template),
+            &host.airtable_api_key,
+        )
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+        let (tables, dropped) = template_tables(schema.tables);
+
+        let created: CreatedAirtableBase =
+            airtable_meta_request(reqwest::Method::POST, "bases", &self.airtable_api_key)
+                .json(&NewAirtableBase {
+                    name: format!("{} {}", self.name, name),
+                    workspace_id: self.airtable_workspace_id.to_string(),
+                    tables,
+                })
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+
+        Ok((created.id, dropped))
+    }
+
+    /// Commit a default config to the company's `configs` repo, unless it already has configs.
+    async fn seed_configs(&self, report: &mut ProvisioningReport) {
+        let step = "Default configs";
+
+        if self.github_app_installation_id == 0 || self.github_org.is_empty() {
+            report.push(
+                step,
+                ProvisioningStatus::NeedsFollowUp,
+                "install the GitHub app, then provision the company again",
+            );
+            return;
+        }
+
+        let github = match self.authenticate_github() {
+            Ok(github) => github,
+            Err(e) => {
+                report.push(step, ProvisioningStatus::Failed, e);
+                return;
+            }
+        };
+
+        let repo = match github.repos().get(&self.github_org, "configs").await {
+            Ok(repo) => repo,
+            Err(e) => {
+                report.push(
+                    step,
+                    ProvisioningStatus::NeedsFollowUp,
+                    format!(
+                        "create a `configs` repo in `{}` and give the GitHub app access to it: {}",
+                        self.github_org, e
+                    ),
+                );
+                return;
+            }
+        };
+
+        // An error here is a repo without the directory yet.
+        let existing = github
+            .repos()
+            .get_content_vec_entries(&self.github_org, "configs", "/configs/", &repo.default_branch)
+            .await
+            .unwrap_or_default();
+        if !existing.is_empty() {
+            report.push(
+                step,
+                ProvisioningStatus::Skipped,
+                "the `configs` repo already has configs",
+            );
+            return;
+        }
+
+        let config = match default_config() {
+            Ok(config) => config,
+            Err(e) => {
+                report.push(step, ProvisioningStatus::Failed, e);
+                return;
+            }
+        };
+
+        let result = create_or_update_file_in_github_repo(
+            &github,
+            &self.github_org,
+            "configs",
+            &repo.default_branch,
+            DEFAULT_CONFIG_PATH,
+            config.into_bytes(),
+        )
+        .await;
+
+        match result {
+            // The default config is only a starting point.
+            Ok(_) => report.push(
+                step,
+                ProvisioningStatus::NeedsFollowUp,
+                format!(
+                    "committed `{}`, fill in the users, groups and app config",
+                    DEFAULT_CONFIG_PATH
+                ),
+            ),
+            Err(e) => report.push(step, ProvisioningStatus::Failed, e),
+        }
+    }
+
+    async fn register_webhooks(&self, db: &Database, report: &mut ProvisioningReport) {
+        // GitHub sends the events of every repo the app is installed on to the app's webhook.
+        if self.github_app_installation_id == 0 {
+            report.push(
+                "GitHub webhooks",
+                ProvisioningStatus::NeedsFollowUp,
+                "events are delivered once the GitHub app is installed",
+            );
+        } else {
+            report.push(
+                "GitHub webhooks",
+                ProvisioningStatus::Skipped,
+                "events are delivered through the GitHub app installation",
+            );
+        }
+
+        match env::var("SHIPPO_WEBHOOKS_URL") {
+            Ok(url) => {
+                let result = ensure_shippo_webhook(&url).await;
+                report.push_webhook("Shippo webhooks", result);
+            }
+            Err(_) => report.push(
+                "Shippo webhooks",
+                ProvisioningStatus::NeedsFollowUp,
+                "set SHIPPO_WEBHOOKS_URL, or add the `track_updated` webhook in Shippo by hand",
+            ),
+        }
+
+        match env::var("DOCUSIGN_WEBHOOK_ENDPOINT") {
+            Ok(url) => {
+                let result = self.ensure_docusign_webhook(db, &url).await;
+                report.push_webhook("DocuSign webhooks", result);
+            }
+            Err(_) => report.push(
+                "DocuSign webhooks",
+                ProvisioningStatus::NeedsFollowUp,
+                "set DOCUSIGN_WEBHOOK_ENDPOINT, or add the Connect webhook in DocuSign by hand",
+            ),
+        }
+
+        if self.mailchimp_list_id.is_empty() {
+            report.push(
+                "MailChimp webhooks",
+                ProvisioningStatus::Skipped,
+                "no MailChimp audience",
+            );
+        } else {
+            match env::var("MAILCHIMP_WEBHOOKS_URL") {
+                Ok(url) => {
+                    let result = self.ensure_mailchimp_webhook(&url).await;
+                    report.push_webhook("MailChimp webhooks", result);
+                }
+                Err(_) => report.push(
+                    "MailChimp webhooks",
+                    ProvisioningStatus::NeedsFollowUp,
+                    "set MAILCHIMP_WEBHOOKS_URL, or add the audience webhook in MailChimp by hand",
+                ),
+            }
+        }
+
+        if self.shipbob_pat.is_empty() {
+            report.push(
+                "ShipBob webhooks",
+                ProvisioningStatus::Skipped,
+                "no ShipBob access token",
+            );
+        } else {
+            let result = self
+                .ensure_shipbob_webhooks()
+                .await
+                .map(|_| Some("registered the ShipBob webhooks".to_string()));
+            report.push_webhook("ShipBob webhooks", result);
+        }
+    }
+
+    /// Returns `None` when the webhook already existed.
+    async fn ensure_docusign_webhook(&self, db: &Database, url: &str) -> Result<Option<String>> {
+        let ds = self.authenticate_docusign(db).await?;
+
+        let webhooks = ds.list_webhooks().await?;
+        if webhooks.iter().any(|webhook| webhook.url_to_publish_to == url) {
+            return Ok(None);
+        }
+
+        let webhook = ds.create_connect_webhook(url).await?;
+
+        Ok(Some(format!("created Connect webhook `{}`", webhook.connect_id)))
+    }
+
+    /// Returns `None` when the webhook already existed.
+    async fn ensure_mailchimp_webhook(&self, url: &str) -> Result<Option<String>> {
+        let mailchimp = self.authenticate_mailchimp().await?;
+
+        let webhooks = mailchimp.list_webhooks(&self.mailchimp_list_id).await?;
+        if webhooks.iter().any(|webhook| webhook.url == url) {
+            return Ok(None);
+        }
+
+        let webhook = mailchimp
+            .create_webhook(
+                &self.mailchimp_list_id,
+                &mailchimp_minimal_api::NewWebhook {
+                    url: url.to_string(),
+                    events: mailchimp_minimal_api::WebhookEvents {
+                        subscribe: true,
+                        unsubscribe: true,
+                        profile: true,
+                        cleaned: true,
+                        upemail: true,
+                        campaign: false,
+                    },
+                    sources: mailchimp_minimal_api::WebhookSources {
+                        user: true,
+                        admin: true,
+                        api: false,
+                    },
+                },
+            )
+            .await?;
+
+        Ok(Some(format!("created audience webhook `{}`", webhook.id)))
+    }
+}
+
+impl ProvisioningReport {
+    fn push_webhook(&mut self, name: &str, result: Result<Option<String>>) {
+        match result {
+            Ok(Some(detail)) => self.push(name, ProvisioningStatus::Done, detail),
+            Ok(None) => self.push(name, ProvisioningStatus::Skipped, "the webhook already exists"),
+            Err(e) => self.push(name, ProvisioningStatus::Failed, e),
+        }
+    }
+}
+
+/// Shippo is shared by every company, so there is one `track_updated` webhook for all of them.
+/// Returns `None` when it already existed.
+async fn ensure_shippo_webhook(url: &str) -> Result<Option<String>> {
+    let shippo = shippo::Shippo::new_from_env();
+
+    let webhooks = shippo.list_webhooks().await?;
+    if webhooks
+        .iter()
+        .any(|webhook| webhook.event == "track_updated" && webhook.url == url)
+    {
+        return Ok(None);
+    }
+
+    let webhook = shippo.create_webhook("track_updated", url).await?;
+
+    Ok(Some(format!("created webhook `{}`", webhook.object_id)))
+}
+
+fn airtable_base_id<'a>(company: &'a Company, base: &str) -> Option<&'a String> {
+    Some(match base {
+        "customer_leads" => &company.airtable_base_id_customer_leads,
+        "directory" => &company.airtable_base_id_directory,
+        "misc" => &company.airtable_base_id_misc,
+        "roadmap" => &company.airtable_base_id_roadmap,
+        "hiring" => &company.airtable_base_id_hiring,
+        "shipments" => &company.airtable_base_id_shipments,
+        "finance" => &company.airtable_base_id_finance,
+        "swag" => &company.airtable_base_id_swag,
+        "assets" => &company.airtable_base_id_assets,
+        "travel" => &company.airtable_base_id_travel,
+        _ => return None,
+    })
+}
+
+fn airtable_base_id_mut<'a>(company: &'a mut Company, base: &str) -> Option<&'a mut String> {
+    Some(match base {
+        "customer_leads" => &mut company.airtable_base_id_customer_leads,
+        "directory" => &mut company.airtable_base_id_directory,
+        "misc" => &mut company.airtable_base_id_misc,
+        "roadmap" => &mut company.airtable_base_id_roadmap,
+        "hiring" => &mut company.airtable_base_id_hiring,
+        "shipments" => &mut company.airtable_base_id_shipments,
+        "finance" => &mut company.airtable_base_id_finance,
+        "swag" => &mut company.airtable_base_id_swag,
+        "assets" => &mut company.airtable_base_id_assets,
+        "travel" => &mut company.airtable_base_id_travel,
+        _ => return None,
+    })
+}
+
+fn airtable_meta_request(method: reqwest::Method, path: &str, api_key: &str) -> reqwest::RequestBuilder {
+    reqwest::Client::new()
+        .request(method, format!("{}/{}", AIRTABLE_META_ENDPOINT, path))
+        .bearer_auth(api_key)
+}
+
+#[derive(Debug, Deserialize)]
+struct AirtableTables {
+    tables: Vec<AirtableTable>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+struct AirtableTable {
+    name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    description: String,
+    #[serde(default, rename = "primaryFieldId", skip_serializing)]
+    primary_field_id: String,
+    fields: Vec<AirtableField>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+struct AirtableField {
+    #[serde(default, skip_serializing)]
+    id: String,
+    name: String,
+    #[serde(rename = "type")]
+    type_: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    options: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+struct NewAirtableBase {
+    name: String,
+    #[serde(rename = "workspaceId")]
+    workspace_id: String,
+    tables: Vec<AirtableTable>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CreatedAirtableBase {
+    id: String,
+}
+
+/// Turn the tables of a template base into tables that can be created in a new base. Returns the
+/// fields that could not be copied as `Table.Field`.
+fn template_tables(tables: Vec<AirtableTable>) -> (Vec<AirtableTable>, Vec<String>) {
+    let mut dropped = Vec::new();
+
+    let tables = tables
+        .into_iter()
+        .map(|mut table| {
+            // The first field of a new table is its primary field.
+            if let Some(i) = table.fields.iter().position(|f| f.id == table.primary_field_id) {
+                let primary = table.fields.remove(i);
+                table.fields.insert(0, primary);
+            }
+
+            let mut fields = Vec::new();
+            for (i, mut field) in table.fields.into_iter().enumerate() {
+                let copyable = COPYABLE_FIELD_TYPES.contains(&field.type_.as_str());
+
+                if i == 0 && !PRIMARY_FIELD_TYPES.contains(&field.type_.as_str()) {
+                    // A table needs a primary field, so it is created as text.
+                    dropped.push(format!("{}.{} (created as text)", table.name, field.name));
+                    field.type_ = "singleLineText".to_string();
+                    field.options = None;
+                } else if !copyable {
+                    dropped.push(format!("{}.{}", table.name, field.name));
+                    continue;
+                }
+
+                field.options = field.options.map(template_field_options);
+                if field.type_ == "multipleAttachments" {
+                    // Airtable does not accept options for attachments.
+                    field.options = None;
+                }
+
+                fields.push(field);
+            }
+
+            AirtableTable {
+                name: table.name,
+                description: table.description,
+                primary_field_id: String::new(),
+                fields,
+            }
+        })
+        .collect();
+
+    (tables, dropped)
+}
+
+/// Remove the ids of the choices of a select field, new ones are made for the new base.
+fn template_field_options(mut options: serde_json::Value) -> serde_json::Value {
+    if let Some(choices) = options.get_mut("choices").and_then(|c| c.as_array_mut()) {
+        for choice in choices {
+            if let Some(choice) = choice.as_object_mut() {
+                choice.remove("id");
+            }
+        }
+    }
+
+    options
+}
+
+/// The config a new company starts with, with every section of the app config at its default.
+fn default_config() -> Result<String> {
+    let config = Config {
+        app_config: AppConfig::default(),
+        ..Default::default()
+    };
+
+    let mut value = toml::Value::try_from(&config)?;
+    let table = value
+        .as_table_mut()
+        .ok_or_else(|| anyhow!("the config did not serialize to a table"))?;
+    // Leave out the empty users, groups and such, so that the file only has what to fill in.
+    let empty: Vec<String> = table
+        .iter()
+        .filter(|(_, v)| matches!(v, toml::Value::Table(t) if t.is_empty()))
+        .map(|(k, _)| k.to_string())
+        .collect();
+    for key in empty {
+        table.remove(&key);
+    }
+
+    Ok(toml::to_string(&value)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{
+        default_config, template_tables, AirtableTable, ProvisioningReport, ProvisioningStatus, ProvisioningStep,
+    };
+    use crate::configs::Config;
+
+    fn field(id: &str, name: &str, type_: &str) -> serde_json::Value {
+        json!({ "id": id, "name": name, "type": type_ })
+    }
+
+    #[test]
+    fn test_template_tables() {
+        let tables: Vec<AirtableTable> = serde_json::from_value(json!([
+            {
+                "id": "tbl1",
+                "name": "Applicants",
+                "primaryFieldId": "fld2",
+                "fields": [
+                    field("fld1", "Email", "email"),
+                    field("fld2", "Name", "singleLineText"),
+                    field("fld3", "Score", "formula"),
+                    {
+                        "id": "fld4",
+                        "name": "Status",
+                        "type": "singleSelect",
+                        "options": { "choices": [{ "id": "sel1", "name": "Hired", "color": "greenBright" }] }
+                    },
+                    field("fld5", "Interviews", "multipleRecordLinks"),
+                ],
+                "views": [{ "id": "viw1", "name": "Grid view", "type": "grid" }]
+            },
+            {
+                "id": "tbl2",
+                "name": "Interviews",
+                "primaryFieldId": "fld6",
+                "fields": [field("fld6", "Summary", "formula"), field("fld7", "Done", "checkbox")]
+            }
+        ]))
+        .unwrap();
+
+        let (tables, dropped) = template_tables(tables);
+
+        assert_eq!(
+            vec![
+                "Applicants.Score",
+                "Applicants.Interviews",
+                "Interviews.Summary (created as text)"
+            ],
+            dropped
+        );
+
+        let created = serde_json::to_value(&tables).unwrap();
+        assert_eq!(
+            json!([
+                {
+                    "name": "Applicants",
+                    "fields": [
+                        { "name": "Name", "type": "singleLineText" },
+                        { "name": "Email", "type": "email" },
+                        {
+                            "name": "Status",
+                            "type": "singleSelect",
+                            "options": { "choices": [{ "name": "Hired", "color": "greenBright" }] }
+                        }
+                    ]
+                },
+                {
+                    "name": "Interviews",
+                    "fields": [
+                        { "name": "Summary", "type": "singleLineText" },
+                        { "name": "Done", "type": "checkbox" }
+                    ]
+                }
+            ]),
+            created
+        );
+    }
+
+    #[test]
+    fn test_default_config_parses() {
+        let config: Config = toml::from_str(&default_config().unwrap()).unwrap();
+
+        assert!(config.users.is_empty());
+        assert!(config.groups.is_empty());
+    }
+
+    #[test]
+    fn test_follow_ups() {
+        let step = |name: &str, status| ProvisioningStep {
+            name: name.to_string(),
+            status,
+            detail: String::new(),
+        };
+
+        let mut report = ProvisioningReport {
+            company: "Oxide".to_string(),
+            steps: vec![
+                step("GitHub", ProvisioningStatus::Done),
+                step("Okta", ProvisioningStatus::Skipped),
+            ],
+        };
+        assert!(report.is_complete());
+
+        report.steps.push(step("Slack", ProvisioningStatus::NeedsFollowUp));
+        report.steps.push(step("DocuSign webhooks", ProvisioningStatus::Failed));
+
+        let follow_ups: Vec<&str> = report.follow_ups().iter().map(|step| step.name.as_str()).collect();
+        assert_eq!(vec!["Slack", "DocuSign webhooks"], follow_ups);
+        assert!(!report.is_complete());
+    }
+}
@@ -0,0 +1,531 @@
+/*!
+ * Numbers on how applicants move through the recruiting pipeline.
+ *
+ * The funnel is computed over the applicants who applied in a window of time: how long they
+ * spent in each stage, how many from each source made it to interviews, an offer and a hire, how
+ * many offers were accepted, and how many interviews each interviewer did. The same numbers are
+ * served by the API and posted to Slack once a week.
+ *
+ * Time in stage comes from the timestamps kept on each applicant, so an applicant only counts
+ * toward a stage once they have left it.
+ */
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Duration, Utc};
+use diesel::{ExpressionMethods, Insertable, QueryDsl};
+use log::info;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    app_config::ApplyConfig,
+    applicant_status::Status,
+    applicants::Applicant,
+    companies::Company,
+    db::Database,
+    interviews::ApplicantInterview,
+    schema::{applicant_interviews, applicant_sources, applicants},
+};
+
+/// What applicants without a recorded source are counted under.
+const UNKNOWN_SOURCE: &str = "unknown";
+
+#[derive(Insertable)]
+#[diesel(table_name = applicant_sources)]
+struct NewApplicantSource {
+    applicant_id: i32,
+    source: String,
+    cio_company_id: i32,
+}
+
+/// Record where the applicant heard about the role. The first source recorded is kept, so
+/// applying again does not change it.
+pub async fn record_applicant_source(db: &Database, applicant: &Applicant, source: &str) -> Result<()> {
+    diesel::insert_into(applicant_sources::table)
+        .values(NewApplicantSource {
+            applicant_id: applicant.id,
+            source: source.trim().to_lowercase(),
+            cio_company_id: applicant.cio_company_id,
+        })
+        .on_conflict(applicant_sources::applicant_id)
+        .do_nothing()
+        .execute_async(db.pool())
+        .await?;
+
+    Ok(())
+}
+
+/// The window of applications to compute the funnel over, as query parameters. Defaults to the
+/// last 90 days.
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize)]
+pub struct RecruitingFunnelQuery {
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct RecruitingFunnel {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    /// The number of applicants who applied in the window.
+    pub applicants: usize,
+    pub time_in_stage: Vec<StageTime>,
+    pub sources: Vec<SourceConversion>,
+    pub offers: OfferAcceptance,
+    pub interviewer_load: Vec<InterviewerLoad>,
+}
+
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct StageTime {
+    /// The stage, ie. `triage`.
+    pub stage: String,
+    /// The number of applicants who have been through the stage.
+    pub applicants: usize,
+    pub median_days: f64,
+    pub average_days: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct SourceConversion {
+    pub source: String,
+    pub applicants: usize,
+    pub interviewed: usize,
+    pub offered: usize,
+    pub hired: usize,
+    /// The share of applicants from the source who were interviewed.
+    pub interview_rate: f64,
+    /// The share of applicants from the source who were hired.
+    pub hire_rate: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct OfferAcceptance {
+    pub offers: usize,
+    pub accepted: usize,
+    pub declined: usize,
+    /// Offers that were neither accepted nor declined yet.
+    pub pending: usize,
+    /// The share of decided offers that were accepted.
+    pub acceptance_rate: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct InterviewerLoad {
+    /// The email of the interviewer.
+    pub interviewer: String,
+    pub interviews: usize,
+    pub hours: f64,
+}
+
+fn status(applicant: &Applicant) -> Status {
+    applicant.status.parse().unwrap_or_default()
+}
+
+fn was_interviewed(applicant: &Applicant) -> bool {
+    applicant.interviews_started.is_some()
+        || matches!(
+            status(applicant),
+            Status::Interviewing | Status::GivingOffer | Status::Onboarding | Status::Hired
+        )
+}
+
+fn was_offered(applicant: &Applicant) -> bool {
+    applicant.offer_created.is_some()
+        || matches!(
+            status(applicant),
+            Status::GivingOffer | Status::Onboarding | Status::Hired
+        )
+}
+
+fn was_hired(applicant: &Applicant) -> bool {
+    matches!(status(applicant), Status::Onboarding | Status::Hired)
+}
+
+fn ratio(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+fn stage_time(stage: &str, mut days: Vec<f64>) -> StageTime {
+    days.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let median_days = match days.len() {
+        0 => 0.0,
+        n if n % 2 == 0 => (days[n / 2 - 1] + days[n / 2]) / 2.0,
+        n => days[n / 2],
+    };
+    let average_days = if days.is_empty() {
+        0.0
+    } else {
+        days.iter().sum::<f64>() / days.len() as f64
+    };
+
+    StageTime {
+        stage: stage.to_string(),
+        applicants: days.len(),
+        median_days,
+        average_days,
+    }
+}
+
+fn days_between(start: Option<DateTime<Utc>>, end: Option<DateTime<Utc>>) -> Option<f64> {
+    match (start, end) {
+        (Some(start), Some(end)) if end >= start => Some((end - start).num_minutes() as f64 / (60.0 * 24.0)),
+        _ => None,
+    }
+}
+
+fn time_in_stage(applicants: &[Applicant]) -> Vec<StageTime> {
+    // Each stage is bounded by the timestamps of entering it and of entering the next one.
+    let stages: [(&str, fn(&Applicant) -> Option<f64>); 4] = [
+        ("triage", |a| days_between(Some(a.submitted_time), a.interviews_started)),
+        ("interviewing", |a| {
+            days_between(a.interviews_started, a.interviews_completed)
+        }),
+        ("decision", |a| days_between(a.interviews_completed, a.offer_created)),
+        ("offer", |a| days_between(a.offer_created, a.offer_completed)),
+    ];
+
+    stages
+        .iter()
+        .map(|(stage, days)| stage_time(stage, applicants.iter().filter_map(days).collect()))
+        .collect()
+}
+
+fn source_conversions(applicants: &[Applicant], sources: &BTreeMap<i32, String>) -> Vec<SourceConversion> {
+    let mut by_source: BTreeMap<&str, Vec<&Applicant>> = BTreeMap::new();
+    for applicant in applicants {
+        let source = sources
+            .get(&applicant.id)
+            .map(|source| source.as_str())
+            .unwrap_or(UNKNOWN_SOURCE);
+        by_source.entry(source).or_default().push(applicant);
+    }
+
+    by_source
+        .into_iter()
+        .map(|(source, applicants)| {
+            let interviewed = applicants.iter().filter(|a| was_interviewed(a)).count();
+            let hired = applicants.iter().filter(|a| was_hired(a)).count();
+
+            SourceConversion {
+                source: source.to_string(),
+                applicants: applicants.len(),
+                interviewed,
+                offered: applicants.iter().filter(|a| was_offered(a)).count(),
+                hired,
+                interview_rate: ratio(interviewed, applicants.len()),
+                hire_rate: ratio(hired, applicants.len()),
+            }
+        })
+        .collect()
+}
+
+fn offer_acceptance(applicants: &[Applicant]) -> OfferAcceptance {
+    let mut offers = OfferAcceptance::default();
+    for applicant in applicants.iter().filter(|a| was_offered(a)) {
+        offers.offers += 1;
+        if was_hired(applicant) || applicant.offer_completed.is_some() {
+            offers.accepted += 1;
+        } else if matches!(status(applicant), Status::Declined | Status::Withdrawn) {
+            offers.declined += 1;
+        } else {
+            offers.pending += 1;
+        }
+    }
+    offers.acceptance_rate = ratio(offers.accepted, offers.accepted + offers.declined);
+
+    offers
+}
+
+fn interviewer_load(interviews: &[ApplicantInterview]) -> Vec<InterviewerLoad> {
+    let mut by_interviewer: BTreeMap<String, (usize, Duration)> = BTreeMap::new();
+    for interview in interviews {
+        for interviewer in &interview.interviewers {
+            let load = by_interviewer
+                .entry(interviewer.to_lowercase())
+                .or_insert((0, Duration::zero()));
+            load.0 += 1;
+            load.1 = load.1 + (interview.end_time - interview.start_time);
+        }
+    }
+
+    let mut load = by_interviewer
+        .into_iter()
+        .map(|(interviewer, (interviews, time))| InterviewerLoad {
+            interviewer,
+            interviews,
+            hours: time.num_minutes() as f64 / 60.0,
+        })
+        .collect::<Vec<_>>();
+    // The busiest interviewers first.
+    load.sort_by(|a, b| b.interviews.cmp(&a.interviews).then(a.interviewer.cmp(&b.interviewer)));
+
+    load
+}
+
+/// Compute the recruiting funnel over the applicants who applied in the window.
+pub async fn recruiting_funnel(
+    db: &Database,
+    company: &Company,
+    query: &RecruitingFunnelQuery,
+) -> Result<RecruitingFunnel> {
+    let until = query.until.unwrap_or_else(Utc::now);
+    let since = query.since.unwrap_or(until - Duration::days(90));
+
+    let applicants = applicants::dsl::applicants
+        .filter(applicants::dsl::cio_company_id.eq(company.id))
+        .filter(applicants::dsl::submitted_time.ge(since))
+        .filter(applicants::dsl::submitted_time.lt(until))
+        .load_async::<Applicant>(db.pool())
+        .await?;
+
+    let sources = applicant_sources::dsl::applicant_sources
+        .filter(applicant_sources::dsl::cio_company_id.eq(company.id))
+        .filter(applicant_sources::dsl::applicant_id.eq_any(applicants.iter().map(|a| a.id).collect::<Vec<_>>()))
+        .select((applicant_sources::dsl::applicant_id, applicant_sources::dsl::source))
+        .load_async::<(i32, String)>(db.pool())
+        .await?
+        .into_iter()
+        .collect::<BTreeMap<_, _>>();
+
+    // Interviewer load is over the interviews held in the window, whoever they were with.
+    let interviews = applicant_interviews::dsl::applicant_interviews
+        .filter(applicant_interviews::dsl::cio_company_id.eq(company.id))
+        .filter(applicant_interviews::dsl::start_time.ge(since))
+        .filter(applicant_interviews::dsl::start_time.lt(until))
+        .load_async::<ApplicantInterview>(db.pool())
+        .await?;
+
+    Ok(RecruitingFunnel {
+        since,
+        until,
+        applicants: applicants.len(),
+        time_in_stage: time_in_stage(&applicants),
+        sources: source_conversions(&applicants, &sources),
+        offers: offer_acceptance(&applicants),
+        interviewer_load: interviewer_load(&interviews),
+    })
+}
+
+fn summary_text(funnel: &RecruitingFunnel) -> String {
+    let stages = funnel
+        .time_in_stage
+        .iter()
+        .map(|stage| {
+            format!(
+                "• {}: median `{:.1}` days over {} applicants",
+                stage.stage, stage.median_days, stage.applicants
+            )
+        })
+        .collect::<Vec<_>>();
+    let sources = funnel
+        .sources
+        .iter()
+        .map(|source| {
+            format!(
+                "• {}: {} applied, {:.0}% interviewed, {} offered, {} hired",
+                source.source,
+                source.applicants,
+                source.interview_rate * 100.0,
+                source.offered,
+                source.hired
+            )
+        })
+        .collect::<Vec<_>>();
+    let interviewers = funnel
+        .interviewer_load
+        .iter()
+        .take(5)
+        .map(|load| {
+            format!(
+                "• {}: {} interviews, {:.1} hours",
+                load.interviewer, load.interviews, load.hours
+            )
+        })
+        .collect::<Vec<_>>();
+
+    format!(
+        "*{} applicants* applied between {} and {}.\n\n*Time in stage*\n{}\n\n*By source*\n{}\n\n*Offers*\n{} made, \
+         {} accepted, {} declined, {} pending: `{:.0}%` acceptance\n\n*Busiest interviewers*\n{}",
+        funnel.applicants,
+        funnel.since.format("%b %-d"),
+        funnel.until.format("%b %-d"),
+        stages.join("\n"),
+        sources.join("\n"),
+        funnel.offers.offers,
+        funnel.offers.accepted,
+        funnel.offers.declined,
+        funnel.offers.pending,
+        funnel.offers.acceptance_rate * 100.0,
+        interviewers.join("\n")
+    )
+}
+
+/// Post the recruiting funnel for the last 90 days to Slack.
+pub async fn send_recruiting_summary(db: &Database, company: &Company, config: &ApplyConfig) -> Result<()> {
+    let funnel = recruiting_funnel(db, company, &RecruitingFunnelQuery::default()).await?;
+
+    let channel = if config.funnel_summary_slack_channel.is_empty() {
+        company.slack_channel_applicants.to_string()
+    } else {
+        config.funnel_summary_slack_channel.to_string()
+    };
+
+    let msg = FormattedMessage {
+        channel,
+        blocks: vec![
+            MessageBlock {
+                block_type: MessageBlockType::Header,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::PlainText,
+                    text: "Recruiting funnel".to_string(),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+            MessageBlock {
+                block_type: MessageBlockType::Section,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text: summary_text(&funnel),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+        ],
+        attachments: Default::default(),
+    };
+
+    company.post_to_slack_channel(db, &msg).await?;
+
+    info!(
+        "posted the recruiting funnel summary for {} applicants",
+        funnel.applicants
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+
+    use super::{interviewer_load, offer_acceptance, source_conversions, stage_time, time_in_stage};
+    use crate::{applicants::tests::mock_applicant, interviews::ApplicantInterview};
+
+    fn mock_interview(start_time: DateTime<Utc>, minutes: i64, interviewers: &[&str]) -> ApplicantInterview {
+        ApplicantInterview {
+            id: 1,
+            start_time,
+            end_time: start_time + Duration::minutes(minutes),
+            name: "Jane Doe".to_string(),
+            email: "jane@example.com".to_string(),
+            interviewers: interviewers.iter().map(|i| i.to_string()).collect(),
+            google_event_id: String::default(),
+            event_link: String::default(),
+            zoom_meeting_id: String::default(),
+            zoom_join_url: String::default(),
+            applicant: Default::default(),
+            cio_company_id: 1,
+            airtable_record_id: String::default(),
+        }
+    }
+
+    #[test]
+    fn test_stage_time() {
+        let stage = stage_time("triage", vec![4.0, 1.0, 2.0, 9.0]);
+        assert_eq!(4, stage.applicants);
+        assert_eq!(3.0, stage.median_days);
+        assert_eq!(4.0, stage.average_days);
+
+        assert_eq!(0.0, stage_time("triage", vec![]).median_days);
+    }
+
+    #[test]
+    fn test_time_in_stage() {
+        let submitted = Utc.ymd(2023, 3, 1).and_hms(12, 0, 0);
+
+        let mut interviewing = mock_applicant();
+        interviewing.submitted_time = submitted;
+        interviewing.interviews_started = Some(submitted + Duration::days(3));
+
+        // Has not left triage yet.
+        let mut triaging = mock_applicant();
+        triaging.submitted_time = submitted;
+
+        let stages = time_in_stage(&[interviewing, triaging]);
+        assert_eq!("triage", stages[0].stage);
+        assert_eq!(1, stages[0].applicants);
+        assert_eq!(3.0, stages[0].median_days);
+        assert_eq!(0, stages[1].applicants);
+    }
+
+    #[test]
+    fn test_source_conversions_and_offers() {
+        let mut referred = mock_applicant();
+        referred.id = 1;
+        referred.status = "Hired".to_string();
+        referred.interviews_started = Some(Utc::now());
+        referred.offer_created = Some(Utc::now());
+
+        let mut declined = mock_applicant();
+        declined.id = 2;
+        declined.status = "Declined".to_string();
+        declined.offer_created = Some(Utc::now());
+
+        let mut unknown = mock_applicant();
+        unknown.id = 3;
+        unknown.status = "Needs to be triaged".to_string();
+
+        let sources = BTreeMap::from([(1, "referral".to_string()), (2, "referral".to_string())]);
+        let applicants = vec![referred, declined, unknown];
+
+        let conversions = source_conversions(&applicants, &sources);
+        assert_eq!(
+            vec![("referral", 2, 1, 2, 1), ("unknown", 1, 0, 0, 0)],
+            conversions
+                .iter()
+                .map(|c| (c.source.as_str(), c.applicants, c.interviewed, c.offered, c.hired))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(0.5, conversions[0].hire_rate);
+
+        let offers = offer_acceptance(&applicants);
+        assert_eq!(
+            (2, 1, 1, 0),
+            (offers.offers, offers.accepted, offers.declined, offers.pending)
+        );
+        assert_eq!(0.5, offers.acceptance_rate);
+    }
+
+    #[test]
+    fn test_interviewer_load() {
+        let start = Utc.ymd(2023, 3, 1).and_hms(17, 0, 0);
+
+        let load = interviewer_load(&[
+            mock_interview(start, 90, &["a@example.com", "B@example.com"]),
+            mock_interview(start, 60, &["b@example.com"]),
+        ]);
+        assert_eq!(
+            vec![("b@example.com", 2, 2.5), ("a@example.com", 1, 1.5)],
+            load.iter()
+                .map(|l| (l.interviewer.as_str(), l.interviews, l.hours))
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,307 @@
+/*!
+ * Redaction of personal data from logs, Sentry events and stored webhook bodies.
+ *
+ * Data is redacted by class, ie. email addresses. A class names the fields whose values are
+ * redacted outright, wherever they appear in structured data, and the patterns that are redacted
+ * wherever they appear in text. Each class can be limited to some of the places data is redacted
+ * from, and turned off, in the `redaction` section of the app config.
+ *
+ * The redactor is global so that the logger and the Sentry client, which are set up before the
+ * app config is read, use the config once it is. Until then the built-in classes are used.
+ */
+use std::{
+    borrow::Cow,
+    sync::{Arc, RwLock},
+};
+
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::app_config::{DataClassConfig, RedactionConfig};
+
+/// What redacted values are replaced with.
+pub const REDACTED: &str = "[redacted]";
+
+/// The places data is redacted from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactionTarget {
+    /// Everything that is logged, which is also what Sentry gets as breadcrumbs.
+    Logs,
+    /// Events sent to Sentry, ie. errors.
+    Sentry,
+    /// The bodies of inbound webhooks, once they have been processed.
+    WebhookEvents,
+}
+
+impl RedactionTarget {
+    pub fn all() -> Vec<RedactionTarget> {
+        vec![
+            RedactionTarget::Logs,
+            RedactionTarget::Sentry,
+            RedactionTarget::WebhookEvents,
+        ]
+    }
+}
+
+/// The classes that are redacted without being configured, as their name, fields and patterns.
+const BUILT_IN_CLASSES: [(&str, &[&str], &[&str]); 2] = [
+    (
+        "email",
+        &[
+            "email",
+            "applicant_email",
+            "recipient_email",
+            "recovery_email",
+            "personal_email",
+            "email_address",
+        ],
+        &[r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}"],
+    ),
+    (
+        "tracking_number",
+        &["tracking_number", "tracking_code", "tracking_link", "tracking_url"],
+        // UPS, then FedEx and USPS, which are only digits.
+        &[r"\b1Z[0-9A-Z]{16}\b", r"\b(?:\d{12}|\d{15}|\d{20,22})\b"],
+    ),
+];
+
+#[derive(Debug)]
+struct DataClass {
+    fields: Vec<String>,
+    patterns: Vec<Regex>,
+    targets: Vec<RedactionTarget>,
+}
+
+#[derive(Debug)]
+pub struct Redactor {
+    classes: Vec<DataClass>,
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        // The built-in patterns are known to compile.
+        Redactor::new(&RedactionConfig::default()).unwrap()
+    }
+}
+
+impl Redactor {
+    /// Build a redactor from the config, which is merged into the built-in classes. Fails when
+    /// a pattern is not a valid regular expression.
+    pub fn new(config: &RedactionConfig) -> Result<Self> {
+        let mut configs = BUILT_IN_CLASSES
+            .iter()
+            .map(|(name, fields, patterns)| {
+                let mut class = config.classes.get(*name).cloned().unwrap_or_default();
+                class.fields.extend(fields.iter().map(|field| field.to_string()));
+                class
+                    .patterns
+                    .extend(patterns.iter().map(|pattern| pattern.to_string()));
+                (name.to_string(), class)
+            })
+            .collect::<Vec<(String, DataClassConfig)>>();
+        for (name, class) in &config.classes {
+            if !BUILT_IN_CLASSES.iter().any(|(built_in, _, _)| built_in == name) {
+                configs.push((name.to_string(), class.clone()));
+            }
+        }
+
+        let mut classes = vec![];
+        for (name, class) in configs {
+            if !class.enabled {
+                continue;
+            }
+
+            let patterns = class
+                .patterns
+                .iter()
+                .map(|pattern| Regex::new(pattern).with_context(|| format!("invalid pattern for redacting `{}`", name)))
+                .collect::<Result<Vec<_>>>()?;
+
+            classes.push(DataClass {
+                fields: class.fields.iter().map(|field| field.to_lowercase()).collect(),
+                patterns,
+                targets: class.targets,
+            });
+        }
+
+        Ok(Redactor { classes })
+    }
+
+    fn classes(&self, target: RedactionTarget) -> impl Iterator<Item = &DataClass> {
+        self.classes.iter().filter(move |class| class.targets.contains(&target))
+    }
+
+    /// Whether the values of fields named `field` are redacted outright.
+    pub fn is_redacted_field(&self, target: RedactionTarget, field: &str) -> bool {
+        let field = field.to_lowercase();
+        self.classes(target).any(|class| class.fields.contains(&field))
+    }
+
+    /// Redact the values that match a pattern from text.
+    pub fn redact_str<'a>(&self, target: RedactionTarget, text: &'a str) -> Cow<'a, str> {
+        let mut text = Cow::Borrowed(text);
+        for pattern in self.classes(target).flat_map(|class| class.patterns.iter()) {
+            let redacted = match pattern.replace_all(&text, REDACTED) {
+                Cow::Owned(redacted) => redacted,
+                Cow::Borrowed(_) => continue,
+            };
+            text = Cow::Owned(redacted);
+        }
+
+        text
+    }
+
+    /// Redact the value of the field named `field`, which is either redacted outright or the
+    /// same as any other value.
+    pub fn redact_field(&self, target: RedactionTarget, field: &str, value: &mut serde_json::Value) {
+        if value.is_null() {
+            return;
+        }
+
+        if self.is_redacted_field(target, field) {
+            *value = serde_json::Value::String(REDACTED.to_string());
+        } else {
+            self.redact_json(target, value);
+        }
+    }
+
+    /// Redact the fields and the text of structured data, however deeply it is nested.
+    pub fn redact_json(&self, target: RedactionTarget, value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::String(text) => {
+                let redacted = match self.redact_str(target, text) {
+                    Cow::Owned(redacted) => redacted,
+                    Cow::Borrowed(_) => return,
+                };
+                *text = redacted;
+            }
+            serde_json::Value::Array(values) => {
+                for value in values {
+                    self.redact_json(target, value);
+                }
+            }
+            serde_json::Value::Object(fields) => {
+                for (field, value) in fields.iter_mut() {
+                    self.redact_field(target, field, value);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+lazy_static! {
+    static ref REDACTOR: RwLock<Arc<Redactor>> = RwLock::new(Arc::new(Redactor::default()));
+}
+
+/// The redactor for the current config.
+pub fn redactor() -> Arc<Redactor> {
+    REDACTOR.read().unwrap().clone()
+}
+
+/// Redact with `config` from now on. The current redactor is kept when the config is invalid.
+pub fn configure(config: &RedactionConfig) -> Result<()> {
+    let redactor = Redactor::new(config)?;
+    *REDACTOR.write().unwrap() = Arc::new(redactor);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{RedactionTarget, Redactor, REDACTED};
+    use crate::app_config::RedactionConfig;
+
+    #[test]
+    fn test_redact_str() {
+        let redactor = Redactor::default();
+
+        assert_eq!(
+            format!("sent the offer to {} (shipment {})", REDACTED, REDACTED),
+            redactor.redact_str(
+                RedactionTarget::Logs,
+                "sent the offer to jane.doe+jobs@example.com (shipment 1Z999AA10123456784)"
+            )
+        );
+        assert_eq!(
+            format!("usps {} fedex {}", REDACTED, REDACTED),
+            redactor.redact_str(
+                RedactionTarget::Sentry,
+                "usps 9400111899223397739437 fedex 123456789012"
+            )
+        );
+        assert_eq!(
+            "synced 42 users for 2023-04-12",
+            redactor.redact_str(RedactionTarget::Logs, "synced 42 users for 2023-04-12")
+        );
+    }
+
+    #[test]
+    fn test_redact_json() {
+        let redactor = Redactor::default();
+
+        let mut payload = json!({
+            "data": {
+                "Email": "jane@example.com",
+                "tracking_number": "92055901755477000271990251",
+                "tracking_history": [{ "status_details": "Delivered to jane@example.com" }],
+                "carrier": "usps",
+                "eta": null,
+            },
+        });
+        redactor.redact_json(RedactionTarget::WebhookEvents, &mut payload);
+
+        assert_eq!(
+            json!({
+                "data": {
+                    "Email": REDACTED,
+                    "tracking_number": REDACTED,
+                    "tracking_history": [{ "status_details": format!("Delivered to {}", REDACTED) }],
+                    "carrier": "usps",
+                    "eta": null,
+                },
+            }),
+            payload
+        );
+    }
+
+    #[test]
+    fn test_redaction_config() {
+        let config: RedactionConfig = toml::from_str(
+            r#"
+[email]
+targets = ['sentry']
+
+[tracking_number]
+enabled = false
+
+[phone]
+fields = ['phone', 'phone_number']
+patterns = ['\+1\d{10}']
+"#,
+        )
+        .unwrap();
+        let redactor = Redactor::new(&config).unwrap();
+
+        let text = "call jane@example.com at +14155550100 about 1Z999AA10123456784";
+        assert_eq!(
+            format!("call jane@example.com at {} about 1Z999AA10123456784", REDACTED),
+            redactor.redact_str(RedactionTarget::Logs, text)
+        );
+        assert_eq!(
+            format!("call {} at {} about 1Z999AA10123456784", REDACTED, REDACTED),
+            redactor.redact_str(RedactionTarget::Sentry, text)
+        );
+        assert!(redactor.is_redacted_field(RedactionTarget::Logs, "Phone_Number"));
+        assert!(!redactor.is_redacted_field(RedactionTarget::Logs, "email"));
+        assert!(!redactor.is_redacted_field(RedactionTarget::Sentry, "tracking_number"));
+
+        let invalid: RedactionConfig = toml::from_str("[phone]\npatterns = ['(']").unwrap();
+        assert!(Redactor::new(&invalid).is_err());
+    }
+}
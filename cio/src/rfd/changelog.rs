@@ -1,10 +1,10 @@
 use anyhow::Result;
 use chrono::{Duration, Utc};
-use sendgrid_api::{traits::MailOps, Client as SendGrid};
 
 use super::RFDs;
 use crate::companies::Company;
 use crate::db::Database;
+use crate::email::Mailer;
 
 /// Create a changelog email for the RFDs.
 pub async fn send_rfd_changelog(db: &Database, company: &Company) -> Result<()> {
@@ -34,12 +34,10 @@ pub async fn send_rfd_changelog(db: &Database, company: &Company) -> Result<()>
         }
     }
 
-    // Initialize the SendGrid clVient.
-    let sendgrid_client = SendGrid::new_from_env();
+    let mailer = Mailer::new(db, company);
 
     // Send the message.
-    sendgrid_client
-        .mail_send()
+    mailer
         .send_plain_text(
             &format!("RFD changelog for the week from {}", week_format),
             &changelog,
@@ -1,7 +1,9 @@
 use anyhow::{bail, Result};
 use comrak::{markdown_to_html, ComrakOptions};
 use log::info;
+use parse_rfd::{RfdFormat, RfdMetadata, Span};
 use regex::Regex;
+use ring::digest;
 use std::{
     borrow::Cow,
     env, fmt, fs,
@@ -104,7 +106,12 @@ impl<'a> RFDContent<'a> {
     pub async fn to_html(&self, number: &RFDNumber, branch: &GitHubRFDBranch) -> Result<RFDHtml> {
         match self {
             Self::Asciidoc(adoc) => adoc.to_html(number, branch).await,
-            Self::Markdown(md) => md.to_html(number),
+            Self::Markdown(md) => {
+                let mut html = md.to_html(number)?;
+                html.fingerprint_images(number, &branch.get_images(number).await?);
+
+                Ok(html)
+            }
         }
     }
 
@@ -125,42 +132,35 @@ impl<'a> RFDContent<'a> {
         }
     }
 
+    /// Parse the metadata attributes (authors, labels, discussion link and state) of the document
+    pub fn metadata(&self) -> RfdMetadata {
+        match self {
+            Self::Asciidoc(adoc) => RfdMetadata::parse(&adoc.content, RfdFormat::Asciidoc),
+            Self::Markdown(md) => RfdMetadata::parse(&md.content, RfdFormat::Markdown),
+        }
+    }
+
     /// Update the discussion link stored within the document to the passed link
     pub fn update_discussion_link(&mut self, link: &str) {
-        let (re, pre, content) = match self {
-            RFDContent::Asciidoc(ref mut adoc) => (
-                Regex::new(r"(?m)(:discussion:.*$)").unwrap(),
-                ":",
-                adoc.content.to_mut(),
-            ),
-            RFDContent::Markdown(ref mut md) => (Regex::new(r"(?m)(discussion:.*$)").unwrap(), "", md.content.to_mut()),
-        };
-
-        let replacement = if let Some(v) = re.find(content) {
-            v.as_str().to_string()
-        } else {
-            String::new()
-        };
-
-        *content = content.replacen(&replacement, &format!("{}discussion: {}", pre, link.trim()), 1);
+        let span = self.metadata().discussion.map(|field| field.span);
+        self.replace_attribute(span, "discussion", link);
     }
 
     /// Update the state stored within the document to the passed state
     pub fn update_state(&mut self, state: &str) {
-        let (re, pre, content) = match self {
-            RFDContent::Asciidoc(ref mut adoc) => {
-                (Regex::new(r"(?m)(:state:.*$)").unwrap(), ":", adoc.content.to_mut())
-            }
-            RFDContent::Markdown(ref mut md) => (Regex::new(r"(?m)(state:.*$)").unwrap(), "", md.content.to_mut()),
-        };
+        let span = self.metadata().state.map(|field| field.span);
+        self.replace_attribute(span, "state", state);
+    }
 
-        let replacement = if let Some(v) = re.find(content) {
-            v.as_str().to_string()
-        } else {
-            String::new()
+    fn replace_attribute(&mut self, span: Option<Span>, name: &str, value: &str) {
+        let (pre, content) = match self {
+            RFDContent::Asciidoc(ref mut adoc) => (":", adoc.content.to_mut()),
+            RFDContent::Markdown(ref mut md) => ("", md.content.to_mut()),
         };
 
-        *content = content.replacen(&replacement, &format!("{}state: {}", pre, state.trim()), 1);
+        // A document without the attribute has it added to the very start
+        let range = span.map(|span| span.range()).unwrap_or(0..0);
+        content.replace_range(range, &format!("{}{}: {}", pre, name, value.trim()));
     }
 
     /// Extract the title from the internal content
@@ -208,74 +208,33 @@ impl<'a> RFDContent<'a> {
     /// Get the state value stored within the document. If one can not be found, then an empty
     /// string is returned
     pub fn get_state(&self) -> String {
-        let re = Regex::new(r"(?m)(state:.*$)").unwrap();
-
         // TODO: This should return Option<&str>
-        match re.find(self.raw()) {
-            Some(v) => v.as_str().replace("state:", "").trim().to_string(),
-            None => Default::default(),
-        }
+        self.metadata().state.map(|field| field.value).unwrap_or_default()
     }
 
     /// Get the discussion link stored within the document. If one can not be found, then an empty
     /// string is returned
     pub fn get_discussion(&self) -> String {
-        let re = Regex::new(r"(?m)(discussion:.*$)").unwrap();
-
         // TODO: This should return Option<&str>
-        match re.find(self.raw()) {
-            Some(v) => {
-                let d = v.as_str().replace("discussion:", "").trim().to_string();
-
-                if !d.starts_with("http") {
-                    Default::default()
-                } else {
-                    d
-                }
-            }
-            None => Default::default(),
-        }
+        self.metadata()
+            .discussion
+            .map(|field| field.value)
+            .filter(|discussion| discussion.starts_with("http"))
+            .unwrap_or_default()
     }
 
     /// Get the authors line stored within the document. The returned string may contain multiple
     /// names. If none can be found, then and empty string is returned
     pub fn get_authors(&self) -> String {
-        match self {
-            Self::Asciidoc(RFDAsciidoc { content, .. }) => {
-                // We must have asciidoc content.
-                // We want to find the line under the first "=" line (which is the title), authors
-                // is under that.
-                let re = Regex::new(r"(?m:^[#=].*$)[\n\r](?m)(.*$)").unwrap();
-                match re.find(content) {
-                    Some(v) => {
-                        let val = v.as_str().trim().to_string();
-                        let parts: Vec<&str> = val.split('\n').collect();
-                        if parts.len() < 2 {
-                            Default::default()
-                        } else {
-                            let mut authors = parts[1].to_string();
-                            if authors == "{authors}" {
-                                // Do the traditional check.
-                                let re = Regex::new(r"(?m)(^:authors.*$)").unwrap();
-                                if let Some(v) = re.find(content) {
-                                    authors = v.as_str().replace(":authors:", "").trim().to_string();
-                                }
-                            }
-                            authors
-                        }
-                    }
-                    None => Default::default(),
-                }
-            }
-            Self::Markdown(RFDMarkdown { content }) => {
-                // TODO: make work w asciidoc.
-                let re = Regex::new(r"(?m)(^authors.*$)").unwrap();
-                match re.find(content) {
-                    Some(v) => v.as_str().replace("authors:", "").trim().to_string(),
-                    None => Default::default(),
-                }
-            }
-        }
+        self.metadata()
+            .authors
+            .map(|field| self.raw()[field.value_span.range()].to_string())
+            .unwrap_or_default()
+    }
+
+    /// Get the labels stored within the document
+    pub fn get_labels(&self) -> Vec<String> {
+        self.metadata().labels.map(|field| field.value).unwrap_or_default()
     }
 }
 
@@ -297,10 +256,11 @@ impl<'a> RFDAsciidoc<'a> {
     /// Generate an HTML string by combining RFD contents with static resources that are stored for
     /// a given RFD number on a specific branch
     pub async fn to_html(&self, number: &RFDNumber, branch: &GitHubRFDBranch) -> Result<RFDHtml> {
-        self.download_images(number, branch).await?;
+        let images = self.download_images(number, branch).await?;
 
         let mut html = RFDHtml(from_utf8(&self.parse(RFDOutputFormat::Html).await?)?.to_string());
         html.clean_links(&number.as_number_string());
+        html.fingerprint_images(number, &images);
 
         Ok(html)
     }
@@ -374,8 +334,13 @@ impl<'a> RFDAsciidoc<'a> {
     }
 
     /// Downloads images that are stored on the provided GitHub branch for the given RFD number.
-    /// These are stored locally so in a tmp directory for use by asciidoctor
-    async fn download_images(&self, number: &RFDNumber, branch: &GitHubRFDBranch) -> Result<()> {
+    /// These are stored locally so in a tmp directory for use by asciidoctor. Returns the images
+    /// that were downloaded
+    async fn download_images(
+        &self,
+        number: &RFDNumber,
+        branch: &GitHubRFDBranch,
+    ) -> Result<Vec<octorust::types::ContentFile>> {
         let dir = number.repo_directory();
 
         let storage_path = self.tmp_path();
@@ -384,7 +349,7 @@ impl<'a> RFDAsciidoc<'a> {
             .ok_or_else(|| anyhow::anyhow!("Unable to convert image temp storage path to string"))?;
         let images = branch.get_images(number).await?;
 
-        for image in images {
+        for image in &images {
             // Save the image to our temporary directory.
             let image_path = format!(
                 "{}/{}",
@@ -405,7 +370,7 @@ impl<'a> RFDAsciidoc<'a> {
             );
         }
 
-        Ok(())
+        Ok(images)
     }
 
     /// Computes the temporary path for use when generating asciidoc HTML. This returns None for
@@ -512,6 +477,33 @@ impl<'a> RFDMarkdown<'a> {
     }
 }
 
+/// The path of an image relative to the directory of its RFD, ie. `diagrams/network.svg` for
+/// `rfd/0032/diagrams/network.svg`.
+pub fn image_sub_path(number: &RFDNumber, path: &str) -> String {
+    path.trim_start_matches('/')
+        .trim_start_matches(number.repo_directory().trim_start_matches('/'))
+        .trim_start_matches('/')
+        .to_string()
+}
+
+/// The path an image is served from, with a hash of its contents before the extension, ie.
+/// `diagrams/network.3f2a9c1be04d.svg`. An image that changes gets a new path, so browsers can
+/// cache images forever and still show the latest version.
+pub fn fingerprint_image_path(sub_path: &str, data: &[u8]) -> String {
+    let hash = hex::encode(digest::digest(&digest::SHA256, data));
+    let hash = &hash[..12];
+
+    let (dir, file) = match sub_path.rsplit_once('/') {
+        Some((dir, file)) => (format!("{}/", dir), file),
+        None => (String::new(), sub_path),
+    };
+
+    match file.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => format!("{}{}.{}.{}", dir, stem, hash, extension),
+        _ => format!("{}{}.{}", dir, file, hash),
+    }
+}
+
 pub struct RFDHtml(pub String);
 
 impl RFDHtml {
@@ -551,6 +543,27 @@ impl RFDHtml {
             .replace("link:", &format!("link:https://{}.rfd.oxide.computer/", num))
             .replace(&format!("link:https://{}.rfd.oxide.computer/http", num), "link:http");
     }
+
+    /// Point the images of the RFD at their fingerprinted paths. Must run after [`clean_links`],
+    /// which makes the image paths absolute.
+    ///
+    /// [`clean_links`]: RFDHtml::clean_links
+    pub fn fingerprint_images(&mut self, number: &RFDNumber, images: &[octorust::types::ContentFile]) {
+        let prefix = format!("/static/images/{}/", number.as_number_string());
+        let mut html = self.0.replace(&format!("{}./", prefix), &prefix);
+
+        for image in images {
+            let sub_path = image_sub_path(number, &image.path);
+            let fingerprinted = fingerprint_image_path(&sub_path, &decode_base64(&image.content));
+
+            html = html.replace(
+                &format!("{}{}\"", prefix, sub_path),
+                &format!("{}{}\"", prefix, fingerprinted),
+            );
+        }
+
+        self.0 = html;
+    }
 }
 
 #[cfg(test)]
@@ -650,6 +663,65 @@ notreallystate: discussion
         assert_eq!(expected, html.0);
     }
 
+    #[test]
+    fn test_image_sub_path() {
+        let number = RFDNumber::from(32);
+
+        assert_eq!("thing.png", image_sub_path(&number, "rfd/0032/thing.png"));
+        assert_eq!(
+            "diagrams/thing.svg",
+            image_sub_path(&number, "/rfd/0032/diagrams/thing.svg")
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_image_path() {
+        let path = fingerprint_image_path("diagrams/thing.svg", b"<svg></svg>");
+        assert!(path.starts_with("diagrams/thing."));
+        assert!(path.ends_with(".svg"));
+        assert_eq!("diagrams/thing..svg".len() + 12, path.len());
+
+        // The same contents always get the same path, and new contents a new one.
+        assert_eq!(path, fingerprint_image_path("diagrams/thing.svg", b"<svg></svg>"));
+        assert_ne!(path, fingerprint_image_path("diagrams/thing.svg", b"<svg><g/></svg>"));
+
+        assert!(fingerprint_image_path("Makefile", b"").starts_with("Makefile."));
+        assert!(fingerprint_image_path(".hidden", b"").starts_with(".hidden."));
+    }
+
+    #[test]
+    fn test_fingerprint_rfd_html_images() {
+        let image = |path: &str, content: &str| octorust::types::ContentFile {
+            path: path.to_string(),
+            content: base64::encode(content),
+            ..Default::default()
+        };
+        let images = vec![image("rfd/0032/thing.png", "png"), image("rfd/0032/a/thing.svg", "svg")];
+        let png = fingerprint_image_path("thing.png", b"png");
+        let svg = fingerprint_image_path("a/thing.svg", b"svg");
+
+        let mut html = RFDHtml(
+            r#"<img src="/static/images/0032/thing.png">
+<img src="/static/images/0032/./thing.png">
+<object data="/static/images/0032/a/thing.svg">
+<img src="/static/images/0032/thing.png.old">
+<img src="https://example.com/thing.png">"#
+                .to_string(),
+        );
+        html.fingerprint_images(&RFDNumber::from(32), &images);
+
+        let expected = format!(
+            r#"<img src="/static/images/0032/{png}">
+<img src="/static/images/0032/{png}">
+<object data="/static/images/0032/{svg}">
+<img src="/static/images/0032/thing.png.old">
+<img src="https://example.com/thing.png">"#,
+            png = png,
+            svg = svg
+        );
+        assert_eq!(expected, html.0);
+    }
+
     // Read authors tests
 
     #[test]
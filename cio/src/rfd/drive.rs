@@ -1,33 +1,27 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use google_drive::{
-    traits::{DriveOps, FileOps},
-    Client as GoogleDrive,
-};
+use google_drive::traits::FileOps;
 use log::info;
 
-use crate::{companies::Company, db::Database};
+use crate::{
+    companies::Company,
+    db::Database,
+    drive::{DocumentStore, AUTOMATED_DOCUMENTS_DRIVE},
+};
 
 use super::{PDFStorage, RFDPdf, RFDs, RFD};
 
+/// RFD PDFs are stored in the shared drive : "Automated Documents"/"rfds"
+const RFD_PDF_FOLDER: &[&str] = &["rfds"];
+
 #[async_trait]
-impl PDFStorage for GoogleDrive {
+impl PDFStorage for DocumentStore {
     async fn store_rfd_pdf(&self, pdf: &RFDPdf) -> Result<String> {
-        // Figure out where our directory is.
-        // It should be in the shared drive : "Automated Documents"/"rfds"
-        let shared_drive = self.drives().get_by_name("Automated Documents").await?;
-        let drive_id = shared_drive.id.to_string();
-
-        // Get the directory by the name.
-        let parent_id = self.files().create_folder(&drive_id, "", "rfds").await?;
-
-        // Create or update the file in the google_drive.
-        let drive_file = self
-            .files()
-            .create_or_update(&drive_id, &parent_id, &pdf.filename, "application/pdf", &pdf.contents)
+        let document = self
+            .upload(RFD_PDF_FOLDER, &pdf.filename, "application/pdf", &pdf.contents)
             .await?;
 
-        Ok(format!("https://drive.google.com/open?id={}", drive_file.id))
+        Ok(document.url)
     }
 }
 
@@ -41,21 +35,15 @@ pub async fn cleanup_rfd_pdfs(db: &Database, company: &Company) -> Result<()> {
     #[allow(clippy::needless_collect)]
     let valid_pdf_filenames = rfds.iter().map(|rfd| rfd.get_pdf_filename()).collect::<Vec<String>>();
 
-    let drive_client = company.authenticate_google_drive(db).await?;
-
-    // Figure out where our directory is.
-    // It should be in the shared drive : "Automated Documents"/"rfds"
-    let shared_drive = drive_client.drives().get_by_name("Automated Documents").await?;
-    let drive_id = shared_drive.id.to_string();
-
-    // Get the directory by the name.
-    let parent_id = drive_client.files().create_folder(&drive_id, "", "rfds").await?;
+    let store = DocumentStore::new(db, company, AUTOMATED_DOCUMENTS_DRIVE).await?;
+    let parent_id = store.folder(RFD_PDF_FOLDER).await?;
 
-    let drive_files = drive_client
+    let drive_files = store
+        .client()
         .files()
         .list_all(
             "drive",                                // corpa
-            &drive_id,                              // drive id
+            store.drive_id(),                       // drive id
             true,                                   // include items from all drives
             "",                                     // include permissions for view
             false,                                  // include team drive items
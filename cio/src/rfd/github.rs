@@ -52,6 +52,10 @@ impl GitHubRFDRepo {
         })
     }
 
+    pub fn client(&self) -> &Octorust {
+        &self.client
+    }
+
     /// Get an accessor for a RFD on a specific branch
     pub fn branch(&self, branch: String) -> GitHubRFDBranch {
         GitHubRFDBranch {
@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Utc};
+use diesel::{Insertable, Queryable};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{GitHubRFDRepo, RFDNumber, RFDs, RFD};
+use crate::{companies::Company, db::Database, schema::rfd_history_events};
+
+/// A change to an RFD, as of the commit that made it.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct RFDHistoryEvent {
+    pub id: i32,
+    pub rfd_number: i32,
+    /// One of `created`, `state`, `title` or `authors`.
+    pub kind: String,
+    pub previous_value: String,
+    pub value: String,
+    pub sha: String,
+    pub committed_at: DateTime<Utc>,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = rfd_history_events)]
+struct NewRFDHistoryEvent {
+    rfd_number: i32,
+    kind: String,
+    previous_value: String,
+    value: String,
+    sha: String,
+    committed_at: DateTime<Utc>,
+    cio_company_id: i32,
+}
+
+/// The metadata of an RFD as of one commit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RFDRevision {
+    sha: String,
+    committed_at: DateTime<Utc>,
+    state: String,
+    title: String,
+    authors: String,
+}
+
+/// The changes between consecutive revisions of an RFD, which are expected oldest first. The
+/// first revision is recorded as the RFD being created in its state at the time.
+fn history_events(number: i32, company_id: i32, revisions: &[RFDRevision]) -> Vec<NewRFDHistoryEvent> {
+    let event = |revision: &RFDRevision, kind: &str, previous_value: &str, value: &str| NewRFDHistoryEvent {
+        rfd_number: number,
+        kind: kind.to_string(),
+        previous_value: previous_value.to_string(),
+        value: value.to_string(),
+        sha: revision.sha.to_string(),
+        committed_at: revision.committed_at,
+        cio_company_id: company_id,
+    };
+
+    let mut events = vec![];
+    let mut previous: Option<&RFDRevision> = None;
+    for revision in revisions {
+        match previous {
+            None => events.push(event(revision, "created", "", &revision.state)),
+            Some(previous) => {
+                for (kind, before, after) in [
+                    ("state", &previous.state, &revision.state),
+                    ("title", &previous.title, &revision.title),
+                    ("authors", &previous.authors, &revision.authors),
+                ] {
+                    if before != after {
+                        events.push(event(revision, kind, before, after));
+                    }
+                }
+            }
+        }
+
+        previous = Some(revision);
+    }
+
+    events
+}
+
+/// Read the metadata of the RFD at every commit that touched it, oldest first. Commits are taken
+/// from the default branch and, for RFDs that have not been merged yet, from the RFD's own branch.
+async fn revisions(repo: &GitHubRFDRepo, rfd: &RFD) -> Result<Vec<RFDRevision>> {
+    let number: RFDNumber = rfd.number.into();
+
+    let mut branches = vec![repo.default_branch.to_string()];
+    if !rfd.link.contains(&format!("/{}/", repo.default_branch)) {
+        branches.push(number.as_number_string());
+    }
+
+    let mut commits: HashMap<String, DateTime<Utc>> = HashMap::new();
+    for branch in &branches {
+        let list = repo
+            .client()
+            .repos()
+            .list_all_commits(
+                &repo.owner,
+                &repo.repo,
+                branch,
+                &format!("/rfd/{}/", number.as_number_string()),
+                "",
+                None,
+                None,
+            )
+            .await;
+
+        // The branch of an RFD is deleted once it is merged.
+        let list = match list {
+            Ok(list) => list,
+            Err(e) => {
+                info!("[rfd.history] No commits for RFD {} on {}: {}", rfd.number, branch, e);
+                continue;
+            }
+        };
+
+        for commit in list {
+            if let Some(committer) = &commit.commit.committer {
+                if let Ok(date) = committer.date.parse::<DateTime<Utc>>() {
+                    commits.insert(commit.sha, date);
+                }
+            }
+        }
+    }
+
+    let mut commits = commits.into_iter().collect::<Vec<_>>();
+    commits.sort_by_key(|(sha, date)| (*date, sha.to_string()));
+
+    let mut revisions = vec![];
+    for (sha, committed_at) in commits {
+        // A commit can be read the same way as a branch, by its sha.
+        let readme = match repo.branch(sha.to_string()).get_readme_contents(&number).await {
+            Ok(readme) => readme,
+            Err(e) => {
+                // ie. the commit that moved the RFD from another path.
+                info!("[rfd.history] No README for RFD {} at {}: {}", rfd.number, sha, e);
+                continue;
+            }
+        };
+
+        revisions.push(RFDRevision {
+            sha,
+            committed_at,
+            state: readme.content.get_state(),
+            title: readme.content.get_title(),
+            authors: readme.content.get_authors(),
+        });
+    }
+
+    Ok(revisions)
+}
+
+/// Reconstruct the history of RFDs from the git history of the rfd repo, so that it goes back
+/// further than the sync has been recording it. Only the RFD with `number` is backfilled when it
+/// is given. Events are keyed on their commit, so running the backfill again only adds what is
+/// missing.
+pub async fn backfill_rfd_history(db: &Database, company: &Company, number: Option<i32>) -> Result<()> {
+    let repo = GitHubRFDRepo::new(company).await?;
+
+    let mut failed = 0;
+    for rfd in RFDs::get_from_db(db, company.id).await? {
+        if matches!(number, Some(number) if number != rfd.number) {
+            continue;
+        }
+
+        let revisions = match revisions(&repo, &rfd).await {
+            Ok(revisions) => revisions,
+            Err(e) => {
+                warn!("[rfd.history] Failed to read the history of RFD {}: {}", rfd.number, e);
+                failed += 1;
+                continue;
+            }
+        };
+
+        let events = history_events(rfd.number, company.id, &revisions);
+        if events.is_empty() {
+            continue;
+        }
+
+        let added = diesel::insert_into(rfd_history_events::table)
+            .values(events)
+            .on_conflict((
+                rfd_history_events::cio_company_id,
+                rfd_history_events::rfd_number,
+                rfd_history_events::sha,
+                rfd_history_events::kind,
+            ))
+            .do_nothing()
+            .execute_async(db.pool())
+            .await?;
+
+        info!(
+            "[rfd.history] Backfilled {} events from {} revisions of RFD {}",
+            added,
+            revisions.len(),
+            rfd.number
+        );
+    }
+
+    if failed > 0 {
+        bail!("failed to backfill the history of {} RFDs", failed);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+
+    use super::{history_events, RFDRevision};
+
+    fn revision(sha: &str, committed_at: &str, state: &str, title: &str, authors: &str) -> RFDRevision {
+        RFDRevision {
+            sha: sha.to_string(),
+            committed_at: committed_at.parse::<DateTime<Utc>>().unwrap(),
+            state: state.to_string(),
+            title: title.to_string(),
+            authors: authors.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_history_events() {
+        let revisions = vec![
+            revision("a", "2022-01-03T10:00:00Z", "ideation", "Widgets", "Jane Doe"),
+            revision("b", "2022-01-04T10:00:00Z", "ideation", "Widgets", "Jane Doe"),
+            revision("c", "2022-01-05T10:00:00Z", "discussion", "Better widgets", "Jane Doe"),
+            revision(
+                "d",
+                "2022-02-01T10:00:00Z",
+                "published",
+                "Better widgets",
+                "Jane Doe, Sam Roe",
+            ),
+        ];
+
+        let events = history_events(42, 1, &revisions)
+            .into_iter()
+            .map(|event| (event.sha, event.kind, event.previous_value, event.value))
+            .collect::<Vec<_>>();
+
+        let expected = [
+            ("a", "created", "", "ideation"),
+            ("c", "state", "ideation", "discussion"),
+            ("c", "title", "Widgets", "Better widgets"),
+            ("d", "state", "discussion", "published"),
+            ("d", "authors", "Jane Doe", "Jane Doe, Sam Roe"),
+        ]
+        .into_iter()
+        .map(|(sha, kind, previous, value)| {
+            (
+                sha.to_string(),
+                kind.to_string(),
+                previous.to_string(),
+                value.to_string(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    fn test_history_events_without_revisions() {
+        assert!(history_events(42, 1, &[]).is_empty());
+    }
+}
@@ -5,16 +5,22 @@ mod changelog;
 mod content;
 pub mod drive;
 mod github;
+mod history;
 mod model;
 mod pdf;
+mod reviews;
 mod search;
+mod visibility;
 
 pub use changelog::send_rfd_changelog;
-pub use content::{RFDContent, RFDOutputError, RFDOutputFormat};
+pub use content::{fingerprint_image_path, image_sub_path, RFDContent, RFDOutputError, RFDOutputFormat};
 pub use github::{GitHubRFDBranch, GitHubRFDReadme, GitHubRFDReadmeLocation, GitHubRFDRepo, GitHubRFDUpdate};
+pub use history::{backfill_rfd_history, RFDHistoryEvent};
 pub use model::{NewRFD, RFDEntry, RFDIndexEntry, RFDs, RemoteRFD, RFD};
 pub use pdf::{PDFStorage, RFDPdf};
+pub use reviews::{discussion_pull_number, remind_rfd_reviewers, RFDReviewerSet, RFDReviewers};
 pub use search::{IndexDocument, RFDSearchIndex};
+pub use visibility::RFDVisibility;
 
 #[derive(Debug, Copy, Clone, Deserialize, Serialize)]
 pub struct RFDNumber(i32);
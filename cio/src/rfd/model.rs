@@ -3,7 +3,7 @@
 use anyhow::{anyhow, bail, Result};
 use async_bb8_diesel::AsyncRunQueryDsl;
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use macros::db;
 use partial_struct::partial;
 use schemars::JsonSchema;
@@ -13,7 +13,7 @@ use crate::{
     airtable::AIRTABLE_RFD_TABLE,
     companies::Company,
     core::UpdateAirtableRecord,
-    rfd::{GitHubRFDBranch, GitHubRFDReadmeLocation, GitHubRFDRepo, GitHubRFDUpdate, RFDContent},
+    rfd::{GitHubRFDBranch, GitHubRFDReadmeLocation, GitHubRFDRepo, GitHubRFDUpdate, RFDContent, RFDVisibility},
     schema::rfds as r_f_ds,
     schema::rfds,
     utils::truncate,
@@ -84,6 +84,17 @@ pub struct NewRFD {
     pub pdf_link_github: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub pdf_link_google_drive: String,
+    /// visibility is who the RFD may be shown to: public, internal or embargoed. Only public RFDs
+    /// are served by the API, searchable and published to the RFD site.
+    #[serde(default = "default_visibility")]
+    #[partial(RFDIndexEntry(skip))]
+    #[partial(RFDEntry(skip))]
+    pub visibility: String,
+    /// embargoed_until is the day an embargoed RFD becomes public
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[partial(RFDIndexEntry(skip))]
+    #[partial(RFDEntry(skip))]
+    pub embargoed_until: Option<NaiveDate>,
     /// The CIO company ID.
     #[serde(default)]
     #[partial(RFDIndexEntry(skip))]
@@ -91,6 +102,10 @@ pub struct NewRFD {
     pub cio_company_id: i32,
 }
 
+fn default_visibility() -> String {
+    "public".to_string()
+}
+
 pub struct RemoteRFD {
     pub rfd: NewRFD,
     pub location: GitHubRFDReadmeLocation,
@@ -118,6 +133,8 @@ impl NewRFD {
         // Parse the discussion from the contents.
         let discussion = readme.content.get_discussion();
 
+        let visibility = RFDVisibility::from_metadata(&readme.content.metadata());
+
         let html = readme.content.to_html(&update.number, &update.branch).await?.0;
 
         // TODO: Unsure if this should actually be an error, but this mirrors the previous logic
@@ -196,6 +213,8 @@ impl NewRFD {
                 // while new PDFs are generated
                 pdf_link_github: Default::default(),
                 pdf_link_google_drive: Default::default(),
+                visibility: visibility.as_str().to_string(),
+                embargoed_until: visibility.embargoed_until(),
                 cio_company_id: company.id,
             },
             location: readme.location,
@@ -305,6 +324,16 @@ impl RFD {
         Ok(())
     }
 
+    pub fn visibility(&self) -> RFDVisibility {
+        RFDVisibility::from_columns(&self.visibility, self.embargoed_until)
+    }
+
+    /// If the RFD may be shown outside of the company today. Embargoed RFDs become public on their
+    /// next sync after the embargo ends.
+    pub fn is_public(&self) -> bool {
+        self.visibility().is_public(Utc::now().date().naive_utc())
+    }
+
     pub fn content(&self) -> Result<RFDContent> {
         RFDContent::new(&self.content)
     }
@@ -2,7 +2,12 @@ use anyhow::Result;
 use async_trait::async_trait;
 use log::info;
 
-use crate::{companies::Company, db::Database, features::Features};
+use crate::{
+    companies::Company,
+    db::Database,
+    drive::{DocumentStore, AUTOMATED_DOCUMENTS_DRIVE},
+    features::Features,
+};
 
 use super::{GitHubRFDRepo, RFDNumber};
 
@@ -38,7 +43,8 @@ impl RFDPdf {
             };
 
             let google_drive_url = if Features::is_enabled("RFD_PDFS_IN_GOOGLE_DRIVE") {
-                Some(company.authenticate_google_drive(db).await?.store_rfd_pdf(self).await?)
+                let store = DocumentStore::new(db, company, AUTOMATED_DOCUMENTS_DRIVE).await?;
+                Some(store.store_rfd_pdf(self).await?)
             } else {
                 None
             };
@@ -0,0 +1,442 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, Insertable, PgTextExpressionMethods, QueryDsl, Queryable};
+use log::{info, warn};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use super::{GitHubRFDRepo, RFDs, RFD};
+use crate::{
+    app_config::RFDReviewsConfig,
+    companies::Company,
+    db::Database,
+    delivery::{post_to_slack_in_window, DeliveryWindow},
+    github_event_routes::glob_matches,
+    schema::{rfd_review_reminders, users},
+    utils::get_file_content_from_repo,
+};
+
+/// Reminders stop escalating after this level, until the RFD is reviewed.
+const MAX_ESCALATION_LEVEL: i32 = 3;
+
+/// Who reviews an RFD, as GitHub logins.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RFDReviewerSet {
+    pub users: Vec<String>,
+    /// Team slugs, without the org.
+    pub teams: Vec<String>,
+}
+
+impl RFDReviewerSet {
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty() && self.teams.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ReviewerRule {
+    pattern: String,
+    reviewers: RFDReviewerSet,
+}
+
+/// The reviewers file of the rfd repo. Each line is a pattern followed by the reviewers of the
+/// RFDs it matches, ie. `rfd/00* @jane @oxidecomputer/hardware`. Like a CODEOWNERS file, the
+/// last line that matches an RFD decides its reviewers.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RFDReviewers {
+    rules: Vec<ReviewerRule>,
+}
+
+impl RFDReviewers {
+    pub fn parse(contents: &str) -> Self {
+        let rules = contents
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or_default().trim())
+            .filter(|line| !line.is_empty())
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                let pattern = normalize_pattern(parts.next()?);
+
+                let mut reviewers = RFDReviewerSet::default();
+                for owner in parts {
+                    let owner = owner.trim_start_matches('@');
+                    match owner.split_once('/') {
+                        Some((_org, team)) => reviewers.teams.push(team.to_string()),
+                        None => reviewers.users.push(owner.to_string()),
+                    }
+                }
+
+                Some(ReviewerRule { pattern, reviewers })
+            })
+            .collect();
+
+        Self { rules }
+    }
+
+    /// Read the reviewers file from the default branch of the rfd repo. A repo without one has no
+    /// reviewers.
+    pub async fn get(github: &octorust::Client, owner: &str, repo: &str, default_branch: &str, path: &str) -> Self {
+        match get_file_content_from_repo(github, owner, repo, default_branch, path).await {
+            Ok((contents, _)) => Self::parse(&String::from_utf8_lossy(&contents)),
+            Err(e) => {
+                info!("[rfd] No RFD reviewers file at {}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    /// The reviewers of an RFD, by its number with the leading zeros, ie. `0042`.
+    pub fn for_rfd(&self, number_string: &str) -> RFDReviewerSet {
+        let path = format!("rfd/{}", number_string);
+
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| glob_matches(&rule.pattern, number_string) || glob_matches(&rule.pattern, &path))
+            .map(|rule| rule.reviewers.clone())
+            .unwrap_or_default()
+    }
+}
+
+/// Patterns are matched against `rfd/0042`, so `/rfd/0042/` and `rfd/0042/README.adoc` work as
+/// they would in a CODEOWNERS file.
+fn normalize_pattern(pattern: &str) -> String {
+    let pattern = pattern.trim_start_matches('/').trim_end_matches('/');
+    let pattern = pattern
+        .strip_suffix("/README.adoc")
+        .or_else(|| pattern.strip_suffix("/README.md"))
+        .unwrap_or(pattern);
+
+    pattern.to_string()
+}
+
+/// The number of the pull request an RFD is discussed in, from its discussion link.
+pub fn discussion_pull_number(discussion: &str) -> Option<i64> {
+    let (_, rest) = discussion.split_once("/pull/")?;
+    rest.split(|c: char| !c.is_ascii_digit()).next()?.parse().ok()
+}
+
+/// How far reminders about an RFD that has not been reviewed in `idle_days` have escalated.
+pub fn escalation_level(idle_days: i64, stale_days: i64) -> i32 {
+    if stale_days <= 0 {
+        return 0;
+    }
+
+    (idle_days / stale_days).min(MAX_ESCALATION_LEVEL as i64) as i32
+}
+
+#[derive(Debug, Queryable, PartialEq, Clone)]
+pub struct RFDReviewReminder {
+    pub id: i32,
+    pub rfd_number: i32,
+    pub pull_request_number: i64,
+    pub last_activity_at: DateTime<Utc>,
+    pub escalation_level: i32,
+    pub reminded_at: DateTime<Utc>,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, diesel::AsChangeset)]
+#[diesel(table_name = rfd_review_reminders)]
+struct NewRFDReviewReminder {
+    rfd_number: i32,
+    pull_request_number: i64,
+    last_activity_at: DateTime<Utc>,
+    escalation_level: i32,
+    reminded_at: DateTime<Utc>,
+    cio_company_id: i32,
+}
+
+/// The last time anyone reviewed the pull request, or when it was opened if no one has yet.
+async fn last_review_activity(repo: &GitHubRFDRepo, number: i64) -> Result<Option<DateTime<Utc>>> {
+    let pull = repo.client().pulls().get(&repo.owner, &repo.repo, number).await?;
+    if pull.state.to_string() != "open" {
+        return Ok(None);
+    }
+
+    let reviews = repo
+        .client()
+        .pulls()
+        .list_all_reviews(&repo.owner, &repo.repo, number)
+        .await?;
+
+    Ok(reviews
+        .iter()
+        .filter_map(|review| review.submitted_at)
+        .chain(pull.created_at)
+        .max())
+}
+
+/// The emails and Slack users of the reviewers, found by the GitHub login in their user config.
+async fn slack_reviewers(
+    db: &Database,
+    company: &Company,
+    reviewers: &RFDReviewerSet,
+) -> Result<Vec<(String, String)>> {
+    if reviewers.users.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let slack_users: HashMap<String, String> = company
+        .authenticate_slack(db)
+        .await?
+        .list_users()
+        .await?
+        .into_iter()
+        .map(|slack_user| (slack_user.email.to_lowercase(), slack_user.id))
+        .collect();
+
+    let mut found = vec![];
+    for login in &reviewers.users {
+        let email = users::dsl::users
+            .filter(users::dsl::cio_company_id.eq(company.id))
+            .filter(users::dsl::github.ilike(login.to_string()))
+            .select(users::dsl::email)
+            .first_async::<String>(db.pool())
+            .await
+            .ok();
+
+        let reviewer = email.and_then(|email| {
+            let id = slack_users.get(&email.to_lowercase())?.to_string();
+            Some((email, id))
+        });
+        match reviewer {
+            Some(reviewer) => found.push(reviewer),
+            None => info!("[rfd] No Slack user for RFD reviewer {}", login),
+        }
+    }
+
+    Ok(found)
+}
+
+fn reminder_message(
+    channel: &str,
+    rfd: &RFD,
+    reviewers: &RFDReviewerSet,
+    slack_ids: &[String],
+    idle_days: i64,
+    level: i32,
+) -> FormattedMessage {
+    let mut text = format!(
+        "*<{}|RFD {} {}>* has been in discussion for {} days without a review.",
+        rfd.discussion, rfd.number, rfd.title, idle_days
+    );
+
+    if level >= MAX_ESCALATION_LEVEL {
+        let mut mentions = slack_ids.iter().map(|id| format!("<@{}>", id)).collect::<Vec<_>>();
+        mentions.extend(reviewers.teams.iter().map(|team| format!("the {} team", team)));
+        if !mentions.is_empty() {
+            text.push_str(&format!(" Its reviewers are {}.", mentions.join(", ")));
+        }
+    } else if level > 1 {
+        text.push_str(" This is the second reminder, please take a look or hand it off to someone who can.");
+    } else {
+        text.push_str(" You are one of its reviewers, please take a look.");
+    }
+
+    FormattedMessage {
+        channel: channel.to_string(),
+        attachments: Default::default(),
+        blocks: vec![MessageBlock {
+            block_type: MessageBlockType::Section,
+            text: Some(MessageBlockText {
+                text_type: MessageType::Markdown,
+                text,
+            }),
+            elements: Default::default(),
+            accessory: Default::default(),
+            block_id: Default::default(),
+            fields: Default::default(),
+        }],
+    }
+}
+
+/// Send the reminder for an escalation level. The first levels go to the reviewers directly, the
+/// last one to the escalation channel. Reviewers are reminded during their own working hours.
+async fn send_reminder(
+    db: &Database,
+    company: &Company,
+    config: &RFDReviewsConfig,
+    rfd: &RFD,
+    reviewers: &RFDReviewerSet,
+    idle_days: i64,
+    level: i32,
+) -> Result<()> {
+    let found = slack_reviewers(db, company, reviewers).await?;
+    let slack_ids = found.iter().map(|(_, id)| id.to_string()).collect::<Vec<_>>();
+
+    if level >= MAX_ESCALATION_LEVEL {
+        if config.escalation_channel.is_empty() {
+            return Ok(());
+        }
+
+        company
+            .post_to_slack_channel(
+                db,
+                &reminder_message(&config.escalation_channel, rfd, reviewers, &slack_ids, idle_days, level),
+            )
+            .await?;
+    } else {
+        for (email, id) in &found {
+            let message = reminder_message(id, rfd, reviewers, &slack_ids, idle_days, level);
+            post_to_slack_in_window(db, company, email, &message, DeliveryWindow::WORKING_HOURS).await?;
+        }
+    }
+
+    info!(
+        "[rfd] Sent level {} review reminder for RFD {} to {} reviewers",
+        level,
+        rfd.number,
+        slack_ids.len()
+    );
+
+    Ok(())
+}
+
+/// Remind the reviewers of RFDs in discussion that have not been reviewed in a while, and
+/// escalate the longer they go without one.
+pub async fn remind_rfd_reviewers(db: &Database, company: &Company, config: &RFDReviewsConfig) -> Result<()> {
+    let repo = GitHubRFDRepo::new(company).await?;
+    let reviewers = RFDReviewers::get(
+        repo.client(),
+        &repo.owner,
+        &repo.repo,
+        &repo.default_branch,
+        &config.reviewers_file,
+    )
+    .await;
+
+    let reminders: HashMap<i32, RFDReviewReminder> = rfd_review_reminders::dsl::rfd_review_reminders
+        .filter(rfd_review_reminders::dsl::cio_company_id.eq(company.id))
+        .load_async::<RFDReviewReminder>(db.pool())
+        .await?
+        .into_iter()
+        .map(|reminder| (reminder.rfd_number, reminder))
+        .collect();
+
+    let now = Utc::now();
+    for rfd in RFDs::get_from_db(db, company.id).await? {
+        if rfd.state != "discussion" {
+            continue;
+        }
+
+        let rfd_reviewers = reviewers.for_rfd(&rfd.number_string);
+        let pull_number = match discussion_pull_number(&rfd.discussion) {
+            Some(number) if !rfd_reviewers.is_empty() => number,
+            _ => continue,
+        };
+
+        let last_activity_at = match last_review_activity(&repo, pull_number).await {
+            Ok(Some(at)) => at,
+            Ok(None) => continue,
+            Err(e) => {
+                warn!("[rfd] Failed to get the reviews of RFD {}: {}", rfd.number, e);
+                continue;
+            }
+        };
+
+        // Reviews since the last reminder start the escalation over.
+        let sent_level = reminders
+            .get(&rfd.number)
+            .filter(|reminder| reminder.last_activity_at == last_activity_at)
+            .map(|reminder| reminder.escalation_level)
+            .unwrap_or_default();
+
+        let idle_days = (now - last_activity_at).num_days();
+        let level = escalation_level(idle_days, config.stale_days);
+        if level <= sent_level {
+            continue;
+        }
+
+        if let Err(e) = send_reminder(db, company, config, &rfd, &rfd_reviewers, idle_days, level).await {
+            warn!("[rfd] Failed to remind the reviewers of RFD {}: {}", rfd.number, e);
+            continue;
+        }
+
+        let reminder = NewRFDReviewReminder {
+            rfd_number: rfd.number,
+            pull_request_number: pull_number,
+            last_activity_at,
+            escalation_level: level,
+            reminded_at: now,
+            cio_company_id: company.id,
+        };
+        diesel::insert_into(rfd_review_reminders::table)
+            .values(&reminder)
+            .on_conflict((rfd_review_reminders::cio_company_id, rfd_review_reminders::rfd_number))
+            .do_update()
+            .set(&reminder)
+            .execute_async(db.pool())
+            .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{discussion_pull_number, escalation_level, RFDReviewerSet, RFDReviewers};
+
+    #[test]
+    fn test_reviewers_for_rfd() {
+        let reviewers = RFDReviewers::parse(
+            "# Everyone reviews the RFDs no one else does.\n\
+             * @oxidecomputer/rfd-reviewers\n\
+             \n\
+             /rfd/00*/ @jane @oxidecomputer/hardware # The early ones.\n\
+             0042 @joe\n",
+        );
+
+        assert_eq!(
+            RFDReviewerSet {
+                users: vec!["joe".to_string()],
+                teams: vec![],
+            },
+            reviewers.for_rfd("0042")
+        );
+        assert_eq!(
+            RFDReviewerSet {
+                users: vec!["jane".to_string()],
+                teams: vec!["hardware".to_string()],
+            },
+            reviewers.for_rfd("0043")
+        );
+        assert_eq!(
+            RFDReviewerSet {
+                users: vec![],
+                teams: vec!["rfd-reviewers".to_string()],
+            },
+            reviewers.for_rfd("0123")
+        );
+        assert!(RFDReviewers::parse("").for_rfd("0042").is_empty());
+    }
+
+    #[test]
+    fn test_discussion_pull_number() {
+        assert_eq!(
+            Some(123),
+            discussion_pull_number("https://github.com/oxidecomputer/rfd/pull/123")
+        );
+        assert_eq!(
+            Some(123),
+            discussion_pull_number("https://github.com/oxidecomputer/rfd/pull/123/files")
+        );
+        assert_eq!(
+            None,
+            discussion_pull_number("https://github.com/oxidecomputer/rfd/issues/123")
+        );
+        assert_eq!(None, discussion_pull_number(""));
+    }
+
+    #[test]
+    fn test_escalation_level() {
+        assert_eq!(0, escalation_level(6, 7));
+        assert_eq!(1, escalation_level(7, 7));
+        assert_eq!(2, escalation_level(15, 7));
+        assert_eq!(3, escalation_level(21, 7));
+        assert_eq!(3, escalation_level(100, 7));
+        assert_eq!(0, escalation_level(100, 0));
+    }
+}
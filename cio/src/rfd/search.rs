@@ -1,12 +1,19 @@
 use anyhow::Result;
 use hmac::{Hmac, Mac};
 use md5::Md5;
-use meilisearch_minimal_api::{IndexClient, MeiliClient, SearchQuery};
+use meilisearch_minimal_api::{IndexClient, IndexSettings, MeiliClient, SearchQuery};
 use parse_rfd::{parse, ParsedDoc, Section};
 use serde::{Deserialize, Serialize};
-use std::{cmp::min, collections::HashMap};
+use std::{
+    cmp::min,
+    collections::{HashMap, HashSet},
+};
 
 use super::RFDNumber;
+use crate::search_index;
+
+/// Meilisearch will not return more hits than this for a single search.
+const MAX_SECTIONS: u32 = 1000;
 
 pub struct RFDSearchIndex {}
 
@@ -93,23 +100,72 @@ impl RFDSearchIndex {
         ))
     }
 
-    /// Trigger updating the search index for the RFD.
+    /// Matches in section titles rank above matches in their content, and sections can be
+    /// filtered and faceted by the RFD they belong to.
+    pub fn index_settings() -> IndexSettings {
+        let searchable = [
+            "hierarchy_lvl0",
+            "hierarchy_lvl1",
+            "hierarchy_lvl2",
+            "hierarchy_lvl3",
+            "hierarchy_lvl4",
+            "hierarchy_lvl5",
+            "name",
+            "content",
+        ];
+
+        IndexSettings {
+            searchable_attributes: Some(searchable.iter().map(|s| s.to_string()).collect()),
+            filterable_attributes: Some(vec!["rfd_number".to_string(), "level".to_string()]),
+            sortable_attributes: Some(vec!["rfd_number".to_string()]),
+            ..Default::default()
+        }
+    }
+
+    /// Apply the index settings. Meilisearch only rebuilds the index when the settings change.
+    pub async fn configure_index(client: &MeiliClient, index: String) -> Result<()> {
+        search_index::configure_index(client, &index, &Self::index_settings()).await
+    }
+
+    /// Update the search index for the RFD. The new sections are indexed before any sections
+    /// that no longer exist are removed, so searches never see the RFD without content, and
+    /// each step is waited on so that a failure does not leave stale sections behind.
     pub async fn index_rfd(client: &MeiliClient, index: String, rfd_number: &RFDNumber, content: &str) -> Result<()> {
         let index = client.index(index);
-        let ids_to_delete = Self::find_rfd_ids(&index, rfd_number).await?;
-
-        log::info!("Deleting documents for RFD {}: {:?}", rfd_number.0, ids_to_delete);
-        index.delete_documents(&ids_to_delete).await?;
 
         let parsed = Self::parse_document(rfd_number, content)?;
-        index.index_documents(&parsed, "objectID").await?;
+        let task = index.index_documents(&parsed, "objectID").await?;
+        search_index::wait_for_task(client, task).await?;
+
+        let current = parsed.iter().map(|doc| &doc.object_id).collect::<HashSet<_>>();
+        let ids_to_delete = Self::find_rfd_ids(&index, rfd_number)
+            .await?
+            .into_iter()
+            .filter(|id| !current.contains(id))
+            .collect::<Vec<_>>();
+
+        if !ids_to_delete.is_empty() {
+            log::info!("Deleting documents for RFD {}: {:?}", rfd_number.0, ids_to_delete);
+            let task = index.delete_documents(&ids_to_delete).await?;
+            search_index::wait_for_task(client, task).await?;
+        }
 
         Ok(())
     }
 
+    /// Remove every section of the RFD from the index.
+    pub async fn delete_rfd(client: &MeiliClient, index: String, rfd_number: &RFDNumber) -> Result<()> {
+        let task = client
+            .index(index)
+            .delete_documents_by_filter(&format!("rfd_number = {}", rfd_number.0))
+            .await?;
+        search_index::wait_for_task(client, task).await
+    }
+
     pub async fn find_rfd_ids(index: &IndexClient, rfd_number: &RFDNumber) -> Result<Vec<String>> {
         let mut query = SearchQuery::default();
         query.filter = Some(vec![format!("rfd_number = {}", rfd_number.0)]);
+        query.limit = Some(MAX_SECTIONS);
 
         let results = index.search::<RfdId>(query).await?;
 
@@ -0,0 +1,122 @@
+use chrono::NaiveDate;
+use parse_rfd::RfdMetadata;
+
+/// Who an RFD may be shown to. Non-public RFDs still go through the whole pipeline, but are kept
+/// out of the API, the search index and the files published for the RFD site.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RFDVisibility {
+    Public,
+    Internal,
+    /// Public from the given day on. An embargo without a day is treated as internal until the
+    /// RFD is given one.
+    Embargoed(Option<NaiveDate>),
+}
+
+impl RFDVisibility {
+    /// Read the visibility from the `visibility` and `embargoed-until` attributes of an RFD. RFDs
+    /// without either attribute are public, and an unknown visibility is treated as internal so
+    /// that a typo does not publish an RFD.
+    pub fn from_metadata(metadata: &RfdMetadata) -> Self {
+        let embargoed_until = metadata
+            .embargoed_until
+            .as_ref()
+            .and_then(|field| NaiveDate::parse_from_str(field.value.trim(), "%Y-%m-%d").ok());
+
+        let visibility = metadata
+            .visibility
+            .as_ref()
+            .map(|field| field.value.trim().to_lowercase())
+            .unwrap_or_default();
+
+        match visibility.as_str() {
+            "" | "public" if metadata.embargoed_until.is_none() => Self::Public,
+            "" | "public" | "embargoed" => Self::Embargoed(embargoed_until),
+            _ => Self::Internal,
+        }
+    }
+
+    /// Read the visibility back from the columns it is stored in.
+    pub fn from_columns(visibility: &str, embargoed_until: Option<NaiveDate>) -> Self {
+        match visibility {
+            "public" => Self::Public,
+            "embargoed" => Self::Embargoed(embargoed_until),
+            _ => Self::Internal,
+        }
+    }
+
+    /// The value stored in the visibility column.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::Internal => "internal",
+            Self::Embargoed(_) => "embargoed",
+        }
+    }
+
+    pub fn embargoed_until(&self) -> Option<NaiveDate> {
+        match self {
+            Self::Embargoed(until) => *until,
+            _ => None,
+        }
+    }
+
+    /// If the RFD may be published on the given day.
+    pub fn is_public(&self, today: NaiveDate) -> bool {
+        match self {
+            Self::Public => true,
+            Self::Internal => false,
+            Self::Embargoed(until) => until.map(|until| until <= today).unwrap_or(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use parse_rfd::{RfdFormat, RfdMetadata};
+
+    use super::RFDVisibility;
+
+    fn visibility(content: &str) -> RFDVisibility {
+        RFDVisibility::from_metadata(&RfdMetadata::parse(content, RfdFormat::Asciidoc))
+    }
+
+    #[test]
+    fn test_visibility_from_metadata() {
+        let until = NaiveDate::from_ymd(2023, 4, 5);
+
+        assert_eq!(RFDVisibility::Public, visibility(":state: discussion\n"));
+        assert_eq!(RFDVisibility::Public, visibility(":visibility: Public\n"));
+        assert_eq!(RFDVisibility::Internal, visibility(":visibility: internal\n"));
+        assert_eq!(RFDVisibility::Internal, visibility(":visibility: secret\n"));
+        assert_eq!(
+            RFDVisibility::Embargoed(Some(until)),
+            visibility(":visibility: embargoed\n:embargoed-until: 2023-04-05\n")
+        );
+        assert_eq!(
+            RFDVisibility::Embargoed(Some(until)),
+            visibility(":embargoed-until: 2023-04-05\n")
+        );
+        assert_eq!(
+            RFDVisibility::Embargoed(None),
+            visibility(":visibility: embargoed\n:embargoed-until: next week\n")
+        );
+    }
+
+    #[test]
+    fn test_is_public() {
+        let until = NaiveDate::from_ymd(2023, 4, 5);
+        let embargoed = RFDVisibility::Embargoed(Some(until));
+
+        assert!(!embargoed.is_public(until.pred()));
+        assert!(embargoed.is_public(until));
+        assert!(!RFDVisibility::Embargoed(None).is_public(until));
+        assert!(!RFDVisibility::Internal.is_public(until));
+        assert!(RFDVisibility::Public.is_public(until));
+
+        assert_eq!(
+            embargoed,
+            RFDVisibility::from_columns(embargoed.as_str(), embargoed.embargoed_until())
+        );
+    }
+}
@@ -0,0 +1,277 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use aws_sdk_route53::{
+    model::{Change, ChangeAction, ChangeBatch, HostedZone, ResourceRecord, ResourceRecordSet, RrType},
+    Client,
+};
+use std::{
+    ops::Add,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use crate::dns_providers::{DNSProviderOps, DnsRecord, DnsRecordType, DnsUpdateMode};
+
+/// Records are written with a short TTL so that ACME challenges are visible quickly.
+const RECORD_TTL: i64 = 60;
+
+struct ZoneCache {
+    zones: Vec<HostedZone>,
+    expires_at: Instant,
+}
+
+impl ZoneCache {
+    pub fn new(zones: Vec<HostedZone>, ttl: u64) -> Self {
+        ZoneCache {
+            zones,
+            expires_at: Instant::now().add(Duration::from_secs(ttl)),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Instant::now()
+    }
+}
+
+pub struct Route53Client {
+    inner: Client,
+    zone_cache: Arc<RwLock<ZoneCache>>,
+    zone_cache_ttl: u64,
+}
+
+impl Route53Client {
+    pub fn new(client: Client) -> Self {
+        Route53Client {
+            inner: client,
+            zone_cache: Arc::new(RwLock::new(ZoneCache::new(vec![], 0))),
+            zone_cache_ttl: 30,
+        }
+    }
+
+    /// Find the public hosted zone that the domain belongs to. When zones are nested the most
+    /// specific zone is used.
+    pub async fn translate_domain_to_zone(&self, domain: &str) -> Result<Option<HostedZone>> {
+        let expired = self.zone_cache.read().unwrap().is_expired();
+
+        if expired {
+            let mut zones = vec![];
+            let mut marker: Option<String> = None;
+
+            loop {
+                let response = self.inner.list_hosted_zones().set_marker(marker.take()).send().await?;
+
+                zones.extend(response.hosted_zones().unwrap_or_default().iter().cloned());
+
+                if response.is_truncated() {
+                    marker = response.next_marker().map(|m| m.to_string());
+                } else {
+                    break;
+                }
+            }
+
+            log::info!("[Route53] Updated zone cache with {} zones", zones.len());
+            *self.zone_cache.write().unwrap() = ZoneCache::new(zones, self.zone_cache_ttl);
+        }
+
+        let domain = to_dns_name(domain);
+
+        Ok(self
+            .zone_cache
+            .read()
+            .unwrap()
+            .zones
+            .iter()
+            .filter(|zone| {
+                let private = zone.config().map(|config| config.private_zone()).unwrap_or(false);
+                !private && zone.name().map(|name| is_in_zone(&domain, name)).unwrap_or(false)
+            })
+            .max_by_key(|zone| zone.name().map(|name| name.len()).unwrap_or(0))
+            .cloned())
+    }
+
+    async fn find_record_set(&self, zone_id: &str, record: &DnsRecord) -> Result<Option<ResourceRecordSet>> {
+        let name = to_dns_name(&record.name);
+        let type_ = to_rr_type(&record.type_);
+
+        let response = self
+            .inner
+            .list_resource_record_sets()
+            .hosted_zone_id(zone_id)
+            .start_record_name(&name)
+            .start_record_type(type_.clone())
+            .max_items(1)
+            .send()
+            .await?;
+
+        // Record sets are listed starting from the requested name and type, so the first set
+        // returned is only a match if it has exactly that name and type
+        Ok(response
+            .resource_record_sets()
+            .unwrap_or_default()
+            .iter()
+            .find(|set| set.name().map(to_dns_name).as_deref() == Some(name.as_str()) && set.r#type() == Some(&type_))
+            .cloned())
+    }
+
+    async fn change(&self, zone_id: &str, action: ChangeAction, set: ResourceRecordSet) -> Result<()> {
+        self.inner
+            .change_resource_record_sets()
+            .hosted_zone_id(zone_id)
+            .change_batch(
+                ChangeBatch::builder()
+                    .changes(Change::builder().action(action).resource_record_set(set).build())
+                    .build(),
+            )
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    async fn zone_id(&self, record: &DnsRecord) -> Result<String> {
+        self.translate_domain_to_zone(&record.name)
+            .await?
+            .and_then(|zone| zone.id().map(|id| id.to_string()))
+            .ok_or_else(|| anyhow!("[Route53] Failed to find zone for {:?}", record))
+    }
+}
+
+/// Route53 zone names are stored with a trailing dot.
+fn to_dns_name(name: &str) -> String {
+    name.trim_end_matches('.').to_lowercase() + "."
+}
+
+fn is_in_zone(domain: &str, zone: &str) -> bool {
+    let zone = to_dns_name(zone);
+    domain == zone || domain.ends_with(&format!(".{}", zone))
+}
+
+fn to_rr_type(type_: &DnsRecordType) -> RrType {
+    RrType::from(type_.to_string().as_str())
+}
+
+/// Route53 requires TXT record values to be quoted.
+fn to_record_value(record: &DnsRecord) -> String {
+    match record.type_ {
+        DnsRecordType::TXT if !record.content.starts_with('"') => format!("\"{}\"", record.content),
+        _ => record.content.to_string(),
+    }
+}
+
+#[async_trait]
+impl DNSProviderOps for Route53Client {
+    /// Ensure the record exists and has the correct information.
+    async fn ensure_record(&self, record: DnsRecord, mode: DnsUpdateMode) -> Result<()> {
+        let zone_id = self.zone_id(&record).await?;
+        let value = to_record_value(&record);
+
+        let mut values = vec![];
+
+        if let Some(existing) = self.find_record_set(&zone_id, &record).await? {
+            let existing_values = existing
+                .resource_records()
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|r| r.value().map(|v| v.to_string()))
+                .collect::<Vec<_>>();
+
+            if existing_values.contains(&value) && (mode == DnsUpdateMode::Append || existing_values.len() == 1) {
+                log::info!("[Route53] Record for {:?} already exists. No updates needed.", record);
+                return Ok(());
+            }
+
+            if mode == DnsUpdateMode::Append {
+                values = existing_values;
+            }
+        }
+
+        values.push(value);
+
+        let set = ResourceRecordSet::builder()
+            .name(to_dns_name(&record.name))
+            .r#type(to_rr_type(&record.type_))
+            .ttl(RECORD_TTL)
+            .set_resource_records(Some(
+                values
+                    .into_iter()
+                    .map(|value| ResourceRecord::builder().value(value).build())
+                    .collect(),
+            ))
+            .build();
+
+        self.change(&zone_id, ChangeAction::Upsert, set).await?;
+
+        log::info!("[Route53] Upserted {}::{} record", record.type_, record.name);
+
+        Ok(())
+    }
+
+    /// Delete the record if it exists.
+    async fn delete_record(&self, record: DnsRecord) -> Result<()> {
+        let zone_id = self.zone_id(&record).await?;
+
+        let existing = match self.find_record_set(&zone_id, &record).await? {
+            Some(existing) => existing,
+            None => {
+                log::info!("[Route53] Record for {:?} does not exist. Nothing to delete.", record);
+                return Ok(());
+            }
+        };
+
+        let value = to_record_value(&record);
+        let remaining = existing
+            .resource_records()
+            .unwrap_or_default()
+            .iter()
+            .filter(|r| r.value() != Some(value.as_str()))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        // Only the value being deleted is removed, any other values in the set are kept
+        if remaining.is_empty() {
+            self.change(&zone_id, ChangeAction::Delete, existing).await?;
+        } else {
+            let set = ResourceRecordSet::builder()
+                .name(to_dns_name(&record.name))
+                .r#type(to_rr_type(&record.type_))
+                .ttl(existing.ttl().unwrap_or(RECORD_TTL))
+                .set_resource_records(Some(remaining))
+                .build();
+
+            self.change(&zone_id, ChangeAction::Upsert, set).await?;
+        }
+
+        log::info!("[Route53] Deleted {}::{} record", record.type_, record.name);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_in_zone() {
+        assert!(is_in_zone("_acme-challenge.example.com.", "example.com."));
+        assert!(is_in_zone("example.com.", "Example.com"));
+        assert!(!is_in_zone("notexample.com.", "example.com."));
+    }
+
+    #[test]
+    fn test_txt_values_are_quoted() {
+        let record = DnsRecord {
+            name: "_acme-challenge.example.com".to_string(),
+            type_: DnsRecordType::TXT,
+            content: "proof".to_string(),
+        };
+        assert_eq!("\"proof\"", to_record_value(&record));
+
+        let record = DnsRecord {
+            type_: DnsRecordType::A,
+            content: "10.0.0.1".to_string(),
+            ..record
+        };
+        assert_eq!("10.0.0.1", to_record_value(&record));
+    }
+}
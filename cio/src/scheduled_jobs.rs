@@ -0,0 +1,97 @@
+/*!
+ * Making sure a scheduled job runs once per interval, no matter how many replicas of the server
+ * are scheduling it.
+ *
+ * Every replica keeps its own schedule, and when a job comes due each of them tries to claim the
+ * run. A claim only succeeds when nobody has run the job for most of its interval. Claiming
+ * locks the job's row for the rest of the transaction, so Postgres lets exactly one replica
+ * through. The replicas do not need to agree on when a job is due; whichever gets there first runs
+ * it and the others skip it.
+ */
+use std::ops::DerefMut;
+
+use anyhow::Result;
+use async_bb8_diesel::AsyncConnection;
+use chrono::{DateTime, Duration, Utc};
+use diesel::{ExpressionMethods, Insertable, QueryDsl, RunQueryDsl};
+
+use crate::{db::Database, schema::scheduled_job_runs};
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = scheduled_job_runs)]
+struct NewScheduledJobRun {
+    job: String,
+    runner: String,
+    last_run_at: DateTime<Utc>,
+}
+
+/// The latest a job can have last run for a new run to be claimed at `now`. Schedulers on
+/// different replicas drift apart a little, so a run is allowed a tenth of the interval early.
+fn claim_threshold(now: DateTime<Utc>, every: Duration) -> DateTime<Utc> {
+    now - (every - every / 10)
+}
+
+/// Try to claim the run of `job` that is due now, for a job that runs `every` so often. Returns
+/// whether `runner` won the claim and should run the job.
+pub async fn claim_scheduled_run(db: &Database, job: &str, runner: &str, every: Duration) -> Result<bool> {
+    let now = Utc::now();
+    let run = NewScheduledJobRun {
+        job: job.to_string(),
+        runner: runner.to_string(),
+        last_run_at: now,
+    };
+
+    let threshold = claim_threshold(now, every);
+
+    db.pool()
+        .transaction(move |conn| {
+            // The first run of a job has nothing to wait for.
+            let inserted = diesel::insert_into(scheduled_job_runs::table)
+                .values(run.clone())
+                .on_conflict_do_nothing()
+                .execute(conn.deref_mut())?;
+            if inserted > 0 {
+                return Ok(true);
+            }
+
+            let last_run_at = scheduled_job_runs::dsl::scheduled_job_runs
+                .find(&run.job)
+                .select(scheduled_job_runs::dsl::last_run_at)
+                .for_update()
+                .first::<DateTime<Utc>>(conn.deref_mut())?;
+            if last_run_at > threshold {
+                return Ok(false);
+            }
+
+            diesel::update(scheduled_job_runs::dsl::scheduled_job_runs.find(&run.job))
+                .set((
+                    scheduled_job_runs::dsl::runner.eq(&run.runner),
+                    scheduled_job_runs::dsl::last_run_at.eq(run.last_run_at),
+                ))
+                .execute(conn.deref_mut())?;
+
+            Ok(true)
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::claim_threshold;
+
+    #[test]
+    fn test_claim_threshold() {
+        let now = Utc.ymd(2023, 3, 24).and_hms(12, 0, 0);
+
+        assert_eq!(
+            Utc.ymd(2023, 3, 24).and_hms(1, 12, 0),
+            claim_threshold(now, Duration::hours(12))
+        );
+        assert_eq!(
+            Utc.ymd(2023, 3, 24).and_hms(11, 46, 30),
+            claim_threshold(now, Duration::minutes(15))
+        );
+    }
+}
@@ -1,3 +1,27 @@
+table! {
+    access_requests (id) {
+        id -> Int4,
+        kind -> Varchar,
+        resource -> Varchar,
+        permission -> Varchar,
+        requester_email -> Varchar,
+        requester_slack_id -> Varchar,
+        reason -> Varchar,
+        days -> Nullable<Int4>,
+        status -> Varchar,
+        decided_by -> Varchar,
+        decided_at -> Nullable<Timestamptz>,
+        granted_at -> Nullable<Timestamptz>,
+        expires_at -> Nullable<Timestamptz>,
+        revoked_at -> Nullable<Timestamptz>,
+        error -> Varchar,
+        cio_company_id -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        had_access -> Bool,
+    }
+}
+
 table! {
     accounts_payables (id) {
         id -> Int4,
@@ -40,6 +64,26 @@ table! {
     }
 }
 
+table! {
+    applicant_duplicates (id) {
+        id -> Int4,
+        applicant_id -> Int4,
+        duplicate_id -> Int4,
+        applicant_name -> Varchar,
+        applicant_email -> Varchar,
+        applicant_role -> Varchar,
+        duplicate_name -> Varchar,
+        duplicate_email -> Varchar,
+        duplicate_role -> Varchar,
+        reasons -> Array<Text>,
+        score -> Int4,
+        status -> Varchar,
+        applicants -> Array<Text>,
+        cio_company_id -> Int4,
+        airtable_record_id -> Varchar,
+    }
+}
+
 table! {
     applicant_interviews (id) {
         id -> Int4,
@@ -50,6 +94,8 @@ table! {
         interviewers -> Array<Text>,
         google_event_id -> Varchar,
         event_link -> Varchar,
+        zoom_meeting_id -> Varchar,
+        zoom_join_url -> Varchar,
         applicant -> Array<Text>,
         cio_company_id -> Int4,
         airtable_record_id -> Varchar,
@@ -90,6 +136,16 @@ table! {
     }
 }
 
+table! {
+    applicant_sources (id) {
+        id -> Int4,
+        applicant_id -> Int4,
+        source -> Varchar,
+        cio_company_id -> Int4,
+        created_at -> Timestamptz,
+    }
+}
+
 table! {
     applicants (id) {
         id -> Int4,
@@ -155,6 +211,10 @@ table! {
         scoring_underwhelming_materials_count -> Int4,
         criminal_background_check_status -> Varchar,
         motor_vehicle_background_check_status -> Varchar,
+        checkr_report_id -> Varchar,
+        checkr_adverse_action_id -> Varchar,
+        adverse_action_status -> Varchar,
+        background_check_status -> Varchar,
         start_date -> Nullable<Date>,
         interested_in -> Array<Text>,
         geocode_cache -> Varchar,
@@ -166,7 +226,13 @@ table! {
         docusign_piia_envelope_status -> Varchar,
         piia_envelope_created -> Nullable<Timestamptz>,
         piia_envelope_completed -> Nullable<Timestamptz>,
+        offer_salary -> Varchar,
+        offer_equity -> Varchar,
+        offer_documents_link -> Varchar,
+        employee_agreements_link -> Varchar,
         link_to_reviews -> Array<Text>,
+        score -> Float4,
+        score_details -> Varchar,
         cio_company_id -> Int4,
         airtable_record_id -> Varchar,
     }
@@ -180,6 +246,21 @@ table! {
         created_at -> Timestamptz,
         expires_at -> Timestamptz,
         used_at -> Nullable<Timestamptz>,
+        cio_company_id -> Int4,
+    }
+}
+
+table! {
+    asset_assignments (id) {
+        id -> Int4,
+        asset_item_id -> Int4,
+        username -> Varchar,
+        checked_out_at -> Timestamptz,
+        due_back -> Nullable<Date>,
+        checked_in_at -> Nullable<Timestamptz>,
+        last_nagged_at -> Nullable<Timestamptz>,
+        notes -> Varchar,
+        cio_company_id -> Int4,
     }
 }
 
@@ -203,6 +284,10 @@ table! {
         barcode_png -> Varchar,
         barcode_svg -> Varchar,
         barcode_pdf_label -> Varchar,
+        qr_code_png -> Varchar,
+        qr_code_svg -> Varchar,
+        purchase_date -> Nullable<Date>,
+        vendor -> Varchar,
         cio_company_id -> Int4,
         airtable_record_id -> Varchar,
     }
@@ -266,6 +351,22 @@ table! {
     }
 }
 
+table! {
+    background_check_events (id) {
+        id -> Int4,
+        subject -> Varchar,
+        email -> Varchar,
+        name -> Varchar,
+        role -> Varchar,
+        status -> Varchar,
+        provider -> Varchar,
+        provider_event_type -> Varchar,
+        report_id -> Varchar,
+        cio_company_id -> Int4,
+        created_at -> Timestamptz,
+    }
+}
+
 table! {
     barcode_scans (id) {
         id -> Int4,
@@ -335,6 +436,28 @@ table! {
     }
 }
 
+table! {
+    celebration_posts (id) {
+        id -> Int4,
+        username -> Varchar,
+        occasion -> Varchar,
+        occasion_date -> Date,
+        cio_company_id -> Int4,
+        posted_at -> Timestamptz,
+    }
+}
+
+table! {
+    celebration_preferences (id) {
+        id -> Int4,
+        username -> Varchar,
+        opted_out -> Bool,
+        share_birthday -> Bool,
+        cio_company_id -> Int4,
+        updated_at -> Timestamptz,
+    }
+}
+
 table! {
     certificates (id) {
         id -> Int4,
@@ -441,6 +564,76 @@ table! {
     }
 }
 
+table! {
+    data_subject_requests (id) {
+        id -> Int4,
+        kind -> Varchar,
+        subject_hash -> Varchar,
+        requested_by -> Varchar,
+        actions -> Array<Text>,
+        cio_company_id -> Int4,
+        created_at -> Timestamptz,
+    }
+}
+
+table! {
+    device_credentials (id) {
+        id -> Int4,
+        username -> Varchar,
+        kind -> Varchar,
+        external_id -> Varchar,
+        expires_at -> Timestamptz,
+        revoked_at -> Nullable<Timestamptz>,
+        download_token_hash -> Varchar,
+        download_expires_at -> Timestamptz,
+        downloaded_at -> Nullable<Timestamptz>,
+        payload -> Nullable<Bytea>,
+        cio_company_id -> Int4,
+        created_at -> Timestamptz,
+    }
+}
+
+table! {
+    drive_documents (id) {
+        id -> Int4,
+        shared_drive -> Varchar,
+        path -> Varchar,
+        name -> Varchar,
+        content_hash -> Varchar,
+        file_id -> Varchar,
+        url -> Varchar,
+        uploaded_at -> Timestamptz,
+        cio_company_id -> Int4,
+    }
+}
+
+table! {
+    email_messages (id) {
+        id -> Int4,
+        provider -> Varchar,
+        provider_message_id -> Varchar,
+        template -> Varchar,
+        subject -> Varchar,
+        recipients -> Array<Text>,
+        status -> Varchar,
+        status_reason -> Varchar,
+        sent_at -> Timestamptz,
+        status_updated_at -> Timestamptz,
+        cio_company_id -> Int4,
+    }
+}
+
+table! {
+    email_suppressions (id) {
+        id -> Int4,
+        email -> Varchar,
+        reason -> Varchar,
+        source -> Varchar,
+        suppressed_at -> Timestamptz,
+        cio_company_id -> Int4,
+    }
+}
+
 table! {
     expensed_items (id) {
         id -> Int4,
@@ -478,6 +671,31 @@ table! {
     }
 }
 
+table! {
+    geocode_cache_entries (id) {
+        id -> Int4,
+        address_hash -> Varchar,
+        address -> Varchar,
+        latitude -> Float8,
+        longitude -> Float8,
+        cached_at -> Timestamptz,
+        time_zone_id -> Varchar,
+    }
+}
+
+table! {
+    github_event_routes (id) {
+        id -> Int4,
+        event_type -> Varchar,
+        repo_glob -> Varchar,
+        action -> Varchar,
+        handler -> Varchar,
+        enabled -> Bool,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
 table! {
     github_repos (id) {
         id -> Int4,
@@ -577,6 +795,29 @@ table! {
     }
 }
 
+table! {
+    huddle_attendances (id) {
+        id -> Int4,
+        huddle -> Varchar,
+        meeting_date -> Date,
+        email -> Varchar,
+        duration_seconds -> Int4,
+        recorded_at -> Timestamptz,
+        cio_company_id -> Int4,
+    }
+}
+
+table! {
+    huddle_zoom_meetings (id) {
+        id -> Int4,
+        huddle -> Varchar,
+        meeting_id -> Varchar,
+        webinar -> Bool,
+        join_url -> Varchar,
+        cio_company_id -> Int4,
+    }
+}
+
 table! {
     inbound_shipments (id) {
         id -> Int4,
@@ -597,6 +838,33 @@ table! {
     }
 }
 
+table! {
+    interview_schedules (id) {
+        id -> Int4,
+        applicant_id -> Int4,
+        email -> Varchar,
+        panel -> Jsonb,
+        proposed_times -> Array<Timestamptz>,
+        token -> Varchar,
+        status -> Varchar,
+        booked_time -> Nullable<Timestamptz>,
+        google_event_ids -> Array<Text>,
+        cio_company_id -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+table! {
+    interviewer_packets (id) {
+        id -> Int4,
+        google_event_id -> Varchar,
+        interviewer -> Varchar,
+        cio_company_id -> Int4,
+        sent_at -> Timestamptz,
+    }
+}
+
 table! {
     journal_club_meetings (id) {
         id -> Int4,
@@ -613,6 +881,35 @@ table! {
     }
 }
 
+table! {
+    journal_club_proposals (id) {
+        id -> Int4,
+        link -> Varchar,
+        title -> Varchar,
+        proposed_by -> Varchar,
+        source -> Varchar,
+        source_link -> Varchar,
+        status -> Varchar,
+        doi -> Varchar,
+        authors -> Array<Text>,
+        published_year -> Nullable<Int4>,
+        venue -> Varchar,
+        abstract_text -> Text,
+        pdf_url -> Varchar,
+        fetched_at -> Nullable<Timestamptz>,
+        huddle -> Varchar,
+        meeting_date -> Nullable<Date>,
+        airtable_topic_id -> Varchar,
+        slack_channel -> Varchar,
+        slack_ts -> Varchar,
+        discussion_notes -> Text,
+        recording -> Varchar,
+        cio_company_id -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
 table! {
     journal_club_papers (id) {
         id -> Int4,
@@ -671,6 +968,82 @@ table! {
     }
 }
 
+table! {
+    notification_rules (id) {
+        id -> Int4,
+        event -> Varchar,
+        channel -> Varchar,
+        target -> Varchar,
+        conditions -> Jsonb,
+        enabled -> Bool,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        cio_company_id -> Int4,
+    }
+}
+
+table! {
+    offboarding_actions (id) {
+        id -> Int4,
+        username -> Varchar,
+        service -> Varchar,
+        status -> Varchar,
+        detail -> Varchar,
+        cio_company_id -> Int4,
+        created_at -> Timestamptz,
+    }
+}
+
+table! {
+    offer_approvals (id) {
+        id -> Int4,
+        applicant_id -> Int4,
+        salary -> Varchar,
+        equity -> Varchar,
+        start_date -> Nullable<Date>,
+        status -> Varchar,
+        decided_by -> Varchar,
+        decided_at -> Nullable<Timestamptz>,
+        envelope_id -> Varchar,
+        sent_at -> Nullable<Timestamptz>,
+        completed_at -> Nullable<Timestamptz>,
+        cio_company_id -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+table! {
+    onboarding_tasks (id) {
+        id -> Int4,
+        username -> Varchar,
+        name -> Varchar,
+        kind -> Varchar,
+        due_date -> Date,
+        status -> Varchar,
+        attempts -> Int4,
+        last_error -> Varchar,
+        next_attempt_at -> Nullable<Timestamptz>,
+        completed_at -> Nullable<Timestamptz>,
+        cio_company_id -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+table! {
+    outbound_shipment_events (id) {
+        id -> Int4,
+        outbound_shipment_id -> Int4,
+        kind -> Varchar,
+        previous_value -> Varchar,
+        value -> Varchar,
+        changed_by -> Varchar,
+        created_at -> Timestamptz,
+        cio_company_id -> Int4,
+    }
+}
+
 table! {
     outbound_shipments (id) {
         id -> Int4,
@@ -747,6 +1120,61 @@ table! {
     }
 }
 
+table! {
+    pagerduty_oncalls (id) {
+        id -> Int4,
+        schedule_id -> Varchar,
+        schedule_name -> Varchar,
+        escalation_level -> Int4,
+        pagerduty_user_id -> Varchar,
+        name -> Varchar,
+        email -> Varchar,
+        username -> Varchar,
+        start_time -> Nullable<Timestamptz>,
+        end_time -> Nullable<Timestamptz>,
+        updated_at -> Timestamptz,
+        cio_company_id -> Int4,
+    }
+}
+
+table! {
+    payments (id) {
+        id -> Int4,
+        stripe_id -> Varchar,
+        kind -> Varchar,
+        status -> Varchar,
+        amount -> Float4,
+        amount_refunded -> Float4,
+        currency -> Varchar,
+        customer_name -> Varchar,
+        customer_email -> Varchar,
+        description -> Varchar,
+        stripe_payment_intent -> Varchar,
+        stripe_invoice_number -> Varchar,
+        quickbooks_id -> Varchar,
+        quickbooks_type -> Varchar,
+        paid_at -> Timestamptz,
+        refunded_at -> Nullable<Timestamptz>,
+        updated_at -> Timestamptz,
+        cio_company_id -> Int4,
+    }
+}
+
+table! {
+    policy_acknowledgments (id) {
+        id -> Int4,
+        policy -> Varchar,
+        user_email -> Varchar,
+        user_name -> Varchar,
+        docusign_batch_id -> Varchar,
+        docusign_envelope_id -> Varchar,
+        docusign_envelope_status -> Varchar,
+        sent_at -> Timestamptz,
+        completed_at -> Nullable<Timestamptz>,
+        cio_company_id -> Int4,
+    }
+}
+
 table! {
     rack_line_subscribers (id) {
         id -> Int4,
@@ -790,6 +1218,31 @@ table! {
     }
 }
 
+table! {
+    rfd_history_events (id) {
+        id -> Int4,
+        rfd_number -> Int4,
+        kind -> Varchar,
+        previous_value -> Varchar,
+        value -> Varchar,
+        sha -> Varchar,
+        committed_at -> Timestamptz,
+        cio_company_id -> Int4,
+    }
+}
+
+table! {
+    rfd_review_reminders (id) {
+        id -> Int4,
+        rfd_number -> Int4,
+        pull_request_number -> Int8,
+        last_activity_at -> Timestamptz,
+        escalation_level -> Int4,
+        reminded_at -> Timestamptz,
+        cio_company_id -> Int4,
+    }
+}
+
 table! {
     rfds (id) {
         id -> Int4,
@@ -811,11 +1264,36 @@ table! {
         relevant_components -> Array<Text>,
         pdf_link_github -> Varchar,
         pdf_link_google_drive -> Varchar,
+        visibility -> Varchar,
+        embargoed_until -> Nullable<Date>,
         cio_company_id -> Int4,
         airtable_record_id -> Varchar,
     }
 }
 
+table! {
+    scheduled_job_runs (job) {
+        job -> Varchar,
+        runner -> Varchar,
+        last_run_at -> Timestamptz,
+    }
+}
+
+table! {
+    scheduled_messages (id) {
+        id -> Int4,
+        kind -> Varchar,
+        recipient -> Varchar,
+        message -> Jsonb,
+        send_at -> Timestamptz,
+        sent_at -> Nullable<Timestamptz>,
+        attempts -> Int4,
+        last_error -> Varchar,
+        created_at -> Timestamptz,
+        cio_company_id -> Int4,
+    }
+}
+
 table! {
     software_vendors (id) {
         id -> Int4,
@@ -877,6 +1355,35 @@ table! {
     }
 }
 
+table! {
+    swag_preference_requests (id) {
+        id -> Int4,
+        outbound_shipment_id -> Int4,
+        name -> Varchar,
+        email -> Varchar,
+        token_hash -> Varchar,
+        expires_at -> Timestamptz,
+        shirt_size -> Varchar,
+        submitted_at -> Nullable<Timestamptz>,
+        cio_company_id -> Int4,
+        created_at -> Timestamptz,
+    }
+}
+
+table! {
+    swag_stock_levels (id) {
+        id -> Int4,
+        swag_inventory_item_id -> Int4,
+        location -> Varchar,
+        on_hand -> Int4,
+        low_stock_threshold -> Int4,
+        low_stock_alerted_at -> Nullable<Timestamptz>,
+        cio_company_id -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
 table! {
     users (id) {
         id -> Int4,
@@ -891,6 +1398,7 @@ table! {
         github -> Varchar,
         twitter -> Varchar,
         department -> Varchar,
+        title -> Varchar,
         manager -> Varchar,
         link_to_manager -> Array<Text>,
         groups -> Array<Text>,
@@ -932,80 +1440,211 @@ table! {
         geocode_cache -> Varchar,
         working_on -> Array<Text>,
         gusto_pull_permission -> Bool,
+        checkr_candidate_id -> Varchar,
+        continuous_check_status -> Varchar,
+        background_check_status -> Varchar,
+        termination_date -> Nullable<Date>,
+        photo_url -> Varchar,
         cio_company_id -> Int4,
         airtable_record_id -> Varchar,
     }
 }
 
+table! {
+    webhook_consumers (id) {
+        id -> Int4,
+        name -> Varchar,
+        url -> Varchar,
+        secret -> Varchar,
+        events -> Array<Text>,
+        enabled -> Bool,
+        cio_company_id -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+table! {
+    webhook_deliveries (id) {
+        id -> Int4,
+        consumer_id -> Int4,
+        event -> Varchar,
+        event_key -> Varchar,
+        payload -> Jsonb,
+        status -> Varchar,
+        attempts -> Int4,
+        next_attempt_at -> Timestamptz,
+        last_attempt_at -> Nullable<Timestamptz>,
+        response_status -> Nullable<Int4>,
+        last_error -> Varchar,
+        delivered_at -> Nullable<Timestamptz>,
+        cio_company_id -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+table! {
+    webhook_events (id) {
+        id -> Int4,
+        source -> Varchar,
+        event_type -> Varchar,
+        payload -> Jsonb,
+        status -> Varchar,
+        attempts -> Int4,
+        last_error -> Varchar,
+        received_at -> Timestamptz,
+        available_at -> Timestamptz,
+        started_at -> Nullable<Timestamptz>,
+        finished_at -> Nullable<Timestamptz>,
+        correlation_id -> Varchar,
+    }
+}
+
+joinable!(access_requests -> companys (cio_company_id));
 joinable!(accounts_payables -> companys (cio_company_id));
 joinable!(api_tokens -> companys (auth_company_id));
+joinable!(applicant_duplicates -> companys (cio_company_id));
 joinable!(applicant_interviews -> companys (cio_company_id));
 joinable!(applicant_reviewers -> companys (cio_company_id));
 joinable!(applicant_reviews -> companys (cio_company_id));
+joinable!(applicant_sources -> applicants (applicant_id));
+joinable!(applicant_sources -> companys (cio_company_id));
 joinable!(applicants -> companys (cio_company_id));
+joinable!(asset_assignments -> asset_items (asset_item_id));
+joinable!(asset_assignments -> companys (cio_company_id));
 joinable!(asset_items -> companys (cio_company_id));
 joinable!(auth_user_logins -> companys (cio_company_id));
 joinable!(auth_users -> companys (cio_company_id));
+joinable!(background_check_events -> companys (cio_company_id));
 joinable!(barcode_scans -> companys (cio_company_id));
 joinable!(bookings -> companys (cio_company_id));
 joinable!(buildings -> companys (cio_company_id));
+joinable!(celebration_posts -> companys (cio_company_id));
+joinable!(celebration_preferences -> companys (cio_company_id));
 joinable!(certificates -> companys (cio_company_id));
 joinable!(credit_card_transactions -> companys (cio_company_id));
+joinable!(data_subject_requests -> companys (cio_company_id));
+joinable!(device_credentials -> companys (cio_company_id));
+joinable!(drive_documents -> companys (cio_company_id));
+joinable!(email_messages -> companys (cio_company_id));
+joinable!(email_suppressions -> companys (cio_company_id));
 joinable!(expensed_items -> companys (cio_company_id));
 joinable!(functions -> companys (cio_company_id));
 joinable!(github_repos -> companys (cio_company_id));
 joinable!(groups -> companys (cio_company_id));
+joinable!(huddle_attendances -> companys (cio_company_id));
+joinable!(huddle_zoom_meetings -> companys (cio_company_id));
 joinable!(inbound_shipments -> companys (cio_company_id));
+joinable!(interview_schedules -> companys (cio_company_id));
+joinable!(interviewer_packets -> companys (cio_company_id));
 joinable!(journal_club_meetings -> companys (cio_company_id));
 joinable!(journal_club_papers -> companys (cio_company_id));
+joinable!(journal_club_proposals -> companys (cio_company_id));
 joinable!(links -> companys (cio_company_id));
 joinable!(mailing_list_subscribers -> companys (cio_company_id));
+joinable!(notification_rules -> companys (cio_company_id));
+joinable!(offboarding_actions -> companys (cio_company_id));
+joinable!(offer_approvals -> companys (cio_company_id));
+joinable!(onboarding_tasks -> companys (cio_company_id));
+joinable!(outbound_shipment_events -> companys (cio_company_id));
+joinable!(outbound_shipment_events -> outbound_shipments (outbound_shipment_id));
 joinable!(outbound_shipments -> companys (cio_company_id));
 joinable!(package_pickups -> companys (cio_company_id));
 joinable!(page_views -> companys (cio_company_id));
+joinable!(pagerduty_oncalls -> companys (cio_company_id));
+joinable!(payments -> companys (cio_company_id));
+joinable!(policy_acknowledgments -> companys (cio_company_id));
 joinable!(rack_line_subscribers -> companys (cio_company_id));
 joinable!(recorded_meetings -> companys (cio_company_id));
 joinable!(resources -> companys (cio_company_id));
+joinable!(rfd_history_events -> companys (cio_company_id));
+joinable!(rfd_review_reminders -> companys (cio_company_id));
 joinable!(rfds -> companys (cio_company_id));
+joinable!(scheduled_messages -> companys (cio_company_id));
 joinable!(software_vendors -> companys (cio_company_id));
 joinable!(swag_inventory_items -> companys (cio_company_id));
 joinable!(swag_items -> companys (cio_company_id));
+joinable!(swag_preference_requests -> companys (cio_company_id));
+joinable!(swag_preference_requests -> outbound_shipments (outbound_shipment_id));
+joinable!(swag_stock_levels -> companys (cio_company_id));
+joinable!(swag_stock_levels -> swag_inventory_items (swag_inventory_item_id));
+joinable!(upload_tokens -> companys (cio_company_id));
 joinable!(users -> companys (cio_company_id));
+joinable!(webhook_consumers -> companys (cio_company_id));
+joinable!(webhook_deliveries -> companys (cio_company_id));
+joinable!(webhook_deliveries -> webhook_consumers (consumer_id));
 
 allow_tables_to_appear_in_same_query!(
+    access_requests,
     accounts_payables,
     api_tokens,
+    applicant_duplicates,
     applicant_interviews,
     applicant_reviewers,
     applicant_reviews,
+    applicant_sources,
     applicants,
+    asset_assignments,
     asset_items,
     auth_user_logins,
     auth_users,
+    background_check_events,
     barcode_scans,
     bookings,
     buildings,
+    celebration_posts,
+    celebration_preferences,
     certificates,
     companys,
     credit_card_transactions,
+    data_subject_requests,
+    device_credentials,
+    drive_documents,
+    email_messages,
+    email_suppressions,
     expensed_items,
     functions,
+    geocode_cache_entries,
+    github_event_routes,
     github_repos,
     groups,
+    huddle_attendances,
+    huddle_zoom_meetings,
     inbound_shipments,
+    interview_schedules,
+    interviewer_packets,
     journal_club_meetings,
     journal_club_papers,
+    journal_club_proposals,
     links,
     mailing_list_subscribers,
+    notification_rules,
+    offboarding_actions,
+    offer_approvals,
+    onboarding_tasks,
+    outbound_shipment_events,
     outbound_shipments,
     package_pickups,
     page_views,
+    pagerduty_oncalls,
+    payments,
+    policy_acknowledgments,
     rack_line_subscribers,
     recorded_meetings,
     resources,
+    rfd_history_events,
+    rfd_review_reminders,
     rfds,
+    scheduled_job_runs,
+    scheduled_messages,
     software_vendors,
     swag_inventory_items,
     swag_items,
+    swag_preference_requests,
+    swag_stock_levels,
     users,
+    webhook_consumers,
+    webhook_deliveries,
+    webhook_events,
 );
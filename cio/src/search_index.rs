@@ -0,0 +1,29 @@
+/*!
+ * What the Meilisearch indexes of RFDs, applicants and journal club papers have in common.
+ *
+ * Meilisearch applies every change to an index as a task in the background. The indexes wait for
+ * the tasks that add and remove documents, so that a sync that moves on has seen them applied, and
+ * that a change Meilisearch could not make fails the sync rather than going unnoticed.
+ */
+use std::time::Duration;
+
+use anyhow::Result;
+use meilisearch_minimal_api::{IndexSettings, MeiliClient, TaskResponse};
+
+/// How long to wait for Meilisearch to apply a change to an index.
+const TASK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Apply the settings of `index`. Meilisearch only rebuilds the index when the settings change.
+pub async fn configure_index(client: &MeiliClient, index: &str, settings: &IndexSettings) -> Result<()> {
+    client.index(index.to_string()).update_settings(settings).await?;
+
+    Ok(())
+}
+
+/// Wait for Meilisearch to apply the change it queued as `task`. Fails when the change could not
+/// be made, or was not made within [`TASK_TIMEOUT`].
+pub async fn wait_for_task(client: &MeiliClient, task: TaskResponse) -> Result<()> {
+    client.wait_for_task(task.task_uid, TASK_TIMEOUT).await?;
+
+    Ok(())
+}
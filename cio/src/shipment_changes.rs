@@ -0,0 +1,417 @@
+/*!
+ * Changes ops make to outbound shipments once their label is bought: printing the label again
+ * on a chosen printer, and correcting the address of a package that has not shipped yet.
+ *
+ * A label is bought for an address, so correcting the address of a shipment that has one voids
+ * the label in Shippo and buys and prints a new one, the same way the first one was. Every change
+ * is recorded in the history of the shipment, along with the labels that were voided and bought.
+ */
+use std::fmt;
+
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable};
+use log::info;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use shippo::{Address, NewRefund, Shippo};
+
+use crate::{
+    db::Database,
+    printer::LabelPrinter,
+    schema::{outbound_shipment_events, outbound_shipments},
+    shipment_status::Status,
+    shipments::OutboundShipment,
+    swag_preferences::validation_messages,
+};
+
+pub const SHIPMENT_EVENT_LABEL_REPRINTED: &str = "label_reprinted";
+pub const SHIPMENT_EVENT_ADDRESS_CORRECTED: &str = "address_corrected";
+pub const SHIPMENT_EVENT_LABEL_VOIDED: &str = "label_voided";
+pub const SHIPMENT_EVENT_LABEL_PURCHASED: &str = "label_purchased";
+
+/// A change to an outbound shipment.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct OutboundShipmentEvent {
+    pub id: i32,
+    pub outbound_shipment_id: i32,
+    /// One of `label_reprinted`, `address_corrected`, `label_voided` or `label_purchased`.
+    pub kind: String,
+    /// The address or the tracking number before the change.
+    pub previous_value: String,
+    /// The address or the tracking number after the change, or the printer the label was
+    /// printed on.
+    pub value: String,
+    pub changed_by: String,
+    pub created_at: DateTime<Utc>,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = outbound_shipment_events)]
+struct NewOutboundShipmentEvent {
+    outbound_shipment_id: i32,
+    kind: String,
+    previous_value: String,
+    value: String,
+    changed_by: String,
+    cio_company_id: i32,
+}
+
+impl OutboundShipmentEvent {
+    /// The history of a shipment, oldest first.
+    pub async fn list_for_shipment(db: &Database, company_id: i32, shipment_id: i32) -> Result<Vec<Self>> {
+        Ok(outbound_shipment_events::dsl::outbound_shipment_events
+            .filter(outbound_shipment_events::dsl::cio_company_id.eq(company_id))
+            .filter(outbound_shipment_events::dsl::outbound_shipment_id.eq(shipment_id))
+            .order_by(outbound_shipment_events::dsl::id)
+            .load_async::<OutboundShipmentEvent>(db.pool())
+            .await?)
+    }
+}
+
+async fn record_event(
+    db: &Database,
+    shipment: &OutboundShipment,
+    kind: &str,
+    previous_value: &str,
+    value: &str,
+    changed_by: &str,
+) -> Result<()> {
+    diesel::insert_into(outbound_shipment_events::table)
+        .values(NewOutboundShipmentEvent {
+            outbound_shipment_id: shipment.id,
+            kind: kind.to_string(),
+            previous_value: previous_value.to_string(),
+            value: value.to_string(),
+            changed_by: changed_by.to_string(),
+            cio_company_id: shipment.cio_company_id,
+        })
+        .execute_async(db.pool())
+        .await?;
+
+    Ok(())
+}
+
+/// Why a shipment could not be changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShipmentChangeRejection {
+    UnknownShipment,
+    NoLabel,
+    NoPrinter,
+    /// The package is on its way, or further along, with the status it is in.
+    Shipped(String),
+    /// Only labels bought through Shippo can be voided, the provider is who the label was
+    /// bought through.
+    NotShippo(String),
+    /// Shippo could not validate the address, with what it had to say about it.
+    UndeliverableAddress(String),
+}
+
+impl fmt::Display for ShipmentChangeRejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShipmentChangeRejection::UnknownShipment => write!(f, "there is no such shipment"),
+            ShipmentChangeRejection::NoLabel => write!(f, "the shipment does not have a label yet"),
+            ShipmentChangeRejection::NoPrinter => write!(f, "the company does not have a printer"),
+            ShipmentChangeRejection::Shipped(status) => {
+                write!(f, "the shipment can no longer be changed, it is `{}`", status)
+            }
+            ShipmentChangeRejection::NotShippo(provider) => {
+                write!(f, "labels bought through `{}` can not be voided", provider)
+            }
+            ShipmentChangeRejection::UndeliverableAddress(messages) if messages.is_empty() => {
+                write!(f, "the address could not be validated")
+            }
+            ShipmentChangeRejection::UndeliverableAddress(messages) => {
+                write!(f, "the address could not be validated: {}", messages)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ShipmentChangeRejection {}
+
+async fn find_shipment(db: &Database, company_id: i32, id: i32) -> Result<OutboundShipment> {
+    outbound_shipments::dsl::outbound_shipments
+        .filter(outbound_shipments::dsl::cio_company_id.eq(company_id))
+        .filter(outbound_shipments::dsl::id.eq(id))
+        .first_async::<OutboundShipment>(db.pool())
+        .await
+        .optional()?
+        .ok_or_else(|| ShipmentChangeRejection::UnknownShipment.into())
+}
+
+/// Print the label of a shipment again.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct LabelReprint {
+    #[serde(default)]
+    pub printer: LabelPrinter,
+    /// Who asked for the label, for the history of the shipment.
+    #[serde(default)]
+    pub changed_by: String,
+}
+
+/// Send the label of a shipment to a printer again, ie. because the first one jammed.
+pub async fn reprint_label(db: &Database, company_id: i32, id: i32, reprint: LabelReprint) -> Result<OutboundShipment> {
+    let mut shipment = find_shipment(db, company_id, id).await?;
+    if shipment.label_link.trim().is_empty() {
+        return Err(ShipmentChangeRejection::NoLabel.into());
+    }
+    if shipment.company(db).await?.printer_url.is_empty() {
+        return Err(ShipmentChangeRejection::NoPrinter.into());
+    }
+
+    shipment.print_label_to(db, reprint.printer).await?;
+    if shipment.status == Status::LabelCreated.to_string() {
+        shipment.set_status(Status::LabelPrinted).await?;
+        shipment = shipment.update(db).await?;
+    }
+
+    record_event(
+        db,
+        &shipment,
+        SHIPMENT_EVENT_LABEL_REPRINTED,
+        "",
+        reprint.printer.as_str(),
+        &reprint.changed_by,
+    )
+    .await?;
+    info!(
+        "reprinted the label of shipment {} on the {} printer",
+        shipment.id,
+        reprint.printer.as_str()
+    );
+
+    Ok(shipment)
+}
+
+/// The address a shipment should have been sent to.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct AddressCorrection {
+    pub street_1: String,
+    #[serde(default)]
+    pub street_2: String,
+    pub city: String,
+    #[serde(default)]
+    pub state: String,
+    pub zipcode: String,
+    pub country: String,
+    /// The phone number of the recipient is kept when this is empty.
+    #[serde(default)]
+    pub phone: String,
+    /// Who corrected the address, for the history of the shipment.
+    #[serde(default)]
+    pub changed_by: String,
+}
+
+/// Whether the address of a shipment can still be corrected, which is until it is picked up by
+/// the carrier.
+fn check_not_shipped(shipment: &OutboundShipment) -> std::result::Result<(), ShipmentChangeRejection> {
+    let correctable = [
+        Status::Queued,
+        Status::OnHold,
+        Status::LabelCreated,
+        Status::LabelPrinted,
+        Status::WaitingForPickup,
+    ];
+
+    if shipment.local_pickup
+        || shipment.shipped_time.is_some()
+        || shipment.delivered_time.is_some()
+        || !correctable.iter().any(|status| shipment.status == status.to_string())
+    {
+        return Err(ShipmentChangeRejection::Shipped(shipment.status.to_string()));
+    }
+
+    Ok(())
+}
+
+/// Correct the address of a shipment that has not shipped yet. The address is validated with
+/// Shippo, and the shipment gets the address as Shippo corrected it. When the shipment already
+/// has a label, the label is voided and a new one is bought and printed.
+pub async fn correct_address(
+    db: &Database,
+    company_id: i32,
+    id: i32,
+    correction: AddressCorrection,
+) -> Result<OutboundShipment> {
+    let mut shipment = find_shipment(db, company_id, id).await?;
+    check_not_shipped(&shipment)?;
+
+    let has_label = !shipment.provider_id.is_empty();
+    if has_label && shipment.provider != "Shippo" {
+        return Err(ShipmentChangeRejection::NotShippo(shipment.provider.to_string()).into());
+    }
+
+    let shippo = Shippo::new_from_env();
+    let address = shippo
+        .validate_address(&Address {
+            name: shipment.name.to_string(),
+            street1: correction.street_1.trim().to_string(),
+            street2: correction.street_2.trim().to_string(),
+            city: correction.city.trim().to_string(),
+            state: correction.state.trim().to_string(),
+            zip: correction.zipcode.trim().to_string(),
+            country: correction.country.trim().to_string(),
+            phone: correction.phone.trim().to_string(),
+            email: shipment.email.to_string(),
+            ..Default::default()
+        })
+        .await?;
+    if !address
+        .validation_results
+        .as_ref()
+        .is_some_and(|results| results.is_valid)
+    {
+        return Err(ShipmentChangeRejection::UndeliverableAddress(validation_messages(&address)).into());
+    }
+
+    let previous_address = shipment.address_formatted.to_string();
+    let previous_tracking_number = shipment.tracking_number.to_string();
+
+    if has_label {
+        // A correction that failed after the label was voided is retried without asking Shippo to
+        // refund the label a second time.
+        let label = shippo.get_shipping_label(&shipment.provider_id).await?;
+        if label.status != "REFUNDED" && label.status != "REFUNDPENDING" {
+            let refund = shippo
+                .create_refund(NewRefund {
+                    transaction: shipment.provider_id.to_string(),
+                    r#async: false,
+                })
+                .await?;
+            if refund.status == "ERROR" {
+                bail!(
+                    "Shippo would not void label {} of shipment {}",
+                    shipment.provider_id,
+                    shipment.id
+                );
+            }
+        }
+
+        // The shipment is bought a label again as if it were new.
+        shipment.carrier = Default::default();
+        shipment.cost = 0.0;
+        shipment.tracking_number = Default::default();
+        shipment.tracking_link = Default::default();
+        shipment.oxide_tracking_link = Default::default();
+        shipment.tracking_status = Default::default();
+        shipment.label_link = Default::default();
+        shipment.eta = None;
+        shipment.provider_id = Default::default();
+        shipment.messages = Default::default();
+        shipment.set_status(Status::Queued).await?;
+        // Keep that the label is void before anything else can fail, so the shipment never points
+        // at a label that no longer works. Should buying the new one fail, the shipment is queued
+        // without a label, and gets one the next time the shipments are refreshed.
+        shipment = shipment.update(db).await?;
+
+        record_event(
+            db,
+            &shipment,
+            SHIPMENT_EVENT_LABEL_VOIDED,
+            &previous_tracking_number,
+            "",
+            &correction.changed_by,
+        )
+        .await?;
+    }
+
+    shipment.street_1 = address.street1;
+    shipment.street_2 = address.street2;
+    shipment.city = address.city;
+    shipment.state = address.state;
+    shipment.zipcode = address.zip;
+    shipment.country = address.country;
+    if !address.phone.is_empty() {
+        shipment.phone = address.phone;
+    }
+    shipment.populate_formatted_address();
+    // Anything we had geocoded was for the old address.
+    shipment.latitude = 0.0;
+    shipment.longitude = 0.0;
+    shipment = shipment.update(db).await?;
+
+    record_event(
+        db,
+        &shipment,
+        SHIPMENT_EVENT_ADDRESS_CORRECTED,
+        &previous_address,
+        &shipment.address_formatted,
+        &correction.changed_by,
+    )
+    .await?;
+
+    if has_label {
+        shipment.create_or_get_shippo_shipment(db).await?;
+        shipment = shipment.update(db).await?;
+
+        record_event(
+            db,
+            &shipment,
+            SHIPMENT_EVENT_LABEL_PURCHASED,
+            &previous_tracking_number,
+            &shipment.tracking_number,
+            &correction.changed_by,
+        )
+        .await?;
+    }
+
+    info!("corrected the address of shipment {}", shipment.id);
+
+    Ok(shipment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_not_shipped, ShipmentChangeRejection};
+    use crate::shipments::OutboundShipment;
+
+    fn mock_shipment(status: &str) -> OutboundShipment {
+        serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "name": "Jane Doe",
+            "contents": "Hoodie",
+            "street_1": "1 Main St",
+            "city": "Oakland",
+            "state": "CA",
+            "zipcode": "94607",
+            "country": "US",
+            "status": status,
+            "provider": "Shippo",
+            "created_time": "2023-03-01T00:00:00Z",
+            "cio_company_id": 1
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_check_not_shipped() {
+        for status in [
+            "Queued",
+            "On hold",
+            "Label created",
+            "Label printed",
+            "Waiting for pickup",
+        ] {
+            assert_eq!(Ok(()), check_not_shipped(&mock_shipment(status)), "{}", status);
+        }
+
+        for status in ["Shipped", "Delivered", "Picked up", "Returned", "Cancelled"] {
+            assert_eq!(
+                Err(ShipmentChangeRejection::Shipped(status.to_string())),
+                check_not_shipped(&mock_shipment(status))
+            );
+        }
+
+        let mut shipment = mock_shipment("Label printed");
+        shipment.shipped_time = Some("2023-03-02T00:00:00Z".parse().unwrap());
+        assert!(check_not_shipped(&shipment).is_err());
+
+        let mut shipment = mock_shipment("Queued");
+        shipment.local_pickup = true;
+        assert!(check_not_shipped(&shipment).is_err());
+    }
+}
@@ -6,12 +6,10 @@ use async_bb8_diesel::AsyncRunQueryDsl;
 use async_trait::async_trait;
 use chrono::{naive::NaiveDate, offset::Utc, DateTime, Duration, NaiveTime};
 use chrono_humanize::HumanTime;
-use google_geocode::Geocode;
 use log::{info, warn};
 use macros::db;
 use reqwest::StatusCode;
 use schemars::JsonSchema;
-use sendgrid_api::{traits::MailOps, Client as SendGrid};
 use serde::{Deserialize, Serialize};
 use shippo::{Address, CustomsDeclaration, CustomsItem, NewShipment, NewTransaction, Parcel, Shippo};
 use slack_chat_api::{
@@ -24,7 +22,8 @@ use crate::{
     configs::User,
     core::UpdateAirtableRecord,
     db::Database,
-    printer::Printer,
+    email::Mailer,
+    printer::{LabelPrinter, Printer},
     schema::{inbound_shipments, outbound_shipments, package_pickups},
 };
 
@@ -947,7 +946,7 @@ impl UpdateAirtableRecord<OutboundShipment> for OutboundShipment {
 }
 
 impl OutboundShipment {
-    fn populate_formatted_address(&mut self) {
+    pub(crate) fn populate_formatted_address(&mut self) {
         let mut street_address = self.street_1.to_string();
         if !self.street_2.is_empty() {
             street_address = format!("{}\n{}", self.street_1, self.street_2,);
@@ -1009,6 +1008,11 @@ impl OutboundShipment {
 
     /// Send the label to our printer.
     pub async fn print_label(&self, db: &Database) -> Result<()> {
+        self.print_label_to(db, LabelPrinter::Rollo).await
+    }
+
+    /// Send the label to one of our label printers.
+    pub async fn print_label_to(&self, db: &Database, printer: LabelPrinter) -> Result<()> {
         if self.label_link.trim().is_empty() {
             warn!("[print]: Failed to print label due to missing label link");
 
@@ -1025,7 +1029,7 @@ impl OutboundShipment {
             return Ok(());
         }
 
-        let printer_url = format!("{}/rollo", company.printer_url);
+        let printer_url = format!("{}/{}", company.printer_url, printer.as_str());
         let printer_key = Printer::key();
 
         info!(
@@ -1034,11 +1038,22 @@ impl OutboundShipment {
             printer_url
         );
 
+        // The Rollo printer takes the link to the label, the Zebra printer takes it as a print
+        // request like the barcode labels it prints.
+        let body = match printer {
+            LabelPrinter::Rollo => json!(self.label_link),
+            LabelPrinter::Zebra => json!(cio_api_types::swag_inventory::PrintRequest {
+                url: self.label_link.to_string(),
+                quantity: 1,
+                content: String::new(),
+            }),
+        };
+
         let client = reqwest::Client::new();
         let resp = client
             .post(&printer_url)
             .bearer_auth(printer_key)
-            .body(json!(self.label_link).to_string())
+            .body(body.to_string())
             .send()
             .await?;
 
@@ -1085,11 +1100,9 @@ impl OutboundShipment {
 
         let company = self.company(db).await?;
 
-        // Initialize the SendGrid client.
-        let sendgrid_client = SendGrid::new_from_env();
+        let mailer = Mailer::new(db, &company);
         // Send the message.
-        sendgrid_client
-            .mail_send()
+        mailer
             .send_plain_text(
                 &format!("{}, your order from {} has been received!", self.name, company.name),
                 &format!(
@@ -1130,11 +1143,9 @@ xoxo,
         }
 
         let company = self.company(db).await?;
-        // Initialize the SendGrid client.
-        let sendgrid_client = SendGrid::new_from_env();
+        let mailer = Mailer::new(db, &company);
         // Send the message.
-        sendgrid_client
-            .mail_send()
+        mailer
             .send_plain_text(
                 &format!("{}, your package from {} is on the way!", self.name, company.name),
                 &format!(
@@ -1173,11 +1184,9 @@ xoxo,
     /// Send an email internally that we need to package the shipment.
     pub async fn send_email_internally(&self, db: &Database) -> Result<()> {
         let company = self.company(db).await?;
-        // Initialize the SendGrid client.
-        let sendgrid_client = SendGrid::new_from_env();
+        let mailer = Mailer::new(db, &company);
         // Send the message.
-        sendgrid_client
-            .mail_send()
+        mailer
             .send_plain_text(
                 &format!("Shipment to {} is ready to be packaged", self.name),
                 &format!(
@@ -1233,16 +1242,12 @@ The Shipping Bot",
     }
 
     pub async fn set_lat_lng(&mut self, db: &Database) -> Result<()> {
-        // Create the geocode client.
-        let geocode = Geocode::new_from_env();
-
         // If we don't already have the latitude and longitude for the shipment
         // let's update that first.
         if self.latitude == 0.0 || self.longitude == 0.0 {
-            let result = geocode.get(&clean_address_string(&self.address_formatted)).await?;
-            let location = result.geometry.location;
-            self.latitude = location.lat as f32;
-            self.longitude = location.lng as f32;
+            let location = crate::geocode::geocode_address(db, &clean_address_string(&self.address_formatted)).await?;
+            self.latitude = location.latitude as f32;
+            self.longitude = location.longitude as f32;
             // Update here just in case something goes wrong later.
             self.update(db).await?;
         }
@@ -1329,6 +1334,11 @@ The Shipping Bot",
             return Ok(());
         }
 
+        if self.status == crate::shipment_status::Status::OnHold.to_string() {
+            // Return early, we are waiting on the recipient to tell us where to ship it.
+            return Ok(());
+        }
+
         let company = self.company(db).await?;
 
         // Update the formatted address.
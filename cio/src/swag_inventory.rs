@@ -30,6 +30,7 @@ use crate::{
     db::Database,
     printer::Printer,
     schema::{barcode_scans, swag_inventory_items, swag_items},
+    swag_stock::{stock_total, sync_item_stock, take_stock, OutOfStock},
 };
 
 // The zebra label printer's dpi is 300.
@@ -640,6 +641,10 @@ pub async fn refresh_swag_inventory_items(db: &Database, company: &Company) -> R
 
         let mut db_inventory_item = inventory_item.upsert_in_db(db).await?;
         db_inventory_item.airtable_record_id = inventory_item_record.id.to_string();
+        // What is on hand is counted by the stock levels, not in Airtable.
+        if let Some(total) = stock_total(db, db_inventory_item.id).await? {
+            db_inventory_item.current_stock = total;
+        }
         db_inventory_item.update(db).await?;
     }
 
@@ -695,9 +700,10 @@ impl UpdateAirtableRecord<BarcodeScan> for BarcodeScan {
 }
 
 impl BarcodeScan {
-    // Takes a scanned barcode and updates the inventory count for the item
-    // as well as adds the scan to the barcodes_scan table for tracking.
-    pub async fn scan(b: String) -> Result<()> {
+    // Takes a scanned barcode and updates the inventory count for the item at the
+    // location of the scanner, as well as adds the scan to the barcodes_scan table
+    // for tracking.
+    pub async fn scan(b: String, location: &str) -> Result<()> {
         let time = Utc::now();
 
         // Make sure the barcode is formatted correctly.
@@ -712,17 +718,23 @@ impl BarcodeScan {
             .first_async::<SwagInventoryItem>(db.pool())
             .await
         {
-            Ok(mut swag_inventory_item) => {
+            Ok(swag_inventory_item) => {
                 // We found the matching inventory item!
-                // Now let's subtract 1 from the current inventory and update it
-                // in the database.
-                swag_inventory_item.current_stock -= 1;
-                // Update the database.
-                swag_inventory_item.update(&db).await?;
-                info!(
-                    "subtracted one from {} stock, we now have {}",
-                    swag_inventory_item.name, swag_inventory_item.current_stock
-                );
+                // Now let's take 1 from the stock where the scanner is.
+                match take_stock(
+                    &db,
+                    swag_inventory_item.cio_company_id,
+                    location,
+                    &[(swag_inventory_item.id, 1)],
+                )
+                .await
+                {
+                    Ok(_) => info!("subtracted one from {} stock at {}", swag_inventory_item.name, location),
+                    // The item is already gone, so the count was off. Record the scan anyway.
+                    Err(e) if e.downcast_ref::<OutOfStock>().is_some() => warn!("{}", e),
+                    Err(e) => return Err(e),
+                }
+                sync_item_stock(&db, swag_inventory_item.id).await?;
 
                 // Now add our barcode scan to the barcode scans database.
                 let new_barcode_scan = NewBarcodeScan {
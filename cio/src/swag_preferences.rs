@@ -0,0 +1,512 @@
+/*!
+ * Asking the people we send swag to for their shirt size and where to ship it.
+ *
+ * New hires are asked as one of their onboarding tasks, and conference winners whenever someone
+ * asks for them to be. Either way their shipment is created on hold, and they are sent a link to
+ * a form in Slack, or by email when they are not in our Slack. The address they submit is checked
+ * with Shippo, and what Shippo corrected it to is written onto the shipment along with their
+ * shirt size. The shipment is then queued to ship like any other.
+ *
+ * As with the links to download device credentials, only a hash of the token in the link is
+ * kept.
+ */
+use std::fmt;
+
+use anyhow::{anyhow, bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Duration, Utc};
+use diesel::{ExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable};
+use log::{info, warn};
+use ring::{
+    digest,
+    rand::{SecureRandom, SystemRandom},
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use shippo::{Address, Shippo};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    app_config::SwagPreferencesConfig,
+    companies::Company,
+    db::Database,
+    email::Mailer,
+    schema::swag_preference_requests,
+    shipment_status::Status,
+    shipments::{NewOutboundShipment, OutboundShipment},
+};
+
+#[derive(Debug, Queryable, PartialEq, Clone, Serialize)]
+pub struct SwagPreferenceRequest {
+    pub id: i32,
+    /// The shipment, on hold until the preferences are submitted.
+    pub outbound_shipment_id: i32,
+    pub name: String,
+    pub email: String,
+    #[serde(skip)]
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub shirt_size: String,
+    pub submitted_at: Option<DateTime<Utc>>,
+    pub cio_company_id: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = swag_preference_requests)]
+struct NewSwagPreferenceRequest {
+    outbound_shipment_id: i32,
+    name: String,
+    email: String,
+    token_hash: String,
+    expires_at: DateTime<Utc>,
+    cio_company_id: i32,
+}
+
+/// Someone to send swag to, who is not an employee, ie. a conference winner.
+#[derive(Debug, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct SwagRecipient {
+    pub name: String,
+    pub email: String,
+    /// What is in the package.
+    pub contents: String,
+}
+
+/// What the form is filled in with.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct SwagPreferencesForm {
+    pub name: String,
+    pub contents: String,
+    pub shirt_sizes: Vec<String>,
+    /// The address we already have for the person, if any, for them to check.
+    pub street_1: String,
+    pub street_2: String,
+    pub city: String,
+    pub state: String,
+    pub zipcode: String,
+    pub country: String,
+    pub phone: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// The preferences submitted through the form.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct SwagPreferences {
+    pub shirt_size: String,
+    pub street_1: String,
+    #[serde(default)]
+    pub street_2: String,
+    pub city: String,
+    #[serde(default)]
+    pub state: String,
+    pub zipcode: String,
+    pub country: String,
+    #[serde(default)]
+    pub phone: String,
+}
+
+/// Why preferences could not be submitted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SwagPreferenceRejection {
+    UnknownLink,
+    Expired,
+    Submitted,
+    UnknownShirtSize(String),
+    /// Shippo could not validate the address, with what it had to say about it.
+    UndeliverableAddress(String),
+}
+
+impl fmt::Display for SwagPreferenceRejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SwagPreferenceRejection::UnknownLink => write!(f, "there is nothing to fill in at this link"),
+            SwagPreferenceRejection::Expired => write!(f, "the link has expired, ask for a new one"),
+            SwagPreferenceRejection::Submitted => write!(f, "the preferences were submitted already"),
+            SwagPreferenceRejection::UnknownShirtSize(size) => write!(f, "`{}` is not a shirt size we have", size),
+            SwagPreferenceRejection::UndeliverableAddress(messages) if messages.is_empty() => {
+                write!(f, "the address could not be validated")
+            }
+            SwagPreferenceRejection::UndeliverableAddress(messages) => {
+                write!(f, "the address could not be validated: {}", messages)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SwagPreferenceRejection {}
+
+impl SwagPreferenceRequest {
+    fn check(&self, now: DateTime<Utc>) -> std::result::Result<(), SwagPreferenceRejection> {
+        if self.submitted_at.is_some() {
+            Err(SwagPreferenceRejection::Submitted)
+        } else if self.expires_at <= now {
+            Err(SwagPreferenceRejection::Expired)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn generate_token() -> Result<String> {
+    let mut token = [0; 32];
+    SystemRandom::new()
+        .fill(&mut token)
+        .map_err(|_| anyhow!("failed to generate a swag preferences token"))?;
+
+    Ok(hex::encode(token))
+}
+
+fn hash_token(token: &str) -> String {
+    hex::encode(digest::digest(&digest::SHA256, token.as_bytes()))
+}
+
+/// The shirt size as it is configured, matched case insensitively.
+fn shirt_size(config: &SwagPreferencesConfig, size: &str) -> std::result::Result<String, SwagPreferenceRejection> {
+    config
+        .shirt_sizes
+        .iter()
+        .find(|s| s.eq_ignore_ascii_case(size.trim()))
+        .cloned()
+        .ok_or_else(|| SwagPreferenceRejection::UnknownShirtSize(size.trim().to_string()))
+}
+
+pub(crate) fn validation_messages(address: &Address) -> String {
+    address
+        .validation_results
+        .as_ref()
+        .map(|results| {
+            results
+                .messages
+                .iter()
+                .map(|message| message.text.to_string())
+                .filter(|text| !text.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_default()
+}
+
+fn preferences_message(channel: &str, contents: &str, link: &str, expires_at: DateTime<Utc>) -> FormattedMessage {
+    FormattedMessage {
+        channel: channel.to_string(),
+        attachments: Default::default(),
+        blocks: vec![MessageBlock {
+            block_type: MessageBlockType::Section,
+            text: Some(MessageBlockText {
+                text_type: MessageType::Markdown,
+                text: format!(
+                    ":package: We are sending you {}! <{}|Tell us your shirt size and where to ship it> by {}.",
+                    contents,
+                    link,
+                    expires_at.format("%B %-d")
+                ),
+            }),
+            elements: Default::default(),
+            accessory: Default::default(),
+            block_id: Default::default(),
+            fields: Default::default(),
+        }],
+    }
+}
+
+impl From<&SwagRecipient> for NewOutboundShipment {
+    fn from(recipient: &SwagRecipient) -> Self {
+        NewOutboundShipment {
+            created_time: Utc::now(),
+            name: recipient.name.to_string(),
+            email: recipient.email.to_string(),
+            contents: recipient.contents.to_string(),
+            phone: Default::default(),
+            street_1: Default::default(),
+            street_2: Default::default(),
+            city: Default::default(),
+            state: Default::default(),
+            zipcode: Default::default(),
+            country: Default::default(),
+            address_formatted: Default::default(),
+            latitude: Default::default(),
+            longitude: Default::default(),
+            carrier: Default::default(),
+            pickup_date: None,
+            delivered_time: None,
+            shipped_time: None,
+            provider: "Shippo".to_string(),
+            provider_id: Default::default(),
+            status: Status::OnHold.to_string(),
+            tracking_link: Default::default(),
+            oxide_tracking_link: Default::default(),
+            tracking_number: Default::default(),
+            tracking_status: Default::default(),
+            cost: Default::default(),
+            label_link: Default::default(),
+            eta: None,
+            messages: Default::default(),
+            notes: Default::default(),
+            geocode_cache: Default::default(),
+            local_pickup: Default::default(),
+            link_to_package_pickup: Default::default(),
+            cio_company_id: Default::default(),
+        }
+    }
+}
+
+/// Put the shipment on hold and send its recipient the link to the form. The shipment is
+/// deleted again if the link cannot be sent, so that asking again does not leave it behind.
+pub async fn request_swag_preferences(
+    db: &Database,
+    company: &Company,
+    config: &SwagPreferencesConfig,
+    mut new_shipment: NewOutboundShipment,
+) -> Result<SwagPreferenceRequest> {
+    if config.form_url.is_empty() {
+        bail!("there is no swag preferences form to send {} to", new_shipment.email);
+    }
+
+    let slack_user = company
+        .authenticate_slack(db)
+        .await?
+        .list_users()
+        .await?
+        .into_iter()
+        .find(|slack_user| !slack_user.deleted && slack_user.email.eq_ignore_ascii_case(&new_shipment.email));
+    if slack_user.is_none() && config.letter.is_none() {
+        bail!(
+            "{} is not in Slack and there is no letter to email them the swag preferences form",
+            new_shipment.email
+        );
+    }
+
+    new_shipment.status = Status::OnHold.to_string();
+    new_shipment.provider = "Shippo".to_string();
+    new_shipment.cio_company_id = company.id;
+    // Match on is the tracking number, which pending shipments do not have yet, so these are
+    // always created.
+    let shipment = new_shipment.create(db).await?;
+
+    let token = generate_token()?;
+    let request = diesel::insert_into(swag_preference_requests::table)
+        .values(NewSwagPreferenceRequest {
+            outbound_shipment_id: shipment.id,
+            name: shipment.name.to_string(),
+            email: shipment.email.to_string(),
+            token_hash: hash_token(&token),
+            expires_at: Utc::now() + Duration::days(config.expires_days),
+            cio_company_id: company.id,
+        })
+        .get_result_async::<SwagPreferenceRequest>(db.pool())
+        .await?;
+
+    let link = format!("{}/{}", config.form_url.trim_end_matches('/'), token);
+    let sent = match slack_user {
+        Some(slack_user) => {
+            company
+                .post_to_slack_channel(
+                    db,
+                    &preferences_message(&slack_user.id, &shipment.contents, &link, request.expires_at),
+                )
+                .await
+        }
+        None => {
+            // Checked above.
+            let letter = config.create_letter(&shipment.name, &shipment.contents, &link).unwrap();
+            Mailer::new(db, company)
+                .send_plain_text(
+                    &letter.subject,
+                    &letter.body,
+                    &[shipment.email.to_string()],
+                    &letter.cc,
+                    &letter.bcc,
+                    &letter.from,
+                )
+                .await
+        }
+    };
+    if let Err(e) = sent {
+        // This takes the request with it.
+        if let Err(e) = shipment.delete(db).await {
+            warn!("failed to delete shipment {} to {}: {}", shipment.id, shipment.email, e);
+        }
+
+        bail!("sending the swag preferences form to {} failed: {}", shipment.email, e);
+    }
+
+    info!("asked {} for their swag preferences", shipment.email);
+
+    Ok(request)
+}
+
+async fn find_request(db: &Database, token: &str) -> Result<SwagPreferenceRequest> {
+    Ok(swag_preference_requests::dsl::swag_preference_requests
+        .filter(swag_preference_requests::dsl::token_hash.eq(hash_token(token)))
+        .first_async::<SwagPreferenceRequest>(db.pool())
+        .await
+        .optional()?
+        .ok_or(SwagPreferenceRejection::UnknownLink)?)
+}
+
+/// What the form behind a link is filled in with. Fails with a [`SwagPreferenceRejection`] when
+/// the link does not work.
+pub async fn get_swag_preferences_form(
+    db: &Database,
+    config: &SwagPreferencesConfig,
+    token: &str,
+) -> Result<SwagPreferencesForm> {
+    let request = find_request(db, token).await?;
+    request.check(Utc::now())?;
+
+    let shipment = OutboundShipment::get_by_id(db, request.outbound_shipment_id).await?;
+
+    Ok(SwagPreferencesForm {
+        name: request.name,
+        contents: shipment.contents,
+        shirt_sizes: config.shirt_sizes.clone(),
+        street_1: shipment.street_1,
+        street_2: shipment.street_2,
+        city: shipment.city,
+        state: shipment.state,
+        zipcode: shipment.zipcode,
+        country: shipment.country,
+        phone: shipment.phone,
+        expires_at: request.expires_at,
+    })
+}
+
+/// Write the submitted preferences onto the shipment, and queue it to ship. Fails with a
+/// [`SwagPreferenceRejection`] when the link does not work, or what was submitted will not do.
+pub async fn submit_swag_preferences(
+    db: &Database,
+    config: &SwagPreferencesConfig,
+    token: &str,
+    preferences: SwagPreferences,
+) -> Result<OutboundShipment> {
+    let request = find_request(db, token).await?;
+    request.check(Utc::now())?;
+
+    let size = shirt_size(config, &preferences.shirt_size)?;
+
+    let address = Shippo::new_from_env()
+        .validate_address(&Address {
+            name: request.name.to_string(),
+            street1: preferences.street_1.trim().to_string(),
+            street2: preferences.street_2.trim().to_string(),
+            city: preferences.city.trim().to_string(),
+            state: preferences.state.trim().to_string(),
+            zip: preferences.zipcode.trim().to_string(),
+            country: preferences.country.trim().to_string(),
+            phone: preferences.phone.trim().to_string(),
+            email: request.email.to_string(),
+            ..Default::default()
+        })
+        .await?;
+    if !address
+        .validation_results
+        .as_ref()
+        .map_or(false, |results| results.is_valid)
+    {
+        return Err(SwagPreferenceRejection::UndeliverableAddress(validation_messages(&address)).into());
+    }
+
+    // Only the first of two submissions that race each other gets through.
+    let submitted = diesel::update(swag_preference_requests::dsl::swag_preference_requests.find(request.id))
+        .filter(swag_preference_requests::dsl::submitted_at.is_null())
+        .set((
+            swag_preference_requests::dsl::shirt_size.eq(size.to_string()),
+            swag_preference_requests::dsl::submitted_at.eq(Some(Utc::now())),
+        ))
+        .execute_async(db.pool())
+        .await?;
+    if submitted == 0 {
+        return Err(SwagPreferenceRejection::Submitted.into());
+    }
+
+    let mut shipment = OutboundShipment::get_by_id(db, request.outbound_shipment_id).await?;
+    shipment.street_1 = address.street1;
+    shipment.street_2 = address.street2;
+    shipment.city = address.city;
+    shipment.state = address.state;
+    shipment.zipcode = address.zip;
+    shipment.country = address.country;
+    if !address.phone.is_empty() {
+        shipment.phone = address.phone;
+    }
+    shipment.contents = format!("{} (shirt size {})", shipment.contents, size);
+    // Anything we had geocoded was for the old address.
+    shipment.latitude = 0.0;
+    shipment.longitude = 0.0;
+    shipment.set_status(Status::Queued).await?;
+    shipment = shipment.update(db).await?;
+
+    shipment.create_or_get_shippo_shipment(db).await?;
+    let shipment = shipment.update(db).await?;
+
+    info!("{} submitted their swag preferences", request.email);
+
+    Ok(shipment)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use super::{shirt_size, SwagPreferenceRejection, SwagPreferenceRequest};
+    use crate::app_config::{Letter, SwagPreferencesConfig};
+
+    fn request() -> SwagPreferenceRequest {
+        SwagPreferenceRequest {
+            id: 1,
+            outbound_shipment_id: 1,
+            name: "Jess Frazelle".to_string(),
+            email: "jess@example.com".to_string(),
+            token_hash: Default::default(),
+            expires_at: Utc::now() + Duration::days(1),
+            shirt_size: Default::default(),
+            submitted_at: None,
+            cio_company_id: 1,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let now = Utc::now();
+        let mut request = request();
+        assert_eq!(Ok(()), request.check(now));
+
+        request.expires_at = now;
+        assert_eq!(Err(SwagPreferenceRejection::Expired), request.check(now));
+
+        request.submitted_at = Some(now);
+        assert_eq!(Err(SwagPreferenceRejection::Submitted), request.check(now));
+    }
+
+    #[test]
+    fn test_shirt_size() {
+        let config = SwagPreferencesConfig::default();
+        assert_eq!(Ok("2XL".to_string()), shirt_size(&config, " 2xl"));
+        assert_eq!(
+            Err(SwagPreferenceRejection::UnknownShirtSize("XXXL".to_string())),
+            shirt_size(&config, "XXXL")
+        );
+    }
+
+    #[test]
+    fn test_create_letter() {
+        let mut config = SwagPreferencesConfig::default();
+        assert_eq!(
+            None,
+            config.create_letter("Jess", "a hoodie", "https://swag.example.com/abc")
+        );
+
+        config.letter = Some(Letter {
+            subject: "{name}, you won {contents}".to_string(),
+            body: "Tell us where to ship it: {form_link}".to_string(),
+            from: "swag@example.com".to_string(),
+            ..Default::default()
+        });
+        let letter = config
+            .create_letter("Jess", "a hoodie", "https://swag.example.com/abc")
+            .unwrap();
+        assert_eq!("Jess, you won a hoodie", letter.subject);
+        assert_eq!("Tell us where to ship it: https://swag.example.com/abc", letter.body);
+    }
+}
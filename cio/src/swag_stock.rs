@@ -0,0 +1,441 @@
+/*!
+ * How much swag is on hand, and where.
+ *
+ * Every swag inventory item, ie. a hoodie in size M, is a SKU with its own barcode. The
+ * `swag_stock_levels` table counts how many of each are on hand at each location that swag is
+ * kept at, and the `current_stock` of an inventory item, which is what Airtable and the store
+ * see, is kept as the total across its locations.
+ *
+ * The stock for an order is taken before its shipment is created, for all of the items of the
+ * order at once, so that an order we cannot fill in full does not ship at all. New stock comes
+ * in by receiving it at a location. When a location runs low on an item, the office manager is
+ * told in Slack, once, until the stock there is built back up.
+ */
+use std::{collections::BTreeMap, fmt, ops::DerefMut};
+
+use anyhow::{bail, Result};
+use async_bb8_diesel::{AsyncConnection, AsyncRunQueryDsl};
+use chrono::{DateTime, Utc};
+use diesel::{dsl::sum, ExpressionMethods, Insertable, QueryDsl, Queryable, RunQueryDsl};
+use log::info;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    app_config::SwagConfig, companies::Company, db::Database, schema::swag_stock_levels,
+    swag_inventory::SwagInventoryItem,
+};
+
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct SwagStockLevel {
+    pub id: i32,
+    /// The swag inventory item, ie. the SKU, that is stocked.
+    pub swag_inventory_item_id: i32,
+    pub location: String,
+    pub on_hand: i32,
+    /// Stock at or below this is low.
+    pub low_stock_threshold: i32,
+    /// When the office manager was told that the stock is low, cleared once it is built back up.
+    pub low_stock_alerted_at: Option<DateTime<Utc>>,
+    pub cio_company_id: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = swag_stock_levels)]
+struct NewSwagStockLevel {
+    swag_inventory_item_id: i32,
+    location: String,
+    on_hand: i32,
+    low_stock_threshold: i32,
+    cio_company_id: i32,
+}
+
+impl SwagStockLevel {
+    pub fn is_low(&self) -> bool {
+        self.on_hand <= self.low_stock_threshold
+    }
+
+    /// Whether the office manager has yet to be told that the stock is low.
+    fn needs_alert(&self) -> bool {
+        self.is_low() && self.low_stock_alerted_at.is_none()
+    }
+
+    fn low_stock_line(&self, item: &str) -> String {
+        format!(
+            "• *{}* at {}: {} left (low at {})",
+            item, self.location, self.on_hand, self.low_stock_threshold
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct StockReceipt {
+    /// The swag inventory item that was received.
+    pub swag_inventory_item_id: i32,
+    /// Where the stock was received, the default location from the config when empty.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub location: String,
+    pub quantity: i32,
+    /// A new low stock threshold for the item at the location. Stock that is received at a
+    /// location for the first time gets the default threshold from the config otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub low_stock_threshold: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shortfall {
+    pub swag_inventory_item_id: i32,
+    pub wanted: i32,
+    pub on_hand: i32,
+}
+
+/// The stock for some items could not be taken, because there is not enough of them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutOfStock {
+    pub location: String,
+    pub shortfalls: Vec<Shortfall>,
+}
+
+impl fmt::Display for OutOfStock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let shortfalls = self
+            .shortfalls
+            .iter()
+            .map(|shortfall| {
+                format!(
+                    "item {} (wanted {}, {} on hand)",
+                    shortfall.swag_inventory_item_id, shortfall.wanted, shortfall.on_hand
+                )
+            })
+            .collect::<Vec<_>>();
+
+        write!(f, "not enough stock at {}: {}", self.location, shortfalls.join(", "))
+    }
+}
+
+impl std::error::Error for OutOfStock {}
+
+/// The quantity wanted of each item, as an order can list the same item more than once.
+fn wanted_by_item(items: &[(i32, i32)]) -> BTreeMap<i32, i32> {
+    let mut wanted = BTreeMap::new();
+    for (item_id, quantity) in items.iter().filter(|(_, quantity)| *quantity > 0) {
+        *wanted.entry(*item_id).or_insert(0) += quantity;
+    }
+
+    wanted
+}
+
+/// What each of `levels` is left with once `wanted` is taken from them, as pairs of the level id
+/// and its new count. Nothing is taken unless everything can be.
+fn take_from(
+    levels: &[SwagStockLevel],
+    location: &str,
+    wanted: &BTreeMap<i32, i32>,
+) -> std::result::Result<Vec<(i32, i32)>, OutOfStock> {
+    let mut taken = vec![];
+    let mut shortfalls = vec![];
+
+    for (item_id, quantity) in wanted {
+        match levels.iter().find(|level| level.swag_inventory_item_id == *item_id) {
+            Some(level) if level.on_hand >= *quantity => taken.push((level.id, level.on_hand - quantity)),
+            level => shortfalls.push(Shortfall {
+                swag_inventory_item_id: *item_id,
+                wanted: *quantity,
+                on_hand: level.map(|level| level.on_hand).unwrap_or_default(),
+            }),
+        }
+    }
+
+    if !shortfalls.is_empty() {
+        return Err(OutOfStock {
+            location: location.to_string(),
+            shortfalls,
+        });
+    }
+
+    Ok(taken)
+}
+
+/// Take the stock for `items`, pairs of a swag inventory item id and a quantity, from
+/// `location`. Fails with [`OutOfStock`], taking nothing, if any of the items do not have
+/// enough on hand.
+pub async fn take_stock(
+    db: &Database,
+    cio_company_id: i32,
+    location: &str,
+    items: &[(i32, i32)],
+) -> Result<Vec<SwagStockLevel>> {
+    let location = location.to_string();
+    let wanted = wanted_by_item(items);
+
+    db.pool()
+        .transaction(move |conn| {
+            let levels = swag_stock_levels::dsl::swag_stock_levels
+                .filter(swag_stock_levels::dsl::cio_company_id.eq(cio_company_id))
+                .filter(swag_stock_levels::dsl::location.eq(location.to_string()))
+                .filter(
+                    swag_stock_levels::dsl::swag_inventory_item_id.eq_any(wanted.keys().copied().collect::<Vec<_>>()),
+                )
+                .order_by(swag_stock_levels::dsl::id)
+                .for_update()
+                .load::<SwagStockLevel>(conn.deref_mut())?;
+
+            let mut updated = vec![];
+            for (id, on_hand) in take_from(&levels, &location, &wanted)? {
+                updated.push(
+                    diesel::update(swag_stock_levels::dsl::swag_stock_levels.find(id))
+                        .set((
+                            swag_stock_levels::dsl::on_hand.eq(on_hand),
+                            swag_stock_levels::dsl::updated_at.eq(Utc::now()),
+                        ))
+                        .get_result::<SwagStockLevel>(conn.deref_mut())?,
+                );
+            }
+
+            Ok(updated)
+        })
+        .await
+}
+
+/// Put the stock for `items` that was taken from `location` back, ie. when the shipment it was
+/// taken for could not be created.
+pub async fn return_stock(db: &Database, cio_company_id: i32, location: &str, items: &[(i32, i32)]) -> Result<()> {
+    for (item_id, quantity) in wanted_by_item(items) {
+        diesel::update(swag_stock_levels::dsl::swag_stock_levels)
+            .filter(swag_stock_levels::dsl::cio_company_id.eq(cio_company_id))
+            .filter(swag_stock_levels::dsl::location.eq(location.to_string()))
+            .filter(swag_stock_levels::dsl::swag_inventory_item_id.eq(item_id))
+            .set((
+                swag_stock_levels::dsl::on_hand.eq(swag_stock_levels::dsl::on_hand + quantity),
+                swag_stock_levels::dsl::updated_at.eq(Utc::now()),
+            ))
+            .execute_async(db.pool())
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Add received stock to its location, starting to track the item there if it was not yet.
+pub async fn receive_stock(db: &Database, config: &SwagConfig, receipt: &StockReceipt) -> Result<SwagStockLevel> {
+    if receipt.quantity <= 0 {
+        bail!("the quantity received must be positive, got {}", receipt.quantity);
+    }
+
+    let item = SwagInventoryItem::get_by_id(db, receipt.swag_inventory_item_id).await?;
+    let location = if receipt.location.trim().is_empty() {
+        config.default_location.to_string()
+    } else {
+        receipt.location.trim().to_string()
+    };
+
+    let mut level = diesel::insert_into(swag_stock_levels::table)
+        .values(NewSwagStockLevel {
+            swag_inventory_item_id: item.id,
+            location,
+            on_hand: receipt.quantity,
+            low_stock_threshold: receipt.low_stock_threshold.unwrap_or(config.low_stock_threshold),
+            cio_company_id: item.cio_company_id,
+        })
+        .on_conflict((
+            swag_stock_levels::dsl::swag_inventory_item_id,
+            swag_stock_levels::dsl::location,
+        ))
+        .do_update()
+        .set((
+            swag_stock_levels::dsl::on_hand.eq(swag_stock_levels::dsl::on_hand + receipt.quantity),
+            swag_stock_levels::dsl::updated_at.eq(Utc::now()),
+        ))
+        .get_result_async::<SwagStockLevel>(db.pool())
+        .await?;
+
+    if let Some(threshold) = receipt.low_stock_threshold {
+        if level.low_stock_threshold != threshold {
+            level = diesel::update(swag_stock_levels::dsl::swag_stock_levels.find(level.id))
+                .set(swag_stock_levels::dsl::low_stock_threshold.eq(threshold))
+                .get_result_async::<SwagStockLevel>(db.pool())
+                .await?;
+        }
+    }
+
+    info!(
+        "received {} of `{}` at {}, which now has {}",
+        receipt.quantity, item.name, level.location, level.on_hand
+    );
+
+    sync_item_stock(db, item.id).await?;
+
+    Ok(level)
+}
+
+/// The stock of an item across all of its locations, or `None` if it is not stocked anywhere.
+pub async fn stock_total(db: &Database, swag_inventory_item_id: i32) -> Result<Option<i32>> {
+    let total = swag_stock_levels::dsl::swag_stock_levels
+        .filter(swag_stock_levels::dsl::swag_inventory_item_id.eq(swag_inventory_item_id))
+        .select(sum(swag_stock_levels::dsl::on_hand))
+        .first_async::<Option<i64>>(db.pool())
+        .await?;
+
+    Ok(total.map(|total| total as i32))
+}
+
+/// Set the `current_stock` of an item to its stock across all of its locations.
+pub async fn sync_item_stock(db: &Database, swag_inventory_item_id: i32) -> Result<()> {
+    let total = match stock_total(db, swag_inventory_item_id).await? {
+        Some(total) => total,
+        None => return Ok(()),
+    };
+
+    let mut item = SwagInventoryItem::get_by_id(db, swag_inventory_item_id).await?;
+    let company = item.company(db).await?;
+
+    // This will also set the value.
+    item.send_slack_notification_if_inventory_changed(db, &company, total)
+        .await?;
+    item.update(db).await?;
+
+    Ok(())
+}
+
+fn markdown_message(channel: &str, text: String) -> FormattedMessage {
+    FormattedMessage {
+        channel: channel.to_string(),
+        attachments: Default::default(),
+        blocks: vec![MessageBlock {
+            block_type: MessageBlockType::Section,
+            text: Some(MessageBlockText {
+                text_type: MessageType::Markdown,
+                text,
+            }),
+            elements: Default::default(),
+            accessory: Default::default(),
+            block_id: Default::default(),
+            fields: Default::default(),
+        }],
+    }
+}
+
+/// Tell the office manager about the stock that ran low since they were last told, and forget
+/// that they were told about stock that has since been built back up.
+pub async fn alert_low_stock(db: &Database, company: &Company, config: &SwagConfig) -> Result<()> {
+    let levels = swag_stock_levels::dsl::swag_stock_levels
+        .filter(swag_stock_levels::dsl::cio_company_id.eq(company.id))
+        .order_by(swag_stock_levels::dsl::id)
+        .load_async::<SwagStockLevel>(db.pool())
+        .await?;
+
+    let restocked = levels
+        .iter()
+        .filter(|level| !level.is_low() && level.low_stock_alerted_at.is_some())
+        .map(|level| level.id)
+        .collect::<Vec<_>>();
+    if !restocked.is_empty() {
+        diesel::update(swag_stock_levels::dsl::swag_stock_levels)
+            .filter(swag_stock_levels::dsl::id.eq_any(restocked))
+            .set(swag_stock_levels::dsl::low_stock_alerted_at.eq(None::<DateTime<Utc>>))
+            .execute_async(db.pool())
+            .await?;
+    }
+
+    let low = levels.iter().filter(|level| level.needs_alert()).collect::<Vec<_>>();
+    if low.is_empty() {
+        return Ok(());
+    }
+
+    let mut lines = vec![];
+    for level in &low {
+        let item = SwagInventoryItem::get_by_id(db, level.swag_inventory_item_id).await?;
+        lines.push(level.low_stock_line(&item.name));
+    }
+
+    let channel = if config.office_manager.is_empty() {
+        company.slack_channel_swag.to_string()
+    } else {
+        config.office_manager.to_string()
+    };
+    company
+        .post_to_slack_channel(
+            db,
+            &markdown_message(&channel, format!("*Swag is running low*\n{}", lines.join("\n"))),
+        )
+        .await?;
+
+    diesel::update(swag_stock_levels::dsl::swag_stock_levels)
+        .filter(swag_stock_levels::dsl::id.eq_any(low.iter().map(|level| level.id).collect::<Vec<_>>()))
+        .set(swag_stock_levels::dsl::low_stock_alerted_at.eq(Some(Utc::now())))
+        .execute_async(db.pool())
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::{take_from, wanted_by_item, OutOfStock, Shortfall, SwagStockLevel};
+
+    fn mock_level(id: i32, swag_inventory_item_id: i32, on_hand: i32) -> SwagStockLevel {
+        SwagStockLevel {
+            id,
+            swag_inventory_item_id,
+            location: "office".to_string(),
+            on_hand,
+            low_stock_threshold: 2,
+            low_stock_alerted_at: None,
+            cio_company_id: 1,
+            created_at: Utc.ymd(2023, 3, 1).and_hms(0, 0, 0),
+            updated_at: Utc.ymd(2023, 3, 1).and_hms(0, 0, 0),
+        }
+    }
+
+    #[test]
+    fn test_take_from() {
+        let levels = vec![mock_level(1, 10, 5), mock_level(2, 11, 1)];
+
+        // The same item listed twice is taken together, and items ordered zero of are ignored.
+        let wanted = wanted_by_item(&[(10, 2), (11, 1), (10, 3), (12, 0)]);
+        assert_eq!(Ok(vec![(1, 0), (2, 0)]), take_from(&levels, "office", &wanted));
+
+        let wanted = wanted_by_item(&[(10, 1), (11, 2), (12, 1)]);
+        let err = take_from(&levels, "office", &wanted).unwrap_err();
+        assert_eq!(
+            OutOfStock {
+                location: "office".to_string(),
+                shortfalls: vec![
+                    Shortfall {
+                        swag_inventory_item_id: 11,
+                        wanted: 2,
+                        on_hand: 1,
+                    },
+                    Shortfall {
+                        swag_inventory_item_id: 12,
+                        wanted: 1,
+                        on_hand: 0,
+                    },
+                ],
+            },
+            err
+        );
+        assert_eq!(
+            "not enough stock at office: item 11 (wanted 2, 1 on hand), item 12 (wanted 1, 0 on hand)",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn test_needs_alert() {
+        let mut level = mock_level(1, 10, 3);
+        assert!(!level.needs_alert());
+
+        level.on_hand = 2;
+        assert!(level.needs_alert());
+
+        level.low_stock_alerted_at = Some(Utc.ymd(2023, 3, 2).and_hms(0, 0, 0));
+        assert!(level.is_low());
+        assert!(!level.needs_alert());
+    }
+}
@@ -5,7 +5,14 @@ use log::info;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::{companies::Company, db::Database, shipments::NewOutboundShipment, swag_inventory::SwagInventoryItem};
+use crate::{
+    app_config::SwagConfig,
+    companies::Company,
+    db::Database,
+    shipments::NewOutboundShipment,
+    swag_inventory::SwagInventoryItem,
+    swag_stock::{return_stock, sync_item_stock, take_stock},
+};
 
 #[derive(Debug, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
 pub struct Order {
@@ -80,36 +87,7 @@ impl Order {
         Ok(())
     }
 
-    pub async fn subtract_order_from_inventory(&self, db: &Database) -> Result<()> {
-        for item in &self.items {
-            // Get the swag item from the database.
-            let mut swag_inventory_item = SwagInventoryItem::get_by_id(db, item.id).await?;
-            let mut new = swag_inventory_item.current_stock - item.quantity;
-            if swag_inventory_item.current_stock < 0 {
-                // TODO: Hopefully this never happens. The store code _should_ only allow people
-                // to order what is in stock but just in case let's make sure this does not
-                // go negative.
-                new = 0;
-            }
-
-            let company = swag_inventory_item.company(db).await?;
-
-            // This will also set the value.
-            swag_inventory_item
-                .send_slack_notification_if_inventory_changed(db, &company, new)
-                .await?;
-
-            info!(
-                "subtracted `{}` from current stock of `{}` making the total now `{}`",
-                item.quantity, swag_inventory_item.name, swag_inventory_item.current_stock
-            );
-            swag_inventory_item.update(db).await?;
-        }
-
-        Ok(())
-    }
-
-    pub async fn do_order(&self, db: &Database) -> Result<()> {
+    pub async fn do_order(&self, db: &Database, config: &SwagConfig) -> Result<()> {
         // If their email is empty return early.
         if self.email.is_empty()
             || self.street_1.is_empty()
@@ -125,8 +103,28 @@ impl Order {
             return Ok(());
         }
 
-        self.create_shipment_for_order(db).await?;
-        self.subtract_order_from_inventory(db).await?;
+        // Take the stock first, so that an order we cannot fill does not ship.
+        let items = self
+            .items
+            .iter()
+            .map(|item| (item.id, item.quantity))
+            .collect::<Vec<_>>();
+        take_stock(db, self.cio_company_id, &config.default_location, &items).await?;
+
+        if let Err(e) = self.create_shipment_for_order(db).await {
+            // Nothing is going out, so the stock is still on hand.
+            return_stock(db, self.cio_company_id, &config.default_location, &items).await?;
+            return Err(e);
+        }
+
+        for item in &self.items {
+            sync_item_stock(db, item.id).await?;
+        }
+
+        info!(
+            "took the stock for the order of {} from {}",
+            self.email, config.default_location
+        );
 
         Ok(())
     }
@@ -1,11 +1,18 @@
 use std::collections::BTreeMap;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use chrono::{Duration, Utc};
 use cloudflare::endpoints::dns;
-use log::info;
+use log::{info, warn};
+use tailscale_api::{AuthKey, CreateKeyRequest, KeyCreateCapabilities};
 
-use crate::companies::Company;
+use crate::{
+    app_config::TailscaleConfig,
+    companies::Company,
+    configs::{Groups, Users},
+    db::Database,
+    utils::update_github_secrets,
+};
 
 /// When we generate VMs for the console repo on every branch we get lingering
 /// Tailscale devices that need to cleaned up when they are no longer active.
@@ -127,3 +134,203 @@ pub async fn cleanup_old_tailscale_cloudflare_dns(company: &Company) -> Result<(
 
     Ok(())
 }
+
+/// Regenerate the groups in the Tailscale ACL policy from the groups in our configs, so that
+/// ACL rules can refer to ie. `group:eng`. Groups in the policy that are not in our configs
+/// are left alone.
+pub async fn sync_tailscale_acl_groups(db: &Database, company: &Company) -> Result<()> {
+    if company.tailscale_api_key.is_empty() {
+        info!("skipping `sync_tailscale_acl_groups` for company `{}`", company.name);
+
+        // Return early.
+        return Ok(());
+    }
+
+    let groups = Groups::get_from_db(db, company.id).await?;
+    let users = Users::get_from_db(db, company.id).await?;
+
+    let mut members: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for group in groups {
+        let mut emails: Vec<String> = users
+            .iter()
+            .filter(|user| !user.email.is_empty() && user.groups.contains(&group.name))
+            .map(|user| user.email.to_string())
+            .collect();
+        emails.sort();
+
+        members.insert(format!("group:{}", group.name), emails);
+    }
+
+    // Initialize the Tailscale API.
+    let tailscale = company.authenticate_tailscale();
+
+    let acl = tailscale.get_acl().await?;
+    let mut policy = acl.policy.clone();
+    policy.groups.extend(members);
+
+    if policy == acl.policy {
+        info!("tailscale acl groups are already up to date");
+        return Ok(());
+    }
+
+    let validation = tailscale.validate_acl(&policy).await?;
+    if !validation.is_valid() {
+        bail!("generated tailscale acl policy is invalid: {}", validation.message);
+    }
+
+    tailscale.set_acl(&policy, acl.etag.as_deref()).await?;
+
+    info!("synced tailscale acl groups successfully");
+
+    Ok(())
+}
+
+/// Reissue the auth keys used by our infrastructure before they expire and update the
+/// GitHub Actions secrets that hold them. The replaced keys are revoked so that they do not
+/// linger.
+pub async fn rotate_tailscale_auth_keys(company: &Company, config: &TailscaleConfig) -> Result<()> {
+    if company.tailscale_api_key.is_empty() || config.auth_keys.is_empty() {
+        info!("skipping `rotate_tailscale_auth_keys` for company `{}`", company.name);
+
+        // Return early.
+        return Ok(());
+    }
+
+    // Initialize the Tailscale API.
+    let tailscale = company.authenticate_tailscale();
+
+    // The list only includes the ids of the keys.
+    let mut keys: Vec<AuthKey> = Vec::new();
+    for key in tailscale.list_keys().await? {
+        keys.push(tailscale.get_key(&key.id).await?);
+    }
+
+    let github = company.authenticate_github()?;
+
+    for (name, key_config) in &config.auth_keys {
+        let existing: Vec<&AuthKey> = keys
+            .iter()
+            .filter(|key| key.description == *name && key.revoked.is_none())
+            .collect();
+
+        let rotate_at = Utc::now() + Duration::days(key_config.rotate_days_before_expiry);
+        match existing.iter().filter_map(|key| key.expires).max() {
+            Some(expires) if expires > rotate_at => continue,
+            _ => (),
+        }
+
+        let mut request = CreateKeyRequest::new(KeyCreateCapabilities {
+            reusable: key_config.reusable,
+            ephemeral: key_config.ephemeral,
+            preauthorized: key_config.preauthorized,
+            tags: key_config.tags.clone(),
+        });
+        request.expiry_seconds = Some(Duration::days(key_config.expiry_days).num_seconds());
+        request.description = name.to_string();
+
+        let new_key = tailscale.create_key(&request).await?;
+
+        let mut secrets: BTreeMap<String, String> = BTreeMap::new();
+        secrets.insert(
+            key_config.github_actions_secret_name.to_string(),
+            new_key.key.to_string(),
+        );
+
+        for repo in &key_config.repos {
+            if let Err(e) = update_github_secrets(&github, company, repo, &secrets).await {
+                // Otherwise the new key would be mistaken for one that is in use on the next run.
+                if let Err(e) = tailscale.delete_key(&new_key.id).await {
+                    warn!("failed to revoke tailscale auth key {}: {}", new_key.id, e);
+                }
+
+                bail!(
+                    "failed to update the `{}` tailscale auth key secret in repo {}: {}",
+                    name,
+                    repo,
+                    e
+                );
+            }
+        }
+
+        // The old keys are only revoked once everything is using the new one.
+        for key in existing {
+            tailscale.delete_key(&key.id).await?;
+        }
+
+        info!("rotated tailscale auth key `{}`", name);
+    }
+
+    Ok(())
+}
+
+/// Bring the servers in our tailnet in line with the config so that onboarding a machine only
+/// requires it to join the tailnet: authorize it, set its tags, disable key expiry and approve
+/// its subnet routes.
+pub async fn sync_tailscale_devices(company: &Company, config: &TailscaleConfig) -> Result<()> {
+    if company.tailscale_api_key.is_empty() || config.devices.is_empty() {
+        info!("skipping `sync_tailscale_devices` for company `{}`", company.name);
+
+        // Return early.
+        return Ok(());
+    }
+
+    // Initialize the Tailscale API.
+    let tailscale = company.authenticate_tailscale();
+
+    for device in tailscale.list_devices().await? {
+        let device_config = match config.devices.get(&device.hostname) {
+            Some(device_config) => device_config,
+            None => continue,
+        };
+
+        if !device.authorized {
+            info!("authorizing tailscale device {}", device.name);
+            tailscale.authorize_device(&device.id).await?;
+        }
+
+        let mut tags = device_config.tags.clone();
+        tags.sort();
+        let mut current_tags = device.tags.clone();
+        current_tags.sort();
+        if tags != current_tags {
+            info!("setting tags of tailscale device {} to {:?}", device.name, tags);
+            tailscale.set_device_tags(&device.id, &tags).await?;
+        }
+
+        if device_config.disable_key_expiry != device.key_expiry_disabled {
+            info!(
+                "setting key expiry disabled of tailscale device {} to {}",
+                device.name, device_config.disable_key_expiry
+            );
+            tailscale
+                .set_device_key_expiry_disabled(&device.id, device_config.disable_key_expiry)
+                .await?;
+        }
+
+        let routes = tailscale.get_device_routes(&device.id).await?;
+        for route in &device_config.routes {
+            if !routes.advertised_routes.contains(route) {
+                warn!(
+                    "tailscale device {} does not advertise the configured route {}",
+                    device.name, route
+                );
+            }
+        }
+
+        let mut enabled_routes = device_config.routes.clone();
+        enabled_routes.sort();
+        let mut current_routes = routes.enabled_routes.clone();
+        current_routes.sort();
+        if enabled_routes != current_routes {
+            info!(
+                "setting enabled routes of tailscale device {} to {:?}",
+                device.name, enabled_routes
+            );
+            tailscale.set_device_routes(&device.id, &enabled_routes).await?;
+        }
+    }
+
+    info!("synced tailscale devices successfully");
+
+    Ok(())
+}
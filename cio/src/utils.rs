@@ -343,6 +343,34 @@ pub async fn encrypt_github_secrets(
     Ok((pk.key_id, secrets))
 }
 
+/// Create or update the given GitHub Actions secrets, keyed by name, for a repo in the
+/// company's org.
+pub async fn update_github_secrets(
+    github: &octorust::Client,
+    company: &Company,
+    repo: &str,
+    s: &BTreeMap<String, String>,
+) -> Result<()> {
+    let (key_id, secrets) = encrypt_github_secrets(github, company, repo, s).await?;
+
+    for (name, encrypted_value) in secrets {
+        github
+            .actions()
+            .create_or_update_repo_secret(
+                &company.github_org,
+                repo,
+                &name,
+                &octorust::types::ActionsCreateUpdateRepoSecretRequest {
+                    encrypted_value,
+                    key_id: key_id.to_string(),
+                },
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
 /// Generate a random string that we can use as a temporary password for new users
 /// when we set up their account.
 pub fn generate_password() -> String {
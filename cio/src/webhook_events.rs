@@ -0,0 +1,246 @@
+/*!
+ * A durable queue of inbound webhook events.
+ *
+ * Webhooks are verified and stored as soon as they arrive, and the work they trigger is done
+ * later by workers that claim events from the queue. Claiming uses `FOR UPDATE SKIP LOCKED` so
+ * any number of workers, in any number of processes, can drain the queue without handing the
+ * same event to two of them.
+ *
+ * The bodies of events are redacted once they have been processed, so the queue does not keep
+ * personal data around longer than it is needed.
+ */
+use std::ops::DerefMut;
+
+use anyhow::Result;
+use async_bb8_diesel::{AsyncConnection, AsyncRunQueryDsl};
+use chrono::{DateTime, Duration, Utc};
+use diesel::{
+    BoolExpressionMethods, ExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable, RunQueryDsl,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::Database,
+    failures::FailureFilter,
+    redaction::{redactor, RedactionTarget},
+    schema::webhook_events,
+};
+
+pub const WEBHOOK_EVENT_STATUS_PENDING: &str = "pending";
+pub const WEBHOOK_EVENT_STATUS_PROCESSING: &str = "processing";
+pub const WEBHOOK_EVENT_STATUS_DONE: &str = "done";
+pub const WEBHOOK_EVENT_STATUS_FAILED: &str = "failed";
+
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct WebhookEvent {
+    pub id: i32,
+    /// The service that sent the webhook, ie. `github`.
+    pub source: String,
+    /// The kind of event, for services that send it outside of the body.
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub status: String,
+    pub attempts: i32,
+    pub last_error: String,
+    pub received_at: DateTime<Utc>,
+    /// The event is not handed to a worker before this time, which is how failed events back
+    /// off before they are retried.
+    pub available_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// The correlation id of the request the event arrived with, see [`crate::correlation`].
+    pub correlation_id: String,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = webhook_events)]
+pub struct NewWebhookEvent {
+    pub source: String,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub correlation_id: String,
+}
+
+impl NewWebhookEvent {
+    pub async fn enqueue(&self, db: &Database) -> Result<WebhookEvent> {
+        Ok(diesel::insert_into(webhook_events::table)
+            .values(self.clone())
+            .get_result_async::<WebhookEvent>(db.pool())
+            .await?)
+    }
+}
+
+/// How long to wait before retrying an event that has failed `attempts` times. This doubles
+/// from 30 seconds up to an hour.
+pub fn retry_backoff(attempts: i32) -> Duration {
+    let exponent = attempts.clamp(1, 8) - 1;
+    std::cmp::min(Duration::seconds(30 * 2i64.pow(exponent as u32)), Duration::hours(1))
+}
+
+impl WebhookEvent {
+    /// The number of events from `source` that are waiting for a worker.
+    pub async fn count_pending(db: &Database, source: &str) -> Result<i64> {
+        Ok(webhook_events::dsl::webhook_events
+            .filter(webhook_events::dsl::source.eq(source.to_string()))
+            .filter(webhook_events::dsl::status.eq(WEBHOOK_EVENT_STATUS_PENDING.to_string()))
+            .count()
+            .get_result_async::<i64>(db.pool())
+            .await?)
+    }
+
+    /// Claim the oldest event from `source` that is ready to be processed. Events that have been
+    /// processing for longer than `stale_after` are claimed again, since the worker processing
+    /// them must have gone away without finishing.
+    pub async fn claim_next(db: &Database, source: &str, stale_after: Duration) -> Result<Option<WebhookEvent>> {
+        let source = source.to_string();
+
+        db.pool()
+            .transaction(move |conn| {
+                let now = Utc::now();
+
+                let event = webhook_events::dsl::webhook_events
+                    .filter(webhook_events::dsl::source.eq(source))
+                    .filter(
+                        webhook_events::dsl::status
+                            .eq(WEBHOOK_EVENT_STATUS_PENDING.to_string())
+                            .and(webhook_events::dsl::available_at.le(now))
+                            .or(webhook_events::dsl::status
+                                .eq(WEBHOOK_EVENT_STATUS_PROCESSING.to_string())
+                                .and(webhook_events::dsl::started_at.lt(now - stale_after))),
+                    )
+                    .order_by(webhook_events::dsl::id)
+                    .for_update()
+                    .skip_locked()
+                    .first::<WebhookEvent>(conn.deref_mut())
+                    .optional()?;
+
+                let event = match event {
+                    Some(event) => event,
+                    None => return Ok(None),
+                };
+
+                let claimed = diesel::update(webhook_events::dsl::webhook_events.find(event.id))
+                    .set((
+                        webhook_events::dsl::status.eq(WEBHOOK_EVENT_STATUS_PROCESSING.to_string()),
+                        webhook_events::dsl::attempts.eq(event.attempts + 1),
+                        webhook_events::dsl::started_at.eq(Some(now)),
+                    ))
+                    .get_result::<WebhookEvent>(conn.deref_mut())?;
+
+                Ok(Some(claimed))
+            })
+            .await
+    }
+
+    /// Record that the event was processed. The body of the event is redacted now that it is
+    /// no longer needed, see [`crate::redaction`]. Failed events keep theirs so they can be
+    /// requeued.
+    pub async fn complete(&self, db: &Database) -> Result<()> {
+        let mut payload = self.payload.clone();
+        redactor().redact_json(RedactionTarget::WebhookEvents, &mut payload);
+
+        diesel::update(webhook_events::dsl::webhook_events.find(self.id))
+            .set((
+                webhook_events::dsl::status.eq(WEBHOOK_EVENT_STATUS_DONE.to_string()),
+                webhook_events::dsl::payload.eq(payload),
+                webhook_events::dsl::last_error.eq(String::new()),
+                webhook_events::dsl::finished_at.eq(Some(Utc::now())),
+            ))
+            .execute_async(db.pool())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Record that processing the event failed. The event is retried after a backoff until it
+    /// has been attempted `max_attempts` times, after which it is left as failed. Returns
+    /// whether the event will be retried.
+    pub async fn fail(&self, db: &Database, error: &str, max_attempts: i32) -> Result<bool> {
+        let now = Utc::now();
+        let retry = self.attempts < max_attempts;
+
+        let (status, finished_at) = if retry {
+            (WEBHOOK_EVENT_STATUS_PENDING, None)
+        } else {
+            (WEBHOOK_EVENT_STATUS_FAILED, Some(now))
+        };
+
+        diesel::update(webhook_events::dsl::webhook_events.find(self.id))
+            .set((
+                webhook_events::dsl::status.eq(status.to_string()),
+                webhook_events::dsl::last_error.eq(error.to_string()),
+                webhook_events::dsl::available_at.eq(now + retry_backoff(self.attempts)),
+                webhook_events::dsl::finished_at.eq(finished_at),
+            ))
+            .execute_async(db.pool())
+            .await?;
+
+        Ok(retry)
+    }
+
+    /// The events that failed for good and match `filter`, newest first, at most `limit` of them.
+    pub async fn list_failed(db: &Database, filter: &FailureFilter, limit: usize) -> Result<Vec<WebhookEvent>> {
+        let mut query = webhook_events::dsl::webhook_events
+            .filter(webhook_events::dsl::status.eq(WEBHOOK_EVENT_STATUS_FAILED.to_string()))
+            .into_boxed();
+
+        if let Some(source) = &filter.source {
+            query = query.filter(webhook_events::dsl::source.eq(source.to_string()));
+        }
+        if let Some(since) = filter.since {
+            query = query.filter(webhook_events::dsl::received_at.ge(since));
+        }
+        if let Some(until) = filter.until {
+            query = query.filter(webhook_events::dsl::received_at.lt(until));
+        }
+
+        let events = query
+            .order_by(webhook_events::dsl::id.desc())
+            .load_async::<WebhookEvent>(db.pool())
+            .await?;
+
+        // The class of an error is not stored, so it is filtered on here.
+        Ok(events
+            .into_iter()
+            .filter(|event| filter.matches_error(&event.last_error))
+            .take(limit)
+            .collect())
+    }
+
+    /// Put failed events back in the queue to be tried again from scratch, as if they had just
+    /// arrived. Events that are no longer failed are left alone. Returns how many were requeued.
+    pub async fn requeue_failed(db: &Database, ids: Vec<i32>) -> Result<usize> {
+        Ok(diesel::update(
+            webhook_events::dsl::webhook_events
+                .filter(webhook_events::dsl::id.eq_any(ids))
+                .filter(webhook_events::dsl::status.eq(WEBHOOK_EVENT_STATUS_FAILED.to_string())),
+        )
+        .set((
+            webhook_events::dsl::status.eq(WEBHOOK_EVENT_STATUS_PENDING.to_string()),
+            webhook_events::dsl::attempts.eq(0),
+            webhook_events::dsl::available_at.eq(Utc::now()),
+            webhook_events::dsl::started_at.eq(None::<DateTime<Utc>>),
+            webhook_events::dsl::finished_at.eq(None::<DateTime<Utc>>),
+        ))
+        .execute_async(db.pool())
+        .await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::retry_backoff;
+
+    #[test]
+    fn test_retry_backoff() {
+        assert_eq!(Duration::seconds(30), retry_backoff(0));
+        assert_eq!(Duration::seconds(30), retry_backoff(1));
+        assert_eq!(Duration::seconds(60), retry_backoff(2));
+        assert_eq!(Duration::minutes(16), retry_backoff(6));
+        assert_eq!(Duration::hours(1), retry_backoff(8));
+        assert_eq!(Duration::hours(1), retry_backoff(100));
+    }
+}
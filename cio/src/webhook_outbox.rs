@@ -0,0 +1,542 @@
+/*!
+ * Notifying other services of things that happen in cio, through webhooks they register for.
+ *
+ * A consumer registers a URL and the events it wants, ie. `rfd_published`. Publishing an event
+ * adds a delivery of it for every consumer that wants it to `webhook_deliveries`, the outbox,
+ * and the `send-webhook-deliveries` job posts them. A delivery that fails is retried, backing
+ * off each time, until it runs out of attempts. The deliveries are kept as the log of what was
+ * sent to whom, and what came back, except for the events about a person, which are deleted
+ * with the rest of their data when they ask for it to be erased.
+ *
+ * Every delivery is signed with the consumer's secret the same way Stripe signs its webhooks:
+ * the `X-Cio-Signature` header is `t=<unix timestamp>,v1=<signature>`, where the signature is
+ * the hex HMAC-SHA256 of `<timestamp>.<body>`. Events have a key that is the same every time
+ * the same thing is published, so a consumer gets each event at most once from the outbox and
+ * can use the `X-Cio-Event-Key` header to drop repeats of a delivery it already handled.
+ */
+use std::fmt;
+
+use anyhow::{anyhow, bail, Result};
+use async_bb8_diesel::{AsyncRunQueryDsl, OptionalExtension};
+use chrono::{DateTime, Duration, Utc};
+use diesel::{ExpressionMethods, Insertable, QueryDsl, Queryable};
+use log::{info, warn};
+use ring::{
+    hmac,
+    rand::{SecureRandom, SystemRandom},
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    companies::Company,
+    db::Database,
+    schema::{webhook_consumers, webhook_deliveries},
+};
+
+/// A delivery is given up on after this many failed attempts, which with the backoff is about
+/// ten and a half hours after it was published.
+const MAX_ATTEMPTS: i32 = 8;
+
+/// The most deliveries the log returns at once.
+pub const MAX_DELIVERIES: i64 = 500;
+
+/// The events consumers can register for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutboxEvent {
+    NewHire,
+    RfdPublished,
+    ShipmentDelivered,
+}
+
+impl OutboxEvent {
+    pub const ALL: [OutboxEvent; 3] = [
+        OutboxEvent::NewHire,
+        OutboxEvent::RfdPublished,
+        OutboxEvent::ShipmentDelivered,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutboxEvent::NewHire => "new_hire",
+            OutboxEvent::RfdPublished => "rfd_published",
+            OutboxEvent::ShipmentDelivered => "shipment_delivered",
+        }
+    }
+
+    pub fn parse(event: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| e.as_str() == event)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Delivered,
+    /// The delivery ran out of attempts.
+    Failed,
+}
+
+impl fmt::Display for WebhookDeliveryStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            WebhookDeliveryStatus::Pending => "pending",
+            WebhookDeliveryStatus::Delivered => "delivered",
+            WebhookDeliveryStatus::Failed => "failed",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct WebhookConsumer {
+    pub id: i32,
+    pub name: String,
+    pub url: String,
+    /// Only handed out when the consumer is registered.
+    #[serde(skip_serializing)]
+    pub secret: String,
+    /// The events the consumer gets, ie. `rfd_published`.
+    pub events: Vec<String>,
+    pub enabled: bool,
+    pub cio_company_id: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A consumer as it is registered.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct NewWebhookConsumer {
+    pub name: String,
+    pub url: String,
+    pub events: Vec<String>,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = webhook_consumers)]
+struct NewWebhookConsumerRow {
+    name: String,
+    url: String,
+    secret: String,
+    events: Vec<String>,
+    enabled: bool,
+    cio_company_id: i32,
+}
+
+/// A consumer that was just registered, with the secret its deliveries are signed with.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Serialize)]
+pub struct RegisteredWebhookConsumer {
+    pub consumer: WebhookConsumer,
+    pub secret: String,
+}
+
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct WebhookDelivery {
+    pub id: i32,
+    pub consumer_id: i32,
+    pub event: String,
+    /// The same for every delivery of the same event, ie. `rfd_published:123`.
+    pub event_key: String,
+    /// The body that is posted.
+    pub payload: serde_json::Value,
+    pub status: String,
+    pub attempts: i32,
+    /// When the delivery is tried next, while it is pending.
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_attempt_at: Option<DateTime<Utc>>,
+    /// The status code the consumer answered the last attempt with.
+    pub response_status: Option<i32>,
+    pub last_error: String,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub cio_company_id: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = webhook_deliveries)]
+struct NewWebhookDelivery {
+    consumer_id: i32,
+    event: String,
+    event_key: String,
+    payload: serde_json::Value,
+    cio_company_id: i32,
+}
+
+impl NewWebhookConsumer {
+    /// Check the consumer has a URL to post to and only asks for events we know.
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("a consumer needs a name".to_string());
+        }
+
+        if !self.url.starts_with("https://") {
+            return Err("a consumer needs an `https://` url to deliver to".to_string());
+        }
+
+        if self.events.is_empty() {
+            return Err("a consumer needs at least one event".to_string());
+        }
+
+        match self.events.iter().find(|event| OutboxEvent::parse(event).is_none()) {
+            Some(event) => Err(format!(
+                "unknown event `{}`, expected one of: {}",
+                event,
+                OutboxEvent::ALL.map(|e| e.as_str()).join(", ")
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Register the consumer with a new secret.
+    pub async fn register(&self, db: &Database, cio_company_id: i32) -> Result<RegisteredWebhookConsumer> {
+        let mut secret = [0; 32];
+        SystemRandom::new()
+            .fill(&mut secret)
+            .map_err(|_| anyhow!("failed to generate a webhook consumer secret"))?;
+        let secret = hex::encode(secret);
+
+        let consumer = diesel::insert_into(webhook_consumers::table)
+            .values(NewWebhookConsumerRow {
+                name: self.name.trim().to_string(),
+                url: self.url.to_string(),
+                secret: secret.to_string(),
+                events: self.events.clone(),
+                enabled: self.enabled,
+                cio_company_id,
+            })
+            .get_result_async::<WebhookConsumer>(db.pool())
+            .await?;
+
+        Ok(RegisteredWebhookConsumer { consumer, secret })
+    }
+}
+
+impl WebhookConsumer {
+    pub async fn list(db: &Database, cio_company_id: i32) -> Result<Vec<WebhookConsumer>> {
+        Ok(webhook_consumers::dsl::webhook_consumers
+            .filter(webhook_consumers::dsl::cio_company_id.eq(cio_company_id))
+            .order_by(webhook_consumers::dsl::id)
+            .load_async::<WebhookConsumer>(db.pool())
+            .await?)
+    }
+
+    pub async fn get(db: &Database, cio_company_id: i32, id: i32) -> Result<Option<WebhookConsumer>> {
+        Ok(webhook_consumers::dsl::webhook_consumers
+            .filter(webhook_consumers::dsl::cio_company_id.eq(cio_company_id))
+            .filter(webhook_consumers::dsl::id.eq(id))
+            .first_async::<WebhookConsumer>(db.pool())
+            .await
+            .optional()?)
+    }
+
+    /// Delete the company's consumer with `id`, along with its deliveries. Returns whether there
+    /// was such a consumer.
+    pub async fn delete(db: &Database, cio_company_id: i32, id: i32) -> Result<bool> {
+        let deleted = diesel::delete(
+            webhook_consumers::dsl::webhook_consumers
+                .filter(webhook_consumers::dsl::cio_company_id.eq(cio_company_id))
+                .filter(webhook_consumers::dsl::id.eq(id)),
+        )
+        .execute_async(db.pool())
+        .await?;
+
+        Ok(deleted > 0)
+    }
+}
+
+/// How long to wait before retrying a delivery that has failed `attempts` times. This doubles
+/// from 5 minutes, reaching 640 minutes by the last attempt, and never exceeds 12 hours.
+fn retry_backoff(attempts: i32) -> Duration {
+    let exponent = attempts.clamp(1, 10) - 1;
+    std::cmp::min(Duration::minutes(5 * 2i64.pow(exponent as u32)), Duration::hours(12))
+}
+
+/// The `X-Cio-Signature` header of a delivery of `body` at `timestamp`.
+fn signature(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+
+    let mut signed = format!("{}.", timestamp).into_bytes();
+    signed.extend_from_slice(body);
+
+    format!("t={},v1={}", timestamp, hex::encode(hmac::sign(&key, &signed).as_ref()))
+}
+
+impl WebhookDelivery {
+    /// The deliveries to the company's consumer with `id`, newest first, optionally only those
+    /// with `status`.
+    pub async fn list_for_consumer(
+        db: &Database,
+        cio_company_id: i32,
+        consumer_id: i32,
+        status: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>> {
+        let mut query = webhook_deliveries::dsl::webhook_deliveries
+            .filter(webhook_deliveries::dsl::cio_company_id.eq(cio_company_id))
+            .filter(webhook_deliveries::dsl::consumer_id.eq(consumer_id))
+            .into_boxed();
+        if let Some(status) = status {
+            query = query.filter(webhook_deliveries::dsl::status.eq(status.to_string()));
+        }
+
+        Ok(query
+            .order_by(webhook_deliveries::dsl::id.desc())
+            .limit(limit.clamp(1, MAX_DELIVERIES))
+            .load_async::<WebhookDelivery>(db.pool())
+            .await?)
+    }
+
+    /// Send the company's delivery with `id` again on the next run, with its attempts reset.
+    /// Returns `None` when there is no such delivery.
+    pub async fn redeliver(db: &Database, cio_company_id: i32, id: i32) -> Result<Option<WebhookDelivery>> {
+        Ok(diesel::update(
+            webhook_deliveries::dsl::webhook_deliveries
+                .filter(webhook_deliveries::dsl::cio_company_id.eq(cio_company_id))
+                .filter(webhook_deliveries::dsl::id.eq(id)),
+        )
+        .set((
+            webhook_deliveries::dsl::status.eq(WebhookDeliveryStatus::Pending.to_string()),
+            webhook_deliveries::dsl::attempts.eq(0),
+            webhook_deliveries::dsl::next_attempt_at.eq(Utc::now()),
+            webhook_deliveries::dsl::updated_at.eq(Utc::now()),
+        ))
+        .get_result_async::<WebhookDelivery>(db.pool())
+        .await
+        .optional()?)
+    }
+
+    /// Claim the delivery for this attempt. The next attempt is scheduled as part of the claim,
+    /// so a delivery whose attempt never finishes is picked up again once that time comes.
+    /// Returns whether nobody else had claimed it, so that overlapping runs do not both post it.
+    async fn claim(&self, db: &Database) -> Result<bool> {
+        let now = Utc::now();
+        let claimed = diesel::update(webhook_deliveries::dsl::webhook_deliveries)
+            .filter(webhook_deliveries::dsl::id.eq(self.id))
+            .filter(webhook_deliveries::dsl::status.eq(WebhookDeliveryStatus::Pending.to_string()))
+            .filter(webhook_deliveries::dsl::attempts.eq(self.attempts))
+            .set((
+                webhook_deliveries::dsl::attempts.eq(self.attempts + 1),
+                webhook_deliveries::dsl::last_attempt_at.eq(Some(now)),
+                webhook_deliveries::dsl::next_attempt_at.eq(now + retry_backoff(self.attempts + 1)),
+                webhook_deliveries::dsl::updated_at.eq(now),
+            ))
+            .execute_async(db.pool())
+            .await?;
+
+        Ok(claimed > 0)
+    }
+
+    async fn record_attempt(&self, db: &Database, response_status: Option<i32>, error: Option<&str>) -> Result<()> {
+        let status = match error {
+            None => WebhookDeliveryStatus::Delivered,
+            Some(_) if self.attempts + 1 >= MAX_ATTEMPTS => WebhookDeliveryStatus::Failed,
+            Some(_) => WebhookDeliveryStatus::Pending,
+        };
+        let delivered_at = if status == WebhookDeliveryStatus::Delivered {
+            Some(Utc::now())
+        } else {
+            None
+        };
+
+        diesel::update(webhook_deliveries::dsl::webhook_deliveries.find(self.id))
+            .set((
+                webhook_deliveries::dsl::status.eq(status.to_string()),
+                webhook_deliveries::dsl::response_status.eq(response_status),
+                webhook_deliveries::dsl::last_error.eq(error.unwrap_or_default().to_string()),
+                webhook_deliveries::dsl::delivered_at.eq(delivered_at),
+                webhook_deliveries::dsl::updated_at.eq(Utc::now()),
+            ))
+            .execute_async(db.pool())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Post the delivery to the consumer. Returns the status code it answered with.
+    async fn post(&self, client: &reqwest::Client, consumer: &WebhookConsumer) -> Result<reqwest::StatusCode> {
+        let body = serde_json::to_vec(&self.payload)?;
+
+        let response = client
+            .post(&consumer.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header("X-Cio-Event", &self.event)
+            .header("X-Cio-Event-Key", &self.event_key)
+            .header("X-Cio-Delivery", self.id.to_string())
+            .header(
+                "X-Cio-Signature",
+                signature(&consumer.secret, Utc::now().timestamp(), &body),
+            )
+            .timeout(std::time::Duration::from_secs(30))
+            .body(body)
+            .send()
+            .await?;
+
+        Ok(response.status())
+    }
+}
+
+/// Add a delivery of the event to every enabled consumer of the company that wants it. `key` is
+/// what the event is about, ie. the number of an RFD, and an event is only added once for the
+/// same key, so publishing the same thing again is harmless.
+pub async fn publish(
+    db: &Database,
+    company: &Company,
+    event: OutboxEvent,
+    key: &str,
+    data: serde_json::Value,
+) -> Result<()> {
+    let consumers = webhook_consumers::dsl::webhook_consumers
+        .filter(webhook_consumers::dsl::cio_company_id.eq(company.id))
+        .filter(webhook_consumers::dsl::enabled.eq(true))
+        .load_async::<WebhookConsumer>(db.pool())
+        .await?
+        .into_iter()
+        .filter(|consumer| consumer.events.iter().any(|e| e == event.as_str()))
+        .collect::<Vec<_>>();
+    if consumers.is_empty() {
+        return Ok(());
+    }
+
+    let event_key = format!("{}:{}", event.as_str(), key);
+    let payload = serde_json::json!({
+        "event": event.as_str(),
+        "key": event_key,
+        "published_at": Utc::now(),
+        "data": data,
+    });
+
+    let added = diesel::insert_into(webhook_deliveries::table)
+        .values(
+            consumers
+                .iter()
+                .map(|consumer| NewWebhookDelivery {
+                    consumer_id: consumer.id,
+                    event: event.as_str().to_string(),
+                    event_key: event_key.to_string(),
+                    payload: payload.clone(),
+                    cio_company_id: company.id,
+                })
+                .collect::<Vec<_>>(),
+        )
+        .on_conflict((webhook_deliveries::consumer_id, webhook_deliveries::event_key))
+        .do_nothing()
+        .execute_async(db.pool())
+        .await?;
+
+    if added > 0 {
+        info!("published `{}` to {} webhook consumers", event_key, added);
+    }
+
+    Ok(())
+}
+
+/// Post the deliveries that are due to their consumers.
+pub async fn send_webhook_deliveries(db: &Database, company: &Company) -> Result<()> {
+    let due = webhook_deliveries::dsl::webhook_deliveries
+        .filter(webhook_deliveries::dsl::cio_company_id.eq(company.id))
+        .filter(webhook_deliveries::dsl::status.eq(WebhookDeliveryStatus::Pending.to_string()))
+        .filter(webhook_deliveries::dsl::next_attempt_at.le(Utc::now()))
+        .order_by(webhook_deliveries::dsl::id)
+        .load_async::<WebhookDelivery>(db.pool())
+        .await?;
+    if due.is_empty() {
+        return Ok(());
+    }
+
+    let consumers = WebhookConsumer::list(db, company.id).await?;
+    let client = reqwest::Client::new();
+
+    let mut failed = 0;
+    for delivery in &due {
+        // Deliveries to consumers that were disabled wait until they are enabled again.
+        let consumer = match consumers.iter().find(|consumer| consumer.id == delivery.consumer_id) {
+            Some(consumer) if consumer.enabled => consumer,
+            _ => continue,
+        };
+
+        if !delivery.claim(db).await? {
+            continue;
+        }
+
+        let (response_status, error) = match delivery.post(&client, consumer).await {
+            Ok(status) if status.is_success() => (Some(status.as_u16().into()), None),
+            Ok(status) => (
+                Some(status.as_u16().into()),
+                Some(format!("{} answered with {}", consumer.url, status)),
+            ),
+            Err(e) => (None, Some(e.to_string())),
+        };
+
+        if let Some(error) = &error {
+            warn!(
+                "failed to deliver `{}` to webhook consumer {} on attempt {}: {}",
+                delivery.event_key,
+                consumer.name,
+                delivery.attempts + 1,
+                error
+            );
+            failed += 1;
+        }
+
+        delivery.record_attempt(db, response_status, error.as_deref()).await?;
+    }
+
+    info!("sent {} of {} webhook deliveries", due.len() - failed, due.len());
+
+    if failed > 0 {
+        bail!("{} webhook deliveries failed", failed);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::{retry_backoff, signature, NewWebhookConsumer};
+
+    #[test]
+    fn test_retry_backoff() {
+        assert_eq!(retry_backoff(0), Duration::minutes(5));
+        assert_eq!(retry_backoff(1), Duration::minutes(5));
+        assert_eq!(retry_backoff(2), Duration::minutes(10));
+        assert_eq!(retry_backoff(4), Duration::minutes(40));
+        assert_eq!(retry_backoff(8), Duration::minutes(640));
+        assert_eq!(retry_backoff(100), Duration::hours(12));
+    }
+
+    #[test]
+    fn test_signature() {
+        assert_eq!(
+            signature("whsec", 1680000000, br#"{"event":"new_hire"}"#),
+            "t=1680000000,v1=f3bb29b593d746da9f7c6ba8976e79d796c43d7dff3ba788012bfa086201657d"
+        );
+    }
+
+    #[test]
+    fn test_validate_consumer() {
+        let mut consumer = NewWebhookConsumer {
+            name: "Inventory".to_string(),
+            url: "https://inventory.internal.example.com/hooks/cio".to_string(),
+            events: vec!["shipment_delivered".to_string(), "new_hire".to_string()],
+            enabled: true,
+        };
+        assert!(consumer.validate().is_ok());
+
+        consumer.events.push("applicant_hired".to_string());
+        assert!(consumer.validate().is_err());
+
+        consumer.events.pop();
+        consumer.url = "http://inventory.internal.example.com/hooks/cio".to_string();
+        assert!(consumer.validate().is_err());
+    }
+}
@@ -0,0 +1,332 @@
+/*!
+ * Creating Zoom meetings and webinars for interviews and huddles.
+ *
+ * The `zoom-api` crate is used for users and recordings, but its meeting types are awkward to
+ * build by hand, so meetings are managed with this small client instead.
+ */
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use log::info;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+const ZOOM_ENDPOINT: &str = "https://api.zoom.us/v2";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZoomMeetingKind {
+    Meeting,
+    Webinar,
+}
+
+impl ZoomMeetingKind {
+    fn path(&self) -> &'static str {
+        match self {
+            ZoomMeetingKind::Meeting => "meetings",
+            ZoomMeetingKind::Webinar => "webinars",
+        }
+    }
+
+    /// The Zoom type for a meeting at a single time.
+    fn scheduled_type(&self) -> i32 {
+        match self {
+            ZoomMeetingKind::Meeting => 2,
+            ZoomMeetingKind::Webinar => 5,
+        }
+    }
+
+    /// The Zoom type for a recurring meeting that can be joined at any time. The schedule
+    /// itself lives on the calendar event.
+    fn recurring_type(&self) -> i32 {
+        match self {
+            ZoomMeetingKind::Meeting => 3,
+            ZoomMeetingKind::Webinar => 9,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ZoomMeetingSettings {
+    /// The emails of the alternative hosts, separated by `;`.
+    #[serde(default)]
+    pub alternative_hosts: String,
+    #[serde(default)]
+    pub join_before_host: bool,
+    #[serde(default)]
+    pub waiting_room: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ZoomMeetingRequest {
+    pub topic: String,
+    #[serde(rename = "type")]
+    pub type_: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<DateTime<Utc>>,
+    /// The length of the meeting in minutes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<i64>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub timezone: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub agenda: String,
+    pub settings: ZoomMeetingSettings,
+}
+
+impl ZoomMeetingRequest {
+    pub fn scheduled(
+        kind: ZoomMeetingKind,
+        topic: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        alternative_hosts: &[String],
+    ) -> Self {
+        ZoomMeetingRequest {
+            topic: topic.to_string(),
+            type_: kind.scheduled_type(),
+            start_time: Some(start),
+            duration: Some((end - start).num_minutes()),
+            timezone: "UTC".to_string(),
+            agenda: String::new(),
+            settings: ZoomMeetingSettings {
+                alternative_hosts: alternative_hosts.join(";"),
+                join_before_host: true,
+                waiting_room: false,
+            },
+        }
+    }
+
+    pub fn recurring(kind: ZoomMeetingKind, topic: &str, agenda: &str, alternative_hosts: &[String]) -> Self {
+        ZoomMeetingRequest {
+            topic: topic.to_string(),
+            type_: kind.recurring_type(),
+            start_time: None,
+            duration: None,
+            timezone: String::new(),
+            agenda: agenda.to_string(),
+            settings: ZoomMeetingSettings {
+                alternative_hosts: alternative_hosts.join(";"),
+                join_before_host: true,
+                waiting_room: false,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ZoomMeeting {
+    pub id: i64,
+    pub join_url: String,
+    #[serde(default)]
+    pub topic: String,
+    #[serde(default)]
+    pub host_email: String,
+    #[serde(default)]
+    pub start_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub duration: Option<i64>,
+    #[serde(default)]
+    pub agenda: String,
+    #[serde(default)]
+    pub settings: ZoomMeetingSettings,
+}
+
+impl ZoomMeeting {
+    /// Whether the meeting already looks the way the request would leave it. Alternative hosts
+    /// are compared without regard to order or case, since Zoom does not keep either.
+    fn matches(&self, request: &ZoomMeetingRequest) -> bool {
+        let hosts = |hosts: &str| -> Vec<String> {
+            let mut hosts: Vec<String> = hosts
+                .split([';', ','])
+                .map(|host| host.trim().to_lowercase())
+                .filter(|host| !host.is_empty())
+                .collect();
+            hosts.sort();
+            hosts
+        };
+
+        self.topic == request.topic
+            && self.agenda == request.agenda
+            && (request.start_time.is_none() || self.start_time == request.start_time)
+            && (request.duration.is_none() || self.duration == request.duration)
+            && hosts(&self.settings.alternative_hosts) == hosts(&request.settings.alternative_hosts)
+    }
+}
+
+/// A minimal client for the Zoom meetings and webinars APIs.
+pub struct ZoomMeetings {
+    client: reqwest::Client,
+    token: String,
+}
+
+impl ZoomMeetings {
+    pub fn new(token: &str) -> Self {
+        ZoomMeetings {
+            client: reqwest::Client::new(),
+            token: token.to_string(),
+        }
+    }
+
+    /// Create a meeting that is hosted by `host`, which can be the email of any user in the
+    /// Zoom account.
+    pub async fn create(&self, kind: ZoomMeetingKind, host: &str, request: &ZoomMeetingRequest) -> Result<ZoomMeeting> {
+        Ok(self
+            .client
+            .post(format!("{}/users/{}/{}", ZOOM_ENDPOINT, host, kind.path()))
+            .bearer_auth(&self.token)
+            .json(request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    /// Get a meeting, meetings that have been deleted are returned as `None`.
+    pub async fn get(&self, kind: ZoomMeetingKind, id: &str) -> Result<Option<ZoomMeeting>> {
+        let response = self
+            .client
+            .get(format!("{}/{}/{}", ZOOM_ENDPOINT, kind.path(), id))
+            .bearer_auth(&self.token)
+            .send()
+            .await?;
+
+        // Zoom responds with a 400 rather than a 404 for some meetings that have been deleted.
+        if response.status() == StatusCode::NOT_FOUND || response.status() == StatusCode::BAD_REQUEST {
+            return Ok(None);
+        }
+
+        Ok(Some(response.error_for_status()?.json().await?))
+    }
+
+    pub async fn update(&self, kind: ZoomMeetingKind, id: &str, request: &ZoomMeetingRequest) -> Result<()> {
+        self.client
+            .patch(format!("{}/{}/{}", ZOOM_ENDPOINT, kind.path(), id))
+            .bearer_auth(&self.token)
+            .json(request)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+
+    /// Delete a meeting without notifying the people that were invited to it, since the
+    /// calendar event takes care of that. Deleting a meeting that is already gone is not an
+    /// error.
+    pub async fn delete(&self, kind: ZoomMeetingKind, id: &str) -> Result<()> {
+        let response = self
+            .client
+            .delete(format!("{}/{}/{}", ZOOM_ENDPOINT, kind.path(), id))
+            .bearer_auth(&self.token)
+            .query(&[("schedule_for_reminder", "false")])
+            .send()
+            .await?;
+
+        match response.status() {
+            status if status.is_success() => Ok(()),
+            StatusCode::NOT_FOUND | StatusCode::BAD_REQUEST => Ok(()),
+            status => bail!("deleting zoom {} `{}` failed: {}", kind.path(), id, status),
+        }
+    }
+
+    /// Make sure the meeting with `id` exists, is hosted by `host` and matches the request.
+    /// A new meeting is created when there is no meeting yet, when it has been deleted, or when
+    /// its host has changed, since Zoom does not allow changing the host of a meeting.
+    pub async fn ensure(
+        &self,
+        kind: ZoomMeetingKind,
+        id: &str,
+        host: &str,
+        request: &ZoomMeetingRequest,
+    ) -> Result<ZoomMeeting> {
+        if !id.is_empty() {
+            match self.get(kind, id).await? {
+                Some(meeting) if meeting.host_email.to_lowercase() == host.to_lowercase() => {
+                    if !meeting.matches(request) {
+                        self.update(kind, id, request).await?;
+                        info!("updated zoom {} `{}`", kind.path(), request.topic);
+                    }
+
+                    return Ok(meeting);
+                }
+                Some(_) => {
+                    self.delete(kind, id).await?;
+                    info!(
+                        "deleted zoom {} `{}` since its host changed",
+                        kind.path(),
+                        request.topic
+                    );
+                }
+                None => (),
+            }
+        }
+
+        let meeting = self.create(kind, host, request).await?;
+        info!("created zoom {} `{}` hosted by {}", kind.path(), request.topic, host);
+
+        Ok(meeting)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::{ZoomMeeting, ZoomMeetingKind, ZoomMeetingRequest};
+
+    #[test]
+    fn test_scheduled_request() {
+        let start = Utc.ymd(2023, 3, 14).and_hms(17, 0, 0);
+        let request = ZoomMeetingRequest::scheduled(
+            ZoomMeetingKind::Meeting,
+            "Jane Doe (joe, sam)",
+            start,
+            start + chrono::Duration::minutes(45),
+            &["sam@example.com".to_string()],
+        );
+
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(2, value["type"]);
+        assert_eq!(45, value["duration"]);
+        assert_eq!("2023-03-14T17:00:00Z", value["start_time"]);
+        assert_eq!("sam@example.com", value["settings"]["alternative_hosts"]);
+        assert!(value.get("agenda").is_none());
+
+        let value = serde_json::to_value(&ZoomMeetingRequest::recurring(
+            ZoomMeetingKind::Webinar,
+            "Product huddle",
+            "",
+            &[],
+        ))
+        .unwrap();
+        assert_eq!(9, value["type"]);
+        assert!(value.get("start_time").is_none());
+    }
+
+    #[test]
+    fn test_meeting_matches() {
+        let start = Utc.ymd(2023, 3, 14).and_hms(17, 0, 0);
+        let request = ZoomMeetingRequest::scheduled(
+            ZoomMeetingKind::Meeting,
+            "Interview",
+            start,
+            start + chrono::Duration::hours(1),
+            &["b@example.com".to_string(), "a@example.com".to_string()],
+        );
+
+        let mut meeting: ZoomMeeting = serde_json::from_value(serde_json::json!({
+            "id": 123,
+            "join_url": "https://zoom.us/j/123",
+            "topic": "Interview",
+            "host_email": "host@example.com",
+            "start_time": "2023-03-14T17:00:00Z",
+            "duration": 60,
+            "settings": { "alternative_hosts": "A@example.com;b@example.com" }
+        }))
+        .unwrap();
+        assert!(meeting.matches(&request));
+
+        meeting.duration = Some(30);
+        assert!(!meeting.matches(&request));
+    }
+}
@@ -75,9 +75,6 @@ Third in the list"#;
             .await
             .unwrap();
 
-        // Wait for indexing to complete
-        std::thread::sleep(std::time::Duration::from_secs(1));
-
         let mut search = SearchQuery::default();
         search.filter = Some(vec!["rfd_number = 123".to_string()]);
 
@@ -99,5 +96,27 @@ Third in the list"#;
         assert!(section_names.contains(&"The Second Option".to_string()));
         assert!(section_names.contains(&"Further Nested Details".to_string()));
         assert!(section_names.contains(&"The Third Option".to_string()));
+
+        // Re-indexing without the nested sections removes them from the index
+        let content = content.split("=== The First Option").next().unwrap();
+        RFDSearchIndex::index_rfd(&db.client, db.index_name.to_string(), &123.into(), content)
+            .await
+            .unwrap();
+
+        let mut search = SearchQuery::default();
+        search.filter = Some(vec!["rfd_number = 123".to_string()]);
+
+        let results = index.search::<IndexDocument>(search).await.unwrap();
+        assert_eq!(results.hits.len(), 2);
+
+        RFDSearchIndex::delete_rfd(&db.client, db.index_name.to_string(), &123.into())
+            .await
+            .unwrap();
+
+        let mut search = SearchQuery::default();
+        search.filter = Some(vec!["rfd_number = 123".to_string()]);
+
+        let results = index.search::<IndexDocument>(search).await.unwrap();
+        assert!(results.hits.is_empty());
     }
 }
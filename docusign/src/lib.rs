@@ -21,14 +21,35 @@
  * ```
  */
 #![allow(clippy::field_reassign_with_default)]
-use std::{env, error, fmt, sync::Arc};
+use std::{
+    collections::HashMap,
+    env, error, fmt,
+    sync::{Arc, Mutex},
+};
 
 use bytes::Bytes;
-use chrono::{offset::Utc, DateTime};
+use chrono::{offset::Utc, DateTime, Duration};
+use lazy_static::lazy_static;
 use reqwest::{header, Client, Method, Request, StatusCode, Url};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+/// Access tokens are refreshed this long before DocuSign would expire them, so a
+/// token never expires in the middle of a long running job.
+pub const TOKEN_REFRESH_MARGIN_SECONDS: i64 = 300;
+
+/// An access token we have already been issued for a DocuSign account.
+#[derive(Debug, Clone)]
+struct CachedToken {
+    access_token: String,
+    expires_at: DateTime<Utc>,
+}
+
+lazy_static! {
+    /// Process wide access tokens, keyed by DocuSign account id.
+    static ref TOKEN_CACHE: Mutex<HashMap<String, CachedToken>> = Mutex::new(HashMap::new());
+}
+
 /// Entrypoint for interacting with the DocuSign API.
 #[derive(Debug, Clone)]
 pub struct DocuSign {
@@ -194,52 +215,124 @@ impl DocuSign {
         Ok(resp.json().await.unwrap())
     }
 
-    pub async fn refresh_access_token(&mut self) -> Result<AccessToken, APIError> {
+    /// Use the access token cached for this account, if we have one that is not
+    /// about to expire. Returns if a cached token was used.
+    pub fn use_cached_token(&mut self) -> bool {
+        let cache = TOKEN_CACHE.lock().unwrap();
+        match cache.get(&self.account_id) {
+            Some(cached) if cached.expires_at > Utc::now() + Duration::seconds(TOKEN_REFRESH_MARGIN_SECONDS) => {
+                self.token = cached.access_token.to_string();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Remember a newly issued access token for this account and start using it.
+    fn cache_token(&mut self, t: &AccessToken) {
+        self.token = t.access_token.to_string();
+        if !t.refresh_token.is_empty() {
+            self.refresh_token = t.refresh_token.to_string();
+        }
+
+        if self.account_id.is_empty() || t.expires_in <= 0 {
+            return;
+        }
+
+        TOKEN_CACHE.lock().unwrap().insert(
+            self.account_id.to_string(),
+            CachedToken {
+                access_token: t.access_token.to_string(),
+                expires_at: Utc::now() + Duration::seconds(t.expires_in),
+            },
+        );
+    }
+
+    /// Send a request to the OAuth token endpoint, mapping OAuth errors to an AuthError.
+    async fn request_token(&mut self, params: &[(&str, &str)], basic_auth: bool) -> Result<AccessToken, AuthError> {
         let mut headers = header::HeaderMap::new();
         headers.append(header::ACCEPT, header::HeaderValue::from_static("application/json"));
 
-        let params = [("grant_type", "refresh_token"), ("refresh_token", &self.refresh_token)];
-        let client = reqwest::Client::new();
-        let resp = client
+        let mut rb = self
+            .client
             .post("https://account.docusign.com/oauth/token")
             .headers(headers)
-            .form(&params)
-            .basic_auth(&self.client_id, Some(&self.client_secret))
-            .send()
-            .await
-            .unwrap();
+            .form(params);
+        if basic_auth {
+            rb = rb.basic_auth(&self.client_id, Some(&self.client_secret));
+        }
+        let resp = rb.send().await.unwrap();
+
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                let body = resp.text().await.unwrap();
+                let error: OAuthError = serde_json::from_str(&body).unwrap_or_default();
+
+                return Err(match error.error.as_str() {
+                    "consent_required" => AuthError::ConsentRequired {
+                        consent_url: self.user_consent_url(),
+                    },
+                    "invalid_grant" => AuthError::InvalidGrant(error.error_description),
+                    _ => AuthError::API(APIError { status_code: s, body }),
+                });
+            }
+        };
 
         // Unwrap the response.
         let t: AccessToken = resp.json().await.unwrap();
 
-        self.token = t.access_token.to_string();
-        self.refresh_token = t.refresh_token.to_string();
+        self.cache_token(&t);
 
         Ok(t)
     }
 
-    pub async fn get_access_token(&mut self, code: &str) -> Result<AccessToken, APIError> {
-        let mut headers = header::HeaderMap::new();
-        headers.append(header::ACCEPT, header::HeaderValue::from_static("application/json"));
+    pub async fn refresh_access_token(&mut self) -> Result<AccessToken, AuthError> {
+        let refresh_token = self.refresh_token.to_string();
+        let params = [
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token.as_str()),
+        ];
 
+        self.request_token(&params, true).await
+    }
+
+    pub async fn get_access_token(&mut self, code: &str) -> Result<AccessToken, AuthError> {
         let params = [("grant_type", "authorization_code"), ("code", code)];
-        let client = reqwest::Client::new();
-        let resp = client
-            .post("https://account.docusign.com/oauth/token")
-            .headers(headers)
-            .form(&params)
-            .basic_auth(&self.client_id, Some(&self.client_secret))
-            .send()
-            .await
-            .unwrap();
 
-        // Unwrap the response.
-        let t: AccessToken = resp.json().await.unwrap();
+        self.request_token(&params, true).await
+    }
 
-        self.token = t.access_token.to_string();
-        self.refresh_token = t.refresh_token.to_string();
+    /// Get an access token for the given user with the JWT grant.
+    /// The user must have already consented to impersonation, otherwise this returns
+    /// `AuthError::ConsentRequired` with the url the user needs to visit.
+    /// https://developers.docusign.com/platform/auth/jwt/jwt-get-token/
+    pub async fn request_jwt_user_token(&mut self, user_id: &str, private_key: &str) -> Result<AccessToken, AuthError> {
+        let now = Utc::now();
+        let claims = JwtClaims {
+            iss: self.client_id.to_string(),
+            sub: user_id.to_string(),
+            aud: "account.docusign.com".to_string(),
+            iat: now.timestamp(),
+            exp: (now + Duration::hours(1)).timestamp(),
+            scope: "signature impersonation".to_string(),
+        };
 
-        Ok(t)
+        let key = jsonwebtoken::EncodingKey::from_rsa_pem(private_key.as_bytes())
+            .map_err(|e| AuthError::InvalidGrant(format!("invalid private key: {}", e)))?;
+        let assertion = jsonwebtoken::encode(
+            &jsonwebtoken::Header::new(jsonwebtoken::Algorithm::RS256),
+            &claims,
+            &key,
+        )
+        .map_err(|e| AuthError::InvalidGrant(format!("signing jwt failed: {}", e)))?;
+
+        let params = [
+            ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+            ("assertion", assertion.as_str()),
+        ];
+
+        self.request_token(&params, false).await
     }
 
     /// List templates.
@@ -374,6 +467,70 @@ impl DocuSign {
         Ok(resp.json().await.unwrap())
     }
 
+    /// Create a webhook with "Connect" that publishes events in the JSON SIM
+    /// format, signed with the account's HMAC keys.
+    /// https://developers.docusign.com/platform/webhooks/connect/json-sim-event-model/
+    pub async fn create_connect_webhook(&self, url: &str) -> Result<Webhook, APIError> {
+        let mut connect: Webhook = Default::default();
+        connect.url_to_publish_to = url.to_string();
+        connect.allow_envelope_publish = "true".to_string();
+        connect.delivery_mode = "SIM".to_string();
+        connect.events = vec![
+            "envelope-sent".to_string(),
+            "envelope-delivered".to_string(),
+            "envelope-completed".to_string(),
+            "envelope-declined".to_string(),
+            "envelope-voided".to_string(),
+            "recipient-completed".to_string(),
+        ];
+        connect.all_users = "true".to_string();
+        connect.name = "CIO Connect Webhook".to_string();
+        // This is the only valid choice.
+        connect.configuration_type = "custom".to_string();
+        connect.include_hmac = "true".to_string();
+        connect.include_time_zone_information = "true".to_string();
+        connect.use_soap_interface = "false".to_string();
+        connect.event_data = WebhookEventData {
+            format: "json".to_string(),
+            include_data: vec![
+                "documents".to_string(),
+                "custom_fields".to_string(),
+                "recipients".to_string(),
+            ],
+            version: "restv2.1".to_string(),
+        };
+
+        // Get all the webhooks to check if we already have one.
+        let webhooks = self.list_webhooks().await?;
+        for webhook in webhooks {
+            if webhook.name == connect.name {
+                return Ok(webhook);
+            }
+        }
+
+        // Build the request.
+        let request = self.request(
+            Method::POST,
+            &format!("accounts/{}/connect", self.account_id),
+            connect,
+            None,
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            StatusCode::CREATED => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(resp.json().await.unwrap())
+    }
+
     /// Create an envelope.
     pub async fn create_envelope(&self, envelope: Envelope) -> Result<Envelope, APIError> {
         // Build the request.
@@ -399,6 +556,120 @@ impl DocuSign {
         Ok(resp.json().await.unwrap())
     }
 
+    /// Void an envelope that has been sent but not yet completed. The reason is
+    /// shown to the recipients.
+    pub async fn void_envelope(&self, envelope_id: &str, reason: &str) -> Result<(), APIError> {
+        let body = EnvelopeUpdate {
+            status: "voided".to_string(),
+            voided_reason: reason.to_string(),
+        };
+
+        // Build the request.
+        let request = self.request(
+            Method::PUT,
+            &format!("accounts/{}/envelopes/{}", self.account_id, envelope_id),
+            body,
+            None,
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Resend an envelope to any recipients that have not yet completed it.
+    pub async fn resend_envelope(&self, envelope_id: &str) -> Result<(), APIError> {
+        // Build the request.
+        let request = self.request(
+            Method::PUT,
+            &format!("accounts/{}/envelopes/{}", self.account_id, envelope_id),
+            EnvelopeUpdate::default(),
+            Some(&[("resend_envelope", "true")]),
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Update the recipients of an envelope that is in flight, optionally resending
+    /// the envelope to them.
+    pub async fn update_envelope_recipients(
+        &self,
+        envelope_id: &str,
+        recipients: Recipients,
+        resend: bool,
+    ) -> Result<(), APIError> {
+        // Build the request.
+        let request = self.request(
+            Method::PUT,
+            &format!("accounts/{}/envelopes/{}/recipients", self.account_id, envelope_id),
+            recipients,
+            Some(&[("resend_envelope", if resend { "true" } else { "false" })]),
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Create a URL for the DocuSign correction view of an envelope that is in flight.
+    /// The sender is returned to `return_url` once they are done making corrections.
+    pub async fn create_correct_view(&self, envelope_id: &str, return_url: &str) -> Result<EnvelopeView, APIError> {
+        let body = EnvelopeViewRequest {
+            return_url: return_url.to_string(),
+        };
+
+        // Build the request.
+        let request = self.request(
+            Method::POST,
+            &format!("accounts/{}/envelopes/{}/views/correct", self.account_id, envelope_id),
+            body,
+            None,
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            StatusCode::CREATED => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(resp.json().await.unwrap())
+    }
+
     /// Get envelope form fields.
     pub async fn get_envelope_form_data(&self, envelope_id: &str) -> Result<Vec<FormDatum>, APIError> {
         // Build the request.
@@ -482,6 +753,16 @@ impl DocuSign {
         Ok(resp.bytes().await.unwrap())
     }
 
+    /// Get all of the documents in an envelope combined into a single PDF.
+    pub async fn get_combined_document(&self, envelope_id: &str) -> Result<Bytes, APIError> {
+        self.get_document(envelope_id, "combined").await
+    }
+
+    /// Get the certificate of completion for an envelope as a PDF.
+    pub async fn get_certificate_of_completion(&self, envelope_id: &str) -> Result<Bytes, APIError> {
+        self.get_document(envelope_id, "certificate").await
+    }
+
     /// Update document fields.
     pub async fn update_document_fields(
         &self,
@@ -513,6 +794,157 @@ impl DocuSign {
 
         Ok(())
     }
+
+    /// Create a bulk send list. Each copy in the list becomes its own envelope
+    /// once the list is sent.
+    pub async fn create_bulk_send_list(&self, list: BulkSendingList) -> Result<BulkSendingList, APIError> {
+        // Build the request.
+        let request = self.request(
+            Method::POST,
+            &format!("accounts/{}/bulk_send_lists", self.account_id),
+            list,
+            None,
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            StatusCode::CREATED => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(resp.json().await.unwrap())
+    }
+
+    /// Delete a bulk send list.
+    pub async fn delete_bulk_send_list(&self, list_id: &str) -> Result<(), APIError> {
+        // Build the request.
+        let request = self.request(
+            Method::DELETE,
+            &format!("accounts/{}/bulk_send_lists/{}", self.account_id, list_id),
+            (),
+            None,
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Send a bulk send list using the given envelope or template as the source
+    /// of every envelope in the batch.
+    pub async fn send_bulk_send_list(
+        &self,
+        list_id: &str,
+        envelope_or_template_id: &str,
+        batch_name: &str,
+    ) -> Result<BulkSendResponse, APIError> {
+        let body = BulkSendRequest {
+            envelope_or_template_id: envelope_or_template_id.to_string(),
+            batch_name: batch_name.to_string(),
+        };
+
+        // Build the request.
+        let request = self.request(
+            Method::POST,
+            &format!("accounts/{}/bulk_send_lists/{}/send", self.account_id, list_id),
+            body,
+            None,
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            StatusCode::CREATED => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(resp.json().await.unwrap())
+    }
+
+    /// Get the status of a bulk send batch.
+    pub async fn get_bulk_send_batch_status(&self, batch_id: &str) -> Result<BulkSendBatchStatus, APIError> {
+        // Build the request.
+        let request = self.request(
+            Method::GET,
+            &format!("accounts/{}/bulk_send_batch/{}", self.account_id, batch_id),
+            (),
+            None,
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(resp.json().await.unwrap())
+    }
+
+    /// List the envelopes that were created by a bulk send batch, including their recipients.
+    pub async fn list_bulk_send_batch_envelopes(&self, batch_id: &str) -> Result<Vec<Envelope>, APIError> {
+        let mut envelopes: Vec<Envelope> = Default::default();
+        let mut start_position = 0;
+
+        loop {
+            let start = start_position.to_string();
+
+            // Build the request.
+            let request = self.request(
+                Method::GET,
+                &format!("accounts/{}/bulk_send_batch/{}/envelopes", self.account_id, batch_id),
+                (),
+                Some(&[("include", "recipients"), ("start_position", start.as_str())]),
+            );
+
+            let resp = self.client.execute(request).await.unwrap();
+            match resp.status() {
+                StatusCode::OK => (),
+                s => {
+                    return Err(APIError {
+                        status_code: s,
+                        body: resp.text().await.unwrap(),
+                    })
+                }
+            };
+
+            let r: EnvelopesResponse = resp.json().await.unwrap();
+            let count = r.envelopes.len();
+            envelopes.extend(r.envelopes);
+
+            // Keep paging until DocuSign stops handing us a next page.
+            if r.next_uri.is_empty() || count == 0 {
+                break;
+            }
+            start_position += count;
+        }
+
+        Ok(envelopes)
+    }
 }
 
 /// Error type returned by our library.
@@ -549,6 +981,60 @@ impl error::Error for APIError {
     }
 }
 
+/// Error type returned when getting an access token.
+#[derive(Debug)]
+pub enum AuthError {
+    /// The user has not consented to impersonation, they need to visit the url
+    /// before we can get a token on their behalf.
+    ConsentRequired {
+        consent_url: String,
+    },
+    /// The grant was rejected, for example an expired refresh token or a bad key.
+    InvalidGrant(String),
+    API(APIError),
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AuthError::ConsentRequired { consent_url } => {
+                write!(f, "AuthError: consent required, visit {}", consent_url)
+            }
+            AuthError::InvalidGrant(description) => write!(f, "AuthError: invalid grant: {}", description),
+            AuthError::API(e) => write!(f, "AuthError: {}", e),
+        }
+    }
+}
+
+impl error::Error for AuthError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            AuthError::API(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// The error body returned by the OAuth token endpoint.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct OAuthError {
+    #[serde(default)]
+    error: String,
+    #[serde(default)]
+    error_description: String,
+}
+
+/// The claims of the JWT sent for the JWT grant.
+#[derive(Debug, Clone, Serialize)]
+struct JwtClaims {
+    iss: String,
+    sub: String,
+    aud: String,
+    iat: i64,
+    exp: i64,
+    scope: String,
+}
+
 #[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
 pub struct Envelope {
     #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "envelopeDocuments")]
@@ -561,6 +1047,8 @@ pub struct Envelope {
     pub declined_date_time: Option<DateTime<Utc>>,
     #[serde(default, skip_serializing_if = "Option::is_none", rename = "deliveredDateTime")]
     pub delivered_date_time: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "voidedDateTime")]
+    pub voided_date_time: Option<DateTime<Utc>>,
     #[serde(default, skip_serializing_if = "String::is_empty", rename = "transactionId")]
     pub transaction_id: String,
     /// Indicates the envelope status. Valid values are:
@@ -574,6 +1062,9 @@ pub struct Envelope {
     /// * `voided`: The envelope is no longer valid and recipients cannot access or sign the envelope.
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub status: String,
+    /// The reason the envelope was voided, if it has been voided.
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "voidedReason")]
+    pub voided_reason: String,
     #[serde(default, skip_serializing_if = "String::is_empty", rename = "documentsUri")]
     pub documents_uri: String,
     #[serde(default, skip_serializing_if = "String::is_empty", rename = "recipientsUri")]
@@ -647,6 +1138,27 @@ pub struct Envelope {
     pub recipients: Recipients,
 }
 
+/// The fields of an envelope that can be changed after it has been sent.
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct EnvelopeUpdate {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub status: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "voidedReason")]
+    pub voided_reason: String,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct EnvelopeViewRequest {
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "returnUrl")]
+    pub return_url: String,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct EnvelopeView {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub url: String,
+}
+
 #[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
 pub struct Document {
     #[serde(default, skip_serializing_if = "String::is_empty", rename = "documentId")]
@@ -720,6 +1232,72 @@ pub struct TemplateRole {
     pub routing_order: String,
     #[serde(default, rename = "emailNotification")]
     pub email_notification: EmailNotification,
+    /// Tabs to prefill for this recipient. The tab labels must match the labels in the template.
+    #[serde(default, skip_serializing_if = "Tabs::is_empty")]
+    pub tabs: Tabs,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct Tabs {
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "textTabs")]
+    pub text_tabs: Vec<TextTab>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "dateTabs")]
+    pub date_tabs: Vec<DateTab>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "checkboxTabs")]
+    pub checkbox_tabs: Vec<CheckboxTab>,
+}
+
+impl Tabs {
+    pub fn is_empty(&self) -> bool {
+        self.text_tabs.is_empty() && self.date_tabs.is_empty() && self.checkbox_tabs.is_empty()
+    }
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct TextTab {
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "tabId")]
+    pub tab_id: String,
+    /// The label used to match the tab to the tab in the template.
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "tabLabel")]
+    pub tab_label: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub value: String,
+    /// When set to `true`, the signer cannot change the value of the tab.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub locked: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub required: String,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct DateTab {
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "tabId")]
+    pub tab_id: String,
+    /// The label used to match the tab to the tab in the template.
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "tabLabel")]
+    pub tab_label: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub value: String,
+    /// When set to `true`, the signer cannot change the value of the tab.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub locked: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub required: String,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct CheckboxTab {
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "tabId")]
+    pub tab_id: String,
+    /// The label used to match the tab to the tab in the template.
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "tabLabel")]
+    pub tab_label: String,
+    /// When set to `true`, the checkbox is checked.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub selected: String,
+    /// When set to `true`, the signer cannot change the value of the tab.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub locked: String,
 }
 
 #[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
@@ -747,8 +1325,11 @@ pub struct TextCustomField {
     #[serde(default, skip_serializing_if = "String::is_empty", rename = "fieldId")]
     pub field_id: String,
     pub name: String,
+    #[serde(default)]
     pub show: String,
+    #[serde(default)]
     pub required: String,
+    #[serde(default)]
     pub value: String,
     #[serde(default, skip_serializing_if = "String::is_empty", rename = "configurationType")]
     pub configuration_type: String,
@@ -1037,6 +1618,12 @@ pub struct Webhook {
     pub salesforce_callback_url: String,
     #[serde(default, rename = "eventData")]
     pub event_data: WebhookEventData,
+    /// Either `SIM` (send individual messages) or `aggregate`.
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "deliveryMode")]
+    pub delivery_mode: String,
+    /// The events to publish when using the `SIM` delivery mode, for example `envelope-completed`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<String>,
 }
 
 #[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
@@ -1049,6 +1636,54 @@ pub struct WebhookEventData {
     pub version: String,
 }
 
+/// An event published by DocuSign Connect in the JSON SIM format.
+/// https://developers.docusign.com/platform/webhooks/connect/json-sim-event-model/
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct ConnectEvent {
+    /// The event that triggered the message, for example `envelope-completed`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub event: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "apiVersion")]
+    pub api_version: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub uri: String,
+    #[serde(default, rename = "retryCount")]
+    pub retry_count: i64,
+    #[serde(default, rename = "configurationId")]
+    pub configuration_id: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "generatedDateTime")]
+    pub generated_date_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub data: ConnectEventData,
+}
+
+impl ConnectEvent {
+    /// Returns the envelope the event refers to. The envelope id is always set, even
+    /// if the configuration did not include the envelope summary in the payload.
+    pub fn envelope(&self) -> Envelope {
+        let mut envelope = self.data.envelope_summary.clone();
+        if envelope.envelope_id.is_empty() {
+            envelope.envelope_id = self.data.envelope_id.to_string();
+        }
+
+        envelope
+    }
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct ConnectEventData {
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "accountId")]
+    pub account_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "userId")]
+    pub user_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "envelopeId")]
+    pub envelope_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "recipientId")]
+    pub recipient_id: String,
+    #[serde(default, rename = "envelopeSummary")]
+    pub envelope_summary: Envelope,
+}
+
 #[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
 pub struct WebhooksResponse {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -1057,6 +1692,119 @@ pub struct WebhooksResponse {
     pub total_records: String,
 }
 
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct EnvelopesResponse {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub envelopes: Vec<Envelope>,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "resultSetSize")]
+    pub result_set_size: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "totalSetSize")]
+    pub total_set_size: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "nextUri")]
+    pub next_uri: String,
+}
+
+/// A list of recipients that can be sent a copy of the same envelope or template in one operation.
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct BulkSendingList {
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "listId")]
+    pub list_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "bulkCopies")]
+    pub bulk_copies: Vec<BulkSendingCopy>,
+}
+
+/// A single copy in a bulk send list. This becomes one envelope when the list is sent.
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct BulkSendingCopy {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recipients: Vec<BulkSendingCopyRecipient>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "customFields")]
+    pub custom_fields: Vec<BulkSendingCopyCustomField>,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "emailSubject")]
+    pub email_subject: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "emailBlurb")]
+    pub email_blurb: String,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct BulkSendingCopyRecipient {
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "recipientId")]
+    pub recipient_id: String,
+    /// The role name in the template that this recipient fills.
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "roleName")]
+    pub role_name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub email: String,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct BulkSendingCopyCustomField {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub value: String,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct BulkSendRequest {
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "envelopeOrTemplateId")]
+    pub envelope_or_template_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "batchName")]
+    pub batch_name: String,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct BulkSendResponse {
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "batchId")]
+    pub batch_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "batchName")]
+    pub batch_name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "batchSize")]
+    pub batch_size: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "envelopeOrTemplateId")]
+    pub envelope_or_template_id: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "queueLimit")]
+    pub queue_limit: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "totalQueued")]
+    pub total_queued: String,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct BulkSendBatchStatus {
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "batchId")]
+    pub batch_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "batchName")]
+    pub batch_name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "batchSize")]
+    pub batch_size: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub sent: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub failed: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub queued: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "submittedDate")]
+    pub submitted_date: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "bulkErrors")]
+    pub bulk_errors: Vec<BulkSendErrorStatus>,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct BulkSendErrorStatus {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub created: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "errorMessage")]
+    pub error_message: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "recipientEmails")]
+    pub recipient_emails: Vec<String>,
+}
+
 #[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
 pub struct AccessToken {
     #[serde(default, skip_serializing_if = "String::is_empty")]
@@ -0,0 +1,409 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use dropshot::{ApiEndpointBodyContentType, Extractor, ExtractorMetadata, HttpError, RequestContext, ServerContext};
+use jsonwebtoken::{
+    decode, decode_header,
+    jwk::{AlgorithmParameters, Jwk, JwkSet},
+    Algorithm, DecodingKey, Validation,
+};
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    bearer::BearerToken,
+    http::{internal_error, unauthorized},
+};
+
+/// How long a fetched key set (or OIDC discovery document) is used before it is fetched again
+const CACHE_TTL: Duration = Duration::from_secs(600);
+
+/// A token signed with a key that is not in the cached key set causes the key set to be
+/// fetched again, but no more often than this. Identity providers rotate their keys by
+/// publishing the new key before using it, so this only matters right after a rotation.
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A trait that is implemented by entities that can provide the parameters for validating a
+/// JWT bearer token.
+#[async_trait]
+pub trait JwtProvider {
+    /// The URL of the JSON Web Key Set that tokens are signed with.
+    async fn jwks_url() -> Result<String>;
+
+    /// The audiences that a token may have been issued for. A token must have been issued for
+    /// at least one of them.
+    async fn audience() -> Result<Vec<String>>;
+
+    /// The issuer that a token must have been issued by. The issuer is not checked when this
+    /// returns `None`.
+    async fn issuer() -> Result<Option<String>> {
+        Ok(None)
+    }
+}
+
+/// A trait that is implemented by entities that identify an OpenID Connect provider. Use
+/// [`Oidc`] to validate tokens issued by the provider.
+#[async_trait]
+pub trait OidcProvider {
+    /// The issuer URL of the provider. The key set of the provider is found via the discovery
+    /// document at `{issuer}/.well-known/openid-configuration`.
+    async fn issuer() -> Result<String>;
+
+    /// The audiences that a token may have been issued for, usually the client id of the caller.
+    async fn audience() -> Result<Vec<String>>;
+}
+
+/// A [`JwtProvider`] for tokens issued by the OpenID Connect provider `T`.
+pub struct Oidc<T> {
+    _provider: PhantomData<T>,
+}
+
+#[async_trait]
+impl<T> JwtProvider for Oidc<T>
+where
+    T: OidcProvider + Send + Sync,
+{
+    async fn jwks_url() -> Result<String> {
+        let issuer = T::issuer().await?;
+        discover(&issuer).await.map(|discovery| discovery.jwks_uri)
+    }
+
+    async fn audience() -> Result<Vec<String>> {
+        T::audience().await
+    }
+
+    async fn issuer() -> Result<Option<String>> {
+        Ok(Some(T::issuer().await?))
+    }
+}
+
+/// The claims of a validated token. Any claims that are not listed here are available in
+/// `extra`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Claims {
+    #[serde(default)]
+    pub sub: String,
+    #[serde(default)]
+    pub iss: Option<String>,
+    pub exp: u64,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// A bearer JWT that has been validated against the parameters provided by `T`.
+pub struct Jwt<T> {
+    claims: Claims,
+    _provider: PhantomData<T>,
+}
+
+impl<T> Jwt<T> {
+    pub fn claims(&self) -> &Claims {
+        &self.claims
+    }
+}
+
+/// A bearer JWT that has been checked against the parameters provided by `T`. Unlike [Jwt],
+/// this audit struct can be queried directly to determine if validation succeeded.
+pub struct JwtAudit<T> {
+    claims: Option<Claims>,
+    _provider: PhantomData<T>,
+}
+
+impl<T> JwtAudit<T> {
+    /// Returns that status of if this request passed verification
+    pub fn verified(&self) -> bool {
+        self.claims.is_some()
+    }
+
+    /// The claims of the token, if it passed verification
+    pub fn claims(&self) -> Option<&Claims> {
+        self.claims.as_ref()
+    }
+}
+
+/// Validates the bearer token of the request as a JWT. This extractor will fail with an
+/// [`INTERNAL_SERVER_ERROR`](http::status::StatusCode::INTERNAL_SERVER_ERROR) if the provider `T`
+/// fails to provide validation parameters, or if its key set can not be fetched. If the token
+/// is missing or invalid, then an [`UNAUTHORIZED`](http::status::StatusCode::UNAUTHORIZED)
+/// [`HttpError`](dropshot::HttpError) is returned.
+#[async_trait]
+impl<T> Extractor for Jwt<T>
+where
+    T: JwtProvider + Send + Sync,
+{
+    async fn from_request<Context: ServerContext>(rqctx: Arc<RequestContext<Context>>) -> Result<Jwt<T>, HttpError> {
+        let audit = JwtAudit::<T>::from_request(rqctx).await?;
+
+        match audit.claims {
+            Some(claims) => Ok(Jwt {
+                claims,
+                _provider: PhantomData,
+            }),
+            None => Err(unauthorized()),
+        }
+    }
+
+    fn metadata(_body_content_type: ApiEndpointBodyContentType) -> ExtractorMetadata {
+        ExtractorMetadata {
+            paginated: false,
+            parameters: vec![],
+        }
+    }
+}
+
+/// Validates the bearer token of the request as a JWT. This extractor should only fail when
+/// validation can not be performed at all.
+#[async_trait]
+impl<T> Extractor for JwtAudit<T>
+where
+    T: JwtProvider + Send + Sync,
+{
+    async fn from_request<Context: ServerContext>(
+        rqctx: Arc<RequestContext<Context>>,
+    ) -> Result<JwtAudit<T>, HttpError> {
+        let jwks_url = T::jwks_url().await.map_err(|err| {
+            log::warn!("Failed to find key set for verifying JWT: {}", err);
+            internal_error()
+        })?;
+        let audience = T::audience().await.map_err(|_| internal_error())?;
+        let issuer = T::issuer().await.map_err(|_| internal_error())?;
+
+        let token = BearerToken::from_request(rqctx.clone())
+            .await
+            .ok()
+            .and_then(|token| token.inner().cloned());
+
+        let claims = match token {
+            Some(token) => {
+                let keys = KeySetCache::get(&jwks_url, &token).await.map_err(|err| {
+                    log::warn!("Failed to fetch key set from {}: {}", jwks_url, err);
+                    internal_error()
+                })?;
+
+                match validate(&token, &keys, &audience, issuer.as_deref()) {
+                    Ok(claims) => Some(claims),
+                    Err(err) => {
+                        log::debug!("Failed to validate JWT. req_id: {} err: {}", rqctx.request_id, err);
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+
+        if let Some(claims) = &claims {
+            log::info!(
+                "Successfully verified request via JWT. req_id: {} uri: {} sub: {}",
+                rqctx.request_id,
+                rqctx.request.lock().await.uri(),
+                claims.sub
+            );
+        } else {
+            log::info!(
+                "Failed to verify request via JWT. req_id: {} uri: {}",
+                rqctx.request_id,
+                rqctx.request.lock().await.uri()
+            );
+        }
+
+        Ok(JwtAudit {
+            claims,
+            _provider: PhantomData,
+        })
+    }
+
+    fn metadata(_body_content_type: ApiEndpointBodyContentType) -> ExtractorMetadata {
+        ExtractorMetadata {
+            paginated: false,
+            parameters: vec![],
+        }
+    }
+}
+
+fn validate(token: &str, keys: &JwkSet, audience: &[String], issuer: Option<&str>) -> Result<Claims> {
+    let header = decode_header(token)?;
+
+    // Without a key id the token can only be checked when there is a single key to check it
+    // against
+    let jwk = match &header.kid {
+        Some(kid) => keys.find(kid),
+        None if keys.keys.len() == 1 => keys.keys.first(),
+        None => None,
+    }
+    .ok_or_else(|| anyhow!("no key found for key id {:?}", header.kid))?;
+
+    // The algorithm is taken from the key whenever possible so that a token can not pick a
+    // weaker algorithm than the one the key is meant for
+    let algorithm = jwk.common.algorithm.unwrap_or(header.alg);
+    if algorithm != header.alg || !algorithm_matches_key(algorithm, jwk) {
+        return Err(anyhow!(
+            "algorithm {:?} can not be used with key {:?}",
+            header.alg,
+            header.kid
+        ));
+    }
+
+    let mut validation = Validation::new(algorithm);
+    validation.set_audience(audience);
+    if let Some(issuer) = issuer {
+        validation.set_issuer(&[issuer]);
+    }
+
+    Ok(decode::<Claims>(token, &DecodingKey::from_jwk(jwk)?, &validation)?.claims)
+}
+
+fn algorithm_matches_key(algorithm: Algorithm, jwk: &Jwk) -> bool {
+    use Algorithm::*;
+
+    match jwk.algorithm {
+        AlgorithmParameters::RSA(_) => matches!(algorithm, RS256 | RS384 | RS512 | PS256 | PS384 | PS512),
+        AlgorithmParameters::EllipticCurve(_) => matches!(algorithm, ES256 | ES384),
+        AlgorithmParameters::OctetKeyPair(_) => matches!(algorithm, EdDSA),
+        AlgorithmParameters::OctetKey(_) => matches!(algorithm, HS256 | HS384 | HS512),
+    }
+}
+
+struct Cached<T> {
+    value: T,
+    fetched_at: Instant,
+}
+
+lazy_static! {
+    static ref KEY_SETS: Mutex<HashMap<String, Cached<JwkSet>>> = Mutex::new(HashMap::new());
+    static ref DISCOVERIES: Mutex<HashMap<String, Cached<Discovery>>> = Mutex::new(HashMap::new());
+}
+
+struct KeySetCache;
+
+impl KeySetCache {
+    /// Returns the key set at `url`, fetching it if it is not cached, if it has expired, or if
+    /// it does not contain the key that `token` was signed with.
+    async fn get(url: &str, token: &str) -> Result<JwkSet> {
+        let kid = decode_header(token).ok().and_then(|header| header.kid);
+
+        if let Some(cached) = KEY_SETS.lock().unwrap().get(url) {
+            let age = cached.fetched_at.elapsed();
+            let has_key = kid.as_ref().map(|kid| cached.value.find(kid).is_some()).unwrap_or(true);
+
+            if age < CACHE_TTL && (has_key || age < MIN_REFRESH_INTERVAL) {
+                return Ok(cached.value.clone());
+            }
+        }
+
+        let keys: JwkSet = reqwest::get(url).await?.error_for_status()?.json().await?;
+        log::info!("Fetched {} keys from {}", keys.keys.len(), url);
+
+        KEY_SETS.lock().unwrap().insert(
+            url.to_string(),
+            Cached {
+                value: keys.clone(),
+                fetched_at: Instant::now(),
+            },
+        );
+
+        Ok(keys)
+    }
+}
+
+/// The parts of an OpenID Connect discovery document that are needed to validate tokens
+#[derive(Debug, Clone, Deserialize)]
+struct Discovery {
+    issuer: String,
+    jwks_uri: String,
+}
+
+async fn discover(issuer: &str) -> Result<Discovery> {
+    if let Some(cached) = DISCOVERIES.lock().unwrap().get(issuer) {
+        if cached.fetched_at.elapsed() < CACHE_TTL {
+            return Ok(cached.value.clone());
+        }
+    }
+
+    let url = format!("{}/.well-known/openid-configuration", issuer.trim_end_matches('/'));
+    let discovery: Discovery = reqwest::get(&url).await?.error_for_status()?.json().await?;
+
+    // The issuer in the discovery document must match the issuer that was used to find it
+    if discovery.issuer != issuer {
+        return Err(anyhow!(
+            "discovery document at {} is for issuer {}",
+            url,
+            discovery.issuer
+        ));
+    }
+
+    DISCOVERIES.lock().unwrap().insert(
+        issuer.to_string(),
+        Cached {
+            value: discovery.clone(),
+            fetched_at: Instant::now(),
+        },
+    );
+
+    Ok(discovery)
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonwebtoken::{encode, jwk::JwkSet, EncodingKey, Header};
+    use serde_json::json;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use super::validate;
+
+    // The `k` parameter of the test key is the base64 encoding of this secret
+    const SECRET: &[u8] = b"test-secret-that-is-long-enough-a";
+
+    fn keys() -> JwkSet {
+        serde_json::from_value(json!({
+            "keys": [{
+                "kty": "oct",
+                "kid": "test",
+                "alg": "HS256",
+                "k": "dGVzdC1zZWNyZXQtdGhhdC1pcy1sb25nLWVub3VnaC1h",
+            }]
+        }))
+        .unwrap()
+    }
+
+    fn token(kid: &str, claims: serde_json::Value) -> String {
+        let mut header = Header::default();
+        header.kid = Some(kid.to_string());
+        encode(&header, &claims, &EncodingKey::from_secret(SECRET)).unwrap()
+    }
+
+    fn exp() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 300
+    }
+
+    #[test]
+    fn test_validate_token() {
+        let audience = vec!["webhooky".to_string()];
+        let token = token(
+            "test",
+            json!({ "sub": "ci", "iss": "https://idp.example.com", "aud": "webhooky", "exp": exp(), "email": "ci@example.com" }),
+        );
+
+        let claims = validate(&token, &keys(), &audience, Some("https://idp.example.com")).unwrap();
+        assert_eq!("ci", claims.sub);
+        assert_eq!(json!("ci@example.com"), claims.extra["email"]);
+
+        assert!(validate(&token, &keys(), &["other".to_string()], None).is_err());
+        assert!(validate(&token, &keys(), &audience, Some("https://other.example.com")).is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_expired_and_unknown_keys() {
+        let audience = vec!["webhooky".to_string()];
+
+        let expired = token("test", json!({ "sub": "ci", "aud": "webhooky", "exp": 1_600_000_000 }));
+        assert!(validate(&expired, &keys(), &audience, None).is_err());
+
+        let unknown = token("rotated", json!({ "sub": "ci", "aud": "webhooky", "exp": exp() }));
+        assert!(validate(&unknown, &keys(), &audience, None).is_err());
+    }
+}
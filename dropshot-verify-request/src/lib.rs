@@ -4,6 +4,7 @@ use serde::de::DeserializeOwned;
 
 pub mod bearer;
 mod http;
+pub mod jwt;
 pub mod query;
 pub mod sig;
 
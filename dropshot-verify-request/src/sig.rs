@@ -1,16 +1,29 @@
+use anyhow::anyhow;
 use async_trait::async_trait;
 use digest::KeyInit;
 use dropshot::{
     ApiEndpointBodyContentType, Extractor, ExtractorMetadata, HttpError, RequestContext, ServerContext, UntypedBody,
 };
-use hmac::Mac;
-use std::{borrow::Cow, marker::PhantomData, sync::Arc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::{
+    borrow::Cow,
+    marker::PhantomData,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use crate::{
-    http::{internal_error, unauthorized},
+    http::{internal_error, unauthorized, Headers},
     FromBytes,
 };
 
+/// The header that carries the hex encoded signature of a request signed with a shared secret
+pub const SIGNATURE_HEADER: &str = "X-Signature";
+
+/// The header that carries the unix timestamp (in seconds) at which a request was signed
+pub const SIGNATURE_TIMESTAMP_HEADER: &str = "X-Signature-Timestamp";
+
 /// A request body that has been verified by an HMAC verifier `T`.
 #[derive(Debug)]
 pub struct HmacVerifiedBody<T, BodyType> {
@@ -178,3 +191,149 @@ where
         BodyType::metadata(body_content_type)
     }
 }
+
+/// A trait that is implemented by entities that can provide a shared secret for verifying
+/// requests signed via [`sign_request`].
+#[async_trait]
+pub trait SharedSecretProvider {
+    async fn secret() -> anyhow::Result<Vec<u8>>;
+
+    /// How far the signing timestamp of a request may be from the current time. Requests
+    /// outside of this window are rejected so that a captured request can not be replayed
+    /// later on.
+    fn tolerance() -> Duration {
+        Duration::from_secs(300)
+    }
+}
+
+/// An [`HmacSignatureVerifier`] for requests that have been signed with a secret shared
+/// between the caller and the server. The signature covers the signing timestamp, the
+/// request method, the request path and the body, and is sent in the [`SIGNATURE_HEADER`]
+/// and [`SIGNATURE_TIMESTAMP_HEADER`] headers.
+#[derive(Debug)]
+pub struct SharedSecretVerifier<T> {
+    _provider: PhantomData<T>,
+}
+
+#[async_trait]
+impl<T> HmacSignatureVerifier for SharedSecretVerifier<T>
+where
+    T: SharedSecretProvider + Send + Sync,
+{
+    type Algo = Hmac<Sha256>;
+
+    async fn key<Context: ServerContext>(_: Arc<RequestContext<Context>>) -> anyhow::Result<Vec<u8>> {
+        T::secret().await
+    }
+
+    async fn signature<Context: ServerContext>(rqctx: Arc<RequestContext<Context>>) -> anyhow::Result<Vec<u8>> {
+        let headers = Headers::from_request(rqctx.clone()).await?;
+
+        // A request that was signed too long ago (or too far in the future) is treated the same
+        // as a request with an invalid signature
+        let timestamp = header_str(&headers, SIGNATURE_TIMESTAMP_HEADER)?.parse::<u64>()?;
+        check_timestamp(timestamp, SystemTime::now(), T::tolerance())?;
+
+        Ok(hex::decode(header_str(&headers, SIGNATURE_HEADER)?)?)
+    }
+
+    async fn content<'a, 'b, Context: ServerContext>(
+        rqctx: &'a Arc<RequestContext<Context>>,
+        body: &'b UntypedBody,
+    ) -> anyhow::Result<Cow<'b, [u8]>> {
+        let headers = Headers::from_request(rqctx.clone()).await?;
+
+        // A request without a timestamp can not have a valid signature, which `signature` fails
+        // on, so it is rejected as unauthorized rather than as a failure to verify it
+        let timestamp = match header_str(&headers, SIGNATURE_TIMESTAMP_HEADER) {
+            Ok(timestamp) => timestamp,
+            Err(_) => return Ok(Cow::Borrowed(&[])),
+        };
+
+        let request = rqctx.request.lock().await;
+        let path = request
+            .uri()
+            .path_and_query()
+            .map(|path| path.as_str())
+            .unwrap_or_else(|| request.uri().path());
+
+        Ok(Cow::Owned(signed_content(
+            timestamp,
+            request.method().as_str(),
+            path,
+            body.as_bytes(),
+        )))
+    }
+}
+
+/// Computes the value of the [`SIGNATURE_HEADER`] header for a request that is sent with
+/// `timestamp` as the value of the [`SIGNATURE_TIMESTAMP_HEADER`] header. `path` must include
+/// the query string of the request, if there is one.
+pub fn sign_request(secret: &[u8], timestamp: u64, method: &str, path: &str, body: &[u8]) -> anyhow::Result<String> {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(secret)?;
+    mac.update(&signed_content(&timestamp.to_string(), method, path, body));
+
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
+
+fn signed_content(timestamp: &str, method: &str, path: &str, body: &[u8]) -> Vec<u8> {
+    let mut content = format!("v1:{}:{}:{}:", timestamp, method.to_uppercase(), path).into_bytes();
+    content.extend_from_slice(body);
+    content
+}
+
+fn header_str<'a>(headers: &'a Headers, name: &str) -> anyhow::Result<&'a str> {
+    Ok(headers
+        .0
+        .get(name)
+        .ok_or_else(|| anyhow!("request is missing the {} header", name))?
+        .to_str()?)
+}
+
+fn check_timestamp(timestamp: u64, now: SystemTime, tolerance: Duration) -> anyhow::Result<()> {
+    let now = now.duration_since(UNIX_EPOCH)?.as_secs();
+
+    if now.abs_diff(timestamp) > tolerance.as_secs() {
+        return Err(anyhow!(
+            "request was signed at {} which is outside of the allowed tolerance of {:?}",
+            timestamp,
+            tolerance
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::{check_timestamp, sign_request};
+
+    #[test]
+    fn test_check_timestamp() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_676_000_000);
+        let tolerance = Duration::from_secs(300);
+
+        assert!(check_timestamp(1_676_000_000, now, tolerance).is_ok());
+        assert!(check_timestamp(1_676_000_300, now, tolerance).is_ok());
+        assert!(check_timestamp(1_675_999_700, now, tolerance).is_ok());
+        assert!(check_timestamp(1_675_999_699, now, tolerance).is_err());
+        assert!(check_timestamp(1_676_000_301, now, tolerance).is_err());
+    }
+
+    #[test]
+    fn test_signature_covers_the_request() {
+        let sign = |timestamp, method, path, body: &str| {
+            sign_request(b"secret", timestamp, method, path, body.as_bytes()).unwrap()
+        };
+        let signature = sign(1_676_000_000, "POST", "/run/sync-rfds", "{}");
+
+        assert_eq!(64, signature.len());
+        assert_eq!(signature, sign(1_676_000_000, "post", "/run/sync-rfds", "{}"));
+        assert_ne!(signature, sign(1_676_000_001, "POST", "/run/sync-rfds", "{}"));
+        assert_ne!(signature, sign(1_676_000_000, "PUT", "/run/sync-rfds", "{}"));
+        assert_ne!(signature, sign(1_676_000_000, "POST", "/run/sync-repos", "{}"));
+        assert_ne!(signature, sign(1_676_000_000, "POST", "/run/sync-rfds", "[]"));
+    }
+}
@@ -20,26 +20,64 @@
  *     println!("{:?}", g);
  * }
  * ```
+ *
+ * Requests can be spaced out to stay under the API's queries per second quota, and
+ * identical addresses in a batch are only looked up once:
+ *
+ * ```ignore
+ * let geocode = Geocode::new_from_env().with_qps(10);
+ * let replies = geocode.get_many(&addresses).await;
+ * ```
  */
 #![allow(clippy::field_reassign_with_default)]
 use std::{
+    collections::HashMap,
     env, error,
     fmt::{self, Debug, Display, Formatter},
     hash::Hash,
     sync::Arc,
+    time::Duration,
 };
 
 use reqwest::{header, Client, Method, Request, StatusCode, Url};
 use serde::{Deserialize, Serialize};
+use tokio::{sync::Mutex, time::Instant};
 
 /// Endpoint for the Geocode API.
 const ENDPOINT: &str = "https://maps.google.com/maps/api/geocode/json";
 
+/// Endpoint for the Time Zone API.
+const TIMEZONE_ENDPOINT: &str = "https://maps.googleapis.com/maps/api/timezone/json";
+
 /// Entrypoint for interacting with the Geocode API.
 pub struct Geocode {
     key: String,
 
     client: Arc<Client>,
+
+    limiter: Option<RateLimiter>,
+}
+
+/// Spaces requests out evenly so that no more than a fixed number are sent per second.
+struct RateLimiter {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    fn new(qps: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(1) / qps.max(1),
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    async fn wait(&self) {
+        // Holding the lock while sleeping queues up concurrent callers behind each other.
+        let mut next = self.next.lock().await;
+        tokio::time::sleep_until(*next).await;
+        *next = Instant::now() + self.interval;
+    }
 }
 
 impl Geocode {
@@ -56,6 +94,8 @@ impl Geocode {
                 key: key.to_string(),
 
                 client: Arc::new(c),
+
+                limiter: None,
             },
             Err(e) => panic!("creating client failed: {e:?}"),
         }
@@ -71,11 +111,24 @@ impl Geocode {
         Geocode::new(key)
     }
 
-    fn request<B>(&self, method: Method, path: &str, body: B, query: Option<Vec<(&str, String)>>) -> Request
+    /// Limit the client to sending at most `qps` requests per second.
+    pub fn with_qps(mut self, qps: u32) -> Self {
+        self.limiter = Some(RateLimiter::new(qps));
+        self
+    }
+
+    fn request<B>(
+        &self,
+        endpoint: &str,
+        method: Method,
+        path: &str,
+        body: B,
+        query: Option<Vec<(&str, String)>>,
+    ) -> Request
     where
         B: Serialize,
     {
-        let base = Url::parse(ENDPOINT).unwrap();
+        let base = Url::parse(endpoint).unwrap();
         let url = base.join(path).unwrap();
 
         // Set the default headers.
@@ -109,12 +162,69 @@ impl Geocode {
 
     /// Get information for an address.
     pub async fn get(&self, address: &str) -> Result<Reply, APIError> {
+        self.lookup(("address", address.to_string())).await
+    }
+
+    /// Get information for the address closest to a latitude and longitude.
+    pub async fn reverse(&self, lat: f64, lng: f64) -> Result<Reply, APIError> {
+        self.lookup(("latlng", format!("{lat},{lng}"))).await
+    }
+
+    /// Get the time zone of a latitude and longitude. The timestamp, in seconds since the
+    /// epoch, determines whether daylight savings is reflected in the offsets.
+    pub async fn timezone(&self, lat: f64, lng: f64, timestamp: i64) -> Result<TimeZone, APIError> {
+        if let Some(limiter) = &self.limiter {
+            limiter.wait().await;
+        }
+
         // Build the request.
         let request = self.request(
+            TIMEZONE_ENDPOINT,
             Method::GET,
             "",
             (),
-            Some(vec![("address", address.to_string()), ("key", self.key.to_string())]),
+            Some(vec![
+                ("location", format!("{lat},{lng}")),
+                ("timestamp", timestamp.to_string()),
+                ("key", self.key.to_string()),
+            ]),
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        // Failed lookups are still returned with a 200, the status holds the reason.
+        let r: TimeZone = resp.json().await.unwrap();
+        if r.status != "OK" {
+            return Err(APIError {
+                status_code: StatusCode::NOT_FOUND,
+                body: format!("{}: {}", r.status, r.error_message),
+            });
+        }
+
+        Ok(r)
+    }
+
+    async fn lookup(&self, query: (&str, String)) -> Result<Reply, APIError> {
+        if let Some(limiter) = &self.limiter {
+            limiter.wait().await;
+        }
+
+        // Build the request.
+        let request = self.request(
+            ENDPOINT,
+            Method::GET,
+            "",
+            (),
+            Some(vec![query, ("key", self.key.to_string())]),
         );
 
         let resp = self.client.execute(request).await.unwrap();
@@ -137,6 +247,27 @@ impl Geocode {
         }
         Ok(r.results.get(0).unwrap().clone())
     }
+
+    /// Get information for a batch of addresses, keyed by address. Each distinct address is
+    /// only looked up once no matter how many times it appears in the batch.
+    pub async fn get_many<S>(&self, addresses: &[S]) -> HashMap<String, Result<Reply, APIError>>
+    where
+        S: AsRef<str>,
+    {
+        let mut replies = HashMap::new();
+
+        for address in addresses {
+            let address = address.as_ref();
+            if address.is_empty() || replies.contains_key(address) {
+                continue;
+            }
+
+            let reply = self.get(address).await;
+            replies.insert(address.to_string(), reply);
+        }
+
+        replies
+    }
 }
 
 /// Error type returned by our library.
@@ -235,6 +366,28 @@ impl Display for FormattedAddress {
     }
 }
 
+/// A reply from the Google Time Zone API
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeZone {
+    /// The offset for daylight-savings time in seconds.
+    #[serde(default)]
+    pub dst_offset: i64,
+    /// The offset from UTC, in seconds, not including daylight savings.
+    #[serde(default)]
+    pub raw_offset: i64,
+    #[serde(default)]
+    pub status: String,
+    /// The IANA name of the time zone, ie. `America/Los_Angeles`.
+    #[serde(default)]
+    pub time_zone_id: String,
+    /// The long form name of the time zone, ie. `Pacific Daylight Time`.
+    #[serde(default)]
+    pub time_zone_name: String,
+    #[serde(default)]
+    pub error_message: String,
+}
+
 #[derive(Debug, Default, Deserialize)]
 pub struct ReplyResult {
     #[serde(default)]
@@ -1225,3 +1378,26 @@ pub struct Coordinates {
 /// For example, you can use the place_id in a Places SDK request to get details of a local business, such as phone number, opening hours, user reviews, and more. See the place ID overview.
 #[derive(Debug, Clone, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct PlaceId(String);
+
+#[cfg(test)]
+mod tests {
+    use super::TimeZone;
+
+    #[test]
+    fn test_parse_timezone() {
+        let tz: TimeZone = serde_json::from_str(
+            r#"{
+                "dstOffset": 3600,
+                "rawOffset": -28800,
+                "status": "OK",
+                "timeZoneId": "America/Los_Angeles",
+                "timeZoneName": "Pacific Daylight Time"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!("America/Los_Angeles", tz.time_zone_id);
+        assert_eq!(-28800, tz.raw_offset);
+        assert_eq!(3600, tz.dst_offset);
+    }
+}
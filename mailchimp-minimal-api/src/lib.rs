@@ -1,15 +1,30 @@
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
 use http::{
     header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE},
     StatusCode,
 };
+use md5::{Digest, Md5};
 use reqwest::{Client, Method, RequestBuilder, Url};
-use std::{error, fmt, str::FromStr};
+use serde::de::DeserializeOwned;
+use std::{error, fmt, io::Read, str::FromStr};
 use url::ParseError;
 
 mod types;
 
 pub use crate::types::*;
 
+/// The most members MailChimp will add to or remove from a static segment in one request.
+const SEGMENT_MEMBERS_PER_REQUEST: usize = 500;
+
+/// The id MailChimp uses for a member in urls, the MD5 hash of their lowercased email address.
+pub fn subscriber_hash<T>(email: T) -> String
+where
+    T: AsRef<str>,
+{
+    format!("{:x}", Md5::digest(email.as_ref().to_lowercase().as_bytes()))
+}
+
 #[derive(Debug)]
 pub enum AuthMode {
     Basic(BasicAuth),
@@ -154,6 +169,532 @@ impl MailChimp {
 
         Ok(members)
     }
+
+    /// Send a request and parse the JSON response, any status other than `expected` is an error.
+    async fn execute<T>(&self, rb: RequestBuilder, expected: StatusCode) -> Result<T, MailChimpError>
+    where
+        T: DeserializeOwned,
+    {
+        let resp = self.client.execute(rb.build()?).await?;
+
+        if resp.status() == expected {
+            Ok(resp.json().await?)
+        } else {
+            Err(MailChimpError::APIError(MailChimpAPIError {
+                status_code: resp.status(),
+                body: resp.text().await?,
+            }))
+        }
+    }
+
+    /// Send a request for an endpoint that replies with `204 No Content` on success.
+    async fn execute_empty(&self, rb: RequestBuilder) -> Result<(), MailChimpError> {
+        let resp = self.client.execute(rb.build()?).await?;
+
+        match resp.status() {
+            StatusCode::NO_CONTENT => Ok(()),
+            status => Err(MailChimpError::APIError(MailChimpAPIError {
+                status_code: status,
+                body: resp.text().await?,
+            })),
+        }
+    }
+
+    /// Add a member to an audience or update them if they are already in it. New members
+    /// added with a `status_if_new` of "pending" are sent a confirmation email first.
+    ///
+    /// FROM: https://mailchimp.com/developer/marketing/api/list-members/add-or-update-list-member/
+    pub async fn upsert_member<T>(&self, list_id: T, member: &MemberUpsert) -> Result<Member, MailChimpError>
+    where
+        T: AsRef<str>,
+    {
+        let rb = self
+            .request(
+                Method::PUT,
+                format!(
+                    "3.0/lists/{}/members/{}",
+                    list_id.as_ref(),
+                    subscriber_hash(&member.email_address)
+                ),
+            )?
+            .json(member);
+
+        self.execute(rb, StatusCode::OK).await
+    }
+
+    /// Archive a member so that they no longer receive anything. Their history is kept and
+    /// they can be added back later.
+    ///
+    /// FROM: https://mailchimp.com/developer/marketing/api/list-members/archive-list-member/
+    pub async fn archive_member<T, E>(&self, list_id: T, email: E) -> Result<(), MailChimpError>
+    where
+        T: AsRef<str>,
+        E: AsRef<str>,
+    {
+        let rb = self.request(
+            Method::DELETE,
+            format!("3.0/lists/{}/members/{}", list_id.as_ref(), subscriber_hash(email)),
+        )?;
+
+        self.execute_empty(rb).await
+    }
+
+    /// List the saved segments and tags of an audience.
+    ///
+    /// FROM: https://mailchimp.com/developer/marketing/api/list-segments/list-segments/
+    pub async fn list_segments<T>(&self, list_id: T) -> Result<Vec<Segment>, MailChimpError>
+    where
+        T: AsRef<str>,
+    {
+        let per_page = 1000;
+        let mut offset: usize = 0;
+        let mut segments: Vec<Segment> = Default::default();
+
+        loop {
+            let rb = self.request(
+                Method::GET,
+                format!(
+                    "3.0/lists/{}/segments?count={}&offset={}",
+                    list_id.as_ref(),
+                    per_page,
+                    offset
+                ),
+            )?;
+
+            let mut page: ListSegmentsResponse = self.execute(rb, StatusCode::OK).await?;
+
+            if page.segments.is_empty() {
+                break;
+            }
+
+            offset += page.segments.len();
+            segments.append(&mut page.segments);
+        }
+
+        Ok(segments)
+    }
+
+    /// Create a segment. A segment with `static_segment` set is a tag, one with `options`
+    /// is a saved segment that MailChimp keeps up to date from its conditions.
+    ///
+    /// FROM: https://mailchimp.com/developer/marketing/api/list-segments/add-segment/
+    pub async fn create_segment<T>(&self, list_id: T, segment: &NewSegment) -> Result<Segment, MailChimpError>
+    where
+        T: AsRef<str>,
+    {
+        let rb = self
+            .request(Method::POST, format!("3.0/lists/{}/segments", list_id.as_ref()))?
+            .json(segment);
+
+        self.execute(rb, StatusCode::OK).await
+    }
+
+    pub async fn update_segment<T>(
+        &self,
+        list_id: T,
+        segment_id: i64,
+        segment: &NewSegment,
+    ) -> Result<Segment, MailChimpError>
+    where
+        T: AsRef<str>,
+    {
+        let rb = self
+            .request(
+                Method::PATCH,
+                format!("3.0/lists/{}/segments/{}", list_id.as_ref(), segment_id),
+            )?
+            .json(segment);
+
+        self.execute(rb, StatusCode::OK).await
+    }
+
+    pub async fn delete_segment<T>(&self, list_id: T, segment_id: i64) -> Result<(), MailChimpError>
+    where
+        T: AsRef<str>,
+    {
+        let rb = self.request(
+            Method::DELETE,
+            format!("3.0/lists/{}/segments/{}", list_id.as_ref(), segment_id),
+        )?;
+
+        self.execute_empty(rb).await
+    }
+
+    /// Add and remove members of a static segment or tag in bulk. The emails are sent in
+    /// chunks to stay under MailChimp's per-request limit and the results are combined.
+    ///
+    /// FROM: https://mailchimp.com/developer/marketing/api/list-segments/batch-add-or-remove-members/
+    pub async fn update_segment_members<T>(
+        &self,
+        list_id: T,
+        segment_id: i64,
+        members_to_add: &[String],
+        members_to_remove: &[String],
+    ) -> Result<SegmentMembersResponse, MailChimpError>
+    where
+        T: AsRef<str>,
+    {
+        let mut result = SegmentMembersResponse::default();
+
+        let additions = members_to_add.chunks(SEGMENT_MEMBERS_PER_REQUEST).map(|c| (c, &[][..]));
+        let removals = members_to_remove
+            .chunks(SEGMENT_MEMBERS_PER_REQUEST)
+            .map(|c| (&[][..], c));
+
+        for (add, remove) in additions.chain(removals) {
+            let rb = self
+                .request(
+                    Method::POST,
+                    format!("3.0/lists/{}/segments/{}", list_id.as_ref(), segment_id),
+                )?
+                .json(&SegmentMembers {
+                    members_to_add: add.to_vec(),
+                    members_to_remove: remove.to_vec(),
+                });
+
+            let mut r: SegmentMembersResponse = self.execute(rb, StatusCode::OK).await?;
+
+            result.members_added.append(&mut r.members_added);
+            result.members_removed.append(&mut r.members_removed);
+            result.errors.append(&mut r.errors);
+            result.total_added += r.total_added;
+            result.total_removed += r.total_removed;
+            result.error_count += r.error_count;
+        }
+
+        Ok(result)
+    }
+
+    /// Add or remove tags on a single member. Tags that do not exist yet are created.
+    ///
+    /// FROM: https://mailchimp.com/developer/marketing/api/list-member-tags/add-or-remove-member-tags/
+    pub async fn update_member_tags<T, E>(
+        &self,
+        list_id: T,
+        email: E,
+        tags: Vec<MemberTag>,
+    ) -> Result<(), MailChimpError>
+    where
+        T: AsRef<str>,
+        E: AsRef<str>,
+    {
+        let rb = self
+            .request(
+                Method::POST,
+                format!("3.0/lists/{}/members/{}/tags", list_id.as_ref(), subscriber_hash(email)),
+            )?
+            .json(&MemberTags { tags });
+
+        self.execute_empty(rb).await
+    }
+
+    /// Queue a set of operations to be run in the background by MailChimp. Batches are not
+    /// subject to the per-request rate limits, so use them for bulk changes.
+    ///
+    /// FROM: https://mailchimp.com/developer/marketing/api/batch-operations/start-batch-operation/
+    pub async fn create_batch(&self, operations: Vec<BatchOperation>) -> Result<Batch, MailChimpError> {
+        let rb = self
+            .request(Method::POST, "3.0/batches")?
+            .json(&NewBatch { operations });
+
+        self.execute(rb, StatusCode::OK).await
+    }
+
+    /// Get the status of a batch, poll this until `Batch::is_finished` returns true.
+    pub async fn get_batch<T>(&self, batch_id: T) -> Result<Batch, MailChimpError>
+    where
+        T: AsRef<str>,
+    {
+        let rb = self.request(Method::GET, format!("3.0/batches/{}", batch_id.as_ref()))?;
+
+        self.execute(rb, StatusCode::OK).await
+    }
+
+    /// Download the responses of every operation of a finished batch.
+    ///
+    /// FROM: https://mailchimp.com/developer/marketing/guides/run-async-requests-batch-endpoint/#check-the-status-of-a-batch-operation
+    pub async fn get_batch_results(&self, batch: &Batch) -> Result<Vec<BatchOperationResult>, MailChimpError> {
+        if batch.response_body_url.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // The results url is pre-signed, so it must be fetched without our credentials.
+        let resp = self.client.get(&batch.response_body_url).send().await?;
+        if resp.status() != StatusCode::OK {
+            return Err(MailChimpError::APIError(MailChimpAPIError {
+                status_code: resp.status(),
+                body: resp.text().await?,
+            }));
+        }
+        let bytes = resp.bytes().await?;
+
+        // The results are a gzipped tarball of JSON files, each holding an array of results.
+        let mut archive = tar::Archive::new(GzDecoder::new(&bytes[..]));
+        let mut results: Vec<BatchOperationResult> = Default::default();
+
+        for entry in archive.entries().map_err(MailChimpError::InvalidBatchResults)? {
+            let mut entry = entry.map_err(MailChimpError::InvalidBatchResults)?;
+
+            let is_json = entry
+                .path()
+                .map(|path| path.extension().map(|ext| ext == "json").unwrap_or(false))
+                .unwrap_or(false);
+            if !is_json {
+                continue;
+            }
+
+            let mut contents = String::new();
+            entry
+                .read_to_string(&mut contents)
+                .map_err(MailChimpError::InvalidBatchResults)?;
+
+            let mut file_results: Vec<BatchOperationResult> =
+                serde_json::from_str(&contents).map_err(|e| MailChimpError::InvalidBatchResults(e.into()))?;
+            results.append(&mut file_results);
+        }
+
+        Ok(results)
+    }
+
+    /// List the templates of the given type, ie. "user" for the templates we created.
+    ///
+    /// FROM: https://mailchimp.com/developer/marketing/api/templates/list-templates/
+    pub async fn list_templates<T>(&self, template_type: T) -> Result<Vec<Template>, MailChimpError>
+    where
+        T: AsRef<str>,
+    {
+        let per_page = 1000;
+        let mut offset: usize = 0;
+        let mut templates: Vec<Template> = Default::default();
+
+        loop {
+            let rb = self.request(
+                Method::GET,
+                format!(
+                    "3.0/templates?type={}&count={}&offset={}",
+                    urlencoding::encode(template_type.as_ref()),
+                    per_page,
+                    offset
+                ),
+            )?;
+
+            let mut page: ListTemplatesResponse = self.execute(rb, StatusCode::OK).await?;
+
+            if page.templates.is_empty() {
+                break;
+            }
+
+            offset += page.templates.len();
+            templates.append(&mut page.templates);
+        }
+
+        Ok(templates)
+    }
+
+    /// Create a template from html. Editable sections are marked with `mc:edit` attributes.
+    ///
+    /// FROM: https://mailchimp.com/developer/marketing/api/templates/add-template/
+    pub async fn create_template(&self, template: &NewTemplate) -> Result<Template, MailChimpError> {
+        let rb = self.request(Method::POST, "3.0/templates")?.json(template);
+
+        self.execute(rb, StatusCode::OK).await
+    }
+
+    /// Get the editable sections of a template and their default content.
+    ///
+    /// FROM: https://mailchimp.com/developer/marketing/api/template-default-content/view-default-content/
+    pub async fn get_template_default_content(&self, template_id: i64) -> Result<TemplateContent, MailChimpError> {
+        let rb = self.request(Method::GET, format!("3.0/templates/{}/default-content", template_id))?;
+
+        self.execute(rb, StatusCode::OK).await
+    }
+
+    /// List the classic automations of the account.
+    ///
+    /// FROM: https://mailchimp.com/developer/marketing/api/automation/list-automations/
+    pub async fn list_automations(&self) -> Result<Vec<Automation>, MailChimpError> {
+        let rb = self.request(Method::GET, "3.0/automations?count=1000")?;
+
+        let r: ListAutomationsResponse = self.execute(rb, StatusCode::OK).await?;
+
+        Ok(r.automations)
+    }
+
+    /// Queue an email of a classic automation to be sent to a member of its audience.
+    ///
+    /// FROM: https://mailchimp.com/developer/marketing/api/automation-email-queue/add-subscriber-to-workflow-email/
+    pub async fn queue_automation_email<W, E, A>(
+        &self,
+        workflow_id: W,
+        workflow_email_id: E,
+        email: A,
+    ) -> Result<AutomationSubscriber, MailChimpError>
+    where
+        W: AsRef<str>,
+        E: AsRef<str>,
+        A: AsRef<str>,
+    {
+        let rb = self
+            .request(
+                Method::POST,
+                format!(
+                    "3.0/automations/{}/emails/{}/queue",
+                    workflow_id.as_ref(),
+                    workflow_email_id.as_ref()
+                ),
+            )?
+            .json(&JourneyContact {
+                email_address: email.as_ref().to_string(),
+            });
+
+        self.execute(rb, StatusCode::OK).await
+    }
+
+    /// Move a member of the audience into a customer journey at the given step. The step must
+    /// have an "API trigger" starting point.
+    ///
+    /// FROM: https://mailchimp.com/developer/marketing/api/customer-journeys-journeys-steps-actions/
+    pub async fn trigger_journey_step<E>(&self, journey_id: i64, step_id: i64, email: E) -> Result<(), MailChimpError>
+    where
+        E: AsRef<str>,
+    {
+        let rb = self
+            .request(
+                Method::POST,
+                format!(
+                    "3.0/customer-journeys/journeys/{}/steps/{}/actions/trigger",
+                    journey_id, step_id
+                ),
+            )?
+            .json(&JourneyContact {
+                email_address: email.as_ref().to_string(),
+            });
+
+        self.execute_empty(rb).await
+    }
+
+    /// Create a campaign, it will be left as a draft until it is scheduled or sent.
+    ///
+    /// FROM: https://mailchimp.com/developer/marketing/api/campaigns/add-campaign/
+    pub async fn create_campaign(&self, campaign: &NewCampaign) -> Result<Campaign, MailChimpError> {
+        let rb = self.request(Method::POST, "3.0/campaigns")?.json(campaign);
+
+        self.execute(rb, StatusCode::OK).await
+    }
+
+    pub async fn get_campaign<T>(&self, campaign_id: T) -> Result<Campaign, MailChimpError>
+    where
+        T: AsRef<str>,
+    {
+        let rb = self.request(Method::GET, format!("3.0/campaigns/{}", campaign_id.as_ref()))?;
+
+        self.execute(rb, StatusCode::OK).await
+    }
+
+    /// Set the body of a draft campaign.
+    ///
+    /// FROM: https://mailchimp.com/developer/marketing/api/campaign-content/set-campaign-content/
+    pub async fn set_campaign_content<T>(
+        &self,
+        campaign_id: T,
+        content: &CampaignContent,
+    ) -> Result<CampaignContentResponse, MailChimpError>
+    where
+        T: AsRef<str>,
+    {
+        let rb = self
+            .request(Method::PUT, format!("3.0/campaigns/{}/content", campaign_id.as_ref()))?
+            .json(content);
+
+        self.execute(rb, StatusCode::OK).await
+    }
+
+    /// Schedule a campaign to be sent at the given time, which MailChimp requires to be on
+    /// a quarter hour.
+    pub async fn schedule_campaign<T>(&self, campaign_id: T, schedule_time: DateTime<Utc>) -> Result<(), MailChimpError>
+    where
+        T: AsRef<str>,
+    {
+        let rb = self
+            .request(
+                Method::POST,
+                format!("3.0/campaigns/{}/actions/schedule", campaign_id.as_ref()),
+            )?
+            .json(&ScheduleCampaign { schedule_time });
+
+        self.execute_empty(rb).await
+    }
+
+    pub async fn unschedule_campaign<T>(&self, campaign_id: T) -> Result<(), MailChimpError>
+    where
+        T: AsRef<str>,
+    {
+        let rb = self.request(
+            Method::POST,
+            format!("3.0/campaigns/{}/actions/unschedule", campaign_id.as_ref()),
+        )?;
+
+        self.execute_empty(rb).await
+    }
+
+    /// Send a campaign immediately.
+    pub async fn send_campaign<T>(&self, campaign_id: T) -> Result<(), MailChimpError>
+    where
+        T: AsRef<str>,
+    {
+        let rb = self.request(
+            Method::POST,
+            format!("3.0/campaigns/{}/actions/send", campaign_id.as_ref()),
+        )?;
+
+        self.execute_empty(rb).await
+    }
+
+    /// Send a test copy of a campaign to the given addresses.
+    pub async fn send_test_campaign<T>(&self, campaign_id: T, test_emails: Vec<String>) -> Result<(), MailChimpError>
+    where
+        T: AsRef<str>,
+    {
+        let rb = self
+            .request(
+                Method::POST,
+                format!("3.0/campaigns/{}/actions/test", campaign_id.as_ref()),
+            )?
+            .json(&TestCampaign {
+                test_emails,
+                send_type: "html".to_string(),
+            });
+
+        self.execute_empty(rb).await
+    }
+
+    /// List the webhooks of an audience.
+    ///
+    /// FROM: https://mailchimp.com/developer/marketing/api/list-webhooks/list-webhooks/
+    pub async fn list_webhooks<T>(&self, list_id: T) -> Result<Vec<Webhook>, MailChimpError>
+    where
+        T: AsRef<str>,
+    {
+        let rb = self.request(Method::GET, format!("3.0/lists/{}/webhooks", list_id.as_ref()))?;
+        let resp: ListWebhooksResponse = self.execute(rb, StatusCode::OK).await?;
+
+        Ok(resp.webhooks)
+    }
+
+    /// Create a webhook for an audience. MailChimp sends a GET request to the url to validate it
+    /// before the webhook is created.
+    ///
+    /// FROM: https://mailchimp.com/developer/marketing/api/list-webhooks/add-webhook/
+    pub async fn create_webhook<T>(&self, list_id: T, webhook: &NewWebhook) -> Result<Webhook, MailChimpError>
+    where
+        T: AsRef<str>,
+    {
+        let rb = self
+            .request(Method::POST, format!("3.0/lists/{}/webhooks", list_id.as_ref()))?
+            .json(webhook);
+
+        self.execute(rb, StatusCode::OK).await
+    }
 }
 
 #[derive(Debug)]
@@ -166,6 +707,7 @@ pub struct MailChimpAPIError {
 pub enum MailChimpError {
     APIError(MailChimpAPIError),
     InternalError(reqwest::Error),
+    InvalidBatchResults(std::io::Error),
     InvalidDataCenterEndpoint(ParseError),
     InvalidUri(ParseError),
     MalformedAPIKey,
@@ -194,7 +736,7 @@ impl error::Error for MailChimpError {
 
 #[cfg(test)]
 mod tests {
-    use super::{AuthMode, MailChimpError};
+    use super::{subscriber_hash, AuthMode, MailChimpError};
     use base64;
     use std::str::from_utf8;
 
@@ -243,4 +785,16 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_subscriber_hash_ignores_case() {
+        assert_eq!(
+            "62eeb292278cc15f5817cb78f7790b08",
+            subscriber_hash("urist.mcvankab@freddiesjokes.com")
+        );
+        assert_eq!(
+            subscriber_hash("urist.mcvankab@freddiesjokes.com"),
+            subscriber_hash("Urist.McVankab@FreddiesJokes.com")
+        );
+    }
 }
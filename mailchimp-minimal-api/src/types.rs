@@ -3,6 +3,8 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::subscriber_hash;
+
 #[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
 pub struct AccessToken {
     #[serde(
@@ -170,6 +172,143 @@ pub struct Webhook {
     pub data: WebhookData,
 }
 
+/// A typed webhook event from MailChimp. MailChimp does not sign its webhooks, so the
+/// endpoint receiving these must check a secret token in the url instead.
+///
+/// FROM: https://mailchimp.com/developer/marketing/guides/sync-audience-data-webhooks/#webhook-events
+#[derive(Debug, Clone, JsonSchema, Deserialize, Serialize)]
+pub enum WebhookEvent {
+    Subscribe(WebhookPayload<MemberEventData>),
+    Unsubscribe(WebhookPayload<UnsubscribeEventData>),
+    Profile(WebhookPayload<MemberEventData>),
+    EmailChanged(WebhookPayload<EmailChangedEventData>),
+    Cleaned(WebhookPayload<CleanedEventData>),
+}
+
+impl WebhookEvent {
+    /// Parse the form encoded body of a webhook request.
+    pub fn from_form(body: &[u8]) -> Result<Self, WebhookParseError> {
+        // MailChimp sends the nested `data[...]` keys with the brackets url encoded, which
+        // is only accepted in non-strict mode.
+        let config = serde_qs::Config::new(10, false);
+
+        let envelope: WebhookEnvelope = config.deserialize_bytes(body).map_err(WebhookParseError::Malformed)?;
+
+        let event = match envelope.webhook_type.as_str() {
+            "subscribe" => WebhookEvent::Subscribe(config.deserialize_bytes(body)?),
+            "unsubscribe" => WebhookEvent::Unsubscribe(config.deserialize_bytes(body)?),
+            "profile" => WebhookEvent::Profile(config.deserialize_bytes(body)?),
+            "upemail" => WebhookEvent::EmailChanged(config.deserialize_bytes(body)?),
+            "cleaned" => WebhookEvent::Cleaned(config.deserialize_bytes(body)?),
+            other => return Err(WebhookParseError::UnsupportedType(other.to_string())),
+        };
+
+        Ok(event)
+    }
+
+    /// The audience the event happened in.
+    pub fn list_id(&self) -> &str {
+        match self {
+            WebhookEvent::Subscribe(e) | WebhookEvent::Profile(e) => &e.data.list_id,
+            WebhookEvent::Unsubscribe(e) => &e.data.list_id,
+            WebhookEvent::EmailChanged(e) => &e.data.list_id,
+            WebhookEvent::Cleaned(e) => &e.data.list_id,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum WebhookParseError {
+    Malformed(serde_qs::Error),
+    UnsupportedType(String),
+}
+
+impl From<serde_qs::Error> for WebhookParseError {
+    fn from(error: serde_qs::Error) -> Self {
+        WebhookParseError::Malformed(error)
+    }
+}
+
+impl std::fmt::Display for WebhookParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            WebhookParseError::Malformed(e) => write!(f, "malformed MailChimp webhook: {e}"),
+            WebhookParseError::UnsupportedType(t) => write!(f, "unsupported MailChimp webhook type `{t}`"),
+        }
+    }
+}
+
+impl std::error::Error for WebhookParseError {}
+
+#[derive(Debug, Clone, Deserialize)]
+struct WebhookEnvelope {
+    #[serde(rename = "type")]
+    webhook_type: String,
+}
+
+#[derive(Debug, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct WebhookPayload<T> {
+    #[serde(
+        deserialize_with = "mailchimp_date_format::deserialize",
+        serialize_with = "mailchimp_date_format::serialize"
+    )]
+    pub fired_at: DateTime<Utc>,
+    pub data: T,
+}
+
+/// The data sent when a member subscribes or updates their profile.
+#[derive(Debug, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct MemberEventData {
+    pub id: String,
+    pub list_id: String,
+    pub email: String,
+    #[serde(default)]
+    pub email_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip_opt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip_signup: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_id: Option<String>,
+    pub merges: WebhookMerges,
+}
+
+#[derive(Debug, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct UnsubscribeEventData {
+    /// Either "unsub" or "delete".
+    pub action: String,
+    /// Either "manual" or "abuse".
+    #[serde(default)]
+    pub reason: String,
+    pub id: String,
+    pub list_id: String,
+    pub email: String,
+    #[serde(default)]
+    pub email_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub campaign_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merges: Option<WebhookMerges>,
+}
+
+#[derive(Debug, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct EmailChangedEventData {
+    pub list_id: String,
+    pub new_id: String,
+    pub new_email: String,
+    pub old_email: String,
+}
+
+#[derive(Debug, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct CleanedEventData {
+    pub list_id: String,
+    /// Either "hard" for a hard bounce or "abuse".
+    pub reason: String,
+    pub email: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub campaign_id: Option<String>,
+}
+
 mod mailchimp_date_format {
     use chrono::{DateTime, TimeZone, Utc};
     use serde::{self, Deserialize, Deserializer, Serializer};
@@ -455,3 +594,589 @@ pub struct EcommerceData {
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub currency_code: String,
 }
+
+/// The body for creating a campaign.
+///
+/// FROM: https://mailchimp.com/developer/marketing/api/campaigns/add-campaign/
+#[derive(Debug, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct NewCampaign {
+    /// One of "regular", "plaintext", "absplit", "rss" or "variate".
+    #[serde(rename = "type")]
+    pub campaign_type: String,
+    pub recipients: CampaignRecipients,
+    pub settings: CampaignSettings,
+}
+
+impl NewCampaign {
+    /// A regular html campaign sent to an audience.
+    pub fn regular(list_id: &str, settings: CampaignSettings) -> Self {
+        NewCampaign {
+            campaign_type: "regular".to_string(),
+            recipients: CampaignRecipients {
+                list_id: list_id.to_string(),
+                ..Default::default()
+            },
+            settings,
+        }
+    }
+
+    /// Only send to the members of a saved segment of the audience.
+    pub fn with_segment(mut self, segment_id: i64) -> Self {
+        self.recipients.segment_opts = Some(CampaignSegmentOpts {
+            saved_segment_id: Some(segment_id),
+            ..Default::default()
+        });
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct CampaignRecipients {
+    /// The unique list id.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub list_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segment_opts: Option<CampaignSegmentOpts>,
+    /// The number of recipients the campaign will be sent to.
+    #[serde(default, skip_serializing)]
+    pub recipient_count: i64,
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct CampaignSegmentOpts {
+    /// The id of a saved segment, to send to everyone in it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub saved_segment_id: Option<i64>,
+    /// Segment match type, "any" or "all", when sending to an ad-hoc segment.
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "match")]
+    pub match_type: String,
+    /// The conditions of an ad-hoc segment.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conditions: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct CampaignSettings {
+    /// The subject line for the campaign.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub subject_line: String,
+    /// The preview text shown next to the subject in most inboxes.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub preview_text: String,
+    /// The title of the campaign, only shown within MailChimp.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub title: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub from_name: String,
+    /// The reply-to email address, it must be verified in MailChimp.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub reply_to: String,
+    /// The id of the template to use.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_id: Option<i64>,
+}
+
+/// The data type for a campaign.
+///
+/// FROM: https://mailchimp.com/developer/marketing/api/campaigns/get-campaign-info/
+#[derive(Debug, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct Campaign {
+    pub id: String,
+    #[serde(default)]
+    pub web_id: i64,
+    #[serde(default, rename = "type")]
+    pub campaign_type: String,
+    #[serde(default)]
+    pub create_time: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub archive_url: String,
+    /// One of "save", "paused", "schedule", "sending" or "sent".
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub status: String,
+    #[serde(default)]
+    pub emails_sent: i64,
+    #[serde(default)]
+    pub send_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub recipients: CampaignRecipients,
+    #[serde(default)]
+    pub settings: CampaignSettings,
+}
+
+/// The body for setting the content of a campaign, either as raw html or a template.
+///
+/// FROM: https://mailchimp.com/developer/marketing/api/campaign-content/set-campaign-content/
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct CampaignContent {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub html: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub plain_text: String,
+    /// Render the campaign from a template, replacing the content of its editable sections.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<CampaignTemplate>,
+}
+
+impl CampaignContent {
+    /// Content rendered from a template with the html of each named `mc:edit` section.
+    pub fn from_template(template_id: i64, sections: HashMap<String, String>) -> Self {
+        CampaignContent {
+            template: Some(CampaignTemplate {
+                id: template_id,
+                sections,
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct CampaignTemplate {
+    pub id: i64,
+    /// The html of each editable section keyed by its `mc:edit` name. Sections that are
+    /// left out keep the template's default content.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub sections: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct CampaignContentResponse {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub html: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub plain_text: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub archive_html: String,
+}
+
+#[derive(Debug, Clone, JsonSchema, Deserialize, Serialize)]
+pub(crate) struct ScheduleCampaign {
+    pub schedule_time: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, JsonSchema, Deserialize, Serialize)]
+pub(crate) struct TestCampaign {
+    pub test_emails: Vec<String>,
+    pub send_type: String,
+}
+
+/// The data type for a segment or tag of an audience.
+///
+/// FROM: https://mailchimp.com/developer/marketing/api/list-segments/get-segment-info/
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct Segment {
+    #[serde(default)]
+    pub id: i64,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    /// The number of active subscribers currently included in the segment.
+    #[serde(default)]
+    pub member_count: i64,
+    /// One of "saved", "static" or "fuzzy". Tags are static segments.
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "type")]
+    pub segment_type: String,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub options: Option<SegmentOptions>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub list_id: String,
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct ListSegmentsResponse {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub segments: Vec<Segment>,
+    #[serde(default)]
+    pub total_items: i64,
+}
+
+/// The body for creating or updating a segment.
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct NewSegment {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    /// The emails of the members of a static segment. This replaces the existing members
+    /// when updating a segment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub static_segment: Option<Vec<String>>,
+    /// The conditions of a saved segment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<SegmentOptions>,
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct SegmentOptions {
+    /// Match type, "any" or "all".
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "match")]
+    pub match_type: String,
+    /// The conditions of the segment. These are loosely typed since MailChimp has a
+    /// different shape for each condition type.
+    ///
+    /// FROM: https://mailchimp.com/developer/marketing/docs/alternative-schemas/#segment-condition-schemas
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conditions: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub(crate) struct SegmentMembers {
+    pub members_to_add: Vec<String>,
+    pub members_to_remove: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct SegmentMembersResponse {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub members_added: Vec<Member>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub members_removed: Vec<Member>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<SegmentMembersError>,
+    #[serde(default)]
+    pub total_added: i64,
+    #[serde(default)]
+    pub total_removed: i64,
+    #[serde(default)]
+    pub error_count: i64,
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct SegmentMembersError {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub email_addresses: Vec<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct MemberTag {
+    pub name: String,
+    /// "active" to add the tag, "inactive" to remove it.
+    pub status: String,
+}
+
+impl MemberTag {
+    pub fn add(name: &str) -> Self {
+        MemberTag {
+            name: name.to_string(),
+            status: "active".to_string(),
+        }
+    }
+
+    pub fn remove(name: &str) -> Self {
+        MemberTag {
+            name: name.to_string(),
+            status: "inactive".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub(crate) struct MemberTags {
+    pub tags: Vec<MemberTag>,
+}
+
+/// The body for adding or updating a member of an audience.
+///
+/// FROM: https://mailchimp.com/developer/marketing/api/list-members/add-or-update-list-member/
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct MemberUpsert {
+    pub email_address: String,
+    /// The status to use if the member is new to the audience.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub status_if_new: String,
+    /// The status to set on an existing member, left unchanged when empty.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub status: String,
+    /// Merge fields keyed by their merge tag, ie. `FNAME`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub merge_fields: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// The marketing permissions (GDPR consent fields) the member opted in or out of.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub marketing_permissions: Vec<MarketingPermissions>,
+    /// IP address the subscriber signed up from.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub ip_signup: String,
+    /// The date and time the subscriber signed up in ISO 8601 format.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub timestamp_signup: String,
+}
+
+/// A single request in a batch.
+///
+/// FROM: https://mailchimp.com/developer/marketing/api/batch-operations/start-batch-operation/
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct BatchOperation {
+    /// The HTTP method to use.
+    pub method: String,
+    /// The relative path of the endpoint, without the `/3.0` prefix.
+    pub path: String,
+    /// The JSON encoded body of the request.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub body: String,
+    /// An id of our choosing used to match the operation to its result.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub operation_id: String,
+}
+
+impl BatchOperation {
+    pub fn new<B>(method: &str, path: &str, body: &B, operation_id: &str) -> Result<Self, serde_json::Error>
+    where
+        B: Serialize,
+    {
+        Ok(BatchOperation {
+            method: method.to_string(),
+            path: path.to_string(),
+            body: serde_json::to_string(body)?,
+            operation_id: operation_id.to_string(),
+        })
+    }
+
+    /// Add a member to the audience or update them if they are already in it. The email
+    /// address is used as the operation id.
+    pub fn upsert_member(list_id: &str, member: &MemberUpsert) -> Self {
+        BatchOperation::new(
+            "PUT",
+            &format!("/lists/{}/members/{}", list_id, subscriber_hash(&member.email_address)),
+            member,
+            &member.email_address,
+        )
+        .expect("a member upsert can always be serialized")
+    }
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub(crate) struct NewBatch {
+    pub operations: Vec<BatchOperation>,
+}
+
+/// The data type for the status of a batch.
+///
+/// FROM: https://mailchimp.com/developer/marketing/api/batch-operations/get-batch-operation-status/
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct Batch {
+    pub id: String,
+    /// One of "pending", "preprocessing", "started", "finalizing" or "finished".
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub status: String,
+    #[serde(default)]
+    pub total_operations: i64,
+    #[serde(default)]
+    pub finished_operations: i64,
+    #[serde(default)]
+    pub errored_operations: i64,
+    #[serde(default)]
+    pub submitted_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub completed_at: Option<DateTime<Utc>>,
+    /// The url of a gzipped tarball of the results, only set once the batch is finished.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub response_body_url: String,
+}
+
+impl Batch {
+    pub fn is_finished(&self) -> bool {
+        self.status == "finished"
+    }
+}
+
+/// The response to a single operation of a batch.
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct BatchOperationResult {
+    #[serde(default)]
+    pub status_code: u16,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub operation_id: String,
+    /// The JSON encoded body of the response.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub response: String,
+}
+
+impl BatchOperationResult {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status_code)
+    }
+}
+
+/// The data type for a template.
+///
+/// FROM: https://mailchimp.com/developer/marketing/api/templates/get-template-info/
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct Template {
+    #[serde(default)]
+    pub id: i64,
+    /// One of "user", "base" or "gallery".
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "type")]
+    pub template_type: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default)]
+    pub drag_and_drop: bool,
+    #[serde(default)]
+    pub responsive: bool,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub category: String,
+    #[serde(default)]
+    pub date_created: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub date_edited: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub active: bool,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub folder_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub thumbnail: String,
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct ListTemplatesResponse {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub templates: Vec<Template>,
+    #[serde(default)]
+    pub total_items: i64,
+}
+
+/// The body for creating a template.
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct NewTemplate {
+    pub name: String,
+    /// The raw html of the template, using MailChimp's template language.
+    pub html: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub folder_id: String,
+}
+
+/// The editable sections of a template and their default html.
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct TemplateContent {
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub sections: HashMap<String, String>,
+}
+
+/// The data type for a classic automation.
+///
+/// FROM: https://mailchimp.com/developer/marketing/api/automation/get-automation-info/
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct Automation {
+    pub id: String,
+    /// One of "save", "paused" or "sending".
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub status: String,
+    #[serde(default)]
+    pub emails_sent: i64,
+    #[serde(default)]
+    pub create_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub start_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub recipients: CampaignRecipients,
+    #[serde(default)]
+    pub settings: AutomationSettings,
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct AutomationSettings {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub title: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub from_name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub reply_to: String,
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct ListAutomationsResponse {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub automations: Vec<Automation>,
+    #[serde(default)]
+    pub total_items: i64,
+}
+
+/// A member queued to receive an automation email.
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct AutomationSubscriber {
+    /// The MD5 hash of the lowercase version of the member's email address.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub workflow_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub email_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub list_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub email_address: String,
+    /// When the automation email will be sent.
+    #[serde(default)]
+    pub next_send: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub(crate) struct JourneyContact {
+    pub email_address: String,
+}
+
+/// A webhook that MailChimp calls when something changes in an audience.
+///
+/// FROM: https://mailchimp.com/developer/marketing/api/list-webhooks/get-webhook-info/
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct Webhook {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub url: String,
+    #[serde(default)]
+    pub events: WebhookEvents,
+    #[serde(default)]
+    pub sources: WebhookSources,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub list_id: String,
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct ListWebhooksResponse {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<Webhook>,
+    #[serde(default)]
+    pub total_items: i64,
+}
+
+/// The body for creating a webhook.
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct NewWebhook {
+    pub url: String,
+    pub events: WebhookEvents,
+    pub sources: WebhookSources,
+}
+
+/// The audience events a webhook is called for.
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct WebhookEvents {
+    #[serde(default)]
+    pub subscribe: bool,
+    #[serde(default)]
+    pub unsubscribe: bool,
+    #[serde(default)]
+    pub profile: bool,
+    #[serde(default)]
+    pub cleaned: bool,
+    #[serde(default)]
+    pub upemail: bool,
+    #[serde(default)]
+    pub campaign: bool,
+}
+
+/// Which changes to an audience a webhook is called for, by who made them.
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct WebhookSources {
+    #[serde(default)]
+    pub user: bool,
+    #[serde(default)]
+    pub admin: bool,
+    #[serde(default)]
+    pub api: bool,
+}
@@ -0,0 +1,81 @@
+use mailchimp_minimal_api::{WebhookEvent, WebhookParseError};
+
+fn fixture(name: &str) -> Vec<u8> {
+    std::fs::read(format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)).unwrap()
+}
+
+#[test]
+fn test_parses_subscribe_event() {
+    match WebhookEvent::from_form(&fixture("subscribe.txt")).unwrap() {
+        WebhookEvent::Subscribe(event) => {
+            assert_eq!("example@gmail.com", event.data.email);
+            assert_eq!("8a6d823488", event.data.list_id);
+            assert_eq!(Some("98.128.229.135".to_string()), event.data.ip_opt);
+
+            let groupings = event.data.merges.groupings.unwrap();
+            assert_eq!(3, groupings.len());
+            assert_eq!("Interested in the Oxide newsletter?", groupings[1].name);
+            assert_eq!(Some("Yes".to_string()), groupings[1].groups);
+        }
+        other => panic!("Expected a subscribe event, but instead received {:?}", other),
+    }
+}
+
+#[test]
+fn test_parses_unsubscribe_event() {
+    match WebhookEvent::from_form(&fixture("unsubscribe.txt")).unwrap() {
+        WebhookEvent::Unsubscribe(event) => {
+            assert_eq!("unsub", event.data.action);
+            assert_eq!("manual", event.data.reason);
+            assert_eq!("api+unsub@mailchimp.com", event.data.email);
+            assert_eq!(Some("cb398d21d2".to_string()), event.data.campaign_id);
+            assert_eq!("2009-03-26T21:40:57+00:00", event.fired_at.to_rfc3339());
+        }
+        other => panic!("Expected an unsubscribe event, but instead received {:?}", other),
+    }
+}
+
+#[test]
+fn test_parses_profile_event() {
+    match WebhookEvent::from_form(&fixture("profile.txt")).unwrap() {
+        WebhookEvent::Profile(event) => {
+            assert_eq!("api@mailchimp.com", event.data.email);
+            assert_eq!(Some("Mailchimp".to_string()), event.data.merges.first_name);
+            assert_eq!(Some("Intuit".to_string()), event.data.merges.company);
+        }
+        other => panic!("Expected a profile event, but instead received {:?}", other),
+    }
+}
+
+#[test]
+fn test_parses_email_changed_event() {
+    let event = WebhookEvent::from_form(&fixture("upemail.txt")).unwrap();
+    assert_eq!("a6b5da1054", event.list_id());
+
+    match event {
+        WebhookEvent::EmailChanged(event) => {
+            assert_eq!("api+old@mailchimp.com", event.data.old_email);
+            assert_eq!("api+new@mailchimp.com", event.data.new_email);
+        }
+        other => panic!("Expected an email changed event, but instead received {:?}", other),
+    }
+}
+
+#[test]
+fn test_parses_cleaned_event() {
+    match WebhookEvent::from_form(&fixture("cleaned.txt")).unwrap() {
+        WebhookEvent::Cleaned(event) => {
+            assert_eq!("hard", event.data.reason);
+            assert_eq!("api+cleaned@mailchimp.com", event.data.email);
+        }
+        other => panic!("Expected a cleaned event, but instead received {:?}", other),
+    }
+}
+
+#[test]
+fn test_rejects_unsupported_event() {
+    match WebhookEvent::from_form(&fixture("campaign.txt")) {
+        Err(WebhookParseError::UnsupportedType(t)) => assert_eq!("campaign", t),
+        other => panic!("Expected an unsupported type error, but instead received {:?}", other),
+    }
+}
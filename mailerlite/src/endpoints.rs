@@ -9,8 +9,9 @@ use serde::{
 use std::{collections::HashMap, net::Ipv4Addr};
 
 use crate::{
-    ApiSubscriber, FailedToTranslateDateError, FormattedDateTime, MailerliteClientContext, MailerliteError, Subscriber,
-    SubscriberFields, SubscriberStatus,
+    ApiSubscriber, ApiSubscriberGroup, FailedToTranslateDateError, Field, FieldType, FormattedDateTime,
+    MailerliteClientContext, MailerliteError, Segment, Subscriber, SubscriberFields, SubscriberGroup, SubscriberStatus,
+    Webhook, WebhookEventType,
 };
 
 trait AddOptionalQueryParam {
@@ -419,6 +420,539 @@ where
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum WriteResponse<T> {
+    Success {
+        data: T,
+    },
+    Error {
+        message: String,
+        errors: Option<HashMap<String, Vec<String>>>,
+    },
+}
+
+/// The response to endpoints that return an empty body on success
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DeleteResponse {
+    Success,
+    NotFound,
+    Error { message: String },
+}
+
+async fn handle_delete_response(response: Response) -> Result<DeleteResponse, MailerliteError> {
+    match response.status() {
+        StatusCode::NO_CONTENT => Ok(DeleteResponse::Success),
+        StatusCode::NOT_FOUND => Ok(DeleteResponse::NotFound),
+        _ => {
+            #[derive(Deserialize)]
+            struct ErrorBody {
+                message: String,
+            }
+
+            let ErrorBody { message } = response.json().await?;
+            Ok(DeleteResponse::Error { message })
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ListPageResponse<T> {
+    Success {
+        data: Vec<T>,
+        links: ListSegmentSubscribersResponseLinks,
+        meta: ListPageResponseMeta,
+    },
+    Error {
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ListPageResponseMeta {
+    pub current_page: u64,
+    pub last_page: u64,
+    #[serde(deserialize_with = "as_number")]
+    pub per_page: u64,
+    pub total: u64,
+}
+
+#[derive(Debug, Clone, Builder)]
+#[builder(pattern = "owned")]
+pub struct ListSegmentsRequest {
+    #[builder(setter(strip_option), default)]
+    limit: Option<u64>,
+    #[builder(setter(strip_option), default)]
+    page: Option<u64>,
+}
+
+#[async_trait]
+impl MailerliteEndpoint for ListSegmentsRequest {
+    type Response = ListPageResponse<Segment>;
+
+    fn to_request_builder<Tz>(
+        &self,
+        base_url: &str,
+        client: &Client,
+        _ctx: &MailerliteClientContext<Tz>,
+    ) -> RequestBuilder
+    where
+        Tz: TimeZone + Send + Sync,
+    {
+        client
+            .get(format!("{base_url}/segments"))
+            .optional_query("limit", self.limit.as_ref())
+            .optional_query("page", self.page.as_ref())
+    }
+}
+
+#[derive(Debug, Clone, Builder)]
+#[builder(pattern = "owned")]
+pub struct ListGroupsRequest {
+    #[builder(setter(strip_option), default)]
+    filter_name: Option<String>,
+    #[builder(setter(strip_option), default)]
+    limit: Option<u64>,
+    #[builder(setter(strip_option), default)]
+    page: Option<u64>,
+}
+
+#[async_trait]
+impl MailerliteEndpoint for ListGroupsRequest {
+    type Response = ListPageResponse<SubscriberGroup>;
+
+    fn to_request_builder<Tz>(
+        &self,
+        base_url: &str,
+        client: &Client,
+        _ctx: &MailerliteClientContext<Tz>,
+    ) -> RequestBuilder
+    where
+        Tz: TimeZone + Send + Sync,
+    {
+        client
+            .get(format!("{base_url}/groups"))
+            .optional_query("filter[name]", self.filter_name.as_ref())
+            .optional_query("limit", self.limit.as_ref())
+            .optional_query("page", self.page.as_ref())
+    }
+
+    async fn handle_response<Tz>(
+        &self,
+        response: Response,
+        ctx: &MailerliteClientContext<Tz>,
+    ) -> Result<Self::Response, MailerliteError>
+    where
+        Self::Response: DeserializeOwned,
+        Tz: TimeZone + Send + Sync,
+    {
+        let response: ListPageResponse<ApiSubscriberGroup> = response.json().await?;
+
+        Ok(match response {
+            ListPageResponse::Success { data, links, meta } => ListPageResponse::Success {
+                data: data
+                    .into_iter()
+                    .map(|g| g.into_group(&ctx.time_zone))
+                    .collect::<Result<Vec<SubscriberGroup>, FailedToTranslateDateError>>()?,
+                links,
+                meta,
+            },
+            ListPageResponse::Error { message } => ListPageResponse::Error { message },
+        })
+    }
+}
+
+/// Creates a new group, or renames an existing group when a `group_id` is supplied
+#[derive(Debug, Clone, Serialize, Deserialize, Builder)]
+#[builder(pattern = "owned")]
+pub struct WriteGroupRequest {
+    #[builder(setter(strip_option), default)]
+    #[serde(skip)]
+    group_id: Option<String>,
+    name: String,
+}
+
+#[async_trait]
+impl MailerliteEndpoint for WriteGroupRequest {
+    type Response = WriteResponse<SubscriberGroup>;
+
+    fn to_request_builder<Tz>(
+        &self,
+        base_url: &str,
+        client: &Client,
+        _ctx: &MailerliteClientContext<Tz>,
+    ) -> RequestBuilder
+    where
+        Tz: TimeZone + Send + Sync,
+    {
+        match &self.group_id {
+            Some(group_id) => client.put(format!("{base_url}/groups/{group_id}")),
+            None => client.post(format!("{base_url}/groups")),
+        }
+        .json(&self)
+    }
+
+    async fn handle_response<Tz>(
+        &self,
+        response: Response,
+        ctx: &MailerliteClientContext<Tz>,
+    ) -> Result<Self::Response, MailerliteError>
+    where
+        Self::Response: DeserializeOwned,
+        Tz: TimeZone + Send + Sync,
+    {
+        handle_group_response(response, ctx).await
+    }
+}
+
+async fn handle_group_response<Tz>(
+    response: Response,
+    ctx: &MailerliteClientContext<Tz>,
+) -> Result<WriteResponse<SubscriberGroup>, MailerliteError>
+where
+    Tz: TimeZone + Send + Sync,
+{
+    let response: WriteResponse<ApiSubscriberGroup> = response.json().await?;
+
+    Ok(match response {
+        WriteResponse::Success { data } => WriteResponse::Success {
+            data: data.into_group(&ctx.time_zone)?,
+        },
+        WriteResponse::Error { message, errors } => WriteResponse::Error { message, errors },
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Builder)]
+#[builder(pattern = "owned")]
+pub struct DeleteGroupRequest {
+    group_id: String,
+}
+
+#[async_trait]
+impl MailerliteEndpoint for DeleteGroupRequest {
+    type Response = DeleteResponse;
+
+    fn to_request_builder<Tz>(
+        &self,
+        base_url: &str,
+        client: &Client,
+        _ctx: &MailerliteClientContext<Tz>,
+    ) -> RequestBuilder
+    where
+        Tz: TimeZone + Send + Sync,
+    {
+        client.delete(format!("{base_url}/groups/{}", self.group_id))
+    }
+
+    async fn handle_response<Tz>(
+        &self,
+        response: Response,
+        _ctx: &MailerliteClientContext<Tz>,
+    ) -> Result<Self::Response, MailerliteError>
+    where
+        Self::Response: DeserializeOwned,
+        Tz: TimeZone + Send + Sync,
+    {
+        handle_delete_response(response).await
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Builder)]
+#[builder(pattern = "owned")]
+pub struct AssignSubscriberToGroupRequest {
+    subscriber_id: String,
+    group_id: String,
+}
+
+#[async_trait]
+impl MailerliteEndpoint for AssignSubscriberToGroupRequest {
+    type Response = WriteResponse<SubscriberGroup>;
+
+    fn to_request_builder<Tz>(
+        &self,
+        base_url: &str,
+        client: &Client,
+        _ctx: &MailerliteClientContext<Tz>,
+    ) -> RequestBuilder
+    where
+        Tz: TimeZone + Send + Sync,
+    {
+        client.post(format!(
+            "{base_url}/subscribers/{}/groups/{}",
+            self.subscriber_id, self.group_id
+        ))
+    }
+
+    async fn handle_response<Tz>(
+        &self,
+        response: Response,
+        ctx: &MailerliteClientContext<Tz>,
+    ) -> Result<Self::Response, MailerliteError>
+    where
+        Self::Response: DeserializeOwned,
+        Tz: TimeZone + Send + Sync,
+    {
+        handle_group_response(response, ctx).await
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Builder)]
+#[builder(pattern = "owned")]
+pub struct UnassignSubscriberFromGroupRequest {
+    subscriber_id: String,
+    group_id: String,
+}
+
+#[async_trait]
+impl MailerliteEndpoint for UnassignSubscriberFromGroupRequest {
+    type Response = DeleteResponse;
+
+    fn to_request_builder<Tz>(
+        &self,
+        base_url: &str,
+        client: &Client,
+        _ctx: &MailerliteClientContext<Tz>,
+    ) -> RequestBuilder
+    where
+        Tz: TimeZone + Send + Sync,
+    {
+        client.delete(format!(
+            "{base_url}/subscribers/{}/groups/{}",
+            self.subscriber_id, self.group_id
+        ))
+    }
+
+    async fn handle_response<Tz>(
+        &self,
+        response: Response,
+        _ctx: &MailerliteClientContext<Tz>,
+    ) -> Result<Self::Response, MailerliteError>
+    where
+        Self::Response: DeserializeOwned,
+        Tz: TimeZone + Send + Sync,
+    {
+        handle_delete_response(response).await
+    }
+}
+
+#[derive(Debug, Clone, Builder)]
+#[builder(pattern = "owned")]
+pub struct ListFieldsRequest {
+    #[builder(setter(strip_option), default)]
+    filter_keyword: Option<String>,
+    #[builder(setter(strip_option), default)]
+    filter_type: Option<FieldType>,
+    #[builder(setter(strip_option), default)]
+    limit: Option<u64>,
+    #[builder(setter(strip_option), default)]
+    page: Option<u64>,
+}
+
+#[async_trait]
+impl MailerliteEndpoint for ListFieldsRequest {
+    type Response = ListPageResponse<Field>;
+
+    fn to_request_builder<Tz>(
+        &self,
+        base_url: &str,
+        client: &Client,
+        _ctx: &MailerliteClientContext<Tz>,
+    ) -> RequestBuilder
+    where
+        Tz: TimeZone + Send + Sync,
+    {
+        client
+            .get(format!("{base_url}/fields"))
+            .optional_query("filter[keyword]", self.filter_keyword.as_ref())
+            .optional_query("filter[type]", self.filter_type.as_ref())
+            .optional_query("limit", self.limit.as_ref())
+            .optional_query("page", self.page.as_ref())
+    }
+}
+
+/// Creates a new field. The type of a field can not be changed once it has been created.
+#[derive(Debug, Clone, Serialize, Deserialize, Builder)]
+#[builder(pattern = "owned")]
+pub struct CreateFieldRequest {
+    name: String,
+    #[serde(rename = "type")]
+    field_type: FieldType,
+}
+
+#[async_trait]
+impl MailerliteEndpoint for CreateFieldRequest {
+    type Response = WriteResponse<Field>;
+
+    fn to_request_builder<Tz>(
+        &self,
+        base_url: &str,
+        client: &Client,
+        _ctx: &MailerliteClientContext<Tz>,
+    ) -> RequestBuilder
+    where
+        Tz: TimeZone + Send + Sync,
+    {
+        client.post(format!("{base_url}/fields")).json(&self)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Builder)]
+#[builder(pattern = "owned")]
+pub struct UpdateFieldRequest {
+    #[serde(skip)]
+    field_id: String,
+    name: String,
+}
+
+#[async_trait]
+impl MailerliteEndpoint for UpdateFieldRequest {
+    type Response = WriteResponse<Field>;
+
+    fn to_request_builder<Tz>(
+        &self,
+        base_url: &str,
+        client: &Client,
+        _ctx: &MailerliteClientContext<Tz>,
+    ) -> RequestBuilder
+    where
+        Tz: TimeZone + Send + Sync,
+    {
+        client.put(format!("{base_url}/fields/{}", self.field_id)).json(&self)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Builder)]
+#[builder(pattern = "owned")]
+pub struct DeleteFieldRequest {
+    field_id: String,
+}
+
+#[async_trait]
+impl MailerliteEndpoint for DeleteFieldRequest {
+    type Response = DeleteResponse;
+
+    fn to_request_builder<Tz>(
+        &self,
+        base_url: &str,
+        client: &Client,
+        _ctx: &MailerliteClientContext<Tz>,
+    ) -> RequestBuilder
+    where
+        Tz: TimeZone + Send + Sync,
+    {
+        client.delete(format!("{base_url}/fields/{}", self.field_id))
+    }
+
+    async fn handle_response<Tz>(
+        &self,
+        response: Response,
+        _ctx: &MailerliteClientContext<Tz>,
+    ) -> Result<Self::Response, MailerliteError>
+    where
+        Self::Response: DeserializeOwned,
+        Tz: TimeZone + Send + Sync,
+    {
+        handle_delete_response(response).await
+    }
+}
+
+#[derive(Debug, Clone, Builder)]
+#[builder(pattern = "owned")]
+pub struct ListWebhooksRequest {
+    #[builder(setter(strip_option), default)]
+    limit: Option<u64>,
+    #[builder(setter(strip_option), default)]
+    page: Option<u64>,
+}
+
+#[async_trait]
+impl MailerliteEndpoint for ListWebhooksRequest {
+    type Response = ListPageResponse<Webhook>;
+
+    fn to_request_builder<Tz>(
+        &self,
+        base_url: &str,
+        client: &Client,
+        _ctx: &MailerliteClientContext<Tz>,
+    ) -> RequestBuilder
+    where
+        Tz: TimeZone + Send + Sync,
+    {
+        client
+            .get(format!("{base_url}/webhooks"))
+            .optional_query("limit", self.limit.as_ref())
+            .optional_query("page", self.page.as_ref())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Builder)]
+#[builder(pattern = "owned")]
+pub struct CreateWebhookRequest {
+    name: String,
+    events: Vec<WebhookEventType>,
+    url: String,
+    #[builder(setter(strip_option), default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    enabled: Option<bool>,
+    #[builder(setter(strip_option), default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    batchable: Option<bool>,
+}
+
+#[async_trait]
+impl MailerliteEndpoint for CreateWebhookRequest {
+    type Response = WriteResponse<Webhook>;
+
+    fn to_request_builder<Tz>(
+        &self,
+        base_url: &str,
+        client: &Client,
+        _ctx: &MailerliteClientContext<Tz>,
+    ) -> RequestBuilder
+    where
+        Tz: TimeZone + Send + Sync,
+    {
+        client.post(format!("{base_url}/webhooks")).json(&self)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Builder)]
+#[builder(pattern = "owned")]
+pub struct DeleteWebhookRequest {
+    webhook_id: String,
+}
+
+#[async_trait]
+impl MailerliteEndpoint for DeleteWebhookRequest {
+    type Response = DeleteResponse;
+
+    fn to_request_builder<Tz>(
+        &self,
+        base_url: &str,
+        client: &Client,
+        _ctx: &MailerliteClientContext<Tz>,
+    ) -> RequestBuilder
+    where
+        Tz: TimeZone + Send + Sync,
+    {
+        client.delete(format!("{base_url}/webhooks/{}", self.webhook_id))
+    }
+
+    async fn handle_response<Tz>(
+        &self,
+        response: Response,
+        _ctx: &MailerliteClientContext<Tz>,
+    ) -> Result<Self::Response, MailerliteError>
+    where
+        Self::Response: DeserializeOwned,
+        Tz: TimeZone + Send + Sync,
+    {
+        handle_delete_response(response).await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -580,4 +1114,119 @@ mod tests {
 
         assert_eq!(&expected, request.url());
     }
+
+    #[test]
+    fn test_creates_write_group_requests() {
+        let create = WriteGroupRequestBuilder::default()
+            .name("Newsletter".to_string())
+            .build()
+            .unwrap()
+            .to_request_builder("https://localhost:1234/api", &Client::new(), &ctx())
+            .build()
+            .unwrap();
+
+        assert_eq!(&reqwest::Method::POST, create.method());
+        assert_eq!("https://localhost:1234/api/groups", create.url().as_str());
+        assert_eq!(
+            r#"{"name":"Newsletter"}"#,
+            std::str::from_utf8(create.body().unwrap().as_bytes().unwrap()).unwrap()
+        );
+
+        let update = WriteGroupRequestBuilder::default()
+            .group_id("1234".to_string())
+            .name("Newsletter".to_string())
+            .build()
+            .unwrap()
+            .to_request_builder("https://localhost:1234/api", &Client::new(), &ctx())
+            .build()
+            .unwrap();
+
+        assert_eq!(&reqwest::Method::PUT, update.method());
+        assert_eq!("https://localhost:1234/api/groups/1234", update.url().as_str());
+        assert_eq!(
+            r#"{"name":"Newsletter"}"#,
+            std::str::from_utf8(update.body().unwrap().as_bytes().unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_creates_unassign_subscriber_from_group_request() {
+        let req = UnassignSubscriberFromGroupRequest {
+            subscriber_id: "5678".to_string(),
+            group_id: "1234".to_string(),
+        };
+
+        let request = req
+            .to_request_builder("https://localhost:1234/api", &Client::new(), &ctx())
+            .build()
+            .unwrap();
+
+        assert_eq!(&reqwest::Method::DELETE, request.method());
+        assert_eq!(
+            "https://localhost:1234/api/subscribers/5678/groups/1234",
+            request.url().as_str()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_request_handles_empty_response() {
+        let req = DeleteFieldRequest {
+            field_id: "1".to_string(),
+        };
+
+        let response: reqwest::Response = http::response::Response::builder()
+            .status(http::status::StatusCode::NO_CONTENT)
+            .body("")
+            .unwrap()
+            .into();
+
+        match req.handle_response(response, &ctx()).await.unwrap() {
+            DeleteResponse::Success => {}
+            other => panic!("Expected a successful delete, but instead received {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_creates_field_request() {
+        let req = CreateFieldRequest {
+            name: "Company size".to_string(),
+            field_type: FieldType::Number,
+        };
+
+        let request = req
+            .to_request_builder("https://localhost:1234/api", &Client::new(), &ctx())
+            .build()
+            .unwrap();
+
+        assert_eq!(&reqwest::Method::POST, request.method());
+        assert_eq!(
+            r#"{"name":"Company size","type":"number"}"#,
+            std::str::from_utf8(request.body().unwrap().as_bytes().unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_creates_webhook_request() {
+        let req = CreateWebhookRequestBuilder::default()
+            .name("cio".to_string())
+            .events(vec![
+                WebhookEventType::SubscriberCreated,
+                WebhookEventType::SubscriberAddedToGroup,
+            ])
+            .url("https://localhost:5678/mailerlite".to_string())
+            .build()
+            .unwrap();
+
+        let request = req
+            .to_request_builder("https://localhost:1234/api", &Client::new(), &ctx())
+            .build()
+            .unwrap();
+
+        assert_eq!(&reqwest::Method::POST, request.method());
+        assert_eq!("https://localhost:1234/api/webhooks", request.url().as_str());
+        assert_eq!(
+            r#"{"name":"cio","events":["subscriber.created","subscriber.added_to_group"],"url":"https://localhost:5678/mailerlite"}"#,
+            std::str::from_utf8(request.body().unwrap().as_bytes().unwrap()).unwrap()
+        );
+    }
 }
@@ -73,6 +73,11 @@ where
         self.base_url = base_url;
     }
 
+    /// The time zone that the account reports its dates in
+    pub fn time_zone(&self) -> &Tz {
+        &self.context.time_zone
+    }
+
     fn auth(&self, builder: RequestBuilder) -> RequestBuilder {
         builder.bearer_auth(self.bearer.expose_secret())
     }
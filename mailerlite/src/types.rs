@@ -75,6 +75,7 @@ pub struct ApiSubscriber {
     created_at: FormattedDateTime,
     updated_at: FormattedDateTime,
     fields: SubscriberFields,
+    #[serde(default)]
     groups: Vec<ApiSubscriberGroup>,
     opted_in_at: Option<FormattedDateTime>,
     optin_ip: Option<String>,
@@ -146,6 +147,111 @@ impl ApiSubscriberGroup {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Segment {
+    pub id: String,
+    pub name: String,
+    /// The number of subscribers currently matching the segment
+    pub total: u64,
+}
+
+/// A custom field that can be set on subscribers. The `key` is the name used in
+/// [`SubscriberFields`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Field {
+    pub id: String,
+    pub name: String,
+    pub key: String,
+    #[serde(rename = "type")]
+    pub field_type: FieldType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    Text,
+    Number,
+    Date,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    pub events: Vec<WebhookEventType>,
+    pub enabled: bool,
+    /// The secret used to sign each delivery to this webhook
+    #[serde(default)]
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WebhookEventType {
+    #[serde(rename = "subscriber.created")]
+    SubscriberCreated,
+    #[serde(rename = "subscriber.updated")]
+    SubscriberUpdated,
+    #[serde(rename = "subscriber.unsubscribed")]
+    SubscriberUnsubscribed,
+    #[serde(rename = "subscriber.added_to_group")]
+    SubscriberAddedToGroup,
+    #[serde(rename = "subscriber.removed_from_group")]
+    SubscriberRemovedFromGroup,
+    #[serde(rename = "subscriber.bounced")]
+    SubscriberBounced,
+    #[serde(rename = "subscriber.spam_reported")]
+    SubscriberSpamReported,
+    #[serde(rename = "campaign.sent")]
+    CampaignSent,
+    #[serde(other)]
+    Other,
+}
+
+/// A delivery to a webhook. Batchable webhooks deliver multiple events in a single request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ApiWebhookPayload {
+    Batch { events: Vec<ApiWebhookEvent> },
+    Single(ApiWebhookEvent),
+}
+
+impl ApiWebhookPayload {
+    pub fn into_events(self, tz: &impl TimeZone) -> Result<Vec<WebhookEvent>, FailedToTranslateDateError> {
+        match self {
+            ApiWebhookPayload::Batch { events } => events.into_iter().map(|e| e.into_event(tz)).collect(),
+            ApiWebhookPayload::Single(event) => Ok(vec![event.into_event(tz)?]),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookEvent {
+    pub event_type: WebhookEventType,
+    pub subscriber: Subscriber,
+    /// The group that the subscriber was added to or removed from for group events
+    pub group: Option<SubscriberGroup>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiWebhookEvent {
+    #[serde(rename = "type")]
+    event_type: WebhookEventType,
+    #[serde(flatten)]
+    subscriber: ApiSubscriber,
+    group: Option<ApiSubscriberGroup>,
+}
+
+impl ApiWebhookEvent {
+    pub fn into_event(self, tz: &impl TimeZone) -> Result<WebhookEvent, FailedToTranslateDateError> {
+        Ok(WebhookEvent {
+            event_type: self.event_type,
+            subscriber: self.subscriber.into_subscriber(tz)?,
+            group: self.group.map(|g| g.into_group(tz)).transpose()?,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Error)]
 pub struct FailedToTranslateDateError;
 
@@ -253,4 +359,53 @@ mod tests {
         assert_eq!(converted.updated_at, expected_date);
         assert_eq!(converted.opted_in_at, Some(expected_date));
     }
+
+    #[test]
+    fn test_parses_webhook_group_event() {
+        let body = r#"{
+    "id": "31986843064993537",
+    "email": "test-email@test-domain.com",
+    "status": "active",
+    "source": "api",
+    "sent": 0,
+    "opens_count": 0,
+    "clicks_count": 0,
+    "open_rate": 0,
+    "click_rate": 0,
+    "ip_address": null,
+    "subscribed_at": "2021-09-01 14:03:50",
+    "unsubscribed_at": null,
+    "created_at": "2021-09-01 14:03:50",
+    "updated_at": "2021-09-01 14:03:50",
+    "fields": {
+        "name": "Dummy"
+    },
+    "opted_in_at": null,
+    "optin_ip": null,
+    "type": "subscriber.added_to_group",
+    "group": {
+        "id": "1234",
+        "name": "Newsletter",
+        "active_count": 1,
+        "sent_count": 0,
+        "opens_count": 0,
+        "open_rate": { "float": 0, "string": "0%" },
+        "clicks_count": 0,
+        "click_rate": { "float": 0, "string": "0%" },
+        "unsubscribed_count": 0,
+        "unconfirmed_count": 0,
+        "bounced_count": 0,
+        "junk_count": 0,
+        "created_at": "2021-09-01 14:03:50"
+    }
+}"#;
+
+        let payload: ApiWebhookPayload = serde_json::from_str(body).unwrap();
+        let events = payload.into_events(&Utc).unwrap();
+
+        assert_eq!(1, events.len());
+        assert_eq!(WebhookEventType::SubscriberAddedToGroup, events[0].event_type);
+        assert_eq!("test-email@test-domain.com", events[0].subscriber.email);
+        assert_eq!("Newsletter", events[0].group.as_ref().unwrap().name);
+    }
 }
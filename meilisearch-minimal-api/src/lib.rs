@@ -2,7 +2,7 @@ use chrono::{DateTime, Utc};
 use reqwest::{Client, StatusCode};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 pub struct MeiliClient {
     inner: Arc<InnerClient>,
@@ -12,6 +12,8 @@ pub struct MeiliClient {
 pub enum MeiliError {
     Client(reqwest::Error),
     FailedToParseResponse(serde_json::Error),
+    TaskFailed(Box<Task>),
+    TaskTimedOut(u32),
 }
 
 impl std::fmt::Display for MeiliError {
@@ -19,6 +21,14 @@ impl std::fmt::Display for MeiliError {
         match self {
             MeiliError::Client(inner) => write!(f, "Client error: {inner}"),
             MeiliError::FailedToParseResponse(inner) => write!(f, "Failed to parse response: {inner}"),
+            MeiliError::TaskFailed(task) => write!(
+                f,
+                "Task {} finished with status {:?}: {}",
+                task.uid,
+                task.status,
+                task.error.as_ref().map(|e| e.message.as_str()).unwrap_or_default()
+            ),
+            MeiliError::TaskTimedOut(uid) => write!(f, "Task {uid} did not finish in time"),
         }
     }
 }
@@ -28,6 +38,7 @@ impl std::error::Error for MeiliError {
         match self {
             MeiliError::Client(inner) => Some(inner),
             MeiliError::FailedToParseResponse(inner) => Some(inner),
+            _ => None,
         }
     }
 }
@@ -57,8 +68,45 @@ impl MeiliClient {
             id,
         }
     }
+
+    pub async fn get_task(&self, task_uid: u32) -> Result<Task, MeiliError> {
+        let response = self
+            .inner
+            .client
+            .get(format!("{}/tasks/{task_uid}", self.inner.url))
+            .bearer_auth(&self.inner.key)
+            .send()
+            .await?;
+
+        let content = response.text().await?;
+        Ok(serde_json::from_str::<Task>(&content)?)
+    }
+
+    /// Poll a task until it has finished processing. Returns an error if the task did not
+    /// succeed, or if it did not finish within the timeout.
+    pub async fn wait_for_task(&self, task_uid: u32, timeout: Duration) -> Result<Task, MeiliError> {
+        let started = tokio::time::Instant::now();
+
+        loop {
+            let task = self.get_task(task_uid).await?;
+
+            match task.status {
+                TaskStatus::Succeeded => return Ok(task),
+                TaskStatus::Failed | TaskStatus::Canceled => return Err(MeiliError::TaskFailed(Box::new(task))),
+                TaskStatus::Enqueued | TaskStatus::Processing => (),
+            }
+
+            if started.elapsed() >= timeout {
+                return Err(MeiliError::TaskTimedOut(task_uid));
+            }
+
+            tokio::time::sleep(TASK_POLL_INTERVAL).await;
+        }
+    }
 }
 
+const TASK_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
 pub struct IndexClient {
     inner: Arc<InnerClient>,
     id: String,
@@ -68,13 +116,72 @@ pub struct IndexClient {
 pub struct SearchQuery {
     #[serde(rename = "q", skip_serializing_if = "Option::is_none")]
     pub query: Option<String>,
+    /// Filter expressions that must all match, ie. `rfd_number = 123`. The attributes used must
+    /// be filterable in the index settings.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub filter: Option<Vec<String>>,
+    /// Attributes to return the distribution of values for across all matching documents. The
+    /// attributes used must be filterable in the index settings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facets: Option<Vec<String>>,
+    /// Sort expressions, ie. `rfd_number:desc`. The attributes used must be sortable in the
+    /// index settings.
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
+    #[serde(rename = "attributesToRetrieve", skip_serializing_if = "Option::is_none")]
     pub attributes_to_retrieve: Option<Vec<String>>,
+    #[serde(rename = "attributesToHighlight", skip_serializing_if = "Option::is_none")]
+    pub attributes_to_highlight: Option<Vec<String>>,
 }
 
 impl IndexClient {
+    pub async fn get_settings(&self) -> Result<IndexSettings, MeiliError> {
+        let response = self
+            .inner
+            .client
+            .get(format!("{}/indexes/{}/settings", self.inner.url, self.id))
+            .bearer_auth(&self.inner.key)
+            .send()
+            .await?;
+
+        let content = response.text().await?;
+        Ok(serde_json::from_str::<IndexSettings>(&content)?)
+    }
+
+    /// Update the settings of the index. Only the settings that are set are changed, and the
+    /// index is rebuilt in the background when they differ from the current settings.
+    pub async fn update_settings(&self, settings: &IndexSettings) -> Result<TaskResponse, MeiliError> {
+        let response = self
+            .inner
+            .client
+            .patch(format!("{}/indexes/{}/settings", self.inner.url, self.id))
+            .bearer_auth(&self.inner.key)
+            .json(settings)
+            .send()
+            .await?;
+
+        let content = response.text().await?;
+        Ok(serde_json::from_str::<TaskResponse>(&content)?)
+    }
+
+    /// Reset every setting of the index to the Meilisearch defaults.
+    pub async fn reset_settings(&self) -> Result<TaskResponse, MeiliError> {
+        let response = self
+            .inner
+            .client
+            .delete(format!("{}/indexes/{}/settings", self.inner.url, self.id))
+            .bearer_auth(&self.inner.key)
+            .send()
+            .await?;
+
+        let content = response.text().await?;
+        Ok(serde_json::from_str::<TaskResponse>(&content)?)
+    }
+
     pub async fn settings(&self, settings: IndexSettings) -> Result<serde_json::Value, MeiliError> {
         Ok(self
             .inner
@@ -102,7 +209,13 @@ impl IndexClient {
             .await?;
 
         if response.status() == StatusCode::NOT_FOUND {
-            Ok(SearchResponse { hits: vec![] })
+            Ok(SearchResponse {
+                hits: vec![],
+                facet_distribution: None,
+                estimated_total_hits: Some(0),
+                limit: None,
+                offset: None,
+            })
         } else {
             let content = response.text().await?;
             Ok(serde_json::from_str::<SearchResponse<T>>(&content)?)
@@ -145,6 +258,22 @@ impl IndexClient {
             .await?)
     }
 
+    /// Delete every document that matches the filter. The attributes used must be filterable
+    /// in the index settings.
+    pub async fn delete_documents_by_filter(&self, filter: &str) -> Result<TaskResponse, MeiliError> {
+        let response = self
+            .inner
+            .client
+            .post(format!("{}/indexes/{}/documents/delete", self.inner.url, self.id))
+            .bearer_auth(&self.inner.key)
+            .json(&serde_json::json!({ "filter": filter }))
+            .send()
+            .await?;
+
+        let content = response.text().await?;
+        Ok(serde_json::from_str::<TaskResponse>(&content)?)
+    }
+
     pub async fn delete(&self) -> Result<TaskResponse, MeiliError> {
         let response = self
             .inner
@@ -159,28 +288,88 @@ impl IndexClient {
     }
 }
 
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
 pub struct IndexSettings {
+    /// The attributes that are searched, in order of importance. `["*"]` searches every
+    /// attribute.
+    #[serde(rename = "searchableAttributes", skip_serializing_if = "Option::is_none")]
+    pub searchable_attributes: Option<Vec<String>>,
+    #[serde(rename = "displayedAttributes", skip_serializing_if = "Option::is_none")]
+    pub displayed_attributes: Option<Vec<String>>,
     #[serde(rename = "filterableAttributes", skip_serializing_if = "Option::is_none")]
     pub filterable_attributes: Option<Vec<String>>,
     #[serde(rename = "sortableAttributes", skip_serializing_if = "Option::is_none")]
     pub sortable_attributes: Option<Vec<String>>,
+    #[serde(rename = "rankingRules", skip_serializing_if = "Option::is_none")]
+    pub ranking_rules: Option<Vec<String>>,
+    #[serde(rename = "distinctAttribute", skip_serializing_if = "Option::is_none")]
+    pub distinct_attribute: Option<String>,
+    #[serde(rename = "stopWords", skip_serializing_if = "Option::is_none")]
+    pub stop_words: Option<Vec<String>>,
+    /// Words that are treated as equivalent to each other, keyed by word.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub synonyms: Option<HashMap<String, Vec<String>>>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct SearchResponse<T> {
     pub hits: Vec<T>,
+    /// The number of matching documents for each value of each requested facet.
+    #[serde(rename = "facetDistribution", default, skip_serializing_if = "Option::is_none")]
+    pub facet_distribution: Option<HashMap<String, HashMap<String, u64>>>,
+    #[serde(rename = "estimatedTotalHits", default, skip_serializing_if = "Option::is_none")]
+    pub estimated_total_hits: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct TaskResponse {
     #[serde(rename = "taskUid")]
-    task_uid: u32,
+    pub task_uid: u32,
+    #[serde(rename = "indexUid")]
+    pub index_uid: String,
+    pub status: String,
+    #[serde(rename = "enqueuedAt")]
+    pub enqueued_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TaskStatus {
+    Enqueued,
+    Processing,
+    Succeeded,
+    Failed,
+    Canceled,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TaskError {
+    pub message: String,
+    pub code: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Task {
+    pub uid: u32,
     #[serde(rename = "indexUid")]
-    index_uid: String,
-    status: String,
+    pub index_uid: Option<String>,
+    pub status: TaskStatus,
+    #[serde(rename = "type")]
+    pub type_: String,
+    #[serde(default)]
+    pub error: Option<TaskError>,
     #[serde(rename = "enqueuedAt")]
-    enqueued_at: DateTime<Utc>,
+    pub enqueued_at: DateTime<Utc>,
+    #[serde(rename = "startedAt", default)]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(rename = "finishedAt", default)]
+    pub finished_at: Option<DateTime<Utc>>,
 }
 
 struct InnerClient {
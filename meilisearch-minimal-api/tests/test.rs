@@ -1,4 +1,4 @@
-use meilisearch_minimal_api::{IndexClient, MeiliClient, SearchQuery};
+use meilisearch_minimal_api::{IndexClient, IndexSettings, MeiliClient, SearchQuery};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -86,3 +86,64 @@ async fn test_search() {
         assert!(delete_index_result.is_ok());
     }
 }
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FacetedDocument {
+    #[serde(rename = "objectID")]
+    object_id: String,
+    state: String,
+}
+
+#[tokio::test]
+async fn test_settings_and_faceted_search() {
+    if let Some(client) = TestDB::new("test_settings_and_faceted_search") {
+        let index = client.index_client();
+
+        let documents = vec![
+            FacetedDocument {
+                object_id: "doc1".to_string(),
+                state: "published".to_string(),
+            },
+            FacetedDocument {
+                object_id: "doc2".to_string(),
+                state: "published".to_string(),
+            },
+            FacetedDocument {
+                object_id: "doc3".to_string(),
+                state: "discussion".to_string(),
+            },
+        ];
+
+        let index_result = index.index_documents(&documents, "objectID").await;
+        assert!(index_result.is_ok());
+
+        let settings = IndexSettings {
+            filterable_attributes: Some(vec!["state".to_string()]),
+            sortable_attributes: Some(vec!["objectID".to_string()]),
+            ..Default::default()
+        };
+        let settings_result = index.update_settings(&settings).await;
+        assert!(settings_result.is_ok());
+
+        // Indexing and applying settings are asynchronous and can be slow
+        std::thread::sleep(std::time::Duration::from_secs(1));
+
+        let current = index.get_settings().await.unwrap();
+        assert_eq!(Some(vec!["state".to_string()]), current.filterable_attributes);
+
+        let search = SearchQuery {
+            filter: Some(vec!["state = published".to_string()]),
+            facets: Some(vec!["state".to_string()]),
+            sort: Some(vec!["objectID:desc".to_string()]),
+            ..Default::default()
+        };
+        let results = index.search::<FacetedDocument>(search).await.unwrap();
+
+        assert_eq!(2, results.hits.len());
+        assert_eq!("doc2", results.hits[0].object_id);
+        assert_eq!(Some(&2), results.facet_distribution.unwrap()["state"].get("published"));
+
+        let delete_index_result = index.delete().await;
+        assert!(delete_index_result.is_ok());
+    }
+}
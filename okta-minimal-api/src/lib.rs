@@ -0,0 +1,236 @@
+/*!
+ * A minimal client for the parts of the Okta API that the generated `okta` crate does not
+ * cover well, namely assigning users to applications.
+ *
+ * Okta docs: https://developer.okta.com/docs/reference/api/apps/
+ */
+use reqwest::{
+    header::{HeaderValue, LINK},
+    Client, Method, RequestBuilder, Response, StatusCode,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// The most results Okta returns in a single page.
+const PAGE_LIMIT: u32 = 200;
+
+#[derive(Debug)]
+pub enum OktaError {
+    Client(reqwest::Error),
+    FailedToParseResponse(serde_json::Error),
+    Api { status: StatusCode, body: String },
+}
+
+impl std::fmt::Display for OktaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OktaError::Client(inner) => write!(f, "Client error: {inner}"),
+            OktaError::FailedToParseResponse(inner) => write!(f, "Failed to parse response: {inner}"),
+            OktaError::Api { status, body } => write!(f, "Okta responded with {status}: {body}"),
+        }
+    }
+}
+
+impl std::error::Error for OktaError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            OktaError::Client(inner) => Some(inner),
+            OktaError::FailedToParseResponse(inner) => Some(inner),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for OktaError {
+    fn from(value: reqwest::Error) -> OktaError {
+        OktaError::Client(value)
+    }
+}
+
+impl From<serde_json::Error> for OktaError {
+    fn from(value: serde_json::Error) -> OktaError {
+        OktaError::FailedToParseResponse(value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Application {
+    pub id: String,
+    /// The name the application is shown with in the Okta dashboard.
+    pub label: String,
+    /// The name of the integration the application was created from, ie. `slack`.
+    #[serde(default)]
+    pub name: String,
+    /// Either `ACTIVE` or `INACTIVE`.
+    #[serde(default)]
+    pub status: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AppUser {
+    /// The id of the Okta user.
+    pub id: String,
+    /// `USER` when the user was assigned directly, `GROUP` when they were assigned through one
+    /// of their groups.
+    #[serde(default)]
+    pub scope: String,
+    #[serde(default)]
+    pub status: String,
+}
+
+#[derive(Debug, Serialize)]
+struct AssignUserRequest<'a> {
+    id: &'a str,
+    scope: &'a str,
+}
+
+pub struct OktaClient {
+    /// The url of the Okta organization, ie. `https://company.okta.com`.
+    endpoint: String,
+    key: String,
+    client: Client,
+}
+
+impl OktaClient {
+    pub fn new<E, K>(endpoint: E, key: K) -> Self
+    where
+        E: ToString,
+        K: ToString,
+    {
+        Self {
+            endpoint: endpoint.to_string().trim_end_matches('/').to_string(),
+            key: key.to_string(),
+            client: Client::new(),
+        }
+    }
+
+    fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        // Okta uses its own authorization scheme for API tokens.
+        let auth = HeaderValue::from_str(&format!("SSWS {}", self.key)).expect("API key is a valid header value");
+
+        self.client
+            .request(method, url)
+            .header(reqwest::header::AUTHORIZATION, auth)
+            .header(reqwest::header::ACCEPT, "application/json")
+    }
+
+    async fn check(response: Response) -> Result<Response, OktaError> {
+        if response.status().is_success() {
+            Ok(response)
+        } else {
+            Err(OktaError::Api {
+                status: response.status(),
+                body: response.text().await?,
+            })
+        }
+    }
+
+    /// Follow the `next` links of a listing until every page has been fetched.
+    async fn list_all<T>(&self, path: &str) -> Result<Vec<T>, OktaError>
+    where
+        T: DeserializeOwned,
+    {
+        let mut results = vec![];
+        let mut next = Some(format!("{}{}?limit={}", self.endpoint, path, PAGE_LIMIT));
+
+        while let Some(url) = next {
+            let response = Self::check(self.request(Method::GET, &url).send().await?).await?;
+
+            next = response
+                .headers()
+                .get_all(LINK)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .find_map(next_link);
+
+            let content = response.text().await?;
+            results.append(&mut serde_json::from_str::<Vec<T>>(&content)?);
+        }
+
+        Ok(results)
+    }
+
+    pub async fn list_apps(&self) -> Result<Vec<Application>, OktaError> {
+        self.list_all("/api/v1/apps").await
+    }
+
+    /// The users that are assigned to an application, directly or through a group.
+    pub async fn list_app_users(&self, app_id: &str) -> Result<Vec<AppUser>, OktaError> {
+        self.list_all(&format!("/api/v1/apps/{app_id}/users")).await
+    }
+
+    /// Assign a user directly to an application. Assigning a user that is already assigned
+    /// updates the existing assignment.
+    pub async fn assign_user_to_app(&self, app_id: &str, user_id: &str) -> Result<AppUser, OktaError> {
+        let response = self
+            .request(Method::POST, &format!("{}/api/v1/apps/{app_id}/users", self.endpoint))
+            .json(&AssignUserRequest {
+                id: user_id,
+                scope: "USER",
+            })
+            .send()
+            .await?;
+
+        let content = Self::check(response).await?.text().await?;
+        Ok(serde_json::from_str::<AppUser>(&content)?)
+    }
+
+    pub async fn unassign_user_from_app(&self, app_id: &str, user_id: &str) -> Result<(), OktaError> {
+        let response = self
+            .request(
+                Method::DELETE,
+                &format!("{}/api/v1/apps/{app_id}/users/{user_id}", self.endpoint),
+            )
+            .send()
+            .await?;
+
+        Self::check(response).await?;
+        Ok(())
+    }
+}
+
+/// The url of a `Link` header value with `rel="next"`, ie.
+/// `<https://company.okta.com/api/v1/apps?after=abc&limit=200>; rel="next"`.
+fn next_link(value: &str) -> Option<String> {
+    value.split(',').find_map(|link| {
+        let mut parts = link.split(';').map(|part| part.trim());
+        let url = parts.next()?.strip_prefix('<')?.strip_suffix('>')?;
+
+        parts.any(|part| part == "rel=\"next\"").then(|| url.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{next_link, AppUser, Application};
+
+    #[test]
+    fn test_next_link() {
+        assert_eq!(
+            Some("https://company.okta.com/api/v1/apps?after=abc&limit=200".to_string()),
+            next_link("<https://company.okta.com/api/v1/apps?after=abc&limit=200>; rel=\"next\"")
+        );
+        assert_eq!(
+            Some("https://company.okta.com/api/v1/apps?after=abc".to_string()),
+            next_link(
+                "<https://company.okta.com/api/v1/apps?limit=200>; rel=\"self\", <https://company.okta.com/api/v1/apps?after=abc>; rel=\"next\""
+            )
+        );
+        assert_eq!(
+            None,
+            next_link("<https://company.okta.com/api/v1/apps?limit=200>; rel=\"self\"")
+        );
+    }
+
+    #[test]
+    fn test_parse_app_listing() {
+        let apps: Vec<Application> = serde_json::from_str(
+            r#"[{"id": "0oa1", "label": "Slack", "name": "slack", "status": "ACTIVE", "signOnMode": "SAML_2_0"}]"#,
+        )
+        .unwrap();
+        assert_eq!("Slack", apps[0].label);
+
+        let users: Vec<AppUser> =
+            serde_json::from_str(r#"[{"id": "00u1", "scope": "GROUP", "status": "PROVISIONED"}]"#).unwrap();
+        assert_eq!("GROUP", users[0].scope);
+    }
+}
@@ -0,0 +1,494 @@
+/*!
+ * A minimal client for the parts of the PagerDuty REST API that we use: users, teams,
+ * escalation policies, schedules and on-calls. Alerts are sent through the separate Events API,
+ * which is authenticated by the routing key of the service they are for rather than an API key.
+ *
+ * PagerDuty docs: https://developer.pagerduty.com/api-reference/
+ */
+use chrono::{DateTime, Utc};
+use reqwest::{
+    header::{HeaderValue, ACCEPT, AUTHORIZATION},
+    Client, Method, RequestBuilder, Response, StatusCode,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+const ENDPOINT: &str = "https://api.pagerduty.com";
+
+const EVENTS_ENDPOINT: &str = "https://events.pagerduty.com/v2/enqueue";
+
+/// The most results PagerDuty returns in a single page.
+const PAGE_LIMIT: u32 = 100;
+
+#[derive(Debug)]
+pub enum PagerDutyError {
+    Client(reqwest::Error),
+    FailedToParseResponse(serde_json::Error),
+    Api { status: StatusCode, body: String },
+}
+
+impl std::fmt::Display for PagerDutyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PagerDutyError::Client(inner) => write!(f, "Client error: {inner}"),
+            PagerDutyError::FailedToParseResponse(inner) => write!(f, "Failed to parse response: {inner}"),
+            PagerDutyError::Api { status, body } => write!(f, "PagerDuty responded with {status}: {body}"),
+        }
+    }
+}
+
+impl std::error::Error for PagerDutyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PagerDutyError::Client(inner) => Some(inner),
+            PagerDutyError::FailedToParseResponse(inner) => Some(inner),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for PagerDutyError {
+    fn from(value: reqwest::Error) -> PagerDutyError {
+        PagerDutyError::Client(value)
+    }
+}
+
+impl From<serde_json::Error> for PagerDutyError {
+    fn from(value: serde_json::Error) -> PagerDutyError {
+        PagerDutyError::FailedToParseResponse(value)
+    }
+}
+
+/// A reference to another object, ie. the schedule of an escalation rule.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Reference {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub summary: String,
+}
+
+impl Reference {
+    pub fn new<T>(id: T, type_: &str) -> Self
+    where
+        T: ToString,
+    {
+        Reference {
+            id: id.to_string(),
+            type_: type_.to_string(),
+            summary: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct User {
+    pub id: String,
+    pub name: String,
+    pub email: String,
+    #[serde(default)]
+    pub role: String,
+    #[serde(default)]
+    pub teams: Vec<Reference>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NewUser {
+    pub name: String,
+    pub email: String,
+    /// One of `admin`, `limited_user`, `observer`, `owner`, `read_only_user`,
+    /// `restricted_access`, `read_only_limited_user` or `user`.
+    pub role: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub time_zone: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Team {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TeamMember {
+    pub user: Reference,
+    /// One of `observer`, `responder` or `manager`.
+    pub role: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Schedule {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub time_zone: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct EscalationRule {
+    pub escalation_delay_in_minutes: i64,
+    pub targets: Vec<Reference>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct EscalationPolicy {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    #[serde(rename = "type", default = "escalation_policy_type")]
+    pub type_: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub escalation_rules: Vec<EscalationRule>,
+    #[serde(default)]
+    pub teams: Vec<Reference>,
+}
+
+fn escalation_policy_type() -> String {
+    "escalation_policy".to_string()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct OnCall {
+    pub user: Reference,
+    pub schedule: Option<Reference>,
+    pub escalation_policy: Reference,
+    pub escalation_level: i32,
+    /// Empty for people that are always on call.
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+}
+
+/// The details of an alert sent to the Events API.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AlertPayload {
+    pub summary: String,
+    /// Where the alert came from, ie. the name of the host or service.
+    pub source: String,
+    /// One of `critical`, `error`, `warning` or `info`.
+    pub severity: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_details: Option<serde_json::Value>,
+}
+
+#[derive(Serialize)]
+struct AlertRequest<'a> {
+    routing_key: &'a str,
+    event_action: &'a str,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    dedup_key: &'a str,
+    payload: &'a AlertPayload,
+}
+
+/// Open an incident on the service that `routing_key` belongs to. Alerts with the same
+/// `dedup_key` are grouped into the incident that is already open for it.
+pub async fn trigger_alert(routing_key: &str, dedup_key: &str, payload: &AlertPayload) -> Result<(), PagerDutyError> {
+    let response = Client::new()
+        .post(EVENTS_ENDPOINT)
+        .json(&AlertRequest {
+            routing_key,
+            event_action: "trigger",
+            dedup_key,
+            payload,
+        })
+        .send()
+        .await?;
+
+    PagerDutyClient::check(response).await?;
+
+    Ok(())
+}
+
+pub struct PagerDutyClient {
+    key: String,
+    /// The email of the user that changes are made on behalf of, PagerDuty requires one for
+    /// some writes.
+    from: String,
+    client: Client,
+}
+
+impl PagerDutyClient {
+    pub fn new<K, F>(key: K, from: F) -> Self
+    where
+        K: ToString,
+        F: ToString,
+    {
+        Self {
+            key: key.to_string(),
+            from: from.to_string(),
+            client: Client::new(),
+        }
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let auth =
+            HeaderValue::from_str(&format!("Token token={}", self.key)).expect("API key is a valid header value");
+
+        self.client
+            .request(method, format!("{ENDPOINT}{path}"))
+            .header(AUTHORIZATION, auth)
+            .header(ACCEPT, "application/vnd.pagerduty+json;version=2")
+            .header("From", &self.from)
+    }
+
+    async fn check(response: Response) -> Result<Response, PagerDutyError> {
+        if response.status().is_success() {
+            Ok(response)
+        } else {
+            Err(PagerDutyError::Api {
+                status: response.status(),
+                body: response.text().await?,
+            })
+        }
+    }
+
+    async fn send<T>(&self, request: RequestBuilder) -> Result<T, PagerDutyError>
+    where
+        T: DeserializeOwned,
+    {
+        let content = Self::check(request.send().await?).await?.text().await?;
+        Ok(serde_json::from_str::<T>(&content)?)
+    }
+
+    /// Page through a listing, which PagerDuty returns wrapped in an object under `key`.
+    async fn list_all<T>(&self, path: &str, key: &str, query: &[(&str, String)]) -> Result<Vec<T>, PagerDutyError>
+    where
+        T: DeserializeOwned,
+    {
+        let mut results = vec![];
+        let mut offset = 0;
+
+        loop {
+            let page: serde_json::Value = self
+                .send(
+                    self.request(Method::GET, path)
+                        .query(query)
+                        .query(&[("limit", PAGE_LIMIT), ("offset", offset)]),
+                )
+                .await?;
+
+            let items = page.get(key).cloned().unwrap_or_else(|| serde_json::json!([]));
+            let mut items: Vec<T> = serde_json::from_value(items)?;
+            offset += items.len() as u32;
+            results.append(&mut items);
+
+            if !page.get("more").and_then(|more| more.as_bool()).unwrap_or(false) {
+                return Ok(results);
+            }
+        }
+    }
+
+    pub async fn list_users(&self) -> Result<Vec<User>, PagerDutyError> {
+        self.list_all("/users", "users", &[]).await
+    }
+
+    pub async fn create_user(&self, user: &NewUser) -> Result<User, PagerDutyError> {
+        let response: UserResponse = self
+            .send(self.request(Method::POST, "/users").json(&UserRequest { user }))
+            .await?;
+        Ok(response.user)
+    }
+
+    pub async fn list_teams(&self) -> Result<Vec<Team>, PagerDutyError> {
+        self.list_all("/teams", "teams", &[]).await
+    }
+
+    pub async fn create_team(&self, name: &str, description: &str) -> Result<Team, PagerDutyError> {
+        let response: TeamResponse = self
+            .send(self.request(Method::POST, "/teams").json(&TeamRequest {
+                team: NewTeam {
+                    type_: "team",
+                    name,
+                    description,
+                },
+            }))
+            .await?;
+        Ok(response.team)
+    }
+
+    pub async fn list_team_members(&self, team_id: &str) -> Result<Vec<TeamMember>, PagerDutyError> {
+        self.list_all(&format!("/teams/{team_id}/members"), "members", &[])
+            .await
+    }
+
+    /// Add a user to a team, or change their role on it.
+    pub async fn add_user_to_team(&self, team_id: &str, user_id: &str, role: &str) -> Result<(), PagerDutyError> {
+        let response = self
+            .request(Method::PUT, &format!("/teams/{team_id}/users/{user_id}"))
+            .json(&TeamMembershipRequest { role })
+            .send()
+            .await?;
+
+        Self::check(response).await?;
+        Ok(())
+    }
+
+    pub async fn remove_user_from_team(&self, team_id: &str, user_id: &str) -> Result<(), PagerDutyError> {
+        let response = self
+            .request(Method::DELETE, &format!("/teams/{team_id}/users/{user_id}"))
+            .send()
+            .await?;
+
+        Self::check(response).await?;
+        Ok(())
+    }
+
+    pub async fn list_schedules(&self) -> Result<Vec<Schedule>, PagerDutyError> {
+        self.list_all("/schedules", "schedules", &[]).await
+    }
+
+    pub async fn list_escalation_policies(&self) -> Result<Vec<EscalationPolicy>, PagerDutyError> {
+        self.list_all("/escalation_policies", "escalation_policies", &[]).await
+    }
+
+    pub async fn create_escalation_policy(
+        &self,
+        policy: &EscalationPolicy,
+    ) -> Result<EscalationPolicy, PagerDutyError> {
+        let response: EscalationPolicyResponse = self
+            .send(
+                self.request(Method::POST, "/escalation_policies")
+                    .json(&EscalationPolicyRequest {
+                        escalation_policy: policy,
+                    }),
+            )
+            .await?;
+        Ok(response.escalation_policy)
+    }
+
+    pub async fn update_escalation_policy(
+        &self,
+        policy: &EscalationPolicy,
+    ) -> Result<EscalationPolicy, PagerDutyError> {
+        let response: EscalationPolicyResponse = self
+            .send(
+                self.request(Method::PUT, &format!("/escalation_policies/{}", policy.id))
+                    .json(&EscalationPolicyRequest {
+                        escalation_policy: policy,
+                    }),
+            )
+            .await?;
+        Ok(response.escalation_policy)
+    }
+
+    /// The people that are on call right now for the given schedules.
+    pub async fn list_oncalls(&self, schedule_ids: &[String]) -> Result<Vec<OnCall>, PagerDutyError> {
+        let query: Vec<(&str, String)> = schedule_ids
+            .iter()
+            .map(|id| ("schedule_ids[]", id.to_string()))
+            .collect();
+
+        self.list_all("/oncalls", "oncalls", &query).await
+    }
+}
+
+#[derive(Serialize)]
+struct UserRequest<'a> {
+    user: &'a NewUser,
+}
+
+#[derive(Deserialize)]
+struct UserResponse {
+    user: User,
+}
+
+#[derive(Serialize)]
+struct NewTeam<'a> {
+    #[serde(rename = "type")]
+    type_: &'a str,
+    name: &'a str,
+    description: &'a str,
+}
+
+#[derive(Serialize)]
+struct TeamRequest<'a> {
+    team: NewTeam<'a>,
+}
+
+#[derive(Deserialize)]
+struct TeamResponse {
+    team: Team,
+}
+
+#[derive(Serialize)]
+struct TeamMembershipRequest<'a> {
+    role: &'a str,
+}
+
+#[derive(Serialize)]
+struct EscalationPolicyRequest<'a> {
+    escalation_policy: &'a EscalationPolicy,
+}
+
+#[derive(Deserialize)]
+struct EscalationPolicyResponse {
+    escalation_policy: EscalationPolicy,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AlertPayload, AlertRequest, EscalationPolicy, OnCall};
+
+    #[test]
+    fn test_parse_oncall() {
+        let oncalls: Vec<OnCall> = serde_json::from_str(
+            r#"[{
+                "user": {"id": "PUSER1", "type": "user_reference", "summary": "Jane Doe"},
+                "schedule": {"id": "PSCHED1", "type": "schedule_reference", "summary": "Infra primary"},
+                "escalation_policy": {"id": "PPOLICY1", "type": "escalation_policy_reference"},
+                "escalation_level": 1,
+                "start": "2023-03-20T17:00:00Z",
+                "end": "2023-03-27T17:00:00Z"
+            }, {
+                "user": {"id": "PUSER2", "type": "user_reference"},
+                "schedule": null,
+                "escalation_policy": {"id": "PPOLICY1", "type": "escalation_policy_reference"},
+                "escalation_level": 2,
+                "start": null,
+                "end": null
+            }]"#,
+        )
+        .unwrap();
+
+        assert_eq!("Jane Doe", oncalls[0].user.summary);
+        assert_eq!("PSCHED1", oncalls[0].schedule.as_ref().unwrap().id);
+        assert!(oncalls[1].schedule.is_none());
+        assert!(oncalls[1].end.is_none());
+    }
+
+    #[test]
+    fn test_serialize_new_escalation_policy() {
+        let policy: EscalationPolicy = serde_json::from_str(
+            r#"{"name": "Infra", "escalation_rules": [{"escalation_delay_in_minutes": 30, "targets": [{"id": "PSCHED1", "type": "schedule_reference"}]}]}"#,
+        )
+        .unwrap();
+
+        let value = serde_json::to_value(&policy).unwrap();
+        assert_eq!("escalation_policy", value["type"]);
+        assert!(value.get("id").is_none());
+    }
+
+    #[test]
+    fn test_serialize_alert() {
+        let payload = AlertPayload {
+            summary: "Certificate for example.com expires soon".to_string(),
+            source: "cio".to_string(),
+            severity: "warning".to_string(),
+            custom_details: None,
+        };
+
+        let value = serde_json::to_value(AlertRequest {
+            routing_key: "R0UT1NG",
+            event_action: "trigger",
+            dedup_key: "",
+            payload: &payload,
+        })
+        .unwrap();
+        assert_eq!("trigger", value["event_action"]);
+        assert_eq!("warning", value["payload"]["severity"]);
+        assert!(value.get("dedup_key").is_none());
+        assert!(value["payload"].get("custom_details").is_none());
+    }
+}
@@ -9,6 +9,10 @@ use std::{
     str::from_utf8,
 };
 
+mod metadata;
+
+pub use metadata::{Author, Field, RfdFormat, RfdMetadata, Span};
+
 static PARSER: &str = include_str!("../parser/dist/index.js");
 
 #[derive(Debug, Deserialize, PartialEq)]
@@ -0,0 +1,382 @@
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// The markup language that an RFD is written in. Asciidoc RFDs store their metadata as
+/// document attributes (`:state: published`), while Markdown RFDs store it as front matter
+/// (`state: published`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum RfdFormat {
+    Asciidoc,
+    Markdown,
+}
+
+/// A byte range within the source of an RFD
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
+/// A metadata value along with where it was found in the source
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Field<T> {
+    pub value: T,
+    /// The whole line that the value was read from without its indentation, for instance
+    /// `:state: published`. Replacing this span replaces the attribute.
+    pub span: Span,
+    /// Only the value of the attribute, for instance `published`.
+    pub value_span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Author {
+    pub name: String,
+    pub email: Option<String>,
+}
+
+/// The metadata of an RFD. Fields are `None` when the RFD does not have the attribute at all,
+/// an attribute that is present but empty is returned with an empty value.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RfdMetadata {
+    pub authors: Option<Field<Vec<Author>>>,
+    pub labels: Option<Field<Vec<String>>>,
+    pub discussion: Option<Field<String>>,
+    pub state: Option<Field<String>>,
+    /// Who the RFD may be shown to: `public`, `internal` or `embargoed`
+    pub visibility: Option<Field<String>>,
+    /// The day an embargoed RFD becomes public, written as `2023-04-05`
+    pub embargoed_until: Option<Field<String>>,
+}
+
+impl RfdMetadata {
+    pub fn parse(content: &str, format: RfdFormat) -> Self {
+        let lines = match format {
+            RfdFormat::Asciidoc => lines(content),
+            RfdFormat::Markdown => front_matter(content).unwrap_or_else(|| lines(content)),
+        };
+
+        let attribute = |name: &str| find_attribute(&lines, format, name);
+
+        let authors = match format {
+            // Asciidoc authors are written on the line below the title, and that line usually
+            // refers back to the authors attribute
+            RfdFormat::Asciidoc => author_line(&lines)
+                .filter(|field| !field.value.is_empty() && field.value != "{authors}")
+                .or_else(|| attribute("authors")),
+            RfdFormat::Markdown => attribute("authors"),
+        };
+
+        RfdMetadata {
+            authors: authors.map(|field| field.map(|value| parse_authors(&value))),
+            labels: attribute("labels").map(|field| field.map(|value| split_list(&value))),
+            discussion: attribute("discussion"),
+            state: attribute("state"),
+            visibility: attribute("visibility"),
+            embargoed_until: attribute("embargoed-until"),
+        }
+    }
+}
+
+impl<T> Field<T> {
+    fn map<U, F>(self, f: F) -> Field<U>
+    where
+        F: FnOnce(T) -> U,
+    {
+        Field {
+            value: f(self.value),
+            span: self.span,
+            value_span: self.value_span,
+        }
+    }
+}
+
+/// A line of the source without its line ending, along with the offset it starts at
+struct Line<'a> {
+    offset: usize,
+    text: &'a str,
+}
+
+fn lines(content: &str) -> Vec<Line<'_>> {
+    let mut offset = 0;
+
+    content
+        .split_inclusive('\n')
+        .map(|line| {
+            let start = offset;
+            offset += line.len();
+
+            Line {
+                offset: start,
+                text: line.trim_end_matches(['\n', '\r']),
+            }
+        })
+        .collect()
+}
+
+/// The lines between the `---` delimiters at the start of a Markdown document
+fn front_matter(content: &str) -> Option<Vec<Line<'_>>> {
+    let mut lines = lines(content)
+        .into_iter()
+        .skip_while(|line| line.text.trim().is_empty());
+
+    if lines.next()?.text.trim() != "---" {
+        return None;
+    }
+
+    let mut front_matter = vec![];
+    for line in lines {
+        if line.text.trim() == "---" {
+            return Some(front_matter);
+        }
+
+        front_matter.push(line);
+    }
+
+    // Without a closing delimiter this is not front matter
+    None
+}
+
+fn find_attribute(lines: &[Line], format: RfdFormat, name: &str) -> Option<Field<String>> {
+    let key = match format {
+        RfdFormat::Asciidoc => format!(":{}:", name),
+        RfdFormat::Markdown => format!("{}:", name),
+    };
+
+    lines.iter().find_map(|line| {
+        let text = line.text.trim_start();
+        let start = line.offset + (line.text.len() - text.len());
+
+        text.strip_prefix(&key).map(|value| {
+            let value_start = start + key.len() + (value.len() - value.trim_start().len());
+            let value = value.trim();
+
+            Field {
+                value: value.to_string(),
+                span: Span {
+                    start,
+                    end: start + text.trim_end().len(),
+                },
+                value_span: Span {
+                    start: value_start,
+                    end: value_start + value.len(),
+                },
+            }
+        })
+    })
+}
+
+/// The line below the first title line of an Asciidoc document
+fn author_line(lines: &[Line]) -> Option<Field<String>> {
+    let title = lines
+        .iter()
+        .position(|line| line.text.starts_with('=') || line.text.starts_with('#'))?;
+    let line = lines.get(title + 1)?;
+
+    let text = line.text.trim();
+    let start = line.offset + (line.text.len() - line.text.trim_start().len());
+    let span = Span {
+        start,
+        end: start + text.len(),
+    };
+
+    Some(Field {
+        value: text.to_string(),
+        span,
+        value_span: span,
+    })
+}
+
+/// Splits an authors line like `Jane Doe <jane@company.com>, John Doe` into its authors.
+/// Commas within an email address are not treated as separators.
+fn parse_authors(value: &str) -> Vec<Author> {
+    let mut authors = vec![];
+    let mut current = String::new();
+    let mut in_email = false;
+
+    for c in value.chars().chain(std::iter::once(',')) {
+        match c {
+            '<' => in_email = true,
+            '>' => in_email = false,
+            ',' if !in_email => {
+                if let Some(author) = parse_author(&current) {
+                    authors.push(author);
+                }
+                current.clear();
+                continue;
+            }
+            _ => (),
+        }
+
+        current.push(c);
+    }
+
+    authors
+}
+
+fn parse_author(value: &str) -> Option<Author> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+
+    let author = match (value.find('<'), value.rfind('>')) {
+        (Some(start), Some(end)) if start < end => Author {
+            name: value[..start].trim().to_string(),
+            email: Some(value[start + 1..end].trim().to_string()).filter(|email| !email.is_empty()),
+        },
+        _ => Author {
+            name: value.to_string(),
+            email: None,
+        },
+    };
+
+    Some(author)
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_asciidoc_metadata() {
+        let content = r#":showtitle:
+:toc: left
+:state: published
+:discussion: https://github.com/organization/repo/pull/123
+:labels: networking, storage
+:revremark: State: {state} | {discussion}
+:authors: Firstname Lastname <author@organization.com>, Other Author
+
+= RFD 123 On Parsing Documents
+{authors}
+"#;
+        let metadata = RfdMetadata::parse(content, RfdFormat::Asciidoc);
+
+        let state = metadata.state.unwrap();
+        assert_eq!("published", state.value);
+        assert_eq!(":state: published", &content[state.span.range()]);
+        assert_eq!("published", &content[state.value_span.range()]);
+
+        assert_eq!(
+            "https://github.com/organization/repo/pull/123",
+            metadata.discussion.unwrap().value
+        );
+        assert_eq!(vec!["networking", "storage"], metadata.labels.unwrap().value);
+
+        let authors = metadata.authors.unwrap();
+        assert_eq!(
+            vec![
+                Author {
+                    name: "Firstname Lastname".to_string(),
+                    email: Some("author@organization.com".to_string()),
+                },
+                Author {
+                    name: "Other Author".to_string(),
+                    email: None,
+                },
+            ],
+            authors.value
+        );
+        assert_eq!(
+            "Firstname Lastname <author@organization.com>, Other Author",
+            &content[authors.value_span.range()]
+        );
+    }
+
+    #[test]
+    fn test_parse_asciidoc_authors_below_title() {
+        let content = ":state: ideation\n\n= RFD 12 Title\nJane <jane@company.com>, Joe <joe@company.com>\n";
+        let metadata = RfdMetadata::parse(content, RfdFormat::Asciidoc);
+
+        let authors = metadata.authors.unwrap();
+        assert_eq!(2, authors.value.len());
+        assert_eq!("Joe", authors.value[1].name);
+        assert_eq!(
+            "Jane <jane@company.com>, Joe <joe@company.com>",
+            &content[authors.span.range()]
+        );
+        assert!(metadata.labels.is_none());
+        assert!(metadata.discussion.is_none());
+        assert!(metadata.visibility.is_none());
+    }
+
+    #[test]
+    fn test_parse_visibility() {
+        let content = ":state: discussion\n:visibility: embargoed\n:embargoed-until: 2023-04-05\n\n= RFD 12 Title\n";
+        let metadata = RfdMetadata::parse(content, RfdFormat::Asciidoc);
+
+        assert_eq!("embargoed", metadata.visibility.unwrap().value);
+        let embargoed_until = metadata.embargoed_until.unwrap();
+        assert_eq!("2023-04-05", embargoed_until.value);
+        assert_eq!(":embargoed-until: 2023-04-05", &content[embargoed_until.span.range()]);
+
+        let content = "---\nvisibility: internal\n---\n\n# RFD 12 Title\n";
+        let metadata = RfdMetadata::parse(content, RfdFormat::Markdown);
+
+        assert_eq!("internal", metadata.visibility.unwrap().value);
+        assert!(metadata.embargoed_until.is_none());
+    }
+
+    #[test]
+    fn test_parse_markdown_front_matter() {
+        let content = "\n---\nauthors: Jane <jane@company.com>\nstate: discussion   \ndiscussion:\nlabels: api\n---\n\n# RFD 123\n\nstate: ignored\n";
+        let metadata = RfdMetadata::parse(content, RfdFormat::Markdown);
+
+        let state = metadata.state.unwrap();
+        assert_eq!("discussion", state.value);
+        assert_eq!("state: discussion", &content[state.span.range()]);
+
+        let discussion = metadata.discussion.unwrap();
+        assert_eq!("", discussion.value);
+        assert_eq!("discussion:", &content[discussion.span.range()]);
+
+        assert_eq!(vec!["api"], metadata.labels.unwrap().value);
+        assert_eq!(
+            Some("jane@company.com".to_string()),
+            metadata.authors.unwrap().value[0].email
+        );
+    }
+
+    #[test]
+    fn test_parse_markdown_without_front_matter() {
+        let content = "# RFD 1\n    state:   prediscussion\n:authors: nope\n";
+        let metadata = RfdMetadata::parse(content, RfdFormat::Markdown);
+
+        let state = metadata.state.unwrap();
+        assert_eq!("prediscussion", state.value);
+        assert_eq!("state:   prediscussion", &content[state.span.range()]);
+        assert!(metadata.authors.is_none());
+    }
+
+    #[test]
+    fn test_parse_authors() {
+        assert_eq!(
+            vec![
+                Author {
+                    name: "things".to_string(),
+                    email: None,
+                },
+                Author {
+                    name: "joe".to_string(),
+                    email: Some("joe@email.com".to_string()),
+                },
+            ],
+            parse_authors("things, joe <joe@email.com>,")
+        );
+        assert!(parse_authors("  ").is_empty());
+    }
+}
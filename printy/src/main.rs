@@ -1,19 +1,24 @@
 use std::{env, fs::File, io::Write, process::Command, str::from_utf8, sync::Arc};
 
+use anyhow::{anyhow, bail, Result};
 use dropshot::{
     endpoint, ApiDescription, ConfigDropshot, ConfigLogging, ConfigLoggingLevel, HttpError, HttpResponseAccepted,
-    HttpResponseOk, HttpServerStarter, RequestContext, TypedBody,
+    HttpResponseOk, HttpServerStarter, Path, RequestContext, TypedBody,
 };
 use dropshot_verify_request::bearer::Bearer;
 use log::{info, warn};
+use schemars::JsonSchema;
 use sentry::IntoDsn;
+use serde::Deserialize;
 use uuid::Uuid;
 
 use cio_api_types::swag_inventory::PrintRequest;
 
 mod bearer;
+mod queue;
 
 use bearer::EnvToken;
+use queue::{JobQueue, JobSource, PrintJob, Printer};
 
 #[tokio::main]
 async fn main() -> Result<(), String> {
@@ -99,6 +104,8 @@ async fn main() -> Result<(), String> {
     api.register(listen_print_receipt_requests).unwrap();
     api.register(listen_print_rollo_requests).unwrap();
     api.register(listen_print_zebra_requests).unwrap();
+    api.register(list_print_jobs).unwrap();
+    api.register(get_print_job).unwrap();
 
     let mut api_definition = &mut api.openapi("Print API", "0.0.1");
     api_definition = api_definition
@@ -114,7 +121,10 @@ async fn main() -> Result<(), String> {
     /*
      * The functions that implement our API endpoints will share this context.
      */
-    let api_context = Context::new(schema).await;
+    let queue = Arc::new(JobQueue::load().map_err(|error| format!("failed to load print queue: {error}"))?);
+    tokio::spawn(queue.clone().run());
+
+    let api_context = Context::new(schema, queue).await;
 
     /*
      * Set up the server.
@@ -131,14 +141,22 @@ async fn main() -> Result<(), String> {
  */
 struct Context {
     schema: String,
+    queue: Arc<JobQueue>,
 }
 
 impl Context {
     /**
      * Return a new Context.
      */
-    pub async fn new(schema: String) -> Context {
-        Context { schema }
+    pub async fn new(schema: String, queue: Arc<JobQueue>) -> Context {
+        Context { schema, queue }
+    }
+
+    fn enqueue(&self, printer: Printer, source: JobSource, quantity: i32) -> Result<String, HttpError> {
+        self.queue
+            .enqueue(printer, source, quantity)
+            .map(|job| job.id)
+            .map_err(|e| HttpError::for_internal_error(format!("failed to queue print job: {e}")))
     }
 }
 
@@ -177,26 +195,21 @@ async fn ping(_rqctx: Arc<RequestContext<Context>>) -> Result<HttpResponseOk<Str
     path = "/print/rollo",
 }]
 async fn listen_print_rollo_requests(
-    _rqctx: Arc<RequestContext<Context>>,
+    rqctx: Arc<RequestContext<Context>>,
     _auth: Bearer<EnvToken>,
     body_param: TypedBody<String>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
     sentry::start_session();
     let url = body_param.into_inner();
-    let printer = get_printer("rollo");
-    info!("printer {:?}", printer);
 
+    // Queue the label for the rollo printer, the response is the id of the print job.
+    let mut id = "ok".to_string();
     if !url.trim().is_empty() {
-        // Save the contents of our URL to a file.
-        let file = save_url_to_file(&url, "pdf").await;
-
-        // Print the file.
-        print_file(&printer, &file, "4.00x6.00", 1);
+        id = rqctx.context().enqueue(Printer::Rollo, JobSource::Url(url), 1)?;
     }
 
-    // Print the body to the rollo printer.
     sentry::end_session();
-    Ok(HttpResponseAccepted("ok".to_string()))
+    Ok(HttpResponseAccepted(id))
 }
 
 /** Listen for print requests for the Zebra label printer */
@@ -205,26 +218,23 @@ async fn listen_print_rollo_requests(
     path = "/print/zebra",
 }]
 async fn listen_print_zebra_requests(
-    _rqctx: Arc<RequestContext<Context>>,
+    rqctx: Arc<RequestContext<Context>>,
     _auth: Bearer<EnvToken>,
     body_param: TypedBody<PrintRequest>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
     sentry::start_session();
     let r = body_param.into_inner();
-    let printer = get_printer("zebra");
-    info!("printer {:?}", printer);
 
+    // Queue the label for the zebra printer, the response is the id of the print job.
+    let mut id = "ok".to_string();
     if !r.url.trim().is_empty() && r.quantity > 0 {
-        // Save the contents of our URL to a file.
-        let file = save_url_to_file(&r.url, "pdf").await;
-
-        // Print the file.
-        print_file(&printer, &file, "2.00x1.33", r.quantity);
+        id = rqctx
+            .context()
+            .enqueue(Printer::Zebra, JobSource::Url(r.url), r.quantity)?;
     }
 
-    // Print the body to the rollo printer.
     sentry::end_session();
-    Ok(HttpResponseAccepted("ok".to_string()))
+    Ok(HttpResponseAccepted(id))
 }
 
 /** Listen for print requests for the receipt printer */
@@ -233,26 +243,60 @@ async fn listen_print_zebra_requests(
     path = "/print/receipt",
 }]
 async fn listen_print_receipt_requests(
-    _rqctx: Arc<RequestContext<Context>>,
+    rqctx: Arc<RequestContext<Context>>,
     _auth: Bearer<EnvToken>,
     body_param: TypedBody<PrintRequest>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
     sentry::start_session();
     let r = body_param.into_inner();
-    let printer = get_printer("receipt");
-    info!("printer {:?}", printer);
 
+    // Queue the receipt, the response is the id of the print job.
+    let mut id = "ok".to_string();
     if !r.content.trim().is_empty() && r.quantity > 0 {
-        // Save the contents of our URL to a file.
-        let file = save_content_to_file(r.content.as_bytes(), "txt");
-
-        // Print the file.
-        print_file(&printer, &file, "", r.quantity);
+        id = rqctx
+            .context()
+            .enqueue(Printer::Receipt, JobSource::Content(r.content), r.quantity)?;
     }
 
-    // Print the body to the rollo printer.
     sentry::end_session();
-    Ok(HttpResponseAccepted("ok".to_string()))
+    Ok(HttpResponseAccepted(id))
+}
+
+/** List the queued print jobs along with the most recently finished ones */
+#[endpoint {
+    method = GET,
+    path = "/jobs",
+}]
+async fn list_print_jobs(
+    rqctx: Arc<RequestContext<Context>>,
+    _auth: Bearer<EnvToken>,
+) -> Result<HttpResponseOk<Vec<PrintJob>>, HttpError> {
+    Ok(HttpResponseOk(rqctx.context().queue.list()))
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct PrintJobPathParams {
+    id: String,
+}
+
+/** Get the status of a print job */
+#[endpoint {
+    method = GET,
+    path = "/jobs/{id}",
+}]
+async fn get_print_job(
+    rqctx: Arc<RequestContext<Context>>,
+    _auth: Bearer<EnvToken>,
+    path_params: Path<PrintJobPathParams>,
+) -> Result<HttpResponseOk<PrintJob>, HttpError> {
+    let id = path_params.into_inner().id;
+
+    rqctx
+        .context()
+        .queue
+        .get(&id)
+        .map(HttpResponseOk)
+        .ok_or_else(|| HttpError::for_not_found(None, format!("print job {id} was not found")))
 }
 
 // Return the printer we are looking for.
@@ -285,32 +329,35 @@ fn get_printer(name: &str) -> String {
 
 // Save URL contents to a temporary file.
 // Returns the filepath.
-async fn save_url_to_file(url: &str, ext: &str) -> String {
+async fn save_url_to_file(url: &str, ext: &str) -> Result<String> {
     info!("getting contents of URL `{}` to print", url);
-    let body = reqwest::get(url).await.unwrap().bytes().await.unwrap();
+    let body = reqwest::get(url).await?.error_for_status()?.bytes().await?;
 
     save_content_to_file(&body, ext)
 }
 
 // Save content to a temporary file.
 // Returns the filepath.
-fn save_content_to_file(body: &[u8], ext: &str) -> String {
+fn save_content_to_file(body: &[u8], ext: &str) -> Result<String> {
     let mut dir = env::temp_dir();
     let file_name = format!("{}.{}", Uuid::new_v4(), ext);
     dir.push(file_name);
 
-    let mut file = File::create(&dir).unwrap();
-    file.write_all(body).unwrap();
+    let mut file = File::create(&dir)?;
+    file.write_all(body)?;
 
-    let path = dir.to_str().unwrap().to_string();
+    let path = dir
+        .to_str()
+        .ok_or_else(|| anyhow!("temporary file path is not valid utf-8"))?
+        .to_string();
     info!("saved contents of URL to `{}`", path);
 
-    path
+    Ok(path)
 }
 
-// Save URL contents to a temporary file.
-// Returns the filepath.
-fn print_file(printer: &str, file: &str, media: &str, copies: i32) {
+// Send a file to the printer.
+// Returns the id of the CUPS job.
+fn print_file(printer: &str, file: &str, media: &str, copies: i32) -> Result<String> {
     info!("sending file `{}` to printer `{}`", file, printer);
     let output = if !media.is_empty() {
         Command::new("lp")
@@ -333,23 +380,31 @@ fn print_file(printer: &str, file: &str, media: &str, copies: i32) {
                 "page-bottom=0",
                 file,
             ])
-            .output()
-            .expect("failed to execute process")
+            .output()?
     } else {
         Command::new("lp")
             .args(["-d", printer, "-n", &format!("{copies}"), file])
-            .output()
-            .expect("failed to execute process")
+            .output()?
     };
     if !output.status.success() {
-        let e = format!(
-            "lpstat stderr: {}\nstdout: {}",
-            from_utf8(&output.stderr).unwrap(),
-            from_utf8(&output.stdout).unwrap()
+        bail!(
+            "lp stderr: {}\nstdout: {}",
+            from_utf8(&output.stderr)?,
+            from_utf8(&output.stdout)?
         );
-        warn!("{}", e);
-        return;
     }
 
-    info!("printing: {}", from_utf8(&output.stdout).unwrap());
+    let stdout = from_utf8(&output.stdout)?;
+    info!("printing: {}", stdout);
+
+    parse_cups_job_id(stdout).ok_or_else(|| anyhow!("could not find the job id in `{}`", stdout.trim()))
+}
+
+// lp reports the job it created as `request id is zebra-42 (1 file(s))`.
+fn parse_cups_job_id(output: &str) -> Option<String> {
+    output
+        .trim()
+        .strip_prefix("request id is ")
+        .and_then(|rest| rest.split_whitespace().next())
+        .map(|id| id.to_string())
 }
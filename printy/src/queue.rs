@@ -0,0 +1,351 @@
+use std::{
+    env, fs,
+    path::PathBuf,
+    process::Command,
+    str::from_utf8,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+use crate::{get_printer, print_file, save_content_to_file, save_url_to_file};
+
+/// How long to wait before trying again when the printer is offline or out of paper.
+const RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Jobs are failed after this many attempts, which gives someone about an hour to notice that
+/// the printer needs attention.
+const MAX_ATTEMPTS: u32 = 120;
+
+/// How often CUPS is checked to see if a submitted job has finished.
+const COMPLETION_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const COMPLETION_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// The number of finished jobs that are kept for the status API.
+const FINISHED_JOBS_TO_KEEP: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Printer {
+    Receipt,
+    Rollo,
+    Zebra,
+}
+
+impl Printer {
+    fn name(&self) -> &'static str {
+        match self {
+            Printer::Receipt => "receipt",
+            Printer::Rollo => "rollo",
+            Printer::Zebra => "zebra",
+        }
+    }
+
+    fn media(&self) -> &'static str {
+        match self {
+            Printer::Receipt => "",
+            Printer::Rollo => "4.00x6.00",
+            Printer::Zebra => "2.00x1.33",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Printing,
+    Done,
+    Failed,
+}
+
+#[derive(Debug, Clone, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobSource {
+    /// A PDF to download and print.
+    Url(String),
+    /// Plain text to print.
+    Content(String),
+}
+
+#[derive(Debug, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct PrintJob {
+    pub id: String,
+    pub printer: Printer,
+    pub source: JobSource,
+    pub quantity: i32,
+    pub status: JobStatus,
+    pub attempts: u32,
+    /// The reason the last attempt did not succeed.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+enum AttemptError {
+    /// The printer can not print right now, the job should be tried again later.
+    Offline(anyhow::Error),
+    /// The job itself is broken, trying again will not help.
+    Fatal(anyhow::Error),
+}
+
+/// A queue of print jobs that is saved to disk after every change, so that labels are not lost
+/// when the printer is out of paper or printy is restarted.
+pub struct JobQueue {
+    path: PathBuf,
+    jobs: Mutex<Vec<PrintJob>>,
+    notify: Notify,
+}
+
+impl JobQueue {
+    /// Load the queue from `PRINTY_QUEUE_FILE`. Jobs that were printing when printy stopped
+    /// are queued again.
+    pub fn load() -> Result<Self> {
+        let path = PathBuf::from(env::var("PRINTY_QUEUE_FILE").unwrap_or_else(|_| "printy-jobs.json".to_string()));
+
+        let mut jobs: Vec<PrintJob> = if path.exists() {
+            serde_json::from_slice(&fs::read(&path)?)?
+        } else {
+            vec![]
+        };
+
+        for job in jobs.iter_mut().filter(|job| job.status == JobStatus::Printing) {
+            job.status = JobStatus::Queued;
+        }
+
+        info!("loaded {} print jobs from `{}`", jobs.len(), path.display());
+
+        Ok(JobQueue {
+            path,
+            jobs: Mutex::new(jobs),
+            notify: Notify::new(),
+        })
+    }
+
+    pub fn enqueue(&self, printer: Printer, source: JobSource, quantity: i32) -> Result<PrintJob> {
+        let now = Utc::now();
+        let job = PrintJob {
+            id: Uuid::new_v4().to_string(),
+            printer,
+            source,
+            quantity,
+            status: JobStatus::Queued,
+            attempts: 0,
+            error: String::new(),
+            created_at: now,
+            updated_at: now,
+        };
+
+        {
+            let mut jobs = self.jobs.lock().unwrap();
+            jobs.push(job.clone());
+            self.save(&jobs)?;
+        }
+
+        info!("queued print job {} for the {} printer", job.id, printer.name());
+        self.notify.notify_one();
+
+        Ok(job)
+    }
+
+    pub fn get(&self, id: &str) -> Option<PrintJob> {
+        self.jobs.lock().unwrap().iter().find(|job| job.id == id).cloned()
+    }
+
+    pub fn list(&self) -> Vec<PrintJob> {
+        self.jobs.lock().unwrap().clone()
+    }
+
+    fn save(&self, jobs: &[PrintJob]) -> Result<()> {
+        // Write to a temporary file first so that a crash does not leave a partial queue.
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(jobs)?)?;
+        fs::rename(&tmp, &self.path)?;
+
+        Ok(())
+    }
+
+    fn update<F>(&self, id: &str, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut PrintJob),
+    {
+        let mut jobs = self.jobs.lock().unwrap();
+
+        if let Some(job) = jobs.iter_mut().find(|job| job.id == id) {
+            f(job);
+            job.updated_at = Utc::now();
+        }
+
+        // Drop the oldest finished jobs so the queue file does not grow forever.
+        let finished = jobs
+            .iter()
+            .filter(|job| matches!(job.status, JobStatus::Done | JobStatus::Failed))
+            .count();
+        if finished > FINISHED_JOBS_TO_KEEP {
+            let mut to_remove = finished - FINISHED_JOBS_TO_KEEP;
+            jobs.retain(|job| {
+                if to_remove > 0 && matches!(job.status, JobStatus::Done | JobStatus::Failed) {
+                    to_remove -= 1;
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+
+        self.save(&jobs)
+    }
+
+    fn next_queued(&self) -> Option<PrintJob> {
+        self.jobs
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|job| job.status == JobStatus::Queued)
+            .cloned()
+    }
+
+    /// Print queued jobs one at a time, in the order they were queued.
+    pub async fn run(self: Arc<Self>) {
+        loop {
+            let job = match self.next_queued() {
+                Some(job) => job,
+                None => {
+                    self.notify.notified().await;
+                    continue;
+                }
+            };
+
+            if let Err(e) = self.update(&job.id, |job| {
+                job.status = JobStatus::Printing;
+                job.attempts += 1;
+            }) {
+                warn!("failed to save print job {}: {}", job.id, e);
+            }
+
+            let result = attempt(&job).await;
+
+            let saved = match result {
+                Ok(()) => {
+                    info!("print job {} is done", job.id);
+                    self.update(&job.id, |job| {
+                        job.status = JobStatus::Done;
+                        job.error = String::new();
+                    })
+                }
+                Err(AttemptError::Offline(e)) if job.attempts + 1 < MAX_ATTEMPTS => {
+                    warn!("print job {} will be retried: {}", job.id, e);
+                    let saved = self.update(&job.id, |job| {
+                        job.status = JobStatus::Queued;
+                        job.error = e.to_string();
+                    });
+
+                    // Every job is waiting on the same printer, so there is no point moving on
+                    // to the next one.
+                    tokio::time::sleep(RETRY_DELAY).await;
+
+                    saved
+                }
+                Err(AttemptError::Offline(e)) | Err(AttemptError::Fatal(e)) => {
+                    warn!("print job {} failed: {}", job.id, e);
+                    self.update(&job.id, |job| {
+                        job.status = JobStatus::Failed;
+                        job.error = e.to_string();
+                    })
+                }
+            };
+
+            if let Err(e) = saved {
+                warn!("failed to save print job {}: {}", job.id, e);
+            }
+        }
+    }
+}
+
+async fn attempt(job: &PrintJob) -> Result<(), AttemptError> {
+    let file = match &job.source {
+        JobSource::Url(url) => save_url_to_file(url, "pdf").await.map_err(AttemptError::Offline)?,
+        JobSource::Content(content) => save_content_to_file(content.as_bytes(), "txt").map_err(AttemptError::Fatal)?,
+    };
+
+    let job = job.clone();
+    tokio::task::spawn_blocking(move || {
+        let result = print_and_wait(&job, &file);
+        let _ = fs::remove_file(&file);
+        result
+    })
+    .await
+    .map_err(|e| AttemptError::Fatal(e.into()))?
+}
+
+fn print_and_wait(job: &PrintJob, file: &str) -> Result<(), AttemptError> {
+    let printer = get_printer(job.printer.name());
+    if printer.is_empty() {
+        return Err(AttemptError::Offline(anyhow!(
+            "could not find the {} printer",
+            job.printer.name()
+        )));
+    }
+
+    ensure_printer_enabled(&printer).map_err(AttemptError::Offline)?;
+
+    let cups_job = print_file(&printer, file, job.printer.media(), job.quantity).map_err(AttemptError::Fatal)?;
+
+    let started = std::time::Instant::now();
+    while started.elapsed() < COMPLETION_TIMEOUT {
+        std::thread::sleep(COMPLETION_POLL_INTERVAL);
+
+        if lpstat(&["-W", "completed", "-o", &printer])
+            .map(|out| out.split_whitespace().any(|word| word == cups_job))
+            .unwrap_or(false)
+        {
+            return Ok(());
+        }
+
+        // CUPS stops the printer when it runs out of paper or goes offline. The job is
+        // cancelled so that it does not print twice once we retry it.
+        if let Err(e) = ensure_printer_enabled(&printer) {
+            cancel(&cups_job);
+            return Err(AttemptError::Offline(e));
+        }
+    }
+
+    cancel(&cups_job);
+    Err(AttemptError::Offline(anyhow!(
+        "job {} did not finish printing within {:?}",
+        cups_job,
+        COMPLETION_TIMEOUT
+    )))
+}
+
+fn ensure_printer_enabled(printer: &str) -> Result<()> {
+    let status = lpstat(&["-p", printer])?;
+    if status.contains("disabled") {
+        bail!("printer is disabled: {}", status.trim());
+    }
+
+    Ok(())
+}
+
+fn lpstat(args: &[&str]) -> Result<String> {
+    let output = Command::new("lpstat").args(args).output()?;
+    if !output.status.success() {
+        bail!("lpstat stderr: {}", from_utf8(&output.stderr)?);
+    }
+
+    Ok(from_utf8(&output.stdout)?.to_string())
+}
+
+fn cancel(cups_job: &str) {
+    if let Err(e) = Command::new("cancel").arg(cups_job).output() {
+        warn!("failed to cancel `{}`: {}", cups_job, e);
+    }
+}
@@ -20,18 +20,26 @@
  * }
  * ```
  */
+mod query;
+
 use std::{env, error, fmt, sync::Arc};
 
 use chrono::{DateTime, NaiveDate, Utc};
+use futures::{stream, Stream, TryStreamExt};
 use reqwest::{header, Client, Method, Request, StatusCode, Url};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+pub use crate::query::{Order, Query, QueryEntity, QueryValue};
+
 /// Endpoint for the QuickBooks API.
 const ENDPOINT: &str = "https://quickbooks.api.intuit.com/v3/";
 
 const QUERY_PAGE_SIZE: i64 = 1000;
 
+/// The most operations QuickBooks accepts in a single batch request.
+pub const BATCH_MAX_ITEMS: usize = 30;
+
 /// Entrypoint for interacting with the QuickBooks API.
 #[derive(Debug, Clone)]
 pub struct QuickBooks {
@@ -170,6 +178,15 @@ impl QuickBooks {
             .send()
             .await
             .unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
 
         // Unwrap the response.
         let t: AccessToken = resp.json().await.unwrap();
@@ -198,6 +215,15 @@ impl QuickBooks {
             .send()
             .await
             .unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
 
         // Unwrap the response.
         let t: AccessToken = resp.json().await.unwrap();
@@ -326,6 +352,131 @@ impl QuickBooks {
         Ok(r.query_response.attachable)
     }
 
+    /// Upload a file as an attachment, optionally linking it to an entity such as a
+    /// `Purchase` or `Bill` in the same request.
+    pub async fn upload_attachment(
+        &self,
+        file_name: &str,
+        content_type: &str,
+        content: Vec<u8>,
+        entity_ref: Option<NtRef>,
+    ) -> Result<Attachment, APIError> {
+        let metadata = AttachableUpload {
+            file_name: file_name.to_string(),
+            content_type: content_type.to_string(),
+            attachable_ref: entity_ref
+                .map(|entity_ref| {
+                    vec![AttachableRef {
+                        entity_ref,
+                        include_on_send: false,
+                    }]
+                })
+                .unwrap_or_default(),
+        };
+
+        let form = reqwest::multipart::Form::new()
+            .part(
+                "file_metadata_01",
+                reqwest::multipart::Part::text(serde_json::to_string(&metadata).unwrap())
+                    .mime_str("application/json")
+                    .unwrap(),
+            )
+            .part(
+                "file_content_01",
+                reqwest::multipart::Part::bytes(content)
+                    .file_name(file_name.to_string())
+                    .mime_str(content_type)
+                    .map_err(|e| APIError {
+                        status_code: StatusCode::BAD_REQUEST,
+                        body: format!("invalid content type `{content_type}`: {e}"),
+                    })?,
+            );
+
+        let url = Url::parse(ENDPOINT)
+            .unwrap()
+            .join(&format!("company/{}/upload", self.company_id))
+            .unwrap();
+
+        // The upload endpoint is multipart so we can not use our usual JSON request.
+        let resp = self
+            .client
+            .post(url)
+            .bearer_auth(&self.token)
+            .header(header::ACCEPT, "application/json")
+            .multipart(form)
+            .send()
+            .await
+            .unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        let r: AttachableUploadResponse = resp.json().await.unwrap();
+
+        match r.attachable_response.into_iter().next() {
+            Some(AttachableUploadResult {
+                attachable: Some(attachable),
+                ..
+            }) => Ok(attachable),
+            Some(AttachableUploadResult { fault, .. }) => Err(APIError {
+                status_code: StatusCode::BAD_REQUEST,
+                body: fault
+                    .map(|fault| fault.to_string())
+                    .unwrap_or_else(|| "upload returned no attachable".to_string()),
+            }),
+            None => Err(APIError {
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+                body: "upload returned no attachable".to_string(),
+            }),
+        }
+    }
+
+    /// Link an existing attachment to another entity, keeping any entities it is already
+    /// linked to.
+    pub async fn link_attachment(&self, attachment: &Attachment, entity_ref: NtRef) -> Result<Attachment, APIError> {
+        let mut attachable_ref = attachment.attachable_ref.clone();
+        attachable_ref.push(AttachableRef {
+            entity_ref,
+            include_on_send: false,
+        });
+
+        let update = AttachableLink {
+            id: attachment.id.to_string(),
+            sync_token: attachment.sync_token.to_string(),
+            sparse: true,
+            attachable_ref,
+        };
+
+        // Build the request.
+        let request = self.request(
+            Method::POST,
+            &format!("company/{}/attachable", self.company_id),
+            &update,
+            None,
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        let r: AttachableEntityResponse = resp.json().await.unwrap();
+
+        Ok(r.attachable)
+    }
+
     pub async fn get_bill(&self, bill_id: &str) -> Result<Bill, APIError> {
         // Build the request.
         let request = self.request(
@@ -351,6 +502,91 @@ impl QuickBooks {
         Ok(r.bill)
     }
 
+    pub async fn fetch_bill_page(&self, start_position: i64) -> Result<Vec<Bill>, APIError> {
+        // Build the request.
+        let request = self.request(
+            Method::GET,
+            &format!("company/{}/query", self.company_id),
+            (),
+            Some(&[(
+                "query",
+                &format!("SELECT * FROM Bill ORDERBY Id STARTPOSITION {start_position} MAXRESULTS {QUERY_PAGE_SIZE}"),
+            )]),
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        let r: BillQueryResponse = resp.json().await.unwrap();
+
+        Ok(r.query_response.bill)
+    }
+
+    pub async fn list_bills(&self) -> Result<Vec<Bill>, APIError> {
+        // Build the request.
+        let request = self.request(
+            Method::GET,
+            &format!("company/{}/query", self.company_id),
+            (),
+            Some(&[("query", "SELECT COUNT(*) FROM Bill")]),
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        let r: CountResponse = resp.json().await.unwrap();
+        let mut bills: Vec<Bill> = Vec::new();
+
+        let mut i = 0;
+        while i < r.query_response.total_count {
+            let mut page = self.fetch_bill_page(i + 1).await?;
+
+            // Add our page to our array.
+            bills.append(&mut page);
+
+            i += QUERY_PAGE_SIZE;
+        }
+
+        Ok(bills)
+    }
+
+    /// Create a bill. The bill needs at least a vendor and one line.
+    pub async fn create_bill(&self, bill: &Bill) -> Result<Bill, APIError> {
+        // Build the request.
+        let request = self.request(Method::POST, &format!("company/{}/bill", self.company_id), bill, None);
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        let r: BillResponse = resp.json().await.unwrap();
+
+        Ok(r.bill)
+    }
+
     pub async fn fetch_bill_payment_page(&self, start_position: i64) -> Result<Vec<BillPayment>, APIError> {
         // Build the request.
         let request = self.request(
@@ -417,6 +653,124 @@ impl QuickBooks {
         Ok(bill_payments)
     }
 
+    /// Create a bill payment. Each line links to the bill being paid with `LinkedTxn`.
+    pub async fn create_bill_payment(&self, bill_payment: &BillPayment) -> Result<BillPayment, APIError> {
+        // Build the request.
+        let request = self.request(
+            Method::POST,
+            &format!("company/{}/billpayment", self.company_id),
+            bill_payment,
+            None,
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        let r: BillPaymentEntityResponse = resp.json().await.unwrap();
+
+        Ok(r.bill_payment)
+    }
+
+    pub async fn fetch_vendor_credit_page(&self, start_position: i64) -> Result<Vec<VendorCredit>, APIError> {
+        // Build the request.
+        let request = self.request(
+            Method::GET,
+            &format!("company/{}/query", self.company_id),
+            (),
+            Some(&[(
+                "query",
+                &format!(
+                    "SELECT * FROM VendorCredit ORDERBY Id STARTPOSITION {start_position} MAXRESULTS {QUERY_PAGE_SIZE}"
+                ),
+            )]),
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        let r: VendorCreditQueryResponse = resp.json().await.unwrap();
+
+        Ok(r.query_response.vendor_credit)
+    }
+
+    pub async fn list_vendor_credits(&self) -> Result<Vec<VendorCredit>, APIError> {
+        // Build the request.
+        let request = self.request(
+            Method::GET,
+            &format!("company/{}/query", self.company_id),
+            (),
+            Some(&[("query", "SELECT COUNT(*) FROM VendorCredit")]),
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        let r: CountResponse = resp.json().await.unwrap();
+        let mut vendor_credits: Vec<VendorCredit> = Vec::new();
+
+        let mut i = 0;
+        while i < r.query_response.total_count {
+            let mut page = self.fetch_vendor_credit_page(i + 1).await?;
+
+            // Add our page to our array.
+            vendor_credits.append(&mut page);
+
+            i += QUERY_PAGE_SIZE;
+        }
+
+        Ok(vendor_credits)
+    }
+
+    /// Create a vendor credit. The credit needs at least a vendor and one line.
+    pub async fn create_vendor_credit(&self, vendor_credit: &VendorCredit) -> Result<VendorCredit, APIError> {
+        // Build the request.
+        let request = self.request(
+            Method::POST,
+            &format!("company/{}/vendorcredit", self.company_id),
+            vendor_credit,
+            None,
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        let r: VendorCreditResponse = resp.json().await.unwrap();
+
+        Ok(r.vendor_credit)
+    }
+
     pub async fn fetch_purchase_page(&self, start_position: i64) -> Result<Vec<Purchase>, APIError> {
         // Build the request.
         let request = self.request(
@@ -507,38 +861,409 @@ impl QuickBooks {
 
         Ok(items.query_response.item)
     }
-}
-
-/// Error type returned by our library.
-pub struct APIError {
-    pub status_code: StatusCode,
-    pub body: String,
-}
 
-impl fmt::Display for APIError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "APIError: status code -> {}, body -> {}",
-            self.status_code, self.body
-        )
+    /// List the classes used to track spend by cost center.
+    pub async fn list_classes(&self) -> Result<Vec<Class>, APIError> {
+        self.query_all(Query::new()).await
     }
-}
 
-impl fmt::Debug for APIError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "APIError: status code -> {}, body -> {}",
-            self.status_code, self.body
-        )
+    /// List the departments (called locations in some companies) transactions can be tagged with.
+    pub async fn list_departments(&self) -> Result<Vec<Department>, APIError> {
+        self.query_all(Query::new()).await
     }
-}
 
-// This is important for other errors to wrap this one.
-impl error::Error for APIError {
-    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
-        // Generic error, underlying cause isn't tracked.
+    /// Get the entities of the given types that changed since the given time. QuickBooks
+    /// only looks back 30 days and returns at most 1000 entities per type.
+    /// https://developer.intuit.com/app/developer/qbo/docs/api/accounting/all-entities/changedatacapture
+    pub async fn change_data_capture(
+        &self,
+        entities: &[&str],
+        changed_since: DateTime<Utc>,
+    ) -> Result<ChangedEntities, APIError> {
+        let entities = entities.join(",");
+        let changed_since = changed_since.to_rfc3339();
+
+        // Build the request.
+        let request = self.request(
+            Method::GET,
+            &format!("company/{}/cdc", self.company_id),
+            (),
+            Some(&[("entities", &entities), ("changedSince", &changed_since)]),
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        let r: CdcResponse = resp.json().await.unwrap();
+
+        let mut changes = ChangedEntities::default();
+        for query_response in r.cdc_response.into_iter().flat_map(|c| c.query_response) {
+            for purchase in query_response.purchase {
+                match purchase {
+                    Changed::Deleted(d) => changes.deleted.push(d.with_entity("Purchase")),
+                    Changed::Entity(p) => changes.purchases.push(p),
+                }
+            }
+            for bill_payment in query_response.bill_payment {
+                match bill_payment {
+                    Changed::Deleted(d) => changes.deleted.push(d.with_entity("BillPayment")),
+                    Changed::Entity(b) => changes.bill_payments.push(b),
+                }
+            }
+        }
+
+        Ok(changes)
+    }
+
+    /// Run a report, for example `ProfitAndLoss`, `BalanceSheet` or `AgedReceivables`.
+    /// https://developer.intuit.com/app/developer/qbo/docs/api/accounting/report-entities/profitandloss
+    pub async fn get_report(&self, report_name: &str, query: &ReportQuery) -> Result<Report, APIError> {
+        let start_date = query.start_date.map(|d| d.to_string()).unwrap_or_default();
+        let end_date = query.end_date.map(|d| d.to_string()).unwrap_or_default();
+        let class = query.class.join(",");
+        let department = query.department.join(",");
+
+        let mut params: Vec<(&str, &str)> = Vec::new();
+        if !start_date.is_empty() {
+            params.push(("start_date", &start_date));
+        }
+        if !end_date.is_empty() {
+            params.push(("end_date", &end_date));
+            // The aging reports are as of a single date.
+            params.push(("report_date", &end_date));
+        }
+        if !class.is_empty() {
+            params.push(("class", &class));
+        }
+        if !department.is_empty() {
+            params.push(("department", &department));
+        }
+        if !query.accounting_method.is_empty() {
+            params.push(("accounting_method", &query.accounting_method));
+        }
+        if !query.summarize_column_by.is_empty() {
+            params.push(("summarize_column_by", &query.summarize_column_by));
+        }
+
+        // Build the request.
+        let request = self.request(
+            Method::GET,
+            &format!("company/{}/reports/{report_name}", self.company_id),
+            (),
+            Some(params.as_slice()),
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(resp.json().await.unwrap())
+    }
+
+    pub async fn profit_and_loss(&self, query: &ReportQuery) -> Result<Report, APIError> {
+        self.get_report("ProfitAndLoss", query).await
+    }
+
+    pub async fn balance_sheet(&self, query: &ReportQuery) -> Result<Report, APIError> {
+        self.get_report("BalanceSheet", query).await
+    }
+
+    /// The accounts receivable aging summary as of the query's `end_date`.
+    pub async fn aged_receivables(&self, query: &ReportQuery) -> Result<Report, APIError> {
+        self.get_report("AgedReceivables", query).await
+    }
+
+    /// Fetch a single page of results for a query, starting at `start_position` (1-indexed).
+    pub async fn fetch_query_page<T: QueryEntity>(
+        &self,
+        query: &Query<T>,
+        start_position: i64,
+    ) -> Result<Vec<T>, APIError> {
+        // Build the request.
+        let request = self.request(
+            Method::GET,
+            &format!("company/{}/query", self.company_id),
+            (),
+            Some(&[("query", &query.to_page_sql(start_position))]),
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        let r: CountResponse = resp.json().await.unwrap();
+
+        Ok(T::from_query_response(r.query_response))
+    }
+
+    /// Run a query, fetching pages as the stream is consumed.
+    pub fn query<'a, T: QueryEntity + 'a>(&'a self, query: Query<T>) -> impl Stream<Item = Result<T, APIError>> + 'a {
+        let page_size = query.get_page_size();
+
+        stream::try_unfold((query, Some(1)), move |(query, start_position)| async move {
+            let start_position = match start_position {
+                Some(start_position) => start_position,
+                None => return Ok(None),
+            };
+
+            let page = self.fetch_query_page(&query, start_position).await?;
+
+            // A short page means there is nothing left to fetch.
+            let next = if (page.len() as i64) < page_size {
+                None
+            } else {
+                Some(start_position + page_size)
+            };
+
+            Ok(Some((page, (query, next))))
+        })
+        .map_ok(|page| stream::iter(page.into_iter().map(Ok)))
+        .try_flatten()
+    }
+
+    /// Run a query and collect every result.
+    pub async fn query_all<T: QueryEntity>(&self, query: Query<T>) -> Result<Vec<T>, APIError> {
+        self.query(query).try_collect().await
+    }
+
+    /// Run many operations with as few requests as possible. The items are sent in
+    /// batches of `BATCH_MAX_ITEMS` and the responses are returned in the same order.
+    /// An operation failing does not fail the batch, check each response's `fault`.
+    /// https://developer.intuit.com/app/developer/qbo/docs/api/accounting/all-entities/batch
+    pub async fn batch(&self, items: Vec<BatchItemRequest>) -> Result<Vec<BatchItemResponse>, APIError> {
+        let mut responses: Vec<BatchItemResponse> = Vec::new();
+
+        for chunk in items.chunks(BATCH_MAX_ITEMS) {
+            let body = BatchRequest {
+                batch_item_request: chunk.to_vec(),
+            };
+
+            // Build the request.
+            let request = self.request(Method::POST, &format!("company/{}/batch", self.company_id), &body, None);
+
+            let resp = self.client.execute(request).await.unwrap();
+            match resp.status() {
+                StatusCode::OK => (),
+                s => {
+                    return Err(APIError {
+                        status_code: s,
+                        body: resp.text().await.unwrap(),
+                    })
+                }
+            };
+
+            let mut r: BatchResponse = resp.json().await.unwrap();
+
+            // QuickBooks does not promise to keep the order, so put it back.
+            r.batch_item_response.sort_by_key(|response| {
+                chunk
+                    .iter()
+                    .position(|item| item.b_id == response.b_id)
+                    .unwrap_or(usize::MAX)
+            });
+
+            responses.append(&mut r.batch_item_response);
+        }
+
+        Ok(responses)
+    }
+
+    pub async fn get_invoice(&self, invoice_id: &str) -> Result<Invoice, APIError> {
+        // Build the request.
+        let request = self.request(
+            Method::GET,
+            &format!("company/{}/invoice/{invoice_id}", self.company_id),
+            (),
+            None,
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        let r: InvoiceResponse = resp.json().await.unwrap();
+
+        Ok(r.invoice)
+    }
+
+    /// Create an invoice. The invoice needs at least a customer and one line.
+    pub async fn create_invoice(&self, invoice: &Invoice) -> Result<Invoice, APIError> {
+        // Build the request.
+        let request = self.request(
+            Method::POST,
+            &format!("company/{}/invoice", self.company_id),
+            invoice,
+            None,
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        let r: InvoiceResponse = resp.json().await.unwrap();
+
+        Ok(r.invoice)
+    }
+
+    /// Update an invoice. This is a full update, so the invoice must include its
+    /// `Id` and latest `SyncToken`, and any field left out will be cleared.
+    pub async fn update_invoice(&self, invoice: &Invoice) -> Result<Invoice, APIError> {
+        // Build the request.
+        let request = self.request(
+            Method::POST,
+            &format!("company/{}/invoice", self.company_id),
+            invoice,
+            Some(&[("operation", "update")]),
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        let r: InvoiceResponse = resp.json().await.unwrap();
+
+        Ok(r.invoice)
+    }
+
+    /// Email an invoice to the customer. If `send_to` is empty the invoice is sent
+    /// to the invoice's `BillEmail`.
+    pub async fn send_invoice(&self, invoice_id: &str, send_to: &str) -> Result<Invoice, APIError> {
+        let query: Vec<(&str, &str)> = if send_to.is_empty() {
+            vec![]
+        } else {
+            vec![("sendTo", send_to)]
+        };
+
+        // Build the request.
+        let mut request = self.request(
+            Method::POST,
+            &format!("company/{}/invoice/{invoice_id}/send", self.company_id),
+            (),
+            Some(query.as_slice()),
+        );
+        // The send endpoint takes an empty octet-stream body rather than JSON.
+        *request.body_mut() = None;
+        request.headers_mut().insert(
+            header::CONTENT_TYPE,
+            header::HeaderValue::from_static("application/octet-stream"),
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        let r: InvoiceResponse = resp.json().await.unwrap();
+
+        Ok(r.invoice)
+    }
+
+    /// Create a sales receipt. The receipt needs at least one line.
+    pub async fn create_sales_receipt(&self, sales_receipt: &SalesReceipt) -> Result<SalesReceipt, APIError> {
+        // Build the request.
+        let request = self.request(
+            Method::POST,
+            &format!("company/{}/salesreceipt", self.company_id),
+            sales_receipt,
+            None,
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        let r: SalesReceiptResponse = resp.json().await.unwrap();
+
+        Ok(r.sales_receipt)
+    }
+}
+
+/// Error type returned by our library.
+pub struct APIError {
+    pub status_code: StatusCode,
+    pub body: String,
+}
+
+impl fmt::Display for APIError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "APIError: status code -> {}, body -> {}",
+            self.status_code, self.body
+        )
+    }
+}
+
+impl fmt::Debug for APIError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "APIError: status code -> {}, body -> {}",
+            self.status_code, self.body
+        )
+    }
+}
+
+// This is important for other errors to wrap this one.
+impl error::Error for APIError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        // Generic error, underlying cause isn't tracked.
         None
     }
 }
@@ -581,6 +1306,18 @@ pub struct QueryResponse {
     pub attachable: Vec<Attachment>,
     #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "BillPayment")]
     pub bill_payment: Vec<BillPayment>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "Bill")]
+    pub bill: Vec<Bill>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "VendorCredit")]
+    pub vendor_credit: Vec<VendorCredit>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "Invoice")]
+    pub invoice: Vec<Invoice>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "SalesReceipt")]
+    pub sales_receipt: Vec<SalesReceipt>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "Class")]
+    pub class: Vec<Class>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "Department")]
+    pub department: Vec<Department>,
     #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "CompanyInfo")]
     pub company_info: Vec<CompanyInfo>,
     #[serde(default, rename = "startPosition")]
@@ -678,6 +1415,20 @@ pub struct BillPaymentResponse {
     pub time: String,
 }
 
+#[derive(Debug, JsonSchema, Clone, Serialize, Deserialize)]
+pub struct BillQueryResponse {
+    #[serde(default, rename = "QueryResponse")]
+    pub query_response: QueryResponse,
+    pub time: String,
+}
+
+#[derive(Debug, JsonSchema, Clone, Serialize, Deserialize)]
+pub struct VendorCreditQueryResponse {
+    #[serde(default, rename = "QueryResponse")]
+    pub query_response: QueryResponse,
+    pub time: String,
+}
+
 #[derive(Debug, JsonSchema, Clone, Serialize, Deserialize)]
 pub struct AttachmentResponse {
     #[serde(default, rename = "QueryResponse")]
@@ -718,30 +1469,76 @@ pub struct Purchase {
     pub doc_number: String,
     #[serde(default, skip_serializing_if = "String::is_empty", rename = "PrivateNote")]
     pub private_note: String,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "DepartmentRef")]
+    pub department_ref: Option<NtRef>,
 }
 
+/// A line of a purchase, bill, bill payment or vendor credit. Every field QuickBooks documents
+/// for expense lines is modeled, so that the lines can be sent back in an update unchanged.
 #[derive(Debug, JsonSchema, Default, Clone, Serialize, Deserialize)]
 pub struct Line {
     #[serde(default, skip_serializing_if = "String::is_empty", rename = "Id")]
     pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "LineNum")]
+    pub line_num: Option<i64>,
     #[serde(default, skip_serializing_if = "String::is_empty", rename = "Description")]
     pub description: String,
     #[serde(default, rename = "Amount")]
-    pub amount: f32,
+    pub amount: f64,
     #[serde(default, skip_serializing_if = "String::is_empty", rename = "DetailType")]
     pub detail_type: String,
-    #[serde(default, rename = "AccountBasedExpenseLineDetail")]
-    pub account_based_expense_line_detail: AccountBasedExpenseLineDetail,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "AccountBasedExpenseLineDetail"
+    )]
+    pub account_based_expense_line_detail: Option<AccountBasedExpenseLineDetail>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "ItemBasedExpenseLineDetail"
+    )]
+    pub item_based_expense_line_detail: Option<ItemBasedExpenseLineDetail>,
     #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "LinkedTxn")]
     pub linked_txn: Vec<LinkedTxn>,
 }
 
+impl Line {
+    /// Whether the line is of a type that is modeled completely, so sending it back to QuickBooks
+    /// does not drop any of its fields.
+    pub fn round_trips(&self) -> bool {
+        match self.detail_type.as_str() {
+            "AccountBasedExpenseLineDetail" => self.account_based_expense_line_detail.is_some(),
+            "ItemBasedExpenseLineDetail" => self.item_based_expense_line_detail.is_some(),
+            _ => false,
+        }
+    }
+
+    /// The class of the line, if it is a line that can have one.
+    pub fn class_ref(&self) -> Option<&NtRef> {
+        if let Some(detail) = &self.account_based_expense_line_detail {
+            return detail.class_ref.as_ref();
+        }
+        if let Some(detail) = &self.item_based_expense_line_detail {
+            return detail.class_ref.as_ref();
+        }
+        None
+    }
+
+    /// Whether the line can have a class, the rest are things like linked payments.
+    pub fn has_class(&self) -> bool {
+        self.account_based_expense_line_detail.is_some() || self.item_based_expense_line_detail.is_some()
+    }
+}
+
 #[derive(Debug, JsonSchema, Default, Clone, Serialize, Deserialize)]
 pub struct LinkedTxn {
     #[serde(default, skip_serializing_if = "String::is_empty", rename = "TxnId")]
     pub txn_id: String,
     #[serde(default, skip_serializing_if = "String::is_empty", rename = "TxnType")]
     pub txn_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "TxnLineId")]
+    pub txn_line_id: Option<String>,
 }
 
 #[derive(Debug, JsonSchema, Default, Clone, Serialize, Deserialize)]
@@ -750,22 +1547,125 @@ pub struct AccountBasedExpenseLineDetail {
     pub account_ref: NtRef,
     #[serde(default, skip_serializing_if = "String::is_empty", rename = "BillableStatus")]
     pub billable_status: String,
-    #[serde(default, rename = "TaxCodeRef")]
-    pub tax_code_ref: NtRef,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "TaxCodeRef")]
+    pub tax_code_ref: Option<NtRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "ClassRef")]
+    pub class_ref: Option<NtRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "CustomerRef")]
+    pub customer_ref: Option<NtRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "MarkupInfo")]
+    pub markup_info: Option<MarkupInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "TaxAmount")]
+    pub tax_amount: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "TaxInclusiveAmt")]
+    pub tax_inclusive_amt: Option<f64>,
 }
 
 #[derive(Debug, JsonSchema, Default, Clone, Serialize, Deserialize)]
-pub struct PurchaseEx {
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub any: Vec<Any>,
+pub struct ItemBasedExpenseLineDetail {
+    #[serde(default, rename = "ItemRef")]
+    pub item_ref: NtRef,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "BillableStatus")]
+    pub billable_status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "TaxCodeRef")]
+    pub tax_code_ref: Option<NtRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "ClassRef")]
+    pub class_ref: Option<NtRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "CustomerRef")]
+    pub customer_ref: Option<NtRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "PriceLevelRef")]
+    pub price_level_ref: Option<NtRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "MarkupInfo")]
+    pub markup_info: Option<MarkupInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "Qty")]
+    pub qty: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "UnitPrice")]
+    pub unit_price: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "TaxInclusiveAmt")]
+    pub tax_inclusive_amt: Option<f64>,
 }
 
 #[derive(Debug, JsonSchema, Default, Clone, Serialize, Deserialize)]
-pub struct Any {
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub name: String,
-    #[serde(default, skip_serializing_if = "String::is_empty", rename = "declaredType")]
-    pub declared_type: String,
+pub struct MarkupInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "PriceLevelRef")]
+    pub price_level_ref: Option<NtRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "Percent")]
+    pub percent: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "MarkUpIncomeAccountRef")]
+    pub mark_up_income_account_ref: Option<NtRef>,
+}
+
+fn set_line_classes(lines: &mut [Line], class: &Class) {
+    for line in lines.iter_mut() {
+        if let Some(detail) = &mut line.account_based_expense_line_detail {
+            detail.class_ref = Some(class.into());
+        }
+        if let Some(detail) = &mut line.item_based_expense_line_detail {
+            detail.class_ref = Some(class.into());
+        }
+    }
+}
+
+/// A sparse update of a purchase or bill. Only the fields that are set are changed, the rest of
+/// the transaction is left as it is in QuickBooks. The lines are replaced as a whole even in a
+/// sparse update, so when they are set every one of them must be sent.
+#[derive(Debug, JsonSchema, Default, Clone, Serialize, Deserialize)]
+pub struct SparseTransaction {
+    #[serde(rename = "Id")]
+    pub id: String,
+    #[serde(rename = "SyncToken")]
+    pub sync_token: String,
+    pub sparse: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "Line")]
+    pub line: Vec<Line>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "DepartmentRef")]
+    pub department_ref: Option<NtRef>,
+}
+
+impl SparseTransaction {
+    pub fn new(id: &str, sync_token: &str) -> Self {
+        SparseTransaction {
+            id: id.to_string(),
+            sync_token: sync_token.to_string(),
+            sparse: true,
+            ..Default::default()
+        }
+    }
+
+    /// Attribute every line of the transaction to the given class. This returns false, and
+    /// changes nothing, if any of the lines would not round trip.
+    pub fn set_class(&mut self, lines: &[Line], class: &Class) -> bool {
+        if !lines.iter().all(Line::round_trips) {
+            return false;
+        }
+
+        self.line = lines.to_vec();
+        set_line_classes(&mut self.line, class);
+        true
+    }
+
+    pub fn set_department(&mut self, department: &Department) {
+        self.department_ref = Some(department.into());
+    }
+
+    /// Whether the update would not change anything.
+    pub fn is_empty(&self) -> bool {
+        self.line.is_empty() && self.department_ref.is_none()
+    }
+}
+
+#[derive(Debug, JsonSchema, Default, Clone, Serialize, Deserialize)]
+pub struct PurchaseEx {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub any: Vec<Any>,
+}
+
+#[derive(Debug, JsonSchema, Default, Clone, Serialize, Deserialize)]
+pub struct Any {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "declaredType")]
+    pub declared_type: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub scope: String,
     #[serde(default)]
@@ -786,6 +1686,8 @@ pub struct Attachment {
     pub file_access_uri: String,
     #[serde(default, skip_serializing_if = "String::is_empty", rename = "TempDownloadUri")]
     pub temp_download_uri: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "ContentType")]
+    pub content_type: String,
     #[serde(default, rename = "Size")]
     pub size: i64,
     #[serde(default, skip_serializing_if = "String::is_empty")]
@@ -810,6 +1712,52 @@ pub struct AttachableRef {
     pub include_on_send: bool,
 }
 
+/// The metadata sent along with a file upload.
+#[derive(Debug, JsonSchema, Default, Clone, Serialize, Deserialize)]
+pub struct AttachableUpload {
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "FileName")]
+    pub file_name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "ContentType")]
+    pub content_type: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "AttachableRef")]
+    pub attachable_ref: Vec<AttachableRef>,
+}
+
+/// A sparse update of the entities an attachment is linked to.
+#[derive(Debug, JsonSchema, Default, Clone, Serialize, Deserialize)]
+pub struct AttachableLink {
+    #[serde(rename = "Id")]
+    pub id: String,
+    #[serde(rename = "SyncToken")]
+    pub sync_token: String,
+    #[serde(default)]
+    pub sparse: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "AttachableRef")]
+    pub attachable_ref: Vec<AttachableRef>,
+}
+
+#[derive(Debug, JsonSchema, Clone, Serialize, Deserialize)]
+pub struct AttachableUploadResponse {
+    #[serde(default, rename = "AttachableResponse")]
+    pub attachable_response: Vec<AttachableUploadResult>,
+    pub time: String,
+}
+
+#[derive(Debug, JsonSchema, Clone, Serialize, Deserialize)]
+pub struct AttachableUploadResult {
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "Attachable")]
+    pub attachable: Option<Attachment>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "Fault")]
+    pub fault: Option<Fault>,
+}
+
+#[derive(Debug, JsonSchema, Clone, Serialize, Deserialize)]
+pub struct AttachableEntityResponse {
+    #[serde(rename = "Attachable")]
+    pub attachable: Attachment,
+    pub time: DateTime<Utc>,
+}
+
 #[derive(Debug, JsonSchema, Clone, Serialize, Deserialize)]
 pub struct BillPayment {
     #[serde(default, rename = "VendorRef")]
@@ -828,8 +1776,8 @@ pub struct BillPayment {
     pub id: String,
     #[serde(default, skip_serializing_if = "String::is_empty", rename = "SyncToken")]
     pub sync_token: String,
-    #[serde(rename = "MetaData")]
-    pub meta_data: MetaData,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "MetaData")]
+    pub meta_data: Option<MetaData>,
     #[serde(default, skip_serializing_if = "String::is_empty", rename = "DocNumber")]
     pub doc_number: String,
     #[serde(rename = "TxnDate")]
@@ -861,7 +1809,7 @@ pub struct BillResponse {
     pub time: DateTime<Utc>,
 }
 
-#[derive(Debug, JsonSchema, Clone, Serialize, Deserialize)]
+#[derive(Debug, Default, JsonSchema, Clone, Serialize, Deserialize)]
 pub struct Bill {
     #[serde(default, skip_serializing_if = "String::is_empty", rename = "SyncToken")]
     pub sync_token: String,
@@ -873,8 +1821,8 @@ pub struct Bill {
     pub ap_account_ref: NtRef,
     #[serde(default, rename = "VendorRef")]
     pub vendor_ref: NtRef,
-    #[serde(rename = "TxnDate")]
-    pub txn_date: NaiveDate,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "TxnDate")]
+    pub txn_date: Option<NaiveDate>,
     #[serde(default, rename = "TotalAmt")]
     pub total_amt: f64,
     #[serde(default, rename = "CurrencyRef")]
@@ -883,16 +1831,125 @@ pub struct Bill {
     pub linked_txn: Vec<LinkedTxn>,
     #[serde(default, rename = "SalesTermRef")]
     pub sales_term_ref: NtRef,
-    #[serde(rename = "DueDate")]
-    pub due_date: NaiveDate,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "DueDate")]
+    pub due_date: Option<NaiveDate>,
     #[serde(default)]
     pub sparse: bool,
     #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "Line")]
     pub line: Vec<Line>,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "DocNumber")]
+    pub doc_number: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "PrivateNote")]
+    pub private_note: String,
     #[serde(default, rename = "Balance")]
-    pub balance: i64,
-    #[serde(rename = "MetaData")]
-    pub meta_data: MetaData,
+    pub balance: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "DepartmentRef")]
+    pub department_ref: Option<NtRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "MetaData")]
+    pub meta_data: Option<MetaData>,
+}
+
+#[derive(Debug, Default, JsonSchema, Clone, Serialize, Deserialize)]
+pub struct Class {
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "Id")]
+    pub id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "SyncToken")]
+    pub sync_token: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "Name")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "FullyQualifiedName")]
+    pub fully_qualified_name: String,
+    #[serde(default, rename = "SubClass")]
+    pub sub_class: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "ParentRef")]
+    pub parent_ref: Option<NtRef>,
+    #[serde(default, rename = "Active")]
+    pub active: bool,
+}
+
+impl From<&Class> for NtRef {
+    fn from(class: &Class) -> Self {
+        NtRef {
+            value: class.id.to_string(),
+            name: class.fully_qualified_name.to_string(),
+            entity_ref_type: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Default, JsonSchema, Clone, Serialize, Deserialize)]
+pub struct Department {
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "Id")]
+    pub id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "SyncToken")]
+    pub sync_token: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "Name")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "FullyQualifiedName")]
+    pub fully_qualified_name: String,
+    #[serde(default, rename = "SubDepartment")]
+    pub sub_department: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "ParentRef")]
+    pub parent_ref: Option<NtRef>,
+    #[serde(default, rename = "Active")]
+    pub active: bool,
+}
+
+impl From<&Department> for NtRef {
+    fn from(department: &Department) -> Self {
+        NtRef {
+            value: department.id.to_string(),
+            name: department.fully_qualified_name.to_string(),
+            entity_ref_type: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, JsonSchema, Clone, Serialize, Deserialize)]
+pub struct BillPaymentEntityResponse {
+    #[serde(rename = "BillPayment")]
+    pub bill_payment: BillPayment,
+    pub time: DateTime<Utc>,
+}
+
+#[derive(Debug, JsonSchema, Clone, Serialize, Deserialize)]
+pub struct VendorCreditResponse {
+    #[serde(rename = "VendorCredit")]
+    pub vendor_credit: VendorCredit,
+    pub time: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, JsonSchema, Clone, Serialize, Deserialize)]
+pub struct VendorCredit {
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "SyncToken")]
+    pub sync_token: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub domain: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "Id")]
+    pub id: String,
+    #[serde(default, rename = "APAccountRef")]
+    pub ap_account_ref: NtRef,
+    #[serde(default, rename = "VendorRef")]
+    pub vendor_ref: NtRef,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "TxnDate")]
+    pub txn_date: Option<NaiveDate>,
+    #[serde(default, rename = "TotalAmt")]
+    pub total_amt: f64,
+    /// The amount of the credit that has not been applied to a bill yet.
+    #[serde(default, rename = "Balance")]
+    pub balance: f64,
+    #[serde(default, rename = "CurrencyRef")]
+    pub currency_ref: NtRef,
+    #[serde(default)]
+    pub sparse: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "Line")]
+    pub line: Vec<Line>,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "DocNumber")]
+    pub doc_number: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "PrivateNote")]
+    pub private_note: String,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "MetaData")]
+    pub meta_data: Option<MetaData>,
 }
 
 #[derive(Debug, JsonSchema, Clone, Serialize, Deserialize)]
@@ -965,3 +2022,620 @@ pub struct PrimaryPhone {
 
 #[derive(Debug, Default, JsonSchema, Clone, Serialize, Deserialize)]
 pub struct WebAddr {}
+
+#[derive(Debug, JsonSchema, Clone, Serialize, Deserialize)]
+pub struct InvoiceResponse {
+    #[serde(rename = "Invoice")]
+    pub invoice: Invoice,
+    pub time: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, JsonSchema, Clone, Serialize, Deserialize)]
+pub struct Invoice {
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "Id")]
+    pub id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "SyncToken")]
+    pub sync_token: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub domain: String,
+    #[serde(default)]
+    pub sparse: bool,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "DocNumber")]
+    pub doc_number: String,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "TxnDate")]
+    pub txn_date: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "DueDate")]
+    pub due_date: Option<NaiveDate>,
+    #[serde(default, rename = "CustomerRef")]
+    pub customer_ref: NtRef,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "CurrencyRef")]
+    pub currency_ref: Option<NtRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "SalesTermRef")]
+    pub sales_term_ref: Option<NtRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "BillEmail")]
+    pub bill_email: Option<Email>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "BillAddr")]
+    pub bill_addr: Option<Addr>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "Line")]
+    pub line: Vec<InvoiceLine>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "CustomerMemo")]
+    pub customer_memo: Option<NtRef>,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "PrivateNote")]
+    pub private_note: String,
+    /// One of `NotSet`, `NeedToSend` or `EmailSent`.
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "EmailStatus")]
+    pub email_status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "DeliveryInfo")]
+    pub delivery_info: Option<DeliveryInfo>,
+    #[serde(default, rename = "TotalAmt")]
+    pub total_amt: f64,
+    #[serde(default, rename = "Balance")]
+    pub balance: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "MetaData")]
+    pub meta_data: Option<MetaData>,
+}
+
+#[derive(Debug, Default, JsonSchema, Clone, Serialize, Deserialize)]
+pub struct InvoiceLine {
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "Id")]
+    pub id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "Description")]
+    pub description: String,
+    #[serde(default, rename = "Amount")]
+    pub amount: f64,
+    /// This is `SalesItemLineDetail` for a line item, `SubTotalLineDetail` for
+    /// the subtotal QuickBooks adds to every invoice.
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "DetailType")]
+    pub detail_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "SalesItemLineDetail")]
+    pub sales_item_line_detail: Option<SalesItemLineDetail>,
+}
+
+#[derive(Debug, Default, JsonSchema, Clone, Serialize, Deserialize)]
+pub struct SalesItemLineDetail {
+    #[serde(default, rename = "ItemRef")]
+    pub item_ref: NtRef,
+    #[serde(default, rename = "Qty")]
+    pub qty: f64,
+    #[serde(default, rename = "UnitPrice")]
+    pub unit_price: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "ServiceDate")]
+    pub service_date: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "TaxCodeRef")]
+    pub tax_code_ref: Option<NtRef>,
+}
+
+#[derive(Debug, Default, JsonSchema, Clone, Serialize, Deserialize)]
+pub struct DeliveryInfo {
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "DeliveryType")]
+    pub delivery_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "DeliveryTime")]
+    pub delivery_time: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, JsonSchema, Clone, Serialize, Deserialize)]
+pub struct SalesReceiptResponse {
+    #[serde(rename = "SalesReceipt")]
+    pub sales_receipt: SalesReceipt,
+    pub time: DateTime<Utc>,
+}
+
+/// A sale that was paid for at the time it was made, as opposed to an invoice which is paid
+/// later on.
+#[derive(Debug, Default, JsonSchema, Clone, Serialize, Deserialize)]
+pub struct SalesReceipt {
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "Id")]
+    pub id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "SyncToken")]
+    pub sync_token: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "DocNumber")]
+    pub doc_number: String,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "TxnDate")]
+    pub txn_date: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "CustomerRef")]
+    pub customer_ref: Option<NtRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "BillEmail")]
+    pub bill_email: Option<Email>,
+    /// The account the money is deposited to, QuickBooks uses Undeposited Funds when this
+    /// is not set.
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "DepositToAccountRef")]
+    pub deposit_to_account_ref: Option<NtRef>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "Line")]
+    pub line: Vec<InvoiceLine>,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "PrivateNote")]
+    pub private_note: String,
+    #[serde(default, rename = "TotalAmt")]
+    pub total_amt: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "MetaData")]
+    pub meta_data: Option<MetaData>,
+}
+
+#[derive(Debug, JsonSchema, Clone, Serialize, Deserialize)]
+pub struct CdcResponse {
+    #[serde(default, rename = "CDCResponse")]
+    pub cdc_response: Vec<CdcQueryResponses>,
+    pub time: String,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct CdcQueryResponses {
+    #[serde(default, rename = "QueryResponse")]
+    pub query_response: Vec<CdcQueryResponse>,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct CdcQueryResponse {
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "Purchase")]
+    pub purchase: Vec<Changed<Purchase>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "BillPayment")]
+    pub bill_payment: Vec<Changed<BillPayment>>,
+}
+
+/// An entity returned from change data capture, which is either the entity as it is
+/// now or a marker that it was deleted.
+#[derive(Debug, JsonSchema, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Changed<T> {
+    Deleted(DeletedEntity),
+    Entity(T),
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct DeletedEntity {
+    /// The entity type, this is not sent by QuickBooks but filled in from the response.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub entity: String,
+    #[serde(rename = "status")]
+    pub status: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "Id")]
+    pub id: String,
+}
+
+impl DeletedEntity {
+    fn with_entity(mut self, entity: &str) -> Self {
+        self.entity = entity.to_string();
+        self
+    }
+}
+
+/// The entities that changed, as returned by `change_data_capture`.
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct ChangedEntities {
+    pub purchases: Vec<Purchase>,
+    pub bill_payments: Vec<BillPayment>,
+    pub deleted: Vec<DeletedEntity>,
+}
+
+/// The body of a webhook sent by QuickBooks when entities change.
+/// https://developer.intuit.com/app/developer/qbo/docs/develop/webhooks
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct WebhookPayload {
+    #[serde(default, rename = "eventNotifications")]
+    pub event_notifications: Vec<EventNotification>,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct EventNotification {
+    /// The QuickBooks company id.
+    #[serde(default, rename = "realmId")]
+    pub realm_id: String,
+    #[serde(default, rename = "dataChangeEvent")]
+    pub data_change_event: DataChangeEvent,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct DataChangeEvent {
+    #[serde(default)]
+    pub entities: Vec<EntityChange>,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct EntityChange {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub id: String,
+    /// One of `Create`, `Update`, `Delete`, `Merge`, `Void` or `Emailed`.
+    #[serde(default)]
+    pub operation: String,
+    /// This is sent both as `2015-10-05T14:42:19-0700` and `2015-10-05T14:42:19.000Z`.
+    #[serde(default, rename = "lastUpdated")]
+    pub last_updated: String,
+}
+
+impl EntityChange {
+    /// Parse the time the entity was last updated.
+    pub fn last_updated(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.last_updated)
+            .or_else(|_| DateTime::parse_from_str(&self.last_updated, "%Y-%m-%dT%H:%M:%S%.f%z"))
+            .ok()
+            .map(|d| d.with_timezone(&Utc))
+    }
+}
+
+/// The filters for a report. Empty fields are left up to QuickBooks' defaults.
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct ReportQuery {
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    /// Only include transactions with these class ids.
+    #[serde(default)]
+    pub class: Vec<String>,
+    /// Only include transactions with these department ids.
+    #[serde(default)]
+    pub department: Vec<String>,
+    /// Either `Cash` or `Accrual`.
+    #[serde(default)]
+    pub accounting_method: String,
+    /// For example `Total`, `Month` or `Classes`.
+    #[serde(default)]
+    pub summarize_column_by: String,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct Report {
+    #[serde(default, rename = "Header")]
+    pub header: ReportHeader,
+    #[serde(default, rename = "Columns")]
+    pub columns: ReportColumns,
+    #[serde(default, rename = "Rows")]
+    pub rows: ReportRows,
+}
+
+impl Report {
+    /// Find the total for a section of the report by its group, for example `Income`,
+    /// `Expenses` or `NetIncome` in a profit and loss report. This is the value of the
+    /// last column, which is the total unless the report was summarized by column.
+    pub fn total(&self, group: &str) -> Option<f64> {
+        self.rows.find_group(group).and_then(|row| {
+            row.summary
+                .as_ref()
+                .and_then(|summary| summary.col_data.last())
+                .and_then(|col| col.value.parse().ok())
+        })
+    }
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct ReportHeader {
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "ReportName")]
+    pub report_name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "StartPeriod")]
+    pub start_period: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "EndPeriod")]
+    pub end_period: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "Currency")]
+    pub currency: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "ReportBasis")]
+    pub report_basis: String,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "Time")]
+    pub time: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct ReportColumns {
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "Column")]
+    pub column: Vec<ReportColumn>,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct ReportColumn {
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "ColTitle")]
+    pub col_title: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "ColType")]
+    pub col_type: String,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct ReportRows {
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "Row")]
+    pub row: Vec<ReportRow>,
+}
+
+impl ReportRows {
+    fn find_group(&self, group: &str) -> Option<&ReportRow> {
+        self.row.iter().find_map(|row| {
+            if row.group == group {
+                Some(row)
+            } else {
+                row.rows.as_ref().and_then(|rows| rows.find_group(group))
+            }
+        })
+    }
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct ReportRow {
+    /// Either `Section` or `Data`.
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "type")]
+    pub row_type: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub group: String,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "Header")]
+    pub header: Option<ReportRowData>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "Rows")]
+    pub rows: Option<ReportRows>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "Summary")]
+    pub summary: Option<ReportRowData>,
+    /// The cells of a `Data` row.
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "ColData")]
+    pub col_data: Vec<ColData>,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct ReportRowData {
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "ColData")]
+    pub col_data: Vec<ColData>,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct ColData {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub value: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct BatchRequest {
+    #[serde(rename = "BatchItemRequest")]
+    pub batch_item_request: Vec<BatchItemRequest>,
+}
+
+/// A single operation in a batch.
+#[derive(Debug, JsonSchema, Clone, Serialize, Deserialize)]
+pub struct BatchItemRequest {
+    /// The id used to match the response to this request, unique within the batch.
+    #[serde(rename = "bId")]
+    pub b_id: String,
+    /// One of `create`, `update` or `delete`. This is empty for queries.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub operation: String,
+    #[serde(flatten)]
+    pub entity: BatchEntity,
+}
+
+impl BatchItemRequest {
+    pub fn create(b_id: &str, entity: BatchEntity) -> Self {
+        BatchItemRequest {
+            b_id: b_id.to_string(),
+            operation: "create".to_string(),
+            entity,
+        }
+    }
+
+    /// An update, the entity must include its `Id` and latest `SyncToken`. Unless the entity is
+    /// sparse this replaces all of it, so any field that is not modeled is cleared.
+    pub fn update(b_id: &str, entity: BatchEntity) -> Self {
+        BatchItemRequest {
+            b_id: b_id.to_string(),
+            operation: "update".to_string(),
+            entity,
+        }
+    }
+
+    pub fn delete(b_id: &str, entity: BatchEntity) -> Self {
+        BatchItemRequest {
+            b_id: b_id.to_string(),
+            operation: "delete".to_string(),
+            entity,
+        }
+    }
+
+    pub fn query(b_id: &str, query: &str) -> Self {
+        BatchItemRequest {
+            b_id: b_id.to_string(),
+            operation: String::new(),
+            entity: BatchEntity::Query(query.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, JsonSchema, Clone, Serialize, Deserialize)]
+pub enum BatchEntity {
+    Purchase(Purchase),
+    Bill(Bill),
+    BillPayment(BillPayment),
+    VendorCredit(VendorCredit),
+    Invoice(Invoice),
+    Query(String),
+    #[serde(rename = "Purchase", skip_deserializing)]
+    SparsePurchase(SparseTransaction),
+    #[serde(rename = "Bill", skip_deserializing)]
+    SparseBill(SparseTransaction),
+}
+
+#[derive(Debug, JsonSchema, Clone, Serialize, Deserialize)]
+pub struct BatchResponse {
+    #[serde(default, rename = "BatchItemResponse")]
+    pub batch_item_response: Vec<BatchItemResponse>,
+    pub time: String,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct BatchItemResponse {
+    #[serde(default, rename = "bId")]
+    pub b_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "Purchase")]
+    pub purchase: Option<Purchase>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "Bill")]
+    pub bill: Option<Bill>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "BillPayment")]
+    pub bill_payment: Option<BillPayment>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "VendorCredit")]
+    pub vendor_credit: Option<VendorCredit>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "Invoice")]
+    pub invoice: Option<Invoice>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "QueryResponse")]
+    pub query_response: Option<QueryResponse>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "Fault")]
+    pub fault: Option<Fault>,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct Fault {
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "type")]
+    pub fault_type: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "Error")]
+    pub error: Vec<FaultError>,
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let errors: Vec<String> = self
+            .error
+            .iter()
+            .map(|e| format!("{} ({}): {}", e.message, e.code, e.detail))
+            .collect();
+        write!(f, "{}: {}", self.fault_type, errors.join(", "))
+    }
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct FaultError {
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "Message")]
+    pub message: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "Detail")]
+    pub detail: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub code: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub element: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{
+        BatchEntity, BatchItemRequest, Invoice, InvoiceLine, InvoiceResponse, Line, NtRef, SalesItemLineDetail,
+        SparseTransaction,
+    };
+
+    #[test]
+    fn test_lines_round_trip() {
+        let lines = serde_json::json!([
+            {
+                "Id": "1",
+                "LineNum": 1,
+                "Amount": 1234567.89,
+                "DetailType": "AccountBasedExpenseLineDetail",
+                "AccountBasedExpenseLineDetail": {
+                    "AccountRef": {"value": "7", "name": "Software"},
+                    "BillableStatus": "Billable",
+                    "TaxCodeRef": {"value": "NON"},
+                    "CustomerRef": {"value": "3", "name": "Acme"},
+                    "TaxAmount": 1.5
+                }
+            },
+            {
+                "Id": "2",
+                "LineNum": 2,
+                "Amount": 20.0,
+                "DetailType": "ItemBasedExpenseLineDetail",
+                "ItemBasedExpenseLineDetail": {
+                    "ItemRef": {"value": "11", "name": "Cables"},
+                    "BillableStatus": "NotBillable",
+                    "Qty": 4.0,
+                    "UnitPrice": 5.0,
+                    "MarkupInfo": {"Percent": 10.0}
+                }
+            }
+        ]);
+
+        let parsed: Vec<Line> = serde_json::from_value(lines.clone()).unwrap();
+        assert!(parsed.iter().all(Line::round_trips));
+        assert_eq!(lines, serde_json::to_value(&parsed).unwrap());
+    }
+
+    #[test]
+    fn test_sparse_update_only_sends_what_changed() {
+        let mut update = SparseTransaction::new("12", "3");
+        assert!(update.is_empty());
+        update.department_ref = Some(Default::default());
+
+        let request = BatchItemRequest::update("bill-12", BatchEntity::SparseBill(update));
+        let body = serde_json::to_value(&request).unwrap();
+        assert_eq!(
+            serde_json::json!({
+                "bId": "bill-12",
+                "operation": "update",
+                "Bill": {"Id": "12", "SyncToken": "3", "sparse": true, "DepartmentRef": {}}
+            }),
+            body
+        );
+    }
+
+    #[test]
+    fn test_new_invoice_only_sends_what_is_set() {
+        let invoice = Invoice {
+            customer_ref: NtRef {
+                value: "58".to_string(),
+                ..Default::default()
+            },
+            due_date: NaiveDate::from_ymd_opt(2023, 5, 1),
+            line: vec![InvoiceLine {
+                amount: 150.0,
+                detail_type: "SalesItemLineDetail".to_string(),
+                sales_item_line_detail: Some(SalesItemLineDetail {
+                    item_ref: NtRef {
+                        value: "1".to_string(),
+                        ..Default::default()
+                    },
+                    qty: 3.0,
+                    unit_price: 50.0,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            serde_json::json!({
+                "sparse": false,
+                "DueDate": "2023-05-01",
+                "CustomerRef": {"value": "58"},
+                "Line": [{
+                    "Amount": 150.0,
+                    "DetailType": "SalesItemLineDetail",
+                    "SalesItemLineDetail": {"ItemRef": {"value": "1"}, "Qty": 3.0, "UnitPrice": 50.0}
+                }],
+                "TotalAmt": 0.0,
+                "Balance": 0.0
+            }),
+            serde_json::to_value(&invoice).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_sent_invoice_response() {
+        let response: InvoiceResponse = serde_json::from_value(serde_json::json!({
+            "Invoice": {
+                "Id": "130",
+                "SyncToken": "1",
+                "DocNumber": "1037",
+                "CustomerRef": {"value": "58", "name": "Acme"},
+                "BillEmail": {"Address": "billing@example.com"},
+                "EmailStatus": "EmailSent",
+                "DeliveryInfo": {"DeliveryType": "Email", "DeliveryTime": "2023-04-20T13:37:36Z"},
+                "Line": [
+                    {"Amount": 150.0, "DetailType": "SalesItemLineDetail", "SalesItemLineDetail": {"ItemRef": {"value": "1"}}},
+                    {"Amount": 150.0, "DetailType": "SubTotalLineDetail", "SubTotalLineDetail": {}}
+                ],
+                "TotalAmt": 150.0,
+                "Balance": 150.0
+            },
+            "time": "2023-04-20T13:37:36Z"
+        }))
+        .unwrap();
+
+        let invoice = response.invoice;
+        assert_eq!("130", invoice.id);
+        assert_eq!("1", invoice.sync_token);
+        assert_eq!("EmailSent", invoice.email_status);
+        assert_eq!("Email", invoice.delivery_info.unwrap().delivery_type);
+        assert_eq!(2, invoice.line.len());
+        assert!(invoice.line[1].sales_item_line_detail.is_none());
+    }
+}
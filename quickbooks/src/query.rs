@@ -0,0 +1,272 @@
+/*!
+ * A typed builder for the QuickBooks query language.
+ *
+ * https://developer.intuit.com/app/developer/qbo/docs/learn/explore-the-quickbooks-online-api/data-queries
+ */
+use std::{fmt, marker::PhantomData};
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::{
+    Attachment, Bill, BillPayment, Class, Department, Invoice, Item, Purchase, QueryResponse, SalesReceipt,
+    VendorCredit, QUERY_PAGE_SIZE,
+};
+
+/// An entity that can be selected with a query.
+pub trait QueryEntity: Sized {
+    /// The name of the entity in the query language, ie. `Purchase`.
+    const NAME: &'static str;
+
+    /// Take the entities of this type out of a query response.
+    fn from_query_response(response: QueryResponse) -> Vec<Self>;
+}
+
+macro_rules! query_entity {
+    ($entity:ty, $name:literal, $field:ident) => {
+        impl QueryEntity for $entity {
+            const NAME: &'static str = $name;
+
+            fn from_query_response(response: QueryResponse) -> Vec<Self> {
+                response.$field
+            }
+        }
+    };
+}
+
+query_entity!(Purchase, "Purchase", purchase);
+query_entity!(Bill, "Bill", bill);
+query_entity!(BillPayment, "BillPayment", bill_payment);
+query_entity!(VendorCredit, "VendorCredit", vendor_credit);
+query_entity!(Invoice, "Invoice", invoice);
+query_entity!(SalesReceipt, "SalesReceipt", sales_receipt);
+query_entity!(Item, "Item", item);
+query_entity!(Attachment, "Attachable", attachable);
+query_entity!(Class, "Class", class);
+query_entity!(Department, "Department", department);
+
+/// A literal value on the right hand side of a where clause.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryValue {
+    String(String),
+    Number(f64),
+    Bool(bool),
+}
+
+impl fmt::Display for QueryValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            // Backslashes and quotes are the only characters that need escaping.
+            QueryValue::String(s) => write!(f, "'{}'", s.replace('\\', "\\\\").replace('\'', "\\'")),
+            QueryValue::Number(n) => write!(f, "{}", n),
+            QueryValue::Bool(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+impl From<&str> for QueryValue {
+    fn from(s: &str) -> Self {
+        QueryValue::String(s.to_string())
+    }
+}
+
+impl From<String> for QueryValue {
+    fn from(s: String) -> Self {
+        QueryValue::String(s)
+    }
+}
+
+impl From<f64> for QueryValue {
+    fn from(n: f64) -> Self {
+        QueryValue::Number(n)
+    }
+}
+
+impl From<i64> for QueryValue {
+    fn from(n: i64) -> Self {
+        QueryValue::Number(n as f64)
+    }
+}
+
+impl From<bool> for QueryValue {
+    fn from(b: bool) -> Self {
+        QueryValue::Bool(b)
+    }
+}
+
+impl From<NaiveDate> for QueryValue {
+    fn from(d: NaiveDate) -> Self {
+        QueryValue::String(d.format("%Y-%m-%d").to_string())
+    }
+}
+
+impl From<DateTime<Utc>> for QueryValue {
+    fn from(d: DateTime<Utc>) -> Self {
+        QueryValue::String(d.to_rfc3339())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+/// A query for entities of type `T`. Pagination is handled when the query is run, so
+/// `STARTPOSITION` and `MAXRESULTS` are never set here.
+///
+/// ```ignore
+/// let purchases = Query::<Purchase>::new()
+///     .where_gte("TxnDate", start_date)
+///     .where_eq("PaymentType", "CreditCard")
+///     .order_by("TxnDate", Order::Desc);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Query<T> {
+    conditions: Vec<String>,
+    order_by: Vec<(String, Order)>,
+    page_size: i64,
+    entity: PhantomData<T>,
+}
+
+impl<T: QueryEntity> Default for Query<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: QueryEntity> Query<T> {
+    pub fn new() -> Self {
+        Query {
+            conditions: Vec::new(),
+            order_by: Vec::new(),
+            page_size: QUERY_PAGE_SIZE,
+            entity: PhantomData,
+        }
+    }
+
+    fn condition(mut self, field: &str, operator: &str, value: String) -> Self {
+        self.conditions.push(format!("{} {} {}", field, operator, value));
+        self
+    }
+
+    pub fn where_eq<V: Into<QueryValue>>(self, field: &str, value: V) -> Self {
+        self.condition(field, "=", value.into().to_string())
+    }
+
+    pub fn where_lt<V: Into<QueryValue>>(self, field: &str, value: V) -> Self {
+        self.condition(field, "<", value.into().to_string())
+    }
+
+    pub fn where_lte<V: Into<QueryValue>>(self, field: &str, value: V) -> Self {
+        self.condition(field, "<=", value.into().to_string())
+    }
+
+    pub fn where_gt<V: Into<QueryValue>>(self, field: &str, value: V) -> Self {
+        self.condition(field, ">", value.into().to_string())
+    }
+
+    pub fn where_gte<V: Into<QueryValue>>(self, field: &str, value: V) -> Self {
+        self.condition(field, ">=", value.into().to_string())
+    }
+
+    /// Matches with `%` as the wildcard, ie. `where_like("DisplayName", "Amazon%")`.
+    pub fn where_like(self, field: &str, pattern: &str) -> Self {
+        self.condition(field, "LIKE", QueryValue::from(pattern).to_string())
+    }
+
+    pub fn where_in<V: Into<QueryValue>>(self, field: &str, values: Vec<V>) -> Self {
+        let values: Vec<String> = values.into_iter().map(|v| v.into().to_string()).collect();
+        self.condition(field, "IN", format!("({})", values.join(", ")))
+    }
+
+    pub fn order_by(mut self, field: &str, order: Order) -> Self {
+        self.order_by.push((field.to_string(), order));
+        self
+    }
+
+    /// The number of results fetched per request, QuickBooks caps this at 1000.
+    pub fn page_size(mut self, page_size: i64) -> Self {
+        self.page_size = page_size.clamp(1, QUERY_PAGE_SIZE);
+        self
+    }
+
+    pub fn get_page_size(&self) -> i64 {
+        self.page_size
+    }
+
+    /// Render the query for the page starting at `start_position`, which is 1-indexed.
+    pub fn to_page_sql(&self, start_position: i64) -> String {
+        let mut sql = format!("SELECT * FROM {}", T::NAME);
+
+        if !self.conditions.is_empty() {
+            sql.push_str(&format!(" WHERE {}", self.conditions.join(" AND ")));
+        }
+
+        // Paging without an order is not stable, so fall back to the id.
+        if self.order_by.is_empty() {
+            sql.push_str(" ORDERBY Id");
+        } else {
+            let order: Vec<String> = self
+                .order_by
+                .iter()
+                .map(|(field, order)| match order {
+                    Order::Asc => field.to_string(),
+                    Order::Desc => format!("{} DESC", field),
+                })
+                .collect();
+            sql.push_str(&format!(" ORDERBY {}", order.join(", ")));
+        }
+
+        sql.push_str(&format!(
+            " STARTPOSITION {} MAXRESULTS {}",
+            start_position, self.page_size
+        ));
+
+        sql
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{Order, Query};
+    use crate::{Attachment, Purchase};
+
+    #[test]
+    fn test_query_defaults_to_ordering_by_id() {
+        let query = Query::<Purchase>::new();
+
+        assert_eq!(
+            "SELECT * FROM Purchase ORDERBY Id STARTPOSITION 1 MAXRESULTS 1000",
+            query.to_page_sql(1)
+        );
+    }
+
+    #[test]
+    fn test_query_conditions_and_ordering() {
+        let query = Query::<Purchase>::new()
+            .where_gte("TxnDate", NaiveDate::from_ymd(2022, 1, 1))
+            .where_in("PaymentType", vec!["Cash", "CreditCard"])
+            .order_by("TxnDate", Order::Desc)
+            .order_by("Id", Order::Asc)
+            .page_size(100);
+
+        assert_eq!(
+            "SELECT * FROM Purchase WHERE TxnDate >= '2022-01-01' AND PaymentType IN ('Cash', 'CreditCard') \
+             ORDERBY TxnDate DESC, Id STARTPOSITION 101 MAXRESULTS 100",
+            query.to_page_sql(101)
+        );
+    }
+
+    #[test]
+    fn test_query_escapes_strings() {
+        let query = Query::<Attachment>::new().where_eq("FileName", "Joe's \\ receipt.pdf");
+
+        assert_eq!(
+            "SELECT * FROM Attachable WHERE FileName = 'Joe\\'s \\\\ receipt.pdf' ORDERBY Id STARTPOSITION 1 \
+             MAXRESULTS 1000",
+            query.to_page_sql(1)
+        );
+    }
+}
@@ -4,7 +4,7 @@ use oauth2::{
     reqwest::async_http_client,
     AuthUrl, ClientId, ClientSecret, Scope, StandardErrorResponse, TokenResponse, TokenUrl,
 };
-use reqwest::{header::HeaderValue, Client, Method, RequestBuilder, Response, StatusCode};
+use reqwest::{header::HeaderValue, Client, Method, RequestBuilder, Response, StatusCode, Url};
 use serde::{Deserialize, Serialize};
 
 use std::{
@@ -31,12 +31,142 @@ pub struct Reimbursement {
     pub receipts: Vec<String>,
     pub transaction_date: Option<chrono::NaiveDate>,
     pub user_id: String,
+    #[serde(default)]
+    pub state: Option<String>,
+    #[serde(default)]
+    pub memo: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ListReimbursementsQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_date: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_date: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
+}
+
+/// An amount in the minor units of its currency, ie. cents
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct CurrencyAmount {
+    pub amount: i64,
+    pub currency_code: String,
+}
+
+impl CurrencyAmount {
+    /// The amount in the major units of its currency, ie. dollars
+    pub fn to_major_units(&self) -> f64 {
+        self.amount as f64 / 100.0
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct BillVendor {
+    #[serde(default)]
+    pub remote_id: Option<String>,
+    pub remote_name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct BillPayment {
+    pub amount: CurrencyAmount,
+    #[serde(default)]
+    pub payment_date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub payment_method: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct Bill {
+    pub id: String,
+    pub amount: CurrencyAmount,
+    pub status: String,
+    #[serde(default)]
+    pub vendor: Option<BillVendor>,
+    #[serde(default)]
+    pub invoice_number: Option<String>,
+    #[serde(default)]
+    pub invoice_urls: Vec<String>,
+    #[serde(default)]
+    pub memo: Option<String>,
+    #[serde(default)]
+    pub issued_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub due_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub paid_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub payment: Option<BillPayment>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ListBillsQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_issued_date: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_issued_date: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct ReceiptImage {
+    pub content_type: String,
+    pub content: Vec<u8>,
+}
+
+impl ReceiptImage {
+    /// The file extension to use when storing the receipt, receipts that are not images are PDFs.
+    pub fn extension(&self) -> &'static str {
+        match self.content_type.as_str() {
+            "image/png" => "png",
+            "image/jpeg" => "jpg",
+            "image/gif" => "gif",
+            _ => "pdf",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct AccountingCategories {
     pub category_id: Option<String>,
     pub category_name: Option<String>,
+    #[serde(default)]
+    pub tracking_category_remote_id: Option<String>,
+    #[serde(default)]
+    pub tracking_category_remote_name: Option<String>,
+    #[serde(default)]
+    pub tracking_category_remote_type: Option<String>,
+}
+
+/// A value selected for one of the accounting fields that are synced to the accounting provider
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct AccountingFieldSelection {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub external_id: Option<String>,
+    #[serde(default, rename = "type")]
+    pub type_: Option<String>,
+    #[serde(default)]
+    pub category_info: Option<AccountingFieldCategoryInfo>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct AccountingFieldCategoryInfo {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub external_id: Option<String>,
+    #[serde(default, rename = "type")]
+    pub type_: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
@@ -98,6 +228,8 @@ pub enum PolicyViolationType {
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct Transaction {
     pub accounting_categories: Vec<AccountingCategories>,
+    #[serde(default)]
+    pub accounting_field_selections: Vec<AccountingFieldSelection>,
     pub amount: f64,
     pub card_holder: CardHolder,
     pub card_id: String,
@@ -114,6 +246,96 @@ pub struct Transaction {
     pub user_transaction_time: Option<DateTime<Utc>>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub enum CardState {
+    #[serde(rename = "ACTIVE")]
+    Active,
+    #[serde(rename = "CHIP_LOCKED")]
+    ChipLocked,
+    #[serde(rename = "SUSPENDED")]
+    Suspended,
+    #[serde(rename = "TERMINATED")]
+    Terminated,
+    #[serde(rename = "UNACTIVATED")]
+    Unactivated,
+    #[serde(other)]
+    FallthroughString,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub enum SpendingInterval {
+    #[serde(rename = "DAILY")]
+    Daily,
+    #[serde(rename = "MONTHLY")]
+    Monthly,
+    #[serde(rename = "QUARTERLY")]
+    Quarterly,
+    #[serde(rename = "YEARLY")]
+    Yearly,
+    #[serde(rename = "TOTAL")]
+    Total,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct SpendingRestrictions {
+    pub amount: f64,
+    pub interval: SpendingInterval,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_amount_limit: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lock_date: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<i64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blocked_categories: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Card {
+    pub id: String,
+    pub display_name: String,
+    pub last_four: String,
+    pub cardholder_id: String,
+    pub cardholder_name: String,
+    pub is_physical: bool,
+    pub state: CardState,
+    pub spending_restrictions: Option<SpendingRestrictions>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CreateVirtualCardDeferred {
+    pub idempotency_key: String,
+    pub user_id: String,
+    pub display_name: String,
+    pub spending_restrictions: SpendingRestrictions,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UpdateCard {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spending_restrictions: Option<SpendingRestrictions>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UpdateCardResponse {}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ListCardsQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CardStateChange {
+    pub idempotency_key: String,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Department {
     pub id: String,
@@ -256,6 +478,16 @@ pub struct ResponsePagination {
     pub next: Option<String>,
 }
 
+impl ResponsePagination {
+    /// The cursor to pass as `start` to fetch the next page, if there is one
+    pub fn start(&self) -> Option<String> {
+        let next = Url::parse(self.next.as_deref()?).ok()?;
+        next.query_pairs()
+            .find(|(key, _)| key == "start")
+            .map(|(_, value)| value.to_string())
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct DeferredTaskId {
     pub id: String,
@@ -374,27 +606,137 @@ impl RampClient {
             .request(method, format!("https://api.ramp.com/developer/v1/{path}"))
     }
 
-    pub fn departments(&self) -> DepartmentClient {
+    pub fn bills(&self) -> BillClient<'_> {
+        BillClient { client: self }
+    }
+
+    pub fn cards(&self) -> CardClient<'_> {
+        CardClient { client: self }
+    }
+
+    pub fn departments(&self) -> DepartmentClient<'_> {
         DepartmentClient { client: self }
     }
 
-    pub fn receipts(&self) -> ReceiptClient {
+    pub fn receipts(&self) -> ReceiptClient<'_> {
         ReceiptClient { client: self }
     }
 
-    pub fn reimbursements(&self) -> ReimbursementClient {
+    pub fn reimbursements(&self) -> ReimbursementClient<'_> {
         ReimbursementClient { client: self }
     }
 
-    pub fn transactions(&self) -> TransactionClient {
+    pub fn transactions(&self) -> TransactionClient<'_> {
         TransactionClient { client: self }
     }
 
-    pub fn users(&self) -> UserClient {
+    pub fn users(&self) -> UserClient<'_> {
         UserClient { client: self }
     }
 }
 
+pub struct BillClient<'a> {
+    client: &'a RampClient,
+}
+
+impl<'a> BillClient<'a> {
+    pub async fn get(&self, bill_id: &str) -> Result<Bill, Error> {
+        let req = self.client.request(Method::GET, &format!("bills/{bill_id}"));
+        Ok(self.client.execute(req).await?.json().await?)
+    }
+
+    pub async fn list(&self, query: &ListBillsQuery) -> Result<ResponseList<Bill>, Error> {
+        let req = self.client.request(Method::GET, "bills").query(query);
+        Ok(self.client.execute(req).await?.json().await?)
+    }
+
+    /// Fetch every page of bills that match the query
+    pub async fn list_all(&self, query: &ListBillsQuery) -> Result<Vec<Bill>, Error> {
+        let mut query = query.clone();
+        let mut bills = vec![];
+
+        loop {
+            let mut page = self.list(&query).await?;
+            bills.append(&mut page.data);
+
+            match page.page.start() {
+                Some(start) => query.start = Some(start),
+                None => break,
+            }
+        }
+
+        Ok(bills)
+    }
+}
+
+pub struct CardClient<'a> {
+    client: &'a RampClient,
+}
+
+impl<'a> CardClient<'a> {
+    pub async fn get(&self, card_id: &str) -> Result<Card, Error> {
+        let req = self.client.request(Method::GET, &format!("cards/{card_id}"));
+        Ok(self.client.execute(req).await?.json().await?)
+    }
+
+    pub async fn list(&self, query: &ListCardsQuery) -> Result<ResponseList<Card>, Error> {
+        let req = self.client.request(Method::GET, "cards").query(query);
+        Ok(self.client.execute(req).await?.json().await?)
+    }
+
+    /// Fetch every page of cards that match the query
+    pub async fn list_all(&self, query: &ListCardsQuery) -> Result<Vec<Card>, Error> {
+        let mut query = query.clone();
+        let mut cards = vec![];
+
+        loop {
+            let mut page = self.list(&query).await?;
+            cards.append(&mut page.data);
+
+            match page.page.start() {
+                Some(start) => query.start = Some(start),
+                None => break,
+            }
+        }
+
+        Ok(cards)
+    }
+
+    /// Issue a virtual card. The card is created asynchronously by Ramp.
+    pub async fn deferred_create_virtual(&self, payload: &CreateVirtualCardDeferred) -> Result<DeferredTaskId, Error> {
+        let req = self
+            .client
+            .request(Method::POST, "cards/deferred/virtual")
+            .json(payload);
+        Ok(self.client.execute(req).await?.json().await?)
+    }
+
+    pub async fn update(&self, card_id: &str, payload: &UpdateCard) -> Result<UpdateCardResponse, Error> {
+        let req = self
+            .client
+            .request(Method::PATCH, &format!("cards/{card_id}"))
+            .json(payload);
+        Ok(self.client.execute(req).await?.json().await?)
+    }
+
+    /// Lock a card from any further spending. A suspended card can be unsuspended later.
+    pub async fn deferred_suspend(&self, card_id: &str, payload: &CardStateChange) -> Result<DeferredTaskId, Error> {
+        let req = self
+            .client
+            .request(Method::POST, &format!("cards/{card_id}/deferred/suspension"))
+            .json(payload);
+        Ok(self.client.execute(req).await?.json().await?)
+    }
+
+    pub async fn deferred_unsuspend(&self, card_id: &str, payload: &CardStateChange) -> Result<DeferredTaskId, Error> {
+        let req = self
+            .client
+            .request(Method::POST, &format!("cards/{card_id}/deferred/unsuspension"))
+            .json(payload);
+        Ok(self.client.execute(req).await?.json().await?)
+    }
+}
+
 pub struct DepartmentClient<'a> {
     client: &'a RampClient,
 }
@@ -415,6 +757,31 @@ impl<'a> ReceiptClient<'a> {
         let req = self.client.request(Method::GET, &format!("receipts/{receipt_id}"));
         Ok(self.client.execute(req).await?.json().await?)
     }
+
+    /// Download the image of a receipt. The receipt url is pre-signed, so it is fetched without
+    /// the Ramp credentials.
+    pub async fn download(&self, receipt: &Receipt) -> Result<ReceiptImage, Error> {
+        let response = self.client.client.get(&receipt.receipt_url).send().await?;
+
+        if !response.status().is_success() {
+            return Err(Error::RequestFailed {
+                status: response.status(),
+                error: None,
+            });
+        }
+
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("application/pdf")
+            .to_string();
+
+        Ok(ReceiptImage {
+            content_type,
+            content: response.bytes().await?.to_vec(),
+        })
+    }
 }
 
 pub struct ReimbursementClient<'a> {
@@ -422,44 +789,74 @@ pub struct ReimbursementClient<'a> {
 }
 
 impl<'a> ReimbursementClient<'a> {
-    pub async fn list(&self) -> Result<ResponseList<Reimbursement>, Error> {
-        let req = self.client.request(Method::GET, "reimbursements");
+    pub async fn get(&self, reimbursement_id: &str) -> Result<Reimbursement, Error> {
+        let req = self
+            .client
+            .request(Method::GET, &format!("reimbursements/{reimbursement_id}"));
         Ok(self.client.execute(req).await?.json().await?)
     }
+
+    pub async fn list(&self, query: &ListReimbursementsQuery) -> Result<ResponseList<Reimbursement>, Error> {
+        let req = self.client.request(Method::GET, "reimbursements").query(query);
+        Ok(self.client.execute(req).await?.json().await?)
+    }
+
+    /// Fetch every page of reimbursements that match the query
+    pub async fn list_all(&self, query: &ListReimbursementsQuery) -> Result<Vec<Reimbursement>, Error> {
+        let mut query = query.clone();
+        let mut reimbursements = vec![];
+
+        loop {
+            let mut page = self.list(&query).await?;
+            reimbursements.append(&mut page.data);
+
+            match page.page.start() {
+                Some(start) => query.start = Some(start),
+                None => break,
+            }
+        }
+
+        Ok(reimbursements)
+    }
 }
 
 pub struct TransactionClient<'a> {
     client: &'a RampClient,
 }
 
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct ListTransactionsQuery {
     #[serde(skip_serializing_if = "Option::is_none")]
-    department_id: Option<String>,
+    pub department_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    location_id: Option<String>,
+    pub location_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    merchant_id: Option<String>,
+    pub merchant_id: Option<String>,
     #[serde(rename = "sk_category_id", skip_serializing_if = "Option::is_none")]
-    category_id: Option<String>,
+    pub category_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    from_date: Option<DateTime<Utc>>,
+    pub from_date: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    to_date: Option<DateTime<Utc>>,
+    pub to_date: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "is_false")]
-    order_by_date_asc: bool,
+    pub order_by_date_asc: bool,
     #[serde(skip_serializing_if = "is_false")]
-    order_by_date_desc: bool,
+    pub order_by_date_desc: bool,
     #[serde(skip_serializing_if = "is_false")]
-    order_by_amount_asc: bool,
+    pub order_by_amount_asc: bool,
     #[serde(skip_serializing_if = "is_false")]
-    order_by_amount_desc: bool,
+    pub order_by_amount_desc: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    state: Option<String>,
-    min_amount: f32,
-    max_amount: f32,
+    pub min_amount: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    page_size: Option<u32>,
+    pub max_amount: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<u32>,
+    /// The cursor returned by the previous page, see [`ResponsePagination::start`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
 }
 
 impl<'a> TransactionClient<'a> {
@@ -474,6 +871,24 @@ impl<'a> TransactionClient<'a> {
         let req = self.client.request(Method::GET, "transactions/").query(query);
         Ok(self.client.execute(req).await?.json().await?)
     }
+
+    /// Fetch every page of transactions that match the query
+    pub async fn list_all(&self, query: &ListTransactionsQuery) -> Result<Vec<Transaction>, Error> {
+        let mut query = query.clone();
+        let mut transactions = vec![];
+
+        loop {
+            let mut page = self.list(&query).await?;
+            transactions.append(&mut page.data);
+
+            match page.page.start() {
+                Some(start) => query.start = Some(start),
+                None => break,
+            }
+        }
+
+        Ok(transactions)
+    }
 }
 
 pub struct UserClient<'a> {
@@ -572,3 +987,103 @@ impl std::error::Error for Error {
 fn is_false(value: &bool) -> bool {
     !(*value)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pagination_start() {
+        let page = ResponsePagination {
+            next: Some("https://api.ramp.com/developer/v1/transactions?page_size=100&start=5f3e1c2a".to_string()),
+        };
+        assert_eq!(Some("5f3e1c2a".to_string()), page.start());
+
+        let last = ResponsePagination { next: None };
+        assert_eq!(None, last.start());
+    }
+
+    #[test]
+    fn test_parse_bill() {
+        let bill: Bill = serde_json::from_value(serde_json::json!({
+            "id": "b1",
+            "amount": { "amount": 123456, "currency_code": "USD" },
+            "status": "PAID",
+            "vendor": { "remote_id": "v1", "remote_name": "Digi-Key" },
+            "invoice_number": "INV-42",
+            "invoice_urls": ["https://example.com/invoice.pdf"],
+            "issued_at": "2023-01-02T00:00:00Z",
+            "paid_at": "2023-01-20T00:00:00Z",
+            "payment": {
+                "amount": { "amount": 123456, "currency_code": "USD" },
+                "payment_date": "2023-01-20T00:00:00Z",
+                "payment_method": "ACH"
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(1234.56, bill.amount.to_major_units());
+        assert_eq!("Digi-Key", bill.vendor.unwrap().remote_name);
+        assert_eq!(Some("ACH".to_string()), bill.payment.unwrap().payment_method);
+        assert!(bill.due_at.is_none());
+    }
+
+    #[test]
+    fn test_parse_transaction_accounting_fields() {
+        let transaction: Transaction = serde_json::from_value(serde_json::json!({
+            "accounting_categories": [{
+                "category_id": "57",
+                "category_name": "Software",
+                "tracking_category_remote_id": "91",
+                "tracking_category_remote_name": "Expense Account",
+                "tracking_category_remote_type": "GL_ACCOUNT"
+            }],
+            "accounting_field_selections": [{
+                "id": "b0f2",
+                "name": "Software Subscriptions",
+                "external_id": "133",
+                "type": "GL_ACCOUNT",
+                "category_info": { "id": "c1a9", "name": "Expense Account", "type": "GL_ACCOUNT" }
+            }],
+            "amount": 12.5,
+            "card_holder": {
+                "department_id": "d1",
+                "department_name": "Engineering",
+                "first_name": "Ada",
+                "last_name": "Lovelace",
+                "location_id": "l1",
+                "location_name": "Emeryville"
+            },
+            "card_id": "c1",
+            "disputes": [],
+            "id": "t1",
+            "memo": null,
+            "merchant_id": "m1",
+            "merchant_name": "GitHub",
+            "policy_violations": [],
+            "receipts": ["r1"],
+            "sk_category_id": 4.0,
+            "sk_category_name": "SaaS / Software",
+            "state": "CLEARED",
+            "user_transaction_time": "2023-01-05T16:00:00Z"
+        }))
+        .unwrap();
+
+        assert_eq!(
+            Some("GL_ACCOUNT".to_string()),
+            transaction.accounting_categories[0].tracking_category_remote_type
+        );
+        assert_eq!(
+            "Software Subscriptions",
+            transaction.accounting_field_selections[0].name
+        );
+        assert_eq!(
+            "Expense Account",
+            transaction.accounting_field_selections[0]
+                .category_info
+                .as_ref()
+                .unwrap()
+                .name
+        );
+    }
+}
@@ -153,6 +153,27 @@ impl Shippo {
         Ok(resp.json().await.unwrap())
     }
 
+    /// Validate an address. The address that is returned has been corrected by Shippo where it
+    /// could be, and its `validation_results` say whether it is deliverable.
+    /// FROM: https://goshippo.com/docs/reference#addresses-validate
+    pub async fn validate_address(&self, address: &Address) -> Result<Address, APIError> {
+        // Build the request.
+        let request = self.request(Method::POST, "addresses", NewAddress { address, validate: true }, None);
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::CREATED => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(resp.json().await.unwrap())
+    }
+
     /// List the orders.
     /// FROM: https://goshippo.com/docs/reference#orders-list
     pub async fn list_orders(&self) -> Result<Vec<Order>, APIError> {
@@ -375,6 +396,26 @@ impl Shippo {
         Ok(resp.json().await.unwrap())
     }
 
+    /// Void a shipping label by requesting a refund for it.
+    /// FROM: https://goshippo.com/docs/reference#refunds-create
+    pub async fn create_refund(&self, nr: NewRefund) -> Result<Refund, APIError> {
+        // Build the request.
+        let request = self.request(Method::POST, "refunds/", nr, None);
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::CREATED => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(resp.json().await.unwrap())
+    }
+
     /// Get a shipping label.
     /// FROM: https://goshippo.com/docs/reference#transactions-retrieve
     pub async fn get_shipping_label(&self, id: &str) -> Result<Transaction, APIError> {
@@ -480,6 +521,57 @@ impl Shippo {
 
         Ok(resp.json().await.unwrap_or_default())
     }
+
+    /// List the webhooks of the account.
+    /// FROM: https://goshippo.com/docs/reference#webhooks-list
+    pub async fn list_webhooks(&self) -> Result<Vec<Webhook>, APIError> {
+        // Build the request.
+        let request = self.request(Method::GET, "webhooks", (), None);
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        let r: WebhooksAPIResponse = resp.json().await.unwrap();
+
+        Ok(r.webhooks)
+    }
+
+    /// Create a webhook for the account. Shippo calls it for the event on every
+    /// shipment, not only the ones registered with `register_tracking_webhook`.
+    /// FROM: https://goshippo.com/docs/reference#webhooks-create
+    pub async fn create_webhook(&self, event: &str, url: &str) -> Result<Webhook, APIError> {
+        let body = Webhook {
+            event: event.to_string(),
+            url: url.to_string(),
+            active: true,
+            ..Default::default()
+        };
+
+        // Build the request.
+        let request = self.request(Method::POST, "webhooks", body, None);
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            StatusCode::CREATED => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(resp.json().await.unwrap())
+    }
 }
 
 /// Error type returned by our library.
@@ -576,6 +668,34 @@ pub struct CarrierAccountsAPIResponse {
     pub carrier_accounts: Vec<CarrierAccount>,
 }
 
+/// The data type for an API response for webhooks.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct WebhooksAPIResponse {
+    #[serde(default, skip_serializing_if = "Vec::is_empty", alias = "results")]
+    pub webhooks: Vec<Webhook>,
+}
+
+/// The data type for a webhook.
+/// FROM: https://goshippo.com/docs/reference#webhooks
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Webhook {
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_string::deserialize",
+        skip_serializing_if = "String::is_empty"
+    )]
+    pub object_id: String,
+    /// The event the webhook is called for, ie. "track_updated".
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub event: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub url: String,
+    #[serde(default)]
+    pub active: bool,
+    #[serde(default)]
+    pub is_test: bool,
+}
+
 /// The data type for a transactions API response.
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct TransactionsAPIResponse {
@@ -753,6 +873,14 @@ pub struct Address {
     pub validation_results: Option<ValidationResults>,
 }
 
+/// The data type for creating an address, and validating it while we are at it.
+#[derive(Debug, Serialize)]
+struct NewAddress<'a> {
+    #[serde(flatten)]
+    address: &'a Address,
+    validate: bool,
+}
+
 fn is_false(t: &bool) -> bool {
     !t
 }
@@ -1156,6 +1284,42 @@ pub struct NewTransaction {
     pub r#async: bool,
 }
 
+/// The data type for a refund.
+/// A refund voids a shipping label that has not been used, and refunds what it cost.
+/// FROM: https://goshippo.com/docs/reference#refunds
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Refund {
+    /// Unique identifier of the given Refund object.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub object_id: String,
+    /// Date and time of Refund creation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object_created: Option<DateTime<Utc>>,
+    /// Date and time of last Refund update.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object_updated: Option<DateTime<Utc>>,
+    /// Username of the user who created the Refund object.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub object_owner: String,
+    /// Indicates the status of the Refund.
+    /// "QUEUED" | "PENDING" | "SUCCESS" | "ERROR"
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub status: String,
+    /// Object ID of the Transaction to be refunded.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub transaction: String,
+    /// Indicates whether the object has been created in test mode.
+    #[serde(default)]
+    pub test: bool,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct NewRefund {
+    pub transaction: String,
+    #[serde(default)]
+    pub r#async: bool,
+}
+
 #[derive(Clone, Debug, Default, JsonSchema, Serialize, Deserialize)]
 pub struct Message {
     #[serde(default, skip_serializing_if = "String::is_empty")]
@@ -22,7 +22,7 @@
  * ```
  */
 #![allow(clippy::field_reassign_with_default)]
-use std::{env, error, fmt, sync::Arc};
+use std::{collections::BTreeMap, env, error, fmt, sync::Arc};
 
 use chrono::{offset::Utc, DateTime};
 use reqwest::{header, Client, Method, Request, StatusCode, Url};
@@ -168,6 +168,286 @@ impl Tailscale {
 
         Ok(())
     }
+
+    /// Authorize a device to join the tailnet, for tailnets that require device approval.
+    pub async fn authorize_device(&self, device_id: &str) -> Result<(), APIError> {
+        let request = self.request(
+            Method::POST,
+            &format!("device/{device_id}/authorized"),
+            DeviceAuthorizedRequest { authorized: true },
+            None,
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Set the ACL tags of a device, replacing any it already has.
+    pub async fn set_device_tags(&self, device_id: &str, tags: &[String]) -> Result<(), APIError> {
+        let request = self.request(
+            Method::POST,
+            &format!("device/{device_id}/tags"),
+            DeviceTagsRequest { tags: tags.to_vec() },
+            None,
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Disable key expiry for a device so that it does not need to be re-authenticated, this is
+    /// what we want for servers.
+    pub async fn set_device_key_expiry_disabled(&self, device_id: &str, disabled: bool) -> Result<(), APIError> {
+        let request = self.request(
+            Method::POST,
+            &format!("device/{device_id}/key"),
+            DeviceKeyRequest {
+                key_expiry_disabled: disabled,
+            },
+            None,
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Get the subnet routes a device advertises and which of them are enabled.
+    pub async fn get_device_routes(&self, device_id: &str) -> Result<DeviceRoutes, APIError> {
+        let request = self.request(Method::GET, &format!("device/{device_id}/routes"), (), None);
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(resp.json().await.unwrap())
+    }
+
+    /// Set the subnet routes that are enabled for a device. Routes that are not advertised by
+    /// the device are accepted, but only take effect once the device advertises them.
+    pub async fn set_device_routes(&self, device_id: &str, routes: &[String]) -> Result<DeviceRoutes, APIError> {
+        let request = self.request(
+            Method::POST,
+            &format!("device/{device_id}/routes"),
+            DeviceRoutesRequest {
+                routes: routes.to_vec(),
+            },
+            None,
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(resp.json().await.unwrap())
+    }
+
+    /// Get the ACL policy file of the tailnet along with its ETag, which can be passed back
+    /// to `set_acl` to avoid overwriting changes made in the meantime.
+    pub async fn get_acl(&self) -> Result<Acl, APIError> {
+        let mut request = self.request(Method::GET, &format!("tailnet/{}/acl", self.domain), (), None);
+        // Without this the policy is returned as HuJSON, which may contain comments.
+        request
+            .headers_mut()
+            .insert(header::ACCEPT, header::HeaderValue::from_static("application/json"));
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        let etag = resp
+            .headers()
+            .get(header::ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+        let policy: AclPolicy = resp.json().await.unwrap();
+
+        Ok(Acl { policy, etag })
+    }
+
+    /// Check an ACL policy file for errors without applying it.
+    pub async fn validate_acl(&self, policy: &AclPolicy) -> Result<AclValidation, APIError> {
+        let request = self.request(
+            Method::POST,
+            &format!("tailnet/{}/acl/validate", self.domain),
+            policy,
+            None,
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(resp.json().await.unwrap())
+    }
+
+    /// Replace the ACL policy file of the tailnet. When an `etag` is given the update is
+    /// rejected if the policy has changed since it was read.
+    pub async fn set_acl(&self, policy: &AclPolicy, etag: Option<&str>) -> Result<AclPolicy, APIError> {
+        let mut request = self.request(Method::POST, &format!("tailnet/{}/acl", self.domain), policy, None);
+        request
+            .headers_mut()
+            .insert(header::ACCEPT, header::HeaderValue::from_static("application/json"));
+        if let Some(etag) = etag {
+            request
+                .headers_mut()
+                .insert(header::IF_MATCH, header::HeaderValue::from_str(etag).unwrap());
+        }
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(resp.json().await.unwrap())
+    }
+
+    /// List the auth keys of the tailnet. Only the ids are returned, use `get_key` for the rest.
+    pub async fn list_keys(&self) -> Result<Vec<AuthKey>, APIError> {
+        let request = self.request(Method::GET, &format!("tailnet/{}/keys", self.domain), (), None);
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        let r: KeysResponse = resp.json().await.unwrap();
+
+        Ok(r.keys)
+    }
+
+    /// Get an auth key. The secret itself is only ever returned when the key is created.
+    pub async fn get_key(&self, key_id: &str) -> Result<AuthKey, APIError> {
+        let request = self.request(
+            Method::GET,
+            &format!("tailnet/{}/keys/{}", self.domain, key_id),
+            (),
+            None,
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(resp.json().await.unwrap())
+    }
+
+    /// Create an auth key that devices can use to join the tailnet.
+    pub async fn create_key(&self, key: &CreateKeyRequest) -> Result<AuthKey, APIError> {
+        let request = self.request(Method::POST, &format!("tailnet/{}/keys", self.domain), key, None);
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(resp.json().await.unwrap())
+    }
+
+    /// Revoke an auth key. Devices that already joined with the key are not affected.
+    pub async fn delete_key(&self, key_id: &str) -> Result<(), APIError> {
+        let request = self.request(
+            Method::DELETE,
+            &format!("tailnet/{}/keys/{}", self.domain, key_id),
+            (),
+            None,
+        );
+
+        let resp = self.client.execute(request).await.unwrap();
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
+        Ok(())
+    }
 }
 
 /// Error type returned by our library.
@@ -250,6 +530,8 @@ pub struct Device {
     pub expires: DateTime<Utc>,
     #[serde(default, rename = "neverExpires")]
     pub never_expires: bool,
+    #[serde(default, rename = "keyExpiryDisabled")]
+    pub key_expiry_disabled: bool,
     #[serde(default)]
     pub authorized: bool,
     #[serde(default, rename = "isExternal")]
@@ -260,4 +542,168 @@ pub struct Device {
     pub route_all: bool,
     #[serde(default, rename = "hasSubnet")]
     pub has_subnet: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+struct DeviceAuthorizedRequest {
+    authorized: bool,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+struct DeviceTagsRequest {
+    tags: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+struct DeviceKeyRequest {
+    #[serde(rename = "keyExpiryDisabled")]
+    key_expiry_disabled: bool,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+struct DeviceRoutesRequest {
+    routes: Vec<String>,
+}
+
+/// The subnet routes of a device.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct DeviceRoutes {
+    /// The routes the device offers to the tailnet.
+    #[serde(default, rename = "advertisedRoutes")]
+    pub advertised_routes: Vec<String>,
+    /// The routes that have been approved.
+    #[serde(default, rename = "enabledRoutes")]
+    pub enabled_routes: Vec<String>,
+}
+
+/// An ACL policy file along with the ETag it was read with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Acl {
+    pub policy: AclPolicy,
+    pub etag: Option<String>,
+}
+
+/// The data type for an ACL policy file. Only the sections we manage are typed, the rest are
+/// kept as is so that they survive being written back.
+///
+/// FROM: https://tailscale.com/kb/1018/acls/
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct AclPolicy {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub acls: Vec<AclRule>,
+    /// Groups of users keyed by their name, which must start with `group:`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub groups: BTreeMap<String, Vec<String>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub hosts: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty", rename = "tagOwners")]
+    pub tag_owners: BTreeMap<String, Vec<String>>,
+    #[serde(flatten)]
+    pub other: BTreeMap<String, serde_json::Value>,
+}
+
+/// The data type for a single rule of an ACL policy file.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct AclRule {
+    pub action: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub proto: String,
+    #[serde(default, alias = "users")]
+    pub src: Vec<String>,
+    #[serde(default, alias = "ports")]
+    pub dst: Vec<String>,
+}
+
+/// The result of validating an ACL policy file. A valid policy has an empty message.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct AclValidation {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub data: Vec<serde_json::Value>,
+}
+
+impl AclValidation {
+    pub fn is_valid(&self) -> bool {
+        self.message.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+struct KeysResponse {
+    #[serde(default)]
+    keys: Vec<AuthKey>,
+}
+
+/// The data type for an auth key.
+///
+/// FROM: https://github.com/tailscale/tailscale/blob/main/api.md#keys
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct AuthKey {
+    pub id: String,
+    /// The secret, only set in the response to creating the key.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub key: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revoked: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub capabilities: KeyCapabilities,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct KeyCapabilities {
+    #[serde(default)]
+    pub devices: KeyDeviceCapabilities,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct KeyDeviceCapabilities {
+    #[serde(default)]
+    pub create: KeyCreateCapabilities,
+}
+
+/// What devices that join the tailnet with the key are allowed to do.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct KeyCreateCapabilities {
+    /// The key can be used more than once.
+    #[serde(default)]
+    pub reusable: bool,
+    /// Devices are removed from the tailnet once they go offline.
+    #[serde(default)]
+    pub ephemeral: bool,
+    /// Devices do not need to be approved by an admin.
+    #[serde(default)]
+    pub preauthorized: bool,
+    /// The ACL tags applied to devices, ie. `tag:ci`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// The body of a request to create an auth key.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct CreateKeyRequest {
+    pub capabilities: KeyCapabilities,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "expirySeconds")]
+    pub expiry_seconds: Option<i64>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+}
+
+impl CreateKeyRequest {
+    pub fn new(capabilities: KeyCreateCapabilities) -> Self {
+        Self {
+            capabilities: KeyCapabilities {
+                devices: KeyDeviceCapabilities { create: capabilities },
+            },
+            ..Default::default()
+        }
+    }
 }
@@ -0,0 +1,67 @@
+/*!
+ * A typed client for webhooky, generated from the OpenAPI document the server describes itself
+ * with. There is a method on [`Client`] for every endpoint, and a type in [`types`] for every
+ * request and response body.
+ *
+ * The document is checked in at `webhooky/openapi-webhooky.json`. After changing the endpoints
+ * of the server, write it out again with:
+ *
+ * ```text
+ * cargo run --bin webhooky -- create-server-spec webhooky/openapi-webhooky.json
+ * ```
+ */
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+
+progenitor::generate_api!("../webhooky/openapi-webhooky.json");
+
+#[derive(Debug)]
+pub enum WebhookyClientError {
+    Client(reqwest::Error),
+    InvalidToken(reqwest::header::InvalidHeaderValue),
+}
+
+impl std::fmt::Display for WebhookyClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WebhookyClientError::Client(inner) => write!(f, "Client error: {inner}"),
+            WebhookyClientError::InvalidToken(inner) => write!(f, "Invalid token: {inner}"),
+        }
+    }
+}
+
+impl std::error::Error for WebhookyClientError {}
+
+impl Client {
+    /// Create a client that sends `token` as a bearer token with every request. Most endpoints
+    /// that internal tools call, ie. the `/admin` and `/run` endpoints, take the internal token.
+    pub fn new_with_token(baseurl: &str, token: &str) -> Result<Self, WebhookyClientError> {
+        let mut auth = HeaderValue::from_str(&format!("Bearer {token}")).map_err(WebhookyClientError::InvalidToken)?;
+        auth.set_sensitive(true);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, auth);
+
+        let client = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .map_err(WebhookyClientError::Client)?;
+
+        Ok(Client::new_with_client(baseurl, client))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Client, WebhookyClientError};
+
+    #[test]
+    fn test_new_with_token() {
+        let client = Client::new_with_token("https://webhooks.example.com", "secret").unwrap();
+        assert_eq!("https://webhooks.example.com", client.baseurl().as_str());
+
+        assert!(matches!(
+            Client::new_with_token("https://webhooks.example.com", "not\na token"),
+            Err(WebhookyClientError::InvalidToken(_))
+        ));
+    }
+}
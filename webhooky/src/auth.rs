@@ -1,6 +1,12 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use dropshot_verify_request::{bearer::BearerProvider, query::QueryTokenProvider};
+use dropshot::{ApiEndpointBodyContentType, Extractor, ExtractorMetadata, HttpError, RequestContext, ServerContext};
+use dropshot_verify_request::{
+    bearer::{BearerAudit, BearerProvider},
+    jwt::{JwtAudit, Oidc, OidcProvider},
+    query::QueryTokenProvider,
+};
+use std::sync::Arc;
 
 pub struct InternalToken;
 
@@ -54,6 +60,24 @@ impl QueryTokenProvider for ShippoToken {
     }
 }
 
+pub struct SendGridToken;
+
+#[async_trait]
+impl QueryTokenProvider for SendGridToken {
+    async fn token() -> Result<String> {
+        Ok(std::env::var("SENDGRID_WH_KEY")?)
+    }
+}
+
+pub struct CareersInboxToken;
+
+#[async_trait]
+impl QueryTokenProvider for CareersInboxToken {
+    async fn token() -> Result<String> {
+        Ok(std::env::var("CAREERS_INBOX_WH_KEY")?)
+    }
+}
+
 pub struct MailChimpToken;
 
 #[async_trait]
@@ -62,3 +86,65 @@ impl QueryTokenProvider for MailChimpToken {
         Ok(std::env::var("MAILCHIMP_WH_KEY")?)
     }
 }
+
+/// The identity provider that issues short-lived tokens to internal tools.
+pub struct InternalIdentity;
+
+#[async_trait]
+impl OidcProvider for InternalIdentity {
+    async fn issuer() -> Result<String> {
+        Ok(std::env::var("INTERNAL_OIDC_ISSUER")?)
+    }
+
+    async fn audience() -> Result<Vec<String>> {
+        Ok(std::env::var("INTERNAL_OIDC_AUDIENCE")?
+            .split(',')
+            .map(|audience| audience.trim().to_string())
+            .filter(|audience| !audience.is_empty())
+            .collect())
+    }
+}
+
+/// Authorization for internal endpoints. A request is authorized if it carries either the
+/// static internal bearer token, or a token issued by the internal identity provider when
+/// `INTERNAL_OIDC_ISSUER` is configured.
+pub struct InternalAuth;
+
+#[async_trait]
+impl Extractor for InternalAuth {
+    async fn from_request<Context: ServerContext>(
+        rqctx: Arc<RequestContext<Context>>,
+    ) -> Result<InternalAuth, HttpError> {
+        // The static token may be removed once every caller has moved to short-lived tokens, so
+        // a missing token is treated the same as a token that does not match
+        let bearer = BearerAudit::<InternalToken>::from_request(rqctx.clone())
+            .await
+            .map(|audit| audit.verified())
+            .unwrap_or(false);
+
+        if bearer {
+            return Ok(InternalAuth);
+        }
+
+        if std::env::var("INTERNAL_OIDC_ISSUER").is_ok() {
+            let jwt = JwtAudit::<Oidc<InternalIdentity>>::from_request(rqctx).await?;
+
+            if jwt.verified() {
+                return Ok(InternalAuth);
+            }
+        }
+
+        Err(HttpError::for_client_error(
+            None,
+            http::StatusCode::UNAUTHORIZED,
+            "".to_string(),
+        ))
+    }
+
+    fn metadata(_body_content_type: ApiEndpointBodyContentType) -> ExtractorMetadata {
+        ExtractorMetadata {
+            paginated: false,
+            parameters: vec![],
+        }
+    }
+}
@@ -5,6 +5,7 @@ use cio_api::{
     companies::{Company, Companys},
     configs::get_configs_from_repo,
     db::Database,
+    redaction,
 };
 use std::sync::{Arc, RwLock};
 
@@ -48,6 +49,11 @@ impl Context {
         let github = company.authenticate_github()?;
         let configs = get_configs_from_repo(&github, &company).await?;
 
+        // Logs and Sentry events are redacted with the built-in classes until now.
+        if let Err(e) = redaction::configure(&configs.app_config.redaction) {
+            log::warn!("failed to configure redaction, using the built-in classes: {:?}", e);
+        }
+
         // Create the context.
         Ok(Context {
             app_config: Arc::new(RwLock::new(configs.app_config)),
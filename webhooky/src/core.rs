@@ -22,21 +22,44 @@ pub enum SubCommand {
     Server(Server),
 
     CreateServerSpec(SpecOut),
+    #[clap(name = "backfill-rfd-history")]
+    BackfillRFDHistory(BackfillRFDHistory),
+    RenewCertificates(RenewCertificates),
+    RevokeExpiredAccess(RevokeExpiredAccess),
+    RotateTailscaleKeys(RotateTailscaleKeys),
+    SendCelebrations(SendCelebrations),
+    SendExpenseReport(SendExpenseReport),
+    SendFinanceSummary(SendFinanceSummary),
+    SendInterviewerPackets(SendInterviewerPackets),
+    SendRecruitingSummary(SendRecruitingSummary),
     SendRFDChangelog(SendRFDChangelog),
+    SendRFDReviewReminders(SendRFDReviewReminders),
+    SendScheduledMessages(SendScheduledMessages),
+    SendWebhookDeliveries(SendWebhookDeliveries),
     SyncAnalytics(SyncAnalytics),
     #[clap(name = "sync-api-tokens")]
     SyncAPITokens(SyncAPITokens),
+    SyncApplicantDuplicates(SyncApplicantDuplicates),
     SyncApplications(SyncApplications),
     SyncAssetInventory(SyncAssetInventory),
     SyncCompanies(SyncCompanies),
     SyncConfigs(SyncConfigs),
     SyncFinance(SyncFinance),
     SyncFunctions(SyncFunctions),
+    #[clap(name = "sync-github-repo-settings")]
+    SyncGitHubRepoSettings(SyncGitHubRepoSettings),
+    SyncGitHubTeams(SyncGitHubTeams),
+    SyncGoogleGroups(SyncGoogleGroups),
+    SyncGusto(SyncGusto),
     SyncHuddles(SyncHuddles),
     SyncInterviews(SyncInterviews),
     SyncJournalClubs(SyncJournalClubs),
     SyncMailingLists(SyncMailingLists),
+    SyncOffboarding(SyncOffboarding),
+    SyncOnboarding(SyncOnboarding),
+    SyncOrgChart(SyncOrgChart),
     SyncOther(SyncOther),
+    SyncPagerDuty(SyncPagerDuty),
     SyncRecordedMeetings(SyncRecordedMeetings),
     SyncRepos(SyncRepos),
     #[clap(name = "sync-rfds")]
@@ -68,10 +91,62 @@ pub struct SpecOut {
     pub spec_file: std::path::PathBuf,
 }
 
+/// A subcommand for reconstructing the history of RFDs from the git history of the rfd repo.
+#[derive(Parser, Clone, Debug)]
+pub struct BackfillRFDHistory {
+    /// Only backfill the RFD with this number
+    #[clap(long)]
+    pub number: Option<i32>,
+}
+
+/// A subcommand for renewing the certificates that are about to expire.
+#[derive(Parser, Clone, Debug)]
+pub struct RenewCertificates {}
+
+/// A subcommand for revoking the access that was granted through access requests once it expires.
+#[derive(Parser, Clone, Debug)]
+pub struct RevokeExpiredAccess {}
+
+/// A subcommand for rotating the Tailscale auth keys used by our infrastructure.
+#[derive(Parser, Clone, Debug)]
+pub struct RotateTailscaleKeys {}
+
+/// A subcommand for posting birthdays and work anniversaries, and the weekly digest for managers.
+#[derive(Parser, Clone, Debug)]
+pub struct SendCelebrations {}
+
+/// A subcommand for sending the weekly report of expenses that look out of the ordinary.
+#[derive(Parser, Clone, Debug)]
+pub struct SendExpenseReport {}
+
+/// A subcommand for sending the monthly finance summary.
+#[derive(Parser, Clone, Debug)]
+pub struct SendFinanceSummary {}
+
+/// A subcommand for sending interviewers the packets for their interviews the next day.
+#[derive(Parser, Clone, Debug)]
+pub struct SendInterviewerPackets {}
+
+/// A subcommand for sending the weekly summary of the recruiting funnel.
+#[derive(Parser, Clone, Debug)]
+pub struct SendRecruitingSummary {}
+
 /// A subcommand for sending the RFD changelog.
 #[derive(Parser, Clone, Debug)]
 pub struct SendRFDChangelog {}
 
+/// A subcommand for reminding the reviewers of RFDs in discussion that have not been reviewed.
+#[derive(Parser, Clone, Debug)]
+pub struct SendRFDReviewReminders {}
+
+/// A subcommand for sending the queued messages whose recipients' working hours have started.
+#[derive(Parser, Clone, Debug)]
+pub struct SendScheduledMessages {}
+
+/// A subcommand for posting the events that are due to the webhook consumers that registered for them.
+#[derive(Parser, Clone, Debug)]
+pub struct SendWebhookDeliveries {}
+
 /// A subcommand for running the background job of syncing analytics.
 #[derive(Parser, Debug, Clone)]
 pub struct SyncAnalytics {}
@@ -80,9 +155,17 @@ pub struct SyncAnalytics {}
 #[derive(Parser, Debug, Clone)]
 pub struct SyncAPITokens {}
 
+/// A subcommand for running the background job of finding and merging duplicate applicants.
+#[derive(Parser, Debug, Clone)]
+pub struct SyncApplicantDuplicates {}
+
 /// A subcommand for running the background job of syncing applications.
 #[derive(Parser, Debug, Clone)]
-pub struct SyncApplications {}
+pub struct SyncApplications {
+    /// Only sync the applicant with this email
+    #[clap(long)]
+    pub email: Option<String>,
+}
 
 /// A subcommand for running the background job of syncing asset inventory.
 #[derive(Parser, Debug, Clone)]
@@ -104,6 +187,22 @@ pub struct SyncFinance {}
 #[derive(Parser, Debug, Clone)]
 pub struct SyncFunctions {}
 
+/// A subcommand for enforcing the settings of the repos in the GitHub org from the configs.
+#[derive(Parser, Debug, Clone)]
+pub struct SyncGitHubRepoSettings {}
+
+/// A subcommand for running the background job of syncing GitHub teams, their members and repos.
+#[derive(Parser, Debug, Clone)]
+pub struct SyncGitHubTeams {}
+
+/// A subcommand for running the background job of syncing Google groups and their members.
+#[derive(Parser, Debug, Clone)]
+pub struct SyncGoogleGroups {}
+
+/// A subcommand for running the background job of syncing employee records from Gusto.
+#[derive(Parser, Debug, Clone)]
+pub struct SyncGusto {}
+
 /// A subcommand for running the background job of syncing interviews.
 #[derive(Parser, Debug, Clone)]
 pub struct SyncInterviews {}
@@ -120,10 +219,26 @@ pub struct SyncJournalClubs {}
 #[derive(Parser, Debug, Clone)]
 pub struct SyncMailingLists {}
 
+/// A subcommand for running the background job of offboarding people whose last day has passed.
+#[derive(Parser, Debug, Clone)]
+pub struct SyncOffboarding {}
+
+/// A subcommand for running the background job of running the onboarding tasks of new hires.
+#[derive(Parser, Debug, Clone)]
+pub struct SyncOnboarding {}
+
+/// A subcommand for running the background job of updating the org chart in the configs repo.
+#[derive(Parser, Debug, Clone)]
+pub struct SyncOrgChart {}
+
 /// A subcommand for running the background job of syncing other things.
 #[derive(Parser, Debug, Clone)]
 pub struct SyncOther {}
 
+/// A subcommand for running the background job of syncing PagerDuty teams and who is on call.
+#[derive(Parser, Debug, Clone)]
+pub struct SyncPagerDuty {}
+
 /// A subcommand for running the background job of syncing recorded_meetings.
 #[derive(Parser, Debug, Clone)]
 pub struct SyncRecordedMeetings {}
@@ -134,7 +249,11 @@ pub struct SyncRepos {}
 
 /// A subcommand for running the background job of syncing RFDs.
 #[derive(Parser, Debug, Clone)]
-pub struct SyncRFDs {}
+pub struct SyncRFDs {
+    /// Only sync the RFD with this number
+    #[clap(long)]
+    pub number: Option<i32>,
+}
 
 /// A subcommand for running the background job of syncing shipments.
 #[derive(Parser, Debug, Clone)]
@@ -156,25 +275,63 @@ pub struct SyncTravel {}
 #[derive(Parser, Debug, Clone)]
 pub struct SyncZoho {}
 
+/// Parse the name of a background job into the subcommand that runs it. Arguments to the job
+/// follow its name the same way they do on the command line, ie. `sync-rfds --number 123`.
 pub fn into_job_command(cmd: &str) -> Option<SubCommand> {
+    let mut parts = cmd.split_whitespace();
+    let name = parts.next()?;
+    let args = parts.collect::<Vec<_>>();
+
+    let job = job_without_args(name)?;
+    if args.is_empty() {
+        return Some(job);
+    }
+
+    Opts::try_parse_from(["webhooky", name].into_iter().chain(args))
+        .ok()
+        .map(|opts| opts.subcmd)
+}
+
+fn job_without_args(cmd: &str) -> Option<SubCommand> {
     match cmd {
+        "backfill-rfd-history" => Some(SubCommand::BackfillRFDHistory(BackfillRFDHistory { number: None })),
+        "renew-certificates" => Some(SubCommand::RenewCertificates(RenewCertificates {})),
+        "revoke-expired-access" => Some(SubCommand::RevokeExpiredAccess(RevokeExpiredAccess {})),
+        "rotate-tailscale-keys" => Some(SubCommand::RotateTailscaleKeys(RotateTailscaleKeys {})),
+        "send-celebrations" => Some(SubCommand::SendCelebrations(SendCelebrations {})),
+        "send-expense-report" => Some(SubCommand::SendExpenseReport(SendExpenseReport {})),
+        "send-finance-summary" => Some(SubCommand::SendFinanceSummary(SendFinanceSummary {})),
+        "send-interviewer-packets" => Some(SubCommand::SendInterviewerPackets(SendInterviewerPackets {})),
+        "send-recruiting-summary" => Some(SubCommand::SendRecruitingSummary(SendRecruitingSummary {})),
         "send-rfd-changelog" => Some(SubCommand::SendRFDChangelog(SendRFDChangelog {})),
+        "send-rfd-review-reminders" => Some(SubCommand::SendRFDReviewReminders(SendRFDReviewReminders {})),
+        "send-scheduled-messages" => Some(SubCommand::SendScheduledMessages(SendScheduledMessages {})),
+        "send-webhook-deliveries" => Some(SubCommand::SendWebhookDeliveries(SendWebhookDeliveries {})),
         "sync-analytics" => Some(SubCommand::SyncAnalytics(SyncAnalytics {})),
         "sync-api-tokens" => Some(SubCommand::SyncAPITokens(SyncAPITokens {})),
-        "sync-applications" => Some(SubCommand::SyncApplications(SyncApplications {})),
+        "sync-applicant-duplicates" => Some(SubCommand::SyncApplicantDuplicates(SyncApplicantDuplicates {})),
+        "sync-applications" => Some(SubCommand::SyncApplications(SyncApplications { email: None })),
         "sync-asset-inventory" => Some(SubCommand::SyncAssetInventory(SyncAssetInventory {})),
         "sync-companies" => Some(SubCommand::SyncCompanies(SyncCompanies {})),
         "sync-configs" => Some(SubCommand::SyncConfigs(SyncConfigs {})),
         "sync-finance" => Some(SubCommand::SyncFinance(SyncFinance {})),
         "sync-functions" => Some(SubCommand::SyncFunctions(SyncFunctions {})),
+        "sync-github-repo-settings" => Some(SubCommand::SyncGitHubRepoSettings(SyncGitHubRepoSettings {})),
+        "sync-github-teams" => Some(SubCommand::SyncGitHubTeams(SyncGitHubTeams {})),
+        "sync-google-groups" => Some(SubCommand::SyncGoogleGroups(SyncGoogleGroups {})),
+        "sync-gusto" => Some(SubCommand::SyncGusto(SyncGusto {})),
         "sync-huddles" => Some(SubCommand::SyncHuddles(SyncHuddles {})),
         "sync-interviews" => Some(SubCommand::SyncInterviews(SyncInterviews {})),
         "sync-journal-clubs" => Some(SubCommand::SyncJournalClubs(SyncJournalClubs {})),
         "sync-mailing-lists" => Some(SubCommand::SyncMailingLists(SyncMailingLists {})),
+        "sync-offboarding" => Some(SubCommand::SyncOffboarding(SyncOffboarding {})),
+        "sync-onboarding" => Some(SubCommand::SyncOnboarding(SyncOnboarding {})),
+        "sync-org-chart" => Some(SubCommand::SyncOrgChart(SyncOrgChart {})),
         "sync-other" => Some(SubCommand::SyncOther(SyncOther {})),
+        "sync-pagerduty" => Some(SubCommand::SyncPagerDuty(SyncPagerDuty {})),
         "sync-recorded-meetings" => Some(SubCommand::SyncRecordedMeetings(SyncRecordedMeetings {})),
         "sync-repos" => Some(SubCommand::SyncRepos(SyncRepos {})),
-        "sync-rfds" => Some(SubCommand::SyncRFDs(SyncRFDs {})),
+        "sync-rfds" => Some(SubCommand::SyncRFDs(SyncRFDs { number: None })),
         "sync-shipments" => Some(SubCommand::SyncShipments(SyncShipments {})),
         "sync-shorturls" => Some(SubCommand::SyncShorturls(SyncShorturls {})),
         "sync-swag-inventory" => Some(SubCommand::SyncSwagInventory(SyncSwagInventory {})),
@@ -183,3 +340,35 @@ pub fn into_job_command(cmd: &str) -> Option<SubCommand> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{into_job_command, SubCommand};
+
+    #[test]
+    fn test_into_job_command() {
+        assert!(matches!(
+            into_job_command("sync-rfds"),
+            Some(SubCommand::SyncRFDs(cmd)) if cmd.number.is_none()
+        ));
+        assert!(matches!(
+            into_job_command("sync-rfds --number 123"),
+            Some(SubCommand::SyncRFDs(cmd)) if cmd.number == Some(123)
+        ));
+        assert!(matches!(
+            into_job_command("backfill-rfd-history --number 12"),
+            Some(SubCommand::BackfillRFDHistory(cmd)) if cmd.number == Some(12)
+        ));
+        assert!(matches!(
+            into_job_command("sync-applications --email jane@example.com"),
+            Some(SubCommand::SyncApplications(cmd)) if cmd.email.as_deref() == Some("jane@example.com")
+        ));
+
+        // Only background jobs can be run, and only with the arguments they take.
+        assert!(into_job_command("server").is_none());
+        assert!(into_job_command("server --do-cron").is_none());
+        assert!(into_job_command("sync-rfds --email jane@example.com").is_none());
+        assert!(into_job_command("sync-rfds --number not-a-number").is_none());
+        assert!(into_job_command("").is_none());
+    }
+}
@@ -0,0 +1,247 @@
+/*!
+ * Processing inbound webhooks off of the request path.
+ *
+ * Endpoints for webhooks that trigger slow work only verify and store the event, and then
+ * respond right away. Workers drain the stored events in the background, with a fixed number
+ * of workers for each source so that a burst of events from one service can not starve the
+ * others. When too many events from a source are waiting, new ones are turned away with a
+ * `503` so that the sender backs off and redelivers them later.
+ *
+ * Each instance of the server runs its own workers, so the concurrency limits are per instance.
+ *
+ * Events keep the correlation id of the request they arrived with, and workers process them
+ * under that id, so the work an event triggers can be traced back to the webhook that sent it.
+ */
+use std::{str::FromStr, time::Duration};
+
+use anyhow::Result;
+use cio_api::{
+    correlation::{self, CorrelationId},
+    webhook_events::{NewWebhookEvent, WebhookEvent},
+};
+use dropshot::HttpError;
+use log::{info, warn};
+use serde::Serialize;
+
+use crate::{context::ServerContext, event_types::EventType};
+
+/// How many times processing an event is attempted before it is left as failed.
+const MAX_ATTEMPTS: i32 = 5;
+
+/// How long an event can be processing before it is assumed its worker went away.
+const STALE_AFTER_MINUTES: i64 = 30;
+
+/// How long an idle worker waits before checking the queue again.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventSource {
+    GitHub,
+    QuickBooks,
+    Stripe,
+}
+
+impl EventSource {
+    pub const ALL: [EventSource; 3] = [EventSource::GitHub, EventSource::QuickBooks, EventSource::Stripe];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventSource::GitHub => "github",
+            EventSource::QuickBooks => "quickbooks",
+            EventSource::Stripe => "stripe",
+        }
+    }
+
+    /// How many events from this source each instance processes at once. QuickBooks events
+    /// each sync everything that changed since they were sent, so running them one at a time
+    /// avoids syncing the same changes over and over.
+    pub fn concurrency(&self) -> usize {
+        match self {
+            EventSource::GitHub => 4,
+            EventSource::QuickBooks => 1,
+            EventSource::Stripe => 2,
+        }
+    }
+
+    /// How many events from this source can be waiting before new ones are turned away.
+    pub fn max_pending(&self) -> i64 {
+        match self {
+            EventSource::GitHub => 500,
+            EventSource::QuickBooks => 100,
+            EventSource::Stripe => 500,
+        }
+    }
+}
+
+/// Whether another event can be accepted from a source that has `pending` events waiting.
+fn has_capacity(source: EventSource, pending: i64) -> bool {
+    pending < source.max_pending()
+}
+
+/// Store an event to be processed by the workers for `source`. Responds with a `503` when the
+/// queue for the source is full.
+pub async fn enqueue<T: Serialize>(
+    api_context: &ServerContext,
+    source: EventSource,
+    event_type: &str,
+    payload: &T,
+    correlation_id: &CorrelationId,
+) -> Result<(), HttpError> {
+    let db = &api_context.app.db;
+
+    let pending = WebhookEvent::count_pending(db, source.as_str())
+        .await
+        .map_err(internal_error)?;
+    if !has_capacity(source, pending) {
+        warn!(
+            "turning away {} webhook, {} events are already waiting",
+            source.as_str(),
+            pending
+        );
+        return Err(HttpError::for_status(None, http::StatusCode::SERVICE_UNAVAILABLE));
+    }
+
+    let event = NewWebhookEvent {
+        source: source.as_str().to_string(),
+        event_type: event_type.to_string(),
+        payload: serde_json::to_value(payload).map_err(|e| internal_error(e.into()))?,
+        correlation_id: correlation_id.to_string(),
+    }
+    .enqueue(db)
+    .await
+    .map_err(internal_error)?;
+
+    info!(
+        "queued {} webhook event {} `{}` ({})",
+        source.as_str(),
+        event.id,
+        event_type,
+        event.correlation_id
+    );
+
+    Ok(())
+}
+
+fn internal_error(err: anyhow::Error) -> HttpError {
+    sentry::integrations::anyhow::capture_anyhow(&err);
+    HttpError::for_internal_error(format!("{:?}", err))
+}
+
+/// Start the workers for every source. They run for as long as the server does.
+pub fn start_workers(api_context: &ServerContext) {
+    for source in EventSource::ALL {
+        for _ in 0..source.concurrency() {
+            tokio::spawn(run_worker(api_context.clone(), source));
+        }
+    }
+}
+
+async fn run_worker(api_context: ServerContext, source: EventSource) {
+    let stale_after = chrono::Duration::minutes(STALE_AFTER_MINUTES);
+
+    loop {
+        match WebhookEvent::claim_next(&api_context.app.db, source.as_str(), stale_after).await {
+            Ok(Some(event)) => process(&api_context, source, event).await,
+            Ok(None) => tokio::time::sleep(POLL_INTERVAL).await,
+            Err(e) => {
+                warn!("claiming a {} webhook event failed: {}", source.as_str(), e);
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        }
+    }
+}
+
+async fn process(api_context: &ServerContext, source: EventSource, event: WebhookEvent) {
+    let db = &api_context.app.db;
+
+    // Events queued before they carried a correlation id get one of their own.
+    let correlation_id = CorrelationId::parse(&event.correlation_id).unwrap_or_default();
+
+    info!(
+        "processing {} webhook event {} `{}` (attempt {}, {})",
+        source.as_str(),
+        event.id,
+        event.event_type,
+        event.attempts,
+        correlation_id
+    );
+
+    let result = match correlation::scope(correlation_id, dispatch(api_context, source, &event)).await {
+        Ok(()) => event.complete(db).await,
+        Err(err) => match event.fail(db, &format!("{:?}", err), MAX_ATTEMPTS).await {
+            Ok(true) => {
+                warn!(
+                    "{} webhook event {} failed, it will be retried: {:?}",
+                    source.as_str(),
+                    event.id,
+                    err
+                );
+                Ok(())
+            }
+            Ok(false) => {
+                warn!(
+                    "{} webhook event {} failed {} times, giving up: {:?}",
+                    source.as_str(),
+                    event.id,
+                    event.attempts,
+                    err
+                );
+                sentry::integrations::anyhow::capture_anyhow(&err);
+                Ok(())
+            }
+            Err(e) => Err(e),
+        },
+    };
+
+    if let Err(e) = result {
+        warn!(
+            "recording the outcome of {} webhook event {} failed: {}",
+            source.as_str(),
+            event.id,
+            e
+        );
+    }
+}
+
+async fn dispatch(api_context: &ServerContext, source: EventSource, event: &WebhookEvent) -> Result<()> {
+    match source {
+        EventSource::GitHub => {
+            let event_type = EventType::from_str(&event.event_type).map_err(anyhow::Error::msg)?;
+            crate::handlers_github::handle_github(
+                api_context,
+                event_type,
+                serde_json::from_value(event.payload.clone())?,
+            )
+            .await
+        }
+        EventSource::QuickBooks => {
+            crate::handlers::handle_quickbooks_webhook(api_context, serde_json::from_value(event.payload.clone())?)
+                .await
+        }
+        EventSource::Stripe => {
+            crate::handlers::handle_stripe_webhook(api_context, serde_json::from_value(event.payload.clone())?).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{has_capacity, EventSource};
+
+    #[test]
+    fn test_has_capacity() {
+        assert!(has_capacity(EventSource::GitHub, 0));
+        assert!(has_capacity(EventSource::GitHub, 499));
+        assert!(!has_capacity(EventSource::GitHub, 500));
+        assert!(has_capacity(EventSource::QuickBooks, 99));
+        assert!(!has_capacity(EventSource::QuickBooks, 100));
+    }
+
+    #[test]
+    fn test_every_source_has_a_worker() {
+        for source in EventSource::ALL {
+            assert!(source.concurrency() > 0, "{} has no workers", source.as_str());
+            assert!(source.max_pending() > 0, "{} has no room in its queue", source.as_str());
+        }
+    }
+}
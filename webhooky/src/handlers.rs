@@ -2,42 +2,75 @@ use std::{collections::HashMap, ffi::OsStr, str::FromStr, sync::Arc};
 
 use anyhow::{bail, Result};
 use async_bb8_diesel::AsyncRunQueryDsl;
-use chrono::{TimeZone, Utc};
+use chrono::{Duration, TimeZone, Utc};
 use chrono_humanize::HumanTime;
 use cio_api::{
+    access_requests::{decide_access_request, handle_access_command, ACCESS_APPROVE_ACTION, ACCESS_REJECT_ACTION},
     analytics::NewPageView,
+    api_tokens::APIToken,
+    app_config::MailingListProviderKind,
     applicants::Applicant,
+    asset_assignments::{assignment_history, AssetAssignment, CheckInRequest, CheckOutRequest},
     asset_inventory::AssetItem,
+    background_checks::{
+        export_background_checks, record_applicant_background_check, update_employee_background_check,
+    },
+    careers_inbox::{
+        classify_attachments, find_applicant, ingest_application, match_role, parse_sender, post_unmatched_application,
+        profile_links, InboundApplication, InboundEmail,
+    },
+    celebrations::handle_celebrations_command,
     certs::Certificate,
-    companies::Company,
+    companies::{Company, NewCompany},
     configs::User,
+    data_subjects::{erase_data_subject, export_data_subject, DataSubjectErasure},
+    db::Database,
+    device_credentials::{download_device_credential, find_device_credential_download},
+    directory::{directory, directory_profile, DirectoryProfile, DirectoryQuery},
+    email::{record_sendgrid_events, SendGridEvent},
+    email_suppressions::{suppress_email, SuppressionReason},
+    journal_club_proposals::handle_papers_command,
     journal_clubs::JournalClubMeeting,
-    rfd::RFD,
-    schema::{applicants, inbound_shipments, journal_club_meetings, outbound_shipments},
-    shipments::{InboundShipment, NewInboundShipment, OutboundShipment, OutboundShipments},
+    mailing_list::{MailingListSubscriber, NewMailingListSubscriber},
+    mailing_list_providers::MailingListProviderOps,
+    notifications::{notify, Notification, NotificationEvent},
+    offer_approvals::{OFFER_APPROVE_ACTION, OFFER_REJECT_ACTION},
+    provisioning::ProvisioningReport,
+    schema::{applicants, inbound_shipments, journal_club_meetings, outbound_shipments, users},
+    shipments::{InboundShipment, NewInboundShipment, NewOutboundShipment, OutboundShipment, OutboundShipments},
     swag_inventory::SwagInventoryItem,
+    swag_preferences::{
+        get_swag_preferences_form, request_swag_preferences, submit_swag_preferences, SwagPreferences,
+        SwagPreferencesForm, SwagRecipient,
+    },
+    swag_stock::{receive_stock, StockReceipt, SwagStockLevel},
     swag_store::Order,
     utils::{decode_base64, merge_json},
+    webhook_outbox::{publish, OutboxEvent},
 };
 use diesel::{BoolExpressionMethods, ExpressionMethods, PgTextExpressionMethods, QueryDsl};
 use dropshot::{Path, RequestContext};
-use google_drive::traits::{DriveOps, FileOps};
+use google_drive::{
+    traits::{DriveOps, FileOps},
+    Client as GoogleDrive,
+};
 use log::{info, warn};
+use mailerlite::WebhookEventType;
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
 use slack_chat_api::{
     BotCommand, FormattedMessage, InputBlock, InputBlockElement, InputType, InteractivePayload, InteractiveResponse,
     MessageAttachment, MessageBlock, MessageBlockText, MessageBlockType, MessageResponse, MessageResponseType,
-    MessageType, SelectInputOption, View,
+    MessageType, SelectInputOption, Slack, View,
 };
 
 use crate::{
     context::ServerContext,
-    handlers_github::RFDUpdater,
     server::{
-        AirtableRowEvent, ApplicationFileUploadData, CounterResponse, GitHubRateLimit, RFDPathParams,
-        ShippoTrackingUpdateEvent,
+        AirtableRowEvent, ApplicationFileUploadData, BackgroundCheckExportQuery, CounterResponse,
+        DataSubjectRequestBody, GitHubRateLimit, PolicyPathParams, RFDPathParams, ShippoTrackingUpdateEvent,
     },
     slack_commands::SlackCommand,
+    uploads::{self, DiscardUpload, DriveUpload, UploadLimits, UploadRejection, UploadSink},
 };
 
 pub async fn handle_products_sold_count(rqctx: Arc<RequestContext<ServerContext>>) -> Result<CounterResponse> {
@@ -70,24 +103,7 @@ pub async fn handle_rfd_update_by_number(
     path_params: Path<RFDPathParams>,
 ) -> Result<()> {
     let num = path_params.into_inner().num;
-    info!("triggering an update for RFD number `{}`", num);
-
-    let api_context = rqctx.context();
-    let db = &api_context.app.db;
-
-    // Get the company id for Oxide.
-    // TODO: split this out per company.
-    let oxide = Company::get_from_db(db, "Oxide".to_string()).await.unwrap();
-
-    let rfd = RFD::get_from_db(db, num)
-        .await
-        .ok_or_else(|| anyhow::anyhow!("no RFD was found with number `{}`", num))?;
-
-    let update = rfd.create_sync(&oxide).await?;
-    let updater = RFDUpdater::default();
-    updater.handle(&api_context.app, &[update]).await?;
-
-    Ok(())
+    crate::handlers_rfd::refresh_db_rfd(&rqctx.context().app, num).await
 }
 
 pub async fn handle_github_rate_limit(rqctx: Arc<RequestContext<ServerContext>>) -> Result<GitHubRateLimit> {
@@ -372,7 +388,32 @@ pub async fn handle_slack_commands(
 
             msg
         }
+        SlackCommand::Celebrations => {
+            let reply = handle_celebrations_command(db, &company, &bot_command.user_id, text).await?;
+
+            json!(MessageResponse {
+                response_type: MessageResponseType::Ephemeral,
+                text: reply,
+            })
+        }
+        SlackCommand::Access => {
+            let config = api_context.app.app_config.read().unwrap().access_requests.clone();
+            let reply = handle_access_command(db, &company, &config, &bot_command.user_id, text).await?;
+
+            json!(MessageResponse {
+                response_type: MessageResponseType::Ephemeral,
+                text: reply,
+            })
+        }
         SlackCommand::Papers => {
+            let config = api_context.app.app_config.read().unwrap().journal_club.clone();
+            if let Some(reply) = handle_papers_command(db, &company, &config, &bot_command.user_id, text).await? {
+                return Ok(json!(MessageResponse {
+                    response_type: MessageResponseType::Ephemeral,
+                    text: reply,
+                }));
+            }
+
             // If we asked for the closed meetings then only show those, otherwise
             // default to the open meetings.
             let mut state = "open";
@@ -587,6 +628,62 @@ pub async fn handle_slack_interactive(
                 sentry::integrations::anyhow::capture_anyhow(&anyhow::anyhow!("{:?}", e));
             }
         }
+
+        // Approve or reject the terms of an offer.
+        if action.action_id == OFFER_APPROVE_ACTION || action.action_id == OFFER_REJECT_ACTION {
+            let response = match crate::handlers_hiring::handle_offer_approval_decision(
+                &ctx.app,
+                &company,
+                &action.value,
+                &payload.user.id,
+                action.action_id == OFFER_APPROVE_ACTION,
+            )
+            .await
+            {
+                // Replace the request, so that nobody else decides on it.
+                Ok(decision) => json!({ "replace_original": true, "text": decision }),
+                // Only the person that pressed the button sees why it did not work.
+                Err(e) => json!({
+                    "replace_original": false,
+                    "response_type": "ephemeral",
+                    "text": format!("Deciding on the offer failed: {}", e),
+                }),
+            };
+
+            Slack::post_to_channel(&payload.response_url, &response).await?;
+        }
+
+        // Approve or reject a request for access.
+        if action.action_id == ACCESS_APPROVE_ACTION || action.action_id == ACCESS_REJECT_ACTION {
+            let config = ctx.app.app_config.read().unwrap().access_requests.clone();
+            let decision = match action.value.parse::<i32>() {
+                Ok(id) => {
+                    decide_access_request(
+                        db,
+                        &company,
+                        &config,
+                        id,
+                        &payload.user.id,
+                        action.action_id == ACCESS_APPROVE_ACTION,
+                    )
+                    .await
+                }
+                Err(e) => Err(e.into()),
+            };
+
+            let response = match decision {
+                // Replace the request, so that nobody else decides on it.
+                Ok(decision) => json!({ "replace_original": true, "text": decision }),
+                // Only the person that pressed the button sees why it did not work.
+                Err(e) => json!({
+                    "replace_original": false,
+                    "response_type": "ephemeral",
+                    "text": format!("Deciding on the access request failed: {}", e),
+                }),
+            };
+
+            Slack::post_to_channel(&payload.response_url, &response).await?;
+        }
     }
 
     Ok(interactive_response)
@@ -625,7 +722,8 @@ pub async fn handle_airtable_certificates_renew(
     let mut cert = Certificate::get_from_airtable(&event.record_id, &api_context.app.db, event.cio_company_id).await?;
 
     let company = cert.company(&api_context.app.db).await?;
-    let storage = company.cert_storage().await?;
+    let certificates_config = api_context.app.app_config.read().unwrap().certificates.clone();
+    let storage = company.cert_storage_with_config(&certificates_config).await?;
 
     // Renew the cert.
     cert.renew(&api_context.app.db, &company, &storage)
@@ -658,6 +756,163 @@ pub async fn handle_airtable_assets_items_print_barcode_label(
     Ok(())
 }
 
+pub async fn handle_assets_check_out(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    request: CheckOutRequest,
+) -> Result<AssetAssignment> {
+    let db = &rqctx.context().app.db;
+
+    let mut asset_item = AssetItem::get_by_id(db, request.asset_item_id).await?;
+    asset_item
+        .check_out(db, &request.username, request.due_back, &request.notes)
+        .await
+}
+
+pub async fn handle_assets_check_in(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    request: CheckInRequest,
+) -> Result<Option<AssetAssignment>> {
+    let db = &rqctx.context().app.db;
+
+    let mut asset_item = AssetItem::get_by_id(db, request.asset_item_id).await?;
+    asset_item.check_in(db).await
+}
+
+pub async fn handle_assets_assignment_history(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    asset_item_id: i32,
+) -> Result<Vec<AssetAssignment>> {
+    assignment_history(&rqctx.context().app.db, asset_item_id).await
+}
+
+/// The page a download link opens, which downloads the credential once the employee confirms.
+/// Nothing else on the page comes from the request, so nothing on it needs escaping.
+pub async fn handle_device_credential_confirmation(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    token: String,
+) -> Result<http::Response<hyper::Body>> {
+    let credential = find_device_credential_download(&rqctx.context().app.db, &token).await?;
+
+    let page = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head><meta charset="utf-8"><meta name="robots" content="noindex"><title>Download your {kind}</title></head>
+<body>
+<p>This link works once. Download your {kind} on the device it is for.</p>
+<form method="post"><button type="submit">Download</button></form>
+</body>
+</html>
+"#,
+        kind = credential.description()
+    );
+
+    Ok(http::Response::builder()
+        .status(http::StatusCode::OK)
+        .header(http::header::CONTENT_TYPE, "text/html; charset=utf-8")
+        .header(http::header::CACHE_CONTROL, "no-store")
+        .body(hyper::Body::from(page))?)
+}
+
+pub async fn handle_device_credential_download(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    token: String,
+) -> Result<http::Response<hyper::Body>> {
+    let download = download_device_credential(&rqctx.context().app.db, &token).await?;
+
+    Ok(http::Response::builder()
+        .status(http::StatusCode::OK)
+        .header(http::header::CONTENT_TYPE, download.content_type)
+        .header(
+            http::header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", download.file_name),
+        )
+        .header(http::header::CACHE_CONTROL, "no-store")
+        .body(hyper::Body::from(download.contents))?)
+}
+
+pub async fn handle_background_checks_export(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    query: BackgroundCheckExportQuery,
+) -> Result<http::Response<hyper::Body>> {
+    let api_context = rqctx.context();
+    let csv = export_background_checks(&api_context.app.db, &api_context.app.company, query.since, query.until).await?;
+
+    Ok(http::Response::builder()
+        .status(http::StatusCode::OK)
+        .header(http::header::CONTENT_TYPE, "text/csv")
+        .header(
+            http::header::CONTENT_DISPOSITION,
+            "attachment; filename=\"background-checks.csv\"",
+        )
+        .header(http::header::CACHE_CONTROL, "no-store")
+        .body(hyper::Body::from(csv))?)
+}
+
+pub async fn handle_data_subject_export(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    request: DataSubjectRequestBody,
+) -> Result<http::Response<hyper::Body>> {
+    let api_context = rqctx.context();
+    let export = export_data_subject(
+        &api_context.app.db,
+        &api_context.app.company,
+        &request.email,
+        &request.requested_by,
+    )
+    .await?;
+
+    Ok(http::Response::builder()
+        .status(http::StatusCode::OK)
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .header(
+            http::header::CONTENT_DISPOSITION,
+            "attachment; filename=\"data-export.json\"",
+        )
+        .header(http::header::CACHE_CONTROL, "no-store")
+        .body(hyper::Body::from(serde_json::to_vec_pretty(&export)?))?)
+}
+
+pub async fn handle_data_subject_erasure(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    request: DataSubjectRequestBody,
+) -> Result<DataSubjectErasure> {
+    let api_context = rqctx.context();
+    let mailing_list = api_context.app.app_config.read().unwrap().mailing_list.clone();
+
+    erase_data_subject(
+        &api_context.app.db,
+        &api_context.app.company,
+        &mailing_list,
+        &request.email,
+        &request.requested_by,
+    )
+    .await
+}
+
+pub async fn handle_company_provision(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    new_company: NewCompany,
+) -> Result<ProvisioningReport> {
+    let api_context = rqctx.context();
+    Company::provision(&api_context.app.db, &api_context.app.company, &new_company).await
+}
+
+pub async fn handle_directory(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    query: DirectoryQuery,
+) -> Result<Vec<DirectoryProfile>> {
+    let api_context = rqctx.context();
+    directory(&api_context.app.db, &api_context.app.company, &query).await
+}
+
+pub async fn handle_directory_profile(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    username: String,
+) -> Result<DirectoryProfile> {
+    let api_context = rqctx.context();
+    directory_profile(&api_context.app.db, &api_context.app.company, &username).await
+}
+
 pub async fn handle_airtable_swag_inventory_items_print_barcode_labels(
     rqctx: Arc<RequestContext<ServerContext>>,
     event: AirtableRowEvent,
@@ -724,7 +979,8 @@ pub async fn handle_airtable_applicants_update(
     // Grab the status and the status raw.
     let status = cio_api::applicant_status::Status::from_str(&applicant.status).unwrap();
 
-    let status_changed = db_applicant.status != status.to_string();
+    let previous_status = db_applicant.status.to_string();
+    let status_changed = previous_status != status.to_string();
 
     db_applicant.status = status.to_string();
     if !applicant.raw_status.is_empty() {
@@ -746,15 +1002,15 @@ pub async fn handle_airtable_applicants_update(
         let company = db_applicant.company(&api_context.app.db).await?;
         let dsa = company.authenticate_docusign(&api_context.app.db).await;
         if let Ok(ds) = dsa {
-            let offer_letter = api_context
-                .app
-                .app_config
-                .read()
-                .unwrap()
-                .envelopes
-                .create_offer_letter(&db_applicant);
+            let (offer_letter, offer_approval) = {
+                let app_config = api_context.app.app_config.read().unwrap();
+                (
+                    app_config.envelopes.create_offer_letter(&db_applicant),
+                    app_config.envelopes.offer_approval.clone(),
+                )
+            };
             db_applicant
-                .do_docusign_offer(&api_context.app.db, &ds, offer_letter)
+                .do_docusign_offer(&api_context.app.db, &ds, offer_letter, &offer_approval)
                 .await?;
 
             let piia_letter = api_context
@@ -773,6 +1029,28 @@ pub async fn handle_airtable_applicants_update(
     // Update the row in our database.
     db_applicant.update(&api_context.app.db).await?;
 
+    if status_changed {
+        let company = db_applicant.company(&api_context.app.db).await?;
+        let notification = Notification::new(
+            NotificationEvent::ApplicantStageChanged,
+            &db_applicant.id.to_string(),
+            &format!("{} moved to {}", db_applicant.name, db_applicant.status),
+            &format!(
+                "{} for _{}_ moved from {} to {}",
+                db_applicant.name, db_applicant.role, previous_status, db_applicant.status
+            ),
+        )
+        .with_attribute("role", &db_applicant.role)
+        .with_attribute("status", &db_applicant.status)
+        .with_attribute("previous_status", &previous_status);
+        if let Err(e) = notify(&api_context.app.db, &company, &notification).await {
+            warn!(
+                "failed to notify that applicant {} changed status: {}",
+                db_applicant.id, e
+            );
+        }
+    }
+
     info!("applicant {} updated successfully", applicant.email);
     Ok(())
 }
@@ -1099,36 +1377,11 @@ pub async fn handle_application_files_upload(
         bail!("could not get applicant information for: {:?}", data);
     }
 
-    // TODO: Add the files to google drive.
     let api_context = rqctx.context();
     let db = &api_context.app.db;
 
     let company = Company::get_by_id(db, data.cio_company_id).await?;
-
-    // Initialize the Google Drive client.
-    let drive = company.authenticate_google_drive(db).await?;
-
-    // Figure out where our directory is.
-    // It should be in the shared drive : "Automated Documents"/"application_content"
-    let shared_drive = drive.drives().get_by_name("Automated Documents").await?;
-
-    // Get the directory by the name.
-    let parent_id = drive
-        .files()
-        .create_folder(&shared_drive.id, "", "application_content")
-        .await?;
-
-    // Create the folder for our candidate with their email.
-    let email_folder_id = drive
-        .files()
-        .create_folder(&shared_drive.id, &parent_id, &data.email)
-        .await?;
-
-    // Create the folder for our candidate with the role.
-    let role_folder_id = drive
-        .files()
-        .create_folder(&shared_drive.id, &email_folder_id, &data.role)
-        .await?;
+    let (drive, shared_drive_id, role_folder_id) = application_drive_folder(db, &company, &data).await?;
 
     let mut files: HashMap<String, (String, String)> = HashMap::new();
     files.insert(
@@ -1163,7 +1416,7 @@ pub async fn handle_application_files_upload(
         let drive_file = drive
             .files()
             .create_or_update(
-                &shared_drive.id,
+                &shared_drive_id,
                 &role_folder_id,
                 &file_name,
                 &content_type,
@@ -1180,10 +1433,302 @@ pub async fn handle_application_files_upload(
     Ok(response)
 }
 
+/// The Google Drive client of `company`, with the ids of its shared drive and the folder the
+/// materials of an application go in, creating the folder if it does not exist yet.
+async fn application_drive_folder(
+    db: &Database,
+    company: &Company,
+    data: &ApplicationFileUploadData,
+) -> Result<(GoogleDrive, String, String)> {
+    // Initialize the Google Drive client.
+    let drive = company.authenticate_google_drive(db).await?;
+
+    // Figure out where our directory is.
+    // It should be in the shared drive : "Automated Documents"/"application_content"
+    let shared_drive = drive.drives().get_by_name("Automated Documents").await?;
+
+    // Get the directory by the name.
+    let parent_id = drive
+        .files()
+        .create_folder(&shared_drive.id, "", "application_content")
+        .await?;
+
+    // Create the folder for our candidate with their email.
+    let email_folder_id = drive
+        .files()
+        .create_folder(&shared_drive.id, &parent_id, &data.email)
+        .await?;
+
+    // Create the folder for our candidate with the role.
+    let role_folder_id = drive
+        .files()
+        .create_folder(&shared_drive.id, &email_folder_id, &data.role)
+        .await?;
+
+    Ok((drive, shared_drive.id, role_folder_id))
+}
+
+/// The names of the files of an application, and whether each is required.
+const APPLICATION_FILES: [(&str, bool); 3] = [("resume", true), ("materials", true), ("portfolio_pdf", false)];
+
+/// Read the materials of an application sent as `multipart/form-data`, streaming each file into
+/// the application's folder in Google Drive, or nowhere when `dry_run` is set.
+///
+/// The fields that say who the application is from (`cio_company_id`, `email`, `role`,
+/// `user_name` and `interested_in`) have to come before the files, so that the upload token is
+/// used up before anything is stored. Files are sent in fields named after them, ie. `resume`.
+pub async fn handle_application_files_multipart_upload(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    token: &str,
+    dry_run: bool,
+) -> Result<HashMap<String, String>> {
+    let limits = UploadLimits::application_files();
+    let mut multipart = uploads::multipart(&rqctx, &limits).await?;
+
+    let mut data = ApplicationFileUploadData::default();
+    let mut field = loop {
+        let field = uploads::next_field(&mut multipart)
+            .await?
+            .ok_or_else(|| UploadRejection::bad_request("the upload has no files"))?;
+
+        if field.file_name().is_some() {
+            break field;
+        }
+
+        let name = field.name().unwrap_or_default().to_string();
+        let value = uploads::text(field).await?;
+        match name.as_str() {
+            "cio_company_id" => {
+                data.cio_company_id = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| UploadRejection::bad_request("`cio_company_id` is not a number"))?
+            }
+            "email" => data.email = value,
+            "role" => data.role = value,
+            "user_name" => data.user_name = value,
+            "interested_in" => data.interested_in.push(value),
+            _ => warn!("ignoring field `{}` of an application upload", name),
+        }
+    };
+
+    if data.email.is_empty() || data.role.is_empty() || data.cio_company_id <= 0 || data.user_name.is_empty() {
+        return Err(UploadRejection::bad_request(
+            "`cio_company_id`, `email`, `role` and `user_name` have to come before the files",
+        )
+        .into());
+    }
+
+    let api_context = rqctx.context();
+    let db = &api_context.app.db;
+
+    // As with uploads sent as JSON, the client is not told why its token was not accepted.
+    api_context
+        .app
+        .upload_token_store
+        .consume(&data.email, token, data.cio_company_id)
+        .await
+        .map_err(|err| {
+            info!("Failed to consume upload token due to {:?}", err);
+            UploadRejection::new(http::StatusCode::CONFLICT, "")
+        })?;
+
+    let drive = if dry_run {
+        None
+    } else {
+        let company = Company::get_by_id(db, data.cio_company_id).await?;
+        let (_, shared_drive_id, role_folder_id) = application_drive_folder(db, &company, &data).await?;
+
+        // The client is authenticated, and its token refreshed, by now.
+        let access_token = APIToken::get_from_db(db, company.id, "google".to_string())
+            .await
+            .map(|t| t.access_token)
+            .ok_or_else(|| anyhow::anyhow!("no Google token for company {}", company.id))?;
+
+        Some((access_token, shared_drive_id, role_folder_id))
+    };
+
+    let mut response: HashMap<String, String> = Default::default();
+    loop {
+        let name = field.name().unwrap_or_default().to_string();
+        if !APPLICATION_FILES.iter().any(|(file, _)| *file == name) {
+            return Err(UploadRejection::bad_request(format!("`{}` is not a file of an application", name)).into());
+        }
+        if response.contains_key(&name) {
+            return Err(UploadRejection::bad_request(format!("`{}` was sent more than once", name)).into());
+        }
+        if response.len() >= limits.max_files {
+            return Err(UploadRejection::too_large(format!("an upload has at most {} files", limits.max_files)).into());
+        }
+
+        let ext = field
+            .file_name()
+            .and_then(get_extension_from_filename)
+            .ok_or_else(|| UploadRejection::bad_request(format!("`{}` needs a file name with an extension", name)))?
+            .to_string();
+        let content_type = mime_guess_2::from_ext(&ext)
+            .first_or_octet_stream()
+            .essence_str()
+            .to_string();
+        let file_name = format!("{} - {}.{}", data.user_name, name, ext);
+
+        let sink: Box<dyn UploadSink> = match &drive {
+            Some((access_token, shared_drive_id, role_folder_id)) => Box::new(
+                DriveUpload::start(access_token, shared_drive_id, role_folder_id, &file_name, &content_type).await?,
+            ),
+            None => Box::new(DiscardUpload::new(&file_name, &content_type)),
+        };
+        let (link, written) = uploads::stream_to(field, sink).await?;
+        info!("uploaded {} bytes of {} for {}", written, name, data.email);
+
+        response.insert(name, link);
+
+        field = match uploads::next_field(&mut multipart).await? {
+            Some(field) => field,
+            None => break,
+        };
+    }
+
+    for (file, required) in APPLICATION_FILES {
+        if required && !response.contains_key(file) {
+            return Err(UploadRejection::bad_request(format!("the upload is missing `{}`", file)).into());
+        }
+    }
+
+    Ok(response)
+}
+
 fn get_extension_from_filename(filename: &str) -> Option<&str> {
     std::path::Path::new(filename).extension().and_then(OsStr::to_str)
 }
 
+/// Read an email sent to the careers inbox, as posted by SendGrid Inbound Parse, and turn it into
+/// an application. The company is the one the inbox belongs to, by the domain it was sent to.
+///
+/// Attachments are read whole, since which of them is the resume is only known once all of
+/// their names are, and only the files the application is missing are stored in its folder.
+pub async fn handle_careers_inbound_email(rqctx: Arc<RequestContext<ServerContext>>) -> Result<()> {
+    let limits = UploadLimits::careers_inbox();
+    let mut multipart = uploads::multipart(&rqctx, &limits).await?;
+
+    let mut email = InboundEmail::default();
+    let mut to = String::new();
+    let mut attachments: Vec<(String, bytes::Bytes)> = Default::default();
+    while let Some(field) = uploads::next_field(&mut multipart).await? {
+        if let Some(file_name) = field.file_name().map(|name| name.to_string()) {
+            if attachments.len() >= limits.max_files {
+                return Err(UploadRejection::too_large(format!(
+                    "an email has at most {} attachments",
+                    limits.max_files
+                ))
+                .into());
+            }
+
+            let contents = field.bytes().await.map_err(UploadRejection::from)?;
+            attachments.push((file_name, contents));
+            continue;
+        }
+
+        // The rest of the fields, ie. the HTML body and the headers, are not needed.
+        match field.name().unwrap_or_default() {
+            "from" => email.from = uploads::text(field).await?,
+            "to" => to = uploads::text(field).await?,
+            "subject" => email.subject = uploads::text(field).await?,
+            "text" => email.text = uploads::text(field).await?,
+            _ => {}
+        }
+    }
+
+    let (name, sender) = parse_sender(&email.from)
+        .ok_or_else(|| UploadRejection::bad_request(format!("`{}` is not an email address", email.from)))?;
+    let domain = to
+        .split(',')
+        .find_map(|to| parse_sender(to).and_then(|(_, to)| to.split_once('@').map(|(_, domain)| domain.to_string())))
+        .ok_or_else(|| UploadRejection::bad_request(format!("`{}` is not an email address", to)))?;
+
+    let api_context = rqctx.context();
+    let db = &api_context.app.db;
+    let company = Company::get_from_domain(db, &domain).await?;
+    let app_config = api_context.app.app_config.read().unwrap().clone();
+
+    let role = match match_role(&app_config.apply.careers_inbox, &email) {
+        Some(role) => role,
+        None => {
+            info!(
+                "could not tell which role the email from {} to the careers inbox is for",
+                sender
+            );
+            return post_unmatched_application(db, &company, &email).await;
+        }
+    };
+
+    let existing = find_applicant(db, &company, &sender).await;
+    let needs_resume = existing.as_ref().map(|a| a.resume.is_empty()).unwrap_or(true);
+    let needs_materials = existing.as_ref().map(|a| a.materials.is_empty()).unwrap_or(true);
+
+    let names = attachments.iter().map(|(name, _)| name.to_string()).collect::<Vec<_>>();
+    let found = classify_attachments(&names);
+
+    let mut files: Vec<(&str, String, bytes::Bytes)> = Default::default();
+    if let (Some(i), true) = (found.resume, needs_resume) {
+        files.push(("resume", attachments[i].0.to_string(), attachments[i].1.clone()));
+    }
+    match (found.materials, needs_materials) {
+        (Some(i), true) => files.push(("materials", attachments[i].0.to_string(), attachments[i].1.clone())),
+        // Answers written in the email itself are kept as the materials.
+        (None, true) if !email.text.trim().is_empty() => files.push((
+            "materials",
+            "email.txt".to_string(),
+            bytes::Bytes::from(email.text.to_string()),
+        )),
+        _ => {}
+    }
+
+    let (github, linkedin) = profile_links(&email.text);
+    let mut application = InboundApplication {
+        name,
+        email: sender,
+        role,
+        github,
+        linkedin,
+        ..Default::default()
+    };
+
+    if !files.is_empty() {
+        let data = ApplicationFileUploadData {
+            cio_company_id: company.id,
+            email: application.email.to_string(),
+            role: application.role.to_string(),
+            ..Default::default()
+        };
+        let (drive, shared_drive_id, role_folder_id) = application_drive_folder(db, &company, &data).await?;
+
+        for (kind, file_name, contents) in files {
+            let ext = get_extension_from_filename(&file_name).unwrap_or("txt");
+            let content_type = mime_guess_2::from_ext(ext)
+                .first_or_octet_stream()
+                .essence_str()
+                .to_string();
+            let file_name = format!("{} - {}.{}", application.name, kind, ext);
+
+            let drive_file = drive
+                .files()
+                .create_or_update(&shared_drive_id, &role_folder_id, &file_name, &content_type, &contents)
+                .await?;
+            let link = format!("https://drive.google.com/open?id={}", drive_file.id);
+            match kind {
+                "resume" => application.resume = link,
+                _ => application.materials = link,
+            }
+        }
+    }
+
+    let outcome = ingest_application(db, &company, &app_config, application).await?;
+    info!("handled email to the careers inbox from {}: {:?}", email.from, outcome);
+
+    Ok(())
+}
+
 pub async fn handle_airtable_shipments_inbound_create(
     rqctx: Arc<RequestContext<ServerContext>>,
     event: AirtableRowEvent,
@@ -1221,12 +1766,64 @@ pub async fn handle_airtable_shipments_inbound_create(
 pub async fn handle_store_order_create(rqctx: Arc<RequestContext<ServerContext>>, event: Order) -> Result<()> {
     let api_context = rqctx.context();
 
-    event.do_order(&api_context.app.db).await?;
+    let swag_config = api_context.app.app_config.read().unwrap().swag.clone();
+    event.do_order(&api_context.app.db, &swag_config).await?;
 
     info!("order for {} created successfully", event.email);
     Ok(())
 }
 
+pub async fn handle_store_inventory_receive(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    receipt: StockReceipt,
+) -> Result<SwagStockLevel> {
+    let api_context = rqctx.context();
+
+    let swag_config = api_context.app.app_config.read().unwrap().swag.clone();
+    receive_stock(&api_context.app.db, &swag_config, &receipt).await
+}
+
+pub async fn handle_swag_preferences_request(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    recipient: SwagRecipient,
+) -> Result<()> {
+    let api_context = rqctx.context();
+
+    let config = api_context.app.app_config.read().unwrap().swag.preferences.clone();
+    request_swag_preferences(
+        &api_context.app.db,
+        &api_context.app.company,
+        &config,
+        NewOutboundShipment::from(&recipient),
+    )
+    .await?;
+
+    Ok(())
+}
+
+pub async fn handle_swag_preferences_form(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    token: String,
+) -> Result<SwagPreferencesForm> {
+    let api_context = rqctx.context();
+
+    let config = api_context.app.app_config.read().unwrap().swag.preferences.clone();
+    get_swag_preferences_form(&api_context.app.db, &config, &token).await
+}
+
+pub async fn handle_swag_preferences_submit(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    token: String,
+    preferences: SwagPreferences,
+) -> Result<()> {
+    let api_context = rqctx.context();
+
+    let config = api_context.app.app_config.read().unwrap().swag.preferences.clone();
+    submit_swag_preferences(&api_context.app.db, &config, &token, preferences).await?;
+
+    Ok(())
+}
+
 pub async fn handle_easypost_tracking_update(
     _rqctx: Arc<RequestContext<ServerContext>>,
     event: crate::server::EasyPostTrackingUpdateEvent,
@@ -1238,6 +1835,19 @@ pub async fn handle_easypost_tracking_update(
     Ok(())
 }
 
+pub async fn handle_sendgrid_events(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    events: Vec<SendGridEvent>,
+) -> Result<()> {
+    let api_context = rqctx.context();
+
+    record_sendgrid_events(&api_context.app.db, &events).await?;
+
+    info!("recorded {} sendgrid events", events.len());
+
+    Ok(())
+}
+
 pub async fn handle_shippo_tracking_update(
     rqctx: Arc<RequestContext<ServerContext>>,
     event: serde_json::Value,
@@ -1275,11 +1885,65 @@ pub async fn handle_shippo_tracking_update(
     )
     .await
     {
+        let previous_status = shipment.status.to_string();
+
         // Update the shipment in shippo.
         // TODO: we likely don't need the extra request here, but it makes the code more DRY.
         // Clean this up eventually.
         shipment.create_or_get_shippo_shipment(&api_context.app.db).await?;
         shipment.update(&api_context.app.db).await?;
+
+        let delivered = cio_api::shipment_status::Status::Delivered.to_string();
+        if shipment.status == delivered && previous_status != delivered {
+            let company = shipment.company(&api_context.app.db).await?;
+            let mailing_list_config = api_context.app.app_config.read().unwrap().mailing_list.clone();
+
+            if let Err(e) = cio_api::mailing_list::trigger_mailchimp_journey(
+                &company,
+                &mailing_list_config,
+                "shipment_delivered",
+                &shipment.email,
+            )
+            .await
+            {
+                warn!("failed to start mailchimp journey for shipment {}: {}", shipment.id, e);
+            }
+
+            let notification = Notification::new(
+                NotificationEvent::ShipmentDelivered,
+                &shipment.tracking_number,
+                &format!("Shipment to {} delivered", shipment.name),
+                &format!("_{}_ via {}", shipment.contents, shipment.carrier),
+            )
+            .with_url(&shipment.tracking_link)
+            .with_attribute("carrier", &shipment.carrier)
+            .with_attribute("country", &shipment.country)
+            .with_attribute("provider", &shipment.provider);
+            if let Err(e) = notify(&api_context.app.db, &company, &notification).await {
+                warn!("failed to notify that shipment {} was delivered: {}", shipment.id, e);
+            }
+
+            let data = json!({
+                "id": shipment.id,
+                "name": shipment.name,
+                "email": shipment.email,
+                "contents": shipment.contents,
+                "carrier": shipment.carrier,
+                "tracking_number": shipment.tracking_number,
+                "delivered_time": shipment.delivered_time,
+            });
+            if let Err(e) = publish(
+                &api_context.app.db,
+                &company,
+                OutboxEvent::ShipmentDelivered,
+                &shipment.id.to_string(),
+                data,
+            )
+            .await
+            {
+                warn!("failed to publish that shipment {} was delivered: {}", shipment.id, e);
+            }
+        }
     }
 
     info!("shipment {} tracking status updated successfully", ts.tracking_number);
@@ -1292,12 +1956,15 @@ pub async fn handle_checkr_background_update(
 ) -> Result<()> {
     let api_context = rqctx.context();
 
-    // Run the update of the background checks.
+    let event_type = event.event_type();
+    if !event_type.is_report() && !event_type.is_invitation() {
+        // Return early we don't care.
+        info!("ignoring checkr event of type `{}`", event.type_);
+        return Ok(());
+    }
+
     // If we have a candidate ID let's get them from checkr.
-    if event.data.object.candidate_id.is_empty()
-        || event.data.object.package.is_empty()
-        || event.data.object.status.is_empty()
-    {
+    if event.data.object.candidate_id.is_empty() || event.data.object.package.is_empty() {
         // Return early we don't care.
         info!("checkr candidate id is empty for event: {:?}", event);
         return Ok(());
@@ -1326,21 +1993,38 @@ pub async fn handle_checkr_background_update(
         .filter(applicants::dsl::status.eq(cio_api::applicant_status::Status::Onboarding.to_string()))
         .first_async::<Applicant>(api_context.app.db.pool())
         .await;
-    if result.is_ok() {
-        let mut applicant = result?;
+    let config = api_context.app.app_config.read().unwrap().background_checks.clone();
+
+    if let Ok(mut applicant) = result {
         // Keep the fields from Airtable we need just in case they changed.
         applicant.keep_fields_from_airtable(&api_context.app.db).await;
 
-        // Set the status for the report.
-        if event.data.object.package.contains("premium_criminal") {
-            applicant.criminal_background_check_status = event.data.object.status.to_string();
-        }
-        if event.data.object.package.contains("motor_vehicle") {
-            applicant.motor_vehicle_background_check_status = event.data.object.status.to_string();
+        let background_check_status = applicant.background_check_status.to_string();
+        if applicant.update_background_check_from_checkr(&event) {
+            // Update the applicant.
+            applicant.update(&api_context.app.db).await?;
+
+            info!(
+                "updated background check for applicant {} from checkr event `{}`",
+                applicant.email, event.type_
+            );
+
+            if applicant.background_check_status != background_check_status {
+                record_applicant_background_check(&api_context.app.db, &oxide, &config, &applicant, &event).await?;
+            }
         }
 
-        // Update the applicant.
-        applicant.update(&api_context.app.db).await?;
+        return Ok(());
+    }
+
+    // Otherwise this is a continuous check of an employee.
+    let user = users::dsl::users
+        .filter(users::dsl::cio_company_id.eq(oxide.id))
+        .filter(users::dsl::checkr_candidate_id.eq(event.data.object.candidate_id.to_string()))
+        .first_async::<User>(api_context.app.db.pool())
+        .await;
+    if let Ok(mut user) = user {
+        update_employee_background_check(&api_context.app.db, &oxide, &config, &mut user, &event).await?;
     }
 
     Ok(())
@@ -1415,6 +2099,321 @@ pub async fn handle_docusign_envelope_update(
     Ok(())
 }
 
+pub async fn handle_docusign_connect_event(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    event: docusign::ConnectEvent,
+) -> Result<()> {
+    // Recipient level events do not change the state of the envelope, the envelope level
+    // event will follow once all of the recipients have finished.
+    if !event.event.starts_with("envelope-") {
+        info!(
+            "ignoring docusign connect event `{}` for envelope {}",
+            event.event, event.data.envelope_id
+        );
+        return Ok(());
+    }
+
+    let envelope = event.envelope();
+    if envelope.envelope_id.is_empty() {
+        bail!("docusign connect event `{}` is missing an envelope id", event.event);
+    }
+
+    handle_docusign_envelope_update(rqctx, envelope).await
+}
+
+/// The entity types we keep in sync from QuickBooks.
+const QUICKBOOKS_SYNCED_ENTITIES: &[&str] = &["Purchase", "BillPayment"];
+
+pub async fn handle_quickbooks_webhook(api_context: &ServerContext, payload: quickbooks::WebhookPayload) -> Result<()> {
+    let db = &api_context.app.db;
+    let finance_config = api_context.app.app_config.read().unwrap().finance.clone();
+
+    for notification in payload.event_notifications {
+        let changed: Vec<&quickbooks::EntityChange> = notification
+            .data_change_event
+            .entities
+            .iter()
+            .filter(|entity| QUICKBOOKS_SYNCED_ENTITIES.contains(&entity.name.as_str()))
+            .collect();
+        if changed.is_empty() {
+            continue;
+        }
+
+        // The webhook only tells us what changed, so ask for everything that changed since the
+        // oldest update and sync those. Go back a minute to allow for clock skew.
+        let changed_since = changed
+            .iter()
+            .filter_map(|entity| entity.last_updated())
+            .min()
+            .unwrap_or_else(Utc::now)
+            - Duration::minutes(1);
+
+        let company = Company::get_from_quickbooks_realm_id(db, &notification.realm_id).await?;
+
+        cio_api::finance::sync_quickbooks_changes(db, &company, &finance_config, changed_since).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn handle_stripe_webhook(api_context: &ServerContext, event: cio_api::payments::StripeEvent) -> Result<()> {
+    let db = &api_context.app.db;
+    let stripe_config = api_context.app.app_config.read().unwrap().finance.stripe.clone();
+
+    // TODO: find a better way to do this.
+    let company = match Company::get_from_db(db, "Oxide".to_string()).await {
+        Some(company) => company,
+        None => bail!("failed to find the company for stripe webhooks"),
+    };
+
+    cio_api::payments::handle_stripe_event(db, &company, &stripe_config, event).await
+}
+
+pub async fn handle_mailchimp_mailing_list(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    event: mailchimp_minimal_api::WebhookEvent,
+) -> Result<()> {
+    let db = &rqctx.context().app.db;
+
+    let company = Company::get_from_mailchimp_list_id(db, event.list_id()).await?;
+
+    match event {
+        mailchimp_minimal_api::WebhookEvent::Subscribe(payload) => {
+            let mut subscriber: NewMailingListSubscriber = payload.into();
+            subscriber.cio_company_id = company.id;
+
+            let existing = MailingListSubscriber::get_from_db(db, subscriber.email.to_string()).await;
+            let subscriber = subscriber.upsert(db).await?;
+
+            if existing.is_none() {
+                subscriber.send_slack_notification(db, &company).await?;
+            }
+
+            info!("mailchimp subscriber `{}` subscribed", subscriber.email);
+        }
+        mailchimp_minimal_api::WebhookEvent::Profile(payload) => {
+            let mut subscriber: NewMailingListSubscriber = payload.into();
+            subscriber.cio_company_id = company.id;
+
+            // A profile update should not move when they first signed up.
+            if let Some(existing) = MailingListSubscriber::get_from_db(db, subscriber.email.to_string()).await {
+                subscriber.date_added = existing.date_added;
+                subscriber.date_optin = existing.date_optin;
+                subscriber.tags = existing.tags;
+            }
+
+            subscriber.upsert(db).await?;
+
+            info!("mailchimp subscriber `{}` updated their profile", subscriber.email);
+        }
+        mailchimp_minimal_api::WebhookEvent::EmailChanged(payload) => {
+            if let Some(mut subscriber) =
+                MailingListSubscriber::get_from_db(db, payload.data.old_email.to_string()).await
+            {
+                subscriber.email = payload.data.new_email.to_string();
+                subscriber.date_last_changed = payload.fired_at;
+                subscriber.update(db).await?;
+            }
+
+            info!(
+                "mailchimp subscriber `{}` changed their email to `{}`",
+                payload.data.old_email, payload.data.new_email
+            );
+        }
+        mailchimp_minimal_api::WebhookEvent::Unsubscribe(payload) => {
+            unsubscribe_mailing_list_subscriber(
+                rqctx.context(),
+                &company,
+                &payload.data.email,
+                payload.fired_at,
+                SuppressionReason::Unsubscribed,
+                MailingListProviderKind::MailChimp,
+            )
+            .await?;
+
+            info!(
+                "mailchimp subscriber `{}` unsubscribed: {}",
+                payload.data.email, payload.data.reason
+            );
+        }
+        mailchimp_minimal_api::WebhookEvent::Cleaned(payload) => {
+            unsubscribe_mailing_list_subscriber(
+                rqctx.context(),
+                &company,
+                &payload.data.email,
+                payload.fired_at,
+                SuppressionReason::Bounced,
+                MailingListProviderKind::MailChimp,
+            )
+            .await?;
+
+            info!(
+                "mailchimp subscriber `{}` was cleaned: {}",
+                payload.data.email, payload.data.reason
+            );
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn handle_mailerlite_mailing_list(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    webhook: serde_json::Value,
+) -> Result<()> {
+    let api_context = rqctx.context();
+    let db = &api_context.app.db;
+    let company = &api_context.app.company;
+
+    // Mailerlite reports dates in the local time of the account, which only the client knows.
+    let client = cio_api::mailerlite::Mailerlite::new()?;
+    let payload: mailerlite::ApiWebhookPayload = serde_json::from_value(webhook)?;
+
+    for event in payload.into_events(client.client.time_zone())? {
+        let email = event.subscriber.email.to_string();
+
+        match event.event_type {
+            WebhookEventType::SubscriberCreated | WebhookEventType::SubscriberUpdated => {
+                let mut subscriber: NewMailingListSubscriber = event.subscriber.into();
+                subscriber.cio_company_id = company.id;
+
+                let existing = MailingListSubscriber::get_from_db(db, email.to_string()).await;
+
+                // Mailerlite does not know about the updates a subscriber asked for, so keep
+                // whatever we already have for them.
+                if let Some(existing) = &existing {
+                    subscriber.date_added = existing.date_added;
+                    subscriber.date_optin = existing.date_optin;
+                    subscriber.tags = existing.tags.clone();
+                    subscriber.wants_podcast_updates = existing.wants_podcast_updates;
+                    subscriber.wants_newsletter = existing.wants_newsletter;
+                    subscriber.wants_product_updates = existing.wants_product_updates;
+                }
+
+                let subscriber = subscriber.upsert(db).await?;
+
+                if existing.is_none() {
+                    subscriber.send_slack_notification(db, company).await?;
+                }
+
+                info!("mailerlite subscriber `{}` was created or updated", email);
+            }
+            WebhookEventType::SubscriberUnsubscribed
+            | WebhookEventType::SubscriberBounced
+            | WebhookEventType::SubscriberSpamReported => {
+                let reason = match event.event_type {
+                    WebhookEventType::SubscriberBounced => SuppressionReason::Bounced,
+                    WebhookEventType::SubscriberSpamReported => SuppressionReason::Complained,
+                    _ => SuppressionReason::Unsubscribed,
+                };
+
+                unsubscribe_mailing_list_subscriber(
+                    api_context,
+                    company,
+                    &email,
+                    event.subscriber.updated_at,
+                    reason,
+                    MailingListProviderKind::Mailerlite,
+                )
+                .await?;
+
+                info!(
+                    "mailerlite subscriber `{}` will no longer receive emails: {:?}",
+                    email, event.event_type
+                );
+            }
+            WebhookEventType::SubscriberAddedToGroup | WebhookEventType::SubscriberRemovedFromGroup => {
+                let group = match event.group {
+                    Some(group) => group,
+                    None => {
+                        warn!("mailerlite group event for `{}` is missing its group", email);
+                        continue;
+                    }
+                };
+
+                // Groups are tracked as tags so that they sit alongside the MailChimp tags.
+                if let Some(mut subscriber) = MailingListSubscriber::get_from_db(db, email.to_string()).await {
+                    if event.event_type == WebhookEventType::SubscriberAddedToGroup {
+                        if !subscriber.tags.contains(&group.name) {
+                            subscriber.tags.push(group.name.to_string());
+                        }
+                    } else {
+                        subscriber.tags.retain(|tag| tag != &group.name);
+                    }
+
+                    subscriber.date_last_changed = event.subscriber.updated_at;
+                    subscriber.update(db).await?;
+                }
+
+                info!(
+                    "mailerlite subscriber `{}` group `{}` changed: {:?}",
+                    email, group.name, event.event_type
+                );
+            }
+            other => info!("ignoring mailerlite webhook of type `{:?}`", other),
+        }
+    }
+
+    Ok(())
+}
+
+/// Turn off every update for a subscriber that will no longer receive our emails and suppress
+/// their address so that nothing else is sent to them either. The suppression is passed on to
+/// the company's mailing list when it did not come from there.
+async fn unsubscribe_mailing_list_subscriber(
+    api_context: &ServerContext,
+    company: &Company,
+    email: &str,
+    changed_at: chrono::DateTime<Utc>,
+    reason: SuppressionReason,
+    source: MailingListProviderKind,
+) -> Result<()> {
+    let db = &api_context.app.db;
+
+    if let Some(mut subscriber) = MailingListSubscriber::get_from_db(db, email.to_string()).await {
+        subscriber.wants_podcast_updates = false;
+        subscriber.wants_newsletter = false;
+        subscriber.wants_product_updates = false;
+        subscriber.date_last_changed = changed_at;
+        subscriber.update(db).await?;
+    }
+
+    suppress_email(db, company.id, email, reason, &source.to_string(), changed_at).await?;
+
+    let config = api_context.app.app_config.read().unwrap().mailing_list.clone();
+    if config.provider(&company.name) != source {
+        let provider = company.authenticate_mailing_list_provider(&config).await?;
+
+        if let Err(err) = provider.remove_member(email).await {
+            warn!(
+                "failed to remove suppressed subscriber `{}` from mailing list: {}",
+                email, err
+            );
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn handle_send_policy_acknowledgments(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    path_params: Path<PolicyPathParams>,
+) -> Result<String> {
+    let policy = path_params.into_inner().policy;
+    info!("sending policy `{}` to employees for acknowledgment", policy);
+
+    let api_context = rqctx.context();
+    let app_config = api_context.app.app_config.read().unwrap().clone();
+
+    cio_api::policy_acknowledgments::send_policy_acknowledgments(
+        &api_context.app.db,
+        &api_context.app.company,
+        &app_config,
+        &policy,
+    )
+    .await
+}
+
 pub async fn handle_analytics_page_view(
     rqctx: Arc<RequestContext<ServerContext>>,
     mut event: NewPageView,
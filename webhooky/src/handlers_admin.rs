@@ -0,0 +1,714 @@
+use std::{collections::BTreeSet, time::Duration};
+
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Utc};
+use cio_api::{
+    failures::{ErrorClass, FailureFilter},
+    functions::Function,
+    github_event_routes::{GitHubEventRoute, NewGitHubEventRoute},
+    notifications::{NewNotificationRule, NotificationRule},
+    progress::ProgressEvent,
+    schema::functions,
+    shipment_changes::{
+        correct_address, reprint_label, AddressCorrection, LabelReprint, OutboundShipmentEvent, ShipmentChangeRejection,
+    },
+    shipments::OutboundShipment,
+    webhook_events::WebhookEvent,
+    webhook_outbox::{NewWebhookConsumer, RegisteredWebhookConsumer, WebhookConsumer, WebhookDelivery},
+};
+use diesel::{ExpressionMethods, QueryDsl};
+use dropshot::HttpError;
+use hyper::{body::Bytes, Body, Response};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{context::ServerContext, sagas::JobProgress};
+
+/// A request to run a background job, ie. `sync-rfds`, on demand.
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct AdminRunRequest {
+    /// The name of the job, the same as the subcommand that runs it.
+    pub job: String,
+    /// Only sync the RFD with this number. Only for `sync-rfds`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rfd_number: Option<i32>,
+    /// Only sync the applicant with this email. Only for `sync-applications`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub applicant_email: Option<String>,
+}
+
+impl AdminRunRequest {
+    /// The job with its arguments, in the form the saga for the job is named.
+    fn command(&self) -> Result<String, String> {
+        let mut cmd = self.job.trim().to_string();
+
+        if let Some(number) = self.rfd_number {
+            cmd += &format!(" --number {}", number);
+        }
+
+        if let Some(email) = &self.applicant_email {
+            if email.is_empty() || email.contains(char::is_whitespace) {
+                return Err(format!("`{}` is not an email", email));
+            }
+            cmd += &format!(" --email {}", email);
+        }
+
+        if crate::core::into_job_command(&cmd).is_none() {
+            return Err(format!("`{}` is not a job, or does not take these arguments", cmd));
+        }
+
+        Ok(cmd)
+    }
+}
+
+/// A job that has started and not yet finished.
+#[derive(Debug, Clone, JsonSchema, Serialize)]
+pub struct AdminJob {
+    pub saga_id: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    /// How far along the job is. This is only known for jobs running in the instance of the
+    /// server that answered the request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<JobProgress>,
+}
+
+pub async fn handle_run_job(api_context: &ServerContext, request: AdminRunRequest) -> Result<uuid::Uuid, HttpError> {
+    let cmd = request
+        .command()
+        .map_err(|message| HttpError::for_bad_request(None, message))?;
+
+    info!("running `{}` on demand", cmd);
+
+    crate::handlers_cron::run_subcmd_job(api_context, &cmd)
+        .await
+        .map_err(|e| HttpError::for_internal_error(format!("{:?}", e)))
+}
+
+pub async fn handle_list_jobs(api_context: &ServerContext) -> anyhow::Result<Vec<AdminJob>> {
+    let running = functions::dsl::functions
+        .filter(functions::dsl::status.eq(octorust::types::JobStatus::InProgress.to_string()))
+        .order_by(functions::dsl::created_at.desc())
+        .load_async::<Function>(api_context.app.db.pool())
+        .await?;
+
+    Ok(running
+        .into_iter()
+        .map(|f| AdminJob {
+            progress: uuid::Uuid::parse_str(&f.saga_id)
+                .ok()
+                .and_then(|id| crate::sagas::job_progress(&id)),
+            saga_id: f.saga_id,
+            name: f.name,
+            created_at: f.created_at,
+        })
+        .collect())
+}
+
+pub fn handle_cancel_job(saga_id: uuid::Uuid) -> Result<(), HttpError> {
+    if crate::sagas::cancel_job(&saga_id) {
+        info!("cancelling job {}", saga_id);
+        Ok(())
+    } else {
+        Err(HttpError::for_client_error(
+            None,
+            http::StatusCode::NOT_FOUND,
+            format!("job {} is not running in this instance", saga_id),
+        ))
+    }
+}
+
+/// How many failures are listed when a request does not say.
+const DEFAULT_FAILURE_LIMIT: usize = 100;
+
+/// The most failures a single request can list or requeue.
+const MAX_FAILURES: usize = 1000;
+
+/// How much of the end of a failed job's logs is returned with it. The error a job failed with
+/// is at the end of its logs.
+const FAILED_JOB_ERROR_CHARS: usize = 2000;
+
+/// Which failures to list, as query parameters.
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize)]
+pub struct FailureQuery {
+    /// The source of webhook events, ie. `github`, or the name of jobs, ie. `sync-rfds`.
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub error_class: Option<ErrorClass>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl FailureQuery {
+    fn filter(&self) -> FailureFilter {
+        FailureFilter {
+            source: self.source.clone(),
+            since: self.since,
+            until: self.until,
+            error_class: self.error_class,
+        }
+    }
+
+    fn limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_FAILURE_LIMIT).min(MAX_FAILURES)
+    }
+}
+
+/// A webhook event that failed every time it was attempted.
+#[derive(Debug, Clone, JsonSchema, Serialize)]
+pub struct FailedWebhookEvent {
+    pub id: i32,
+    pub source: String,
+    pub event_type: String,
+    pub attempts: i32,
+    pub error: String,
+    pub error_class: ErrorClass,
+    pub received_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub correlation_id: String,
+}
+
+impl From<WebhookEvent> for FailedWebhookEvent {
+    fn from(event: WebhookEvent) -> Self {
+        FailedWebhookEvent {
+            error_class: ErrorClass::of(&event.last_error),
+            id: event.id,
+            source: event.source,
+            event_type: event.event_type,
+            attempts: event.attempts,
+            error: event.last_error,
+            received_at: event.received_at,
+            finished_at: event.finished_at,
+            correlation_id: event.correlation_id,
+        }
+    }
+}
+
+/// A run of a job that failed or timed out.
+#[derive(Debug, Clone, JsonSchema, Serialize)]
+pub struct FailedJob {
+    pub saga_id: String,
+    pub name: String,
+    /// The end of the job's logs.
+    pub error: String,
+    pub error_class: ErrorClass,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl From<Function> for FailedJob {
+    fn from(f: Function) -> Self {
+        let skip = f.logs.chars().count().saturating_sub(FAILED_JOB_ERROR_CHARS);
+
+        FailedJob {
+            error_class: ErrorClass::of(&f.logs),
+            error: f.logs.chars().skip(skip).collect(),
+            saga_id: f.saga_id,
+            name: f.name,
+            created_at: f.created_at,
+            completed_at: f.completed_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, JsonSchema, Serialize)]
+pub struct RequeueResult {
+    /// How many webhook events were put back in the queue.
+    pub requeued: usize,
+}
+
+pub async fn handle_list_failed_webhooks(
+    api_context: &ServerContext,
+    query: FailureQuery,
+) -> anyhow::Result<Vec<FailedWebhookEvent>> {
+    let failed = WebhookEvent::list_failed(&api_context.app.db, &query.filter(), query.limit()).await?;
+
+    Ok(failed.into_iter().map(FailedWebhookEvent::from).collect())
+}
+
+/// Put the failed webhook events that match `filter` back in the queue, up to the most a
+/// request can requeue. Requeue again to pick up any that are left.
+pub async fn handle_requeue_failed_webhooks(
+    api_context: &ServerContext,
+    filter: FailureFilter,
+) -> anyhow::Result<RequeueResult> {
+    let db = &api_context.app.db;
+
+    let ids = WebhookEvent::list_failed(db, &filter, MAX_FAILURES)
+        .await?
+        .into_iter()
+        .map(|event| event.id)
+        .collect::<Vec<_>>();
+
+    let requeued = WebhookEvent::requeue_failed(db, ids).await?;
+    info!("requeued {} failed webhook events matching {:?}", requeued, filter);
+
+    Ok(RequeueResult { requeued })
+}
+
+pub async fn handle_list_failed_jobs(
+    api_context: &ServerContext,
+    query: FailureQuery,
+) -> anyhow::Result<Vec<FailedJob>> {
+    let failed = Function::list_failed(&api_context.app.db, &query.filter(), query.limit()).await?;
+
+    Ok(failed.into_iter().map(FailedJob::from).collect())
+}
+
+/// Run again each job that has failed runs matching `filter`. A job that failed many times is
+/// run once. Returns the saga ids of the new runs.
+pub async fn handle_requeue_failed_jobs(
+    api_context: &ServerContext,
+    filter: FailureFilter,
+) -> anyhow::Result<Vec<uuid::Uuid>> {
+    let names = Function::list_failed(&api_context.app.db, &filter, MAX_FAILURES)
+        .await?
+        .into_iter()
+        .map(|f| f.name)
+        .collect::<BTreeSet<_>>();
+
+    let mut saga_ids = vec![];
+    for name in names {
+        info!("rerunning failed job `{}`", name);
+        saga_ids.push(crate::handlers_cron::run_subcmd_job(api_context, &name).await?);
+    }
+
+    Ok(saga_ids)
+}
+
+/// How often a comment is sent down an idle progress stream, so that proxies in between do not
+/// close it.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// A progress event in the format of a server-sent event.
+fn sse_message(event: &ProgressEvent) -> String {
+    format!(
+        "event: {}\ndata: {}\n\n",
+        event.kind,
+        serde_json::to_string(event).unwrap_or_default()
+    )
+}
+
+/// Stream the progress events of a running job as server-sent events. The events the job has
+/// already reported are sent first, and the stream ends with a `done` event when the job
+/// finishes.
+pub fn handle_job_events(saga_id: uuid::Uuid) -> Result<Response<Body>, HttpError> {
+    let (history, mut events) = crate::sagas::subscribe_job_progress(&saga_id).ok_or_else(|| {
+        HttpError::for_client_error(
+            None,
+            http::StatusCode::NOT_FOUND,
+            format!("job {} is not running in this instance", saga_id),
+        )
+    })?;
+
+    let (mut sender, body) = Body::channel();
+
+    tokio::spawn(async move {
+        for event in history {
+            if sender.send_data(Bytes::from(sse_message(&event))).await.is_err() {
+                return;
+            }
+        }
+
+        let mut keep_alive = tokio::time::interval(KEEP_ALIVE_INTERVAL);
+
+        loop {
+            let message = tokio::select! {
+                event = events.recv() => match event {
+                    Ok(event) => sse_message(&event),
+                    Err(RecvError::Lagged(missed)) => {
+                        warn!("progress stream for job {} fell behind, {} events were dropped", saga_id, missed);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                _ = keep_alive.tick() => ": keep-alive\n\n".to_string(),
+            };
+
+            // The client went away.
+            if sender.send_data(Bytes::from(message)).await.is_err() {
+                return;
+            }
+        }
+
+        let _ = sender.send_data(Bytes::from("event: done\ndata: {}\n\n")).await;
+    });
+
+    Response::builder()
+        .status(http::StatusCode::OK)
+        .header(http::header::CONTENT_TYPE, "text/event-stream")
+        .header(http::header::CACHE_CONTROL, "no-cache")
+        .body(body)
+        .map_err(|e| HttpError::for_internal_error(format!("{:?}", e)))
+}
+
+pub async fn handle_list_github_routes(api_context: &ServerContext) -> anyhow::Result<Vec<GitHubEventRoute>> {
+    GitHubEventRoute::list(&api_context.app.db).await
+}
+
+pub async fn handle_create_github_route(
+    api_context: &ServerContext,
+    route: NewGitHubEventRoute,
+) -> Result<GitHubEventRoute, HttpError> {
+    crate::handlers_github::routes::validate_route(&route)
+        .map_err(|message| HttpError::for_bad_request(None, message))?;
+
+    let route = route
+        .create(&api_context.app.db)
+        .await
+        .map_err(|e| HttpError::for_internal_error(format!("{:?}", e)))?;
+
+    info!(
+        "added GitHub event route {}: `{}` on {} ({}) to {}",
+        route.id, route.event_type, route.repo_glob, route.action, route.handler
+    );
+
+    Ok(route)
+}
+
+pub async fn handle_update_github_route(
+    api_context: &ServerContext,
+    id: i32,
+    route: NewGitHubEventRoute,
+) -> Result<GitHubEventRoute, HttpError> {
+    crate::handlers_github::routes::validate_route(&route)
+        .map_err(|message| HttpError::for_bad_request(None, message))?;
+
+    let route = GitHubEventRoute::update(&api_context.app.db, id, &route)
+        .await
+        .map_err(|e| HttpError::for_internal_error(format!("{:?}", e)))?
+        .ok_or_else(|| route_not_found(id))?;
+
+    info!(
+        "updated GitHub event route {}: `{}` on {} ({}) to {}",
+        route.id, route.event_type, route.repo_glob, route.action, route.handler
+    );
+
+    Ok(route)
+}
+
+pub async fn handle_delete_github_route(api_context: &ServerContext, id: i32) -> Result<(), HttpError> {
+    let deleted = GitHubEventRoute::delete(&api_context.app.db, id)
+        .await
+        .map_err(|e| HttpError::for_internal_error(format!("{:?}", e)))?;
+
+    if deleted {
+        info!("deleted GitHub event route {}", id);
+        Ok(())
+    } else {
+        Err(route_not_found(id))
+    }
+}
+
+fn route_not_found(id: i32) -> HttpError {
+    HttpError::for_client_error(
+        None,
+        http::StatusCode::NOT_FOUND,
+        format!("there is no GitHub event route {}", id),
+    )
+}
+
+pub async fn handle_list_notification_rules(api_context: &ServerContext) -> anyhow::Result<Vec<NotificationRule>> {
+    NotificationRule::list(&api_context.app.db, api_context.app.company.id).await
+}
+
+pub async fn handle_create_notification_rule(
+    api_context: &ServerContext,
+    mut rule: NewNotificationRule,
+) -> Result<NotificationRule, HttpError> {
+    rule.validate()
+        .map_err(|message| HttpError::for_bad_request(None, message))?;
+    rule.cio_company_id = api_context.app.company.id;
+
+    let rule = rule
+        .create(&api_context.app.db)
+        .await
+        .map_err(|e| HttpError::for_internal_error(format!("{:?}", e)))?;
+
+    info!(
+        "added notification rule {}: `{}` to {} {}",
+        rule.id, rule.event, rule.channel, rule.target
+    );
+
+    Ok(rule)
+}
+
+pub async fn handle_update_notification_rule(
+    api_context: &ServerContext,
+    id: i32,
+    mut rule: NewNotificationRule,
+) -> Result<NotificationRule, HttpError> {
+    rule.validate()
+        .map_err(|message| HttpError::for_bad_request(None, message))?;
+    rule.cio_company_id = api_context.app.company.id;
+
+    let rule = NotificationRule::update(&api_context.app.db, api_context.app.company.id, id, &rule)
+        .await
+        .map_err(|e| HttpError::for_internal_error(format!("{:?}", e)))?
+        .ok_or_else(|| notification_rule_not_found(id))?;
+
+    info!(
+        "updated notification rule {}: `{}` to {} {}",
+        rule.id, rule.event, rule.channel, rule.target
+    );
+
+    Ok(rule)
+}
+
+pub async fn handle_delete_notification_rule(api_context: &ServerContext, id: i32) -> Result<(), HttpError> {
+    let deleted = NotificationRule::delete(&api_context.app.db, api_context.app.company.id, id)
+        .await
+        .map_err(|e| HttpError::for_internal_error(format!("{:?}", e)))?;
+
+    if deleted {
+        info!("deleted notification rule {}", id);
+        Ok(())
+    } else {
+        Err(notification_rule_not_found(id))
+    }
+}
+
+fn notification_rule_not_found(id: i32) -> HttpError {
+    HttpError::for_client_error(
+        None,
+        http::StatusCode::NOT_FOUND,
+        format!("there is no notification rule {}", id),
+    )
+}
+
+pub async fn handle_list_webhook_consumers(api_context: &ServerContext) -> anyhow::Result<Vec<WebhookConsumer>> {
+    WebhookConsumer::list(&api_context.app.db, api_context.app.company.id).await
+}
+
+pub async fn handle_register_webhook_consumer(
+    api_context: &ServerContext,
+    consumer: NewWebhookConsumer,
+) -> Result<RegisteredWebhookConsumer, HttpError> {
+    consumer
+        .validate()
+        .map_err(|message| HttpError::for_bad_request(None, message))?;
+
+    let registered = consumer
+        .register(&api_context.app.db, api_context.app.company.id)
+        .await
+        .map_err(|e| HttpError::for_internal_error(format!("{:?}", e)))?;
+
+    info!(
+        "registered webhook consumer {} `{}` for {}",
+        registered.consumer.id,
+        registered.consumer.name,
+        registered.consumer.events.join(", ")
+    );
+
+    Ok(registered)
+}
+
+pub async fn handle_delete_webhook_consumer(api_context: &ServerContext, id: i32) -> Result<(), HttpError> {
+    let deleted = WebhookConsumer::delete(&api_context.app.db, api_context.app.company.id, id)
+        .await
+        .map_err(|e| HttpError::for_internal_error(format!("{:?}", e)))?;
+
+    if deleted {
+        info!("deleted webhook consumer {}", id);
+        Ok(())
+    } else {
+        Err(webhook_consumer_not_found(id))
+    }
+}
+
+/// Which deliveries of a webhook consumer to list, as query parameters.
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize)]
+pub struct WebhookDeliveryQuery {
+    /// One of `pending`, `delivered` or `failed`.
+    #[serde(default)]
+    pub status: Option<String>,
+    /// Defaults to 100, newest first.
+    #[serde(default)]
+    pub limit: Option<i64>,
+}
+
+pub async fn handle_list_webhook_deliveries(
+    api_context: &ServerContext,
+    consumer_id: i32,
+    query: WebhookDeliveryQuery,
+) -> Result<Vec<WebhookDelivery>, HttpError> {
+    let db = &api_context.app.db;
+    let company_id = api_context.app.company.id;
+
+    WebhookConsumer::get(db, company_id, consumer_id)
+        .await
+        .map_err(|e| HttpError::for_internal_error(format!("{:?}", e)))?
+        .ok_or_else(|| webhook_consumer_not_found(consumer_id))?;
+
+    WebhookDelivery::list_for_consumer(
+        db,
+        company_id,
+        consumer_id,
+        query.status.as_deref(),
+        query.limit.unwrap_or(100),
+    )
+    .await
+    .map_err(|e| HttpError::for_internal_error(format!("{:?}", e)))
+}
+
+pub async fn handle_redeliver_webhook_delivery(
+    api_context: &ServerContext,
+    id: i32,
+) -> Result<WebhookDelivery, HttpError> {
+    let delivery = WebhookDelivery::redeliver(&api_context.app.db, api_context.app.company.id, id)
+        .await
+        .map_err(|e| HttpError::for_internal_error(format!("{:?}", e)))?
+        .ok_or_else(|| {
+            HttpError::for_client_error(
+                None,
+                http::StatusCode::NOT_FOUND,
+                format!("there is no webhook delivery {}", id),
+            )
+        })?;
+
+    info!(
+        "queued webhook delivery {} of `{}` again",
+        delivery.id, delivery.event_key
+    );
+
+    Ok(delivery)
+}
+
+fn webhook_consumer_not_found(id: i32) -> HttpError {
+    HttpError::for_client_error(
+        None,
+        http::StatusCode::NOT_FOUND,
+        format!("there is no webhook consumer {}", id),
+    )
+}
+
+pub async fn handle_reprint_shipment_label(
+    api_context: &ServerContext,
+    id: i32,
+    reprint: LabelReprint,
+) -> Result<OutboundShipment, HttpError> {
+    reprint_label(&api_context.app.db, api_context.app.company.id, id, reprint)
+        .await
+        .map_err(shipment_change_error)
+}
+
+pub async fn handle_correct_shipment_address(
+    api_context: &ServerContext,
+    id: i32,
+    correction: AddressCorrection,
+) -> Result<OutboundShipment, HttpError> {
+    correct_address(&api_context.app.db, api_context.app.company.id, id, correction)
+        .await
+        .map_err(shipment_change_error)
+}
+
+pub async fn handle_list_shipment_history(
+    api_context: &ServerContext,
+    id: i32,
+) -> anyhow::Result<Vec<OutboundShipmentEvent>> {
+    OutboundShipmentEvent::list_for_shipment(&api_context.app.db, api_context.app.company.id, id).await
+}
+
+fn shipment_change_error(err: anyhow::Error) -> HttpError {
+    let status = match err.downcast_ref::<ShipmentChangeRejection>() {
+        Some(ShipmentChangeRejection::UnknownShipment) => http::StatusCode::NOT_FOUND,
+        Some(ShipmentChangeRejection::UndeliverableAddress(_)) => http::StatusCode::BAD_REQUEST,
+        Some(_) => http::StatusCode::CONFLICT,
+        None => return HttpError::for_internal_error(format!("{:?}", err)),
+    };
+
+    HttpError::for_client_error(None, status, err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use cio_api::progress::{ProgressEvent, ProgressEventKind};
+
+    use super::{sse_message, AdminRunRequest, FailedJob, FailureQuery, MAX_FAILURES};
+
+    #[test]
+    fn test_failure_query_limit() {
+        assert_eq!(100, FailureQuery::default().limit());
+
+        let query = |limit| FailureQuery {
+            limit: Some(limit),
+            ..Default::default()
+        };
+        assert_eq!(10, query(10).limit());
+        assert_eq!(MAX_FAILURES, query(1_000_000).limit());
+    }
+
+    #[test]
+    fn test_failed_job_error() {
+        let job = FailedJob::from(cio_api::functions::Function {
+            id: 1,
+            name: "sync-rfds".to_string(),
+            status: "completed".to_string(),
+            conclusion: "failure".to_string(),
+            created_at: Utc::now(),
+            completed_at: None,
+            logs: format!("{}code: 503, error: Service Unavailable", "ok\n".repeat(2000)),
+            saga_id: "not-a-real-uuid".to_string(),
+            cio_company_id: 1,
+            airtable_record_id: String::new(),
+        });
+
+        assert_eq!(2000, job.error.chars().count());
+        assert!(job.error.ends_with("code: 503, error: Service Unavailable"));
+        assert_eq!(cio_api::failures::ErrorClass::ServerError, job.error_class);
+    }
+
+    #[test]
+    fn test_sse_message() {
+        let event = ProgressEvent {
+            kind: ProgressEventKind::StageProgress,
+            stage: "rfds".to_string(),
+            done: Some(3),
+            total: Some(10),
+            error: None,
+            at: Utc::now(),
+        };
+
+        let message = sse_message(&event);
+        assert!(message.starts_with("event: stage_progress\ndata: {"));
+        assert!(message.ends_with("}\n\n"));
+        assert!(message.contains("\"stage\":\"rfds\""));
+        assert_eq!(1, message.matches("data: ").count());
+    }
+
+    #[test]
+    fn test_admin_run_request_command() {
+        let request = |job: &str, rfd_number: Option<i32>, applicant_email: Option<&str>| AdminRunRequest {
+            job: job.to_string(),
+            rfd_number,
+            applicant_email: applicant_email.map(|email| email.to_string()),
+        };
+
+        assert_eq!(
+            Ok("sync-repos".to_string()),
+            request("sync-repos", None, None).command()
+        );
+        assert_eq!(
+            Ok("sync-rfds --number 123".to_string()),
+            request("sync-rfds", Some(123), None).command()
+        );
+        assert_eq!(
+            Ok("sync-applications --email jane@example.com".to_string()),
+            request("sync-applications", None, Some("jane@example.com")).command()
+        );
+
+        assert!(request("server", None, None).command().is_err());
+        assert!(request("sync-repos", Some(123), None).command().is_err());
+        assert!(request("sync-applications", None, Some("jane@example.com --debug"))
+            .command()
+            .is_err());
+    }
+}
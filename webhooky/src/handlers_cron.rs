@@ -40,8 +40,9 @@ pub async fn run_subcmd_job(server_context: &ServerContext, cmd_name: &str) -> R
 
     let id = uuid::Uuid::new_v4();
 
-    // Run the saga.
-    crate::sagas::run_cmd(db, &server_context.sec, &id, cmd_name).await?;
+    // Run the saga, as part of whatever webhook or scheduled run asked for it.
+    let correlation_id = cio_api::correlation::current().unwrap_or_default();
+    crate::sagas::run_cmd(db, &server_context.sec, &id, cmd_name, &correlation_id).await?;
 
     Ok(id)
 }
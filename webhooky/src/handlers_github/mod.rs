@@ -8,6 +8,10 @@ use cio_api::{
         sync_links, sync_resources, sync_users,
     },
     core::GitHubCommit,
+    journal_club_proposals::{
+        proposal_from_issue, propose_paper, proposer_from_github, PaperProposal, ProposalRejection, ProposalSource,
+    },
+    redaction,
     repos::NewRepo,
     rfd::{GitHubRFDBranch, GitHubRFDRepo, GitHubRFDUpdate},
     shorturls::{generate_shorturls_for_configs_links, generate_shorturls_for_repos},
@@ -24,12 +28,13 @@ use crate::{
     event_types::EventType,
     github_types::GitHubWebhook,
     http::Headers,
-    repos::Repo,
 };
 
 pub mod rfd;
+pub mod routes;
 
 pub use rfd::RFDUpdater;
+pub use routes::GitHubHandler;
 
 #[derive(Debug)]
 pub struct GitHubWebhookVerification;
@@ -65,26 +70,20 @@ impl HmacSignatureVerifier for GitHubWebhookVerification {
     }
 }
 
-/// Handle a request to the /github endpoint.
-pub async fn handle_github(rqctx: Arc<RequestContext<ServerContext>>, event: GitHubWebhook) -> Result<()> {
-    let api_context = rqctx.context();
-
-    // Parse the `X-GitHub-Event` header. Ensure the request lock is dropped once the
-    // event_type has been extracted.
-    // TODO: make this nicer when supported as a first class method in dropshot.
-    let event_type_string = {
-        let req = rqctx.request.lock().await;
-        let req_headers = req.headers();
-        req_headers
-            .get("X-GitHub-Event")
-            .unwrap_or(&http::header::HeaderValue::from_str("")?)
-            .to_str()
-            .unwrap()
-            .to_string()
+/// Read the type of a GitHub webhook from its `X-GitHub-Event` header.
+pub async fn github_event_type(rqctx: &Arc<RequestContext<ServerContext>>) -> Result<EventType> {
+    let headers = Headers::from_request(rqctx.clone()).await?;
+    let event_type = match headers.0.get("X-GitHub-Event") {
+        Some(header_value) => header_value.to_str()?,
+        None => "",
     };
 
-    let event_type =
-        EventType::from_str(&event_type_string).expect("Event type from GitHub does not match a known event type");
+    EventType::from_str(event_type).map_err(anyhow::Error::msg)
+}
+
+/// Handle an event that was sent to the /github endpoint.
+pub async fn handle_github(api_context: &ServerContext, event_type: EventType, event: GitHubWebhook) -> Result<()> {
+    let event_type_string = event_type.to_string();
 
     info!(
         "Processing incoming {} webhook event on {}",
@@ -162,22 +161,36 @@ pub async fn handle_github(rqctx: Arc<RequestContext<ServerContext>>, event: Git
         _ => (),
     }
 
-    // Run the correct handler function based on the event type and repo.
+    // Run the handlers the routing table has for the event type and repo.
     if !event.repository.name.is_empty() {
+        let handlers = routes::handlers_for_event(&api_context.app.db, event_type, &event).await?;
+        if handlers.is_empty() {
+            // We can throw this out, log it and return early.
+            info!(
+                "`{}` event was to the {} repo, no automations are set up for this repo yet",
+                event_type, event.repository.name
+            );
+            return Ok(());
+        }
+
         let repo = &event.repository;
-        let repo_name = Repo::from_str(&repo.name).unwrap();
 
         let company = Company::get_from_github_org(&api_context.app.db, &repo.owner.login).await?;
         let github = Arc::new(company.authenticate_github()?);
 
-        match repo_name {
-            Repo::RFD => match event_type {
-                EventType::Push => {
-                    sentry::configure_scope(|scope| {
-                        scope.set_context("github.webhook", sentry::protocol::Context::Other(event.clone().into()));
-                        scope.set_tag("github.event.type", &event_type_string);
-                    });
+        sentry::configure_scope(|scope| {
+            scope.set_context("github.webhook", sentry::protocol::Context::Other(event.clone().into()));
+            scope.set_tag("github.event.type", &event_type_string);
+        });
 
+        for handler in handlers {
+            info!(
+                "Running the {} handler for `{}` event on {}",
+                handler, event_type, repo.name
+            );
+
+            match handler {
+                GitHubHandler::RFDPush => {
                     match handle_rfd_push(github.clone(), &api_context.app, event.clone()).await {
                         Ok(_) => ( /* Silence */ ),
                         Err(e) => {
@@ -187,11 +200,7 @@ pub async fn handle_github(rqctx: Arc<RequestContext<ServerContext>>, event: Git
                         }
                     }
                 }
-                EventType::PullRequest => {
-                    sentry::configure_scope(|scope| {
-                        scope.set_context("github.webhook", sentry::protocol::Context::Other(event.clone().into()));
-                        scope.set_tag("github.event.type", &event_type_string);
-                    });
+                GitHubHandler::RFDPullRequest => {
                     // Let's create the check run.
                     let check_run_id = event.create_check_run(&github).await?;
 
@@ -213,17 +222,7 @@ pub async fn handle_github(rqctx: Arc<RequestContext<ServerContext>>, event: Git
                         }
                     }
                 }
-                EventType::CheckRun => {}
-                EventType::CheckSuite => {}
-                _ => (),
-            },
-            Repo::Configs => {
-                if let EventType::Push = event_type {
-                    sentry::configure_scope(|scope| {
-                        scope.set_context("github.webhook", sentry::protocol::Context::Other(event.clone().into()));
-                        scope.set_tag("github.event.type", &event_type_string);
-                    });
-
+                GitHubHandler::ConfigsPush => {
                     match handle_configs_push(&github, &api_context.app, event.clone(), &company).await {
                         Ok(message) => {
                             info!("{}", message);
@@ -236,13 +235,24 @@ pub async fn handle_github(rqctx: Arc<RequestContext<ServerContext>>, event: Git
                         }
                     }
                 }
-            }
-            _ => {
-                // We can throw this out, log it and return early.
-                info!(
-                    "`{}` event was to the {} repo, no automations are set up for this repo yet",
-                    event_type, repo_name
-                );
+                GitHubHandler::PaperProposal => {
+                    let message = match handle_paper_proposal_issue(&api_context.app, event.clone(), &company).await {
+                        Ok(message) => message,
+                        Err(e) => event.get_error_string("proposing the paper of the issue", e),
+                    };
+
+                    if !message.is_empty() {
+                        github
+                            .issues()
+                            .create_comment(
+                                &repo.owner.login,
+                                &repo.name,
+                                event.issue.number,
+                                &octorust::types::PullsUpdateReviewRequest { body: message },
+                            )
+                            .await?;
+                    }
+                }
             }
         }
     }
@@ -410,6 +420,51 @@ pub async fn handle_rfd_pull_request(
     ))
 }
 
+/// Handle an `issues` event for an issue proposing a paper for the journal club. Returns what to
+/// reply to the issue with.
+pub async fn handle_paper_proposal_issue(
+    api_context: &Context,
+    event: GitHubWebhook,
+    company: &Company,
+) -> Result<String> {
+    if event.action != "opened" {
+        return Ok(String::new());
+    }
+
+    let issue = &event.issue;
+    let (link, title) = match proposal_from_issue(&issue.title, &issue.body) {
+        Some(proposal) => proposal,
+        None => return Ok("Add a link to the paper to the issue to propose it for the journal club.".to_string()),
+    };
+
+    let app_config = api_context.app_config.read().unwrap().clone();
+    let proposal = PaperProposal {
+        link,
+        title,
+        proposed_by: proposer_from_github(&api_context.db, company, &issue.user.login).await?,
+        source: ProposalSource::GitHub,
+        source_link: issue.html_url.to_string(),
+    };
+
+    match propose_paper(&api_context.db, company, &app_config.journal_club, proposal).await {
+        Ok(proposal) => {
+            let mut message = format!("Proposed **{}** for the journal club.", proposal.display_title());
+            if let Some(date) = proposal.meeting_date {
+                message.push_str(&format!(" It is on the agenda for {}.", date.format("%A %B %-d")));
+            }
+            if !proposal.thread_link().is_empty() {
+                message.push_str(&format!(" Discuss it in [Slack]({}).", proposal.thread_link()));
+            }
+
+            Ok(message)
+        }
+        Err(e) => match e.downcast_ref::<ProposalRejection>() {
+            Some(rejection) => Ok(format!("Could not propose the paper: {}.", rejection)),
+            None => Err(e),
+        },
+    }
+}
+
 /// Handle a `push` event for the configs repo.
 pub async fn handle_configs_push(
     github: &octorust::Client,
@@ -470,6 +525,10 @@ pub async fn handle_configs_push(
     // Check if the cio.toml file has changed. This contains app configuration data and should be
     // used to overwrite the existing app config
     if commit.file_changed("configs/cio.toml") {
+        if let Err(e) = redaction::configure(&configs.app_config.redaction) {
+            a(&format!("[ERROR]: redaction config, keeping the previous one: {}", e));
+        }
+
         let mut app_config = api_context.app_config.write().unwrap();
         *app_config = configs.app_config;
     }
@@ -3,9 +3,14 @@ use async_trait::async_trait;
 use cio_api::{
     core::GitHubPullRequest,
     features::Features,
-    rfd::{GitHubRFDReadmeLocation, GitHubRFDUpdate, NewRFD, RFDOutputError, RFDSearchIndex, RemoteRFD, RFD},
+    notifications::{notify, Notification, NotificationEvent},
+    rfd::{
+        fingerprint_image_path, image_sub_path, GitHubRFDReadmeLocation, GitHubRFDUpdate, NewRFD, RFDOutputError,
+        RFDReviewers, RFDSearchIndex, RemoteRFD, RFD,
+    },
     shorturls::generate_shorturls_for_rfds,
     utils::{create_or_update_file_in_github_repo, decode_base64, get_file_content_from_repo},
+    webhook_outbox::{publish, OutboxEvent},
 };
 use google_drive::traits::{DriveOps, FileOps};
 use google_storage1::{
@@ -46,6 +51,8 @@ impl Default for RFDUpdater {
             Box::new(GenerateShortUrls),
             Box::new(CreatePullRequest),
             Box::new(UpdatePullRequest),
+            Box::new(RequestReviewers),
+            Box::new(NotifyPublished),
             Box::new(UpdateDiscussionUrl),                    // Stops on error
             Box::new(EnsureRFDWithPullRequestIsInValidState), // Stops on error
             Box::new(EnsureRFDOnDefaultIsInValidState),       // Stops on error
@@ -243,12 +250,18 @@ impl RFDUpdateAction for CopyImagesToGCP {
     async fn run(
         &self,
         ctx: &mut RFDUpdateActionContext,
-        _rfd: &mut RFD,
+        rfd: &mut RFD,
     ) -> Result<RFDUpdateActionResponse, RFDUpdateActionErr> {
         let RFDUpdateActionContext {
             api_context, update, ..
         } = ctx;
 
+        // The static storage is served publicly by the RFD site
+        if !rfd.is_public() {
+            info!("Skipping copying images of RFD {} as it is not public", rfd.number);
+            return Ok(RFDUpdateActionResponse::default());
+        }
+
         let images = update
             .branch
             .get_images(&update.number)
@@ -274,29 +287,44 @@ impl RFDUpdateAction for CopyImagesToGCP {
         );
 
         for image in images {
-            let sub_path = image
-                .path
-                .replace(&format!("rfd/{}/", update.number.as_number_string()), "");
-            let object_name = format!("rfd/{}/latest/{}", update.number, sub_path);
-            let mime_type = mime_guess::guess_mime_type(&object_name);
+            let sub_path = image_sub_path(&update.number, &image.path);
             let data = decode_base64(&image.content);
 
-            log::info!(
-                "Writing {} ({}) with size {} to GCP",
-                object_name,
-                mime_type,
-                data.len()
-            );
+            // The rendered RFD links to the fingerprinted copy, which never changes and can be
+            // cached forever. The copy under the original name is kept for links to it from
+            // elsewhere.
+            let fingerprinted = fingerprint_image_path(&sub_path, &data);
+            let copies = [
+                (sub_path, Object::default()),
+                (
+                    fingerprinted,
+                    Object {
+                        cache_control: Some("public, max-age=31536000, immutable".to_string()),
+                        ..Default::default()
+                    },
+                ),
+            ];
+
+            for (path, request) in copies {
+                let object_name = format!("rfd/{}/latest/{}", update.number, path);
+                let mime_type = mime_guess::guess_mime_type(&object_name);
+
+                log::info!(
+                    "Writing {} ({}) with size {} to GCP",
+                    object_name,
+                    mime_type,
+                    data.len()
+                );
 
-            let cursor = std::io::Cursor::new(data);
+                let cursor = std::io::Cursor::new(data.clone());
 
-            let request = Object::default();
-            hub.objects()
-                .insert(request, &api_context.company.rfd_static_storage())
-                .name(&object_name)
-                .upload(cursor, mime_type)
-                .await
-                .map_err(|err| RFDUpdateActionErr::Continue(err.into()))?;
+                hub.objects()
+                    .insert(request, &api_context.company.rfd_static_storage())
+                    .name(&object_name)
+                    .upload(cursor, mime_type)
+                    .await
+                    .map_err(|err| RFDUpdateActionErr::Continue(err.into()))?;
+            }
         }
 
         Ok(RFDUpdateActionResponse::default())
@@ -314,6 +342,20 @@ impl RFDUpdateAction for UpdateSearch {
     ) -> Result<RFDUpdateActionResponse, RFDUpdateActionErr> {
         let RFDUpdateActionContext { update, .. } = ctx;
         let client = RFDSearchIndex::default_client().map_err(RFDUpdateActionErr::Continue)?;
+
+        // Remove anything indexed while the RFD was public, and keep it out until it is public
+        if !rfd.is_public() {
+            RFDSearchIndex::delete_rfd(&client, "rfd".to_string(), &rfd.number.into())
+                .await
+                .map_err(RFDUpdateActionErr::Continue)?;
+            info!(
+                "Removed RFD {} from the search index as it is not public",
+                update.number
+            );
+
+            return Ok(RFDUpdateActionResponse::default());
+        }
+
         RFDSearchIndex::index_rfd(&client, "rfd".to_string(), &rfd.number.into(), &rfd.content)
             .await
             .map_err(RFDUpdateActionErr::Continue)?;
@@ -463,6 +505,13 @@ impl RFDUpdateAction for UpdatePDFs {
             ..
         } = ctx;
 
+        // PDFs are published to GitHub and Google Drive, neither of which should see an RFD
+        // before it is public
+        if !rfd.is_public() {
+            info!("Skipping generating PDFs of RFD {} as it is not public", rfd.number);
+            return Ok(RFDUpdateActionResponse::default());
+        }
+
         Self::upload(api_context, update, rfd)
             .await
             .map_err(RFDUpdateActionErr::Continue)?;
@@ -685,6 +734,113 @@ impl RFDUpdateAction for UpdatePullRequest {
     }
 }
 
+pub struct RequestReviewers;
+
+#[async_trait]
+impl RFDUpdateAction for RequestReviewers {
+    async fn run(
+        &self,
+        ctx: &mut RFDUpdateActionContext,
+        rfd: &mut RFD,
+    ) -> Result<RFDUpdateActionResponse, RFDUpdateActionErr> {
+        let RFDUpdateActionContext {
+            api_context,
+            update,
+            pull_requests,
+            github,
+            ..
+        } = ctx;
+
+        // We only want to operate on open pull requests
+        let open_prs = pull_requests
+            .iter()
+            .filter(|pr| pr.state == "open")
+            .collect::<Vec<&GitHubPullRequest>>();
+
+        // Explicitly we will only request reviews on a pull request if it is the only open pull
+        // request for the branch that we are working on
+        let pull_request = match open_prs.as_slice() {
+            [pull_request] => pull_request,
+            _ => return Ok(RFDUpdateActionResponse::default()),
+        };
+
+        let reviewers_file = api_context
+            .app_config
+            .read()
+            .unwrap()
+            .rfd_reviews
+            .reviewers_file
+            .clone();
+        let reviewers = RFDReviewers::get(
+            github,
+            &update.branch.owner,
+            &update.branch.repo,
+            &update.branch.default_branch,
+            &reviewers_file,
+        )
+        .await
+        .for_rfd(&rfd.number_string);
+
+        if reviewers.is_empty() {
+            return Ok(RFDUpdateActionResponse::default());
+        }
+
+        // Requesting a review from someone who already reviewed asks them to review again, which
+        // should only happen when the author asks for it.
+        let reviews = github
+            .pulls()
+            .list_all_reviews(&update.branch.owner, &update.branch.repo, pull_request.number)
+            .await
+            .map_err(RFDUpdateActionErr::Continue)?;
+        let reviewed = reviews
+            .iter()
+            .filter_map(|review| review.user.as_ref().map(|user| user.login.to_lowercase()))
+            .collect::<Vec<String>>();
+
+        let users = reviewers
+            .users
+            .into_iter()
+            .filter(|login| {
+                let login = login.to_lowercase();
+                login != pull_request.user.login.to_lowercase() && !reviewed.contains(&login)
+            })
+            .collect::<Vec<String>>();
+
+        if users.is_empty() && reviewers.teams.is_empty() {
+            return Ok(RFDUpdateActionResponse::default());
+        }
+
+        github
+            .pulls()
+            .request_reviewers(
+                &update.branch.owner,
+                &update.branch.repo,
+                pull_request.number,
+                &octorust::types::PullsRequestReviewersRequest {
+                    reviewers: users.clone(),
+                    team_reviewers: reviewers.teams.clone(),
+                },
+            )
+            .await
+            .map_err(|err| {
+                RFDUpdateActionErr::Continue(anyhow!(
+                    "unable to request reviews from {:?} and teams {:?} for pr#{}: {}",
+                    users,
+                    reviewers.teams,
+                    pull_request.number,
+                    err,
+                ))
+            })?;
+
+        info!(
+            "Requested reviews on RFD {} from {:?} and teams {:?}",
+            rfd.number, users, reviewers.teams
+        );
+
+        Ok(RFDUpdateActionResponse::default())
+    }
+}
+
 pub struct UpdateDiscussionUrl;
 
 #[async_trait]
@@ -826,3 +982,61 @@ impl RFDUpdateAction for EnsureRFDOnDefaultIsInValidState {
         Ok(RFDUpdateActionResponse::default())
     }
 }
+
+pub struct NotifyPublished;
+
+#[async_trait]
+impl RFDUpdateAction for NotifyPublished {
+    async fn run(
+        &self,
+        ctx: &mut RFDUpdateActionContext,
+        rfd: &mut RFD,
+    ) -> Result<RFDUpdateActionResponse, RFDUpdateActionErr> {
+        let RFDUpdateActionContext {
+            api_context, old_rfd, ..
+        } = ctx;
+
+        // The state usually changes on the RFD's own branch, just before it is merged, so this
+        // runs for updates on any branch.
+        let was_published = old_rfd.map(|old| old.state == "published").unwrap_or(false);
+        if rfd.state != "published" || was_published {
+            return Ok(RFDUpdateActionResponse::default());
+        }
+
+        let notification = Notification::new(
+            NotificationEvent::RfdPublished,
+            &rfd.number_string,
+            &format!("RFD {} published", rfd.name),
+            &format!("*{}* by {}", rfd.name, rfd.authors),
+        )
+        .with_url(&rfd.rendered_link)
+        .with_attribute("number", &rfd.number.to_string())
+        .with_attribute("authors", &rfd.authors)
+        .with_attribute("visibility", &rfd.visibility);
+
+        // Visibility is passed along for consumers to decide what they can show.
+        let data = serde_json::json!({
+            "number": rfd.number,
+            "title": rfd.title,
+            "authors": rfd.authors,
+            "visibility": rfd.visibility,
+            "link": rfd.rendered_link,
+        });
+        if let Err(e) = publish(
+            &api_context.db,
+            &api_context.company,
+            OutboxEvent::RfdPublished,
+            &rfd.number.to_string(),
+            data,
+        )
+        .await
+        {
+            warn!("failed to publish that RFD {} was published: {}", rfd.number, e);
+        }
+
+        notify(&api_context.db, &api_context.company, &notification)
+            .await
+            .map(|_| RFDUpdateActionResponse::default())
+            .map_err(RFDUpdateActionErr::Continue)
+    }
+}
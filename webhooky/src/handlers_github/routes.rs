@@ -0,0 +1,167 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::Result;
+use cio_api::{
+    db::Database,
+    github_event_routes::{GitHubEventRoute, NewGitHubEventRoute},
+};
+use log::warn;
+
+use crate::{event_types::EventType, github_types::GitHubWebhook};
+
+/// The handlers that routes in the GitHub event routing table can run.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum GitHubHandler {
+    /// Update the RFDs changed by a `push` to the rfd repo.
+    RFDPush,
+    /// Check and update the RFD of a pull request to the rfd repo.
+    RFDPullRequest,
+    /// Sync everything that is configured in the configs repo.
+    ConfigsPush,
+    /// Propose the paper of an issue opened with the paper template for the journal club.
+    PaperProposal,
+}
+
+impl GitHubHandler {
+    pub const ALL: [GitHubHandler; 4] = [
+        GitHubHandler::RFDPush,
+        GitHubHandler::RFDPullRequest,
+        GitHubHandler::ConfigsPush,
+        GitHubHandler::PaperProposal,
+    ];
+
+    /// Returns a static string for the handler name.
+    pub fn name(self) -> &'static str {
+        match self {
+            GitHubHandler::RFDPush => "rfd-push",
+            GitHubHandler::RFDPullRequest => "rfd-pull-request",
+            GitHubHandler::ConfigsPush => "configs-push",
+            GitHubHandler::PaperProposal => "paper-proposal",
+        }
+    }
+}
+
+impl FromStr for GitHubHandler {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        GitHubHandler::ALL
+            .into_iter()
+            .find(|handler| handler.name() == s)
+            .ok_or_else(|| format!("invalid GitHub handler: `{}`", s))
+    }
+}
+
+impl fmt::Display for GitHubHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Check that a route names a known event type and handler, before it is saved.
+pub fn validate_route(route: &NewGitHubEventRoute) -> Result<(), String> {
+    // Unknown event types parse as the wildcard.
+    if EventType::from_str(&route.event_type)?.name() != route.event_type {
+        return Err(format!("invalid GitHub event: `{}`", route.event_type));
+    }
+
+    if route.repo_glob.trim().is_empty() {
+        return Err("a route needs a repo glob, use `*` for every repo".to_string());
+    }
+
+    if route.action.trim().is_empty() {
+        return Err("a route needs an action, use `*` for every action".to_string());
+    }
+
+    GitHubHandler::from_str(&route.handler)?;
+
+    Ok(())
+}
+
+/// The handlers of the routes that `routes` has for an event to `repo` with `action`, each
+/// handler once and in the order its first route was created.
+fn handlers_for(routes: &[GitHubEventRoute], repo: &str, action: &str) -> Vec<GitHubHandler> {
+    let mut handlers = vec![];
+
+    for route in routes.iter().filter(|route| route.matches(repo, action)) {
+        match GitHubHandler::from_str(&route.handler) {
+            Ok(handler) if !handlers.contains(&handler) => handlers.push(handler),
+            Ok(_) => (),
+            // Routes are checked when they are saved, so this is a handler that has since been
+            // removed.
+            Err(e) => warn!("skipping GitHub event route {}: {}", route.id, e),
+        }
+    }
+
+    handlers
+}
+
+/// The handlers to run for `event`.
+pub async fn handlers_for_event(
+    db: &Database,
+    event_type: EventType,
+    event: &GitHubWebhook,
+) -> Result<Vec<GitHubHandler>> {
+    let routes = GitHubEventRoute::list_enabled_for_event(db, &event_type.to_string()).await?;
+
+    Ok(handlers_for(&routes, &event.repository.name, &event.action))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use cio_api::github_event_routes::{GitHubEventRoute, NewGitHubEventRoute};
+
+    use super::{handlers_for, validate_route, GitHubHandler};
+
+    fn route(id: i32, repo_glob: &str, action: &str, handler: &str) -> GitHubEventRoute {
+        GitHubEventRoute {
+            id,
+            event_type: "push".to_string(),
+            repo_glob: repo_glob.to_string(),
+            action: action.to_string(),
+            handler: handler.to_string(),
+            enabled: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_handlers_for() {
+        let routes = vec![
+            route(1, "rfd", "*", "rfd-push"),
+            route(2, "configs*", "*", "configs-push"),
+            route(3, "*", "opened", "rfd-pull-request"),
+            route(4, "rfd", "*", "not-a-handler"),
+            route(5, "rfd*", "*", "rfd-push"),
+        ];
+
+        assert_eq!(vec![GitHubHandler::RFDPush], handlers_for(&routes, "rfd", ""));
+        assert_eq!(
+            vec![GitHubHandler::RFDPush, GitHubHandler::RFDPullRequest],
+            handlers_for(&routes, "rfd", "opened")
+        );
+        assert_eq!(
+            vec![GitHubHandler::ConfigsPush],
+            handlers_for(&routes, "configs-staging", "")
+        );
+        assert!(handlers_for(&routes, "omicron", "").is_empty());
+    }
+
+    #[test]
+    fn test_validate_route() {
+        let new_route = |event_type: &str, handler: &str| NewGitHubEventRoute {
+            event_type: event_type.to_string(),
+            repo_glob: "rfd".to_string(),
+            action: "*".to_string(),
+            handler: handler.to_string(),
+            enabled: true,
+        };
+
+        assert!(validate_route(&new_route("push", "rfd-push")).is_ok());
+        assert!(validate_route(&new_route("*", "configs-push")).is_ok());
+        assert!(validate_route(&new_route("not-an-event", "rfd-push")).is_err());
+        assert!(validate_route(&new_route("push", "not-a-handler")).is_err());
+    }
+}
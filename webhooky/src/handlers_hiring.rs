@@ -1,10 +1,18 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_bb8_diesel::AsyncRunQueryDsl;
 use chrono::{DateTime, Utc};
-use cio_api::{applicants::Applicant, schema::applicants};
+use cio_api::{
+    applicant_duplicates::{merge_applicants, ApplicantMerge},
+    applicants::Applicant,
+    companies::Company,
+    interview_scheduling::{book_interview, propose_interview_times, InterviewPanel, InterviewSchedule},
+    offer_approvals::decide_offer_approval,
+    recruiting_funnel::{recruiting_funnel, RecruitingFunnel, RecruitingFunnelQuery},
+    schema::applicants,
+};
 use diesel::{ExpressionMethods, QueryDsl};
 use schemars::JsonSchema;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::context::Context;
 
@@ -47,11 +55,223 @@ pub struct ApplicantUploadToken {
     token: String,
 }
 
-pub async fn handle_applicant_upload_token(ctx: &Context, email: String) -> Result<ApplicantUploadToken> {
-    let token = ctx.upload_token_store.get(&email).await?;
+pub async fn handle_applicant_upload_token(
+    ctx: &Context,
+    email: String,
+    cio_company_id: i32,
+) -> Result<ApplicantUploadToken> {
+    let token = ctx.upload_token_store.get(&email, cio_company_id).await?;
 
     Ok(ApplicantUploadToken {
         email,
         token: token.token,
     })
 }
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct VoidOfferRequest {
+    /// The reason for voiding the offer. This is shown to the applicant.
+    reason: String,
+    /// Generate a new offer from the applicant's current record on the next sync.
+    #[serde(default)]
+    reissue: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CorrectOfferRequest {
+    /// Where DocuSign should send the sender once they have finished the correction.
+    return_url: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AdverseActionRequest {
+    /// When to send the post-adverse action notice. Defaults to the end of the mandatory
+    /// waiting period.
+    #[serde(default)]
+    post_notice_scheduled_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ApplicantMergeRequest {
+    /// The applicant that is kept.
+    applicant_id: i32,
+    /// The applicant that is merged into the other, and then deleted.
+    duplicate_id: i32,
+}
+
+pub async fn handle_interview_times_propose(
+    ctx: &Context,
+    email: String,
+    panel: InterviewPanel,
+) -> Result<InterviewSchedule> {
+    let applicant = get_newest_applicant(ctx, &email).await?;
+    let config = ctx.app_config.read().unwrap().apply.interviews.clone();
+
+    propose_interview_times(&ctx.db, &ctx.company, &config, &applicant, &panel).await
+}
+
+/// The interview times proposed to an applicant, as the applicant sees them.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct InterviewProposal {
+    status: String,
+    times: Vec<DateTime<Utc>>,
+    booked_time: Option<DateTime<Utc>>,
+    /// The names of the sessions, in the order they run in.
+    sessions: Vec<String>,
+    duration_minutes: i64,
+}
+
+impl TryFrom<InterviewSchedule> for InterviewProposal {
+    type Error = anyhow::Error;
+
+    fn try_from(schedule: InterviewSchedule) -> Result<Self> {
+        let panel = schedule.panel()?;
+
+        Ok(InterviewProposal {
+            status: schedule.status,
+            times: schedule.proposed_times,
+            booked_time: schedule.booked_time,
+            sessions: panel.sessions.iter().map(|session| session.name.to_string()).collect(),
+            duration_minutes: panel.duration().num_minutes(),
+        })
+    }
+}
+
+pub async fn handle_interview_proposal(ctx: &Context, token: String) -> Result<Option<InterviewProposal>> {
+    InterviewSchedule::get_by_token(&ctx.db, &token)
+        .await?
+        .map(InterviewProposal::try_from)
+        .transpose()
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct BookInterviewRequest {
+    /// One of the proposed times.
+    time: DateTime<Utc>,
+}
+
+pub async fn handle_interview_book(
+    ctx: &Context,
+    token: String,
+    request: BookInterviewRequest,
+) -> Result<InterviewProposal> {
+    let (config, zoom_config) = {
+        let app_config = ctx.app_config.read().unwrap();
+        (app_config.apply.interviews.clone(), app_config.zoom.clone())
+    };
+
+    book_interview(&ctx.db, &ctx.company, &config, &zoom_config, &token, request.time)
+        .await?
+        .try_into()
+}
+
+/// Record an approver's decision on the terms of an offer, and send the offer right away when
+/// it was approved. Returns what was decided, for the Slack message that asked for it.
+pub async fn handle_offer_approval_decision(
+    ctx: &Context,
+    company: &Company,
+    approval_id: &str,
+    slack_user_id: &str,
+    approve: bool,
+) -> Result<String> {
+    let (config, envelopes) = {
+        let app_config = ctx.app_config.read().unwrap();
+        (
+            app_config.envelopes.offer_approval.clone(),
+            app_config.envelopes.clone(),
+        )
+    };
+
+    let approval =
+        decide_offer_approval(&ctx.db, company, &config, approval_id.parse()?, slack_user_id, approve).await?;
+
+    let mut applicant = applicants::dsl::applicants
+        .find(approval.applicant_id)
+        .first_async::<Applicant>(ctx.db.pool())
+        .await?;
+
+    if approve {
+        let ds = company.authenticate_docusign(&ctx.db).await?;
+        applicant
+            .do_docusign_offer(&ctx.db, &ds, envelopes.create_offer_letter(&applicant), &config)
+            .await?;
+    }
+
+    Ok(format!(
+        "The offer for *{}* ({}, {}) was {} by <@{}>.",
+        applicant.name, approval.salary, approval.equity, approval.status, slack_user_id
+    ))
+}
+
+async fn get_newest_applicant(ctx: &Context, email: &str) -> Result<Applicant> {
+    applicants::dsl::applicants
+        .filter(applicants::dsl::email.eq(email.to_string()))
+        .filter(applicants::dsl::cio_company_id.eq(ctx.company.id))
+        .order_by(applicants::dsl::id.desc())
+        .load_async::<Applicant>(ctx.db.pool())
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("no applicant found with email {}", email))
+}
+
+pub async fn handle_applicant_offer_void(ctx: &Context, email: String, request: VoidOfferRequest) -> Result<()> {
+    let mut applicant = get_newest_applicant(ctx, &email).await?;
+    let ds = ctx.company.authenticate_docusign(&ctx.db).await?;
+
+    applicant
+        .void_docusign_offer(&ctx.db, &ds, &request.reason, request.reissue)
+        .await
+}
+
+pub async fn handle_applicant_offer_resend(ctx: &Context, email: String) -> Result<()> {
+    let applicant = get_newest_applicant(ctx, &email).await?;
+    let ds = ctx.company.authenticate_docusign(&ctx.db).await?;
+
+    applicant.resend_docusign_offer(&ds).await
+}
+
+pub async fn handle_applicant_offer_correct(
+    ctx: &Context,
+    email: String,
+    request: CorrectOfferRequest,
+) -> Result<String> {
+    let applicant = get_newest_applicant(ctx, &email).await?;
+    let ds = ctx.company.authenticate_docusign(&ctx.db).await?;
+
+    applicant.correct_docusign_offer(&ds, &request.return_url).await
+}
+
+pub async fn handle_applicant_adverse_action(
+    ctx: &Context,
+    email: String,
+    request: AdverseActionRequest,
+) -> Result<()> {
+    let mut applicant = get_newest_applicant(ctx, &email).await?;
+    let checkr = ctx
+        .company
+        .authenticate_checkr()
+        .ok_or_else(|| anyhow!("company {} does not have a checkr api key", ctx.company.name))?;
+
+    applicant
+        .start_adverse_action(&ctx.db, &checkr, request.post_notice_scheduled_at)
+        .await
+}
+
+pub async fn handle_applicant_adverse_action_cancel(ctx: &Context, email: String) -> Result<()> {
+    let mut applicant = get_newest_applicant(ctx, &email).await?;
+    let checkr = ctx
+        .company
+        .authenticate_checkr()
+        .ok_or_else(|| anyhow!("company {} does not have a checkr api key", ctx.company.name))?;
+
+    applicant.cancel_adverse_action(&ctx.db, &checkr).await
+}
+
+pub async fn handle_recruiting_funnel(ctx: &Context, query: RecruitingFunnelQuery) -> Result<RecruitingFunnel> {
+    recruiting_funnel(&ctx.db, &ctx.company, &query).await
+}
+
+pub async fn handle_applicant_merge(ctx: &Context, request: ApplicantMergeRequest) -> Result<ApplicantMerge> {
+    merge_applicants(&ctx.db, &ctx.company, request.applicant_id, request.duplicate_id).await
+}
@@ -1,26 +1,40 @@
-use anyhow::Result;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Result};
 use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::Utc;
 use cio_api::{
-    rfd::{GitHubRFDRepo, NewRFD, RFDEntry, RFDIndexEntry, RFDs, RFD},
+    circuit_breaker::CircuitBreaker,
+    progress,
+    rfd::{GitHubRFDRepo, NewRFD, RFDEntry, RFDIndexEntry, RFDSearchIndex, RFDs, RFD},
     schema::rfds,
 };
-use diesel::{ExpressionMethods, QueryDsl};
-use log::{info, warn};
+use diesel::{BoolExpressionMethods, ExpressionMethods, QueryDsl};
+use log::{error, info, warn};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
 
 use crate::{
     context::Context,
     handlers_github::{
         rfd::{
             CopyImagesToGCP, CreatePullRequest, EnsureRFDOnDefaultIsInValidState,
-            EnsureRFDWithPullRequestIsInValidState, GenerateShortUrls, UpdateDiscussionUrl, UpdatePDFs,
-            UpdatePullRequest, UpdateSearch,
+            EnsureRFDWithPullRequestIsInValidState, GenerateShortUrls, RequestReviewers, UpdateDiscussionUrl,
+            UpdatePDFs, UpdatePullRequest, UpdateSearch,
         },
         RFDUpdater,
     },
 };
 
 pub async fn handle_rfd_index(ctx: &Context, offset: i32, limit: u32) -> Result<Vec<RFDIndexEntry>> {
+    // Internal RFDs and those still under embargo are left out of the index
+    let today = Utc::now().date().naive_utc();
+
     let rfds = rfds::dsl::rfds
+        .filter(
+            rfds::dsl::visibility.eq("public").or(rfds::dsl::visibility
+                .eq("embargoed")
+                .and(rfds::dsl::embargoed_until.le(today))),
+        )
         .order_by(rfds::dsl::number)
         .offset(offset as i64)
         .limit(limit as i64)
@@ -53,27 +67,91 @@ pub async fn handle_rfd_view(ctx: &Context, num: i32) -> Result<Option<RFDEntry>
         .load_async::<RFD>(ctx.db.pool())
         .await?;
 
-    if !rfd.is_empty() {
-        let new_rfd: NewRFD = rfd.pop().unwrap().into();
-        Ok(Some(new_rfd.into()))
-    } else {
-        Ok(None)
+    match rfd.pop() {
+        // Non-public RFDs are reported as missing rather than forbidden so that their existence
+        // is not given away
+        Some(rfd) if rfd.is_public() => {
+            let new_rfd: NewRFD = rfd.into();
+            Ok(Some(new_rfd.into()))
+        }
+        _ => Ok(None),
     }
 }
 
+// Sync a single rfd with our database.
+pub async fn refresh_db_rfd(context: &Context, number: i32) -> Result<()> {
+    info!("triggering an update for RFD number `{}`", number);
+
+    let rfd = RFD::get_from_db(&context.db, number)
+        .await
+        .ok_or_else(|| anyhow::anyhow!("no RFD was found with number `{}`", number))?;
+
+    let update = rfd.create_sync(&context.company).await?;
+    RFDUpdater::default().handle(context, &[update]).await
+}
+
 // Sync the rfds with our database.
 pub async fn refresh_db_rfds(context: &Context) -> Result<()> {
+    // Make sure the search index can be filtered by RFD before we update any documents.
+    match RFDSearchIndex::default_client() {
+        Ok(client) => {
+            if let Err(e) = RFDSearchIndex::configure_index(&client, "rfd".to_string()).await {
+                warn!("[rfd] Failed to configure the search index: {}", e);
+            }
+        }
+        Err(e) => warn!("[rfd] Failed to create the search client: {}", e),
+    }
+
     let repo = GitHubRFDRepo::new(&context.company).await?;
     let updates = repo.get_rfd_sync_updates().await?;
 
+    let total = updates.len();
+    let mut done = 0;
+
     let batches = chunk(updates, 3);
 
+    // Every RFD update talks to GitHub, so while GitHub is down every one of them fails. Rather
+    // than working through all of them, pause once and give up if it is still down after that.
+    let breaker_config = context.app_config.read().unwrap().github.circuit_breaker.clone();
+    let mut breaker = CircuitBreaker::new(
+        breaker_config.failure_threshold,
+        Duration::from_secs(breaker_config.cooldown_secs),
+    );
+
     // TODO: Turn this into proper batch jobs instead of small parallelism
     for batch in batches.into_iter() {
+        if let Some(cooldown) = breaker.cooldown_remaining(Instant::now()) {
+            if breaker.trips() > 1 {
+                bail!(
+                    "[rfd] GitHub was still failing after the sync paused, stopping after {} of {} RFDs",
+                    done,
+                    total
+                );
+            }
+
+            error!(
+                "[rfd] {} RFD updates failed in a row, pausing the sync for {}s",
+                breaker_config.failure_threshold,
+                cooldown.as_secs()
+            );
+            if !breaker_config.alert_channel.is_empty() {
+                let alert = breaker_alert(&breaker_config.alert_channel, done, total, cooldown);
+                if let Err(e) = context.company.post_to_slack_channel(&context.db, &alert).await {
+                    warn!("[rfd] Failed to alert that the sync paused: {}", e);
+                }
+            }
+
+            tokio::time::sleep(cooldown).await;
+        }
+
+        let batch_len = batch.len();
         let mut tasks: Vec<tokio::task::JoinHandle<Result<()>>> = vec![];
+        let mut numbers = vec![];
 
         for update in batch.into_iter() {
-            let task = tokio::spawn(enclose! { (context) async move {
+            numbers.push(update.number);
+
+            let task = cio_api::correlation::spawn(enclose! { (context) async move {
 
                 let updater = RFDUpdater::new(vec![
                     Box::new(CopyImagesToGCP),
@@ -81,6 +159,7 @@ pub async fn refresh_db_rfds(context: &Context) -> Result<()> {
                     Box::new(UpdatePDFs),
                     Box::new(CreatePullRequest),
                     Box::new(UpdatePullRequest),
+                    Box::new(RequestReviewers),
                     Box::new(UpdateDiscussionUrl),
                     Box::new(EnsureRFDWithPullRequestIsInValidState),
                     Box::new(EnsureRFDOnDefaultIsInValidState),
@@ -99,11 +178,19 @@ pub async fn refresh_db_rfds(context: &Context) -> Result<()> {
             results.push(task.await?);
         }
 
-        for result in results {
-            if let Err(e) = result {
-                warn!("[rfd] Refresh task failed with  {}", e);
+        for (number, result) in numbers.into_iter().zip(results) {
+            match result {
+                Ok(()) => breaker.record_success(),
+                Err(e) => {
+                    warn!("[rfd] Refresh task failed with  {}", e);
+                    progress::stage_failed("rfds", format!("RFD {}: {}", number, e));
+                    breaker.record_failure(Instant::now());
+                }
             }
         }
+
+        done += batch_len;
+        progress::stage_progress("rfds", done, Some(total));
     }
 
     // Generate all short urls once after updating all of the RFDs. Once the RFDUpdater supports
@@ -121,6 +208,30 @@ pub async fn refresh_db_rfds(context: &Context) -> Result<()> {
     Ok(())
 }
 
+fn breaker_alert(channel: &str, done: usize, total: usize, cooldown: Duration) -> FormattedMessage {
+    FormattedMessage {
+        channel: channel.to_string(),
+        attachments: Default::default(),
+        blocks: vec![MessageBlock {
+            block_type: MessageBlockType::Section,
+            text: Some(MessageBlockText {
+                text_type: MessageType::Markdown,
+                text: format!(
+                    "*The RFD sync is paused, GitHub is failing*\n{} of {} RFDs were synced. The sync tries \
+                     again in {} minutes, and stops if GitHub is still failing then.",
+                    done,
+                    total,
+                    (cooldown.as_secs() + 59) / 60
+                ),
+            }),
+            elements: Default::default(),
+            accessory: Default::default(),
+            block_id: Default::default(),
+            fields: Default::default(),
+        }],
+    }
+}
+
 fn chunk<T>(mut source: Vec<T>, chunk_size: usize) -> Vec<Vec<T>> {
     let mut chunks = vec![];
 
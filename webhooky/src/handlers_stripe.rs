@@ -0,0 +1,121 @@
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use dropshot::{Extractor, RequestContext, ServerContext, UntypedBody};
+use dropshot_verify_request::sig::HmacSignatureVerifier;
+use hmac::Hmac;
+use log::{info, warn};
+use sha2::Sha256;
+use std::{
+    borrow::Cow,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::http::Headers;
+
+/// How far the signing timestamp of a Stripe webhook may be from the current time, this is the
+/// tolerance the Stripe libraries use.
+const STRIPE_SIGNATURE_TOLERANCE_SECS: u64 = 300;
+
+#[derive(Debug)]
+pub struct StripeWebhookVerification;
+
+/// The parts of a `Stripe-Signature` header, which looks like `t=1492774577,v1=5257a869...`.
+/// While a webhook secret is being rolled there is a `v1` signature for each secret, and the
+/// first one is for the current secret.
+#[derive(Debug, PartialEq, Eq)]
+struct StripeSignature {
+    timestamp: String,
+    signature: Vec<u8>,
+}
+
+fn parse_stripe_signature(header: &str) -> Result<StripeSignature> {
+    let mut timestamp = None;
+    let mut signature = None;
+
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) if timestamp.is_none() => timestamp = Some(value.to_string()),
+            Some(("v1", value)) if signature.is_none() => signature = Some(hex::decode(value)?),
+            _ => (),
+        }
+    }
+
+    Ok(StripeSignature {
+        timestamp: timestamp.ok_or_else(|| anyhow!("Stripe signature is missing a timestamp"))?,
+        signature: signature.ok_or_else(|| anyhow!("Stripe signature is missing a v1 signature"))?,
+    })
+}
+
+async fn stripe_signature<Context: ServerContext>(rqctx: &Arc<RequestContext<Context>>) -> Result<StripeSignature> {
+    let headers = Headers::from_request(rqctx.clone()).await?;
+    headers
+        .0
+        .get("Stripe-Signature")
+        .ok_or_else(|| anyhow!("Stripe webhook is missing signature"))
+        .and_then(|header_value| Ok(header_value.to_str()?))
+        .and_then(parse_stripe_signature)
+        .map_err(|err| {
+            info!("Stripe webhook is missing a well-formed signature: {}", err);
+            err
+        })
+}
+
+#[async_trait]
+impl HmacSignatureVerifier for StripeWebhookVerification {
+    type Algo = Hmac<Sha256>;
+
+    async fn key<Context: ServerContext>(_: Arc<RequestContext<Context>>) -> Result<Vec<u8>> {
+        Ok(std::env::var("STRIPE_WH_KEY")
+            .map(|key| key.into_bytes())
+            .map_err(|err| {
+                warn!("Failed to find webhook key for verifying Stripe webhooks");
+                err
+            })?)
+    }
+
+    async fn signature<Context: ServerContext>(rqctx: Arc<RequestContext<Context>>) -> Result<Vec<u8>> {
+        let signature = stripe_signature(&rqctx).await?;
+
+        // A webhook that was signed too long ago is treated the same as one with an invalid
+        // signature, so that a captured webhook can not be replayed.
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        if now.abs_diff(signature.timestamp.parse::<u64>()?) > STRIPE_SIGNATURE_TOLERANCE_SECS {
+            bail!(
+                "Stripe webhook was signed at {} which is too long ago",
+                signature.timestamp
+            );
+        }
+
+        Ok(signature.signature)
+    }
+
+    async fn content<'a, 'b, Context: ServerContext>(
+        rqctx: &'a Arc<RequestContext<Context>>,
+        body: &'b UntypedBody,
+    ) -> anyhow::Result<Cow<'b, [u8]>> {
+        let signature = stripe_signature(rqctx).await?;
+
+        let mut content = (signature.timestamp + ".").into_bytes();
+        content.extend_from_slice(body.as_bytes());
+
+        Ok(Cow::Owned(content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_stripe_signature;
+
+    #[test]
+    fn test_parse_stripe_signature() {
+        let signature = parse_stripe_signature("t=1492774577,v1=5257a869,v0=6ffbb59b,v1=0a0b").unwrap();
+
+        assert_eq!("1492774577", signature.timestamp);
+        assert_eq!(vec![0x52, 0x57, 0xa8, 0x69], signature.signature);
+
+        assert!(parse_stripe_signature("v1=5257a869").is_err());
+        assert!(parse_stripe_signature("t=1492774577,v0=6ffbb59b").is_err());
+        assert!(parse_stripe_signature("t=1492774577,v1=not-hex").is_err());
+    }
+}
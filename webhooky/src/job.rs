@@ -1,12 +1,107 @@
-use crate::context::Context;
 use anyhow::Result;
+use cio_api::progress;
+
+use crate::context::Context;
 
 pub async fn run_job_cmd(cmd: crate::core::SubCommand, context: Context) -> Result<()> {
     match cmd {
+        crate::core::SubCommand::BackfillRFDHistory(crate::core::BackfillRFDHistory { number }) => {
+            let Context { db, company, .. } = context;
+            cio_api::rfd::backfill_rfd_history(&db, &company, number).await?;
+        }
+        crate::core::SubCommand::RenewCertificates(_) => {
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+            let app_config = app_config.read().unwrap().clone();
+            cio_api::certs::renew_expiring_certificates(&db, &company, &app_config.certificates).await?;
+        }
+        crate::core::SubCommand::RevokeExpiredAccess(_) => {
+            let Context { db, company, .. } = context;
+            cio_api::access_requests::revoke_expired_access(&db, &company).await?;
+        }
+        crate::core::SubCommand::RotateTailscaleKeys(_) => {
+            let Context {
+                app_config, company, ..
+            } = context;
+            let app_config = app_config.read().unwrap().clone();
+            cio_api::tailscale::rotate_tailscale_auth_keys(&company, &app_config.tailscale).await?;
+        }
+        crate::core::SubCommand::SendCelebrations(_) => {
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+            let app_config = app_config.read().unwrap().clone();
+            cio_api::celebrations::send_celebrations(&db, &company, &app_config.celebrations).await?;
+        }
+        crate::core::SubCommand::SendExpenseReport(_) => {
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+            let app_config = app_config.read().unwrap().clone();
+            cio_api::expense_anomalies::send_expense_report(&db, &company, &app_config.finance).await?;
+        }
+        crate::core::SubCommand::SendFinanceSummary(_) => {
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+            let app_config = app_config.read().unwrap().clone();
+            cio_api::finance::send_monthly_finance_summary(&db, &company, &app_config.finance).await?;
+        }
+        crate::core::SubCommand::SendInterviewerPackets(_) => {
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+            let app_config = app_config.read().unwrap().clone();
+            cio_api::interviewer_packets::send_interviewer_packets(&db, &company, &app_config.apply).await?;
+        }
+        crate::core::SubCommand::SendRecruitingSummary(_) => {
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+            let app_config = app_config.read().unwrap().clone();
+            cio_api::recruiting_funnel::send_recruiting_summary(&db, &company, &app_config.apply).await?;
+        }
         crate::core::SubCommand::SendRFDChangelog(_) => {
             let Context { db, company, .. } = context;
             cio_api::rfd::send_rfd_changelog(&db, &company).await?;
         }
+        crate::core::SubCommand::SendRFDReviewReminders(_) => {
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+            let app_config = app_config.read().unwrap().clone();
+            cio_api::rfd::remind_rfd_reviewers(&db, &company, &app_config.rfd_reviews).await?;
+        }
+        crate::core::SubCommand::SendScheduledMessages(_) => {
+            let Context { db, company, .. } = context;
+            cio_api::delivery::send_scheduled_messages(&db, &company).await?;
+        }
+        crate::core::SubCommand::SendWebhookDeliveries(_) => {
+            let Context { db, company, .. } = context;
+            cio_api::webhook_outbox::send_webhook_deliveries(&db, &company).await?;
+        }
         crate::core::SubCommand::SyncAnalytics(_) => {
             let Context { db, company, .. } = context;
             cio_api::analytics::refresh_analytics(&db, &company).await?;
@@ -15,6 +110,20 @@ pub async fn run_job_cmd(cmd: crate::core::SubCommand, context: Context) -> Resu
             let Context { db, company, .. } = context;
             cio_api::api_tokens::refresh_api_tokens(&db, &company).await?;
         }
+        crate::core::SubCommand::SyncApplicantDuplicates(_) => {
+            let Context { db, company, .. } = context;
+            cio_api::applicant_duplicates::refresh_applicant_duplicates(&db, &company).await?;
+        }
+        crate::core::SubCommand::SyncApplications(crate::core::SyncApplications { email: Some(email) }) => {
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+            let app_config = app_config.read().unwrap().clone();
+            cio_api::applicants::refresh_applicant(&db, &company, &app_config, &email).await?;
+        }
         crate::core::SubCommand::SyncApplications(_) => {
             let Context {
                 app_config,
@@ -25,15 +134,37 @@ pub async fn run_job_cmd(cmd: crate::core::SubCommand, context: Context) -> Resu
 
             // Do the new applicants.
             let app_config = app_config.read().unwrap().clone();
-            cio_api::applicants::refresh_new_applicants_and_reviews(&db, &company, &app_config).await?;
-            cio_api::applicant_reviews::refresh_reviews(&db, &company).await?;
+            progress::stage(
+                "applicants",
+                cio_api::applicants::refresh_new_applicants_and_reviews(&db, &company, &app_config),
+            )
+            .await?;
+            progress::stage("reviews", cio_api::applicant_reviews::refresh_reviews(&db, &company)).await?;
 
             // Refresh DocuSign for the applicants.
-            cio_api::applicants::refresh_docusign_for_applicants(&db, &company, &app_config).await?;
+            progress::stage(
+                "docusign",
+                cio_api::applicants::refresh_docusign_for_applicants(&db, &company, &app_config),
+            )
+            .await?;
+
+            // Refresh continuous checks and adverse actions.
+            progress::stage(
+                "background-checks",
+                cio_api::background_checks::refresh_background_checks(&db, &company, &app_config.background_checks),
+            )
+            .await?;
         }
         crate::core::SubCommand::SyncAssetInventory(_) => {
-            let Context { db, company, .. } = context;
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+            let app_config = app_config.read().unwrap().clone();
             cio_api::asset_inventory::refresh_asset_items(&db, &company).await?;
+            cio_api::asset_assignments::remind_unreturned_assets(&db, &company, &app_config.assets).await?;
         }
         crate::core::SubCommand::SyncCompanies(_) => {
             let Context { db, .. } = context;
@@ -63,33 +194,92 @@ pub async fn run_job_cmd(cmd: crate::core::SubCommand, context: Context) -> Resu
             let Context { db, company, .. } = context;
             cio_api::functions::refresh_functions(&db, &company).await?;
         }
+        crate::core::SubCommand::SyncGitHubRepoSettings(_) => {
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+            let app_config = app_config.read().unwrap().clone();
+            cio_api::github_repo_settings::sync_github_repo_settings(&db, &company, &app_config.github).await?;
+        }
+        crate::core::SubCommand::SyncGitHubTeams(_) => {
+            let Context {
+                app_config, company, ..
+            } = context;
+            let app_config = app_config.read().unwrap().clone();
+            cio_api::github_teams::sync_github_teams(&company, &app_config.github).await?;
+        }
+        crate::core::SubCommand::SyncGoogleGroups(_) => {
+            let Context { db, company, .. } = context;
+            cio_api::gsuite::sync_google_groups(&db, &company).await?;
+        }
+        crate::core::SubCommand::SyncGusto(_) => {
+            let Context { db, company, .. } = context;
+            cio_api::gusto::sync_gusto_employees(&db, &company).await?;
+        }
         crate::core::SubCommand::SyncHuddles(_) => {
             let Context { db, company, .. } = context;
+            cio_api::huddles::sync_huddle_events(&db, &company).await?;
             cio_api::huddles::sync_changes_to_google_events(&db, &company).await?;
             cio_api::huddles::sync_huddles(&db, &company).await?;
             cio_api::huddles::send_huddle_reminders(&db, &company).await?;
             cio_api::huddles::sync_huddle_meeting_notes(&company).await?;
         }
         crate::core::SubCommand::SyncInterviews(_) => {
-            let Context { db, company, .. } = context;
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+            let app_config = app_config.read().unwrap().clone();
             cio_api::interviews::refresh_interviews(&db, &company).await?;
+            cio_api::interviews::sync_interview_zoom_meetings(&db, &company, &app_config.zoom).await?;
             cio_api::interviews::compile_packets(&db, &company).await?;
         }
         crate::core::SubCommand::SyncJournalClubs(_) => {
-            let Context { db, company, .. } = context;
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+            let app_config = app_config.read().unwrap().clone();
+
             cio_api::journal_clubs::refresh_db_journal_club_meetings(&db, &company).await?;
+            cio_api::journal_club_proposals::sync_journal_club_proposals(&db, &company, &app_config.journal_club)
+                .await?;
         }
         crate::core::SubCommand::SyncMailingLists(_) => {
             if std::env::var("MAILERLITE_ENABLED")
                 .map(|v| v == "true")
                 .unwrap_or(false)
             {
-                let Context { db, .. } = context;
+                let Context {
+                    app_config,
+                    db,
+                    company,
+                    ..
+                } = context;
+                let app_config = app_config.read().unwrap().clone();
 
-                crate::mailing_lists::sync_pending_mailing_list_subscribers(&db).await?;
+                crate::mailing_lists::sync_pending_mailing_list_subscribers(&db, &company, &app_config.mailing_list)
+                    .await?;
                 crate::mailing_lists::sync_pending_wait_list_subscribers(&db).await?;
             }
         }
+        crate::core::SubCommand::SyncPagerDuty(_) => {
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+            let app_config = app_config.read().unwrap().clone();
+            cio_api::pagerduty::sync_pagerduty(&db, &company, &app_config.pagerduty).await?;
+        }
         crate::core::SubCommand::SyncRecordedMeetings(_) => {
             let Context { db, company, .. } = context;
             cio_api::recorded_meetings::refresh_zoom_recorded_meetings(&db, &company).await?;
@@ -117,15 +307,53 @@ pub async fn run_job_cmd(cmd: crate::core::SubCommand, context: Context) -> Resu
             sync_result?;
             refresh_result?;
         }
+        crate::core::SubCommand::SyncRFDs(crate::core::SyncRFDs { number: Some(number) }) => {
+            crate::handlers_rfd::refresh_db_rfd(&context, number).await?;
+        }
         crate::core::SubCommand::SyncRFDs(_) => {
             let Context { db, company, .. } = &context;
-            crate::handlers_rfd::refresh_db_rfds(&context).await?;
-            cio_api::rfd::drive::cleanup_rfd_pdfs(db, company).await?;
+            progress::stage("rfds", crate::handlers_rfd::refresh_db_rfds(&context)).await?;
+            progress::stage("rfd-pdfs", cio_api::rfd::drive::cleanup_rfd_pdfs(db, company)).await?;
+        }
+        crate::core::SubCommand::SyncOffboarding(_) => {
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+            let app_config = app_config.read().unwrap().clone();
+            cio_api::offboarding::run_offboarding(&db, &company, &app_config).await?;
+        }
+        crate::core::SubCommand::SyncOnboarding(_) => {
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+            let app_config = app_config.read().unwrap().clone();
+            cio_api::onboarding::run_onboarding(&db, &company, &app_config).await?;
+            cio_api::device_credentials::renew_device_credentials(&db, &company, &app_config.device_credentials)
+                .await?;
+        }
+        crate::core::SubCommand::SyncOrgChart(_) => {
+            let Context { db, company, .. } = context;
+            cio_api::org_chart::refresh_org_chart(&db, &company).await?;
         }
         crate::core::SubCommand::SyncOther(_) => {
-            let Context { company, .. } = context;
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+            let app_config = app_config.read().unwrap().clone();
+            cio_api::policy_acknowledgments::refresh_policy_acknowledgments(&db, &company).await?;
             cio_api::tailscale::cleanup_old_tailscale_devices(&company).await?;
             cio_api::tailscale::cleanup_old_tailscale_cloudflare_dns(&company).await?;
+            cio_api::tailscale::sync_tailscale_acl_groups(&db, &company).await?;
+            cio_api::tailscale::sync_tailscale_devices(&company, &app_config.tailscale).await?;
             cio_api::customers::sync_customer_meeting_notes(&company).await?;
         }
         crate::core::SubCommand::SyncShipments(_) => {
@@ -149,10 +377,17 @@ pub async fn run_job_cmd(cmd: crate::core::SubCommand, context: Context) -> Resu
             cio_api::shorturls::refresh_shorturls(&db, &company).await?;
         }
         crate::core::SubCommand::SyncSwagInventory(_) => {
-            let Context { db, company, .. } = context;
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+            let app_config = app_config.read().unwrap().clone();
             cio_api::swag_inventory::refresh_swag_items(&db, &company).await?;
             cio_api::swag_inventory::refresh_swag_inventory_items(&db, &company).await?;
             cio_api::swag_inventory::refresh_barcode_scans(&db, &company).await?;
+            cio_api::swag_stock::alert_low_stock(&db, &company, &app_config.swag).await?;
         }
         crate::core::SubCommand::SyncTravel(_) => {
             let Context { db, company, .. } = context;
@@ -4,26 +4,32 @@ pub mod context;
 #[macro_use]
 pub mod core;
 mod cors;
+mod event_queue;
 mod event_types;
 pub mod github_types;
 mod handlers;
+pub mod handlers_admin;
 pub mod handlers_auth;
 pub mod handlers_checkr;
 pub mod handlers_cron;
 pub mod handlers_docusign;
 pub mod handlers_github;
 pub mod handlers_hiring;
+pub mod handlers_mailerlite;
+pub mod handlers_quickbooks;
 pub mod handlers_rfd;
 pub mod handlers_slack;
+pub mod handlers_stripe;
 // mod handlers_sendgrid;
 mod health;
 mod http;
 mod job;
 mod mailing_lists;
-mod repos;
+mod rate_limit;
 mod sagas;
 pub mod server;
 mod slack_commands;
+mod uploads;
 // mod tracking_numbers;
 #[macro_use]
 extern crate serde_json;
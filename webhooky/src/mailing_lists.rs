@@ -1,15 +1,24 @@
 use anyhow::Result;
 use cio_api::{
+    app_config::MailingListConfig,
+    companies::Company,
     db::Database,
+    email_suppressions::is_suppressed,
     mailerlite::Mailerlite,
     mailing_list::{MailingListSubscriber, NewMailingListSubscriber},
+    mailing_list_providers::MailingListProviderOps,
     rack_line::{NewRackLineSubscriber, RackLineSubscriber},
 };
 use mailerlite::endpoints::BatchResponse;
 
-pub async fn sync_pending_mailing_list_subscribers(db: &Database) -> Result<()> {
+pub async fn sync_pending_mailing_list_subscribers(
+    db: &Database,
+    company: &Company,
+    config: &MailingListConfig,
+) -> Result<()> {
     let client = Mailerlite::new()?;
     let subscribers = client.pending_mailing_list_subscribers().await?;
+    let provider = company.authenticate_mailing_list_provider(config).await?;
 
     log::info!("Processing {} mailing list subscribers", subscribers.len());
 
@@ -34,6 +43,20 @@ pub async fn sync_pending_mailing_list_subscribers(db: &Database) -> Result<()>
                     );
                     err
                 })?;
+
+                // Do not add anyone back to the mailing list that has asked not to be emailed.
+                if is_suppressed(db, company.id, &new_subscriber.email).await? {
+                    log::info!(
+                        "Mailerlite subscriber {} is suppressed and will not be added to the mailing list provider",
+                        subscriber.id
+                    );
+                } else if let Err(err) = provider.upsert_member(&new_subscriber).await {
+                    log::warn!(
+                        "Failed to add mailing list subscriber {} to the mailing list provider due to {:?}",
+                        subscriber.id,
+                        err
+                    );
+                }
             } else {
                 log::info!(
                     "Mailerlite subscriber {} already exists in mailing list database",
@@ -4,26 +4,33 @@ mod context;
 #[macro_use]
 mod core;
 mod cors;
+mod event_queue;
 mod event_types;
 mod github_types;
 mod handlers;
+mod handlers_admin;
 mod handlers_auth;
 mod handlers_checkr;
 mod handlers_cron;
 mod handlers_docusign;
 mod handlers_github;
 mod handlers_hiring;
+mod handlers_mailerlite;
+mod handlers_quickbooks;
 mod handlers_rfd;
 mod handlers_slack;
+mod handlers_stripe;
 // mod handlers_sendgrid;
 mod health;
 mod http;
 mod job;
 mod mailing_lists;
-mod repos;
+mod rate_limit;
+mod redaction;
 mod sagas;
 mod server;
 mod slack_commands;
+mod uploads;
 // mod tracking_numbers;
 #[macro_use]
 extern crate serde_json;
@@ -44,6 +51,7 @@ use std::fs::File;
 
 use crate::context::ServerContext;
 use crate::health::SelfMemory;
+use crate::redaction::RedactingDrain;
 use crate::server::APIConfig;
 
 fn main() -> Result<()> {
@@ -72,7 +80,8 @@ async fn tokio_main() -> Result<()> {
         traces_sample_rate: 0.1,
 
         // Define custom rate limiting for database query events. Without aggressive rate limiting
-        // these will far exceed any transactions limits we are allowed.
+        // these will far exceed any transactions limits we are allowed. Events that are sent
+        // are redacted first.
         before_send: Some(std::sync::Arc::new(|event: Event<'static>| {
             if let Some(SentryContext::Trace(trace_ctx)) = event.contexts.get("trace") {
                 if let Some(ref op) = trace_ctx.op {
@@ -82,7 +91,7 @@ async fn tokio_main() -> Result<()> {
                 }
             }
 
-            Some(event)
+            Some(crate::redaction::redact_sentry_event(event))
         })),
 
         release: Some(env::var("GIT_HASH").unwrap_or_default().into()),
@@ -108,12 +117,14 @@ async fn tokio_main() -> Result<()> {
             .fuse();
         let drain = slog_async::Async::new(drain).build().fuse();
         let drain = sentry::integrations::slog::SentryDrain::new(drain);
+        let drain = RedactingDrain::new(drain);
         slog::Logger::root(drain, slog::slog_o!())
     } else {
         let decorator = slog_term::TermDecorator::new().build();
         let drain = slog_term::FullFormat::new(decorator).build().fuse();
         let drain = slog_async::Async::new(drain).build().fuse();
         let drain = sentry::integrations::slog::SentryDrain::new(drain);
+        let drain = RedactingDrain::new(drain);
         slog::Logger::root(drain, slog::slog_o!())
     };
 
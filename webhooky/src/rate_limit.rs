@@ -0,0 +1,380 @@
+/*!
+ * Per client rate limits for the endpoints of the server.
+ *
+ * Every request is counted against the source IP it came from, and requests that carry a token
+ * are also counted against that token once they are within the limit of their IP, so that made
+ * up tokens cannot add buckets faster than an IP is allowed to make requests. Each client gets a
+ * bucket that holds a minute's worth of requests and refills continuously, so a client can burst
+ * up to its limit and then keep going at that rate. Requests over the limit are rejected with a
+ * `429` before anything else about them is looked at.
+ *
+ * The limits are in requests per minute and are read from `WEBHOOKY_RATE_LIMIT_PER_TOKEN` and
+ * `WEBHOOKY_RATE_LIMIT_PER_IP`, a limit of `0` turns it off. Buckets live in memory, so each
+ * instance of the server enforces the limits on its own.
+ *
+ * The source IP is taken from `X-Forwarded-For`, which every proxy in front of the server appends
+ * the address it got the request from to. Only the addresses appended by our own proxies can be
+ * trusted, so the client is the address `WEBHOOKY_TRUSTED_PROXIES` entries from the end, one by
+ * default for the load balancer.
+ *
+ * Endpoints that already take three other extractors call [`check`] from their handler instead
+ * of taking a [`RateLimit`].
+ */
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use async_trait::async_trait;
+use dropshot::{ApiEndpointBodyContentType, Extractor, ExtractorMetadata, HttpError, RequestContext, ServerContext};
+use lazy_static::lazy_static;
+use log::warn;
+use schemars::JsonSchema;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+const DEFAULT_LIMIT_PER_TOKEN: u32 = 1200;
+const DEFAULT_LIMIT_PER_IP: u32 = 600;
+const DEFAULT_TRUSTED_PROXIES: u32 = 1;
+
+/// The most buckets a limiter keeps. Past this, the least recently used bucket is dropped to make
+/// room for a new client.
+const MAX_BUCKETS: usize = 10_000;
+
+/// Rejections are counted for this many clients, the ones after that are counted together under
+/// `other`.
+const MAX_LIMITED_CLIENTS: usize = 1_000;
+
+lazy_static! {
+    static ref TOKEN_LIMITER: RateLimiter =
+        RateLimiter::new(limit_from_env("WEBHOOKY_RATE_LIMIT_PER_TOKEN", DEFAULT_LIMIT_PER_TOKEN));
+    static ref IP_LIMITER: RateLimiter =
+        RateLimiter::new(limit_from_env("WEBHOOKY_RATE_LIMIT_PER_IP", DEFAULT_LIMIT_PER_IP));
+    static ref TRUSTED_PROXIES: usize =
+        limit_from_env("WEBHOOKY_TRUSTED_PROXIES", DEFAULT_TRUSTED_PROXIES).max(1) as usize;
+    static ref METRICS: Mutex<RateLimitMetrics> = Mutex::new(RateLimitMetrics::default());
+}
+
+fn limit_from_env(name: &str, default: u32) -> u32 {
+    match std::env::var(name) {
+        Ok(value) => value.trim().parse().unwrap_or_else(|_| {
+            warn!("`{}` is not a number, using {}", name, default);
+            default
+        }),
+        Err(_) => default,
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    /// When the bucket was last used, as a position in [`Buckets::by_use`].
+    used: u64,
+}
+
+#[derive(Debug, Default)]
+struct Buckets {
+    by_client: HashMap<String, Bucket>,
+    /// The clients in the order their buckets were last used, so that the least recently used
+    /// one can be found without going through all of them.
+    by_use: BTreeMap<u64, String>,
+    next_use: u64,
+}
+
+#[derive(Debug)]
+pub struct RateLimiter {
+    per_minute: u32,
+    buckets: Mutex<Buckets>,
+}
+
+impl RateLimiter {
+    pub fn new(per_minute: u32) -> Self {
+        RateLimiter {
+            per_minute,
+            buckets: Mutex::new(Buckets::default()),
+        }
+    }
+
+    /// Take a request out of the client's bucket. Returns false when the bucket is empty.
+    pub fn check(&self, client: &str, now: Instant) -> bool {
+        if self.per_minute == 0 {
+            return true;
+        }
+
+        let capacity = self.per_minute as f64;
+        let mut buckets = self.buckets.lock().unwrap();
+        let Buckets {
+            by_client,
+            by_use,
+            next_use,
+        } = &mut *buckets;
+
+        if !by_client.contains_key(client) && by_client.len() >= MAX_BUCKETS {
+            if let Some((_, oldest)) = by_use.pop_first() {
+                by_client.remove(&oldest);
+            }
+        }
+
+        let used = *next_use;
+        *next_use += 1;
+
+        let bucket = by_client.entry(client.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+            used,
+        });
+        by_use.remove(&bucket.used);
+        bucket.used = used;
+        by_use.insert(used, client.to_string());
+
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * capacity / 60.0).min(capacity);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Counters for the requests that went through the rate limits.
+#[derive(Debug, Default, Clone, JsonSchema, Serialize)]
+pub struct RateLimitMetrics {
+    pub allowed: u64,
+    pub limited: u64,
+    /// The number of rejected requests for each client, keyed by `ip:<address>` or
+    /// `token:<hash of the token>`. Only the first clients that were rejected get their own
+    /// count, the rest are counted under `other`.
+    pub limited_by_client: BTreeMap<String, u64>,
+}
+
+impl RateLimitMetrics {
+    fn record(&mut self, limited_client: Option<&str>) {
+        let client = match limited_client {
+            Some(client) => client,
+            None => {
+                self.allowed += 1;
+                return;
+            }
+        };
+
+        self.limited += 1;
+        let client =
+            if self.limited_by_client.len() < MAX_LIMITED_CLIENTS || self.limited_by_client.contains_key(client) {
+                client
+            } else {
+                "other"
+            };
+        let count = self.limited_by_client.entry(client.to_string()).or_default();
+        *count += 1;
+
+        // Log the first rejection and then every power of ten, rather than every rejected
+        // request of a client that is flooding us.
+        let mut n = *count;
+        while n % 10 == 0 {
+            n /= 10;
+        }
+        if n == 1 {
+            warn!("rate limited {} ({} requests rejected)", client, count);
+        }
+    }
+}
+
+/// The current rate limiting counters.
+pub fn metrics() -> RateLimitMetrics {
+    METRICS.lock().unwrap().clone()
+}
+
+/// The address the request came from, as seen by the outermost of the `trusted_proxies` proxies
+/// in front of the server. The addresses before it in `X-Forwarded-For` are whatever the client
+/// sent, so they are not looked at.
+fn client_ip(headers: &http::HeaderMap, trusted_proxies: usize) -> String {
+    let forwarded = headers
+        .get("X-Forwarded-For")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .split(',')
+        .collect::<Vec<_>>();
+    let ip = forwarded[forwarded.len().saturating_sub(trusted_proxies)].trim();
+
+    if ip.is_empty() {
+        "ip:unknown".to_string()
+    } else {
+        format!("ip:{}", ip)
+    }
+}
+
+/// The token the request carries, either as a bearer token or in the `token` query parameter.
+/// Only a hash of the token is kept, since the key ends up in logs and metrics.
+fn client_token(headers: &http::HeaderMap, query: Option<&str>) -> Option<String> {
+    let bearer = headers
+        .get(http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string());
+
+    let token = bearer.or_else(|| {
+        serde_urlencoded::from_str::<HashMap<String, String>>(query?)
+            .ok()?
+            .remove("token")
+    })?;
+
+    if token.is_empty() {
+        return None;
+    }
+
+    Some(format!(
+        "token:{}",
+        &hex::encode(Sha256::digest(token.as_bytes()))[..16]
+    ))
+}
+
+/// Count the request against the limits of its client, and fail with a `429` when the client is
+/// over either of them.
+pub async fn check<Context: ServerContext>(rqctx: &Arc<RequestContext<Context>>) -> Result<(), HttpError> {
+    let (ip, token) = {
+        let request = rqctx.request.lock().await;
+        (
+            client_ip(request.headers(), *TRUSTED_PROXIES),
+            client_token(request.headers(), request.uri().query()),
+        )
+    };
+
+    // The IP is checked first so that a client over its IP limit cannot add token buckets.
+    let now = Instant::now();
+    let limited_client = match token {
+        _ if !IP_LIMITER.check(&ip, now) => Some(ip),
+        Some(token) if !TOKEN_LIMITER.check(&token, now) => Some(token),
+        _ => None,
+    };
+
+    METRICS.lock().unwrap().record(limited_client.as_deref());
+
+    if limited_client.is_some() {
+        return Err(HttpError::for_client_error(
+            None,
+            http::StatusCode::TOO_MANY_REQUESTS,
+            "rate limit exceeded".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Extracting a [`RateLimit`] runs [`check`] on the request.
+pub struct RateLimit;
+
+#[async_trait]
+impl Extractor for RateLimit {
+    async fn from_request<Context: ServerContext>(rqctx: Arc<RequestContext<Context>>) -> Result<RateLimit, HttpError> {
+        check(&rqctx).await?;
+
+        Ok(RateLimit)
+    }
+
+    fn metadata(_body_content_type: ApiEndpointBodyContentType) -> ExtractorMetadata {
+        ExtractorMetadata {
+            paginated: false,
+            parameters: vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{client_ip, client_token, RateLimitMetrics, RateLimiter, MAX_BUCKETS, MAX_LIMITED_CLIENTS};
+
+    #[test]
+    fn test_rate_limiter_refills() {
+        let limiter = RateLimiter::new(60);
+        let start = Instant::now();
+
+        for _ in 0..60 {
+            assert!(limiter.check("ip:10.0.0.1", start));
+        }
+        assert!(!limiter.check("ip:10.0.0.1", start));
+        // Other clients have their own bucket.
+        assert!(limiter.check("ip:10.0.0.2", start));
+
+        // The bucket refills at a request per second.
+        assert!(limiter.check("ip:10.0.0.1", start + Duration::from_secs(1)));
+        assert!(!limiter.check("ip:10.0.0.1", start + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn test_rate_limiter_drops_least_recently_used() {
+        let limiter = RateLimiter::new(1);
+        let now = Instant::now();
+
+        assert!(limiter.check("ip:10.0.0.1", now));
+        for i in 0..MAX_BUCKETS - 1 {
+            assert!(limiter.check(&format!("ip:10.1.{}.{}", i / 256, i % 256), now));
+        }
+        // Using the first bucket again makes the second one the least recently used.
+        assert!(!limiter.check("ip:10.0.0.1", now));
+        assert!(limiter.check("ip:10.2.0.1", now));
+
+        let buckets = limiter.buckets.lock().unwrap();
+        assert_eq!(MAX_BUCKETS, buckets.by_client.len());
+        assert_eq!(MAX_BUCKETS, buckets.by_use.len());
+        assert!(buckets.by_client.contains_key("ip:10.0.0.1"));
+        assert!(!buckets.by_client.contains_key("ip:10.1.0.0"));
+    }
+
+    #[test]
+    fn test_disabled_rate_limiter() {
+        let limiter = RateLimiter::new(0);
+        let now = Instant::now();
+
+        for _ in 0..1000 {
+            assert!(limiter.check("ip:10.0.0.1", now));
+        }
+    }
+
+    #[test]
+    fn test_client_keys() {
+        let mut headers = http::HeaderMap::new();
+        assert_eq!("ip:unknown", client_ip(&headers, 1));
+        assert!(client_token(&headers, None).is_none());
+
+        // The first address is made up by the client, the load balancer appended the last one.
+        headers.insert("X-Forwarded-For", "198.51.100.1, 203.0.113.7".parse().unwrap());
+        assert_eq!("ip:203.0.113.7", client_ip(&headers, 1));
+        assert_eq!("ip:198.51.100.1", client_ip(&headers, 2));
+        assert_eq!("ip:198.51.100.1", client_ip(&headers, 3));
+
+        let from_query = client_token(&headers, Some("token=secret&other=1")).unwrap();
+        assert!(from_query.starts_with("token:"));
+        assert!(!from_query.contains("secret"));
+
+        headers.insert(http::header::AUTHORIZATION, "Bearer secret".parse().unwrap());
+        assert_eq!(Some(from_query), client_token(&headers, None));
+    }
+
+    #[test]
+    fn test_metrics() {
+        let mut metrics = RateLimitMetrics::default();
+        metrics.record(None);
+        metrics.record(Some("ip:10.0.0.1"));
+        metrics.record(Some("ip:10.0.0.1"));
+
+        assert_eq!(1, metrics.allowed);
+        assert_eq!(2, metrics.limited);
+        assert_eq!(2, metrics.limited_by_client["ip:10.0.0.1"]);
+
+        for i in 0..MAX_LIMITED_CLIENTS + 10 {
+            metrics.record(Some(&format!("ip:10.1.{}.{}", i / 256, i % 256)));
+        }
+        metrics.record(Some("ip:10.0.0.1"));
+        assert_eq!(MAX_LIMITED_CLIENTS + 1, metrics.limited_by_client.len());
+        assert_eq!(11, metrics.limited_by_client["other"]);
+        assert_eq!(3, metrics.limited_by_client["ip:10.0.0.1"]);
+    }
+}
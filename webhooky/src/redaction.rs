@@ -0,0 +1,141 @@
+/*!
+ * Applying the redactor from [`cio_api::redaction`] to what the server logs and sends to Sentry.
+ */
+use std::{collections::BTreeMap, fmt};
+
+use cio_api::redaction::{redactor, RedactionTarget, Redactor, REDACTED};
+use sentry::protocol::Event;
+use slog::{BorrowedKV, Drain, Key, OwnedKVList, Record, RecordStatic, Serializer, KV};
+
+/// A drain that redacts the message and the values of records before passing them on.
+pub struct RedactingDrain<D> {
+    drain: D,
+}
+
+impl<D> RedactingDrain<D> {
+    pub fn new(drain: D) -> Self {
+        RedactingDrain { drain }
+    }
+}
+
+impl<D: Drain> Drain for RedactingDrain<D> {
+    type Ok = D::Ok;
+    type Err = D::Err;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> Result<Self::Ok, Self::Err> {
+        let redactor = redactor();
+
+        let message = redactor
+            .redact_str(RedactionTarget::Logs, &record.msg().to_string())
+            .into_owned();
+
+        let mut kv = RedactedKV {
+            redactor: &redactor,
+            values: vec![],
+        };
+        // Collecting the values only fails if formatting one of them does.
+        let _ = record.kv().serialize(record, &mut kv);
+
+        let record_static = RecordStatic {
+            location: record.location(),
+            tag: record.tag(),
+            level: record.level(),
+        };
+
+        self.drain.log(
+            &Record::new(&record_static, &format_args!("{}", message), BorrowedKV(&kv)),
+            values,
+        )
+    }
+
+    fn is_enabled(&self, level: slog::Level) -> bool {
+        self.drain.is_enabled(level)
+    }
+}
+
+/// The values of a record, redacted as they are collected.
+struct RedactedKV<'a> {
+    redactor: &'a Redactor,
+    values: Vec<(Key, String)>,
+}
+
+impl Serializer for RedactedKV<'_> {
+    fn emit_arguments(&mut self, key: Key, value: &fmt::Arguments) -> slog::Result {
+        let value = if self.redactor.is_redacted_field(RedactionTarget::Logs, key) {
+            REDACTED.to_string()
+        } else {
+            self.redactor
+                .redact_str(RedactionTarget::Logs, &value.to_string())
+                .into_owned()
+        };
+
+        self.values.push((key, value));
+        Ok(())
+    }
+}
+
+impl KV for RedactedKV<'_> {
+    fn serialize(&self, _record: &Record, serializer: &mut dyn Serializer) -> slog::Result {
+        for (key, value) in &self.values {
+            serializer.emit_str(*key, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Redact the parts of a Sentry event that carry data from the request or the error, ie. the
+/// messages of exceptions and breadcrumbs.
+pub fn redact_sentry_event(mut event: Event<'static>) -> Event<'static> {
+    let redactor = redactor();
+    let target = RedactionTarget::Sentry;
+
+    let redact = |text: &mut String| *text = redactor.redact_str(target, text).into_owned();
+    let redact_map = |map: &mut BTreeMap<String, serde_json::Value>| {
+        for (field, value) in map.iter_mut() {
+            redactor.redact_field(target, field, value);
+        }
+    };
+
+    if let Some(message) = &mut event.message {
+        redact(message);
+    }
+    if let Some(logentry) = &mut event.logentry {
+        redact(&mut logentry.message);
+        for param in &mut logentry.params {
+            redactor.redact_json(target, param);
+        }
+    }
+    for exception in &mut event.exception.values {
+        if let Some(value) = &mut exception.value {
+            redact(value);
+        }
+    }
+    for breadcrumb in &mut event.breadcrumbs.values {
+        if let Some(message) = &mut breadcrumb.message {
+            redact(message);
+        }
+        redact_map(&mut breadcrumb.data);
+    }
+    for value in event.tags.values_mut() {
+        redact(value);
+    }
+    redact_map(&mut event.extra);
+
+    if let Some(user) = &mut event.user {
+        if let Some(email) = &mut user.email {
+            redact(email);
+        }
+        redact_map(&mut user.other);
+    }
+    if let Some(request) = &mut event.request {
+        for text in [&mut request.data, &mut request.query_string].into_iter().flatten() {
+            redact(text);
+        }
+        for value in request.headers.values_mut() {
+            redact(value);
+        }
+    }
+
+    event
+}
@@ -1,18 +1,125 @@
+use std::collections::HashMap;
 use std::io;
 use std::sync::Arc;
 use std::sync::Mutex;
 
 use anyhow::Result;
 use cio_api::{
+    correlation::{self, CorrelationId},
     db::Database,
     functions::{FnOutput, Function},
+    progress::{self, ProgressEvent, ProgressReporter},
 };
+use lazy_static::lazy_static;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use slog::Drain;
 use slog_scope_futures::FutureExt as _;
+use tokio::sync::{broadcast, oneshot};
 
 use crate::health::SelfMemory;
 
+lazy_static! {
+    /// The jobs that are running in this process, keyed by their saga id.
+    static ref RUNNING_JOBS: Mutex<HashMap<uuid::Uuid, RunningJob>> = Mutex::new(HashMap::new());
+}
+
+struct RunningJob {
+    output: Arc<Mutex<Vec<u8>>>,
+    progress: ProgressReporter,
+    cancel: Option<oneshot::Sender<()>>,
+}
+
+/// Removes a job from the running jobs when its action finishes, however it finishes.
+struct RunningJobGuard(uuid::Uuid);
+
+impl RunningJobGuard {
+    fn register(
+        saga_id: uuid::Uuid,
+        output: Arc<Mutex<Vec<u8>>>,
+        progress: ProgressReporter,
+    ) -> (Self, oneshot::Receiver<()>) {
+        let (cancel, cancelled) = oneshot::channel();
+        RUNNING_JOBS.lock().unwrap().insert(
+            saga_id,
+            RunningJob {
+                output,
+                progress,
+                cancel: Some(cancel),
+            },
+        );
+
+        (RunningJobGuard(saga_id), cancelled)
+    }
+}
+
+impl Drop for RunningJobGuard {
+    fn drop(&mut self) {
+        RUNNING_JOBS.lock().unwrap().remove(&self.0);
+    }
+}
+
+/// How far along a job that is running in this process is, going by what it has logged.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Serialize)]
+pub struct JobProgress {
+    pub log_lines: usize,
+    pub last_message: String,
+}
+
+impl JobProgress {
+    fn from_output(output: &[u8]) -> Self {
+        #[derive(Deserialize)]
+        struct Line {
+            msg: String,
+        }
+
+        let output = String::from_utf8_lossy(output);
+        let lines = output.lines().filter(|line| !line.is_empty()).collect::<Vec<_>>();
+
+        JobProgress {
+            log_lines: lines.len(),
+            last_message: lines
+                .last()
+                .and_then(|line| serde_json::from_str::<Line>(line).ok())
+                .map(|line| line.msg)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// The progress of a job, if it is running in this process.
+pub fn job_progress(saga_id: &uuid::Uuid) -> Option<JobProgress> {
+    let jobs = RUNNING_JOBS.lock().unwrap();
+    let output = jobs.get(saga_id)?.output.lock().unwrap();
+
+    Some(JobProgress::from_output(&output))
+}
+
+/// Watch the progress events of a job that is running in this process. Returns the events it
+/// has reported so far, and a channel of the ones it reports from now on, which closes when the
+/// job finishes.
+pub fn subscribe_job_progress(
+    saga_id: &uuid::Uuid,
+) -> Option<(Vec<ProgressEvent>, broadcast::Receiver<ProgressEvent>)> {
+    Some(RUNNING_JOBS.lock().unwrap().get(saga_id)?.progress.subscribe())
+}
+
+/// Cancel a job that is running in this process. The job stops at the next point it waits on
+/// something, so work it has already done is not undone. Returns false if the job is not
+/// running here, or has already been cancelled.
+pub fn cancel_job(saga_id: &uuid::Uuid) -> bool {
+    let cancel = RUNNING_JOBS
+        .lock()
+        .unwrap()
+        .get_mut(saga_id)
+        .and_then(|job| job.cancel.take());
+
+    match cancel {
+        Some(cancel) => cancel.send(()).is_ok(),
+        None => false,
+    }
+}
+
 #[derive(Debug, Clone)]
 struct SagaLogOutput {
     output: Arc<Mutex<Vec<u8>>>,
@@ -43,7 +150,16 @@ impl io::Write for SagaLogOutput {
     }
 }
 
-fn create_saga_logger<W>(out: W, cmd_name: String, saga_id: String) -> slog::Logger
+fn read_output(handle: &Mutex<Vec<u8>>) -> String {
+    if let Ok(guard) = handle.lock() {
+        std::str::from_utf8(&guard).ok().map(|s| s.to_string())
+    } else {
+        None
+    }
+    .unwrap_or_default()
+}
+
+fn create_saga_logger<W>(out: W, cmd_name: String, saga_id: String, correlation_id: String) -> slog::Logger
 where
     W: io::Write + Send + Sync + 'static,
 {
@@ -61,7 +177,11 @@ where
     .fuse();
 
     let drain = sentry::integrations::slog::SentryDrain::new(drain);
-    slog::Logger::root(drain, slog::slog_o!("cmd" => cmd_name, "saga_id" => saga_id))
+    let drain = crate::redaction::RedactingDrain::new(drain);
+    slog::Logger::root(
+        drain,
+        slog::slog_o!("cmd" => cmd_name, "saga_id" => saga_id, "correlation_id" => correlation_id),
+    )
 }
 
 /// Define our saga for syncing repos.
@@ -72,6 +192,9 @@ pub struct Saga;
 pub struct Params {
     cmd_name: String,
     saga_id: uuid::Uuid,
+    /// Sagas created before they carried a correlation id get one of their own when resumed.
+    #[serde(default)]
+    correlation_id: CorrelationId,
 }
 
 #[derive(Debug)]
@@ -99,11 +222,13 @@ pub async fn do_saga(
     id: &uuid::Uuid,
     template: steno::SagaTemplate<Saga>,
     cmd_name: &str,
+    correlation_id: &CorrelationId,
 ) -> Result<()> {
     let context = Arc::new(Context { db: db.clone() });
     let params = Params {
         cmd_name: cmd_name.to_string(),
         saga_id: *id,
+        correlation_id: correlation_id.clone(),
     };
 
     let saga_template = Arc::new(template);
@@ -120,7 +245,13 @@ pub async fn do_saga(
     Ok(())
 }
 
-pub async fn run_cmd(db: &Database, sec: &steno::SecClient, id: &uuid::Uuid, cmd_name: &str) -> Result<()> {
+pub async fn run_cmd(
+    db: &Database,
+    sec: &steno::SecClient,
+    id: &uuid::Uuid,
+    cmd_name: &str,
+    correlation_id: &CorrelationId,
+) -> Result<()> {
     let mut builder = steno::SagaTemplateBuilder::new();
     builder.append(
         // name of this action's output (can be used in subsequent actions)
@@ -135,13 +266,14 @@ pub async fn run_cmd(db: &Database, sec: &steno::SecClient, id: &uuid::Uuid, cmd
         ),
     );
 
-    do_saga(db, sec, id, builder.build(), cmd_name).await
+    do_saga(db, sec, id, builder.build(), cmd_name, correlation_id).await
 }
 
 async fn action_run_cmd(action_context: steno::ActionContext<Saga>) -> Result<FnOutput, steno::ActionError> {
     let db = &action_context.user_data().db;
     let cmd_name = &action_context.saga_params().cmd_name;
     let saga_id = &action_context.saga_params().saga_id;
+    let correlation_id = &action_context.saga_params().correlation_id;
 
     if let Some(sub_cmd) = crate::core::into_job_command(cmd_name) {
         if let Ok(mem) = SelfMemory::new() {
@@ -150,38 +282,59 @@ async fn action_run_cmd(action_context: steno::ActionContext<Saga>) -> Result<Fn
 
         let saga_log_output = SagaLogOutput::new();
         let output_handle = saga_log_output.handle();
-        let logger = create_saga_logger(saga_log_output, cmd_name.to_string(), saga_id.to_string());
+        let logger = create_saga_logger(
+            saga_log_output,
+            cmd_name.to_string(),
+            saga_id.to_string(),
+            correlation_id.to_string(),
+        );
+        let reporter = ProgressReporter::new();
+        let (_running, cancelled) = RunningJobGuard::register(*saga_id, output_handle.clone(), reporter.clone());
 
         let context = crate::context::Context::new(1).await.map_err(AsActionError)?;
-        let result = crate::job::run_job_cmd(sub_cmd, context).with_logger(logger).await;
+        let job = progress::scope(
+            reporter,
+            correlation::scope(
+                correlation_id.clone(),
+                crate::job::run_job_cmd(sub_cmd, context).with_logger(logger),
+            ),
+        );
+
+        // Dropping the job is what stops it when it is cancelled.
+        let result = tokio::select! {
+            result = job => Some(result),
+            Ok(()) = cancelled => None,
+        };
 
         if let Ok(mem) = SelfMemory::new() {
             log::info!("Memory after running {}({}): {:?}", cmd_name, saga_id, mem);
         }
 
         match result {
-            Ok(_) => {
-                let output = {
-                    if let Ok(guard) = output_handle.lock() {
-                        std::str::from_utf8(&guard).ok().map(|s| s.to_string())
-                    } else {
-                        None
-                    }
-                    .unwrap_or_default()
-                };
+            Some(Ok(_)) => {
+                let output = read_output(&output_handle);
 
                 Function::add_logs_with_conclusion(db, saga_id, &output, &octorust::types::Conclusion::Success)
                     .await
                     .map_err(AsActionError)?;
                 Ok(FnOutput(output))
             }
-            Err(err) => {
+            Some(Err(err)) => {
                 let output = format!("{:?}", err);
                 Function::add_logs_with_conclusion(db, saga_id, &output, &octorust::types::Conclusion::Failure)
                     .await
                     .map_err(AsActionError)?;
                 Err(AsActionError(err).into())
             }
+            None => {
+                log::info!("Cancelled {}({})", cmd_name, saga_id);
+
+                let output = format!("{}\n\nCancelled", read_output(&output_handle));
+                Function::add_logs_with_conclusion(db, saga_id, output.trim(), &octorust::types::Conclusion::Cancelled)
+                    .await
+                    .map_err(AsActionError)?;
+                Err(steno::ActionError::action_failed(format!("{} was cancelled", cmd_name)))
+            }
         }
     } else {
         Err(steno::ActionError::action_failed(format!(
@@ -212,11 +365,61 @@ mod tests {
         assert_eq!(vec![1, 2, 3], output.handle().lock().unwrap().clone());
     }
 
+    #[test]
+    fn test_job_progress_from_output() {
+        assert_eq!(JobProgress::default(), JobProgress::from_output(&[]));
+
+        let output = b"{\"msg\":\"first\",\"level\":\"INFO\"}\n{\"msg\":\"second\",\"level\":\"INFO\"}\n";
+        assert_eq!(
+            JobProgress {
+                log_lines: 2,
+                last_message: "second".to_string(),
+            },
+            JobProgress::from_output(output)
+        );
+    }
+
+    #[test]
+    fn test_cancel_job() {
+        let saga_id = uuid::Uuid::new_v4();
+        assert!(!cancel_job(&saga_id));
+
+        let (running, mut cancelled) =
+            RunningJobGuard::register(saga_id, SagaLogOutput::new().handle(), ProgressReporter::new());
+        assert!(job_progress(&saga_id).is_some());
+        assert!(cancel_job(&saga_id));
+        assert!(cancelled.try_recv().is_ok());
+
+        // A job can only be cancelled once, and is forgotten once its action finishes.
+        assert!(!cancel_job(&saga_id));
+        drop(running);
+        assert!(job_progress(&saga_id).is_none());
+    }
+
+    #[test]
+    fn test_subscribe_job_progress() {
+        let saga_id = uuid::Uuid::new_v4();
+        assert!(subscribe_job_progress(&saga_id).is_none());
+
+        let (running, _) = RunningJobGuard::register(saga_id, SagaLogOutput::new().handle(), ProgressReporter::new());
+        let (history, mut events) = subscribe_job_progress(&saga_id).unwrap();
+        assert!(history.is_empty());
+
+        // Watchers see the job end when its action finishes.
+        drop(running);
+        assert!(matches!(events.try_recv(), Err(broadcast::error::TryRecvError::Closed)));
+    }
+
     #[test]
     fn test_saga_logger_output() {
         let output = SagaLogOutput::new();
         let handle = output.handle();
-        let logger = create_saga_logger(output.clone(), "test_cmd".to_string(), "not-a-real-uuid".to_string());
+        let logger = create_saga_logger(
+            output.clone(),
+            "test_cmd".to_string(),
+            "not-a-real-uuid".to_string(),
+            "test-correlation-id".to_string(),
+        );
         slog::info!(&logger, "First message that should be available from the handle");
         slog::info!(&logger, "Second message that should be available from the handle");
 
@@ -231,6 +434,7 @@ mod tests {
             msg: String,
             cmd: String,
             saga_id: String,
+            correlation_id: String,
         }
 
         let lines = records
@@ -245,6 +449,7 @@ mod tests {
         assert_eq!("First message that should be available from the handle", lines[0].msg);
         assert_eq!("test_cmd", lines[0].cmd);
         assert_eq!("not-a-real-uuid", lines[0].saga_id);
+        assert_eq!("test-correlation-id", lines[0].correlation_id);
 
         assert_eq!("Second message that should be available from the handle", lines[1].msg);
         assert_eq!("test_cmd", lines[1].cmd);
@@ -2,19 +2,39 @@
 use std::{collections::HashMap, env, pin::Pin, sync::Arc};
 
 use anyhow::{anyhow, Result};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Duration, Utc};
 use cio_api::{
     analytics::NewPageView,
+    applicant_duplicates::ApplicantMerge,
+    asset_assignments::{AssetAssignment, AssetCheckedOut, CheckInRequest, CheckOutRequest},
+    companies::NewCompany,
+    correlation::{self, CorrelationId, CORRELATION_ID_HEADER},
+    data_subjects::DataSubjectErasure,
+    device_credentials::DownloadRejection,
+    directory::{DirectoryProfile, DirectoryQuery},
+    email::SendGridEvent,
+    failures::FailureFilter,
     functions::Function,
+    github_event_routes::{GitHubEventRoute, NewGitHubEventRoute},
+    interview_scheduling::{BookingRejection, InterviewPanel, InterviewSchedule},
+    notifications::{NewNotificationRule, NotificationRule},
+    provisioning::ProvisioningReport,
+    recruiting_funnel::{RecruitingFunnel, RecruitingFunnelQuery},
     rfd::{RFDEntry, RFDIndexEntry},
+    scheduled_jobs::claim_scheduled_run,
+    shipment_changes::{AddressCorrection, LabelReprint, OutboundShipmentEvent},
+    shipments::OutboundShipment,
+    swag_preferences::{SwagPreferenceRejection, SwagPreferences, SwagPreferencesForm, SwagRecipient},
+    swag_stock::{OutOfStock, StockReceipt, SwagStockLevel},
     swag_store::Order,
+    webhook_outbox::{NewWebhookConsumer, RegisteredWebhookConsumer, WebhookConsumer, WebhookDelivery},
 };
 use clokwerk::{AsyncScheduler, Job, TimeUnits};
 use docusign::DocuSign;
 use dropshot::{
     endpoint, ApiDescription, ConfigDropshot, ConfigLogging, ConfigLoggingLevel, HttpError, HttpResponseAccepted,
     HttpResponseHeaders, HttpResponseOk, HttpServerStarter, OpenApiDefinition, PaginationOrder, PaginationParams, Path,
-    Query, RequestContext, ResultsPage, TypedBody, WhichPage,
+    Query, RequestContext, ResultsPage, TypedBody, UntypedBody, WhichPage,
 };
 use dropshot_verify_request::{
     bearer::{Bearer, BearerToken},
@@ -24,6 +44,7 @@ use dropshot_verify_request::{
 use google_drive::Client as GoogleDrive;
 use gusto_api::Client as Gusto;
 use http::{header::HeaderValue, StatusCode};
+use lazy_static::lazy_static;
 use log::{info, warn};
 use quickbooks::QuickBooks;
 use schemars::JsonSchema;
@@ -37,11 +58,22 @@ use slack_chat_api::{BotCommand, Slack};
 use zoom_api::Client as Zoom;
 
 use crate::{
-    auth::{AirtableToken, HiringToken, InternalToken, RFDToken, ShippoToken},
+    auth::{
+        AirtableToken, CareersInboxToken, HiringToken, InternalAuth, InternalToken, MailChimpToken, RFDToken,
+        SendGridToken, ShippoToken,
+    },
     context::ServerContext,
+    event_queue::{enqueue, EventSource},
     github_types::GitHubWebhook,
-    handlers_hiring::{ApplicantInfo, ApplicantUploadToken},
+    handlers_admin::{
+        AdminJob, AdminRunRequest, FailedJob, FailedWebhookEvent, FailureQuery, RequeueResult, WebhookDeliveryQuery,
+    },
+    handlers_hiring::{
+        AdverseActionRequest, ApplicantInfo, ApplicantMergeRequest, ApplicantUploadToken, BookInterviewRequest,
+        CorrectOfferRequest, InterviewProposal, VoidOfferRequest,
+    },
     handlers_slack::InteractiveEvent,
+    rate_limit::{self, RateLimit, RateLimitMetrics},
 };
 
 pub struct APIConfig {
@@ -74,12 +106,18 @@ fn create_api() -> ApiDescription<ServerContext> {
      */
     api.register(ping).unwrap();
     api.register(github_rate_limit).unwrap();
+    api.register(rate_limits).unwrap();
     api.register(listen_airtable_applicants_request_background_check_webhooks)
         .unwrap();
     api.register(listen_airtable_applicants_update_webhooks).unwrap();
     api.register(listen_airtable_applicants_recreate_piia_webhooks).unwrap();
     api.register(listen_airtable_assets_items_print_barcode_label_webhooks)
         .unwrap();
+    api.register(listen_assets_check_out).unwrap();
+    api.register(listen_assets_check_in).unwrap();
+    api.register(listen_assets_assignment_history).unwrap();
+    api.register(listen_device_credential_confirmation).unwrap();
+    api.register(listen_device_credential_download).unwrap();
     api.register(listen_airtable_employees_print_home_address_label_webhooks)
         .unwrap();
     api.register(listen_airtable_certificates_renew_webhooks).unwrap();
@@ -107,7 +145,26 @@ fn create_api() -> ApiDescription<ServerContext> {
     api.register(listen_application_files_upload_requests_cors).unwrap();
     api.register(listen_application_files_upload_requests).unwrap();
     api.register(listen_applicant_info).unwrap();
+    api.register(listen_test_application_files_multipart_upload_requests_cors)
+        .unwrap();
+    api.register(listen_test_application_files_multipart_upload_requests)
+        .unwrap();
+    api.register(listen_application_files_multipart_upload_requests_cors)
+        .unwrap();
+    api.register(listen_application_files_multipart_upload_requests)
+        .unwrap();
     api.register(listen_applicant_upload_token).unwrap();
+    api.register(listen_careers_inbound_email).unwrap();
+    api.register(trigger_applicant_offer_void).unwrap();
+    api.register(trigger_applicant_offer_resend).unwrap();
+    api.register(trigger_applicant_offer_correct).unwrap();
+    api.register(trigger_applicant_adverse_action).unwrap();
+    api.register(trigger_applicant_adverse_action_cancel).unwrap();
+    api.register(trigger_applicant_merge).unwrap();
+    api.register(trigger_interview_times_propose).unwrap();
+    api.register(listen_interview_proposal).unwrap();
+    api.register(listen_interview_book_requests).unwrap();
+    api.register(listen_recruiting_funnel).unwrap();
 
     api.register(listen_auth_docusign_callback).unwrap();
     api.register(listen_auth_docusign_consent).unwrap();
@@ -125,34 +182,84 @@ fn create_api() -> ApiDescription<ServerContext> {
     api.register(listen_auth_slack_consent).unwrap();
     api.register(listen_auth_quickbooks_callback).unwrap();
     api.register(listen_auth_quickbooks_consent).unwrap();
+    api.register(listen_quickbooks_webhooks).unwrap();
+    api.register(listen_mailchimp_mailing_list_webhooks).unwrap();
+    api.register(ping_mailchimp_mailing_list_webhooks).unwrap();
+    api.register(listen_mailerlite_mailing_list_webhooks).unwrap();
     api.register(listen_checkr_background_update_webhooks).unwrap();
+    api.register(listen_background_checks_export).unwrap();
+    api.register(listen_data_subject_export).unwrap();
+    api.register(trigger_data_subject_erasure).unwrap();
+    api.register(trigger_company_provision).unwrap();
+    api.register(listen_directory).unwrap();
+    api.register(listen_directory_profile).unwrap();
     api.register(listen_docusign_envelope_update_webhooks).unwrap();
+    api.register(listen_docusign_connect_webhooks).unwrap();
+    api.register(trigger_docusign_policy_acknowledgments_send).unwrap();
     api.register(listen_github_webhooks).unwrap();
     api.register(listen_products_sold_count_requests).unwrap();
     api.register(listen_shippo_tracking_update_webhooks).unwrap();
     api.register(listen_easypost_tracking_update_webhooks).unwrap();
+    api.register(listen_sendgrid_events_webhooks).unwrap();
     api.register(listen_slack_commands_webhooks).unwrap();
     api.register(listen_slack_interactive_webhooks).unwrap();
     api.register(listen_shipbob_webhooks).unwrap();
     api.register(listen_store_order_create).unwrap();
+    api.register(listen_store_inventory_receive).unwrap();
+    api.register(trigger_swag_preferences_request).unwrap();
+    api.register(listen_swag_preferences_form).unwrap();
+    api.register(listen_swag_preferences_submit).unwrap();
+    api.register(listen_stripe_webhooks).unwrap();
     api.register(listen_rfd_index).unwrap();
     api.register(listen_rfd_view).unwrap();
     api.register(trigger_rfd_update_by_number).unwrap();
     api.register(trigger_cleanup_create).unwrap();
 
+    api.register(admin_run_job).unwrap();
+    api.register(admin_list_jobs).unwrap();
+    api.register(admin_cancel_job).unwrap();
+    api.register(admin_job_events).unwrap();
+    api.register(admin_list_failed_webhooks).unwrap();
+    api.register(admin_requeue_failed_webhooks).unwrap();
+    api.register(admin_list_failed_jobs).unwrap();
+    api.register(admin_requeue_failed_jobs).unwrap();
+    api.register(admin_list_github_routes).unwrap();
+    api.register(admin_create_github_route).unwrap();
+    api.register(admin_update_github_route).unwrap();
+    api.register(admin_delete_github_route).unwrap();
+    api.register(admin_list_notification_rules).unwrap();
+    api.register(admin_create_notification_rule).unwrap();
+    api.register(admin_update_notification_rule).unwrap();
+    api.register(admin_delete_notification_rule).unwrap();
+    api.register(admin_list_webhook_consumers).unwrap();
+    api.register(admin_register_webhook_consumer).unwrap();
+    api.register(admin_delete_webhook_consumer).unwrap();
+    api.register(admin_list_webhook_deliveries).unwrap();
+    api.register(admin_redeliver_webhook_delivery).unwrap();
+    api.register(admin_reprint_shipment_label).unwrap();
+    api.register(admin_correct_shipment_address).unwrap();
+    api.register(admin_list_shipment_history).unwrap();
     api.register(trigger_sync_analytics_create).unwrap();
     api.register(trigger_sync_api_tokens_create).unwrap();
     api.register(trigger_sync_applications_create).unwrap();
+    api.register(trigger_sync_applicant_duplicates_create).unwrap();
     api.register(trigger_sync_asset_inventory_create).unwrap();
     api.register(trigger_sync_companies_create).unwrap();
     api.register(trigger_sync_configs_create).unwrap();
     api.register(trigger_sync_finance_create).unwrap();
     api.register(trigger_sync_functions_create).unwrap();
+    api.register(trigger_sync_github_teams_create).unwrap();
+    api.register(trigger_sync_google_groups_create).unwrap();
+    api.register(trigger_sync_gusto_create).unwrap();
     api.register(trigger_sync_huddles_create).unwrap();
     api.register(trigger_sync_interviews_create).unwrap();
     api.register(trigger_sync_journal_clubs_create).unwrap();
     api.register(trigger_sync_mailing_lists_create).unwrap();
+    api.register(trigger_sync_offboarding_create).unwrap();
+    api.register(trigger_sync_onboarding_create).unwrap();
+    api.register(trigger_sync_org_chart_create).unwrap();
     api.register(trigger_sync_other_create).unwrap();
+    api.register(trigger_sync_pagerduty_create).unwrap();
     api.register(trigger_sync_recorded_meetings_create).unwrap();
     api.register(trigger_sync_repos_create).unwrap();
     api.register(trigger_sync_rfds_create).unwrap();
@@ -189,7 +296,8 @@ pub async fn create_server(
      */
     let config_dropshot = ConfigDropshot {
         bind_address: s.address.parse()?,
-        request_body_max_bytes: 107374182400, // 100 Gigiabytes.
+        // Uploads of files are streamed and have limits of their own.
+        request_body_max_bytes: crate::uploads::request_body_max_bytes(),
         tls: None,
     };
 
@@ -221,6 +329,9 @@ pub async fn server(
 ) -> Result<()> {
     let server = create_server(&s, api, server_context.clone(), debug).await?;
 
+    // Process the webhook events that endpoints queue up instead of handling inline.
+    crate::event_queue::start_workers(&server_context);
+
     // This really only applied for when we are running with `do-cron` but we need the variable
     // for the scheduler to be in the top level so we can run as async later based on the options.
     let mut scheduler = AsyncScheduler::with_tz(chrono_tz::US::Pacific);
@@ -229,67 +340,51 @@ pub async fn server(
     if s.do_cron {
         /*
          * Setup our cron jobs, with our timezone.
+         *
+         * Every replica that runs with `do-cron` schedules every job, and each run is claimed
+         * in the database first so that only one replica runs it.
          */
-        scheduler
-            .every(1.day())
-            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-analytics")});
-        scheduler
-            .every(23.hours())
-            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-api-tokens")});
-        scheduler
-            .every(7.hours())
-            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-applications")});
-        scheduler
-            .every(2.hours())
-            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-asset-inventory")});
-        scheduler
-            .every(12.hours())
-            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-companies")});
-        scheduler
-            .every(1.hours())
-            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-configs")});
-        scheduler
-            .every(6.hours())
-            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-finance")});
-        scheduler
-            .every(12.hours())
-            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-functions")});
-        scheduler
-            .every(1.hours())
-            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-huddles")});
-        scheduler
-            .every(4.hours())
-            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-interviews")});
-        scheduler
-            .every(12.hours())
-            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-journal-clubs")});
-        scheduler
-            .every(9.hours())
-            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-mailing-lists")});
-        scheduler
-            .every(18.hours())
-            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-other")});
-        scheduler.every(3.hours()).run(
-            enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-recorded-meetings")},
-        );
-        scheduler
-            .every(16.hours())
-            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-repos")});
-        scheduler
-            .every(14.hours())
-            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-rfds")});
-        scheduler
-            .every(2.hours())
-            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-shipments")});
-        scheduler
-            .every(3.hours())
-            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-shorturls")});
-        scheduler
-            .every(9.hours())
-            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-swag-inventory")});
-        scheduler
-            .every(5.hours())
-            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-travel")});
+        for (every, job) in [
+            (Duration::days(1), "sync-analytics"),
+            (Duration::hours(23), "sync-api-tokens"),
+            (Duration::hours(6), "sync-applicant-duplicates"),
+            (Duration::hours(7), "sync-applications"),
+            (Duration::hours(2), "sync-asset-inventory"),
+            (Duration::hours(12), "sync-companies"),
+            (Duration::hours(1), "sync-configs"),
+            (Duration::hours(6), "sync-finance"),
+            (Duration::hours(12), "sync-functions"),
+            (Duration::hours(12), "sync-github-repo-settings"),
+            (Duration::hours(6), "sync-github-teams"),
+            (Duration::hours(6), "sync-google-groups"),
+            (Duration::hours(4), "sync-gusto"),
+            (Duration::hours(1), "sync-huddles"),
+            (Duration::hours(4), "sync-interviews"),
+            (Duration::hours(12), "sync-journal-clubs"),
+            (Duration::hours(9), "sync-mailing-lists"),
+            (Duration::hours(6), "sync-offboarding"),
+            (Duration::hours(1), "sync-onboarding"),
+            (Duration::hours(6), "sync-org-chart"),
+            (Duration::hours(18), "sync-other"),
+            (Duration::minutes(15), "sync-pagerduty"),
+            (Duration::hours(3), "sync-recorded-meetings"),
+            (Duration::hours(16), "sync-repos"),
+            (Duration::hours(14), "sync-rfds"),
+            (Duration::hours(2), "sync-shipments"),
+            (Duration::hours(3), "sync-shorturls"),
+            (Duration::hours(9), "sync-swag-inventory"),
+            (Duration::hours(5), "sync-travel"),
+            (Duration::days(1), "rotate-tailscale-keys"),
+            (Duration::days(1), "renew-certificates"),
+            (Duration::hours(1), "revoke-expired-access"),
+            (Duration::days(1), "send-rfd-review-reminders"),
+            (Duration::hours(1), "send-celebrations"),
+            (Duration::hours(1), "send-interviewer-packets"),
+            (Duration::minutes(15), "send-scheduled-messages"),
+            (Duration::minutes(5), "send-webhook-deliveries"),
+        ] {
+            schedule_job(&mut scheduler, &server_context, every, job);
+        }
         scheduler
             .every(5.minutes())
             .run(|| async { crate::health::scheduler_health_check() });
@@ -298,7 +393,37 @@ pub async fn server(
         scheduler
             .every(clokwerk::Interval::Monday)
             .at("8:00 am")
-            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "send-rfd-changelog")});
+            .run(enclose! { (server_context) move || {
+                create_do_job_fn(server_context.clone(), "send-rfd-changelog", Duration::weeks(1))
+            }});
+
+        // Report the last week's expenses that look out of the ordinary.
+        scheduler
+            .every(clokwerk::Interval::Monday)
+            .at("9:00 am")
+            .run(enclose! { (server_context) move || {
+                create_do_job_fn(server_context.clone(), "send-expense-report", Duration::weeks(1))
+            }});
+
+        // Summarize the recruiting funnel for the hiring team.
+        scheduler
+            .every(clokwerk::Interval::Monday)
+            .at("9:00 am")
+            .run(enclose! { (server_context) move || {
+                create_do_job_fn(server_context.clone(), "send-recruiting-summary", Duration::weeks(1))
+            }});
+
+        // Send the finance summary for last month on the first of the month.
+        scheduler
+            .every(1.day())
+            .at("9:00 am")
+            .run(enclose! { (server_context) move || {
+                if Utc::now().day() == 1 {
+                    create_do_job_fn(server_context.clone(), "send-finance-summary", Duration::days(1))
+                } else {
+                    Box::pin(async {})
+                }
+            }});
     }
 
     // For Cloud run & ctrl+c, shutdown gracefully.
@@ -343,16 +468,56 @@ pub async fn server(
     Ok(())
 }
 
-pub fn create_do_job_fn(ctx: ServerContext, job: &str) -> Pin<Box<dyn std::future::Future<Output = ()> + Send>> {
-    Box::pin(do_job(ctx, job.to_string()))
+lazy_static! {
+    /// Identifies this replica in the claims it makes on scheduled runs.
+    static ref SCHEDULER_RUNNER: String = format!(
+        "{}-{}",
+        env::var("HOSTNAME").unwrap_or_else(|_| "webhooky".to_string()),
+        uuid::Uuid::new_v4()
+    );
+}
+
+/// Schedule `job` to run `every` so often.
+fn schedule_job(
+    scheduler: &mut AsyncScheduler<chrono_tz::Tz>,
+    server_context: &ServerContext,
+    every: Duration,
+    job: &'static str,
+) {
+    let ctx = server_context.clone();
+    scheduler
+        .every(clokwerk::Interval::Seconds(every.num_seconds() as u32))
+        .run(move || create_do_job_fn(ctx.clone(), job, every));
+}
+
+pub fn create_do_job_fn(
+    ctx: ServerContext,
+    job: &str,
+    every: Duration,
+) -> Pin<Box<dyn std::future::Future<Output = ()> + Send>> {
+    Box::pin(do_job(ctx, job.to_string(), every))
 }
 
-pub async fn do_job(ctx: ServerContext, job: String) {
+pub async fn do_job(ctx: ServerContext, job: String, every: Duration) {
+    // Another replica may have already run the job for this interval.
+    match claim_scheduled_run(&ctx.app.db, &job, &SCHEDULER_RUNNER, every).await {
+        Ok(true) => (),
+        Ok(false) => {
+            info!("skipping cron job `{}`, another replica has already run it", job);
+            return;
+        }
+        Err(e) => {
+            warn!("skipping cron job `{}`, failed to claim the run: {}", job, e);
+            sentry::integrations::anyhow::capture_anyhow(&e);
+            return;
+        }
+    }
+
     let mut txn = start_sentry_cron_transaction(&job);
     let errored = txn
         .run(|| async {
-            info!("triggering cron job `{}`", job);
-            match crate::handlers_cron::run_subcmd_job(&ctx, &job).await {
+            info!("triggering cron job `{}` ({})", job, txn.correlation_id());
+            match correlation::scope(txn.correlation_id(), crate::handlers_cron::run_subcmd_job(&ctx, &job)).await {
                 Ok(_) => false,
                 // Send the error to sentry.
                 Err(e) => {
@@ -396,6 +561,7 @@ pub struct CounterResponse {
 }]
 async fn listen_products_sold_count_requests(
     rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
 ) -> Result<HttpResponseOk<CounterResponse>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
@@ -420,16 +586,27 @@ async fn listen_products_sold_count_requests(
 }]
 async fn listen_github_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
     body: HmacVerifiedBody<crate::handlers_github::GitHubWebhookVerification, GitHubWebhook>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
     let webhook = body.into_inner()?;
+    let event_type = crate::handlers_github::github_event_type(&rqctx)
+        .await
+        .map_err(handle_anyhow_err_as_http_err)?;
 
     let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&webhook)).await;
 
-    if let Err(e) = txn.run(|| crate::handlers_github::handle_github(rqctx, webhook)).await {
-        // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
+    if let Err(e) = enqueue(
+        rqctx.context(),
+        EventSource::GitHub,
+        &event_type.to_string(),
+        &webhook,
+        &txn.correlation_id(),
+    )
+    .await
+    {
+        txn.finish(e.status_code);
+        return Err(e);
     }
 
     txn.finish(http::StatusCode::ACCEPTED);
@@ -449,7 +626,8 @@ pub struct RFDPathParams {
 }]
 async fn trigger_rfd_update_by_number(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<InternalToken>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
     path_params: Path<RFDPathParams>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
@@ -475,6 +653,7 @@ async fn trigger_rfd_update_by_number(
 }]
 async fn github_rate_limit(
     rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
 ) -> Result<HttpResponseOk<GitHubRateLimit>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
@@ -492,6 +671,18 @@ async fn github_rate_limit(
     }
 }
 
+/** Get the counters for requests that went through, or were turned away by, the rate limits. */
+#[endpoint {
+    method = GET,
+    path = "/rate-limits",
+}]
+async fn rate_limits(
+    _rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: InternalAuth,
+) -> Result<HttpResponseOk<RateLimitMetrics>, HttpError> {
+    Ok(HttpResponseOk(crate::rate_limit::metrics()))
+}
+
 /// A GitHub RateLimit
 #[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
 pub struct GitHubRateLimit {
@@ -512,6 +703,7 @@ pub struct GitHubRateLimit {
 }]
 async fn listen_airtable_employees_print_home_address_label_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
@@ -541,6 +733,7 @@ async fn listen_airtable_employees_print_home_address_label_webhooks(
 }]
 async fn listen_airtable_certificates_renew_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
@@ -570,6 +763,7 @@ async fn listen_airtable_certificates_renew_webhooks(
 }]
 async fn listen_airtable_assets_items_print_barcode_label_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
@@ -590,6 +784,162 @@ async fn listen_airtable_assets_items_print_barcode_label_webhooks(
     Ok(HttpResponseAccepted("ok".to_string()))
 }
 
+/**
+ * Check an asset out to an employee.
+ */
+#[endpoint {
+    method = POST,
+    path = "/assets/check_out",
+}]
+async fn listen_assets_check_out(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    body_param: TypedBody<CheckOutRequest>,
+) -> Result<HttpResponseOk<AssetAssignment>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    match txn.run(|| crate::handlers::handle_assets_check_out(rqctx, body)).await {
+        Ok(assignment) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(assignment))
+        }
+        Err(e) => Err(handle_assets_check_out_err(&mut txn, e)),
+    }
+}
+
+/**
+ * Check an asset back in.
+ */
+#[endpoint {
+    method = POST,
+    path = "/assets/check_in",
+}]
+async fn listen_assets_check_in(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    body_param: TypedBody<CheckInRequest>,
+) -> Result<HttpResponseOk<Option<AssetAssignment>>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    match txn.run(|| crate::handlers::handle_assets_check_in(rqctx, body)).await {
+        Ok(assignment) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(assignment))
+        }
+        Err(e) => {
+            // Send the error to sentry.
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct AssetItemParams {
+    id: i32,
+}
+
+/**
+ * List who an asset was checked out to, latest first.
+ */
+#[endpoint {
+    method = GET,
+    path = "/assets/{id}/assignments",
+}]
+async fn listen_assets_assignment_history(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    path_params: Path<AssetItemParams>,
+) -> Result<HttpResponseOk<Vec<AssetAssignment>>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    let id = path_params.into_inner().id;
+    match txn
+        .run(|| crate::handlers::handle_assets_assignment_history(rqctx, id))
+        .await
+    {
+        Ok(assignments) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(assignments))
+        }
+        Err(e) => {
+            // Send the error to sentry.
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct DeviceCredentialParams {
+    token: String,
+}
+
+/// The route of device credential downloads. The token in the path is a live download link, so
+/// this is what the requests are reported to Sentry as instead of their path.
+const CREDENTIALS_ROUTE: &str = "/credentials/{token}";
+
+/**
+ * The page a device credential download link opens, to confirm the download. Opening it does not
+ * use the link up, so that link previews and scanners do not.
+ */
+#[endpoint {
+    method = GET,
+    path = "/credentials/{token}",
+}]
+async fn listen_device_credential_confirmation(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    path_params: Path<DeviceCredentialParams>,
+) -> Result<http::Response<hyper::Body>, HttpError> {
+    let mut txn = start_sentry_http_transaction_for_route::<()>(rqctx.clone(), None, Some(CREDENTIALS_ROUTE)).await;
+
+    let token = path_params.into_inner().token;
+    match txn
+        .run(|| crate::handlers::handle_device_credential_confirmation(rqctx, token))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(r)
+        }
+        Err(e) => Err(handle_device_credential_download_err(&mut txn, e)),
+    }
+}
+
+/**
+ * Download a device credential through the link sent to the employee it was issued to. The link
+ * only works once.
+ */
+#[endpoint {
+    method = POST,
+    path = "/credentials/{token}",
+}]
+async fn listen_device_credential_download(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    path_params: Path<DeviceCredentialParams>,
+) -> Result<http::Response<hyper::Body>, HttpError> {
+    let mut txn = start_sentry_http_transaction_for_route::<()>(rqctx.clone(), None, Some(CREDENTIALS_ROUTE)).await;
+
+    let token = path_params.into_inner().token;
+    match txn
+        .run(|| crate::handlers::handle_device_credential_download(rqctx, token))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(r)
+        }
+        Err(e) => Err(handle_device_credential_download_err(&mut txn, e)),
+    }
+}
+
 /**
  * Listen for a button pressed to print barcode labels for a swag inventory item.
  */
@@ -599,6 +949,7 @@ async fn listen_airtable_assets_items_print_barcode_label_webhooks(
 }]
 async fn listen_airtable_swag_inventory_items_print_barcode_labels_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
@@ -628,6 +979,7 @@ async fn listen_airtable_swag_inventory_items_print_barcode_labels_webhooks(
 }]
 async fn listen_airtable_applicants_request_background_check_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
@@ -658,6 +1010,7 @@ async fn listen_airtable_applicants_request_background_check_webhooks(
 }]
 async fn listen_airtable_applicants_update_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
@@ -688,6 +1041,7 @@ async fn listen_airtable_applicants_update_webhooks(
 }]
 async fn listen_airtable_applicants_recreate_piia_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
@@ -718,6 +1072,7 @@ async fn listen_airtable_applicants_recreate_piia_webhooks(
 }]
 async fn listen_airtable_shipments_outbound_create_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
@@ -756,6 +1111,7 @@ pub struct AirtableRowEvent {
 }]
 async fn listen_airtable_shipments_outbound_reprint_label_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
@@ -785,6 +1141,7 @@ async fn listen_airtable_shipments_outbound_reprint_label_webhooks(
 }]
 async fn listen_airtable_shipments_outbound_reprint_receipt_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
@@ -814,6 +1171,7 @@ async fn listen_airtable_shipments_outbound_reprint_receipt_webhooks(
 }]
 async fn listen_airtable_shipments_outbound_resend_shipment_status_email_to_recipient_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
@@ -845,6 +1203,7 @@ async fn listen_airtable_shipments_outbound_resend_shipment_status_email_to_reci
 }]
 async fn listen_airtable_shipments_outbound_schedule_pickup_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
@@ -914,7 +1273,8 @@ pub struct IncomingEmail {
 }]
 async fn listen_applicant_review_requests(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<InternalToken>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
     body_param: TypedBody<cio_api::applicant_reviews::NewApplicantReview>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
     let body = body_param.into_inner();
@@ -936,6 +1296,12 @@ struct ApplicantInfoParams {
     email: String,
 }
 
+#[derive(Deserialize, JsonSchema)]
+struct UploadTokenQuery {
+    /// The company the materials are uploaded to. The token only works for uploads to it.
+    cio_company_id: i32,
+}
+
 // Listen for applicant info requests. This assume that the caller has performed the necessary
 // authentication to verify ownership of the email that we are being sent
 #[endpoint {
@@ -944,6 +1310,7 @@ struct ApplicantInfoParams {
 }]
 async fn listen_applicant_info(
     rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
     _auth: Bearer<HiringToken>,
     path_params: Path<ApplicantInfoParams>,
 ) -> Result<HttpResponseOk<ApplicantInfo>, HttpError> {
@@ -978,14 +1345,21 @@ async fn listen_applicant_upload_token(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<HiringToken>,
     path_params: Path<ApplicantInfoParams>,
+    query_args: Query<UploadTokenQuery>,
 ) -> Result<HttpResponseOk<ApplicantUploadToken>, HttpError> {
+    rate_limit::check(&rqctx).await?;
+
     let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
 
     log::info!("Running applicant upload token handler");
 
     let result = txn
         .run(|| {
-            crate::handlers_hiring::handle_applicant_upload_token(&rqctx.context().app, path_params.into_inner().email)
+            crate::handlers_hiring::handle_applicant_upload_token(
+                &rqctx.context().app,
+                path_params.into_inner().email,
+                query_args.into_inner().cio_company_id,
+            )
         })
         .await;
 
@@ -1001,162 +1375,626 @@ async fn listen_applicant_upload_token(
     }
 }
 
-/**
- * Listen for applications being submitted for incoming job applications */
+/** Void the offer letter that was sent to an applicant. */
 #[endpoint {
     method = POST,
-    path = "/application-test/submit",
+    path = "/offer/{email}/void",
 }]
-async fn listen_test_application_submit_requests(
+async fn trigger_applicant_offer_void(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<HiringToken>,
-    body_param: TypedBody<cio_api::application_form::ApplicationForm>,
+    _auth: InternalAuth,
+    path_params: Path<ApplicantInfoParams>,
+    body_param: TypedBody<VoidOfferRequest>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    let body = body_param.into_inner();
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+    rate_limit::check(&rqctx).await?;
 
-    if let Err(e) = txn
-        .run(|| crate::handlers::handle_test_application_submit(rqctx, body))
-        .await
-    {
-        // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
-    }
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
 
-    txn.finish(http::StatusCode::ACCEPTED);
+    let result = txn
+        .run(|| {
+            crate::handlers_hiring::handle_applicant_offer_void(
+                &rqctx.context().app,
+                path_params.into_inner().email,
+                body_param.into_inner(),
+            )
+        })
+        .await;
 
-    Ok(HttpResponseAccepted("ok".to_string()))
+    match result {
+        Ok(_) => {
+            txn.finish(http::StatusCode::ACCEPTED);
+            Ok(HttpResponseAccepted("ok".to_string()))
+        }
+        Err(err) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
 }
 
-/**
- * Listen for applications being submitted for incoming job applications */
+/** Resend the offer letter to an applicant that has not yet signed it. */
 #[endpoint {
     method = POST,
-    path = "/application/submit",
+    path = "/offer/{email}/resend",
 }]
-async fn listen_application_submit_requests(
+async fn trigger_applicant_offer_resend(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<HiringToken>,
-    body_param: TypedBody<cio_api::application_form::ApplicationForm>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    path_params: Path<ApplicantInfoParams>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    let body = body_param.into_inner();
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
-
-    if let Err(e) = txn
-        .run(|| crate::handlers::handle_application_submit(rqctx, body))
-        .await
-    {
-        // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
-    }
-
-    txn.finish(http::StatusCode::ACCEPTED);
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
 
-    Ok(HttpResponseAccepted("ok".to_string()))
-}
+    let result = txn
+        .run(|| {
+            crate::handlers_hiring::handle_applicant_offer_resend(&rqctx.context().app, path_params.into_inner().email)
+        })
+        .await;
 
-/// Application file upload data.
-#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
-pub struct ApplicationFileUploadData {
-    #[serde(default)]
-    pub cio_company_id: i32,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub resume: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub materials: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub portfolio_pdf_name: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub email: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub role: String,
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub interested_in: Vec<String>,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub user_name: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub resume_contents: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub materials_contents: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub portfolio_pdf_contents: String,
+    match result {
+        Ok(_) => {
+            txn.finish(http::StatusCode::ACCEPTED);
+            Ok(HttpResponseAccepted("ok".to_string()))
+        }
+        Err(err) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
 }
 
-/**
- * CORS functionality for file uploads
- */
+/** Get a DocuSign URL for correcting the offer letter that was sent to an applicant. */
 #[endpoint {
-    method = OPTIONS,
-    path = "/application-test/files/upload",
+    method = POST,
+    path = "/offer/{email}/correct",
 }]
-async fn listen_test_application_files_upload_requests_cors(
-    _qctx: Arc<RequestContext<ServerContext>>,
-) -> Result<HttpResponseHeaders<HttpResponseOk<String>>, HttpError> {
-    let mut resp = HttpResponseHeaders::new_unnamed(HttpResponseOk("".to_string()));
-    let headers = resp.headers_mut();
+async fn trigger_applicant_offer_correct(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: InternalAuth,
+    path_params: Path<ApplicantInfoParams>,
+    body_param: TypedBody<CorrectOfferRequest>,
+) -> Result<HttpResponseOk<String>, HttpError> {
+    rate_limit::check(&rqctx).await?;
 
-    headers.insert("Access-Control-Allow-Origin", HeaderValue::from_static("*"));
-    headers.insert("Access-Control-Allow-Headers", HeaderValue::from_static("*"));
-    headers.insert("Access-Control-Allow-Method", HeaderValue::from_static("*"));
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
 
-    Ok(resp)
+    let result = txn
+        .run(|| {
+            crate::handlers_hiring::handle_applicant_offer_correct(
+                &rqctx.context().app,
+                path_params.into_inner().email,
+                body_param.into_inner(),
+            )
+        })
+        .await;
+
+    match result {
+        Ok(url) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(url))
+        }
+        Err(err) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
+}
+
+/** Start the adverse action flow for an applicant's background check. */
+#[endpoint {
+    method = POST,
+    path = "/background-check/{email}/adverse-action",
+}]
+async fn trigger_applicant_adverse_action(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: InternalAuth,
+    path_params: Path<ApplicantInfoParams>,
+    body_param: TypedBody<AdverseActionRequest>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    rate_limit::check(&rqctx).await?;
+
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    let result = txn
+        .run(|| {
+            crate::handlers_hiring::handle_applicant_adverse_action(
+                &rqctx.context().app,
+                path_params.into_inner().email,
+                body_param.into_inner(),
+            )
+        })
+        .await;
+
+    match result {
+        Ok(_) => {
+            txn.finish(http::StatusCode::ACCEPTED);
+            Ok(HttpResponseAccepted("ok".to_string()))
+        }
+        Err(err) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
+}
+
+/** Cancel the adverse action flow for an applicant's background check. */
+#[endpoint {
+    method = POST,
+    path = "/background-check/{email}/adverse-action/cancel",
+}]
+async fn trigger_applicant_adverse_action_cancel(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    path_params: Path<ApplicantInfoParams>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    let result = txn
+        .run(|| {
+            crate::handlers_hiring::handle_applicant_adverse_action_cancel(
+                &rqctx.context().app,
+                path_params.into_inner().email,
+            )
+        })
+        .await;
+
+    match result {
+        Ok(_) => {
+            txn.finish(http::StatusCode::ACCEPTED);
+            Ok(HttpResponseAccepted("ok".to_string()))
+        }
+        Err(err) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
+}
+
+/** Find times that work for an interview panel and email them to the applicant. */
+#[endpoint {
+    method = POST,
+    path = "/interviews/{email}/propose",
+}]
+async fn trigger_interview_times_propose(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: InternalAuth,
+    path_params: Path<ApplicantInfoParams>,
+    body_param: TypedBody<InterviewPanel>,
+) -> Result<HttpResponseOk<InterviewSchedule>, HttpError> {
+    rate_limit::check(&rqctx).await?;
+
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    let result = txn
+        .run(|| {
+            crate::handlers_hiring::handle_interview_times_propose(
+                &rqctx.context().app,
+                path_params.into_inner().email,
+                body_param.into_inner(),
+            )
+        })
+        .await;
+
+    match result {
+        Ok(schedule) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(schedule))
+        }
+        Err(err) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct InterviewProposalParams {
+    token: String,
+}
+
+// Listen for requests for the interview times proposed to an applicant, by the token that was
+// emailed to them.
+#[endpoint {
+    method = GET,
+    path = "/interviews/proposals/{token}",
+}]
+async fn listen_interview_proposal(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: Bearer<HiringToken>,
+    path_params: Path<InterviewProposalParams>,
+) -> Result<HttpResponseOk<InterviewProposal>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    let result = txn
+        .run(|| crate::handlers_hiring::handle_interview_proposal(&rqctx.context().app, path_params.into_inner().token))
+        .await;
+
+    match result {
+        Ok(Some(proposal)) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(proposal))
+        }
+        Ok(None) => {
+            txn.finish(http::StatusCode::NOT_FOUND);
+            Err(HttpError::for_not_found(None, "".to_string()))
+        }
+        Err(err) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
+}
+
+// Listen for applicants booking one of the interview times proposed to them.
+#[endpoint {
+    method = POST,
+    path = "/interviews/proposals/{token}/book",
+}]
+async fn listen_interview_book_requests(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<HiringToken>,
+    path_params: Path<InterviewProposalParams>,
+    body_param: TypedBody<BookInterviewRequest>,
+) -> Result<HttpResponseOk<InterviewProposal>, HttpError> {
+    rate_limit::check(&rqctx).await?;
+
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    let result = txn
+        .run(|| {
+            crate::handlers_hiring::handle_interview_book(
+                &rqctx.context().app,
+                path_params.into_inner().token,
+                body_param.into_inner(),
+            )
+        })
+        .await;
+
+    match result {
+        Ok(proposal) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(proposal))
+        }
+        Err(err) => Err(handle_booking_err(&mut txn, err)),
+    }
+}
+
+/** Merge an applicant who applied more than once into the applicant they duplicate. */
+#[endpoint {
+    method = POST,
+    path = "/applicants/merge",
+}]
+async fn trigger_applicant_merge(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    body_param: TypedBody<ApplicantMergeRequest>,
+) -> Result<HttpResponseOk<ApplicantMerge>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    let result = txn
+        .run(|| crate::handlers_hiring::handle_applicant_merge(&rqctx.context().app, body_param.into_inner()))
+        .await;
+
+    match result {
+        Ok(merge) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(merge))
+        }
+        Err(err) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
+}
+
+/** Compute the recruiting funnel over the applicants who applied in a window of time. */
+#[endpoint {
+    method = GET,
+    path = "/recruiting/funnel",
+}]
+async fn listen_recruiting_funnel(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    query_args: Query<RecruitingFunnelQuery>,
+) -> Result<HttpResponseOk<RecruitingFunnel>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    let result = txn
+        .run(|| crate::handlers_hiring::handle_recruiting_funnel(&rqctx.context().app, query_args.into_inner()))
+        .await;
+
+    match result {
+        Ok(funnel) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(funnel))
+        }
+        Err(err) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
 }
 
 /**
- * Listen for files being uploaded for incoming job applications */
+ * Listen for applications being submitted for incoming job applications */
 #[endpoint {
     method = POST,
-    path = "/application-test/files/upload",
+    path = "/application-test/submit",
 }]
-async fn listen_test_application_files_upload_requests(
+async fn listen_test_application_submit_requests(
     rqctx: Arc<RequestContext<ServerContext>>,
-    bearer: BearerToken,
-    body_param: TypedBody<ApplicationFileUploadData>,
-) -> Result<HttpResponseHeaders<HttpResponseOk<HashMap<String, String>>>, HttpError> {
+    _rate_limit: RateLimit,
+    _auth: Bearer<HiringToken>,
+    body_param: TypedBody<cio_api::application_form::ApplicationForm>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
     let body = body_param.into_inner();
     let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
-    // We require that the user has supplied an upload token in the bearer header
-    if let Some(token) = bearer.inner() {
-        // Attempt to consume the token and mark it as unusable by other requests. A token may fail
-        // to be consumed due a number of reasons to:
-        //  1. Token was previously used
-        //  2. Token is invalid
-        //  3. Token does not match the email submitted
-        //  4. Token is expired
-        //
-        // We currently return a single error code, 409 Conflict so as not to expose which of these
-        // cases occurred. In the future we may want to relax this and return individual error codes
-        let token_result = rqctx
-            .context()
-            .app
-            .upload_token_store
-            .consume(&body.email, token)
-            .await
-            .map_err(|err| {
-                log::info!("Failed to consume upload token due to {:?}", err);
-                HttpError::for_status(None, http::StatusCode::CONFLICT)
-            });
+    if let Err(e) = txn
+        .run(|| crate::handlers::handle_test_application_submit(rqctx, body))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
 
-        match token_result {
-            Ok(_) => {
-                let upload_result = txn
-                    .run(|| crate::handlers::handle_test_application_files_upload(rqctx, body))
-                    .await;
+    txn.finish(http::StatusCode::ACCEPTED);
 
-                match upload_result {
-                    Ok(r) => {
-                        txn.finish(http::StatusCode::OK);
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
 
-                        let mut resp = HttpResponseHeaders::new_unnamed(HttpResponseOk(r));
+/**
+ * Listen for applications being submitted for incoming job applications */
+#[endpoint {
+    method = POST,
+    path = "/application/submit",
+}]
+async fn listen_application_submit_requests(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: Bearer<HiringToken>,
+    body_param: TypedBody<cio_api::application_form::ApplicationForm>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
-                        let headers = resp.headers_mut();
-                        headers.insert("Access-Control-Allow-Origin", http::HeaderValue::from_static("*"));
+    if let Err(e) = txn
+        .run(|| crate::handlers::handle_application_submit(rqctx, body))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
 
-                        Ok(resp)
-                    }
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/// Application file upload data.
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct ApplicationFileUploadData {
+    #[serde(default)]
+    pub cio_company_id: i32,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub resume: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub materials: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub portfolio_pdf_name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub email: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub role: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub interested_in: Vec<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub user_name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub resume_contents: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub materials_contents: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub portfolio_pdf_contents: String,
+}
+
+/**
+ * CORS functionality for file uploads
+ */
+#[endpoint {
+    method = OPTIONS,
+    path = "/application-test/files/upload",
+}]
+async fn listen_test_application_files_upload_requests_cors(
+    _qctx: Arc<RequestContext<ServerContext>>,
+) -> Result<HttpResponseHeaders<HttpResponseOk<String>>, HttpError> {
+    let mut resp = HttpResponseHeaders::new_unnamed(HttpResponseOk("".to_string()));
+    let headers = resp.headers_mut();
+
+    headers.insert("Access-Control-Allow-Origin", HeaderValue::from_static("*"));
+    headers.insert("Access-Control-Allow-Headers", HeaderValue::from_static("*"));
+    headers.insert("Access-Control-Allow-Method", HeaderValue::from_static("*"));
+
+    Ok(resp)
+}
+
+/**
+ * Listen for files being uploaded for incoming job applications */
+#[endpoint {
+    method = POST,
+    path = "/application-test/files/upload",
+}]
+async fn listen_test_application_files_upload_requests(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    bearer: BearerToken,
+    body_param: TypedBody<ApplicationFileUploadData>,
+) -> Result<HttpResponseHeaders<HttpResponseOk<HashMap<String, String>>>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    // We require that the user has supplied an upload token in the bearer header
+    if let Some(token) = bearer.inner() {
+        // Attempt to consume the token and mark it as unusable by other requests. A token may fail
+        // to be consumed due a number of reasons to:
+        //  1. Token was previously used
+        //  2. Token is invalid
+        //  3. Token does not match the email submitted
+        //  4. Token is expired
+        //
+        // We currently return a single error code, 409 Conflict so as not to expose which of these
+        // cases occurred. In the future we may want to relax this and return individual error codes
+        let token_result = rqctx
+            .context()
+            .app
+            .upload_token_store
+            .consume(&body.email, token, body.cio_company_id)
+            .await
+            .map_err(|err| {
+                log::info!("Failed to consume upload token due to {:?}", err);
+                HttpError::for_status(None, http::StatusCode::CONFLICT)
+            });
+
+        match token_result {
+            Ok(_) => {
+                let upload_result = txn
+                    .run(|| crate::handlers::handle_test_application_files_upload(rqctx, body))
+                    .await;
+
+                match upload_result {
+                    Ok(r) => {
+                        txn.finish(http::StatusCode::OK);
+
+                        let mut resp = HttpResponseHeaders::new_unnamed(HttpResponseOk(r));
+
+                        let headers = resp.headers_mut();
+                        headers.insert("Access-Control-Allow-Origin", http::HeaderValue::from_static("*"));
+
+                        Ok(resp)
+                    }
+                    // Send the error to sentry.
+                    Err(e) => {
+                        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+                        Err(handle_anyhow_err_as_http_err(e))
+                    }
+                }
+            }
+            Err(err) => {
+                txn.finish(err.status_code);
+                Err(err)
+            }
+        }
+    } else {
+        txn.finish(http::StatusCode::UNAUTHORIZED);
+        Err(HttpError::for_status(None, http::StatusCode::UNAUTHORIZED))
+    }
+}
+
+/**
+ * CORS functionality for file uploads
+ */
+#[endpoint {
+    method = OPTIONS,
+    path = "/application/files/upload",
+}]
+async fn listen_application_files_upload_requests_cors(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+) -> Result<HttpResponseHeaders<HttpResponseOk<String>>, HttpError> {
+    let mut resp = HttpResponseHeaders::new_unnamed(HttpResponseOk("".to_string()));
+    let headers = resp.headers_mut();
+
+    let allowed_origins = crate::cors::get_cors_origin_header(
+        rqctx.clone(),
+        &["https://apply.oxide.computer", "https://oxide.computer"],
+    )
+    .await?;
+    headers.insert("Access-Control-Allow-Origin", allowed_origins);
+    headers.insert("Access-Control-Allow-Headers", HeaderValue::from_static("*"));
+    headers.insert("Access-Control-Allow-Method", HeaderValue::from_static("*"));
+
+    Ok(resp)
+}
+
+/**
+ * Listen for files being uploaded for incoming job applications */
+#[endpoint {
+    method = POST,
+    path = "/application/files/upload",
+}]
+async fn listen_application_files_upload_requests(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    bearer: BearerToken,
+    body_param: TypedBody<ApplicationFileUploadData>,
+) -> Result<HttpResponseHeaders<HttpResponseOk<HashMap<String, String>>>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    // We require that the user has supplied an upload token in the bearer header
+    if let Some(token) = bearer.inner() {
+        // Attempt to consume the token marked it as unusable by other requests. A token may fail
+        // to be consumed due to:
+        //  1. Token was previously used
+        //  2. Token is invalid
+        //  3. Token does not match the email submitted
+        //  4. Token is expired
+        //
+        // We currently return a single error code, 409 Conflict so as not to expose which of these
+        // cases occurred. In the future we may want to relax this and return individual error codes
+        let token_result = rqctx
+            .context()
+            .app
+            .upload_token_store
+            .consume(&body.email, token, body.cio_company_id)
+            .await
+            .map_err(|err| {
+                log::info!("Failed to consume upload token due to {:?}", err);
+                HttpError::for_status(None, http::StatusCode::CONFLICT)
+            });
+
+        log::info!("Application materials upload token consume result {:?}", token_result);
+
+        match token_result {
+            Ok(_) => {
+                // Check the origin header. In the future this may be upgraded to a hard failure
+                let origin_access = crate::cors::get_cors_origin_header(
+                    rqctx.clone(),
+                    &["https://apply.oxide.computer", "https://oxide.computer"],
+                )
+                .await;
+
+                let upload_result = txn
+                    .run(|| crate::handlers::handle_application_files_upload(rqctx, body))
+                    .await;
+
+                match upload_result {
+                    Ok(r) => {
+                        txn.finish(http::StatusCode::OK);
+
+                        let mut resp = HttpResponseHeaders::new_unnamed(HttpResponseOk(r));
+
+                        match origin_access {
+                            Ok(origin) => {
+                                let headers = resp.headers_mut();
+                                headers.insert("Access-Control-Allow-Origin", origin);
+                            }
+                            Err(err) => {
+                                warn!(
+                                    "Submission to /application/files/upload failed CORS check. Err {:?}",
+                                    err
+                                );
+                            }
+                        }
+
+                        Ok(resp)
+                    }
                     // Send the error to sentry.
                     Err(e) => {
                         txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
@@ -1170,146 +2008,1251 @@ async fn listen_test_application_files_upload_requests(
             }
         }
     } else {
+        log::info!("Applicant upload request is missing a bearer token");
+
         txn.finish(http::StatusCode::UNAUTHORIZED);
         Err(HttpError::for_status(None, http::StatusCode::UNAUTHORIZED))
     }
 }
 
-/**
- * CORS functionality for file uploads
- */
+/**
+ * CORS functionality for file uploads
+ */
+#[endpoint {
+    method = OPTIONS,
+    path = "/application-test/files/multipart",
+}]
+async fn listen_test_application_files_multipart_upload_requests_cors(
+    _qctx: Arc<RequestContext<ServerContext>>,
+) -> Result<HttpResponseHeaders<HttpResponseOk<String>>, HttpError> {
+    let mut resp = HttpResponseHeaders::new_unnamed(HttpResponseOk("".to_string()));
+    let headers = resp.headers_mut();
+
+    headers.insert("Access-Control-Allow-Origin", HeaderValue::from_static("*"));
+    headers.insert("Access-Control-Allow-Headers", HeaderValue::from_static("*"));
+    headers.insert("Access-Control-Allow-Method", HeaderValue::from_static("*"));
+
+    Ok(resp)
+}
+
+/**
+ * Listen for files being uploaded for incoming job applications as `multipart/form-data`, and
+ * read them without storing them anywhere. */
+#[endpoint {
+    method = POST,
+    path = "/application-test/files/multipart",
+}]
+async fn listen_test_application_files_multipart_upload_requests(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    bearer: BearerToken,
+) -> Result<HttpResponseHeaders<HttpResponseOk<HashMap<String, String>>>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    // We require that the user has supplied an upload token in the bearer header. It is consumed
+    // once the upload says which applicant it is for.
+    let token = match bearer.inner() {
+        Some(token) => token.to_string(),
+        None => {
+            txn.finish(http::StatusCode::UNAUTHORIZED);
+            return Err(HttpError::for_status(None, http::StatusCode::UNAUTHORIZED));
+        }
+    };
+
+    match txn
+        .run(|| crate::handlers::handle_application_files_multipart_upload(rqctx, &token, true))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
+
+            let mut resp = HttpResponseHeaders::new_unnamed(HttpResponseOk(r));
+
+            let headers = resp.headers_mut();
+            headers.insert("Access-Control-Allow-Origin", http::HeaderValue::from_static("*"));
+
+            Ok(resp)
+        }
+        Err(e) => Err(handle_upload_err(&mut txn, e)),
+    }
+}
+
+/**
+ * CORS functionality for file uploads
+ */
+#[endpoint {
+    method = OPTIONS,
+    path = "/application/files/multipart",
+}]
+async fn listen_application_files_multipart_upload_requests_cors(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+) -> Result<HttpResponseHeaders<HttpResponseOk<String>>, HttpError> {
+    let mut resp = HttpResponseHeaders::new_unnamed(HttpResponseOk("".to_string()));
+    let headers = resp.headers_mut();
+
+    let allowed_origins = crate::cors::get_cors_origin_header(
+        rqctx.clone(),
+        &["https://apply.oxide.computer", "https://oxide.computer"],
+    )
+    .await?;
+    headers.insert("Access-Control-Allow-Origin", allowed_origins);
+    headers.insert("Access-Control-Allow-Headers", HeaderValue::from_static("*"));
+    headers.insert("Access-Control-Allow-Method", HeaderValue::from_static("*"));
+
+    Ok(resp)
+}
+
+/**
+ * Listen for files being uploaded for incoming job applications as `multipart/form-data`. Files
+ * are streamed into Google Drive as they arrive, rather than read whole like the JSON uploads. */
+#[endpoint {
+    method = POST,
+    path = "/application/files/multipart",
+}]
+async fn listen_application_files_multipart_upload_requests(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    bearer: BearerToken,
+) -> Result<HttpResponseHeaders<HttpResponseOk<HashMap<String, String>>>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    // We require that the user has supplied an upload token in the bearer header. It is consumed
+    // once the upload says which applicant it is for.
+    let token = match bearer.inner() {
+        Some(token) => token.to_string(),
+        None => {
+            log::info!("Applicant upload request is missing a bearer token");
+
+            txn.finish(http::StatusCode::UNAUTHORIZED);
+            return Err(HttpError::for_status(None, http::StatusCode::UNAUTHORIZED));
+        }
+    };
+
+    // Check the origin header. In the future this may be upgraded to a hard failure
+    let origin_access = crate::cors::get_cors_origin_header(
+        rqctx.clone(),
+        &["https://apply.oxide.computer", "https://oxide.computer"],
+    )
+    .await;
+
+    match txn
+        .run(|| crate::handlers::handle_application_files_multipart_upload(rqctx, &token, false))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
+
+            let mut resp = HttpResponseHeaders::new_unnamed(HttpResponseOk(r));
+
+            match origin_access {
+                Ok(origin) => {
+                    let headers = resp.headers_mut();
+                    headers.insert("Access-Control-Allow-Origin", origin);
+                }
+                Err(err) => {
+                    warn!(
+                        "Submission to /application/files/multipart failed CORS check. Err {:?}",
+                        err
+                    );
+                }
+            }
+
+            Ok(resp)
+        }
+        Err(e) => Err(handle_upload_err(&mut txn, e)),
+    }
+}
+
+/**
+ * Listen for emails sent to the careers inbox, as posted by SendGrid Inbound Parse, and turn
+ * them into applications. */
+#[endpoint {
+    method = POST,
+    path = "/careers/inbound",
+}]
+async fn listen_careers_inbound_email(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: QueryToken<CareersInboxToken>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    if let Err(e) = txn.run(|| crate::handlers::handle_careers_inbound_email(rqctx)).await {
+        return Err(handle_upload_err(&mut txn, e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/**
+ * Listen for rows created in our Airtable workspace.
+ * These are set up with an Airtable script on the workspaces themselves.
+ */
+#[endpoint {
+    method = POST,
+    path = "/airtable/shipments/inbound/create",
+}]
+async fn listen_airtable_shipments_inbound_create_webhooks(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: Bearer<AirtableToken>,
+    body_param: TypedBody<AirtableRowEvent>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers::handle_airtable_shipments_inbound_create(rqctx, body))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/**
+ * Listen for orders being created by the Oxide store.
+ */
+#[endpoint {
+    method = POST,
+    path = "/store/order",
+}]
+async fn listen_store_order_create(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    body_param: TypedBody<Order>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers::handle_store_order_create(rqctx, body))
+        .await
+    {
+        return Err(handle_store_order_err(&mut txn, e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/**
+ * Receive new swag stock at a location.
+ */
+#[endpoint {
+    method = POST,
+    path = "/store/inventory/receive",
+}]
+async fn listen_store_inventory_receive(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    body_param: TypedBody<StockReceipt>,
+) -> Result<HttpResponseOk<SwagStockLevel>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    match txn
+        .run(|| crate::handlers::handle_store_inventory_receive(rqctx, body))
+        .await
+    {
+        Ok(level) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(level))
+        }
+        Err(e) => {
+            // Send the error to sentry.
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+/**
+ * Ask someone that is not an employee, ie. a conference winner, for their shirt size and where to
+ * ship their swag. Their shipment is held until they answer.
+ */
+#[endpoint {
+    method = POST,
+    path = "/swag/preferences",
+}]
+async fn trigger_swag_preferences_request(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    body_param: TypedBody<SwagRecipient>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers::handle_swag_preferences_request(rqctx, body))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct SwagPreferencesParams {
+    token: String,
+}
+
+/**
+ * Get what the swag preferences form behind a link is filled in with.
+ */
+#[endpoint {
+    method = GET,
+    path = "/swag/preferences/{token}",
+}]
+async fn listen_swag_preferences_form(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    path_params: Path<SwagPreferencesParams>,
+) -> Result<HttpResponseOk<SwagPreferencesForm>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    let token = path_params.into_inner().token;
+    match txn
+        .run(|| crate::handlers::handle_swag_preferences_form(rqctx, token))
+        .await
+    {
+        Ok(form) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(form))
+        }
+        Err(e) => Err(handle_swag_preferences_err(&mut txn, e)),
+    }
+}
+
+/**
+ * Submit a shirt size and shipping address through the link sent to the recipient of a shipment.
+ * The address is validated before the shipment is queued to ship to it.
+ */
+#[endpoint {
+    method = POST,
+    path = "/swag/preferences/{token}",
+}]
+async fn listen_swag_preferences_submit(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    path_params: Path<SwagPreferencesParams>,
+    body_param: TypedBody<SwagPreferences>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    // The body is a home address, so it is not sent to sentry.
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    let token = path_params.into_inner().token;
+    let body = body_param.into_inner();
+    if let Err(e) = txn
+        .run(|| crate::handlers::handle_swag_preferences_submit(rqctx, token, body))
+        .await
+    {
+        return Err(handle_swag_preferences_err(&mut txn, e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/**
+ * Listen for shipment tracking updated from EasyPost.
+ */
+#[endpoint {
+    method = POST,
+    path = "/easypost/tracking/update",
+}]
+async fn listen_easypost_tracking_update_webhooks(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    body_param: TypedBody<EasyPostTrackingUpdateEvent>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers::handle_easypost_tracking_update(rqctx, body))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/// An EasyPost tracking update event.
+/// FROM: https://www.easypost.com/docs/api#events
+#[derive(Debug, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct EasyPostTrackingUpdateEvent {
+    /// "Event".
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub object: String,
+    /// Unique identifier, begins with "evt_".
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    /// "test" or "production"
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub mode: String,
+    /// Result type and event name, see the "Possible Event Types" section for more information.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    /* /// Previous values of relevant result attributes.
+    #[serde(default)]
+    pub previous_attributes: serde_json::Value,
+    /// The object associated with the Event. See the "object" attribute on the result to determine
+    /// its specific type. This field will not be returned when retrieving events directly from the
+    /// API.
+    #[serde(default)]
+    pub result: serde_json::Value,*/
+    /// The current status of the event. Possible values are "completed", "failed", "in_queue",
+    /// "retrying", or "pending" (deprecated).
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub status: String,
+    /// Webhook URLs that have not yet been successfully notified as of the time this webhook event
+    /// was sent. The URL receiving the Event will still be listed in pending_urls, as will any
+    /// other URLs that receive the Event at the same time.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pending_urls: Vec<String>,
+    /// Webhook URLs that have already been successfully notified as of the time this webhook was
+    /// sent.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub completed_urls: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/**
+ * Listen for shipment tracking updated from Shippo.
+ */
+#[endpoint {
+    method = POST,
+    path = "/shippo/tracking/update",
+}]
+async fn listen_shippo_tracking_update_webhooks(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: QueryToken<ShippoToken>,
+    body_param: TypedBody<serde_json::Value>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers::handle_shippo_tracking_update(rqctx, body))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/// A Shippo tracking update event.
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct ShippoTrackingUpdateEvent {
+    #[serde(default)]
+    pub data: shippo::TrackingStatus,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub event: String,
+    #[serde(default)]
+    pub test: bool,
+}
+
+/** Listen for delivery events for the email we send through SendGrid. */
+#[endpoint {
+    method = POST,
+    path = "/sendgrid/events",
+}]
+async fn listen_sendgrid_events_webhooks(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: QueryToken<SendGridToken>,
+    body_param: TypedBody<Vec<SendGridEvent>>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let events = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&events)).await;
+
+    if let Err(e) = txn.run(|| crate::handlers::handle_sendgrid_events(rqctx, events)).await {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/** Listen for updates to our checkr background checks. */
+#[endpoint {
+    method = POST,
+    path = "/checkr/background/update",
+}]
+async fn listen_checkr_background_update_webhooks(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    body: HmacVerifiedBodyAudit<crate::handlers_checkr::CheckrWebhookVerification, checkr::WebhookEvent>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let webhook = body.into_inner()?;
+
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&webhook)).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers::handle_checkr_background_update(rqctx, webhook))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize)]
+pub struct BackgroundCheckExportQuery {
+    /// Only export changes made at or after this time.
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    /// Only export changes made before this time.
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+}
+
+/** Export every change to the status of a background check as a CSV, for compliance reviews. */
+#[endpoint {
+    method = GET,
+    path = "/background-checks/export",
+}]
+async fn listen_background_checks_export(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    query_args: Query<BackgroundCheckExportQuery>,
+) -> Result<http::Response<hyper::Body>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    match txn
+        .run(|| crate::handlers::handle_background_checks_export(rqctx, query_args.into_inner()))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(r)
+        }
+        Err(e) => {
+            // Send the error to sentry.
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+/// A request for the data we hold on a person. The email is sent in the body rather than the
+/// path, so that it is not written to access logs or Sentry with the URL.
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize)]
+pub struct DataSubjectRequestBody {
+    /// The email of the person the request is for.
+    pub email: String,
+    /// Who asked for the request to be carried out, for the audit record.
+    pub requested_by: String,
+}
+
+/** Export everything we hold on a person, by their email, as a JSON file. */
+#[endpoint {
+    method = POST,
+    path = "/data-subjects/export",
+}]
+async fn listen_data_subject_export(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    body_param: TypedBody<DataSubjectRequestBody>,
+) -> Result<http::Response<hyper::Body>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    match txn
+        .run(|| crate::handlers::handle_data_subject_export(rqctx, body_param.into_inner()))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(r)
+        }
+        Err(e) => {
+            // Send the error to sentry.
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+/** Erase a person, by their email, from our records and the copies of them in Airtable and the mailing list provider. */
+#[endpoint {
+    method = POST,
+    path = "/data-subjects/erasure",
+}]
+async fn trigger_data_subject_erasure(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    body_param: TypedBody<DataSubjectRequestBody>,
+) -> Result<HttpResponseOk<DataSubjectErasure>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    match txn
+        .run(|| crate::handlers::handle_data_subject_erasure(rqctx, body_param.into_inner()))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(r))
+        }
+        Err(e) => {
+            // Send the error to sentry.
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+/** Create a company from its credentials and set up its integrations, bases, configs and webhooks. The report lists the steps that need someone to finish them by hand. */
+#[endpoint {
+    method = POST,
+    path = "/companies/provision",
+}]
+async fn trigger_company_provision(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    body_param: TypedBody<NewCompany>,
+) -> Result<HttpResponseOk<ProvisioningReport>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    match txn
+        .run(|| crate::handlers::handle_company_provision(rqctx, body_param.into_inner()))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(r))
+        }
+        Err(e) => {
+            // Send the error to sentry.
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+/** List the people in the employee directory, filtered by the query. */
+#[endpoint {
+    method = GET,
+    path = "/directory",
+}]
+async fn listen_directory(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    query_args: Query<DirectoryQuery>,
+) -> Result<HttpResponseOk<Vec<DirectoryProfile>>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    match txn
+        .run(|| crate::handlers::handle_directory(rqctx, query_args.into_inner()))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(r))
+        }
+        Err(e) => {
+            // Send the error to sentry.
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct DirectoryPathParams {
+    pub username: String,
+}
+
+/** Get the profile of a single person in the employee directory. */
+#[endpoint {
+    method = GET,
+    path = "/directory/{username}",
+}]
+async fn listen_directory_profile(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    path_params: Path<DirectoryPathParams>,
+) -> Result<HttpResponseOk<DirectoryProfile>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    match txn
+        .run(|| crate::handlers::handle_directory_profile(rqctx, path_params.into_inner().username))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(r))
+        }
+        Err(e) => {
+            // Send the error to sentry.
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct UserConsentURL {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct AuthCallback {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub code: String,
+    /// The state that we had passed in through the user consent URL.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub state: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "realmId")]
+    pub realm_id: String,
+}
+
+/** Get the consent URL for Google auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/google/consent",
+}]
+async fn listen_auth_google_consent(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    // Initialize the Google client.
+    // You can use any of the libs here, they all use the same endpoint
+    // for tokens and we will send all the scopes.
+    let g = txn.run(|| GoogleDrive::new_from_env("", "")).await;
+
+    txn.finish(http::StatusCode::OK);
+
+    Ok(HttpResponseOk(UserConsentURL {
+        url: g.user_consent_url(&cio_api::companies::get_google_scopes()),
+    }))
+}
+
+/** Listen for callbacks to Google auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/google/callback",
+}]
+async fn listen_auth_google_callback(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    query_args: Query<AuthCallback>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers_auth::handle_auth_google_callback(rqctx, query_args))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/** Get the consent URL for GitHub auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/github/consent",
+}]
+async fn listen_auth_github_consent(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    txn.finish(http::StatusCode::OK);
+
+    Ok(HttpResponseOk(UserConsentURL {
+        url: "https://github.com/apps/oxidecomputerbot/installations/new".to_string(),
+    }))
+}
+
+/** Listen for callbacks to GitHub auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/github/callback",
+}]
+async fn listen_auth_github_callback(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    body_param: TypedBody<serde_json::Value>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    txn.run(|| {
+        warn!("github callback: {:?}", body);
+    });
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/** Get the consent URL for Gusto auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/gusto/consent",
+}]
+async fn listen_auth_gusto_consent(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    // Initialize the Gusto client.
+    let g = txn.run(|| Gusto::new_from_env("", ""));
+
+    txn.finish(http::StatusCode::OK);
+
+    Ok(HttpResponseOk(UserConsentURL {
+        // We don't need to define scopes for Gusto.
+        url: g.user_consent_url(&[]),
+    }))
+}
+
+/** Listen for callbacks to Gusto auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/gusto/callback",
+}]
+async fn listen_auth_gusto_callback(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    query_args: Query<AuthCallback>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers_auth::handle_auth_gusto_callback(rqctx, query_args))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/** Listen to deauthorization requests for our Zoom app. */
+#[endpoint {
+    method = GET,
+    path = "/auth/zoom/deauthorization",
+}]
+async fn listen_auth_zoom_deauthorization(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    body_param: TypedBody<serde_json::Value>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    txn.run(|| {
+        warn!("zoom deauthorization: {:?}", body);
+    });
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/** Get the consent URL for Zoom auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/zoom/consent",
+}]
+async fn listen_auth_zoom_consent(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    // Initialize the Zoom client.
+    let g = txn.run(|| Zoom::new_from_env("", ""));
+
+    txn.finish(http::StatusCode::OK);
+
+    Ok(HttpResponseOk(UserConsentURL {
+        url: g.user_consent_url(&[]),
+    }))
+}
+
+/** Listen for callbacks to Zoom auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/zoom/callback",
+}]
+async fn listen_auth_zoom_callback(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    query_args: Query<AuthCallback>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers_auth::handle_auth_zoom_callback(rqctx, query_args))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/** Get the consent URL for Slack auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/slack/consent",
+}]
+async fn listen_auth_slack_consent(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    // Initialize the Slack client.
+    let s = txn.run(|| Slack::new_from_env("", "", ""));
+
+    txn.finish(http::StatusCode::OK);
+
+    Ok(HttpResponseOk(UserConsentURL {
+        url: s.user_consent_url(),
+    }))
+}
+
+/** Listen for callbacks to Slack auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/slack/callback",
+}]
+async fn listen_auth_slack_callback(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    query_args: Query<AuthCallback>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers_auth::handle_auth_slack_callback(rqctx, query_args))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/** Get the consent URL for QuickBooks auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/quickbooks/consent",
+}]
+async fn listen_auth_quickbooks_consent(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    // Initialize the QuickBooks client.
+    let g = txn.run(|| QuickBooks::new_from_env("", "", ""));
+
+    txn.finish(http::StatusCode::OK);
+
+    Ok(HttpResponseOk(UserConsentURL {
+        url: g.user_consent_url(),
+    }))
+}
+
+/** Listen for callbacks to QuickBooks auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/quickbooks/callback",
+}]
+async fn listen_auth_quickbooks_callback(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    query_args: Query<AuthCallback>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers_auth::handle_auth_quickbooks_callback(rqctx, query_args))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/** Listen for webhooks from Plaid. */
+#[endpoint {
+    method = POST,
+    path = "/plaid",
+}]
+async fn listen_auth_plaid_callback(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    body_param: TypedBody<serde_json::Value>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    txn.run(|| {
+        warn!("plaid callback: {:?}", body);
+    });
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/** Get the consent URL for DocuSign auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/docusign/consent",
+}]
+async fn listen_auth_docusign_consent(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    // Initialize the DocuSign client.
+    let g = txn.run(|| DocuSign::new_from_env("", "", "", ""));
+
+    txn.finish(http::StatusCode::OK);
+
+    Ok(HttpResponseOk(UserConsentURL {
+        url: g.user_consent_url(),
+    }))
+}
+
+/** Listen for callbacks to DocuSign auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/docusign/callback",
+}]
+async fn listen_auth_docusign_callback(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    query_args: Query<AuthCallback>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers_auth::handle_auth_docusign_callback(rqctx, query_args))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/** Listen for QuickBooks webhooks for changed entities. */
 #[endpoint {
-    method = OPTIONS,
-    path = "/application/files/upload",
+    method = POST,
+    path = "/quickbooks",
 }]
-async fn listen_application_files_upload_requests_cors(
+async fn listen_quickbooks_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
-) -> Result<HttpResponseHeaders<HttpResponseOk<String>>, HttpError> {
-    let mut resp = HttpResponseHeaders::new_unnamed(HttpResponseOk("".to_string()));
-    let headers = resp.headers_mut();
+    _rate_limit: RateLimit,
+    body: HmacVerifiedBody<crate::handlers_quickbooks::QuickBooksWebhookVerification, quickbooks::WebhookPayload>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let webhook = body.into_inner()?;
 
-    let allowed_origins = crate::cors::get_cors_origin_header(
-        rqctx.clone(),
-        &["https://apply.oxide.computer", "https://oxide.computer"],
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&webhook)).await;
+
+    if let Err(e) = enqueue(
+        rqctx.context(),
+        EventSource::QuickBooks,
+        "",
+        &webhook,
+        &txn.correlation_id(),
     )
-    .await?;
-    headers.insert("Access-Control-Allow-Origin", allowed_origins);
-    headers.insert("Access-Control-Allow-Headers", HeaderValue::from_static("*"));
-    headers.insert("Access-Control-Allow-Method", HeaderValue::from_static("*"));
+    .await
+    {
+        txn.finish(e.status_code);
+        return Err(e);
+    }
 
-    Ok(resp)
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
 }
 
-/**
- * Listen for files being uploaded for incoming job applications */
+/** Listen for Stripe webhooks for payments and refunds. */
 #[endpoint {
     method = POST,
-    path = "/application/files/upload",
+    path = "/stripe",
 }]
-async fn listen_application_files_upload_requests(
+async fn listen_stripe_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
-    bearer: BearerToken,
-    body_param: TypedBody<ApplicationFileUploadData>,
-) -> Result<HttpResponseHeaders<HttpResponseOk<HashMap<String, String>>>, HttpError> {
-    let body = body_param.into_inner();
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+    _rate_limit: RateLimit,
+    body: HmacVerifiedBody<crate::handlers_stripe::StripeWebhookVerification, cio_api::payments::StripeEvent>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let event = body.into_inner()?;
 
-    // We require that the user has supplied an upload token in the bearer header
-    if let Some(token) = bearer.inner() {
-        // Attempt to consume the token marked it as unusable by other requests. A token may fail
-        // to be consumed due to:
-        //  1. Token was previously used
-        //  2. Token is invalid
-        //  3. Token does not match the email submitted
-        //  4. Token is expired
-        //
-        // We currently return a single error code, 409 Conflict so as not to expose which of these
-        // cases occurred. In the future we may want to relax this and return individual error codes
-        let token_result = rqctx
-            .context()
-            .app
-            .upload_token_store
-            .consume(&body.email, token)
-            .await
-            .map_err(|err| {
-                log::info!("Failed to consume upload token due to {:?}", err);
-                HttpError::for_status(None, http::StatusCode::CONFLICT)
-            });
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&event)).await;
 
-        log::info!("Application materials upload token consume result {:?}", token_result);
+    if let Err(e) = enqueue(
+        rqctx.context(),
+        EventSource::Stripe,
+        &event.type_,
+        &event,
+        &txn.correlation_id(),
+    )
+    .await
+    {
+        txn.finish(e.status_code);
+        return Err(e);
+    }
 
-        match token_result {
-            Ok(_) => {
-                // Check the origin header. In the future this may be upgraded to a hard failure
-                let origin_access = crate::cors::get_cors_origin_header(
-                    rqctx.clone(),
-                    &["https://apply.oxide.computer", "https://oxide.computer"],
-                )
-                .await;
+    txn.finish(http::StatusCode::ACCEPTED);
 
-                let upload_result = txn
-                    .run(|| crate::handlers::handle_application_files_upload(rqctx, body))
-                    .await;
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
 
-                match upload_result {
-                    Ok(r) => {
-                        txn.finish(http::StatusCode::OK);
+/** Listen for changes to our MailChimp audience. */
+#[endpoint {
+    method = POST,
+    path = "/mailchimp/mailing_list",
+}]
+async fn listen_mailchimp_mailing_list_webhooks(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: QueryToken<MailChimpToken>,
+    body_param: UntypedBody,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let event = match mailchimp_minimal_api::WebhookEvent::from_form(body_param.as_bytes()) {
+        Ok(event) => event,
+        Err(mailchimp_minimal_api::WebhookParseError::UnsupportedType(webhook_type)) => {
+            // Campaign events and anything added later are not something we act on.
+            info!("ignoring mailchimp webhook of type `{}`", webhook_type);
+            return Ok(HttpResponseAccepted("ok".to_string()));
+        }
+        Err(e) => return Err(HttpError::for_bad_request(None, e.to_string())),
+    };
 
-                        let mut resp = HttpResponseHeaders::new_unnamed(HttpResponseOk(r));
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&event)).await;
 
-                        match origin_access {
-                            Ok(origin) => {
-                                let headers = resp.headers_mut();
-                                headers.insert("Access-Control-Allow-Origin", origin);
-                            }
-                            Err(err) => {
-                                warn!(
-                                    "Submission to /application/files/upload failed CORS check. Err {:?}",
-                                    err
-                                );
-                            }
-                        }
+    if let Err(e) = txn
+        .run(|| crate::handlers::handle_mailchimp_mailing_list(rqctx, event))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
 
-                        Ok(resp)
-                    }
-                    // Send the error to sentry.
-                    Err(e) => {
-                        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-                        Err(handle_anyhow_err_as_http_err(e))
-                    }
-                }
-            }
-            Err(err) => {
-                txn.finish(err.status_code);
-                Err(err)
-            }
-        }
-    } else {
-        log::info!("Applicant upload request is missing a bearer token");
+    txn.finish(http::StatusCode::ACCEPTED);
 
-        txn.finish(http::StatusCode::UNAUTHORIZED);
-        Err(HttpError::for_status(None, http::StatusCode::UNAUTHORIZED))
-    }
+    Ok(HttpResponseAccepted("ok".to_string()))
 }
 
-/**
- * Listen for rows created in our Airtable workspace.
- * These are set up with an Airtable script on the workspaces themselves.
- */
+/** MailChimp checks that the webhook url exists with a GET before saving it. */
+#[endpoint {
+    method = GET,
+    path = "/mailchimp/mailing_list",
+}]
+async fn ping_mailchimp_mailing_list_webhooks(
+    _rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: QueryToken<MailChimpToken>,
+) -> Result<HttpResponseOk<String>, HttpError> {
+    Ok(HttpResponseOk("ok".to_string()))
+}
+
+/** Listen for subscriber events from our Mailerlite account. */
 #[endpoint {
     method = POST,
-    path = "/airtable/shipments/inbound/create",
+    path = "/mailerlite/mailing_list",
 }]
-async fn listen_airtable_shipments_inbound_create_webhooks(
+async fn listen_mailerlite_mailing_list_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<AirtableToken>,
-    body_param: TypedBody<AirtableRowEvent>,
+    _rate_limit: RateLimit,
+    body: HmacVerifiedBody<crate::handlers_mailerlite::MailerliteWebhookVerification, serde_json::Value>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    let body = body_param.into_inner();
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+    let webhook = body.into_inner()?;
+
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&webhook)).await;
 
     if let Err(e) = txn
-        .run(|| crate::handlers::handle_airtable_shipments_inbound_create(rqctx, body))
+        .run(|| crate::handlers::handle_mailerlite_mailing_list(rqctx, webhook))
         .await
     {
         // Send the error to sentry.
@@ -1322,23 +3265,22 @@ async fn listen_airtable_shipments_inbound_create_webhooks(
     Ok(HttpResponseAccepted("ok".to_string()))
 }
 
-/**
- * Listen for orders being created by the Oxide store.
- */
+/** Listen for updates to our docusign envelopes. */
 #[endpoint {
     method = POST,
-    path = "/store/order",
+    path = "/docusign/envelope/update",
 }]
-async fn listen_store_order_create(
+async fn listen_docusign_envelope_update_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<InternalToken>,
-    body_param: TypedBody<Order>,
+    _rate_limit: RateLimit,
+    body: HmacVerifiedBody<crate::handlers_docusign::DocusignWebhookVerification, docusign::Envelope>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    let body = body_param.into_inner();
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+    let webhook = body.into_inner()?;
+
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&webhook)).await;
 
     if let Err(e) = txn
-        .run(|| crate::handlers::handle_store_order_create(rqctx, body))
+        .run(|| crate::handlers::handle_docusign_envelope_update(rqctx, webhook))
         .await
     {
         // Send the error to sentry.
@@ -1351,22 +3293,22 @@ async fn listen_store_order_create(
     Ok(HttpResponseAccepted("ok".to_string()))
 }
 
-/**
- * Listen for shipment tracking updated from EasyPost.
- */
+/** Listen for DocuSign Connect events for our envelopes. */
 #[endpoint {
     method = POST,
-    path = "/easypost/tracking/update",
+    path = "/docusign/connect",
 }]
-async fn listen_easypost_tracking_update_webhooks(
+async fn listen_docusign_connect_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
-    body_param: TypedBody<EasyPostTrackingUpdateEvent>,
+    _rate_limit: RateLimit,
+    body: HmacVerifiedBody<crate::handlers_docusign::DocusignWebhookVerification, docusign::ConnectEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    let body = body_param.into_inner();
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+    let webhook = body.into_inner()?;
+
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&webhook)).await;
 
     if let Err(e) = txn
-        .run(|| crate::handlers::handle_easypost_tracking_update(rqctx, body))
+        .run(|| crate::handlers::handle_docusign_connect_event(rqctx, webhook))
         .await
     {
         // Send the error to sentry.
@@ -1379,66 +3321,56 @@ async fn listen_easypost_tracking_update_webhooks(
     Ok(HttpResponseAccepted("ok".to_string()))
 }
 
-/// An EasyPost tracking update event.
-/// FROM: https://www.easypost.com/docs/api#events
-#[derive(Debug, Clone, JsonSchema, Deserialize, Serialize)]
-pub struct EasyPostTrackingUpdateEvent {
-    /// "Event".
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub object: String,
-    /// Unique identifier, begins with "evt_".
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub id: String,
-    /// "test" or "production"
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub mode: String,
-    /// Result type and event name, see the "Possible Event Types" section for more information.
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub description: String,
-    /* /// Previous values of relevant result attributes.
-    #[serde(default)]
-    pub previous_attributes: serde_json::Value,
-    /// The object associated with the Event. See the "object" attribute on the result to determine
-    /// its specific type. This field will not be returned when retrieving events directly from the
-    /// API.
-    #[serde(default)]
-    pub result: serde_json::Value,*/
-    /// The current status of the event. Possible values are "completed", "failed", "in_queue",
-    /// "retrying", or "pending" (deprecated).
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub status: String,
-    /// Webhook URLs that have not yet been successfully notified as of the time this webhook event
-    /// was sent. The URL receiving the Event will still be listed in pending_urls, as will any
-    /// other URLs that receive the Event at the same time.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub pending_urls: Vec<String>,
-    /// Webhook URLs that have already been successfully notified as of the time this webhook was
-    /// sent.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub completed_urls: Vec<String>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub created_at: Option<DateTime<Utc>>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub updated_at: Option<DateTime<Utc>>,
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct PolicyPathParams {
+    pub policy: String,
 }
 
-/**
- * Listen for shipment tracking updated from Shippo.
- */
+/** Send a policy to every employee for acknowledgment with DocuSign bulk send. */
+#[endpoint {
+    method = POST,
+    path = "/docusign/policies/{policy}/send",
+}]
+async fn trigger_docusign_policy_acknowledgments_send(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    path_params: Path<PolicyPathParams>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    match txn
+        .run(|| crate::handlers::handle_send_policy_acknowledgments(rqctx, path_params))
+        .await
+    {
+        Ok(batch_id) => {
+            txn.finish(http::StatusCode::ACCEPTED);
+
+            Ok(HttpResponseAccepted(batch_id))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+/** Listen for analytics page view events. */
 #[endpoint {
     method = POST,
-    path = "/shippo/tracking/update",
+    path = "/analytics/page_view",
 }]
-async fn listen_shippo_tracking_update_webhooks(
+async fn listen_analytics_page_view_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: QueryToken<ShippoToken>,
-    body_param: TypedBody<serde_json::Value>,
+    _rate_limit: RateLimit,
+    body_param: TypedBody<NewPageView>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
     let body = body_param.into_inner();
     let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
     if let Err(e) = txn
-        .run(|| crate::handlers::handle_shippo_tracking_update(rqctx, body))
+        .run(|| crate::handlers::handle_analytics_page_view(rqctx, body))
         .await
     {
         // Send the error to sentry.
@@ -1451,32 +3383,52 @@ async fn listen_shippo_tracking_update_webhooks(
     Ok(HttpResponseAccepted("ok".to_string()))
 }
 
-/// A Shippo tracking update event.
-#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
-pub struct ShippoTrackingUpdateEvent {
-    #[serde(default)]
-    pub data: shippo::TrackingStatus,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub event: String,
-    #[serde(default)]
-    pub test: bool,
+/** Listen for Slack commands webhooks. */
+#[endpoint {
+    method = POST,
+    path = "/slack/commands",
+    content_type = "application/x-www-form-urlencoded"
+}]
+async fn listen_slack_commands_webhooks(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    body: HmacVerifiedBodyAudit<crate::handlers_slack::SlackWebhookVerification, BotCommand>,
+) -> Result<HttpResponseOk<serde_json::Value>, HttpError> {
+    let command = body.into_inner()?;
+
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&command)).await;
+
+    match txn.run(|| crate::handlers::handle_slack_commands(rqctx, command)).await {
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(r))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
 }
 
-/** Listen for updates to our checkr background checks. */
+/** Listen for Slack interactive webhooks. */
 #[endpoint {
     method = POST,
-    path = "/checkr/background/update",
+    path = "/slack/interactive",
+    content_type = "application/x-www-form-urlencoded"
 }]
-async fn listen_checkr_background_update_webhooks(
+async fn listen_slack_interactive_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
-    body: HmacVerifiedBodyAudit<crate::handlers_checkr::CheckrWebhookVerification, checkr::WebhookEvent>,
-) -> Result<HttpResponseAccepted<String>, HttpError> {
-    let webhook = body.into_inner()?;
+    _rate_limit: RateLimit,
+    body: HmacVerifiedBodyAudit<crate::handlers_slack::SlackWebhookVerification, InteractiveEvent>,
+) -> Result<HttpResponseOk<String>, HttpError> {
+    let event = body.into_inner()?;
 
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&webhook)).await;
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&event.payload)).await;
 
     if let Err(e) = txn
-        .run(|| crate::handlers::handle_checkr_background_update(rqctx, webhook))
+        .run(|| crate::handlers::handle_slack_interactive(rqctx, event.payload))
         .await
     {
         // Send the error to sentry.
@@ -1484,600 +3436,898 @@ async fn listen_checkr_background_update_webhooks(
         return Err(handle_anyhow_err_as_http_err(e));
     }
 
-    txn.finish(http::StatusCode::ACCEPTED);
+    txn.finish(http::StatusCode::OK);
 
-    Ok(HttpResponseAccepted("ok".to_string()))
+    Ok(HttpResponseOk("ok".to_string()))
 }
 
-#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
-pub struct UserConsentURL {
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub url: String,
+/** Listen for shipbob webhooks. */
+#[endpoint {
+    method = POST,
+    path = "/shipbob",
+}]
+async fn listen_shipbob_webhooks(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: QueryTokenAudit<InternalToken>,
+    body_param: TypedBody<serde_json::Value>,
+) -> Result<HttpResponseOk<String>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    if let Err(e) = txn.run(|| crate::handlers::handle_shipbob(rqctx, body)).await {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::OK);
+
+    Ok(HttpResponseOk("ok".to_string()))
 }
 
-#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
-pub struct AuthCallback {
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub code: String,
-    /// The state that we had passed in through the user consent URL.
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub state: String,
-    #[serde(default, skip_serializing_if = "String::is_empty", rename = "realmId")]
-    pub realm_id: String,
+// The RFD index does not support any sorting mechanisms
+#[derive(Debug, Deserialize, JsonSchema)]
+struct RFDIndexScanParam {}
+
+// The RFD index is always sorted by number in ascending order
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+enum RFDIndexPageSelector {
+    Number(PaginationOrder, i32),
 }
 
-/** Get the consent URL for Google auth. */
+fn rfd_index_scan_params(_params: &WhichPage<RFDIndexScanParam, RFDIndexPageSelector>) -> RFDIndexScanParam {
+    RFDIndexScanParam {}
+}
+
+fn rfd_index_page_selector(item: &RFDIndexEntry, _scan_params: &RFDIndexScanParam) -> RFDIndexPageSelector {
+    RFDIndexPageSelector::Number(PaginationOrder::Ascending, item.number)
+}
+
+/// List metadata of all RFDs
 #[endpoint {
     method = GET,
-    path = "/auth/google/consent",
+    path = "/rfds",
 }]
-async fn listen_auth_google_consent(
+async fn listen_rfd_index(
     rqctx: Arc<RequestContext<ServerContext>>,
-) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+    _rate_limit: RateLimit,
+    _auth: Bearer<RFDToken>,
+    query: Query<PaginationParams<RFDIndexScanParam, RFDIndexPageSelector>>,
+) -> Result<HttpResponseOk<ResultsPage<RFDIndexEntry>>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
 
-    // Initialize the Google client.
-    // You can use any of the libs here, they all use the same endpoint
-    // for tokens and we will send all the scopes.
-    let g = txn.run(|| GoogleDrive::new_from_env("", "")).await;
+    let params = query.into_inner();
+    let offset = match params.page {
+        WhichPage::First(_) => 0,
+        WhichPage::Next(RFDIndexPageSelector::Number(_dir, offset)) => offset,
+    };
+    let limit = rqctx.page_limit(&params)?.get();
+    let scan_params = rfd_index_scan_params(&params.page);
 
-    txn.finish(http::StatusCode::OK);
+    match txn
+        .run(|| crate::handlers_rfd::handle_rfd_index(&rqctx.context().app, offset, limit))
+        .await
+    {
+        Ok(entries) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(ResultsPage::new(
+                entries,
+                &scan_params,
+                rfd_index_page_selector,
+            )?))
+        }
+        Err(err) => {
+            // Send the error to sentry.
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
+}
 
-    Ok(HttpResponseOk(UserConsentURL {
-        url: g.user_consent_url(&cio_api::companies::get_google_scopes()),
-    }))
+/// Get an rfd
+#[endpoint {
+    method = GET,
+    path = "/rfd/{num}",
+}]
+async fn listen_rfd_view(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: Bearer<RFDToken>,
+    path_params: Path<RFDPathParams>,
+) -> Result<HttpResponseOk<RFDEntry>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    match txn
+        .run(|| crate::handlers_rfd::handle_rfd_view(&rqctx.context().app, path_params.into_inner().num))
+        .await
+    {
+        Ok(Some(rfd)) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(rfd))
+        }
+        Ok(None) => {
+            txn.finish(http::StatusCode::NOT_FOUND);
+            Err(HttpError::for_not_found(None, "".to_string()))
+        }
+        Err(err) => {
+            // Send the error to sentry.
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
 }
 
-/** Listen for callbacks to Google auth. */
+/** Run a background job on demand, optionally for a single RFD or applicant. */
+#[endpoint {
+    method = POST,
+    path = "/admin/jobs",
+}]
+async fn admin_run_job(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    body_param: TypedBody<AdminRunRequest>,
+) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    match txn
+        .run(|| {
+            correlation::scope(
+                txn.correlation_id(),
+                crate::handlers_admin::handle_run_job(rqctx.context(), body_param.into_inner()),
+            )
+        })
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::ACCEPTED);
+
+            Ok(HttpResponseAccepted(r))
+        }
+        Err(e) => {
+            txn.finish(e.status_code);
+            Err(e)
+        }
+    }
+}
+
+/** List the background jobs that are running. */
 #[endpoint {
     method = GET,
-    path = "/auth/google/callback",
+    path = "/admin/jobs",
 }]
-async fn listen_auth_google_callback(
+async fn admin_list_jobs(
     rqctx: Arc<RequestContext<ServerContext>>,
-    query_args: Query<AuthCallback>,
-) -> Result<HttpResponseAccepted<String>, HttpError> {
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+) -> Result<HttpResponseOk<Vec<AdminJob>>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    if let Err(e) = txn
-        .run(|| crate::handlers_auth::handle_auth_google_callback(rqctx, query_args))
+    match txn
+        .run(|| crate::handlers_admin::handle_list_jobs(rqctx.context()))
         .await
     {
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(r))
+        }
         // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
     }
+}
 
-    txn.finish(http::StatusCode::ACCEPTED);
-
-    Ok(HttpResponseAccepted("ok".to_string()))
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct AdminJobPathParams {
+    pub saga_id: uuid::Uuid,
 }
 
-/** Get the consent URL for GitHub auth. */
+/** Cancel a background job that is running. */
 #[endpoint {
-    method = GET,
-    path = "/auth/github/consent",
+    method = DELETE,
+    path = "/admin/jobs/{saga_id}",
 }]
-async fn listen_auth_github_consent(
+async fn admin_cancel_job(
     rqctx: Arc<RequestContext<ServerContext>>,
-) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    path_params: Path<AdminJobPathParams>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    txn.finish(http::StatusCode::OK);
+    match txn.run(|| crate::handlers_admin::handle_cancel_job(path_params.into_inner().saga_id)) {
+        Ok(()) => {
+            txn.finish(http::StatusCode::ACCEPTED);
 
-    Ok(HttpResponseOk(UserConsentURL {
-        url: "https://github.com/apps/oxidecomputerbot/installations/new".to_string(),
-    }))
+            Ok(HttpResponseAccepted("ok".to_string()))
+        }
+        Err(e) => {
+            txn.finish(e.status_code);
+            Err(e)
+        }
+    }
 }
 
-/** Listen for callbacks to GitHub auth. */
+/** Stream the progress of a running background job, as server-sent events. */
 #[endpoint {
     method = GET,
-    path = "/auth/github/callback",
+    path = "/admin/jobs/{saga_id}/events",
 }]
-async fn listen_auth_github_callback(
+async fn admin_job_events(
     rqctx: Arc<RequestContext<ServerContext>>,
-    body_param: TypedBody<serde_json::Value>,
-) -> Result<HttpResponseAccepted<String>, HttpError> {
-    let body = body_param.into_inner();
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
-
-    txn.run(|| {
-        warn!("github callback: {:?}", body);
-    });
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    path_params: Path<AdminJobPathParams>,
+) -> Result<http::Response<hyper::Body>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    txn.finish(http::StatusCode::ACCEPTED);
+    match txn.run(|| crate::handlers_admin::handle_job_events(path_params.into_inner().saga_id)) {
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
 
-    Ok(HttpResponseAccepted("ok".to_string()))
+            Ok(r)
+        }
+        Err(e) => {
+            txn.finish(e.status_code);
+            Err(e)
+        }
+    }
 }
 
-/** Get the consent URL for Gusto auth. */
+/** List the webhook events that failed every time they were attempted. */
 #[endpoint {
     method = GET,
-    path = "/auth/gusto/consent",
+    path = "/admin/failures/webhooks",
 }]
-async fn listen_auth_gusto_consent(
+async fn admin_list_failed_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
-) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    query_args: Query<FailureQuery>,
+) -> Result<HttpResponseOk<Vec<FailedWebhookEvent>>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    // Initialize the Gusto client.
-    let g = txn.run(|| Gusto::new_from_env("", ""));
-
-    txn.finish(http::StatusCode::OK);
+    match txn
+        .run(|| crate::handlers_admin::handle_list_failed_webhooks(rqctx.context(), query_args.into_inner()))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
 
-    Ok(HttpResponseOk(UserConsentURL {
-        // We don't need to define scopes for Gusto.
-        url: g.user_consent_url(&[]),
-    }))
+            Ok(HttpResponseOk(r))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
 }
 
-/** Listen for callbacks to Gusto auth. */
+/** Put the failed webhook events that match a filter back in the queue. */
 #[endpoint {
-    method = GET,
-    path = "/auth/gusto/callback",
+    method = POST,
+    path = "/admin/failures/webhooks/requeue",
 }]
-async fn listen_auth_gusto_callback(
+async fn admin_requeue_failed_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
-    query_args: Query<AuthCallback>,
-) -> Result<HttpResponseAccepted<String>, HttpError> {
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    body_param: TypedBody<FailureFilter>,
+) -> Result<HttpResponseAccepted<RequeueResult>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    if let Err(e) = txn
-        .run(|| crate::handlers_auth::handle_auth_gusto_callback(rqctx, query_args))
+    match txn
+        .run(|| crate::handlers_admin::handle_requeue_failed_webhooks(rqctx.context(), body_param.into_inner()))
         .await
     {
+        Ok(r) => {
+            txn.finish(http::StatusCode::ACCEPTED);
+
+            Ok(HttpResponseAccepted(r))
+        }
         // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
     }
-
-    txn.finish(http::StatusCode::ACCEPTED);
-
-    Ok(HttpResponseAccepted("ok".to_string()))
 }
 
-/** Listen to deauthorization requests for our Zoom app. */
+/** List the runs of jobs that failed or timed out. */
 #[endpoint {
     method = GET,
-    path = "/auth/zoom/deauthorization",
+    path = "/admin/failures/jobs",
 }]
-async fn listen_auth_zoom_deauthorization(
+async fn admin_list_failed_jobs(
     rqctx: Arc<RequestContext<ServerContext>>,
-    body_param: TypedBody<serde_json::Value>,
-) -> Result<HttpResponseAccepted<String>, HttpError> {
-    let body = body_param.into_inner();
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
-
-    txn.run(|| {
-        warn!("zoom deauthorization: {:?}", body);
-    });
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    query_args: Query<FailureQuery>,
+) -> Result<HttpResponseOk<Vec<FailedJob>>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    txn.finish(http::StatusCode::ACCEPTED);
+    match txn
+        .run(|| crate::handlers_admin::handle_list_failed_jobs(rqctx.context(), query_args.into_inner()))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
 
-    Ok(HttpResponseAccepted("ok".to_string()))
+            Ok(HttpResponseOk(r))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
 }
 
-/** Get the consent URL for Zoom auth. */
+/** Run again the jobs that have failed runs matching a filter. */
 #[endpoint {
-    method = GET,
-    path = "/auth/zoom/consent",
+    method = POST,
+    path = "/admin/failures/jobs/requeue",
 }]
-async fn listen_auth_zoom_consent(
+async fn admin_requeue_failed_jobs(
     rqctx: Arc<RequestContext<ServerContext>>,
-) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    body_param: TypedBody<FailureFilter>,
+) -> Result<HttpResponseAccepted<Vec<uuid::Uuid>>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    // Initialize the Zoom client.
-    let g = txn.run(|| Zoom::new_from_env("", ""));
-
-    txn.finish(http::StatusCode::OK);
+    match txn
+        .run(|| crate::handlers_admin::handle_requeue_failed_jobs(rqctx.context(), body_param.into_inner()))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::ACCEPTED);
 
-    Ok(HttpResponseOk(UserConsentURL {
-        url: g.user_consent_url(&[]),
-    }))
+            Ok(HttpResponseAccepted(r))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
 }
 
-/** Listen for callbacks to Zoom auth. */
+/** List the routes that decide which handlers run for GitHub webhooks. */
 #[endpoint {
     method = GET,
-    path = "/auth/zoom/callback",
+    path = "/admin/github-routes",
 }]
-async fn listen_auth_zoom_callback(
+async fn admin_list_github_routes(
     rqctx: Arc<RequestContext<ServerContext>>,
-    query_args: Query<AuthCallback>,
-) -> Result<HttpResponseAccepted<String>, HttpError> {
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+) -> Result<HttpResponseOk<Vec<GitHubEventRoute>>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    if let Err(e) = txn
-        .run(|| crate::handlers_auth::handle_auth_zoom_callback(rqctx, query_args))
+    match txn
+        .run(|| crate::handlers_admin::handle_list_github_routes(rqctx.context()))
         .await
     {
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(r))
+        }
         // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
     }
-
-    txn.finish(http::StatusCode::ACCEPTED);
-
-    Ok(HttpResponseAccepted("ok".to_string()))
 }
 
-/** Get the consent URL for Slack auth. */
+/** Add a route for GitHub webhooks. It applies to events that arrive after it is added. */
 #[endpoint {
-    method = GET,
-    path = "/auth/slack/consent",
+    method = POST,
+    path = "/admin/github-routes",
 }]
-async fn listen_auth_slack_consent(
+async fn admin_create_github_route(
     rqctx: Arc<RequestContext<ServerContext>>,
-) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    body_param: TypedBody<NewGitHubEventRoute>,
+) -> Result<HttpResponseOk<GitHubEventRoute>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    // Initialize the Slack client.
-    let s = txn.run(|| Slack::new_from_env("", "", ""));
+    match txn
+        .run(|| crate::handlers_admin::handle_create_github_route(rqctx.context(), body_param.into_inner()))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
 
-    txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(r))
+        }
+        Err(e) => {
+            txn.finish(e.status_code);
+            Err(e)
+        }
+    }
+}
 
-    Ok(HttpResponseOk(UserConsentURL {
-        url: s.user_consent_url(),
-    }))
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct GitHubRoutePathParams {
+    pub id: i32,
 }
 
-/** Listen for callbacks to Slack auth. */
+/** Replace a route for GitHub webhooks. */
 #[endpoint {
-    method = GET,
-    path = "/auth/slack/callback",
+    method = PUT,
+    path = "/admin/github-routes/{id}",
 }]
-async fn listen_auth_slack_callback(
+async fn admin_update_github_route(
     rqctx: Arc<RequestContext<ServerContext>>,
-    query_args: Query<AuthCallback>,
-) -> Result<HttpResponseAccepted<String>, HttpError> {
+    _auth: InternalAuth,
+    path_params: Path<GitHubRoutePathParams>,
+    body_param: TypedBody<NewGitHubEventRoute>,
+) -> Result<HttpResponseOk<GitHubEventRoute>, HttpError> {
+    rate_limit::check(&rqctx).await?;
+
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    if let Err(e) = txn
-        .run(|| crate::handlers_auth::handle_auth_slack_callback(rqctx, query_args))
+    match txn
+        .run(|| {
+            crate::handlers_admin::handle_update_github_route(
+                rqctx.context(),
+                path_params.into_inner().id,
+                body_param.into_inner(),
+            )
+        })
         .await
     {
-        // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
-    }
-
-    txn.finish(http::StatusCode::ACCEPTED);
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
 
-    Ok(HttpResponseAccepted("ok".to_string()))
+            Ok(HttpResponseOk(r))
+        }
+        Err(e) => {
+            txn.finish(e.status_code);
+            Err(e)
+        }
+    }
 }
 
-/** Get the consent URL for QuickBooks auth. */
+/** Delete a route for GitHub webhooks. */
 #[endpoint {
-    method = GET,
-    path = "/auth/quickbooks/consent",
+    method = DELETE,
+    path = "/admin/github-routes/{id}",
 }]
-async fn listen_auth_quickbooks_consent(
+async fn admin_delete_github_route(
     rqctx: Arc<RequestContext<ServerContext>>,
-) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    path_params: Path<GitHubRoutePathParams>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    // Initialize the QuickBooks client.
-    let g = txn.run(|| QuickBooks::new_from_env("", "", ""));
-
-    txn.finish(http::StatusCode::OK);
+    match txn
+        .run(|| crate::handlers_admin::handle_delete_github_route(rqctx.context(), path_params.into_inner().id))
+        .await
+    {
+        Ok(()) => {
+            txn.finish(http::StatusCode::ACCEPTED);
 
-    Ok(HttpResponseOk(UserConsentURL {
-        url: g.user_consent_url(),
-    }))
+            Ok(HttpResponseAccepted("ok".to_string()))
+        }
+        Err(e) => {
+            txn.finish(e.status_code);
+            Err(e)
+        }
+    }
 }
 
-/** Listen for callbacks to QuickBooks auth. */
+/** List the rules that decide where notifications, ie. for a published RFD, are sent. */
 #[endpoint {
     method = GET,
-    path = "/auth/quickbooks/callback",
+    path = "/admin/notification-rules",
 }]
-async fn listen_auth_quickbooks_callback(
+async fn admin_list_notification_rules(
     rqctx: Arc<RequestContext<ServerContext>>,
-    query_args: Query<AuthCallback>,
-) -> Result<HttpResponseAccepted<String>, HttpError> {
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+) -> Result<HttpResponseOk<Vec<NotificationRule>>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    if let Err(e) = txn
-        .run(|| crate::handlers_auth::handle_auth_quickbooks_callback(rqctx, query_args))
+    match txn
+        .run(|| crate::handlers_admin::handle_list_notification_rules(rqctx.context()))
         .await
     {
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(r))
+        }
         // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
     }
+}
 
-    txn.finish(http::StatusCode::ACCEPTED);
+/** Add a rule for where notifications are sent. */
+#[endpoint {
+    method = POST,
+    path = "/admin/notification-rules",
+}]
+async fn admin_create_notification_rule(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    body_param: TypedBody<NewNotificationRule>,
+) -> Result<HttpResponseOk<NotificationRule>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    Ok(HttpResponseAccepted("ok".to_string()))
+    match txn
+        .run(|| crate::handlers_admin::handle_create_notification_rule(rqctx.context(), body_param.into_inner()))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(r))
+        }
+        Err(e) => {
+            txn.finish(e.status_code);
+            Err(e)
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct NotificationRulePathParams {
+    pub id: i32,
 }
 
-/** Listen for webhooks from Plaid. */
+/** Replace a rule for where notifications are sent. */
 #[endpoint {
-    method = POST,
-    path = "/plaid",
+    method = PUT,
+    path = "/admin/notification-rules/{id}",
 }]
-async fn listen_auth_plaid_callback(
+async fn admin_update_notification_rule(
     rqctx: Arc<RequestContext<ServerContext>>,
-    body_param: TypedBody<serde_json::Value>,
-) -> Result<HttpResponseAccepted<String>, HttpError> {
-    let body = body_param.into_inner();
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+    _auth: InternalAuth,
+    path_params: Path<NotificationRulePathParams>,
+    body_param: TypedBody<NewNotificationRule>,
+) -> Result<HttpResponseOk<NotificationRule>, HttpError> {
+    rate_limit::check(&rqctx).await?;
 
-    txn.run(|| {
-        warn!("plaid callback: {:?}", body);
-    });
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    txn.finish(http::StatusCode::ACCEPTED);
+    match txn
+        .run(|| {
+            crate::handlers_admin::handle_update_notification_rule(
+                rqctx.context(),
+                path_params.into_inner().id,
+                body_param.into_inner(),
+            )
+        })
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
 
-    Ok(HttpResponseAccepted("ok".to_string()))
+            Ok(HttpResponseOk(r))
+        }
+        Err(e) => {
+            txn.finish(e.status_code);
+            Err(e)
+        }
+    }
 }
 
-/** Get the consent URL for DocuSign auth. */
+/** Delete a rule for where notifications are sent. */
 #[endpoint {
-    method = GET,
-    path = "/auth/docusign/consent",
+    method = DELETE,
+    path = "/admin/notification-rules/{id}",
 }]
-async fn listen_auth_docusign_consent(
+async fn admin_delete_notification_rule(
     rqctx: Arc<RequestContext<ServerContext>>,
-) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    path_params: Path<NotificationRulePathParams>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    // Initialize the DocuSign client.
-    let g = txn.run(|| DocuSign::new_from_env("", "", "", ""));
-
-    txn.finish(http::StatusCode::OK);
+    match txn
+        .run(|| crate::handlers_admin::handle_delete_notification_rule(rqctx.context(), path_params.into_inner().id))
+        .await
+    {
+        Ok(()) => {
+            txn.finish(http::StatusCode::ACCEPTED);
 
-    Ok(HttpResponseOk(UserConsentURL {
-        url: g.user_consent_url(),
-    }))
+            Ok(HttpResponseAccepted("ok".to_string()))
+        }
+        Err(e) => {
+            txn.finish(e.status_code);
+            Err(e)
+        }
+    }
 }
 
-/** Listen for callbacks to DocuSign auth. */
+/** List the services that are sent cio events, ie. a new hire, through webhooks. */
 #[endpoint {
     method = GET,
-    path = "/auth/docusign/callback",
+    path = "/admin/webhook-consumers",
 }]
-async fn listen_auth_docusign_callback(
+async fn admin_list_webhook_consumers(
     rqctx: Arc<RequestContext<ServerContext>>,
-    query_args: Query<AuthCallback>,
-) -> Result<HttpResponseAccepted<String>, HttpError> {
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+) -> Result<HttpResponseOk<Vec<WebhookConsumer>>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    if let Err(e) = txn
-        .run(|| crate::handlers_auth::handle_auth_docusign_callback(rqctx, query_args))
+    match txn
+        .run(|| crate::handlers_admin::handle_list_webhook_consumers(rqctx.context()))
         .await
     {
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(r))
+        }
         // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
     }
-
-    txn.finish(http::StatusCode::ACCEPTED);
-
-    Ok(HttpResponseAccepted("ok".to_string()))
 }
 
-/** Listen for updates to our docusign envelopes. */
+/** Register a service to be sent cio events through a webhook. The response has the secret the deliveries are signed with, which is not shown again. */
 #[endpoint {
     method = POST,
-    path = "/docusign/envelope/update",
+    path = "/admin/webhook-consumers",
 }]
-async fn listen_docusign_envelope_update_webhooks(
+async fn admin_register_webhook_consumer(
     rqctx: Arc<RequestContext<ServerContext>>,
-    body: HmacVerifiedBody<crate::handlers_docusign::DocusignWebhookVerification, docusign::Envelope>,
-) -> Result<HttpResponseAccepted<String>, HttpError> {
-    let webhook = body.into_inner()?;
-
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&webhook)).await;
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    body_param: TypedBody<NewWebhookConsumer>,
+) -> Result<HttpResponseOk<RegisteredWebhookConsumer>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    if let Err(e) = txn
-        .run(|| crate::handlers::handle_docusign_envelope_update(rqctx, webhook))
+    match txn
+        .run(|| crate::handlers_admin::handle_register_webhook_consumer(rqctx.context(), body_param.into_inner()))
         .await
     {
-        // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
-    }
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
 
-    txn.finish(http::StatusCode::ACCEPTED);
+            Ok(HttpResponseOk(r))
+        }
+        Err(e) => {
+            txn.finish(e.status_code);
+            Err(e)
+        }
+    }
+}
 
-    Ok(HttpResponseAccepted("ok".to_string()))
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct WebhookConsumerPathParams {
+    pub id: i32,
 }
 
-/** Listen for analytics page view events. */
+/** Stop sending cio events to a service, and delete the log of what was sent to it. */
 #[endpoint {
-    method = POST,
-    path = "/analytics/page_view",
+    method = DELETE,
+    path = "/admin/webhook-consumers/{id}",
 }]
-async fn listen_analytics_page_view_webhooks(
+async fn admin_delete_webhook_consumer(
     rqctx: Arc<RequestContext<ServerContext>>,
-    body_param: TypedBody<NewPageView>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    path_params: Path<WebhookConsumerPathParams>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    let body = body_param.into_inner();
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    if let Err(e) = txn
-        .run(|| crate::handlers::handle_analytics_page_view(rqctx, body))
+    match txn
+        .run(|| crate::handlers_admin::handle_delete_webhook_consumer(rqctx.context(), path_params.into_inner().id))
         .await
     {
-        // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
-    }
-
-    txn.finish(http::StatusCode::ACCEPTED);
+        Ok(()) => {
+            txn.finish(http::StatusCode::ACCEPTED);
 
-    Ok(HttpResponseAccepted("ok".to_string()))
+            Ok(HttpResponseAccepted("ok".to_string()))
+        }
+        Err(e) => {
+            txn.finish(e.status_code);
+            Err(e)
+        }
+    }
 }
 
-/** Listen for Slack commands webhooks. */
+/** List the deliveries of cio events to a service, with what the service answered. */
 #[endpoint {
-    method = POST,
-    path = "/slack/commands",
-    content_type = "application/x-www-form-urlencoded"
+    method = GET,
+    path = "/admin/webhook-consumers/{id}/deliveries",
 }]
-async fn listen_slack_commands_webhooks(
+async fn admin_list_webhook_deliveries(
     rqctx: Arc<RequestContext<ServerContext>>,
-    body: HmacVerifiedBodyAudit<crate::handlers_slack::SlackWebhookVerification, BotCommand>,
-) -> Result<HttpResponseOk<serde_json::Value>, HttpError> {
-    let command = body.into_inner()?;
+    _auth: InternalAuth,
+    path_params: Path<WebhookConsumerPathParams>,
+    query_args: Query<WebhookDeliveryQuery>,
+) -> Result<HttpResponseOk<Vec<WebhookDelivery>>, HttpError> {
+    rate_limit::check(&rqctx).await?;
 
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&command)).await;
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    match txn.run(|| crate::handlers::handle_slack_commands(rqctx, command)).await {
+    match txn
+        .run(|| {
+            crate::handlers_admin::handle_list_webhook_deliveries(
+                rqctx.context(),
+                path_params.into_inner().id,
+                query_args.into_inner(),
+            )
+        })
+        .await
+    {
         Ok(r) => {
             txn.finish(http::StatusCode::OK);
 
             Ok(HttpResponseOk(r))
         }
-        // Send the error to sentry.
         Err(e) => {
-            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-            Err(handle_anyhow_err_as_http_err(e))
+            txn.finish(e.status_code);
+            Err(e)
         }
     }
 }
 
-/** Listen for Slack interactive webhooks. */
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct WebhookDeliveryPathParams {
+    pub id: i32,
+}
+
+/** Send a delivery of a cio event again, ie. one that ran out of attempts, with its attempts reset. */
 #[endpoint {
     method = POST,
-    path = "/slack/interactive",
-    content_type = "application/x-www-form-urlencoded"
+    path = "/admin/webhook-deliveries/{id}/redeliver",
 }]
-async fn listen_slack_interactive_webhooks(
+async fn admin_redeliver_webhook_delivery(
     rqctx: Arc<RequestContext<ServerContext>>,
-    body: HmacVerifiedBodyAudit<crate::handlers_slack::SlackWebhookVerification, InteractiveEvent>,
-) -> Result<HttpResponseOk<String>, HttpError> {
-    let event = body.into_inner()?;
-
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&event.payload)).await;
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    path_params: Path<WebhookDeliveryPathParams>,
+) -> Result<HttpResponseOk<WebhookDelivery>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    if let Err(e) = txn
-        .run(|| crate::handlers::handle_slack_interactive(rqctx, event.payload))
+    match txn
+        .run(|| crate::handlers_admin::handle_redeliver_webhook_delivery(rqctx.context(), path_params.into_inner().id))
         .await
     {
-        // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
-    }
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
 
-    txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(r))
+        }
+        Err(e) => {
+            txn.finish(e.status_code);
+            Err(e)
+        }
+    }
+}
 
-    Ok(HttpResponseOk("ok".to_string()))
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct OutboundShipmentPathParams {
+    pub id: i32,
 }
 
-/** Listen for shipbob webhooks. */
+/** Print the label of an outbound shipment again, on the Rollo printer unless another is chosen. */
 #[endpoint {
     method = POST,
-    path = "/shipbob",
+    path = "/admin/shipments/outbound/{id}/reprint-label",
 }]
-async fn listen_shipbob_webhooks(
+async fn admin_reprint_shipment_label(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: QueryTokenAudit<InternalToken>,
-    body_param: TypedBody<serde_json::Value>,
-) -> Result<HttpResponseOk<String>, HttpError> {
-    let body = body_param.into_inner();
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
-
-    if let Err(e) = txn.run(|| crate::handlers::handle_shipbob(rqctx, body)).await {
-        // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
-    }
-
-    txn.finish(http::StatusCode::OK);
-
-    Ok(HttpResponseOk("ok".to_string()))
-}
-
-// The RFD index does not support any sorting mechanisms
-#[derive(Debug, Deserialize, JsonSchema)]
-struct RFDIndexScanParam {}
+    _auth: InternalAuth,
+    path_params: Path<OutboundShipmentPathParams>,
+    body_param: TypedBody<LabelReprint>,
+) -> Result<HttpResponseOk<OutboundShipment>, HttpError> {
+    rate_limit::check(&rqctx).await?;
 
-// The RFD index is always sorted by number in ascending order
-#[derive(Debug, Deserialize, Serialize, JsonSchema)]
-enum RFDIndexPageSelector {
-    Number(PaginationOrder, i32),
-}
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-fn rfd_index_scan_params(_params: &WhichPage<RFDIndexScanParam, RFDIndexPageSelector>) -> RFDIndexScanParam {
-    RFDIndexScanParam {}
-}
+    match txn
+        .run(|| {
+            crate::handlers_admin::handle_reprint_shipment_label(
+                rqctx.context(),
+                path_params.into_inner().id,
+                body_param.into_inner(),
+            )
+        })
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
 
-fn rfd_index_page_selector(item: &RFDIndexEntry, _scan_params: &RFDIndexScanParam) -> RFDIndexPageSelector {
-    RFDIndexPageSelector::Number(PaginationOrder::Ascending, item.number)
+            Ok(HttpResponseOk(r))
+        }
+        Err(e) => {
+            txn.finish(e.status_code);
+            Err(e)
+        }
+    }
 }
 
-/// List metadata of all RFDs
+/**
+ * Correct the address of an outbound shipment that has not shipped yet. A label that was bought
+ * for the old address is voided, and a new one is bought and printed.
+ */
 #[endpoint {
-    method = GET,
-    path = "/rfds",
+    method = POST,
+    path = "/admin/shipments/outbound/{id}/correct-address",
 }]
-async fn listen_rfd_index(
+async fn admin_correct_shipment_address(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<RFDToken>,
-    query: Query<PaginationParams<RFDIndexScanParam, RFDIndexPageSelector>>,
-) -> Result<HttpResponseOk<ResultsPage<RFDIndexEntry>>, HttpError> {
-    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+    _auth: InternalAuth,
+    path_params: Path<OutboundShipmentPathParams>,
+    body_param: TypedBody<AddressCorrection>,
+) -> Result<HttpResponseOk<OutboundShipment>, HttpError> {
+    rate_limit::check(&rqctx).await?;
 
-    let params = query.into_inner();
-    let offset = match params.page {
-        WhichPage::First(_) => 0,
-        WhichPage::Next(RFDIndexPageSelector::Number(_dir, offset)) => offset,
-    };
-    let limit = rqctx.page_limit(&params)?.get();
-    let scan_params = rfd_index_scan_params(&params.page);
+    // The body is a home address, so it is not sent to sentry.
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     match txn
-        .run(|| crate::handlers_rfd::handle_rfd_index(&rqctx.context().app, offset, limit))
+        .run(|| {
+            crate::handlers_admin::handle_correct_shipment_address(
+                rqctx.context(),
+                path_params.into_inner().id,
+                body_param.into_inner(),
+            )
+        })
         .await
     {
-        Ok(entries) => {
+        Ok(r) => {
             txn.finish(http::StatusCode::OK);
-            Ok(HttpResponseOk(ResultsPage::new(
-                entries,
-                &scan_params,
-                rfd_index_page_selector,
-            )?))
+
+            Ok(HttpResponseOk(r))
         }
-        Err(err) => {
-            // Send the error to sentry.
-            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-            Err(handle_anyhow_err_as_http_err(err))
+        Err(e) => {
+            txn.finish(e.status_code);
+            Err(e)
         }
     }
 }
 
-/// Get an rfd
+/** List the changes made to an outbound shipment, ie. its address being corrected, oldest first. */
 #[endpoint {
     method = GET,
-    path = "/rfd/{num}",
+    path = "/admin/shipments/outbound/{id}/history",
 }]
-async fn listen_rfd_view(
+async fn admin_list_shipment_history(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<RFDToken>,
-    path_params: Path<RFDPathParams>,
-) -> Result<HttpResponseOk<RFDEntry>, HttpError> {
-    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+    path_params: Path<OutboundShipmentPathParams>,
+) -> Result<HttpResponseOk<Vec<OutboundShipmentEvent>>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     match txn
-        .run(|| crate::handlers_rfd::handle_rfd_view(&rqctx.context().app, path_params.into_inner().num))
+        .run(|| crate::handlers_admin::handle_list_shipment_history(rqctx.context(), path_params.into_inner().id))
         .await
     {
-        Ok(Some(rfd)) => {
+        Ok(r) => {
             txn.finish(http::StatusCode::OK);
-            Ok(HttpResponseOk(rfd))
-        }
-        Ok(None) => {
-            txn.finish(http::StatusCode::NOT_FOUND);
-            Err(HttpError::for_not_found(None, "".to_string()))
+
+            Ok(HttpResponseOk(r))
         }
-        Err(err) => {
-            // Send the error to sentry.
+        // Send the error to sentry.
+        Err(e) => {
             txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-            Err(handle_anyhow_err_as_http_err(err))
+            Err(handle_anyhow_err_as_http_err(e))
         }
     }
 }
@@ -2089,7 +4339,8 @@ async fn listen_rfd_view(
 }]
 async fn trigger_sync_repos_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<InternalToken>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
@@ -2117,7 +4368,8 @@ async fn trigger_sync_repos_create(
 }]
 async fn trigger_sync_rfds_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<InternalToken>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
@@ -2145,7 +4397,8 @@ async fn trigger_sync_rfds_create(
 }]
 async fn trigger_sync_travel_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<InternalToken>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
@@ -2173,7 +4426,8 @@ async fn trigger_sync_travel_create(
 }]
 async fn trigger_sync_zoho_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<InternalToken>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
@@ -2201,7 +4455,8 @@ async fn trigger_sync_zoho_create(
 }]
 async fn trigger_sync_functions_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<InternalToken>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
@@ -2222,6 +4477,93 @@ async fn trigger_sync_functions_create(
     }
 }
 
+/** Listen for triggering a function run of sync github teams. */
+#[endpoint {
+    method = POST,
+    path = "/run/sync-github-teams",
+}]
+async fn trigger_sync_github_teams_create(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    match txn
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-github-teams"))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::ACCEPTED);
+
+            Ok(HttpResponseAccepted(r))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+/** Listen for triggering a function run of sync google groups. */
+#[endpoint {
+    method = POST,
+    path = "/run/sync-google-groups",
+}]
+async fn trigger_sync_google_groups_create(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    match txn
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-google-groups"))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::ACCEPTED);
+
+            Ok(HttpResponseAccepted(r))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+/** Listen for triggering a function run of sync gusto. */
+#[endpoint {
+    method = POST,
+    path = "/run/sync-gusto",
+}]
+async fn trigger_sync_gusto_create(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    match txn
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-gusto"))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::ACCEPTED);
+
+            Ok(HttpResponseAccepted(r))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
 /** Listen for triggering a function run of sync finance. */
 #[endpoint {
     method = POST,
@@ -2229,7 +4571,8 @@ async fn trigger_sync_functions_create(
 }]
 async fn trigger_sync_finance_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<InternalToken>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
@@ -2257,7 +4600,8 @@ async fn trigger_sync_finance_create(
 }]
 async fn trigger_sync_shipments_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<InternalToken>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
@@ -2285,7 +4629,8 @@ async fn trigger_sync_shipments_create(
 }]
 async fn trigger_sync_shorturls_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<InternalToken>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
@@ -2313,7 +4658,8 @@ async fn trigger_sync_shorturls_create(
 }]
 async fn trigger_sync_configs_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<InternalToken>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
@@ -2341,7 +4687,8 @@ async fn trigger_sync_configs_create(
 }]
 async fn trigger_sync_recorded_meetings_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<InternalToken>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
@@ -2369,7 +4716,8 @@ async fn trigger_sync_recorded_meetings_create(
 }]
 async fn trigger_sync_asset_inventory_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<InternalToken>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
@@ -2397,7 +4745,8 @@ async fn trigger_sync_asset_inventory_create(
 }]
 async fn trigger_sync_swag_inventory_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<InternalToken>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
@@ -2425,7 +4774,8 @@ async fn trigger_sync_swag_inventory_create(
 }]
 async fn trigger_sync_interviews_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<InternalToken>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
@@ -2453,7 +4803,8 @@ async fn trigger_sync_interviews_create(
 }]
 async fn trigger_sync_applications_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<InternalToken>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
@@ -2474,6 +4825,35 @@ async fn trigger_sync_applications_create(
     }
 }
 
+/** Listen for triggering a function run of sync applicant duplicates. */
+#[endpoint {
+    method = POST,
+    path = "/run/sync-applicant-duplicates",
+}]
+async fn trigger_sync_applicant_duplicates_create(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    match txn
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-applicant-duplicates"))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::ACCEPTED);
+
+            Ok(HttpResponseAccepted(r))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
 /** Listen for triggering a function run of sync analytics. */
 #[endpoint {
     method = POST,
@@ -2481,7 +4861,8 @@ async fn trigger_sync_applications_create(
 }]
 async fn trigger_sync_analytics_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<InternalToken>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
@@ -2509,7 +4890,8 @@ async fn trigger_sync_analytics_create(
 }]
 async fn trigger_sync_companies_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<InternalToken>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
@@ -2530,6 +4912,93 @@ async fn trigger_sync_companies_create(
     }
 }
 
+/** Listen for triggering a function run of sync offboarding. */
+#[endpoint {
+    method = POST,
+    path = "/run/sync-offboarding",
+}]
+async fn trigger_sync_offboarding_create(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    match txn
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-offboarding"))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::ACCEPTED);
+
+            Ok(HttpResponseAccepted(r))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+/** Listen for triggering a function run of sync onboarding. */
+#[endpoint {
+    method = POST,
+    path = "/run/sync-onboarding",
+}]
+async fn trigger_sync_onboarding_create(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    match txn
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-onboarding"))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::ACCEPTED);
+
+            Ok(HttpResponseAccepted(r))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+/** Listen for triggering a function run of sync org chart. */
+#[endpoint {
+    method = POST,
+    path = "/run/sync-org-chart",
+}]
+async fn trigger_sync_org_chart_create(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    match txn
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-org-chart"))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::ACCEPTED);
+
+            Ok(HttpResponseAccepted(r))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
 /** Listen for triggering a function run of sync other. */
 #[endpoint {
     method = POST,
@@ -2537,7 +5006,8 @@ async fn trigger_sync_companies_create(
 }]
 async fn trigger_sync_other_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<InternalToken>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
@@ -2558,6 +5028,35 @@ async fn trigger_sync_other_create(
     }
 }
 
+/** Listen for triggering a function run of sync pagerduty. */
+#[endpoint {
+    method = POST,
+    path = "/run/sync-pagerduty",
+}]
+async fn trigger_sync_pagerduty_create(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
+) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    match txn
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-pagerduty"))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::ACCEPTED);
+
+            Ok(HttpResponseAccepted(r))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
 /** Listen for triggering a function run of sync huddles. */
 #[endpoint {
     method = POST,
@@ -2565,7 +5064,8 @@ async fn trigger_sync_other_create(
 }]
 async fn trigger_sync_huddles_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<InternalToken>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
@@ -2593,7 +5093,8 @@ async fn trigger_sync_huddles_create(
 }]
 async fn trigger_sync_mailing_lists_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<InternalToken>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
@@ -2621,7 +5122,8 @@ async fn trigger_sync_mailing_lists_create(
 }]
 async fn trigger_sync_journal_clubs_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<InternalToken>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
@@ -2649,7 +5151,8 @@ async fn trigger_sync_journal_clubs_create(
 }]
 async fn trigger_sync_api_tokens_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<InternalToken>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
@@ -2678,7 +5181,8 @@ async fn trigger_sync_api_tokens_create(
 }]
 async fn trigger_cleanup_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<InternalToken>,
+    _rate_limit: RateLimit,
+    _auth: InternalAuth,
 ) -> Result<HttpResponseAccepted<()>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
@@ -2739,16 +5243,125 @@ fn handle_anyhow_err_as_http_err(err: anyhow::Error) -> HttpError {
     HttpError::for_internal_error(format!("{:?}", err))
 }
 
+/// Uploads that are turned away because of something the client did get that back, anything
+/// else is an error of ours.
+fn handle_upload_err(txn: &mut SentryTransaction, err: anyhow::Error) -> HttpError {
+    match crate::uploads::rejection(&err) {
+        Some(rejection) => {
+            txn.finish(rejection.status_code);
+            rejection
+        }
+        // Send the error to sentry.
+        None => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            handle_anyhow_err_as_http_err(err)
+        }
+    }
+}
+
+/// Applicants are told why they could not book an interview, so that they can pick another
+/// time or ask for new ones.
+fn handle_booking_err(txn: &mut SentryTransaction, err: anyhow::Error) -> HttpError {
+    let status = match err.downcast_ref::<BookingRejection>() {
+        Some(BookingRejection::UnknownToken) => http::StatusCode::NOT_FOUND,
+        Some(BookingRejection::TimeNotProposed(_)) => http::StatusCode::BAD_REQUEST,
+        Some(BookingRejection::NotProposed(_)) | Some(BookingRejection::TimeUnavailable(_)) => {
+            http::StatusCode::CONFLICT
+        }
+        // Send the error to sentry.
+        None => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            return handle_anyhow_err_as_http_err(err);
+        }
+    };
+
+    txn.finish(status);
+    HttpError::for_client_error(None, status, err.to_string())
+}
+
+/// Assets that someone else has checked out are turned away, they have to be checked in first.
+fn handle_assets_check_out_err(txn: &mut SentryTransaction, err: anyhow::Error) -> HttpError {
+    if err.downcast_ref::<AssetCheckedOut>().is_some() {
+        txn.finish(http::StatusCode::CONFLICT);
+        return HttpError::for_client_error(None, http::StatusCode::CONFLICT, err.to_string());
+    }
+
+    // Send the error to sentry.
+    txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+    handle_anyhow_err_as_http_err(err)
+}
+
+/// Links that were used already, or expired, are gone for good. Unknown links are not found.
+fn handle_device_credential_download_err(txn: &mut SentryTransaction, err: anyhow::Error) -> HttpError {
+    if let Some(rejection) = err.downcast_ref::<DownloadRejection>() {
+        let status = match rejection {
+            DownloadRejection::UnknownLink => http::StatusCode::NOT_FOUND,
+            _ => http::StatusCode::GONE,
+        };
+        txn.finish(status);
+        return HttpError::for_client_error(None, status, err.to_string());
+    }
+
+    // Send the error to sentry.
+    txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+    handle_anyhow_err_as_http_err(err)
+}
+
+/// People are told why their swag preferences were turned away, so that they can fix them or ask
+/// for a new link.
+fn handle_swag_preferences_err(txn: &mut SentryTransaction, err: anyhow::Error) -> HttpError {
+    let status = match err.downcast_ref::<SwagPreferenceRejection>() {
+        Some(SwagPreferenceRejection::UnknownLink) => http::StatusCode::NOT_FOUND,
+        Some(SwagPreferenceRejection::Expired) | Some(SwagPreferenceRejection::Submitted) => http::StatusCode::GONE,
+        Some(SwagPreferenceRejection::UnknownShirtSize(_)) | Some(SwagPreferenceRejection::UndeliverableAddress(_)) => {
+            http::StatusCode::BAD_REQUEST
+        }
+        // Send the error to sentry.
+        None => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            return handle_anyhow_err_as_http_err(err);
+        }
+    };
+
+    txn.finish(status);
+    HttpError::for_client_error(None, status, err.to_string())
+}
+
+/// Orders that cannot be filled from the stock on hand are turned away, so the store can tell
+/// the person ordering.
+fn handle_store_order_err(txn: &mut SentryTransaction, err: anyhow::Error) -> HttpError {
+    if err.downcast_ref::<OutOfStock>().is_some() {
+        txn.finish(http::StatusCode::CONFLICT);
+        return HttpError::for_client_error(None, http::StatusCode::CONFLICT, err.to_string());
+    }
+
+    // Send the error to sentry.
+    txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+    handle_anyhow_err_as_http_err(err)
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct SentryTransaction {
     transaction: Option<sentry::TransactionOrSpan>,
     parent_span: Option<sentry::TransactionOrSpan>,
     hub: Option<Arc<sentry::Hub>>,
+    correlation_id: CorrelationId,
 }
 
 async fn start_sentry_http_transaction<T: serde::Serialize>(
     rqctx: Arc<RequestContext<ServerContext>>,
     body: Option<T>,
+) -> SentryTransaction {
+    start_sentry_http_transaction_for_route(rqctx, body, None).await
+}
+
+/// Start the transaction of a request. When `route` is given, ie. `/credentials/{token}` for a
+/// path that carries a secret, the transaction is named after it and the URL sent to Sentry is
+/// the route rather than the path that was requested.
+async fn start_sentry_http_transaction_for_route<T: serde::Serialize>(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    body: Option<T>,
+    route: Option<&str>,
 ) -> SentryTransaction {
     // Create a new Sentry hub for every request.
     // Ensures the scope stays right.
@@ -2764,13 +5377,28 @@ async fn start_sentry_http_transaction<T: serde::Serialize>(
     let raw_req = rqctx.request.lock().await;
     let raw_headers = raw_req.headers().clone();
 
+    // Keep the correlation id of callers that send one, so their requests can be followed in here.
+    let correlation_id = raw_headers
+        .get(CORRELATION_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(CorrelationId::parse)
+        .unwrap_or_default();
+
     let data = body.as_ref().map(|b| serde_json::to_string(b).unwrap());
 
     let url = raw_req.uri();
 
-    let query_string = url.query().map(|query_string| query_string.to_string());
+    let query_string = match route {
+        Some(_) => None,
+        None => url.query().map(|query_string| query_string.to_string()),
+    };
+
+    let url = match route {
+        Some(route) => route.to_string(),
+        None => url.to_string(),
+    };
 
-    let url = if let Ok(u) = url.to_string().parse::<String>() {
+    let url = if let Ok(u) = url.parse::<String>() {
         if !u.is_empty() && !u.starts_with("http://") && !u.starts_with("https://") {
             let url_string = format!(
                 // TODO: should probably make this url configurable.
@@ -2779,7 +5407,7 @@ async fn start_sentry_http_transaction<T: serde::Serialize>(
             );
             Some(reqwest::Url::parse(&url_string).unwrap())
         } else {
-            url.to_string().parse().ok()
+            url.parse().ok()
         }
     } else {
         None
@@ -2813,7 +5441,7 @@ async fn start_sentry_http_transaction<T: serde::Serialize>(
         .iter()
         .flat_map(|(header, value)| value.to_str().ok().map(|value| (header.as_str(), value)));
 
-    let tx_name = format!("{} {}", raw_req.method(), raw_req.uri().path());
+    let tx_name = format!("{} {}", raw_req.method(), route.unwrap_or_else(|| raw_req.uri().path()));
 
     let trx_ctx = sentry::TransactionContext::continue_from_headers(&tx_name, "http.server", headers);
 
@@ -2824,12 +5452,15 @@ async fn start_sentry_http_transaction<T: serde::Serialize>(
         // Set the request data for the transaction.
         transaction.set_request(sentry_req.clone());
 
+        scope.set_tag("correlation_id", &correlation_id);
+
         let parent_span = scope.get_span();
         scope.set_span(Some(transaction.clone()));
         trx = SentryTransaction {
             transaction: Some(transaction),
             parent_span,
             hub: Some(hub.clone()),
+            correlation_id: correlation_id.clone(),
         };
     });
 
@@ -2837,6 +5468,11 @@ async fn start_sentry_http_transaction<T: serde::Serialize>(
 }
 
 impl SentryTransaction {
+    /// The correlation id of the request or scheduled run this transaction is for.
+    pub fn correlation_id(&self) -> CorrelationId {
+        self.correlation_id.clone()
+    }
+
     pub fn run<F: FnOnce() -> R, R>(&self, f: F) -> R {
         Hub::run(self.hub.as_ref().unwrap().clone(), f)
     }
@@ -2902,17 +5538,23 @@ fn start_sentry_cron_transaction(job: &str) -> SentryTransaction {
 
     let trx_ctx = sentry::TransactionContext::new(job, "job.exec");
 
+    // Every scheduled run is its own piece of work.
+    let correlation_id = CorrelationId::new();
+
     let mut trx: SentryTransaction = Default::default();
 
     hub.configure_scope(|scope| {
         let transaction: sentry::TransactionOrSpan = sentry::start_transaction(trx_ctx).into();
 
+        scope.set_tag("correlation_id", &correlation_id);
+
         let parent_span = scope.get_span();
         scope.set_span(Some(transaction.clone()));
         trx = SentryTransaction {
             transaction: Some(transaction),
             parent_span,
             hub: Some(hub.clone()),
+            correlation_id: correlation_id.clone(),
         };
     });
 
@@ -15,6 +15,10 @@ pub enum SlackCommand {
     Paper,
 
     Shipments,
+
+    Celebrations,
+
+    Access,
 }
 
 impl SlackCommand {
@@ -27,6 +31,8 @@ impl SlackCommand {
             SlackCommand::Papers => "/papers",
             SlackCommand::Paper => "/paper",
             SlackCommand::Shipments => "/shipments",
+            SlackCommand::Celebrations => "/celebrations",
+            SlackCommand::Access => "/access",
         }
     }
 }
@@ -42,6 +48,8 @@ impl FromStr for SlackCommand {
             "/papers" => Ok(SlackCommand::Papers),
             "/paper" => Ok(SlackCommand::Paper),
             "/shipments" => Ok(SlackCommand::Shipments),
+            "/celebrations" => Ok(SlackCommand::Celebrations),
+            "/access" => Ok(SlackCommand::Access),
             _ => Err(format!("invalid Slack command: `{}`", s)),
         }
     }
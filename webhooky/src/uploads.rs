@@ -0,0 +1,527 @@
+/*!
+ * Streaming file uploads.
+ *
+ * Uploads are sent as `multipart/form-data` and read a chunk at a time, straight from the
+ * request into wherever the files are stored, so that an upload never has to fit in memory.
+ * Each endpoint that takes uploads has its own [`UploadLimits`], and a request that goes over
+ * them is turned away with a `413` as soon as it does, rather than once it has been read.
+ *
+ * The limits can be changed per endpoint with environment variables, ie. for application
+ * materials `WEBHOOKY_APPLICATION_UPLOAD_MAX_FILE_BYTES`, `_MAX_REQUEST_BYTES` and `_MAX_FILES`.
+ */
+use std::{fmt, str::FromStr, sync::Arc};
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
+use dropshot::{HttpError, RequestContext, ServerContext};
+use http::StatusCode;
+use log::{info, warn};
+use multer::{Constraints, Field, Multipart, SizeLimit};
+
+const MIB: u64 = 1024 * 1024;
+
+/// The most bytes of any body that is read whole, ie. JSON, unless
+/// `WEBHOOKY_REQUEST_BODY_MAX_BYTES` says otherwise. This leaves room for the application
+/// materials that are still sent base64 encoded in JSON.
+const DEFAULT_REQUEST_BODY_MAX_BYTES: u64 = 128 * MIB;
+
+/// Text fields, ie. the email of an applicant, are read whole, so they are kept small.
+const MAX_TEXT_FIELD_BYTES: usize = 64 * 1024;
+
+/// Google Drive takes the parts of a resumable upload in multiples of 256 KiB, all but the last.
+const DRIVE_CHUNK_BYTES: usize = 32 * 256 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UploadLimits {
+    /// The most bytes a single file can have.
+    pub max_file_bytes: u64,
+    /// The most bytes a whole request can have, files and fields together.
+    pub max_request_bytes: u64,
+    /// The most files a request can have.
+    pub max_files: usize,
+}
+
+impl UploadLimits {
+    /// The limits for the materials of an application: a resume, other materials and a
+    /// portfolio.
+    pub fn application_files() -> Self {
+        UploadLimits {
+            max_file_bytes: 25 * MIB,
+            max_request_bytes: 80 * MIB,
+            max_files: 3,
+        }
+        .with_env("WEBHOOKY_APPLICATION_UPLOAD")
+    }
+
+    /// The limits for an email sent to the careers inbox, with its attachments.
+    pub fn careers_inbox() -> Self {
+        UploadLimits {
+            max_file_bytes: 30 * MIB,
+            max_request_bytes: 40 * MIB,
+            max_files: 10,
+        }
+        .with_env("WEBHOOKY_CAREERS_INBOX")
+    }
+
+    fn with_env(self, prefix: &str) -> Self {
+        UploadLimits {
+            max_file_bytes: limit_from_env(&format!("{}_MAX_FILE_BYTES", prefix), self.max_file_bytes),
+            max_request_bytes: limit_from_env(&format!("{}_MAX_REQUEST_BYTES", prefix), self.max_request_bytes),
+            max_files: limit_from_env(&format!("{}_MAX_FILES", prefix), self.max_files),
+        }
+    }
+}
+
+/// The most bytes the server reads of a body that it reads whole.
+pub fn request_body_max_bytes() -> usize {
+    limit_from_env("WEBHOOKY_REQUEST_BODY_MAX_BYTES", DEFAULT_REQUEST_BODY_MAX_BYTES) as usize
+}
+
+fn limit_from_env<T: FromStr + fmt::Display + Copy>(name: &str, default: T) -> T {
+    parse_limit(name, std::env::var(name).ok(), default)
+}
+
+fn parse_limit<T: FromStr + fmt::Display + Copy>(name: &str, value: Option<String>, default: T) -> T {
+    match value {
+        Some(value) => value.trim().parse().unwrap_or_else(|_| {
+            warn!("`{}` is not a number, using {}", name, default);
+            default
+        }),
+        None => default,
+    }
+}
+
+/// An upload that is turned away because of something the client did, ie. it is too large.
+/// Handlers return these inside their `anyhow::Error`, and [`rejection`] turns them back into
+/// the response the client gets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UploadRejection {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl UploadRejection {
+    pub fn new<S: Into<String>>(status: StatusCode, message: S) -> Self {
+        UploadRejection {
+            status,
+            message: message.into(),
+        }
+    }
+
+    pub fn bad_request<S: Into<String>>(message: S) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, message)
+    }
+
+    pub fn too_large<S: Into<String>>(message: S) -> Self {
+        Self::new(StatusCode::PAYLOAD_TOO_LARGE, message)
+    }
+}
+
+impl fmt::Display for UploadRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.status, self.message)
+    }
+}
+
+impl std::error::Error for UploadRejection {}
+
+impl From<multer::Error> for UploadRejection {
+    fn from(e: multer::Error) -> Self {
+        match e {
+            multer::Error::StreamSizeExceeded { limit } => {
+                UploadRejection::too_large(format!("the upload is over the limit of {} bytes", limit))
+            }
+            multer::Error::FieldSizeExceeded { limit, field_name } => UploadRejection::too_large(format!(
+                "`{}` is over the limit of {} bytes",
+                field_name.unwrap_or_default(),
+                limit
+            )),
+            e => UploadRejection::bad_request(e.to_string()),
+        }
+    }
+}
+
+/// The response for an error that is an [`UploadRejection`], or `None` for any other error.
+pub fn rejection(err: &anyhow::Error) -> Option<HttpError> {
+    err.downcast_ref::<UploadRejection>()
+        .map(|rejection| HttpError::for_client_error(None, rejection.status, rejection.message.to_string()))
+}
+
+/// Start reading the `multipart/form-data` body of a request, within `limits`. The body is
+/// taken out of the request, so nothing else can read it after this.
+pub async fn multipart<Context: ServerContext>(
+    rqctx: &Arc<RequestContext<Context>>,
+    limits: &UploadLimits,
+) -> Result<Multipart<'static>, UploadRejection> {
+    let mut request = rqctx.request.lock().await;
+
+    let content_type = request
+        .headers()
+        .get(http::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let boundary = multer::parse_boundary(content_type).map_err(|_| {
+        UploadRejection::new(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "uploads are sent as `multipart/form-data`",
+        )
+    })?;
+
+    // Turn away a request that says up front that it is too large, before reading any of it.
+    let length = request
+        .headers()
+        .get(http::header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+    if let Some(length) = length {
+        if length > limits.max_request_bytes {
+            return Err(UploadRejection::too_large(format!(
+                "the upload is over the limit of {} bytes",
+                limits.max_request_bytes
+            )));
+        }
+    }
+
+    let body = std::mem::take(request.body_mut());
+
+    Ok(multipart_with_limits(body, boundary, limits))
+}
+
+fn multipart_with_limits<S, O, E>(body: S, boundary: String, limits: &UploadLimits) -> Multipart<'static>
+where
+    S: futures::Stream<Item = std::result::Result<O, E>> + Send + 'static,
+    O: Into<Bytes> + 'static,
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+{
+    let constraints = Constraints::new().size_limit(
+        SizeLimit::new()
+            .whole_stream(limits.max_request_bytes)
+            .per_field(limits.max_file_bytes),
+    );
+
+    Multipart::with_constraints(body, boundary, constraints)
+}
+
+/// Read the next field of an upload.
+pub async fn next_field(multipart: &mut Multipart<'static>) -> Result<Option<Field<'static>>, UploadRejection> {
+    Ok(multipart.next_field().await?)
+}
+
+/// Read a text field of an upload whole.
+pub async fn text(mut field: Field<'_>) -> Result<String, UploadRejection> {
+    let name = field.name().unwrap_or_default().to_string();
+    let mut value = BytesMut::new();
+
+    while let Some(chunk) = field.chunk().await? {
+        if value.len() + chunk.len() > MAX_TEXT_FIELD_BYTES {
+            return Err(UploadRejection::too_large(format!(
+                "`{}` is over the limit of {} bytes",
+                name, MAX_TEXT_FIELD_BYTES
+            )));
+        }
+        value.extend_from_slice(&chunk);
+    }
+
+    String::from_utf8(value.to_vec()).map_err(|_| UploadRejection::bad_request(format!("`{}` is not UTF-8", name)))
+}
+
+/// Where the files of an upload are written, a chunk at a time.
+#[async_trait]
+pub trait UploadSink: Send {
+    async fn write(&mut self, chunk: Bytes) -> Result<()>;
+
+    /// Finish writing the file. Returns a link to it.
+    async fn finish(self: Box<Self>) -> Result<String>;
+}
+
+/// Copy the contents of `field` into `sink`, and finish it. Returns the link to the file and how
+/// many bytes it has.
+pub async fn stream_to(mut field: Field<'_>, mut sink: Box<dyn UploadSink>) -> Result<(String, u64)> {
+    let mut written = 0;
+
+    while let Some(chunk) = field.chunk().await.map_err(UploadRejection::from)? {
+        written += chunk.len() as u64;
+        sink.write(chunk).await?;
+    }
+
+    Ok((sink.finish().await?, written))
+}
+
+/// Writes a file nowhere, for trying out uploads without storing anything.
+pub struct DiscardUpload {
+    name: String,
+    content_type: String,
+    written: u64,
+}
+
+impl DiscardUpload {
+    pub fn new(name: &str, content_type: &str) -> Self {
+        DiscardUpload {
+            name: name.to_string(),
+            content_type: content_type.to_string(),
+            written: 0,
+        }
+    }
+}
+
+#[async_trait]
+impl UploadSink for DiscardUpload {
+    async fn write(&mut self, chunk: Bytes) -> Result<()> {
+        self.written += chunk.len() as u64;
+        Ok(())
+    }
+
+    async fn finish(self: Box<Self>) -> Result<String> {
+        info!(
+            "Test upload would write {} bytes of type {} to {}",
+            self.written, self.content_type, self.name
+        );
+
+        Ok(format!(
+            "https://drive.google.com/open?id=test-application-submission-file_{}",
+            self.name
+        ))
+    }
+}
+
+/// Writes a file to a folder in a shared Google Drive with a resumable upload, a part at a time.
+/// A file of the same name in the folder has its contents replaced.
+pub struct DriveUpload {
+    client: reqwest::Client,
+    access_token: String,
+    session: String,
+    buffer: BytesMut,
+    offset: u64,
+}
+
+impl DriveUpload {
+    pub async fn start(
+        access_token: &str,
+        drive_id: &str,
+        parent_id: &str,
+        name: &str,
+        content_type: &str,
+    ) -> Result<Self> {
+        // The parts of the upload answer with a `308` that is not a redirect.
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()?;
+
+        let existing = find_drive_file(&client, access_token, drive_id, parent_id, name).await?;
+        let request = match &existing {
+            Some(id) => client
+                .patch(format!(
+                    "https://www.googleapis.com/upload/drive/v3/files/{}?uploadType=resumable&supportsAllDrives=true",
+                    id
+                ))
+                .json(&serde_json::json!({ "mimeType": content_type })),
+            None => client
+                .post("https://www.googleapis.com/upload/drive/v3/files?uploadType=resumable&supportsAllDrives=true")
+                .json(&serde_json::json!({
+                    "name": name,
+                    "mimeType": content_type,
+                    "parents": [parent_id],
+                })),
+        };
+
+        let resp = request
+            .bearer_auth(access_token)
+            .header("X-Upload-Content-Type", content_type)
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            bail!(
+                "starting upload of {} to Google Drive failed: {} {}",
+                name,
+                resp.status(),
+                resp.text().await.unwrap_or_default()
+            );
+        }
+
+        let session = resp
+            .headers()
+            .get(http::header::LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| anyhow!("Google Drive did not return an upload session for {}", name))?
+            .to_string();
+
+        Ok(DriveUpload {
+            client,
+            access_token: access_token.to_string(),
+            session,
+            buffer: BytesMut::new(),
+            offset: 0,
+        })
+    }
+
+    /// Send a part of the file. The last part is sent with the size of the whole file.
+    async fn send(&mut self, part: Bytes, total: Option<u64>) -> Result<reqwest::Response> {
+        let range = drive_content_range(self.offset, part.len() as u64, total);
+        let len = part.len() as u64;
+
+        let resp = self
+            .client
+            .put(&self.session)
+            .bearer_auth(&self.access_token)
+            .header(http::header::CONTENT_RANGE, range)
+            .body(part)
+            .send()
+            .await?;
+
+        let expected = match total {
+            Some(_) => resp.status().is_success(),
+            None => resp.status() == StatusCode::PERMANENT_REDIRECT,
+        };
+        if !expected {
+            bail!(
+                "uploading to Google Drive failed: {} {}",
+                resp.status(),
+                resp.text().await.unwrap_or_default()
+            );
+        }
+
+        self.offset += len;
+
+        Ok(resp)
+    }
+}
+
+#[async_trait]
+impl UploadSink for DriveUpload {
+    async fn write(&mut self, chunk: Bytes) -> Result<()> {
+        self.buffer.extend_from_slice(&chunk);
+
+        while self.buffer.len() >= DRIVE_CHUNK_BYTES {
+            let part = self.buffer.split_to(DRIVE_CHUNK_BYTES).freeze();
+            self.send(part, None).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<String> {
+        let part = std::mem::take(&mut self.buffer).freeze();
+        let total = self.offset + part.len() as u64;
+
+        let file: serde_json::Value = self.send(part, Some(total)).await?.json().await?;
+        let id = file["id"]
+            .as_str()
+            .ok_or_else(|| anyhow!("Google Drive did not return the id of the uploaded file"))?;
+
+        Ok(format!("https://drive.google.com/open?id={}", id))
+    }
+}
+
+/// The `Content-Range` of a part of a resumable upload, for a file of `total` bytes when it is
+/// known.
+fn drive_content_range(offset: u64, len: u64, total: Option<u64>) -> String {
+    let total = total.map(|total| total.to_string()).unwrap_or_else(|| "*".to_string());
+
+    if len == 0 {
+        format!("bytes */{}", total)
+    } else {
+        format!("bytes {}-{}/{}", offset, offset + len - 1, total)
+    }
+}
+
+/// The id of the file called `name` in the folder `parent_id`, if there is one.
+async fn find_drive_file(
+    client: &reqwest::Client,
+    access_token: &str,
+    drive_id: &str,
+    parent_id: &str,
+    name: &str,
+) -> Result<Option<String>> {
+    let query = format!(
+        "name = '{}' and '{}' in parents and trashed = false",
+        name.replace('\\', "\\\\").replace('\'', "\\'"),
+        parent_id
+    );
+
+    let files: serde_json::Value = client
+        .get("https://www.googleapis.com/drive/v3/files")
+        .bearer_auth(access_token)
+        .query(&[
+            ("q", query.as_str()),
+            ("driveId", drive_id),
+            ("corpora", "drive"),
+            ("includeItemsFromAllDrives", "true"),
+            ("supportsAllDrives", "true"),
+            ("fields", "files(id)"),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(files["files"][0]["id"].as_str().map(|id| id.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::{drive_content_range, multipart_with_limits, parse_limit, text, UploadLimits, UploadRejection};
+
+    fn body(parts: &[(&str, Option<&str>, &str)]) -> Vec<std::result::Result<Bytes, std::io::Error>> {
+        let mut body = String::new();
+        for (name, file_name, contents) in parts {
+            body.push_str("--BOUNDARY\r\n");
+            match file_name {
+                Some(file_name) => body.push_str(&format!(
+                    "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n\r\n",
+                    name, file_name
+                )),
+                None => body.push_str(&format!("Content-Disposition: form-data; name=\"{}\"\r\n\r\n", name)),
+            }
+            body.push_str(contents);
+            body.push_str("\r\n");
+        }
+        body.push_str("--BOUNDARY--\r\n");
+
+        vec![Ok(Bytes::from(body))]
+    }
+
+    #[tokio::test]
+    async fn test_multipart_limits() {
+        let limits = UploadLimits {
+            max_file_bytes: 8,
+            max_request_bytes: 1024,
+            max_files: 1,
+        };
+
+        let mut multipart = multipart_with_limits(
+            futures::stream::iter(body(&[
+                ("email", None, "a@b.co"),
+                ("resume", Some("r.pdf"), "%PDF-1.7 and more"),
+            ])),
+            "BOUNDARY".to_string(),
+            &limits,
+        );
+
+        let email = multipart.next_field().await.unwrap().unwrap();
+        assert_eq!(Some("email"), email.name());
+        assert_eq!("a@b.co", text(email).await.unwrap());
+
+        let mut resume = multipart.next_field().await.unwrap().unwrap();
+        assert_eq!(Some("r.pdf"), resume.file_name());
+        let rejection = UploadRejection::from(resume.chunk().await.unwrap_err());
+        assert_eq!(http::StatusCode::PAYLOAD_TOO_LARGE, rejection.status);
+    }
+
+    #[test]
+    fn test_parse_limit() {
+        assert_eq!(25, parse_limit("MAX", None, 25u64));
+        assert_eq!(10, parse_limit("MAX", Some(" 10 ".to_string()), 25u64));
+        assert_eq!(25, parse_limit("MAX", Some("lots".to_string()), 25u64));
+    }
+
+    #[test]
+    fn test_drive_content_range() {
+        assert_eq!("bytes 0-9/*", drive_content_range(0, 10, None));
+        assert_eq!("bytes 10-14/15", drive_content_range(10, 5, Some(15)));
+        assert_eq!("bytes */10", drive_content_range(10, 0, Some(10)));
+    }
+}
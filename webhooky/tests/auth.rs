@@ -1,6 +1,7 @@
 use anyhow::{anyhow, Result};
+use async_trait::async_trait;
 use checkr::WebhookEvent as CheckrWebhook;
-use docusign::Envelope;
+use docusign::{ConnectEvent, Envelope};
 use dropshot::{
     endpoint, ApiDescription, ConfigDropshot, ConfigLogging, ConfigLoggingLevel, HttpError, HttpResponseAccepted,
     HttpServer, HttpServerStarter, RequestContext,
@@ -8,12 +9,20 @@ use dropshot::{
 use dropshot_verify_request::{
     bearer::{Bearer, BearerAudit},
     query::{QueryToken, QueryTokenAudit},
-    sig::{HmacVerifiedBody, HmacVerifiedBodyAudit},
+    sig::{sign_request, HmacVerifiedBody, HmacVerifiedBodyAudit, SharedSecretProvider, SharedSecretVerifier},
 };
+use quickbooks::WebhookPayload;
 use slack_chat_api::BotCommand;
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
-use webhooky::{auth::InternalToken, github_types::GitHubWebhook, handlers_slack::InteractiveEvent};
+use webhooky::{
+    auth::{InternalAuth, InternalToken},
+    github_types::GitHubWebhook,
+    handlers_slack::InteractiveEvent,
+};
 
 #[endpoint {
     method = POST,
@@ -87,6 +96,36 @@ async fn hmac_docusign_audit(
     Ok(HttpResponseAccepted("ok".to_string()))
 }
 
+#[endpoint {
+    method = POST,
+    path = "/hmac/docusign/connect/verify",
+}]
+async fn hmac_docusign_connect_verification(
+    _rqctx: Arc<RequestContext<()>>,
+    body: HmacVerifiedBody<webhooky::handlers_docusign::DocusignWebhookVerification, ConnectEvent>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let event = body.into_inner()?;
+    Ok(HttpResponseAccepted(event.envelope().envelope_id))
+}
+
+#[endpoint {
+    method = POST,
+    path = "/hmac/quickbooks/verify",
+}]
+async fn hmac_quickbooks_verification(
+    _rqctx: Arc<RequestContext<()>>,
+    body: HmacVerifiedBody<webhooky::handlers_quickbooks::QuickBooksWebhookVerification, WebhookPayload>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let payload = body.into_inner()?;
+    Ok(HttpResponseAccepted(
+        payload
+            .event_notifications
+            .get(0)
+            .map(|notification| notification.realm_id.to_string())
+            .unwrap_or_default(),
+    ))
+}
+
 #[endpoint {
     method = POST,
     path = "/hmac/slack/verify",
@@ -185,6 +224,38 @@ async fn token_audit(
     Ok(HttpResponseAccepted("ok".to_string()))
 }
 
+struct TestSigningSecret;
+
+#[async_trait]
+impl SharedSecretProvider for TestSigningSecret {
+    async fn secret() -> Result<Vec<u8>> {
+        Ok(std::env::var("TEST_SIGNING_SECRET")?.into_bytes())
+    }
+}
+
+#[endpoint {
+    method = POST,
+    path = "/signed/verify",
+}]
+async fn signed_verification(
+    _rqctx: Arc<RequestContext<()>>,
+    body: HmacVerifiedBody<SharedSecretVerifier<TestSigningSecret>, serde_json::Value>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    body.into_inner()?;
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+#[endpoint {
+    method = POST,
+    path = "/internal/verify",
+}]
+async fn internal_verification(
+    _rqctx: Arc<RequestContext<()>>,
+    _: InternalAuth,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
 static INIT: std::sync::Once = std::sync::Once::new();
 
 /// Setup function that is only run once, even if called multiple times.
@@ -215,10 +286,12 @@ fn make_server() -> (u16, HttpServer<()>) {
     //     hex::encode(result.into_bytes())
     // }
     std::env::set_var("INTERNAL_AUTH_BEARER", "TEST_BEARER");
+    std::env::set_var("TEST_SIGNING_SECRET", "TEST_SIGNING_SECRET");
     std::env::set_var("DOCUSIGN_WH_KEY", "vkPkH4G2k8XNC5HWA6QgZd08v37P8KcVZMjaP4zgGWc=");
     std::env::set_var("GH_WH_KEY", "vkPkH4G2k8XNC5HWA6QgZd08v37P8KcVZMjaP4zgGWc=");
     std::env::set_var("SLACK_WH_KEY", "vkPkH4G2k8XNC5HWA6QgZd08v37P8KcVZMjaP4zgGWc=");
     std::env::set_var("CHECKR_API_KEY", "vkPkH4G2k8XNC5HWA6QgZd08v37P8KcVZMjaP4zgGWc=");
+    std::env::set_var("QUICKBOOKS_WH_KEY", "vkPkH4G2k8XNC5HWA6QgZd08v37P8KcVZMjaP4zgGWc=");
 
     let config_dropshot = ConfigDropshot {
         bind_address: "127.0.0.1:0".parse().unwrap(),
@@ -237,6 +310,8 @@ fn make_server() -> (u16, HttpServer<()>) {
     api.register(hmac_checkr_audit).unwrap();
     api.register(hmac_docusign_verification).unwrap();
     api.register(hmac_docusign_audit).unwrap();
+    api.register(hmac_docusign_connect_verification).unwrap();
+    api.register(hmac_quickbooks_verification).unwrap();
     api.register(hmac_slack_verification).unwrap();
     api.register(hmac_slack_audit).unwrap();
     api.register(hmac_slack_interactive_verification).unwrap();
@@ -245,6 +320,8 @@ fn make_server() -> (u16, HttpServer<()>) {
     api.register(bearer_audit).unwrap();
     api.register(token_verification).unwrap();
     api.register(token_audit).unwrap();
+    api.register(signed_verification).unwrap();
+    api.register(internal_verification).unwrap();
 
     let api_context = ();
     let server = HttpServerStarter::new(&config_dropshot, api, api_context.clone(), &log)
@@ -473,6 +550,94 @@ async fn test_docusign_hmac_audit_passes_with_invalid_signature() {
     assert_eq!(response.status(), reqwest::StatusCode::ACCEPTED);
 }
 
+const DOCUSIGN_CONNECT_TEST_BODY: &str = r#"{"event":"envelope-completed","apiVersion":"v2.1","uri":"/restapi/v2.1/accounts/2ffd4f5b-3fb5-4b1a-9ab2-1c5d3f8e2a10/envelopes/4f1e8a0c-6b7d-4c2e-9d3a-8e5f1b2c7a90","retryCount":0,"configurationId":10418,"generatedDateTime":"2022-07-12T18:22:03.2700000Z","data":{"accountId":"2ffd4f5b-3fb5-4b1a-9ab2-1c5d3f8e2a10","userId":"7b3e1f2a-9c4d-4e8b-a1f6-3d2c5b7e9f01","envelopeId":"4f1e8a0c-6b7d-4c2e-9d3a-8e5f1b2c7a90","envelopeSummary":{"status":"completed","emailSubject":"Your offer letter","envelopeId":"4f1e8a0c-6b7d-4c2e-9d3a-8e5f1b2c7a90","createdDateTime":"2022-07-12T17:58:41.1230000Z","completedDateTime":"2022-07-12T18:21:59.4400000Z"}}}"#;
+
+#[tokio::test]
+async fn test_docusign_connect_hmac_passes() {
+    let (port, _server) = make_server();
+
+    let test_signature = "m8h6vGIBQ4jgsls4/Kriity2oe3lSox707TOPKISOHE=";
+
+    // Make the post API call.
+    let client = reqwest::Client::new();
+    let response = client
+        .post(format!("http://127.0.0.1:{}/hmac/docusign/connect/verify", port))
+        .header("X-DocuSign-Signature-1", test_signature)
+        .body(DOCUSIGN_CONNECT_TEST_BODY)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), reqwest::StatusCode::ACCEPTED);
+
+    let envelope_id: String = response.json().await.unwrap();
+    assert_eq!(envelope_id, "4f1e8a0c-6b7d-4c2e-9d3a-8e5f1b2c7a90");
+}
+
+#[tokio::test]
+async fn test_docusign_connect_hmac_fails() {
+    let (port, _server) = make_server();
+
+    let test_signature = "1111111111111111111111111111111111111111111111111111111111111";
+
+    // Make the post API call.
+    let client = reqwest::Client::new();
+    let response = client
+        .post(format!("http://127.0.0.1:{}/hmac/docusign/connect/verify", port))
+        .header("X-DocuSign-Signature-1", test_signature)
+        .body(DOCUSIGN_CONNECT_TEST_BODY)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+}
+
+/// Test QuickBooks signatures
+
+const QUICKBOOKS_TEST_BODY: &str = r#"{"eventNotifications":[{"realmId":"4620816365187474250","dataChangeEvent":{"entities":[{"name":"Purchase","id":"1287","operation":"Create","lastUpdated":"2022-07-12T18:22:03.000Z"}]}}]}"#;
+
+#[tokio::test]
+async fn test_quickbooks_hmac_passes() {
+    let (port, _server) = make_server();
+
+    let test_signature = "79m+bryFy6KOXV9WHm1dJcQWcoOJE914er+HjLtQCGI=";
+
+    // Make the post API call.
+    let client = reqwest::Client::new();
+    let response = client
+        .post(format!("http://127.0.0.1:{}/hmac/quickbooks/verify", port))
+        .header("intuit-signature", test_signature)
+        .body(QUICKBOOKS_TEST_BODY)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), reqwest::StatusCode::ACCEPTED);
+
+    let realm_id: String = response.json().await.unwrap();
+    assert_eq!(realm_id, "4620816365187474250");
+}
+
+#[tokio::test]
+async fn test_quickbooks_hmac_fails() {
+    let (port, _server) = make_server();
+
+    let test_signature = "1111111111111111111111111111111111111111111111111111111111111";
+
+    // Make the post API call.
+    let client = reqwest::Client::new();
+    let response = client
+        .post(format!("http://127.0.0.1:{}/hmac/quickbooks/verify", port))
+        .header("intuit-signature", test_signature)
+        .body(QUICKBOOKS_TEST_BODY)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+}
+
 /// Test Slack signatures
 
 const SLACK_TEST_BODY: &str = r#"user_name=test&command=fakecommand"#;
@@ -746,3 +911,152 @@ async fn test_query_token_audit_pass_with_invalid_token() {
 
     assert_eq!(response.status(), reqwest::StatusCode::ACCEPTED);
 }
+
+/// Test requests signed with a shared secret
+
+const SIGNED_TEST_BODY: &str = r#"{"name":"sync-rfds"}"#;
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+async fn send_signed(port: u16, timestamp: u64, signature: &str) -> reqwest::Response {
+    let client = reqwest::Client::new();
+    client
+        .post(format!("http://127.0.0.1:{}/signed/verify", port))
+        .header("X-Signature", signature)
+        .header("X-Signature-Timestamp", timestamp.to_string())
+        .body(SIGNED_TEST_BODY)
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_signed_request_passes() {
+    let (port, _server) = make_server();
+
+    let timestamp = now();
+    let signature = sign_request(
+        b"TEST_SIGNING_SECRET",
+        timestamp,
+        "POST",
+        "/signed/verify",
+        SIGNED_TEST_BODY.as_bytes(),
+    )
+    .unwrap();
+
+    let response = send_signed(port, timestamp, &signature).await;
+
+    assert_eq!(response.status(), reqwest::StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+async fn test_signed_request_with_wrong_secret_fails() {
+    let (port, _server) = make_server();
+
+    let timestamp = now();
+    let signature = sign_request(
+        b"OTHER_SECRET",
+        timestamp,
+        "POST",
+        "/signed/verify",
+        SIGNED_TEST_BODY.as_bytes(),
+    )
+    .unwrap();
+
+    let response = send_signed(port, timestamp, &signature).await;
+
+    assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_signed_request_outside_tolerance_fails() {
+    let (port, _server) = make_server();
+
+    let timestamp = now() - 600;
+    let signature = sign_request(
+        b"TEST_SIGNING_SECRET",
+        timestamp,
+        "POST",
+        "/signed/verify",
+        SIGNED_TEST_BODY.as_bytes(),
+    )
+    .unwrap();
+
+    let response = send_signed(port, timestamp, &signature).await;
+
+    assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_signed_request_without_timestamp_fails() {
+    let (port, _server) = make_server();
+
+    let signature = sign_request(
+        b"TEST_SIGNING_SECRET",
+        now(),
+        "POST",
+        "/signed/verify",
+        SIGNED_TEST_BODY.as_bytes(),
+    )
+    .unwrap();
+
+    let client = reqwest::Client::new();
+    let response = client
+        .post(format!("http://127.0.0.1:{}/signed/verify", port))
+        .header("X-Signature", signature)
+        .body(SIGNED_TEST_BODY)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_unsigned_request_fails() {
+    let (port, _server) = make_server();
+
+    let client = reqwest::Client::new();
+    let response = client
+        .post(format!("http://127.0.0.1:{}/signed/verify", port))
+        .body(SIGNED_TEST_BODY)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+}
+
+/// Test internal authorization
+
+#[tokio::test]
+async fn test_internal_auth_passes_with_bearer() {
+    let (port, _server) = make_server();
+
+    let client = reqwest::Client::new();
+    let response = client
+        .post(format!("http://127.0.0.1:{}/internal/verify", port))
+        .header("Authorization", "Bearer TEST_BEARER")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), reqwest::StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+async fn test_internal_auth_fails_with_invalid_token() {
+    let (port, _server) = make_server();
+
+    let client = reqwest::Client::new();
+    let response = client
+        .post(format!("http://127.0.0.1:{}/internal/verify", port))
+        .header("Authorization", "Bearer TEST_BEARER_2")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+}
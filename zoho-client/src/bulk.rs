@@ -0,0 +1,359 @@
+use std::{
+    collections::HashMap,
+    io::{Cursor, Read},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+
+use crate::client::{RecordsModule, Zoho, ZohoClient};
+
+const BULK_ENDPOINT: &str = "https://www.zohoapis.com/crm/bulk/v2/";
+
+// Bulk read jobs are processed in a queue by Zoho and can take several minutes to complete
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+const MAX_POLL_ATTEMPTS: u32 = 90;
+
+/// A single exported record, keyed by the API name of each field
+pub type BulkReadRecord = HashMap<String, String>;
+
+impl Zoho {
+    /// Constructs a client for exporting large numbers of records via the Bulk Read API. The
+    /// regular records API is limited to 200 records per page, whereas a bulk read job can
+    /// export up to 200,000 records per page.
+    pub fn bulk_read_client(&self) -> BulkReadClient {
+        BulkReadClient {
+            client: self.inner.clone(),
+        }
+    }
+}
+
+pub struct BulkReadClient {
+    client: Arc<ZohoClient>,
+}
+
+impl BulkReadClient {
+    /// https://www.zoho.com/crm/developer/docs/api/v2/bulk-read/create-job.html
+    pub async fn create(&self, request: &BulkReadRequest) -> Result<BulkReadCreateResponse> {
+        let request = self.client.request(BULK_ENDPOINT, &Method::POST, "read", request, None);
+
+        let response = self.client.execute(request).await?;
+
+        match response.status() {
+            StatusCode::CREATED | StatusCode::OK => Ok(response.json().await?),
+            s => Err(anyhow!("status code: {}, body: {}", s, response.text().await?)),
+        }
+    }
+
+    /// https://www.zoho.com/crm/developer/docs/api/v2/bulk-read/get-status.html
+    pub async fn get<S>(&self, id: S) -> Result<BulkReadJob>
+    where
+        S: AsRef<str>,
+    {
+        let path = ["read", id.as_ref()].join("/");
+        let request = self.client.request(BULK_ENDPOINT, &Method::GET, path, &(), None);
+
+        let response = self.client.execute(request).await?;
+
+        match response.status() {
+            StatusCode::OK => {
+                let mut jobs: BulkReadJobResponse = response.json().await?;
+                jobs.data
+                    .pop()
+                    .ok_or_else(|| anyhow!("bulk read job {} was not found", id.as_ref()))
+            }
+            s => Err(anyhow!("status code: {}, body: {}", s, response.text().await?)),
+        }
+    }
+
+    /// Downloads the result of a completed job. Zoho returns a zip archive containing a single
+    /// CSV file, which is parsed in to one map per record.
+    /// https://www.zoho.com/crm/developer/docs/api/v2/bulk-read/download-result.html
+    pub async fn download<S>(&self, id: S) -> Result<Vec<BulkReadRecord>>
+    where
+        S: AsRef<str>,
+    {
+        let path = ["read", id.as_ref(), "result"].join("/");
+        let request = self.client.request(BULK_ENDPOINT, &Method::GET, path, &(), None);
+
+        let response = self.client.execute(request).await?;
+
+        match response.status() {
+            StatusCode::OK => parse_result(&response.bytes().await?),
+            s => Err(anyhow!("status code: {}, body: {}", s, response.text().await?)),
+        }
+    }
+
+    /// Polls a job until Zoho has finished processing it
+    pub async fn wait<S>(&self, id: S) -> Result<BulkReadJob>
+    where
+        S: AsRef<str>,
+    {
+        for _ in 0..MAX_POLL_ATTEMPTS {
+            let job = self.get(id.as_ref()).await?;
+
+            match job.state {
+                BulkReadState::Completed => return Ok(job),
+                BulkReadState::Failure => return Err(anyhow!("bulk read job {} failed", job.id)),
+                _ => tokio::time::sleep(POLL_INTERVAL).await,
+            }
+        }
+
+        Err(anyhow!(
+            "bulk read job {} did not complete after {} attempts",
+            id.as_ref(),
+            MAX_POLL_ATTEMPTS
+        ))
+    }
+
+    /// Exports every record matching the query. A separate job is created for each page of
+    /// results, and pages are requested until Zoho reports that there are no more records.
+    pub async fn read_all(&self, query: BulkReadQuery) -> Result<Vec<BulkReadRecord>> {
+        let mut records = vec![];
+        let mut page = query.page.unwrap_or(1);
+
+        loop {
+            let request = BulkReadRequest {
+                callback: None,
+                query: BulkReadQuery {
+                    page: Some(page),
+                    ..query.clone()
+                },
+                file_type: None,
+            };
+
+            let created = self.create(&request).await?;
+            let id = created
+                .data
+                .into_iter()
+                .next()
+                .map(|entry| entry.details.id)
+                .ok_or_else(|| anyhow!("Zoho did not return a bulk read job for page {}", page))?;
+
+            let job = self.wait(&id).await?;
+            records.append(&mut self.download(&id).await?);
+
+            match job.result {
+                Some(result) if result.more_records => page += 1,
+                _ => break,
+            }
+        }
+
+        Ok(records)
+    }
+}
+
+fn parse_result(bytes: &[u8]) -> Result<Vec<BulkReadRecord>> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes))?;
+
+    if archive.is_empty() {
+        return Err(anyhow!("bulk read result archive is empty"));
+    }
+
+    let mut contents = String::new();
+    archive.by_index(0)?.read_to_string(&mut contents)?;
+
+    let mut reader = csv::Reader::from_reader(contents.as_bytes());
+    let headers = reader.headers()?.clone();
+
+    reader
+        .records()
+        .map(|record| {
+            let record = record?;
+            Ok(headers
+                .iter()
+                .zip(record.iter())
+                .map(|(header, value)| (header.to_string(), value.to_string()))
+                .collect())
+        })
+        .collect()
+}
+
+/// https://www.zoho.com/crm/developer/docs/api/v2/bulk-read/create-job.html
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkReadRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub callback: Option<BulkReadCallback>,
+    pub query: BulkReadQuery,
+    /// Defaults to csv. The ics file type is only supported for the Events module.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkReadCallback {
+    pub url: String,
+    pub method: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BulkReadQuery {
+    pub module: String,
+    /// The API names of the fields to export. All fields are exported when this is not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub criteria: Option<BulkReadCriteria>,
+    /// The id of a custom view to filter records by
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cvid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+}
+
+impl BulkReadQuery {
+    pub fn new<M>() -> Self
+    where
+        M: RecordsModule,
+    {
+        Self {
+            module: M::api_path().to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+/// A criteria is either a single comparison, or a group of criteria joined by `and` / `or`
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum BulkReadCriteria {
+    Comparison {
+        api_name: String,
+        comparator: String,
+        value: serde_json::Value,
+    },
+    Group {
+        group_operator: String,
+        group: Vec<BulkReadCriteria>,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkReadCreateResponse {
+    pub data: Vec<BulkReadCreateResponseEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkReadCreateResponseEntry {
+    pub status: String,
+    pub code: String,
+    pub message: String,
+    pub details: BulkReadCreateResponseDetails,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkReadCreateResponseDetails {
+    pub id: String,
+    pub operation: String,
+    pub state: BulkReadState,
+    pub created_time: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkReadJobResponse {
+    pub data: Vec<BulkReadJob>,
+}
+
+/// https://www.zoho.com/crm/developer/docs/api/v2/bulk-read/get-status.html
+#[derive(Debug, Deserialize)]
+pub struct BulkReadJob {
+    pub id: String,
+    pub operation: String,
+    pub state: BulkReadState,
+    pub created_time: Option<String>,
+    pub result: Option<BulkReadResult>,
+    pub file_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum BulkReadState {
+    #[serde(rename = "ADDED")]
+    Added,
+    #[serde(rename = "QUEUED")]
+    Queued,
+    #[serde(rename = "IN PROGRESS")]
+    InProgress,
+    #[serde(rename = "COMPLETED")]
+    Completed,
+    #[serde(rename = "FAILURE")]
+    Failure,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkReadResult {
+    pub page: u32,
+    pub count: u32,
+    pub download_url: String,
+    pub per_page: u32,
+    pub more_records: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_completed_job() {
+        let body = r#"{"data":[{"id":"554023000001569001","operation":"read","state":"COMPLETED","query":{"module":"Deals","page":1},"created_by":{"id":"554023000000235011","name":"Patricia Boyle"},"created_time":"2022-10-14T12:36:03+05:30","result":{"page":1,"count":3,"download_url":"/crm/bulk/v2/read/554023000001569001/result","per_page":200000,"more_records":false},"file_type":"csv"}]}"#;
+        let response: BulkReadJobResponse = serde_json::from_str(body).unwrap();
+        let job = &response.data[0];
+
+        assert_eq!("554023000001569001", job.id);
+        assert_eq!(BulkReadState::Completed, job.state);
+
+        let result = job.result.as_ref().unwrap();
+        assert_eq!(3, result.count);
+        assert!(!result.more_records);
+    }
+
+    #[test]
+    fn test_serialize_query() {
+        let query = BulkReadQuery {
+            module: "Leads".to_string(),
+            fields: Some(vec!["Email".to_string(), "Lead_Status".to_string()]),
+            criteria: Some(BulkReadCriteria::Comparison {
+                api_name: "Lead_Status".to_string(),
+                comparator: "equal".to_string(),
+                value: "Contacted".into(),
+            }),
+            page: Some(2),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            serde_json::json!({
+                "module": "Leads",
+                "fields": ["Email", "Lead_Status"],
+                "criteria": { "api_name": "Lead_Status", "comparator": "equal", "value": "Contacted" },
+                "page": 2,
+            }),
+            serde_json::to_value(&query).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_result_archive() {
+        let mut buffer = Cursor::new(vec![]);
+        {
+            let mut archive = zip::ZipWriter::new(&mut buffer);
+            archive
+                .start_file("554023000001569001.csv", zip::write::FileOptions::default())
+                .unwrap();
+            archive
+                .write_all(b"Id,Deal_Name,Amount\n1,\"Rack, Full\",100\n2,Support,\n")
+                .unwrap();
+            archive.finish().unwrap();
+        }
+
+        let records = parse_result(buffer.get_ref()).unwrap();
+
+        assert_eq!(2, records.len());
+        assert_eq!("Rack, Full", records[0]["Deal_Name"]);
+        assert_eq!("", records[1]["Amount"]);
+    }
+}
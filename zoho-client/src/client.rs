@@ -9,10 +9,12 @@ use anyhow::{anyhow, Result};
 use reqwest::{header, Client, Method, Request, Response, StatusCode, Url};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
+use crate::modules::{DealsInput, Leads};
+
 const TOKEN_ENDPOINT: &str = "https://accounts.zoho.com";
 const CRM_ENDPOINT: &str = "https://www.zohoapis.com/crm/v2/";
 
-struct ZohoClient {
+pub(crate) struct ZohoClient {
     // Access tokens only last one hour and need frequent refreshing
     access_token: RwLock<String>,
     refresh_token: Option<String>,
@@ -23,7 +25,7 @@ struct ZohoClient {
 
 #[derive(Clone)]
 pub struct Zoho {
-    inner: Arc<ZohoClient>,
+    pub(crate) inner: Arc<ZohoClient>,
 }
 
 impl Zoho {
@@ -163,7 +165,7 @@ impl Zoho {
 
 impl ZohoClient {
     // Constructs a request for sending to the Zoho API
-    fn request<B, P>(
+    pub(crate) fn request<B, P>(
         &self,
         base: &str,
         method: &Method,
@@ -242,7 +244,7 @@ impl ZohoClient {
         }
     }
 
-    async fn execute(&self, request: Request) -> Result<Response> {
+    pub(crate) async fn execute(&self, request: Request) -> Result<Response> {
         let response = self.client.execute(request).await?;
 
         if response.status() != StatusCode::OK {
@@ -462,13 +464,154 @@ where
         Ok(data)
     }
 
+    /// https://www.zoho.com/crm/developer/docs/api/v2/search-records.html
+    pub async fn search(&self, params: SearchModuleRecordsParams) -> Result<GetModuleRecordsResponse<M>> {
+        let path = [M::api_path(), "search"].join("/");
+        let request = self
+            .client
+            .request(CRM_ENDPOINT, &Method::GET, path, &(), Some(params.into()));
+
+        let response = self.client.execute(request).await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(response.json().await?),
+            StatusCode::NO_CONTENT => Ok(GetModuleRecordsResponse {
+                data: vec![],
+                info: ModuleRecordsPagination {
+                    call: None,
+                    per_page: 0,
+                    count: 0,
+                    page: 1,
+                    email: None,
+                    more_records: false,
+                },
+            }),
+            s => Err(anyhow!("status code: {}, body: {}", s, response.text().await?)),
+        }
+    }
+
     // TODO: https://www.zoho.com/crm/developer/docs/api/v2/get-deleted-records.html
-    // TODO: https://www.zoho.com/crm/developer/docs/api/v2/search-records.html
 }
 
-// impl ModuleClient<Leads> {
-// TODO: https://www.zoho.com/crm/developer/docs/api/v2/convert-lead.html
-// }
+impl ModuleClient<Leads> {
+    /// Converts a lead in to a contact, and optionally an account and a deal.
+    /// https://www.zoho.com/crm/developer/docs/api/v2/convert-lead.html
+    pub async fn convert<S>(&self, id: S, input: ConvertLeadInput) -> Result<ConvertLeadResponse>
+    where
+        S: AsRef<str>,
+    {
+        let path = [Leads::api_path(), id.as_ref(), "actions", "convert"].join("/");
+        let request = self.client.request(
+            CRM_ENDPOINT,
+            &Method::POST,
+            path,
+            &ModuleUpdateRequest {
+                data: vec![input],
+                trigger: None,
+            },
+            None,
+        );
+
+        let response = self.client.execute(request).await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(response.json().await?),
+            s => Err(anyhow!("status code: {}, body: {}", s, response.text().await?)),
+        }
+    }
+}
+
+/// Exactly one of the search fields should be set, Zoho will only apply the first one it finds.
+#[derive(Default)]
+pub struct SearchModuleRecordsParams {
+    /// A criteria expression, ie. `((Last_Name:equals:Burns)and(First_Name:starts_with:Ma))`
+    pub criteria: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub word: Option<String>,
+    pub converted: Option<ModuleConvertedFlag>,
+    pub approved: Option<ModuleApprovedFlag>,
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+}
+
+impl From<SearchModuleRecordsParams> for Vec<(&str, String)> {
+    fn from(search_params: SearchModuleRecordsParams) -> Self {
+        let mut params = vec![];
+
+        if let Some(criteria) = search_params.criteria {
+            params.push(("criteria", criteria));
+        }
+
+        if let Some(email) = search_params.email {
+            params.push(("email", email));
+        }
+
+        if let Some(phone) = search_params.phone {
+            params.push(("phone", phone));
+        }
+
+        if let Some(word) = search_params.word {
+            params.push(("word", word));
+        }
+
+        if let Some(converted) = search_params.converted {
+            params.push(("converted", converted.to_string()));
+        }
+
+        if let Some(approved) = search_params.approved {
+            params.push(("approved", approved.to_string()));
+        }
+
+        if let Some(page) = search_params.page {
+            params.push(("page", page.to_string()));
+        }
+
+        if let Some(per_page) = search_params.per_page {
+            params.push(("per_page", per_page.to_string()));
+        }
+
+        params
+    }
+}
+
+/// https://www.zoho.com/crm/developer/docs/api/v2/convert-lead.html
+#[derive(Debug, Default, Serialize)]
+pub struct ConvertLeadInput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overwrite: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notify_lead_owner: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notify_new_entity_owner: Option<bool>,
+    /// The id of an existing account to associate the lead with
+    #[serde(rename = "Accounts", skip_serializing_if = "Option::is_none")]
+    pub accounts: Option<String>,
+    /// The id of an existing contact to associate the lead with
+    #[serde(rename = "Contacts", skip_serializing_if = "Option::is_none")]
+    pub contacts: Option<String>,
+    #[serde(rename = "assign_to", skip_serializing_if = "Option::is_none")]
+    pub assign_to: Option<String>,
+    /// A deal to create along with the contact
+    #[serde(rename = "Deals", skip_serializing_if = "Option::is_none")]
+    pub deals: Option<DealsInput>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConvertLeadResponse {
+    pub data: Vec<ConvertLeadResponseEntry>,
+}
+
+/// The ids of the records the lead was converted in to
+#[derive(Debug, Deserialize)]
+pub struct ConvertLeadResponseEntry {
+    #[serde(rename = "Contacts")]
+    pub contacts: Option<String>,
+    #[serde(rename = "Deals")]
+    pub deals: Option<String>,
+    #[serde(rename = "Accounts")]
+    pub accounts: Option<String>,
+}
 
 #[derive(Default)]
 pub struct GetModuleRecordsParams {
@@ -4,6 +4,7 @@
  * For more information, the Zoho API is documented at [zoho.com/bigin/developer/docs/apis](https://www.zoho.com/bigin/developer/docs/apis/)
  *
  */
+pub mod bulk;
 pub mod client;
 pub mod modules;
 